@@ -0,0 +1,31 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::net::SocketAddr;
+
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+
+/// Checks whether a request is allowed to reach the broker.
+pub trait AccessValidator: Send + Sync {
+    type AccessResource;
+
+    /// Extract the credentials and the resources a request wants to access.
+    fn parse(&self, request: &RemotingCommand, remote_addr: SocketAddr) -> Self::AccessResource;
+
+    /// Fail with [`AclError::NoPermission`](crate::acl_error::AclError) when the request is not
+    /// allowed.
+    fn validate(&self, access_resource: &Self::AccessResource) -> crate::Result<()>;
+}
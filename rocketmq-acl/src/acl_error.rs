@@ -0,0 +1,37 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::remoting_error::RemotingError;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AclError {
+    /// The request is not allowed, the message is returned to the caller.
+    #[error("{0}")]
+    NoPermission(String),
+
+    /// The acl file can not be read or parsed.
+    #[error("{0}")]
+    ConfigError(String),
+}
+
+impl From<AclError> for RemotingError {
+    #[inline]
+    fn from(value: AclError) -> Self {
+        RemotingError::AbortProcessError(ResponseCode::NoPermission.into(), value.to_string())
+    }
+}
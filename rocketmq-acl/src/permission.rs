@@ -0,0 +1,77 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_remoting::code::request_code::RequestCode;
+
+pub const DENY: u8 = 1;
+pub const ANY: u8 = 1 << 1;
+pub const PUB: u8 = 1 << 2;
+pub const SUB: u8 = 1 << 3;
+
+/// Whether the `owned` permission grants the `needed` one.
+pub fn check_permission(needed: u8, owned: u8) -> bool {
+    if owned & DENY > 0 {
+        return false;
+    }
+    if needed & ANY > 0 {
+        return owned & PUB > 0 || owned & SUB > 0;
+    }
+    needed & owned > 0
+}
+
+/// Parse `PUB`, `SUB`, `PUB|SUB` and `DENY`, anything else is denied.
+pub fn parse_perm_from_string(perm: &str) -> u8 {
+    match perm.trim() {
+        "PUB" => PUB,
+        "SUB" => SUB,
+        "PUB|SUB" | "SUB|PUB" => PUB | SUB,
+        _ => DENY,
+    }
+}
+
+/// Requests changing the broker metadata are reserved to admin accounts.
+pub fn need_admin_perm(request_code: i32) -> bool {
+    matches!(
+        RequestCode::from(request_code),
+        RequestCode::UpdateAndCreateTopic
+            | RequestCode::UpdateBrokerConfig
+            | RequestCode::DeleteTopicInBroker
+            | RequestCode::UpdateAndCreateSubscriptionGroup
+            | RequestCode::DeleteSubscriptionGroup
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_permission_honors_deny_and_any() {
+        assert!(check_permission(PUB, PUB | SUB));
+        assert!(!check_permission(PUB, SUB));
+        assert!(!check_permission(SUB, DENY | SUB));
+        assert!(check_permission(ANY, SUB));
+        assert!(!check_permission(ANY, DENY));
+    }
+
+    #[test]
+    fn parse_perm_from_string_defaults_to_deny() {
+        assert_eq!(parse_perm_from_string("PUB"), PUB);
+        assert_eq!(parse_perm_from_string("SUB|PUB"), PUB | SUB);
+        assert_eq!(parse_perm_from_string("DENY"), DENY);
+        assert_eq!(parse_perm_from_string("unknown"), DENY);
+    }
+}
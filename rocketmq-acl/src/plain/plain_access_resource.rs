@@ -0,0 +1,173 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::mix_all;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::protocol::heartbeat::heartbeat_data::HeartbeatData;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+
+use crate::acl_utils;
+use crate::permission;
+use crate::session_credentials::SessionCredentials;
+
+/// The credentials carried by a request and the topics/groups it needs to access.
+///
+/// Groups are stored as their retry topic (`%RETRY%group`), the same way the accounts of
+/// `plain_acl.yml` store group permissions.
+#[derive(Debug, Clone, Default)]
+pub struct PlainAccessResource {
+    pub access_key: Option<CheetahString>,
+    pub signature: Option<CheetahString>,
+    pub security_token: Option<CheetahString>,
+    pub remote_ip: String,
+    pub request_code: i32,
+    pub resource_perm_map: HashMap<CheetahString, u8>,
+    /// The content covered by the signature.
+    pub content: Vec<u8>,
+}
+
+impl PlainAccessResource {
+    pub fn parse(request: &RemotingCommand, remote_addr: SocketAddr) -> Self {
+        let mut resource = PlainAccessResource {
+            remote_ip: remote_addr.ip().to_string(),
+            request_code: request.code(),
+            ..Default::default()
+        };
+        let Some(ext_fields) = request.get_ext_fields() else {
+            return resource;
+        };
+        let field = |key: &str| ext_fields.get(key).cloned();
+        resource.access_key = field(SessionCredentials::ACCESS_KEY);
+        resource.signature = field(SessionCredentials::SIGNATURE);
+        resource.security_token = field(SessionCredentials::SECURITY_TOKEN);
+
+        match RequestCode::from(request.code()) {
+            RequestCode::SendMessage => {
+                resource.add_send_topic(field("topic"));
+            }
+            RequestCode::SendMessageV2 | RequestCode::SendBatchMessage => {
+                resource.add_send_topic(field("b").or_else(|| field("topic")));
+            }
+            RequestCode::ConsumerSendMsgBack => {
+                resource.add_group(field("group"), permission::SUB);
+            }
+            RequestCode::PullMessage => {
+                resource.add_resource(field("topic"), permission::SUB);
+                resource.add_group(field("consumerGroup"), permission::SUB);
+            }
+            RequestCode::QueryMessage => {
+                resource.add_resource(field("topic"), permission::SUB);
+            }
+            RequestCode::HeartBeat => {
+                if let Some(heartbeat_data) = request
+                    .get_body()
+                    .and_then(|body| HeartbeatData::decode(body.as_ref()).ok())
+                {
+                    for consumer_data in heartbeat_data.consumer_data_set.iter() {
+                        resource.add_group(Some(consumer_data.group_name.clone()), permission::SUB);
+                        for subscription in consumer_data.subscription_data_set.iter() {
+                            resource
+                                .add_resource(Some(subscription.topic.clone()), permission::SUB);
+                        }
+                    }
+                }
+            }
+            RequestCode::UnregisterClient | RequestCode::GetConsumerListByGroup => {
+                resource.add_group(field("consumerGroup"), permission::SUB);
+            }
+            RequestCode::UpdateConsumerOffset | RequestCode::QueryConsumerOffset => {
+                resource.add_group(field("consumerGroup"), permission::SUB);
+                resource.add_resource(field("topic"), permission::SUB);
+            }
+            _ => {}
+        }
+
+        let fields = ext_fields
+            .iter()
+            .filter(|(key, _)| key.as_str() != SessionCredentials::SIGNATURE)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<BTreeMap<_, _>>();
+        resource.content =
+            acl_utils::combine_request_content(&fields, request.get_body().map(|b| b.as_ref()));
+        resource
+    }
+
+    pub fn is_retry_topic(topic: &str) -> bool {
+        topic.starts_with(mix_all::RETRY_GROUP_TOPIC_PREFIX)
+    }
+
+    fn add_send_topic(&mut self, topic: Option<CheetahString>) {
+        match topic {
+            Some(topic) if Self::is_retry_topic(topic.as_str()) => {
+                self.add_resource(Some(topic), permission::SUB)
+            }
+            topic => self.add_resource(topic, permission::PUB),
+        }
+    }
+
+    fn add_group(&mut self, group: Option<CheetahString>, perm: u8) {
+        if let Some(group) = group {
+            self.add_resource(
+                Some(CheetahString::from_string(mix_all::get_retry_topic(
+                    group.as_str(),
+                ))),
+                perm,
+            );
+        }
+    }
+
+    fn add_resource(&mut self, resource: Option<CheetahString>, perm: u8) {
+        if let Some(resource) = resource.filter(|resource| !resource.is_empty()) {
+            self.resource_perm_map.insert(resource, perm);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::protocol::header::pull_message_request_header::PullMessageRequestHeader;
+
+    use super::*;
+
+    #[test]
+    fn parse_pull_message_request() {
+        let mut request = RemotingCommand::create_request_command(
+            RequestCode::PullMessage,
+            PullMessageRequestHeader {
+                consumer_group: "GroupA".into(),
+                topic: "TopicA".into(),
+                ..Default::default()
+            },
+        );
+        request.make_custom_header_to_net();
+        request.add_ext_field(SessionCredentials::ACCESS_KEY, "ak");
+        request.add_ext_field(SessionCredentials::SIGNATURE, "sig");
+
+        let resource = PlainAccessResource::parse(&request, "10.0.0.1:1234".parse().unwrap());
+        assert_eq!(resource.access_key, Some("ak".into()));
+        assert_eq!(resource.signature, Some("sig".into()));
+        assert_eq!(resource.remote_ip, "10.0.0.1");
+        assert_eq!(resource.resource_perm_map["TopicA"], permission::SUB);
+        assert_eq!(resource.resource_perm_map["%RETRY%GroupA"], permission::SUB);
+        assert!(!resource.content.is_empty());
+    }
+}
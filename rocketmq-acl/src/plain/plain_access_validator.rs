@@ -0,0 +1,85 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::runtime::RPCHook;
+
+use crate::access_validator::AccessValidator;
+use crate::plain::plain_access_resource::PlainAccessResource;
+use crate::plain::plain_permission_manager::PlainPermissionManager;
+
+/// Validates requests against the accounts of `plain_acl.yml`, registered on the remoting server
+/// as a [`RPCHook`] so that rejected requests are answered with `NO_PERMISSION`.
+#[derive(Clone)]
+pub struct PlainAccessValidator {
+    permission_manager: Arc<PlainPermissionManager>,
+}
+
+impl PlainAccessValidator {
+    pub fn new(file_path: impl Into<PathBuf>) -> crate::Result<Self> {
+        let permission_manager = PlainPermissionManager::new(file_path);
+        permission_manager.load()?;
+        Ok(PlainAccessValidator {
+            permission_manager: Arc::new(permission_manager),
+        })
+    }
+
+    pub fn permission_manager(&self) -> &Arc<PlainPermissionManager> {
+        &self.permission_manager
+    }
+
+    /// Reload the acl file whenever it changes, must be called inside a tokio runtime.
+    pub fn start_watch(&self, interval: Duration) {
+        self.permission_manager.start_watch(interval);
+    }
+}
+
+impl AccessValidator for PlainAccessValidator {
+    type AccessResource = PlainAccessResource;
+
+    fn parse(&self, request: &RemotingCommand, remote_addr: SocketAddr) -> PlainAccessResource {
+        PlainAccessResource::parse(request, remote_addr)
+    }
+
+    fn validate(&self, access_resource: &PlainAccessResource) -> crate::Result<()> {
+        self.permission_manager.validate(access_resource)
+    }
+}
+
+impl RPCHook for PlainAccessValidator {
+    fn do_before_request(
+        &self,
+        remote_addr: SocketAddr,
+        request: &mut RemotingCommand,
+    ) -> rocketmq_remoting::Result<()> {
+        let access_resource = self.parse(request, remote_addr);
+        self.validate(&access_resource)?;
+        Ok(())
+    }
+
+    fn do_after_response(
+        &self,
+        _remote_addr: SocketAddr,
+        _response: &mut RemotingCommand,
+    ) -> rocketmq_remoting::Result<()> {
+        Ok(())
+    }
+}
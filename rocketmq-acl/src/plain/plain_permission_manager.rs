@@ -0,0 +1,357 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use cheetah_string::CheetahString;
+use config::File;
+use config::FileFormat;
+use config::Source;
+use config::Value;
+use parking_lot::Mutex;
+use parking_lot::RwLock;
+use rocketmq_common::common::base::plain_access_config::PlainAccessConfig;
+use rocketmq_common::common::mix_all;
+use serde::Deserialize;
+use tracing::error;
+use tracing::info;
+
+use crate::acl_error::AclError;
+use crate::acl_utils;
+use crate::permission;
+use crate::plain::plain_access_resource::PlainAccessResource;
+use crate::plain::remote_address_strategy::RemoteAddressStrategy;
+
+/// Content of `plain_acl.yml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlainAclConfig {
+    pub global_white_remote_addresses: Vec<CheetahString>,
+    pub accounts: Vec<PlainAccessConfig>,
+}
+
+/// An account of `plain_acl.yml` with its permissions parsed.
+#[derive(Debug, Clone)]
+struct PlainAccessAccount {
+    secret_key: CheetahString,
+    remote_address_strategy: RemoteAddressStrategy,
+    admin: bool,
+    default_topic_perm: u8,
+    default_group_perm: u8,
+    resource_perm_map: HashMap<CheetahString, u8>,
+}
+
+impl PlainAccessAccount {
+    fn new(config: &PlainAccessConfig) -> Self {
+        let mut resource_perm_map = HashMap::new();
+        for (resources, is_group) in [(&config.topic_perms, false), (&config.group_perms, true)] {
+            for resource in resources.iter() {
+                let Some((name, perm)) = resource.split_once('=') else {
+                    continue;
+                };
+                let name = name.trim();
+                let name = if is_group {
+                    mix_all::get_retry_topic(name)
+                } else {
+                    name.to_string()
+                };
+                resource_perm_map.insert(
+                    CheetahString::from_string(name),
+                    permission::parse_perm_from_string(perm),
+                );
+            }
+        }
+        PlainAccessAccount {
+            secret_key: config.secret_key.clone().unwrap_or_default(),
+            remote_address_strategy: RemoteAddressStrategy::new(
+                config
+                    .white_remote_address
+                    .as_ref()
+                    .map(|addr| addr.as_str()),
+            ),
+            admin: config.admin,
+            default_topic_perm: config
+                .default_topic_perm
+                .as_ref()
+                .map_or(permission::DENY, |perm| {
+                    permission::parse_perm_from_string(perm)
+                }),
+            default_group_perm: config
+                .default_group_perm
+                .as_ref()
+                .map_or(permission::DENY, |perm| {
+                    permission::parse_perm_from_string(perm)
+                }),
+            resource_perm_map,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PlainAclState {
+    global_white_remote_address_strategies: Vec<RemoteAddressStrategy>,
+    accounts: HashMap<CheetahString, PlainAccessAccount>,
+}
+
+/// Holds the accounts of `plain_acl.yml` and validates requests against them.
+pub struct PlainPermissionManager {
+    file_path: PathBuf,
+    state: RwLock<PlainAclState>,
+    last_modified: Mutex<Option<SystemTime>>,
+}
+
+impl PlainPermissionManager {
+    pub fn new(file_path: impl Into<PathBuf>) -> Self {
+        PlainPermissionManager {
+            file_path: file_path.into(),
+            state: RwLock::new(PlainAclState::default()),
+            last_modified: Mutex::new(None),
+        }
+    }
+
+    pub fn file_path(&self) -> &Path {
+        self.file_path.as_path()
+    }
+
+    /// Load the acl file, the accounts loaded before are kept if it can not be parsed.
+    pub fn load(&self) -> crate::Result<()> {
+        let modified = Self::modified_time(&self.file_path);
+        // collect the file source directly, `Config::builder` lowercases the top level keys
+        let config = File::from(self.file_path.as_path())
+            .format(FileFormat::Yaml)
+            .collect()
+            .and_then(|table| Value::new(None, table).try_deserialize::<PlainAclConfig>())
+            .map_err(|err| {
+                AclError::ConfigError(format!(
+                    "load acl file {} failed: {}",
+                    self.file_path.display(),
+                    err
+                ))
+            })?;
+        self.update(&config);
+        *self.last_modified.lock() = modified;
+        info!(
+            "load acl file {} success, accounts: {}",
+            self.file_path.display(),
+            config.accounts.len()
+        );
+        Ok(())
+    }
+
+    /// Replace the loaded accounts.
+    pub fn update(&self, config: &PlainAclConfig) {
+        let global_white_remote_address_strategies = config
+            .global_white_remote_addresses
+            .iter()
+            .map(|addr| RemoteAddressStrategy::new(Some(addr.as_str())))
+            .collect();
+        let accounts = config
+            .accounts
+            .iter()
+            .filter_map(|account| {
+                account
+                    .access_key
+                    .clone()
+                    .filter(|access_key| !access_key.is_empty())
+                    .map(|access_key| (access_key, PlainAccessAccount::new(account)))
+            })
+            .collect();
+        *self.state.write() = PlainAclState {
+            global_white_remote_address_strategies,
+            accounts,
+        };
+    }
+
+    /// Reload the acl file if it was modified since the last load.
+    pub fn reload_if_modified(&self) -> bool {
+        let modified = Self::modified_time(&self.file_path);
+        if modified.is_none() || modified == *self.last_modified.lock() {
+            return false;
+        }
+        match self.load() {
+            Ok(_) => true,
+            Err(err) => {
+                error!("{}", err);
+                // do not retry until the file changes again
+                *self.last_modified.lock() = modified;
+                false
+            }
+        }
+    }
+
+    /// Poll the modified time of the acl file and reload it when it changes.
+    pub fn start_watch(self: &Arc<Self>, interval: Duration) {
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.reload_if_modified();
+            }
+        });
+    }
+
+    pub fn validate(&self, resource: &PlainAccessResource) -> crate::Result<()> {
+        let state = self.state.read();
+        if state
+            .global_white_remote_address_strategies
+            .iter()
+            .any(|strategy| strategy.matches(resource.remote_ip.as_str()))
+        {
+            return Ok(());
+        }
+
+        let Some(access_key) = resource.access_key.as_ref() else {
+            return Err(AclError::NoPermission(format!(
+                "No accessKey is configured, request from {}",
+                resource.remote_ip
+            )));
+        };
+        let Some(account) = state.accounts.get(access_key) else {
+            return Err(AclError::NoPermission(format!(
+                "No acl config for {}",
+                access_key
+            )));
+        };
+        if account
+            .remote_address_strategy
+            .matches(resource.remote_ip.as_str())
+        {
+            return Ok(());
+        }
+
+        let signature = acl_utils::cal_signature(&resource.content, account.secret_key.as_str());
+        if resource.signature.as_deref() != Some(signature.as_str()) {
+            return Err(AclError::NoPermission(format!(
+                "Check signature failed for accessKey={}",
+                access_key
+            )));
+        }
+
+        if account.admin {
+            return Ok(());
+        }
+        if permission::need_admin_perm(resource.request_code) {
+            return Err(AclError::NoPermission(format!(
+                "Need admin permission for request code={}, but accessKey={} is not admin",
+                resource.request_code, access_key
+            )));
+        }
+        for (name, needed) in resource.resource_perm_map.iter() {
+            let owned = match account.resource_perm_map.get(name) {
+                Some(owned) => *owned,
+                None if PlainAccessResource::is_retry_topic(name.as_str()) => {
+                    account.default_group_perm
+                }
+                None => account.default_topic_perm,
+            };
+            if !permission::check_permission(*needed, owned) {
+                return Err(AclError::NoPermission(format!(
+                    "No permission for resource {}, accessKey={}",
+                    name, access_key
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn modified_time(file_path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(file_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACL_YAML: &str = r#"
+globalWhiteRemoteAddresses:
+  - 10.10.103.*
+accounts:
+  - accessKey: RocketMQ
+    secretKey: "12345678"
+    whiteRemoteAddress:
+    admin: false
+    defaultTopicPerm: DENY
+    defaultGroupPerm: SUB
+    topicPerms:
+      - topicA=PUB|SUB
+      - topicB=SUB
+    groupPerms:
+      - groupA=DENY
+"#;
+
+    fn resource(topic: &str, perm: u8) -> PlainAccessResource {
+        let mut resource = PlainAccessResource {
+            access_key: Some("RocketMQ".into()),
+            remote_ip: "127.0.0.1".to_string(),
+            content: b"content".to_vec(),
+            ..Default::default()
+        };
+        resource.signature = Some(acl_utils::cal_signature(&resource.content, "12345678").into());
+        resource.resource_perm_map.insert(topic.into(), perm);
+        resource
+    }
+
+    #[test]
+    fn load_and_validate() {
+        let file_path =
+            std::env::temp_dir().join(format!("plain_acl_{}_{}.yml", std::process::id(), line!()));
+        std::fs::write(&file_path, ACL_YAML).unwrap();
+        let manager = PlainPermissionManager::new(file_path.clone());
+        manager.load().unwrap();
+        let _ = std::fs::remove_file(&file_path);
+
+        assert!(manager
+            .validate(&resource("topicA", permission::PUB))
+            .is_ok());
+        assert!(manager
+            .validate(&resource("topicB", permission::PUB))
+            .is_err());
+        assert!(manager
+            .validate(&resource("topicC", permission::SUB))
+            .is_err());
+        assert!(manager
+            .validate(&resource("%RETRY%groupB", permission::SUB))
+            .is_ok());
+        assert!(manager
+            .validate(&resource("%RETRY%groupA", permission::SUB))
+            .is_err());
+
+        let mut bad_signature = resource("topicA", permission::PUB);
+        bad_signature.signature = Some("invalid".into());
+        assert!(manager.validate(&bad_signature).is_err());
+
+        let mut unknown = resource("topicA", permission::PUB);
+        unknown.access_key = Some("unknown".into());
+        assert!(manager.validate(&unknown).is_err());
+
+        let mut whitelisted = unknown.clone();
+        whitelisted.remote_ip = "10.10.103.7".to_string();
+        assert!(manager.validate(&whitelisted).is_ok());
+    }
+}
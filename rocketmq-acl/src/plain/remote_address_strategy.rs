@@ -0,0 +1,133 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+/// Matches the remote address of a request against a white remote address of `plain_acl.yml`.
+///
+/// Supported formats are `*`, an exact ip (`192.168.0.1`), wildcard or range segments
+/// (`192.168.*.1-100`), enumerated last segments (`192.168.0.{1,2,3}`) and comma separated
+/// lists of those.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteAddressStrategy {
+    /// No address configured, nothing matches.
+    Blank,
+    /// `*` or `*.*.*.*`, everything matches.
+    Any,
+    Patterns(Vec<String>),
+}
+
+impl RemoteAddressStrategy {
+    pub fn new(remote_address: Option<&str>) -> Self {
+        let remote_address = remote_address.map(str::trim).unwrap_or_default();
+        if remote_address.is_empty() {
+            return RemoteAddressStrategy::Blank;
+        }
+        if remote_address == "*" || remote_address == "*.*.*.*" {
+            return RemoteAddressStrategy::Any;
+        }
+        let mut patterns = Vec::new();
+        let mut current = String::new();
+        let mut in_braces = false;
+        for c in remote_address.chars() {
+            match c {
+                '{' => {
+                    in_braces = true;
+                    current.push(c);
+                }
+                '}' => {
+                    in_braces = false;
+                    current.push(c);
+                }
+                ',' if !in_braces => {
+                    patterns.push(current.trim().to_string());
+                    current.clear();
+                }
+                _ => current.push(c),
+            }
+        }
+        patterns.push(current.trim().to_string());
+        patterns.retain(|pattern| !pattern.is_empty());
+        RemoteAddressStrategy::Patterns(patterns)
+    }
+
+    pub fn matches(&self, remote_ip: &str) -> bool {
+        match self {
+            RemoteAddressStrategy::Blank => false,
+            RemoteAddressStrategy::Any => true,
+            RemoteAddressStrategy::Patterns(patterns) => patterns
+                .iter()
+                .any(|pattern| Self::match_pattern(pattern, remote_ip)),
+        }
+    }
+
+    fn match_pattern(pattern: &str, remote_ip: &str) -> bool {
+        let pattern_segments = pattern.split('.').collect::<Vec<_>>();
+        let ip_segments = remote_ip.split('.').collect::<Vec<_>>();
+        if pattern_segments.len() != ip_segments.len() {
+            return false;
+        }
+        pattern_segments
+            .iter()
+            .zip(ip_segments.iter())
+            .all(|(pattern, segment)| Self::match_segment(pattern, segment))
+    }
+
+    fn match_segment(pattern: &str, segment: &str) -> bool {
+        if pattern == "*" {
+            return true;
+        }
+        if let Some(values) = pattern
+            .strip_prefix('{')
+            .and_then(|pattern| pattern.strip_suffix('}'))
+        {
+            return values.split(',').any(|value| value.trim() == segment);
+        }
+        if let Some((start, end)) = pattern.split_once('-') {
+            return match (
+                start.parse::<u32>(),
+                end.parse::<u32>(),
+                segment.parse::<u32>(),
+            ) {
+                (Ok(start), Ok(end), Ok(value)) => start <= value && value <= end,
+                _ => false,
+            };
+        }
+        pattern == segment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blank_and_any_strategies() {
+        assert!(!RemoteAddressStrategy::new(None).matches("127.0.0.1"));
+        assert!(!RemoteAddressStrategy::new(Some(" ")).matches("127.0.0.1"));
+        assert!(RemoteAddressStrategy::new(Some("*")).matches("127.0.0.1"));
+        assert!(RemoteAddressStrategy::new(Some("*.*.*.*")).matches("10.0.0.1"));
+    }
+
+    #[test]
+    fn pattern_strategies() {
+        let strategy = RemoteAddressStrategy::new(Some("192.168.0.*,10.10.1-10.{1,2}"));
+        assert!(strategy.matches("192.168.0.15"));
+        assert!(strategy.matches("10.10.5.2"));
+        assert!(!strategy.matches("10.10.11.2"));
+        assert!(!strategy.matches("10.10.5.3"));
+        assert!(RemoteAddressStrategy::new(Some("127.0.0.1")).matches("127.0.0.1"));
+        assert!(!RemoteAddressStrategy::new(Some("127.0.0.1")).matches("127.0.0.2"));
+    }
+}
@@ -15,12 +15,12 @@
  * limitations under the License.
  */
 
-use std::path::PathBuf;
-
 use clap::Parser;
 use rocketmq_broker::command::Args;
 use rocketmq_broker::Builder;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::common::server::config::ServerConfig;
 use rocketmq_common::EnvUtils::EnvUtils;
 use rocketmq_common::ParseConfigFile;
 use rocketmq_rust::rocketmq;
@@ -31,43 +31,45 @@ use tracing::info;
 async fn main() -> anyhow::Result<()> {
     // init logger
     rocketmq_common::log::init_logger();
-    let (broker_config, message_store_config) = parse_config_file();
+    let args = Args::parse();
+    let (broker_config, message_store_config) = args.load_config()?;
+    if args.print_config_item || args.print_important_config {
+        print_config(&args, &broker_config, &message_store_config)?;
+        return Ok(());
+    }
+    info!("Rocketmq(Rust) home: {}", EnvUtils::get_rocketmq_home());
+    let server_config = ServerConfig {
+        listen_port: broker_config.listen_port,
+        ..broker_config.broker_server_config.clone()
+    };
     // boot strap broker
     Builder::new()
         .set_broker_config(broker_config)
         .set_message_store_config(message_store_config)
+        .set_server_config(server_config)
         .build()
         .boot()
         .await;
     Ok(())
 }
 
-fn parse_config_file() -> (BrokerConfig, MessageStoreConfig) {
-    let args = Args::parse();
-    let home = EnvUtils::get_rocketmq_home();
-    let config = if let Some(ref config_file) = args.config_file {
-        let config_file = PathBuf::from(config_file);
+fn print_config(
+    args: &Args,
+    broker_config: &BrokerConfig,
+    message_store_config: &MessageStoreConfig,
+) -> anyhow::Result<()> {
+    let (mut broker_properties, mut store_properties) = if args.print_config_item {
         (
-            ParseConfigFile::parse_config_file::<BrokerConfig>(config_file.clone())
-                .ok()
-                .unwrap(),
-            ParseConfigFile::parse_config_file::<MessageStoreConfig>(config_file)
-                .ok()
-                .unwrap(),
+            ParseConfigFile::config_to_properties(broker_config)?,
+            ParseConfigFile::config_to_properties(message_store_config)?,
         )
     } else {
-        let path_buf = PathBuf::from(home.as_str())
-            .join("conf")
-            .join("broker.toml");
         (
-            ParseConfigFile::parse_config_file::<BrokerConfig>(path_buf.clone())
-                .ok()
-                .unwrap(),
-            ParseConfigFile::parse_config_file::<MessageStoreConfig>(path_buf)
-                .ok()
-                .unwrap(),
+            broker_config.get_properties(),
+            message_store_config.get_properties(),
         )
     };
-    info!("Rocketmq(Rust) home: {}", home);
-    config
+    broker_properties.extend(store_properties.drain());
+    print!("{}", mix_all::properties_to_string(&broker_properties));
+    Ok(())
 }
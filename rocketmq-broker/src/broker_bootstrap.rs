@@ -18,9 +18,15 @@
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::server::config::ServerConfig;
 use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use rocketmq_store::hook::put_message_hook::BoxedPutMessageHook;
+use rocketmq_store::hook::send_message_back_hook::ArcSendMessageBackHook;
 use tracing::error;
+use tracing::info;
+use tracing::warn;
 
 use crate::broker_runtime::BrokerRuntime;
+use crate::mqtrace::consume_message_hook::ConsumeMessageHook;
+use crate::mqtrace::send_message_hook::SendMessageHook;
 
 pub struct BrokerBootstrap {
     broker_runtime: BrokerRuntime,
@@ -32,16 +38,46 @@ impl BrokerBootstrap {
             error!("initialize fail");
             return;
         }
-        let (_start_result, _ctrl_c) = tokio::join!(self.start(), tokio::signal::ctrl_c());
+        self.start().await;
+        wait_for_shutdown_signal().await;
+        info!("Received shutdown signal, shutting down broker gracefully");
+        self.broker_runtime.graceful_shutdown().await;
     }
 
-    async fn initialize(&mut self) -> bool {
+    /// Loads the metadata and the message store, `false` if the broker can not be started.
+    pub async fn initialize(&mut self) -> bool {
         self.broker_runtime.initialize().await
     }
 
-    async fn start(&mut self) {
+    /// Starts serving and registers to the name servers without waiting for a shutdown signal,
+    /// for brokers embedded in another process such as tests.
+    pub async fn start(&mut self) {
         self.broker_runtime.start().await;
     }
+
+    pub async fn shutdown(&mut self) {
+        self.broker_runtime.graceful_shutdown().await;
+    }
+
+    /// Registers a hook run around every message sent to the broker. Call before `start`.
+    pub fn register_send_message_hook(&mut self, hook: Box<dyn SendMessageHook>) {
+        self.broker_runtime.register_send_message_hook(hook);
+    }
+
+    /// Registers a hook run around every message consumed from the broker. Call before `start`.
+    pub fn register_consume_message_hook(&mut self, hook: Box<dyn ConsumeMessageHook>) {
+        self.broker_runtime.register_consume_message_hook(hook);
+    }
+
+    /// Registers a hook the message store runs before putting a message.
+    pub fn register_put_message_hook(&mut self, hook: BoxedPutMessageHook) {
+        self.broker_runtime.register_put_message_hook(hook);
+    }
+
+    /// Sets the hook the message store uses to send messages back to another broker.
+    pub fn set_send_message_back_hook(&mut self, hook: ArcSendMessageBackHook) {
+        self.broker_runtime.set_send_message_back_hook(hook);
+    }
 }
 
 pub struct Builder {
@@ -90,3 +126,29 @@ impl Default for Builder {
         Self::new()
     }
 }
+
+/// Waits for ctrl-c, or for SIGTERM sent by process managers on unix.
+async fn wait_for_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::signal;
+        use tokio::signal::unix::SignalKind;
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(e) => {
+                warn!("install SIGTERM handler failed: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
@@ -17,8 +17,15 @@
 
 use std::path::PathBuf;
 
-// Default broker config path
+use parking_lot::RwLock;
+
+static BROKER_CONFIG_PATH: RwLock<Option<String>> = RwLock::new(None);
+
+// Broker config path, the file the broker was started from or the default one
 pub fn get_broker_config_path() -> String {
+    if let Some(path) = BROKER_CONFIG_PATH.read().as_ref() {
+        return path.clone();
+    }
     let mut path = dirs::home_dir().unwrap();
     path.push("store");
     path.push("config");
@@ -26,6 +33,10 @@ pub fn get_broker_config_path() -> String {
     path.to_string_lossy().into_owned()
 }
 
+pub fn set_broker_config_path(path: impl Into<String>) {
+    *BROKER_CONFIG_PATH.write() = Some(path.into());
+}
+
 // Topic config path
 pub fn get_topic_config_path(root_dir: &str) -> String {
     PathBuf::from(root_dir)
@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
@@ -22,54 +23,85 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use cheetah_string::CheetahString;
+use rocketmq_acl::PlainAccessValidator;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::config::TopicConfig;
 use rocketmq_common::common::config_manager::ConfigManager;
 use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::metrics::metrics_exporter_type::MetricsExporterType;
+use rocketmq_common::common::metrics::prometheus_exporter::PrometheusExporter;
+use rocketmq_common::common::mix_all;
 use rocketmq_common::common::server::config::ServerConfig;
 use rocketmq_common::common::statistics::state_getter::StateGetter;
+use rocketmq_common::EnvUtils::EnvUtils;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_common::UtilAll::compute_next_morning_time_millis;
+use rocketmq_remoting::net::transport::TransportAddr;
 use rocketmq_remoting::protocol::body::broker_body::broker_member_group::BrokerMemberGroup;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigAndMappingSerializeWrapper;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigSerializeWrapper;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
+use rocketmq_remoting::protocol::namesrv::RegisterBrokerResult;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_detail::TopicQueueMappingDetail;
 use rocketmq_remoting::protocol::DataVersion;
 use rocketmq_remoting::remoting_server::server::RocketMQServer;
 use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_runtime::RocketMQRuntime;
 use rocketmq_rust::ArcMut;
 use rocketmq_store::base::store_enum::StoreType;
+use rocketmq_store::config::broker_role::BrokerRole;
 use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use rocketmq_store::dledger::member_state::DLedgerRole;
+use rocketmq_store::hook::put_message_hook::BoxedPutMessageHook;
+use rocketmq_store::hook::send_message_back_hook::ArcSendMessageBackHook;
 use rocketmq_store::log_file::MessageStore;
 use rocketmq_store::message_store::default_message_store::DefaultMessageStore;
 use rocketmq_store::stats::broker_stats::BrokerStats;
 use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
 use rocketmq_store::timer::timer_message_store::TimerMessageStore;
+use tokio::task::JoinHandle;
+use tracing::error;
 use tracing::info;
 use tracing::warn;
 
 use crate::broker::broker_hook::BrokerShutdownHook;
+use crate::client::client_housekeeping_service::ClientHousekeepingService;
 use crate::client::default_consumer_ids_change_listener::DefaultConsumerIdsChangeListener;
 use crate::client::manager::consumer_manager::ConsumerManager;
 use crate::client::manager::producer_manager::ProducerManager;
 use crate::client::net::broker_to_client::Broker2Client;
 use crate::client::rebalance::rebalance_lock_manager::RebalanceLockManager;
+use crate::coldctr::cold_data_cg_ctr_service::ColdDataCgCtrService;
+use crate::controller::replicas_manager::ReplicasManager;
+use crate::filter::commit_log_dispatcher_calc_bit_map::CommitLogDispatcherCalcBitMap;
 use crate::filter::manager::consumer_filter_manager::ConsumerFilterManager;
 use crate::hook::batch_check_before_put_message::BatchCheckBeforePutMessageHook;
 use crate::hook::check_before_put_message::CheckBeforePutMessageHook;
+use crate::latency::broker_fast_failure::BrokerFastFailure;
+use crate::latency::request_rate_limiter::RequestRateLimiter;
 use crate::long_polling::long_polling_service::pull_request_hold_service::PullRequestHoldService;
 use crate::long_polling::notify_message_arriving_listener::NotifyMessageArrivingListener;
+use crate::long_polling::polling_num_table::PollingNumTable;
+use crate::metrics::broker_metrics_manager::BrokerMetricsManager;
+use crate::mqtrace::consume_message_hook::ConsumeMessageHook;
+use crate::mqtrace::send_message_hook::SendMessageHook;
 use crate::offset::manager::broadcast_offset_manager::BroadcastOffsetManager;
 use crate::offset::manager::consumer_offset_manager::ConsumerOffsetManager;
 use crate::offset::manager::consumer_order_info_manager::ConsumerOrderInfoManager;
 use crate::out_api::broker_outer_api::BrokerOuterAPI;
+use crate::processor::ack_message_processor::AckMessageProcessor;
 use crate::processor::admin_broker_processor::AdminBrokerProcessor;
+use crate::processor::change_invisible_time_processor::ChangeInvisibleTimeProcessor;
 use crate::processor::client_manage_processor::ClientManageProcessor;
 use crate::processor::consumer_manage_processor::ConsumerManageProcessor;
 use crate::processor::default_pull_message_result_handler::DefaultPullMessageResultHandler;
 use crate::processor::end_transaction_processor::EndTransactionProcessor;
+use crate::processor::notification_processor::NotificationProcessor;
+use crate::processor::peek_message_processor::PeekMessageProcessor;
+use crate::processor::polling_info_processor::PollingInfoProcessor;
+use crate::processor::pop_message_processor::PopMessageProcessor;
+use crate::processor::pop_revive_service::PopReviveService;
 use crate::processor::pull_message_processor::PullMessageProcessor;
 use crate::processor::pull_message_result_handler::PullMessageResultHandler;
 use crate::processor::query_assignment_processor::QueryAssignmentProcessor;
@@ -78,6 +110,7 @@ use crate::processor::reply_message_processor::ReplyMessageProcessor;
 use crate::processor::send_message_processor::SendMessageProcessor;
 use crate::processor::BrokerRequestProcessor;
 use crate::schedule::schedule_message_service::ScheduleMessageService;
+use crate::slave::slave_synchronize::SlaveSynchronize;
 use crate::subscription::manager::subscription_group_manager::SubscriptionGroupManager;
 use crate::topic::manager::topic_config_manager::TopicConfigManager;
 use crate::topic::manager::topic_queue_mapping_manager::TopicQueueMappingManager;
@@ -89,9 +122,12 @@ use crate::transaction::queue::transactional_message_bridge::TransactionalMessag
 use crate::transaction::transaction_metrics_flush_service::TransactionMetricsFlushService;
 use crate::transaction::transactional_message_check_service::TransactionalMessageCheckService;
 
+const HA_ADDRESS_MIN_LENGTH: usize = 6;
+const ACL_FILE_WATCH_INTERVAL_MILLIS: u64 = 500;
+
 pub(crate) struct BrokerRuntime {
-    broker_config: Arc<BrokerConfig>,
-    message_store_config: Arc<MessageStoreConfig>,
+    broker_config: ArcMut<BrokerConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     server_config: Arc<ServerConfig>,
     topic_config_manager: TopicConfigManager,
     topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
@@ -113,6 +149,7 @@ pub(crate) struct BrokerRuntime {
     broker_runtime: Option<RocketMQRuntime>,
     producer_manager: Arc<ProducerManager>,
     consumer_manager: Arc<ConsumerManager>,
+    consumer_ids_change_listener: DefaultConsumerIdsChangeListener,
     broadcast_offset_manager: Arc<BroadcastOffsetManager>,
     drop: Arc<AtomicBool>,
     shutdown: Arc<AtomicBool>,
@@ -124,6 +161,8 @@ pub(crate) struct BrokerRuntime {
     is_isolated: Arc<AtomicBool>,
     #[cfg(feature = "local_file_store")]
     pull_request_hold_service: Option<ArcMut<PullRequestHoldService<DefaultMessageStore>>>,
+    #[cfg(feature = "local_file_store")]
+    pop_revive_service: Option<ArcMut<PopReviveService<DefaultMessageStore>>>,
     rebalance_lock_manager: Arc<RebalanceLockManager>,
     broker_member_group: Arc<BrokerMemberGroup>,
     #[cfg(feature = "local_file_store")]
@@ -135,6 +174,20 @@ pub(crate) struct BrokerRuntime {
     transactional_message_check_service: Option<Arc<TransactionalMessageCheckService>>,
     transaction_metrics_flush_service: Option<Arc<TransactionMetricsFlushService>>,
     topic_route_info_manager: Arc<TopicRouteInfoManager>,
+    broker_fast_failure: Arc<BrokerFastFailure>,
+    request_rate_limiter: Arc<RequestRateLimiter>,
+    #[cfg(feature = "local_file_store")]
+    slave_synchronize: Option<SlaveSynchronize<DefaultMessageStore>>,
+    #[cfg(feature = "local_file_store")]
+    replicas_manager: Option<ReplicasManager>,
+    rpc_hooks: Vec<Arc<Box<dyn RPCHook>>>,
+    send_message_hook_vec: ArcMut<Vec<Box<dyn SendMessageHook>>>,
+    consume_message_hook_vec: ArcMut<Vec<Box<dyn ConsumeMessageHook>>>,
+    // plugin store hooks registered before the message store exists
+    put_message_hook_vec: Arc<parking_lot::Mutex<Vec<BoxedPutMessageHook>>>,
+    send_message_back_hook: Option<ArcSendMessageBackHook>,
+    server_shutdown: Arc<tokio::sync::watch::Sender<bool>>,
+    server_handles: Arc<parking_lot::Mutex<Vec<JoinHandle<()>>>>,
 }
 
 impl Clone for BrokerRuntime {
@@ -147,7 +200,7 @@ impl Clone for BrokerRuntime {
             topic_queue_mapping_manager: self.topic_queue_mapping_manager.clone(),
             consumer_offset_manager: self.consumer_offset_manager.clone(),
             subscription_group_manager: self.subscription_group_manager.clone(),
-            consumer_filter_manager: Arc::new(Default::default()),
+            consumer_filter_manager: self.consumer_filter_manager.clone(),
             consumer_order_info_manager: Arc::new(Default::default()),
             message_store: self.message_store.clone(),
             broker_stats: self.broker_stats.clone(),
@@ -157,6 +210,7 @@ impl Clone for BrokerRuntime {
             broker_runtime: None,
             producer_manager: self.producer_manager.clone(),
             consumer_manager: self.consumer_manager.clone(),
+            consumer_ids_change_listener: self.consumer_ids_change_listener.clone(),
             broadcast_offset_manager: self.broadcast_offset_manager.clone(),
             drop: self.drop.clone(),
             shutdown: self.shutdown.clone(),
@@ -167,6 +221,7 @@ impl Clone for BrokerRuntime {
             should_start_time: self.should_start_time.clone(),
             is_isolated: self.is_isolated.clone(),
             pull_request_hold_service: self.pull_request_hold_service.clone(),
+            pop_revive_service: self.pop_revive_service.clone(),
             rebalance_lock_manager: self.rebalance_lock_manager.clone(),
             broker_member_group: self.broker_member_group.clone(),
             transactional_message_service: self.transactional_message_service.clone(),
@@ -174,6 +229,17 @@ impl Clone for BrokerRuntime {
             transactional_message_check_service: None,
             transaction_metrics_flush_service: None,
             topic_route_info_manager: self.topic_route_info_manager.clone(),
+            broker_fast_failure: self.broker_fast_failure.clone(),
+            request_rate_limiter: self.request_rate_limiter.clone(),
+            slave_synchronize: self.slave_synchronize.clone(),
+            replicas_manager: self.replicas_manager.clone(),
+            rpc_hooks: self.rpc_hooks.clone(),
+            send_message_hook_vec: self.send_message_hook_vec.clone(),
+            consume_message_hook_vec: self.consume_message_hook_vec.clone(),
+            put_message_hook_vec: self.put_message_hook_vec.clone(),
+            send_message_back_hook: self.send_message_back_hook.clone(),
+            server_shutdown: self.server_shutdown.clone(),
+            server_handles: self.server_handles.clone(),
         }
     }
 }
@@ -184,12 +250,12 @@ impl BrokerRuntime {
         message_store_config: MessageStoreConfig,
         server_config: ServerConfig,
     ) -> Self {
-        let broker_config = Arc::new(broker_config);
+        let broker_config = ArcMut::new(broker_config);
         let runtime = RocketMQRuntime::new_multi(10, "broker-thread");
         let broker_outer_api =
             Arc::new(BrokerOuterAPI::new(Arc::new(TokioClientConfig::default())));
         let server_config = Arc::new(server_config);
-        let message_store_config = Arc::new(message_store_config);
+        let message_store_config = ArcMut::new(message_store_config);
         let topic_queue_mapping_manager =
             Arc::new(TopicQueueMappingManager::new(broker_config.clone()));
         let broker_runtime_inner = Arc::new(BrokerRuntimeInner {
@@ -203,8 +269,13 @@ impl BrokerRuntime {
             TopicConfigManager::new(broker_config.clone(), broker_runtime_inner);
         let mut stats_manager = BrokerStatsManager::new(broker_config.clone());
         let producer_manager = Arc::new(ProducerManager::new());
+        let consumer_filter_manager = Arc::new(ConsumerFilterManager::new(broker_config.clone()));
+        let consumer_ids_change_listener = DefaultConsumerIdsChangeListener::new(
+            broker_config.clone(),
+            consumer_filter_manager.clone(),
+        );
         let consumer_manager = Arc::new(ConsumerManager::new_with_broker_stats(
-            Box::new(DefaultConsumerIdsChangeListener {}),
+            Box::new(consumer_ids_change_listener.clone()),
             broker_config.clone(),
         ));
         stats_manager.set_producer_state_getter(Arc::new(ProducerStateGetter {
@@ -236,16 +307,20 @@ impl BrokerRuntime {
                 broker_config.clone(),
                 None,
             )),
-            consumer_filter_manager: Arc::new(Default::default()),
-            consumer_order_info_manager: Arc::new(Default::default()),
+            consumer_filter_manager,
+            consumer_order_info_manager: Arc::new(ConsumerOrderInfoManager {
+                broker_config: broker_config.clone(),
+                ..Default::default()
+            }),
             message_store: None,
             broker_stats: None,
-            schedule_message_service: Default::default(),
+            schedule_message_service: ScheduleMessageService::new(broker_config.clone()),
             timer_message_store: None,
             broker_out_api: broker_outer_api.clone(),
             broker_runtime: Some(runtime),
             producer_manager,
             consumer_manager,
+            consumer_ids_change_listener,
             broadcast_offset_manager: Arc::new(Default::default()),
             drop: Arc::new(AtomicBool::new(false)),
             shutdown: Arc::new(AtomicBool::new(false)),
@@ -256,12 +331,24 @@ impl BrokerRuntime {
             should_start_time: Arc::new(AtomicU64::new(0)),
             is_isolated: Arc::new(AtomicBool::new(false)),
             pull_request_hold_service: None,
+            pop_revive_service: None,
             rebalance_lock_manager: Arc::new(Default::default()),
             broker_member_group: Arc::new(broker_member_group),
             transactional_message_service: None,
             transactional_message_check_listener: None,
             transactional_message_check_service: None,
             transaction_metrics_flush_service: None,
+            broker_fast_failure: Arc::new(BrokerFastFailure::new(broker_config.clone())),
+            request_rate_limiter: Arc::new(RequestRateLimiter::new(broker_config.clone())),
+            slave_synchronize: None,
+            replicas_manager: None,
+            rpc_hooks: Vec::new(),
+            send_message_hook_vec: ArcMut::new(Vec::new()),
+            consume_message_hook_vec: ArcMut::new(Vec::new()),
+            put_message_hook_vec: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            send_message_back_hook: None,
+            server_shutdown: Arc::new(tokio::sync::watch::channel(false).0),
+            server_handles: Arc::new(parking_lot::Mutex::new(Vec::new())),
             topic_route_info_manager: Arc::new(TopicRouteInfoManager::new(
                 broker_outer_api,
                 broker_config,
@@ -277,23 +364,101 @@ impl BrokerRuntime {
         &self.message_store_config
     }
 
-    pub fn shutdown(&mut self) {
-        self.broker_out_api.shutdown();
+    /// Shuts the broker down in order: unregister from the name servers, stop accepting new
+    /// requests, wait up to `shutdown_drain_timeout_mills` for in-flight requests to be
+    /// answered, then persist metadata and flush the store.
+    pub async fn graceful_shutdown(&mut self) {
+        if self
+            .drop
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+        self.shutdown_basic_service();
+
+        if !self.broker_config.duplication_enable {
+            let cluster_name = self
+                .broker_config
+                .broker_identity
+                .broker_cluster_name
+                .clone();
+            let broker_name = self.broker_config.broker_identity.broker_name.clone();
+            let broker_addr = CheetahString::from_string(format!(
+                "{}:{}",
+                self.broker_config.broker_ip1, self.server_config.listen_port
+            ));
+            self.broker_out_api
+                .unregister_broker_all(
+                    &cluster_name,
+                    &broker_addr,
+                    &broker_name,
+                    self.broker_config.broker_identity.broker_id,
+                )
+                .await;
+            info!("[Broker shutdown]unregister from name servers success");
+        }
+
+        let _ = self.server_shutdown.send(true);
+        let handles = std::mem::take(&mut *self.server_handles.lock());
+        let drain_timeout =
+            Duration::from_millis(self.broker_config.shutdown_drain_timeout_mills.get());
+        let drain = async move {
+            for handle in handles {
+                let _ = handle.await;
+            }
+        };
+        match tokio::time::timeout(drain_timeout, drain).await {
+            Ok(_) => info!("[Broker shutdown]in-flight requests drained"),
+            Err(_) => warn!(
+                "[Broker shutdown]in-flight requests not drained within {}ms",
+                self.broker_config.shutdown_drain_timeout_mills.get()
+            ),
+        }
+
+        self.shutdown().await;
+    }
+
+    pub async fn shutdown(&mut self) {
+        self.shutdown_services();
+
+        // flushes the commit log, consume queues and checkpoint, then removes the abort file
         if let Some(message_store) = &mut self.message_store {
-            message_store.shutdown()
+            message_store.shutdown().await
         }
 
-        self.topic_config_manager.persist();
-        info!("[Broker shutdown]TopicConfigManager persist success");
-        let _ = self.topic_config_manager.stop();
+        self.broker_out_api.shutdown();
+
+        if let Some(runtime) = self.broker_runtime.take() {
+            runtime.shutdown();
+        }
+    }
+
+    /// Stops the services and persists the metadata, everything before the store is shut down.
+    fn shutdown_services(&mut self) {
+        self.broker_fast_failure.shutdown();
+        self.broker_stats_manager.shutdown();
 
         if let Some(pull_request_hold_service) = self.pull_request_hold_service.as_mut() {
             pull_request_hold_service.shutdown();
         }
 
-        if let Some(runtime) = self.broker_runtime.take() {
-            runtime.shutdown();
+        if let Some(pop_revive_service) = self.pop_revive_service.as_ref() {
+            pop_revive_service.shutdown();
         }
+
+        self.topic_config_manager.persist();
+        info!("[Broker shutdown]TopicConfigManager persist success");
+        let _ = self.topic_config_manager.stop();
+
+        self.topic_queue_mapping_manager.persist();
+        info!("[Broker shutdown]TopicQueueMappingManager persist success");
+
+        self.consumer_offset_manager.persist();
+        info!("[Broker shutdown]ConsumerOffsetManager persist success");
+
+        self.subscription_group_manager.persist();
+        info!("[Broker shutdown]SubscriptionGroupManager persist success");
     }
 
     pub(crate) fn shutdown_basic_service(&mut self) {
@@ -312,7 +477,19 @@ impl Drop for BrokerRuntime {
                 .clone()
                 .compare_exchange(false, true, Ordering::SeqCst, Ordering::Relaxed);
         if result.is_ok() {
-            self.shutdown();
+            self.shutdown_services();
+            if let Some(mut message_store) = self.message_store.take() {
+                match tokio::runtime::Handle::try_current() {
+                    Ok(handle) => {
+                        handle.spawn(async move { message_store.shutdown().await });
+                    }
+                    Err(_) => warn!("[Broker shutdown]no runtime to shut the message store down"),
+                }
+            }
+            self.broker_out_api.shutdown();
+            if let Some(runtime) = self.broker_runtime.take() {
+                runtime.shutdown();
+            }
         }
     }
 }
@@ -360,6 +537,10 @@ impl BrokerRuntime {
                 let time_message_store = TimerMessageStore::new(Some(message_store.clone()));
                 message_store.set_timer_message_store(Arc::new(time_message_store));
             }
+            message_store.add_first_dispatcher(Box::new(CommitLogDispatcherCalcBitMap::new(
+                self.broker_config.clone(),
+                self.consumer_filter_manager.clone(),
+            )));
             self.consumer_offset_manager
                 .set_message_store(Some(message_store.clone()));
             self.topic_config_manager
@@ -379,8 +560,7 @@ impl BrokerRuntime {
         let mut result: bool = true;
 
         if self.broker_config.enable_controller_mode {
-            info!("Start controller mode(Support for future versions)");
-            unimplemented!()
+            info!("Start controller mode");
         }
         if self.message_store.is_some() {
             self.register_message_store_hook();
@@ -397,7 +577,7 @@ impl BrokerRuntime {
             self.initialize_resources();
             self.initialize_scheduled_tasks().await;
             self.initial_transaction();
-            self.initial_acl();
+            result &= self.initial_acl();
             self.initial_rpc_hooks();
             self.initial_request_pipeline();
         }
@@ -413,9 +593,50 @@ impl BrokerRuntime {
             message_store.set_put_message_hook(Box::new(BatchCheckBeforePutMessageHook::new(
                 self.topic_config_manager.topic_config_table(),
             )));
+            for hook in self.put_message_hook_vec.lock().drain(..) {
+                message_store.set_put_message_hook(hook);
+            }
+            if let Some(hook) = self.send_message_back_hook.clone() {
+                message_store.set_send_message_back_hook(hook);
+            }
+        }
+    }
+
+    /// Registers a hook run around every message sent to this broker, including replies.
+    ///
+    /// Hooks must be registered before the broker starts serving.
+    pub fn register_send_message_hook(&mut self, hook: Box<dyn SendMessageHook>) {
+        info!("register SendMessageHook Hook, {}", hook.hook_name());
+        self.send_message_hook_vec.push(hook);
+    }
+
+    /// Registers a hook run around every message pulled from or sent back to this broker.
+    ///
+    /// Hooks must be registered before the broker starts serving.
+    pub fn register_consume_message_hook(&mut self, hook: Box<dyn ConsumeMessageHook>) {
+        info!("register ConsumeMessageHook Hook, {}", hook.hook_name());
+        self.consume_message_hook_vec.push(hook);
+    }
+
+    /// Registers a hook the message store runs before putting a message, after the built-in
+    /// checks.
+    pub fn register_put_message_hook(&mut self, hook: BoxedPutMessageHook) {
+        info!("register PutMessageHook Hook, {}", hook.hook_name());
+        match self.message_store {
+            Some(ref message_store) => message_store.set_put_message_hook(hook),
+            None => self.put_message_hook_vec.lock().push(hook),
         }
     }
 
+    /// Sets the hook the message store uses to send messages back to another broker.
+    pub fn set_send_message_back_hook(&mut self, hook: ArcSendMessageBackHook) {
+        info!("set SendMessageBackHook Hook, {}", hook.hook_name());
+        if let Some(ref message_store) = self.message_store {
+            message_store.set_send_message_back_hook(hook.clone());
+        }
+        self.send_message_back_hook = Some(hook);
+    }
+
     fn initialize_remoting_server(&mut self) {
 
         // fast broker remoting_server implementation in future versions
@@ -431,27 +652,37 @@ impl BrokerRuntime {
         DefaultMessageStore,
         DefaultTransactionalMessageService<DefaultMessageStore>,
     > {
-        let send_message_processor = SendMessageProcessor::new(
+        let mut send_message_processor = SendMessageProcessor::new(
             self.topic_queue_mapping_manager.clone(),
             self.subscription_group_manager.clone(),
             self.topic_config_manager.clone(),
             self.broker_config.clone(),
+            self.message_store_config.clone(),
             self.message_store.clone().unwrap(),
             self.transactional_message_service.as_ref().unwrap().clone(),
             self.rebalance_lock_manager.clone(),
             self.broker_stats_manager.clone(),
         );
-        let reply_message_processor = ReplyMessageProcessor::new(
+        let mut reply_message_processor = ReplyMessageProcessor::new(
             self.topic_queue_mapping_manager.clone(),
             self.subscription_group_manager.clone(),
             self.topic_config_manager.clone(),
             self.broker_config.clone(),
+            self.message_store_config.clone(),
             self.message_store.clone().unwrap(),
             self.rebalance_lock_manager.clone(),
             self.broker_stats_manager.clone(),
             Some(self.producer_manager.clone()),
             self.transactional_message_service.as_ref().unwrap().clone(),
         );
+        send_message_processor.set_message_hooks(
+            self.send_message_hook_vec.clone(),
+            self.consume_message_hook_vec.clone(),
+        );
+        reply_message_processor.set_message_hooks(
+            self.send_message_hook_vec.clone(),
+            self.consume_message_hook_vec.clone(),
+        );
         let mut pull_message_result_handler =
             ArcMut::new(Box::new(DefaultPullMessageResultHandler::new(
                 self.message_store_config.clone(),
@@ -461,7 +692,7 @@ impl BrokerRuntime {
                 self.broadcast_offset_manager.clone(),
                 self.broker_stats_manager.clone(),
                 self.broker_config.clone(),
-                Arc::new(Default::default()),
+                self.consume_message_hook_vec.clone(),
             )) as Box<dyn PullMessageResultHandler>);
         let message_store = self.message_store.clone().unwrap();
         let pull_message_processor = ArcMut::new(PullMessageProcessor::new(
@@ -475,6 +706,10 @@ impl BrokerRuntime {
             Arc::new(self.consumer_offset_manager.clone()),
             Arc::new(BroadcastOffsetManager::default()),
             message_store.clone(),
+            Arc::new(ColdDataCgCtrService::new(
+                self.broker_config.clone(),
+                self.message_store_config.clone(),
+            )),
             self.broker_out_api.clone(),
         ));
 
@@ -515,26 +750,68 @@ impl BrokerRuntime {
             self.message_store_config.clone(),
             self.topic_config_manager.clone(),
             self.consumer_offset_manager.clone(),
+            self.subscription_group_manager.clone(),
             self.topic_queue_mapping_manager.clone(),
             self.message_store.as_ref().unwrap().clone(),
             self.schedule_message_service.clone(),
             self.broker_stats.clone(),
             self.consumer_manager.clone(),
+            self.producer_manager.clone(),
             self.broker_out_api.clone(),
             self.broker_stats_manager.clone(),
             self.rebalance_lock_manager.clone(),
             self.broker_member_group.clone(),
         );
 
+        let polling_num_table = Arc::new(PollingNumTable::default());
+        let pop_revive_service = ArcMut::new(PopReviveService::new(
+            self.broker_config.clone(),
+            self.topic_config_manager.clone(),
+            Arc::new(self.consumer_offset_manager.clone()),
+            message_store.clone(),
+        ));
+        self.pop_revive_service = Some(pop_revive_service.clone());
         BrokerRequestProcessor {
             send_message_processor: ArcMut::new(send_message_processor),
             pull_message_processor,
-            peek_message_processor: Default::default(),
-            pop_message_processor: Default::default(),
-            ack_message_processor: Default::default(),
-            change_invisible_time_processor: Default::default(),
-            notification_processor: Default::default(),
-            polling_info_processor: Default::default(),
+            peek_message_processor: ArcMut::new(PeekMessageProcessor::new(
+                self.broker_config.clone(),
+                Arc::new(self.topic_config_manager.clone()),
+                self.subscription_group_manager.clone(),
+                Arc::new(self.consumer_offset_manager.clone()),
+                message_store.clone(),
+            )),
+            pop_message_processor: ArcMut::new(PopMessageProcessor::new(
+                self.broker_config.clone(),
+                Arc::new(self.topic_config_manager.clone()),
+                self.subscription_group_manager.clone(),
+                self.consumer_filter_manager.clone(),
+                message_store.clone(),
+                pop_revive_service.clone(),
+                polling_num_table.clone(),
+            )),
+            ack_message_processor: ArcMut::new(AckMessageProcessor::new(
+                Arc::new(self.topic_config_manager.clone()),
+                pop_revive_service.clone(),
+            )),
+            change_invisible_time_processor: ArcMut::new(ChangeInvisibleTimeProcessor::new(
+                Arc::new(self.topic_config_manager.clone()),
+                pop_revive_service,
+            )),
+            notification_processor: ArcMut::new(NotificationProcessor::new(
+                self.broker_config.clone(),
+                Arc::new(self.topic_config_manager.clone()),
+                self.subscription_group_manager.clone(),
+                Arc::new(self.consumer_offset_manager.clone()),
+                message_store.clone(),
+                polling_num_table.clone(),
+            )),
+            polling_info_processor: ArcMut::new(PollingInfoProcessor::new(
+                self.broker_config.clone(),
+                Arc::new(self.topic_config_manager.clone()),
+                self.subscription_group_manager.clone(),
+                polling_num_table,
+            )),
             reply_message_processor: ArcMut::new(reply_message_processor),
             admin_broker_processor: ArcMut::new(admin_broker_processor),
             client_manage_processor: ArcMut::new(ClientManageProcessor::new(
@@ -550,6 +827,8 @@ impl BrokerRuntime {
                 self.broker_config.clone(),
                 self.topic_route_info_manager.clone(),
                 self.consumer_manager.clone(),
+                Arc::new(self.consumer_offset_manager.clone()),
+                self.message_store.as_ref().unwrap().clone(),
             )),
             query_message_processor: ArcMut::new(query_message_processor),
             end_transaction_processor: ArcMut::new(EndTransactionProcessor::new(
@@ -558,6 +837,10 @@ impl BrokerRuntime {
                 self.transactional_message_service.as_ref().unwrap().clone(),
                 self.message_store.as_ref().unwrap().clone(),
             )),
+            broker_fast_failure: self.broker_fast_failure.clone(),
+            request_rate_limiter: self.request_rate_limiter.clone(),
+            replicas_manager: self.replicas_manager.clone(),
+            fast_channel: false,
         }
     }
 
@@ -583,6 +866,31 @@ impl BrokerRuntime {
                 }
             });
 
+        let client_housekeeping_service = ClientHousekeepingService::new(
+            self.producer_manager.clone(),
+            self.consumer_manager.clone(),
+            self.rebalance_lock_manager.clone(),
+            self.broker_stats_manager.clone(),
+            self.broker_config.channel_expired_timeout,
+        );
+        self.broker_runtime
+            .as_ref()
+            .unwrap()
+            .get_handle()
+            .spawn(async move {
+                info!("ClientHousekeepingService Start scheduled task");
+                tokio::time::sleep(Duration::from_millis(1000 * 10)).await;
+                loop {
+                    let current_execution_time = tokio::time::Instant::now();
+                    client_housekeeping_service.scan_exception_channel();
+                    let next_execution_time =
+                        current_execution_time + Duration::from_millis(1000 * 10);
+                    let delay =
+                        next_execution_time.saturating_duration_since(tokio::time::Instant::now());
+                    tokio::time::sleep(delay).await;
+                }
+            });
+
         let consumer_offset_manager = self.consumer_offset_manager.clone();
         let flush_consumer_offset_interval = self.broker_config.flush_consumer_offset_interval;
         self.broker_runtime
@@ -661,10 +969,83 @@ impl BrokerRuntime {
                 }
             });
 
+        let consumer_ids_change_listener = self.consumer_ids_change_listener.clone();
+        self.broker_runtime
+            .as_ref()
+            .unwrap()
+            .get_handle()
+            .spawn(async move {
+                info!("Consumer ids change notify Start scheduled task");
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                loop {
+                    let current_execution_time = tokio::time::Instant::now();
+                    consumer_ids_change_listener.notify_consumer_change().await;
+                    let next_execution_time = current_execution_time + Duration::from_secs(15);
+                    let delay =
+                        next_execution_time.saturating_duration_since(tokio::time::Instant::now());
+                    tokio::time::sleep(delay).await;
+                }
+            });
+
         if self.broker_config.enable_controller_mode {
             self.update_master_haserver_addr_periodically = true;
         }
 
+        let is_slave_without_controller = !self.message_store_config.enable_dledger_commit_log
+            && !self.message_store_config.duplication_enable
+            && !self.broker_config.enable_controller_mode
+            && self.message_store_config.broker_role == BrokerRole::Slave;
+        if is_slave_without_controller {
+            self.update_master_haserver_addr_periodically = self
+                .message_store_config
+                .ha_master_address
+                .as_ref()
+                .map_or(true, |address| address.len() < HA_ADDRESS_MIN_LENGTH);
+        }
+        // in controller mode any replica may become a slave, the replicas manager sets the
+        // master to synchronize from
+        if is_slave_without_controller || self.broker_config.enable_controller_mode {
+            let slave_synchronize = SlaveSynchronize::new(
+                self.broker_config.clone(),
+                self.broker_out_api.clone(),
+                self.topic_config_manager.clone(),
+                self.topic_queue_mapping_manager.clone(),
+                self.consumer_offset_manager.clone(),
+                self.subscription_group_manager.clone(),
+                self.schedule_message_service.clone(),
+            );
+            self.slave_synchronize = Some(slave_synchronize.clone());
+            self.broker_runtime
+                .as_ref()
+                .unwrap()
+                .get_handle()
+                .spawn(async move {
+                    info!("Slave synchronize Start scheduled task");
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                    loop {
+                        let current_execution_time = tokio::time::Instant::now();
+                        slave_synchronize.sync_all().await;
+                        let next_execution_time = current_execution_time + Duration::from_secs(60);
+                        let delay = next_execution_time
+                            .saturating_duration_since(tokio::time::Instant::now());
+                        tokio::time::sleep(delay).await;
+                    }
+                });
+        }
+
+        if self.broker_config.enable_controller_mode {
+            self.replicas_manager = Some(ReplicasManager::new(
+                self.broker_config.clone(),
+                self.broker_out_api.clone(),
+                self.message_store.clone().unwrap(),
+                self.slave_synchronize.clone(),
+                CheetahString::from_string(format!(
+                    "{}:{}",
+                    self.broker_config.broker_ip1, self.server_config.listen_port
+                )),
+            ));
+        }
+
         if let Some(ref namesrv_address) = self.broker_config.namesrv_addr.clone() {
             self.update_namesrv_addr().await;
             info!(
@@ -716,7 +1097,29 @@ impl BrokerRuntime {
         self.transaction_metrics_flush_service = Some(Arc::new(TransactionMetricsFlushService));
     }
 
-    fn initial_acl(&mut self) {}
+    /// Registers the plain acl validator when acl is enabled. Fails the broker's
+    /// initialization if the acl file cannot be loaded, rather than serving unauthenticated.
+    fn initial_acl(&mut self) -> bool {
+        if !self.broker_config.acl_enable {
+            info!("The broker does not enable acl");
+            return true;
+        }
+        let acl_file = PathBuf::from(EnvUtils::get_rocketmq_home())
+            .join("conf")
+            .join("plain_acl.yml");
+        match PlainAccessValidator::new(acl_file.clone()) {
+            Ok(validator) => {
+                validator.start_watch(Duration::from_millis(ACL_FILE_WATCH_INTERVAL_MILLIS));
+                info!("Load acl file {} success", acl_file.display());
+                self.rpc_hooks.push(Arc::new(Box::new(validator)));
+                true
+            }
+            Err(err) => {
+                error!("Initial acl failed: {}", err);
+                false
+            }
+        }
+    }
 
     fn initial_rpc_hooks(&mut self) {}
 
@@ -726,28 +1129,81 @@ impl BrokerRuntime {
 
     fn start_basic_service(&mut self) {
         let request_processor = self.init_processor();
-        let fast_request_processor = request_processor.clone();
+        let fast_request_processor = request_processor.fast_channel();
         self.message_store
             .as_mut()
             .unwrap()
             .start()
             .expect("Message store start error");
+        self.broker_stats_manager.start();
 
-        let server = RocketMQServer::new(self.server_config.clone());
+        let mut server = RocketMQServer::new(self.server_config.clone());
+        for hook in self.rpc_hooks.iter() {
+            server.register_rpc_hook(hook.clone());
+        }
         //start nomarl broker remoting_server
-        tokio::spawn(async move { server.run(request_processor).await });
+        let mut server_shutdown = self.server_shutdown.subscribe();
+        let server_handle = tokio::spawn(async move {
+            server
+                .run_until(request_processor, async move {
+                    let _ = server_shutdown.wait_for(|shutdown| *shutdown).await;
+                })
+                .await
+        });
         //start fast broker remoting_server
-        let mut fast_server_config = (*self.server_config).clone();
-        fast_server_config.listen_port = self.server_config.listen_port - 2;
-        let fast_server = RocketMQServer::new(Arc::new(fast_server_config));
-        tokio::spawn(async move { fast_server.run(fast_request_processor).await });
+        let fast_server_config = fast_server_config(&self.server_config);
+        let mut fast_server = RocketMQServer::new(Arc::new(fast_server_config));
+        for hook in self.rpc_hooks.iter() {
+            fast_server.register_rpc_hook(hook.clone());
+        }
+        let mut fast_server_shutdown = self.server_shutdown.subscribe();
+        let fast_server_handle = tokio::spawn(async move {
+            fast_server
+                .run_until(fast_request_processor, async move {
+                    let _ = fast_server_shutdown.wait_for(|shutdown| *shutdown).await;
+                })
+                .await
+        });
+        self.server_handles
+            .lock()
+            .extend([server_handle, fast_server_handle]);
 
         if let Some(pull_request_hold_service) = self.pull_request_hold_service.as_mut() {
             let this = pull_request_hold_service.clone();
             pull_request_hold_service.start(this);
         }
 
+        if let Some(pop_revive_service) = self.pop_revive_service.as_ref() {
+            pop_revive_service.start(pop_revive_service.clone());
+        }
+
         self.topic_route_info_manager.start();
+
+        self.broker_fast_failure
+            .start(self.message_store.as_ref().unwrap().clone());
+    }
+
+    fn start_metrics_exporter(&self) {
+        if self.broker_config.metrics_exporter_type != MetricsExporterType::Prom {
+            return;
+        }
+        let metrics_manager = BrokerMetricsManager::new(
+            self.broker_config.clone(),
+            self.message_store_config.clone(),
+            self.message_store.clone().unwrap(),
+            self.broker_stats.clone(),
+            self.broker_stats_manager.clone(),
+            self.producer_manager.clone(),
+            self.consumer_manager.clone(),
+            self.topic_config_manager.topic_config_table(),
+            self.subscription_group_manager.clone(),
+            self.pull_request_hold_service.clone(),
+        );
+        PrometheusExporter::start(
+            self.broker_config.metrics_prom_exporter_host.as_str(),
+            self.broker_config.metrics_prom_exporter_port,
+            Arc::new(move || metrics_manager.collect()),
+        );
     }
 
     async fn update_namesrv_addr(&mut self) {
@@ -777,7 +1233,10 @@ impl BrokerRuntime {
         }
 
         self.broker_out_api.start().await;
+        self.register_dledger_role_change_listener();
         self.start_basic_service();
+        self.start_metrics_exporter();
+        self.start_replicas_manager().await;
 
         if !self.is_isolated.load(Ordering::Acquire)
             && !self.message_store_config.enable_dledger_commit_log
@@ -789,6 +1248,7 @@ impl BrokerRuntime {
         let mut cloned_broker_runtime = self.clone();
         let should_start_time = self.should_start_time.clone();
         let is_isolated = self.is_isolated.clone();
+        let shutdown = self.shutdown.clone();
         let broker_config = self.broker_config.clone();
         self.broker_runtime
             .as_ref()
@@ -801,6 +1261,9 @@ impl BrokerRuntime {
                 let initial_delay = Duration::from_secs(10);
                 tokio::time::sleep(initial_delay).await;
                 loop {
+                    if shutdown.load(Ordering::Acquire) {
+                        break;
+                    }
                     let start_time = should_start_time.load(Ordering::Relaxed);
                     if get_current_millis() < start_time {
                         info!("Register to namesrv after {}", start_time);
@@ -869,6 +1332,81 @@ impl BrokerRuntime {
 
     pub(crate) fn schedule_send_heartbeat(&mut self) {}
 
+    /// Joins the controller, then keeps sending heartbeats and following the replica
+    /// info of the group.
+    async fn start_replicas_manager(&mut self) {
+        let Some(replicas_manager) = self.replicas_manager.clone() else {
+            return;
+        };
+        if !replicas_manager.start().await {
+            error!("Replicas manager start failed, the broker keeps its configured role");
+        }
+        let heartbeat_interval =
+            Duration::from_millis(self.broker_config.broker_heartbeat_interval);
+        let heartbeat_replicas_manager = replicas_manager.clone();
+        self.broker_runtime
+            .as_ref()
+            .unwrap()
+            .get_handle()
+            .spawn(async move {
+                loop {
+                    tokio::time::sleep(heartbeat_interval).await;
+                    heartbeat_replicas_manager
+                        .send_heartbeat_to_controller()
+                        .await;
+                }
+            });
+        let sync_period = Duration::from_millis(self.broker_config.sync_controller_metadata_period);
+        self.broker_runtime
+            .as_ref()
+            .unwrap()
+            .get_handle()
+            .spawn(async move {
+                loop {
+                    tokio::time::sleep(sync_period).await;
+                    replicas_manager.sync_controller_metadata().await;
+                }
+            });
+    }
+
+    /// Follows the dledger elections: the leader registers as the master of the group,
+    /// the followers as slaves with an id derived from their member id.
+    fn register_dledger_role_change_listener(&mut self) {
+        if !self.message_store_config.enable_dledger_commit_log {
+            return;
+        }
+        let Some(dledger_server) = self
+            .message_store
+            .as_ref()
+            .and_then(|message_store| message_store.get_dledger_server().cloned())
+        else {
+            return;
+        };
+        let slave_broker_id = dledger_slave_broker_id(dledger_server.self_id().as_str());
+        let broker_runtime = self.clone();
+        let handle = self.broker_runtime.as_ref().unwrap().get_handle().clone();
+        dledger_server.register_role_change_listener(Box::new(move |role, term| {
+            let broker_id = match role {
+                DLedgerRole::Leader => mix_all::MASTER_ID,
+                DLedgerRole::Follower => slave_broker_id,
+                DLedgerRole::Candidate => return,
+            };
+            info!(
+                "dledger role changed to {} in term {}, register as broker {}",
+                role, term, broker_id
+            );
+            let mut broker_runtime = broker_runtime.clone();
+            broker_runtime
+                .broker_config
+                .mut_from_ref()
+                .broker_identity
+                .broker_id = broker_id;
+            handle.spawn(async move {
+                broker_runtime.register_broker_all(true, false, true).await;
+            });
+        }));
+    }
+
     pub(crate) fn start_service_without_condition(&mut self) {}
 
     /// Register broker to name remoting_server
@@ -881,19 +1419,20 @@ impl BrokerRuntime {
         let mut topic_config_table = HashMap::new();
         let table = self.topic_config_manager.topic_config_table();
         for topic_config in table.lock().values() {
-            let new_topic_config = if !PermName::is_writeable(self.broker_config.broker_permission)
-                || !PermName::is_readable(self.broker_config.broker_permission)
-            {
-                TopicConfig {
-                    topic_name: topic_config.topic_name.clone(),
-                    read_queue_nums: topic_config.read_queue_nums,
-                    write_queue_nums: topic_config.write_queue_nums,
-                    perm: topic_config.perm & self.broker_config.broker_permission,
-                    ..TopicConfig::default()
-                }
-            } else {
-                topic_config.clone()
-            };
+            let new_topic_config =
+                if !PermName::is_writeable(self.broker_config.broker_permission.get())
+                    || !PermName::is_readable(self.broker_config.broker_permission.get())
+                {
+                    TopicConfig {
+                        topic_name: topic_config.topic_name.clone(),
+                        read_queue_nums: topic_config.read_queue_nums,
+                        write_queue_nums: topic_config.write_queue_nums,
+                        perm: topic_config.perm & self.broker_config.broker_permission.get(),
+                        ..TopicConfig::default()
+                    }
+                } else {
+                    topic_config.clone()
+                };
             topic_config_table.insert(
                 new_topic_config.topic_name.as_ref().unwrap().clone(),
                 new_topic_config,
@@ -935,37 +1474,43 @@ impl BrokerRuntime {
 
         if self.broker_config.enable_split_registration
             || force_register
-            || Self::need_register(
-                self.broker_config
-                    .broker_identity
-                    .broker_cluster_name
-                    .clone()
-                    .as_str(),
-                self.broker_config.broker_ip1.clone().as_str(),
-                self.broker_config
-                    .broker_identity
-                    .broker_name
-                    .clone()
-                    .as_str(),
-                self.broker_config.broker_identity.broker_id,
-                self.broker_config.register_broker_timeout_mills,
-                self.broker_config.is_in_broker_container,
-            )
+            || self
+                .need_register(
+                    topic_config_wrapper
+                        .topic_config_serialize_wrapper
+                        .data_version
+                        .clone(),
+                )
+                .await
         {
             self.do_register_broker_all(check_order_config, oneway, topic_config_wrapper)
                 .await;
         }
     }
 
-    fn need_register(
-        _cluster_name: &str,
-        _broker_addr: &str,
-        _broker_name: &str,
-        _broker_id: u64,
-        _register_timeout_mills: i32,
-        _in_broker_container: bool,
-    ) -> bool {
-        unimplemented!()
+    /// Whether any name server holds a topic config data version different from the local one,
+    /// a full registration is skipped when all name servers are up to date.
+    async fn need_register(&self, data_version: DataVersion) -> bool {
+        let broker_addr = CheetahString::from_string(format!(
+            "{}:{}",
+            self.broker_config.broker_ip1, self.server_config.listen_port
+        ));
+        let changed_list = self
+            .broker_out_api
+            .need_register(
+                self.broker_config
+                    .broker_identity
+                    .broker_cluster_name
+                    .clone(),
+                broker_addr,
+                self.broker_config.broker_identity.broker_name.clone(),
+                self.broker_config.broker_identity.broker_id,
+                data_version,
+                self.broker_config.register_broker_timeout_mills as u64,
+                self.broker_config.is_in_broker_container,
+            )
+            .await;
+        changed_list.into_iter().any(|changed| changed)
     }
 
     async fn do_register_broker_all(
@@ -984,34 +1529,70 @@ impl BrokerRuntime {
             "{}:{}",
             self.broker_config.broker_ip1, self.server_config.listen_port
         ));
+        let ha_server_addr = CheetahString::from_string(format!(
+            "{}:{}",
+            self.broker_config
+                .broker_ip2
+                .as_ref()
+                .unwrap_or(&self.broker_config.broker_ip1),
+            self.message_store_config.ha_listen_port
+        ));
         let broker_id = self.broker_config.broker_identity.broker_id;
         let weak = Arc::downgrade(&self.broker_out_api);
-        self.broker_out_api
+        let register_broker_result_list = self
+            .broker_out_api
             .register_broker_all(
                 cluster_name,
-                broker_addr.clone(),
+                broker_addr,
                 broker_name,
                 broker_id,
-                broker_addr,
+                ha_server_addr,
                 topic_config_wrapper,
                 vec![],
                 oneway,
-                10000,
-                false,
-                false,
-                None,
-                Default::default(),
+                self.broker_config.register_broker_timeout_mills as u64,
+                self.broker_config.enable_slave_acting_master,
+                self.broker_config.compressed_register,
+                self.broker_config
+                    .enable_slave_acting_master
+                    .then_some(self.broker_config.broker_not_active_timeout_millis),
+                self.broker_config.region_id.clone(),
+                self.broker_config.broker_identity.clone(),
                 weak,
             )
             .await;
+        if let Some(register_broker_result) = register_broker_result_list.first() {
+            self.handle_register_broker_result(register_broker_result);
+        }
+    }
+
+    fn handle_register_broker_result(&mut self, register_broker_result: &RegisterBrokerResult) {
+        // the controller decides the master of the group in controller mode
+        if self.replicas_manager.is_some() {
+            return;
+        }
+        if self.update_master_haserver_addr_periodically
+            && !register_broker_result.ha_server_addr.is_empty()
+        {
+            if let Some(message_store) = self.message_store.as_ref() {
+                message_store
+                    .update_ha_master_address(register_broker_result.ha_server_addr.clone());
+            }
+        }
+        if let Some(slave_synchronize) = self.slave_synchronize.as_ref() {
+            slave_synchronize.set_master_addr(
+                Some(register_broker_result.master_addr.clone())
+                    .filter(|master_addr| !master_addr.is_empty()),
+            );
+        }
     }
 }
 
 #[derive(Clone)]
 pub(crate) struct BrokerRuntimeInner {
     pub(crate) broker_out_api: Arc<BrokerOuterAPI>,
-    pub(crate) broker_config: Arc<BrokerConfig>,
-    pub(crate) message_store_config: Arc<MessageStoreConfig>,
+    pub(crate) broker_config: ArcMut<BrokerConfig>,
+    pub(crate) message_store_config: ArcMut<MessageStoreConfig>,
     pub(crate) server_config: Arc<ServerConfig>,
     pub(crate) topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
 }
@@ -1019,10 +1600,10 @@ pub(crate) struct BrokerRuntimeInner {
 impl BrokerRuntimeInner {
     pub async fn register_single_topic_all(&self, topic_config: TopicConfig) {
         let mut topic_config = topic_config;
-        if !PermName::is_writeable(self.broker_config.broker_permission)
-            || !PermName::is_readable(self.broker_config.broker_permission)
+        if !PermName::is_writeable(self.broker_config.broker_permission.get())
+            || !PermName::is_readable(self.broker_config.broker_permission.get())
         {
-            topic_config.perm &= self.broker_config.broker_permission;
+            topic_config.perm &= self.broker_config.broker_permission.get();
         }
         self.broker_out_api
             .register_single_topic_all(
@@ -1041,6 +1622,19 @@ impl BrokerRuntimeInner {
         topic_config_list: Vec<TopicConfig>,
         data_version: DataVersion,
     ) {
+        let serialize_wrapper =
+            self.increment_topic_config_wrapper(topic_config_list, data_version);
+        self.do_register_broker_all(true, false, serialize_wrapper)
+            .await;
+    }
+
+    /// The topic configs and queue mappings registered for `topic_config_list`, with the
+    /// permissions the broker currently lacks masked out.
+    fn increment_topic_config_wrapper(
+        &self,
+        topic_config_list: Vec<TopicConfig>,
+        data_version: DataVersion,
+    ) -> TopicConfigAndMappingSerializeWrapper {
         let mut serialize_wrapper = TopicConfigAndMappingSerializeWrapper {
             topic_config_serialize_wrapper: TopicConfigSerializeWrapper {
                 data_version: data_version.clone(),
@@ -1052,11 +1646,11 @@ impl BrokerRuntimeInner {
         let mut topic_config_table = HashMap::new();
         for topic_config in topic_config_list.iter() {
             let register_topic_config =
-                if !PermName::is_writeable(self.broker_config.broker_permission)
-                    || !PermName::is_readable(self.broker_config.broker_permission)
+                if !PermName::is_writeable(self.broker_config.broker_permission.get())
+                    || !PermName::is_readable(self.broker_config.broker_permission.get())
                 {
                     TopicConfig {
-                        perm: topic_config.perm & self.broker_config.broker_permission,
+                        perm: topic_config.perm & self.broker_config.broker_permission.get(),
                         ..topic_config.clone()
                     }
                 } else {
@@ -1083,8 +1677,7 @@ impl BrokerRuntimeInner {
             }
         }
         serialize_wrapper.topic_queue_mapping_info_map = topic_queue_mapping_info_map;
-        self.do_register_broker_all(true, false, serialize_wrapper)
-            .await;
+        serialize_wrapper
     }
 
     async fn do_register_broker_all(
@@ -1103,23 +1696,34 @@ impl BrokerRuntimeInner {
             "{}:{}",
             self.broker_config.broker_ip1, self.server_config.listen_port
         ));
+        let ha_server_addr = CheetahString::from_string(format!(
+            "{}:{}",
+            self.broker_config
+                .broker_ip2
+                .as_ref()
+                .unwrap_or(&self.broker_config.broker_ip1),
+            self.message_store_config.ha_listen_port
+        ));
         let broker_id = self.broker_config.broker_identity.broker_id;
         let weak = Arc::downgrade(&self.broker_out_api);
         self.broker_out_api
             .register_broker_all(
                 cluster_name,
-                broker_addr.clone(),
+                broker_addr,
                 broker_name,
                 broker_id,
-                broker_addr,
+                ha_server_addr,
                 topic_config_wrapper,
                 vec![],
                 oneway,
-                10000,
-                false,
-                false,
-                None,
-                Default::default(),
+                self.broker_config.register_broker_timeout_mills as u64,
+                self.broker_config.enable_slave_acting_master,
+                self.broker_config.compressed_register,
+                self.broker_config
+                    .enable_slave_acting_master
+                    .then_some(self.broker_config.broker_not_active_timeout_millis),
+                self.broker_config.region_id.clone(),
+                self.broker_config.broker_identity.clone(),
                 weak,
             )
             .await;
@@ -1184,3 +1788,96 @@ impl StateGetter for ConsumerStateGetter {
         }
     }
 }
+
+/// Config of the fast remoting server: two ports below the main one over TCP, and a sibling
+/// `.fast` address for unix and in-process transports, which have no port to tell them apart.
+fn fast_server_config(server_config: &ServerConfig) -> ServerConfig {
+    let mut fast_server_config = server_config.clone();
+    match TransportAddr::parse(&server_config.bind_address) {
+        TransportAddr::Tcp(_) => fast_server_config.listen_port = server_config.listen_port - 2,
+        _ => fast_server_config.bind_address = format!("{}.fast", server_config.bind_address),
+    }
+    fast_server_config
+}
+
+/// Broker id of a dledger follower: member `n0` registers as slave 1, `n1` as slave 2 and
+/// so on.
+fn dledger_slave_broker_id(self_id: &str) -> u64 {
+    self_id
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .parse::<u64>()
+        .map_or(1, |index| index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fast_server_gets_its_own_address() {
+        let mut config = ServerConfig {
+            listen_port: 10911,
+            ..Default::default()
+        };
+        let fast = fast_server_config(&config);
+        assert_eq!(fast.bind_address, config.bind_address);
+        assert_eq!(fast.listen_port, 10909);
+
+        config.bind_address = "unix:/tmp/broker.sock".to_string();
+        let fast = fast_server_config(&config);
+        assert_eq!(fast.bind_address, "unix:/tmp/broker.sock.fast");
+        assert_eq!(fast.listen_port, config.listen_port);
+    }
+
+    fn runtime_inner(broker_permission: u32) -> BrokerRuntimeInner {
+        let broker_config = ArcMut::new(BrokerConfig::default());
+        broker_config.broker_permission.set(broker_permission);
+        BrokerRuntimeInner {
+            broker_out_api: Arc::new(BrokerOuterAPI::new(Arc::new(TokioClientConfig::default()))),
+            topic_queue_mapping_manager: Arc::new(TopicQueueMappingManager::new(
+                broker_config.clone(),
+            )),
+            broker_config,
+            message_store_config: ArcMut::new(MessageStoreConfig::default()),
+            server_config: Arc::new(ServerConfig::default()),
+        }
+    }
+
+    #[test]
+    fn increment_register_carries_only_the_given_topics() {
+        let mut data_version = DataVersion::default();
+        data_version.next_version();
+        let wrapper = runtime_inner(PermName::PERM_READ | PermName::PERM_WRITE)
+            .increment_topic_config_wrapper(
+                vec![TopicConfig::with_queues("topic", 4, 4)],
+                data_version.clone(),
+            );
+        let serialize_wrapper = &wrapper.topic_config_serialize_wrapper;
+        assert_eq!(serialize_wrapper.data_version, data_version);
+        assert_eq!(serialize_wrapper.topic_config_table.len(), 1);
+        assert_eq!(
+            serialize_wrapper.topic_config_table["topic"].perm,
+            PermName::PERM_READ | PermName::PERM_WRITE
+        );
+        assert!(wrapper.topic_queue_mapping_info_map.is_empty());
+    }
+
+    #[test]
+    fn increment_register_masks_the_missing_broker_permission() {
+        let wrapper = runtime_inner(PermName::PERM_READ).increment_topic_config_wrapper(
+            vec![TopicConfig::with_queues("topic", 4, 4)],
+            DataVersion::default(),
+        );
+        assert_eq!(
+            wrapper.topic_config_serialize_wrapper.topic_config_table["topic"].perm,
+            PermName::PERM_READ
+        );
+    }
+
+    #[test]
+    fn dledger_followers_register_as_slaves() {
+        assert_eq!(dledger_slave_broker_id("n0"), 1);
+        assert_eq!(dledger_slave_broker_id("n2"), 3);
+        assert_eq!(dledger_slave_broker_id("leader"), 1);
+    }
+}
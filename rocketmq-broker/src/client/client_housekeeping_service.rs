@@ -0,0 +1,230 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+
+use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
+use tracing::warn;
+
+use crate::client::manager::consumer_manager::ConsumerManager;
+use crate::client::manager::producer_manager::ProducerManager;
+use crate::client::rebalance::rebalance_lock_manager::RebalanceLockManager;
+
+/// Expires the clients that stopped sending heartbeats to the broker.
+pub struct ClientHousekeepingService {
+    producer_manager: Arc<ProducerManager>,
+    consumer_manager: Arc<ConsumerManager>,
+    rebalance_lock_manager: Arc<RebalanceLockManager>,
+    broker_stats_manager: Arc<BrokerStatsManager>,
+    channel_expired_timeout: u64,
+}
+
+impl ClientHousekeepingService {
+    pub fn new(
+        producer_manager: Arc<ProducerManager>,
+        consumer_manager: Arc<ConsumerManager>,
+        rebalance_lock_manager: Arc<RebalanceLockManager>,
+        broker_stats_manager: Arc<BrokerStatsManager>,
+        channel_expired_timeout: u64,
+    ) -> Self {
+        Self {
+            producer_manager,
+            consumer_manager,
+            rebalance_lock_manager,
+            broker_stats_manager,
+            channel_expired_timeout,
+        }
+    }
+
+    /// Removes the producer and consumer channels whose last heartbeat exceeds the channel
+    /// expiration time and releases the orderly consume locks held by the expired consumers.
+    pub fn scan_exception_channel(&self) {
+        let expired_producers = self
+            .producer_manager
+            .scan_not_active_channel(self.channel_expired_timeout);
+        for (group, client_channel_info) in &expired_producers {
+            warn!(
+                "ClientHousekeepingService: producer[{}] of group {} expired, remote address: {}",
+                client_channel_info.client_id(),
+                group,
+                client_channel_info.channel().remote_address()
+            );
+            self.broker_stats_manager.inc_channel_idle_num();
+        }
+
+        let expired_consumers = self.consumer_manager.scan_not_active_channel();
+        for (group, client_channel_info) in &expired_consumers {
+            warn!(
+                "ClientHousekeepingService: consumer[{}] of group {} expired, remote address: {}",
+                client_channel_info.client_id(),
+                group,
+                client_channel_info.channel().remote_address()
+            );
+            self.rebalance_lock_manager
+                .unlock_all(group, client_channel_info.client_id());
+            self.broker_stats_manager.inc_channel_idle_num();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::collections::HashMap;
+    use std::collections::HashSet;
+
+    use cheetah_string::CheetahString;
+    use parking_lot::Mutex;
+    use rocketmq_common::common::broker::broker_config::BrokerConfig;
+    use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+    use rocketmq_common::common::message::message_queue::MessageQueue;
+    use rocketmq_common::TimeUtils::get_current_millis;
+    use rocketmq_remoting::connection::Connection;
+    use rocketmq_remoting::net::channel::Channel;
+    use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
+    use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
+    use rocketmq_remoting::protocol::LanguageCode;
+    use rocketmq_rust::ArcMut;
+
+    use super::*;
+    use crate::client::client_channel_info::ClientChannelInfo;
+    use crate::client::consumer_group_event::ConsumerGroupEvent;
+    use crate::client::consumer_ids_change_listener::ConsumerIdsChangeListener;
+
+    const EXPIRED_TIMEOUT: u64 = 1000;
+
+    struct RecordingListener(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl ConsumerIdsChangeListener for RecordingListener {
+        fn handle(&self, event: ConsumerGroupEvent, group: &str, _args: &[&dyn Any]) {
+            self.0
+                .lock()
+                .push((format!("{:?}", event), group.to_string()));
+        }
+
+        fn shutdown(&self) {}
+    }
+
+    fn client_channel_info(client_id: &str, port: u16, idle: u64) -> ClientChannelInfo {
+        let (stream, _) = tokio::io::duplex(64);
+        let channel = Channel::new(
+            "127.0.0.1:10911".parse().unwrap(),
+            format!("127.0.0.1:{}", port).parse().unwrap(),
+            Connection::new(Box::new(stream)),
+            ArcMut::new(HashMap::new()),
+        );
+        let mut info = ClientChannelInfo::new(
+            channel,
+            CheetahString::from(client_id),
+            LanguageCode::RUST,
+            1,
+        );
+        info.set_last_update_timestamp(get_current_millis() - idle);
+        info
+    }
+
+    fn service(
+        events: Arc<Mutex<Vec<(String, String)>>>,
+    ) -> (
+        ClientHousekeepingService,
+        Arc<ProducerManager>,
+        Arc<ConsumerManager>,
+        Arc<RebalanceLockManager>,
+    ) {
+        let producer_manager = Arc::new(ProducerManager::new());
+        let consumer_manager = Arc::new(ConsumerManager::new(
+            Box::new(RecordingListener(events)),
+            EXPIRED_TIMEOUT,
+        ));
+        let rebalance_lock_manager = Arc::new(RebalanceLockManager::default());
+        let broker_stats_manager = Arc::new(BrokerStatsManager::new(ArcMut::new(
+            BrokerConfig::default(),
+        )));
+        let service = ClientHousekeepingService::new(
+            producer_manager.clone(),
+            consumer_manager.clone(),
+            rebalance_lock_manager.clone(),
+            broker_stats_manager,
+            EXPIRED_TIMEOUT,
+        );
+        (
+            service,
+            producer_manager,
+            consumer_manager,
+            rebalance_lock_manager,
+        )
+    }
+
+    #[tokio::test]
+    async fn scan_exception_channel_expires_idle_producers() {
+        let (service, producer_manager, _, _) = service(Arc::default());
+        let group = CheetahString::from("producer_group");
+        producer_manager.register_producer(&group, &client_channel_info("active", 1, 0));
+        producer_manager.register_producer(&group, &client_channel_info("idle", 2, 5000));
+
+        service.scan_exception_channel();
+
+        assert_eq!(producer_manager.connection_count(), 1);
+        assert!(producer_manager.find_channel("active").is_some());
+        assert!(producer_manager.find_channel("idle").is_none());
+    }
+
+    #[tokio::test]
+    async fn scan_exception_channel_expires_idle_consumers_and_releases_their_locks() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let (service, _, consumer_manager, rebalance_lock_manager) = service(events.clone());
+        let group = CheetahString::from("consumer_group");
+        for info in [
+            client_channel_info("active", 1, 0),
+            client_channel_info("idle", 2, 0),
+        ] {
+            consumer_manager.register_consumer(
+                &group,
+                info,
+                ConsumeType::ConsumePassively,
+                MessageModel::Clustering,
+                ConsumeFromWhere::ConsumeFromLastOffset,
+                HashSet::new(),
+                false,
+            );
+        }
+        let channel_info_table = consumer_manager
+            .get_consumer_group_info(&group)
+            .unwrap()
+            .get_channel_info_table();
+        for info in channel_info_table.write().values_mut() {
+            if info.client_id() == "idle" {
+                info.set_last_update_timestamp(get_current_millis() - 5000);
+            }
+        }
+        let mq = MessageQueue::from_parts("TopicTest", "broker-a", 0);
+        rebalance_lock_manager.try_lock_batch(group.as_str(), &HashSet::from([mq]), "idle");
+        events.lock().clear();
+
+        service.scan_exception_channel();
+
+        assert!(consumer_manager.find_channel(&group, "active").is_some());
+        assert!(consumer_manager.find_channel(&group, "idle").is_none());
+        assert!(rebalance_lock_manager.is_lock_all_expired(group.as_str()));
+        let events = events.lock();
+        assert_eq!(
+            events[0],
+            ("ClientUnregister".to_string(), group.to_string())
+        );
+        assert_eq!(events[1], ("Change".to_string(), group.to_string()));
+    }
+}
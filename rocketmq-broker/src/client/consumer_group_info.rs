@@ -22,6 +22,7 @@ use cheetah_string::CheetahString;
 use parking_lot::Mutex;
 use parking_lot::RwLock;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::common::mq_version::RocketMqVersion;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
@@ -182,9 +183,11 @@ impl ConsumerGroupInfo {
         } else {
             channel_info_table.insert(info_new.channel().clone(), info_new.clone());
             info!(
-                "New consumer connected, group: {} channel: {:?}",
+                "New consumer connected, group: {} channel: {:?} language: {} version: {}",
                 self.group_name,
-                info_new.channel()
+                info_new.channel(),
+                info_new.language(),
+                RocketMqVersion::version_desc(info_new.version())
             );
             updated = true;
         }
@@ -201,7 +204,19 @@ impl ConsumerGroupInfo {
         let mut subscription_table = self.subscription_table.write();
         for sub in sub_list.iter() {
             if let Some(old) = subscription_table.get(sub.topic.as_str()) {
-                if sub.sub_version > old.sub_version {
+                let expression_changed = Self::is_expression_changed(old, sub);
+                if expression_changed && sub.sub_version >= old.sub_version {
+                    info!(
+                        "Subscription expression changed, group: {} OLD: {:?} NEW: {:?}",
+                        self.group_name, old, sub
+                    );
+                    let mut sub = sub.clone();
+                    // a client that did not bump the version still gets a newer one, so that
+                    // pulls and filters built from the old expression are seen as stale
+                    sub.sub_version = sub.sub_version.max(old.sub_version + 1);
+                    subscription_table.insert(sub.topic.clone(), sub);
+                    updated = true;
+                } else if sub.sub_version > old.sub_version {
                     if *self.consume_type.read() == ConsumeType::ConsumePassively {
                         info!(
                             "Subscription changed, group: {} OLD: {:?} NEW: {:?}",
@@ -239,6 +254,12 @@ impl ConsumerGroupInfo {
         updated
     }
 
+    fn is_expression_changed(old: &SubscriptionData, new: &SubscriptionData) -> bool {
+        old.sub_string != new.sub_string
+            || old.expression_type != new.expression_type
+            || old.class_filter_mode != new.class_filter_mode
+    }
+
     pub fn get_subscribe_topics(&self) -> HashSet<CheetahString> {
         let subscription_table = self.subscription_table.read();
         subscription_table.keys().cloned().collect()
@@ -402,4 +423,48 @@ mod tests {
 
         assert!(consumer_group_info.update_subscription(&sub_list));
     }
+
+    #[test]
+    fn update_subscription_bumps_version_on_expression_change() {
+        let consumer_group_info = ConsumerGroupInfo::new(
+            "test_group",
+            ConsumeType::ConsumePassively,
+            MessageModel::Clustering,
+            ConsumeFromWhere::ConsumeFromLastOffset,
+        );
+        let subscription = |sub_string: &str, sub_version: i64| {
+            HashSet::from([SubscriptionData {
+                topic: "topic".into(),
+                sub_string: sub_string.into(),
+                sub_version,
+                ..Default::default()
+            }])
+        };
+
+        assert!(consumer_group_info.update_subscription(&subscription("TagA", 100)));
+        // same expression, nothing to rebalance
+        assert!(!consumer_group_info.update_subscription(&subscription("TagA", 100)));
+
+        // changed expression with an unchanged client version
+        assert!(consumer_group_info.update_subscription(&subscription("TagA || TagB", 100)));
+        let current = consumer_group_info
+            .find_subscription_data(&"topic".into())
+            .unwrap();
+        assert_eq!(current.sub_string, "TagA || TagB");
+        assert_eq!(current.sub_version, 101);
+
+        // a heartbeat still carrying the old expression is stale
+        assert!(!consumer_group_info.update_subscription(&subscription("TagA", 100)));
+        let current = consumer_group_info
+            .find_subscription_data(&"topic".into())
+            .unwrap();
+        assert_eq!(current.sub_string, "TagA || TagB");
+
+        // changed expression with a newer client version keeps the client version
+        assert!(consumer_group_info.update_subscription(&subscription("TagC", 200)));
+        let current = consumer_group_info
+            .find_subscription_data(&"topic".into())
+            .unwrap();
+        assert_eq!(current.sub_version, 200);
+    }
 }
@@ -15,17 +15,216 @@
  * limitations under the License.
  */
 use std::any::Any;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
+use rocketmq_rust::ArcMut;
+use tokio::runtime::Handle;
+use tracing::warn;
 
 use crate::client::consumer_group_event::ConsumerGroupEvent;
 use crate::client::consumer_ids_change_listener::ConsumerIdsChangeListener;
+use crate::client::net::broker_to_client::Broker2Client;
+use crate::filter::manager::consumer_filter_manager::ConsumerFilterManager;
+
+/// Notifies consumers with `NOTIFY_CONSUMER_IDS_CHANGED` when the members of their group change
+/// so that they rebalance immediately instead of waiting for the next rebalance period.
+///
+/// When `real_time_notify_consumer_change` is disabled the channels are cached and notified in
+/// batches by [`notify_consumer_change`](Self::notify_consumer_change).
+#[derive(Clone)]
+pub struct DefaultConsumerIdsChangeListener {
+    broker_config: ArcMut<BrokerConfig>,
+    broker_to_client: Broker2Client,
+    consumer_channel_map: Arc<Mutex<HashMap<CheetahString, Vec<Channel>>>>,
+    consumer_filter_manager: Arc<ConsumerFilterManager>,
+}
+
+impl DefaultConsumerIdsChangeListener {
+    pub fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        consumer_filter_manager: Arc<ConsumerFilterManager>,
+    ) -> Self {
+        Self {
+            broker_config,
+            broker_to_client: Broker2Client,
+            consumer_channel_map: Arc::new(Mutex::new(HashMap::new())),
+            consumer_filter_manager,
+        }
+    }
 
-#[derive(Default)]
-pub struct DefaultConsumerIdsChangeListener {}
+    /// Flushes the cached group changes collected while real-time notification is disabled.
+    pub async fn notify_consumer_change(&self) {
+        if self.broker_config.real_time_notify_consumer_change {
+            return;
+        }
+        let consumer_channel_map = std::mem::take(&mut *self.consumer_channel_map.lock());
+        for (group, channels) in consumer_channel_map {
+            for mut channel in channels {
+                self.broker_to_client
+                    .notify_consumer_ids_changed(&mut channel, &group)
+                    .await;
+            }
+        }
+    }
+
+    fn notify_channels(&self, group: &str, channels: Vec<Channel>) {
+        let Ok(handle) = Handle::try_current() else {
+            warn!(
+                "notify consumer ids changed outside of a runtime, group={}",
+                group
+            );
+            return;
+        };
+        let broker_to_client = self.broker_to_client.clone();
+        let group = CheetahString::from_string(group.to_string());
+        handle.spawn(async move {
+            for mut channel in channels {
+                broker_to_client
+                    .notify_consumer_ids_changed(&mut channel, &group)
+                    .await;
+            }
+        });
+    }
+}
 
 impl ConsumerIdsChangeListener for DefaultConsumerIdsChangeListener {
-    fn handle(&self, _event: ConsumerGroupEvent, _group: &str, _args: &[&dyn Any]) {}
+    fn handle(&self, event: ConsumerGroupEvent, group: &str, args: &[&dyn Any]) {
+        match event {
+            ConsumerGroupEvent::Change => {
+                let Some(channels) = args
+                    .first()
+                    .and_then(|arg| arg.downcast_ref::<Vec<Channel>>())
+                else {
+                    return;
+                };
+                if !self.broker_config.notify_consumer_ids_changed_enable {
+                    return;
+                }
+                if self.broker_config.real_time_notify_consumer_change {
+                    self.notify_channels(group, channels.clone());
+                } else {
+                    self.consumer_channel_map
+                        .lock()
+                        .insert(group.to_string().into(), channels.clone());
+                }
+            }
+            ConsumerGroupEvent::Register => {
+                if let Some(sub_list) = args
+                    .first()
+                    .and_then(|arg| arg.downcast_ref::<HashSet<SubscriptionData>>())
+                {
+                    self.consumer_filter_manager
+                        .register_subscriptions(&CheetahString::from_slice(group), sub_list);
+                }
+            }
+            ConsumerGroupEvent::Unregister => {
+                self.consumer_filter_manager
+                    .unregister(&CheetahString::from_slice(group));
+            }
+            _ => {}
+        }
+    }
 
     fn shutdown(&self) {
-        todo!()
+        self.consumer_channel_map.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rocketmq_remoting::connection::Connection;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::DuplexStream;
+
+    use super::*;
+
+    fn channel() -> (Channel, DuplexStream) {
+        let (stream, peer) = tokio::io::duplex(4096);
+        let channel = Channel::new(
+            "127.0.0.1:10911".parse().unwrap(),
+            "127.0.0.1:1".parse().unwrap(),
+            Connection::new(Box::new(stream)),
+            ArcMut::new(HashMap::new()),
+        );
+        (channel, peer)
+    }
+
+    fn listener(real_time: bool, enabled: bool) -> DefaultConsumerIdsChangeListener {
+        let broker_config = ArcMut::new(BrokerConfig {
+            real_time_notify_consumer_change: real_time,
+            notify_consumer_ids_changed_enable: enabled,
+            ..BrokerConfig::default()
+        });
+        let consumer_filter_manager = Arc::new(ConsumerFilterManager::new(broker_config.clone()));
+        DefaultConsumerIdsChangeListener::new(broker_config, consumer_filter_manager)
+    }
+
+    /// What the client on the other end of `peer` received, empty if nothing arrives in time.
+    async fn received(peer: &mut DuplexStream) -> String {
+        let mut buf = vec![0u8; 4096];
+        match tokio::time::timeout(Duration::from_millis(500), peer.read(&mut buf)).await {
+            Ok(Ok(read)) => String::from_utf8_lossy(&buf[..read]).into_owned(),
+            _ => String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn changes_are_sent_right_away_in_real_time() {
+        let listener = listener(true, true);
+        let (channel, mut peer) = channel();
+        listener.handle(
+            ConsumerGroupEvent::Change,
+            "group",
+            &[&vec![channel] as &dyn Any],
+        );
+
+        assert!(listener.consumer_channel_map.lock().is_empty());
+        assert!(received(&mut peer)
+            .await
+            .contains(r#""consumerGroup":"group""#));
+    }
+
+    #[tokio::test]
+    async fn changes_are_batched_until_notified() {
+        let listener = listener(false, true);
+        let (channel, mut peer) = channel();
+        listener.handle(
+            ConsumerGroupEvent::Change,
+            "group",
+            &[&vec![channel] as &dyn Any],
+        );
+        assert!(listener.consumer_channel_map.lock().contains_key("group"));
+        assert!(received(&mut peer).await.is_empty());
+
+        listener.notify_consumer_change().await;
+        assert!(listener.consumer_channel_map.lock().is_empty());
+        assert!(received(&mut peer)
+            .await
+            .contains(r#""consumerGroup":"group""#));
+    }
+
+    #[tokio::test]
+    async fn nothing_is_sent_when_notification_is_disabled() {
+        let listener = listener(false, false);
+        let (channel, mut peer) = channel();
+        listener.handle(
+            ConsumerGroupEvent::Change,
+            "group",
+            &[&vec![channel] as &dyn Any],
+        );
+        listener.handle(ConsumerGroupEvent::Unregister, "other", &[]);
+
+        assert!(listener.consumer_channel_map.lock().is_empty());
+        listener.notify_consumer_change().await;
+        assert!(received(&mut peer).await.is_empty());
     }
 }
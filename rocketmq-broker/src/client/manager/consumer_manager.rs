@@ -24,10 +24,14 @@ use cheetah_string::CheetahString;
 use parking_lot::RwLock;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
+use rocketmq_rust::ArcMut;
 use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
+use tracing::info;
 
 use crate::client::client_channel_info::ClientChannelInfo;
 use crate::client::consumer_group_event::ConsumerGroupEvent;
@@ -62,7 +66,7 @@ impl ConsumerManager {
 
     pub fn new_with_broker_stats(
         consumer_ids_change_listener: Box<dyn ConsumerIdsChangeListener + Send + Sync + 'static>,
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
     ) -> Self {
         let consumer_ids_change_listener_list = vec![consumer_ids_change_listener];
         ConsumerManager {
@@ -118,6 +122,15 @@ impl ConsumerManager {
         0
     }
 
+    /// Number of online consumer channels of every group.
+    pub fn connection_count_by_group(&self) -> Vec<(CheetahString, usize)> {
+        self.consumer_table
+            .read()
+            .iter()
+            .map(|(group, info)| (group.clone(), info.get_channel_info_table().read().len()))
+            .collect()
+    }
+
     pub fn get_consumer_group_info(&self, group: &CheetahString) -> Option<ConsumerGroupInfo> {
         self.get_consumer_group_info_internal(group, false)
     }
@@ -235,6 +248,20 @@ impl ConsumerManager {
             this.brokerStatsManager.incConsumerRegisterTime((int) (System.currentTimeMillis() - start));
         }*/
 
+        // hand the stored subscriptions on, their versions may have been bumped above the
+        // client's so that filters compiled from a changed expression get rebuilt
+        let sub_list = if r2 {
+            sub_list
+                .iter()
+                .map(|sub| {
+                    consumer_group_info
+                        .find_subscription_data(&sub.topic)
+                        .unwrap_or_else(|| sub.clone())
+                })
+                .collect::<HashSet<SubscriptionData>>()
+        } else {
+            sub_list
+        };
         self.call_consumer_ids_change_listener(
             ConsumerGroupEvent::Register,
             group,
@@ -244,6 +271,180 @@ impl ConsumerManager {
         r1 || r2
     }
 
+    /// Registers (or refreshes) a consumer channel without touching the subscription table.
+    /// Used by heartbeat v2 when the client reports an unchanged heartbeat fingerprint.
+    pub fn register_consumer_without_sub(
+        &self,
+        group: &CheetahString,
+        client_channel_info: ClientChannelInfo,
+        consume_type: ConsumeType,
+        message_model: MessageModel,
+        consume_from_where: ConsumeFromWhere,
+        is_notify_consumer_ids_changed_enable: bool,
+    ) -> bool {
+        self.register_consumer_ext(
+            group,
+            client_channel_info,
+            consume_type,
+            message_model,
+            consume_from_where,
+            HashSet::new(),
+            is_notify_consumer_ids_changed_enable,
+            false,
+        )
+    }
+
+    pub fn unregister_consumer(
+        &self,
+        group: &CheetahString,
+        client_channel_info: &ClientChannelInfo,
+        is_notify_consumer_ids_changed_enable: bool,
+    ) {
+        let Some(consumer_group_info) = self.consumer_table.read().get(group).cloned() else {
+            return;
+        };
+        if consumer_group_info.unregister_channel(client_channel_info) {
+            self.call_consumer_ids_change_listener(
+                ConsumerGroupEvent::ClientUnregister,
+                group,
+                &[client_channel_info as &dyn Any],
+            );
+        }
+        self.remove_group_if_empty(group);
+        if is_notify_consumer_ids_changed_enable {
+            let all_channel = consumer_group_info.get_all_channels();
+            self.call_consumer_ids_change_listener(
+                ConsumerGroupEvent::Change,
+                group,
+                &[&all_channel as &dyn Any],
+            );
+        }
+    }
+
+    fn remove_group_if_empty(&self, group: &CheetahString) {
+        let mut write_guard = self.consumer_table.write();
+        let is_empty = write_guard
+            .get(group)
+            .is_some_and(|info| info.get_channel_info_table().read().is_empty());
+        if !is_empty {
+            return;
+        }
+        write_guard.remove(group);
+        drop(write_guard);
+        info!(
+            "unregister consumer ok, no any connection, and remove consumer group, {}",
+            group
+        );
+        self.call_consumer_ids_change_listener(ConsumerGroupEvent::Unregister, group, &[]);
+    }
+
+    /// Removes `channel` from every consumer group it belongs to. Returns `true` if the channel
+    /// was registered in at least one group.
+    pub fn do_channel_close_event(&self, remote_addr: &str, channel: &Channel) -> bool {
+        let mut removed = false;
+        let groups = self
+            .consumer_table
+            .read()
+            .iter()
+            .map(|(group, info)| (group.clone(), info.clone()))
+            .collect::<Vec<_>>();
+        for (group, consumer_group_info) in groups {
+            let Some(client_channel_info) = consumer_group_info.handle_channel_close_event(channel)
+            else {
+                continue;
+            };
+            removed = true;
+            self.call_consumer_ids_change_listener(
+                ConsumerGroupEvent::ClientUnregister,
+                &group,
+                &[&client_channel_info as &dyn Any],
+            );
+            info!(
+                "remove not active consumer channel, group: {}, remote address: {}",
+                group, remote_addr
+            );
+            self.remove_group_if_empty(&group);
+            let all_channel = consumer_group_info.get_all_channels();
+            self.call_consumer_ids_change_listener(
+                ConsumerGroupEvent::Change,
+                &group,
+                &[&all_channel as &dyn Any],
+            );
+        }
+        removed
+    }
+
+    /// Removes the consumer channels whose last heartbeat is older than the channel expiration
+    /// time, returning them with their group.
+    pub fn scan_not_active_channel(&self) -> Vec<(CheetahString, ClientChannelInfo)> {
+        let now = get_current_millis();
+        let mut expired = Vec::new();
+        let groups = self
+            .consumer_table
+            .read()
+            .iter()
+            .map(|(group, info)| (group.clone(), info.clone()))
+            .collect::<Vec<_>>();
+        for (group, consumer_group_info) in groups {
+            let expired_in_group = {
+                let channel_info_table = consumer_group_info.get_channel_info_table();
+                let mut channel_info_table = channel_info_table.write();
+                let expired_channels = channel_info_table
+                    .iter()
+                    .filter(|(_, info)| {
+                        now.saturating_sub(info.last_update_timestamp())
+                            > self.channel_expired_timeout
+                    })
+                    .map(|(channel, _)| channel.clone())
+                    .collect::<Vec<_>>();
+                expired_channels
+                    .iter()
+                    .filter_map(|channel| channel_info_table.remove(channel))
+                    .collect::<Vec<_>>()
+            };
+            if expired_in_group.is_empty() {
+                continue;
+            }
+            for client_channel_info in &expired_in_group {
+                info!(
+                    "ConsumerManager#scanNotActiveChannel: remove expired channel[{}] from \
+                     consumer group: {}",
+                    client_channel_info.client_id(),
+                    group
+                );
+                self.call_consumer_ids_change_listener(
+                    ConsumerGroupEvent::ClientUnregister,
+                    &group,
+                    &[client_channel_info as &dyn Any],
+                );
+            }
+            self.remove_group_if_empty(&group);
+            let all_channel = consumer_group_info.get_all_channels();
+            self.call_consumer_ids_change_listener(
+                ConsumerGroupEvent::Change,
+                &group,
+                &[&all_channel as &dyn Any],
+            );
+            expired.extend(
+                expired_in_group
+                    .into_iter()
+                    .map(|client_channel_info| (group.clone(), client_channel_info)),
+            );
+        }
+        expired
+    }
+
+    pub fn find_channel(
+        &self,
+        group: &CheetahString,
+        client_id: &str,
+    ) -> Option<ClientChannelInfo> {
+        self.consumer_table
+            .read()
+            .get(group)?
+            .find_channel_by_client_id(client_id)
+    }
+
     pub fn call_consumer_ids_change_listener(
         &self,
         event: ConsumerGroupEvent,
@@ -265,3 +466,128 @@ impl ConsumerManager {
         groups
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use parking_lot::Mutex;
+    use rocketmq_remoting::connection::Connection;
+    use rocketmq_remoting::protocol::LanguageCode;
+
+    use super::*;
+
+    struct RecordingListener(Arc<Mutex<Vec<String>>>);
+
+    impl ConsumerIdsChangeListener for RecordingListener {
+        fn handle(&self, event: ConsumerGroupEvent, _group: &str, _args: &[&dyn Any]) {
+            self.0.lock().push(format!("{:?}", event));
+        }
+
+        fn shutdown(&self) {}
+    }
+
+    fn client_channel_info(client_id: &str, port: u16) -> ClientChannelInfo {
+        let (stream, _) = tokio::io::duplex(64);
+        let channel = Channel::new(
+            "127.0.0.1:10911".parse().unwrap(),
+            format!("127.0.0.1:{}", port).parse().unwrap(),
+            Connection::new(Box::new(stream)),
+            ArcMut::new(HashMap::new()),
+        );
+        ClientChannelInfo::new(
+            channel,
+            CheetahString::from(client_id),
+            LanguageCode::RUST,
+            1,
+        )
+    }
+
+    fn subscriptions(topic: &str) -> HashSet<SubscriptionData> {
+        HashSet::from([SubscriptionData {
+            topic: topic.into(),
+            sub_string: "*".into(),
+            ..Default::default()
+        }])
+    }
+
+    fn register(manager: &ConsumerManager, group: &str, client_channel_info: &ClientChannelInfo) {
+        manager.register_consumer(
+            &group.into(),
+            client_channel_info.clone(),
+            ConsumeType::ConsumePassively,
+            MessageModel::Clustering,
+            ConsumeFromWhere::ConsumeFromLastOffset,
+            subscriptions("topic"),
+            true,
+        );
+    }
+
+    fn manager() -> (ConsumerManager, Arc<Mutex<Vec<String>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let manager = ConsumerManager::new(Box::new(RecordingListener(events.clone())), 1000);
+        (manager, events)
+    }
+
+    #[tokio::test]
+    async fn unregistering_the_last_consumer_removes_the_group() {
+        let (manager, events) = manager();
+        let group = CheetahString::from("group");
+        let first = client_channel_info("c1", 1);
+        let second = client_channel_info("c2", 2);
+        register(&manager, &group, &first);
+        register(&manager, &group, &second);
+        events.lock().clear();
+
+        manager.unregister_consumer(&group, &first, true);
+        assert_eq!(
+            manager
+                .get_consumer_group_info(&group)
+                .unwrap()
+                .get_all_client_ids(),
+            vec![CheetahString::from("c2")]
+        );
+        assert_eq!(*events.lock(), vec!["ClientUnregister", "Change"]);
+        events.lock().clear();
+
+        // no change notification when the group turned it off
+        manager.unregister_consumer(&group, &second, false);
+        assert!(manager.get_consumer_group_info(&group).is_none());
+        assert_eq!(*events.lock(), vec!["ClientUnregister", "Unregister"]);
+    }
+
+    #[tokio::test]
+    async fn closed_channel_leaves_every_group() {
+        let (manager, _) = manager();
+        let closed = client_channel_info("c1", 1);
+        let other = client_channel_info("c2", 2);
+        register(&manager, "group_a", &closed);
+        register(&manager, "group_b", &closed);
+        register(&manager, "group_b", &other);
+
+        assert!(manager.do_channel_close_event("127.0.0.1:1", closed.channel()));
+        assert!(manager.get_consumer_group_info(&"group_a".into()).is_none());
+        assert!(manager.find_channel(&"group_b".into(), "c1").is_none());
+        assert!(manager.find_channel(&"group_b".into(), "c2").is_some());
+        assert!(!manager.do_channel_close_event("127.0.0.1:1", closed.channel()));
+    }
+
+    #[tokio::test]
+    async fn registering_without_subscriptions_keeps_the_group_subscriptions() {
+        let (manager, _) = manager();
+        let group = CheetahString::from("group");
+        register(&manager, &group, &client_channel_info("c1", 1));
+
+        let changed = manager.register_consumer_without_sub(
+            &group,
+            client_channel_info("c2", 2),
+            ConsumeType::ConsumePassively,
+            MessageModel::Clustering,
+            ConsumeFromWhere::ConsumeFromLastOffset,
+            true,
+        );
+        assert!(changed);
+        assert!(manager.find_channel(&group, "c2").is_some());
+        assert!(manager
+            .find_subscription_data(&group, &"topic".into())
+            .is_some());
+    }
+}
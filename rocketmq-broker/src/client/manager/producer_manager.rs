@@ -47,6 +47,23 @@ impl ProducerManager {
 }
 
 impl ProducerManager {
+    /// Number of producer channels across all groups.
+    pub fn connection_count(&self) -> usize {
+        self.group_channel_table
+            .lock()
+            .values()
+            .map(HashMap::len)
+            .sum()
+    }
+
+    /// Snapshot of the channels registered by a producer group, `None` if the group is unknown.
+    pub fn get_group_channel_info(
+        &self,
+        group: &CheetahString,
+    ) -> Option<HashMap<Channel, ClientChannelInfo>> {
+        self.group_channel_table.lock().get(group).cloned()
+    }
+
     pub fn group_online(&self, group: String) -> bool {
         let binding = self.group_channel_table.lock();
         let channels = binding.get(group.as_str());
@@ -70,6 +87,9 @@ impl ProducerManager {
                     let old = ct.remove(ctx.channel());
                     //let old = ct.remove(client_channel_info.channel());
                     if old.is_some() {
+                        self.client_channel_table
+                            .lock()
+                            .remove(client_channel_info.client_id());
                         info!(
                             "unregister a producer[{}] from groupChannelTable {:?}",
                             group, client_channel_info
@@ -117,6 +137,66 @@ impl ProducerManager {
         );
     }
 
+    /// Removes `channel` from every producer group. Returns `true` if the channel was
+    /// registered in at least one group.
+    pub fn do_channel_close_event(&self, remote_addr: &str, channel: &Channel) -> bool {
+        let mut removed = false;
+        let mut group_channel_table = self.group_channel_table.lock();
+        group_channel_table.retain(|group, channel_table| {
+            if let Some(client_channel_info) = channel_table.remove(channel) {
+                removed = true;
+                self.client_channel_table
+                    .lock()
+                    .remove(client_channel_info.client_id());
+                info!(
+                    "NETTY EVENT: remove channel[{}][{}] from ProducerManager groupChannelTable, \
+                     producer group: {}",
+                    client_channel_info.client_id(),
+                    remote_addr,
+                    group
+                );
+            }
+            !channel_table.is_empty()
+        });
+        removed
+    }
+
+    /// Removes the producer channels whose last heartbeat is older than
+    /// `channel_expired_timeout` milliseconds, returning them with their group.
+    pub fn scan_not_active_channel(
+        &self,
+        channel_expired_timeout: u64,
+    ) -> Vec<(CheetahString, ClientChannelInfo)> {
+        let now = get_current_millis();
+        let mut expired = Vec::new();
+        let mut group_channel_table = self.group_channel_table.lock();
+        group_channel_table.retain(|group, channel_table| {
+            channel_table.retain(|_, client_channel_info| {
+                let idle = now.saturating_sub(client_channel_info.last_update_timestamp());
+                if idle <= channel_expired_timeout {
+                    return true;
+                }
+                let mut client_channel_table = self.client_channel_table.lock();
+                if client_channel_table.get(client_channel_info.client_id())
+                    == Some(client_channel_info.channel())
+                {
+                    client_channel_table.remove(client_channel_info.client_id());
+                }
+                info!(
+                    "ProducerManager#scanNotActiveChannel: remove expired channel[{}] from \
+                     groupChannelTable, producer group: {}, idle: {}ms",
+                    client_channel_info.client_id(),
+                    group,
+                    idle
+                );
+                expired.push((group.clone(), client_channel_info.clone()));
+                false
+            });
+            !channel_table.is_empty()
+        });
+        expired
+    }
+
     pub fn find_channel(&self, client_id: &str) -> Option<Channel> {
         self.client_channel_table.lock().get(client_id).cloned()
     }
@@ -140,3 +220,45 @@ impl ProducerManager {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::connection::Connection;
+    use rocketmq_remoting::protocol::LanguageCode;
+    use rocketmq_rust::ArcMut;
+
+    use super::*;
+
+    fn client_channel_info(client_id: &str, port: u16) -> ClientChannelInfo {
+        let (stream, _) = tokio::io::duplex(64);
+        let channel = Channel::new(
+            "127.0.0.1:10911".parse().unwrap(),
+            format!("127.0.0.1:{}", port).parse().unwrap(),
+            Connection::new(Box::new(stream)),
+            ArcMut::new(HashMap::new()),
+        );
+        ClientChannelInfo::new(
+            channel,
+            CheetahString::from(client_id),
+            LanguageCode::RUST,
+            1,
+        )
+    }
+
+    #[tokio::test]
+    async fn closed_channel_leaves_every_group() {
+        let manager = ProducerManager::new();
+        let closed = client_channel_info("p1", 1);
+        let other = client_channel_info("p2", 2);
+        manager.register_producer(&"group_a".into(), &closed);
+        manager.register_producer(&"group_b".into(), &closed);
+        manager.register_producer(&"group_b".into(), &other);
+
+        assert!(manager.do_channel_close_event("127.0.0.1:1", closed.channel()));
+        assert!(manager.find_channel("p1").is_none());
+        assert!(manager.find_channel("p2").is_some());
+        assert!(!manager.group_online("group_a".to_string()));
+        assert!(manager.group_online("group_b".to_string()));
+        assert!(!manager.do_channel_close_event("127.0.0.1:1", closed.channel()));
+    }
+}
@@ -19,8 +19,14 @@ use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::MessageDecoder;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::body::reset_offset_body::ResetOffsetBody;
 use rocketmq_remoting::protocol::header::check_transaction_state_request_header::CheckTransactionStateRequestHeader;
+use rocketmq_remoting::protocol::header::notify_consumer_ids_changed_request_header::NotifyConsumerIdsChangedRequestHeader;
+use rocketmq_remoting::protocol::header::reset_offset_request_header::ResetOffsetRequestHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use tracing::error;
+use tracing::info;
 
 use crate::broker_error::BrokerError::BrokerCommonError;
 use crate::broker_error::BrokerError::BrokerRemotingError;
@@ -66,4 +72,57 @@ impl Broker2Client {
             Err(e) => Err(BrokerRemotingError(e)),
         }
     }
+
+    pub async fn notify_consumer_ids_changed(
+        &self,
+        channel: &mut Channel,
+        consumer_group: &CheetahString,
+    ) {
+        if consumer_group.is_empty() {
+            error!("notifyConsumerIdsChanged consumerGroup is null");
+            return;
+        }
+        let request_header = NotifyConsumerIdsChangedRequestHeader {
+            consumer_group: consumer_group.clone(),
+            rpc_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::NotifyConsumerIdsChanged,
+            request_header,
+        );
+        if let Err(e) = channel.send_one_way(request, 10).await {
+            error!(
+                "notifyConsumerIdsChanged exception. group={}, error={}",
+                consumer_group, e
+            );
+        }
+    }
+
+    /// Tells a consumer client to seek the queues in `body` to the given offsets.
+    pub async fn reset_consumer_client_offset(
+        &self,
+        channel: &mut Channel,
+        request_header: ResetOffsetRequestHeader,
+        body: &ResetOffsetBody,
+    ) -> Result<()> {
+        let topic = request_header.topic.clone();
+        let group = request_header.group.clone();
+        let mut request = RemotingCommand::create_request_command(
+            RequestCode::ResetConsumerClientOffset,
+            request_header,
+        );
+        request.set_body_mut_ref(body.encode().map_err(BrokerCommonError)?);
+        match channel.send_one_way(request, 5000).await {
+            Ok(_) => {
+                info!(
+                    "[reset-offset] reset offset success. topic={}, group={}, clientId={}",
+                    topic,
+                    group,
+                    channel.remote_address()
+                );
+                Ok(())
+            }
+            Err(e) => Err(BrokerRemotingError(e)),
+        }
+    }
 }
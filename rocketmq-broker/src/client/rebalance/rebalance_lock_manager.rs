@@ -163,6 +163,25 @@ impl RebalanceLockManager {
         }
     }
 
+    /// Releases every message queue of `group` locked by `client_id`, returning how many were
+    /// released.
+    pub fn unlock_all(&self, group: &str, client_id: &str) -> usize {
+        let mut write_guard = self.mq_lock_table.write();
+        let Some(group_value) = write_guard.get_mut(group) else {
+            return 0;
+        };
+        let before = group_value.len();
+        group_value.retain(|_, lock_entry| lock_entry.client_id != client_id);
+        let released = before - group_value.len();
+        if released > 0 {
+            info!(
+                "RebalanceLockManager#unlockAll: unlock {} message queues, group={}, clientId={}",
+                released, group, client_id
+            );
+        }
+        released
+    }
+
     fn is_locked(&self, group: &str, mq: &MessageQueue, client_id: &str) -> bool {
         let lock_table = self.mq_lock_table.read();
         let group_value = lock_table.get(group);
@@ -280,6 +299,19 @@ mod rebalance_lock_manager_tests {
         assert!(!manager.is_lock_all_expired("test_group"));
     }
 
+    #[test]
+    fn unlock_all_releases_only_queues_of_the_client() {
+        let manager = RebalanceLockManager::default();
+        let mq1 = MessageQueue::from_parts("topic", "broker", 0);
+        let mq2 = MessageQueue::from_parts("topic", "broker", 1);
+        manager.try_lock_batch("test_group", &HashSet::from([mq1.clone()]), "client_1");
+        manager.try_lock_batch("test_group", &HashSet::from([mq2.clone()]), "client_2");
+        assert_eq!(manager.unlock_all("test_group", "client_1"), 1);
+        assert!(!manager.is_locked("test_group", &mq1, "client_1"));
+        assert!(manager.is_locked("test_group", &mq2, "client_2"));
+        assert_eq!(manager.unlock_all("unknown_group", "client_2"), 0);
+    }
+
     #[test]
     fn is_locked_returns_true_for_locked_message_queue() {
         let manager = RebalanceLockManager::default();
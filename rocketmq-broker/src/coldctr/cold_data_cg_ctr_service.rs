@@ -15,11 +15,140 @@
  * limitations under the License.
  */
 
-#[derive(Default)]
-pub struct ColdDataCgCtrService;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use parking_lot::Mutex;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::mix_all::is_sys_consumer_group_for_no_cold_read_limit;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
+
+/// Cold read accounting restarts every window, so the thresholds are bytes per window.
+const COLD_READ_WINDOW_MILLIS: u64 = 1000;
+
+/// Tracks how many bytes each consumer group reads from disk instead of the page cache and
+/// tells the pull path when a group has to back off to protect hot reads.
+pub struct ColdDataCgCtrService {
+    broker_config: ArcMut<BrokerConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
+    cg_cold_acc_table: Mutex<HashMap<String, i64>>,
+    global_cold_acc: AtomicI64,
+    window_begin_millis: AtomicU64,
+}
 
 impl ColdDataCgCtrService {
-    pub fn is_cg_need_cold_data_flow_ctr(&self, _consumer_group: &str) -> bool {
-        false
+    pub fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
+    ) -> Self {
+        Self {
+            broker_config,
+            message_store_config,
+            cg_cold_acc_table: Mutex::new(HashMap::new()),
+            global_cold_acc: AtomicI64::new(0),
+            window_begin_millis: AtomicU64::new(get_current_millis()),
+        }
+    }
+
+    /// Records `cold_data_size` bytes read from disk by `consumer_group`.
+    pub fn cold_acc(&self, consumer_group: &str, cold_data_size: i64) {
+        if cold_data_size <= 0 {
+            return;
+        }
+        self.roll_window_if_necessary(get_current_millis());
+        self.global_cold_acc
+            .fetch_add(cold_data_size, Ordering::Relaxed);
+        *self
+            .cg_cold_acc_table
+            .lock()
+            .entry(consumer_group.to_string())
+            .or_default() += cold_data_size;
+    }
+
+    pub fn is_cg_need_cold_data_flow_ctr(&self, consumer_group: &str) -> bool {
+        if !self.message_store_config.cold_data_flow_control_enable
+            || is_sys_consumer_group_for_no_cold_read_limit(consumer_group)
+        {
+            return false;
+        }
+        self.roll_window_if_necessary(get_current_millis());
+        if self.is_global_cold_ctr() {
+            return true;
+        }
+        self.cg_cold_acc_table
+            .lock()
+            .get(consumer_group)
+            .is_some_and(|acc| *acc > self.broker_config.cg_cold_read_threshold.get())
+    }
+
+    pub fn is_global_cold_ctr(&self) -> bool {
+        self.global_cold_acc.load(Ordering::Relaxed)
+            > self.broker_config.global_cold_read_threshold.get()
+    }
+
+    fn roll_window_if_necessary(&self, now: u64) {
+        let window_begin = self.window_begin_millis.load(Ordering::Relaxed);
+        if now.saturating_sub(window_begin) < COLD_READ_WINDOW_MILLIS {
+            return;
+        }
+        if self
+            .window_begin_millis
+            .compare_exchange(window_begin, now, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok()
+        {
+            self.cg_cold_acc_table.lock().clear();
+            self.global_cold_acc.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(cg_threshold: i64, global_threshold: i64) -> ColdDataCgCtrService {
+        let broker_config = BrokerConfig {
+            cg_cold_read_threshold: cg_threshold.into(),
+            global_cold_read_threshold: global_threshold.into(),
+            ..Default::default()
+        };
+        let message_store_config = MessageStoreConfig {
+            cold_data_flow_control_enable: true,
+            ..Default::default()
+        };
+        ColdDataCgCtrService::new(
+            ArcMut::new(broker_config),
+            ArcMut::new(message_store_config),
+        )
+    }
+
+    #[test]
+    fn group_over_threshold_needs_flow_ctr() {
+        let service = service(100, i64::MAX);
+        service.cold_acc("group_a", 60);
+        assert!(!service.is_cg_need_cold_data_flow_ctr("group_a"));
+        service.cold_acc("group_a", 60);
+        assert!(service.is_cg_need_cold_data_flow_ctr("group_a"));
+        assert!(!service.is_cg_need_cold_data_flow_ctr("group_b"));
+    }
+
+    #[test]
+    fn global_threshold_limits_every_group() {
+        let service = service(i64::MAX, 100);
+        service.cold_acc("group_a", 150);
+        assert!(service.is_cg_need_cold_data_flow_ctr("group_b"));
+    }
+
+    #[test]
+    fn window_roll_resets_accounting() {
+        let service = service(100, i64::MAX);
+        service.cold_acc("group_a", 150);
+        let now = service.window_begin_millis.load(Ordering::Relaxed) + COLD_READ_WINDOW_MILLIS;
+        service.roll_window_if_necessary(now);
+        assert!(!service.is_cg_need_cold_data_flow_ctr("group_a"));
     }
 }
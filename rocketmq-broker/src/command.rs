@@ -14,9 +14,24 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 
+use anyhow::bail;
+use cheetah_string::CheetahString;
 use clap::Parser;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::EnvUtils::EnvUtils;
+use rocketmq_common::ParseConfigFile;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use tracing::warn;
+
+use crate::broker_path_config_helper::set_broker_config_path;
+
+/// Environment variables with this prefix override the config file, e.g.
+/// `ROCKETMQ_BROKER_LISTEN_PORT=10921`.
+pub const BROKER_ENV_PREFIX: &str = "ROCKETMQ_BROKER_";
 
 #[derive(Parser, Debug)]
 #[command(
@@ -34,16 +49,198 @@ pub struct Args {
     pub print_important_config: bool,
 
     /// Name remoting_server address list, eg: '192.168.0.1:9876;192.168.0.2:9876'
-    #[arg(
-        short,
-        long,
-        value_name = "IP",
-        default_value = "127.0.0.1:9876",
-        required = false
-    )]
-    pub namesrv_addr: String,
+    #[arg(short, long, value_name = "IP", required = false)]
+    pub namesrv_addr: Option<String>,
 
     ///Print all config item
     #[arg(short, long, required = false)]
     pub print_config_item: bool,
 }
+
+impl Args {
+    /// Loads the broker and store config in layers: defaults, then the config file (the `-c`
+    /// file or `$ROCKETMQ_HOME/conf/broker.toml`), then `ROCKETMQ_BROKER_*` environment
+    /// variables, then the command line flags.
+    /// The config file becomes the one runtime config updates are persisted to.
+    pub fn load_config(&self) -> anyhow::Result<(BrokerConfig, MessageStoreConfig)> {
+        let config_file = self.config_file.clone().unwrap_or_else(|| {
+            PathBuf::from(EnvUtils::get_rocketmq_home().as_str())
+                .join("conf")
+                .join("broker.toml")
+        });
+        let configs = self.load_config_with(
+            &config_file,
+            ParseConfigFile::env_properties(BROKER_ENV_PREFIX),
+        )?;
+        if self.config_file.is_some() || config_file.exists() {
+            set_broker_config_path(config_file.to_string_lossy());
+        }
+        Ok(configs)
+    }
+
+    fn load_config_with(
+        &self,
+        config_file: &Path,
+        env_properties: HashMap<CheetahString, CheetahString>,
+    ) -> anyhow::Result<(BrokerConfig, MessageStoreConfig)> {
+        let (mut broker_config, mut message_store_config, mut properties) =
+            if ParseConfigFile::is_properties_file(config_file) {
+                (
+                    BrokerConfig::default(),
+                    MessageStoreConfig::default(),
+                    ParseConfigFile::parse_properties_file(config_file)?,
+                )
+            } else {
+                (
+                    ParseConfigFile::parse_config_file::<BrokerConfig>(config_file.to_path_buf())?,
+                    ParseConfigFile::parse_config_file::<MessageStoreConfig>(
+                        config_file.to_path_buf(),
+                    )?,
+                    HashMap::new(),
+                )
+            };
+        properties.extend(env_properties);
+        if let Some(namesrv_addr) = &self.namesrv_addr {
+            properties.insert("namesrvAddr".into(), namesrv_addr.as_str().into());
+        }
+
+        if !properties.is_empty() {
+            let (config, broker_unknown) =
+                ParseConfigFile::apply_properties(&broker_config, &properties)?;
+            broker_config = config;
+            let (config, store_unknown) =
+                ParseConfigFile::apply_properties(&message_store_config, &properties)?;
+            message_store_config = config;
+            for key in broker_unknown
+                .iter()
+                .filter(|key| store_unknown.contains(key))
+            {
+                warn!("Unknown broker config item: {}", key);
+            }
+        }
+        normalize_and_validate(&mut broker_config, &message_store_config)?;
+        Ok((broker_config, message_store_config))
+    }
+}
+
+fn normalize_and_validate(
+    broker_config: &mut BrokerConfig,
+    message_store_config: &MessageStoreConfig,
+) -> anyhow::Result<()> {
+    if let Some(namesrv_addr) = broker_config.namesrv_addr.as_ref() {
+        let namesrv_addr = namesrv_addr
+            .split([';', ','])
+            .map(str::trim)
+            .filter(|addr| !addr.is_empty())
+            .collect::<Vec<_>>()
+            .join(";");
+        broker_config.namesrv_addr = if namesrv_addr.is_empty() {
+            None
+        } else {
+            Some(namesrv_addr.into())
+        };
+    }
+    if broker_config.broker_identity.broker_name.is_empty() {
+        bail!("brokerName must not be empty");
+    }
+    if broker_config.broker_identity.broker_cluster_name.is_empty() {
+        bail!("brokerClusterName must not be empty");
+    }
+    // the fast remoting server listens on listenPort - 2
+    if !(3..=65535).contains(&broker_config.listen_port) {
+        bail!("Invalid listenPort: {}", broker_config.listen_port);
+    }
+    if message_store_config.mapped_file_size_commit_log == 0 {
+        bail!("mappedFileSizeCommitLog must be greater than 0");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn args(namesrv_addr: Option<&str>) -> Args {
+        Args {
+            config_file: None,
+            print_important_config: false,
+            namesrv_addr: namesrv_addr.map(str::to_string),
+            print_config_item: false,
+        }
+    }
+
+    const BROKER_PROPERTIES: &str = concat!(
+        "brokerClusterName=TestCluster\n",
+        "brokerName=broker-a\n",
+        "brokerId=1\n",
+        "listenPort=10921\n",
+        "namesrvAddr=127.0.0.1:9876\n",
+        "maxMessageSize=1024\n",
+    );
+
+    #[test]
+    fn properties_file_is_loaded() {
+        let path = write_config("broker-plain.conf", BROKER_PROPERTIES);
+        let (broker_config, message_store_config) =
+            args(None).load_config_with(&path, HashMap::new()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(
+            broker_config.broker_identity.broker_cluster_name,
+            "TestCluster"
+        );
+        assert_eq!(broker_config.broker_identity.broker_name, "broker-a");
+        assert_eq!(broker_config.broker_identity.broker_id, 1);
+        assert_eq!(broker_config.listen_port, 10921);
+        assert_eq!(
+            broker_config.namesrv_addr.as_deref(),
+            Some("127.0.0.1:9876")
+        );
+        assert_eq!(message_store_config.max_message_size, 1024);
+    }
+
+    #[test]
+    fn properties_file_then_env_then_cli() {
+        let path = write_config("broker.conf", BROKER_PROPERTIES);
+        let mut env = HashMap::new();
+        env.insert("listenPort".into(), "10931".into());
+        env.insert("namesrvAddr".into(), "10.0.0.9:9876".into());
+        let (broker_config, message_store_config) = args(Some("10.0.0.1:9876, 10.0.0.2:9876"))
+            .load_config_with(&path, env)
+            .unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(
+            broker_config.broker_identity.broker_cluster_name,
+            "TestCluster"
+        );
+        assert_eq!(broker_config.broker_identity.broker_name, "broker-a");
+        assert_eq!(broker_config.broker_name, "broker-a");
+        assert_eq!(broker_config.broker_identity.broker_id, 1);
+        assert_eq!(broker_config.listen_port, 10931);
+        assert_eq!(
+            broker_config.namesrv_addr.as_deref(),
+            Some("10.0.0.1:9876;10.0.0.2:9876")
+        );
+        assert_eq!(message_store_config.max_message_size, 1024);
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let path = write_config("broker-invalid.conf", "listenPort=abc\n");
+        let result = args(None).load_config_with(&path, HashMap::new());
+        std::fs::remove_file(path).unwrap();
+        assert!(result.unwrap_err().to_string().contains("listenPort"));
+
+        let path = write_config("broker-empty-name.conf", "brokerName=\n");
+        let result = args(None).load_config_with(&path, HashMap::new());
+        std::fs::remove_file(path).unwrap();
+        assert!(result.is_err());
+    }
+}
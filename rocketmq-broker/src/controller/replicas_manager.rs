@@ -14,9 +14,446 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-#[derive(Default)]
-pub struct ReplicasManager {}
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::body::sync_state_set::SyncStateSet;
+use rocketmq_remoting::protocol::header::broker::broker_heartbeat_request_header::BrokerHeartbeatRequestHeader;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterRequestHeader;
+use rocketmq_remoting::protocol::header::controller::notify_broker_role_changed_request_header::NotifyBrokerRoleChangedRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::log_file::MessageStore;
+use rocketmq_store::message_store::default_message_store::DefaultMessageStore;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::out_api::broker_outer_api::BrokerOuterAPI;
+use crate::slave::slave_synchronize::SlaveSynchronize;
+
+/// What this replica last learned from the controller about its group.
+#[derive(Debug, Default)]
+struct ReplicasState {
+    is_master: Option<bool>,
+    master_broker_id: Option<i64>,
+    master_address: Option<CheetahString>,
+    master_epoch: i32,
+    sync_state_set: HashSet<i64>,
+    sync_state_set_epoch: i32,
+    member_broker_ids: HashSet<i64>,
+}
+
+/// Keeps this broker in line with the elections of the controller.
+///
+/// The replica registers to the controller, asks for an election when its group has no
+/// master, sends heartbeats and follows role changes by switching the direction of the
+/// commit log replication. The HA port of a master is expected to be its listen port
+/// plus one, which is the default layout of a broker.
+#[derive(Clone)]
+pub(crate) struct ReplicasManager {
+    broker_config: ArcMut<BrokerConfig>,
+    broker_out_api: Arc<BrokerOuterAPI>,
+    message_store: ArcMut<DefaultMessageStore>,
+    slave_synchronize: Option<SlaveSynchronize<DefaultMessageStore>>,
+    local_address: CheetahString,
+    state: Arc<parking_lot::Mutex<ReplicasState>>,
+}
 
 impl ReplicasManager {
-    pub fn start(&mut self) {}
+    pub(crate) fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        broker_out_api: Arc<BrokerOuterAPI>,
+        message_store: ArcMut<DefaultMessageStore>,
+        slave_synchronize: Option<SlaveSynchronize<DefaultMessageStore>>,
+        local_address: CheetahString,
+    ) -> Self {
+        Self {
+            broker_config,
+            broker_out_api,
+            message_store,
+            slave_synchronize,
+            local_address,
+            state: Arc::new(parking_lot::Mutex::new(ReplicasState::default())),
+        }
+    }
+
+    /// Registers to the controller and takes the role it assigns, asking for an election
+    /// when the group has no alive master.
+    pub(crate) async fn start(&self) -> bool {
+        let Some(controller_addr) = self.controller_addr() else {
+            error!("Controller mode is enabled but controllerAddr is not configured");
+            return false;
+        };
+        self.send_heartbeat_to_controller().await;
+        let request_header = RegisterBrokerToControllerRequestHeader {
+            cluster_name: self.cluster_name(),
+            broker_name: self.broker_name(),
+            broker_id: self.broker_id(),
+            broker_address: self.local_address.clone(),
+            invoke_time: None,
+        };
+        match self
+            .broker_out_api
+            .register_broker_to_controller(&controller_addr, request_header)
+            .await
+        {
+            Ok(response_header) => {
+                info!(
+                    "Register to controller {} success, master {:?}",
+                    controller_addr, response_header.master_broker_id
+                );
+                if let (Some(master_broker_id), Some(master_address), Some(master_epoch)) = (
+                    response_header.master_broker_id,
+                    response_header.master_address,
+                    response_header.master_epoch,
+                ) {
+                    self.change_role(
+                        master_broker_id,
+                        master_address,
+                        master_epoch,
+                        response_header.sync_state_set_epoch.unwrap_or_default(),
+                        None,
+                    );
+                    return true;
+                }
+            }
+            Err(e) => {
+                error!("Register to controller {} failed: {}", controller_addr, e);
+                return false;
+            }
+        }
+        self.elect_master(&controller_addr).await;
+        true
+    }
+
+    pub(crate) async fn send_heartbeat_to_controller(&self) {
+        let Some(controller_addr) = self.controller_addr() else {
+            return;
+        };
+        let max_phy_offset = self.message_store.get_max_phy_offset();
+        let request_header = BrokerHeartbeatRequestHeader {
+            cluster_name: self.cluster_name(),
+            broker_addr: self.local_address.clone(),
+            broker_name: self.broker_name(),
+            broker_id: Some(self.broker_id()),
+            epoch: Some(self.state.lock().master_epoch),
+            max_offset: Some(max_phy_offset),
+            confirm_offset: Some(max_phy_offset),
+            heartbeat_timeout_mills: Some(
+                self.broker_config.controller_heartbeat_timeout_mills as i64,
+            ),
+            election_priority: Some(self.broker_config.broker_election_priority),
+        };
+        self.broker_out_api
+            .send_heartbeat_to_controller(&controller_addr, request_header)
+            .await;
+    }
+
+    /// Pulls the replica info of the group, following a master change the notification of
+    /// which was lost, and lets the master maintain its sync state set.
+    pub(crate) async fn sync_controller_metadata(&self) {
+        let Some(controller_addr) = self.controller_addr() else {
+            return;
+        };
+        let (response_header, response_body) = match self
+            .broker_out_api
+            .get_replica_info(&controller_addr, self.broker_name())
+            .await
+        {
+            Ok(replica_info) => replica_info,
+            Err(e) => {
+                warn!(
+                    "Get replica info from controller {} failed: {}",
+                    controller_addr, e
+                );
+                return;
+            }
+        };
+        if let Some(member_group) = response_body
+            .as_ref()
+            .and_then(|body| body.broker_member_group.as_ref())
+        {
+            self.state.lock().member_broker_ids = member_group
+                .broker_addrs
+                .keys()
+                .map(|broker_id| *broker_id as i64)
+                .collect();
+        }
+        match (
+            response_header.master_broker_id,
+            response_header.master_address,
+            response_header.master_epoch,
+        ) {
+            (Some(master_broker_id), Some(master_address), Some(master_epoch)) => {
+                self.change_role(
+                    master_broker_id,
+                    master_address,
+                    master_epoch,
+                    response_header.sync_state_set_epoch.unwrap_or_default(),
+                    response_body.map(|body| body.sync_state_set),
+                );
+            }
+            _ => {
+                self.elect_master(&controller_addr).await;
+                return;
+            }
+        }
+        if self.is_master() {
+            self.maintain_sync_state_set(&controller_addr).await;
+        }
+    }
+
+    /// Applies the new master pushed by the controller after an election.
+    pub(crate) fn notify_broker_role_changed(&self, request: &RemotingCommand) -> RemotingCommand {
+        let request_header =
+            match request.decode_command_custom_header::<NotifyBrokerRoleChangedRequestHeader>() {
+                Ok(request_header) => request_header,
+                Err(e) => {
+                    return RemotingCommand::create_response_command_with_code_remark(
+                        ResponseCode::SystemError,
+                        format!("decode NotifyBrokerRoleChangedRequestHeader fail: {}", e),
+                    );
+                }
+            };
+        let sync_state_set = request
+            .get_body()
+            .and_then(|body| SyncStateSet::decode(body.as_ref()).ok());
+        info!(
+            "Receive notify broker role changed, master {:?} at epoch {:?}",
+            request_header.master_broker_id, request_header.master_epoch
+        );
+        if let (Some(master_broker_id), Some(master_address), Some(master_epoch)) = (
+            request_header.master_broker_id,
+            request_header.master_address,
+            request_header.master_epoch,
+        ) {
+            let sync_state_set_epoch = sync_state_set
+                .as_ref()
+                .map(|set| set.sync_state_set_epoch)
+                .or(request_header.sync_state_set_epoch)
+                .unwrap_or_default();
+            self.change_role(
+                master_broker_id,
+                master_address,
+                master_epoch,
+                sync_state_set_epoch,
+                sync_state_set.map(|set| set.sync_state_set),
+            );
+        }
+        RemotingCommand::create_response_command()
+    }
+
+    pub(crate) fn is_master(&self) -> bool {
+        self.state.lock().is_master == Some(true)
+    }
+
+    pub(crate) fn master_address(&self) -> Option<CheetahString> {
+        self.state.lock().master_address.clone()
+    }
+
+    async fn elect_master(&self, controller_addr: &CheetahString) {
+        let request_header = ElectMasterRequestHeader::of_broker_trigger(
+            self.cluster_name(),
+            self.broker_name(),
+            self.broker_id(),
+        );
+        match self
+            .broker_out_api
+            .elect_master(controller_addr, request_header)
+            .await
+        {
+            Ok((response_header, response_body)) => {
+                if let (Some(master_broker_id), Some(master_address), Some(master_epoch)) = (
+                    response_header.master_broker_id,
+                    response_header.master_address,
+                    response_header.master_epoch,
+                ) {
+                    self.change_role(
+                        master_broker_id,
+                        master_address,
+                        master_epoch,
+                        response_header.sync_state_set_epoch.unwrap_or_default(),
+                        response_body.map(|body| body.sync_state_set),
+                    );
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "Elect master through controller {} failed: {}",
+                    controller_addr, e
+                );
+            }
+        }
+    }
+
+    /// Switches to master or slave when the controller elected a new master. Information
+    /// from an older master epoch is ignored.
+    fn change_role(
+        &self,
+        master_broker_id: i64,
+        master_address: CheetahString,
+        master_epoch: i32,
+        sync_state_set_epoch: i32,
+        sync_state_set: Option<HashSet<i64>>,
+    ) {
+        let mut state = self.state.lock();
+        if master_epoch < state.master_epoch {
+            return;
+        }
+        if sync_state_set_epoch >= state.sync_state_set_epoch {
+            if let Some(sync_state_set) = sync_state_set {
+                state.sync_state_set = sync_state_set;
+            }
+            state.sync_state_set_epoch = sync_state_set_epoch;
+        }
+        let is_master = master_broker_id == self.broker_id();
+        if state.master_epoch == master_epoch
+            && state.master_broker_id == Some(master_broker_id)
+            && state.is_master == Some(is_master)
+        {
+            return;
+        }
+        state.master_broker_id = Some(master_broker_id);
+        state.master_address = Some(master_address.clone());
+        state.master_epoch = master_epoch;
+        state.is_master = Some(is_master);
+        drop(state);
+
+        if is_master {
+            info!(
+                "Change to master at epoch {}, broker id {}",
+                master_epoch, master_broker_id
+            );
+            if let Some(ha_service) = self.message_store.get_ha_service() {
+                if let Err(e) = ha_service.change_to_master() {
+                    error!("HA service change to master failed: {}", e);
+                }
+            }
+            if let Some(slave_synchronize) = self.slave_synchronize.as_ref() {
+                slave_synchronize.set_master_addr(None);
+            }
+        } else {
+            info!(
+                "Change to slave of {} at epoch {}, master broker id {}",
+                master_address, master_epoch, master_broker_id
+            );
+            if let Some(ha_address) = master_ha_address(&master_address) {
+                if let Some(ha_service) = self.message_store.get_ha_service() {
+                    ha_service.change_to_slave(ha_address);
+                }
+            }
+            if let Some(slave_synchronize) = self.slave_synchronize.as_ref() {
+                slave_synchronize.set_master_addr(Some(master_address));
+            }
+        }
+    }
+
+    /// Adds the slaves to the sync state set once they caught up with the master and
+    /// removes them when no slave is connected anymore.
+    async fn maintain_sync_state_set(&self, controller_addr: &CheetahString) {
+        let Some(ha_service) = self.message_store.get_ha_service() else {
+            return;
+        };
+        let broker_id = self.broker_id();
+        let (current, sync_state_set_epoch, master_epoch, member_broker_ids) = {
+            let state = self.state.lock();
+            (
+                state.sync_state_set.clone(),
+                state.sync_state_set_epoch,
+                state.master_epoch,
+                state.member_broker_ids.clone(),
+            )
+        };
+        let connection_count = ha_service.get_connection_count();
+        let new_sync_state_set = if connection_count == 0 {
+            HashSet::from([broker_id])
+        } else if connection_count + 1 >= member_broker_ids.len()
+            && ha_service.is_slave_ok(self.message_store.get_max_phy_offset())
+        {
+            let mut all = member_broker_ids;
+            all.insert(broker_id);
+            all
+        } else {
+            return;
+        };
+        if new_sync_state_set == current {
+            return;
+        }
+        let request_header = AlterSyncStateSetRequestHeader {
+            broker_name: self.broker_name(),
+            master_broker_id: broker_id,
+            master_epoch,
+            invoke_time: None,
+        };
+        let sync_state_set = SyncStateSet::new(new_sync_state_set.clone(), sync_state_set_epoch);
+        match self
+            .broker_out_api
+            .alter_sync_state_set(controller_addr, request_header, &sync_state_set)
+            .await
+        {
+            Ok(new_epoch) => {
+                info!(
+                    "Alter sync state set to {:?} at epoch {}",
+                    new_sync_state_set, new_epoch
+                );
+                let mut state = self.state.lock();
+                state.sync_state_set = new_sync_state_set;
+                state.sync_state_set_epoch = new_epoch;
+            }
+            Err(e) => {
+                warn!("Alter sync state set failed: {}", e);
+            }
+        }
+    }
+
+    fn controller_addr(&self) -> Option<CheetahString> {
+        self.broker_config
+            .controller_addr
+            .clone()
+            .filter(|addr| !addr.is_empty())
+    }
+
+    fn cluster_name(&self) -> CheetahString {
+        self.broker_config
+            .broker_identity
+            .broker_cluster_name
+            .clone()
+    }
+
+    fn broker_name(&self) -> CheetahString {
+        self.broker_config.broker_identity.broker_name.clone()
+    }
+
+    fn broker_id(&self) -> i64 {
+        self.broker_config.broker_identity.broker_id as i64
+    }
+}
+
+/// The HA address of a master, by convention its listen port plus one.
+fn master_ha_address(master_address: &str) -> Option<CheetahString> {
+    let (host, port) = master_address.rsplit_once(':')?;
+    let port = port.parse::<u32>().ok()?;
+    Some(CheetahString::from_string(format!("{}:{}", host, port + 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn master_ha_address_uses_next_port() {
+        assert_eq!(
+            master_ha_address("127.0.0.1:10911"),
+            Some(CheetahString::from_static_str("127.0.0.1:10912"))
+        );
+        assert_eq!(master_ha_address("127.0.0.1"), None);
+    }
 }
@@ -0,0 +1,180 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::base::commit_log_dispatcher::CommitLogDispatcher;
+use rocketmq_store::base::dispatch_request::DispatchRequest;
+use tracing::error;
+
+use crate::filter::manager::consumer_filter_manager::ConsumerFilterManager;
+use crate::filter::message_evaluation_context::MessageEvaluationContext;
+
+/// Evaluates the filters registered on the message's topic once, at dispatch time, and records
+/// the matching groups as a bloom filter bitmap that the consume queue ext persists. Pulls of
+/// SQL-filtered groups then test the bitmap instead of evaluating the expression again.
+///
+/// Must run before the consume queue dispatcher.
+pub struct CommitLogDispatcherCalcBitMap {
+    broker_config: ArcMut<BrokerConfig>,
+    consumer_filter_manager: Arc<ConsumerFilterManager>,
+}
+
+impl CommitLogDispatcherCalcBitMap {
+    pub fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        consumer_filter_manager: Arc<ConsumerFilterManager>,
+    ) -> Self {
+        Self {
+            broker_config,
+            consumer_filter_manager,
+        }
+    }
+}
+
+impl CommitLogDispatcher for CommitLogDispatcherCalcBitMap {
+    fn dispatch(&self, dispatch_request: &mut DispatchRequest) {
+        if !self.broker_config.enable_calc_filter_bit_map {
+            return;
+        }
+        let Some(bloom_filter) = self.consumer_filter_manager.get_bloom_filter() else {
+            return;
+        };
+        let filter_datas = self.consumer_filter_manager.get(&dispatch_request.topic);
+        if filter_datas.is_empty() {
+            return;
+        }
+
+        let context = MessageEvaluationContext::new(dispatch_request.properties_map.as_ref());
+        let mut bits = vec![0u8; bloom_filter.m() as usize / 8];
+        for filter_data in &filter_datas {
+            let (Some(compiled_expression), Some(bloom_filter_data)) = (
+                filter_data.compiled_expression(),
+                filter_data.bloom_filter_data(),
+            ) else {
+                continue;
+            };
+            match compiled_expression.evaluate(&context) {
+                Ok(result) => {
+                    if result.downcast_ref::<bool>().copied().unwrap_or(false) {
+                        bloom_filter.hash_to_bits(bloom_filter_data, &mut bits);
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        "calc filter bit map error, group={}, topic={}, error={}",
+                        filter_data.consumer_group(),
+                        filter_data.topic(),
+                        e
+                    );
+                }
+            }
+        }
+        dispatch_request.bit_map = Some(bits);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::collections::HashMap;
+    use std::error::Error;
+
+    use cheetah_string::CheetahString;
+    use rocketmq_filter::expression::evaluation_context::EvaluationContext;
+    use rocketmq_filter::expression::Expression;
+    use rocketmq_filter::filter_factory::FilterFactory;
+    use rocketmq_filter::filter_spi::FilterSpi;
+
+    use super::*;
+
+    // Matches messages whose `region` property equals the compiled expression.
+    struct RegionIs(String);
+
+    impl Expression for RegionIs {
+        fn evaluate(
+            &self,
+            context: &dyn EvaluationContext,
+        ) -> Result<Box<dyn Any>, Box<dyn Error>> {
+            let matched = context
+                .get("region")
+                .and_then(|value| value.downcast_ref::<CheetahString>())
+                .is_some_and(|value| value.as_str() == self.0);
+            Ok(Box::new(matched))
+        }
+    }
+
+    struct RegionFilter;
+
+    impl FilterSpi for RegionFilter {
+        fn compile(
+            &self,
+            expr: &str,
+        ) -> Result<Box<dyn Expression + Send + Sync + 'static>, Box<dyn Error + Send + Sync>>
+        {
+            Ok(Box::new(RegionIs(expr.to_string())))
+        }
+
+        fn of_type(&self) -> &str {
+            "REGION"
+        }
+    }
+
+    #[test]
+    fn bits_are_set_only_for_groups_whose_filter_matches() {
+        FilterFactory::register(Arc::new(RegionFilter));
+        let broker_config = ArcMut::new(BrokerConfig {
+            enable_calc_filter_bit_map: true,
+            ..Default::default()
+        });
+        let manager = Arc::new(ConsumerFilterManager::new(broker_config.clone()));
+        let topic = CheetahString::from_static_str("topic");
+        for (group, region) in [("group_eu", "eu"), ("group_us", "us")] {
+            assert!(manager.register(
+                topic.clone(),
+                CheetahString::from_static_str(group),
+                Some(CheetahString::from_static_str(region)),
+                Some(CheetahString::from_static_str("REGION")),
+                1,
+            ));
+        }
+
+        let dispatcher = CommitLogDispatcherCalcBitMap::new(broker_config, manager.clone());
+        let mut request = DispatchRequest {
+            topic: topic.clone(),
+            properties_map: Some(HashMap::from([(
+                CheetahString::from_static_str("region"),
+                CheetahString::from_static_str("eu"),
+            )])),
+            ..Default::default()
+        };
+        dispatcher.dispatch(&mut request);
+
+        let bits = request.bit_map.expect("bit map calculated");
+        let bloom_filter = manager.get_bloom_filter().unwrap();
+        let filter_data_of = |group: &'static str| {
+            manager
+                .get_consumer_filter_data(&topic, &CheetahString::from_static_str(group))
+                .unwrap()
+        };
+        let eu = filter_data_of("group_eu");
+        let us = filter_data_of("group_us");
+        assert!(bloom_filter.is_hit(eu.bloom_filter_data().unwrap(), &bits));
+        assert!(!bloom_filter.is_hit(us.bloom_filter_data().unwrap(), &bits));
+    }
+}
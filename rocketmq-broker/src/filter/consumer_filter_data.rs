@@ -62,6 +62,21 @@ impl ConsumerFilterData {
         self.dead_time
     }
 
+    pub fn compiled_expression(&self) -> Option<&Arc<Box<dyn Expression + Send + Sync + 'static>>> {
+        self.compiled_expression.as_ref()
+    }
+
+    /// A filter is dead once its group unsubscribed, it is kept until the dead time expires.
+    pub fn is_dead(&self) -> bool {
+        self.dead_time >= self.born_time
+    }
+
+    /// Whether a message stored at `msg_store_time` was dispatched after this filter was
+    /// registered, i.e. its bloom filter bits were calculated for this filter.
+    pub fn is_msg_in_live(&self, msg_store_time: i64) -> bool {
+        msg_store_time > self.born_time as i64
+    }
+
     pub fn bloom_filter_data(&self) -> Option<&BloomFilterData> {
         self.bloom_filter_data.as_ref()
     }
@@ -94,6 +109,13 @@ impl ConsumerFilterData {
         self.dead_time = dead_time;
     }
 
+    pub fn set_compiled_expression(
+        &mut self,
+        compiled_expression: Box<dyn Expression + Send + Sync + 'static>,
+    ) {
+        self.compiled_expression = Some(Arc::new(compiled_expression));
+    }
+
     pub fn set_bloom_filter_data(&mut self, bloom_filter_data: Option<BloomFilterData>) {
         self.bloom_filter_data = bloom_filter_data;
     }
@@ -17,14 +17,18 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use bytes::Bytes;
 use cheetah_string::CheetahString;
 use rocketmq_common::common::filter::expression_type::ExpressionType;
+use rocketmq_common::common::message::message_decoder;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
 use rocketmq_store::consume_queue::consume_queue_ext::CqExtUnit;
 use rocketmq_store::filter::MessageFilter;
+use tracing::error;
 
 use crate::filter::consumer_filter_data::ConsumerFilterData;
 use crate::filter::manager::consumer_filter_manager::ConsumerFilterManager;
+use crate::filter::message_evaluation_context::MessageEvaluationContext;
 
 pub struct ExpressionMessageFilter {
     subscription_data: Option<SubscriptionData>,
@@ -81,7 +85,35 @@ impl MessageFilter for ExpressionMessageFilter {
                 .code_set
                 .contains(&(tags_code.unwrap() as i32))
         } else {
-            unimplemented!("SQL92 expression type is not supported yet.")
+            // no expression or no bloom filter, leave it to the commit log check
+            let Some(filter_data) = self.consumer_filter_data.as_ref() else {
+                return true;
+            };
+            let Some(bloom_filter_data) = filter_data.bloom_filter_data() else {
+                return true;
+            };
+            if filter_data.expression().is_none() || filter_data.compiled_expression().is_none() {
+                return true;
+            }
+            // messages stored before the filter was registered have no bits for it
+            let Some(cq_ext_unit) = cq_ext_unit else {
+                return true;
+            };
+            if !filter_data.is_msg_in_live(cq_ext_unit.msg_store_time()) {
+                return true;
+            }
+            let Some(filter_bit_map) = cq_ext_unit.filter_bit_map().as_ref() else {
+                return true;
+            };
+            let Some(bloom_filter) = self.consumer_filter_manager.get_bloom_filter() else {
+                return true;
+            };
+            if !self.bloom_data_valid
+                || filter_bit_map.len() * 8 != bloom_filter_data.bit_num() as usize
+            {
+                return true;
+            }
+            bloom_filter.is_hit(bloom_filter_data, filter_bit_map)
         }
     }
 
@@ -107,6 +139,129 @@ impl MessageFilter for ExpressionMessageFilter {
         if real_filter_data.expression().is_none() || real_filter_data.expression_type().is_none() {
             return true;
         }
-        unimplemented!("SQL92 expression type is not supported yet.")
+        let Some(compiled_expression) = real_filter_data.compiled_expression() else {
+            return true;
+        };
+        // the store passes the raw message, decode its properties like the pull path does
+        let decoded_properties = match (properties, msg_buffer) {
+            (None, Some(msg_buffer)) => decode_properties(msg_buffer),
+            _ => None,
+        };
+        let context = MessageEvaluationContext::new(properties.or(decoded_properties.as_ref()));
+        match compiled_expression.evaluate(&context) {
+            Ok(result) => result.downcast_ref::<bool>().copied().unwrap_or(false),
+            Err(e) => {
+                error!(
+                    "message filter error, group={}, topic={}, error={}",
+                    real_filter_data.consumer_group(),
+                    real_filter_data.topic(),
+                    e
+                );
+                false
+            }
+        }
+    }
+}
+
+fn decode_properties(msg_buffer: &[u8]) -> Option<HashMap<CheetahString, CheetahString>> {
+    let mut buffer = Bytes::copy_from_slice(msg_buffer);
+    message_decoder::decode(&mut buffer, false, false, false, false, false)
+        .map(|msg| msg.message.properties)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rocketmq_common::common::broker::broker_config::BrokerConfig;
+    use rocketmq_common::common::message::message_decoder::message_properties_to_string;
+    use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+    use rocketmq_common::common::message::MessageTrait;
+    use rocketmq_rust::ArcMut;
+    use rocketmq_store::base::message_status_enum::GetMessageStatus;
+    use rocketmq_store::config::message_store_config::MessageStoreConfig;
+    use rocketmq_store::log_file::MessageStore;
+    use rocketmq_store::message_store::default_message_store::DefaultMessageStore;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn sql92_subscription_pulls_only_matching_messages() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let message_store_config = MessageStoreConfig {
+            store_path_root_dir: temp_dir.path().to_string_lossy().to_string().into(),
+            mapped_file_size_commit_log: 64 * 1024,
+            ..MessageStoreConfig::default()
+        };
+        let broker_config = ArcMut::new(BrokerConfig::default());
+        let mut store = ArcMut::new(DefaultMessageStore::new(
+            ArcMut::new(message_store_config),
+            broker_config.clone(),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        ));
+        let store_clone = store.clone();
+        store.set_message_store_arc(Some(store_clone));
+        assert!(store.load().await);
+        store.start().unwrap();
+
+        let topic = CheetahString::from_static_str("TopicSql");
+        for (region, a) in [("eu", "1"), ("us", "2"), ("eu", "5"), ("eu", "3")] {
+            let mut msg = MessageExtBrokerInner::default();
+            msg.set_topic(topic.clone());
+            msg.set_body(Bytes::from_static(b"body"));
+            msg.put_property(
+                CheetahString::from_static_str("region"),
+                CheetahString::from_static_str(region),
+            );
+            msg.put_property(
+                CheetahString::from_static_str("a"),
+                CheetahString::from_static_str(a),
+            );
+            msg.properties_string = message_properties_to_string(msg.get_properties());
+            let result = store.put_message(msg).await;
+            assert!(result.is_ok());
+        }
+        for _ in 0..100 {
+            if store.get_max_offset_in_queue(&topic, 0) == 4 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(store.get_max_offset_in_queue(&topic, 0), 4);
+
+        let expression = CheetahString::from_static_str("region = 'eu' AND a BETWEEN 1 AND 3");
+        let group = CheetahString::from_static_str("group");
+        let consumer_filter_data = ConsumerFilterManager::build(
+            topic.clone(),
+            group.clone(),
+            Some(expression.clone()),
+            Some(CheetahString::from_static_str(ExpressionType::SQL92)),
+            1,
+        );
+        assert!(consumer_filter_data.is_some());
+        let filter = ExpressionMessageFilter::new(
+            Some(SubscriptionData {
+                topic: topic.clone(),
+                sub_string: expression,
+                expression_type: CheetahString::from_static_str(ExpressionType::SQL92),
+                ..Default::default()
+            }),
+            consumer_filter_data,
+            Arc::new(ConsumerFilterManager::new(broker_config)),
+        );
+        let result = store
+            .get_message(&group, &topic, 0, 0, 32, 1024 * 1024, Some(&filter))
+            .await
+            .unwrap();
+        assert_eq!(result.status(), Some(GetMessageStatus::Found));
+        let matched: Vec<CheetahString> = result
+            .message_mapped_list()
+            .iter()
+            .map(|msg| decode_properties(msg.get_buffer()).unwrap()["a"].clone())
+            .collect();
+        assert_eq!(matched, vec!["1", "3"]);
+        store.shutdown().await;
     }
 }
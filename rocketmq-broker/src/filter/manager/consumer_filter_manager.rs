@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -23,7 +24,12 @@ use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::config_manager::ConfigManager;
 use rocketmq_common::common::filter::expression_type::ExpressionType;
 use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_filter::filter_factory::FilterFactory;
 use rocketmq_filter::utils::bloom_filter::BloomFilter;
+use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
+use rocketmq_rust::ArcMut;
+use tracing::error;
+use tracing::warn;
 
 use crate::broker_path_config_helper::get_consumer_filter_path;
 use crate::filter::consumer_filter_data::ConsumerFilterData;
@@ -33,13 +39,13 @@ const MS_24_HOUR: u64 = Duration::from_hours(24).as_millis() as u64;
 
 #[derive(Default)]
 pub(crate) struct ConsumerFilterManager {
-    broker_config: Arc<BrokerConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     consumer_filter_wrapper: Arc<parking_lot::RwLock<ConsumerFilterWrapper>>,
     bloom_filter: Option<BloomFilter>,
 }
 
 impl ConsumerFilterManager {
-    pub fn new(mut broker_config: Arc<BrokerConfig>) -> Self {
+    pub fn new(broker_config: ArcMut<BrokerConfig>) -> Self {
         let consumer_filter_wrapper =
             Arc::new(parking_lot::RwLock::new(ConsumerFilterWrapper::default()));
         let bloom_filter = BloomFilter::new(
@@ -47,8 +53,9 @@ impl ConsumerFilterManager {
             broker_config.expect_consumer_num_use_filter,
         )
         .unwrap();
-        let broker_config_mut = Arc::make_mut(&mut broker_config);
-        broker_config_mut.bit_map_length_consume_queue_ext = bloom_filter.m();
+        broker_config
+            .mut_from_ref()
+            .bit_map_length_consume_queue_ext = bloom_filter.m();
         ConsumerFilterManager {
             broker_config,
             consumer_filter_wrapper,
@@ -101,25 +108,157 @@ impl ConsumerFilterManager {
         consumer_filter_data.set_consumer_group(consumer_group);
         consumer_filter_data.set_born_time(get_current_millis());
         consumer_filter_data.set_dead_time(0);
+        consumer_filter_data.set_client_version(client_version);
+
+        let Some(filter_spi) = FilterFactory::get(type_.as_deref()?) else {
+            warn!(
+                "no filter registered for expression type {:?}, topic={}, group={}",
+                type_,
+                consumer_filter_data.topic(),
+                consumer_filter_data.consumer_group()
+            );
+            return None;
+        };
+        match filter_spi.compile(expression.as_deref()?) {
+            Ok(compiled_expression) => {
+                consumer_filter_data.set_compiled_expression(compiled_expression);
+            }
+            Err(e) => {
+                error!(
+                    "parse error: expr={:?}, topic={}, group={}, error={}",
+                    expression,
+                    consumer_filter_data.topic(),
+                    consumer_filter_data.consumer_group(),
+                    e
+                );
+                return None;
+            }
+        }
         consumer_filter_data.set_expression(expression);
         consumer_filter_data.set_expression_type(type_);
-        consumer_filter_data.set_client_version(client_version);
+        Some(consumer_filter_data)
+    }
 
-        /*        let filter_factory = FilterFactory;
-                match filter_factory.get(&type_).compile(&expression) {
-                    Ok(compiled_expression) => {
-                        consumer_filter_data.set_compiled_expression(compiled_expression);
-                    }
-                    Err(e) => {
-                        eprintln!(
-                            "parse error: expr={}, topic={}, group={}, error={}",
-                            expression, topic, consumer_group, e
-                        );
-                        return None;
-                    }
+    /// Registers the filters of every non-tag subscription of `consumer_group` and marks its
+    /// filters on topics it no longer subscribes to as dead.
+    pub fn register_subscriptions(
+        &self,
+        consumer_group: &CheetahString,
+        sub_list: &HashSet<SubscriptionData>,
+    ) {
+        for sub in sub_list {
+            if ExpressionType::is_tag_type(Some(sub.expression_type.as_str())) {
+                continue;
+            }
+            self.register(
+                sub.topic.clone(),
+                consumer_group.clone(),
+                Some(sub.sub_string.clone()),
+                Some(sub.expression_type.clone()),
+                sub.sub_version as u64,
+            );
+        }
+
+        let now = get_current_millis();
+        let mut wrapper = self.consumer_filter_wrapper.write();
+        for filter_data_map in wrapper.filter_data_maps_mut() {
+            let subscribed = sub_list
+                .iter()
+                .any(|sub| sub.topic.as_str() == filter_data_map.topic());
+            if subscribed {
+                continue;
+            }
+            if let Some(filter_data) = filter_data_map.get_mut(consumer_group) {
+                if !filter_data.is_dead() {
+                    filter_data.set_dead_time(now);
                 }
-        */
-        Some(consumer_filter_data)
+            }
+        }
+    }
+
+    /// Registers or refreshes the filter of `consumer_group` on `topic`. Returns `true` when the
+    /// filter was added, replaced by a newer client version or brought back to life.
+    pub fn register(
+        &self,
+        topic: CheetahString,
+        consumer_group: CheetahString,
+        expression: Option<CheetahString>,
+        type_: Option<CheetahString>,
+        client_version: u64,
+    ) -> bool {
+        if ExpressionType::is_tag_type(type_.as_deref()) {
+            return false;
+        }
+        let mut wrapper = self.consumer_filter_wrapper.write();
+        let filter_data_map = wrapper.filter_data_map_mut(topic.as_str());
+        if let Some(old) = filter_data_map.get_mut(consumer_group.as_str()) {
+            if client_version <= old.client_version() {
+                if old.expression_type() != type_.as_ref()
+                    || old.expression() != expression.as_ref()
+                {
+                    warn!(
+                        "ignore consumer({} : {}) filter({:?}) with older or equal version {}, \
+                         current filter is {:?}",
+                        consumer_group,
+                        topic,
+                        expression,
+                        client_version,
+                        old.expression()
+                    );
+                }
+                if old.is_dead() {
+                    old.set_dead_time(0);
+                    old.set_born_time(get_current_millis());
+                    return true;
+                }
+                return false;
+            }
+        }
+
+        let Some(mut filter_data) = Self::build(
+            topic.clone(),
+            consumer_group.clone(),
+            expression,
+            type_,
+            client_version,
+        ) else {
+            return false;
+        };
+        filter_data.set_bloom_filter_data(
+            self.bloom_filter
+                .as_ref()
+                .map(|bloom_filter| bloom_filter.hash_to(&format!("{consumer_group}#{topic}"))),
+        );
+        filter_data_map.insert(consumer_group.as_str(), filter_data);
+        true
+    }
+
+    /// Marks every filter of `consumer_group` as dead.
+    pub fn unregister(&self, consumer_group: &CheetahString) {
+        let now = get_current_millis();
+        let mut wrapper = self.consumer_filter_wrapper.write();
+        for filter_data_map in wrapper.filter_data_maps_mut() {
+            if let Some(filter_data) = filter_data_map.get_mut(consumer_group) {
+                if !filter_data.is_dead() {
+                    filter_data.set_dead_time(now);
+                }
+            }
+        }
+    }
+
+    /// The live filters registered on `topic`.
+    pub fn get(&self, topic: &CheetahString) -> Vec<ConsumerFilterData> {
+        self.consumer_filter_wrapper
+            .read()
+            .filter_data_map(topic)
+            .map(|filter_data_map| {
+                filter_data_map
+                    .values()
+                    .filter(|filter_data| !filter_data.is_dead())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     pub fn get_consumer_filter_data(
@@ -127,7 +266,11 @@ impl ConsumerFilterManager {
         topic: &CheetahString,
         consumer_group: &CheetahString,
     ) -> Option<ConsumerFilterData> {
-        None
+        self.consumer_filter_wrapper
+            .read()
+            .filter_data_map(topic)?
+            .get(consumer_group)
+            .cloned()
     }
 
     pub fn get_bloom_filter(&self) -> Option<&BloomFilter> {
@@ -33,3 +33,45 @@ pub struct FilterDataMapByTopic {
     filter_data_map: HashMap<String /* consumer group */, ConsumerFilterData>,
     topic: String,
 }
+
+impl ConsumerFilterWrapper {
+    pub fn filter_data_map(&self, topic: &str) -> Option<&FilterDataMapByTopic> {
+        self.filter_data_by_topic.get(topic)
+    }
+
+    pub fn filter_data_map_mut(&mut self, topic: &str) -> &mut FilterDataMapByTopic {
+        self.filter_data_by_topic
+            .entry(topic.to_string())
+            .or_insert_with(|| FilterDataMapByTopic {
+                filter_data_map: HashMap::new(),
+                topic: topic.to_string(),
+            })
+    }
+
+    pub fn filter_data_maps_mut(&mut self) -> impl Iterator<Item = &mut FilterDataMapByTopic> {
+        self.filter_data_by_topic.values_mut()
+    }
+}
+
+impl FilterDataMapByTopic {
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub fn get(&self, consumer_group: &str) -> Option<&ConsumerFilterData> {
+        self.filter_data_map.get(consumer_group)
+    }
+
+    pub fn get_mut(&mut self, consumer_group: &str) -> Option<&mut ConsumerFilterData> {
+        self.filter_data_map.get_mut(consumer_group)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &ConsumerFilterData> {
+        self.filter_data_map.values()
+    }
+
+    pub fn insert(&mut self, consumer_group: &str, filter_data: ConsumerFilterData) {
+        self.filter_data_map
+            .insert(consumer_group.to_string(), filter_data);
+    }
+}
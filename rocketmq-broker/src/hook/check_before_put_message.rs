@@ -15,7 +15,6 @@
  * limitations under the License.
  */
 use std::ops::Deref;
-use std::sync::Arc;
 
 use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_rust::ArcMut;
@@ -28,11 +27,14 @@ use crate::util::hook_utils::HookUtils;
 
 pub struct CheckBeforePutMessageHook<MS> {
     message_store: ArcMut<MS>,
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
 }
 
 impl<MS: MessageStore> CheckBeforePutMessageHook<MS> {
-    pub fn new(message_store: ArcMut<MS>, message_store_config: Arc<MessageStoreConfig>) -> Self {
+    pub fn new(
+        message_store: ArcMut<MS>,
+        message_store_config: ArcMut<MessageStoreConfig>,
+    ) -> Self {
         Self {
             message_store,
             message_store_config,
@@ -0,0 +1,299 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::log_file::MessageStore;
+use tokio::sync::oneshot;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+struct WaitingRequest {
+    id: u64,
+    create_timestamp: u64,
+    fail_tx: oneshot::Sender<RemotingCommand>,
+}
+
+/// Admission queue in front of a group of processors.
+///
+/// At most `permits` requests are being dispatched at the same time, the others wait in
+/// arrival order until a permit is released or the request is failed by
+/// [`BrokerFastFailure`]. Like the executor threads of the Java send pool, a permit is meant to
+/// be released once the request is handed over, not when its response is ready.
+pub(crate) struct RequestQueue {
+    semaphore: Arc<Semaphore>,
+    capacity: usize,
+    waiting: Mutex<VecDeque<WaitingRequest>>,
+    next_id: AtomicU64,
+}
+
+impl RequestQueue {
+    pub(crate) fn new(permits: usize, capacity: usize) -> Self {
+        RequestQueue {
+            semaphore: Arc::new(Semaphore::new(permits.max(1))),
+            capacity,
+            waiting: Mutex::new(VecDeque::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the number of requests waiting for a permit.
+    pub(crate) fn size(&self) -> usize {
+        self.waiting.lock().len()
+    }
+
+    /// Waits for a processing permit.
+    ///
+    /// Returns the response to send back instead when the queue is full or the request was
+    /// failed while waiting.
+    pub(crate) async fn acquire(&self) -> Result<OwnedSemaphorePermit, RemotingCommand> {
+        if let Ok(permit) = self.semaphore.clone().try_acquire_owned() {
+            return Ok(permit);
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (fail_tx, fail_rx) = oneshot::channel();
+        {
+            let mut waiting = self.waiting.lock();
+            if waiting.len() >= self.capacity {
+                return Err(RemotingCommand::create_response_command_with_code_remark(
+                    RemotingSysResponseCode::SystemBusy,
+                    "[OVERLOAD]system busy, start flow control for a while",
+                ));
+            }
+            waiting.push_back(WaitingRequest {
+                id,
+                create_timestamp: get_current_millis(),
+                fail_tx,
+            });
+        }
+        tokio::select! {
+            permit = self.semaphore.clone().acquire_owned() => {
+                self.waiting.lock().retain(|request| request.id != id);
+                Ok(permit.expect("request queue semaphore closed"))
+            }
+            Ok(response) = fail_rx => Err(response),
+        }
+    }
+
+    /// Fails the waiting requests that have been queued for at least `max_wait_time_mills`.
+    fn clean_expired_request(&self, max_wait_time_mills: u64) {
+        loop {
+            let (request, size) = {
+                let mut waiting = self.waiting.lock();
+                match waiting.front() {
+                    Some(request)
+                        if get_current_millis().saturating_sub(request.create_timestamp)
+                            >= max_wait_time_mills =>
+                    {
+                        let request = waiting.pop_front().unwrap();
+                        (request, waiting.len())
+                    }
+                    _ => return,
+                }
+            };
+            Self::fail(request, "TIMEOUT_CLEAN_QUEUE", size);
+        }
+    }
+
+    /// Fails every waiting request.
+    fn clean_all_request(&self, reason: &str) {
+        loop {
+            let (request, size) = {
+                let mut waiting = self.waiting.lock();
+                match waiting.pop_front() {
+                    Some(request) => (request, waiting.len()),
+                    None => return,
+                }
+            };
+            Self::fail(request, reason, size);
+        }
+    }
+
+    fn fail(request: WaitingRequest, reason: &str, size: usize) {
+        let remark = format!(
+            "[{}]broker busy, start flow control for a while, period in queue: {}ms, size of \
+             queue: {}",
+            reason,
+            get_current_millis().saturating_sub(request.create_timestamp),
+            size
+        );
+        let _ = request
+            .fail_tx
+            .send(RemotingCommand::create_response_command_with_code_remark(
+                RemotingSysResponseCode::SystemBusy,
+                remark,
+            ));
+    }
+}
+
+/// Fails requests that waited too long in the send and pull queues, so that clients get a
+/// `SYSTEM_BUSY` response quickly instead of timing out.
+pub(crate) struct BrokerFastFailure {
+    broker_config: ArcMut<BrokerConfig>,
+    send_queue: RequestQueue,
+    pull_queue: RequestQueue,
+    scheduled_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl BrokerFastFailure {
+    pub(crate) fn new(broker_config: ArcMut<BrokerConfig>) -> Self {
+        BrokerFastFailure {
+            send_queue: RequestQueue::new(
+                broker_config.send_message_thread_pool_nums as usize,
+                broker_config.send_thread_pool_queue_capacity as usize,
+            ),
+            pull_queue: RequestQueue::new(
+                broker_config.pull_message_thread_pool_nums as usize,
+                broker_config.pull_thread_pool_queue_capacity as usize,
+            ),
+            broker_config,
+            scheduled_task: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn send_queue(&self) -> &RequestQueue {
+        &self.send_queue
+    }
+
+    pub(crate) fn pull_queue(&self) -> &RequestQueue {
+        &self.pull_queue
+    }
+
+    pub(crate) fn start<MS>(self: &Arc<Self>, message_store: ArcMut<MS>)
+    where
+        MS: MessageStore + Send + Sync + 'static,
+    {
+        if !self.broker_config.broker_fast_failure_enable {
+            return;
+        }
+        let this = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            let mut interval = tokio::time::interval(Duration::from_millis(10));
+            loop {
+                interval.tick().await;
+                this.clean_expired_request(message_store.is_os_page_cache_busy());
+            }
+        });
+        *self.scheduled_task.lock() = Some(handle);
+        info!("BrokerFastFailure started");
+    }
+
+    pub(crate) fn shutdown(&self) {
+        if let Some(handle) = self.scheduled_task.lock().take() {
+            handle.abort();
+        }
+    }
+
+    fn clean_expired_request(&self, os_page_cache_busy: bool) {
+        if os_page_cache_busy {
+            self.send_queue.clean_all_request("PCBUSY_CLEAN_QUEUE");
+        }
+        self.send_queue
+            .clean_expired_request(self.broker_config.wait_time_mills_in_send_queue.get());
+        self.pull_queue
+            .clean_expired_request(self.broker_config.wait_time_mills_in_pull_queue.get());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::code::response_code::ResponseCode;
+
+    use super::*;
+
+    fn broker_config(wait_time_mills_in_send_queue: u64) -> ArcMut<BrokerConfig> {
+        ArcMut::new(BrokerConfig {
+            send_message_thread_pool_nums: 1,
+            send_thread_pool_queue_capacity: 1,
+            wait_time_mills_in_send_queue: wait_time_mills_in_send_queue.into(),
+            ..BrokerConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn full_queue_rejects_request() {
+        let fast_failure = Arc::new(BrokerFastFailure::new(broker_config(200)));
+        let permit = fast_failure.send_queue().acquire().await.ok().unwrap();
+
+        let waiter = {
+            let fast_failure = fast_failure.clone();
+            tokio::spawn(async move { fast_failure.send_queue().acquire().await.is_ok() })
+        };
+        while fast_failure.send_queue().size() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let response = fast_failure.send_queue().acquire().await.unwrap_err();
+        assert_eq!(
+            ResponseCode::from(response.code()),
+            ResponseCode::SystemBusy
+        );
+        assert!(response.remark().unwrap().starts_with("[OVERLOAD]"));
+
+        drop(permit);
+        assert!(waiter.await.unwrap());
+        assert_eq!(fast_failure.send_queue().size(), 0);
+    }
+
+    #[tokio::test]
+    async fn expired_and_page_cache_busy_requests_fail_fast() {
+        let fast_failure = Arc::new(BrokerFastFailure::new(broker_config(0)));
+        let _permit = fast_failure.send_queue().acquire().await.ok().unwrap();
+
+        let waiter = {
+            let fast_failure = fast_failure.clone();
+            tokio::spawn(async move { fast_failure.send_queue().acquire().await })
+        };
+        while fast_failure.send_queue().size() == 0 {
+            tokio::task::yield_now().await;
+        }
+        fast_failure.clean_expired_request(false);
+        let response = waiter.await.unwrap().unwrap_err();
+        assert!(response
+            .remark()
+            .unwrap()
+            .starts_with("[TIMEOUT_CLEAN_QUEUE]"));
+
+        let waiter = {
+            let fast_failure = fast_failure.clone();
+            tokio::spawn(async move { fast_failure.send_queue().acquire().await })
+        };
+        while fast_failure.send_queue().size() == 0 {
+            tokio::task::yield_now().await;
+        }
+        fast_failure
+            .send_queue
+            .clean_all_request("PCBUSY_CLEAN_QUEUE");
+        let response = waiter.await.unwrap().unwrap_err();
+        assert!(response
+            .remark()
+            .unwrap()
+            .starts_with("[PCBUSY_CLEAN_QUEUE]"));
+    }
+}
@@ -0,0 +1,329 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use arc_swap::ArcSwap;
+use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
+use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header::parse_request_header;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_rust::ArcMut;
+
+/// Token bucket refilled at `permits_per_second` and holding at most one second of
+/// permits, so short bursts pass but the sustained rate is capped.
+struct TokenBucket {
+    permits_per_second: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(permits_per_second: u64, now: Instant) -> Self {
+        TokenBucket {
+            permits_per_second,
+            tokens: permits_per_second as f64,
+            last_refill: now,
+        }
+    }
+
+    fn try_acquire(&mut self, permits_per_second: u64, now: Instant) -> bool {
+        if permits_per_second != self.permits_per_second {
+            self.permits_per_second = permits_per_second;
+            self.tokens = self.tokens.min(permits_per_second as f64);
+        }
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * permits_per_second as f64).min(permits_per_second as f64);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Buckets idle for this long are full again and get dropped, a new bucket starts full too.
+const BUCKET_IDLE_MILLIS: u64 = 1000;
+
+/// The overrides of one config value, parsed once whenever the config value is replaced.
+struct ParsedOverrides {
+    source: Arc<CheetahString>,
+    limits: HashMap<CheetahString, u64>,
+}
+
+impl ParsedOverrides {
+    fn parse(source: Arc<CheetahString>) -> Self {
+        let limits = BrokerConfig::parse_rate_limit_overrides("", &source).unwrap_or_default();
+        ParsedOverrides { source, limits }
+    }
+}
+
+struct Buckets {
+    buckets: HashMap<CheetahString, TokenBucket>,
+    last_eviction: Instant,
+}
+
+/// The buckets of one kind of key, topics or groups.
+struct KeyedRateLimiter {
+    overrides: ArcSwap<ParsedOverrides>,
+    buckets: Mutex<Buckets>,
+}
+
+impl KeyedRateLimiter {
+    fn new(now: Instant) -> Self {
+        KeyedRateLimiter {
+            overrides: ArcSwap::from_pointee(ParsedOverrides::parse(Arc::default())),
+            buckets: Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                last_eviction: now,
+            }),
+        }
+    }
+
+    /// Takes a permit for `key`, the error is the exceeded limit.
+    fn try_acquire(
+        &self,
+        key: &CheetahString,
+        default_limit: u64,
+        overrides: Arc<CheetahString>,
+        now: Instant,
+    ) -> Result<(), u64> {
+        let limit = self.limit(key, default_limit, overrides);
+        if limit == 0 {
+            return Ok(());
+        }
+        let mut buckets = self.buckets.lock();
+        let idle = Duration::from_millis(BUCKET_IDLE_MILLIS);
+        if now.saturating_duration_since(buckets.last_eviction) >= idle {
+            buckets
+                .buckets
+                .retain(|_, bucket| now.saturating_duration_since(bucket.last_refill) < idle);
+            buckets.last_eviction = now;
+        }
+        let acquired = buckets
+            .buckets
+            .entry(key.clone())
+            .or_insert_with(|| TokenBucket::new(limit, now))
+            .try_acquire(limit, now);
+        if acquired {
+            Ok(())
+        } else {
+            Err(limit)
+        }
+    }
+
+    fn limit(&self, key: &CheetahString, default_limit: u64, overrides: Arc<CheetahString>) -> u64 {
+        let mut parsed = self.overrides.load();
+        // the overrides may be replaced at runtime, parse them again once they are
+        if !Arc::ptr_eq(&parsed.source, &overrides) {
+            self.overrides
+                .store(Arc::new(ParsedOverrides::parse(overrides)));
+            parsed = self.overrides.load();
+        }
+        parsed.limits.get(key).copied().unwrap_or(default_limit)
+    }
+}
+
+/// Caps the sends per topic and the pulls per consumer group, so that one noisy tenant
+/// can not starve the broker. The limits are read from the broker config on every request
+/// and can be changed at runtime.
+pub(crate) struct RequestRateLimiter {
+    broker_config: ArcMut<BrokerConfig>,
+    topics: KeyedRateLimiter,
+    groups: KeyedRateLimiter,
+}
+
+impl RequestRateLimiter {
+    pub(crate) fn new(broker_config: ArcMut<BrokerConfig>) -> Self {
+        RequestRateLimiter {
+            broker_config,
+            topics: KeyedRateLimiter::new(Instant::now()),
+            groups: KeyedRateLimiter::new(Instant::now()),
+        }
+    }
+
+    /// Returns the response to send back instead when the topic of the send request
+    /// exceeded its rate.
+    pub(crate) fn check_send(
+        &self,
+        request_code: RequestCode,
+        request: &RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        if self.broker_config.topic_send_rate_limit.get() == 0
+            && self
+                .broker_config
+                .topic_send_rate_limit_overrides
+                .get()
+                .is_empty()
+        {
+            return None;
+        }
+        let topic = parse_request_header(request, request_code).ok()?.topic;
+        let limit = self
+            .topics
+            .try_acquire(
+                &topic,
+                self.broker_config.topic_send_rate_limit.get(),
+                self.broker_config.topic_send_rate_limit_overrides.get(),
+                Instant::now(),
+            )
+            .err()?;
+        Some(flow_control_response("TOPIC", topic.as_str(), limit))
+    }
+
+    /// Returns the response to send back instead when the consumer group of the pull
+    /// request exceeded its rate.
+    pub(crate) fn check_pull(&self, request: &RemotingCommand) -> Option<RemotingCommand> {
+        if self.broker_config.group_pull_rate_limit.get() == 0
+            && self
+                .broker_config
+                .group_pull_rate_limit_overrides
+                .get()
+                .is_empty()
+        {
+            return None;
+        }
+        let group = request.ext_fields()?.get("consumerGroup")?;
+        let limit = self
+            .groups
+            .try_acquire(
+                group,
+                self.broker_config.group_pull_rate_limit.get(),
+                self.broker_config.group_pull_rate_limit_overrides.get(),
+                Instant::now(),
+            )
+            .err()?;
+        Some(flow_control_response("GROUP", group.as_str(), limit))
+    }
+}
+
+fn flow_control_response(kind: &str, key: &str, limit: u64) -> RemotingCommand {
+    RemotingCommand::create_response_command_with_code_remark(
+        RemotingSysResponseCode::SystemBusy,
+        format!(
+            "[{}_RATE_LIMIT]{} exceeded {} requests per second, start flow control for a while",
+            kind, key, limit
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::code::response_code::ResponseCode;
+
+    use super::*;
+
+    #[test]
+    fn bucket_allows_a_burst_then_refills_at_the_rate() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(2, now);
+        assert!(bucket.try_acquire(2, now));
+        assert!(bucket.try_acquire(2, now));
+        assert!(!bucket.try_acquire(2, now));
+        assert!(!bucket.try_acquire(2, now + Duration::from_millis(400)));
+        assert!(bucket.try_acquire(2, now + Duration::from_millis(600)));
+    }
+
+    #[test]
+    fn overrides_win_over_the_default_limit() {
+        let now = Instant::now();
+        let limiter = KeyedRateLimiter::new(now);
+        let overrides = Arc::new(CheetahString::from("TopicA:1;TopicB:0"));
+        let topic_a = CheetahString::from("TopicA");
+        let topic_b = CheetahString::from("TopicB");
+        assert_eq!(
+            limiter.try_acquire(&topic_a, 5, overrides.clone(), now),
+            Ok(())
+        );
+        assert_eq!(
+            limiter.try_acquire(&topic_a, 5, overrides.clone(), now),
+            Err(1)
+        );
+        for _ in 0..10 {
+            assert_eq!(
+                limiter.try_acquire(&topic_b, 5, overrides.clone(), now),
+                Ok(())
+            );
+        }
+        assert!(Arc::ptr_eq(&limiter.overrides.load().source, &overrides));
+        // removing the override at runtime falls back to the default limit
+        let overrides = Arc::new(CheetahString::new());
+        assert_eq!(
+            limiter.try_acquire(&topic_b, 1, overrides.clone(), now),
+            Ok(())
+        );
+        assert_eq!(limiter.try_acquire(&topic_b, 1, overrides, now), Err(1));
+    }
+
+    #[test]
+    fn idle_buckets_are_evicted() {
+        let now = Instant::now();
+        let limiter = KeyedRateLimiter::new(now);
+        let overrides = Arc::new(CheetahString::new());
+        for i in 0..100 {
+            let topic = CheetahString::from(format!("Topic{}", i));
+            assert_eq!(
+                limiter.try_acquire(&topic, 1, overrides.clone(), now),
+                Ok(())
+            );
+        }
+        assert_eq!(limiter.buckets.lock().buckets.len(), 100);
+
+        let later = now + Duration::from_millis(BUCKET_IDLE_MILLIS);
+        let topic = CheetahString::from("Topic0");
+        assert_eq!(
+            limiter.try_acquire(&topic, 1, overrides.clone(), later),
+            Ok(())
+        );
+        assert_eq!(limiter.buckets.lock().buckets.len(), 1);
+        assert_eq!(limiter.try_acquire(&topic, 1, overrides, later), Err(1));
+    }
+
+    #[test]
+    fn pulls_over_the_group_limit_get_flow_control() {
+        let broker_config = ArcMut::new(BrokerConfig {
+            group_pull_rate_limit: 1.into(),
+            ..BrokerConfig::default()
+        });
+        let limiter = RequestRateLimiter::new(broker_config.clone());
+        let request = RemotingCommand::create_remoting_command(RequestCode::PullMessage)
+            .set_ext_fields(HashMap::from([(
+                CheetahString::from("consumerGroup"),
+                CheetahString::from("GroupA"),
+            )]));
+        assert!(limiter.check_pull(&request).is_none());
+        let response = limiter.check_pull(&request).unwrap();
+        assert_eq!(
+            ResponseCode::from(response.code()),
+            ResponseCode::SystemBusy
+        );
+
+        broker_config.group_pull_rate_limit.set(0);
+        assert!(limiter.check_pull(&request).is_none());
+    }
+}
@@ -36,13 +36,16 @@ pub(crate) mod coldctr;
 pub(crate) mod controller;
 pub(crate) mod filter;
 pub(crate) mod hook;
+pub(crate) mod latency;
 pub(crate) mod load_balance;
 pub(crate) mod long_polling;
-pub(crate) mod mqtrace;
+pub(crate) mod metrics;
+pub mod mqtrace;
 pub(crate) mod offset;
 pub(crate) mod out_api;
 pub(crate) mod processor;
 pub(crate) mod schedule;
+pub(crate) mod slave;
 pub(crate) mod subscription;
 pub(crate) mod topic;
 mod transaction;
@@ -18,4 +18,5 @@
 pub(crate) mod long_polling_service;
 pub(crate) mod many_pull_request;
 pub(crate) mod notify_message_arriving_listener;
+pub(crate) mod polling_num_table;
 pub(crate) mod pull_request;
@@ -39,7 +39,7 @@ pub struct PullRequestHoldService<MS> {
     pull_request_table: Arc<parking_lot::RwLock<HashMap<String, ManyPullRequest>>>,
     pull_message_processor: ArcMut<PullMessageProcessor<MS>>,
     message_store: ArcMut<MS>,
-    broker_config: Arc<BrokerConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     shutdown: Arc<Notify>,
 }
 
@@ -50,7 +50,7 @@ where
     pub fn new(
         message_store: ArcMut<MS>,
         pull_message_processor: ArcMut<PullMessageProcessor<MS>>,
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
     ) -> Self {
         PullRequestHoldService {
             pull_request_table: Arc::new(parking_lot::RwLock::new(HashMap::new())),
@@ -70,7 +70,7 @@ where
     pub fn start(&mut self, this: ArcMut<Self>) {
         tokio::spawn(async move {
             loop {
-                let handle_future = if this.broker_config.long_polling_enable {
+                let handle_future = if this.broker_config.long_polling_enable.get() {
                     tokio::time::sleep(tokio::time::Duration::from_secs(5))
                 } else {
                     tokio::time::sleep(tokio::time::Duration::from_millis(
@@ -97,6 +97,15 @@ where
         });
     }
 
+    /// Number of pull requests currently suspended by this service.
+    pub fn hold_request_count(&self) -> usize {
+        self.pull_request_table
+            .read()
+            .values()
+            .map(ManyPullRequest::len)
+            .sum()
+    }
+
     pub fn shutdown(&mut self) {
         self.shutdown.notify_waiters();
     }
@@ -57,6 +57,10 @@ impl ManyPullRequest {
         list.clone()
     }*/
 
+    pub fn len(&self) -> usize {
+        self.pull_request_list.lock().len()
+    }
+
     pub fn is_empty(&self) -> bool {
         let list = self.pull_request_list.lock();
         list.is_empty()
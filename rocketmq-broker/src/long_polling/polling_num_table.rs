@@ -0,0 +1,71 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use parking_lot::Mutex;
+use rocketmq_common::common::key_builder::KeyBuilder;
+
+/// Counts the notification requests the broker is currently holding, keyed by
+/// `topic@group@queue_id`.
+#[derive(Default)]
+pub struct PollingNumTable {
+    table: Mutex<HashMap<String, i32>>,
+}
+
+impl PollingNumTable {
+    pub fn increment(&self, topic: &str, group: &str, queue_id: i32) {
+        let key = KeyBuilder::build_polling_key(topic, group, queue_id);
+        *self.table.lock().entry(key).or_insert(0) += 1;
+    }
+
+    pub fn decrement(&self, topic: &str, group: &str, queue_id: i32) {
+        let key = KeyBuilder::build_polling_key(topic, group, queue_id);
+        let mut table = self.table.lock();
+        if let Some(num) = table.get_mut(&key) {
+            *num -= 1;
+            if *num <= 0 {
+                table.remove(&key);
+            }
+        }
+    }
+
+    pub fn polling_num(&self, topic: &str, group: &str, queue_id: i32) -> i32 {
+        let key = KeyBuilder::build_polling_key(topic, group, queue_id);
+        self.table.lock().get(&key).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_held_requests_per_key() {
+        let table = PollingNumTable::default();
+        table.increment("topic", "group", 0);
+        table.increment("topic", "group", 0);
+        table.increment("topic", "group", -1);
+        assert_eq!(table.polling_num("topic", "group", 0), 2);
+        assert_eq!(table.polling_num("topic", "group", -1), 1);
+
+        table.decrement("topic", "group", 0);
+        table.decrement("topic", "group", 0);
+        table.decrement("topic", "group", 0);
+        assert_eq!(table.polling_num("topic", "group", 0), 0);
+        assert_eq!(table.polling_num("other", "group", 0), 0);
+    }
+}
@@ -0,0 +1,383 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::common::metrics::prometheus_text_encoder::PrometheusTextEncoder;
+use rocketmq_common::common::mix_all::MULTI_PATH_SPLITTER;
+use rocketmq_common::common::stats::Stats;
+use rocketmq_common::utils::util_all;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use rocketmq_store::log_file::MessageStore;
+use rocketmq_store::stats::broker_stats::BrokerStats;
+use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
+
+use crate::client::manager::consumer_manager::ConsumerManager;
+use crate::client::manager::producer_manager::ProducerManager;
+use crate::long_polling::long_polling_service::pull_request_hold_service::PullRequestHoldService;
+use crate::subscription::manager::subscription_group_manager::SubscriptionGroupManager;
+
+const LABEL_CLUSTER: &str = "cluster";
+const LABEL_BROKER: &str = "broker";
+const LABEL_BROKER_IP: &str = "brokerIP";
+const LABEL_TOPIC: &str = "topic";
+const LABEL_GROUP: &str = "group";
+
+/// Store runtime info keys exported as gauges, with the exporter metric name and help text.
+const RUNTIME_INFO_GAUGES: &[(&str, &str, &str)] = &[
+    (
+        "putTps",
+        "rocketmq_brokeruntime_put_tps",
+        "Messages put per second in the last 10 seconds",
+    ),
+    (
+        "getFoundTps",
+        "rocketmq_brokeruntime_getfound_tps",
+        "Found gets per second in the last 10 seconds",
+    ),
+    (
+        "getMissTps",
+        "rocketmq_brokeruntime_getmiss_tps",
+        "Missed gets per second in the last 10 seconds",
+    ),
+    (
+        "getTotalTps",
+        "rocketmq_brokeruntime_gettotal_tps",
+        "Gets per second in the last 10 seconds",
+    ),
+    (
+        "getTransferredTps",
+        "rocketmq_brokeruntime_gettransfered_tps",
+        "Transferred messages per second in the last 10 seconds",
+    ),
+    (
+        "putMessageTimesTotal",
+        "rocketmq_brokeruntime_putmessage_times_total",
+        "Total put message times",
+    ),
+    (
+        "putMessageSizeTotal",
+        "rocketmq_brokeruntime_put_message_size_total",
+        "Total size of put messages",
+    ),
+    (
+        "putMessageAverageSize",
+        "rocketmq_brokeruntime_put_message_average_size",
+        "Average size of put messages",
+    ),
+    (
+        "putMessageEntireTimeMax",
+        "rocketmq_brokeruntime_putmessage_entire_time_max",
+        "Max put message time in milliseconds",
+    ),
+    (
+        "getMessageEntireTimeMax",
+        "rocketmq_brokeruntime_getmessage_entire_time_max",
+        "Max get message time in milliseconds",
+    ),
+    (
+        "dispatchMaxBuffer",
+        "rocketmq_brokeruntime_dispatch_maxbuffer",
+        "Max dispatch buffer",
+    ),
+];
+
+/// Collects broker runtime, connection and per topic/group statistics for the Prometheus
+/// exporter, using the metric names of the RocketMQ exporter.
+pub(crate) struct BrokerMetricsManager<MS: MessageStore> {
+    broker_config: ArcMut<BrokerConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
+    message_store: ArcMut<MS>,
+    broker_stats: Option<Arc<BrokerStats<MS>>>,
+    broker_stats_manager: Arc<BrokerStatsManager>,
+    producer_manager: Arc<ProducerManager>,
+    consumer_manager: Arc<ConsumerManager>,
+    topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
+    subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+    pull_request_hold_service: Option<ArcMut<PullRequestHoldService<MS>>>,
+}
+
+impl<MS> BrokerMetricsManager<MS>
+where
+    MS: MessageStore + Send + Sync,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
+        message_store: ArcMut<MS>,
+        broker_stats: Option<Arc<BrokerStats<MS>>>,
+        broker_stats_manager: Arc<BrokerStatsManager>,
+        producer_manager: Arc<ProducerManager>,
+        consumer_manager: Arc<ConsumerManager>,
+        topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
+        subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+        pull_request_hold_service: Option<ArcMut<PullRequestHoldService<MS>>>,
+    ) -> Self {
+        BrokerMetricsManager {
+            broker_config,
+            message_store_config,
+            message_store,
+            broker_stats,
+            broker_stats_manager,
+            producer_manager,
+            consumer_manager,
+            topic_config_table,
+            subscription_group_manager,
+            pull_request_hold_service,
+        }
+    }
+
+    /// Renders all broker metrics in the Prometheus text format.
+    pub fn collect(&self) -> String {
+        let cluster = self
+            .broker_config
+            .broker_identity
+            .broker_cluster_name
+            .to_string();
+        let broker = self.broker_config.broker_identity.broker_name.to_string();
+        let broker_ip = format!(
+            "{}:{}",
+            self.broker_config.broker_ip1, self.broker_config.listen_port
+        );
+        let base = [
+            (LABEL_CLUSTER, cluster.as_str()),
+            (LABEL_BROKER, broker.as_str()),
+            (LABEL_BROKER_IP, broker_ip.as_str()),
+        ];
+        let mut encoder = PrometheusTextEncoder::new();
+        self.collect_runtime(&mut encoder, &base);
+        self.collect_connections(&mut encoder, &base);
+        self.collect_topic_group_stats(&mut encoder, &base);
+        encoder.encode()
+    }
+
+    fn collect_runtime(&self, encoder: &mut PrometheusTextEncoder, base: &[(&str, &str)]) {
+        let runtime_info = self.message_store.get_runtime_info();
+        for (key, name, help) in RUNTIME_INFO_GAUGES {
+            if let Some(value) = runtime_info.get(*key).and_then(|v| parse_first_number(v)) {
+                encoder.gauge(name, help, base, value);
+            }
+        }
+        if let Some(broker_stats) = self.broker_stats.as_ref() {
+            encoder.gauge(
+                "rocketmq_brokeruntime_msg_put_total_today_now",
+                "Messages put since this morning",
+                base,
+                broker_stats.get_msg_put_total_today_now() as f64,
+            );
+            encoder.gauge(
+                "rocketmq_brokeruntime_msg_gettotal_today_now",
+                "Messages got since this morning",
+                base,
+                broker_stats.get_msg_get_total_today_now() as f64,
+            );
+        }
+        encoder.gauge(
+            "rocketmq_brokeruntime_dispatch_behind_bytes",
+            "Commit log bytes not yet dispatched to consume queues",
+            base,
+            self.message_store.dispatch_behind_bytes() as f64,
+        );
+        encoder.gauge(
+            "rocketmq_brokeruntime_pagecache_lock_time_mills",
+            "Page cache lock time in milliseconds",
+            base,
+            self.message_store.lock_time_mills() as f64,
+        );
+        let commit_log_path = self.message_store_config.get_store_path_commit_log();
+        if let Some(disk_ratio) = commit_log_disk_ratio(commit_log_path.as_str()) {
+            encoder.gauge(
+                "rocketmq_brokeruntime_commitlog_disk_ratio",
+                "Used ratio of the fullest disk holding the commit log",
+                base,
+                disk_ratio,
+            );
+        }
+        let pull_hold_count = self
+            .pull_request_hold_service
+            .as_ref()
+            .map_or(0, |service| service.hold_request_count());
+        encoder.gauge(
+            "rocketmq_brokeruntime_pull_hold_request_count",
+            "Pull requests suspended by long polling",
+            base,
+            pull_hold_count as f64,
+        );
+        encoder.gauge(
+            "rocketmq_topic_number",
+            "Number of topics on the broker",
+            base,
+            self.topic_config_table.lock().len() as f64,
+        );
+        encoder.gauge(
+            "rocketmq_consumer_group_number",
+            "Number of subscription groups on the broker",
+            base,
+            self.subscription_group_manager.subscription_group_count() as f64,
+        );
+    }
+
+    fn collect_connections(&self, encoder: &mut PrometheusTextEncoder, base: &[(&str, &str)]) {
+        encoder.gauge(
+            "rocketmq_producer_connections",
+            "Number of producer connections",
+            base,
+            self.producer_manager.connection_count() as f64,
+        );
+        for (group, count) in self.consumer_manager.connection_count_by_group() {
+            let labels = with_labels(base, &[(LABEL_GROUP, group.as_str())]);
+            encoder.gauge(
+                "rocketmq_consumer_connections",
+                "Number of consumer connections of the group",
+                &labels,
+                count as f64,
+            );
+        }
+    }
+
+    fn collect_topic_group_stats(
+        &self,
+        encoder: &mut PrometheusTextEncoder,
+        base: &[(&str, &str)],
+    ) {
+        let stats_table = self.broker_stats_manager.get_stats_table();
+        let stats_table = stats_table.read();
+        if let Some(set) = stats_table.get(Stats::TOPIC_PUT_NUMS) {
+            for item in set.get_stats_items() {
+                let labels = with_labels(base, &[(LABEL_TOPIC, item.get_stats_key())]);
+                encoder.counter(
+                    "rocketmq_messages_in_total",
+                    "Messages put to the topic",
+                    &labels,
+                    item.get_value() as f64,
+                );
+                encoder.gauge(
+                    "rocketmq_producer_tps",
+                    "Messages put to the topic per second in the last minute",
+                    &labels,
+                    item.get_stats_data_in_minute().get_tps(),
+                );
+            }
+        }
+        if let Some(set) = stats_table.get(Stats::TOPIC_PUT_SIZE) {
+            for item in set.get_stats_items() {
+                let labels = with_labels(base, &[(LABEL_TOPIC, item.get_stats_key())]);
+                encoder.counter(
+                    "rocketmq_throughput_in_total",
+                    "Bytes put to the topic",
+                    &labels,
+                    item.get_value() as f64,
+                );
+            }
+        }
+        if let Some(set) = stats_table.get(Stats::GROUP_GET_NUMS) {
+            for item in set.get_stats_items() {
+                let (topic, group) = split_topic_group(item.get_stats_key());
+                let labels = with_labels(base, &[(LABEL_TOPIC, topic), (LABEL_GROUP, group)]);
+                encoder.counter(
+                    "rocketmq_messages_out_total",
+                    "Messages got by the group from the topic",
+                    &labels,
+                    item.get_value() as f64,
+                );
+                encoder.gauge(
+                    "rocketmq_consumer_tps",
+                    "Messages got by the group per second in the last minute",
+                    &labels,
+                    item.get_stats_data_in_minute().get_tps(),
+                );
+            }
+        }
+        if let Some(set) = stats_table.get(Stats::GROUP_GET_SIZE) {
+            for item in set.get_stats_items() {
+                let (topic, group) = split_topic_group(item.get_stats_key());
+                let labels = with_labels(base, &[(LABEL_TOPIC, topic), (LABEL_GROUP, group)]);
+                encoder.counter(
+                    "rocketmq_throughput_out_total",
+                    "Bytes got by the group from the topic",
+                    &labels,
+                    item.get_value() as f64,
+                );
+            }
+        }
+    }
+}
+
+fn with_labels<'a>(
+    base: &[(&'a str, &'a str)],
+    extra: &[(&'a str, &'a str)],
+) -> Vec<(&'a str, &'a str)> {
+    base.iter().chain(extra.iter()).copied().collect()
+}
+
+/// Stats keys of group statistics are `topic@group`.
+fn split_topic_group(stats_key: &str) -> (&str, &str) {
+    stats_key.split_once('@').unwrap_or((stats_key, ""))
+}
+
+/// Highest used ratio among the existing commit log directories, which are joined by
+/// `MULTI_PATH_SPLITTER` when several are configured.
+fn commit_log_disk_ratio(store_path: &str) -> Option<f64> {
+    store_path
+        .split(MULTI_PATH_SPLITTER.as_str())
+        .map(str::trim)
+        .filter(|path| !path.is_empty() && util_all::is_path_exists(path))
+        .map(util_all::get_disk_partition_space_used_percent)
+        .reduce(f64::max)
+}
+
+/// Runtime info values such as tps are reported as `"10s 60s 600s"`, the first one is used.
+fn parse_first_number(value: &str) -> Option<f64> {
+    value.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_first_number_takes_leading_value() {
+        assert_eq!(parse_first_number("12.5 10.0 8.0"), Some(12.5));
+        assert_eq!(parse_first_number("42"), Some(42.0));
+        assert_eq!(parse_first_number(""), None);
+        assert_eq!(parse_first_number("abc"), None);
+    }
+
+    #[test]
+    fn split_topic_group_uses_stats_key_separator() {
+        assert_eq!(split_topic_group("topic@group"), ("topic", "group"));
+        assert_eq!(split_topic_group("topic"), ("topic", ""));
+    }
+
+    #[test]
+    fn commit_log_disk_ratio_covers_every_existing_path() {
+        let dir = std::env::temp_dir();
+        let dir = dir.to_str().unwrap();
+        let missing = "/rocketmq-missing-commitlog-dir";
+        let ratio = util_all::get_disk_partition_space_used_percent(dir);
+
+        assert_eq!(commit_log_disk_ratio(dir), Some(ratio));
+        let multi_path = format!("{missing}{}{dir}", MULTI_PATH_SPLITTER.as_str());
+        assert_eq!(commit_log_disk_ratio(&multi_path), Some(ratio));
+        assert_eq!(commit_log_disk_ratio(missing), None);
+    }
+}
@@ -48,14 +48,14 @@ pub const TOPIC_GROUP_SEPARATOR: &str = "@";
 
 #[derive(Default, Clone)]
 pub(crate) struct ConsumerOffsetManager {
-    pub(crate) broker_config: Arc<BrokerConfig>,
+    pub(crate) broker_config: ArcMut<BrokerConfig>,
     consumer_offset_wrapper: ConsumerOffsetWrapper,
     message_store: Option<ArcMut<DefaultMessageStore>>,
 }
 
 impl ConsumerOffsetManager {
     pub fn new(
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         message_store: Option<ArcMut<DefaultMessageStore>>,
     ) -> Self {
         ConsumerOffsetManager {
@@ -93,6 +93,21 @@ impl ConsumerOffsetManager {
         }
     }
 
+    /// Removes the committed offsets of every topic consumed by `group`.
+    pub fn remove_offset(&self, group: &str) {
+        self.consumer_offset_wrapper
+            .offset_table
+            .write()
+            .retain(|topic_at_group, _| {
+                let arrays: Vec<&str> = topic_at_group.split(TOPIC_GROUP_SEPARATOR).collect();
+                let remove = arrays.len() == 2 && arrays[1] == group;
+                if remove {
+                    warn!("Clean group's offset, {}", topic_at_group);
+                }
+                !remove
+            });
+    }
+
     pub fn which_group_by_topic(&self, topic: &str) -> HashSet<CheetahString> {
         let read_guard = self.consumer_offset_wrapper.offset_table.read();
         let mut groups = HashSet::new();
@@ -192,6 +207,35 @@ impl ConsumerOffsetManager {
         -1
     }
 
+    /// Copies the committed offsets of `src_group` on `topic` to `dest_group`, replacing what
+    /// `dest_group` had committed on that topic.
+    pub fn clone_offset(
+        &self,
+        src_group: &CheetahString,
+        dest_group: &CheetahString,
+        topic: &CheetahString,
+    ) {
+        let src_key = format!("{}{}{}", topic, TOPIC_GROUP_SEPARATOR, src_group);
+        let mut offset_table = self.consumer_offset_wrapper.offset_table.write();
+        let Some(offsets) = offset_table.get(src_key.as_str()).cloned() else {
+            return;
+        };
+        let dest_key =
+            CheetahString::from_string(format!("{}{}{}", topic, TOPIC_GROUP_SEPARATOR, dest_group));
+        offset_table.insert(dest_key, offsets);
+        drop(offset_table);
+
+        let state_machine_version = if let Some(ref message_store) = self.message_store {
+            message_store.get_state_machine_version()
+        } else {
+            0
+        };
+        self.consumer_offset_wrapper
+            .data_version
+            .mut_from_ref()
+            .next_version_with(state_machine_version);
+    }
+
     pub fn which_topic_by_consumer(&self, group: &CheetahString) -> HashSet<CheetahString> {
         let read_guard = self.consumer_offset_wrapper.offset_table.read();
         let mut topics = HashSet::new();
@@ -259,6 +303,26 @@ impl ConsumerOffsetManager {
             .insert(queue_id, offset);
     }
 
+    /// Returns the last pulled offset of the queue, falling back to the committed offset.
+    pub fn query_pull_offset(
+        &self,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+    ) -> i64 {
+        let key = format!("{}{}{}", topic, TOPIC_GROUP_SEPARATOR, group);
+        if let Some(offset) = self
+            .consumer_offset_wrapper
+            .pull_offset_table
+            .read()
+            .get(key.as_str())
+            .and_then(|value| value.get(&queue_id))
+        {
+            return *offset;
+        }
+        self.query_offset(group, topic, queue_id)
+    }
+
     pub fn query_then_erase_reset_offset(
         &self,
         topic: &CheetahString,
@@ -435,3 +499,59 @@ impl<'de> Deserialize<'de> for ConsumerOffsetWrapper {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_offset_only_cleans_the_given_group() {
+        let manager = ConsumerOffsetManager::new(ArcMut::new(BrokerConfig::default()), None);
+        let client_host: SocketAddr = "127.0.0.1:10911".parse().unwrap();
+        let group_a = CheetahString::from_static_str("group_a");
+        let group_b = CheetahString::from_static_str("group_b");
+        let topic = CheetahString::from_static_str("topic");
+        manager.commit_offset(client_host, &group_a, &topic, 0, 10);
+        manager.commit_offset(client_host, &group_b, &topic, 0, 20);
+
+        manager.remove_offset(group_a.as_str());
+
+        assert_eq!(manager.query_offset(&group_a, &topic, 0), -1);
+        assert_eq!(manager.query_offset(&group_b, &topic, 0), 20);
+    }
+
+    #[test]
+    fn clone_offset_copies_every_queue_of_the_topic() {
+        let manager = ConsumerOffsetManager::new(ArcMut::new(BrokerConfig::default()), None);
+        let client_host: SocketAddr = "127.0.0.1:10911".parse().unwrap();
+        let src = CheetahString::from_static_str("src");
+        let dest = CheetahString::from_static_str("dest");
+        let topic = CheetahString::from_static_str("topic");
+        let other_topic = CheetahString::from_static_str("other_topic");
+        manager.commit_offset(client_host, &src, &topic, 0, 10);
+        manager.commit_offset(client_host, &src, &topic, 1, 20);
+        manager.commit_offset(client_host, &src, &other_topic, 0, 30);
+
+        manager.clone_offset(&src, &dest, &topic);
+
+        assert_eq!(manager.query_offset(&dest, &topic, 0), 10);
+        assert_eq!(manager.query_offset(&dest, &topic, 1), 20);
+        assert_eq!(manager.query_offset(&dest, &other_topic, 0), -1);
+        assert_eq!(manager.query_offset(&src, &topic, 0), 10);
+    }
+
+    #[test]
+    fn query_pull_offset_falls_back_to_committed_offset() {
+        let manager = ConsumerOffsetManager::new(ArcMut::new(BrokerConfig::default()), None);
+        let client_host: SocketAddr = "127.0.0.1:10911".parse().unwrap();
+        let group = CheetahString::from_static_str("group");
+        let topic = CheetahString::from_static_str("topic");
+        assert_eq!(manager.query_pull_offset(&group, &topic, 0), -1);
+
+        manager.commit_offset(client_host, &group, &topic, 0, 10);
+        assert_eq!(manager.query_pull_offset(&group, &topic, 0), 10);
+
+        manager.commit_pull_offset(client_host, &group, &topic, 0, 15);
+        assert_eq!(manager.query_pull_offset(&group, &topic, 0), 15);
+    }
+}
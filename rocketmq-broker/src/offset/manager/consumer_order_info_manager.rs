@@ -17,10 +17,10 @@
 
 use std::collections::HashMap;
 use std::ops::Deref;
-use std::sync::Arc;
 
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::config_manager::ConfigManager;
+use rocketmq_rust::ArcMut;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -29,7 +29,7 @@ use crate::offset::manager::consumer_order_info_lock_manager::ConsumerOrderInfoL
 
 #[derive(Default)]
 pub(crate) struct ConsumerOrderInfoManager {
-    pub(crate) broker_config: Arc<BrokerConfig>,
+    pub(crate) broker_config: ArcMut<BrokerConfig>,
     pub(crate) consumer_order_info_wrapper: parking_lot::Mutex<ConsumerOrderInfoWrapper>,
     pub(crate) consumer_order_info_lock_manager: Option<ConsumerOrderInfoLockManager>,
 }
@@ -17,32 +17,52 @@
 use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::Weak;
+use std::time::Duration;
 
 use cheetah_string::CheetahString;
 use dns_lookup::lookup_host;
+use futures::future::join_all;
 use rocketmq_common::common::broker::broker_config::BrokerIdentity;
 use rocketmq_common::common::config::TopicConfig;
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::mix_all;
 use rocketmq_common::utils::crc32_utils;
 use rocketmq_common::utils::serde_json_utils::SerdeJsonUtils;
 use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
 use rocketmq_remoting::clients::RemotingClient;
+use rocketmq_remoting::code::request_code::ControllerRequestCode;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::protocol::body::broker_body::register_broker_body::RegisterBrokerBody;
 use rocketmq_remoting::protocol::body::kv_table::KVTable;
 use rocketmq_remoting::protocol::body::response::lock_batch_response_body::LockBatchResponseBody;
+use rocketmq_remoting::protocol::body::sync_state_set::ElectMasterResponseBody;
+use rocketmq_remoting::protocol::body::sync_state_set::SyncStateSet;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigAndMappingSerializeWrapper;
+use rocketmq_remoting::protocol::header::broker::broker_heartbeat_request_header::BrokerHeartbeatRequestHeader;
 use rocketmq_remoting::protocol::header::client_request_header::GetRouteInfoRequestHeader;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetRequestHeader;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetResponseHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterResponseHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoResponseHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerResponseHeader;
 use rocketmq_remoting::protocol::header::lock_batch_mq_request_header::LockBatchMqRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::broker_request::UnRegisterBrokerRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::query_data_version_header::QueryDataVersionRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::query_data_version_header::QueryDataVersionResponseHeader;
 use rocketmq_remoting::protocol::header::namesrv::register_broker_header::RegisterBrokerRequestHeader;
 use rocketmq_remoting::protocol::header::namesrv::register_broker_header::RegisterBrokerResponseHeader;
+use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::DeleteTopicFromNamesrvRequestHeader;
 use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::RegisterTopicRequestHeader;
 use rocketmq_remoting::protocol::header::unlock_batch_mq_request_header::UnlockBatchMqRequestHeader;
 use rocketmq_remoting::protocol::namesrv::RegisterBrokerResult;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::route::route_data_view::QueueData;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use rocketmq_remoting::protocol::DataVersion;
 use rocketmq_remoting::protocol::RemotingDeserializable;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::remoting::RemotingService;
@@ -52,6 +72,7 @@ use rocketmq_remoting::rpc::rpc_client_impl::RpcClientImpl;
 use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
 use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_rust::ArcMut;
+use tokio::task::JoinHandle;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -163,6 +184,7 @@ impl BrokerOuterAPI {
         enable_acting_master: bool,
         compressed: bool,
         heartbeat_timeout_millis: Option<i64>,
+        zone_name: CheetahString,
         _broker_identity: BrokerIdentity,
         this: Weak<Self>,
     ) -> Vec<RegisterBrokerResult> {
@@ -176,7 +198,7 @@ impl BrokerOuterAPI {
                 cluster_name,
                 ha_server_addr,
                 enable_acting_master: Some(enable_acting_master),
-                compressed: false,
+                compressed,
                 heartbeat_timeout_millis,
                 body_crc32: 0,
             };
@@ -195,6 +217,7 @@ impl BrokerOuterAPI {
                 let cloned_body = body.clone();
                 let cloned_header = request_header.clone();
                 let addr = namesrv_addr.clone();
+                let zone_name = zone_name.clone();
                 let outer_api = this.clone();
                 let join_handle = tokio::spawn(async move {
                     if let Some(outer_api) = outer_api.upgrade() {
@@ -204,6 +227,7 @@ impl BrokerOuterAPI {
                                 oneway,
                                 timeout_mills,
                                 cloned_header,
+                                zone_name,
                                 cloned_body,
                             )
                             .await
@@ -211,25 +235,16 @@ impl BrokerOuterAPI {
                         None
                     }
                 });
-                /*let handle =
-                self.register_broker(addr, oneway, timeout_mills, cloned_header, cloned_body);*/
-                handle_vec.push(join_handle);
-            }
-            while let Some(handle) = handle_vec.pop() {
-                let result = tokio::join!(handle);
-                match result.0 {
-                    Ok(value) => {
-                        if let Some(v) = value {
-                            register_broker_result_list.push(v);
-                        } else {
-                            error!("Register broker to name remoting_server error");
-                        }
-                    }
-                    Err(e) => {
-                        error!("Register broker to name remoting_server error, error={}", e);
-                    }
-                }
+                handle_vec.push((namesrv_addr.clone(), join_handle));
             }
+            register_broker_result_list =
+                collect_register_results(handle_vec, oneway, timeout_mills).await;
+            info!(
+                "Registering current broker to name server completed. TotalNameServer={}, \
+                 SuccessfulRegistrations={}",
+                name_server_address_list.len(),
+                register_broker_result_list.len()
+            );
         }
 
         register_broker_result_list
@@ -241,6 +256,7 @@ impl BrokerOuterAPI {
         oneway: bool,
         timeout_mills: u64,
         request_header: RegisterBrokerRequestHeader,
+        zone_name: CheetahString,
         body: Vec<u8>,
     ) -> Option<RegisterBrokerResult> {
         debug!(
@@ -251,9 +267,12 @@ impl BrokerOuterAPI {
             request_header,
             body
         );
-        let request =
+        let mut request =
             RemotingCommand::create_request_command(RequestCode::RegisterBroker, request_header)
                 .set_body(body.clone());
+        // the name server keeps the zone of every broker so that zone aware clients can be
+        // routed to the brokers of their own zone
+        request.add_ext_field(mix_all::ZONE_NAME, zone_name);
         if oneway {
             self.remoting_client
                 .invoke_oneway(namesrv_addr, request, timeout_mills)
@@ -301,6 +320,113 @@ impl BrokerOuterAPI {
         }
     }
 
+    /// Query every name server for the topic config data version it holds for this broker.
+    ///
+    /// Each element of the returned list tells whether one name server needs a full
+    /// re-registration, name servers that do not answer in time are left out.
+    pub async fn need_register(
+        &self,
+        cluster_name: CheetahString,
+        broker_addr: CheetahString,
+        broker_name: CheetahString,
+        broker_id: u64,
+        data_version: DataVersion,
+        timeout_mills: u64,
+        _is_in_broker_container: bool,
+    ) -> Vec<bool> {
+        let name_server_address_list = self.remoting_client.get_available_name_srv_list();
+        let mut changed_list = Vec::with_capacity(name_server_address_list.len());
+        if name_server_address_list.is_empty() {
+            return changed_list;
+        }
+        let request_header =
+            QueryDataVersionRequestHeader::new(broker_name, broker_addr, cluster_name, broker_id);
+        let body = match data_version.encode() {
+            Ok(body) => body,
+            Err(e) => {
+                error!("encode DataVersion failed, error={}", e);
+                changed_list.push(true);
+                return changed_list;
+            }
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::QueryDataVersion, request_header)
+                .set_body(body);
+
+        let mut handle_vec = Vec::with_capacity(name_server_address_list.len());
+        for namesrv_addr in name_server_address_list.iter() {
+            let cloned_request = request.clone();
+            let addr = namesrv_addr.clone();
+            let client = self.remoting_client.clone();
+            let join_handle = tokio::spawn(async move {
+                client
+                    .invoke_async(Some(&addr), cloned_request, timeout_mills)
+                    .await
+            });
+            handle_vec.push((namesrv_addr.clone(), join_handle));
+        }
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout_mills);
+        for (namesrv_addr, mut handle) in handle_vec {
+            match tokio::time::timeout_at(deadline, &mut handle).await {
+                Ok(Ok(Ok(response))) => {
+                    if ResponseCode::from(response.code()) != ResponseCode::Success {
+                        warn!(
+                            "Query data version from name server {} failed, code={}",
+                            namesrv_addr,
+                            response.code()
+                        );
+                        continue;
+                    }
+                    let changed = Self::is_data_version_changed(&data_version, &response);
+                    debug!(
+                        "Query data version done, name server={}, changed={}",
+                        namesrv_addr, changed
+                    );
+                    changed_list.push(changed);
+                }
+                // A name server that could not be queried may have lost the registration,
+                // so it counts as changed and the broker registers again.
+                Ok(Ok(Err(e))) => {
+                    error!(
+                        "Query data version from name server {} error, error={}",
+                        namesrv_addr, e
+                    );
+                    changed_list.push(true);
+                }
+                Ok(Err(e)) => {
+                    error!(
+                        "Query data version from name server {} error, error={}",
+                        namesrv_addr, e
+                    );
+                    changed_list.push(true);
+                }
+                Err(_) => {
+                    handle.abort();
+                    warn!(
+                        "Query data version from name server {} timeout, timeout_mills={}",
+                        namesrv_addr, timeout_mills
+                    );
+                    changed_list.push(true);
+                }
+            }
+        }
+        changed_list
+    }
+
+    fn is_data_version_changed(data_version: &DataVersion, response: &RemotingCommand) -> bool {
+        let name_server_data_version = response
+            .get_body()
+            .and_then(|body| DataVersion::decode(body.as_ref()).ok());
+        let mut changed = name_server_data_version.as_ref() != Some(data_version);
+        if !changed {
+            changed = response
+                .decode_command_custom_header::<QueryDataVersionResponseHeader>()
+                .map(|header| header.changed())
+                .unwrap_or(false);
+        }
+        changed
+    }
+
     /// Register the topic route info of single topic to all name remoting_server nodes.
     /// This method is used to replace incremental broker registration feature.
     pub async fn register_single_topic_all(
@@ -328,6 +454,129 @@ impl BrokerOuterAPI {
         }
     }
 
+    /// Removes this broker from the route info of every name server, so clients stop
+    /// sending to it before it goes down.
+    pub async fn unregister_broker_all(
+        &self,
+        cluster_name: &CheetahString,
+        broker_addr: &CheetahString,
+        broker_name: &CheetahString,
+        broker_id: u64,
+    ) {
+        let name_server_address_list = self.remoting_client.get_name_server_address_list();
+        for namesrv_addr in name_server_address_list.iter() {
+            match self
+                .unregister_broker(
+                    namesrv_addr,
+                    cluster_name,
+                    broker_addr,
+                    broker_name,
+                    broker_id,
+                )
+                .await
+            {
+                Ok(_) => info!("unregisterBroker OK, NamesrvAddr: {}", namesrv_addr),
+                Err(e) => warn!("unregisterBroker Exception, {}, {}", namesrv_addr, e),
+            }
+        }
+    }
+
+    async fn unregister_broker(
+        &self,
+        namesrv_addr: &CheetahString,
+        cluster_name: &CheetahString,
+        broker_addr: &CheetahString,
+        broker_name: &CheetahString,
+        broker_id: u64,
+    ) -> Result<()> {
+        let request_header = UnRegisterBrokerRequestHeader {
+            broker_name: broker_name.clone(),
+            broker_addr: broker_addr.clone(),
+            cluster_name: cluster_name.clone(),
+            broker_id,
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::UnregisterBroker, request_header);
+        let response = self
+            .remoting_client
+            .invoke_async(Some(namesrv_addr), request, 3000)
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            Ok(())
+        } else {
+            Err(BrokerError::MQBrokerError(
+                response.code(),
+                response
+                    .remark()
+                    .cloned()
+                    .unwrap_or(CheetahString::empty())
+                    .to_string(),
+                namesrv_addr.to_string(),
+            ))
+        }
+    }
+
+    /// Removes the topic route of `cluster_name` from every name server at once, failures are
+    /// logged and left to the next registration to reconcile.
+    pub async fn delete_topic_in_namesrv_all(
+        &self,
+        cluster_name: &CheetahString,
+        topic: &CheetahString,
+    ) {
+        let name_server_address_list = self.remoting_client.get_name_server_address_list();
+        join_all(
+            name_server_address_list
+                .iter()
+                .map(|namesrv_addr| async move {
+                    match self
+                        .delete_topic_in_namesrv(namesrv_addr, cluster_name, topic)
+                        .await
+                    {
+                        Ok(_) => info!(
+                            "deleteTopicInNamesrv OK, NamesrvAddr: {}, topic: {}",
+                            namesrv_addr, topic
+                        ),
+                        Err(e) => warn!(
+                            "deleteTopicInNamesrv Exception, {}, topic: {}, {}",
+                            namesrv_addr, topic, e
+                        ),
+                    }
+                }),
+        )
+        .await;
+    }
+
+    async fn delete_topic_in_namesrv(
+        &self,
+        namesrv_addr: &CheetahString,
+        cluster_name: &CheetahString,
+        topic: &CheetahString,
+    ) -> Result<()> {
+        let request_header =
+            DeleteTopicFromNamesrvRequestHeader::new(topic.clone(), Some(cluster_name.clone()));
+        let request = RemotingCommand::create_request_command(
+            RequestCode::DeleteTopicInNamesrv,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(Some(namesrv_addr), request, 3000)
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            Ok(())
+        } else {
+            Err(BrokerError::MQBrokerError(
+                response.code(),
+                response
+                    .remark()
+                    .cloned()
+                    .unwrap_or(CheetahString::empty())
+                    .to_string(),
+                namesrv_addr.to_string(),
+            ))
+        }
+    }
+
     pub fn shutdown(&self) {}
 
     pub fn refresh_metadata(&self) {}
@@ -409,6 +658,200 @@ impl BrokerOuterAPI {
         }
     }
 
+    pub async fn get_all_topic_config(
+        &self,
+        addr: &CheetahString,
+    ) -> Result<TopicConfigAndMappingSerializeWrapper> {
+        let body = self
+            .get_all_config(addr, RequestCode::GetAllTopicConfig)
+            .await?;
+        TopicConfigAndMappingSerializeWrapper::decode(body.as_ref()).map_err(|e| {
+            BrokerError::MQBrokerError(
+                ResponseCode::SystemError.into(),
+                format!("decode topic config failed: {}", e),
+                addr.to_string(),
+            )
+        })
+    }
+
+    /// Returns the consumer offset table of the master, encoded as JSON.
+    pub async fn get_all_consumer_offset(&self, addr: &CheetahString) -> Result<String> {
+        self.get_all_config_as_string(addr, RequestCode::GetAllConsumerOffset)
+            .await
+    }
+
+    /// Returns the delay offset table of the master, encoded as JSON.
+    pub async fn get_all_delay_offset(&self, addr: &CheetahString) -> Result<String> {
+        self.get_all_config_as_string(addr, RequestCode::GetAllDelayOffset)
+            .await
+    }
+
+    /// Returns the subscription group table of the master, encoded as JSON.
+    pub async fn get_all_subscription_group_config(&self, addr: &CheetahString) -> Result<String> {
+        self.get_all_config_as_string(addr, RequestCode::GetAllSubscriptionGroupConfig)
+            .await
+    }
+
+    async fn get_all_config_as_string(
+        &self,
+        addr: &CheetahString,
+        request_code: RequestCode,
+    ) -> Result<String> {
+        let body = self.get_all_config(addr, request_code).await?;
+        Ok(String::from_utf8_lossy(body.as_ref()).into_owned())
+    }
+
+    async fn get_all_config(
+        &self,
+        addr: &CheetahString,
+        request_code: RequestCode,
+    ) -> Result<bytes::Bytes> {
+        let request = RemotingCommand::create_remoting_command(request_code);
+        let response = self
+            .remoting_client
+            .invoke_async(Some(addr), request, 3000)
+            .await?;
+        match (ResponseCode::from(response.code()), response.get_body()) {
+            (ResponseCode::Success, Some(body)) => Ok(body.clone()),
+            _ => Err(BrokerError::MQBrokerError(
+                response.code(),
+                response
+                    .remark()
+                    .cloned()
+                    .unwrap_or(CheetahString::empty())
+                    .to_string(),
+                addr.to_string(),
+            )),
+        }
+    }
+
+    /// Registers this replica to the controller, returning the current master of the group
+    /// if there is an alive one.
+    pub async fn register_broker_to_controller(
+        &self,
+        controller_addr: &CheetahString,
+        request_header: RegisterBrokerToControllerRequestHeader,
+    ) -> Result<RegisterBrokerToControllerResponseHeader> {
+        let request = RemotingCommand::create_request_command(
+            ControllerRequestCode::ControllerRegisterBroker,
+            request_header,
+        );
+        let response = self
+            .invoke_controller(controller_addr, request, &[ResponseCode::Success])
+            .await?;
+        Ok(response.decode_command_custom_header::<RegisterBrokerToControllerResponseHeader>()?)
+    }
+
+    /// Asks the controller for an election, an alive master is kept as is.
+    pub async fn elect_master(
+        &self,
+        controller_addr: &CheetahString,
+        request_header: ElectMasterRequestHeader,
+    ) -> Result<(ElectMasterResponseHeader, Option<ElectMasterResponseBody>)> {
+        let request = RemotingCommand::create_request_command(
+            ControllerRequestCode::ControllerElectMaster,
+            request_header,
+        );
+        let response = self
+            .invoke_controller(
+                controller_addr,
+                request,
+                &[
+                    ResponseCode::Success,
+                    ResponseCode::ControllerMasterStillExist,
+                ],
+            )
+            .await?;
+        let response_header =
+            response.decode_command_custom_header::<ElectMasterResponseHeader>()?;
+        let response_body = response
+            .get_body()
+            .and_then(|body| ElectMasterResponseBody::decode(body.as_ref()).ok());
+        Ok((response_header, response_body))
+    }
+
+    pub async fn get_replica_info(
+        &self,
+        controller_addr: &CheetahString,
+        broker_name: CheetahString,
+    ) -> Result<(
+        GetReplicaInfoResponseHeader,
+        Option<ElectMasterResponseBody>,
+    )> {
+        let request = RemotingCommand::create_request_command(
+            ControllerRequestCode::ControllerGetReplicaInfo,
+            GetReplicaInfoRequestHeader { broker_name },
+        );
+        let response = self
+            .invoke_controller(controller_addr, request, &[ResponseCode::Success])
+            .await?;
+        let response_header =
+            response.decode_command_custom_header::<GetReplicaInfoResponseHeader>()?;
+        let response_body = response
+            .get_body()
+            .and_then(|body| ElectMasterResponseBody::decode(body.as_ref()).ok());
+        Ok((response_header, response_body))
+    }
+
+    /// Replaces the sync state set of the group, returning the new sync state set epoch.
+    pub async fn alter_sync_state_set(
+        &self,
+        controller_addr: &CheetahString,
+        request_header: AlterSyncStateSetRequestHeader,
+        sync_state_set: &SyncStateSet,
+    ) -> Result<i32> {
+        let body = sync_state_set.encode()?;
+        let request = RemotingCommand::create_request_command(
+            ControllerRequestCode::ControllerAlterSyncStateSet,
+            request_header,
+        )
+        .set_body(body);
+        let response = self
+            .invoke_controller(controller_addr, request, &[ResponseCode::Success])
+            .await?;
+        let response_header =
+            response.decode_command_custom_header::<AlterSyncStateSetResponseHeader>()?;
+        Ok(response_header
+            .new_sync_state_set_epoch
+            .unwrap_or(sync_state_set.sync_state_set_epoch + 1))
+    }
+
+    pub async fn send_heartbeat_to_controller(
+        &self,
+        controller_addr: &CheetahString,
+        request_header: BrokerHeartbeatRequestHeader,
+    ) {
+        let request =
+            RemotingCommand::create_request_command(RequestCode::BrokerHeartbeat, request_header);
+        self.remoting_client
+            .invoke_oneway(controller_addr, request, 3000)
+            .await;
+    }
+
+    async fn invoke_controller(
+        &self,
+        controller_addr: &CheetahString,
+        request: RemotingCommand,
+        expected_codes: &[ResponseCode],
+    ) -> Result<RemotingCommand> {
+        let response = self
+            .remoting_client
+            .invoke_async(Some(controller_addr), request, 3000)
+            .await?;
+        if expected_codes.contains(&ResponseCode::from(response.code())) {
+            return Ok(response);
+        }
+        Err(BrokerError::MQBrokerError(
+            response.code(),
+            response
+                .remark()
+                .cloned()
+                .unwrap_or(CheetahString::empty())
+                .to_string(),
+            controller_addr.to_string(),
+        ))
+    }
+
     pub async fn get_topic_route_info_from_name_server(
         &self,
         topic: &CheetahString,
@@ -487,6 +930,48 @@ fn dns_lookup_address_by_domain(domain: &str) -> Vec<CheetahString> {
     address_list
 }
 
+/// Waits for the registrations to the name servers, which run concurrently. The whole
+/// registration waits at most `timeout_mills`, name servers that have not answered by then are
+/// skipped.
+async fn collect_register_results<T>(
+    handle_vec: Vec<(CheetahString, JoinHandle<Option<T>>)>,
+    oneway: bool,
+    timeout_mills: u64,
+) -> Vec<T> {
+    let mut results = Vec::with_capacity(handle_vec.len());
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout_mills);
+    for (namesrv_addr, mut handle) in handle_vec {
+        match tokio::time::timeout_at(deadline, &mut handle).await {
+            Ok(Ok(Some(value))) => {
+                results.push(value);
+            }
+            Ok(Ok(None)) => {
+                if !oneway {
+                    error!(
+                        "Register broker to name remoting_server error, namesrv_addr={}",
+                        namesrv_addr
+                    );
+                }
+            }
+            Ok(Err(e)) => {
+                error!(
+                    "Register broker to name remoting_server error, namesrv_addr={}, error={}",
+                    namesrv_addr, e
+                );
+            }
+            Err(_) => {
+                handle.abort();
+                warn!(
+                    "Register broker to name remoting_server timeout, namesrv_addr={}, \
+                     timeout_mills={}",
+                    namesrv_addr, timeout_mills
+                );
+            }
+        }
+    }
+    results
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -511,4 +996,73 @@ mod tests {
         let addresses = dns_lookup_address_by_domain(domain);
         assert!(addresses.is_empty());
     }
+
+    #[test]
+    fn data_version_changed_when_name_server_differs() {
+        let data_version = DataVersion::new();
+        let response = RemotingCommand::create_response_command()
+            .set_command_custom_header(QueryDataVersionResponseHeader::new(false));
+        assert!(BrokerOuterAPI::is_data_version_changed(
+            &data_version,
+            &response
+        ));
+
+        let mut other = DataVersion::new();
+        other.next_version();
+        let response = response.set_body(other.encode().unwrap());
+        assert!(BrokerOuterAPI::is_data_version_changed(
+            &data_version,
+            &response
+        ));
+    }
+
+    #[test]
+    fn data_version_unchanged_when_name_server_matches() {
+        let data_version = DataVersion::new();
+        let response = RemotingCommand::create_response_command()
+            .set_command_custom_header(QueryDataVersionResponseHeader::new(false))
+            .set_body(data_version.encode().unwrap());
+        assert!(!BrokerOuterAPI::is_data_version_changed(
+            &data_version,
+            &response
+        ));
+
+        let mut response = RemotingCommand::create_response_command()
+            .set_command_custom_header(QueryDataVersionResponseHeader::new(true))
+            .set_body(data_version.encode().unwrap());
+        response.make_custom_header_to_net();
+        assert!(BrokerOuterAPI::is_data_version_changed(
+            &data_version,
+            &response
+        ));
+    }
+
+    #[tokio::test]
+    async fn registration_waits_for_name_servers_at_most_the_timeout() {
+        let handle_vec = vec![
+            (
+                CheetahString::from_static_str("127.0.0.1:9876"),
+                tokio::spawn(async { Some(1) }),
+            ),
+            (
+                CheetahString::from_static_str("127.0.0.2:9876"),
+                tokio::spawn(async {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                    Some(2)
+                }),
+            ),
+            (
+                CheetahString::from_static_str("127.0.0.3:9876"),
+                tokio::spawn(async { None }),
+            ),
+            (
+                CheetahString::from_static_str("127.0.0.4:9876"),
+                tokio::spawn(async { Some(4) }),
+            ),
+        ];
+        let start = std::time::Instant::now();
+        let results = collect_register_results(handle_vec, false, 200).await;
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(results, vec![1, 4]);
+    }
 }
@@ -14,7 +14,13 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::sync::Arc;
+
+use rocketmq_common::common::mq_version::RocketMqVersion;
+use rocketmq_remoting::code::request_code::ControllerRequestCode;
 use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
@@ -25,6 +31,9 @@ use rocketmq_store::log_file::MessageStore;
 use tracing::info;
 
 use self::client_manage_processor::ClientManageProcessor;
+use crate::controller::replicas_manager::ReplicasManager;
+use crate::latency::broker_fast_failure::BrokerFastFailure;
+use crate::latency::request_rate_limiter::RequestRateLimiter;
 use crate::processor::ack_message_processor::AckMessageProcessor;
 use crate::processor::admin_broker_processor::AdminBrokerProcessor;
 use crate::processor::change_invisible_time_processor::ChangeInvisibleTimeProcessor;
@@ -53,6 +62,7 @@ pub(crate) mod peek_message_processor;
 pub(crate) mod polling_info_processor;
 pub(crate) mod pop_inflight_message_counter;
 pub(crate) mod pop_message_processor;
+pub(crate) mod pop_revive_service;
 pub(crate) mod pull_message_processor;
 pub(crate) mod pull_message_result_handler;
 pub(crate) mod query_assignment_processor;
@@ -63,12 +73,12 @@ pub(crate) mod send_message_processor;
 pub struct BrokerRequestProcessor<MS, TS> {
     pub(crate) send_message_processor: ArcMut<SendMessageProcessor<MS, TS>>,
     pub(crate) pull_message_processor: ArcMut<PullMessageProcessor<MS>>,
-    pub(crate) peek_message_processor: ArcMut<PeekMessageProcessor>,
-    pub(crate) pop_message_processor: ArcMut<PopMessageProcessor>,
-    pub(crate) ack_message_processor: ArcMut<AckMessageProcessor>,
-    pub(crate) change_invisible_time_processor: ArcMut<ChangeInvisibleTimeProcessor>,
-    pub(crate) notification_processor: ArcMut<NotificationProcessor>,
-    pub(crate) polling_info_processor: ArcMut<PollingInfoProcessor>,
+    pub(crate) peek_message_processor: ArcMut<PeekMessageProcessor<MS>>,
+    pub(crate) pop_message_processor: ArcMut<PopMessageProcessor<MS>>,
+    pub(crate) ack_message_processor: ArcMut<AckMessageProcessor<MS>>,
+    pub(crate) change_invisible_time_processor: ArcMut<ChangeInvisibleTimeProcessor<MS>>,
+    pub(crate) notification_processor: ArcMut<NotificationProcessor<MS>>,
+    pub(crate) polling_info_processor: ArcMut<PollingInfoProcessor<MS>>,
     pub(crate) reply_message_processor: ArcMut<ReplyMessageProcessor<MS, TS>>,
     pub(crate) query_message_processor: ArcMut<QueryMessageProcessor<MS>>,
     pub(crate) client_manage_processor: ArcMut<ClientManageProcessor<MS>>,
@@ -76,6 +86,11 @@ pub struct BrokerRequestProcessor<MS, TS> {
     pub(crate) query_assignment_processor: ArcMut<QueryAssignmentProcessor>,
     pub(crate) end_transaction_processor: ArcMut<EndTransactionProcessor<TS, MS>>,
     pub(crate) admin_broker_processor: ArcMut<AdminBrokerProcessor>,
+    pub(crate) broker_fast_failure: Arc<BrokerFastFailure>,
+    pub(crate) request_rate_limiter: Arc<RequestRateLimiter>,
+    pub(crate) replicas_manager: Option<ReplicasManager>,
+    /// Set on the copy serving the fast (VIP) port, which only takes the light requests.
+    pub(crate) fast_channel: bool,
 }
 impl<MS, TS> Clone for BrokerRequestProcessor<MS, TS> {
     fn clone(&self) -> Self {
@@ -95,10 +110,54 @@ impl<MS, TS> Clone for BrokerRequestProcessor<MS, TS> {
             query_assignment_processor: self.query_assignment_processor.clone(),
             query_message_processor: self.query_message_processor.clone(),
             end_transaction_processor: self.end_transaction_processor.clone(),
+            broker_fast_failure: self.broker_fast_failure.clone(),
+            request_rate_limiter: self.request_rate_limiter.clone(),
+            replicas_manager: self.replicas_manager.clone(),
+            fast_channel: self.fast_channel,
+        }
+    }
+}
+
+impl<MS, TS> BrokerRequestProcessor<MS, TS> {
+    /// A copy of the processors for the fast port, refusing pull style requests so heavy
+    /// consumer traffic can not delay sends going through the VIP channel.
+    pub(crate) fn fast_channel(&self) -> Self {
+        Self {
+            fast_channel: true,
+            ..self.clone()
         }
     }
 }
 
+/// Requests served on the main port only, like Java which does not register them on the
+/// fast remoting server.
+fn is_main_channel_only(request_code: RequestCode) -> bool {
+    matches!(
+        request_code,
+        RequestCode::PullMessage
+            | RequestCode::LitePullMessage
+            | RequestCode::PeekMessage
+            | RequestCode::PopMessage
+            | RequestCode::Notification
+            | RequestCode::PollingInfo
+    )
+}
+
+/// Oldest client version able to use the feature behind `request_code`, `None` when every
+/// version may send it.
+fn min_client_version(request_code: RequestCode) -> Option<RocketMqVersion> {
+    match request_code {
+        RequestCode::PopMessage
+        | RequestCode::AckMessage
+        | RequestCode::BatchAckMessage
+        | RequestCode::ChangeMessageInvisibleTime
+        | RequestCode::Notification
+        | RequestCode::PollingInfo => Some(RocketMqVersion::POP_MIN_VERSION),
+        RequestCode::SendBatchMessage => Some(RocketMqVersion::BATCH_MIN_VERSION),
+        _ => None,
+    }
+}
+
 impl<MS, TS> RequestProcessor for BrokerRequestProcessor<MS, TS>
 where
     MS: MessageStore + Send + Sync + 'static,
@@ -110,16 +169,72 @@ where
         ctx: ConnectionHandlerContext,
         request: RemotingCommand,
     ) -> Result<Option<RemotingCommand>> {
+        if request.code() == ControllerRequestCode::NotifyBrokerRoleChanged.to_i32() {
+            let response = match self.replicas_manager.as_ref() {
+                Some(replicas_manager) => replicas_manager.notify_broker_role_changed(&request),
+                None => RemotingCommand::create_response_command_with_code_remark(
+                    RemotingSysResponseCode::RequestCodeNotSupported,
+                    "The broker is not in controller mode",
+                ),
+            };
+            return Ok(Some(response));
+        }
         let request_code = RequestCode::from(request.code());
         info!("process_request: {:?}", request_code);
+        if self.fast_channel && is_main_channel_only(request_code) {
+            return Ok(Some(
+                RemotingCommand::create_response_command_with_code_remark(
+                    RemotingSysResponseCode::RequestCodeNotSupported,
+                    format!(
+                        " request type {} not supported on the fast channel",
+                        request_code.to_i32()
+                    ),
+                ),
+            ));
+        }
+        if let Some(required) = min_client_version(request_code) {
+            if !RocketMqVersion::is_supported(request.version(), required) {
+                return Ok(Some(
+                    RemotingCommand::create_response_command_with_code_remark(
+                        ResponseCode::VersionNotSupported,
+                        format!(
+                            "the client version {} does not support request type {}, upgrade to \
+                             {} or later",
+                            RocketMqVersion::version_desc(request.version()),
+                            request_code.to_i32(),
+                            required
+                        ),
+                    ),
+                ));
+            }
+        }
         let result = match request_code {
             RequestCode::SendMessage
             | RequestCode::SendMessageV2
             | RequestCode::SendBatchMessage
             | RequestCode::ConsumerSendMsgBack => {
+                if self.send_message_processor.reject_request() {
+                    return Ok(Some(
+                        RemotingCommand::create_response_command_with_code_remark(
+                            RemotingSysResponseCode::SystemBusy,
+                            "[REJECTREQUEST]system busy, start flow control for a while",
+                        ),
+                    ));
+                }
+                if request_code != RequestCode::ConsumerSendMsgBack {
+                    if let Some(response) =
+                        self.request_rate_limiter.check_send(request_code, &request)
+                    {
+                        return Ok(Some(response));
+                    }
+                }
+                let send_permit = match self.broker_fast_failure.send_queue().acquire().await {
+                    Ok(permit) => permit,
+                    Err(response) => return Ok(Some(response)),
+                };
                 return self
                     .send_message_processor
-                    .process_request(channel, ctx, request_code, request)
+                    .process_request(channel, ctx, request_code, request, send_permit)
                     .await
                     .map_err(Into::into);
             }
@@ -138,6 +253,13 @@ where
                     .await
             }
             RequestCode::PullMessage | RequestCode::LitePullMessage => {
+                if let Some(response) = self.request_rate_limiter.check_pull(&request) {
+                    return Ok(Some(response));
+                }
+                let _permit = match self.broker_fast_failure.pull_queue().acquire().await {
+                    Ok(permit) => permit,
+                    Err(response) => return Ok(Some(response)),
+                };
                 self.pull_message_processor
                     .process_request(channel, ctx, request_code, request)
                     .await
@@ -150,7 +272,45 @@ where
                     .await
             }
 
-            RequestCode::QueryMessage | RequestCode::ViewMessageById => {
+            RequestCode::PeekMessage => {
+                self.peek_message_processor
+                    .process_request(channel, ctx, request_code, request)
+                    .await
+            }
+
+            RequestCode::PopMessage => {
+                self.pop_message_processor
+                    .process_request(channel, ctx, request_code, request)
+                    .await
+            }
+
+            RequestCode::AckMessage => {
+                self.ack_message_processor
+                    .process_request(channel, ctx, request_code, request)
+                    .await
+            }
+
+            RequestCode::ChangeMessageInvisibleTime => {
+                self.change_invisible_time_processor
+                    .process_request(channel, ctx, request_code, request)
+                    .await
+            }
+
+            RequestCode::Notification => {
+                self.notification_processor
+                    .process_request(channel, ctx, request_code, request)
+                    .await
+            }
+
+            RequestCode::PollingInfo => {
+                self.polling_info_processor
+                    .process_request(channel, ctx, request_code, request)
+                    .await
+            }
+
+            RequestCode::QueryMessage
+            | RequestCode::ViewMessageById
+            | RequestCode::QueryMessageByOffsetRange => {
                 self.query_message_processor
                     .process_request(channel, ctx, request_code, request)
                     .await
@@ -177,3 +337,31 @@ where
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pull_style_requests_stay_on_the_main_channel() {
+        assert!(is_main_channel_only(RequestCode::PullMessage));
+        assert!(is_main_channel_only(RequestCode::LitePullMessage));
+        assert!(is_main_channel_only(RequestCode::PopMessage));
+        assert!(!is_main_channel_only(RequestCode::SendMessageV2));
+        assert!(!is_main_channel_only(RequestCode::HeartBeat));
+        assert!(!is_main_channel_only(RequestCode::AckMessage));
+    }
+
+    #[test]
+    fn pop_requests_require_a_pop_capable_client() {
+        assert_eq!(
+            min_client_version(RequestCode::PopMessage),
+            Some(RocketMqVersion::POP_MIN_VERSION)
+        );
+        assert_eq!(
+            min_client_version(RequestCode::Notification),
+            Some(RocketMqVersion::POP_MIN_VERSION)
+        );
+        assert_eq!(min_client_version(RequestCode::PullMessage), None);
+    }
+}
@@ -14,22 +14,120 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::sync::Arc;
+
 use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::ack_message_request_header::AckMessageRequestHeader;
+use rocketmq_remoting::protocol::header::extra_info_util::ExtraInfoUtil;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::log_file::MessageStore;
+use tracing::debug;
+
+use crate::processor::pop_revive_service::PopReviveService;
+use crate::topic::manager::topic_config_manager::TopicConfigManager;
+
+/// Acks popped messages so they are not revived once their invisible time expires.
+pub struct AckMessageProcessor<MS> {
+    topic_config_manager: Arc<TopicConfigManager>,
+    pop_revive_service: ArcMut<PopReviveService<MS>>,
+}
 
-#[derive(Default)]
-pub struct AckMessageProcessor {}
+impl<MS> AckMessageProcessor<MS> {
+    pub fn new(
+        topic_config_manager: Arc<TopicConfigManager>,
+        pop_revive_service: ArcMut<PopReviveService<MS>>,
+    ) -> Self {
+        Self {
+            topic_config_manager,
+            pop_revive_service,
+        }
+    }
+}
 
-impl AckMessageProcessor {
+impl<MS> AckMessageProcessor<MS>
+where
+    MS: MessageStore + Send + Sync + 'static,
+{
     pub async fn process_request(
         &mut self,
         _channel: Channel,
         _ctx: ConnectionHandlerContext,
         _request_code: RequestCode,
-        _request: RemotingCommand,
-    ) -> crate::Result<Option<RemotingCommand>> {
-        unimplemented!("AckMessageProcessor process_request")
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header = match request.decode_command_custom_header::<AckMessageRequestHeader>()
+        {
+            Ok(header) => header,
+            Err(e) => {
+                return Some(
+                    response
+                        .set_code(ResponseCode::SystemError)
+                        .set_remark(e.to_string()),
+                );
+            }
+        };
+        let Some(topic_config) = self
+            .topic_config_manager
+            .select_topic_config(&request_header.topic)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::TopicNotExist)
+                    .set_remark(format!(
+                        "topic[{}] not exist, apply first please!",
+                        request_header.topic
+                    )),
+            );
+        };
+        if request_header.queue_id >= topic_config.read_queue_nums as i32
+            || request_header.queue_id < 0
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::MessageIllegal)
+                    .set_remark(format!(
+                        "queueId[{}] is illegal, topic:[{}] topicConfig.readQueueNums:[{}]",
+                        request_header.queue_id, request_header.topic, topic_config.read_queue_nums
+                    )),
+            );
+        }
+        let pop_time = match ExtraInfoUtil::split(&request_header.extra_info)
+            .and_then(|extra_info| ExtraInfoUtil::get_pop_time(&extra_info))
+        {
+            Ok(pop_time) => pop_time,
+            Err(e) => {
+                return Some(
+                    response
+                        .set_code(ResponseCode::MessageIllegal)
+                        .set_remark(e.to_string()),
+                );
+            }
+        };
+        // like Java, acking a message which is no longer in flight succeeds
+        if !self
+            .pop_revive_service
+            .ack(
+                &request_header.consumer_group,
+                &request_header.topic,
+                request_header.queue_id,
+                request_header.offset,
+                pop_time,
+            )
+            .await
+        {
+            debug!(
+                "ack of a message not in flight, group={} topic={} queueId={} offset={}",
+                request_header.consumer_group,
+                request_header.topic,
+                request_header.queue_id,
+                request_header.offset
+            );
+        }
+        Some(response)
     }
 }
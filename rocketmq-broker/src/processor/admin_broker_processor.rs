@@ -32,6 +32,7 @@ use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
 use tracing::warn;
 
 use crate::client::manager::consumer_manager::ConsumerManager;
+use crate::client::manager::producer_manager::ProducerManager;
 use crate::client::rebalance::rebalance_lock_manager::RebalanceLockManager;
 use crate::offset::manager::consumer_offset_manager::ConsumerOffsetManager;
 use crate::out_api::broker_outer_api::BrokerOuterAPI;
@@ -39,9 +40,12 @@ use crate::processor::admin_broker_processor::batch_mq_handler::BatchMqHandler;
 use crate::processor::admin_broker_processor::broker_config_request_handler::BrokerConfigRequestHandler;
 use crate::processor::admin_broker_processor::consumer_request_handler::ConsumerRequestHandler;
 use crate::processor::admin_broker_processor::offset_request_handler::OffsetRequestHandler;
+use crate::processor::admin_broker_processor::producer_request_handler::ProducerRequestHandler;
+use crate::processor::admin_broker_processor::subscription_group_handler::SubscriptionGroupHandler;
 use crate::processor::admin_broker_processor::topic_request_handler::TopicRequestHandler;
 use crate::processor::pop_inflight_message_counter::PopInflightMessageCounter;
 use crate::schedule::schedule_message_service::ScheduleMessageService;
+use crate::subscription::manager::subscription_group_manager::SubscriptionGroupManager;
 use crate::topic::manager::topic_config_manager::TopicConfigManager;
 use crate::topic::manager::topic_queue_mapping_manager::TopicQueueMappingManager;
 
@@ -49,28 +53,34 @@ mod batch_mq_handler;
 mod broker_config_request_handler;
 mod consumer_request_handler;
 mod offset_request_handler;
+mod producer_request_handler;
+mod subscription_group_handler;
 mod topic_request_handler;
 
 pub struct AdminBrokerProcessor {
     topic_request_handler: TopicRequestHandler,
     broker_config_request_handler: BrokerConfigRequestHandler,
     consumer_request_handler: ConsumerRequestHandler,
+    producer_request_handler: ProducerRequestHandler,
     offset_request_handler: OffsetRequestHandler,
     batch_mq_handler: BatchMqHandler,
+    subscription_group_handler: SubscriptionGroupHandler,
 }
 
 impl AdminBrokerProcessor {
     pub fn new(
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         server_config: Arc<ServerConfig>,
-        message_store_config: Arc<MessageStoreConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
         topic_config_manager: TopicConfigManager,
         consumer_offset_manager: ConsumerOffsetManager,
+        subscription_group_manager: Arc<SubscriptionGroupManager<DefaultMessageStore>>,
         topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
         default_message_store: ArcMut<DefaultMessageStore>,
         schedule_message_service: ScheduleMessageService,
         broker_stats: Option<Arc<BrokerStats<DefaultMessageStore>>>,
         consume_manager: Arc<ConsumerManager>,
+        producer_manager: Arc<ProducerManager>,
         broker_out_api: Arc<BrokerOuterAPI>,
         broker_stats_manager: Arc<BrokerStatsManager>,
         rebalance_lock_manager: Arc<RebalanceLockManager>,
@@ -82,12 +92,14 @@ impl AdminBrokerProcessor {
             message_store_config,
             topic_config_manager,
             consumer_offset_manager,
+            subscription_group_manager,
             topic_queue_mapping_manager,
             default_message_store,
             pop_inflight_message_counter: Arc::new(PopInflightMessageCounter),
             schedule_message_service,
             broker_stats,
             consume_manager,
+            producer_manager,
             broker_out_api,
             broker_stats_manager,
             rebalance_lock_manager,
@@ -96,14 +108,18 @@ impl AdminBrokerProcessor {
         let topic_request_handler = TopicRequestHandler::new(inner.clone());
         let broker_config_request_handler = BrokerConfigRequestHandler::new(inner.clone());
         let consumer_request_handler = ConsumerRequestHandler::new(inner.clone());
+        let producer_request_handler = ProducerRequestHandler::new(inner.clone());
         let offset_request_handler = OffsetRequestHandler::new(inner.clone());
         let batch_mq_handler = BatchMqHandler::new(inner.clone());
+        let subscription_group_handler = SubscriptionGroupHandler::new(inner.clone());
         AdminBrokerProcessor {
             topic_request_handler,
             broker_config_request_handler,
             consumer_request_handler,
+            producer_request_handler,
             offset_request_handler,
             batch_mq_handler,
+            subscription_group_handler,
         }
     }
 }
@@ -122,6 +138,11 @@ impl AdminBrokerProcessor {
                     .update_and_create_topic(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::UpdateAndCreateStaticTopic => {
+                self.topic_request_handler
+                    .update_and_create_static_topic(channel, ctx, request_code, request)
+                    .await
+            }
             RequestCode::UpdateAndCreateTopicList => {
                 self.topic_request_handler
                     .update_and_create_topic_list(channel, ctx, request_code, request)
@@ -132,6 +153,11 @@ impl AdminBrokerProcessor {
                     .delete_topic(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::CleanUnusedTopic => {
+                self.topic_request_handler
+                    .clean_unused_topic(channel, ctx, request_code, request)
+                    .await
+            }
             RequestCode::GetAllTopicConfig => {
                 self.topic_request_handler
                     .get_all_topic_config(channel, ctx, request_code, request)
@@ -157,16 +183,51 @@ impl AdminBrokerProcessor {
                     .get_topic_stats_info(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::UpdateAndCreateSubscriptionGroup => {
+                self.subscription_group_handler
+                    .update_and_create_subscription_group(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::GetAllSubscriptionGroupConfig => {
+                self.subscription_group_handler
+                    .get_all_subscription_group(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::DeleteSubscriptionGroup => {
+                self.subscription_group_handler
+                    .delete_subscription_group(channel, ctx, request_code, request)
+                    .await
+            }
             RequestCode::GetConsumerConnectionList => {
                 self.consumer_request_handler
                     .get_consumer_connection_list(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::GetProducerConnectionList => {
+                self.producer_request_handler
+                    .get_producer_connection_list(channel, ctx, request_code, request)
+                    .await
+            }
             RequestCode::GetConsumeStats => {
                 self.consumer_request_handler
                     .get_consume_stats(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::GetPopStats => {
+                self.consumer_request_handler
+                    .get_pop_stats(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::GetConsumerRunningInfo => {
+                self.consumer_request_handler
+                    .get_consumer_running_info(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::ConsumeMessageDirectly => {
+                self.consumer_request_handler
+                    .consume_message_directly(channel, ctx, request_code, request)
+                    .await
+            }
             RequestCode::GetAllConsumerOffset => {
                 self.consumer_request_handler
                     .get_all_consumer_offset(channel, ctx, request_code, request)
@@ -202,6 +263,31 @@ impl AdminBrokerProcessor {
                     .get_min_offset(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::SearchOffsetByTimestamp => {
+                self.offset_request_handler
+                    .search_offset_by_timestamp(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::GetEarliestMsgStoreTime => {
+                self.offset_request_handler
+                    .get_earliest_msg_storetime(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::CloneGroupOffset => {
+                self.offset_request_handler
+                    .clone_group_offset(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::GetAllDelayOffset => {
+                self.offset_request_handler
+                    .get_all_delay_offset(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::InvokeBrokerToResetOffset => {
+                self.offset_request_handler
+                    .reset_offset(channel, ctx, request_code, request)
+                    .await
+            }
 
             RequestCode::LockBatchMq => {
                 self.batch_mq_handler
@@ -233,17 +319,19 @@ fn get_unknown_cmd_response(request_code: RequestCode) -> RemotingCommand {
 
 #[derive(Clone)]
 struct Inner {
-    broker_config: Arc<BrokerConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     server_config: Arc<ServerConfig>,
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     topic_config_manager: TopicConfigManager,
     consumer_offset_manager: ConsumerOffsetManager,
+    subscription_group_manager: Arc<SubscriptionGroupManager<DefaultMessageStore>>,
     topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
     default_message_store: ArcMut<DefaultMessageStore>,
     pop_inflight_message_counter: Arc<PopInflightMessageCounter>,
     schedule_message_service: ScheduleMessageService,
     broker_stats: Option<Arc<BrokerStats<DefaultMessageStore>>>,
     consume_manager: Arc<ConsumerManager>,
+    producer_manager: Arc<ProducerManager>,
     broker_out_api: Arc<BrokerOuterAPI>,
     broker_stats_manager: Arc<BrokerStatsManager>,
     rebalance_lock_manager: Arc<RebalanceLockManager>,
@@ -16,18 +16,27 @@
  */
 
 use std::collections::HashMap;
+use std::path::Path;
 
 use cheetah_string::CheetahString;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::mix_all;
 use rocketmq_common::common::mq_version::RocketMqVersion;
+use rocketmq_common::FileUtils;
+use rocketmq_common::ParseConfigFile;
 use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::body::kv_table::KVTable;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
 use rocketmq_store::log_file::MessageStore;
 use sysinfo::Disks;
+use tracing::error;
+use tracing::info;
 
+use crate::broker_path_config_helper::get_broker_config_path;
 use crate::processor::admin_broker_processor::Inner;
 
 #[derive(Clone)]
@@ -43,12 +52,59 @@ impl BrokerConfigRequestHandler {
 impl BrokerConfigRequestHandler {
     pub async fn update_broker_config(
         &mut self,
-        _channel: Channel,
+        channel: Channel,
         _ctx: ConnectionHandlerContext,
         _request_code: RequestCode,
-        _request: RemotingCommand,
+        request: RemotingCommand,
     ) -> Option<RemotingCommand> {
-        todo!()
+        let Some(body) = request.get_body() else {
+            return Some(RemotingCommand::create_response_command());
+        };
+        let properties = match std::str::from_utf8(body.as_ref())
+            .ok()
+            .and_then(mix_all::string_to_properties)
+        {
+            Some(properties) => properties,
+            None => {
+                return Some(
+                    RemotingCommand::create_response_command_with_code(ResponseCode::SystemError)
+                        .set_remark("string2Properties error"),
+                );
+            }
+        };
+        info!("updateBrokerConfig called by {}", channel.remote_address());
+
+        // validate on copies first so that an invalid property does not leave a half updated
+        // config, the runtime updatable values are atomics so the shared configs are updated
+        // in place while other threads read them
+        if let Err((code, remark)) = apply_properties(
+            &self.inner.broker_config.as_ref().clone(),
+            &self.inner.message_store_config.as_ref().clone(),
+            &properties,
+        ) {
+            return Some(
+                RemotingCommand::create_response_command_with_code(code).set_remark(remark),
+            );
+        }
+        let _ = apply_properties(
+            self.inner.broker_config.as_ref(),
+            self.inner.message_store_config.as_ref(),
+            &properties,
+        );
+        info!("updateBrokerConfig, new config: [{:?}]", properties);
+        self.persist();
+
+        if properties.contains_key("brokerPermission") {
+            // the next registration sends the new permission to the name servers
+            let state_machine_version =
+                self.inner.default_message_store.get_state_machine_version();
+            self.inner
+                .topic_config_manager
+                .data_version()
+                .mut_from_ref()
+                .next_version_with(state_machine_version);
+        }
+        Some(RemotingCommand::create_response_command())
     }
 
     pub async fn get_broker_config(
@@ -61,28 +117,38 @@ impl BrokerConfigRequestHandler {
         let mut response = RemotingCommand::create_response_command();
         // broker config => broker config
         // default message store config => message store config
-        let broker_config = self.inner.broker_config.clone();
-        let message_store_config = self
-            .inner
-            .default_message_store
-            .message_store_config()
-            .clone();
-        let broker_config_properties = broker_config.get_properties();
-        let message_store_config_properties = message_store_config.get_properties();
-        let combine_map = broker_config_properties
-            .iter()
-            .chain(message_store_config_properties.iter())
-            .collect::<HashMap<_, _>>();
-        let mut body = String::new();
-        for (key, value) in combine_map {
-            body.push_str(&format!("{}:{}\n", key, value));
-        }
+        let body = mix_all::properties_to_string(&self.all_properties());
         if !body.is_empty() {
             response.set_body_mut_ref(body);
         }
         Some(response)
     }
 
+    fn all_properties(&self) -> HashMap<CheetahString, CheetahString> {
+        let mut properties = self.inner.broker_config.get_properties();
+        properties.extend(self.inner.message_store_config.get_properties());
+        properties
+    }
+
+    /// Writes the configs back to the file the broker was started from, in its format.
+    fn persist(&self) {
+        let config_path = get_broker_config_path();
+        let result = config_content(
+            Path::new(config_path.as_str()),
+            self.inner.broker_config.as_ref(),
+            self.inner.message_store_config.as_ref(),
+        )
+        .and_then(|content| {
+            Ok(FileUtils::string_to_file(
+                content.as_str(),
+                config_path.as_str(),
+            )?)
+        });
+        if let Err(err) = result {
+            error!("persist broker config to {} failed: {}", config_path, err);
+        }
+    }
+
     pub async fn get_broker_runtime_info(
         &mut self,
         _channel: Channel,
@@ -115,7 +181,7 @@ impl BrokerConfigRequestHandler {
             Some(broker_stats) => broker_stats
                 .get_msg_put_total_yesterday_morning()
                 .to_string(),
-            None => String::from("No broker stats available msgPutTotalYesterdayMorning"),
+            None => String::from("0"),
         };
         runtime_info.insert(
             "msgPutTotalYesterdayMorning".to_string(),
@@ -124,7 +190,7 @@ impl BrokerConfigRequestHandler {
 
         let msg_put_total_today_morning = match &self.inner.broker_stats {
             Some(broker_stats) => broker_stats.get_msg_put_total_today_morning().to_string(),
-            None => String::from("No broker stats available msgPutTotalTodayMorning"),
+            None => String::from("0"),
         };
         runtime_info.insert(
             "msgPutTotalTodayMorning".to_string(),
@@ -133,7 +199,7 @@ impl BrokerConfigRequestHandler {
 
         let msg_put_total_today_now = match &self.inner.broker_stats {
             Some(broker_stats) => broker_stats.get_msg_put_total_today_now().to_string(),
-            None => String::from("No broker stats available msgPutTotalTodayNow"),
+            None => String::from("0"),
         };
         runtime_info.insert("msgPutTotalTodayNow".to_string(), msg_put_total_today_now);
 
@@ -141,7 +207,7 @@ impl BrokerConfigRequestHandler {
             Some(broker_stats) => broker_stats
                 .get_msg_get_total_yesterday_morning()
                 .to_string(),
-            None => String::from("No broker stats available msgGetTotalYesterdayMorning"),
+            None => String::from("0"),
         };
         runtime_info.insert(
             "msgGetTotalYesterdayMorning".to_string(),
@@ -150,7 +216,7 @@ impl BrokerConfigRequestHandler {
 
         let msg_get_total_today_morning = match &self.inner.broker_stats {
             Some(broker_stats) => broker_stats.get_msg_get_total_today_morning().to_string(),
-            None => String::from("No broker stats available msgGetTotalTodayMorning"),
+            None => String::from("0"),
         };
         runtime_info.insert(
             "msgGetTotalTodayMorning".to_string(),
@@ -159,7 +225,7 @@ impl BrokerConfigRequestHandler {
 
         let msg_get_total_today_now = match &self.inner.broker_stats {
             Some(broker_stats) => broker_stats.get_msg_get_total_today_now().to_string(),
-            None => String::from("No broker stats available msgGetTotalTodayNow"),
+            None => String::from("0"),
         };
         runtime_info.insert("msgGetTotalTodayNow".to_string(), msg_get_total_today_now);
         runtime_info.insert(
@@ -292,3 +358,151 @@ impl BrokerConfigRequestHandler {
         true
     }
 }
+
+/// Renders the configs in the format of `config_file`: Java style properties for `.conf` and
+/// `.properties` files, otherwise the structure `ParseConfigFile::parse_config_file` reads.
+fn config_content(
+    config_file: &Path,
+    broker_config: &BrokerConfig,
+    message_store_config: &MessageStoreConfig,
+) -> anyhow::Result<String> {
+    if ParseConfigFile::is_properties_file(config_file) {
+        let mut properties = broker_config.get_properties();
+        properties.extend(message_store_config.get_properties());
+        return Ok(mix_all::properties_to_string(&properties));
+    }
+    ParseConfigFile::config_file_content(
+        config_file,
+        vec![
+            serde_json::to_value(broker_config)?,
+            serde_json::to_value(message_store_config)?,
+        ],
+    )
+}
+
+/// Apply the runtime updatable properties, the error is the response code and remark.
+fn apply_properties(
+    broker_config: &BrokerConfig,
+    message_store_config: &MessageStoreConfig,
+    properties: &HashMap<CheetahString, CheetahString>,
+) -> Result<(), (ResponseCode, String)> {
+    for (key, value) in properties.iter() {
+        let updated = if broker_config
+            .update_property(key, value)
+            .map_err(|err| (ResponseCode::SystemError, err))?
+        {
+            true
+        } else {
+            message_store_config
+                .update_property(key, value)
+                .map_err(|err| (ResponseCode::SystemError, err))?
+        };
+        if !updated {
+            return Err((
+                ResponseCode::NoPermission,
+                format!(
+                    "Can not update config {} at runtime, it requires a broker restart.",
+                    key
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_properties_updates_runtime_configs() {
+        let broker_config = BrokerConfig::default();
+        let message_store_config = MessageStoreConfig::default();
+        let properties = mix_all::string_to_properties(
+            "brokerPermission=4\nlongPollingEnable=false\nflushIntervalCommitLog=1000",
+        )
+        .unwrap();
+        assert!(apply_properties(&broker_config, &message_store_config, &properties).is_ok());
+        assert_eq!(broker_config.broker_permission.get(), 4);
+        assert!(!broker_config.long_polling_enable.get());
+        assert_eq!(message_store_config.flush_interval_commit_log.get(), 1000);
+    }
+
+    #[test]
+    fn apply_properties_rejects_unknown_or_invalid_values() {
+        let broker_config = BrokerConfig::default();
+        let message_store_config = MessageStoreConfig::default();
+        let properties = mix_all::string_to_properties("brokerName=other").unwrap();
+        let (code, _) =
+            apply_properties(&broker_config, &message_store_config, &properties).unwrap_err();
+        assert_eq!(code, ResponseCode::NoPermission);
+
+        // only read when the broker starts
+        for property in ["sendMessageThreadPoolNums=16", "maxMessageSize=1024"] {
+            let properties = mix_all::string_to_properties(property).unwrap();
+            let (code, remark) =
+                apply_properties(&broker_config, &message_store_config, &properties).unwrap_err();
+            assert_eq!(code, ResponseCode::NoPermission);
+            assert!(remark.contains("restart"));
+        }
+
+        let properties = mix_all::string_to_properties("brokerPermission=rw").unwrap();
+        let (code, remark) =
+            apply_properties(&broker_config, &message_store_config, &properties).unwrap_err();
+        assert_eq!(code, ResponseCode::SystemError);
+        assert!(remark.contains("brokerPermission"));
+    }
+
+    #[test]
+    fn config_content_keeps_the_format_of_the_startup_file() {
+        let dir = std::env::temp_dir().join(format!("broker-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut broker_config = BrokerConfig::default();
+        broker_config.broker_identity.broker_name = "broker-x".into();
+        let message_store_config = MessageStoreConfig::default();
+        let properties =
+            mix_all::string_to_properties("brokerPermission=4\nflushIntervalCommitLog=1000")
+                .unwrap();
+        apply_properties(&broker_config, &message_store_config, &properties).unwrap();
+
+        let toml_file = dir.join("broker.toml");
+        let content = config_content(&toml_file, &broker_config, &message_store_config).unwrap();
+        std::fs::write(&toml_file, content).unwrap();
+        let parsed = ParseConfigFile::parse_config_file::<BrokerConfig>(toml_file.clone()).unwrap();
+        assert_eq!(parsed.broker_permission.get(), 4);
+        assert_eq!(parsed.broker_identity.broker_name, "broker-x");
+        let parsed = ParseConfigFile::parse_config_file::<MessageStoreConfig>(toml_file).unwrap();
+        assert_eq!(parsed.flush_interval_commit_log.get(), 1000);
+
+        let properties_file = dir.join("broker.conf");
+        let content =
+            config_content(&properties_file, &broker_config, &message_store_config).unwrap();
+        let parsed = mix_all::string_to_properties(content.as_str()).unwrap();
+        assert_eq!(parsed.get("brokerPermission").unwrap(), "4");
+        assert_eq!(parsed.get("flushIntervalCommitLog").unwrap(), "1000");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn apply_properties_validates_rate_limit_overrides() {
+        let broker_config = BrokerConfig::default();
+        let message_store_config = MessageStoreConfig::default();
+        let properties = mix_all::string_to_properties(
+            "topicSendRateLimit=100\ntopicSendRateLimitOverrides=TopicA:10;TopicB:0",
+        )
+        .unwrap();
+        assert!(apply_properties(&broker_config, &message_store_config, &properties).is_ok());
+        assert_eq!(broker_config.topic_send_rate_limit.get(), 100);
+        assert_eq!(
+            *broker_config.topic_send_rate_limit_overrides.get(),
+            "TopicA:10;TopicB:0"
+        );
+
+        let properties =
+            mix_all::string_to_properties("groupPullRateLimitOverrides=GroupA").unwrap();
+        let (code, remark) =
+            apply_properties(&broker_config, &message_store_config, &properties).unwrap_err();
+        assert_eq!(code, ResponseCode::SystemError);
+        assert!(remark.contains("groupPullRateLimitOverrides"));
+    }
+}
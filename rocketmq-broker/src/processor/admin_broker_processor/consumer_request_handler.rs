@@ -17,8 +17,12 @@
 
 use std::collections::HashSet;
 
+use cheetah_string::CheetahString;
 use rocketmq_common::common::config_manager::ConfigManager;
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::mq_version::RocketMqVersion;
+use rocketmq_common::common::pop_ack_constants::PopAckConstants;
+use rocketmq_common::MessageDecoder;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
@@ -26,16 +30,25 @@ use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
 use rocketmq_remoting::protocol::admin::offset_wrapper::OffsetWrapper;
 use rocketmq_remoting::protocol::body::connection::Connection;
 use rocketmq_remoting::protocol::body::consumer_connection::ConsumerConnection;
+use rocketmq_remoting::protocol::body::pop_stats::PopStats;
+use rocketmq_remoting::protocol::header::consume_message_directly_result_request_header::ConsumeMessageDirectlyResultRequestHeader;
 use rocketmq_remoting::protocol::header::get_consume_stats_request_header::GetConsumeStatsRequestHeader;
 use rocketmq_remoting::protocol::header::get_consumer_connection_list_request_header::GetConsumerConnectionListRequestHeader;
+use rocketmq_remoting::protocol::header::get_consumer_running_info_request_header::GetConsumerRunningInfoRequestHeader;
+use rocketmq_remoting::protocol::header::get_pop_stats_request_header::GetPopStatsRequestHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::RemotingSerializable;
+use rocketmq_remoting::remoting_error::RemotingError;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
 use rocketmq_store::log_file::MessageStore;
 use tracing::warn;
 
+use crate::broker_error::BrokerError;
+use crate::client::net::broker_to_client::Broker2Client;
 use crate::processor::admin_broker_processor::Inner;
 
+const CALL_CONSUMER_TIMEOUT_MILLIS: u64 = 10_000;
+
 #[derive(Clone)]
 pub(super) struct ConsumerRequestHandler {
     inner: Inner,
@@ -69,20 +82,21 @@ impl ConsumerRequestHandler {
                 body_data.set_consume_from_where(consumer_group_info.get_consume_from_where());
                 body_data.set_consume_type(consumer_group_info.get_consume_type());
                 body_data.set_message_model(consumer_group_info.get_message_model());
-                let subscription_table =
-                    consumer_group_info.get_subscription_table().read().clone();
-                body_data
-                    .get_subscription_table()
-                    .extend(subscription_table);
+                body_data.set_subscription_table(
+                    consumer_group_info.get_subscription_table().read().clone(),
+                );
 
+                let mut connection_set = HashSet::new();
                 for (channel, info) in consumer_group_info.get_channel_info_table().read().iter() {
                     let mut connection = Connection::new();
                     connection.set_client_id(info.client_id().clone());
                     connection.set_language(info.language());
                     connection.set_version(info.version());
                     connection.set_client_addr(channel.remote_address().to_string().into());
-                    body_data.get_connection_set().insert(connection);
+                    connection.set_last_update_timestamp(info.last_update_timestamp() as i64);
+                    connection_set.insert(connection);
                 }
+                body_data.set_connection_set(connection_set);
                 let body = body_data
                     .encode()
                     .expect("consumer connection list encode failed");
@@ -100,6 +114,88 @@ impl ConsumerRequestHandler {
         }
     }
 
+    pub async fn get_pop_stats(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header =
+            match request.decode_command_custom_header::<GetPopStatsRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
+        let Some(topic_config) = self
+            .inner
+            .topic_config_manager
+            .select_topic_config(&request_header.topic)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::TopicNotExist)
+                    .set_remark(format!("topic[{}] not exist", request_header.topic)),
+            );
+        };
+        let group = &request_header.consumer_group;
+        let topic = &request_header.topic;
+        let store = &self.inner.default_message_store;
+
+        let mut pop_stats = PopStats {
+            consumer_group: group.clone(),
+            topic: topic.clone(),
+            ..Default::default()
+        };
+        for queue_id in 0..topic_config.read_queue_nums as i32 {
+            let max_offset = store.get_max_offset_in_queue(topic, queue_id);
+            let mut consumer_offset = self
+                .inner
+                .consumer_offset_manager
+                .query_offset(group, topic, queue_id);
+            if consumer_offset < 0 {
+                consumer_offset = store.get_min_offset_in_queue(topic, queue_id);
+            }
+            pop_stats
+                .backlog
+                .insert(queue_id, (max_offset - consumer_offset).max(0));
+            pop_stats.inflight_message_num.insert(
+                queue_id,
+                self.inner
+                    .pop_inflight_message_counter
+                    .get_group_pop_in_flight_message_num(topic, group, queue_id),
+            );
+        }
+
+        let revive_topic = CheetahString::from_string(PopAckConstants::build_cluster_revive_topic(
+            self.inner
+                .broker_config
+                .broker_identity
+                .broker_cluster_name
+                .as_str(),
+        ));
+        let revive_group = CheetahString::from_static_str(PopAckConstants::REVIVE_GROUP);
+        for queue_id in 0..self.inner.broker_config.revive_queue_num as i32 {
+            let max_offset = store.get_max_offset_in_queue(&revive_topic, queue_id);
+            let revive_offset = self
+                .inner
+                .consumer_offset_manager
+                .query_offset(&revive_group, &revive_topic, queue_id)
+                .max(0);
+            pop_stats
+                .revive_behind_messages
+                .insert(queue_id, (max_offset - revive_offset).max(0));
+        }
+        let body = pop_stats.encode().expect("pop stats encode failed");
+        Some(response.set_body(body))
+    }
+
     pub async fn get_consume_stats(
         &mut self,
         _channel: Channel,
@@ -108,9 +204,17 @@ impl ConsumerRequestHandler {
         request: RemotingCommand,
     ) -> Option<RemotingCommand> {
         let mut response = RemotingCommand::create_response_command();
-        let request_header = request
-            .decode_command_custom_header::<GetConsumeStatsRequestHeader>()
-            .unwrap();
+        let request_header =
+            match request.decode_command_custom_header::<GetConsumeStatsRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
         let mut consume_stats = ConsumeStats::new();
         let mut topics = HashSet::new();
         if request_header.get_topic().is_empty() {
@@ -183,7 +287,7 @@ impl ConsumerRequestHandler {
                     consumer_offset = 0;
                 }
 
-                let pull_offset = self.inner.consumer_offset_manager.query_offset(
+                let pull_offset = self.inner.consumer_offset_manager.query_pull_offset(
                     request_header.get_consumer_group(),
                     topic,
                     i as i32,
@@ -204,7 +308,9 @@ impl ConsumerRequestHandler {
                     }
                 }
 
-                consume_stats.get_offset_table().insert(mq, offset_wrapper);
+                consume_stats
+                    .get_offset_table_mut()
+                    .insert(mq, offset_wrapper);
             }
 
             let consume_tps = self
@@ -220,6 +326,153 @@ impl ConsumerRequestHandler {
         Some(response)
     }
 
+    pub async fn get_consumer_running_info(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header =
+            match request.decode_command_custom_header::<GetConsumerRunningInfoRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        RemotingCommand::create_response_command()
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
+        self.call_consumer(
+            request_code,
+            request,
+            &request_header.consumer_group,
+            &request_header.client_id,
+        )
+        .await
+    }
+
+    pub async fn consume_message_directly(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        request_code: RequestCode,
+        mut request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header = match request
+            .decode_command_custom_header::<ConsumeMessageDirectlyResultRequestHeader>()
+        {
+            Ok(header) => header,
+            Err(e) => {
+                return Some(
+                    RemotingCommand::create_response_command()
+                        .set_code(ResponseCode::SystemError)
+                        .set_remark(e.to_string()),
+                );
+            }
+        };
+        request.add_ext_field("brokerName", self.inner.broker_config.broker_name.clone());
+
+        if let Some(message_id) = request_header
+            .msg_id
+            .as_ref()
+            .and_then(|msg_id| MessageDecoder::try_decode_message_id(msg_id))
+        {
+            if let Some(result) = self
+                .inner
+                .default_message_store
+                .select_one_message_by_offset(message_id.offset)
+                .await
+            {
+                if let Some(body) = result.get_bytes() {
+                    request.set_body_mut_ref(body);
+                }
+            }
+        }
+
+        let client_id = request_header.client_id.unwrap_or_default();
+        self.call_consumer(
+            request_code,
+            request,
+            &request_header.consumer_group,
+            &client_id,
+        )
+        .await
+    }
+
+    /// Forwards an admin request to the consumer client `client_id` of `consumer_group` and
+    /// relays the client's response back to the caller.
+    async fn call_consumer(
+        &mut self,
+        request_code: RequestCode,
+        request: RemotingCommand,
+        consumer_group: &CheetahString,
+        client_id: &CheetahString,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let Some(client_channel_info) = self
+            .inner
+            .consume_manager
+            .find_channel(consumer_group, client_id)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "The Consumer <{}> <{}> not online",
+                        consumer_group, client_id
+                    )),
+            );
+        };
+        if client_channel_info.version() < RocketMqVersion::V318Snapshot.into() {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "The Consumer <{}> Version <{}> too low to finish, please upgrade it to \
+                         V3_1_8_SNAPSHOT",
+                        client_id,
+                        RocketMqVersion::version_desc(client_channel_info.version())
+                    )),
+            );
+        }
+
+        let mut new_request = RemotingCommand::create_remoting_command(request_code);
+        if let Some(ext_fields) = request.get_ext_fields() {
+            new_request = new_request.set_ext_fields(ext_fields.clone());
+        }
+        if let Some(body) = request.get_body() {
+            new_request = new_request.set_body(body.clone());
+        }
+        let mut channel = client_channel_info.channel().clone();
+        match Broker2Client
+            .call_client(&mut channel, new_request, CALL_CONSUMER_TIMEOUT_MILLIS)
+            .await
+        {
+            Ok(response) => Some(response),
+            Err(BrokerError::BrokerRemotingError(RemotingError::RemotingTimeoutError(
+                addr,
+                timeout,
+            ))) => Some(
+                response
+                    .set_code(ResponseCode::ConsumeMsgTimeout)
+                    .set_remark(format!(
+                        "consumer <{}> <{}> Timeout: wait response from {} timeout in {}ms",
+                        consumer_group, client_id, addr, timeout
+                    )),
+            ),
+            Err(e) => Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "invoke consumer <{}> <{}> Exception: {}",
+                        consumer_group, client_id, e
+                    )),
+            ),
+        }
+    }
+
     pub async fn get_all_consumer_offset(
         &mut self,
         _channel: Channel,
@@ -14,22 +14,39 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use rocketmq_common::common::boundary_type::BoundaryType;
+use rocketmq_common::common::config_manager::ConfigManager;
+use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::body::reset_offset_body::ResetOffsetBody;
+use rocketmq_remoting::protocol::header::clone_group_offset_request_header::CloneGroupOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::get_earliest_msg_storetime_request_header::GetEarliestMsgStoretimeRequestHeader;
+use rocketmq_remoting::protocol::header::get_earliest_msg_storetime_response_header::GetEarliestMsgStoretimeResponseHeader;
 use rocketmq_remoting::protocol::header::get_max_offset_request_header::GetMaxOffsetRequestHeader;
 use rocketmq_remoting::protocol::header::get_max_offset_response_header::GetMaxOffsetResponseHeader;
 use rocketmq_remoting::protocol::header::get_min_offset_request_header::GetMinOffsetRequestHeader;
 use rocketmq_remoting::protocol::header::get_min_offset_response_header::GetMinOffsetResponseHeader;
 use rocketmq_remoting::protocol::header::message_operation_header::TopicRequestHeaderTrait;
+use rocketmq_remoting::protocol::header::reset_offset_request_header::ResetOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::search_offset_request_header::SearchOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::search_offset_response_header::SearchOffsetResponseHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_context::TopicQueueMappingContext;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_utils::TopicQueueMappingUtils;
+use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::rpc::rpc_client::RpcClient;
 use rocketmq_remoting::rpc::rpc_request::RpcRequest;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
 use rocketmq_store::log_file::MessageStore;
+use tracing::error;
+use tracing::warn;
 
+use crate::client::net::broker_to_client::Broker2Client;
 use crate::processor::admin_broker_processor::Inner;
 
 #[derive(Clone)]
@@ -77,6 +94,159 @@ impl OffsetRequestHandler {
         ))
     }
 
+    pub async fn search_offset_by_timestamp(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header = match request
+            .decode_command_custom_header::<SearchOffsetRequestHeader>()
+        {
+            Ok(header) => header,
+            Err(e) => {
+                return Some(
+                    RemotingCommand::create_response_command_with_code(ResponseCode::SystemError)
+                        .set_remark(e.to_string()),
+                );
+            }
+        };
+        let boundary_type = request_header
+            .boundary_type
+            .as_ref()
+            .and_then(|name| BoundaryType::get_type(name))
+            .unwrap_or(BoundaryType::Lower);
+        let offset = self
+            .inner
+            .default_message_store
+            .get_offset_in_queue_by_time_with_boundary(
+                &request_header.topic,
+                request_header.queue_id,
+                request_header.timestamp,
+                boundary_type,
+            );
+        Some(RemotingCommand::create_response_command_with_header(
+            SearchOffsetResponseHeader { offset },
+        ))
+    }
+
+    pub async fn get_earliest_msg_storetime(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header = match request
+            .decode_command_custom_header::<GetEarliestMsgStoretimeRequestHeader>()
+        {
+            Ok(header) => header,
+            Err(e) => {
+                return Some(
+                    RemotingCommand::create_response_command_with_code(ResponseCode::SystemError)
+                        .set_remark(e.to_string()),
+                );
+            }
+        };
+        let timestamp = self
+            .inner
+            .default_message_store
+            .get_earliest_message_time_in_queue(&request_header.topic, request_header.queue_id);
+        Some(RemotingCommand::create_response_command_with_header(
+            GetEarliestMsgStoretimeResponseHeader { timestamp },
+        ))
+    }
+
+    pub async fn clone_group_offset(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header =
+            match request.decode_command_custom_header::<CloneGroupOffsetRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
+        let src_group = &request_header.src_group;
+        let topics = match request_header.topic.as_ref() {
+            Some(topic) if !topic.is_empty() => HashSet::from([topic.clone()]),
+            _ => self
+                .inner
+                .consumer_offset_manager
+                .which_topic_by_consumer(src_group),
+        };
+        for topic in topics.iter() {
+            if self
+                .inner
+                .topic_config_manager
+                .select_topic_config(topic)
+                .is_none()
+            {
+                warn!("[cloneGroupOffset], topic config not exist, {}", topic);
+                continue;
+            }
+            if !request_header.offline
+                && self
+                    .inner
+                    .consume_manager
+                    .find_subscription_data(src_group, topic)
+                    .is_none()
+                && self
+                    .inner
+                    .consume_manager
+                    .find_subscription_data_count(src_group)
+                    > 0
+            {
+                warn!(
+                    "AdminBrokerProcessor#cloneGroupOffset: topic does not exist in consumer \
+                     group's subscription, topic={}, consumer group={}",
+                    topic, src_group
+                );
+                continue;
+            }
+            self.inner.consumer_offset_manager.clone_offset(
+                src_group,
+                &request_header.dest_group,
+                topic,
+            );
+        }
+        Some(response)
+    }
+
+    pub async fn get_all_delay_offset(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        _request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let content = self.inner.schedule_message_service.encode_pretty(false);
+        if !content.is_empty() {
+            Some(response.set_body(content))
+        } else {
+            error!(
+                "No delay offset in this broker, client: {}",
+                channel.remote_address()
+            );
+            Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark("No delay offset in this broker"),
+            )
+        }
+    }
+
     pub async fn get_min_offset(
         &mut self,
         _channel: Channel,
@@ -258,4 +428,133 @@ impl OffsetRequestHandler {
             },
         ))
     }
+
+    pub async fn reset_offset(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header =
+            match request.decode_command_custom_header::<ResetOffsetRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
+        let topic = &request_header.topic;
+        let group = &request_header.group;
+        let timestamp = request_header.timestamp;
+        let Some(topic_config) = self.inner.topic_config_manager.select_topic_config(topic) else {
+            error!(
+                "[reset-offset] reset offset failed, no topic in this broker. topic={}",
+                topic
+            );
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "[reset-offset] reset offset failed, no topic in this broker. topic={}",
+                        topic
+                    )),
+            );
+        };
+
+        let mut offset_table = HashMap::new();
+        for queue_id in 0..topic_config.write_queue_nums as i32 {
+            let consumer_offset = self
+                .inner
+                .consumer_offset_manager
+                .query_offset(group, topic, queue_id);
+            if consumer_offset == -1 {
+                return Some(
+                    response
+                        .set_code(ResponseCode::SystemError)
+                        .set_remark(format!("THe consumer group <{}> not exist", group)),
+                );
+            }
+            let mut timestamp_offset = if timestamp == -1 {
+                self.inner
+                    .default_message_store
+                    .get_max_offset_in_queue(topic, queue_id)
+            } else {
+                self.inner
+                    .default_message_store
+                    .get_offset_in_queue_by_time(topic, queue_id, timestamp)
+            };
+            if timestamp_offset < 0 {
+                warn!(
+                    "reset offset is invalid. topic={}, queueId={}, timeStampOffset={}",
+                    topic, queue_id, timestamp_offset
+                );
+                timestamp_offset = 0;
+            }
+            let offset = if request_header.is_force || timestamp_offset < consumer_offset {
+                timestamp_offset
+            } else {
+                consumer_offset
+            };
+            offset_table.insert(
+                MessageQueue::from_parts(
+                    topic.clone(),
+                    self.inner.broker_config.broker_name.clone(),
+                    queue_id,
+                ),
+                offset,
+            );
+        }
+        let body = ResetOffsetBody::new(offset_table);
+
+        let channels = self
+            .inner
+            .consume_manager
+            .get_consumer_group_info(group)
+            .map(|consumer_group_info| consumer_group_info.get_all_channels())
+            .unwrap_or_default();
+        if channels.is_empty() {
+            let error_info = format!(
+                "Consumer not online, so can not reset offset, Group: {} Topic: {} Timestamp: {}",
+                group, topic, timestamp
+            );
+            error!("{}", error_info);
+            return Some(
+                response
+                    .set_code(ResponseCode::ConsumerNotOnline)
+                    .set_remark(error_info),
+            );
+        }
+        let notify_header = ResetOffsetRequestHeader {
+            topic: topic.clone(),
+            group: group.clone(),
+            timestamp,
+            is_force: request_header.is_force,
+            ..Default::default()
+        };
+        for mut channel in channels {
+            if let Err(e) = Broker2Client
+                .reset_consumer_client_offset(&mut channel, notify_header.clone(), &body)
+                .await
+            {
+                error!(
+                    "[reset-offset] reset offset exception. topic={}, group={}, error={}",
+                    topic, group, e
+                );
+            }
+        }
+
+        match body.encode() {
+            Ok(bytes) => Some(response.set_code(ResponseCode::Success).set_body(bytes)),
+            Err(e) => Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(e.to_string()),
+            ),
+        }
+    }
 }
@@ -0,0 +1,140 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_common::common::config_manager::ConfigManager;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::delete_subscription_group_request_header::DeleteSubscriptionGroupRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use tracing::info;
+
+use crate::processor::admin_broker_processor::Inner;
+
+#[derive(Clone)]
+pub(super) struct SubscriptionGroupHandler {
+    inner: Inner,
+}
+
+impl SubscriptionGroupHandler {
+    pub fn new(inner: Inner) -> Self {
+        Self { inner }
+    }
+}
+
+impl SubscriptionGroupHandler {
+    pub async fn update_and_create_subscription_group(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        info!(
+            "AdminBrokerProcessor#updateAndCreateSubscriptionGroup called by {}",
+            channel.remote_address()
+        );
+        let Some(body) = request.body() else {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark("the subscription group config body is empty"),
+            );
+        };
+        let config = match SubscriptionGroupConfig::decode(body.as_ref()) {
+            Ok(config) => config,
+            Err(e) => {
+                return Some(
+                    response
+                        .set_code(ResponseCode::SystemError)
+                        .set_remark(format!("decode subscription group config failed, {}", e)),
+                );
+            }
+        };
+        if config.group_name().is_empty() {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark("the subscription group name is blank"),
+            );
+        }
+        self.inner
+            .subscription_group_manager
+            .update_subscription_group_config(&config);
+        Some(response.set_code(ResponseCode::Success))
+    }
+
+    pub async fn get_all_subscription_group(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        _request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let content = self.inner.subscription_group_manager.encode_pretty(false);
+        Some(
+            RemotingCommand::create_response_command()
+                .set_body(content)
+                .set_code(ResponseCode::Success),
+        )
+    }
+
+    pub async fn delete_subscription_group(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header =
+            match request.decode_command_custom_header::<DeleteSubscriptionGroupRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
+        info!(
+            "AdminBrokerProcessor#deleteSubscriptionGroup, caller={}",
+            channel.remote_address()
+        );
+        self.inner
+            .subscription_group_manager
+            .delete_subscription_group_config(&request_header.group_name);
+        if request_header.clean_offset {
+            self.inner
+                .consumer_offset_manager
+                .remove_offset(request_header.group_name.as_str());
+            self.inner
+                .pop_inflight_message_counter
+                .clear_in_flight_message_num_by_group_name(&request_header.group_name);
+        }
+        if self.inner.broker_config.auto_delete_unused_stats {
+            self.inner
+                .broker_stats_manager
+                .on_group_deleted(&request_header.group_name);
+        }
+        Some(response.set_code(ResponseCode::Success))
+    }
+}
@@ -16,8 +16,10 @@
  */
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 use cheetah_string::CheetahString;
+use futures::future::join_all;
 use rocketmq_common::common::attribute::attribute_parser::AttributeParser;
 use rocketmq_common::common::attribute::topic_message_type::TopicMessageType;
 use rocketmq_common::common::config::TopicConfig;
@@ -44,11 +46,13 @@ use rocketmq_remoting::protocol::header::query_topics_by_consumer_request_header
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::static_topic::topic_config_and_queue_mapping::TopicConfigAndQueueMapping;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_detail::TopicQueueMappingDetail;
+use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_utils::TopicQueueMappingUtils;
 use rocketmq_remoting::protocol::RemotingDeserializable;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
 use rocketmq_store::log_file::MessageStore;
 use tracing::info;
+use tracing::warn;
 
 use crate::processor::admin_broker_processor::Inner;
 
@@ -189,6 +193,103 @@ impl TopicRequestHandler {
         Some(response.set_code(ResponseCode::Success))
     }
 
+    pub async fn update_and_create_static_topic(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header = request
+            .decode_command_custom_header::<CreateTopicRequestHeader>()
+            .unwrap();
+        info!(
+            "Broker receive request to update or create static topic={}, caller address={}",
+            request_header.topic,
+            channel.remote_address()
+        );
+        let mapping_detail = match request
+            .body()
+            .as_ref()
+            .map(|body| TopicQueueMappingDetail::decode(body.as_ref()))
+        {
+            Some(Ok(value)) => value,
+            _ => {
+                return Some(
+                    response
+                        .set_code(ResponseCode::SystemError)
+                        .set_remark("The static topic mapping detail is missing or malformed."),
+                );
+            }
+        };
+        let topic = request_header.topic.clone();
+        let result = TopicValidator::validate_topic(topic.as_str());
+        if !result.valid() {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(result.remark().clone()),
+            );
+        }
+        if self
+            .inner
+            .broker_config
+            .validate_system_topic_when_update_topic
+            && TopicValidator::is_system_topic(topic.as_str())
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "The topic[{}] is conflict with system topic.",
+                        topic.as_str()
+                    )),
+            );
+        }
+
+        let mut topic_config = TopicConfig {
+            topic_name: Some(topic.clone()),
+            read_queue_nums: request_header.read_queue_nums as u32,
+            write_queue_nums: request_header.write_queue_nums as u32,
+            perm: request_header.perm as u32,
+            topic_filter_type: TopicFilterType::from(request_header.topic_filter_type.as_str()),
+            topic_sys_flag: request_header.topic_sys_flag.unwrap_or(0) as u32,
+            order: request_header.order,
+            ..TopicConfig::default()
+        };
+        let force = request_header.force.unwrap_or(false);
+        if let Err(e) = self
+            .inner
+            .topic_queue_mapping_manager
+            .update_topic_queue_mapping(mapping_detail, force, false, true)
+        {
+            warn!("Update static topic failed for [{}], {}", topic.as_str(), e);
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(e.to_string()),
+            );
+        }
+        self.inner
+            .topic_config_manager
+            .update_topic_config(&mut topic_config);
+        self.inner
+            .topic_config_manager
+            .broker_runtime_inner()
+            .register_increment_broker_data(
+                vec![topic_config],
+                self.inner
+                    .topic_config_manager
+                    .data_version()
+                    .as_ref()
+                    .clone(),
+            )
+            .await;
+
+        Some(response.set_code(ResponseCode::Success))
+    }
+
     pub async fn update_and_create_topic_list(
         &mut self,
         channel: Channel,
@@ -312,7 +413,7 @@ impl TopicRequestHandler {
             return Some(
                 response
                     .set_code(ResponseCode::SystemError)
-                    .set_remark("he specified topic is blank."),
+                    .set_remark("The specified topic is blank."),
             );
         }
         if self
@@ -334,36 +435,53 @@ impl TopicRequestHandler {
             .inner
             .consumer_offset_manager
             .which_group_by_topic(topic);
-        for group in groups.iter() {
-            let pop_retry_topic_v2 = CheetahString::from_string(KeyBuilder::build_pop_retry_topic(
-                topic,
-                group.as_str(),
-                true,
-            ));
-            if self
-                .inner
-                .topic_config_manager
-                .select_topic_config(pop_retry_topic_v2.as_ref())
-                .is_some()
-            {
-                self.delete_topic_in_broker(pop_retry_topic_v2.as_ref());
-            }
-            let pop_retry_topic_v1 = CheetahString::from_string(
-                KeyBuilder::build_pop_retry_topic_v1(topic, group.as_str()),
-            );
-            if self
-                .inner
-                .topic_config_manager
-                .select_topic_config(pop_retry_topic_v1.as_ref())
+        let topic_config_manager = &self.inner.topic_config_manager;
+        let deleted_topics = topics_to_delete(topic, &groups, |pop_retry_topic| {
+            topic_config_manager
+                .select_topic_config(pop_retry_topic)
                 .is_some()
-            {
-                self.delete_topic_in_broker(pop_retry_topic_v1.as_ref());
-            }
-            self.delete_topic_in_broker(topic);
+        });
+        for deleted_topic in deleted_topics.iter() {
+            self.delete_topic_in_broker(deleted_topic);
         }
+
+        // drop the routes right away so clients stop routing to the deleted topics
+        let cluster_name = &self.inner.broker_config.broker_identity.broker_cluster_name;
+        let broker_out_api = &self.inner.broker_out_api;
+        join_all(deleted_topics.iter().map(|deleted_topic| {
+            broker_out_api.delete_topic_in_namesrv_all(cluster_name, deleted_topic)
+        }))
+        .await;
         Some(response.set_code(ResponseCode::Success))
     }
 
+    pub async fn clean_unused_topic(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        _request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let retain_topics = self
+            .inner
+            .topic_config_manager
+            .topic_config_table()
+            .lock()
+            .keys()
+            .cloned()
+            .collect::<HashSet<_>>();
+        let delete_count = self
+            .inner
+            .default_message_store
+            .clean_unused_topic(&retain_topics);
+        info!(
+            "AdminBrokerProcessor#cleanUnusedTopic: {} unused topics cleaned, caller={}",
+            delete_count,
+            channel.remote_address()
+        );
+        Some(RemotingCommand::create_response_command())
+    }
+
     pub async fn get_all_topic_config(
         &mut self,
         _channel: Channel,
@@ -448,6 +566,27 @@ impl TopicRequestHandler {
             );
         }
         let topic_config = topic_config.unwrap();
+        let logic_only = request_header
+            .topic_request_header
+            .as_ref()
+            .and_then(|header| header.lo)
+            != Some(false);
+        if logic_only {
+            if let Some(mapping_detail) = self
+                .inner
+                .topic_queue_mapping_manager
+                .get_topic_queue_mapping(topic.as_str())
+            {
+                let mut topic_stats_table = TopicStatsTable::new();
+                topic_stats_table.set_offset_table(self.static_topic_offset_table(&mapping_detail));
+                response.set_body_mut_ref(
+                    topic_stats_table
+                        .encode()
+                        .expect("encode TopicStatsTable failed"),
+                );
+                return Some(response);
+            }
+        }
         let max_queue_nums = topic_config
             .write_queue_nums
             .max(topic_config.read_queue_nums);
@@ -586,6 +725,63 @@ impl TopicRequestHandler {
         Some(response)
     }
 
+    /// Reports the offsets of the logical queues whose leader lives on this broker, so they
+    /// stay stable while the physical queues behind them move between brokers.
+    fn static_topic_offset_table(
+        &self,
+        mapping_detail: &TopicQueueMappingDetail,
+    ) -> HashMap<MessageQueue, TopicOffset> {
+        let mut map = HashMap::new();
+        let broker_name = &self.inner.broker_config.broker_name;
+        let Some(hosted_queues) = mapping_detail.hosted_queues.as_ref() else {
+            return map;
+        };
+        let mapping_info = &mapping_detail.topic_queue_mapping_info;
+        let topic = mapping_info.topic.clone().unwrap_or_default();
+        let mock_broker_name = TopicQueueMappingUtils::get_mock_broker_name(
+            mapping_info.scope.as_deref().unwrap_or_default(),
+        );
+        let store = &self.inner.default_message_store;
+        for (global_id, items) in hosted_queues {
+            let Some(leader) = items.last() else {
+                continue;
+            };
+            if leader.bname.as_ref() != Some(broker_name) {
+                continue;
+            }
+            let max_physical = store
+                .get_max_offset_in_queue(&topic, leader.queue_id)
+                .max(0);
+            let max = leader.compute_static_queue_offset_strictly(max_physical);
+            let min = items
+                .iter()
+                .find(|item| item.logic_offset >= 0)
+                .map(|item| {
+                    if item.bname.as_ref() == Some(broker_name) {
+                        let min_physical = store.get_min_offset_in_queue(&topic, item.queue_id);
+                        item.compute_static_queue_offset_loosely(min_physical.max(0))
+                    } else {
+                        item.logic_offset
+                    }
+                })
+                .unwrap_or(0);
+            let mut timestamp = 0;
+            if max_physical > 0 {
+                timestamp =
+                    store.get_message_store_timestamp(&topic, leader.queue_id, max_physical - 1);
+            }
+            let mut topic_offset = TopicOffset::new();
+            topic_offset.set_min_offset(min);
+            topic_offset.set_max_offset(max);
+            topic_offset.set_last_update_timestamp(timestamp);
+            map.insert(
+                MessageQueue::from_parts(topic.clone(), mock_broker_name.as_str(), *global_id),
+                topic_offset,
+            );
+        }
+        map
+    }
+
     fn delete_topic_in_broker(&mut self, topic: &CheetahString) {
         self.inner.topic_config_manager.delete_topic_config(topic);
         self.inner.topic_queue_mapping_manager.delete(topic);
@@ -598,3 +794,62 @@ impl TopicRequestHandler {
         self.inner.default_message_store.delete_topics(vec![topic]);
     }
 }
+
+/// The topics deleted along with `topic`: the pop retry topics of `groups` on `topic` that
+/// `exists` on the broker, then `topic` itself.
+fn topics_to_delete(
+    topic: &CheetahString,
+    groups: &HashSet<CheetahString>,
+    exists: impl Fn(&CheetahString) -> bool,
+) -> Vec<CheetahString> {
+    let mut topics = groups
+        .iter()
+        .flat_map(|group| {
+            [
+                KeyBuilder::build_pop_retry_topic(topic, group.as_str(), true),
+                KeyBuilder::build_pop_retry_topic_v1(topic, group.as_str()),
+            ]
+        })
+        .map(CheetahString::from_string)
+        .filter(|pop_retry_topic| exists(pop_retry_topic))
+        .collect::<Vec<_>>();
+    topics.push(topic.clone());
+    topics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn groups(groups: &[&'static str]) -> HashSet<CheetahString> {
+        groups
+            .iter()
+            .map(|group| CheetahString::from_static_str(group))
+            .collect()
+    }
+
+    #[test]
+    fn existing_pop_retry_topics_are_deleted_with_the_topic() {
+        let topic = CheetahString::from_static_str("TopicTest");
+        let existing = ["%RETRY%group_a+TopicTest", "%RETRY%group_b_TopicTest"];
+        let mut topics = topics_to_delete(&topic, &groups(&["group_a", "group_b"]), |topic| {
+            existing.contains(&topic.as_str())
+        });
+        assert_eq!(topics.pop().unwrap(), topic);
+        topics.sort();
+        assert_eq!(topics, existing);
+    }
+
+    #[test]
+    fn retry_dlq_and_lmq_topics_are_deleted_alone() {
+        // the pop retry topics of another topic must survive
+        let existing = ["%RETRY%group_a+TopicTest", "%RETRY%group_a_TopicTest"];
+        for topic in ["%RETRY%group_a", "%DLQ%group_a", "%LMQ%123"] {
+            let topic = CheetahString::from_static_str(topic);
+            let topics = topics_to_delete(&topic, &groups(&["group_a"]), |topic| {
+                existing.contains(&topic.as_str())
+            });
+            assert_eq!(topics, vec![topic]);
+        }
+    }
+}
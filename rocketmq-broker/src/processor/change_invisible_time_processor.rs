@@ -14,17 +14,129 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::sync::Arc;
+
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::change_invisible_time_request_header::ChangeInvisibleTimeRequestHeader;
+use rocketmq_remoting::protocol::header::change_invisible_time_response_header::ChangeInvisibleTimeResponseHeader;
+use rocketmq_remoting::protocol::header::extra_info_util::ExtraInfoUtil;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::log_file::MessageStore;
+
+use crate::processor::pop_revive_service::PopReviveService;
+use crate::processor::pop_revive_service::POP_REVIVE_QUEUE_ID;
+use crate::topic::manager::topic_config_manager::TopicConfigManager;
+
+/// Changes how long a popped message stays invisible, counted from now on.
+pub struct ChangeInvisibleTimeProcessor<MS> {
+    topic_config_manager: Arc<TopicConfigManager>,
+    pop_revive_service: ArcMut<PopReviveService<MS>>,
+}
 
-#[derive(Default)]
-pub struct ChangeInvisibleTimeProcessor {}
+impl<MS> ChangeInvisibleTimeProcessor<MS> {
+    pub fn new(
+        topic_config_manager: Arc<TopicConfigManager>,
+        pop_revive_service: ArcMut<PopReviveService<MS>>,
+    ) -> Self {
+        Self {
+            topic_config_manager,
+            pop_revive_service,
+        }
+    }
+}
 
-impl ChangeInvisibleTimeProcessor {
-    fn process_request(
-        &self,
+impl<MS> ChangeInvisibleTimeProcessor<MS>
+where
+    MS: MessageStore + Send + Sync + 'static,
+{
+    pub async fn process_request(
+        &mut self,
+        _channel: Channel,
         _ctx: ConnectionHandlerContext,
-        _request: rocketmq_remoting::protocol::remoting_command::RemotingCommand,
-    ) -> rocketmq_remoting::protocol::remoting_command::RemotingCommand {
-        todo!()
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header =
+            match request.decode_command_custom_header::<ChangeInvisibleTimeRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
+        let Some(topic_config) = self
+            .topic_config_manager
+            .select_topic_config(&request_header.topic)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::TopicNotExist)
+                    .set_remark(format!(
+                        "topic[{}] not exist, apply first please!",
+                        request_header.topic
+                    )),
+            );
+        };
+        if request_header.queue_id >= topic_config.read_queue_nums as i32
+            || request_header.queue_id < 0
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::MessageIllegal)
+                    .set_remark(format!(
+                        "queueId[{}] is illegal, topic:[{}] topicConfig.readQueueNums:[{}]",
+                        request_header.queue_id, request_header.topic, topic_config.read_queue_nums
+                    )),
+            );
+        }
+        let pop_time = match ExtraInfoUtil::split(&request_header.extra_info)
+            .and_then(|extra_info| ExtraInfoUtil::get_pop_time(&extra_info))
+        {
+            Ok(pop_time) => pop_time,
+            Err(e) => {
+                return Some(
+                    response
+                        .set_code(ResponseCode::MessageIllegal)
+                        .set_remark(e.to_string()),
+                );
+            }
+        };
+        let Some(new_pop_time) = self
+            .pop_revive_service
+            .change_invisible_time(
+                &request_header.consumer_group,
+                &request_header.topic,
+                request_header.queue_id,
+                request_header.offset,
+                pop_time,
+                request_header.invisible_time,
+            )
+            .await
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoMessage)
+                    .set_remark(format!(
+                        "the message at offset {} of queue {} is not in flight, it was acked or \
+                         revived already",
+                        request_header.offset, request_header.queue_id
+                    )),
+            );
+        };
+        Some(
+            response.set_command_custom_header(ChangeInvisibleTimeResponseHeader {
+                pop_time: new_pop_time,
+                invisible_time: request_header.invisible_time,
+                revive_qid: POP_REVIVE_QUEUE_ID,
+            }),
+        )
     }
 }
@@ -30,9 +30,11 @@ use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::header::unregister_client_request_header::UnregisterClientRequestHeader;
 use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
+use rocketmq_remoting::protocol::heartbeat::consumer_data::ConsumerData;
 use rocketmq_remoting::protocol::heartbeat::heartbeat_data::HeartbeatData;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_rust::ArcMut;
 use rocketmq_store::log_file::MessageStore;
 use tracing::info;
 
@@ -52,7 +54,7 @@ pub struct ClientManageProcessor<MS> {
     consumer_manager: Arc<ConsumerManager>,
     topic_config_manager: TopicConfigManager,
     subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
-    broker_config: Arc<BrokerConfig>,
+    broker_config: ArcMut<BrokerConfig>,
 }
 
 impl<MS> ClientManageProcessor<MS>
@@ -60,7 +62,7 @@ where
     MS: MessageStore,
 {
     pub fn new(
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         producer_manager: Arc<ProducerManager>,
         consumer_manager: Arc<ConsumerManager>,
         topic_config_manager: TopicConfigManager,
@@ -122,8 +124,16 @@ where
                 .unregister_producer(group, &client_channel_info, &ctx);
         }
 
-        if let Some(ref _group) = request_header.consumer_group {
-            unimplemented!()
+        if let Some(ref group) = request_header.consumer_group {
+            let is_notify_consumer_ids_changed_enable = self
+                .subscription_group_manager
+                .find_subscription_group_config(group)
+                .map_or(true, |config| config.notify_consumer_ids_changed_enable());
+            self.consumer_manager.unregister_consumer(
+                group,
+                &client_channel_info,
+                is_notify_consumer_ids_changed_enable,
+            );
         }
 
         Some(RemotingCommand::create_response_command())
@@ -160,50 +170,7 @@ where
                 consumer_data.group_name.clone(),
                 heartbeat_data.heartbeat_fingerprint,
             );
-            let mut has_order_topic_sub = false;
-            for subscription_data in consumer_data.subscription_data_set.iter() {
-                if self
-                    .topic_config_manager
-                    .is_order_topic(subscription_data.topic.as_str())
-                {
-                    has_order_topic_sub = true;
-                    break;
-                }
-            }
-            let subscription_group_config = self
-                .subscription_group_manager
-                .find_subscription_group_config(consumer_data.group_name.as_ref());
-            if subscription_group_config.is_none() {
-                continue;
-            }
-            let subscription_group_config = subscription_group_config.unwrap();
-            let is_notify_consumer_ids_changed_enable =
-                subscription_group_config.notify_consumer_ids_changed_enable();
-            let topic_sys_flag = if consumer_data.unit_mode {
-                topic_sys_flag::build_sys_flag(false, true)
-            } else {
-                0
-            };
-            let new_topic = CheetahString::from_string(mix_all::get_retry_topic(
-                consumer_data.group_name.as_str(),
-            ));
-            self.topic_config_manager
-                .create_topic_in_send_message_back_method(
-                    &new_topic,
-                    subscription_group_config.retry_queue_nums(),
-                    PermName::PERM_WRITE | PermName::PERM_READ,
-                    has_order_topic_sub,
-                    topic_sys_flag,
-                );
-            let changed = self.consumer_manager.register_consumer(
-                consumer_data.group_name.as_ref(),
-                client_channel_info.clone(),
-                consumer_data.consume_type,
-                consumer_data.message_model,
-                consumer_data.consume_from_where,
-                consumer_data.subscription_data_set.clone(),
-                is_notify_consumer_ids_changed_enable,
-            );
+            let changed = self.register_consumer_data(consumer_data, &client_channel_info, true);
             if changed {
                 info!(
                     "ClientManageProcessor: registerConsumer info changed, SDK address={}, \
@@ -225,14 +192,56 @@ where
     }
 
     fn heart_beat_v2(
-        &self,
-        _channel: &Channel,
+        &mut self,
+        channel: &Channel,
         _ctx: &ConnectionHandlerContext,
         heartbeat_data: HeartbeatData,
         client_channel_info: ClientChannelInfo,
     ) -> Option<RemotingCommand> {
-        let is_sub_change = false;
+        let mut is_sub_change = false;
         //handle consumer data
+        for consumer_data in heartbeat_data.consumer_data_set.iter() {
+            if self.broker_config.reject_pull_consumer_enable
+                && ConsumeType::ConsumeActively == consumer_data.consume_type
+            {
+                continue;
+            }
+            let with_sub = !heartbeat_data.is_without_sub;
+            if with_sub {
+                self.consumer_group_heartbeat_table.write().insert(
+                    consumer_data.group_name.clone(),
+                    heartbeat_data.heartbeat_fingerprint,
+                );
+            } else {
+                // The client only sends subscriptions when its fingerprint changes, so ask it to
+                // resend them if this broker has lost or never seen the group's subscriptions.
+                let fingerprint_changed = self
+                    .consumer_group_heartbeat_table
+                    .read()
+                    .get(&consumer_data.group_name)
+                    .map_or(true, |fingerprint| {
+                        *fingerprint != heartbeat_data.heartbeat_fingerprint
+                    });
+                if fingerprint_changed
+                    || self
+                        .consumer_manager
+                        .get_consumer_group_info(&consumer_data.group_name)
+                        .is_none()
+                {
+                    is_sub_change = true;
+                }
+            }
+            let changed =
+                self.register_consumer_data(consumer_data, &client_channel_info, with_sub);
+            if changed {
+                info!(
+                    "heartBeatV2 ClientManageProcessor: registerConsumer info changed, SDK \
+                     address={}, consumerData={:?}",
+                    channel.remote_address(),
+                    consumer_data
+                )
+            }
+        }
 
         //handle producer data
         for producer_data in heartbeat_data.producer_data_set.iter() {
@@ -244,4 +253,62 @@ where
         response_command.add_ext_field(IS_SUB_CHANGE.to_string(), is_sub_change.to_string());
         Some(response_command)
     }
+
+    /// Creates the group's retry topic if needed and registers the consumer channel. When
+    /// `with_sub` is `false` the subscription table of the group is left untouched.
+    fn register_consumer_data(
+        &mut self,
+        consumer_data: &ConsumerData,
+        client_channel_info: &ClientChannelInfo,
+        with_sub: bool,
+    ) -> bool {
+        let mut is_notify_consumer_ids_changed_enable = true;
+        if let Some(subscription_group_config) = self
+            .subscription_group_manager
+            .find_subscription_group_config(&consumer_data.group_name)
+        {
+            is_notify_consumer_ids_changed_enable =
+                subscription_group_config.notify_consumer_ids_changed_enable();
+            let has_order_topic_sub = consumer_data
+                .subscription_data_set
+                .iter()
+                .any(|sub| self.topic_config_manager.is_order_topic(sub.topic.as_str()));
+            let topic_sys_flag = if consumer_data.unit_mode {
+                topic_sys_flag::build_sys_flag(false, true)
+            } else {
+                0
+            };
+            let new_topic = CheetahString::from_string(mix_all::get_retry_topic(
+                consumer_data.group_name.as_str(),
+            ));
+            self.topic_config_manager
+                .create_topic_in_send_message_back_method(
+                    &new_topic,
+                    subscription_group_config.retry_queue_nums(),
+                    PermName::PERM_WRITE | PermName::PERM_READ,
+                    has_order_topic_sub,
+                    topic_sys_flag,
+                );
+        }
+        if with_sub {
+            self.consumer_manager.register_consumer(
+                &consumer_data.group_name,
+                client_channel_info.clone(),
+                consumer_data.consume_type,
+                consumer_data.message_model,
+                consumer_data.consume_from_where,
+                consumer_data.subscription_data_set.clone(),
+                is_notify_consumer_ids_changed_enable,
+            )
+        } else {
+            self.consumer_manager.register_consumer_without_sub(
+                &consumer_data.group_name,
+                client_channel_info.clone(),
+                consumer_data.consume_type,
+                consumer_data.message_model,
+                consumer_data.consume_from_where,
+                is_notify_consumer_ids_changed_enable,
+            )
+        }
+    }
 }
@@ -43,7 +43,7 @@ use crate::topic::manager::topic_config_manager::TopicConfigManager;
 use crate::topic::manager::topic_queue_mapping_manager::TopicQueueMappingManager;
 
 pub struct ConsumerManageProcessor<MS> {
-    broker_config: Arc<BrokerConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     consumer_manager: Arc<ConsumerManager>,
     topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
     consumer_offset_manager: Arc<ConsumerOffsetManager>,
@@ -57,7 +57,7 @@ where
     MS: MessageStore,
 {
     pub fn new(
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         consumer_manager: Arc<ConsumerManager>,
         topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
         subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
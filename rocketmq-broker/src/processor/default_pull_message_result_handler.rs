@@ -18,10 +18,12 @@ use std::any::Any;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use bytes::Buf;
 use bytes::Bytes;
 use bytes::BytesMut;
 use cheetah_string::CheetahString;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::message::message_decoder::MESSAGE_STORE_TIMESTAMP_POSITION;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::mix_all::MASTER_ID;
 use rocketmq_common::common::sys_flag::pull_sys_flag::PullSysFlag;
@@ -66,26 +68,26 @@ use crate::topic::manager::topic_config_manager::TopicConfigManager;
 
 pub struct DefaultPullMessageResultHandler {
     topic_config_manager: Arc<TopicConfigManager>,
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     consumer_offset_manager: Arc<ConsumerOffsetManager>,
     consumer_manager: Arc<ConsumerManager>,
     broadcast_offset_manager: Arc<BroadcastOffsetManager>,
     broker_stats_manager: Arc<BrokerStatsManager>,
-    broker_config: Arc<BrokerConfig>,
-    consume_message_hook_list: Arc<Vec<Box<dyn ConsumeMessageHook>>>,
+    broker_config: ArcMut<BrokerConfig>,
+    consume_message_hook_list: ArcMut<Vec<Box<dyn ConsumeMessageHook>>>,
     pull_request_hold_service: Option<ArcMut<PullRequestHoldService<DefaultMessageStore>>>,
 }
 
 impl DefaultPullMessageResultHandler {
     pub fn new(
-        message_store_config: Arc<MessageStoreConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
         topic_config_manager: Arc<TopicConfigManager>,
         consumer_offset_manager: Arc<ConsumerOffsetManager>,
         consumer_manager: Arc<ConsumerManager>,
         broadcast_offset_manager: Arc<BroadcastOffsetManager>,
         broker_stats_manager: Arc<BrokerStatsManager>,
-        broker_config: Arc<BrokerConfig>,
-        consume_message_hook_list: Arc<Vec<Box<dyn ConsumeMessageHook>>>,
+        broker_config: ArcMut<BrokerConfig>,
+        consume_message_hook_list: ArcMut<Vec<Box<dyn ConsumeMessageHook>>>,
     ) -> Self {
         Self {
             topic_config_manager,
@@ -219,7 +221,7 @@ impl PullMessageResultHandler for DefaultPullMessageResultHandler {
                 };
                 if broker_allow_suspend && has_suspend_flag {
                     let mut polling_time_mills = suspend_timeout_millis_long;
-                    if !self.broker_config.long_polling_enable {
+                    if !self.broker_config.long_polling_enable.get() {
                         polling_time_mills = self.broker_config.short_polling_time_mills;
                     }
                     let topic = request_header.topic.as_str();
@@ -306,17 +308,28 @@ impl DefaultPullMessageResultHandler {
     fn read_get_message_result(
         &self,
         get_message_result: &GetMessageResult,
-        _group: &str,
-        _topic: &str,
-        _queue_id: i32,
+        group: &str,
+        topic: &str,
+        queue_id: i32,
     ) -> Option<Bytes> {
         let mut bytes_mut =
             BytesMut::with_capacity(get_message_result.buffer_total_size() as usize);
+        let mut store_timestamp = 0;
         for msg in get_message_result.message_mapped_list() {
             let data = &msg.mapped_file.as_ref().unwrap().get_mapped_file()
                 [msg.start_offset as usize..(msg.start_offset + msg.size as u64) as usize];
+            if data.len() >= MESSAGE_STORE_TIMESTAMP_POSITION + 8 {
+                let mut timestamp = &data[MESSAGE_STORE_TIMESTAMP_POSITION..];
+                store_timestamp = timestamp.get_i64();
+            }
             bytes_mut.extend_from_slice(data);
         }
+        self.broker_stats_manager.inc_group_get_latency(
+            group,
+            topic,
+            queue_id,
+            (get_current_millis() as i64 - store_timestamp) as i32,
+        );
         Some(bytes_mut.freeze())
     }
 
@@ -358,7 +371,7 @@ impl DefaultPullMessageResultHandler {
 
             match response_code {
                 ResponseCode::Success => {
-                    let commercial_base_count = self.broker_config.commercial_base_count;
+                    let commercial_base_count = self.broker_config.commercial_base_count.get();
                     let inc_value =
                         get_message_result.msg_count4_commercial() * commercial_base_count;
 
@@ -410,7 +423,7 @@ impl DefaultPullMessageResultHandler {
 
 impl DefaultPullMessageResultHandler {
     fn compose_response_header(
-        broker_config: &Arc<BrokerConfig>,
+        broker_config: &ArcMut<BrokerConfig>,
         request_header: &PullMessageRequestHeader,
         get_message_result: &GetMessageResult,
         topic_sys_flag: i32,
@@ -419,7 +432,16 @@ impl DefaultPullMessageResultHandler {
         client_address: &str,
     ) {
         let mut response_header = PullMessageResponseHeader::default();
-        response.set_remark_mut(format!("{:?}", get_message_result.status()));
+        match get_message_result.batch_full_status() {
+            // the batch was cut by a pull or transfer limit, next_begin_offset points at the
+            // first message that was not transferred
+            Some(batch_full_status) => response.set_remark_mut(format!(
+                "{:?} {}",
+                get_message_result.status(),
+                batch_full_status
+            )),
+            None => response.set_remark_mut(format!("{:?}", get_message_result.status())),
+        }
         response_header.next_begin_offset = get_message_result.next_begin_offset();
         response_header.min_offset = get_message_result.min_offset();
         response_header.max_offset = get_message_result.max_offset();
@@ -484,7 +506,7 @@ impl DefaultPullMessageResultHandler {
             }
         }
 
-        if broker_config.slave_read_enable && !broker_config.is_in_broker_container {
+        if broker_config.slave_read_enable.get() && !broker_config.is_in_broker_container {
             if get_message_result.suggest_pulling_from_slave() {
                 response_header.suggest_which_broker_id =
                     subscription_group_config.which_broker_when_consume_slowly();
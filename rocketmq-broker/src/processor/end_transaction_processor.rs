@@ -14,7 +14,6 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use std::sync::Arc;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
@@ -47,16 +46,16 @@ use crate::transaction::transactional_message_service::TransactionalMessageServi
 
 #[derive(Default)]
 pub struct EndTransactionProcessor<TM, MS> {
-    message_store_config: Arc<MessageStoreConfig>,
-    broker_config: Arc<BrokerConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     transactional_message_service: ArcMut<TM>,
     message_store: ArcMut<MS>,
 }
 
 impl<TM, MS> EndTransactionProcessor<TM, MS> {
     pub fn new(
-        message_store_config: Arc<MessageStoreConfig>,
-        broker_config: Arc<BrokerConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         transactional_message_service: ArcMut<TM>,
         message_store: ArcMut<MS>,
     ) -> Self {
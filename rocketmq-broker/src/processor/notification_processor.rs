@@ -14,17 +14,277 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::sync::Arc;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::key_builder::KeyBuilder;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::notification_request_header::NotificationRequestHeader;
+use rocketmq_remoting::protocol::header::notification_response_header::NotificationResponseHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::log_file::MessageStore;
+
+use crate::long_polling::polling_num_table::PollingNumTable;
+use crate::offset::manager::consumer_offset_manager::ConsumerOffsetManager;
+use crate::subscription::manager::subscription_group_manager::SubscriptionGroupManager;
+use crate::topic::manager::topic_config_manager::TopicConfigManager;
+
+/// How often a held notification request re-checks the queues for new messages.
+const NOTIFICATION_CHECK_INTERVAL_MILLIS: u64 = 100;
+
+/// Tells a consumer whether there are messages left to consume without transferring them, and
+/// holds the request for up to `poll_time` when there are none yet.
+pub struct NotificationProcessor<MS> {
+    broker_config: ArcMut<BrokerConfig>,
+    topic_config_manager: Arc<TopicConfigManager>,
+    subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+    consumer_offset_manager: Arc<ConsumerOffsetManager>,
+    message_store: ArcMut<MS>,
+    polling_num_table: Arc<PollingNumTable>,
+}
+
+impl<MS> Clone for NotificationProcessor<MS> {
+    fn clone(&self) -> Self {
+        Self {
+            broker_config: self.broker_config.clone(),
+            topic_config_manager: self.topic_config_manager.clone(),
+            subscription_group_manager: self.subscription_group_manager.clone(),
+            consumer_offset_manager: self.consumer_offset_manager.clone(),
+            message_store: self.message_store.clone(),
+            polling_num_table: self.polling_num_table.clone(),
+        }
+    }
+}
+
+impl<MS> NotificationProcessor<MS> {
+    pub fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        topic_config_manager: Arc<TopicConfigManager>,
+        subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+        consumer_offset_manager: Arc<ConsumerOffsetManager>,
+        message_store: ArcMut<MS>,
+        polling_num_table: Arc<PollingNumTable>,
+    ) -> Self {
+        Self {
+            broker_config,
+            topic_config_manager,
+            subscription_group_manager,
+            consumer_offset_manager,
+            message_store,
+            polling_num_table,
+        }
+    }
+}
 
-#[derive(Default)]
-pub struct NotificationProcessor {}
+impl<MS> NotificationProcessor<MS>
+where
+    MS: MessageStore + Send + Sync + 'static,
+{
+    pub async fn process_request(
+        &mut self,
+        _channel: Channel,
+        ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header =
+            match request.decode_command_custom_header::<NotificationRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
+        if !PermName::is_readable(self.broker_config.broker_permission.get()) {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "the broker[{}] notification is forbidden",
+                        self.broker_config.broker_ip1
+                    )),
+            );
+        }
+        let Some(topic_config) = self
+            .topic_config_manager
+            .select_topic_config(&request_header.topic)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::TopicNotExist)
+                    .set_remark(format!(
+                        "topic[{}] not exist, apply first please!",
+                        request_header.topic
+                    )),
+            );
+        };
+        if !PermName::is_readable(topic_config.perm) {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "the topic[{}] notification is forbidden",
+                        request_header.topic
+                    )),
+            );
+        }
+        if request_header.queue_id >= topic_config.read_queue_nums as i32
+            || request_header.queue_id < -1
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "queueId[{}] is illegal, topic:[{}] topicConfig.readQueueNums:[{}]",
+                        request_header.queue_id, request_header.topic, topic_config.read_queue_nums
+                    )),
+            );
+        }
+        let Some(subscription_group_config) = self
+            .subscription_group_manager
+            .find_subscription_group_config(&request_header.consumer_group)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::SubscriptionGroupNotExist)
+                    .set_remark(format!(
+                        "subscription group [{}] does not exist",
+                        request_header.consumer_group
+                    )),
+            );
+        };
+        if !subscription_group_config.consume_enable() {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "subscription group no permission, {}",
+                        request_header.consumer_group
+                    )),
+            );
+        }
 
-impl NotificationProcessor {
-    fn process_request(
+        let queue_ids = if request_header.queue_id < 0 {
+            (0..topic_config.read_queue_nums as i32).collect::<Vec<_>>()
+        } else {
+            vec![request_header.queue_id]
+        };
+        let has_msg = self.has_msg(&request_header, &queue_ids);
+        if has_msg || request_header.poll_time <= 0 || !self.broker_config.long_polling_enable.get()
+        {
+            return Some(
+                response.set_command_custom_header(NotificationResponseHeader { has_msg }),
+            );
+        }
+
+        let deadline = hold_deadline(
+            request_header.born_time,
+            request_header.poll_time,
+            get_current_millis(),
+        );
+        let opaque = request.opaque();
+        let this = self.clone();
+        tokio::spawn(async move {
+            let topic = request_header.topic.as_str();
+            let group = request_header.consumer_group.as_str();
+            this.polling_num_table
+                .increment(topic, group, request_header.queue_id);
+            let mut has_msg = false;
+            while get_current_millis() < deadline {
+                let wait =
+                    (deadline - get_current_millis()).min(NOTIFICATION_CHECK_INTERVAL_MILLIS);
+                tokio::time::sleep(Duration::from_millis(wait)).await;
+                has_msg = this.has_msg(&request_header, &queue_ids);
+                if has_msg {
+                    break;
+                }
+            }
+            this.polling_num_table
+                .decrement(topic, group, request_header.queue_id);
+            let response = RemotingCommand::create_response_command()
+                .set_command_custom_header(NotificationResponseHeader { has_msg })
+                .set_opaque(opaque)
+                .mark_response_type();
+            if let Some(mut ctx) = ctx.upgrade() {
+                ctx.write(response).await;
+            }
+        });
+        None
+    }
+
+    /// Whether any of `queue_ids` of the topic, or the group's pop retry topics, has messages
+    /// beyond the committed offset of the group.
+    fn has_msg(&self, request_header: &NotificationRequestHeader, queue_ids: &[i32]) -> bool {
+        let group = &request_header.consumer_group;
+        let topic = &request_header.topic;
+        if queue_ids
+            .iter()
+            .any(|queue_id| self.has_msg_from_queue(group, topic, *queue_id))
+        {
+            return true;
+        }
+        [
+            KeyBuilder::build_pop_retry_topic_v1(topic, group),
+            KeyBuilder::build_pop_retry_topic_v2(topic, group),
+        ]
+        .into_iter()
+        .map(CheetahString::from_string)
+        .filter(|retry_topic| {
+            self.topic_config_manager
+                .select_topic_config(retry_topic)
+                .is_some()
+        })
+        .any(|retry_topic| self.has_msg_from_queue(group, &retry_topic, 0))
+    }
+
+    fn has_msg_from_queue(
         &self,
-        _ctx: ConnectionHandlerContext,
-        _request: rocketmq_remoting::protocol::remoting_command::RemotingCommand,
-    ) -> rocketmq_remoting::protocol::remoting_command::RemotingCommand {
-        todo!()
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+    ) -> bool {
+        let max_offset = self.message_store.get_max_offset_in_queue(topic, queue_id);
+        let mut offset = self
+            .consumer_offset_manager
+            .query_offset(group, topic, queue_id);
+        if offset < 0 {
+            offset = self.message_store.get_min_offset_in_queue(topic, queue_id);
+        }
+        max_offset - offset > 0
+    }
+}
+
+/// The time a notification request may be held until, counted from the client's `born_time`
+/// when it is known so that time spent in transit is not waited twice.
+pub(crate) fn hold_deadline(born_time: i64, poll_time: i64, now: u64) -> u64 {
+    let start = if born_time > 0 {
+        (born_time as u64).min(now)
+    } else {
+        now
+    };
+    start + poll_time.max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hold_deadline_counts_from_born_time() {
+        assert_eq!(hold_deadline(1_000, 500, 1_200), 1_500);
+        assert_eq!(hold_deadline(0, 500, 1_200), 1_700);
+        assert_eq!(hold_deadline(2_000, 500, 1_200), 1_700);
     }
 }
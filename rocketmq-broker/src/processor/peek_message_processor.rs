@@ -14,22 +14,232 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::sync::Arc;
+
+use bytes::BytesMut;
+use cheetah_string::CheetahString;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::peek_message_request_header::PeekMessageRequestHeader;
+use rocketmq_remoting::protocol::header::pop_message_response_header::PopMessageResponseHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::log_file::mapped_file::MappedFile;
+use rocketmq_store::log_file::MessageStore;
+
+use crate::offset::manager::consumer_offset_manager::ConsumerOffsetManager;
+use crate::subscription::manager::subscription_group_manager::SubscriptionGroupManager;
+use crate::topic::manager::topic_config_manager::TopicConfigManager;
+
+/// Upper bound of `maxMsgNums` accepted by a single peek request.
+const MAX_PEEK_MSG_NUMS: i32 = 32;
 
-#[derive(Default)]
-pub struct PeekMessageProcessor {}
+pub struct PeekMessageProcessor<MS> {
+    broker_config: ArcMut<BrokerConfig>,
+    topic_config_manager: Arc<TopicConfigManager>,
+    subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+    consumer_offset_manager: Arc<ConsumerOffsetManager>,
+    message_store: ArcMut<MS>,
+}
 
-impl PeekMessageProcessor {
+impl<MS> PeekMessageProcessor<MS> {
+    pub fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        topic_config_manager: Arc<TopicConfigManager>,
+        subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+        consumer_offset_manager: Arc<ConsumerOffsetManager>,
+        message_store: ArcMut<MS>,
+    ) -> Self {
+        Self {
+            broker_config,
+            topic_config_manager,
+            subscription_group_manager,
+            consumer_offset_manager,
+            message_store,
+        }
+    }
+}
+
+impl<MS> PeekMessageProcessor<MS>
+where
+    MS: MessageStore + Send + Sync + 'static,
+{
     pub async fn process_request(
         &mut self,
         _channel: Channel,
         _ctx: ConnectionHandlerContext,
         _request_code: RequestCode,
-        _request: RemotingCommand,
-    ) -> crate::Result<Option<RemotingCommand>> {
-        unimplemented!("PeekMessageProcessor.process_request")
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header =
+            match request.decode_command_custom_header::<PeekMessageRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
+        if !PermName::is_readable(self.broker_config.broker_permission.get()) {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "the broker[{}] peeking message is forbidden",
+                        self.broker_config.broker_ip1
+                    )),
+            );
+        }
+        if request_header.max_msg_nums <= 0 || request_header.max_msg_nums > MAX_PEEK_MSG_NUMS {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "the broker[{}] peek message size must be in (0, {}]",
+                        self.broker_config.broker_ip1, MAX_PEEK_MSG_NUMS
+                    )),
+            );
+        }
+        let Some(topic_config) = self
+            .topic_config_manager
+            .select_topic_config(&request_header.topic)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::TopicNotExist)
+                    .set_remark(format!(
+                        "topic[{}] not exist, apply first please!",
+                        request_header.topic
+                    )),
+            );
+        };
+        if !PermName::is_readable(topic_config.perm) {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "the topic[{}] peeking message is forbidden",
+                        request_header.topic
+                    )),
+            );
+        }
+        if request_header.queue_id >= topic_config.read_queue_nums as i32
+            || request_header.queue_id < -1
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "queueId[{}] is illegal, topic:[{}] topicConfig.readQueueNums:[{}]",
+                        request_header.queue_id, request_header.topic, topic_config.read_queue_nums
+                    )),
+            );
+        }
+        if self
+            .subscription_group_manager
+            .find_subscription_group_config(&request_header.consumer_group)
+            .is_none()
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::SubscriptionGroupNotExist)
+                    .set_remark(format!(
+                        "subscription group [{}] does not exist",
+                        request_header.consumer_group
+                    )),
+            );
+        }
+
+        let queue_ids = if request_header.queue_id < 0 {
+            (0..topic_config.read_queue_nums as i32).collect::<Vec<_>>()
+        } else {
+            vec![request_header.queue_id]
+        };
+        let mut body = BytesMut::new();
+        let mut found = 0;
+        let mut rest_num = 0;
+        for queue_id in queue_ids {
+            let remaining = request_header.max_msg_nums - found;
+            let (peeked, rest) = self
+                .peek_queue(
+                    &request_header.consumer_group,
+                    &request_header.topic,
+                    queue_id,
+                    remaining,
+                    &mut body,
+                )
+                .await;
+            found += peeked;
+            rest_num += rest;
+        }
+
+        let response_header = PopMessageResponseHeader {
+            pop_time: get_current_millis(),
+            rest_num: rest_num as u64,
+            ..Default::default()
+        };
+        if found == 0 {
+            return Some(
+                response
+                    .set_code(ResponseCode::PullNotFound)
+                    .set_remark("no message found")
+                    .set_command_custom_header(response_header),
+            );
+        }
+        Some(
+            response
+                .set_command_custom_header(response_header)
+                .set_body(body.freeze()),
+        )
+    }
+
+    /// Appends up to `max_msg_nums` messages of the queue starting at the group's committed
+    /// offset to `body`, returns the number of appended messages and the messages left behind.
+    async fn peek_queue(
+        &self,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+        max_msg_nums: i32,
+        body: &mut BytesMut,
+    ) -> (i32, i64) {
+        let max_offset = self.message_store.get_max_offset_in_queue(topic, queue_id);
+        let mut offset = self
+            .consumer_offset_manager
+            .query_offset(group, topic, queue_id);
+        if offset < 0 {
+            offset = self.message_store.get_min_offset_in_queue(topic, queue_id);
+        }
+        if max_msg_nums <= 0 {
+            return (0, (max_offset - offset).max(0));
+        }
+        let Some(result) = self
+            .message_store
+            .get_message(group, topic, queue_id, offset, max_msg_nums, i32::MAX, None)
+            .await
+        else {
+            return (0, (max_offset - offset).max(0));
+        };
+        for msg in result.message_mapped_list() {
+            if let Some(mapped_file) = msg.mapped_file.as_ref() {
+                // start_offset is the physical offset, relative to the first file of the log
+                let pos = (msg.start_offset - mapped_file.get_file_from_offset()) as usize;
+                body.extend_from_slice(
+                    &mapped_file.get_mapped_file()[pos..pos + msg.size as usize],
+                );
+            }
+        }
+        (
+            result.message_count(),
+            (max_offset - result.next_begin_offset()).max(0),
+        )
     }
 }
@@ -14,22 +14,145 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::sync::Arc;
+
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::constant::PermName;
 use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::polling_info_request_header::PollingInfoRequestHeader;
+use rocketmq_remoting::protocol::header::polling_info_response_header::PollingInfoResponseHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::log_file::MessageStore;
+
+use crate::long_polling::polling_num_table::PollingNumTable;
+use crate::subscription::manager::subscription_group_manager::SubscriptionGroupManager;
+use crate::topic::manager::topic_config_manager::TopicConfigManager;
 
-#[derive(Default)]
-pub struct PollingInfoProcessor {}
+/// Reports how many notification requests of a group the broker is holding on a queue.
+pub struct PollingInfoProcessor<MS> {
+    broker_config: ArcMut<BrokerConfig>,
+    topic_config_manager: Arc<TopicConfigManager>,
+    subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+    polling_num_table: Arc<PollingNumTable>,
+}
 
-impl PollingInfoProcessor {
+impl<MS> PollingInfoProcessor<MS> {
+    pub fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        topic_config_manager: Arc<TopicConfigManager>,
+        subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+        polling_num_table: Arc<PollingNumTable>,
+    ) -> Self {
+        Self {
+            broker_config,
+            topic_config_manager,
+            subscription_group_manager,
+            polling_num_table,
+        }
+    }
+}
+
+impl<MS> PollingInfoProcessor<MS>
+where
+    MS: MessageStore + Send + Sync + 'static,
+{
     pub async fn process_request(
         &mut self,
         _channel: Channel,
         _ctx: ConnectionHandlerContext,
         _request_code: RequestCode,
-        _request: RemotingCommand,
-    ) -> crate::Result<Option<RemotingCommand>> {
-        todo!()
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header =
+            match request.decode_command_custom_header::<PollingInfoRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
+        if !PermName::is_readable(self.broker_config.broker_permission.get()) {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "the broker[{}] polling info is forbidden",
+                        self.broker_config.broker_ip1
+                    )),
+            );
+        }
+        let Some(topic_config) = self
+            .topic_config_manager
+            .select_topic_config(&request_header.topic)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::TopicNotExist)
+                    .set_remark(format!(
+                        "topic[{}] not exist, apply first please!",
+                        request_header.topic
+                    )),
+            );
+        };
+        if !PermName::is_readable(topic_config.perm) {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "the topic[{}] polling info is forbidden",
+                        request_header.topic
+                    )),
+            );
+        }
+        if request_header.queue_id >= topic_config.read_queue_nums as i32
+            || request_header.queue_id < -1
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "queueId[{}] is illegal, topic:[{}] topicConfig.readQueueNums:[{}]",
+                        request_header.queue_id, request_header.topic, topic_config.read_queue_nums
+                    )),
+            );
+        }
+        let Some(subscription_group_config) = self
+            .subscription_group_manager
+            .find_subscription_group_config(&request_header.consumer_group)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::SubscriptionGroupNotExist)
+                    .set_remark(format!(
+                        "subscription group [{}] does not exist",
+                        request_header.consumer_group
+                    )),
+            );
+        };
+        if !subscription_group_config.consume_enable() {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "subscription group no permission, {}",
+                        request_header.consumer_group
+                    )),
+            );
+        }
+
+        let polling_num = self.polling_num_table.polling_num(
+            &request_header.topic,
+            &request_header.consumer_group,
+            request_header.queue_id,
+        );
+        Some(response.set_command_custom_header(PollingInfoResponseHeader { polling_num }))
     }
 }
@@ -22,4 +22,20 @@ impl PopInflightMessageCounter {
     pub fn clear_in_flight_message_num_by_topic_name(&self, _topic: &CheetahString) {
         // TODO
     }
+
+    pub fn clear_in_flight_message_num_by_group_name(&self, _group: &CheetahString) {
+        // TODO
+    }
+
+    /// Popped but not yet acked messages of `group` in one queue of `topic`. POP is not served
+    /// by this broker yet, so nothing is ever in flight.
+    pub fn get_group_pop_in_flight_message_num(
+        &self,
+        _topic: &CheetahString,
+        _group: &CheetahString,
+        _queue_id: i32,
+    ) -> i64 {
+        // TODO
+        0
+    }
 }
@@ -14,18 +14,491 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::BytesMut;
+use cheetah_string::CheetahString;
+use rand::Rng;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::filter::expression_type::ExpressionType;
+use rocketmq_common::common::key_builder::KeyBuilder;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::filter::filter_api::FilterAPI;
+use rocketmq_remoting::protocol::header::extra_info_util::ExtraInfoUtil;
+use rocketmq_remoting::protocol::header::pop_message_request_header::PopMessageRequestHeader;
+use rocketmq_remoting::protocol::header::pop_message_response_header::PopMessageResponseHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::base::message_status_enum::GetMessageStatus;
+use rocketmq_store::filter::MessageFilter;
+use rocketmq_store::log_file::mapped_file::MappedFile;
+use rocketmq_store::log_file::MessageStore;
+
+use crate::filter::expression_message_filter::ExpressionMessageFilter;
+use crate::filter::manager::consumer_filter_manager::ConsumerFilterManager;
+use crate::long_polling::polling_num_table::PollingNumTable;
+use crate::processor::notification_processor::hold_deadline;
+use crate::processor::pop_revive_service::InflightMessage;
+use crate::processor::pop_revive_service::PopReviveService;
+use crate::processor::pop_revive_service::POP_REVIVE_QUEUE_ID;
+use crate::subscription::manager::subscription_group_manager::SubscriptionGroupManager;
+use crate::topic::manager::topic_config_manager::TopicConfigManager;
+
+/// Upper bound of `maxMsgNums` accepted by a single pop request.
+const MAX_POP_MSG_NUMS: i32 = 32;
+
+/// How often a held pop request tries to pop again.
+const POP_CHECK_INTERVAL_MILLIS: u64 = 100;
 
+/// Messages of one pop request and the offset infos the client builds the receipt handles from.
 #[derive(Default)]
-pub struct PopMessageProcessor {}
+struct PopResult {
+    /// The pop time recorded with the popped messages, acks have to present it.
+    pop_time: i64,
+    body: BytesMut,
+    found: i32,
+    rest_num: i64,
+    start_offset_info: String,
+    msg_offset_info: String,
+}
+
+/// Hands out messages of a group which stay invisible to other consumers of the group until
+/// they are acked or their invisible time expires, after which they are popped again from the
+/// group's pop retry topic.
+pub struct PopMessageProcessor<MS> {
+    broker_config: ArcMut<BrokerConfig>,
+    topic_config_manager: Arc<TopicConfigManager>,
+    subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+    consumer_filter_manager: Arc<ConsumerFilterManager>,
+    message_store: ArcMut<MS>,
+    pop_revive_service: ArcMut<PopReviveService<MS>>,
+    polling_num_table: Arc<PollingNumTable>,
+}
+
+impl<MS> Clone for PopMessageProcessor<MS> {
+    fn clone(&self) -> Self {
+        Self {
+            broker_config: self.broker_config.clone(),
+            topic_config_manager: self.topic_config_manager.clone(),
+            subscription_group_manager: self.subscription_group_manager.clone(),
+            consumer_filter_manager: self.consumer_filter_manager.clone(),
+            message_store: self.message_store.clone(),
+            pop_revive_service: self.pop_revive_service.clone(),
+            polling_num_table: self.polling_num_table.clone(),
+        }
+    }
+}
+
+impl<MS> PopMessageProcessor<MS> {
+    pub fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        topic_config_manager: Arc<TopicConfigManager>,
+        subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+        consumer_filter_manager: Arc<ConsumerFilterManager>,
+        message_store: ArcMut<MS>,
+        pop_revive_service: ArcMut<PopReviveService<MS>>,
+        polling_num_table: Arc<PollingNumTable>,
+    ) -> Self {
+        Self {
+            broker_config,
+            topic_config_manager,
+            subscription_group_manager,
+            consumer_filter_manager,
+            message_store,
+            pop_revive_service,
+            polling_num_table,
+        }
+    }
+}
 
-impl PopMessageProcessor {
-    fn process_request(
+impl<MS> PopMessageProcessor<MS>
+where
+    MS: MessageStore + Send + Sync + 'static,
+{
+    pub async fn process_request(
+        &mut self,
+        _channel: Channel,
+        ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let request_header = match request.decode_command_custom_header::<PopMessageRequestHeader>()
+        {
+            Ok(header) => header,
+            Err(e) => {
+                return Some(
+                    response
+                        .set_code(ResponseCode::SystemError)
+                        .set_remark(e.to_string()),
+                );
+            }
+        };
+        if !PermName::is_readable(self.broker_config.broker_permission.get()) {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "the broker[{}] pop message is forbidden",
+                        self.broker_config.broker_ip1
+                    )),
+            );
+        }
+        if request_header.max_msg_nums <= 0 || request_header.max_msg_nums > MAX_POP_MSG_NUMS {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "the broker[{}] pop message size must be in (0, {}]",
+                        self.broker_config.broker_ip1, MAX_POP_MSG_NUMS
+                    )),
+            );
+        }
+        let Some(topic_config) = self
+            .topic_config_manager
+            .select_topic_config(&request_header.topic)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::TopicNotExist)
+                    .set_remark(format!(
+                        "topic[{}] not exist, apply first please!",
+                        request_header.topic
+                    )),
+            );
+        };
+        if !PermName::is_readable(topic_config.perm) {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "the topic[{}] pop message is forbidden",
+                        request_header.topic
+                    )),
+            );
+        }
+        if request_header.queue_id >= topic_config.read_queue_nums as i32
+            || request_header.queue_id < -1
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "queueId[{}] is illegal, topic:[{}] topicConfig.readQueueNums:[{}]",
+                        request_header.queue_id, request_header.topic, topic_config.read_queue_nums
+                    )),
+            );
+        }
+        let Some(subscription_group_config) = self
+            .subscription_group_manager
+            .find_subscription_group_config(&request_header.consumer_group)
+        else {
+            return Some(
+                response
+                    .set_code(ResponseCode::SubscriptionGroupNotExist)
+                    .set_remark(format!(
+                        "subscription group [{}] does not exist",
+                        request_header.consumer_group
+                    )),
+            );
+        };
+        if !subscription_group_config.consume_enable() {
+            return Some(
+                response
+                    .set_code(ResponseCode::NoPermission)
+                    .set_remark(format!(
+                        "subscription group no permission, {}",
+                        request_header.consumer_group
+                    )),
+            );
+        }
+        let message_filter = match self.build_message_filter(&request_header) {
+            Ok(filter) => filter,
+            Err(remark) => {
+                return Some(
+                    response
+                        .set_code(ResponseCode::SubscriptionParseFailed)
+                        .set_remark(remark),
+                );
+            }
+        };
+
+        let queue_ids = if request_header.queue_id < 0 {
+            (0..topic_config.read_queue_nums as i32).collect::<Vec<_>>()
+        } else {
+            vec![request_header.queue_id]
+        };
+        let result = self
+            .pop(&request_header, &queue_ids, message_filter.as_ref())
+            .await;
+        if result.found > 0
+            || request_header.poll_time <= 0
+            || !self.broker_config.long_polling_enable.get()
+        {
+            return Some(pop_response(
+                &request_header,
+                result,
+                ResponseCode::PullNotFound,
+            ));
+        }
+
+        let deadline = hold_deadline(
+            request_header.born_time,
+            request_header.poll_time,
+            get_current_millis(),
+        );
+        let opaque = request.opaque();
+        let this = self.clone();
+        tokio::spawn(async move {
+            let topic = request_header.topic.as_str();
+            let group = request_header.consumer_group.as_str();
+            this.polling_num_table
+                .increment(topic, group, request_header.queue_id);
+            let mut result = PopResult {
+                pop_time: get_current_millis() as i64,
+                ..Default::default()
+            };
+            while get_current_millis() < deadline {
+                let wait = (deadline - get_current_millis()).min(POP_CHECK_INTERVAL_MILLIS);
+                tokio::time::sleep(Duration::from_millis(wait)).await;
+                result = this
+                    .pop(&request_header, &queue_ids, message_filter.as_ref())
+                    .await;
+                if result.found > 0 {
+                    break;
+                }
+            }
+            this.polling_num_table
+                .decrement(topic, group, request_header.queue_id);
+            let response = pop_response(&request_header, result, ResponseCode::PollingTimeout)
+                .set_opaque(opaque)
+                .mark_response_type();
+            if let Some(mut ctx) = ctx.upgrade() {
+                ctx.write(response).await;
+            }
+        });
+        None
+    }
+
+    fn build_message_filter(
+        &self,
+        request_header: &PopMessageRequestHeader,
+    ) -> Result<Box<dyn MessageFilter>, String> {
+        let expression = request_header
+            .exp
+            .clone()
+            .filter(|exp| !exp.is_empty())
+            .unwrap_or_else(|| CheetahString::from_static_str("*"));
+        let subscription_data = FilterAPI::build(
+            &request_header.topic,
+            &expression,
+            request_header.exp_type.clone(),
+        )
+        .map_err(|e| format!("parse the consumer's subscription failed, {e}"))?;
+        let consumer_filter_data =
+            if ExpressionType::is_tag_type(request_header.exp_type.as_deref()) {
+                None
+            } else {
+                Some(
+                    ConsumerFilterManager::build(
+                        request_header.topic.clone(),
+                        request_header.consumer_group.clone(),
+                        Some(expression),
+                        request_header.exp_type.clone(),
+                        get_current_millis(),
+                    )
+                    .ok_or_else(|| "parse the consumer's subscription failed".to_string())?,
+                )
+            };
+        Ok(Box::new(ExpressionMessageFilter::new(
+            Some(subscription_data),
+            consumer_filter_data,
+            self.consumer_filter_manager.clone(),
+        )))
+    }
+
+    /// Pops from the queues of the topic and from the group's pop retry topic, the retry topic
+    /// goes first once in a while so retried messages are not starved by a busy topic.
+    async fn pop(
         &self,
-        _ctx: ConnectionHandlerContext,
-        _request: RemotingCommand,
-    ) -> RemotingCommand {
-        todo!()
+        request_header: &PopMessageRequestHeader,
+        queue_ids: &[i32],
+        message_filter: &dyn MessageFilter,
+    ) -> PopResult {
+        let pop_time = get_current_millis() as i64;
+        let group = &request_header.consumer_group;
+        let retry_topic = CheetahString::from_string(KeyBuilder::build_pop_retry_topic_v1(
+            &request_header.topic,
+            group,
+        ));
+        let has_retry_topic = self
+            .topic_config_manager
+            .select_topic_config(&retry_topic)
+            .is_some();
+        let (retry_first, start) = {
+            let mut rng = rand::thread_rng();
+            (rng.gen_range(0..5) == 0, rng.gen_range(0..queue_ids.len()))
+        };
+        let mut result = PopResult {
+            pop_time,
+            ..Default::default()
+        };
+        if has_retry_topic && retry_first {
+            self.pop_queue(request_header, &retry_topic, 0, pop_time, None, &mut result)
+                .await;
+        }
+        for i in 0..queue_ids.len() {
+            let queue_id = queue_ids[(start + i) % queue_ids.len()];
+            self.pop_queue(
+                request_header,
+                &request_header.topic,
+                queue_id,
+                pop_time,
+                Some(message_filter),
+                &mut result,
+            )
+            .await;
+        }
+        if has_retry_topic && !retry_first {
+            self.pop_queue(request_header, &retry_topic, 0, pop_time, None, &mut result)
+                .await;
+        }
+        result
+    }
+
+    /// Pops up to the messages still missing in `result` from one queue, skipped while another
+    /// request pops from the same queue.
+    async fn pop_queue(
+        &self,
+        request_header: &PopMessageRequestHeader,
+        topic: &CheetahString,
+        queue_id: i32,
+        pop_time: i64,
+        message_filter: Option<&dyn MessageFilter>,
+        result: &mut PopResult,
+    ) {
+        let group = &request_header.consumer_group;
+        let Some(mut state) = self
+            .pop_revive_service
+            .try_lock_queue(group, topic, queue_id)
+        else {
+            return;
+        };
+        let offset = self.pop_revive_service.pop_offset(
+            &mut state,
+            group,
+            topic,
+            queue_id,
+            request_header.init_mode,
+        );
+        let max_offset = self.message_store.get_max_offset_in_queue(topic, queue_id);
+        let remaining = request_header.max_msg_nums - result.found;
+        if remaining <= 0 {
+            result.rest_num += (max_offset - offset).max(0);
+            return;
+        }
+        let Some(get_message_result) = self
+            .message_store
+            .get_message(
+                group,
+                topic,
+                queue_id,
+                offset,
+                remaining,
+                i32::MAX,
+                message_filter,
+            )
+            .await
+        else {
+            result.rest_num += (max_offset - offset).max(0);
+            return;
+        };
+        let mut msg_offsets = Vec::with_capacity(get_message_result.message_mapped_list().len());
+        for (msg, queue_offset) in get_message_result
+            .message_mapped_list()
+            .iter()
+            .zip(get_message_result.message_queue_offset())
+        {
+            let Some(mapped_file) = msg.mapped_file.as_ref() else {
+                continue;
+            };
+            // start_offset is the physical offset, relative to the first file of the log
+            let pos = (msg.start_offset - mapped_file.get_file_from_offset()) as usize;
+            result
+                .body
+                .extend_from_slice(&mapped_file.get_mapped_file()[pos..pos + msg.size as usize]);
+            state.add_inflight(
+                *queue_offset as i64,
+                InflightMessage {
+                    pop_time,
+                    invisible_time: request_header.invisible_time,
+                    commit_log_offset: msg.start_offset as i64,
+                },
+            );
+            msg_offsets.push(*queue_offset as i64);
+        }
+        if matches!(
+            get_message_result.status(),
+            Some(
+                GetMessageStatus::Found
+                    | GetMessageStatus::NoMatchedMessage
+                    | GetMessageStatus::MessageWasRemoving
+                    | GetMessageStatus::OffsetFoundNull
+                    | GetMessageStatus::OffsetTooSmall
+                    | GetMessageStatus::OffsetOverflowBadly
+            )
+        ) {
+            state.pop_offset = get_message_result.next_begin_offset();
+        }
+        result.rest_num += (max_offset - state.pop_offset).max(0);
+        if !msg_offsets.is_empty() {
+            result.found += msg_offsets.len() as i32;
+            ExtraInfoUtil::build_start_offset_info(
+                &mut result.start_offset_info,
+                topic,
+                queue_id,
+                offset,
+            );
+            ExtraInfoUtil::build_msg_offset_info(
+                &mut result.msg_offset_info,
+                topic,
+                queue_id,
+                msg_offsets,
+            );
+        }
+        self.pop_revive_service
+            .commit_offset(&state, group, topic, queue_id);
+    }
+}
+
+fn pop_response(
+    request_header: &PopMessageRequestHeader,
+    result: PopResult,
+    not_found_code: ResponseCode,
+) -> RemotingCommand {
+    let response_header = PopMessageResponseHeader {
+        pop_time: result.pop_time as u64,
+        invisible_time: request_header.invisible_time as u64,
+        revive_qid: POP_REVIVE_QUEUE_ID as u32,
+        rest_num: result.rest_num as u64,
+        start_offset_info: Some(CheetahString::from_string(result.start_offset_info)),
+        msg_offset_info: Some(CheetahString::from_string(result.msg_offset_info)),
+        order_count_info: None,
+    };
+    let response = RemotingCommand::create_response_command();
+    if result.found == 0 {
+        return response
+            .set_code(not_found_code)
+            .set_remark("no message found")
+            .set_command_custom_header(response_header);
     }
+    response
+        .set_command_custom_header(response_header)
+        .set_body(result.body.freeze())
 }
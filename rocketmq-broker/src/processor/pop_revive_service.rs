@@ -0,0 +1,477 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::constant::consume_init_mode::ConsumeInitMode;
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::key_builder::KeyBuilder;
+use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::MessageAccessor::MessageAccessor;
+use rocketmq_common::MessageDecoder::message_properties_to_string;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::base::message_status_enum::PutMessageStatus;
+use rocketmq_store::log_file::MessageStore;
+use tokio::sync::Notify;
+use tokio::sync::OwnedMutexGuard;
+use tracing::info;
+use tracing::warn;
+
+use crate::offset::manager::consumer_offset_manager::ConsumerOffsetManager;
+use crate::topic::manager::topic_config_manager::TopicConfigManager;
+
+/// Revive queue id handed out with every popped message, this broker keeps the check points in
+/// memory instead of writing them to a revive topic.
+pub(crate) const POP_REVIVE_QUEUE_ID: i32 = 0;
+
+/// How often the in-flight messages are checked for an expired invisible time.
+const REVIVE_INTERVAL_MILLIS: u64 = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PopQueueKey {
+    group: CheetahString,
+    topic: CheetahString,
+    queue_id: i32,
+}
+
+/// A popped message which was neither acked nor revived yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct InflightMessage {
+    pub pop_time: i64,
+    pub invisible_time: i64,
+    pub commit_log_offset: i64,
+}
+
+impl InflightMessage {
+    fn is_expired(&self, now: i64) -> bool {
+        self.pop_time + self.invisible_time <= now
+    }
+}
+
+/// Pop progress of a group on one queue.
+#[derive(Debug)]
+pub(crate) struct PopQueueState {
+    /// Next queue offset to pop, -1 until it is loaded from the committed offset.
+    pub pop_offset: i64,
+    /// In-flight messages by queue offset.
+    inflight: BTreeMap<i64, InflightMessage>,
+}
+
+impl Default for PopQueueState {
+    fn default() -> Self {
+        Self {
+            pop_offset: -1,
+            inflight: BTreeMap::new(),
+        }
+    }
+}
+
+impl PopQueueState {
+    pub fn add_inflight(&mut self, queue_offset: i64, message: InflightMessage) {
+        self.inflight.insert(queue_offset, message);
+    }
+
+    /// Removes the message popped at `pop_time`, a stale pop time means the message was revived
+    /// or its invisible time changed since.
+    fn ack(&mut self, queue_offset: i64, pop_time: i64) -> bool {
+        match self.inflight.get(&queue_offset) {
+            Some(message) if message.pop_time == pop_time => {
+                self.inflight.remove(&queue_offset);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn change_invisible_time(
+        &mut self,
+        queue_offset: i64,
+        pop_time: i64,
+        new_pop_time: i64,
+        invisible_time: i64,
+    ) -> bool {
+        match self.inflight.get_mut(&queue_offset) {
+            Some(message) if message.pop_time == pop_time => {
+                message.pop_time = new_pop_time;
+                message.invisible_time = invisible_time;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expired(&self, now: i64) -> Vec<(i64, InflightMessage)> {
+        self.inflight
+            .iter()
+            .filter(|(_, message)| message.is_expired(now))
+            .map(|(offset, message)| (*offset, *message))
+            .collect()
+    }
+
+    /// The offset the group may commit, nothing below the oldest in-flight message.
+    fn commit_offset(&self) -> i64 {
+        self.inflight
+            .keys()
+            .next()
+            .map_or(self.pop_offset, |offset| (*offset).min(self.pop_offset))
+    }
+}
+
+/// Keeps the check points of popped messages, commits the consume offsets of popping groups and
+/// puts messages which were not acked within their invisible time to the group's pop retry
+/// topic, where they are popped again.
+pub struct PopReviveService<MS> {
+    topic_config_manager: TopicConfigManager,
+    consumer_offset_manager: Arc<ConsumerOffsetManager>,
+    message_store: ArcMut<MS>,
+    store_host: SocketAddr,
+    queue_table: parking_lot::Mutex<HashMap<PopQueueKey, Arc<tokio::sync::Mutex<PopQueueState>>>>,
+    shutdown: Arc<Notify>,
+}
+
+impl<MS> PopReviveService<MS> {
+    pub fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        topic_config_manager: TopicConfigManager,
+        consumer_offset_manager: Arc<ConsumerOffsetManager>,
+        message_store: ArcMut<MS>,
+    ) -> Self {
+        let store_host = format!("{}:{}", broker_config.broker_ip1, broker_config.listen_port)
+            .parse::<SocketAddr>()
+            .unwrap();
+        Self {
+            topic_config_manager,
+            consumer_offset_manager,
+            message_store,
+            store_host,
+            queue_table: parking_lot::Mutex::new(HashMap::new()),
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    fn queue_state(
+        &self,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+    ) -> Arc<tokio::sync::Mutex<PopQueueState>> {
+        let key = PopQueueKey {
+            group: group.clone(),
+            topic: topic.clone(),
+            queue_id,
+        };
+        self.queue_table.lock().entry(key).or_default().clone()
+    }
+
+    /// Locks the queue for popping, `None` while another request pops from it.
+    pub fn try_lock_queue(
+        &self,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+    ) -> Option<OwnedMutexGuard<PopQueueState>> {
+        self.queue_state(group, topic, queue_id)
+            .try_lock_owned()
+            .ok()
+    }
+
+    pub fn shutdown(&self) {
+        self.shutdown.notify_waiters();
+    }
+}
+
+impl<MS> PopReviveService<MS>
+where
+    MS: MessageStore + Send + Sync + 'static,
+{
+    pub fn start(&self, this: ArcMut<Self>) {
+        let shutdown = self.shutdown.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_millis(REVIVE_INTERVAL_MILLIS)) => {}
+                    _ = shutdown.notified() => {
+                        info!("PopReviveService: shutdown..........");
+                        break;
+                    }
+                }
+                this.revive().await;
+            }
+        });
+    }
+
+    /// The offset to pop from, loaded from the committed offset of the group the first time
+    /// the queue is popped.
+    pub fn pop_offset(
+        &self,
+        state: &mut PopQueueState,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+        init_mode: i32,
+    ) -> i64 {
+        if state.pop_offset < 0 {
+            let offset = self
+                .consumer_offset_manager
+                .query_offset(group, topic, queue_id);
+            state.pop_offset = if offset >= 0 {
+                offset
+            } else {
+                self.init_offset(topic, queue_id, init_mode)
+            };
+        }
+        let min_offset = self.message_store.get_min_offset_in_queue(topic, queue_id);
+        if state.pop_offset < min_offset {
+            state.pop_offset = min_offset;
+        }
+        state.pop_offset
+    }
+
+    /// Where a group without a committed offset starts, retry topics always from the oldest
+    /// message, as do queues whose first message is still in memory.
+    fn init_offset(&self, topic: &CheetahString, queue_id: i32, init_mode: i32) -> i64 {
+        let min_offset = self.message_store.get_min_offset_in_queue(topic, queue_id);
+        if init_mode == ConsumeInitMode::MIN || topic.starts_with(mix_all::RETRY_GROUP_TOPIC_PREFIX)
+        {
+            return min_offset;
+        }
+        if min_offset <= 0
+            && self
+                .message_store
+                .check_in_mem_by_consume_offset(topic, queue_id, 0, 1)
+        {
+            return 0;
+        }
+        self.message_store.get_max_offset_in_queue(topic, queue_id)
+    }
+
+    /// Commits the offset of the queue below its oldest in-flight message.
+    pub fn commit_offset(
+        &self,
+        state: &PopQueueState,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+    ) {
+        let offset = state.commit_offset();
+        if offset >= 0 {
+            self.consumer_offset_manager.commit_offset(
+                self.store_host,
+                group,
+                topic,
+                queue_id,
+                offset,
+            );
+        }
+    }
+
+    /// Acks the message popped at `pop_time`, `false` when it is no longer in flight.
+    pub async fn ack(
+        &self,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+        queue_offset: i64,
+        pop_time: i64,
+    ) -> bool {
+        let state = self.queue_state(group, topic, queue_id);
+        let mut state = state.lock().await;
+        let acked = state.ack(queue_offset, pop_time);
+        if acked {
+            self.commit_offset(&state, group, topic, queue_id);
+        }
+        acked
+    }
+
+    /// Makes the message invisible for `invisible_time` from now on and returns its new pop
+    /// time, `None` when it is no longer in flight.
+    pub async fn change_invisible_time(
+        &self,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+        queue_offset: i64,
+        pop_time: i64,
+        invisible_time: i64,
+    ) -> Option<i64> {
+        let state = self.queue_state(group, topic, queue_id);
+        let mut state = state.lock().await;
+        let new_pop_time = get_current_millis() as i64;
+        state
+            .change_invisible_time(queue_offset, pop_time, new_pop_time, invisible_time)
+            .then_some(new_pop_time)
+    }
+
+    async fn revive(&self) {
+        let queues = self
+            .queue_table
+            .lock()
+            .iter()
+            .map(|(key, state)| (key.clone(), state.clone()))
+            .collect::<Vec<_>>();
+        for (key, state) in queues {
+            let mut state = state.lock().await;
+            let expired = state.expired(get_current_millis() as i64);
+            if expired.is_empty() {
+                continue;
+            }
+            for (queue_offset, message) in expired {
+                if self.revive_message(&key, queue_offset, &message).await {
+                    state.inflight.remove(&queue_offset);
+                }
+            }
+            self.commit_offset(&state, &key.group, &key.topic, key.queue_id);
+        }
+    }
+
+    /// Puts the message to the pop retry topic of the group, `false` when it has to be tried
+    /// again later.
+    async fn revive_message(
+        &self,
+        key: &PopQueueKey,
+        queue_offset: i64,
+        message: &InflightMessage,
+    ) -> bool {
+        let Some(msg_ext) = self
+            .message_store
+            .look_message_by_offset(message.commit_log_offset)
+        else {
+            warn!(
+                "revive message not found, group={} topic={} queueId={} offset={}",
+                key.group, key.topic, key.queue_id, queue_offset
+            );
+            return true;
+        };
+        let retry_topic = if key.topic.starts_with(mix_all::RETRY_GROUP_TOPIC_PREFIX) {
+            key.topic.clone()
+        } else {
+            CheetahString::from_string(KeyBuilder::build_pop_retry_topic_v1(&key.topic, &key.group))
+        };
+        if self
+            .topic_config_manager
+            .clone()
+            .create_topic_in_send_message_back_method(
+                &retry_topic,
+                1,
+                PermName::PERM_WRITE | PermName::PERM_READ,
+                false,
+                0,
+            )
+            .is_none()
+        {
+            warn!("create pop retry topic {} failed", retry_topic);
+            return false;
+        }
+
+        let mut msg_inner = MessageExtBrokerInner::default();
+        msg_inner.set_topic(retry_topic);
+        if let Some(body) = msg_ext.get_body() {
+            msg_inner.set_body(body.clone());
+        }
+        msg_inner.set_flag(msg_ext.get_flag());
+        MessageAccessor::set_properties(&mut msg_inner, msg_ext.get_properties().clone());
+        if msg_ext.reconsume_times == 0
+            || msg_inner
+                .get_property(&CheetahString::from_static_str(
+                    MessageConst::PROPERTY_FIRST_POP_TIME,
+                ))
+                .is_none()
+        {
+            MessageAccessor::put_property(
+                &mut msg_inner,
+                CheetahString::from_static_str(MessageConst::PROPERTY_FIRST_POP_TIME),
+                CheetahString::from_string(message.pop_time.to_string()),
+            );
+        }
+        msg_inner.properties_string = message_properties_to_string(msg_inner.get_properties());
+        msg_inner.tags_code = MessageExtBrokerInner::tags_string_to_tags_code(
+            msg_ext.get_tags().unwrap_or_default().as_str(),
+        );
+        msg_inner.message_ext_inner.queue_id = 0;
+        msg_inner.message_ext_inner.sys_flag = msg_ext.sys_flag;
+        msg_inner.message_ext_inner.born_timestamp = msg_ext.born_timestamp;
+        msg_inner.message_ext_inner.born_host = msg_ext.born_host;
+        msg_inner.message_ext_inner.store_host = self.store_host;
+        msg_inner.message_ext_inner.reconsume_times = msg_ext.reconsume_times + 1;
+
+        let put_message_result = self.message_store.clone().put_message(msg_inner).await;
+        match put_message_result.put_message_status() {
+            PutMessageStatus::PutOk
+            | PutMessageStatus::FlushDiskTimeout
+            | PutMessageStatus::FlushSlaveTimeout
+            | PutMessageStatus::SlaveNotAvailable => true,
+            status => {
+                warn!(
+                    "revive message of group {} to the retry topic failed, {}",
+                    key.group, status
+                );
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inflight(pop_time: i64, invisible_time: i64) -> InflightMessage {
+        InflightMessage {
+            pop_time,
+            invisible_time,
+            commit_log_offset: 0,
+        }
+    }
+
+    #[test]
+    fn commit_offset_stops_at_oldest_inflight_message() {
+        let mut state = PopQueueState {
+            pop_offset: 10,
+            ..Default::default()
+        };
+        assert_eq!(state.commit_offset(), 10);
+        state.add_inflight(7, inflight(1000, 60000));
+        state.add_inflight(9, inflight(1000, 60000));
+        assert_eq!(state.commit_offset(), 7);
+        assert!(state.ack(7, 1000));
+        assert_eq!(state.commit_offset(), 9);
+        assert!(state.ack(9, 1000));
+        assert_eq!(state.commit_offset(), 10);
+    }
+
+    #[test]
+    fn stale_pop_time_neither_acks_nor_changes_invisible_time() {
+        let mut state = PopQueueState::default();
+        state.add_inflight(3, inflight(1000, 60000));
+        assert!(state.change_invisible_time(3, 1000, 2000, 5000));
+        assert!(!state.ack(3, 1000));
+        assert!(!state.change_invisible_time(3, 1000, 3000, 5000));
+        assert_eq!(state.expired(6999), Vec::new());
+        assert_eq!(state.expired(7000), vec![(3, inflight(2000, 5000))]);
+        assert!(state.ack(3, 2000));
+        assert!(state.expired(i64::MAX).is_empty());
+    }
+}
@@ -22,6 +22,10 @@ use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::constant::PermName;
 use rocketmq_common::common::filter::expression_type::ExpressionType;
 use rocketmq_common::common::sys_flag::pull_sys_flag::PullSysFlag;
+use rocketmq_common::common::telemetry::messaging_span::attributes;
+use rocketmq_common::common::telemetry::messaging_span::MessagingSpan;
+use rocketmq_common::common::telemetry::messaging_span::SpanKind;
+use rocketmq_common::common::telemetry::tracer_provider::start_span;
 use rocketmq_common::common::FAQUrl;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::code::request_code::RequestCode;
@@ -71,7 +75,7 @@ use crate::topic::manager::topic_queue_mapping_manager::TopicQueueMappingManager
 
 pub struct PullMessageProcessor<MS> {
     pull_message_result_handler: ArcMut<Box<dyn PullMessageResultHandler>>,
-    broker_config: Arc<BrokerConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
     topic_config_manager: Arc<TopicConfigManager>,
     topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
@@ -91,7 +95,7 @@ pub struct PullMessageProcessor<MS> {
 impl<MS> PullMessageProcessor<MS> {
     pub fn new(
         pull_message_result_handler: ArcMut<Box<dyn PullMessageResultHandler>>,
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
         topic_config_manager: Arc<TopicConfigManager>,
         topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
@@ -100,6 +104,7 @@ impl<MS> PullMessageProcessor<MS> {
         consumer_offset_manager: Arc<ConsumerOffsetManager>,
         broadcast_offset_manager: Arc<BroadcastOffsetManager>,
         message_store: ArcMut<MS>,
+        cold_data_cg_ctr_service: Arc<ColdDataCgCtrService>,
         broker_outer_api: Arc<BrokerOuterAPI>,
     ) -> Self {
         let cpus = num_cpus::get();
@@ -114,7 +119,7 @@ impl<MS> PullMessageProcessor<MS> {
             consumer_offset_manager,
             broadcast_offset_manager,
             message_store,
-            cold_data_cg_ctr_service: Arc::new(Default::default()),
+            cold_data_cg_ctr_service,
             broker_outer_api,
             write_message_runtime: Arc::new(RocketMQRuntime::new_multi(
                 cpus,
@@ -351,8 +356,58 @@ where
         request_code: RequestCode,
         request: RemotingCommand,
     ) -> Option<RemotingCommand> {
-        self.process_request_inner(request_code, channel, ctx, request, true, true)
-            .await
+        let pull_span = self.start_pull_span(&request);
+        let response = self
+            .process_request_inner(request_code, channel, ctx, request, true, true)
+            .await;
+        if let Some(mut span) = pull_span {
+            match response.as_ref() {
+                Some(response) if response.code() != ResponseCode::Success as i32 => {
+                    span.record_error(
+                        response
+                            .remark()
+                            .map_or("pull message failed", |remark| remark.as_str()),
+                    );
+                }
+                _ => {}
+            }
+            span.end();
+        }
+        response
+    }
+
+    /// Starts the server span of a pull request when telemetry is enabled. A suspended pull
+    /// ends the span when the request is parked.
+    fn start_pull_span(&self, request: &RemotingCommand) -> Option<Box<dyn MessagingSpan>> {
+        if !self.broker_config.enable_telemetry {
+            return None;
+        }
+        let request_header = request
+            .decode_command_custom_header_fast::<PullMessageRequestHeader>()
+            .ok()?;
+        let mut span = start_span(
+            format!("{} pull", request_header.topic),
+            SpanKind::Server,
+            None,
+        );
+        span.set_attribute(attributes::MESSAGING_OPERATION, "receive".to_string());
+        span.set_attribute(
+            attributes::MESSAGING_DESTINATION_NAME,
+            request_header.topic.to_string(),
+        );
+        span.set_attribute(
+            attributes::MESSAGING_CONSUMER_GROUP,
+            request_header.consumer_group.to_string(),
+        );
+        span.set_attribute(
+            attributes::MESSAGING_QUEUE_ID,
+            request_header.queue_id.to_string(),
+        );
+        span.set_attribute(
+            attributes::MESSAGING_QUEUE_OFFSET,
+            request_header.queue_offset.to_string(),
+        );
+        Some(span)
     }
 
     async fn process_request_inner(
@@ -373,7 +428,7 @@ where
         //info!("receive pull message request: {:?}", request_header);
         let mut response_header = PullMessageResponseHeader::default();
 
-        if !PermName::is_readable(self.broker_config.broker_permission) {
+        if !PermName::is_readable(self.broker_config.broker_permission.get()) {
             response_header.forbidden_type = Some(ForbiddenType::BROKER_FORBIDDEN);
             return Some(
                 response
@@ -690,12 +745,25 @@ where
             ))
         };
 
-        //ColdDataFlow not implement
-
         cfg_if::cfg_if! {
             if #[cfg(feature = "local_file_store")] {
-                if self.cold_data_cg_ctr_service.is_cg_need_cold_data_flow_ctr(request_header.consumer_group.as_str()) {
-                    unimplemented!("ColdDataFlow not implement")
+                if self.cold_data_cg_ctr_service.is_cg_need_cold_data_flow_ctr(request_header.consumer_group.as_str())
+                    && !self.message_store.check_in_mem_by_consume_offset(
+                        request_header.topic.as_ref(),
+                        request_header.queue_id,
+                        request_header.queue_offset,
+                        request_header.max_msg_nums,
+                    )
+                {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemBusy)
+                            .set_remark(format!(
+                                "[PULL] the consumer group {} reads too much cold data, try \
+                                 again later",
+                                request_header.consumer_group
+                            )),
+                    );
                 }
             }
         }
@@ -754,6 +822,10 @@ where
             }
         };
         if let Some(get_message_result) = get_message_result {
+            self.cold_data_cg_ctr_service.cold_acc(
+                request_header.consumer_group.as_str(),
+                get_message_result.cold_data_sum(),
+            );
             return self.pull_message_result_handler.handle(
                 get_message_result,
                 request,
@@ -19,15 +19,19 @@ use crate::load_balance::message_request_mode_manager::MessageRequestModeManager
  use rocketmq_client_rust::consumer::allocate_message_queue_strategy::AllocateMessageQueueStrategy;
  use rocketmq_client_rust::consumer::rebalance_strategy::allocate_message_queue_averagely::AllocateMessageQueueAveragely;
  use rocketmq_client_rust::consumer::rebalance_strategy::allocate_message_queue_averagely_by_circle::AllocateMessageQueueAveragelyByCircle;
+use rocketmq_client_rust::consumer::rebalance_strategy::allocate_message_queue_by_lag::AllocateMessageQueueByLag;
  use rocketmq_common::common::config_manager::ConfigManager;
  use rocketmq_remoting::code::request_code::RequestCode;
  use rocketmq_remoting::net::channel::Channel;
  use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
  use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
  use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use rocketmq_store::log_file::MessageStore;
+use rocketmq_store::message_store::default_message_store::DefaultMessageStore;
  use std::collections::{HashMap, HashSet};
  use std::sync::Arc;
 use tracing::{info, warn};
+use rocketmq_rust::ArcMut;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::message::message_enum::MessageRequestMode;
 use rocketmq_common::common::message::message_queue::MessageQueue;
@@ -43,29 +47,36 @@ use rocketmq_remoting::protocol::{RemotingDeserializable, RemotingSerializable};
 use crate::client::manager::consumer_manager::ConsumerManager;
 use crate::broker_error::BrokerError;
 use crate::broker_error::BrokerError::IllegalArgumentError;
+use crate::offset::manager::consumer_offset_manager::ConsumerOffsetManager;
 use crate::topic::manager::topic_route_info_manager::TopicRouteInfoManager;
 use crate::Result;
 
 pub struct QueryAssignmentProcessor {
     message_request_mode_manager: MessageRequestModeManager,
     load_strategy: HashMap<CheetahString, Arc<dyn AllocateMessageQueueStrategy>>,
-    message_store_config: Arc<MessageStoreConfig>,
-    broker_config: Arc<BrokerConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     topic_route_info_manager: Arc<TopicRouteInfoManager>,
     consumer_manager: Arc<ConsumerManager>,
+    consumer_offset_manager: Arc<ConsumerOffsetManager>,
+    message_store: ArcMut<DefaultMessageStore>,
 }
 
 impl QueryAssignmentProcessor {
     pub fn new(
-        message_store_config: Arc<MessageStoreConfig>,
-        broker_config: Arc<BrokerConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         topic_route_info_manager: Arc<TopicRouteInfoManager>,
         consumer_manager: Arc<ConsumerManager>,
+        consumer_offset_manager: Arc<ConsumerOffsetManager>,
+        message_store: ArcMut<DefaultMessageStore>,
     ) -> Self {
         let allocate_message_queue_averagely: Arc<dyn AllocateMessageQueueStrategy> =
             Arc::new(AllocateMessageQueueAveragely);
         let allocate_message_queue_averagely_by_circle: Arc<dyn AllocateMessageQueueStrategy> =
             Arc::new(AllocateMessageQueueAveragelyByCircle);
+        let allocate_message_queue_by_lag: Arc<dyn AllocateMessageQueueStrategy> =
+            Arc::new(AllocateMessageQueueByLag::default());
         let mut load_strategy = HashMap::new();
         load_strategy.insert(
             CheetahString::from_static_str(allocate_message_queue_averagely.get_name()),
@@ -75,6 +86,10 @@ impl QueryAssignmentProcessor {
             CheetahString::from_static_str(allocate_message_queue_averagely_by_circle.get_name()),
             allocate_message_queue_averagely_by_circle,
         );
+        load_strategy.insert(
+            CheetahString::from_static_str(allocate_message_queue_by_lag.get_name()),
+            allocate_message_queue_by_lag,
+        );
         let manager = MessageRequestModeManager::new(message_store_config.clone());
         let _ = manager.load();
         Self {
@@ -84,6 +99,8 @@ impl QueryAssignmentProcessor {
             broker_config,
             topic_route_info_manager,
             consumer_manager,
+            consumer_offset_manager,
+            message_store,
         }
     }
 }
@@ -271,6 +288,17 @@ impl QueryAssignmentProcessor {
                             cid_all.as_slice(),
                             set_message_request_mode_request_body.pop_share_queue_num,
                         )
+                    } else if strategy.get_name() == AllocateMessageQueueByLag::NAME {
+                        let lag = self.queue_lag(consumer_group, mq_all.as_slice());
+                        match AllocateMessageQueueByLag::with_lag(lag).allocate(
+                            consumer_group,
+                            client_id,
+                            mq_all.as_slice(),
+                            cid_all.as_slice(),
+                        ) {
+                            Ok(value) => Ok(value.into_iter().collect::<HashSet<MessageQueue>>()),
+                            Err(e) => Err(BrokerError::ClientError(e)),
+                        }
                     } else {
                         match strategy.allocate(
                             consumer_group,
@@ -290,6 +318,32 @@ impl QueryAssignmentProcessor {
         }
     }
 
+    /// Backlog of `consumer_group` on the queues hosted by this broker.
+    ///
+    /// Queues of other brokers are left out and weigh nothing in the lag-aware allocation.
+    fn queue_lag(
+        &self,
+        consumer_group: &CheetahString,
+        mq_all: &[MessageQueue],
+    ) -> HashMap<MessageQueue, i64> {
+        mq_all
+            .iter()
+            .filter(|mq| mq.get_broker_name() == &self.broker_config.broker_name)
+            .map(|mq| {
+                let topic = mq.get_topic_cs();
+                let queue_id = mq.get_queue_id();
+                let max_offset = self.message_store.get_max_offset_in_queue(topic, queue_id);
+                let mut consumer_offset =
+                    self.consumer_offset_manager
+                        .query_offset(consumer_group, topic, queue_id);
+                if consumer_offset < 0 {
+                    consumer_offset = self.message_store.get_min_offset_in_queue(topic, queue_id);
+                }
+                (mq.clone(), (max_offset - consumer_offset).max(0))
+            })
+            .collect()
+    }
+
     pub fn allocate_for_pop(
         &self,
         strategy: &Arc<dyn AllocateMessageQueueStrategy>,
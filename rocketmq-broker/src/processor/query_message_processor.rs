@@ -14,12 +14,21 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use std::sync::Arc;
 
+use bytes::Buf;
+use bytes::BytesMut;
+use cheetah_string::CheetahString;
+use rocketmq_common::common::mix_all::TOOLS_CONSUMER_GROUP;
 use rocketmq_common::common::mix_all::UNIQUE_MSG_QUERY_FLAG;
+use rocketmq_common::MessageDecoder::BLANK_MAGIC_CODE;
+use rocketmq_common::MessageDecoder::MESSAGE_MAGIC_CODE;
+use rocketmq_common::MessageDecoder::MESSAGE_MAGIC_CODE_POSITION;
+use rocketmq_common::MessageDecoder::MESSAGE_MAGIC_CODE_V2;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::query_message_by_offset_range_request_header::QueryMessageByOffsetRangeRequestHeader;
+use rocketmq_remoting::protocol::header::query_message_by_offset_range_response_header::QueryMessageByOffsetRangeResponseHeader;
 use rocketmq_remoting::protocol::header::query_message_request_header::QueryMessageRequestHeader;
 use rocketmq_remoting::protocol::header::query_message_response_header::QueryMessageResponseHeader;
 use rocketmq_remoting::protocol::header::view_message_request_header::ViewMessageRequestHeader;
@@ -31,12 +40,15 @@ use rocketmq_store::log_file::MessageStore;
 
 #[derive(Default)]
 pub struct QueryMessageProcessor<MS> {
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     message_store: ArcMut<MS>,
 }
 
 impl<MS> QueryMessageProcessor<MS> {
-    pub fn new(message_store_config: Arc<MessageStoreConfig>, message_store: ArcMut<MS>) -> Self {
+    pub fn new(
+        message_store_config: ArcMut<MessageStoreConfig>,
+        message_store: ArcMut<MS>,
+    ) -> Self {
         Self {
             message_store_config,
             message_store,
@@ -58,6 +70,10 @@ where
         match request_code {
             RequestCode::QueryMessage => self.query_message(channel, ctx, request).await,
             RequestCode::ViewMessageById => self.view_message_by_id(channel, ctx, request).await,
+            RequestCode::QueryMessageByOffsetRange => {
+                self.query_message_by_offset_range(channel, ctx, request)
+                    .await
+            }
             _ => None,
         }
     }
@@ -119,9 +135,17 @@ where
         request: RemotingCommand,
     ) -> Option<RemotingCommand> {
         let mut response = RemotingCommand::create_response_command();
-        let request_header = request
-            .decode_command_custom_header::<ViewMessageRequestHeader>()
-            .unwrap();
+        let request_header =
+            match request.decode_command_custom_header::<ViewMessageRequestHeader>() {
+                Ok(header) => header,
+                Err(e) => {
+                    return Some(
+                        response
+                            .set_code(ResponseCode::SystemError)
+                            .set_remark(e.to_string()),
+                    );
+                }
+            };
         let select_mapped_buffer_result = self
             .message_store
             .select_one_message_by_offset(request_header.offset)
@@ -137,9 +161,164 @@ where
             response
                 .set_code(ResponseCode::SystemError)
                 .set_remark(format!(
-                    "can not find message by offset: {}",
+                    "can not find message by the offset, {}",
                     request_header.offset
                 )),
         )
     }
+
+    async fn query_message_by_offset_range(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let mut response = RemotingCommand::create_response_command_with_header(
+            QueryMessageByOffsetRangeResponseHeader::default(),
+        );
+        let request_header = match request
+            .decode_command_custom_header::<QueryMessageByOffsetRangeRequestHeader>()
+        {
+            Ok(header) => header,
+            Err(e) => {
+                return Some(
+                    response
+                        .set_code(ResponseCode::SystemError)
+                        .set_remark(e.to_string()),
+                );
+            }
+        };
+        if request_header.start_offset < 0
+            || request_header.end_offset < request_header.start_offset
+            || request_header.max_msg_nums <= 0
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "invalid offset range [{}, {}) or max message number {}",
+                        request_header.start_offset,
+                        request_header.end_offset,
+                        request_header.max_msg_nums
+                    )),
+            );
+        }
+        let read = if request_header.by_commit_log_offset {
+            self.read_commit_log_range(&request_header).await
+        } else {
+            self.read_consume_queue_range(&request_header).await
+        };
+        let (body, next_offset, max_offset) = match read {
+            Ok(read) => read,
+            Err(remark) => {
+                return Some(
+                    response
+                        .set_code(ResponseCode::SystemError)
+                        .set_remark(remark),
+                );
+            }
+        };
+        let response_header = response
+            .read_custom_header_mut::<QueryMessageByOffsetRangeResponseHeader>()
+            .unwrap();
+        response_header.next_offset = next_offset;
+        response_header.max_offset = max_offset;
+        if !body.is_empty() {
+            response.set_body_mut_ref(body.freeze());
+        }
+        Some(response)
+    }
+
+    /// Reads the messages of a queue between two consume queue offsets, returning them with the
+    /// next consume queue offset and the max offset of the queue.
+    async fn read_consume_queue_range(
+        &self,
+        request_header: &QueryMessageByOffsetRangeRequestHeader,
+    ) -> Result<(BytesMut, i64, i64), String> {
+        let max_offset = self
+            .message_store
+            .get_max_offset_in_queue(&request_header.topic, request_header.queue_id);
+        let end_offset = request_header.end_offset.min(max_offset);
+        let mut body = BytesMut::new();
+        if request_header.start_offset >= end_offset {
+            return Ok((body, request_header.start_offset, max_offset));
+        }
+        let max_msg_nums = (end_offset - request_header.start_offset)
+            .min(request_header.max_msg_nums as i64) as i32;
+        let group = CheetahString::from_static_str(TOOLS_CONSUMER_GROUP);
+        let Some(get_message_result) = self
+            .message_store
+            .get_message(
+                &group,
+                &request_header.topic,
+                request_header.queue_id,
+                request_header.start_offset,
+                max_msg_nums,
+                self.message_store_config
+                    .max_transfer_bytes_on_message_in_memory as i32,
+                None,
+            )
+            .await
+        else {
+            return Err(format!(
+                "can not read messages of {}:{} from offset {}",
+                request_header.topic, request_header.queue_id, request_header.start_offset
+            ));
+        };
+        for message in get_message_result.message_mapped_list() {
+            body.extend_from_slice(message.get_buffer());
+        }
+        Ok((body, get_message_result.next_begin_offset(), max_offset))
+    }
+
+    /// Reads the messages stored between two commit log offsets, returning them with the next
+    /// commit log offset and the max commit log offset. The start offset must be the first byte
+    /// of a message, or of the blank filling the end of a commit log file.
+    async fn read_commit_log_range(
+        &self,
+        request_header: &QueryMessageByOffsetRangeRequestHeader,
+    ) -> Result<(BytesMut, i64, i64), String> {
+        let max_offset = self.message_store.get_max_phy_offset();
+        let end_offset = request_header.end_offset.min(max_offset);
+        let max_total_size = self
+            .message_store_config
+            .max_transfer_bytes_on_message_in_memory as usize;
+        let mut body = BytesMut::new();
+        let mut offset = request_header.start_offset;
+        let mut count = 0;
+        while offset < end_offset && count < request_header.max_msg_nums {
+            let Some(result) = self
+                .message_store
+                .select_one_message_by_offset(offset)
+                .await
+            else {
+                break;
+            };
+            let data = result.get_buffer();
+            if data.len() < MESSAGE_MAGIC_CODE_POSITION + 4 {
+                break;
+            }
+            let magic_code = (&data[MESSAGE_MAGIC_CODE_POSITION..]).get_i32();
+            if magic_code == BLANK_MAGIC_CODE {
+                offset += data.len() as i64;
+                continue;
+            }
+            if magic_code != MESSAGE_MAGIC_CODE && magic_code != MESSAGE_MAGIC_CODE_V2 {
+                if count == 0 {
+                    return Err(format!(
+                        "commit log offset {} is not the start of a message",
+                        offset
+                    ));
+                }
+                break;
+            }
+            if count > 0 && body.len() + data.len() > max_total_size {
+                break;
+            }
+            body.extend_from_slice(data);
+            offset += data.len() as i64;
+            count += 1;
+        }
+        Ok((body, offset, max_offset))
+    }
 }
@@ -39,6 +39,7 @@ use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerCon
 use rocketmq_rust::ArcMut;
 use rocketmq_store::base::message_result::PutMessageResult;
 use rocketmq_store::base::message_status_enum::PutMessageStatus;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
 use rocketmq_store::log_file::MessageStore;
 use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
 use rocketmq_store::stats::stats_type::StatsType;
@@ -46,7 +47,9 @@ use tracing::warn;
 
 use crate::client::manager::producer_manager::ProducerManager;
 use crate::client::rebalance::rebalance_lock_manager::RebalanceLockManager;
+use crate::mqtrace::consume_message_hook::ConsumeMessageHook;
 use crate::mqtrace::send_message_context::SendMessageContext;
+use crate::mqtrace::send_message_hook::SendMessageHook;
 use crate::processor::send_message_processor::Inner;
 use crate::subscription::manager::subscription_group_manager::SubscriptionGroupManager;
 use crate::topic::manager::topic_config_manager::TopicConfigManager;
@@ -67,7 +70,8 @@ where
         topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
         subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
         topic_config_manager: TopicConfigManager,
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
         message_store: ArcMut<MS>,
         rebalance_lock_manager: Arc<RebalanceLockManager>,
         broker_stats_manager: Arc<BrokerStatsManager>,
@@ -80,6 +84,7 @@ where
         Self {
             inner: Inner {
                 broker_config,
+                message_store_config,
                 topic_config_manager,
                 send_message_hook_vec: ArcMut::new(Vec::new()),
                 consume_message_hook_vec: ArcMut::new(Vec::new()),
@@ -102,6 +107,16 @@ where
     MS: MessageStore,
     TS: TransactionalMessageService,
 {
+    /// Shares hook lists owned elsewhere, replacing the ones of this processor.
+    pub fn set_message_hooks(
+        &mut self,
+        send_message_hook_vec: ArcMut<Vec<Box<dyn SendMessageHook>>>,
+        consume_message_hook_vec: ArcMut<Vec<Box<dyn ConsumeMessageHook>>>,
+    ) {
+        self.inner.send_message_hook_vec = send_message_hook_vec;
+        self.inner.consume_message_hook_vec = consume_message_hook_vec;
+    }
+
     pub async fn process_request(
         &mut self,
         channel: Channel,
@@ -317,7 +332,7 @@ where
                 send_message_context.msg_id = msg_id;
                 send_message_context.queue_id = queue_id;
                 send_message_context.queue_offset = queue_offset;
-                let commercial_base_count = self.inner.broker_config.commercial_base_count;
+                let commercial_base_count = self.inner.broker_config.commercial_base_count.get();
                 let wrote_size = put_message_result
                     .append_message_result()
                     .unwrap()
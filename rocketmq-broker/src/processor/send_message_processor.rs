@@ -37,7 +37,15 @@ use rocketmq_common::common::mix_all;
 use rocketmq_common::common::mix_all::RETRY_GROUP_TOPIC_PREFIX;
 use rocketmq_common::common::mq_version::RocketMqVersion;
 use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
+use rocketmq_common::common::telemetry::messaging_span::attributes;
+use rocketmq_common::common::telemetry::messaging_span::MessagingSpan;
+use rocketmq_common::common::telemetry::messaging_span::SpanKind;
+use rocketmq_common::common::telemetry::trace_parent::TraceParent;
+use rocketmq_common::common::telemetry::tracer_provider::start_span;
 use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_common::common::validation;
+use rocketmq_common::common::validation::ValidationError;
+use rocketmq_common::common::validation::ValidationErrorKind;
 use rocketmq_common::common::FAQUrl;
 use rocketmq_common::common::TopicFilterType;
 use rocketmq_common::common::TopicSysFlag;
@@ -54,7 +62,6 @@ use rocketmq_common::TimeUtils;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
-use rocketmq_remoting::code::response_code::ResponseCode::SystemError;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::header::consumer_send_msg_back_request_header::ConsumerSendMsgBackRequestHeader;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header::parse_request_header;
@@ -69,9 +76,11 @@ use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerCon
 use rocketmq_rust::ArcMut;
 use rocketmq_store::base::message_result::PutMessageResult;
 use rocketmq_store::base::message_status_enum::PutMessageStatus;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
 use rocketmq_store::log_file::MessageStore;
 use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
 use rocketmq_store::stats::stats_type::StatsType;
+use tokio::sync::OwnedSemaphorePermit;
 use tracing::info;
 use tracing::warn;
 
@@ -98,8 +107,33 @@ where
     MS: MessageStore + Send,
     TS: TransactionalMessageService,
 {
+    /// Returns `true` when the store cannot accept writes for now and send requests
+    /// should be rejected before being queued.
+    pub fn reject_request(&self) -> bool {
+        self.inner.message_store.is_os_page_cache_busy()
+            || self.inner.message_store.is_transient_store_pool_deficient()
+    }
+
     pub fn has_send_message_hook(&self) -> bool {
-        self.inner.send_message_hook_vec.is_empty()
+        !self.inner.send_message_hook_vec.is_empty()
+    }
+
+    pub fn register_send_message_hook(&mut self, hook: Box<dyn SendMessageHook>) {
+        self.inner.send_message_hook_vec.push(hook);
+    }
+
+    pub fn register_consume_message_hook(&mut self, hook: Box<dyn ConsumeMessageHook>) {
+        self.inner.consume_message_hook_vec.push(hook);
+    }
+
+    /// Shares hook lists owned elsewhere, replacing the ones of this processor.
+    pub fn set_message_hooks(
+        &mut self,
+        send_message_hook_vec: ArcMut<Vec<Box<dyn SendMessageHook>>>,
+        consume_message_hook_vec: ArcMut<Vec<Box<dyn ConsumeMessageHook>>>,
+    ) {
+        self.inner.send_message_hook_vec = send_message_hook_vec;
+        self.inner.consume_message_hook_vec = consume_message_hook_vec;
     }
 
     fn clear_reserved_properties(request_header: &mut SendMessageRequestHeader) {
@@ -111,17 +145,23 @@ where
         }
     }
 
+    /// Handles a send request admitted by the send queue.
+    ///
+    /// `send_permit` is the request's slot in the send queue. With `async_send_enable` it is
+    /// released as soon as the put is handed to the store, so the queue only bounds requests
+    /// waiting to be dispatched and not the flush or replication wait of the put.
     pub async fn process_request(
         &mut self,
         channel: Channel,
         ctx: ConnectionHandlerContext,
         request_code: RequestCode,
         request: RemotingCommand,
+        send_permit: OwnedSemaphorePermit,
     ) -> crate::Result<Option<RemotingCommand>> {
         match request_code {
             RequestCode::ConsumerSendMsgBack => {
                 self.inner
-                    .consumer_send_msg_back(&channel, &ctx, &request)
+                    .consumer_send_msg_back(&channel, &ctx, &request, send_permit)
                     .await
             }
             _ => {
@@ -146,8 +186,8 @@ where
                 SendMessageProcessor::<MS, TS>::clear_reserved_properties(&mut request_header);
                 let inner = self.inner.clone();
                 let execute_send_message_hook_after =
-                    |ctx: &mut SendMessageContext, cmd: &mut RemotingCommand| {
-                        inner.execute_send_message_hook_after(Some(cmd), ctx)
+                    |ctx: &mut SendMessageContext, cmd: Option<&mut RemotingCommand>| {
+                        inner.execute_send_message_hook_after(cmd, ctx)
                     };
                 if !request_header.batch.unwrap_or(false) {
                     //handle single message
@@ -159,6 +199,7 @@ where
                         request_header,
                         mapping_context,
                         execute_send_message_hook_after,
+                        send_permit,
                     )
                     .await
                 } else {
@@ -171,6 +212,7 @@ where
                         request_header,
                         mapping_context,
                         execute_send_message_hook_after,
+                        send_permit,
                     )
                     .await
                 }
@@ -188,7 +230,8 @@ where
         topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
         subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
         topic_config_manager: TopicConfigManager,
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
         message_store: ArcMut<MS>,
         transactional_message_service: ArcMut<TS>,
         rebalance_lock_manager: Arc<RebalanceLockManager>,
@@ -200,6 +243,7 @@ where
         Self {
             inner: ArcMut::new(Inner {
                 broker_config,
+                message_store_config,
                 topic_config_manager,
                 send_message_hook_vec: ArcMut::new(Vec::new()),
                 consume_message_hook_vec: ArcMut::new(Vec::new()),
@@ -225,10 +269,11 @@ where
         mut send_message_context: SendMessageContext,
         request_header: SendMessageRequestHeader,
         mut mapping_context: TopicQueueMappingContext,
-        _send_message_callback: F,
+        send_message_callback: F,
+        send_permit: OwnedSemaphorePermit,
     ) -> crate::Result<Option<RemotingCommand>>
     where
-        F: Fn(&mut SendMessageContext, &mut RemotingCommand),
+        F: Fn(&mut SendMessageContext, Option<&mut RemotingCommand>),
     {
         let response = self.pre_send(channel, ctx, request.as_ref(), &request_header);
         if response.code() != -1 {
@@ -378,10 +423,12 @@ where
                 } else {
                     message_store.put_messages(batch_message).await
                 }
-            })
-            .await
-            .map_err(|e| RemotingCommandError(e.to_string()))?;
-            Ok(self
+            });
+            drop(send_permit);
+            let put_message_result = put_message_result
+                .await
+                .map_err(|e| RemotingCommandError(e.to_string()))?;
+            let mut response = self
                 .handle_put_message_result(
                     put_message_result,
                     response,
@@ -395,9 +442,9 @@ where
                     &mut mapping_context,
                     MessageType::NormalMsg,
                 )
-                .await)
-            //Java version has a send_message_callback here, but it is not used
-            //send_message_callback(&mut send_message_context, &mut response);
+                .await;
+            send_message_callback(&mut send_message_context, response.as_mut());
+            Ok(response)
         } else {
             let put_message_result = if is_inner_batch {
                 self.inner
@@ -407,7 +454,7 @@ where
             } else {
                 self.inner.message_store.put_messages(batch_message).await
             };
-            Ok(self
+            let mut response = self
                 .handle_put_message_result(
                     put_message_result,
                     response,
@@ -421,9 +468,9 @@ where
                     &mut mapping_context,
                     MessageType::NormalMsg,
                 )
-                .await)
-            //Java version has a send_message_callback here, but it is not used
-            //send_message_callback(&mut send_message_context, &mut response);
+                .await;
+            send_message_callback(&mut send_message_context, response.as_mut());
+            Ok(response)
         }
     }
 
@@ -435,10 +482,11 @@ where
         mut send_message_context: SendMessageContext,
         request_header: SendMessageRequestHeader,
         mut mapping_context: TopicQueueMappingContext,
-        _send_message_callback: F,
+        send_message_callback: F,
+        send_permit: OwnedSemaphorePermit,
     ) -> crate::Result<Option<RemotingCommand>>
     where
-        F: Fn(&mut SendMessageContext, &mut RemotingCommand),
+        F: Fn(&mut SendMessageContext, Option<&mut RemotingCommand>),
     {
         let mut response = self.pre_send(channel, ctx, request.as_ref(), &request_header);
         if response.code() != -1 {
@@ -541,7 +589,7 @@ where
             && !(message_ext.reconsume_times() > 0
                 && message_ext.message_ext_inner.message.get_delay_time_level() > 0)
         {
-            if self.inner.broker_config.reject_transaction_message {
+            if self.inner.broker_config.reject_transaction_message.get() {
                 return Ok(Some(
                     response
                         .set_code(ResponseCode::NoPermission)
@@ -556,6 +604,7 @@ where
             false
         };
 
+        let store_span = self.start_store_span(&message_ext, &request_header);
         let start = Instant::now();
         let topic = message_ext.topic().clone();
         let transaction_id =
@@ -573,10 +622,11 @@ where
                 let mut message_store = self.inner.message_store.clone();
                 tokio::spawn(async move { message_store.put_message(message_ext).await })
             };
+            drop(send_permit);
             let put_message_result = put_message_handle
                 .await
                 .map_err(|e| RemotingCommandError(e.to_string()))?;
-            Ok(self
+            let mut response = self
                 .handle_put_message_result(
                     put_message_result,
                     response,
@@ -590,9 +640,10 @@ where
                     &mut mapping_context,
                     MessageType::NormalMsg,
                 )
-                .await)
-            //Java version has a send_message_callback here, but it is not used
-            //send_message_callback(&mut send_message_context, &mut response);
+                .await;
+            Self::end_store_span(store_span, response.as_ref());
+            send_message_callback(&mut send_message_context, response.as_mut());
+            Ok(response)
         } else {
             let put_message_result = if send_transaction_prepare_message {
                 self.inner
@@ -603,7 +654,7 @@ where
                 self.inner.message_store.put_message(message_ext).await
             };
 
-            Ok(self
+            let mut response = self
                 .handle_put_message_result(
                     put_message_result,
                     response,
@@ -617,9 +668,60 @@ where
                     &mut mapping_context,
                     MessageType::NormalMsg,
                 )
-                .await)
-            //Java version has a send_message_callback here, but it is not used
-            //send_message_callback(&mut send_message_context, &mut response);
+                .await;
+            Self::end_store_span(store_span, response.as_ref());
+            send_message_callback(&mut send_message_context, response.as_mut());
+            Ok(response)
+        }
+    }
+
+    /// Starts the server span of a stored message when telemetry is enabled, continuing the
+    /// trace propagated by the producer.
+    fn start_store_span(
+        &self,
+        message_ext: &MessageExtBrokerInner,
+        request_header: &SendMessageRequestHeader,
+    ) -> Option<Box<dyn MessagingSpan>> {
+        if !self.inner.broker_config.enable_telemetry {
+            return None;
+        }
+        let message = &message_ext.message_ext_inner.message;
+        let mut span = start_span(
+            format!("{} store", message.get_topic()),
+            SpanKind::Server,
+            TraceParent::from_message(message),
+        );
+        span.set_attribute(attributes::MESSAGING_OPERATION, "publish".to_string());
+        span.set_attribute(
+            attributes::MESSAGING_DESTINATION_NAME,
+            message.get_topic().to_string(),
+        );
+        span.set_attribute(
+            attributes::MESSAGING_CONSUMER_GROUP,
+            request_header.producer_group.to_string(),
+        );
+        span.set_attribute(
+            attributes::MESSAGING_QUEUE_ID,
+            message_ext.message_ext_inner.queue_id.to_string(),
+        );
+        if let Some(uniq_id) = MessageClientIDSetter::get_uniq_id(message) {
+            span.set_attribute(attributes::MESSAGING_MESSAGE_ID, uniq_id.to_string());
+        }
+        Some(span)
+    }
+
+    fn end_store_span(span: Option<Box<dyn MessagingSpan>>, response: Option<&RemotingCommand>) {
+        if let Some(mut span) = span {
+            if let Some(response) = response {
+                if response.code() != ResponseCode::Success as i32 {
+                    span.record_error(
+                        response
+                            .remark()
+                            .map_or("put message failed", |remark| remark.as_str()),
+                    );
+                }
+            }
+            span.end();
         }
     }
 
@@ -780,7 +882,7 @@ where
                 send_message_context.msg_id = CheetahString::from_string(msg_id);
                 send_message_context.queue_id = queue_id;
                 send_message_context.queue_offset = queue_offset;
-                let commercial_base_count = self.inner.broker_config.commercial_base_count;
+                let commercial_base_count = self.inner.broker_config.commercial_base_count.get();
                 let wrote_size = put_message_result
                     .append_message_result()
                     .unwrap()
@@ -970,7 +1072,8 @@ pub(crate) struct Inner<MS, TS> {
     pub(crate) consume_message_hook_vec: ArcMut<Vec<Box<dyn ConsumeMessageHook>>>,
     pub(crate) topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
     pub(crate) subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
-    pub(crate) broker_config: Arc<BrokerConfig>,
+    pub(crate) broker_config: ArcMut<BrokerConfig>,
+    pub(crate) message_store_config: ArcMut<MessageStoreConfig>,
     pub(crate) message_store: ArcMut<MS>,
     pub(crate) transactional_message_service: ArcMut<TS>,
     pub(crate) rebalance_lock_manager: Arc<RebalanceLockManager>,
@@ -987,7 +1090,7 @@ where
 {
     #[inline]
     pub fn has_send_message_hook(&self) -> bool {
-        self.send_message_hook_vec.is_empty()
+        !self.send_message_hook_vec.is_empty()
     }
 
     #[inline]
@@ -1034,6 +1137,7 @@ where
         _channel: &Channel,
         _ctx: &ConnectionHandlerContext,
         request: &RemotingCommand,
+        send_permit: OwnedSemaphorePermit,
     ) -> crate::Result<Option<RemotingCommand>> {
         let request_header = request
             .decode_command_custom_header::<ConsumerSendMsgBackRequestHeader>()
@@ -1065,7 +1169,7 @@ where
             ));
         }
 
-        if !PermName::is_writeable(self.broker_config.broker_permission) {
+        if !PermName::is_writeable(self.broker_config.broker_permission.get()) {
             return Ok(Some(
                 RemotingCommand::create_response_command_with_code_remark(
                     ResponseCode::NoPermission,
@@ -1175,6 +1279,38 @@ where
                     ),
                 ));
             }
+            // tells consumers of the DLQ where the message comes from
+            let origin_topic = request_header.origin_topic.clone().unwrap_or_else(|| {
+                let retry_topic = msg_ext
+                    .get_property(&CheetahString::from_static_str(
+                        MessageConst::PROPERTY_RETRY_TOPIC,
+                    ))
+                    .unwrap_or_else(|| msg_ext.get_topic().clone());
+                let group = request_header.group.as_str();
+                if retry_topic.starts_with(&KeyBuilder::build_pop_retry_topic_v1("", group))
+                    || retry_topic.starts_with(&KeyBuilder::build_pop_retry_topic_v2("", group))
+                {
+                    CheetahString::from_string(KeyBuilder::parse_normal_topic(&retry_topic, group))
+                } else {
+                    retry_topic
+                }
+            });
+            let origin_message_id = request_header
+                .origin_msg_id
+                .clone()
+                .filter(|id| !id.is_empty())
+                .or_else(|| MessageClientIDSetter::get_uniq_id(&msg_ext))
+                .unwrap_or_else(|| msg_ext.msg_id.clone());
+            MessageAccessor::put_property(
+                &mut msg_ext,
+                CheetahString::from_static_str(MessageConst::PROPERTY_DLQ_ORIGIN_TOPIC),
+                origin_topic,
+            );
+            MessageAccessor::put_property(
+                &mut msg_ext,
+                CheetahString::from_static_str(MessageConst::PROPERTY_DLQ_ORIGIN_MESSAGE_ID),
+                origin_message_id,
+            );
             msg_ext.set_delay_time_level(0);
             true
         } else {
@@ -1208,29 +1344,65 @@ where
             msg_ext.msg_id.clone()
         };
         MessageAccessor::set_origin_message_id(&mut msg_inner, origin_msg_id);
-        msg_inner.properties_string = message_properties_to_string(msg_ext.get_properties());
+        msg_inner.properties_string = message_properties_to_string(msg_inner.get_properties());
 
         let inner_topic = msg_inner.get_topic().clone();
-        let put_message_result = self.message_store.put_message(msg_inner).await;
+        let put_message_result = if self.broker_config.async_send_enable {
+            let mut message_store = self.message_store.clone();
+            let put_message_handle =
+                tokio::spawn(async move { message_store.put_message(msg_inner).await });
+            drop(send_permit);
+            put_message_handle
+                .await
+                .map_err(|e| RemotingCommandError(e.to_string()))?
+        } else {
+            self.message_store.put_message(msg_inner).await
+        };
         let commercial_owner = request
             .get_ext_fields()
             .and_then(|value| value.get(BrokerStatsManager::COMMERCIAL_OWNER).cloned());
         let (response, succeeded) = match put_message_result.put_message_status() {
             PutMessageStatus::PutOk => {
-                let mut _back_topic = msg_ext.get_topic().clone();
+                let mut back_topic = msg_ext.get_topic().clone();
                 let correct_topic = msg_ext.get_property(&CheetahString::from_static_str(
                     MessageConst::PROPERTY_RETRY_TOPIC,
                 ));
                 if let Some(topic) = correct_topic {
-                    _back_topic = topic;
+                    back_topic = topic;
                 }
 
                 if TopicValidator::RMQ_SYS_SCHEDULE_TOPIC == inner_topic {
-                    //TODO: implement this
+                    let wrote_bytes = put_message_result
+                        .append_message_result()
+                        .map_or(0, |result| result.wrote_bytes);
+                    self.broker_stats_manager
+                        .inc_topic_put_nums(inner_topic.as_str(), 1, 1);
+                    self.broker_stats_manager
+                        .inc_topic_put_size(inner_topic.as_str(), wrote_bytes);
+                    self.broker_stats_manager.inc_queue_put_nums(
+                        inner_topic.as_str(),
+                        queue_id_int,
+                        1,
+                        1,
+                    );
+                    self.broker_stats_manager.inc_queue_put_size(
+                        inner_topic.as_str(),
+                        queue_id_int,
+                        wrote_bytes,
+                    );
                 }
+                self.broker_stats_manager
+                    .inc_send_back_nums(request_header.group.as_str(), back_topic.as_str());
 
                 if is_dlq {
-                    // TODO: implement this
+                    self.broker_stats_manager.inc_dlq_stat_value(
+                        BrokerStatsManager::SNDBCK2DLQ_TIMES,
+                        commercial_owner.as_deref().unwrap_or_default(),
+                        request_header.group.as_str(),
+                        request_header.origin_topic.as_deref().unwrap_or_default(),
+                        BrokerStatsManager::ACCOUNT_SEND_BACK_TO_DLQ,
+                        1,
+                    );
                 }
                 (RemotingCommand::create_response_command(), true)
             }
@@ -1355,7 +1527,7 @@ where
         &mut self,
         channel: &Channel,
         _ctx: &ConnectionHandlerContext,
-        _request: &RemotingCommand,
+        request: &RemotingCommand,
         request_header: &SendMessageRequestHeader,
         response: &mut RemotingCommand,
     ) {
@@ -1374,19 +1546,11 @@ where
         }
 
         //check Topic
-        let result = TopicValidator::validate_topic(request_header.topic.as_str());
-        if !result.valid() {
-            response.with_code(SystemError);
-            response.with_remark(result.remark().clone());
-            return;
-        }
-
-        if TopicValidator::is_not_allowed_send_topic(request_header.topic.as_str()) {
-            response.with_code(ResponseCode::NoPermission);
-            response.with_remark(format!(
-                "Sending message to topic[{}] is forbidden.",
-                request_header.topic.as_str()
-            ));
+        if let Err(error) = validation::check_topic(request_header.topic.as_str())
+            .and_then(|_| validation::check_send_topic(request_header.topic.as_str()))
+        {
+            response.with_code(validation_error_code(&error));
+            response.with_remark(error.remark().to_string());
             return;
         }
         let mut topic_config = self
@@ -1439,8 +1603,25 @@ where
             }
         }
 
-        let queue_id_int = request_header.queue_id;
         let topic_config_inner = topic_config.as_ref().unwrap();
+        let body_length = request.body().as_ref().map_or(0, |body| body.len());
+        let properties_length = request_header
+            .properties
+            .as_ref()
+            .map_or(0, |properties| properties.len());
+        if let Err((code, remark)) = check_writeable_and_size(
+            request_header.topic.as_str(),
+            topic_config_inner.perm,
+            body_length,
+            properties_length,
+            self.message_store_config.max_message_size.max(0) as usize,
+        ) {
+            response.with_code(code);
+            response.with_remark(remark);
+            return;
+        }
+
+        let queue_id_int = request_header.queue_id;
         let id_valid = topic_config_inner
             .write_queue_nums
             .max(topic_config_inner.read_queue_nums);
@@ -1460,6 +1641,36 @@ where
     }
 }
 
+/// Checks that the topic accepts writes and that the message body and properties fit the
+/// limits, returning the code and remark the send is rejected with.
+fn check_writeable_and_size(
+    topic: &str,
+    perm: u32,
+    body_length: usize,
+    properties_length: usize,
+    max_message_size: usize,
+) -> Result<(), (ResponseCode, String)> {
+    if !PermName::is_writeable(perm) {
+        return Err((
+            ResponseCode::NoPermission,
+            format!("the topic[{}] sending message is forbidden", topic),
+        ));
+    }
+    validation::check_body_size(body_length, max_message_size)
+        .and_then(|_| validation::check_properties_length(properties_length))
+        .map_err(|error| (validation_error_code(&error), error.remark().to_string()))
+}
+
+fn validation_error_code(error: &ValidationError) -> ResponseCode {
+    match error.kind() {
+        ValidationErrorKind::IllegalMessage => ResponseCode::MessageIllegal,
+        ValidationErrorKind::ForbiddenTopic => ResponseCode::NoPermission,
+        ValidationErrorKind::IllegalTopic | ValidationErrorKind::IllegalGroup => {
+            ResponseCode::SystemError
+        }
+    }
+}
+
 fn rewrite_response_for_static_topic(
     response_header: &mut SendMessageResponseHeader,
     mapping_context: &TopicQueueMappingContext,
@@ -1487,3 +1698,37 @@ fn rewrite_response_for_static_topic(
     response_header.set_queue_offset(static_logic_offset);
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_without_write_permission_is_rejected() {
+        let (code, remark) =
+            check_writeable_and_size("topic", PermName::PERM_READ, 10, 10, 1024).unwrap_err();
+        assert_eq!(code, ResponseCode::NoPermission);
+        assert_eq!(remark, "the topic[topic] sending message is forbidden");
+        assert!(check_writeable_and_size(
+            "topic",
+            PermName::PERM_READ | PermName::PERM_WRITE,
+            10,
+            10,
+            1024
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn oversized_body_and_properties_are_illegal() {
+        let perm = PermName::PERM_READ | PermName::PERM_WRITE;
+        assert!(check_writeable_and_size("topic", perm, 1024, 0, 1024).is_ok());
+        let (code, _) = check_writeable_and_size("topic", perm, 1025, 0, 1024).unwrap_err();
+        assert_eq!(code, ResponseCode::MessageIllegal);
+
+        assert!(check_writeable_and_size("topic", perm, 0, i16::MAX as usize, 1024).is_ok());
+        let (code, _) =
+            check_writeable_and_size("topic", perm, 0, i16::MAX as usize + 1, 1024).unwrap_err();
+        assert_eq!(code, ResponseCode::MessageIllegal);
+    }
+}
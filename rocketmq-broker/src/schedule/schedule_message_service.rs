@@ -20,14 +20,28 @@ use std::sync::Arc;
 
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::config_manager::ConfigManager;
+use rocketmq_remoting::protocol::DataVersion;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use rocketmq_rust::ArcMut;
 use rocketmq_store::store_path_config_helper::get_delay_offset_store_path;
 
+use crate::schedule::delay_offset_serialize_wrapper::DelayOffsetSerializeWrapper;
+
 #[derive(Default, Clone)]
 pub struct ScheduleMessageService {
-    pub(crate) broker_config: Arc<BrokerConfig>,
+    pub(crate) broker_config: ArcMut<BrokerConfig>,
+    offset_table: Arc<parking_lot::Mutex<HashMap<i32 /* level */, i64 /* offset */>>>,
+    data_version: Arc<parking_lot::Mutex<DataVersion>>,
 }
 
 impl ScheduleMessageService {
+    pub fn new(broker_config: ArcMut<BrokerConfig>) -> Self {
+        Self {
+            broker_config,
+            ..Default::default()
+        }
+    }
+
     pub fn delay_level2queue_id(delay_level: i32) -> i32 {
         delay_level - 1
     }
@@ -39,28 +53,64 @@ impl ScheduleMessageService {
     pub fn get_max_delay_level(&self) -> i32 {
         0
     }
-}
 
-impl ConfigManager for ScheduleMessageService {
-    fn decode0(&mut self, _key: &[u8], _body: &[u8]) {
-        todo!()
+    pub fn offset_table(&self) -> HashMap<i32, i64> {
+        self.offset_table.lock().clone()
     }
 
-    fn stop(&mut self) -> bool {
-        todo!()
+    /// Reloads the delay offsets after a slave wrote the file synchronized from its master.
+    pub fn load_when_sync_delay_offset(&self) -> bool {
+        self.offset_table.lock().clear();
+        self.load()
     }
+}
 
+impl ConfigManager for ScheduleMessageService {
     fn config_file_path(&self) -> String {
         get_delay_offset_store_path(self.broker_config.store_path_root_dir.as_str())
     }
 
-    fn encode(&mut self) -> String {
-        todo!()
+    fn encode_pretty(&self, pretty_format: bool) -> String {
+        let wrapper = DelayOffsetSerializeWrapper::new(
+            self.offset_table.lock().clone(),
+            self.data_version.lock().clone(),
+        );
+        match pretty_format {
+            true => wrapper
+                .to_json_pretty()
+                .expect("encode delay offset pretty failed"),
+            false => wrapper.to_json().expect("encode delay offset failed"),
+        }
     }
 
-    fn encode_pretty(&self, _pretty_format: bool) -> String {
-        todo!()
+    fn decode(&self, json_string: &str) {
+        if json_string.is_empty() {
+            return;
+        }
+        if let Ok(wrapper) = serde_json::from_str::<DelayOffsetSerializeWrapper>(json_string) {
+            self.offset_table
+                .lock()
+                .extend(wrapper.offset_table().iter().map(|(k, v)| (*k, *v)));
+            self.data_version
+                .lock()
+                .assign_new_one(wrapper.data_version());
+        }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn decode(&self, _json_string: &str) {}
+    #[test]
+    fn decode_restores_encoded_delay_offsets() {
+        let service = ScheduleMessageService::default();
+        service.offset_table.lock().insert(1, 100);
+        service.offset_table.lock().insert(3, 300);
+        let json = service.encode_pretty(false);
+
+        let restored = ScheduleMessageService::default();
+        restored.decode(json.as_str());
+        assert_eq!(restored.offset_table(), service.offset_table());
+    }
 }
@@ -0,0 +1,233 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::config_manager::ConfigManager;
+use rocketmq_common::utils::file_utils;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::store_path_config_helper::get_delay_offset_store_path;
+use tracing::error;
+use tracing::info;
+
+use crate::offset::manager::consumer_offset_manager::ConsumerOffsetManager;
+use crate::out_api::broker_outer_api::BrokerOuterAPI;
+use crate::schedule::schedule_message_service::ScheduleMessageService;
+use crate::subscription::manager::subscription_group_manager::SubscriptionGroupManager;
+use crate::topic::manager::topic_config_manager::TopicConfigManager;
+use crate::topic::manager::topic_queue_mapping_manager::TopicQueueMappingManager;
+
+/// Pulls the metadata of the master (topic configs, consumer offsets, delay offsets
+/// and subscription groups) so that a slave can take over with consistent state.
+pub(crate) struct SlaveSynchronize<MS> {
+    broker_config: ArcMut<BrokerConfig>,
+    broker_out_api: Arc<BrokerOuterAPI>,
+    topic_config_manager: TopicConfigManager,
+    topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
+    consumer_offset_manager: ConsumerOffsetManager,
+    subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+    schedule_message_service: ScheduleMessageService,
+    master_addr: Arc<parking_lot::RwLock<Option<CheetahString>>>,
+}
+
+impl<MS> Clone for SlaveSynchronize<MS> {
+    fn clone(&self) -> Self {
+        Self {
+            broker_config: self.broker_config.clone(),
+            broker_out_api: self.broker_out_api.clone(),
+            topic_config_manager: self.topic_config_manager.clone(),
+            topic_queue_mapping_manager: self.topic_queue_mapping_manager.clone(),
+            consumer_offset_manager: self.consumer_offset_manager.clone(),
+            subscription_group_manager: self.subscription_group_manager.clone(),
+            schedule_message_service: self.schedule_message_service.clone(),
+            master_addr: self.master_addr.clone(),
+        }
+    }
+}
+
+impl<MS> SlaveSynchronize<MS> {
+    pub fn new(
+        broker_config: ArcMut<BrokerConfig>,
+        broker_out_api: Arc<BrokerOuterAPI>,
+        topic_config_manager: TopicConfigManager,
+        topic_queue_mapping_manager: Arc<TopicQueueMappingManager>,
+        consumer_offset_manager: ConsumerOffsetManager,
+        subscription_group_manager: Arc<SubscriptionGroupManager<MS>>,
+        schedule_message_service: ScheduleMessageService,
+    ) -> Self {
+        Self {
+            broker_config,
+            broker_out_api,
+            topic_config_manager,
+            topic_queue_mapping_manager,
+            consumer_offset_manager,
+            subscription_group_manager,
+            schedule_message_service,
+            master_addr: Arc::new(parking_lot::RwLock::new(None)),
+        }
+    }
+
+    pub fn master_addr(&self) -> Option<CheetahString> {
+        self.master_addr.read().clone()
+    }
+
+    pub fn set_master_addr(&self, master_addr: Option<CheetahString>) {
+        let mut current = self.master_addr.write();
+        if *current != master_addr {
+            info!(
+                "Update master address from {:?} to {:?}",
+                *current, master_addr
+            );
+            *current = master_addr;
+        }
+    }
+
+    pub async fn sync_all(&self) {
+        self.sync_topic_config().await;
+        self.sync_consumer_offset().await;
+        self.sync_delay_offset().await;
+        self.sync_subscription_group_config().await;
+    }
+
+    /// The master address to synchronize from, unless it is this broker itself.
+    fn master_addr_to_sync(&self) -> Option<CheetahString> {
+        let master_addr = self.master_addr()?;
+        if master_addr.is_empty() || master_addr.as_str() == self.broker_config.get_broker_addr() {
+            return None;
+        }
+        Some(master_addr)
+    }
+
+    async fn sync_topic_config(&self) {
+        let Some(master_addr) = self.master_addr_to_sync() else {
+            return;
+        };
+        let topic_wrapper = match self.broker_out_api.get_all_topic_config(&master_addr).await {
+            Ok(wrapper) => wrapper,
+            Err(e) => {
+                error!("SyncTopicConfig Exception, {}, {}", master_addr, e);
+                return;
+            }
+        };
+
+        let topic_config_wrapper = topic_wrapper.topic_config_serialize_wrapper;
+        let data_version = self.topic_config_manager.data_version();
+        if data_version.as_ref() != &topic_config_wrapper.data_version {
+            data_version
+                .mut_from_ref()
+                .assign_new_one(&topic_config_wrapper.data_version);
+            let new_topic_config_table = topic_config_wrapper.topic_config_table;
+            {
+                let topic_config_table = self.topic_config_manager.topic_config_table();
+                let mut topic_config_table = topic_config_table.lock();
+                topic_config_table.retain(|topic, _| new_topic_config_table.contains_key(topic));
+                topic_config_table.extend(new_topic_config_table);
+            }
+            self.topic_config_manager.persist();
+        }
+
+        if self.topic_queue_mapping_manager.data_version.lock().clone()
+            != topic_wrapper.mapping_data_version
+        {
+            self.topic_queue_mapping_manager
+                .data_version
+                .lock()
+                .assign_new_one(&topic_wrapper.mapping_data_version);
+            let new_mapping_table = topic_wrapper.topic_queue_mapping_detail_map;
+            {
+                let mut mapping_table = self
+                    .topic_queue_mapping_manager
+                    .topic_queue_mapping_table
+                    .lock();
+                mapping_table.retain(|topic, _| new_mapping_table.contains_key(topic));
+                mapping_table.extend(new_mapping_table);
+            }
+            self.topic_queue_mapping_manager.persist();
+        }
+        info!("Update slave topic config from master, {}", master_addr);
+    }
+
+    async fn sync_consumer_offset(&self) {
+        let Some(master_addr) = self.master_addr_to_sync() else {
+            return;
+        };
+        match self
+            .broker_out_api
+            .get_all_consumer_offset(&master_addr)
+            .await
+        {
+            Ok(content) => {
+                self.consumer_offset_manager.decode(content.as_str());
+                self.consumer_offset_manager.persist();
+                info!("Update slave consumer offset from master, {}", master_addr);
+            }
+            Err(e) => {
+                error!("SyncConsumerOffset Exception, {}, {}", master_addr, e);
+            }
+        }
+    }
+
+    async fn sync_delay_offset(&self) {
+        let Some(master_addr) = self.master_addr_to_sync() else {
+            return;
+        };
+        match self.broker_out_api.get_all_delay_offset(&master_addr).await {
+            Ok(content) => {
+                let file_name =
+                    get_delay_offset_store_path(self.broker_config.store_path_root_dir.as_str());
+                if let Err(e) = file_utils::string_to_file(content.as_str(), file_name.as_str()) {
+                    error!("Persist file Exception, {}, {}", file_name, e);
+                    return;
+                }
+                self.schedule_message_service.load_when_sync_delay_offset();
+                info!("Update slave delay offset from master, {}", master_addr);
+            }
+            Err(e) => {
+                error!("SyncDelayOffset Exception, {}, {}", master_addr, e);
+            }
+        }
+    }
+
+    async fn sync_subscription_group_config(&self) {
+        let Some(master_addr) = self.master_addr_to_sync() else {
+            return;
+        };
+        match self
+            .broker_out_api
+            .get_all_subscription_group_config(&master_addr)
+            .await
+        {
+            Ok(content) => {
+                if self
+                    .subscription_group_manager
+                    .replace_all_if_version_changed(content.as_str())
+                {
+                    self.subscription_group_manager.persist();
+                    info!(
+                        "Update slave Subscription Group from master, {}",
+                        master_addr
+                    );
+                }
+            }
+            Err(e) => {
+                error!("SyncSubscriptionGroup Exception, {}, {}", master_addr, e);
+            }
+        }
+    }
+}
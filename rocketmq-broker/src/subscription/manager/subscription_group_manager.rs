@@ -15,45 +15,85 @@
  * limitations under the License.
  */
 
-use std::collections::HashMap;
 use std::sync::Arc;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::config_manager::ConfigManager;
+use rocketmq_common::common::mix_all;
 use rocketmq_common::common::mix_all::is_sys_consumer_group;
-use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_common::common::validation;
+use rocketmq_remoting::protocol::body::subscription_group_wrapper::SubscriptionGroupWrapper;
 use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
-use rocketmq_remoting::protocol::DataVersion;
 use rocketmq_remoting::protocol::RemotingSerializable;
+use rocketmq_rust::ArcMut;
 use rocketmq_store::log_file::MessageStore;
-use serde::Deserialize;
-use serde::Serialize;
 use tracing::info;
+use tracing::warn;
 
 use crate::broker_path_config_helper::get_subscription_group_path;
 
-pub const CHARACTER_MAX_LENGTH: usize = 255;
-pub const TOPIC_MAX_LENGTH: usize = 127;
-
 pub(crate) struct SubscriptionGroupManager<MS> {
-    pub(crate) broker_config: Arc<BrokerConfig>,
+    pub(crate) broker_config: ArcMut<BrokerConfig>,
     subscription_group_wrapper: Arc<parking_lot::Mutex<SubscriptionGroupWrapper>>,
     pub(crate) message_store: Option<MS>,
 }
 
 impl<MS> SubscriptionGroupManager<MS> {
     pub fn new(
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         message_store: Option<MS>,
     ) -> SubscriptionGroupManager<MS> {
-        Self {
+        let manager = Self {
             broker_config,
             subscription_group_wrapper: Arc::new(parking_lot::Mutex::new(
                 SubscriptionGroupWrapper::default(),
             )),
             message_store,
+        };
+        manager.init();
+        manager
+    }
+
+    /// Registers the built-in system consumer groups.
+    fn init(&self) {
+        let mut wrapper = self.subscription_group_wrapper.lock();
+        for (group, consume_broadcast_enable) in [
+            (mix_all::TOOLS_CONSUMER_GROUP, false),
+            (mix_all::FILTERSRV_CONSUMER_GROUP, false),
+            (mix_all::SELF_TEST_CONSUMER_GROUP, false),
+            (mix_all::ONS_HTTP_PROXY_GROUP, true),
+            (mix_all::CID_ONSAPI_PULL_GROUP, true),
+            (mix_all::CID_ONSAPI_PERMISSION_GROUP, true),
+            (mix_all::CID_ONSAPI_OWNER_GROUP, true),
+            (mix_all::CID_SYS_RMQ_TRANS, true),
+        ] {
+            let group = CheetahString::from_static_str(group);
+            let mut config = SubscriptionGroupConfig::new(group.clone());
+            config.set_consume_broadcast_enable(consume_broadcast_enable);
+            wrapper.subscription_group_table.insert(group, config);
+        }
+    }
+}
+
+impl<MS> SubscriptionGroupManager<MS> {
+    /// Replaces the local subscription groups with the ones encoded in `json_string`
+    /// when its data version differs from the local one.
+    ///
+    /// Returns `true` if the local table was replaced.
+    pub fn replace_all_if_version_changed(&self, json_string: &str) -> bool {
+        let Ok(wrapper) = serde_json::from_str::<SubscriptionGroupWrapper>(json_string) else {
+            warn!("decode subscription group config failed");
+            return false;
+        };
+        let mut local = self.subscription_group_wrapper.lock();
+        if local.data_version == wrapper.data_version {
+            return false;
         }
+        local.data_version.assign_new_one(&wrapper.data_version);
+        local.subscription_group_table = wrapper.subscription_group_table;
+        local.forbidden_table = wrapper.forbidden_table;
+        true
     }
 }
 
@@ -108,6 +148,13 @@ impl<MS> SubscriptionGroupManager<MS>
 where
     MS: MessageStore,
 {
+    pub fn subscription_group_count(&self) -> usize {
+        self.subscription_group_wrapper
+            .lock()
+            .subscription_group_table
+            .len()
+    }
+
     pub fn contains_subscription_group(&self, group: &CheetahString) -> bool {
         if group.is_empty() {
             return false;
@@ -124,11 +171,10 @@ where
     ) -> Option<SubscriptionGroupConfig> {
         let mut subscription_group_config = self.find_subscription_group_config_inner(group);
         if subscription_group_config.is_none()
-            && (self.broker_config.auto_create_subscription_group || is_sys_consumer_group(group))
+            && (self.broker_config.auto_create_subscription_group.get()
+                || is_sys_consumer_group(group))
         {
-            if group.len() > CHARACTER_MAX_LENGTH
-                || TopicValidator::is_topic_or_group_illegal(group)
-            {
+            if validation::check_group(group).is_err() {
                 return None;
             }
             let mut subscription_group_config_new = SubscriptionGroupConfig::default();
@@ -144,21 +190,64 @@ where
                     subscription_group_config_new
                 );
             }
-            let state_machine_version = if let Some(ref store) = self.message_store {
-                store.get_state_machine_version()
-            } else {
-                0
-            };
-            self.subscription_group_wrapper
-                .lock()
-                .data_version
-                .next_version_with(state_machine_version);
+            self.update_data_version();
             self.persist();
             subscription_group_config = Some(subscription_group_config_new);
         }
         subscription_group_config
     }
 
+    /// Creates or replaces the config of `config.group_name()`, bumps the data version and
+    /// persists `subscriptionGroup.json`.
+    pub fn update_subscription_group_config(&self, config: &SubscriptionGroupConfig) {
+        let group = CheetahString::from_slice(config.group_name());
+        let old = self
+            .subscription_group_wrapper
+            .lock()
+            .subscription_group_table
+            .insert(group, config.clone());
+        match old {
+            Some(old) => info!(
+                "update subscription group config, old: {:?} new: {:?}",
+                old, config
+            ),
+            None => info!("create new subscription group, {:?}", config),
+        }
+        self.update_data_version();
+        self.persist();
+    }
+
+    pub fn delete_subscription_group_config(&self, group: &CheetahString) {
+        let old = {
+            let mut wrapper = self.subscription_group_wrapper.lock();
+            wrapper.forbidden_table.remove(group);
+            wrapper.subscription_group_table.remove(group)
+        };
+        match old {
+            Some(old) => {
+                info!("delete subscription group OK, subscription group:{:?}", old);
+                self.update_data_version();
+                self.persist();
+            }
+            None => warn!(
+                "delete subscription group failed, subscription groupName: {} not exist",
+                group
+            ),
+        }
+    }
+
+    fn update_data_version(&self) {
+        let state_machine_version = if let Some(ref store) = self.message_store {
+            store.get_state_machine_version()
+        } else {
+            0
+        };
+        self.subscription_group_wrapper
+            .lock()
+            .data_version
+            .next_version_with(state_machine_version);
+    }
+
     pub fn find_subscription_group_config_inner(
         &self,
         group: &CheetahString,
@@ -197,20 +286,31 @@ where
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct SubscriptionGroupWrapper {
-    subscription_group_table: HashMap<CheetahString, SubscriptionGroupConfig>,
-    forbidden_table: HashMap<CheetahString, HashMap<CheetahString, i32>>,
-    data_version: DataVersion,
-}
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::protocol::RemotingSerializable;
+    use rocketmq_store::message_store::default_message_store::DefaultMessageStore;
 
-impl SubscriptionGroupWrapper {
-    pub fn subscription_group_table(&self) -> &HashMap<CheetahString, SubscriptionGroupConfig> {
-        &self.subscription_group_table
-    }
+    use super::*;
+
+    #[test]
+    fn replace_all_if_version_changed_replaces_table_once() {
+        let manager = SubscriptionGroupManager::<DefaultMessageStore>::new(
+            ArcMut::new(BrokerConfig::default()),
+            None,
+        );
+        let group = CheetahString::from_static_str("master_group");
+        let mut master = SubscriptionGroupWrapper::default();
+        master
+            .subscription_group_table
+            .insert(group.clone(), SubscriptionGroupConfig::new(group.clone()));
+        master.data_version.next_version();
+        let json = master.to_json().unwrap();
 
-    pub fn forbidden_table(&self) -> &HashMap<CheetahString, HashMap<CheetahString, i32>> {
-        &self.forbidden_table
+        assert!(manager.replace_all_if_version_changed(json.as_str()));
+        let local = manager.subscription_group_wrapper.lock().clone();
+        assert_eq!(local.subscription_group_table.len(), 1);
+        assert!(local.subscription_group_table.contains_key(&group));
+        assert!(!manager.replace_all_if_version_changed(json.as_str()));
     }
 }
@@ -47,7 +47,7 @@ use crate::broker_runtime::BrokerRuntimeInner;
 pub(crate) struct TopicConfigManager {
     topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
     data_version: ArcMut<DataVersion>,
-    broker_config: Arc<BrokerConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     message_store: Option<ArcMut<DefaultMessageStore>>,
     topic_config_table_lock: Arc<parking_lot::ReentrantMutex<()>>,
     broker_runtime_inner: Arc<BrokerRuntimeInner>,
@@ -70,7 +70,7 @@ impl TopicConfigManager {
     const SCHEDULE_TOPIC_QUEUE_NUM: u32 = 18;
 
     pub fn new(
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         broker_runtime_inner: Arc<BrokerRuntimeInner>,
     ) -> Self {
         let mut manager = Self {
@@ -97,7 +97,7 @@ impl TopicConfigManager {
 
         //auto create topic setting
         {
-            if self.broker_config.auto_create_topic_enable {
+            if self.broker_config.auto_create_topic_enable.get() {
                 let default_topic_queue_nums = self
                     .broker_config
                     .topic_queue_config
@@ -281,7 +281,7 @@ impl TopicConfigManager {
 
             if let Some(mut default_topic_config) = self.get_topic_config(default_topic) {
                 if default_topic == TopicValidator::AUTO_CREATE_TOPIC_KEY_TOPIC
-                    && !self.broker_config.auto_create_topic_enable
+                    && !self.broker_config.auto_create_topic_enable.get()
                 {
                     default_topic_config.perm = PermName::PERM_READ | PermName::PERM_WRITE;
                 }
@@ -384,13 +384,16 @@ impl TopicConfigManager {
         let broker_config = self.broker_config.clone();
         let broker_runtime_inner = self.broker_runtime_inner.clone();
         let topic_config_clone = topic_config.clone();
+        let data_version = self.data_version.as_ref().clone();
         tokio::spawn(async move {
             if broker_config.enable_single_topic_register {
                 broker_runtime_inner
                     .register_single_topic_all(topic_config_clone)
                     .await;
             } else {
-                unimplemented!()
+                broker_runtime_inner
+                    .register_increment_broker_data(vec![topic_config_clone], data_version)
+                    .await;
             }
         });
     }
@@ -16,7 +16,6 @@
  */
 
 use std::collections::HashMap;
-use std::sync::Arc;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
@@ -27,8 +26,11 @@ use rocketmq_remoting::protocol::header::message_operation_header::TopicRequestH
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_context::TopicQueueMappingContext;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_detail::TopicQueueMappingDetail;
+use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_utils::TopicQueueMappingUtils;
 use rocketmq_remoting::protocol::DataVersion;
 use rocketmq_remoting::protocol::RemotingSerializable;
+use rocketmq_remoting::remoting_error::RemotingError;
+use rocketmq_rust::ArcMut;
 use tracing::info;
 use tracing::warn;
 
@@ -39,11 +41,11 @@ pub(crate) struct TopicQueueMappingManager {
     pub(crate) data_version: parking_lot::Mutex<DataVersion>,
     pub(crate) topic_queue_mapping_table:
         parking_lot::Mutex<HashMap<CheetahString /* topic */, TopicQueueMappingDetail>>,
-    pub(crate) broker_config: Arc<BrokerConfig>,
+    pub(crate) broker_config: ArcMut<BrokerConfig>,
 }
 
 impl TopicQueueMappingManager {
-    pub(crate) fn new(broker_config: Arc<BrokerConfig>) -> Self {
+    pub(crate) fn new(broker_config: ArcMut<BrokerConfig>) -> Self {
         Self {
             broker_config,
             ..Default::default()
@@ -194,6 +196,108 @@ impl TopicQueueMappingManager {
         self.topic_queue_mapping_table.lock().get(topic).cloned()
     }
 
+    /// Installs the mapping detail of a static topic, refusing to move the logical queues
+    /// this broker already knows unless `force` is set.
+    pub(crate) fn update_topic_queue_mapping(
+        &self,
+        mut new_detail: TopicQueueMappingDetail,
+        force: bool,
+        is_clean: bool,
+        flush: bool,
+    ) -> rocketmq_remoting::Result<()> {
+        let topic = new_detail
+            .topic_queue_mapping_info
+            .topic
+            .clone()
+            .ok_or_else(|| {
+                RemotingError::IllegalArgument("The topic of the mapping is null".to_string())
+            })?;
+        if new_detail.topic_queue_mapping_info.bname.as_ref()
+            != Some(&self.broker_config.broker_name)
+        {
+            return Err(RemotingError::IllegalArgument(format!(
+                "The broker name is not equal {:?} != {}",
+                new_detail.topic_queue_mapping_info.bname, self.broker_config.broker_name
+            )));
+        }
+        if let Some(hosted_queues) = new_detail.hosted_queues.as_ref() {
+            for items in hosted_queues.values() {
+                TopicQueueMappingUtils::check_logic_queue_mapping_item_offset(items)?;
+            }
+        }
+
+        let mut table = self.topic_queue_mapping_table.lock();
+        let Some(old_detail) = table.get(&topic) else {
+            table.insert(topic, new_detail);
+            drop(table);
+            self.data_version.lock().next_version();
+            if flush {
+                self.persist();
+            }
+            return Ok(());
+        };
+        if force {
+            // Keep the queues only known to the old mapping, they are cleaned separately
+            if !is_clean {
+                if let Some(old_hosted_queues) = old_detail.hosted_queues.as_ref() {
+                    let new_hosted_queues =
+                        new_detail.hosted_queues.get_or_insert_with(HashMap::new);
+                    for (global_id, items) in old_hosted_queues {
+                        new_hosted_queues
+                            .entry(*global_id)
+                            .or_insert_with(|| items.clone());
+                    }
+                }
+            }
+        } else {
+            let old_info = &old_detail.topic_queue_mapping_info;
+            let new_info = &new_detail.topic_queue_mapping_info;
+            if new_info.epoch < old_info.epoch {
+                return Err(RemotingError::IllegalArgument(format!(
+                    "Can't accept data with small epoch {} < {}",
+                    new_info.epoch, old_info.epoch
+                )));
+            }
+            if new_info.scope != old_info.scope {
+                return Err(RemotingError::IllegalArgument(format!(
+                    "Can't accept data with unmatched scope {:?} != {:?}",
+                    new_info.scope, old_info.scope
+                )));
+            }
+            let epoch_equal = new_info.epoch == old_info.epoch;
+            if let Some(old_hosted_queues) = old_detail.hosted_queues.as_ref() {
+                let new_hosted_queues = new_detail.hosted_queues.get_or_insert_with(HashMap::new);
+                for (global_id, old_items) in old_hosted_queues {
+                    match new_hosted_queues.get(global_id) {
+                        None => {
+                            if epoch_equal {
+                                return Err(RemotingError::IllegalArgument(format!(
+                                    "Can't accept data with equal epoch but missing queue {}",
+                                    global_id
+                                )));
+                            }
+                            new_hosted_queues.insert(*global_id, old_items.clone());
+                        }
+                        Some(new_items) => {
+                            TopicQueueMappingUtils::make_sure_logic_queue_mapping_item_immutable(
+                                old_items,
+                                new_items,
+                                epoch_equal,
+                            )?;
+                        }
+                    }
+                }
+            }
+        }
+        table.insert(topic, new_detail);
+        drop(table);
+        self.data_version.lock().next_version();
+        if flush {
+            self.persist();
+        }
+        Ok(())
+    }
+
     pub fn delete(&self, topic: &CheetahString) {
         let old = self.topic_queue_mapping_table.lock().remove(topic);
         match old {
@@ -260,22 +364,27 @@ mod tests {
     use std::sync::Arc;
 
     use rocketmq_common::common::broker::broker_config::BrokerConfig;
+    use rocketmq_remoting::protocol::static_topic::logic_queue_mapping_item::LogicQueueMappingItem;
+    use rocketmq_remoting::protocol::static_topic::topic_queue_info::TopicQueueMappingInfo;
 
     use super::*;
 
     #[test]
     fn new_creates_default_manager() {
-        let broker_config = Arc::new(BrokerConfig::default());
+        let broker_config = ArcMut::new(BrokerConfig::default());
         let manager = TopicQueueMappingManager::new(broker_config.clone());
 
-        assert_eq!(Arc::ptr_eq(&manager.broker_config, &broker_config), true);
+        assert_eq!(
+            Arc::ptr_eq(manager.broker_config.get_inner(), broker_config.get_inner()),
+            true
+        );
         assert_eq!(manager.data_version.lock().get_state_version(), 0);
         assert_eq!(manager.topic_queue_mapping_table.lock().len(), 0);
     }
 
     #[test]
     fn get_topic_queue_mapping_returns_none_for_non_existent_topic() {
-        let broker_config = Arc::new(BrokerConfig::default());
+        let broker_config = ArcMut::new(BrokerConfig::default());
         let manager = TopicQueueMappingManager::new(broker_config);
 
         assert!(manager
@@ -285,7 +394,7 @@ mod tests {
 
     #[test]
     fn get_topic_queue_mapping_returns_mapping_for_existing_topic() {
-        let broker_config = Arc::new(BrokerConfig::default());
+        let broker_config = ArcMut::new(BrokerConfig::default());
         let manager = TopicQueueMappingManager::new(broker_config);
         let detail = TopicQueueMappingDetail::default();
         manager.topic_queue_mapping_table.lock().insert(
@@ -298,7 +407,7 @@ mod tests {
 
     #[test]
     fn delete_removes_existing_topic() {
-        let broker_config = Arc::new(BrokerConfig::default());
+        let broker_config = ArcMut::new(BrokerConfig::default());
         let manager = TopicQueueMappingManager::new(broker_config);
         let detail = TopicQueueMappingDetail::default();
         manager
@@ -310,4 +419,105 @@ mod tests {
 
         assert!(manager.get_topic_queue_mapping("existing_topic").is_none());
     }
+
+    fn mapping_detail(
+        epoch: i64,
+        hosted_queues: HashMap<i32, Vec<LogicQueueMappingItem>>,
+    ) -> TopicQueueMappingDetail {
+        TopicQueueMappingDetail {
+            topic_queue_mapping_info: TopicQueueMappingInfo::new(
+                "static_topic".into(),
+                2,
+                BrokerConfig::default().broker_name,
+                epoch,
+            ),
+            hosted_queues: Some(hosted_queues),
+        }
+    }
+
+    fn mapping_item(gen: i32, bname: &str, logic_offset: i64) -> LogicQueueMappingItem {
+        LogicQueueMappingItem {
+            gen,
+            queue_id: 0,
+            bname: Some(bname.into()),
+            logic_offset,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn update_topic_queue_mapping_keeps_known_queues() {
+        let manager = TopicQueueMappingManager::new(ArcMut::new(BrokerConfig::default()));
+        let broker_name = BrokerConfig::default().broker_name;
+        let detail = mapping_detail(
+            1,
+            HashMap::from([
+                (0, vec![mapping_item(0, &broker_name, 0)]),
+                (1, vec![mapping_item(0, &broker_name, 0)]),
+            ]),
+        );
+        manager
+            .update_topic_queue_mapping(detail, false, false, false)
+            .unwrap();
+        assert_eq!(manager.data_version.lock().get_counter(), 1);
+
+        // a newer epoch may drop a queue, the broker keeps serving it
+        let detail = mapping_detail(
+            2,
+            HashMap::from([(0, vec![mapping_item(0, &broker_name, 0)])]),
+        );
+        manager
+            .update_topic_queue_mapping(detail, false, false, false)
+            .unwrap();
+        let current = manager.get_topic_queue_mapping("static_topic").unwrap();
+        assert_eq!(current.topic_queue_mapping_info.epoch, 2);
+        assert_eq!(current.hosted_queues.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn update_topic_queue_mapping_rejects_stale_or_moved_queues() {
+        let manager = TopicQueueMappingManager::new(ArcMut::new(BrokerConfig::default()));
+        let broker_name = BrokerConfig::default().broker_name;
+        let detail = mapping_detail(
+            2,
+            HashMap::from([(0, vec![mapping_item(0, &broker_name, 0)])]),
+        );
+        manager
+            .update_topic_queue_mapping(detail, false, false, false)
+            .unwrap();
+
+        let stale = mapping_detail(
+            1,
+            HashMap::from([(0, vec![mapping_item(0, &broker_name, 0)])]),
+        );
+        assert!(manager
+            .update_topic_queue_mapping(stale, false, false, false)
+            .is_err());
+
+        let moved = mapping_detail(3, HashMap::from([(0, vec![mapping_item(0, "other", 0)])]));
+        assert!(manager
+            .update_topic_queue_mapping(moved.clone(), false, false, false)
+            .is_err());
+        manager
+            .update_topic_queue_mapping(moved, true, false, false)
+            .unwrap();
+        assert_eq!(
+            manager
+                .get_topic_queue_mapping("static_topic")
+                .unwrap()
+                .topic_queue_mapping_info
+                .epoch,
+            3
+        );
+    }
+
+    #[test]
+    fn update_topic_queue_mapping_rejects_other_broker() {
+        let manager = TopicQueueMappingManager::new(ArcMut::new(BrokerConfig::default()));
+        let mut detail = mapping_detail(1, HashMap::new());
+        detail.topic_queue_mapping_info.bname = Some("other".into());
+        assert!(manager
+            .update_topic_queue_mapping(detail, false, false, false)
+            .is_err());
+    }
 }
@@ -54,12 +54,12 @@ pub(crate) struct TopicRouteInfoManager {
         ArcMut<HashMap<CheetahString /* topic */, TopicPublishInfo>>,
     pub(crate) topic_subscribe_info_table:
         ArcMut<HashMap<CheetahString /* topic */, HashSet<MessageQueue>>>,
-    pub(crate) broker_config: Arc<BrokerConfig>,
+    pub(crate) broker_config: ArcMut<BrokerConfig>,
     pub(crate) broker_outer_api: Arc<BrokerOuterAPI>,
 }
 
 impl TopicRouteInfoManager {
-    pub fn new(broker_outer_api: Arc<BrokerOuterAPI>, broker_config: Arc<BrokerConfig>) -> Self {
+    pub fn new(broker_outer_api: Arc<BrokerOuterAPI>, broker_config: ArcMut<BrokerConfig>) -> Self {
         TopicRouteInfoManager {
             lock: Arc::new(RocketMQTokioMutex::new(())),
             topic_route_table: ArcMut::new(HashMap::new()),
@@ -50,7 +50,7 @@ pub struct DefaultTransactionalMessageCheckListener<MS> {
 
 impl<MS> DefaultTransactionalMessageCheckListener<MS> {
     pub fn new(
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         producer_manager: Arc<ProducerManager>,
         broker_client: Broker2Client,
         topic_config_manager: TopicConfigManager,
@@ -112,14 +112,14 @@ where
 
 #[derive(Clone)]
 struct TransactionalMessageCheckListenerInner {
-    broker_config: Arc<BrokerConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     producer_manager: Arc<ProducerManager>,
     broker_client: ArcMut<Broker2Client>,
 }
 
 impl TransactionalMessageCheckListenerInner {
     pub fn new(
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         producer_manager: Arc<ProducerManager>,
         broker_client: Broker2Client,
     ) -> Self {
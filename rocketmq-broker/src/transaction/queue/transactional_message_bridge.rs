@@ -61,7 +61,7 @@ pub struct TransactionalMessageBridge<MS> {
     pub(crate) store_host: SocketAddr,
     pub(crate) broker_stats_manager: Arc<BrokerStatsManager>,
     pub(crate) consumer_offset_manager: ConsumerOffsetManager,
-    pub(crate) broker_config: Arc<BrokerConfig>,
+    pub(crate) broker_config: ArcMut<BrokerConfig>,
     pub(crate) topic_config_manager: TopicConfigManager,
 }
 
@@ -73,7 +73,7 @@ where
         message_store: ArcMut<MS>,
         broker_stats_manager: Arc<BrokerStatsManager>,
         consumer_offset_manager: ConsumerOffsetManager,
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         topic_config_manager: TopicConfigManager,
     ) -> Self {
         let store_host = format!("{}:{}", broker_config.broker_ip1, broker_config.listen_port)
@@ -31,6 +31,7 @@ use rocketmq_common::common::topic::TopicValidator;
 use rocketmq_common::utils::queue_type_utils::QueueTypeUtils;
 use rocketmq_common::MessageDecoder::message_properties_to_string;
 use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_rust::ArcMut;
 use rocketmq_store::base::message_result::PutMessageResult;
 use rocketmq_store::base::message_status_enum::PutMessageStatus;
 use rocketmq_store::config::broker_role::BrokerRole;
@@ -52,7 +53,7 @@ pub struct HookUtils;
 impl HookUtils {
     pub fn check_before_put_message(
         message_store: &impl MessageStore,
-        message_store_config: &Arc<MessageStoreConfig>,
+        message_store_config: &ArcMut<MessageStoreConfig>,
         msg: &MessageExt,
     ) -> Option<PutMessageResult> {
         if message_store.is_shutdown() {
@@ -131,6 +132,19 @@ impl HookUtils {
             ));
         }
 
+        if message_store_config.transient_store_pool_enable
+            && message_store_config.fast_fail_if_no_buffer_in_store_pool
+            && message_store.is_transient_store_pool_deficient()
+        {
+            let value = PRINT_TIMES.fetch_add(1, Ordering::Relaxed);
+            if (value % 50000) == 0 {
+                warn!("transient store pool is deficient, so putMessage is under flow control");
+            }
+            return Some(PutMessageResult::new_default(
+                PutMessageStatus::OsPageCacheBusy,
+            ));
+        }
+
         None
     }
 
@@ -168,7 +182,7 @@ impl HookUtils {
     pub fn handle_schedule_message(
         timer_message_store: &TimerMessageStore,
         schedule_message_service: &ScheduleMessageService,
-        message_store_config: &Arc<MessageStoreConfig>,
+        message_store_config: &ArcMut<MessageStoreConfig>,
         msg: &mut MessageExtBrokerInner,
     ) -> Option<PutMessageResult> {
         let tran_type = MessageSysFlag::get_transaction_value(msg.sys_flag());
@@ -257,7 +271,7 @@ impl HookUtils {
 
     fn transform_timer_message(
         timer_message_store: &TimerMessageStore,
-        message_store_config: &Arc<MessageStoreConfig>,
+        message_store_config: &ArcMut<MessageStoreConfig>,
         msg: &mut MessageExtBrokerInner,
     ) -> Option<PutMessageResult> {
         let delay_level = msg.message_ext_inner.message.get_delay_time_level();
@@ -395,12 +409,51 @@ mod tests {
     use std::collections::HashMap;
     use std::sync::Arc;
 
+    use bytes::Bytes;
+    use rocketmq_common::common::broker::broker_config::BrokerConfig;
     use rocketmq_common::common::config::TopicConfig;
     use rocketmq_common::common::message::message_ext::MessageExt;
+    use rocketmq_common::common::message::MessageTrait;
     use rocketmq_store::base::message_status_enum::PutMessageStatus;
+    use rocketmq_store::message_store::default_message_store::DefaultMessageStore;
 
     use super::*;
 
+    #[test]
+    fn puts_are_flow_controlled_when_the_transient_store_pool_is_deficient() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let message_store_config = ArcMut::new(MessageStoreConfig {
+            store_path_root_dir: temp_dir.path().to_string_lossy().to_string().into(),
+            transient_store_pool_enable: true,
+            fast_fail_if_no_buffer_in_store_pool: true,
+            ..MessageStoreConfig::default()
+        });
+        // the pool is never initialized, so it has no buffer left
+        let store = DefaultMessageStore::new(
+            message_store_config.clone(),
+            ArcMut::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        );
+        assert!(store.is_transient_store_pool_deficient());
+        let mut msg = MessageExt::default();
+        msg.message.topic = "test_topic".into();
+        msg.set_body(Bytes::from_static(b"body"));
+
+        let result = HookUtils::check_before_put_message(&store, &message_store_config, &msg);
+        assert_eq!(
+            result.unwrap().put_message_status(),
+            PutMessageStatus::OsPageCacheBusy
+        );
+
+        let message_store_config = ArcMut::new(MessageStoreConfig {
+            fast_fail_if_no_buffer_in_store_pool: false,
+            ..message_store_config.as_ref().clone()
+        });
+        assert!(HookUtils::check_before_put_message(&store, &message_store_config, &msg).is_none());
+    }
+
     #[test]
     fn check_inner_batch_returns_message_illegal_when_inner_batch_flag_is_set_but_cq_type_is_not_batch_cq(
     ) {
@@ -0,0 +1,552 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::attribute::attribute_parser::AttributeParser;
+use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_common::utils::util_all;
+use rocketmq_common::MessageDecoder;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use rocketmq_tools::admin::broker_metadata_bundle::BrokerMetadataBundle;
+use rocketmq_tools::admin::default_mq_admin_ext::DefaultMQAdminExt;
+use rocketmq_tools::admin::mq_admin_ext_async::MQAdminExt;
+use rocketmq_tools::command_util::CommandUtil;
+use rocketmq_tools::tools_error::ToolsError;
+use rocketmq_tools::Result;
+use tabled::Table;
+use tabled::Tabled;
+
+use crate::command_line::Commands;
+
+const METADATA_TIMEOUT_MILLIS: u64 = 10_000;
+
+/// Runs an admin subcommand against the name server given by `namesrv_addr`, falling back to
+/// the `NAMESRV_ADDR` environment variable when it is absent.
+pub async fn execute(namesrv_addr: Option<String>, command: Commands) -> Result<()> {
+    let mut admin_ext = DefaultMQAdminExt::new();
+    if let Some(namesrv_addr) = namesrv_addr {
+        admin_ext.set_namesrv_addr(namesrv_addr);
+    }
+    admin_ext.start().await?;
+    let result = execute_with(&admin_ext, command).await;
+    admin_ext.shutdown().await;
+    result
+}
+
+async fn execute_with(admin_ext: &DefaultMQAdminExt, command: Commands) -> Result<()> {
+    match command {
+        Commands::ReadMessageLog { .. } => unreachable!("readMessageLog is not an admin command"),
+        Commands::UpdateTopic {
+            broker_addr,
+            cluster_name,
+            topic,
+            read_queue_nums,
+            write_queue_nums,
+            perm,
+            order,
+            attributes,
+        } => {
+            let attributes = match attributes {
+                Some(attributes) => match AttributeParser::parse_to_map(attributes.as_str()) {
+                    Ok(attributes) => attributes
+                        .into_iter()
+                        .map(|(key, value)| (key.into(), value.into()))
+                        .collect(),
+                    Err(err) => {
+                        return Err(ToolsError::IllegalArgumentError(format!(
+                            "invalid attributes: {}",
+                            err
+                        )));
+                    }
+                },
+                None => HashMap::new(),
+            };
+            let topic_config = TopicConfig {
+                topic_name: Some(topic.into()),
+                read_queue_nums,
+                write_queue_nums,
+                perm,
+                order,
+                attributes,
+                ..Default::default()
+            };
+            let addrs = target_addrs(admin_ext, broker_addr, cluster_name).await?;
+            for addr in addrs {
+                admin_ext
+                    .create_and_update_topic_config(addr.clone(), topic_config.clone())
+                    .await?;
+                println!("create topic to {} success.", addr);
+            }
+            println!("{:?}", topic_config);
+        }
+        Commands::DeleteTopic {
+            topic,
+            cluster_name,
+        } => {
+            admin_ext
+                .delete_topic(topic.clone().into(), cluster_name.clone().into())
+                .await?;
+            println!(
+                "delete topic [{}] from cluster [{}] success.",
+                topic, cluster_name
+            );
+        }
+        Commands::TopicList => {
+            let topic_list = admin_ext.fetch_all_topic_list().await?;
+            for topic in topic_list.topic_list {
+                println!("{}", topic);
+            }
+        }
+        Commands::TopicRoute { topic } => {
+            let topic_route_data = admin_ext.examine_topic_route_info(topic.into()).await?;
+            println!(
+                "{}",
+                topic_route_data
+                    .to_json_pretty()
+                    .expect("encode TopicRouteData failed")
+            );
+        }
+        Commands::ClusterList { cluster_name } => {
+            print_cluster_list(admin_ext, cluster_name).await?;
+        }
+        Commands::ConsumerProgress {
+            group,
+            topic,
+            broker_addr,
+            cluster_name,
+        } => {
+            let consume_stats = admin_ext
+                .examine_consume_stats(
+                    group.into(),
+                    topic.map(CheetahString::from),
+                    cluster_name.map(CheetahString::from),
+                    broker_addr.map(CheetahString::from),
+                    None,
+                )
+                .await?;
+            let mut queues: Vec<_> = consume_stats.get_offset_table().iter().collect();
+            queues.sort_by_key(|(mq, _)| *mq);
+            let rows = queues
+                .into_iter()
+                .map(|(mq, offset_wrapper)| ConsumerProgressRow {
+                    topic: mq.get_topic().to_string(),
+                    broker_name: mq.get_broker_name().to_string(),
+                    queue_id: mq.get_queue_id(),
+                    broker_offset: offset_wrapper.get_broker_offset(),
+                    consumer_offset: offset_wrapper.get_consumer_offset(),
+                    diff: offset_wrapper.get_broker_offset() - offset_wrapper.get_consumer_offset(),
+                    inflight: offset_wrapper.get_pull_offset()
+                        - offset_wrapper.get_consumer_offset(),
+                    last_time: if offset_wrapper.get_last_timestamp() > 0 {
+                        util_all::time_millis_to_human_string2(offset_wrapper.get_last_timestamp())
+                    } else {
+                        "N/A".to_string()
+                    },
+                });
+            println!("{}", Table::new(rows));
+            println!("Consume TPS: {:.2}", consume_stats.get_consume_tps());
+            println!("Consume Diff Total: {}", consume_stats.compute_total_diff());
+            println!(
+                "Consume Inflight Total: {}",
+                consume_stats.compute_inflight_total_diff()
+            );
+        }
+        Commands::QueryMsgById { msg_id, topic } => {
+            let msg = admin_ext
+                .view_message(topic.unwrap_or_default().into(), msg_id.clone().into())
+                .await?;
+            print_msg(&msg_id, &msg);
+        }
+        Commands::ConsumerStatus {
+            group,
+            client_id,
+            jstack,
+        } => {
+            let consumer_running_info = admin_ext
+                .get_consumer_running_info(group.into(), client_id.into(), jstack, None)
+                .await?;
+            println!("{}", consumer_running_info.format_string());
+        }
+        Commands::ResetOffsetByTime {
+            group,
+            topic,
+            timestamp,
+            force,
+            cluster_name,
+        } => {
+            let Some(timestamp) = parse_reset_timestamp(&timestamp) else {
+                return Err(ToolsError::IllegalArgumentError(format!(
+                    "invalid timestamp: {}, expected now, currentTimeMillis or \
+                     yyyy-MM-dd#HH:mm:ss:SSS",
+                    timestamp
+                )));
+            };
+            let offset_table = admin_ext
+                .reset_offset_by_timestamp(
+                    cluster_name.map(CheetahString::from),
+                    topic.into(),
+                    group.into(),
+                    timestamp,
+                    force,
+                )
+                .await?;
+            let mut queues: Vec<_> = offset_table.into_iter().collect();
+            queues.sort_by_key(|(mq, _)| mq.clone());
+            let rows = queues.into_iter().map(|(mq, offset)| ResetOffsetRow {
+                broker_name: mq.get_broker_name().to_string(),
+                queue_id: mq.get_queue_id(),
+                offset,
+            });
+            println!("{}", Table::new(rows));
+        }
+        Commands::UpdateSubGroup {
+            broker_addr,
+            cluster_name,
+            group,
+            consume_enable,
+            consume_from_min_enable,
+            consume_broadcast_enable,
+            consume_message_orderly,
+            retry_queue_nums,
+            retry_max_times,
+        } => {
+            let mut config = SubscriptionGroupConfig::new(group.into());
+            if let Some(consume_enable) = consume_enable {
+                config.set_consume_enable(consume_enable);
+            }
+            if let Some(consume_from_min_enable) = consume_from_min_enable {
+                config.set_consume_from_min_enable(consume_from_min_enable);
+            }
+            if let Some(consume_broadcast_enable) = consume_broadcast_enable {
+                config.set_consume_broadcast_enable(consume_broadcast_enable);
+            }
+            if let Some(consume_message_orderly) = consume_message_orderly {
+                config.set_consume_message_orderly(consume_message_orderly);
+            }
+            if let Some(retry_queue_nums) = retry_queue_nums {
+                config.set_retry_queue_nums(retry_queue_nums);
+            }
+            if let Some(retry_max_times) = retry_max_times {
+                config.set_retry_max_times(retry_max_times);
+            }
+            let addrs = target_addrs(admin_ext, broker_addr, cluster_name).await?;
+            for addr in addrs {
+                admin_ext
+                    .create_and_update_subscription_group_config(addr.clone(), config.clone())
+                    .await?;
+                println!("create subscription group to {} success.", addr);
+            }
+            println!("{:?}", config);
+        }
+        Commands::BrokerStatus {
+            broker_addr,
+            cluster_name,
+        } => {
+            let addrs = match broker_addr {
+                Some(broker_addr) => HashSet::from([CheetahString::from(broker_addr)]),
+                None => {
+                    CommandUtil::fetch_master_and_slave_addr_by_cluster_name(
+                        admin_ext,
+                        &CheetahString::from(cluster_name.unwrap_or_default()),
+                    )
+                    .await?
+                }
+            };
+            for addr in addrs {
+                let kv_table = admin_ext.fetch_broker_runtime_stats(addr.clone()).await?;
+                let mut rows: Vec<_> = kv_table
+                    .table
+                    .into_iter()
+                    .map(|(key, value)| BrokerStatusRow {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                    })
+                    .collect();
+                rows.sort_by_key(|row| row.key.clone());
+                println!("broker: {}", addr);
+                println!("{}", Table::new(rows));
+            }
+        }
+        Commands::ExportMetadata {
+            broker_addr,
+            file,
+            system,
+        } => {
+            let bundle = BrokerMetadataBundle::export(
+                admin_ext,
+                broker_addr.into(),
+                system,
+                METADATA_TIMEOUT_MILLIS,
+            )
+            .await?;
+            let json = bundle.to_json_pretty().map_err(|err| {
+                ToolsError::MetadataFileError(format!("encode broker metadata failed: {}", err))
+            })?;
+            std::fs::write(&file, json).map_err(|err| {
+                ToolsError::MetadataFileError(format!("write {} failed: {}", file.display(), err))
+            })?;
+            println!(
+                "export {} topics, {} subscription groups and {} consumer offsets to {} success.",
+                bundle.topic_config_table.len(),
+                bundle.subscription_group_table.len(),
+                bundle.offsets().len(),
+                file.display()
+            );
+        }
+        Commands::ImportMetadata { broker_addr, file } => {
+            let bundle = std::fs::read(&file)
+                .map_err(|err| err.to_string())
+                .and_then(|content| {
+                    BrokerMetadataBundle::decode(&content).map_err(|err| err.to_string())
+                })
+                .map_err(|err| {
+                    ToolsError::MetadataFileError(format!(
+                        "read {} failed: {}",
+                        file.display(),
+                        err
+                    ))
+                })?;
+            let broker_addr = CheetahString::from(broker_addr);
+            let Some(broker_name) = broker_name_of(admin_ext, &broker_addr).await? else {
+                return Err(ToolsError::IllegalArgumentError(format!(
+                    "broker {} is not registered to the name server",
+                    broker_addr
+                )));
+            };
+            let result = bundle
+                .import(admin_ext, broker_addr.clone(), broker_name)
+                .await;
+            for failure in &result.failures {
+                println!("import failed, {}", failure);
+            }
+            println!(
+                "import {} topics, {} subscription groups and {} consumer offsets to {} success.",
+                result.topics, result.subscription_groups, result.offsets, broker_addr
+            );
+            if !result.failures.is_empty() {
+                return Err(ToolsError::MetadataFileError(format!(
+                    "{} entries of {} failed to import",
+                    result.failures.len(),
+                    file.display()
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the name of the broker listening on `broker_addr`, as registered to the name server.
+async fn broker_name_of(
+    admin_ext: &DefaultMQAdminExt,
+    broker_addr: &CheetahString,
+) -> Result<Option<CheetahString>> {
+    let cluster_info = admin_ext.examine_broker_cluster_info().await?;
+    Ok(cluster_info.broker_addr_table.and_then(|table| {
+        table.into_values().find_map(|broker_data| {
+            broker_data
+                .broker_addrs()
+                .values()
+                .any(|addr| addr == broker_addr)
+                .then(|| broker_data.broker_name().clone())
+        })
+    }))
+}
+
+async fn target_addrs(
+    admin_ext: &DefaultMQAdminExt,
+    broker_addr: Option<String>,
+    cluster_name: Option<String>,
+) -> Result<HashSet<CheetahString>> {
+    match broker_addr {
+        Some(broker_addr) => Ok(HashSet::from([CheetahString::from(broker_addr)])),
+        None => {
+            CommandUtil::fetch_master_addr_by_cluster_name(
+                admin_ext,
+                &CheetahString::from(cluster_name.unwrap_or_default()),
+            )
+            .await
+        }
+    }
+}
+
+async fn print_cluster_list(
+    admin_ext: &DefaultMQAdminExt,
+    cluster_name: Option<String>,
+) -> Result<()> {
+    let cluster_info = admin_ext.examine_broker_cluster_info().await?;
+    let (Some(cluster_addr_table), Some(broker_addr_table)) = (
+        cluster_info.cluster_addr_table.as_ref(),
+        cluster_info.broker_addr_table.as_ref(),
+    ) else {
+        return Ok(());
+    };
+    let mut cluster_names: Vec<_> = match cluster_name {
+        Some(cluster_name) => vec![CheetahString::from(cluster_name)],
+        None => cluster_addr_table.keys().cloned().collect(),
+    };
+    cluster_names.sort();
+
+    let mut rows = Vec::new();
+    for cluster_name in cluster_names {
+        let Some(broker_names) = cluster_addr_table.get(&cluster_name) else {
+            continue;
+        };
+        let mut broker_names: Vec<_> = broker_names.iter().collect();
+        broker_names.sort();
+        for broker_name in broker_names {
+            let Some(broker_data) = broker_addr_table.get(broker_name) else {
+                continue;
+            };
+            let mut broker_addrs: Vec<_> = broker_data.broker_addrs().iter().collect();
+            broker_addrs.sort();
+            for (broker_id, addr) in broker_addrs {
+                let runtime_stats = admin_ext
+                    .fetch_broker_runtime_stats(addr.clone())
+                    .await
+                    .map(|kv_table| kv_table.table)
+                    .unwrap_or_default();
+                let stat = |key: &str| {
+                    runtime_stats
+                        .get(key)
+                        .map(|value| value.to_string())
+                        .unwrap_or_default()
+                };
+                rows.push(ClusterListRow {
+                    cluster_name: cluster_name.to_string(),
+                    broker_name: broker_name.to_string(),
+                    broker_id: *broker_id,
+                    addr: addr.to_string(),
+                    version: stat("brokerVersionDesc"),
+                    in_total_today: stat("msgPutTotalTodayNow"),
+                    out_total_today: stat("msgGetTotalTodayNow"),
+                });
+            }
+        }
+    }
+    println!("{}", Table::new(rows));
+    Ok(())
+}
+
+fn print_msg(msg_id: &str, msg: &MessageExt) {
+    println!("{:<20} {}", "OffsetID:", msg_id);
+    println!("{:<20} {}", "Topic:", msg.get_topic());
+    println!("{:<20} [{}]", "Tags:", msg.get_tags().unwrap_or_default());
+    println!("{:<20} [{}]", "Keys:", msg.get_keys().unwrap_or_default());
+    println!("{:<20} {}", "Queue ID:", msg.queue_id());
+    println!("{:<20} {}", "Queue Offset:", msg.queue_offset());
+    println!("{:<20} {}", "CommitLog Offset:", msg.commit_log_offset());
+    println!("{:<20} {}", "Reconsume Times:", msg.reconsume_times());
+    println!(
+        "{:<20} {}",
+        "Born Timestamp:",
+        util_all::time_millis_to_human_string2(msg.born_timestamp())
+    );
+    println!(
+        "{:<20} {}",
+        "Store Timestamp:",
+        util_all::time_millis_to_human_string2(msg.store_timestamp())
+    );
+    println!("{:<20} {}", "Born Host:", msg.born_host());
+    println!("{:<20} {}", "Store Host:", msg.store_host());
+    println!("{:<20} {}", "System Flag:", msg.sys_flag());
+    println!(
+        "{:<20} {}",
+        "Properties:",
+        MessageDecoder::message_properties_to_string(msg.get_properties())
+    );
+    println!(
+        "{:<20} {}",
+        "Message Body:",
+        msg.get_body()
+            .map(|body| String::from_utf8_lossy(body).into_owned())
+            .unwrap_or_default()
+    );
+}
+
+fn parse_reset_timestamp(timestamp: &str) -> Option<u64> {
+    if timestamp == "now" {
+        return Some(get_current_millis());
+    }
+    if let Ok(millis) = timestamp.parse::<u64>() {
+        return Some(millis);
+    }
+    util_all::parse_date(timestamp, "%Y-%m-%d#%H:%M:%S:%3f")
+        .map(|date| date.and_utc().timestamp_millis() as u64)
+}
+
+#[derive(Tabled)]
+struct ClusterListRow {
+    #[tabled(rename = "#Cluster Name")]
+    cluster_name: String,
+    #[tabled(rename = "#Broker Name")]
+    broker_name: String,
+    #[tabled(rename = "#BID")]
+    broker_id: u64,
+    #[tabled(rename = "#Addr")]
+    addr: String,
+    #[tabled(rename = "#Version")]
+    version: String,
+    #[tabled(rename = "#InTotalToday")]
+    in_total_today: String,
+    #[tabled(rename = "#OutTotalToday")]
+    out_total_today: String,
+}
+
+#[derive(Tabled)]
+struct ConsumerProgressRow {
+    #[tabled(rename = "#Topic")]
+    topic: String,
+    #[tabled(rename = "#Broker Name")]
+    broker_name: String,
+    #[tabled(rename = "#QID")]
+    queue_id: i32,
+    #[tabled(rename = "#Broker Offset")]
+    broker_offset: i64,
+    #[tabled(rename = "#Consumer Offset")]
+    consumer_offset: i64,
+    #[tabled(rename = "#Diff")]
+    diff: i64,
+    #[tabled(rename = "#Inflight")]
+    inflight: i64,
+    #[tabled(rename = "#LastTime")]
+    last_time: String,
+}
+
+#[derive(Tabled)]
+struct ResetOffsetRow {
+    #[tabled(rename = "#brokerName")]
+    broker_name: String,
+    #[tabled(rename = "#queueId")]
+    queue_id: i32,
+    #[tabled(rename = "#offset")]
+    offset: u64,
+}
+
+#[derive(Tabled)]
+struct BrokerStatusRow {
+    #[tabled(rename = "#Key")]
+    key: String,
+    #[tabled(rename = "#Value")]
+    value: String,
+}
@@ -15,15 +15,24 @@
  * limitations under the License.
  */
 use clap::Parser;
+use rocketmq_cli::admin_command;
 use rocketmq_cli::command_line::Commands;
 use rocketmq_cli::command_line::RootCli;
 use rocketmq_cli::content_show::print_content;
+use rocketmq_rust::rocketmq;
 
-fn main() {
+#[rocketmq::main]
+async fn main() {
     let cli = RootCli::parse();
     match cli.command {
         Commands::ReadMessageLog { config, from, to } => {
             print_content(from, to, config);
         }
+        command => {
+            if let Err(err) = admin_command::execute(cli.namesrv_addr, command).await {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        }
     }
 }
@@ -16,12 +16,22 @@
  */
 use std::path::PathBuf;
 
+use clap::ArgGroup;
 use clap::Parser;
 use clap::Subcommand;
 
 #[derive(Parser, Debug)]
 #[command(author = "mxsm", version = "0.2.0", about = "RocketMQ CLI(Rust)")]
 pub struct RootCli {
+    #[arg(
+        short = 'n',
+        long,
+        global = true,
+        value_name = "NAMESRV_ADDR",
+        help = "Name server address list, eg: '192.168.0.1:9876;192.168.0.2:9876'"
+    )]
+    pub namesrv_addr: Option<String>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -64,4 +74,226 @@ pub enum Commands {
         )]
         to: Option<u32>,
     },
+
+    #[command(
+        name = "updateTopic",
+        about = "Update or create topic",
+        group(ArgGroup::new("target").required(true).args(["broker_addr", "cluster_name"]))
+    )]
+    UpdateTopic {
+        #[arg(short = 'b', long, help = "create topic to which broker")]
+        broker_addr: Option<String>,
+
+        #[arg(short = 'c', long, help = "create topic to which cluster")]
+        cluster_name: Option<String>,
+
+        #[arg(short = 't', long, help = "topic name")]
+        topic: String,
+
+        #[arg(short = 'r', long, default_value_t = 8, help = "set read queue nums")]
+        read_queue_nums: u32,
+
+        #[arg(short = 'w', long, default_value_t = 8, help = "set write queue nums")]
+        write_queue_nums: u32,
+
+        #[arg(
+            short = 'p',
+            long,
+            default_value_t = 6,
+            help = "set topic's permission(2|4|6)"
+        )]
+        perm: u32,
+
+        #[arg(short = 'o', long, default_value_t = false, help = "set topic's order")]
+        order: bool,
+
+        #[arg(
+            short = 'a',
+            long,
+            help = "attribute(+a=b,+c=d,-e), only add attributes are supported when creating"
+        )]
+        attributes: Option<String>,
+    },
+
+    #[command(
+        name = "deleteTopic",
+        about = "Delete topic from broker and NameServer"
+    )]
+    DeleteTopic {
+        #[arg(short = 't', long, help = "topic name")]
+        topic: String,
+
+        #[arg(short = 'c', long, help = "delete topic from which cluster")]
+        cluster_name: String,
+    },
+
+    #[command(name = "topicList", about = "Fetch all topic list from name server")]
+    TopicList,
+
+    #[command(name = "topicRoute", about = "Examine topic route info")]
+    TopicRoute {
+        #[arg(short = 't', long, help = "topic name")]
+        topic: String,
+    },
+
+    #[command(name = "clusterList", about = "List cluster infos")]
+    ClusterList {
+        #[arg(short = 'c', long, help = "which cluster")]
+        cluster_name: Option<String>,
+    },
+
+    #[command(name = "consumerProgress", about = "Query consumer's progress, speed")]
+    ConsumerProgress {
+        #[arg(short = 'g', long, help = "consumer group name")]
+        group: String,
+
+        #[arg(short = 't', long, help = "topic name")]
+        topic: Option<String>,
+
+        #[arg(short = 'b', long, help = "query progress from which broker")]
+        broker_addr: Option<String>,
+
+        #[arg(short = 'c', long, help = "query progress from which cluster")]
+        cluster_name: Option<String>,
+    },
+
+    #[command(name = "queryMsgById", about = "Query Message by Id")]
+    QueryMsgById {
+        #[arg(short = 'i', long, help = "Message Id")]
+        msg_id: String,
+
+        #[arg(short = 't', long, help = "topic name")]
+        topic: Option<String>,
+    },
+
+    #[command(
+        name = "consumerStatus",
+        about = "Query consumer's internal data structure"
+    )]
+    ConsumerStatus {
+        #[arg(short = 'g', long, help = "consumer group name")]
+        group: String,
+
+        #[arg(short = 'i', long, help = "The consumer's client id")]
+        client_id: String,
+
+        #[arg(
+            short = 's',
+            long,
+            help = "Run jstack command in the consumer progress"
+        )]
+        jstack: bool,
+    },
+
+    #[command(
+        name = "updateSubGroup",
+        about = "Update or create subscription group",
+        group(ArgGroup::new("target").required(true).args(["broker_addr", "cluster_name"]))
+    )]
+    UpdateSubGroup {
+        #[arg(short = 'b', long, help = "create subscription group to which broker")]
+        broker_addr: Option<String>,
+
+        #[arg(short = 'c', long, help = "create subscription group to which cluster")]
+        cluster_name: Option<String>,
+
+        #[arg(short = 'g', long, help = "consumer group name")]
+        group: String,
+
+        #[arg(short = 's', long, help = "consume enable")]
+        consume_enable: Option<bool>,
+
+        #[arg(short = 'm', long, help = "consume from min enable")]
+        consume_from_min_enable: Option<bool>,
+
+        #[arg(short = 'd', long, help = "broadcast")]
+        consume_broadcast_enable: Option<bool>,
+
+        #[arg(short = 'o', long, help = "consume message orderly")]
+        consume_message_orderly: Option<bool>,
+
+        #[arg(short = 'q', long, help = "retry queue nums")]
+        retry_queue_nums: Option<i32>,
+
+        #[arg(short = 'r', long, help = "retry max times")]
+        retry_max_times: Option<i32>,
+    },
+
+    #[command(
+        name = "resetOffsetByTime",
+        about = "Reset consumer offset by timestamp(without client restart)"
+    )]
+    ResetOffsetByTime {
+        #[arg(short = 'g', long, help = "set the consumer group")]
+        group: String,
+
+        #[arg(short = 't', long, help = "set the topic")]
+        topic: String,
+
+        #[arg(
+            short = 's',
+            long,
+            help = "set the timestamp[now|currentTimeMillis|yyyy-MM-dd#HH:mm:ss:SSS(UTC)]"
+        )]
+        timestamp: String,
+
+        #[arg(
+            short = 'f',
+            long,
+            default_value_t = true,
+            action = clap::ArgAction::Set,
+            help = "set the force rollback by timestamp switch[true|false]"
+        )]
+        force: bool,
+
+        #[arg(short = 'c', long, help = "Cluster name or lmq parent topic")]
+        cluster_name: Option<String>,
+    },
+
+    #[command(
+        name = "brokerStatus",
+        about = "Fetch broker runtime status data",
+        group(ArgGroup::new("target").required(true).args(["broker_addr", "cluster_name"]))
+    )]
+    BrokerStatus {
+        #[arg(short = 'b', long, help = "Broker address")]
+        broker_addr: Option<String>,
+
+        #[arg(short = 'c', long, help = "which cluster")]
+        cluster_name: Option<String>,
+    },
+
+    #[command(
+        name = "exportMetadata",
+        about = "Export the topics, subscription groups and consumer offsets of a broker to a \
+                 JSON file"
+    )]
+    ExportMetadata {
+        #[arg(short = 'b', long, help = "Broker address")]
+        broker_addr: String,
+
+        #[arg(short = 'f', long, value_name = "FILE", help = "export file path")]
+        file: PathBuf,
+
+        #[arg(
+            short = 's',
+            long,
+            default_value_t = false,
+            help = "also export system topics and subscription groups"
+        )]
+        system: bool,
+    },
+
+    #[command(
+        name = "importMetadata",
+        about = "Import topics, subscription groups and consumer offsets exported by \
+                 exportMetadata into a broker"
+    )]
+    ImportMetadata {
+        #[arg(short = 'b', long, help = "Broker address")]
+        broker_addr: String,
+
+        #[arg(short = 'f', long, value_name = "FILE", help = "import file path")]
+        file: PathBuf,
+    },
 }
@@ -15,5 +15,6 @@
  * limitations under the License.
  */
 
+pub mod admin_command;
 pub mod command_line;
 pub mod content_show;
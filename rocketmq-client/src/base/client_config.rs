@@ -22,6 +22,7 @@ use std::time::Duration;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::mix_all;
 use rocketmq_common::utils::name_server_address_utils::NameServerAddressUtils;
 use rocketmq_common::utils::name_server_address_utils::NAMESRV_ENDPOINT_PATTERN;
 use rocketmq_common::utils::network_util::NetworkUtil;
@@ -73,6 +74,12 @@ pub struct ClientConfig {
     pub enable_heartbeat_channel_event_listener: bool,
     pub enable_trace: bool,
     pub trace_topic: Option<CheetahString>,
+    pub enable_telemetry: bool,
+    /// The zone (availability zone) this client runs in.
+    pub zone_name: Option<CheetahString>,
+    /// When enabled, route queries ask the name server to return only the brokers of
+    /// `zone_name`.
+    pub zone_mode: bool,
 }
 
 impl Default for ClientConfig {
@@ -135,6 +142,17 @@ impl ClientConfig {
             enable_heartbeat_channel_event_listener: true,
             enable_trace: false,
             trace_topic: None,
+            enable_telemetry: false,
+            zone_name: env::var(mix_all::ROCKETMQ_ZONE_PROPERTY)
+                .or_else(|_| env::var(mix_all::ROCKETMQ_ZONE_ENV))
+                .ok()
+                .filter(|zone_name| !zone_name.trim().is_empty())
+                .map(CheetahString::from),
+            zone_mode: env::var(mix_all::ROCKETMQ_ZONE_MODE_PROPERTY)
+                .or_else(|_| env::var(mix_all::ROCKETMQ_ZONE_MODE_ENV))
+                .unwrap_or_else(|_| "false".to_string())
+                .parse::<bool>()
+                .unwrap_or(false),
         }
     }
 }
@@ -145,6 +163,14 @@ impl ClientConfig {
             .into()
     }
 
+    pub fn without_namespace(&mut self, resource: &str) -> CheetahString {
+        NamespaceUtil::without_namespace_with_namespace(
+            resource,
+            self.get_namespace().unwrap_or_default().as_str(),
+        )
+        .into()
+    }
+
     pub fn queue_with_namespace(&mut self, mut queue: MessageQueue) -> MessageQueue {
         if let Some(namespace) = self.get_namespace() {
             if !namespace.is_empty() {
@@ -220,3 +246,40 @@ impl ClientConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn namespace_wraps_and_unwraps_resources() {
+        let mut client_config = ClientConfig {
+            namespace: Some("MQ_INST_xxx".into()),
+            ..ClientConfig::default()
+        };
+        assert_eq!(client_config.with_namespace("TopicA"), "MQ_INST_xxx%TopicA");
+        assert_eq!(
+            client_config.with_namespace("MQ_INST_xxx%TopicA"),
+            "MQ_INST_xxx%TopicA"
+        );
+        assert_eq!(
+            client_config.without_namespace("MQ_INST_xxx%TopicA"),
+            "TopicA"
+        );
+        assert_eq!(
+            client_config.without_namespace("%RETRY%MQ_INST_xxx%GroupA"),
+            "%RETRY%GroupA"
+        );
+
+        let queue =
+            client_config.queue_with_namespace(MessageQueue::from_parts("TopicA", "broker-a", 0));
+        assert_eq!(queue.get_topic(), "MQ_INST_xxx%TopicA");
+    }
+
+    #[test]
+    fn namespace_absent_keeps_resources() {
+        let mut client_config = ClientConfig::default();
+        assert_eq!(client_config.with_namespace("TopicA"), "TopicA");
+        assert_eq!(client_config.without_namespace("TopicA"), "TopicA");
+    }
+}
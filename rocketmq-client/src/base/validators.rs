@@ -22,8 +22,13 @@ use rocketmq_common::common::constant::PermName;
 use rocketmq_common::common::message::MessageConst;
 use rocketmq_common::common::message::MessageTrait;
 use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_common::common::validation;
+use rocketmq_common::common::validation::ValidationError;
+use rocketmq_common::common::validation::ValidationErrorKind;
 use rocketmq_remoting::code::response_code::ResponseCode;
 
+use crate::client_error::ClientErr;
+use crate::client_error::MQClientError;
 use crate::mq_client_err;
 use crate::producer::default_mq_producer::ProducerConfig;
 use crate::Result;
@@ -31,26 +36,11 @@ use crate::Result;
 pub struct Validators;
 
 impl Validators {
-    pub const CHARACTER_MAX_LENGTH: usize = 255;
-    pub const TOPIC_MAX_LENGTH: usize = 127;
+    pub const CHARACTER_MAX_LENGTH: usize = validation::CHARACTER_MAX_LENGTH;
+    pub const TOPIC_MAX_LENGTH: usize = validation::TOPIC_MAX_LENGTH;
 
     pub fn check_group(group: &str) -> Result<()> {
-        if group.trim().is_empty() {
-            return mq_client_err!("the specified group is blank");
-        }
-
-        if group.len() > Self::CHARACTER_MAX_LENGTH {
-            return mq_client_err!("the specified group is longer than group max length 255.");
-        }
-
-        if TopicValidator::is_topic_or_group_illegal(group) {
-            return mq_client_err!(format!(
-                "the specified group[{}] contains illegal characters, allowing only \
-                 ^[%|a-zA-Z0-9_-]+$",
-                group
-            ));
-        }
-        Ok(())
+        validation::check_group(group).map_err(Self::to_client_error)
     }
 
     pub fn check_message<M>(msg: Option<&M>, producer_config: &ProducerConfig) -> Result<()>
@@ -82,15 +72,9 @@ impl Validators {
             );
         }
 
-        if length > producer_config.max_message_size() as usize {
-            return mq_client_err!(
-                ResponseCode::MessageIllegal as i32,
-                format!(
-                    "the message body size over max value, MAX: {}",
-                    producer_config.max_message_size()
-                )
-            );
-        }
+        validation::check_body_size(length, producer_config.max_message_size() as usize)
+            .map_err(Self::to_client_error)?;
+        validation::check_properties(msg.get_properties()).map_err(Self::to_client_error)?;
 
         let lmq_path = msg.get_user_property(&CheetahString::from_static_str(
             MessageConst::PROPERTY_INNER_MULTI_DISPATCH,
@@ -112,26 +96,7 @@ impl Validators {
     }
 
     pub fn check_topic(topic: &str) -> Result<()> {
-        if topic.trim().is_empty() {
-            return mq_client_err!("The specified topic is blank");
-        }
-
-        if topic.len() > Self::TOPIC_MAX_LENGTH {
-            return mq_client_err!(format!(
-                "The specified topic is longer than topic max length {}.",
-                Self::TOPIC_MAX_LENGTH
-            ));
-        }
-
-        if TopicValidator::is_topic_or_group_illegal(topic) {
-            return mq_client_err!(format!(
-                "The specified topic[{}] contains illegal characters, allowing only \
-                 ^[%|a-zA-Z0-9_-]+$",
-                topic
-            ));
-        }
-
-        Ok(())
+        validation::check_topic(topic).map_err(Self::to_client_error)
     }
 
     pub fn is_system_topic(topic: &str) -> Result<()> {
@@ -145,11 +110,7 @@ impl Validators {
     }
 
     pub fn is_not_allowed_send_topic(topic: &str) -> Result<()> {
-        if TopicValidator::is_not_allowed_send_topic(topic) {
-            return mq_client_err!(format!("Sending message to topic[{}] is forbidden.", topic));
-        }
-
-        Ok(())
+        validation::check_send_topic(topic).map_err(Self::to_client_error)
     }
 
     pub fn check_topic_config(topic_config: &TopicConfig) -> Result<()> {
@@ -175,6 +136,22 @@ impl Validators {
 
         Ok(())
     }
+
+    fn to_client_error(error: ValidationError) -> MQClientError {
+        let message = error.remark().to_string();
+        let client_err = match error.kind() {
+            ValidationErrorKind::IllegalMessage => {
+                ClientErr::new_with_code(ResponseCode::MessageIllegal as i32, message)
+            }
+            ValidationErrorKind::ForbiddenTopic => {
+                ClientErr::new_with_code(ResponseCode::NoPermission as i32, message)
+            }
+            ValidationErrorKind::IllegalTopic | ValidationErrorKind::IllegalGroup => {
+                ClientErr::new(message)
+            }
+        };
+        MQClientError::MQClientErr(client_err)
+    }
 }
 
 #[cfg(test)]
@@ -235,6 +212,22 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn check_message_rejects_oversized_body_with_message_illegal() {
+        let producer_config = ProducerConfig::default();
+        let body = vec![0u8; producer_config.max_message_size() as usize + 1];
+        let msg = rocketmq_common::common::message::message_single::Message::new(
+            "valid_topic",
+            body.as_slice(),
+        );
+        match Validators::check_message(Some(&msg), &producer_config) {
+            Err(MQClientError::MQClientErr(err)) => {
+                assert_eq!(err.response_code(), ResponseCode::MessageIllegal as i32)
+            }
+            _ => panic!("expected message illegal"),
+        }
+    }
+
     #[test]
     fn check_topic_config_invalid_permission() {
         let topic_config = TopicConfig {
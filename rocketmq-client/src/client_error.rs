@@ -27,6 +27,10 @@ pub enum MQClientError {
     #[error("{0}")]
     RemotingTooMuchRequestError(String),
 
+    /// The producer reached its limit of in-flight async sends.
+    #[error("{0}")]
+    ProducerBusy(String),
+
     #[error("{0}")]
     MQClientBrokerError(#[from] MQBrokerErr),
 
@@ -17,7 +17,9 @@
 pub(crate) mod ack_result;
 pub(crate) mod ack_status;
 pub mod allocate_message_queue_strategy;
+pub mod consume_result_hook;
 pub(crate) mod consumer_impl;
+pub mod default_lite_pull_consumer;
 pub mod default_mq_push_consumer;
 pub mod default_mq_push_consumer_builder;
 pub mod listener;
@@ -32,5 +34,6 @@ pub(crate) mod pull_callback;
 pub mod pull_result;
 pub mod pull_status;
 pub mod rebalance_strategy;
+pub(crate) mod retry_policy;
 pub(crate) mod store;
 pub mod topic_message_queue_change_listener;
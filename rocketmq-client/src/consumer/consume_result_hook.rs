@@ -0,0 +1,31 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_ext::MessageExt;
+
+/// Intercepts the outcome of a failed consumption before the message is sent back to the broker.
+pub trait ConsumeResultHook: Send + Sync {
+    fn hook_name(&self) -> &str;
+
+    /// Called before a failed message is sent back for another attempt with `delay_level`.
+    fn before_retry(&self, _consumer_group: &CheetahString, _msg: &MessageExt, _delay_level: i32) {}
+
+    /// Called when a message has used up its retries and is about to be moved to the dead letter
+    /// queue. Returning `true` means the hook took care of the message (e.g. persisted it
+    /// elsewhere), so it is acknowledged instead of being sent to the DLQ.
+    fn before_dead_letter(&self, consumer_group: &CheetahString, msg: &MessageExt) -> bool;
+}
@@ -16,11 +16,13 @@
  */
 use once_cell::sync::Lazy;
 
+pub(crate) mod assigned_message_queue;
 pub(crate) mod consume_message_concurrently_service;
 pub(crate) mod consume_message_orderly_service;
 pub(crate) mod consume_message_pop_concurrently_service;
 pub(crate) mod consume_message_pop_orderly_service;
 pub(crate) mod consume_message_service;
+pub(crate) mod default_lite_pull_consumer_impl;
 pub(crate) mod default_mq_push_consumer_impl;
 pub(crate) mod message_request;
 pub(crate) mod pop_process_queue;
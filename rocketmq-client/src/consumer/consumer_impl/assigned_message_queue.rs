@@ -0,0 +1,210 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use parking_lot::RwLock;
+use rocketmq_common::common::message::message_queue::MessageQueue;
+
+/// Tracks the queues assigned to a lite pull consumer together with their pull/consume/seek
+/// offsets and a per-queue pause flag.
+#[derive(Default)]
+pub(crate) struct AssignedMessageQueue {
+    assigned_message_queue_state: RwLock<HashMap<MessageQueue, MessageQueueState>>,
+}
+
+struct MessageQueueState {
+    paused: bool,
+    pull_offset: i64,
+    consume_offset: i64,
+    seek_offset: i64,
+}
+
+impl Default for MessageQueueState {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            pull_offset: -1,
+            consume_offset: -1,
+            seek_offset: -1,
+        }
+    }
+}
+
+impl AssignedMessageQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn message_queues(&self) -> HashSet<MessageQueue> {
+        self.assigned_message_queue_state
+            .read()
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    pub fn contains(&self, message_queue: &MessageQueue) -> bool {
+        self.assigned_message_queue_state
+            .read()
+            .contains_key(message_queue)
+    }
+
+    /// Returns `true` if the queue is paused. Queues that are not assigned are reported as
+    /// not paused, matching the Java client.
+    pub fn is_paused(&self, message_queue: &MessageQueue) -> bool {
+        self.assigned_message_queue_state
+            .read()
+            .get(message_queue)
+            .is_some_and(|state| state.paused)
+    }
+
+    pub fn pause(&self, message_queues: &[MessageQueue]) {
+        self.set_paused(message_queues, true);
+    }
+
+    pub fn resume(&self, message_queues: &[MessageQueue]) {
+        self.set_paused(message_queues, false);
+    }
+
+    fn set_paused(&self, message_queues: &[MessageQueue], paused: bool) {
+        let mut table = self.assigned_message_queue_state.write();
+        for message_queue in message_queues {
+            if let Some(state) = table.get_mut(message_queue) {
+                state.paused = paused;
+            }
+        }
+    }
+
+    pub fn get_pull_offset(&self, message_queue: &MessageQueue) -> i64 {
+        self.assigned_message_queue_state
+            .read()
+            .get(message_queue)
+            .map_or(-1, |state| state.pull_offset)
+    }
+
+    pub fn update_pull_offset(&self, message_queue: &MessageQueue, offset: i64) {
+        if let Some(state) = self
+            .assigned_message_queue_state
+            .write()
+            .get_mut(message_queue)
+        {
+            state.pull_offset = offset;
+        }
+    }
+
+    pub fn get_consumer_offset(&self, message_queue: &MessageQueue) -> i64 {
+        self.assigned_message_queue_state
+            .read()
+            .get(message_queue)
+            .map_or(-1, |state| state.consume_offset)
+    }
+
+    pub fn update_consume_offset(&self, message_queue: &MessageQueue, offset: i64) {
+        if let Some(state) = self
+            .assigned_message_queue_state
+            .write()
+            .get_mut(message_queue)
+        {
+            state.consume_offset = offset;
+        }
+    }
+
+    /// Takes the offset set by a seek, the next pull of the queue starts there.
+    pub fn take_seek_offset(&self, message_queue: &MessageQueue) -> i64 {
+        self.assigned_message_queue_state
+            .write()
+            .get_mut(message_queue)
+            .map_or(-1, |state| std::mem::replace(&mut state.seek_offset, -1))
+    }
+
+    pub fn set_seek_offset(&self, message_queue: &MessageQueue, offset: i64) {
+        if let Some(state) = self
+            .assigned_message_queue_state
+            .write()
+            .get_mut(message_queue)
+        {
+            state.seek_offset = offset;
+        }
+    }
+
+    /// Replaces the whole assignment with `assigned`. Queues that stay assigned keep their pause
+    /// flag and offsets, the others are dropped.
+    pub fn update_assigned_message_queue(&self, assigned: &HashSet<MessageQueue>) {
+        let mut table = self.assigned_message_queue_state.write();
+        table.retain(|message_queue, _| assigned.contains(message_queue));
+        for message_queue in assigned {
+            table.entry(message_queue.clone()).or_default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queues(topic: &str, ids: &[i32]) -> HashSet<MessageQueue> {
+        ids.iter()
+            .map(|id| MessageQueue::from_parts(topic, "broker-a", *id))
+            .collect()
+    }
+
+    #[test]
+    fn pause_and_resume_only_affect_given_queues() {
+        let assigned = AssignedMessageQueue::new();
+        assigned.update_assigned_message_queue(&queues("topic", &[0, 1]));
+        let mq0 = MessageQueue::from_parts("topic", "broker-a", 0);
+        let mq1 = MessageQueue::from_parts("topic", "broker-a", 1);
+
+        assigned.pause(std::slice::from_ref(&mq0));
+        assert!(assigned.is_paused(&mq0));
+        assert!(!assigned.is_paused(&mq1));
+
+        assigned.resume(std::slice::from_ref(&mq0));
+        assert!(!assigned.is_paused(&mq0));
+    }
+
+    #[test]
+    fn reassignment_keeps_state_of_retained_queues() {
+        let assigned = AssignedMessageQueue::new();
+        assigned.update_assigned_message_queue(&queues("topic", &[0, 1]));
+        let mq0 = MessageQueue::from_parts("topic", "broker-a", 0);
+        let mq1 = MessageQueue::from_parts("topic", "broker-a", 1);
+        assigned.pause(std::slice::from_ref(&mq0));
+        assigned.update_pull_offset(&mq0, 42);
+        assigned.update_pull_offset(&mq1, 7);
+
+        assigned.update_assigned_message_queue(&queues("topic", &[0, 2]));
+
+        assert!(assigned.is_paused(&mq0));
+        assert_eq!(assigned.get_pull_offset(&mq0), 42);
+        assert_eq!(assigned.get_pull_offset(&mq1), -1);
+        assert_eq!(assigned.message_queues(), queues("topic", &[0, 2]));
+    }
+
+    #[test]
+    fn seek_offset_is_taken_once() {
+        let assigned = AssignedMessageQueue::new();
+        assigned.update_assigned_message_queue(&queues("topic", &[0]));
+        let mq0 = MessageQueue::from_parts("topic", "broker-a", 0);
+
+        assigned.set_seek_offset(&mq0, 5);
+
+        assert_eq!(assigned.take_seek_offset(&mq0), 5);
+        assert_eq!(assigned.take_seek_offset(&mq0), -1);
+    }
+}
@@ -45,6 +45,9 @@ use crate::consumer::listener::consume_concurrently_context::ConsumeConcurrently
 use crate::consumer::listener::consume_concurrently_status::ConsumeConcurrentlyStatus;
 use crate::consumer::listener::consume_return_type::ConsumeReturnType;
 use crate::consumer::listener::message_listener_concurrently::ArcBoxMessageListenerConcurrently;
+use crate::consumer::retry_policy::next_delay_level;
+use crate::consumer::retry_policy::parse_delay_level;
+use crate::consumer::retry_policy::DEFAULT_MESSAGE_DELAY_LEVEL;
 use crate::hook::consume_message_context::ConsumeMessageContext;
 
 pub struct ConsumeMessageConcurrentlyService {
@@ -54,6 +57,8 @@ pub struct ConsumeMessageConcurrentlyService {
     pub(crate) consumer_group: CheetahString,
     pub(crate) message_listener: ArcBoxMessageListenerConcurrently,
     pub(crate) consume_runtime: RocketMQRuntime,
+    /// Delay of each broker delay level in milliseconds, parsed from `messageDelayLevel`.
+    pub(crate) delay_level_table: Vec<i64>,
 }
 
 impl ConsumeMessageConcurrentlyService {
@@ -66,6 +71,14 @@ impl ConsumeMessageConcurrentlyService {
     ) -> Self {
         let consume_thread = consumer_config.consume_thread_max;
         let consumer_group_tag = format!("{}_{}", "ConsumeMessageThread_", consumer_group);
+        let delay_level_table = parse_delay_level(&consumer_config.message_delay_level)
+            .unwrap_or_else(|| {
+                warn!(
+                    "Invalid messageDelayLevel {}, using {}",
+                    consumer_config.message_delay_level, DEFAULT_MESSAGE_DELAY_LEVEL
+                );
+                parse_delay_level(DEFAULT_MESSAGE_DELAY_LEVEL).unwrap()
+            });
         Self {
             default_mqpush_consumer_impl,
             client_config,
@@ -76,6 +89,7 @@ impl ConsumeMessageConcurrentlyService {
                 consume_thread as usize,
                 consumer_group_tag.as_str(),
             ),
+            delay_level_table,
         }
     }
 }
@@ -108,14 +122,37 @@ impl ConsumeMessageConcurrentlyService {
             return;
         }
         let mut ack_index = context.ack_index;
+        let topic = consume_request.message_queue.get_topic();
+        let consumer_stats_manager = self
+            .default_mqpush_consumer_impl
+            .as_ref()
+            .and_then(|consumer_impl| consumer_impl.client_instance.as_ref())
+            .map(|client_instance| client_instance.consumer_stats_manager.clone());
         match status {
             ConsumeConcurrentlyStatus::ConsumeSuccess => {
                 if ack_index >= consume_request.msgs.len() as i32 {
                     ack_index = consume_request.msgs.len() as i32 - 1;
                 }
+                if let Some(consumer_stats_manager) = consumer_stats_manager {
+                    let ok = (ack_index + 1) as u64;
+                    let failed = consume_request.msgs.len() as u64 - ok;
+                    consumer_stats_manager.inc_consume_ok_tps(&self.consumer_group, topic, ok);
+                    consumer_stats_manager.inc_consume_failed_tps(
+                        &self.consumer_group,
+                        topic,
+                        failed,
+                    );
+                }
             }
             ConsumeConcurrentlyStatus::ReconsumeLater => {
                 ack_index = -1;
+                if let Some(consumer_stats_manager) = consumer_stats_manager {
+                    consumer_stats_manager.inc_consume_failed_tps(
+                        &self.consumer_group,
+                        topic,
+                        consume_request.msgs.len() as u64,
+                    );
+                }
             }
         }
 
@@ -195,12 +232,52 @@ impl ConsumeMessageConcurrentlyService {
         });
     }
 
+    fn get_max_reconsume_times(&self) -> i32 {
+        if self.consumer_config.max_reconsume_times == -1 {
+            16
+        } else {
+            self.consumer_config.max_reconsume_times
+        }
+    }
+
+    /// The delay level a failed message is sent back with, or `None` if the consume result hook
+    /// took over a message that would have gone to the DLQ.
+    fn send_back_delay_level(
+        &self,
+        msg: &MessageExt,
+        context: &ConsumeConcurrentlyContext,
+    ) -> Option<i32> {
+        let mut delay_level = context.delay_level_when_next_consume;
+        if delay_level == 0 {
+            if let Some(retry_policy) = self.consumer_config.retry_policy.as_ref() {
+                delay_level = next_delay_level(
+                    retry_policy.as_ref(),
+                    msg.reconsume_times(),
+                    &self.delay_level_table,
+                );
+            }
+        }
+        if let Some(hook) = self.consumer_config.consume_result_hook.as_ref() {
+            if delay_level < 0 || msg.reconsume_times() >= self.get_max_reconsume_times() {
+                if hook.before_dead_letter(&self.consumer_group, msg) {
+                    return None;
+                }
+            } else {
+                hook.before_retry(&self.consumer_group, msg, delay_level);
+            }
+        }
+        Some(delay_level)
+    }
+
     pub async fn send_message_back(
         &mut self,
         msg: &mut MessageExt,
         context: &ConsumeConcurrentlyContext,
     ) -> bool {
-        let delay_level = context.delay_level_when_next_consume;
+        let Some(delay_level) = self.send_back_delay_level(msg, context) else {
+            // the hook took over the message, treat it as consumed
+            return true;
+        };
         msg.set_topic(self.client_config.with_namespace(msg.get_topic().as_str()));
 
         self.default_mqpush_consumer_impl
@@ -430,6 +507,7 @@ impl ConsumeRequest {
                 .iter()
                 .map(|msg| &msg.message_ext_inner)
                 .collect::<Vec<&MessageExt>>();
+            let process_spans = default_mqpush_consumer_impl.start_process_spans(&vec);
             match self.message_listener.consume_message(&vec, &context) {
                 Ok(value) => {
                     status = Some(value);
@@ -438,6 +516,13 @@ impl ConsumeRequest {
                     has_exception = true;
                 }
             }
+            DefaultMQPushConsumerImpl::end_process_spans(
+                process_spans,
+                status
+                    .map_or("Exception".to_string(), |status| status.to_string())
+                    .as_str(),
+                status == Some(ConsumeConcurrentlyStatus::ConsumeSuccess),
+            );
         }
 
         let consume_rt = begin_timestamp.elapsed().as_millis() as u64;
@@ -476,6 +561,14 @@ impl ConsumeRequest {
             default_mqpush_consumer_impl.execute_hook_after(&mut consume_message_context);
         }
 
+        if let Some(client_instance) = default_mqpush_consumer_impl.client_instance.as_ref() {
+            client_instance.consumer_stats_manager.inc_consume_rt(
+                &self.consumer_group,
+                self.message_queue.get_topic(),
+                consume_rt,
+            );
+        }
+
         if self.process_queue.is_dropped() {
             warn!(
                 "the message queue not be able to consume, because it's dropped. group={} {}",
@@ -490,3 +583,127 @@ impl ConsumeRequest {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use rocketmq_remoting::protocol::subscription::customized_retry_policy::CustomizedRetryPolicy;
+
+    use super::*;
+    use crate::consumer::consume_result_hook::ConsumeResultHook;
+    use crate::consumer::listener::message_listener_concurrently::MessageListenerConcurrently;
+
+    struct FailingListener;
+
+    impl MessageListenerConcurrently for FailingListener {
+        fn consume_message(
+            &self,
+            _msgs: &[&MessageExt],
+            _context: &ConsumeConcurrentlyContext,
+        ) -> crate::Result<ConsumeConcurrentlyStatus> {
+            Ok(ConsumeConcurrentlyStatus::ReconsumeLater)
+        }
+    }
+
+    struct RecordingHook {
+        take_over: bool,
+        retries: Arc<Mutex<Vec<i32>>>,
+    }
+
+    impl ConsumeResultHook for RecordingHook {
+        fn hook_name(&self) -> &str {
+            "RecordingHook"
+        }
+
+        fn before_retry(
+            &self,
+            _consumer_group: &CheetahString,
+            _msg: &MessageExt,
+            delay_level: i32,
+        ) {
+            self.retries.lock().unwrap().push(delay_level);
+        }
+
+        fn before_dead_letter(&self, _consumer_group: &CheetahString, _msg: &MessageExt) -> bool {
+            self.take_over
+        }
+    }
+
+    fn new_service(
+        take_over: bool,
+        message_delay_level: &str,
+    ) -> (ConsumeMessageConcurrentlyService, Arc<Mutex<Vec<i32>>>) {
+        let retries = Arc::new(Mutex::new(Vec::new()));
+        let mut consumer_config = ConsumerConfig::default();
+        consumer_config.set_retry_policy(Some(Arc::new(CustomizedRetryPolicy::default())));
+        consumer_config.set_consume_result_hook(Some(Arc::new(RecordingHook {
+            take_over,
+            retries: retries.clone(),
+        })));
+        consumer_config.set_message_delay_level(message_delay_level.into());
+        let service = ConsumeMessageConcurrentlyService::new(
+            ArcMut::new(ClientConfig::default()),
+            ArcMut::new(consumer_config),
+            CheetahString::from_static_str("group"),
+            Arc::new(Box::new(FailingListener)),
+            None,
+        );
+        (service, retries)
+    }
+
+    fn message(reconsume_times: i32) -> MessageExt {
+        let mut msg = MessageExt::default();
+        msg.reconsume_times = reconsume_times;
+        msg
+    }
+
+    #[test]
+    fn retried_messages_are_sent_back_with_the_policy_delay_level() {
+        let (service, retries) = new_service(true, DEFAULT_MESSAGE_DELAY_LEVEL);
+        let context = ConsumeConcurrentlyContext::new(MessageQueue::default());
+        assert_eq!(
+            service.send_back_delay_level(&message(0), &context),
+            Some(3)
+        );
+        assert_eq!(
+            service.send_back_delay_level(&message(2), &context),
+            Some(5)
+        );
+        assert_eq!(*retries.lock().unwrap(), vec![3, 5]);
+
+        // the delays of the policy are rounded up to the configured levels
+        let (service, _) = new_service(true, "5s 1m 1h");
+        assert_eq!(
+            service.send_back_delay_level(&message(0), &context),
+            Some(2)
+        );
+        assert_eq!(
+            service.send_back_delay_level(&message(4), &context),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn hook_decides_whether_exhausted_messages_go_to_the_dlq() {
+        let mut context = ConsumeConcurrentlyContext::new(MessageQueue::default());
+
+        let (service, retries) = new_service(true, DEFAULT_MESSAGE_DELAY_LEVEL);
+        assert_eq!(service.send_back_delay_level(&message(16), &context), None);
+        context.delay_level_when_next_consume = -1;
+        assert_eq!(service.send_back_delay_level(&message(0), &context), None);
+        assert!(retries.lock().unwrap().is_empty());
+
+        let (service, retries) = new_service(false, DEFAULT_MESSAGE_DELAY_LEVEL);
+        assert_eq!(
+            service.send_back_delay_level(&message(0), &context),
+            Some(-1)
+        );
+        context.delay_level_when_next_consume = 0;
+        assert_eq!(
+            service.send_back_delay_level(&message(16), &context),
+            Some(18)
+        );
+        assert!(retries.lock().unwrap().is_empty());
+    }
+}
@@ -245,8 +245,10 @@ impl ConsumeMessageOrderlyService {
         );
         MessageAccessor::clear_property(&mut new_msg, MessageConst::PROPERTY_TRANSACTION_PREPARED);
         new_msg.set_delay_time_level(3 + msg.reconsume_times());
-        let mut default_mqpush_consumer_impl =
-            self.default_mqpush_consumer_impl.as_ref().unwrap().clone();
+        let Some(mut default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl.clone()
+        else {
+            return false;
+        };
 
         let result = default_mqpush_consumer_impl
             .client_instance
@@ -264,6 +266,12 @@ impl ConsumeMessageOrderlyService {
             for msg in msgs {
                 let reconsume_times = msg.message_ext_inner.reconsume_times;
                 if reconsume_times >= self.get_max_reconsume_times() {
+                    if let Some(hook) = self.consumer_config.consume_result_hook.as_ref() {
+                        if hook.before_dead_letter(&self.consumer_group, &msg.message_ext_inner) {
+                            // the hook took over the message, commit it without a DLQ copy
+                            continue;
+                        }
+                    }
                     MessageAccessor::set_reconsume_time(
                         &mut msg.message_ext_inner,
                         CheetahString::from_string(reconsume_times.to_string()),
@@ -290,6 +298,40 @@ impl ConsumeMessageOrderlyService {
         context: &ConsumeOrderlyContext,
         consume_request: &mut ConsumeRequest,
     ) -> bool {
+        if let Some(consumer_stats_manager) = self
+            .default_mqpush_consumer_impl
+            .as_ref()
+            .and_then(|consumer_impl| consumer_impl.client_instance.as_ref())
+            .map(|client_instance| client_instance.consumer_stats_manager.clone())
+        {
+            let topic = consume_request.message_queue.get_topic();
+            match status {
+                ConsumeOrderlyStatus::Success => {
+                    consumer_stats_manager.inc_consume_ok_tps(
+                        &self.consumer_group,
+                        topic,
+                        msgs.len() as u64,
+                    );
+                }
+                ConsumeOrderlyStatus::Commit | ConsumeOrderlyStatus::Rollback
+                    if context.is_auto_commit() =>
+                {
+                    consumer_stats_manager.inc_consume_ok_tps(
+                        &self.consumer_group,
+                        topic,
+                        msgs.len() as u64,
+                    );
+                }
+                ConsumeOrderlyStatus::SuspendCurrentQueueAMoment => {
+                    consumer_stats_manager.inc_consume_failed_tps(
+                        &self.consumer_group,
+                        topic,
+                        msgs.len() as u64,
+                    );
+                }
+                _ => {}
+            }
+        }
         let (continue_consume, commit_offset) = if context.is_auto_commit() {
             match status {
                 ConsumeOrderlyStatus::Success
@@ -628,6 +670,7 @@ impl ConsumeRequest {
                     .iter()
                     .map(|msg| &msg.message_ext_inner)
                     .collect::<Vec<&MessageExt>>();
+                let process_spans = default_mqpush_consumer_impl.start_process_spans(&vec);
 
                 match consume_message_orderly_service_inner
                     .message_listener
@@ -640,6 +683,16 @@ impl ConsumeRequest {
                         has_exception = true;
                     }
                 }
+                DefaultMQPushConsumerImpl::end_process_spans(
+                    process_spans,
+                    status
+                        .map_or("Exception".to_string(), |status| status.to_string())
+                        .as_str(),
+                    matches!(
+                        status,
+                        Some(ConsumeOrderlyStatus::Success) | Some(ConsumeOrderlyStatus::Commit)
+                    ),
+                );
                 drop(consume_lock);
                 if status.is_none()
                     || *status.as_ref().unwrap() == ConsumeOrderlyStatus::Rollback
@@ -687,6 +740,14 @@ impl ConsumeRequest {
                     consume_message_context.as_mut().unwrap().status = status.to_string().into();
                     default_mqpush_consumer_impl.execute_hook_after(&mut consume_message_context);
                 }
+                if let Some(client_instance) = default_mqpush_consumer_impl.client_instance.as_ref()
+                {
+                    client_instance.consumer_stats_manager.inc_consume_rt(
+                        &self.consumer_group,
+                        self.message_queue.get_topic(),
+                        consume_rt,
+                    );
+                }
                 let continue_consume = consume_message_orderly_service_inner
                     .process_consume_result(
                         msgs,
@@ -722,3 +783,79 @@ impl ConsumeRequest {
         drop(locked);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::consumer::consume_result_hook::ConsumeResultHook;
+    use crate::consumer::listener::message_listener_orderly::MessageListenerOrderly;
+
+    struct FailingListener;
+
+    impl MessageListenerOrderly for FailingListener {
+        fn consume_message(
+            &self,
+            _msgs: &[&MessageExt],
+            _context: &mut ConsumeOrderlyContext,
+        ) -> crate::Result<ConsumeOrderlyStatus> {
+            Ok(ConsumeOrderlyStatus::SuspendCurrentQueueAMoment)
+        }
+    }
+
+    struct TakeOverHook(bool);
+
+    impl ConsumeResultHook for TakeOverHook {
+        fn hook_name(&self) -> &str {
+            "TakeOverHook"
+        }
+
+        fn before_dead_letter(&self, _consumer_group: &CheetahString, _msg: &MessageExt) -> bool {
+            self.0
+        }
+    }
+
+    /// Runs `check_reconsume_times` on a message that used up its retries, returns whether the
+    /// queue is suspended and the reconsume times of the message afterwards.
+    fn check_exhausted_message(hook: Option<TakeOverHook>) -> (bool, i32) {
+        let mut consumer_config = ConsumerConfig::default();
+        consumer_config.set_max_reconsume_times(2);
+        consumer_config
+            .set_consume_result_hook(hook.map(|hook| Arc::new(hook) as Arc<dyn ConsumeResultHook>));
+        let mut service = ConsumeMessageOrderlyService::new(
+            ArcMut::new(ClientConfig::default()),
+            ArcMut::new(consumer_config),
+            CheetahString::from_static_str("group"),
+            Arc::new(Box::new(FailingListener)),
+            None,
+        );
+        let mut msg = MessageExt::default();
+        msg.set_body(Bytes::from_static(b"body"));
+        msg.reconsume_times = 2;
+        let mut msgs = vec![ArcMut::new(MessageClientExt::new(msg))];
+
+        let suspend = tokio::runtime::Runtime::new()
+            .unwrap()
+            .block_on(service.check_reconsume_times(&mut msgs));
+        (suspend, msgs[0].message_ext_inner.reconsume_times)
+    }
+
+    #[test]
+    fn hook_taking_over_an_exhausted_message_lets_the_queue_go_on() {
+        assert_eq!(
+            check_exhausted_message(Some(TakeOverHook(true))),
+            (false, 2)
+        );
+    }
+
+    #[test]
+    fn exhausted_message_is_sent_back_unless_the_hook_takes_it_over() {
+        // nothing to send the message back with, so the queue is suspended to retry it
+        assert_eq!(
+            check_exhausted_message(Some(TakeOverHook(false))),
+            (true, 3)
+        );
+        assert_eq!(check_exhausted_message(None), (true, 3));
+    }
+}
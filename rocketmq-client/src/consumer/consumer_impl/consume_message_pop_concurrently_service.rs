@@ -337,6 +337,7 @@ impl ConsumeRequest {
             .iter()
             .map(|msg| &msg.message_ext_inner)
             .collect::<Vec<&MessageExt>>();
+        let process_spans = default_mqpush_consumer_impl.start_process_spans(&vec);
         match self.message_listener.consume_message(&vec, &context) {
             Ok(value) => {
                 status = Some(value);
@@ -345,6 +346,13 @@ impl ConsumeRequest {
                 has_exception = true;
             }
         }
+        DefaultMQPushConsumerImpl::end_process_spans(
+            process_spans,
+            status
+                .map_or("Exception".to_string(), |status| status.to_string())
+                .as_str(),
+            status == Some(ConsumeConcurrentlyStatus::ConsumeSuccess),
+        );
         let consume_rt = begin_timestamp.elapsed().as_millis() as u64;
         if status.is_none() {
             if has_exception {
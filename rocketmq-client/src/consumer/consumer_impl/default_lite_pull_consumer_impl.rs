@@ -0,0 +1,625 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::base::service_state::ServiceState;
+use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::sys_flag::pull_sys_flag::PullSysFlag;
+use rocketmq_remoting::protocol::body::consumer_running_info::ConsumerRunningInfo;
+use rocketmq_remoting::protocol::filter::filter_api::FilterAPI;
+use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
+use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
+use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
+use rocketmq_remoting::runtime::RPCHook;
+use rocketmq_rust::ArcMut;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::base::client_config::ClientConfig;
+use crate::client_error::ClientErr;
+use crate::consumer::consumer_impl::assigned_message_queue::AssignedMessageQueue;
+use crate::consumer::consumer_impl::pull_api_wrapper::PullAPIWrapper;
+use crate::consumer::consumer_impl::pull_request_ext::PullResultExt;
+use crate::consumer::mq_consumer_inner::MQConsumerInner;
+use crate::consumer::mq_consumer_inner::MQConsumerInnerImpl;
+use crate::consumer::pull_callback::PullCallback;
+use crate::consumer::pull_status::PullStatus;
+use crate::consumer::store::offset_store::OffsetStore;
+use crate::consumer::store::read_offset_type::ReadOffsetType;
+use crate::consumer::store::remote_broker_offset_store::RemoteBrokerOffsetStore;
+use crate::factory::mq_client_instance::MQClientInstance;
+use crate::implementation::communication_mode::CommunicationMode;
+use crate::implementation::mq_client_manager::MQClientManager;
+use crate::mq_client_err;
+use crate::Result;
+
+const PULL_TIMEOUT_MILLIS: u64 = 10_000;
+/// Pause between two rounds over the assigned queues when none of them had new messages.
+const PULL_IDLE_DELAY_MILLIS: u64 = 20;
+
+pub(crate) struct DefaultLitePullConsumerImpl {
+    client_config: ClientConfig,
+    pub(crate) consumer_group: CheetahString,
+    pub(crate) consume_from_where: ConsumeFromWhere,
+    pub(crate) pull_batch_size: i32,
+    pub(crate) poll_timeout_millis: u64,
+    pub(crate) auto_commit: bool,
+    pub(crate) auto_commit_interval_millis: u64,
+    rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+    service_state: ServiceState,
+    client_instance: Option<ArcMut<MQClientInstance>>,
+    pull_api_wrapper: Option<PullAPIWrapper>,
+    offset_store: Option<ArcMut<OffsetStore>>,
+    assigned_message_queue: AssignedMessageQueue,
+    sub_expression_for_assign: HashMap<CheetahString, CheetahString>,
+    next_queue_index: usize,
+    next_auto_commit_deadline: Instant,
+}
+
+impl DefaultLitePullConsumerImpl {
+    pub fn new(
+        client_config: ClientConfig,
+        consumer_group: CheetahString,
+        rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+    ) -> Self {
+        Self {
+            client_config,
+            consumer_group,
+            consume_from_where: ConsumeFromWhere::ConsumeFromLastOffset,
+            pull_batch_size: 10,
+            poll_timeout_millis: 5000,
+            auto_commit: true,
+            auto_commit_interval_millis: 5000,
+            rpc_hook,
+            service_state: ServiceState::CreateJust,
+            client_instance: None,
+            pull_api_wrapper: None,
+            offset_store: None,
+            assigned_message_queue: AssignedMessageQueue::new(),
+            sub_expression_for_assign: HashMap::new(),
+            next_queue_index: 0,
+            next_auto_commit_deadline: Instant::now(),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.service_state == ServiceState::Running
+    }
+
+    /// Starts the consumer and registers it on the client instance it shares with the other
+    /// clients of the same client id, `this` is the handle the instance keeps.
+    pub async fn start(&mut self, this: ArcMut<Self>) -> Result<()> {
+        match self.service_state {
+            ServiceState::CreateJust => {
+                self.service_state = ServiceState::StartFailed;
+                if self.consumer_group.is_empty() {
+                    return mq_client_err!("consumerGroup is null");
+                }
+                if self.consume_from_where == ConsumeFromWhere::ConsumeFromTimestamp {
+                    return mq_client_err!(
+                        "The lite pull consumer does not support ConsumeFromTimestamp, seek to \
+                         offset_for_timestamp instead"
+                    );
+                }
+                self.client_config.change_instance_name_to_pid();
+                let mut client_instance = MQClientManager::get_instance()
+                    .get_or_create_mq_client_instance(
+                        self.client_config.clone(),
+                        self.rpc_hook.clone(),
+                    )
+                    .await;
+                self.pull_api_wrapper = Some(PullAPIWrapper::new(
+                    client_instance.clone(),
+                    self.consumer_group.clone(),
+                    false,
+                ));
+                let offset_store = OffsetStore::new_with_remote(RemoteBrokerOffsetStore::new(
+                    client_instance.clone(),
+                    self.consumer_group.clone(),
+                ));
+                offset_store.load().await?;
+                self.offset_store = Some(ArcMut::new(offset_store));
+                if !client_instance
+                    .register_consumer(
+                        &self.consumer_group,
+                        MQConsumerInnerImpl {
+                            default_mqpush_consumer_impl: None,
+                            default_lite_pull_consumer_impl: Some(this),
+                        },
+                    )
+                    .await
+                {
+                    return mq_client_err!(format!(
+                        "The consumer group[{}] has been created before, specify another name \
+                         please.",
+                        self.consumer_group
+                    ));
+                }
+                let cloned = client_instance.clone();
+                client_instance.start(cloned).await?;
+                self.client_instance = Some(client_instance);
+                self.next_auto_commit_deadline =
+                    Instant::now() + Duration::from_millis(self.auto_commit_interval_millis);
+                info!("the lite pull consumer [{}] start OK", self.consumer_group);
+                self.service_state = ServiceState::Running;
+                Ok(())
+            }
+            ServiceState::Running => {
+                mq_client_err!("The LitePullConsumer service state is Running")
+            }
+            ServiceState::ShutdownAlready => {
+                mq_client_err!("The LitePullConsumer service state is ShutdownAlready")
+            }
+            ServiceState::StartFailed => {
+                mq_client_err!("The LitePullConsumer service state not OK, maybe started once")
+            }
+        }
+    }
+
+    pub async fn shutdown(&mut self) {
+        if self.service_state != ServiceState::Running {
+            return;
+        }
+        if self.auto_commit {
+            self.commit_all(true).await;
+        }
+        if let Some(client_instance) = self.client_instance.as_mut() {
+            // the instance is shared, it only shuts down once no other client is registered
+            client_instance
+                .unregister_consumer(self.consumer_group.clone())
+                .await;
+            client_instance.shutdown().await;
+        }
+        self.service_state = ServiceState::ShutdownAlready;
+        info!(
+            "the lite pull consumer [{}] shutdown OK",
+            self.consumer_group
+        );
+    }
+
+    fn make_sure_state_ok(&self) -> std::result::Result<(), ClientErr> {
+        if self.service_state != ServiceState::Running {
+            return Err(ClientErr::new(format!(
+                "The consumer service state not OK, {:?}",
+                self.service_state
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn assignment(&self) -> HashSet<MessageQueue> {
+        self.assigned_message_queue.message_queues()
+    }
+
+    pub fn assign(&self, message_queues: Vec<MessageQueue>) {
+        let message_queues = message_queues.into_iter().collect::<HashSet<_>>();
+        self.assigned_message_queue
+            .update_assigned_message_queue(&message_queues);
+    }
+
+    pub fn set_sub_expression_for_assign(&mut self, topic: &str, sub_expression: &str) {
+        self.sub_expression_for_assign
+            .insert(topic.into(), sub_expression.into());
+    }
+
+    pub fn pause(&self, message_queues: &[MessageQueue]) {
+        self.assigned_message_queue.pause(message_queues);
+    }
+
+    pub fn resume(&self, message_queues: &[MessageQueue]) {
+        self.assigned_message_queue.resume(message_queues);
+    }
+
+    pub fn is_paused(&self, message_queue: &MessageQueue) -> bool {
+        self.assigned_message_queue.is_paused(message_queue)
+    }
+
+    /// Pulls the next batch of messages from the assigned queues that are not paused, waiting
+    /// at most `timeout` milliseconds. Queues are visited round robin so one busy queue can not
+    /// starve the others.
+    pub async fn poll(&mut self, timeout: u64) -> Vec<MessageExt> {
+        if let Err(err) = self.make_sure_state_ok() {
+            warn!("poll messages failed: {}", err);
+            return Vec::new();
+        }
+        let deadline = Instant::now() + Duration::from_millis(timeout);
+        loop {
+            if self.auto_commit && Instant::now() >= self.next_auto_commit_deadline {
+                self.commit_all(true).await;
+                self.next_auto_commit_deadline =
+                    Instant::now() + Duration::from_millis(self.auto_commit_interval_millis);
+            }
+            let mut message_queues = self
+                .assigned_message_queue
+                .message_queues()
+                .into_iter()
+                .filter(|mq| !self.assigned_message_queue.is_paused(mq))
+                .collect::<Vec<_>>();
+            message_queues.sort();
+            for _ in 0..message_queues.len() {
+                let mq = &message_queues[self.next_queue_index % message_queues.len()];
+                self.next_queue_index = self.next_queue_index.wrapping_add(1);
+                match self.pull_queue(mq).await {
+                    Ok(messages) if !messages.is_empty() => return messages,
+                    Ok(_) => {}
+                    Err(err) => warn!("pull message from {} failed: {}", mq, err),
+                }
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Vec::new();
+            }
+            let delay = Duration::from_millis(PULL_IDLE_DELAY_MILLIS).min(deadline - now);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn pull_queue(&mut self, mq: &MessageQueue) -> Result<Vec<MessageExt>> {
+        let offset = self.next_pull_offset(mq).await?;
+        let subscription_data = self.subscription_data(mq)?;
+        let sys_flag = PullSysFlag::build_sys_flag(false, false, true, false);
+        let pull_api_wrapper = self.pull_api_wrapper.as_mut().unwrap();
+        let Some(mut pull_result) = pull_api_wrapper
+            .pull_kernel_impl(
+                mq,
+                subscription_data.sub_string.clone(),
+                subscription_data.expression_type.clone(),
+                subscription_data.sub_version,
+                offset,
+                self.pull_batch_size,
+                i32::MAX,
+                sys_flag as i32,
+                0,
+                0,
+                PULL_TIMEOUT_MILLIS,
+                CommunicationMode::Sync,
+                SyncPullCallback,
+            )
+            .await?
+        else {
+            return Ok(Vec::new());
+        };
+        pull_api_wrapper.process_pull_result(mq, &mut pull_result, &subscription_data);
+
+        // The queue may have been unassigned, or dropped and assigned again, while the pull was in
+        // flight.
+        if !self.assigned_message_queue.contains(mq)
+            || self.assigned_message_queue.get_pull_offset(mq) != offset
+        {
+            return Ok(Vec::new());
+        }
+        let next_offset = pull_result.pull_result.next_begin_offset as i64;
+        self.assigned_message_queue
+            .update_pull_offset(mq, next_offset);
+        if pull_result.pull_result.pull_status != PullStatus::Found {
+            return Ok(Vec::new());
+        }
+        self.assigned_message_queue
+            .update_consume_offset(mq, next_offset);
+        if self.auto_commit {
+            self.offset_store
+                .as_ref()
+                .unwrap()
+                .update_offset(mq, next_offset, false)
+                .await;
+        }
+        Ok(pull_result
+            .pull_result
+            .msg_found_list
+            .iter()
+            .map(|msg| msg.message_ext_inner.clone())
+            .collect())
+    }
+
+    /// Offset the next pull of `mq` starts at: a pending seek wins over the offset left by the
+    /// previous pull, a queue pulled for the first time starts at its committed offset.
+    async fn next_pull_offset(&mut self, mq: &MessageQueue) -> Result<i64> {
+        let seek_offset = self.assigned_message_queue.take_seek_offset(mq);
+        if seek_offset >= 0 {
+            self.assigned_message_queue
+                .update_pull_offset(mq, seek_offset);
+            self.assigned_message_queue
+                .update_consume_offset(mq, seek_offset);
+            return Ok(seek_offset);
+        }
+        let pull_offset = self.assigned_message_queue.get_pull_offset(mq);
+        if pull_offset >= 0 {
+            return Ok(pull_offset);
+        }
+        let committed = self
+            .offset_store
+            .as_ref()
+            .unwrap()
+            .read_offset(mq, ReadOffsetType::MemoryFirstThenStore)
+            .await;
+        let offset = if committed >= 0 {
+            committed
+        } else if self.consume_from_where == ConsumeFromWhere::ConsumeFromFirstOffset {
+            self.min_offset(mq).await?
+        } else {
+            self.max_offset(mq).await?
+        };
+        self.assigned_message_queue.update_pull_offset(mq, offset);
+        self.assigned_message_queue
+            .update_consume_offset(mq, offset);
+        Ok(offset)
+    }
+
+    fn subscription_data(
+        &self,
+        mq: &MessageQueue,
+    ) -> std::result::Result<SubscriptionData, ClientErr> {
+        let sub_expression = self
+            .sub_expression_for_assign
+            .get(mq.get_topic_cs())
+            .cloned()
+            .unwrap_or_else(|| CheetahString::from_static_str(SubscriptionData::SUB_ALL));
+        FilterAPI::build_subscription_data(mq.get_topic_cs(), &sub_expression).map_err(|e| {
+            ClientErr::new(format!(
+                "parse subscription error, topic: {}, sub_expression: {}, {}",
+                mq.get_topic(),
+                sub_expression,
+                e
+            ))
+        })
+    }
+
+    pub async fn seek(&mut self, mq: &MessageQueue, offset: i64) -> Result<()> {
+        self.make_sure_state_ok()?;
+        if !self.assigned_message_queue.contains(mq) {
+            return mq_client_err!(format!(
+                "The message queue is not in assigned list, message queue: {}",
+                mq
+            ));
+        }
+        let min_offset = self.min_offset(mq).await?;
+        let max_offset = self.max_offset(mq).await?;
+        if offset < min_offset || offset > max_offset {
+            return mq_client_err!(format!(
+                "Seek offset illegal, seek offset = {}, min offset = {}, max offset = {}",
+                offset, min_offset, max_offset
+            ));
+        }
+        self.assigned_message_queue.set_seek_offset(mq, offset);
+        Ok(())
+    }
+
+    pub async fn seek_to_begin(&mut self, mq: &MessageQueue) -> Result<()> {
+        self.make_sure_state_ok()?;
+        let offset = self.min_offset(mq).await?;
+        self.seek(mq, offset).await
+    }
+
+    pub async fn seek_to_end(&mut self, mq: &MessageQueue) -> Result<()> {
+        self.make_sure_state_ok()?;
+        let offset = self.max_offset(mq).await?;
+        self.seek(mq, offset).await
+    }
+
+    async fn min_offset(&mut self, mq: &MessageQueue) -> Result<i64> {
+        self.client_instance
+            .as_ref()
+            .unwrap()
+            .get_mq_admin_impl()
+            .min_offset(mq)
+            .await
+    }
+
+    async fn max_offset(&mut self, mq: &MessageQueue) -> Result<i64> {
+        self.client_instance
+            .as_ref()
+            .unwrap()
+            .get_mq_admin_impl()
+            .max_offset(mq)
+            .await
+    }
+
+    pub async fn fetch_message_queues(&mut self, topic: &str) -> Result<Vec<MessageQueue>> {
+        self.make_sure_state_ok()?;
+        let client_instance = self.client_instance.as_ref().unwrap();
+        client_instance
+            .get_mq_admin_impl()
+            .fetch_subscribe_message_queues(topic, client_instance.get_mq_client_api_impl())
+            .await
+    }
+
+    pub async fn offset_for_timestamp(&mut self, mq: &MessageQueue, timestamp: u64) -> Result<i64> {
+        self.make_sure_state_ok()?;
+        self.client_instance
+            .as_ref()
+            .unwrap()
+            .get_mq_admin_impl()
+            .search_offset(mq, timestamp)
+            .await
+    }
+
+    /// Commits the offsets consumed by `poll` for every assigned queue.
+    pub async fn commit_all(&mut self, persist: bool) {
+        let offsets = self
+            .assigned_message_queue
+            .message_queues()
+            .into_iter()
+            .filter_map(|mq| {
+                let offset = self.assigned_message_queue.get_consumer_offset(&mq);
+                (offset >= 0).then_some((mq, offset))
+            })
+            .collect::<HashMap<_, _>>();
+        self.commit_offsets(offsets, persist).await;
+    }
+
+    pub async fn commit_message_queues(
+        &mut self,
+        message_queues: HashSet<MessageQueue>,
+        persist: bool,
+    ) {
+        let offsets = message_queues
+            .into_iter()
+            .filter_map(|mq| {
+                let offset = self.assigned_message_queue.get_consumer_offset(&mq);
+                (offset >= 0).then_some((mq, offset))
+            })
+            .collect::<HashMap<_, _>>();
+        self.commit_offsets(offsets, persist).await;
+    }
+
+    /// Updates the committed offsets of assigned queues, queues that are not assigned any more
+    /// are skipped.
+    pub async fn commit_offsets(&mut self, offset_map: HashMap<MessageQueue, i64>, persist: bool) {
+        if self.make_sure_state_ok().is_err() {
+            warn!("commit offsets failed, the consumer is not running");
+            return;
+        }
+        let offset_store = self.offset_store.as_mut().unwrap();
+        let mut committed = HashSet::with_capacity(offset_map.len());
+        for (mq, offset) in offset_map {
+            if offset < 0 || !self.assigned_message_queue.contains(&mq) {
+                continue;
+            }
+            offset_store.update_offset(&mq, offset, false).await;
+            committed.insert(mq);
+        }
+        if persist && !committed.is_empty() {
+            offset_store.persist_all(&committed).await;
+        }
+    }
+
+    pub async fn committed(&mut self, mq: &MessageQueue) -> Result<i64> {
+        self.make_sure_state_ok()?;
+        let offset = self
+            .offset_store
+            .as_ref()
+            .unwrap()
+            .read_offset(mq, ReadOffsetType::MemoryFirstThenStore)
+            .await;
+        if offset == -2 {
+            return mq_client_err!(format!(
+                "Fetch consume offset from broker exception, {}",
+                mq
+            ));
+        }
+        Ok(offset)
+    }
+
+    pub async fn update_name_server_address(&mut self, name_server_address: &str) {
+        if let Some(client_instance) = self.client_instance.as_ref() {
+            client_instance
+                .get_mq_client_api_impl()
+                .update_name_server_address_list(name_server_address)
+                .await;
+        }
+    }
+}
+
+impl MQConsumerInner for DefaultLitePullConsumerImpl {
+    fn group_name(&self) -> CheetahString {
+        self.consumer_group.clone()
+    }
+
+    fn message_model(&self) -> MessageModel {
+        MessageModel::Clustering
+    }
+
+    fn consume_type(&self) -> ConsumeType {
+        ConsumeType::ConsumeActively
+    }
+
+    fn consume_from_where(&self) -> ConsumeFromWhere {
+        self.consume_from_where
+    }
+
+    fn subscriptions(&self) -> HashSet<SubscriptionData> {
+        self.assigned_message_queue
+            .message_queues()
+            .iter()
+            .filter_map(|mq| self.subscription_data(mq).ok())
+            .collect()
+    }
+
+    fn do_rebalance(&self) {
+        // queues are assigned by the application
+    }
+
+    async fn try_rebalance(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    async fn persist_consumer_offset(&self) {
+        if let Err(err) = self.make_sure_state_ok() {
+            error!(
+                "group: {} persistConsumerOffset exception:{}",
+                self.consumer_group, err
+            );
+            return;
+        }
+        if let Some(offset_store) = self.offset_store.as_ref() {
+            offset_store
+                .mut_from_ref()
+                .persist_all(&self.assigned_message_queue.message_queues())
+                .await;
+        }
+    }
+
+    async fn update_topic_subscribe_info(
+        &self,
+        _topic: CheetahString,
+        _info: &HashSet<MessageQueue>,
+    ) {
+    }
+
+    async fn is_subscribe_topic_need_update(&self, _topic: &str) -> bool {
+        false
+    }
+
+    fn is_unit_mode(&self) -> bool {
+        self.client_config.unit_mode
+    }
+
+    async fn consumer_running_info(&self) -> ConsumerRunningInfo {
+        let mut info = ConsumerRunningInfo::default();
+        info.properties
+            .insert("consumerGroup".to_string(), self.consumer_group.to_string());
+        info.properties.insert(
+            "consumeFromWhere".to_string(),
+            format!("{:?}", self.consume_from_where),
+        );
+        info.properties.insert(
+            "pullBatchSize".to_string(),
+            self.pull_batch_size.to_string(),
+        );
+        info.properties
+            .insert("autoCommit".to_string(), self.auto_commit.to_string());
+        info.subscription_set = self.subscriptions();
+        info
+    }
+}
+
+/// Pulls of the lite pull consumer are synchronous, so the callback is never invoked.
+struct SyncPullCallback;
+
+impl PullCallback for SyncPullCallback {
+    async fn on_success(&mut self, _pull_result: PullResultExt) {}
+
+    fn on_exception(&mut self, _e: Box<dyn std::error::Error + Send>) {}
+}
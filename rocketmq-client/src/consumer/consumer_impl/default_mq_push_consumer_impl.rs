@@ -35,11 +35,18 @@ use rocketmq_common::common::message::MessageTrait;
 use rocketmq_common::common::mix_all;
 use rocketmq_common::common::mix_all::DEFAULT_CONSUMER_GROUP;
 use rocketmq_common::common::sys_flag::pull_sys_flag::PullSysFlag;
+use rocketmq_common::common::telemetry::messaging_span::attributes;
+use rocketmq_common::common::telemetry::messaging_span::MessagingSpan;
+use rocketmq_common::common::telemetry::messaging_span::SpanKind;
+use rocketmq_common::common::telemetry::trace_parent::TraceParent;
+use rocketmq_common::common::telemetry::tracer_provider::start_span;
 use rocketmq_common::common::FAQUrl;
 use rocketmq_common::MessageAccessor::MessageAccessor;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
 use rocketmq_remoting::protocol::body::consumer_running_info::ConsumerRunningInfo;
+use rocketmq_remoting::protocol::body::pop_process_queue_info::PopProcessQueueInfo;
+use rocketmq_remoting::protocol::body::process_queue_info::ProcessQueueInfo;
 use rocketmq_remoting::protocol::filter::filter_api::FilterAPI;
 use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
@@ -134,6 +141,7 @@ pub struct DefaultMQPushConsumerImpl {
     queue_max_span_flow_control_times: u64,
     pop_delay_level: Arc<[i32; 16]>,
     default_mqpush_consumer_impl: Option<ArcMut<DefaultMQPushConsumerImpl>>,
+    consumer_start_timestamp: u64,
 }
 
 impl DefaultMQPushConsumerImpl {
@@ -166,6 +174,7 @@ impl DefaultMQPushConsumerImpl {
                 10, 30, 60, 120, 180, 240, 300, 360, 420, 480, 540, 600, 1200, 1800, 3600, 7200,
             ]),
             default_mqpush_consumer_impl: None,
+            consumer_start_timestamp: get_current_millis(),
         };
         let wrapper = ArcMut::downgrade(&this.rebalance_impl);
         this.rebalance_impl.set_rebalance_impl(wrapper);
@@ -190,6 +199,20 @@ impl DefaultMQPushConsumerImpl {
     pub fn is_consume_orderly(&self) -> bool {
         self.consume_orderly
     }
+
+    /// Number of times pulling was delayed because the cached message count or size of a
+    /// queue exceeded `pull_threshold_for_queue`/`pull_threshold_size_for_queue`.
+    #[inline]
+    pub fn queue_flow_control_times(&self) -> u64 {
+        self.queue_flow_control_times
+    }
+
+    /// Number of times pulling was delayed because the offset span of a queue exceeded
+    /// `consume_concurrently_max_span`.
+    #[inline]
+    pub fn queue_max_span_flow_control_times(&self) -> u64 {
+        self.queue_max_span_flow_control_times
+    }
 }
 
 impl DefaultMQPushConsumerImpl {
@@ -348,6 +371,7 @@ impl DefaultMQPushConsumerImpl {
                         self.consumer_config.consumer_group.as_ref(),
                         MQConsumerInnerImpl {
                             default_mqpush_consumer_impl: self.default_mqpush_consumer_impl.clone(),
+                            default_lite_pull_consumer_impl: None,
                         },
                     )
                     .await;
@@ -360,6 +384,7 @@ impl DefaultMQPushConsumerImpl {
                     self.consumer_config.unit_mode
                 );
                 *self.service_state = ServiceState::Running;
+                self.consumer_start_timestamp = get_current_millis();
             }
             ServiceState::Running => {
                 return mq_client_err!("The PushConsumer service state is Running");
@@ -449,6 +474,44 @@ impl DefaultMQPushConsumerImpl {
         }
     }
 
+    /// Stops pulling new messages for every assigned queue while keeping the current
+    /// rebalance assignment, so consumption can be resumed without re-subscribing.
+    pub fn suspend(&self) {
+        self.pause.store(true, Ordering::Release);
+        info!(
+            "suspend this consumer, {}",
+            self.consumer_config.consumer_group
+        );
+    }
+
+    /// Resumes pulling after [`suspend`](Self::suspend) and triggers a rebalance so queues
+    /// reassigned while paused are picked up immediately.
+    pub async fn resume(&mut self) {
+        self.pause.store(false, Ordering::Release);
+        if let Err(e) = self.try_rebalance().await {
+            warn!(
+                "resume consumer {} rebalance failed: {}",
+                self.consumer_config.consumer_group, e
+            );
+        }
+        info!(
+            "resume this consumer, {}",
+            self.consumer_config.consumer_group
+        );
+    }
+
+    /// Overwrites the locally stored consume offset of `mq`, even if it moves backwards.
+    pub async fn update_consume_offset(&self, mq: &MessageQueue, offset: i64) {
+        if let Some(offset_store) = self.offset_store.as_ref() {
+            offset_store.update_offset(mq, offset, false).await;
+        }
+    }
+
+    #[inline]
+    pub fn is_pause(&self) -> bool {
+        self.pause.load(Ordering::Acquire)
+    }
+
     fn check_config(&mut self) -> Result<()> {
         Validators::check_group(self.consumer_config.consumer_group.as_str())?;
         if self.consumer_config.consumer_group.is_empty() {
@@ -619,18 +682,19 @@ impl DefaultMQPushConsumerImpl {
     }
 
     async fn copy_subscription(&mut self) -> Result<()> {
-        let sub = self.consumer_config.subscription();
+        let sub = self.consumer_config.subscription().clone();
         if !sub.is_empty() {
             for (topic, sub_expression) in sub.as_ref() {
-                let subscription_data = FilterAPI::build_subscription_data(topic, sub_expression)
+                let topic = self.client_config.with_namespace(topic.as_str());
+                let subscription_data = FilterAPI::build_subscription_data(&topic, sub_expression)
                     .map_err(|e| {
-                    MQClientError::MQClientErr(ClientErr::new(format!(
-                        "buildSubscriptionData exception, {}",
-                        e
-                    )))
-                })?;
+                        MQClientError::MQClientErr(ClientErr::new(format!(
+                            "buildSubscriptionData exception, {}",
+                            e
+                        )))
+                    })?;
                 self.rebalance_impl
-                    .put_subscription_data(topic.clone(), subscription_data)
+                    .put_subscription_data(topic, subscription_data)
                     .await;
             }
         }
@@ -662,8 +726,13 @@ impl DefaultMQPushConsumerImpl {
         Ok(())
     }
 
-    pub fn register_consume_message_hook(&mut self, hook: impl ConsumeMessageHook) {
-        unimplemented!("registerConsumeMessageHook");
+    pub fn register_consume_message_hook(
+        &mut self,
+        hook: impl ConsumeMessageHook + Send + Sync + 'static,
+    ) {
+        info!("register consumeMessageHook Hook, {}", hook.hook_name());
+        self.consume_message_hook_list
+            .push(Arc::new(Box::new(hook)));
     }
 
     pub fn register_message_listener(&mut self, message_listener: Option<ArcMut<MessageListener>>) {
@@ -994,6 +1063,7 @@ impl DefaultMQPushConsumerImpl {
                     message_queue_inner: Some(message_queue_inner),
                     subscription_data: Some(subscription_data),
                     pull_request: Some(pull_request.clone()),
+                    begin_timestamp,
                 },
             )
             .await;
@@ -1023,6 +1093,31 @@ impl DefaultMQPushConsumerImpl {
         Ok(())
     }
 
+    /// Queues of `topic`, with the namespace stripped from the topic as users see it.
+    pub async fn fetch_subscribe_message_queues(
+        &mut self,
+        topic: &CheetahString,
+    ) -> Result<Vec<MessageQueue>> {
+        self.make_sure_state_ok()?;
+        let client_instance = self.client_instance.as_mut().unwrap();
+        let mq_client_api_impl = client_instance.mq_client_api_impl.clone().unwrap();
+        let message_queues = client_instance
+            .mq_admin_impl
+            .fetch_subscribe_message_queues(topic, mq_client_api_impl)
+            .await?;
+        Ok(message_queues
+            .into_iter()
+            .map(|message_queue| {
+                MessageQueue::from_parts(
+                    self.client_config
+                        .without_namespace(message_queue.get_topic()),
+                    message_queue.get_broker_name(),
+                    message_queue.get_queue_id(),
+                )
+            })
+            .collect())
+    }
+
     pub(crate) async fn correct_tags_offset(&mut self, pull_request: &PullRequest) {
         if pull_request.process_queue.msg_count() == 0 {
             self.offset_store
@@ -1083,6 +1178,57 @@ impl DefaultMQPushConsumerImpl {
         }
     }
 
+    /// Starts a consumer span per message when telemetry is enabled, continuing the trace
+    /// propagated by the producer through the message properties.
+    pub(crate) fn start_process_spans(&self, msgs: &[&MessageExt]) -> Vec<Box<dyn MessagingSpan>> {
+        if !self.client_config.enable_telemetry {
+            return Vec::new();
+        }
+        msgs.iter()
+            .map(|msg| {
+                let mut span = start_span(
+                    format!("{} process", msg.get_topic()),
+                    SpanKind::Consumer,
+                    TraceParent::from_message(*msg),
+                );
+                span.set_attribute(attributes::MESSAGING_OPERATION, "process".to_string());
+                span.set_attribute(
+                    attributes::MESSAGING_DESTINATION_NAME,
+                    msg.get_topic().to_string(),
+                );
+                span.set_attribute(
+                    attributes::MESSAGING_CONSUMER_GROUP,
+                    self.consumer_config.consumer_group.to_string(),
+                );
+                span.set_attribute(attributes::MESSAGING_MESSAGE_ID, msg.msg_id().to_string());
+                span.set_attribute(attributes::MESSAGING_QUEUE_ID, msg.queue_id().to_string());
+                span.set_attribute(
+                    attributes::MESSAGING_QUEUE_OFFSET,
+                    msg.queue_offset().to_string(),
+                );
+                if let Some(tags) = msg.get_tags() {
+                    span.set_attribute(attributes::MESSAGING_MESSAGE_TAG, tags.to_string());
+                }
+                span
+            })
+            .collect()
+    }
+
+    /// Ends the spans started by [`start_process_spans`](Self::start_process_spans), marking
+    /// them failed with the consume status unless the messages were consumed.
+    pub(crate) fn end_process_spans(
+        spans: Vec<Box<dyn MessagingSpan>>,
+        status: &str,
+        success: bool,
+    ) {
+        for mut span in spans {
+            if !success {
+                span.record_error(status);
+            }
+            span.end();
+        }
+    }
+
     #[inline]
     pub fn has_hook(&self) -> bool {
         !self.consume_message_hook_list.is_empty()
@@ -1333,7 +1479,106 @@ impl MQConsumerInner for DefaultMQPushConsumerImpl {
         self.consumer_config.unit_mode
     }
 
-    fn consumer_running_info(&self) -> ConsumerRunningInfo {
-        todo!()
+    async fn consumer_running_info(&self) -> ConsumerRunningInfo {
+        let mut info = ConsumerRunningInfo::default();
+        info.properties.insert(
+            ConsumerRunningInfo::PROP_CONSUME_ORDERLY.to_string(),
+            self.consume_orderly.to_string(),
+        );
+        info.properties.insert(
+            ConsumerRunningInfo::PROP_THREADPOOL_CORE_SIZE.to_string(),
+            self.consumer_config.consume_thread_min.to_string(),
+        );
+        info.properties.insert(
+            ConsumerRunningInfo::PROP_CONSUMER_START_TIMESTAMP.to_string(),
+            self.consumer_start_timestamp.to_string(),
+        );
+        info.properties.insert(
+            "consumerGroup".to_string(),
+            self.consumer_config.consumer_group.to_string(),
+        );
+        info.properties.insert(
+            "messageModel".to_string(),
+            self.consumer_config.message_model.to_string(),
+        );
+        info.properties.insert(
+            "consumeFromWhere".to_string(),
+            format!("{:?}", self.consumer_config.consume_from_where),
+        );
+        info.properties.insert(
+            "consumeThreadMax".to_string(),
+            self.consumer_config.consume_thread_max.to_string(),
+        );
+        info.properties.insert(
+            "pullBatchSize".to_string(),
+            self.consumer_config.pull_batch_size.to_string(),
+        );
+        info.properties.insert(
+            "consumeMessageBatchMaxSize".to_string(),
+            self.consumer_config
+                .consume_message_batch_max_size
+                .to_string(),
+        );
+        info.properties.insert(
+            "maxReconsumeTimes".to_string(),
+            self.consumer_config.max_reconsume_times.to_string(),
+        );
+
+        let subscription_set = self
+            .rebalance_impl
+            .rebalance_impl_inner
+            .subscription_inner
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        let process_queues = self
+            .rebalance_impl
+            .rebalance_impl_inner
+            .process_queue_table
+            .read()
+            .await
+            .iter()
+            .map(|(mq, pq)| (mq.clone(), pq.clone()))
+            .collect::<Vec<_>>();
+        for (mq, pq) in process_queues {
+            let mut pq_info = ProcessQueueInfo::default();
+            if let Some(offset_store) = self.offset_store.as_ref() {
+                pq_info.commit_offset = offset_store
+                    .read_offset(&mq, ReadOffsetType::MemoryFirstThenStore)
+                    .await
+                    .max(0) as u64;
+            }
+            pq.fill_process_queue_info(&mut pq_info).await;
+            info.mq_table.insert(mq, pq_info);
+        }
+
+        for (mq, pq) in self
+            .rebalance_impl
+            .rebalance_impl_inner
+            .pop_process_queue_table
+            .read()
+            .await
+            .iter()
+        {
+            let mut pop_pq_info = PopProcessQueueInfo::default();
+            pq.fill_pop_process_queue_info(&mut pop_pq_info);
+            info.mq_pop_table.insert(mq.clone(), pop_pq_info);
+        }
+
+        if let Some(client_instance) = self.client_instance.as_ref() {
+            for subscription in subscription_set.iter() {
+                let consume_status = client_instance.consumer_stats_manager.consume_status(
+                    self.consumer_config.consumer_group.as_str(),
+                    subscription.topic.as_str(),
+                );
+                info.status_table
+                    .insert(subscription.topic.clone(), consume_status);
+            }
+        }
+        info.subscription_set = subscription_set;
+        info
     }
 }
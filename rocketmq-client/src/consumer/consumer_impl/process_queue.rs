@@ -350,8 +350,36 @@ impl ProcessQueue {
         drop(lock);
     }
 
-    pub(crate) fn fill_process_queue_info(&self, info: ProcessQueueInfo) {
-        unimplemented!("fill_process_queue_info")
+    pub(crate) async fn fill_process_queue_info(&self, info: &mut ProcessQueueInfo) {
+        let lock = self.tree_map_lock.read().await;
+        let msg_tree_map = self.msg_tree_map.read().await;
+        if let (Some((min_offset, _)), Some((max_offset, _))) = (
+            msg_tree_map.first_key_value(),
+            msg_tree_map.last_key_value(),
+        ) {
+            info.cached_msg_min_offset = *min_offset as u64;
+            info.cached_msg_max_offset = *max_offset as u64;
+            info.cached_msg_count = msg_tree_map.len() as u32;
+        }
+        drop(msg_tree_map);
+        info.cached_msg_size_in_mib = (self.msg_size() / (1024 * 1024)) as u32;
+        let consuming_msg_orderly_tree_map = self.consuming_msg_orderly_tree_map.read().await;
+        if let (Some((min_offset, _)), Some((max_offset, _))) = (
+            consuming_msg_orderly_tree_map.first_key_value(),
+            consuming_msg_orderly_tree_map.last_key_value(),
+        ) {
+            info.transaction_msg_min_offset = *min_offset as u64;
+            info.transaction_msg_max_offset = *max_offset as u64;
+            info.transaction_msg_count = consuming_msg_orderly_tree_map.len() as u32;
+        }
+        drop(consuming_msg_orderly_tree_map);
+        info.locked = self.is_locked();
+        info.try_unlock_times = self.try_unlock_times.load(Ordering::Acquire) as u64;
+        info.last_lock_timestamp = self.last_lock_timestamp.load(Ordering::Acquire);
+        info.droped = self.is_dropped();
+        info.last_pull_timestamp = self.last_pull_timestamp.load(Ordering::Acquire);
+        info.last_consume_timestamp = self.last_consume_timestamp.load(Ordering::Acquire);
+        drop(lock);
     }
 
     pub(crate) fn set_last_pull_timestamp(&self, last_pull_timestamp: u64) {
@@ -29,6 +29,10 @@ use rocketmq_common::common::mix_all;
 use rocketmq_common::common::mq_version::RocketMqVersion;
 use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
 use rocketmq_common::common::sys_flag::pull_sys_flag::PullSysFlag;
+use rocketmq_common::common::telemetry::messaging_span::attributes;
+use rocketmq_common::common::telemetry::messaging_span::MessagingSpan;
+use rocketmq_common::common::telemetry::messaging_span::SpanKind;
+use rocketmq_common::common::telemetry::tracer_provider::start_span;
 use rocketmq_common::MessageAccessor::MessageAccessor;
 use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::TopicRequestHeader;
 use rocketmq_remoting::protocol::header::pull_message_request_header::PullMessageRequestHeader;
@@ -297,7 +301,10 @@ impl PullAPIWrapper {
         if let Some(find_broker_result) = find_broker_result {
             {
                 if !ExpressionType::is_tag_type(Some(expression_type.as_str()))
-                    && find_broker_result.broker_version < RocketMqVersion::V410Snapshot.into()
+                    && !RocketMqVersion::is_supported(
+                        find_broker_result.broker_version,
+                        RocketMqVersion::V410Snapshot,
+                    )
                 {
                     return mq_client_err!(format!(
                         "The broker[{}],[{}] does not support consumer to filter message by \
@@ -350,7 +357,8 @@ impl PullAPIWrapper {
                     .await?;
             }
 
-            MQClientAPIImpl::pull_message(
+            let pull_span = self.start_pull_span(mq, offset, &broker_addr);
+            let result = MQClientAPIImpl::pull_message(
                 self.client_instance.get_mq_client_api_impl(),
                 broker_addr,
                 request_header,
@@ -358,12 +366,53 @@ impl PullAPIWrapper {
                 communication_mode,
                 pull_callback,
             )
-            .await
+            .await;
+            if let Some(mut span) = pull_span {
+                if let Err(err) = &result {
+                    span.record_error(err.to_string().as_str());
+                }
+                span.end();
+            }
+            result
         } else {
             mq_client_err!(format!("The broker[{}] not exist", mq.get_broker_name(),))
         }
     }
 
+    /// Starts the client span of a pull request when telemetry is enabled. In async mode the
+    /// span covers sending the request, the received messages are traced when consumed.
+    fn start_pull_span(
+        &self,
+        mq: &MessageQueue,
+        offset: i64,
+        broker_addr: &CheetahString,
+    ) -> Option<Box<dyn MessagingSpan>> {
+        if !self.client_instance.client_config.enable_telemetry {
+            return None;
+        }
+        let mut span = start_span(
+            format!("{} receive", mq.get_topic()),
+            SpanKind::Client,
+            None,
+        );
+        span.set_attribute(attributes::MESSAGING_OPERATION, "receive".to_string());
+        span.set_attribute(
+            attributes::MESSAGING_DESTINATION_NAME,
+            mq.get_topic().to_string(),
+        );
+        span.set_attribute(
+            attributes::MESSAGING_CONSUMER_GROUP,
+            self.consumer_group.to_string(),
+        );
+        span.set_attribute(
+            attributes::MESSAGING_QUEUE_ID,
+            mq.get_queue_id().to_string(),
+        );
+        span.set_attribute(attributes::MESSAGING_QUEUE_OFFSET, offset.to_string());
+        span.set_attribute(attributes::SERVER_ADDRESS, broker_addr.to_string());
+        Some(span)
+    }
+
     async fn compute_pull_from_which_filter_server(
         &mut self,
         topic: &CheetahString,
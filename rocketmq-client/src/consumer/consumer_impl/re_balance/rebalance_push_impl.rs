@@ -183,18 +183,18 @@ impl Rebalance for RebalancePushImpl {
                 let new_val = 1.max(pull_threshold_for_topic / current_queue_count as i32);
                 info!(
                     "The pullThresholdForQueue is changed from {} to {}",
-                    pull_threshold_for_topic, new_val
+                    self.consumer_config.pull_threshold_for_queue, new_val
                 );
-                self.consumer_config.pull_threshold_for_topic = new_val;
+                self.consumer_config.pull_threshold_for_queue = new_val as u32;
             }
             let pull_threshold_size_for_topic = self.consumer_config.pull_threshold_size_for_topic;
             if pull_threshold_size_for_topic != -1 {
                 let new_val = 1.max(pull_threshold_size_for_topic / current_queue_count as i32);
                 info!(
                     "The pullThresholdSizeForQueue is changed from {} to {}",
-                    pull_threshold_size_for_topic, new_val
+                    self.consumer_config.pull_threshold_size_for_queue, new_val
                 );
-                self.consumer_config.pull_threshold_size_for_topic = new_val;
+                self.consumer_config.pull_threshold_size_for_queue = new_val as u32;
             }
         }
 
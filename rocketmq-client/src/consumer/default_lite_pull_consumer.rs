@@ -0,0 +1,310 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_remoting::runtime::RPCHook;
+use rocketmq_rust::ArcMut;
+
+use crate::base::client_config::ClientConfig;
+use crate::consumer::consumer_impl::default_lite_pull_consumer_impl::DefaultLitePullConsumerImpl;
+use crate::consumer::lite_pull_consumer::LitePullConsumer;
+use crate::consumer::message_queue_listener::MessageQueueListener;
+use crate::consumer::message_selector::MessageSelector;
+use crate::consumer::topic_message_queue_change_listener::TopicMessageQueueChangeListener;
+use crate::mq_client_err;
+use crate::Result;
+
+const SUBSCRIBE_NOT_SUPPORTED: &str =
+    "The lite pull consumer only supports assign mode, assign message queues instead";
+
+/// A pull consumer whose application drives consumption with `poll`.
+///
+/// Queues are assigned manually with `assign`; subscribe mode with rebalancing is not supported
+/// yet. Each assigned queue can be paused and resumed on its own, a paused queue keeps its
+/// assignment and offsets but is skipped by `poll` until it is resumed.
+pub struct DefaultLitePullConsumer {
+    default_lite_pull_consumer_impl: ArcMut<DefaultLitePullConsumerImpl>,
+}
+
+impl DefaultLitePullConsumer {
+    pub fn new(client_config: ClientConfig, consumer_group: impl Into<CheetahString>) -> Self {
+        Self::new_with_rpc_hook(client_config, consumer_group, None)
+    }
+
+    pub fn new_with_rpc_hook(
+        client_config: ClientConfig,
+        consumer_group: impl Into<CheetahString>,
+        rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+    ) -> Self {
+        Self {
+            default_lite_pull_consumer_impl: ArcMut::new(DefaultLitePullConsumerImpl::new(
+                client_config,
+                consumer_group.into(),
+                rpc_hook,
+            )),
+        }
+    }
+
+    /// Where a queue without a committed offset starts: `ConsumeFromFirstOffset` at its first
+    /// message, otherwise at its end.
+    pub fn set_consume_from_where(&mut self, consume_from_where: ConsumeFromWhere) {
+        self.default_lite_pull_consumer_impl.consume_from_where = consume_from_where;
+    }
+
+    /// Maximum number of messages returned by one `poll`.
+    pub fn set_pull_batch_size(&mut self, pull_batch_size: u32) {
+        self.default_lite_pull_consumer_impl.pull_batch_size = pull_batch_size as i32;
+    }
+
+    pub fn set_poll_timeout_millis(&mut self, poll_timeout_millis: u64) {
+        self.default_lite_pull_consumer_impl.poll_timeout_millis = poll_timeout_millis;
+    }
+
+    pub fn set_auto_commit_interval_millis(&mut self, auto_commit_interval_millis: u64) {
+        self.default_lite_pull_consumer_impl
+            .auto_commit_interval_millis = auto_commit_interval_millis;
+    }
+
+    /// Returns `true` if the queue is paused, queues that are not assigned are never paused.
+    pub fn is_paused(&self, message_queue: &MessageQueue) -> bool {
+        self.default_lite_pull_consumer_impl
+            .is_paused(message_queue)
+    }
+}
+
+impl LitePullConsumer for DefaultLitePullConsumer {
+    async fn start(&self) -> Result<()> {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .start(self.default_lite_pull_consumer_impl.clone())
+            .await
+    }
+
+    async fn shutdown(&self) {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .shutdown()
+            .await
+    }
+
+    async fn is_running(&self) -> bool {
+        self.default_lite_pull_consumer_impl.is_running()
+    }
+
+    async fn subscribe(&self, _topic: &str) -> Result<()> {
+        mq_client_err!(SUBSCRIBE_NOT_SUPPORTED)
+    }
+
+    async fn subscribe_with_expression(&self, _topic: &str, _sub_expression: &str) -> Result<()> {
+        mq_client_err!(SUBSCRIBE_NOT_SUPPORTED)
+    }
+
+    async fn subscribe_with_listener<MQL>(
+        &self,
+        _topic: &str,
+        _sub_expression: &str,
+        _listener: MQL,
+    ) -> Result<()>
+    where
+        MQL: MessageQueueListener,
+    {
+        mq_client_err!(SUBSCRIBE_NOT_SUPPORTED)
+    }
+
+    async fn subscribe_with_selector(
+        &self,
+        _topic: &str,
+        _selector: Option<MessageSelector>,
+    ) -> Result<()> {
+        mq_client_err!(SUBSCRIBE_NOT_SUPPORTED)
+    }
+
+    async fn unsubscribe(&self, _topic: &str) {}
+
+    async fn assignment(&self) -> Result<HashSet<MessageQueue>> {
+        Ok(self.default_lite_pull_consumer_impl.assignment())
+    }
+
+    async fn assign(&self, message_queues: Vec<MessageQueue>) {
+        self.default_lite_pull_consumer_impl.assign(message_queues);
+    }
+
+    async fn set_sub_expression_for_assign(&self, topic: &str, sub_expression: &str) {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .set_sub_expression_for_assign(topic, sub_expression);
+    }
+
+    async fn poll(&self) -> Vec<MessageExt> {
+        let timeout = self.default_lite_pull_consumer_impl.poll_timeout_millis;
+        self.poll_with_timeout(timeout).await
+    }
+
+    async fn poll_with_timeout(&self, timeout: u64) -> Vec<MessageExt> {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .poll(timeout)
+            .await
+    }
+
+    async fn seek(&self, message_queue: &MessageQueue, offset: i64) -> Result<()> {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .seek(message_queue, offset)
+            .await
+    }
+
+    async fn pause(&self, message_queues: Vec<MessageQueue>) {
+        self.default_lite_pull_consumer_impl
+            .pause(message_queues.as_slice());
+    }
+
+    async fn resume(&self, message_queues: Vec<MessageQueue>) {
+        self.default_lite_pull_consumer_impl
+            .resume(message_queues.as_slice());
+    }
+
+    async fn is_auto_commit(&self) -> bool {
+        self.default_lite_pull_consumer_impl.auto_commit
+    }
+
+    async fn set_auto_commit(&self, auto_commit: bool) {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .auto_commit = auto_commit;
+    }
+
+    async fn fetch_message_queues(&self, topic: &str) -> Result<Vec<MessageQueue>> {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .fetch_message_queues(topic)
+            .await
+    }
+
+    async fn offset_for_timestamp(
+        &self,
+        message_queue: &MessageQueue,
+        timestamp: u64,
+    ) -> Result<i64> {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .offset_for_timestamp(message_queue, timestamp)
+            .await
+    }
+
+    async fn commit_sync(&self) {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .commit_all(true)
+            .await;
+    }
+
+    async fn commit_sync_with_map(&self, offset_map: HashMap<MessageQueue, i64>, persist: bool) {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .commit_offsets(offset_map, persist)
+            .await;
+    }
+
+    async fn commit(&self) {
+        self.commit_sync().await;
+    }
+
+    async fn commit_with_map(&self, offset_map: HashMap<MessageQueue, i64>, persist: bool) {
+        self.commit_sync_with_map(offset_map, persist).await;
+    }
+
+    async fn commit_with_set(&self, message_queues: HashSet<MessageQueue>, persist: bool) {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .commit_message_queues(message_queues, persist)
+            .await;
+    }
+
+    async fn committed(&self, message_queue: &MessageQueue) -> Result<i64> {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .committed(message_queue)
+            .await
+    }
+
+    async fn register_topic_message_queue_change_listener<TL>(
+        &self,
+        _topic: &str,
+        _listener: TL,
+    ) -> Result<()>
+    where
+        TL: TopicMessageQueueChangeListener,
+    {
+        mq_client_err!(SUBSCRIBE_NOT_SUPPORTED)
+    }
+
+    async fn update_name_server_address(&self, name_server_address: &str) {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .update_name_server_address(name_server_address)
+            .await;
+    }
+
+    async fn seek_to_begin(&self, message_queue: &MessageQueue) -> Result<()> {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .seek_to_begin(message_queue)
+            .await
+    }
+
+    async fn seek_to_end(&self, message_queue: &MessageQueue) -> Result<()> {
+        self.default_lite_pull_consumer_impl
+            .mut_from_ref()
+            .seek_to_end(message_queue)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn paused_queues_stay_assigned() {
+        let consumer = DefaultLitePullConsumer::new(ClientConfig::default(), "group");
+        let mq0 = MessageQueue::from_parts("topic", "broker-a", 0);
+        let mq1 = MessageQueue::from_parts("topic", "broker-a", 1);
+        consumer.assign(vec![mq0.clone(), mq1.clone()]).await;
+
+        consumer.pause(vec![mq0.clone()]).await;
+        assert!(consumer.is_paused(&mq0));
+        assert!(!consumer.is_paused(&mq1));
+        assert_eq!(consumer.assignment().await.unwrap().len(), 2);
+
+        consumer.resume(vec![mq0.clone()]).await;
+        assert!(!consumer.is_paused(&mq0));
+    }
+
+    #[tokio::test]
+    async fn subscribe_mode_is_rejected() {
+        let consumer = DefaultLitePullConsumer::new(ClientConfig::default(), "group");
+        assert!(consumer.subscribe("topic").await.is_err());
+        assert!(consumer.poll_with_timeout(10).await.is_empty());
+    }
+}
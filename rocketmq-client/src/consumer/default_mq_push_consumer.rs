@@ -23,18 +23,22 @@ use cheetah_string::CheetahString;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
 use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::topic::TopicValidator;
 use rocketmq_common::utils::util_all;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
+use rocketmq_remoting::protocol::subscription::retry_policy::RetryPolicy;
 use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_rust::ArcMut;
 use tokio::runtime::Handle;
+use tracing::warn;
 
 use crate::base::client_config::ClientConfig;
 use crate::base::mq_admin::MQAdmin;
 use crate::base::query_result::QueryResult;
 use crate::consumer::allocate_message_queue_strategy::AllocateMessageQueueStrategy;
+use crate::consumer::consume_result_hook::ConsumeResultHook;
 use crate::consumer::consumer_impl::default_mq_push_consumer_impl::DefaultMQPushConsumerImpl;
 use crate::consumer::default_mq_push_consumer_builder::DefaultMQPushConsumerBuilder;
 use crate::consumer::listener::consume_concurrently_context::ConsumeConcurrentlyContext;
@@ -49,6 +53,7 @@ use crate::consumer::message_selector::MessageSelector;
 use crate::consumer::mq_consumer::MQConsumer;
 use crate::consumer::mq_push_consumer::MQPushConsumer;
 use crate::consumer::rebalance_strategy::allocate_message_queue_averagely::AllocateMessageQueueAveragely;
+use crate::consumer::retry_policy::DEFAULT_MESSAGE_DELAY_LEVEL;
 use crate::trace::async_trace_dispatcher::AsyncTraceDispatcher;
 use crate::trace::hook::consume_message_trace_hook_impl::ConsumeMessageTraceHookImpl;
 use crate::trace::trace_dispatcher::TraceDispatcher;
@@ -91,6 +96,12 @@ pub struct ConsumerConfig {
     pub(crate) trace_dispatcher: Option<Arc<Box<dyn TraceDispatcher + Send + Sync>>>,
     pub(crate) client_rebalance: bool,
     pub(crate) rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+    /// Backoff of failed messages, `None` keeps the broker's delay level escalation.
+    pub(crate) retry_policy: Option<Arc<dyn RetryPolicy + Send + Sync>>,
+    pub(crate) consume_result_hook: Option<Arc<dyn ConsumeResultHook>>,
+    /// `messageDelayLevel` of the brokers, the delays of the retry policy are rounded up to
+    /// these levels.
+    pub(crate) message_delay_level: CheetahString,
 }
 
 impl ConsumerConfig {
@@ -222,6 +233,18 @@ impl ConsumerConfig {
         &self.rpc_hook
     }
 
+    pub fn retry_policy(&self) -> &Option<Arc<dyn RetryPolicy + Send + Sync>> {
+        &self.retry_policy
+    }
+
+    pub fn consume_result_hook(&self) -> &Option<Arc<dyn ConsumeResultHook>> {
+        &self.consume_result_hook
+    }
+
+    pub fn message_delay_level(&self) -> &CheetahString {
+        &self.message_delay_level
+    }
+
     pub fn set_consumer_group(&mut self, consumer_group: CheetahString) {
         self.consumer_group = consumer_group;
     }
@@ -377,6 +400,21 @@ impl ConsumerConfig {
     pub fn set_rpc_hook(&mut self, rpc_hook: Option<Arc<Box<dyn RPCHook>>>) {
         self.rpc_hook = rpc_hook;
     }
+
+    pub fn set_retry_policy(&mut self, retry_policy: Option<Arc<dyn RetryPolicy + Send + Sync>>) {
+        self.retry_policy = retry_policy;
+    }
+
+    pub fn set_consume_result_hook(
+        &mut self,
+        consume_result_hook: Option<Arc<dyn ConsumeResultHook>>,
+    ) {
+        self.consume_result_hook = consume_result_hook;
+    }
+
+    pub fn set_message_delay_level(&mut self, message_delay_level: CheetahString) {
+        self.message_delay_level = message_delay_level;
+    }
 }
 
 impl Default for ConsumerConfig {
@@ -421,6 +459,9 @@ impl Default for ConsumerConfig {
             trace_dispatcher: None,
             client_rebalance: true,
             rpc_hook: None,
+            retry_policy: None,
+            consume_result_hook: None,
+            message_delay_level: CheetahString::from_static_str(DEFAULT_MESSAGE_DELAY_LEVEL),
         }
     }
 }
@@ -445,7 +486,12 @@ impl MQConsumer for DefaultMQPushConsumer {
         &mut self,
         topic: &str,
     ) -> crate::Result<Vec<MessageQueue>> {
-        todo!()
+        let topic = self.client_config.with_namespace(topic);
+        self.default_mqpush_consumer_impl
+            .as_mut()
+            .unwrap()
+            .fetch_subscribe_message_queues(&topic)
+            .await
     }
 }
 
@@ -523,7 +569,10 @@ impl MQPushConsumer for DefaultMQPushConsumer {
             let mut dispatcher = AsyncTraceDispatcher::new(
                 self.consumer_config.consumer_group.as_str(),
                 Type::Consume,
-                self.client_config.trace_topic.clone().unwrap().as_str(),
+                self.client_config
+                    .trace_topic
+                    .as_deref()
+                    .unwrap_or(TopicValidator::RMQ_SYS_TRACE_TOPIC),
                 self.consumer_config.rpc_hook.clone(),
             );
             dispatcher
@@ -538,8 +587,13 @@ impl MQPushConsumer for DefaultMQPushConsumer {
             );
         }
 
-        if let Some(ref rpc_hook) = self.consumer_config.trace_dispatcher {
-            unimplemented!("trace hook");
+        if let Some(ref trace_dispatcher) = self.consumer_config.trace_dispatcher {
+            let name_srv_addr = self.client_config.get_namesrv_addr().unwrap_or_default();
+            if let Err(e) =
+                trace_dispatcher.start(name_srv_addr.as_str(), self.client_config.access_channel)
+            {
+                warn!("trace dispatcher start failed: {}", e);
+            }
         }
 
         Ok(())
@@ -603,14 +657,14 @@ impl MQPushConsumer for DefaultMQPushConsumer {
     fn subscribe(&mut self, topic: &str, sub_expression: &str) -> crate::Result<()> {
         let handle = Handle::current();
         let mut default_mqpush_consumer_impl = self.default_mqpush_consumer_impl.clone();
-        let topic = topic.to_string();
+        let topic = self.client_config.with_namespace(topic);
         let sub_expression = sub_expression.to_string();
         match thread::spawn(move || {
             handle.block_on(async move {
                 default_mqpush_consumer_impl
                     .as_mut()
                     .unwrap()
-                    .subscribe(topic.into(), sub_expression.into())
+                    .subscribe(topic, sub_expression.into())
                     .await
             })
         })
@@ -636,11 +690,15 @@ impl MQPushConsumer for DefaultMQPushConsumer {
     }
 
     async fn suspend(&mut self) {
-        todo!()
+        if let Some(ref default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
+            default_mqpush_consumer_impl.suspend();
+        }
     }
 
     async fn resume(&mut self) {
-        todo!()
+        if let Some(ref mut default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
+            default_mqpush_consumer_impl.resume().await;
+        }
     }
 }
 
@@ -20,11 +20,13 @@ use std::sync::Arc;
 use cheetah_string::CheetahString;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
+use rocketmq_remoting::protocol::subscription::retry_policy::RetryPolicy;
 use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_rust::ArcMut;
 
 use crate::base::client_config::ClientConfig;
 use crate::consumer::allocate_message_queue_strategy::AllocateMessageQueueStrategy;
+use crate::consumer::consume_result_hook::ConsumeResultHook;
 use crate::consumer::default_mq_push_consumer::ConsumerConfig;
 use crate::consumer::default_mq_push_consumer::DefaultMQPushConsumer;
 use crate::consumer::message_queue_listener::MessageQueueListener;
@@ -67,6 +69,9 @@ pub struct DefaultMQPushConsumerBuilder {
     trace_dispatcher: Option<Arc<Box<dyn TraceDispatcher + Send + Sync>>>,
     client_rebalance: Option<bool>,
     rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+    retry_policy: Option<Arc<dyn RetryPolicy + Send + Sync>>,
+    consume_result_hook: Option<Arc<dyn ConsumeResultHook>>,
+    message_delay_level: Option<CheetahString>,
 }
 
 impl Default for DefaultMQPushConsumerBuilder {
@@ -105,6 +110,9 @@ impl Default for DefaultMQPushConsumerBuilder {
             trace_dispatcher: None,
             client_rebalance: None,
             rpc_hook: None,
+            retry_policy: None,
+            consume_result_hook: None,
+            message_delay_level: None,
         }
     }
 }
@@ -120,6 +128,45 @@ impl DefaultMQPushConsumerBuilder {
         self
     }
 
+    pub fn enable_trace(mut self, enable_trace: bool) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.enable_trace = enable_trace;
+        }
+        self
+    }
+
+    /// Records OpenTelemetry style spans and propagates the W3C trace context through the
+    /// `TRACEPARENT` message property.
+    pub fn enable_telemetry(mut self, enable_telemetry: bool) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.enable_telemetry = enable_telemetry;
+        }
+        self
+    }
+
+    /// Sets the zone of this client, brokers of the same zone are preferred.
+    pub fn zone_name(mut self, zone_name: impl Into<CheetahString>) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.zone_name = Some(zone_name.into());
+        }
+        self
+    }
+
+    /// Asks the name server to route this client to the brokers of its own zone only.
+    pub fn zone_mode(mut self, zone_mode: bool) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.zone_mode = zone_mode;
+        }
+        self
+    }
+
+    pub fn custom_trace_topic(mut self, trace_topic: impl Into<CheetahString>) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.trace_topic = Some(trace_topic.into());
+        }
+        self
+    }
+
     pub fn client_config(mut self, client_config: ClientConfig) -> Self {
         self.client_config = Some(client_config);
         self
@@ -312,6 +359,24 @@ impl DefaultMQPushConsumerBuilder {
         self
     }
 
+    pub fn retry_policy(mut self, retry_policy: impl RetryPolicy + Send + Sync + 'static) -> Self {
+        self.retry_policy = Some(Arc::new(retry_policy));
+        self
+    }
+
+    pub fn consume_result_hook(
+        mut self,
+        consume_result_hook: impl ConsumeResultHook + 'static,
+    ) -> Self {
+        self.consume_result_hook = Some(Arc::new(consume_result_hook));
+        self
+    }
+
+    pub fn message_delay_level(mut self, message_delay_level: impl Into<CheetahString>) -> Self {
+        self.message_delay_level = Some(message_delay_level.into());
+        self
+    }
+
     // Build method to create a ConsumerConfig instance
     pub fn build(mut self) -> DefaultMQPushConsumer {
         let mut consumer_config = ConsumerConfig::default();
@@ -410,6 +475,11 @@ impl DefaultMQPushConsumerBuilder {
             consumer_config.client_rebalance = client_rebalance;
         }
         consumer_config.rpc_hook = self.rpc_hook.clone();
+        consumer_config.retry_policy = self.retry_policy.take();
+        consumer_config.consume_result_hook = self.consume_result_hook.take();
+        if let Some(message_delay_level) = self.message_delay_level.take() {
+            consumer_config.message_delay_level = message_delay_level;
+        }
 
         let mut consumer = DefaultMQPushConsumer::new(
             self.client_config.take().unwrap_or_default(),
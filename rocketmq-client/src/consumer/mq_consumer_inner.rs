@@ -28,6 +28,7 @@ use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
 use rocketmq_rust::ArcMut;
 
+use crate::consumer::consumer_impl::default_lite_pull_consumer_impl::DefaultLitePullConsumerImpl;
 use crate::consumer::consumer_impl::default_mq_push_consumer_impl::DefaultMQPushConsumerImpl;
 use crate::consumer::consumer_impl::pop_request::PopRequest;
 use crate::consumer::consumer_impl::pull_request::PullRequest;
@@ -87,7 +88,7 @@ pub trait MQConsumerInnerLocal: MQConsumerInnerAny + Sync + 'static {
     fn is_unit_mode(&self) -> bool;
 
     /// Returns the running information of the consumer.
-    fn consumer_running_info(&self) -> ConsumerRunningInfo;
+    async fn consumer_running_info(&self) -> ConsumerRunningInfo;
 }
 
 pub trait MQConsumerInnerAny: std::any::Any {
@@ -109,6 +110,7 @@ impl<T: MQConsumerInner> MQConsumerInnerAny for T {
 #[derive(Clone)]
 pub struct MQConsumerInnerImpl {
     pub(crate) default_mqpush_consumer_impl: Option<ArcMut<DefaultMQPushConsumerImpl>>,
+    pub(crate) default_lite_pull_consumer_impl: Option<ArcMut<DefaultLitePullConsumerImpl>>,
 }
 
 impl MQConsumerInnerImpl {
@@ -145,49 +147,70 @@ impl MQConsumerInner for MQConsumerInnerImpl {
         if let Some(ref default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
             return MQConsumerInner::group_name(default_mqpush_consumer_impl.as_ref());
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::group_name(default_lite_pull_consumer_impl.as_ref());
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
     fn message_model(&self) -> MessageModel {
         if let Some(ref default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
             return MQConsumerInner::message_model(default_mqpush_consumer_impl.as_ref());
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::message_model(default_lite_pull_consumer_impl.as_ref());
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
     fn consume_type(&self) -> ConsumeType {
         if let Some(ref default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
             return MQConsumerInner::consume_type(default_mqpush_consumer_impl.as_ref());
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::consume_type(default_lite_pull_consumer_impl.as_ref());
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
     fn consume_from_where(&self) -> ConsumeFromWhere {
         if let Some(ref default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
             return MQConsumerInner::consume_from_where(default_mqpush_consumer_impl.as_ref());
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::consume_from_where(default_lite_pull_consumer_impl.as_ref());
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
     fn subscriptions(&self) -> HashSet<SubscriptionData> {
         if let Some(ref default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
             return MQConsumerInner::subscriptions(default_mqpush_consumer_impl.as_ref());
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::subscriptions(default_lite_pull_consumer_impl.as_ref());
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
     fn do_rebalance(&self) {
         if let Some(ref default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
             return MQConsumerInner::do_rebalance(default_mqpush_consumer_impl.as_ref());
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::do_rebalance(default_lite_pull_consumer_impl.as_ref());
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
     async fn try_rebalance(&self) -> Result<bool> {
         if let Some(ref default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
             return MQConsumerInner::try_rebalance(default_mqpush_consumer_impl.as_ref()).await;
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::try_rebalance(default_lite_pull_consumer_impl.as_ref()).await;
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
     async fn persist_consumer_offset(&self) {
@@ -195,7 +218,13 @@ impl MQConsumerInner for MQConsumerInnerImpl {
             return MQConsumerInner::persist_consumer_offset(default_mqpush_consumer_impl.as_ref())
                 .await;
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::persist_consumer_offset(
+                default_lite_pull_consumer_impl.as_ref(),
+            )
+            .await;
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
     async fn update_topic_subscribe_info(
@@ -211,7 +240,15 @@ impl MQConsumerInner for MQConsumerInnerImpl {
             )
             .await;
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::update_topic_subscribe_info(
+                default_lite_pull_consumer_impl.mut_from_ref(),
+                topic,
+                info,
+            )
+            .await;
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
     async fn is_subscribe_topic_need_update(&self, topic: &str) -> bool {
@@ -222,20 +259,37 @@ impl MQConsumerInner for MQConsumerInnerImpl {
             )
             .await;
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::is_subscribe_topic_need_update(
+                default_lite_pull_consumer_impl.as_ref(),
+                topic,
+            )
+            .await;
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
     fn is_unit_mode(&self) -> bool {
         if let Some(ref default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
             return MQConsumerInner::is_unit_mode(default_mqpush_consumer_impl.as_ref());
         }
-        panic!("default_mqpush_consumer_impl is None");
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::is_unit_mode(default_lite_pull_consumer_impl.as_ref());
+        }
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 
-    fn consumer_running_info(&self) -> ConsumerRunningInfo {
+    async fn consumer_running_info(&self) -> ConsumerRunningInfo {
         if let Some(ref default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl {
-            return MQConsumerInner::consumer_running_info(default_mqpush_consumer_impl.as_ref());
+            return MQConsumerInner::consumer_running_info(default_mqpush_consumer_impl.as_ref())
+                .await;
+        }
+        if let Some(ref default_lite_pull_consumer_impl) = self.default_lite_pull_consumer_impl {
+            return MQConsumerInner::consumer_running_info(
+                default_lite_pull_consumer_impl.as_ref(),
+            )
+            .await;
         }
-        panic!("default_mqpush_consumer_impl is None");
+        panic!("neither default_mqpush_consumer_impl nor default_lite_pull_consumer_impl is set");
     }
 }
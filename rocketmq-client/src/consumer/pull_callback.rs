@@ -16,6 +16,7 @@
  */
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::mix_all;
@@ -46,6 +47,7 @@ pub(crate) struct DefaultPullCallback {
     pub(crate) message_queue_inner: Option<MessageQueue>,
     pub(crate) subscription_data: Option<SubscriptionData>,
     pub(crate) pull_request: Option<PullRequest>,
+    pub(crate) begin_timestamp: Instant,
 }
 
 impl PullCallback for DefaultPullCallback {
@@ -74,8 +76,15 @@ impl PullCallback for DefaultPullCallback {
             PullStatus::Found => {
                 let prev_request_offset = pull_request.next_offset;
                 pull_request.set_next_offset(pull_result_ext.pull_result.next_begin_offset as i64);
-                /*let pull_rt = get_current_millis() - begin_timestamp.elapsed().as_millis() as u64;
-                self.client_instance.as_mut().unwrap().*/
+                let pull_rt = self.begin_timestamp.elapsed().as_millis() as u64;
+                let consumer_group = push_consumer_impl.consumer_config.consumer_group.clone();
+                if let Some(client_instance) = push_consumer_impl.client_instance.as_ref() {
+                    client_instance.consumer_stats_manager.inc_pull_rt(
+                        consumer_group.as_str(),
+                        message_queue_inner.get_topic(),
+                        pull_rt,
+                    );
+                }
                 let mut first_msg_offset = i64::MAX;
                 if pull_result_ext.pull_result.msg_found_list.is_empty() {
                     push_consumer_impl
@@ -89,6 +98,13 @@ impl PullCallback for DefaultPullCallback {
                         .unwrap()
                         .message_ext_inner
                         .queue_offset;
+                    if let Some(client_instance) = push_consumer_impl.client_instance.as_ref() {
+                        client_instance.consumer_stats_manager.inc_pull_tps(
+                            consumer_group.as_str(),
+                            message_queue_inner.get_topic(),
+                            pull_result_ext.pull_result.msg_found_list.len() as u64,
+                        );
+                    }
                     let vec = pull_result_ext.pull_result.msg_found_list.clone();
                     let dispatch_to_consume = pull_request.process_queue.put_message(vec).await;
                     push_consumer_impl
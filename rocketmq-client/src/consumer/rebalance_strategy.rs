@@ -17,6 +17,7 @@
 pub mod allocate_message_queue_averagely;
 pub mod allocate_message_queue_averagely_by_circle;
 pub mod allocate_message_queue_by_config;
+pub mod allocate_message_queue_by_lag;
 pub mod allocate_message_queue_by_machine_room;
 pub mod allocate_message_queue_by_machine_room_nearby;
 
@@ -0,0 +1,150 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_queue::MessageQueue;
+
+use crate::consumer::allocate_message_queue_strategy::AllocateMessageQueueStrategy;
+use crate::consumer::rebalance_strategy::check;
+
+/// Spreads queues so that every consumer ends up with a similar amount of backlog.
+///
+/// Queues are handed out from the largest lag down, each one to the consumer holding the least
+/// lag so far, then the fewest queues. Without lag information every queue weighs the same and
+/// the result is an even split by count. Brokers serving `QUERY_ASSIGNMENT` feed in the lag they
+/// know about through [`with_lag`](Self::with_lag).
+#[derive(Default)]
+pub struct AllocateMessageQueueByLag {
+    lag: HashMap<MessageQueue, i64>,
+}
+
+impl AllocateMessageQueueByLag {
+    pub const NAME: &'static str = "LAG_AWARE";
+
+    pub fn with_lag(lag: HashMap<MessageQueue, i64>) -> Self {
+        Self { lag }
+    }
+}
+
+impl AllocateMessageQueueStrategy for AllocateMessageQueueByLag {
+    fn allocate(
+        &self,
+        consumer_group: &CheetahString,
+        current_cid: &CheetahString,
+        mq_all: &[MessageQueue],
+        cid_all: &[CheetahString],
+    ) -> crate::Result<Vec<MessageQueue>> {
+        if !check(consumer_group, current_cid, mq_all, cid_all)? {
+            return Ok(Vec::new());
+        }
+        let mut queues = mq_all
+            .iter()
+            .map(|mq| (self.lag.get(mq).copied().unwrap_or(0).max(0), mq))
+            .collect::<Vec<_>>();
+        // stable sort keeps the caller's queue order among equal lags
+        queues.sort_by_key(|(queue_lag, _)| Reverse(*queue_lag));
+
+        let mut load = vec![(0i64, 0usize); cid_all.len()];
+        let mut result = Vec::new();
+        for (queue_lag, mq) in queues {
+            let (index, _) = load
+                .iter()
+                .enumerate()
+                .min_by_key(|(index, load)| (load.0, load.1, *index))
+                .unwrap();
+            load[index].0 = load[index].0.saturating_add(queue_lag);
+            load[index].1 += 1;
+            if &cid_all[index] == current_cid {
+                result.push(mq.clone());
+            }
+        }
+        Ok(result)
+    }
+
+    #[inline]
+    fn get_name(&self) -> &'static str {
+        Self::NAME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queues(count: i32) -> Vec<MessageQueue> {
+        (0..count)
+            .map(|queue_id| MessageQueue::from_parts("topic", "broker", queue_id))
+            .collect()
+    }
+
+    #[test]
+    fn allocate_without_lag_splits_evenly() {
+        let strategy = AllocateMessageQueueByLag::default();
+        let group = CheetahString::from("group");
+        let mq_all = queues(5);
+        let cid_all = vec![CheetahString::from("c1"), CheetahString::from("c2")];
+
+        let first = strategy
+            .allocate(&group, &cid_all[0], &mq_all, &cid_all)
+            .unwrap();
+        let second = strategy
+            .allocate(&group, &cid_all[1], &mq_all, &cid_all)
+            .unwrap();
+        assert_eq!(first.len(), 3);
+        assert_eq!(second.len(), 2);
+        assert!(first.iter().all(|mq| !second.contains(mq)));
+    }
+
+    #[test]
+    fn allocate_balances_backlog() {
+        let group = CheetahString::from("group");
+        let mq_all = queues(4);
+        let cid_all = vec![CheetahString::from("c1"), CheetahString::from("c2")];
+        let lag = HashMap::from([
+            (mq_all[0].clone(), 1000),
+            (mq_all[1].clone(), 10),
+            (mq_all[2].clone(), 10),
+            (mq_all[3].clone(), 10),
+        ]);
+        let strategy = AllocateMessageQueueByLag::with_lag(lag);
+
+        let first = strategy
+            .allocate(&group, &cid_all[0], &mq_all, &cid_all)
+            .unwrap();
+        let second = strategy
+            .allocate(&group, &cid_all[1], &mq_all, &cid_all)
+            .unwrap();
+        assert_eq!(first, vec![mq_all[0].clone()]);
+        assert_eq!(second.len(), 3);
+    }
+
+    #[test]
+    fn allocate_returns_empty_for_unknown_consumer() {
+        let strategy = AllocateMessageQueueByLag::default();
+        let result = strategy
+            .allocate(
+                &CheetahString::from("group"),
+                &CheetahString::from("c3"),
+                &queues(2),
+                &[CheetahString::from("c1")],
+            )
+            .unwrap();
+        assert!(result.is_empty());
+    }
+}
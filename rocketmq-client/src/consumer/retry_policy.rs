@@ -0,0 +1,118 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_remoting::protocol::subscription::retry_policy::RetryPolicy;
+
+/// The delay levels of a broker with the default `messageDelayLevel`.
+pub const DEFAULT_MESSAGE_DELAY_LEVEL: &str =
+    "1s 5s 10s 30s 1m 2m 3m 4m 5m 6m 7m 8m 9m 10m 20m 30m 1h 2h";
+
+/// Parses a `messageDelayLevel` string the way the broker does into the delay of each level in
+/// milliseconds, level `n` is at index `n - 1`. Returns `None` if a level is malformed.
+pub(crate) fn parse_delay_level(message_delay_level: &str) -> Option<Vec<i64>> {
+    let delay_level_table = message_delay_level
+        .split_whitespace()
+        .map(|level| {
+            let unit = level.chars().last()?;
+            let unit_millis = match unit {
+                's' => 1_000,
+                'm' => 60_000,
+                'h' => 3_600_000,
+                'd' => 86_400_000,
+                _ => return None,
+            };
+            let count = level[..level.len() - unit.len_utf8()].parse::<i64>().ok()?;
+            Some(count * unit_millis)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!delay_level_table.is_empty()).then_some(delay_level_table)
+}
+
+/// Maps the delay a [`RetryPolicy`] asks for onto the smallest delay level of
+/// `delay_level_table` that waits at least that long, since send-back only carries a delay
+/// level.
+pub(crate) fn next_delay_level(
+    retry_policy: &dyn RetryPolicy,
+    reconsume_times: i32,
+    delay_level_table: &[i64],
+) -> i32 {
+    let delay_millis = retry_policy.next_delay_duration(reconsume_times);
+    delay_level_table
+        .iter()
+        .position(|level_millis| *level_millis >= delay_millis)
+        .unwrap_or(delay_level_table.len().saturating_sub(1)) as i32
+        + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::protocol::subscription::customized_retry_policy::CustomizedRetryPolicy;
+    use rocketmq_remoting::protocol::subscription::exponential_retry_policy::ExponentialRetryPolicy;
+
+    use super::*;
+
+    fn default_table() -> Vec<i64> {
+        parse_delay_level(DEFAULT_MESSAGE_DELAY_LEVEL).unwrap()
+    }
+
+    #[test]
+    fn delay_levels_are_parsed_like_the_broker() {
+        let table = default_table();
+        assert_eq!(table.len(), 18);
+        assert_eq!(table[0], 1_000);
+        assert_eq!(table[4], 60_000);
+        assert_eq!(table[17], 7_200_000);
+        assert_eq!(
+            parse_delay_level(" 500s  1d "),
+            Some(vec![500_000, 86_400_000])
+        );
+        assert_eq!(parse_delay_level("1s 5x"), None);
+        assert_eq!(parse_delay_level("1s s"), None);
+        assert_eq!(parse_delay_level(""), None);
+    }
+
+    #[test]
+    fn customized_policy_matches_broker_escalation() {
+        let policy = CustomizedRetryPolicy::default();
+        let table = default_table();
+        assert_eq!(next_delay_level(&policy, 0, &table), 3);
+        assert_eq!(next_delay_level(&policy, 1, &table), 4);
+        assert_eq!(next_delay_level(&policy, 100, &table), 18);
+    }
+
+    #[test]
+    fn configured_delay_levels_are_used() {
+        let policy = ExponentialRetryPolicy::new(1_000, 600_000, 2);
+        let table = parse_delay_level("2s 1m 1h").unwrap();
+        assert_eq!(next_delay_level(&policy, 0, &table), 1);
+        assert_eq!(next_delay_level(&policy, 2, &table), 2);
+        assert_eq!(next_delay_level(&policy, 20, &table), 3);
+    }
+
+    #[test]
+    fn exponential_policy_rounds_up_to_a_delay_level() {
+        let policy = ExponentialRetryPolicy::new(1_000, 600_000, 2);
+        let table = default_table();
+        // 1s, 2s -> 5s, 4s -> 5s, 8s -> 10s, 16s -> 30s
+        assert_eq!(next_delay_level(&policy, 0, &table), 1);
+        assert_eq!(next_delay_level(&policy, 1, &table), 2);
+        assert_eq!(next_delay_level(&policy, 2, &table), 2);
+        assert_eq!(next_delay_level(&policy, 3, &table), 3);
+        assert_eq!(next_delay_level(&policy, 4, &table), 4);
+        // capped at max
+        assert_eq!(next_delay_level(&policy, 20, &table), 14);
+    }
+}
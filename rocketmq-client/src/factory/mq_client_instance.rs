@@ -31,9 +31,12 @@ use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::message::message_queue_assignment::MessageQueueAssignment;
 use rocketmq_common::common::mix_all;
+use rocketmq_common::common::mq_version::RocketMqVersion;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::base::connection_net_event::ConnectionNetEvent;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
+use rocketmq_remoting::protocol::body::consumer_running_info::ConsumerRunningInfo;
+use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
 use rocketmq_remoting::protocol::heartbeat::consumer_data::ConsumerData;
 use rocketmq_remoting::protocol::heartbeat::heartbeat_data::HeartbeatData;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
@@ -46,6 +49,8 @@ use rocketmq_runtime::RocketMQRuntime;
 use rocketmq_rust::ArcMut;
 use rocketmq_rust::RocketMQTokioMutex;
 use tokio::runtime::Handle;
+use tokio::select;
+use tokio::sync::broadcast;
 use tokio::sync::RwLock;
 use tracing::error;
 use tracing::info;
@@ -56,17 +61,20 @@ use crate::base::client_config::ClientConfig;
 use crate::client_error::MQClientError::MQClientErr;
 use crate::consumer::consumer_impl::pull_message_service::PullMessageService;
 use crate::consumer::consumer_impl::re_balance::rebalance_service::RebalanceService;
+use crate::consumer::consumer_impl::re_balance::RebalanceLocal;
 use crate::consumer::mq_consumer_inner::MQConsumerInner;
 use crate::consumer::mq_consumer_inner::MQConsumerInnerImpl;
 use crate::implementation::client_remoting_processor::ClientRemotingProcessor;
 use crate::implementation::find_broker_result::FindBrokerResult;
 use crate::implementation::mq_admin_impl::MQAdminImpl;
 use crate::implementation::mq_client_api_impl::MQClientAPIImpl;
+use crate::implementation::mq_client_manager::MQClientManager;
 use crate::mq_client_err;
 use crate::producer::default_mq_producer::DefaultMQProducer;
 use crate::producer::default_mq_producer::ProducerConfig;
 use crate::producer::producer_impl::mq_producer_inner::MQProducerInnerImpl;
 use crate::producer::producer_impl::topic_publish_info::TopicPublishInfo;
+use crate::stat::consumer_stats_manager::ConsumerStatsManager;
 use crate::Result;
 
 const LOCK_TIMEOUT_MILLIS: u64 = 3000;
@@ -119,6 +127,8 @@ pub struct MQClientInstance {
         >,
     >,
     send_heartbeat_times_total: Arc<AtomicI64>,
+    scheduled_task_shutdown: Option<broadcast::Sender<()>>,
+    pub(crate) consumer_stats_manager: Arc<ConsumerStatsManager>,
 }
 
 impl MQClientInstance {
@@ -223,6 +233,8 @@ impl MQClientInstance {
             broker_addr_table,
             broker_version_table: Arc::new(Default::default()),
             send_heartbeat_times_total: Arc::new(AtomicI64::new(0)),
+            scheduled_task_shutdown: None,
+            consumer_stats_manager: Arc::new(ConsumerStatsManager::new()),
         });
         let instance_clone = instance.clone();
         instance.mq_admin_impl.set_client(instance_clone);
@@ -299,7 +311,15 @@ impl MQClientInstance {
             ServiceState::CreateJust => {
                 self.service_state = ServiceState::StartFailed;
                 // If not specified,looking address from name remoting_server
-                if self.client_config.namesrv_addr.is_none() {
+                if let Some(namesrv_addr) = self.client_config.namesrv_addr.clone() {
+                    // the address list set in `new` is applied on another thread, make sure it
+                    // is in place before the first request goes out
+                    self.mq_client_api_impl
+                        .as_mut()
+                        .expect("mq_client_api_impl is None")
+                        .update_name_server_address_list(namesrv_addr.as_str())
+                        .await;
+                } else {
                     self.mq_client_api_impl
                         .as_mut()
                         .expect("mq_client_api_impl is None")
@@ -314,6 +334,7 @@ impl MQClientInstance {
                     .await;
                 // Start various schedule tasks
                 self.start_scheduled_task(this.clone());
+                self.consumer_stats_manager.start();
                 // Start pull service
                 let instance = this.clone();
                 self.pull_message_service.start(instance).await;
@@ -341,7 +362,46 @@ impl MQClientInstance {
         Ok(())
     }
 
-    pub async fn shutdown(&mut self) {}
+    /// Shuts the instance down once no producer (other than the inner default producer),
+    /// consumer or admin ext is registered on it any more. The instance is shared by every
+    /// client with the same client id, so it must outlive all of them.
+    pub async fn shutdown(&mut self) {
+        if !self.consumer_table.read().await.is_empty() {
+            return;
+        }
+        if !self.admin_ext_table.read().await.is_empty() {
+            return;
+        }
+        if self.producer_table.read().await.len() > 1 {
+            return;
+        }
+        if self.service_state != ServiceState::Running {
+            return;
+        }
+        self.service_state = ServiceState::ShutdownAlready;
+        // Boxed as the producer's shutdown may in turn shut a factory down.
+        Box::pin(
+            self.default_producer
+                .default_mqproducer_impl
+                .as_mut()
+                .unwrap()
+                .shutdown_with_factory(false),
+        )
+        .await;
+        self.pull_message_service.shutdown();
+        if let Some(scheduled_task_shutdown) = self.scheduled_task_shutdown.take() {
+            let _ = scheduled_task_shutdown.send(());
+        }
+        if let Some(mq_client_api_impl) = self.mq_client_api_impl.as_mut() {
+            mq_client_api_impl.shutdown();
+        }
+        self.rebalance_service.shutdown();
+        self.consumer_stats_manager.shutdown();
+        MQClientManager::get_instance()
+            .remove_client_factory(self.client_id.as_str())
+            .await;
+        info!("the client factory [{}] shutdown OK", self.client_id);
+    }
 
     pub async fn register_producer(&mut self, group: &str, producer: MQProducerInnerImpl) -> bool {
         if group.is_empty() {
@@ -357,19 +417,27 @@ impl MQClientInstance {
     }
 
     fn start_scheduled_task(&mut self, this: ArcMut<Self>) {
+        let (scheduled_task_shutdown, _) = broadcast::channel::<()>(1);
         if self.client_config.namesrv_addr.is_none() {
             // Fetch name server address
             let mut mq_client_api_impl = self.mq_client_api_impl.as_ref().unwrap().clone();
+            let mut shutdown_rx = scheduled_task_shutdown.subscribe();
             self.instance_runtime.get_handle().spawn(async move {
                 info!("ScheduledTask fetchNameServerAddr started");
-                tokio::time::sleep(Duration::from_secs(10)).await;
+                select! {
+                    _ = tokio::time::sleep(Duration::from_secs(10)) => {}
+                    _ = shutdown_rx.recv() => return,
+                }
                 loop {
                     let current_execution_time = tokio::time::Instant::now();
                     mq_client_api_impl.fetch_name_server_addr().await;
                     let next_execution_time = current_execution_time + Duration::from_secs(120);
                     let delay =
                         next_execution_time.saturating_duration_since(tokio::time::Instant::now());
-                    tokio::time::sleep(delay).await;
+                    select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = shutdown_rx.recv() => return,
+                    }
                 }
             });
         }
@@ -377,9 +445,13 @@ impl MQClientInstance {
         // Update topic route info from name server
         let mut client_instance = this.clone();
         let poll_name_server_interval = self.client_config.poll_name_server_interval;
+        let mut shutdown_rx = scheduled_task_shutdown.subscribe();
         self.instance_runtime.get_handle().spawn(async move {
             info!("ScheduledTask update_topic_route_info_from_name_server started");
-            tokio::time::sleep(Duration::from_millis(10)).await;
+            select! {
+                _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+                _ = shutdown_rx.recv() => return,
+            }
             loop {
                 let current_execution_time = tokio::time::Instant::now();
                 client_instance
@@ -389,16 +461,23 @@ impl MQClientInstance {
                     + Duration::from_millis(poll_name_server_interval as u64);
                 let delay =
                     next_execution_time.saturating_duration_since(tokio::time::Instant::now());
-                tokio::time::sleep(delay).await;
+                select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = shutdown_rx.recv() => return,
+                }
             }
         });
 
         // Clean offline broker and send heartbeat to all broker
         let mut client_instance = this.clone();
         let heartbeat_broker_interval = self.client_config.heartbeat_broker_interval;
+        let mut shutdown_rx = scheduled_task_shutdown.subscribe();
         self.instance_runtime.get_handle().spawn(async move {
             info!("ScheduledTask clean_offline_broker started");
-            tokio::time::sleep(Duration::from_secs(1)).await;
+            select! {
+                _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+                _ = shutdown_rx.recv() => return,
+            }
             loop {
                 let current_execution_time = tokio::time::Instant::now();
                 client_instance.clean_offline_broker().await;
@@ -409,7 +488,10 @@ impl MQClientInstance {
                     + Duration::from_millis(heartbeat_broker_interval as u64);
                 let delay =
                     next_execution_time.saturating_duration_since(tokio::time::Instant::now());
-                tokio::time::sleep(delay).await;
+                select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = shutdown_rx.recv() => return,
+                }
             }
         });
 
@@ -417,9 +499,13 @@ impl MQClientInstance {
         let mut client_instance = this;
         let persist_consumer_offset_interval =
             self.client_config.persist_consumer_offset_interval as u64;
+        let mut shutdown_rx = scheduled_task_shutdown.subscribe();
         self.instance_runtime.get_handle().spawn(async move {
             info!("ScheduledTask persistAllConsumerOffset started");
-            tokio::time::sleep(Duration::from_secs(10)).await;
+            select! {
+                _ = tokio::time::sleep(Duration::from_secs(10)) => {}
+                _ = shutdown_rx.recv() => return,
+            }
             loop {
                 let current_execution_time = tokio::time::Instant::now();
                 client_instance.persist_all_consumer_offset().await;
@@ -427,9 +513,13 @@ impl MQClientInstance {
                     + Duration::from_millis(persist_consumer_offset_interval);
                 let delay =
                     next_execution_time.saturating_duration_since(tokio::time::Instant::now());
-                tokio::time::sleep(delay).await;
+                select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = shutdown_rx.recv() => return,
+                }
             }
         });
+        self.scheduled_task_shutdown = Some(scheduled_task_shutdown);
     }
 
     pub async fn update_topic_route_info_from_name_server(&mut self) {
@@ -517,6 +607,20 @@ impl MQClientInstance {
         None
     }
 
+    /// Picks the broker that computes queue assignments for `topic`.
+    ///
+    /// Every consumer of the topic asks the same broker, the one with the smallest name, so
+    /// that they all allocate from the same consumer list and lag view.
+    async fn find_assignment_broker_addr_by_topic(&self, topic: &str) -> Option<CheetahString> {
+        let topic_route_table = self.topic_route_table.read().await;
+        topic_route_table
+            .get(topic)?
+            .broker_datas
+            .iter()
+            .min_by(|a, b| a.broker_name().cmp(b.broker_name()))?
+            .select_broker_addr()
+    }
+
     pub async fn update_topic_route_info_from_name_server_default(
         &mut self,
         topic: &CheetahString,
@@ -539,7 +643,7 @@ impl MQClientInstance {
                     let queue_nums = producer_config
                         .unwrap()
                         .default_topic_queue_nums()
-                        .max(data.read_queue_nums);
+                        .min(data.read_queue_nums);
                     data.read_queue_nums = queue_nums;
                     data.write_queue_nums = queue_nums;
                 }
@@ -634,26 +738,22 @@ impl MQClientInstance {
     }
 
     async fn is_need_update_topic_route_info(&self, topic: &CheetahString) -> bool {
-        let mut result = false;
         let producer_table = self.producer_table.read().await;
-        for (key, value) in producer_table.iter() {
-            if !result {
-                result = value.is_publish_topic_need_update(topic);
-                break;
-            }
-        }
-        if result {
+        if producer_table
+            .values()
+            .any(|producer| producer.is_publish_topic_need_update(topic))
+        {
             return true;
         }
+        drop(producer_table);
 
         let consumer_table = self.consumer_table.read().await;
-        for (key, value) in consumer_table.iter() {
-            if !result {
-                result = value.is_subscribe_topic_need_update(topic).await;
-                break;
+        for consumer in consumer_table.values() {
+            if consumer.is_subscribe_topic_need_update(topic).await {
+                return true;
             }
         }
-        result
+        false
     }
 
     pub async fn persist_all_consumer_offset(&mut self) {
@@ -731,6 +831,10 @@ impl MQClientInstance {
         self.mq_client_api_impl.as_ref().unwrap().clone()
     }
 
+    pub fn get_mq_admin_impl(&self) -> ArcMut<MQAdminImpl> {
+        self.mq_admin_impl.clone()
+    }
+
     pub async fn get_broker_name_from_message_queue(
         &self,
         message_queue: &MessageQueue,
@@ -1099,10 +1203,14 @@ impl MQClientInstance {
     }
 
     pub async fn unregister_consumer(&mut self, group: impl Into<CheetahString>) {
-        self.unregister_client(None, Some(group.into())).await;
+        let group = group.into();
+        self.consumer_table.write().await.remove(&group);
+        self.unregister_client(None, Some(group)).await;
     }
     pub async fn unregister_producer(&mut self, group: impl Into<CheetahString>) {
-        self.unregister_client(Some(group.into()), None).await;
+        let group = group.into();
+        self.producer_table.write().await.remove(&group);
+        self.unregister_client(Some(group), None).await;
     }
 
     async fn unregister_client(
@@ -1159,11 +1267,11 @@ impl MQClientInstance {
         message_model: MessageModel,
         timeout: u64,
     ) -> Result<Option<HashSet<MessageQueueAssignment>>> {
-        let mut broker_addr = self.find_broker_addr_by_topic(topic).await;
+        let mut broker_addr = self.find_assignment_broker_addr_by_topic(topic).await;
         if broker_addr.is_none() {
             self.update_topic_route_info_from_name_server_topic(topic)
                 .await;
-            broker_addr = self.find_broker_addr_by_topic(topic).await;
+            broker_addr = self.find_assignment_broker_addr_by_topic(topic).await;
         }
         if let Some(broker_addr) = broker_addr {
             let client_id = self.client_id.clone();
@@ -1185,21 +1293,115 @@ impl MQClientInstance {
         }
     }
 
+    /// Seeks the push consumer of `group` to the offsets a broker computed for `topic`.
+    ///
+    /// The consumer is suspended while its process queues are dropped, so that no in-flight
+    /// message commits an offset past the reset point, and resumed once the new offsets are
+    /// stored.
+    pub async fn reset_offset(
+        &self,
+        topic: &CheetahString,
+        group: &CheetahString,
+        offset_table: HashMap<MessageQueue, i64>,
+    ) {
+        let consumer = self
+            .consumer_table
+            .read()
+            .await
+            .get(group)
+            .and_then(|consumer| consumer.default_mqpush_consumer_impl.clone());
+        let Some(mut consumer) = consumer else {
+            info!("[reset-offset] consumer dose not exist. group={}", group);
+            return;
+        };
+        consumer.suspend();
+
+        let process_queue_table = consumer
+            .rebalance_impl
+            .rebalance_impl_inner
+            .process_queue_table
+            .clone();
+        let reset_queues = process_queue_table
+            .read()
+            .await
+            .iter()
+            .filter(|(mq, _)| mq.get_topic() == topic && offset_table.contains_key(*mq))
+            .map(|(mq, pq)| (mq.clone(), pq.clone()))
+            .collect::<Vec<_>>();
+        for (_, pq) in reset_queues.iter() {
+            pq.set_dropped(true);
+            pq.clear().await;
+        }
+
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        for (mq, pq) in reset_queues {
+            let Some(offset) = offset_table.get(&mq) else {
+                continue;
+            };
+            consumer.update_consume_offset(&mq, *offset).await;
+            consumer
+                .rebalance_impl
+                .remove_unnecessary_message_queue(&mq, pq.as_ref())
+                .await;
+            process_queue_table.write().await.remove(&mq);
+        }
+        consumer.resume().await;
+    }
+
     pub async fn consume_message_directly(
         &self,
         message: MessageExt,
         consumer_group: &CheetahString,
         broker_name: Option<CheetahString>,
     ) -> Option<ConsumeMessageDirectlyResult> {
-        let consumer_table = self.consumer_table.read().await;
-        let consumer_inner = consumer_table.get(consumer_group);
-        if let Some(consumer) = consumer_inner {
-            consumer
-                .consume_message_directly(message, broker_name)
-                .await;
-        }
+        let consumer = self
+            .consumer_table
+            .read()
+            .await
+            .get(consumer_group)
+            .cloned()?;
+        consumer
+            .consume_message_directly(message, broker_name)
+            .await
+    }
 
-        None
+    /// Collects the running information of the consumer registered under `consumer_group`,
+    /// adding the client-wide properties on top of what the consumer reports itself.
+    pub async fn consumer_running_info(
+        &self,
+        consumer_group: &CheetahString,
+    ) -> Option<ConsumerRunningInfo> {
+        let consumer = self
+            .consumer_table
+            .read()
+            .await
+            .get(consumer_group)
+            .cloned()?;
+        let mut consumer_running_info = consumer.consumer_running_info().await;
+
+        let namesrv_addr_list = self
+            .mq_client_api_impl
+            .as_ref()?
+            .get_name_server_address_list();
+        consumer_running_info.properties.insert(
+            ConsumerRunningInfo::PROP_NAMESERVER_ADDR.to_string(),
+            namesrv_addr_list.join(";"),
+        );
+        let consume_type = match consumer.consume_type() {
+            ConsumeType::ConsumeActively => "CONSUME_ACTIVELY",
+            ConsumeType::ConsumePassively => "CONSUME_PASSIVELY",
+            ConsumeType::ConsumePop => "CONSUME_POP",
+        };
+        consumer_running_info.properties.insert(
+            ConsumerRunningInfo::PROP_CONSUME_TYPE.to_string(),
+            consume_type.to_string(),
+        );
+        consumer_running_info.properties.insert(
+            ConsumerRunningInfo::PROP_CLIENT_VERSION.to_string(),
+            RocketMqVersion::CURRENT_VERSION.to_string(),
+        );
+        Some(consumer_running_info)
     }
 }
 
@@ -0,0 +1,75 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_client_id_setter::MessageClientIDSetter;
+use rocketmq_common::utils::network_util::NetworkUtil;
+use rocketmq_remoting::grpc::v2;
+
+/// Settings shared by the clients of the `apache.rocketmq.v2` gRPC protocol.
+#[derive(Debug, Clone)]
+pub struct GrpcClientConfig {
+    /// Address of the proxy, as `host:port`.
+    pub endpoints: CheetahString,
+    pub client_id: CheetahString,
+    pub namespace: CheetahString,
+    pub request_timeout: Duration,
+    /// How long the proxy may hold a receive request open waiting for messages.
+    pub long_polling_timeout: Duration,
+}
+
+impl GrpcClientConfig {
+    pub fn new(endpoints: impl Into<CheetahString>) -> Self {
+        let client_id = format!(
+            "{}@{}@{}",
+            NetworkUtil::get_local_address().unwrap_or_else(|| "127.0.0.1".to_string()),
+            std::process::id(),
+            MessageClientIDSetter::create_uniq_id()
+        );
+        GrpcClientConfig {
+            endpoints: endpoints.into(),
+            client_id: CheetahString::from_string(client_id),
+            namespace: CheetahString::new(),
+            request_timeout: Duration::from_secs(3),
+            long_polling_timeout: Duration::from_secs(20),
+        }
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn with_long_polling_timeout(mut self, long_polling_timeout: Duration) -> Self {
+        self.long_polling_timeout = long_polling_timeout;
+        self
+    }
+
+    /// Names a topic or group within the namespace of the client.
+    pub fn resource(&self, name: &str) -> v2::Resource {
+        v2::Resource {
+            resource_namespace: self.namespace.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn with_namespace(mut self, namespace: impl Into<CheetahString>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+}
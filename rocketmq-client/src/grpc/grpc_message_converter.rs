@@ -0,0 +1,265 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! Conversion between the messages of `rocketmq-common` and `apache.rocketmq.v2` messages.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_client_id_setter::MessageClientIDSetter;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::message_single::Message;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_common::common::message::STRING_HASH_SET;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::grpc;
+use rocketmq_remoting::grpc::v2;
+
+use crate::client_error::ClientErr;
+use crate::grpc::received_message::ReceivedMessage;
+
+/// Builds the gRPC message published for `message`. System properties are carried in their
+/// dedicated fields, every other property is sent as a user property.
+pub fn to_grpc_message<M: MessageTrait>(message: &M, transactional: bool) -> v2::Message {
+    let properties = message.get_properties();
+    let property = |key: &'static str| {
+        properties
+            .get(&CheetahString::from_static_str(key))
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string())
+    };
+    let message_group = property(MessageConst::PROPERTY_SHARDING_KEY);
+    let delivery_timestamp = message.get_deliver_time_ms();
+    let message_type = if transactional {
+        v2::MessageType::Transaction
+    } else if message_group.is_some() {
+        v2::MessageType::Fifo
+    } else if delivery_timestamp > 0 {
+        v2::MessageType::Delay
+    } else {
+        v2::MessageType::Normal
+    };
+    let system_properties = v2::SystemProperties {
+        tag: property(MessageConst::PROPERTY_TAGS),
+        keys: property(MessageConst::PROPERTY_KEYS)
+            .map(|keys| {
+                keys.split(MessageConst::KEY_SEPARATOR)
+                    .filter(|key| !key.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        message_id: property(MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX)
+            .unwrap_or_else(MessageClientIDSetter::create_uniq_id),
+        body_encoding: v2::Encoding::Identity as i32,
+        message_type: message_type as i32,
+        born_timestamp: Some(grpc::timestamp_from_millis(get_current_millis() as i64)),
+        delivery_timestamp: (delivery_timestamp > 0)
+            .then(|| grpc::timestamp_from_millis(delivery_timestamp as i64)),
+        message_group,
+        trace_context: property(MessageConst::PROPERTY_TRACE_PARENT),
+        ..Default::default()
+    };
+    v2::Message {
+        topic: Some(v2::Resource::new(message.get_topic().as_str())),
+        user_properties: properties
+            .iter()
+            .filter(|(key, _)| {
+                !STRING_HASH_SET.contains(key.as_str())
+                    && key.as_str() != MessageConst::PROPERTY_SHARDING_KEY
+            })
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        system_properties: Some(system_properties),
+        body: message
+            .get_body()
+            .map(|body| body.to_vec())
+            .unwrap_or_default(),
+    }
+}
+
+/// Builds the message handed to the application from a message received from the proxy.
+pub fn to_received_message(
+    message: v2::Message,
+) -> std::result::Result<ReceivedMessage, ClientErr> {
+    let Some(system_properties) = message.system_properties else {
+        return Err(ClientErr::new("received message without system properties"));
+    };
+    let Some(receipt_handle) = system_properties.receipt_handle else {
+        return Err(ClientErr::new(format!(
+            "received message {} without receipt handle",
+            system_properties.message_id
+        )));
+    };
+    let topic = message.topic.map(|topic| topic.name).unwrap_or_default();
+
+    let mut inner = Message::new(topic, &message.body);
+    for (key, value) in message.user_properties {
+        inner.put_property(
+            CheetahString::from_string(key),
+            CheetahString::from_string(value),
+        );
+    }
+    let mut put_property = |key: &'static str, value: String| {
+        inner.put_property(
+            CheetahString::from_static_str(key),
+            CheetahString::from_string(value),
+        );
+    };
+    let message_id = system_properties.message_id;
+    put_property(
+        MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX,
+        message_id.clone(),
+    );
+    if let Some(tag) = system_properties.tag {
+        put_property(MessageConst::PROPERTY_TAGS, tag);
+    }
+    if !system_properties.keys.is_empty() {
+        put_property(
+            MessageConst::PROPERTY_KEYS,
+            system_properties.keys.join(MessageConst::KEY_SEPARATOR),
+        );
+    }
+    if let Some(message_group) = system_properties.message_group.clone() {
+        put_property(MessageConst::PROPERTY_SHARDING_KEY, message_group);
+    }
+    if let Some(delivery_timestamp) = system_properties.delivery_timestamp {
+        put_property(
+            MessageConst::PROPERTY_TIMER_DELIVER_MS,
+            grpc::timestamp_to_millis(&delivery_timestamp).to_string(),
+        );
+    }
+    if let Some(trace_context) = system_properties.trace_context {
+        put_property(MessageConst::PROPERTY_TRACE_PARENT, trace_context);
+    }
+
+    let delivery_attempt = system_properties.delivery_attempt.unwrap_or(1);
+    let mut message_ext = MessageExt::default();
+    message_ext.set_message_inner(inner);
+    message_ext.set_msg_id(CheetahString::from_string(message_id.clone()));
+    message_ext.set_queue_id(system_properties.queue_id);
+    message_ext.set_queue_offset(system_properties.queue_offset.unwrap_or_default());
+    message_ext.set_reconsume_times(delivery_attempt - 1);
+    if let Some(born_timestamp) = system_properties.born_timestamp {
+        message_ext.set_born_timestamp(grpc::timestamp_to_millis(&born_timestamp));
+    }
+    if let Some(store_timestamp) = system_properties.store_timestamp {
+        message_ext.set_store_timestamp(grpc::timestamp_to_millis(&store_timestamp));
+    }
+    if let Ok(store_host) = system_properties.store_host.parse::<SocketAddr>() {
+        message_ext.set_store_host(store_host);
+    }
+
+    Ok(ReceivedMessage {
+        message: message_ext,
+        message_id: CheetahString::from_string(message_id),
+        receipt_handle: CheetahString::from_string(receipt_handle),
+        delivery_attempt,
+        invisible_duration: system_properties
+            .invisible_duration
+            .map(|duration| Duration::from_millis(grpc::duration_to_millis(&duration))),
+        message_group: system_properties
+            .message_group
+            .map(CheetahString::from_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn grpc_message_carries_system_properties() {
+        let mut message = Message::with_keys("TopicTest", "TagA", "k1 k2", b"hello");
+        message.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_SHARDING_KEY),
+            CheetahString::from_static_str("order-1"),
+        );
+        message.put_user_property(
+            CheetahString::from_static_str("color"),
+            CheetahString::from_static_str("red"),
+        );
+
+        let grpc_message = to_grpc_message(&message, false);
+        let system_properties = grpc_message.system_properties.unwrap();
+        assert_eq!(grpc_message.topic.unwrap().name, "TopicTest");
+        assert_eq!(grpc_message.body, b"hello");
+        assert_eq!(system_properties.tag.as_deref(), Some("TagA"));
+        assert_eq!(system_properties.keys, vec!["k1", "k2"]);
+        assert_eq!(system_properties.message_group.as_deref(), Some("order-1"));
+        assert_eq!(system_properties.message_type, v2::MessageType::Fifo as i32);
+        assert!(!system_properties.message_id.is_empty());
+        assert_eq!(
+            grpc_message.user_properties,
+            HashMap::from([("color".to_string(), "red".to_string())])
+        );
+
+        let message = Message::new("TopicTest", b"hello");
+        let system_properties = to_grpc_message(&message, true).system_properties.unwrap();
+        assert_eq!(
+            system_properties.message_type,
+            v2::MessageType::Transaction as i32
+        );
+    }
+
+    #[test]
+    fn received_message_restores_properties() {
+        let grpc_message = v2::Message {
+            topic: Some(v2::Resource::new("TopicTest")),
+            user_properties: HashMap::from([("color".to_string(), "red".to_string())]),
+            system_properties: Some(v2::SystemProperties {
+                tag: Some("TagA".to_string()),
+                keys: vec!["k1".to_string(), "k2".to_string()],
+                message_id: "0100AB".to_string(),
+                receipt_handle: Some("1|42|broker-a".to_string()),
+                queue_id: 1,
+                queue_offset: Some(42),
+                delivery_attempt: Some(3),
+                ..Default::default()
+            }),
+            body: b"hello".to_vec(),
+        };
+
+        let received = to_received_message(grpc_message).unwrap();
+        assert_eq!(received.message_id, "0100AB");
+        assert_eq!(received.receipt_handle, "1|42|broker-a");
+        assert_eq!(received.delivery_attempt, 3);
+        assert_eq!(received.topic(), "TopicTest");
+        let message = &received.message;
+        assert_eq!(message.get_tags().unwrap(), "TagA");
+        assert_eq!(message.get_keys().unwrap(), "k1 k2");
+        assert_eq!(message.msg_id(), "0100AB");
+        assert_eq!(message.queue_offset(), 42);
+        assert_eq!(message.reconsume_times(), 2);
+        assert_eq!(
+            message
+                .get_property(&CheetahString::from_static_str("color"))
+                .unwrap(),
+            "red"
+        );
+        assert_eq!(message.get_body().unwrap().as_ref(), b"hello");
+
+        let grpc_message = v2::Message {
+            system_properties: Some(v2::SystemProperties::default()),
+            ..Default::default()
+        };
+        assert!(to_received_message(grpc_message).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_remoting::grpc::v2;
+use tracing::warn;
+
+use crate::client_broker_err;
+use crate::grpc::grpc_client_config::GrpcClientConfig;
+use crate::grpc::grpc_message_converter;
+use crate::grpc::rpc_client;
+use crate::grpc::rpc_client::GrpcRpcClient;
+use crate::mq_client_err;
+use crate::producer::local_transaction_state::LocalTransactionState;
+use crate::producer::send_result::SendResult;
+use crate::producer::send_status::SendStatus;
+use crate::Result;
+
+/// Producer publishing messages through the `apache.rocketmq.v2` gRPC protocol of a proxy.
+pub struct GrpcProducer {
+    config: GrpcClientConfig,
+    topics: Vec<CheetahString>,
+    rpc_client: GrpcRpcClient,
+    max_body_size: Option<usize>,
+}
+
+impl GrpcProducer {
+    pub fn new(config: GrpcClientConfig) -> Self {
+        let rpc_client = GrpcRpcClient::new(config.endpoints.clone(), config.client_id.clone());
+        GrpcProducer {
+            config,
+            topics: Vec::new(),
+            rpc_client,
+            max_body_size: None,
+        }
+    }
+
+    /// Declares the topics the producer publishes to, so the proxy can check them on start.
+    pub fn set_topics(&mut self, topics: Vec<CheetahString>) {
+        self.topics = topics;
+    }
+
+    /// Reports the publishing settings to the proxy and applies the limits it answers with.
+    pub async fn start(&mut self) -> Result<()> {
+        for topic in &self.topics {
+            self.query_route(topic).await?;
+        }
+        let publishing = v2::Publishing {
+            topics: self
+                .topics
+                .iter()
+                .map(|topic| self.config.resource(topic))
+                .collect(),
+            ..Default::default()
+        };
+        let settings = self
+            .rpc_client
+            .sync_settings(
+                v2::ClientType::Producer,
+                v2::settings::PubSub::Publishing(publishing),
+                self.config.request_timeout,
+            )
+            .await?;
+        if let Some(v2::settings::PubSub::Publishing(publishing)) = settings {
+            self.max_body_size =
+                (publishing.max_body_size > 0).then_some(publishing.max_body_size as usize);
+        }
+        Ok(())
+    }
+
+    pub async fn send<M: MessageTrait>(&self, message: &M) -> Result<SendResult> {
+        self.send_message(message, false).await
+    }
+
+    /// Sends a half message. It is delivered to consumers once committed with
+    /// [`GrpcProducer::end_transaction`].
+    pub async fn send_in_transaction<M: MessageTrait>(&self, message: &M) -> Result<SendResult> {
+        self.send_message(message, true).await
+    }
+
+    pub async fn end_transaction(
+        &self,
+        topic: &CheetahString,
+        send_result: &SendResult,
+        state: LocalTransactionState,
+    ) -> Result<()> {
+        let resolution = match state {
+            LocalTransactionState::CommitMessage => v2::TransactionResolution::Commit as i32,
+            LocalTransactionState::RollbackMessage => v2::TransactionResolution::Rollback as i32,
+            LocalTransactionState::Unknown => return Ok(()),
+        };
+        let (Some(message_id), Some(transaction_id)) =
+            (&send_result.msg_id, &send_result.transaction_id)
+        else {
+            return mq_client_err!("send result is not of a transactional message");
+        };
+        let request = v2::EndTransactionRequest {
+            topic: Some(self.config.resource(topic)),
+            message_id: message_id.to_string(),
+            transaction_id: transaction_id.clone(),
+            resolution,
+            source: v2::TransactionSource::SourceClient as i32,
+            trace_context: String::new(),
+        };
+        let response = self
+            .rpc_client
+            .end_transaction(request, self.config.request_timeout)
+            .await?;
+        rpc_client::check_status(response.status.as_ref(), self.rpc_client.endpoint())?;
+        Ok(())
+    }
+
+    /// Tells the proxy the producer is going away.
+    pub async fn shutdown(&self) -> Result<()> {
+        let request = v2::NotifyClientTerminationRequest { group: None };
+        let response = self
+            .rpc_client
+            .notify_client_termination(request, self.config.request_timeout)
+            .await?;
+        rpc_client::check_status(response.status.as_ref(), self.rpc_client.endpoint())?;
+        Ok(())
+    }
+
+    async fn query_route(&self, topic: &CheetahString) -> Result<()> {
+        let request = v2::QueryRouteRequest {
+            topic: Some(self.config.resource(topic)),
+            endpoints: Some(rpc_client::to_endpoints(&self.config.endpoints)),
+        };
+        let response = self
+            .rpc_client
+            .query_route(request, self.config.request_timeout)
+            .await?;
+        rpc_client::check_status(response.status.as_ref(), self.rpc_client.endpoint())?;
+        Ok(())
+    }
+
+    async fn send_message<M: MessageTrait>(
+        &self,
+        message: &M,
+        transactional: bool,
+    ) -> Result<SendResult> {
+        let grpc_message = grpc_message_converter::to_grpc_message(message, transactional);
+        if let Some(max_body_size) = self.max_body_size {
+            if grpc_message.body.len() > max_body_size {
+                return mq_client_err!(
+                    v2::Code::MessageBodyTooLarge,
+                    "message body size {} exceeds the limit {}",
+                    grpc_message.body.len(),
+                    max_body_size
+                );
+            }
+        }
+        let request = v2::SendMessageRequest {
+            messages: vec![grpc_message],
+        };
+        let response = self
+            .rpc_client
+            .send_message(request, self.config.request_timeout)
+            .await?;
+        let Some(entry) = response.entries.into_iter().next() else {
+            rpc_client::check_status(response.status.as_ref(), self.rpc_client.endpoint())?;
+            return mq_client_err!("send response without result");
+        };
+        let status = entry.status.unwrap_or_default();
+        let send_status = match status.code() {
+            v2::Code::Ok => SendStatus::SendOk,
+            v2::Code::MasterPersistenceTimeout => SendStatus::FlushDiskTimeout,
+            v2::Code::SlavePersistenceTimeout => SendStatus::FlushSlaveTimeout,
+            v2::Code::HaNotAvailable => SendStatus::SlaveNotAvailable,
+            _ => {
+                return client_broker_err!(
+                    status.code,
+                    status.message,
+                    self.rpc_client.endpoint().to_string()
+                )
+            }
+        };
+        if send_status != SendStatus::SendOk {
+            warn!(
+                "message {} sent with status {:?}: {}",
+                entry.message_id, send_status, status.message
+            );
+        }
+        Ok(SendResult {
+            send_status,
+            msg_id: Some(CheetahString::from_string(entry.message_id)),
+            queue_offset: entry.offset.max(0) as u64,
+            transaction_id: (!entry.transaction_id.is_empty()).then_some(entry.transaction_id),
+            ..Default::default()
+        })
+    }
+}
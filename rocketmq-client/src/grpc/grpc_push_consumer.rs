@@ -0,0 +1,249 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::client_error::ClientErr;
+use crate::consumer::listener::consume_concurrently_context::ConsumeConcurrentlyContext;
+use crate::consumer::listener::consume_concurrently_status::ConsumeConcurrentlyStatus;
+use crate::consumer::listener::message_listener_concurrently::ArcBoxMessageListenerConcurrently;
+use crate::consumer::listener::message_listener_concurrently::MessageListenerConcurrently;
+use crate::consumer::message_selector::MessageSelector;
+use crate::grpc::grpc_client_config::GrpcClientConfig;
+use crate::grpc::grpc_simple_consumer::GrpcSimpleConsumer;
+use crate::grpc::received_message::ReceivedMessage;
+use crate::mq_client_err;
+use crate::Result;
+
+/// Pause before receiving again after a failed receive.
+const RECEIVE_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Delay before a message that failed to be consumed is delivered again, by delivery attempt.
+/// The last delay applies to every later attempt.
+const RECONSUME_DELAYS: [Duration; 18] = [
+    Duration::from_secs(1),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+    Duration::from_secs(2 * 60),
+    Duration::from_secs(3 * 60),
+    Duration::from_secs(4 * 60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(6 * 60),
+    Duration::from_secs(7 * 60),
+    Duration::from_secs(8 * 60),
+    Duration::from_secs(9 * 60),
+    Duration::from_secs(10 * 60),
+    Duration::from_secs(20 * 60),
+    Duration::from_secs(30 * 60),
+    Duration::from_secs(60 * 60),
+    Duration::from_secs(2 * 60 * 60),
+];
+
+/// Consumer that keeps receiving messages through the `apache.rocketmq.v2` gRPC protocol and
+/// hands them to a listener. A message is acknowledged once the listener consumed it
+/// successfully, otherwise its invisible duration is shortened to a backoff delay so it is
+/// delivered again after that delay.
+pub struct GrpcPushConsumer {
+    consumer: Arc<GrpcSimpleConsumer>,
+    listener: Option<ArcBoxMessageListenerConcurrently>,
+    consume_batch_size: i32,
+    invisible_duration: Duration,
+    running: Arc<AtomicBool>,
+    receive_task: Option<JoinHandle<()>>,
+}
+
+impl GrpcPushConsumer {
+    pub fn new(config: GrpcClientConfig, consumer_group: impl Into<CheetahString>) -> Self {
+        GrpcPushConsumer {
+            consumer: Arc::new(GrpcSimpleConsumer::new(config, consumer_group)),
+            listener: None,
+            consume_batch_size: 32,
+            invisible_duration: Duration::from_secs(30),
+            running: Arc::new(AtomicBool::new(false)),
+            receive_task: None,
+        }
+    }
+
+    pub fn set_consume_batch_size(&mut self, consume_batch_size: i32) {
+        self.consume_batch_size = consume_batch_size;
+    }
+
+    /// How long a message may be consumed before it is delivered again.
+    pub fn set_invisible_duration(&mut self, invisible_duration: Duration) {
+        self.invisible_duration = invisible_duration;
+    }
+
+    pub fn subscribe(
+        &mut self,
+        topic: impl Into<CheetahString>,
+        selector: MessageSelector,
+    ) -> std::result::Result<(), ClientErr> {
+        match Arc::get_mut(&mut self.consumer) {
+            Some(consumer) => {
+                consumer.subscribe(topic, selector);
+                Ok(())
+            }
+            None => Err(ClientErr::new(
+                "can not subscribe after the push consumer started",
+            )),
+        }
+    }
+
+    pub fn register_message_listener_concurrently<ML>(&mut self, message_listener: ML)
+    where
+        ML: MessageListenerConcurrently + Send + Sync + 'static,
+    {
+        self.listener = Some(Arc::new(Box::new(message_listener)));
+    }
+
+    pub async fn start(&mut self) -> Result<()> {
+        let Some(listener) = self.listener.clone() else {
+            return mq_client_err!("push consumer has no message listener");
+        };
+        if self.running.swap(true, Ordering::AcqRel) {
+            return Ok(());
+        }
+        if let Err(e) = self.consumer.start().await {
+            self.running.store(false, Ordering::Release);
+            return Err(e);
+        }
+        let consumer = self.consumer.clone();
+        let running = self.running.clone();
+        let consume_batch_size = self.consume_batch_size;
+        let invisible_duration = self.invisible_duration;
+        self.receive_task = Some(tokio::spawn(async move {
+            while running.load(Ordering::Acquire) {
+                let messages = match consumer
+                    .receive(consume_batch_size, invisible_duration)
+                    .await
+                {
+                    Ok(messages) => messages,
+                    Err(e) => {
+                        warn!(
+                            "consumer group {} failed to receive messages: {}",
+                            consumer.consumer_group(),
+                            e
+                        );
+                        tokio::time::sleep(RECEIVE_RETRY_INTERVAL).await;
+                        continue;
+                    }
+                };
+                if messages.is_empty() {
+                    continue;
+                }
+                // Listeners are synchronous and may block, so they run off the async workers.
+                let listener = listener.clone();
+                let consumed =
+                    tokio::task::spawn_blocking(move || consume_messages(&listener, messages))
+                        .await;
+                let consumed = match consumed {
+                    Ok(consumed) => consumed,
+                    Err(e) => {
+                        warn!("message listener panicked: {}", e);
+                        continue;
+                    }
+                };
+                for (message, success) in consumed {
+                    if success {
+                        if let Err(e) = consumer.ack(&message).await {
+                            warn!("failed to ack message {}: {}", message.message_id, e);
+                        }
+                    } else if let Err(e) = consumer
+                        .change_invisible_duration(
+                            &message,
+                            reconsume_delay(message.delivery_attempt),
+                        )
+                        .await
+                    {
+                        warn!(
+                            "failed to schedule message {} for reconsumption: {}",
+                            message.message_id, e
+                        );
+                    }
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    /// Stops receiving messages and tells the proxy the consumer is going away. Messages being
+    /// consumed are delivered again after their invisible duration.
+    pub async fn shutdown(&mut self) -> Result<()> {
+        if !self.running.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+        if let Some(receive_task) = self.receive_task.take() {
+            receive_task.abort();
+        }
+        self.consumer.shutdown().await
+    }
+}
+
+/// Hands the messages to the listener one by one, returning whether each was consumed.
+fn consume_messages(
+    listener: &ArcBoxMessageListenerConcurrently,
+    messages: Vec<ReceivedMessage>,
+) -> Vec<(ReceivedMessage, bool)> {
+    messages
+        .into_iter()
+        .map(|message| {
+            let context = ConsumeConcurrentlyContext::new(MessageQueue::from_parts(
+                message.topic().clone(),
+                message.message.broker_name(),
+                message.message.queue_id(),
+            ));
+            let success = match listener.consume_message(&[&message.message], &context) {
+                Ok(ConsumeConcurrentlyStatus::ConsumeSuccess) => true,
+                Ok(ConsumeConcurrentlyStatus::ReconsumeLater) => false,
+                Err(e) => {
+                    warn!("failed to consume message {}: {}", message.message_id, e);
+                    false
+                }
+            };
+            (message, success)
+        })
+        .collect()
+}
+
+/// Delay before a message is delivered again after its `delivery_attempt`-th delivery failed.
+fn reconsume_delay(delivery_attempt: i32) -> Duration {
+    let index = (delivery_attempt.max(1) as usize).min(RECONSUME_DELAYS.len()) - 1;
+    RECONSUME_DELAYS[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconsume_delay_backs_off_by_delivery_attempt() {
+        assert_eq!(reconsume_delay(0), Duration::from_secs(1));
+        assert_eq!(reconsume_delay(1), Duration::from_secs(1));
+        assert_eq!(reconsume_delay(4), Duration::from_secs(30));
+        assert_eq!(reconsume_delay(18), Duration::from_secs(2 * 60 * 60));
+        assert_eq!(reconsume_delay(100), Duration::from_secs(2 * 60 * 60));
+    }
+}
@@ -0,0 +1,400 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::common::filter::expression_type::ExpressionType;
+use rocketmq_remoting::grpc;
+use rocketmq_remoting::grpc::v2;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::consumer::message_selector::MessageSelector;
+use crate::grpc::grpc_client_config::GrpcClientConfig;
+use crate::grpc::grpc_message_converter;
+use crate::grpc::received_message::ReceivedMessage;
+use crate::grpc::rpc_client;
+use crate::grpc::rpc_client::GrpcRpcClient;
+use crate::mq_client_err;
+use crate::Result;
+
+/// How often the queues assigned to the consumer are queried again, so it follows queues being
+/// added or moved between brokers.
+const ASSIGNMENT_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Consumer that receives messages on demand through the `apache.rocketmq.v2` gRPC protocol and
+/// acknowledges each of them explicitly.
+pub struct GrpcSimpleConsumer {
+    config: GrpcClientConfig,
+    consumer_group: CheetahString,
+    rpc_client: Arc<GrpcRpcClient>,
+    subscriptions: BTreeMap<CheetahString, v2::FilterExpression>,
+    assignments: Arc<Mutex<HashMap<CheetahString, TopicAssignment>>>,
+    topic_index: AtomicUsize,
+    refresh_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// The queues of a topic assigned to the consumer, received from in turn.
+#[derive(Default)]
+struct TopicAssignment {
+    message_queues: Vec<v2::MessageQueue>,
+    next: usize,
+}
+
+impl TopicAssignment {
+    fn update(&mut self, message_queues: Vec<v2::MessageQueue>) {
+        self.message_queues = message_queues;
+    }
+
+    fn next_queue(&mut self) -> Option<v2::MessageQueue> {
+        if self.message_queues.is_empty() {
+            return None;
+        }
+        let message_queue = self.message_queues[self.next % self.message_queues.len()].clone();
+        self.next = self.next.wrapping_add(1);
+        Some(message_queue)
+    }
+}
+
+impl GrpcSimpleConsumer {
+    pub fn new(config: GrpcClientConfig, consumer_group: impl Into<CheetahString>) -> Self {
+        let rpc_client = GrpcRpcClient::new(config.endpoints.clone(), config.client_id.clone());
+        GrpcSimpleConsumer {
+            config,
+            consumer_group: consumer_group.into(),
+            rpc_client: Arc::new(rpc_client),
+            subscriptions: BTreeMap::new(),
+            assignments: Arc::new(Mutex::new(HashMap::new())),
+            topic_index: AtomicUsize::new(0),
+            refresh_task: Mutex::new(None),
+        }
+    }
+
+    pub fn consumer_group(&self) -> &CheetahString {
+        &self.consumer_group
+    }
+
+    pub fn config(&self) -> &GrpcClientConfig {
+        &self.config
+    }
+
+    pub fn subscribe(&mut self, topic: impl Into<CheetahString>, selector: MessageSelector) {
+        let filter_type = if selector.get_expression_type() == ExpressionType::SQL92 {
+            v2::FilterType::Sql
+        } else {
+            v2::FilterType::Tag
+        };
+        self.subscriptions.insert(
+            topic.into(),
+            v2::FilterExpression {
+                r#type: filter_type as i32,
+                expression: selector.get_expression().to_string(),
+            },
+        );
+    }
+
+    pub fn unsubscribe(&mut self, topic: &CheetahString) {
+        self.subscriptions.remove(topic);
+        self.assignments.lock().remove(topic);
+    }
+
+    /// Reports the subscriptions to the proxy and keeps the queues assigned to the consumer up to
+    /// date.
+    pub async fn start(&self) -> Result<()> {
+        if self.subscriptions.is_empty() {
+            return mq_client_err!("simple consumer has no subscription");
+        }
+        let subscription = v2::Subscription {
+            group: Some(self.group_resource()),
+            subscriptions: self
+                .subscriptions
+                .iter()
+                .map(|(topic, expression)| v2::SubscriptionEntry {
+                    topic: Some(self.config.resource(topic)),
+                    expression: Some(expression.clone()),
+                })
+                .collect(),
+            long_polling_timeout: Some(grpc::duration_from_millis(
+                self.config.long_polling_timeout.as_millis() as u64,
+            )),
+            ..Default::default()
+        };
+        self.rpc_client
+            .sync_settings(
+                v2::ClientType::SimpleConsumer,
+                v2::settings::PubSub::Subscription(subscription),
+                self.config.request_timeout,
+            )
+            .await?;
+        let refresh_task = tokio::spawn(refresh_assignments(
+            self.rpc_client.clone(),
+            self.config.clone(),
+            self.group_resource(),
+            self.subscriptions.keys().cloned().collect(),
+            self.assignments.clone(),
+        ));
+        if let Some(previous) = self.refresh_task.lock().replace(refresh_task) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Receives up to `max_messages` messages from the next queue of the next subscribed topic,
+    /// waiting up to the long polling timeout for messages to arrive. Every call long polls a
+    /// single queue, successive calls go round the assigned queues of each topic. The messages
+    /// stay invisible to the other consumers of the group for `invisible_duration`.
+    pub async fn receive(
+        &self,
+        max_messages: i32,
+        invisible_duration: Duration,
+    ) -> Result<Vec<ReceivedMessage>> {
+        if self.subscriptions.is_empty() {
+            return mq_client_err!("simple consumer has no subscription");
+        }
+        let topic_index = self.topic_index.fetch_add(1, Ordering::Relaxed);
+        let Some((topic, filter_expression)) = self
+            .subscriptions
+            .iter()
+            .nth(topic_index % self.subscriptions.len())
+        else {
+            return Ok(Vec::new());
+        };
+        let Some(message_queue) = self.next_message_queue(topic).await? else {
+            return Ok(Vec::new());
+        };
+        let broker_name = message_queue
+            .broker
+            .as_ref()
+            .map(|broker| CheetahString::from_slice(&broker.name))
+            .unwrap_or_default();
+
+        let request = v2::ReceiveMessageRequest {
+            group: Some(self.group_resource()),
+            message_queue: Some(message_queue),
+            filter_expression: Some(filter_expression.clone()),
+            batch_size: max_messages,
+            invisible_duration: Some(grpc::duration_from_millis(
+                invisible_duration.as_millis() as u64
+            )),
+            auto_renew: false,
+            long_polling_timeout: Some(grpc::duration_from_millis(
+                self.config.long_polling_timeout.as_millis() as u64,
+            )),
+        };
+        let responses = self
+            .rpc_client
+            .receive_message(
+                request,
+                self.config.request_timeout + self.config.long_polling_timeout,
+            )
+            .await?;
+
+        let mut messages = Vec::new();
+        for response in responses {
+            match response.content {
+                Some(v2::receive_message_response::Content::Status(status))
+                    if status.code() == v2::Code::MessageNotFound => {}
+                Some(v2::receive_message_response::Content::Status(status)) => {
+                    rpc_client::check_status(Some(&status), self.rpc_client.endpoint())?;
+                }
+                Some(v2::receive_message_response::Content::Message(message)) => {
+                    match grpc_message_converter::to_received_message(message) {
+                        Ok(mut message) => {
+                            message.message.set_broker_name(broker_name.clone());
+                            messages.push(message);
+                        }
+                        Err(e) => warn!("discard malformed message of topic {}: {}", topic, e),
+                    }
+                }
+                Some(v2::receive_message_response::Content::DeliveryTimestamp(_)) | None => {}
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Acknowledges a received message so it is not delivered again.
+    pub async fn ack(&self, message: &ReceivedMessage) -> Result<()> {
+        let request = v2::AckMessageRequest {
+            group: Some(self.group_resource()),
+            topic: Some(self.config.resource(message.topic())),
+            entries: vec![v2::AckMessageEntry {
+                message_id: message.message_id.to_string(),
+                receipt_handle: message.receipt_handle.to_string(),
+            }],
+        };
+        let response = self
+            .rpc_client
+            .ack_message(request, self.config.request_timeout)
+            .await?;
+        let status = match response.entries.first() {
+            Some(entry) => entry.status.as_ref(),
+            None => response.status.as_ref(),
+        };
+        rpc_client::check_status(status, self.rpc_client.endpoint())?;
+        Ok(())
+    }
+
+    /// Extends or shortens how long a received message stays invisible, returning the receipt
+    /// handle to use from now on.
+    pub async fn change_invisible_duration(
+        &self,
+        message: &ReceivedMessage,
+        invisible_duration: Duration,
+    ) -> Result<CheetahString> {
+        let request = v2::ChangeInvisibleDurationRequest {
+            group: Some(self.group_resource()),
+            topic: Some(self.config.resource(message.topic())),
+            receipt_handle: message.receipt_handle.to_string(),
+            invisible_duration: Some(grpc::duration_from_millis(
+                invisible_duration.as_millis() as u64
+            )),
+            message_id: message.message_id.to_string(),
+        };
+        let response = self
+            .rpc_client
+            .change_invisible_duration(request, self.config.request_timeout)
+            .await?;
+        rpc_client::check_status(response.status.as_ref(), self.rpc_client.endpoint())?;
+        Ok(CheetahString::from_string(response.receipt_handle))
+    }
+
+    /// Tells the proxy the consumer is going away.
+    pub async fn shutdown(&self) -> Result<()> {
+        if let Some(refresh_task) = self.refresh_task.lock().take() {
+            refresh_task.abort();
+        }
+        let request = v2::NotifyClientTerminationRequest {
+            group: Some(self.group_resource()),
+        };
+        let response = self
+            .rpc_client
+            .notify_client_termination(request, self.config.request_timeout)
+            .await?;
+        rpc_client::check_status(response.status.as_ref(), self.rpc_client.endpoint())?;
+        Ok(())
+    }
+
+    /// The assigned queue of `topic` to receive from next, querying the assignment if it is not
+    /// known yet.
+    async fn next_message_queue(&self, topic: &CheetahString) -> Result<Option<v2::MessageQueue>> {
+        if let Some(assignment) = self.assignments.lock().get_mut(topic) {
+            return Ok(assignment.next_queue());
+        }
+        let message_queues =
+            query_assignment(&self.rpc_client, &self.config, self.group_resource(), topic).await?;
+        let mut assignments = self.assignments.lock();
+        let assignment = assignments.entry(topic.clone()).or_default();
+        assignment.update(message_queues);
+        Ok(assignment.next_queue())
+    }
+
+    fn group_resource(&self) -> v2::Resource {
+        self.config.resource(&self.consumer_group)
+    }
+}
+
+impl Drop for GrpcSimpleConsumer {
+    fn drop(&mut self) {
+        if let Some(refresh_task) = self.refresh_task.get_mut().take() {
+            refresh_task.abort();
+        }
+    }
+}
+
+async fn query_assignment(
+    rpc_client: &GrpcRpcClient,
+    config: &GrpcClientConfig,
+    group: v2::Resource,
+    topic: &CheetahString,
+) -> Result<Vec<v2::MessageQueue>> {
+    let request = v2::QueryAssignmentRequest {
+        topic: Some(config.resource(topic)),
+        group: Some(group),
+        endpoints: Some(rpc_client::to_endpoints(&config.endpoints)),
+    };
+    let response = rpc_client
+        .query_assignment(request, config.request_timeout)
+        .await?;
+    rpc_client::check_status(response.status.as_ref(), rpc_client.endpoint())?;
+    Ok(response
+        .assignments
+        .into_iter()
+        .filter_map(|assignment| assignment.message_queue)
+        .collect())
+}
+
+/// Queries the assignments of every subscribed topic every [`ASSIGNMENT_REFRESH_INTERVAL`]. A
+/// failed query keeps the known assignment.
+async fn refresh_assignments(
+    rpc_client: Arc<GrpcRpcClient>,
+    config: GrpcClientConfig,
+    group: v2::Resource,
+    topics: Vec<CheetahString>,
+    assignments: Arc<Mutex<HashMap<CheetahString, TopicAssignment>>>,
+) {
+    let mut interval = tokio::time::interval(ASSIGNMENT_REFRESH_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        for topic in &topics {
+            match query_assignment(&rpc_client, &config, group.clone(), topic).await {
+                Ok(message_queues) => assignments
+                    .lock()
+                    .entry(topic.clone())
+                    .or_default()
+                    .update(message_queues),
+                Err(e) => warn!("query assignment of topic {} failed: {}", topic, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message_queue(id: i32) -> v2::MessageQueue {
+        v2::MessageQueue {
+            id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn assigned_queues_are_received_from_in_turn() {
+        let mut assignment = TopicAssignment::default();
+        assert!(assignment.next_queue().is_none());
+
+        assignment.update(vec![message_queue(0), message_queue(1)]);
+        let ids = (0..4)
+            .map(|_| assignment.next_queue().unwrap().id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![0, 1, 0, 1]);
+
+        // a refreshed assignment keeps going round from where it was
+        assignment.update(vec![message_queue(0), message_queue(1), message_queue(2)]);
+        assert_eq!(assignment.next_queue().unwrap().id, 1);
+        assert_eq!(assignment.next_queue().unwrap().id, 2);
+        assert_eq!(assignment.next_queue().unwrap().id, 0);
+    }
+}
@@ -0,0 +1,40 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_ext::MessageExt;
+
+/// A message handed out by a gRPC consumer. It stays invisible to other consumers of the group
+/// until it is acknowledged or its invisible duration elapses.
+#[derive(Debug, Clone)]
+pub struct ReceivedMessage {
+    pub message: MessageExt,
+    pub message_id: CheetahString,
+    /// Opaque handle the proxy needs to acknowledge the message.
+    pub receipt_handle: CheetahString,
+    /// How many times the message has been delivered, starting from 1.
+    pub delivery_attempt: i32,
+    pub invisible_duration: Option<Duration>,
+    pub message_group: Option<CheetahString>,
+}
+
+impl ReceivedMessage {
+    pub fn topic(&self) -> &CheetahString {
+        self.message.topic()
+    }
+}
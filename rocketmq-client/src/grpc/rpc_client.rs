@@ -0,0 +1,404 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::utils::network_util::NetworkUtil;
+use rocketmq_remoting::grpc;
+use rocketmq_remoting::grpc::v2;
+use rocketmq_remoting::grpc::v2::messaging_service_client::MessagingServiceClient;
+use tonic::metadata::MetadataValue;
+use tonic::transport::Channel;
+
+use crate::client_error::ClientErr;
+use crate::mq_client_err;
+use crate::request_timeout_err;
+use crate::Result;
+
+/// Client of the `apache.rocketmq.v2.MessagingService` of a proxy. The channel is created on
+/// first use and reconnects by itself after it fails.
+pub struct GrpcRpcClient {
+    endpoint: CheetahString,
+    client_id: CheetahString,
+    client: Mutex<Option<MessagingServiceClient<Channel>>>,
+}
+
+impl GrpcRpcClient {
+    pub fn new(endpoint: CheetahString, client_id: CheetahString) -> Self {
+        GrpcRpcClient {
+            endpoint,
+            client_id,
+            client: Mutex::new(None),
+        }
+    }
+
+    pub fn endpoint(&self) -> &CheetahString {
+        &self.endpoint
+    }
+
+    pub fn client_id(&self) -> &CheetahString {
+        &self.client_id
+    }
+
+    pub async fn query_route(
+        &self,
+        request: v2::QueryRouteRequest,
+        timeout: Duration,
+    ) -> Result<v2::QueryRouteResponse> {
+        let mut client = self.client()?;
+        let request = self.request(request, timeout);
+        with_timeout("QueryRoute", timeout, async move {
+            Ok(client.query_route(request).await?.into_inner())
+        })
+        .await
+    }
+
+    pub async fn send_message(
+        &self,
+        request: v2::SendMessageRequest,
+        timeout: Duration,
+    ) -> Result<v2::SendMessageResponse> {
+        let mut client = self.client()?;
+        let request = self.request(request, timeout);
+        with_timeout("SendMessage", timeout, async move {
+            Ok(client.send_message(request).await?.into_inner())
+        })
+        .await
+    }
+
+    pub async fn query_assignment(
+        &self,
+        request: v2::QueryAssignmentRequest,
+        timeout: Duration,
+    ) -> Result<v2::QueryAssignmentResponse> {
+        let mut client = self.client()?;
+        let request = self.request(request, timeout);
+        with_timeout("QueryAssignment", timeout, async move {
+            Ok(client.query_assignment(request).await?.into_inner())
+        })
+        .await
+    }
+
+    /// Receives messages of one queue, collecting every response the proxy streams back.
+    pub async fn receive_message(
+        &self,
+        request: v2::ReceiveMessageRequest,
+        timeout: Duration,
+    ) -> Result<Vec<v2::ReceiveMessageResponse>> {
+        let mut client = self.client()?;
+        let request = self.request(request, timeout);
+        with_timeout("ReceiveMessage", timeout, async move {
+            let mut stream = client.receive_message(request).await?.into_inner();
+            let mut responses = Vec::new();
+            while let Some(response) = stream.message().await? {
+                responses.push(response);
+            }
+            Ok(responses)
+        })
+        .await
+    }
+
+    pub async fn ack_message(
+        &self,
+        request: v2::AckMessageRequest,
+        timeout: Duration,
+    ) -> Result<v2::AckMessageResponse> {
+        let mut client = self.client()?;
+        let request = self.request(request, timeout);
+        with_timeout("AckMessage", timeout, async move {
+            Ok(client.ack_message(request).await?.into_inner())
+        })
+        .await
+    }
+
+    pub async fn end_transaction(
+        &self,
+        request: v2::EndTransactionRequest,
+        timeout: Duration,
+    ) -> Result<v2::EndTransactionResponse> {
+        let mut client = self.client()?;
+        let request = self.request(request, timeout);
+        with_timeout("EndTransaction", timeout, async move {
+            Ok(client.end_transaction(request).await?.into_inner())
+        })
+        .await
+    }
+
+    pub async fn notify_client_termination(
+        &self,
+        request: v2::NotifyClientTerminationRequest,
+        timeout: Duration,
+    ) -> Result<v2::NotifyClientTerminationResponse> {
+        let mut client = self.client()?;
+        let request = self.request(request, timeout);
+        with_timeout("NotifyClientTermination", timeout, async move {
+            Ok(client
+                .notify_client_termination(request)
+                .await?
+                .into_inner())
+        })
+        .await
+    }
+
+    pub async fn change_invisible_duration(
+        &self,
+        request: v2::ChangeInvisibleDurationRequest,
+        timeout: Duration,
+    ) -> Result<v2::ChangeInvisibleDurationResponse> {
+        let mut client = self.client()?;
+        let request = self.request(request, timeout);
+        with_timeout("ChangeInvisibleDuration", timeout, async move {
+            Ok(client
+                .change_invisible_duration(request)
+                .await?
+                .into_inner())
+        })
+        .await
+    }
+
+    /// Opens a telemetry stream, sends `command` and waits for the first command the proxy
+    /// writes back.
+    pub async fn telemetry(
+        &self,
+        command: v2::TelemetryCommand,
+        timeout: Duration,
+    ) -> Result<v2::TelemetryCommand> {
+        let mut client = self.client()?;
+        let request = self.request(futures::stream::iter(vec![command]), timeout);
+        let reply = with_timeout("Telemetry", timeout, async move {
+            client
+                .telemetry(request)
+                .await?
+                .into_inner()
+                .message()
+                .await
+        })
+        .await?;
+        match reply {
+            Some(reply) => Ok(reply),
+            None => mq_client_err!("telemetry stream closed without a reply"),
+        }
+    }
+
+    /// Reports the settings of a client over a telemetry stream and returns the publishing or
+    /// subscription settings the proxy answers with.
+    pub async fn sync_settings(
+        &self,
+        client_type: v2::ClientType,
+        pub_sub: v2::settings::PubSub,
+        timeout: Duration,
+    ) -> Result<Option<v2::settings::PubSub>> {
+        let settings = v2::Settings {
+            client_type: Some(client_type as i32),
+            access_point: Some(to_endpoints(&self.endpoint)),
+            request_timeout: Some(grpc::duration_from_millis(timeout.as_millis() as u64)),
+            pub_sub: Some(pub_sub),
+            user_agent: Some(user_agent()),
+            ..Default::default()
+        };
+        let command = v2::TelemetryCommand {
+            status: None,
+            command: Some(v2::telemetry_command::Command::Settings(settings)),
+        };
+        let reply = self.telemetry(command, timeout).await?;
+        check_status(reply.status.as_ref(), &self.endpoint)?;
+        match reply.command {
+            Some(v2::telemetry_command::Command::Settings(settings)) => Ok(settings.pub_sub),
+            _ => Ok(None),
+        }
+    }
+
+    fn client(&self) -> std::result::Result<MessagingServiceClient<Channel>, ClientErr> {
+        let mut client = self.client.lock();
+        if let Some(client) = client.as_ref() {
+            return Ok(client.clone());
+        }
+        let channel = Channel::from_shared(format!("http://{}", self.endpoint))
+            .map_err(|_| ClientErr::new(format!("invalid proxy endpoint {}", self.endpoint)))?
+            .tcp_nodelay(true)
+            .connect_lazy();
+        Ok(client.insert(MessagingServiceClient::new(channel)).clone())
+    }
+
+    /// Wraps a message into a request carrying the headers identifying the client.
+    fn request<T>(&self, message: T, timeout: Duration) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        request.set_timeout(timeout);
+        let metadata = request.metadata_mut();
+        if let Ok(client_id) = MetadataValue::try_from(self.client_id.as_str()) {
+            metadata.insert("x-mq-client-id", client_id);
+        }
+        metadata.insert("x-mq-language", MetadataValue::from_static("RUST"));
+        metadata.insert("x-mq-protocol", MetadataValue::from_static("v2"));
+        metadata.insert(
+            "x-mq-client-version",
+            MetadataValue::from_static(env!("CARGO_PKG_VERSION")),
+        );
+        request
+    }
+}
+
+async fn with_timeout<T>(
+    method: &str,
+    timeout: Duration,
+    call: impl std::future::Future<Output = std::result::Result<T, tonic::Status>>,
+) -> Result<T> {
+    match tokio::time::timeout(timeout, call).await {
+        Ok(Ok(response)) => Ok(response),
+        Ok(Err(status)) => mq_client_err!(
+            status.code() as i32,
+            format!(
+                "gRPC method {} failed with status {}: {}",
+                method,
+                status.code() as i32,
+                status.message()
+            )
+        ),
+        Err(_) => request_timeout_err!(format!(
+            "gRPC method {} timed out after {:?}",
+            method, timeout
+        )),
+    }
+}
+
+/// Fails with the code and message of `status` unless it is `OK`.
+pub(crate) fn check_status(
+    status: Option<&v2::Status>,
+    endpoint: &CheetahString,
+) -> std::result::Result<(), ClientErr> {
+    match status {
+        Some(status) if status.code == v2::Code::Ok as i32 => Ok(()),
+        Some(status) => Err(ClientErr::new_with_code(
+            status.code,
+            format!("{} BROKER: {}", status.message, endpoint),
+        )),
+        None => Err(ClientErr::new_with_code(
+            v2::Code::InternalServerError as i32,
+            format!("response without status BROKER: {}", endpoint),
+        )),
+    }
+}
+
+/// Endpoints of the `host:port` address a client connects to.
+pub(crate) fn to_endpoints(endpoint: &str) -> v2::Endpoints {
+    let (host, port) = endpoint
+        .rsplit_once(':')
+        .map(|(host, port)| (host, port.parse().unwrap_or_default()))
+        .unwrap_or((endpoint, 0));
+    let scheme = match host.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(_)) => v2::AddressScheme::IPv4,
+        Ok(std::net::IpAddr::V6(_)) => v2::AddressScheme::IPv6,
+        Err(_) => v2::AddressScheme::DomainName,
+    };
+    v2::Endpoints {
+        scheme: scheme as i32,
+        addresses: vec![v2::Address {
+            host: host.to_string(),
+            port,
+        }],
+    }
+}
+
+pub(crate) fn user_agent() -> v2::Ua {
+    v2::Ua {
+        language: v2::Language::Rust as i32,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        platform: std::env::consts::OS.to_string(),
+        hostname: NetworkUtil::get_local_address().unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::grpc::v2::messaging_service_server;
+    use rocketmq_remoting::grpc::v2::messaging_service_server::MessagingServiceServer;
+    use tokio::net::TcpListener;
+    use tonic::transport::server::TcpIncoming;
+    use tonic::transport::Server;
+
+    use super::*;
+
+    /// Serves `QueryRoute` with an empty route, every other method is unimplemented.
+    struct MockMessagingService;
+
+    #[tonic::async_trait]
+    impl messaging_service_server::MessagingService for MockMessagingService {
+        async fn query_route(
+            &self,
+            request: tonic::Request<v2::QueryRouteRequest>,
+        ) -> std::result::Result<tonic::Response<v2::QueryRouteResponse>, tonic::Status> {
+            assert_eq!(request.metadata().get("x-mq-client-id").unwrap(), "c1");
+            let request = request.into_inner();
+            Ok(tonic::Response::new(v2::QueryRouteResponse {
+                status: Some(v2::Status::new(
+                    v2::Code::TopicNotFound,
+                    request.topic.unwrap().name,
+                )),
+                message_queues: vec![],
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn unary_call_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = CheetahString::from_string(listener.local_addr().unwrap().to_string());
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(MessagingServiceServer::new(MockMessagingService))
+                .serve_with_incoming(incoming),
+        );
+        let client = GrpcRpcClient::new(endpoint.clone(), CheetahString::from_static_str("c1"));
+
+        let request = v2::QueryRouteRequest {
+            topic: Some(v2::Resource::new("TopicTest")),
+            endpoints: Some(to_endpoints(&endpoint)),
+        };
+        let response = client
+            .query_route(request, Duration::from_secs(3))
+            .await
+            .unwrap();
+        let status = response.status.unwrap();
+        assert_eq!(status.code, v2::Code::TopicNotFound as i32);
+        assert_eq!(status.message, "TopicTest");
+        assert!(check_status(Some(&status), &endpoint).is_err());
+
+        let result = client
+            .ack_message(v2::AckMessageRequest::default(), Duration::from_secs(3))
+            .await;
+        let error = result.err().unwrap().to_string();
+        assert!(
+            error.contains(&format!("status {}", tonic::Code::Unimplemented as i32)),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn endpoints_of_address() {
+        let endpoints = to_endpoints("127.0.0.1:8081");
+        assert_eq!(endpoints.scheme, v2::AddressScheme::IPv4 as i32);
+        assert_eq!(endpoints.addresses[0].host, "127.0.0.1");
+        assert_eq!(endpoints.addresses[0].port, 8081);
+        assert_eq!(
+            to_endpoints("proxy.local:8081").scheme,
+            v2::AddressScheme::DomainName as i32
+        );
+    }
+}
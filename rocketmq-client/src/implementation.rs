@@ -19,5 +19,5 @@ pub(crate) mod client_remoting_processor;
 pub(crate) mod communication_mode;
 pub(crate) mod find_broker_result;
 pub(crate) mod mq_admin_impl;
-pub(crate) mod mq_client_api_impl;
-pub(crate) mod mq_client_manager;
+pub mod mq_client_api_impl;
+pub mod mq_client_manager;
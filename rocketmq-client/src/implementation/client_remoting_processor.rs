@@ -30,12 +30,16 @@ use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::body::reset_offset_body::ResetOffsetBody;
 use rocketmq_remoting::protocol::header::check_transaction_state_request_header::CheckTransactionStateRequestHeader;
 use rocketmq_remoting::protocol::header::consume_message_directly_result_request_header::ConsumeMessageDirectlyResultRequestHeader;
+use rocketmq_remoting::protocol::header::get_consumer_running_info_request_header::GetConsumerRunningInfoRequestHeader;
 use rocketmq_remoting::protocol::header::notify_consumer_ids_changed_request_header::NotifyConsumerIdsChangedRequestHeader;
 use rocketmq_remoting::protocol::header::reply_message_request_header::ReplyMessageRequestHeader;
+use rocketmq_remoting::protocol::header::reset_offset_request_header::ResetOffsetRequestHeader;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingDeserializable;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::remoting_error::RemotingError::RemotingCommandError;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
@@ -73,14 +77,12 @@ impl RequestProcessor for ClientRemotingProcessor {
             RequestCode::CheckTransactionState => {
                 self.check_transaction_state(channel, ctx, request).await
             }
-            RequestCode::ResetConsumerClientOffset => {
-                unimplemented!("ResetConsumerClientOffset")
-            }
+            RequestCode::ResetConsumerClientOffset => self.reset_offset(channel, ctx, request),
             RequestCode::GetConsumerStatusFromClient => {
                 unimplemented!("GetConsumerStatusFromClient")
             }
             RequestCode::GetConsumerRunningInfo => {
-                unimplemented!("GetConsumerRunningInfo")
+                self.get_consumer_running_info(channel, ctx, request).await
             }
             RequestCode::ConsumeMessageDirectly => {
                 self.consume_message_directly(channel, ctx, request).await
@@ -218,6 +220,35 @@ impl ClientRemotingProcessor {
         Ok(None)
     }
 
+    fn reset_offset(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        request: RemotingCommand,
+    ) -> Result<Option<RemotingCommand>> {
+        let request_header = request.decode_command_custom_header::<ResetOffsetRequestHeader>()?;
+        info!(
+            "invoke reset offset operation from broker. brokerAddr={}, topic={}, group={}, \
+             timestamp={}",
+            channel.remote_address(),
+            request_header.topic,
+            request_header.group,
+            request_header.timestamp
+        );
+        let offset_table = match request.body() {
+            Some(body) => ResetOffsetBody::decode(body)?.offset_table,
+            None => Default::default(),
+        };
+        // seeking waits for in-flight messages to drain, keep it off the connection task
+        let client_instance = self.client_instance.clone();
+        tokio::spawn(async move {
+            client_instance
+                .reset_offset(&request_header.topic, &request_header.group, offset_table)
+                .await;
+        });
+        Ok(None)
+    }
+
     async fn check_transaction_state(
         &mut self,
         channel: Channel,
@@ -275,6 +306,39 @@ impl ClientRemotingProcessor {
         Ok(None)
     }
 
+    async fn get_consumer_running_info(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        request: RemotingCommand,
+    ) -> Result<Option<RemotingCommand>> {
+        let request_header =
+            request.decode_command_custom_header::<GetConsumerRunningInfoRequestHeader>()?;
+        let consumer_running_info = self
+            .client_instance
+            .consumer_running_info(&request_header.consumer_group)
+            .await;
+        match consumer_running_info {
+            Some(consumer_running_info) => {
+                // Thread stacks can not be dumped from a Rust process, so `jstack_enable` is
+                // accepted but never fills the `jstack` field.
+                let body = consumer_running_info
+                    .encode()
+                    .map_err(|_| RemotingCommandError("encode result failed".to_string()))?;
+                Ok(Some(
+                    RemotingCommand::create_response_command().set_body(body),
+                ))
+            }
+            None => Ok(Some(
+                RemotingCommand::create_response_command_with_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "The Consumer Group <{}> not exist in this consumer",
+                        request_header.consumer_group
+                    )),
+            )),
+        }
+    }
+
     async fn consume_message_directly(
         &mut self,
         channel: Channel,
@@ -14,7 +14,11 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use cheetah_string::CheetahString;
+use rocketmq_common::common::boundary_type::BoundaryType;
+use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::MessageDecoder;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
 use rocketmq_rust::ArcMut;
 
@@ -93,7 +97,68 @@ impl MQAdminImpl {
         ))
     }
 
+    /// Queues a consumer of `topic` may be assigned, with the topic as stored on the brokers.
+    pub async fn fetch_subscribe_message_queues(
+        &mut self,
+        topic: &str,
+        mq_client_api_impl: ArcMut<MQClientAPIImpl>,
+    ) -> Result<Vec<MessageQueue>> {
+        let topic_route_data = mq_client_api_impl
+            .get_topic_route_info_from_name_server_detail(topic, self.timeout_millis, true)
+            .await?;
+        if let Some(topic_route_data) = topic_route_data {
+            let message_queues =
+                mq_client_instance::topic_route_data2topic_subscribe_info(topic, &topic_route_data);
+            if !message_queues.is_empty() {
+                return Ok(message_queues.into_iter().collect());
+            }
+            return mq_client_err!(format!(
+                "Can not find Message Queue for this topic, {} Namesrv return empty",
+                topic
+            ));
+        }
+        mq_client_err!(format!(
+            "Unknow why, Can not find Message Queue for this topic, {}",
+            topic
+        ))
+    }
+
     pub async fn max_offset(&mut self, mq: &MessageQueue) -> Result<i64> {
+        let broker_addr = self.find_broker_addr(mq).await?;
+        let client = self.client.as_mut().expect("client is None");
+        client
+            .mq_client_api_impl
+            .as_mut()
+            .expect("mq_client_api_impl is None")
+            .get_max_offset(&broker_addr, mq, self.timeout_millis)
+            .await
+    }
+
+    pub async fn min_offset(&mut self, mq: &MessageQueue) -> Result<i64> {
+        let broker_addr = self.find_broker_addr(mq).await?;
+        let client = self.client.as_mut().expect("client is None");
+        client
+            .mq_client_api_impl
+            .as_mut()
+            .expect("mq_client_api_impl is None")
+            .get_min_offset(&broker_addr, mq, self.timeout_millis)
+            .await
+    }
+
+    pub async fn earliest_msg_store_time(&mut self, mq: &MessageQueue) -> Result<i64> {
+        let broker_addr = self.find_broker_addr(mq).await?;
+        let client = self.client.as_mut().expect("client is None");
+        client
+            .mq_client_api_impl
+            .as_mut()
+            .expect("mq_client_api_impl is None")
+            .get_earliest_msg_storetime(&broker_addr, mq, self.timeout_millis)
+            .await
+    }
+
+    /// Looks up the broker address of the queue's broker, refreshing the topic route once when
+    /// the broker is not known yet.
+    async fn find_broker_addr(&mut self, mq: &MessageQueue) -> Result<CheetahString> {
         let client = self.client.as_mut().expect("client is None");
         let broker_name = client.get_broker_name_from_message_queue(mq).await;
         let mut broker_addr = client
@@ -108,19 +173,74 @@ impl MQAdminImpl {
                 .find_broker_address_in_publish(broker_name.as_ref())
                 .await;
         }
-        if let Some(ref broker_addr) = broker_addr {
-            let offset = client
-                .mq_client_api_impl
-                .as_mut()
-                .expect("mq_client_api_impl is None")
-                .get_max_offset(broker_addr, mq, self.timeout_millis)
-                .await?;
-            return Ok(offset);
+        match broker_addr {
+            Some(broker_addr) => Ok(broker_addr),
+            None => mq_client_err!(format!("The broker[{}] not exist", mq.get_broker_name())),
         }
+    }
 
-        unimplemented!("max_offset")
+    /// Reads a single message straight from the commit log of the broker that stored it. The
+    /// store host and physical offset are both decoded from the offset message id `msg_id`.
+    pub async fn view_message(
+        &mut self,
+        topic: &CheetahString,
+        msg_id: &CheetahString,
+    ) -> Result<MessageExt> {
+        let Some(message_id) = MessageDecoder::try_decode_message_id(msg_id) else {
+            return mq_client_err!(format!("{} is not an offset message id", msg_id));
+        };
+        let client = self.client.as_mut().expect("client is None");
+        let addr = CheetahString::from_string(message_id.address.to_string());
+        client
+            .mq_client_api_impl
+            .as_mut()
+            .expect("mq_client_api_impl is None")
+            .view_message(&addr, topic, message_id.offset, self.timeout_millis)
+            .await
     }
+
     pub async fn search_offset(&mut self, mq: &MessageQueue, timestamp: u64) -> Result<i64> {
-        unimplemented!("max_offset")
+        self.search_offset_with_boundary(mq, timestamp, BoundaryType::Lower)
+            .await
+    }
+
+    pub async fn search_offset_with_boundary(
+        &mut self,
+        mq: &MessageQueue,
+        timestamp: u64,
+        boundary_type: BoundaryType,
+    ) -> Result<i64> {
+        let broker_addr = self.find_broker_addr(mq).await?;
+        let client = self.client.as_mut().expect("client is None");
+        client
+            .mq_client_api_impl
+            .as_mut()
+            .expect("mq_client_api_impl is None")
+            .search_offset(
+                &broker_addr,
+                mq,
+                timestamp as i64,
+                boundary_type,
+                self.timeout_millis,
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn view_message_rejects_ids_that_are_not_offset_message_ids() {
+        let mut admin_impl = MQAdminImpl::new();
+        let msg_id = CheetahString::from_static_str("7F0000011F90");
+        let result = admin_impl
+            .view_message(&CheetahString::from_static_str("topic"), &msg_id)
+            .await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("7F0000011F90 is not an offset message id"));
     }
 }
@@ -14,6 +14,7 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
@@ -21,8 +22,12 @@ use std::time::Instant;
 
 use cheetah_string::CheetahString;
 use lazy_static::lazy_static;
+use rocketmq_common::common::attribute::attribute_parser::AttributeParser;
+use rocketmq_common::common::boundary_type::BoundaryType;
+use rocketmq_common::common::config::TopicConfig;
 use rocketmq_common::common::message::message_batch::MessageBatch;
 use rocketmq_common::common::message::message_client_id_setter::MessageClientIDSetter;
+use rocketmq_common::common::message::message_decoder;
 use rocketmq_common::common::message::message_enum::MessageRequestMode;
 use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
@@ -40,38 +45,76 @@ use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
 use rocketmq_remoting::clients::RemotingClient;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
+use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
 use rocketmq_remoting::protocol::body::check_client_request_body::CheckClientRequestBody;
+use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
+use rocketmq_remoting::protocol::body::consumer_connection::ConsumerConnection;
+use rocketmq_remoting::protocol::body::consumer_offset_serialize_wrapper::ConsumerOffsetSerializeWrapper;
+use rocketmq_remoting::protocol::body::consumer_running_info::ConsumerRunningInfo;
 use rocketmq_remoting::protocol::body::get_consumer_listby_group_response_body::GetConsumerListByGroupResponseBody;
+use rocketmq_remoting::protocol::body::kv_table::KVTable;
+use rocketmq_remoting::protocol::body::pop_stats::PopStats;
+use rocketmq_remoting::protocol::body::producer_connection::ProducerConnection;
 use rocketmq_remoting::protocol::body::query_assignment_request_body::QueryAssignmentRequestBody;
 use rocketmq_remoting::protocol::body::query_assignment_response_body::QueryAssignmentResponseBody;
 use rocketmq_remoting::protocol::body::request::lock_batch_request_body::LockBatchRequestBody;
+use rocketmq_remoting::protocol::body::reset_offset_body::ResetOffsetBody;
 use rocketmq_remoting::protocol::body::response::lock_batch_response_body::LockBatchResponseBody;
 use rocketmq_remoting::protocol::body::set_message_request_mode_request_body::SetMessageRequestModeRequestBody;
+use rocketmq_remoting::protocol::body::subscription_group_wrapper::SubscriptionGroupWrapper;
+use rocketmq_remoting::protocol::body::topic::topic_list::TopicList;
+use rocketmq_remoting::protocol::body::topic_info_wrapper::TopicConfigSerializeWrapper;
 use rocketmq_remoting::protocol::body::unlock_batch_request_body::UnlockBatchRequestBody;
 use rocketmq_remoting::protocol::header::client_request_header::GetRouteInfoRequestHeader;
+use rocketmq_remoting::protocol::header::clone_group_offset_request_header::CloneGroupOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::consume_message_directly_result_request_header::ConsumeMessageDirectlyResultRequestHeader;
 use rocketmq_remoting::protocol::header::consumer_send_msg_back_request_header::ConsumerSendMsgBackRequestHeader;
+use rocketmq_remoting::protocol::header::create_topic_request_header::CreateTopicRequestHeader;
+use rocketmq_remoting::protocol::header::delete_topic_request_header::DeleteTopicRequestHeader;
 use rocketmq_remoting::protocol::header::end_transaction_request_header::EndTransactionRequestHeader;
+use rocketmq_remoting::protocol::header::get_consume_stats_request_header::GetConsumeStatsRequestHeader;
+use rocketmq_remoting::protocol::header::get_consumer_connection_list_request_header::GetConsumerConnectionListRequestHeader;
 use rocketmq_remoting::protocol::header::get_consumer_listby_group_request_header::GetConsumerListByGroupRequestHeader;
+use rocketmq_remoting::protocol::header::get_consumer_running_info_request_header::GetConsumerRunningInfoRequestHeader;
+use rocketmq_remoting::protocol::header::get_earliest_msg_storetime_request_header::GetEarliestMsgStoretimeRequestHeader;
+use rocketmq_remoting::protocol::header::get_earliest_msg_storetime_response_header::GetEarliestMsgStoretimeResponseHeader;
 use rocketmq_remoting::protocol::header::get_max_offset_request_header::GetMaxOffsetRequestHeader;
 use rocketmq_remoting::protocol::header::get_max_offset_response_header::GetMaxOffsetResponseHeader;
+use rocketmq_remoting::protocol::header::get_min_offset_request_header::GetMinOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::get_min_offset_response_header::GetMinOffsetResponseHeader;
+use rocketmq_remoting::protocol::header::get_pop_stats_request_header::GetPopStatsRequestHeader;
+use rocketmq_remoting::protocol::header::get_producer_connection_list_request_header::GetProducerConnectionListRequestHeader;
 use rocketmq_remoting::protocol::header::heartbeat_request_header::HeartbeatRequestHeader;
 use rocketmq_remoting::protocol::header::lock_batch_mq_request_header::LockBatchMqRequestHeader;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header::SendMessageRequestHeader;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header_v2::SendMessageRequestHeaderV2;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_response_header::SendMessageResponseHeader;
+use rocketmq_remoting::protocol::header::namesrv::perm_broker_header::AddWritePermOfBrokerRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::perm_broker_header::AddWritePermOfBrokerResponseHeader;
+use rocketmq_remoting::protocol::header::namesrv::perm_broker_header::WipeWritePermOfBrokerRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::perm_broker_header::WipeWritePermOfBrokerResponseHeader;
+use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::DeleteTopicFromNamesrvRequestHeader;
 use rocketmq_remoting::protocol::header::pull_message_request_header::PullMessageRequestHeader;
 use rocketmq_remoting::protocol::header::pull_message_response_header::PullMessageResponseHeader;
 use rocketmq_remoting::protocol::header::query_consumer_offset_request_header::QueryConsumerOffsetRequestHeader;
 use rocketmq_remoting::protocol::header::query_consumer_offset_response_header::QueryConsumerOffsetResponseHeader;
+use rocketmq_remoting::protocol::header::query_message_by_offset_range_request_header::QueryMessageByOffsetRangeRequestHeader;
+use rocketmq_remoting::protocol::header::query_message_by_offset_range_response_header::QueryMessageByOffsetRangeResponseHeader;
+use rocketmq_remoting::protocol::header::reset_offset_request_header::ResetOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::search_offset_request_header::SearchOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::search_offset_response_header::SearchOffsetResponseHeader;
 use rocketmq_remoting::protocol::header::unlock_batch_mq_request_header::UnlockBatchMqRequestHeader;
 use rocketmq_remoting::protocol::header::unregister_client_request_header::UnregisterClientRequestHeader;
 use rocketmq_remoting::protocol::header::update_consumer_offset_header::UpdateConsumerOffsetRequestHeader;
+use rocketmq_remoting::protocol::header::view_message_request_header::ViewMessageRequestHeader;
 use rocketmq_remoting::protocol::heartbeat::heartbeat_data::HeartbeatData;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
 use rocketmq_remoting::protocol::RemotingDeserializable;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::remoting::RemotingService;
@@ -85,6 +128,7 @@ use tracing::warn;
 
 use crate::base::client_config::ClientConfig;
 use crate::client_broker_err;
+use crate::client_error::ClientErr;
 use crate::client_error::MQBrokerErr;
 use crate::client_error::MQClientError;
 use crate::client_error::MQClientError::MQClientBrokerError;
@@ -156,6 +200,10 @@ impl MQClientAPIImpl {
         self.remoting_client.start(client).await;
     }
 
+    pub fn shutdown(&mut self) {
+        self.remoting_client.shutdown();
+    }
+
     pub async fn fetch_name_server_addr(&mut self) -> Option<String> {
         let addrs = self.top_addressing.fetch_ns_addr();
         if addrs.is_some() && !addrs.as_ref().unwrap().is_empty() {
@@ -222,10 +270,17 @@ impl MQClientAPIImpl {
             accept_standard_json_only: None,
             topic_request_header: None,
         };
-        let request = RemotingCommand::create_request_command(
+        let mut request = RemotingCommand::create_request_command(
             RequestCode::GetRouteinfoByTopic,
             request_header,
         );
+        if self.client_config.zone_mode {
+            if let Some(zone_name) = &self.client_config.zone_name {
+                request
+                    .add_ext_field(mix_all::ZONE_MODE, "true")
+                    .add_ext_field(mix_all::ZONE_NAME, zone_name.clone());
+            }
+        }
         let response = self
             .remoting_client
             .invoke_async(None, request, timeout_millis)
@@ -760,13 +815,13 @@ impl MQClientAPIImpl {
             .await?;
         match ResponseCode::from(response.code()) {
             ResponseCode::Success => {
-                let body = response.body();
                 if let Some(body) = response.body() {
                     return match GetConsumerListByGroupResponseBody::decode(body) {
                         Ok(value) => Ok(value.consumer_id_list),
-                        Err(e) => mq_client_err!(response
-                            .remark()
-                            .map_or("".to_string(), |s| s.to_string())),
+                        Err(e) => mq_client_err!(format!(
+                            "decode GetConsumerListByGroupResponseBody failed, group={}, error={}",
+                            consumer_group, e
+                        )),
                     };
                 }
             }
@@ -1238,6 +1293,149 @@ impl MQClientAPIImpl {
         )
     }
 
+    pub async fn get_min_offset(
+        &mut self,
+        addr: &str,
+        message_queue: &MessageQueue,
+        timeout_millis: u64,
+    ) -> Result<i64> {
+        let request_header = GetMinOffsetRequestHeader {
+            topic: CheetahString::from_slice(message_queue.get_topic()),
+            queue_id: message_queue.get_queue_id(),
+            topic_request_header: Some(TopicRequestHeader {
+                rpc_request_header: Some(RpcRequestHeader {
+                    broker_name: Some(CheetahString::from_slice(message_queue.get_broker_name())),
+                    ..Default::default()
+                }),
+                lo: None,
+            }),
+        };
+
+        let request =
+            RemotingCommand::create_request_command(RequestCode::GetMinOffset, request_header);
+
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            let response_header = response
+                .decode_command_custom_header::<GetMinOffsetResponseHeader>()
+                .expect("decode error");
+            return Ok(response_header.offset);
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn search_offset(
+        &mut self,
+        addr: &str,
+        message_queue: &MessageQueue,
+        timestamp: i64,
+        boundary_type: BoundaryType,
+        timeout_millis: u64,
+    ) -> Result<i64> {
+        let request_header = SearchOffsetRequestHeader {
+            topic: CheetahString::from_slice(message_queue.get_topic()),
+            queue_id: message_queue.get_queue_id(),
+            timestamp,
+            boundary_type: Some(CheetahString::from_static_str(boundary_type.get_name())),
+            topic_request_header: Some(TopicRequestHeader {
+                rpc_request_header: Some(RpcRequestHeader {
+                    broker_name: Some(CheetahString::from_slice(message_queue.get_broker_name())),
+                    ..Default::default()
+                }),
+                lo: None,
+            }),
+        };
+
+        let request = RemotingCommand::create_request_command(
+            RequestCode::SearchOffsetByTimestamp,
+            request_header,
+        );
+
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            let response_header = response
+                .decode_command_custom_header::<SearchOffsetResponseHeader>()
+                .expect("decode error");
+            return Ok(response_header.offset);
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_earliest_msg_storetime(
+        &mut self,
+        addr: &str,
+        message_queue: &MessageQueue,
+        timeout_millis: u64,
+    ) -> Result<i64> {
+        let request_header = GetEarliestMsgStoretimeRequestHeader {
+            topic: CheetahString::from_slice(message_queue.get_topic()),
+            queue_id: message_queue.get_queue_id(),
+            topic_request_header: Some(TopicRequestHeader {
+                rpc_request_header: Some(RpcRequestHeader {
+                    broker_name: Some(CheetahString::from_slice(message_queue.get_broker_name())),
+                    ..Default::default()
+                }),
+                lo: None,
+            }),
+        };
+
+        let request = RemotingCommand::create_request_command(
+            RequestCode::GetEarliestMsgStoreTime,
+            request_header,
+        );
+
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            let response_header = response
+                .decode_command_custom_header::<GetEarliestMsgStoretimeResponseHeader>()
+                .expect("decode error");
+            return Ok(response_header.timestamp);
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
     pub async fn set_message_request_mode(
         &mut self,
         broker_addr: &CheetahString,
@@ -1330,4 +1528,969 @@ impl MQClientAPIImpl {
             addr.to_string()
         )
     }
+
+    pub async fn get_topic_list_from_name_server(&self, timeout_millis: u64) -> Result<TopicList> {
+        let request =
+            RemotingCommand::create_remoting_command(RequestCode::GetAllTopicListFromNameserver);
+        let response = self
+            .remoting_client
+            .invoke_async(None, request, timeout_millis)
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return TopicList::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode TopicList failed: {}",
+                        e
+                    )))
+                });
+            }
+            return Ok(TopicList::default());
+        }
+        mq_client_err!(
+            response.code(),
+            response.remark().cloned().unwrap_or_default().to_string()
+        )
+    }
+
+    pub async fn get_broker_cluster_info(&self, timeout_millis: u64) -> Result<ClusterInfo> {
+        let request = RemotingCommand::create_remoting_command(RequestCode::GetBrokerClusterInfo);
+        let response = self
+            .remoting_client
+            .invoke_async(None, request, timeout_millis)
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return ClusterInfo::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode ClusterInfo failed: {}",
+                        e
+                    )))
+                });
+            }
+            return Ok(ClusterInfo::default());
+        }
+        mq_client_err!(
+            response.code(),
+            response.remark().cloned().unwrap_or_default().to_string()
+        )
+    }
+
+    pub async fn create_topic(
+        &self,
+        addr: &CheetahString,
+        default_topic: &CheetahString,
+        topic_config: &TopicConfig,
+        timeout_millis: u64,
+    ) -> Result<()> {
+        let attributes = topic_config
+            .attributes
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let request_header = CreateTopicRequestHeader {
+            topic: topic_config.topic_name.clone().unwrap_or_default(),
+            default_topic: default_topic.clone(),
+            read_queue_nums: topic_config.read_queue_nums as i32,
+            write_queue_nums: topic_config.write_queue_nums as i32,
+            perm: topic_config.perm as i32,
+            topic_filter_type: CheetahString::from_string(
+                topic_config.topic_filter_type.to_string(),
+            ),
+            topic_sys_flag: Some(topic_config.topic_sys_flag as i32),
+            order: topic_config.order,
+            attributes: Some(CheetahString::from_string(
+                AttributeParser::parse_to_string(&attributes),
+            )),
+            force: None,
+            topic_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::UpdateAndCreateTopic,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            return Ok(());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    /// Removes the write permission of `broker_name` from the topic routes held by the name
+    /// server at `namesrv_addr`, returning the number of topics affected.
+    pub async fn wipe_write_perm_of_broker(
+        &self,
+        namesrv_addr: &CheetahString,
+        broker_name: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<i32> {
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(namesrv_addr),
+                wipe_write_perm_of_broker_request(broker_name),
+                timeout_millis,
+            )
+            .await?;
+        Ok(wipe_topic_count(&response)?)
+    }
+
+    /// Restores the write permission of `broker_name` in the topic routes held by the name
+    /// server at `namesrv_addr`, returning the number of topics affected.
+    pub async fn add_write_perm_of_broker(
+        &self,
+        namesrv_addr: &CheetahString,
+        broker_name: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<i32> {
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(namesrv_addr),
+                add_write_perm_of_broker_request(broker_name),
+                timeout_millis,
+            )
+            .await?;
+        Ok(add_topic_count(&response)?)
+    }
+
+    pub async fn delete_topic_in_broker(
+        &self,
+        addr: &CheetahString,
+        topic: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<()> {
+        let request_header = DeleteTopicRequestHeader {
+            topic: topic.clone(),
+            topic_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::DeleteTopicInBroker,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            return Ok(());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn clean_unused_topic(
+        &self,
+        addr: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<()> {
+        let request = RemotingCommand::create_remoting_command(RequestCode::CleanUnusedTopic);
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            return Ok(());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn clone_group_offset(
+        &self,
+        addr: &CheetahString,
+        src_group: &CheetahString,
+        dest_group: &CheetahString,
+        topic: &CheetahString,
+        is_offline: bool,
+        timeout_millis: u64,
+    ) -> Result<()> {
+        let request_header = CloneGroupOffsetRequestHeader {
+            src_group: src_group.clone(),
+            dest_group: dest_group.clone(),
+            topic: Some(topic.clone()),
+            offline: is_offline,
+            rpc_request_header: None,
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::CloneGroupOffset, request_header);
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            return Ok(());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn delete_topic_in_name_server(
+        &self,
+        addr: &CheetahString,
+        topic: &CheetahString,
+        cluster_name: Option<&CheetahString>,
+        timeout_millis: u64,
+    ) -> Result<()> {
+        let request_header =
+            DeleteTopicFromNamesrvRequestHeader::new(topic.clone(), cluster_name.cloned());
+        let request = RemotingCommand::create_request_command(
+            RequestCode::DeleteTopicInNamesrv,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(Some(addr), request, timeout_millis)
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            return Ok(());
+        }
+        mq_client_err!(
+            response.code(),
+            response.remark().cloned().unwrap_or_default().to_string()
+        )
+    }
+
+    pub async fn create_subscription_group(
+        &self,
+        addr: &CheetahString,
+        config: &SubscriptionGroupConfig,
+        timeout_millis: u64,
+    ) -> Result<()> {
+        let request = RemotingCommand::new_request(
+            RequestCode::UpdateAndCreateSubscriptionGroup,
+            config
+                .encode()
+                .expect("encode SubscriptionGroupConfig failed"),
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            return Ok(());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_consumer_connection_list(
+        &self,
+        addr: &CheetahString,
+        consumer_group: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<ConsumerConnection> {
+        let request_header = GetConsumerConnectionListRequestHeader {
+            consumer_group: consumer_group.clone(),
+            rpc_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::GetConsumerConnectionList,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return ConsumerConnection::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode ConsumerConnection failed: {}",
+                        e
+                    )))
+                });
+            }
+            return Ok(ConsumerConnection::new());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_producer_connection_list(
+        &self,
+        addr: &CheetahString,
+        producer_group: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<ProducerConnection> {
+        let request_header = GetProducerConnectionListRequestHeader {
+            producer_group: producer_group.clone(),
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::GetProducerConnectionList,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return ProducerConnection::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode ProducerConnection failed: {}",
+                        e
+                    )))
+                });
+            }
+            return Ok(ProducerConnection::default());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_consume_stats(
+        &self,
+        addr: &CheetahString,
+        consumer_group: &CheetahString,
+        topic: Option<&CheetahString>,
+        timeout_millis: u64,
+    ) -> Result<ConsumeStats> {
+        let request_header = GetConsumeStatsRequestHeader {
+            consumer_group: consumer_group.clone(),
+            topic: topic.cloned().unwrap_or_default(),
+            topic_request_header: None,
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::GetConsumeStats, request_header);
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return ConsumeStats::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode ConsumeStats failed: {}",
+                        e
+                    )))
+                });
+            }
+            return Ok(ConsumeStats::new());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn view_message(
+        &mut self,
+        addr: &CheetahString,
+        topic: &CheetahString,
+        phy_offset: i64,
+        timeout_millis: u64,
+    ) -> Result<MessageExt> {
+        let request = view_message_request(topic, phy_offset);
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            let namespace = self.client_config.get_namespace();
+            if let Some(message_ext) = viewed_message(&response, namespace.as_deref()) {
+                return Ok(message_ext);
+            }
+            return mq_client_err!(format!(
+                "decode message failed, broker={}, offset={}",
+                addr, phy_offset
+            ));
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    /// Reads a batch of the messages a broker stores in an offset range, returning them with the
+    /// offset to resume reading from and the max offset of the range's queue or commit log.
+    pub async fn query_message_by_offset_range(
+        &mut self,
+        addr: &CheetahString,
+        request_header: QueryMessageByOffsetRangeRequestHeader,
+        timeout_millis: u64,
+    ) -> Result<(Vec<MessageExt>, QueryMessageByOffsetRangeResponseHeader)> {
+        let request = RemotingCommand::create_request_command(
+            RequestCode::QueryMessageByOffsetRange,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            let response_header = response
+                .decode_command_custom_header::<QueryMessageByOffsetRangeResponseHeader>()?;
+            let messages = match response.body() {
+                Some(body) => message_decoder::decodes_batch(&mut body.clone(), true, true),
+                None => Vec::new(),
+            };
+            return Ok((messages, response_header));
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_consumer_running_info(
+        &self,
+        addr: &CheetahString,
+        consumer_group: &CheetahString,
+        client_id: &CheetahString,
+        jstack: bool,
+        timeout_millis: u64,
+    ) -> Result<ConsumerRunningInfo> {
+        let request_header = GetConsumerRunningInfoRequestHeader {
+            consumer_group: consumer_group.clone(),
+            client_id: client_id.clone(),
+            jstack_enable: jstack,
+            rpc_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::GetConsumerRunningInfo,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return ConsumerRunningInfo::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode ConsumerRunningInfo failed: {}",
+                        e
+                    )))
+                });
+            }
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn consume_message_directly(
+        &self,
+        addr: &CheetahString,
+        consumer_group: &CheetahString,
+        client_id: &CheetahString,
+        topic: &CheetahString,
+        msg_id: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<ConsumeMessageDirectlyResult> {
+        let request_header = ConsumeMessageDirectlyResultRequestHeader {
+            consumer_group: consumer_group.clone(),
+            client_id: Some(client_id.clone()),
+            msg_id: Some(msg_id.clone()),
+            topic: Some(topic.clone()),
+            ..Default::default()
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::ConsumeMessageDirectly,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return ConsumeMessageDirectlyResult::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode ConsumeMessageDirectlyResult failed: {}",
+                        e
+                    )))
+                });
+            }
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn invoke_broker_to_reset_offset(
+        &self,
+        addr: &CheetahString,
+        topic: &CheetahString,
+        group: &CheetahString,
+        timestamp: i64,
+        is_force: bool,
+        timeout_millis: u64,
+    ) -> Result<HashMap<MessageQueue, i64>> {
+        let request_header = ResetOffsetRequestHeader {
+            topic: topic.clone(),
+            group: group.clone(),
+            timestamp,
+            is_force,
+            ..Default::default()
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::InvokeBrokerToResetOffset,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return ResetOffsetBody::decode(body.as_ref())
+                    .map(|body| body.offset_table)
+                    .map_err(|e| {
+                        MQClientError::MQClientErr(ClientErr::new(format!(
+                            "decode ResetOffsetBody failed: {}",
+                            e
+                        )))
+                    });
+            }
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_pop_stats(
+        &self,
+        addr: &CheetahString,
+        consumer_group: &CheetahString,
+        topic: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<PopStats> {
+        let request_header = GetPopStatsRequestHeader {
+            consumer_group: consumer_group.clone(),
+            topic: topic.clone(),
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::GetPopStats, request_header);
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return PopStats::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode PopStats failed: {}",
+                        e
+                    )))
+                });
+            }
+            return Ok(PopStats::default());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_broker_runtime_info(
+        &self,
+        addr: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<KVTable> {
+        let request = RemotingCommand::create_remoting_command(RequestCode::GetBrokerRuntimeInfo);
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&mix_all::broker_vip_channel(
+                    self.client_config.vip_channel_enabled,
+                    addr,
+                )),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return KVTable::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode KVTable failed: {}",
+                        e
+                    )))
+                });
+            }
+            return Ok(KVTable::default());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_all_topic_config(
+        &self,
+        addr: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<TopicConfigSerializeWrapper> {
+        let request = RemotingCommand::create_remoting_command(RequestCode::GetAllTopicConfig);
+        let response = self
+            .remoting_client
+            .invoke_async(Some(addr), request, timeout_millis)
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return TopicConfigSerializeWrapper::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode TopicConfigSerializeWrapper failed: {}",
+                        e
+                    )))
+                });
+            }
+            return Ok(TopicConfigSerializeWrapper::default());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_all_subscription_group(
+        &self,
+        addr: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<SubscriptionGroupWrapper> {
+        let request =
+            RemotingCommand::create_remoting_command(RequestCode::GetAllSubscriptionGroupConfig);
+        let response = self
+            .remoting_client
+            .invoke_async(Some(addr), request, timeout_millis)
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return SubscriptionGroupWrapper::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode SubscriptionGroupWrapper failed: {}",
+                        e
+                    )))
+                });
+            }
+            return Ok(SubscriptionGroupWrapper::default());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_all_consumer_offset(
+        &self,
+        addr: &CheetahString,
+        timeout_millis: u64,
+    ) -> Result<ConsumerOffsetSerializeWrapper> {
+        let request = RemotingCommand::create_remoting_command(RequestCode::GetAllConsumerOffset);
+        let response = self
+            .remoting_client
+            .invoke_async(Some(addr), request, timeout_millis)
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return ConsumerOffsetSerializeWrapper::decode(body.as_ref()).map_err(|e| {
+                    MQClientError::MQClientErr(ClientErr::new(format!(
+                        "decode ConsumerOffsetSerializeWrapper failed: {}",
+                        e
+                    )))
+                });
+            }
+            return Ok(ConsumerOffsetSerializeWrapper::default());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+}
+
+fn wipe_write_perm_of_broker_request(broker_name: &CheetahString) -> RemotingCommand {
+    RemotingCommand::create_request_command(
+        RequestCode::WipeWritePermOfBroker,
+        WipeWritePermOfBrokerRequestHeader::new(broker_name.clone()),
+    )
+}
+
+fn wipe_topic_count(response: &RemotingCommand) -> std::result::Result<i32, ClientErr> {
+    if ResponseCode::from(response.code()) == ResponseCode::Success {
+        return response
+            .decode_command_custom_header::<WipeWritePermOfBrokerResponseHeader>()
+            .map(|response_header| response_header.wipe_topic_count)
+            .map_err(|e| ClientErr::new(e.to_string()));
+    }
+    Err(ClientErr::new_with_code(
+        response.code(),
+        response.remark().map_or("".to_string(), |s| s.to_string()),
+    ))
+}
+
+fn add_write_perm_of_broker_request(broker_name: &CheetahString) -> RemotingCommand {
+    RemotingCommand::create_request_command(
+        RequestCode::AddWritePermOfBroker,
+        AddWritePermOfBrokerRequestHeader::new(broker_name.clone()),
+    )
+}
+
+fn add_topic_count(response: &RemotingCommand) -> std::result::Result<i32, ClientErr> {
+    if ResponseCode::from(response.code()) == ResponseCode::Success {
+        return response
+            .decode_command_custom_header::<AddWritePermOfBrokerResponseHeader>()
+            .map(|response_header| response_header.add_topic_count)
+            .map_err(|e| ClientErr::new(e.to_string()));
+    }
+    Err(ClientErr::new_with_code(
+        response.code(),
+        response.remark().map_or("".to_string(), |s| s.to_string()),
+    ))
+}
+
+fn view_message_request(topic: &CheetahString, phy_offset: i64) -> RemotingCommand {
+    RemotingCommand::create_request_command(
+        RequestCode::ViewMessageById,
+        ViewMessageRequestHeader {
+            topic: topic.clone(),
+            offset: phy_offset,
+        },
+    )
+}
+
+/// The message carried by a view message response, with the namespace stripped from its topic.
+fn viewed_message(response: &RemotingCommand, namespace: Option<&str>) -> Option<MessageExt> {
+    let mut body = response.body().clone()?;
+    let mut message_ext = message_decoder::decode(&mut body, true, true, false, false, false)?;
+    if let Some(namespace) = namespace {
+        let topic =
+            NamespaceUtil::without_namespace_with_namespace(message_ext.get_topic(), namespace);
+        message_ext.set_topic(CheetahString::from_string(topic));
+    }
+    Some(message_ext)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use rocketmq_remoting::protocol::command_custom_header::CommandCustomHeader;
+
+    use super::*;
+
+    /// The ext fields a command carries on the wire.
+    fn ext_fields(mut command: RemotingCommand) -> HashMap<CheetahString, CheetahString> {
+        command.make_custom_header_to_net();
+        command.ext_fields().cloned().unwrap_or_default()
+    }
+
+    /// The response a name server sends with `header`.
+    fn response(header: impl CommandCustomHeader + Sync + Send + 'static) -> RemotingCommand {
+        let mut response = RemotingCommand::create_response_command_with_header(header);
+        response.make_custom_header_to_net();
+        response
+    }
+
+    #[test]
+    fn write_perm_requests_name_the_broker() {
+        let broker_name = CheetahString::from_static_str("broker-a");
+
+        let request = wipe_write_perm_of_broker_request(&broker_name);
+        assert_eq!(request.code(), RequestCode::WipeWritePermOfBroker as i32);
+        assert_eq!(
+            ext_fields(request).get("brokerName").map(|s| s.as_str()),
+            Some("broker-a")
+        );
+
+        let request = add_write_perm_of_broker_request(&broker_name);
+        assert_eq!(request.code(), RequestCode::AddWritePermOfBroker as i32);
+        assert_eq!(
+            ext_fields(request).get("brokerName").map(|s| s.as_str()),
+            Some("broker-a")
+        );
+    }
+
+    #[test]
+    fn topic_counts_are_read_from_the_response_header() {
+        let wiped = response(WipeWritePermOfBrokerResponseHeader::new(3));
+        assert_eq!(wipe_topic_count(&wiped).unwrap(), 3);
+        let added = response(AddWritePermOfBrokerResponseHeader::new(2));
+        assert_eq!(add_topic_count(&added).unwrap(), 2);
+
+        // each count is read from its own field
+        assert_eq!(wipe_topic_count(&added).unwrap(), 0);
+        assert_eq!(add_topic_count(&wiped).unwrap(), 0);
+
+        let failed = RemotingCommand::create_response_command_with_code_remark(
+            ResponseCode::SystemError,
+            "no such broker",
+        );
+        let e = add_topic_count(&failed).unwrap_err();
+        assert_eq!(e.response_code(), ResponseCode::SystemError as i32);
+        assert_eq!(
+            e.error_message().map(|s| s.as_str()),
+            Some("no such broker")
+        );
+    }
+
+    #[test]
+    fn view_message_request_carries_the_commit_log_offset() {
+        let request = view_message_request(&CheetahString::from_static_str("topic"), 4096);
+        assert_eq!(request.code(), RequestCode::ViewMessageById as i32);
+        let ext_fields = ext_fields(request);
+        assert_eq!(ext_fields.get("topic").map(|s| s.as_str()), Some("topic"));
+        assert_eq!(ext_fields.get("offset").map(|s| s.as_str()), Some("4096"));
+    }
+
+    #[test]
+    fn viewed_message_is_decoded_without_the_namespace() {
+        let mut message_ext = MessageExt::default();
+        message_ext.set_topic(CheetahString::from_static_str("ns%topic"));
+        message_ext.set_body(Bytes::from_static(b"body"));
+        message_ext.commit_log_offset = 4096;
+        let response = RemotingCommand::create_response_command()
+            .set_body(message_decoder::encode(&message_ext, false).unwrap());
+
+        let viewed = viewed_message(&response, None).unwrap();
+        assert_eq!(viewed.get_topic().as_str(), "ns%topic");
+        assert_eq!(viewed.commit_log_offset, 4096);
+        assert_eq!(
+            viewed.get_body().map(|body| body.as_ref()),
+            Some(&b"body"[..])
+        );
+
+        let viewed = viewed_message(&response, Some("ns")).unwrap();
+        assert_eq!(viewed.get_topic().as_str(), "topic");
+
+        assert!(viewed_message(&RemotingCommand::create_response_command(), None).is_none());
+    }
 }
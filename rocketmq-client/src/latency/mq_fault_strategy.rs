@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::thread::scope;
@@ -45,6 +46,7 @@ pub struct MQFaultStrategy {
     not_available_duration: &'static [u64],
     reachable_filter: Box<dyn QueueFilter>,
     available_filter: Box<dyn QueueFilter>,
+    zone_name: Option<CheetahString>,
 }
 
 impl MQFaultStrategy {
@@ -64,6 +66,7 @@ impl MQFaultStrategy {
             available_filter: Box::new(AvailableFilter {
                 latency_fault_tolerance,
             }),
+            zone_name: client_config.zone_name.clone(),
         }
     }
 
@@ -93,29 +96,70 @@ impl MQFaultStrategy {
         THREAD_BROKER_FILTER.with(|filer| {
             filer.borrow_mut().last_broker_name = last_broker_name.cloned();
         });
+        // brokers of the client's own zone are preferred, the other zones are only used when
+        // none of them can take the message
+        if let Some(zone_filter) = self.zone_filter(tp_info) {
+            let mq = self.select_queue(tp_info, reset_index, Some(&zone_filter));
+            if mq.is_some() {
+                return mq;
+            }
+        }
+        self.select_queue(tp_info, reset_index, None)
+    }
+
+    fn zone_filter(&self, tp_info: &TopicPublishInfo) -> Option<ZoneFilter> {
+        let zone_name = self.zone_name.as_ref()?;
+        let broker_names = tp_info
+            .topic_route_data
+            .as_ref()?
+            .broker_datas
+            .iter()
+            .filter(|broker_data| {
+                broker_data
+                    .zone_name()
+                    .as_ref()
+                    .is_some_and(|zone| zone.eq_ignore_ascii_case(zone_name))
+            })
+            .map(|broker_data| broker_data.broker_name().clone())
+            .collect::<HashSet<CheetahString>>();
+        if broker_names.is_empty() {
+            return None;
+        }
+        Some(ZoneFilter { broker_names })
+    }
+
+    fn select_queue(
+        &self,
+        tp_info: &TopicPublishInfo,
+        reset_index: bool,
+        zone_filter: Option<&dyn QueueFilter>,
+    ) -> Option<MessageQueue> {
+        let select = |filters: &[&dyn QueueFilter]| {
+            let mut filters = filters.to_vec();
+            filters.extend(zone_filter);
+            tp_info.select_one_message_queue(&filters)
+        };
         if self.send_latency_fault_enable.load(Ordering::Relaxed) {
             if reset_index {
                 tp_info.reset_index();
             }
             let broker_filter = THREAD_BROKER_FILTER.with_borrow(|f| f.clone());
-            let filter = &[self.available_filter.as_ref(), &broker_filter];
-            let mut mq = tp_info.select_one_message_queue(filter);
+            let mut mq = select(&[self.available_filter.as_ref(), &broker_filter]);
             if mq.is_some() {
                 return mq;
             }
-            let filter = &[self.reachable_filter.as_ref(), &broker_filter];
-            mq = tp_info.select_one_message_queue(filter);
+            mq = select(&[self.reachable_filter.as_ref(), &broker_filter]);
             if mq.is_some() {
                 return mq;
             }
-            return tp_info.select_one_message_queue(&[]);
+            return select(&[]);
         }
         let broker_filter = THREAD_BROKER_FILTER.with_borrow(|f| f.clone());
-        let mq = tp_info.select_one_message_queue(&[&broker_filter]);
+        let mq = select(&[&broker_filter]);
         if mq.is_some() {
             return mq;
         }
-        tp_info.select_one_message_queue(&[])
+        select(&[])
     }
 
     pub fn get_latency_max(&self) -> &'static [u64] {
@@ -177,6 +221,16 @@ impl QueueFilter for BrokerFilter {
     }
 }
 
+struct ZoneFilter {
+    broker_names: HashSet<CheetahString>,
+}
+
+impl QueueFilter for ZoneFilter {
+    fn filter(&self, message_queue: &MessageQueue) -> bool {
+        self.broker_names.contains(message_queue.get_broker_name())
+    }
+}
+
 struct ReachableFilter {
     latency_fault_tolerance:
         ArcMut<LatencyFaultToleranceImpl<DefaultResolver, DefaultServiceDetector>>,
@@ -220,3 +274,72 @@ impl QueueFilter for AvailableFilter {
         flag
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rocketmq_remoting::protocol::route::route_data_view::BrokerData;
+    use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+
+    use super::*;
+
+    fn topic_publish_info() -> TopicPublishInfo {
+        let mut topic_route_data = TopicRouteData::new();
+        let mut message_queue_list = Vec::new();
+        for (broker_name, zone_name) in [("broker-a", "zone-a"), ("broker-b", "zone-b")] {
+            topic_route_data.broker_datas.push(BrokerData::new(
+                "DefaultCluster".into(),
+                broker_name.into(),
+                HashMap::new(),
+                Some(zone_name.into()),
+            ));
+            for queue_id in 0..4 {
+                message_queue_list.push(MessageQueue::from_parts("TopicA", broker_name, queue_id));
+            }
+        }
+        TopicPublishInfo {
+            have_topic_router_info: true,
+            message_queue_list,
+            topic_route_data: Some(topic_route_data),
+            ..TopicPublishInfo::new()
+        }
+    }
+
+    fn fault_strategy(zone_name: Option<&str>) -> MQFaultStrategy {
+        let mut strategy = MQFaultStrategy::new(&ClientConfig {
+            zone_name: zone_name.map(CheetahString::from),
+            ..ClientConfig::new()
+        });
+        strategy.set_send_latency_fault_enable(false);
+        strategy
+    }
+
+    #[test]
+    fn select_one_message_queue_prefers_brokers_of_the_same_zone() {
+        let strategy = fault_strategy(Some("ZONE-B"));
+        let tp_info = topic_publish_info();
+        for _ in 0..8 {
+            let mq = strategy
+                .select_one_message_queue(&tp_info, None, false)
+                .unwrap();
+            assert_eq!(mq.get_broker_name(), "broker-b");
+        }
+    }
+
+    #[test]
+    fn select_one_message_queue_falls_back_to_other_zones() {
+        let strategy = fault_strategy(Some("zone-c"));
+        let tp_info = topic_publish_info();
+        let brokers = (0..8)
+            .map(|_| {
+                strategy
+                    .select_one_message_queue(&tp_info, None, false)
+                    .unwrap()
+                    .get_broker_name()
+                    .clone()
+            })
+            .collect::<HashSet<CheetahString>>();
+        assert_eq!(brokers.len(), 2);
+    }
+}
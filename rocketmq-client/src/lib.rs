@@ -28,10 +28,12 @@ pub mod client_error;
 mod common;
 pub mod consumer;
 pub mod factory;
+pub mod grpc;
 mod hook;
-mod implementation;
+pub mod implementation;
 mod latency;
 pub mod producer;
+mod stat;
 mod trace;
 pub mod utils;
 
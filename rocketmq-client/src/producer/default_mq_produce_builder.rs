@@ -49,6 +49,7 @@ pub struct DefaultMQProducerBuilder {
     enable_backpressure_for_async_mode: Option<bool>,
     back_pressure_for_async_send_num: Option<u32>,
     back_pressure_for_async_send_size: Option<u32>,
+    back_pressure_fail_fast: Option<bool>,
     rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
     compress_level: Option<i32>,
     compress_type: Option<CompressionType>,
@@ -77,6 +78,7 @@ impl DefaultMQProducerBuilder {
             enable_backpressure_for_async_mode: None,
             back_pressure_for_async_send_num: None,
             back_pressure_for_async_send_size: None,
+            back_pressure_fail_fast: None,
             rpc_hook: None,
             compress_level: None,
             compress_type: None,
@@ -122,6 +124,54 @@ impl DefaultMQProducerBuilder {
         self
     }
 
+    pub fn enable_trace(mut self, enable_trace: bool) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.enable_trace = enable_trace;
+        }
+        self
+    }
+
+    /// Sends to the broker's fast port (listen port - 2), which does not serve pull requests,
+    /// so sends are not queued behind heavy consumer traffic.
+    pub fn vip_channel_enabled(mut self, vip_channel_enabled: bool) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.vip_channel_enabled = vip_channel_enabled;
+        }
+        self
+    }
+
+    /// Records OpenTelemetry style spans and propagates the W3C trace context through the
+    /// `TRACEPARENT` message property.
+    pub fn enable_telemetry(mut self, enable_telemetry: bool) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.enable_telemetry = enable_telemetry;
+        }
+        self
+    }
+
+    /// Sets the zone of this client, brokers of the same zone are preferred.
+    pub fn zone_name(mut self, zone_name: impl Into<CheetahString>) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.zone_name = Some(zone_name.into());
+        }
+        self
+    }
+
+    /// Asks the name server to route this client to the brokers of its own zone only.
+    pub fn zone_mode(mut self, zone_mode: bool) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.zone_mode = zone_mode;
+        }
+        self
+    }
+
+    pub fn custom_trace_topic(mut self, trace_topic: impl Into<CheetahString>) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.trace_topic = Some(trace_topic.into());
+        }
+        self
+    }
+
     pub fn create_topic_key(mut self, create_topic_key: impl Into<CheetahString>) -> Self {
         self.create_topic_key = Some(create_topic_key.into());
         self
@@ -210,6 +260,11 @@ impl DefaultMQProducerBuilder {
         self
     }
 
+    pub fn back_pressure_fail_fast(mut self, back_pressure_fail_fast: bool) -> Self {
+        self.back_pressure_fail_fast = Some(back_pressure_fail_fast);
+        self
+    }
+
     pub fn rpc_hook(mut self, rpc_hook: Box<dyn RPCHook>) -> Self {
         self.rpc_hook = Some(Arc::new(rpc_hook));
         self
@@ -290,6 +345,9 @@ impl DefaultMQProducerBuilder {
         if let Some(back_pressure_for_async_send_size) = self.back_pressure_for_async_send_size {
             mq_producer.set_back_pressure_for_async_send_size(back_pressure_for_async_send_size);
         }
+        if let Some(back_pressure_fail_fast) = self.back_pressure_fail_fast {
+            mq_producer.set_back_pressure_fail_fast(back_pressure_fail_fast);
+        }
         mq_producer.set_rpc_hook(self.rpc_hook);
         if let Some(compress_level) = self.compress_level {
             mq_producer.set_compress_level(compress_level);
@@ -315,3 +373,17 @@ impl DefaultMQProducerBuilder {
         mq_producer
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vip_channel_enabled_is_applied_to_client_config() {
+        let producer = DefaultMQProducerBuilder::new()
+            .producer_group("vip_group")
+            .vip_channel_enabled(true)
+            .build();
+        assert!(producer.client_config().vip_channel_enabled);
+    }
+}
@@ -36,6 +36,7 @@ use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_rust::ArcMut;
 use tracing::error;
+use tracing::warn;
 
 use crate::base::client_config::ClientConfig;
 use crate::base::validators::Validators;
@@ -99,6 +100,9 @@ pub struct ProducerConfig {
     /// on BackpressureForAsyncMode, limit maximum message size of on-going sending async messages
     /// default is 100M
     back_pressure_for_async_send_size: u32,
+    /// on BackpressureForAsyncMode, fail an async send with `ProducerBusy` instead of waiting
+    /// when the limits are reached, default is false
+    back_pressure_fail_fast: bool,
     rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
     compress_level: i32,
     compress_type: CompressionType,
@@ -175,6 +179,10 @@ impl ProducerConfig {
         self.back_pressure_for_async_send_size
     }
 
+    pub fn back_pressure_fail_fast(&self) -> bool {
+        self.back_pressure_fail_fast
+    }
+
     pub fn rpc_hook(&self) -> &Option<Arc<Box<dyn RPCHook>>> {
         &self.rpc_hook
     }
@@ -229,6 +237,7 @@ impl Default for ProducerConfig {
             enable_backpressure_for_async_mode: false,
             back_pressure_for_async_send_num: 10000,
             back_pressure_for_async_send_size: 100 * 1024 * 1024,
+            back_pressure_fail_fast: false,
             rpc_hook: None,
             compress_level: std::env::var(MESSAGE_COMPRESS_LEVEL)
                 .unwrap_or("5".to_string())
@@ -330,6 +339,10 @@ impl DefaultMQProducer {
         self.producer_config.back_pressure_for_async_send_size
     }
 
+    pub fn back_pressure_fail_fast(&self) -> bool {
+        self.producer_config.back_pressure_fail_fast
+    }
+
     pub fn rpc_hook(&self) -> &Option<Arc<Box<dyn RPCHook>>> {
         &self.producer_config.rpc_hook
     }
@@ -448,6 +461,10 @@ impl DefaultMQProducer {
         self.producer_config.back_pressure_for_async_send_size = back_pressure_for_async_send_size;
     }
 
+    pub fn set_back_pressure_fail_fast(&mut self, back_pressure_fail_fast: bool) {
+        self.producer_config.back_pressure_fail_fast = back_pressure_fail_fast;
+    }
+
     pub fn set_rpc_hook(&mut self, rpc_hook: Option<Arc<Box<dyn RPCHook>>>) {
         self.producer_config.rpc_hook = rpc_hook;
     }
@@ -458,6 +475,8 @@ impl DefaultMQProducer {
 
     pub fn set_compress_type(&mut self, compress_type: CompressionType) {
         self.producer_config.compress_type = compress_type;
+        self.producer_config.compressor =
+            Some(Arc::new(CompressorFactory::get_compressor(compress_type)));
     }
 
     pub fn set_compressor(&mut self, compressor: Option<Arc<Box<dyn Compressor + Send + Sync>>>) {
@@ -639,7 +658,10 @@ impl MQProducer for DefaultMQProducer {
             let mut dispatcher = AsyncTraceDispatcher::new(
                 self.producer_config.producer_group.as_str(),
                 Type::Produce,
-                self.client_config.trace_topic.clone().unwrap().as_str(),
+                self.client_config
+                    .trace_topic
+                    .as_deref()
+                    .unwrap_or(TopicValidator::RMQ_SYS_TRACE_TOPIC),
                 self.producer_config.rpc_hook.clone(),
             );
             dispatcher.set_host_producer(self.default_mqproducer_impl.as_ref().unwrap().clone());
@@ -654,13 +676,21 @@ impl MQProducer for DefaultMQProducer {
                 .register_end_transaction_hook(EndTransactionTraceHookImpl::new(dispatcher))
         }
 
-        if let Some(ref mut trace_dispatcher) = self.producer_config.trace_dispatcher {
-            //TODO: trace
+        if let Some(ref trace_dispatcher) = self.producer_config.trace_dispatcher {
+            let name_srv_addr = self.client_config.get_namesrv_addr().unwrap_or_default();
+            if let Err(e) =
+                trace_dispatcher.start(name_srv_addr.as_str(), self.client_config.access_channel)
+            {
+                warn!("trace dispatcher start failed: {}", e);
+            }
         }
         Ok(())
     }
 
     async fn shutdown(&mut self) {
+        if let Some(ref mut default_mqproducer_impl) = self.default_mqproducer_impl {
+            default_mqproducer_impl.shutdown().await;
+        }
         if let Some(ref mut produce_accumulator) = self.producer_config.produce_accumulator {
             produce_accumulator.shutdown();
         }
@@ -828,6 +858,7 @@ impl MQProducer for DefaultMQProducer {
         F: Fn(Option<&SendResult>, Option<&dyn std::error::Error>) + Send + Sync + 'static,
     {
         msg.set_topic(self.with_namespace(msg.get_topic()));
+        let mq = self.client_config.queue_with_namespace(mq);
         self.default_mqproducer_impl
             .as_mut()
             .unwrap()
@@ -1044,6 +1075,7 @@ impl MQProducer for DefaultMQProducer {
         mq: MessageQueue,
     ) -> Result<SendResult> {
         let batch = self.batch(msgs)?;
+        let mq = self.client_config.queue_with_namespace(mq);
         let result = self
             .default_mqproducer_impl
             .as_mut()
@@ -1060,6 +1092,7 @@ impl MQProducer for DefaultMQProducer {
         timeout: u64,
     ) -> Result<SendResult> {
         let batch = self.batch(msgs)?;
+        let mq = self.client_config.queue_with_namespace(mq);
         let result = self
             .default_mqproducer_impl
             .as_mut()
@@ -1110,6 +1143,7 @@ impl MQProducer for DefaultMQProducer {
         F: Fn(Option<&SendResult>, Option<&dyn std::error::Error>) + Send + Sync + 'static,
     {
         let batch = self.batch(msgs)?;
+        let mq = self.client_config.queue_with_namespace(mq);
         self.default_mqproducer_impl
             .as_mut()
             .unwrap()
@@ -1128,6 +1162,7 @@ impl MQProducer for DefaultMQProducer {
         F: Fn(Option<&SendResult>, Option<&dyn std::error::Error>) + Send + Sync + 'static,
     {
         let batch = self.batch(msgs)?;
+        let mq = self.client_config.queue_with_namespace(mq);
         self.default_mqproducer_impl
             .as_mut()
             .unwrap()
@@ -1223,6 +1258,7 @@ impl MQProducer for DefaultMQProducer {
         M: MessageTrait + Clone + Send + Sync,
     {
         msg.set_topic(self.with_namespace(msg.get_topic()));
+        let mq = self.client_config.queue_with_namespace(mq);
         self.default_mqproducer_impl
             .as_mut()
             .unwrap()
@@ -1242,6 +1278,7 @@ impl MQProducer for DefaultMQProducer {
         M: MessageTrait + Clone + Send + Sync,
     {
         msg.set_topic(self.with_namespace(msg.get_topic()));
+        let mq = self.client_config.queue_with_namespace(mq);
         self.default_mqproducer_impl
             .as_mut()
             .unwrap()
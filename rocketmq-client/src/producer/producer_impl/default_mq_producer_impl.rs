@@ -38,6 +38,11 @@ use rocketmq_common::common::mix_all;
 use rocketmq_common::common::mix_all::CLIENT_INNER_PRODUCER_GROUP;
 use rocketmq_common::common::mix_all::DEFAULT_PRODUCER_GROUP;
 use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
+use rocketmq_common::common::telemetry::messaging_span::attributes;
+use rocketmq_common::common::telemetry::messaging_span::MessagingSpan;
+use rocketmq_common::common::telemetry::messaging_span::SpanKind;
+use rocketmq_common::common::telemetry::trace_parent::TraceParent;
+use rocketmq_common::common::telemetry::tracer_provider::start_span;
 use rocketmq_common::common::FAQUrl;
 use rocketmq_common::utils::correlation_id_util::CorrelationIdUtil;
 use rocketmq_common::MessageAccessor::MessageAccessor;
@@ -53,8 +58,10 @@ use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_runtime::RocketMQRuntime;
 use rocketmq_rust::ArcMut;
 use tokio::runtime::Handle;
+use tokio::sync::OwnedSemaphorePermit;
 use tokio::sync::RwLock;
 use tokio::sync::Semaphore;
+use tracing::info;
 use tracing::warn;
 
 use crate::base::client_config::ClientConfig;
@@ -99,7 +106,7 @@ pub struct DefaultMQProducerImpl {
     producer_config: Arc<ProducerConfig>,
     topic_publish_info_table: Arc<RwLock<HashMap<CheetahString /* topic */, TopicPublishInfo>>>,
     send_message_hook_list: ArcMut<Vec<Box<dyn SendMessageHook>>>,
-    end_transaction_hook_list: Arc<Vec<Box<dyn EndTransactionHook>>>,
+    end_transaction_hook_list: ArcMut<Vec<Box<dyn EndTransactionHook>>>,
     check_forbidden_hook_list: Vec<Arc<Box<dyn CheckForbiddenHook>>>,
     rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
     service_state: ServiceState,
@@ -135,7 +142,7 @@ impl DefaultMQProducerImpl {
             producer_config: Arc::new(producer_config),
             topic_publish_info_table,
             send_message_hook_list: ArcMut::new(vec![]),
-            end_transaction_hook_list: Arc::new(vec![]),
+            end_transaction_hook_list: ArcMut::new(vec![]),
             check_forbidden_hook_list: vec![],
             rpc_hook: None,
             service_state: ServiceState::CreateJust,
@@ -559,98 +566,42 @@ impl DefaultMQProducerImpl {
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
-        let is_enable_backpressure_for_async_mode =
-            self.producer_config.enable_backpressure_for_async_mode();
+        if !self.producer_config.enable_backpressure_for_async_mode() {
+            self.get_async_sender_executor().get_handle().spawn(f);
+            return Ok(());
+        }
 
-        let (acquire_value_num, acquire_value_size) = if is_enable_backpressure_for_async_mode {
-            //back pressure
-            let cost_time = (Instant::now() - begin_start_time).as_millis() as u64;
-            let is_semaphore_async_numb_acquired = (timeout - cost_time) > 0;
-            if !is_semaphore_async_numb_acquired {
-                send_callback.as_ref().unwrap()(
-                    None,
-                    Some(&RemotingTooMuchRequestError(
-                        "send message tryAcquire semaphoreAsyncNum timeout".to_string(),
-                    )),
-                );
-                return Ok(());
-            }
-            let result = tokio::time::timeout(
-                Duration::from_millis(timeout - cost_time),
-                self.semaphore_async_send_num.acquire(),
-            )
-            .await;
-            let acquire_value_num = match result {
-                Ok(acquire_value) => match acquire_value {
-                    Ok(value) => Some(value),
-                    Err(_) => {
-                        send_callback.as_ref().unwrap()(
-                            None,
-                            Some(&RemotingTooMuchRequestError(
-                                "send message tryAcquire semaphoreAsyncNum timeout".to_string(),
-                            )),
-                        );
-                        return Ok(());
-                    }
-                },
-                Err(_) => {
-                    send_callback.as_ref().unwrap()(
-                        None,
-                        Some(&RemotingTooMuchRequestError(
-                            "send message tryAcquire semaphoreAsyncNum timeout".to_string(),
-                        )),
-                    );
-                    return Ok(());
+        let permits = match acquire_async_send_permits(
+            &self.semaphore_async_send_num,
+            &self.semaphore_async_send_size,
+            self.producer_config
+                .back_pressure_for_async_send_size()
+                .max(1024 * 1024),
+            msg_len,
+            timeout,
+            begin_start_time,
+            self.producer_config.back_pressure_fail_fast(),
+        )
+        .await
+        {
+            Ok(permits) => permits,
+            Err(err @ RemotingTooMuchRequestError(_)) => {
+                if let Some(send_callback) = send_callback.as_ref() {
+                    send_callback(None, Some(&err));
                 }
-            };
-
-            //message size
-            let cost_time = (Instant::now() - begin_start_time).as_millis() as u64;
-            let is_semaphore_async_size_acquired = (timeout - cost_time) > 0;
-            if !is_semaphore_async_size_acquired {
-                send_callback.as_ref().unwrap()(
-                    None,
-                    Some(&RemotingTooMuchRequestError(
-                        "send message tryAcquire semaphoreAsyncSize timeout".to_string(),
-                    )),
-                );
                 return Ok(());
             }
-            let result = tokio::time::timeout(
-                Duration::from_millis(timeout - cost_time),
-                self.semaphore_async_send_size.acquire_many(msg_len as u32),
-            )
-            .await;
-            let acquire_value_size = match result {
-                Ok(acquire_value) => match acquire_value {
-                    Ok(value) => Some(value),
-                    Err(_) => {
-                        send_callback.as_ref().unwrap()(
-                            None,
-                            Some(&RemotingTooMuchRequestError(
-                                "send message tryAcquire semaphoreAsyncSize timeout".to_string(),
-                            )),
-                        );
-                        return Ok(());
-                    }
-                },
-                Err(_) => {
-                    send_callback.as_ref().unwrap()(
-                        None,
-                        Some(&RemotingTooMuchRequestError(
-                            "send message tryAcquire semaphoreAsyncSize timeout".to_string(),
-                        )),
-                    );
-                    return Ok(());
-                }
-            };
-            (acquire_value_num, acquire_value_size)
-        } else {
-            (None, None)
+            Err(err) => return Err(err),
         };
 
-        self.get_async_sender_executor().get_handle().spawn(f);
-        drop((acquire_value_num, acquire_value_size));
+        // the permits are held until the send completes, so in-flight sends stay bounded
+        self.get_async_sender_executor()
+            .get_handle()
+            .spawn(async move {
+                let output = f.await;
+                drop(permits);
+                output
+            });
         Ok(())
     }
 
@@ -976,6 +927,7 @@ impl DefaultMQProducerImpl {
         if !batch {
             MessageClientIDSetter::set_uniq_id(msg);
         }
+        let telemetry_span = self.start_send_span(msg, mq, &broker_addr, batch);
         let mut topic_with_namespace = false;
         if self.client_config.get_namespace().is_some() {
             msg.set_instance_id(self.client_config.get_namespace().unwrap_or_default());
@@ -1050,8 +1002,8 @@ impl DefaultMQProducerImpl {
             if msg_type_flag {
                 send_message_context.msg_type = Some(MessageType::DelayMsg);
             }
-            let send_message_context = Some(send_message_context);
-            self.execute_send_message_hook_before(&send_message_context);
+            let mut send_message_context = Some(send_message_context);
+            self.execute_send_message_hook_before(&mut send_message_context);
             send_message_context
         } else {
             None
@@ -1163,12 +1115,25 @@ impl DefaultMQProducerImpl {
             }
         };
 
+        if let Some(mut span) = telemetry_span {
+            match &send_result {
+                Ok(Some(result)) => {
+                    if let Some(msg_id) = result.msg_id.as_ref() {
+                        span.set_attribute(attributes::MESSAGING_MESSAGE_ID, msg_id.to_string());
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => span.record_error(err.to_string().as_str()),
+            }
+            span.end();
+        }
+
         match send_result {
             Ok(result) => {
                 if self.has_send_message_hook() {
                     let smc = send_message_context.as_mut().unwrap();
                     smc.send_result = result.clone();
-                    self.execute_send_message_hook_after(&send_message_context);
+                    self.execute_send_message_hook_after(&mut send_message_context);
                 }
                 Ok(result)
             }
@@ -1176,14 +1141,59 @@ impl DefaultMQProducerImpl {
                 if self.has_send_message_hook() {
                     //send_message_context.as_mut().unwrap().exception =
                     // Some(Arc::new(err.clone()));
-                    self.execute_send_message_hook_after(&send_message_context);
+                    self.execute_send_message_hook_after(&mut send_message_context);
                 }
                 Err(err)
             }
         }
     }
 
-    pub fn execute_send_message_hook_before(&mut self, context: &Option<SendMessageContext<'_>>) {
+    /// Starts the producer span of a send and propagates its context through the message
+    /// properties; batches carry the context of each inner message instead.
+    fn start_send_span<T>(
+        &self,
+        msg: &mut T,
+        mq: &MessageQueue,
+        broker_addr: &CheetahString,
+        batch: bool,
+    ) -> Option<Box<dyn MessagingSpan>>
+    where
+        T: MessageTrait,
+    {
+        if !self.client_config.enable_telemetry {
+            return None;
+        }
+        let mut span = start_span(
+            format!("{} send", msg.get_topic()),
+            SpanKind::Producer,
+            TraceParent::from_message(msg),
+        );
+        span.set_attribute(attributes::MESSAGING_OPERATION, "publish".to_string());
+        span.set_attribute(
+            attributes::MESSAGING_DESTINATION_NAME,
+            msg.get_topic().to_string(),
+        );
+        span.set_attribute(
+            attributes::MESSAGING_QUEUE_ID,
+            mq.get_queue_id().to_string(),
+        );
+        span.set_attribute(attributes::SERVER_ADDRESS, broker_addr.to_string());
+        if let Some(tags) = msg.get_tags() {
+            span.set_attribute(attributes::MESSAGING_MESSAGE_TAG, tags.to_string());
+        }
+        if let Some(keys) = msg.get_keys() {
+            span.set_attribute(attributes::MESSAGING_MESSAGE_KEYS, keys.to_string());
+        }
+        if !batch {
+            span.context().inject(msg);
+        }
+        Some(span)
+    }
+
+    pub fn execute_send_message_hook_before(
+        &mut self,
+        context: &mut Option<SendMessageContext<'_>>,
+    ) {
         if self.has_send_message_hook() {
             for hook in self.send_message_hook_list.iter() {
                 hook.send_message_before(context);
@@ -1191,7 +1201,7 @@ impl DefaultMQProducerImpl {
         }
     }
 
-    pub fn execute_send_message_hook_after(&self, context: &Option<SendMessageContext<'_>>) {
+    pub fn execute_send_message_hook_after(&self, context: &mut Option<SendMessageContext<'_>>) {
         if self.has_send_message_hook() {
             for hook in self.send_message_hook_list.iter() {
                 hook.send_message_after(context);
@@ -1218,25 +1228,30 @@ impl DefaultMQProducerImpl {
     }
 
     fn try_to_compress_message<T: MessageTrait>(&self, msg: &mut T) -> bool {
-        if let Some(message) = msg.as_any_mut().downcast_mut::<Message>() {
-            if let Some(body) = message.compressed_body.as_mut() {
-                if body.len() >= self.producer_config.compress_msg_body_over_howmuch() as usize {
-                    let data = self
-                        .producer_config
-                        .compressor()
-                        .as_ref()
-                        .unwrap()
-                        .compress(body, self.producer_config.compress_level());
-                    if let Ok(data) = data {
-                        //store the compressed data
-                        msg.set_compressed_body_mut(data);
-                        return true;
-                    }
-                }
+        // batch messages are never compressed
+        let Some(message) = msg.as_any_mut().downcast_mut::<Message>() else {
+            return false;
+        };
+        let Some(compressor) = self.producer_config.compressor().as_ref() else {
+            return false;
+        };
+        let Some(body) = message.body.as_ref() else {
+            return false;
+        };
+        if body.len() < self.producer_config.compress_msg_body_over_howmuch() as usize {
+            return false;
+        }
+        match compressor.compress(body, self.producer_config.compress_level()) {
+            Ok(data) => {
+                //store the compressed data
+                message.compressed_body = Some(data);
+                true
+            }
+            Err(e) => {
+                warn!("failed to compress message body: {}", e);
+                false
             }
         }
-
-        false
     }
 
     #[inline]
@@ -2255,12 +2270,41 @@ impl DefaultMQProducerImpl {
         Ok(())
     }
 
-    pub fn register_end_transaction_hook(&mut self, hook: impl EndTransactionHook) {
-        todo!()
+    pub async fn shutdown(&mut self) {
+        self.shutdown_with_factory(true).await
     }
 
-    pub fn register_send_message_hook(&mut self, hook: impl SendMessageHook) {
-        todo!()
+    pub async fn shutdown_with_factory(&mut self, shutdown_factory: bool) {
+        match self.service_state {
+            ServiceState::CreateJust => {}
+            ServiceState::Running => {
+                if let Some(client_instance) = self.client_instance.as_mut() {
+                    client_instance
+                        .unregister_producer(self.producer_config.producer_group())
+                        .await;
+                    if shutdown_factory {
+                        client_instance.shutdown().await;
+                    }
+                }
+                info!(
+                    "the producer [{}] shutdown OK",
+                    self.producer_config.producer_group()
+                );
+                self.service_state = ServiceState::ShutdownAlready;
+            }
+            ServiceState::ShutdownAlready => {}
+            ServiceState::StartFailed => {}
+        }
+    }
+
+    pub fn register_end_transaction_hook(&mut self, hook: impl EndTransactionHook + 'static) {
+        info!("register end transaction hook, {}", hook.hook_name());
+        self.end_transaction_hook_list.push(Box::new(hook));
+    }
+
+    pub fn register_send_message_hook(&mut self, hook: impl SendMessageHook + 'static) {
+        info!("register send message hook, {}", hook.hook_name());
+        self.send_message_hook_list.push(Box::new(hook));
     }
 
     #[inline]
@@ -2325,3 +2369,158 @@ impl Resolver for DefaultResolver {
             .await
     }
 }
+
+/// Permits held by an in-flight async send, returned to the producer when dropped.
+struct AsyncSendPermits {
+    _num: OwnedSemaphorePermit,
+    _size: OwnedSemaphorePermit,
+}
+
+/// Acquires the number and size permits for one async send.
+///
+/// When `fail_fast` is set a full producer fails with `ProducerBusy` right away, otherwise
+/// the call waits for the remaining send timeout and fails with
+/// `RemotingTooMuchRequestError` when it runs out.
+async fn acquire_async_send_permits(
+    semaphore_num: &Arc<Semaphore>,
+    semaphore_size: &Arc<Semaphore>,
+    size_capacity: u32,
+    msg_len: usize,
+    timeout: u64,
+    begin_start_time: Instant,
+    fail_fast: bool,
+) -> Result<AsyncSendPermits> {
+    // a message larger than the whole budget would otherwise never get its permits
+    let size = msg_len.clamp(1, size_capacity as usize) as u32;
+
+    if fail_fast {
+        let num = semaphore_num.clone().try_acquire_owned().map_err(|_| {
+            MQClientError::ProducerBusy(format!(
+                "producer busy, too many async sends in flight, available permits: {}",
+                semaphore_num.available_permits()
+            ))
+        })?;
+        let size = semaphore_size
+            .clone()
+            .try_acquire_many_owned(size)
+            .map_err(|_| {
+                MQClientError::ProducerBusy(format!(
+                    "producer busy, async send size limit reached, message size: {msg_len}, \
+                     available size: {}",
+                    semaphore_size.available_permits()
+                ))
+            })?;
+        return Ok(AsyncSendPermits {
+            _num: num,
+            _size: size,
+        });
+    }
+
+    let remaining = timeout.saturating_sub(begin_start_time.elapsed().as_millis() as u64);
+    let num = match tokio::time::timeout(
+        Duration::from_millis(remaining),
+        semaphore_num.clone().acquire_owned(),
+    )
+    .await
+    {
+        Ok(Ok(num)) => num,
+        _ => {
+            return Err(RemotingTooMuchRequestError(
+                "send message tryAcquire semaphoreAsyncNum timeout".to_string(),
+            ))
+        }
+    };
+    let remaining = timeout.saturating_sub(begin_start_time.elapsed().as_millis() as u64);
+    let size = match tokio::time::timeout(
+        Duration::from_millis(remaining),
+        semaphore_size.clone().acquire_many_owned(size),
+    )
+    .await
+    {
+        Ok(Ok(size)) => size,
+        _ => {
+            return Err(RemotingTooMuchRequestError(
+                "send message tryAcquire semaphoreAsyncSize timeout".to_string(),
+            ))
+        }
+    };
+    Ok(AsyncSendPermits {
+        _num: num,
+        _size: size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fail_fast_returns_producer_busy_when_full() {
+        let num = Arc::new(Semaphore::new(1));
+        let size = Arc::new(Semaphore::new(1024));
+        let held = acquire_async_send_permits(&num, &size, 1024, 10, 3000, Instant::now(), true)
+            .await
+            .unwrap();
+
+        let busy =
+            acquire_async_send_permits(&num, &size, 1024, 10, 3000, Instant::now(), true).await;
+        assert!(matches!(busy, Err(MQClientError::ProducerBusy(_))));
+
+        drop(held);
+        assert_eq!(num.available_permits(), 1);
+        assert_eq!(size.available_permits(), 1024);
+        assert!(
+            acquire_async_send_permits(&num, &size, 1024, 10, 3000, Instant::now(), true)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn blocking_mode_waits_for_a_released_permit() {
+        let num = Arc::new(Semaphore::new(1));
+        let size = Arc::new(Semaphore::new(1024));
+        let held = acquire_async_send_permits(&num, &size, 1024, 10, 3000, Instant::now(), false)
+            .await
+            .unwrap();
+
+        let waiter = {
+            let (num, size) = (num.clone(), size.clone());
+            tokio::spawn(async move {
+                acquire_async_send_permits(&num, &size, 1024, 10, 3000, Instant::now(), false)
+                    .await
+                    .is_ok()
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+        drop(held);
+        assert!(waiter.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn blocking_mode_times_out_with_too_much_request_error() {
+        let num = Arc::new(Semaphore::new(1));
+        let size = Arc::new(Semaphore::new(1024));
+        let _held = acquire_async_send_permits(&num, &size, 1024, 10, 3000, Instant::now(), false)
+            .await
+            .unwrap();
+
+        let result =
+            acquire_async_send_permits(&num, &size, 1024, 10, 50, Instant::now(), false).await;
+        assert!(matches!(result, Err(RemotingTooMuchRequestError(_))));
+    }
+
+    #[tokio::test]
+    async fn oversized_message_is_capped_at_size_budget() {
+        let num = Arc::new(Semaphore::new(10));
+        let size = Arc::new(Semaphore::new(1024));
+        let permits =
+            acquire_async_send_permits(&num, &size, 1024, 4096, 3000, Instant::now(), true)
+                .await
+                .unwrap();
+        assert_eq!(size.available_permits(), 0);
+        drop(permits);
+        assert_eq!(size.available_permits(), 1024);
+    }
+}
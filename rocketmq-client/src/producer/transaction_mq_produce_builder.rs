@@ -53,6 +53,7 @@ pub struct TransactionMQProducerBuilder {
     enable_backpressure_for_async_mode: Option<bool>,
     back_pressure_for_async_send_num: Option<u32>,
     back_pressure_for_async_send_size: Option<u32>,
+    back_pressure_fail_fast: Option<bool>,
     rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
     compress_level: Option<i32>,
     compress_type: Option<CompressionType>,
@@ -83,6 +84,7 @@ impl TransactionMQProducerBuilder {
             enable_backpressure_for_async_mode: None,
             back_pressure_for_async_send_num: None,
             back_pressure_for_async_send_size: None,
+            back_pressure_fail_fast: None,
             rpc_hook: None,
             compress_level: None,
             compress_type: None,
@@ -218,6 +220,11 @@ impl TransactionMQProducerBuilder {
         self
     }
 
+    pub fn back_pressure_fail_fast(mut self, back_pressure_fail_fast: bool) -> Self {
+        self.back_pressure_fail_fast = Some(back_pressure_fail_fast);
+        self
+    }
+
     pub fn rpc_hook(mut self, rpc_hook: Box<dyn RPCHook>) -> Self {
         self.rpc_hook = Some(Arc::new(rpc_hook));
         self
@@ -303,6 +310,9 @@ impl TransactionMQProducerBuilder {
         if let Some(back_pressure_for_async_send_size) = self.back_pressure_for_async_send_size {
             mq_producer.set_back_pressure_for_async_send_size(back_pressure_for_async_send_size);
         }
+        if let Some(back_pressure_fail_fast) = self.back_pressure_fail_fast {
+            mq_producer.set_back_pressure_fail_fast(back_pressure_fail_fast);
+        }
         mq_producer.set_rpc_hook(self.rpc_hook);
         if let Some(compress_level) = self.compress_level {
             mq_producer.set_compress_level(compress_level);
@@ -19,5 +19,7 @@ pub mod hook;
 pub mod trace_bean;
 pub mod trace_constants;
 pub mod trace_context;
+pub mod trace_data_encoder;
 pub mod trace_dispatcher;
+pub mod trace_transfer_bean;
 pub mod trace_type;
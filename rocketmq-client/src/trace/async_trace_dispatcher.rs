@@ -15,19 +15,64 @@
  * limitations under the License.
  */
 use std::any::Any;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 
 use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::common::message::message_single::Message;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::topic::TopicValidator;
 use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_rust::ArcMut;
+use tokio::sync::mpsc;
+use tokio::sync::Notify;
+use tokio::time::MissedTickBehavior;
+use tracing::info;
+use tracing::warn;
 
 use crate::base::access_channel::AccessChannel;
+use crate::base::client_config::ClientConfig;
 use crate::consumer::consumer_impl::default_mq_push_consumer_impl::DefaultMQPushConsumerImpl;
+use crate::producer::default_mq_producer::DefaultMQProducer;
+use crate::producer::mq_producer::MQProducer;
 use crate::producer::producer_impl::default_mq_producer_impl::DefaultMQProducerImpl;
+use crate::trace::trace_constants::TraceConstants;
+use crate::trace::trace_context::TraceContext;
+use crate::trace::trace_data_encoder::TraceDataEncoder;
 use crate::trace::trace_dispatcher::TraceDispatcher;
 use crate::trace::trace_dispatcher::Type;
+use crate::trace::trace_transfer_bean::TraceTransferBean;
 
-pub struct AsyncTraceDispatcher;
+const DEFAULT_QUEUE_SIZE: usize = 2048;
+const DEFAULT_BATCH_SIZE: usize = 100;
+const DEFAULT_MAX_MSG_SIZE: usize = 128000;
+const WAIT_TIME_THRESHOLD_MILLIS: u64 = 500;
+const TRACE_PRODUCER_SEND_TIMEOUT_MILLIS: u64 = 5000;
+
+/// Collects trace contexts produced by the send/consume hooks and ships them in batches to the
+/// trace topic through a dedicated internal producer.
+pub struct AsyncTraceDispatcher {
+    group: CheetahString,
+    type_: Type,
+    trace_topic_name: CheetahString,
+    rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+    batch_size: usize,
+    max_msg_size: usize,
+    host_producer: Option<ArcMut<DefaultMQProducerImpl>>,
+    host_consumer: Option<ArcMut<DefaultMQPushConsumerImpl>>,
+    namespace_v2: Option<CheetahString>,
+    trace_sender: mpsc::Sender<TraceContext>,
+    trace_receiver: Mutex<Option<mpsc::Receiver<TraceContext>>>,
+    discard_count: AtomicU64,
+    stopped: Arc<AtomicBool>,
+    flush_notify: Arc<Notify>,
+}
 
 impl AsyncTraceDispatcher {
     pub fn new(
@@ -36,25 +81,150 @@ impl AsyncTraceDispatcher {
         trace_topic_name: &str,
         rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
     ) -> Self {
-        AsyncTraceDispatcher
+        let trace_topic_name = if trace_topic_name.trim().is_empty() {
+            CheetahString::from_static_str(TopicValidator::RMQ_SYS_TRACE_TOPIC)
+        } else {
+            CheetahString::from_slice(trace_topic_name)
+        };
+        let (trace_sender, trace_receiver) = mpsc::channel(DEFAULT_QUEUE_SIZE);
+        AsyncTraceDispatcher {
+            group: CheetahString::from_slice(group),
+            type_,
+            trace_topic_name,
+            rpc_hook,
+            batch_size: DEFAULT_BATCH_SIZE,
+            max_msg_size: DEFAULT_MAX_MSG_SIZE,
+            host_producer: None,
+            host_consumer: None,
+            namespace_v2: None,
+            trace_sender,
+            trace_receiver: Mutex::new(Some(trace_receiver)),
+            discard_count: AtomicU64::new(0),
+            stopped: Arc::new(AtomicBool::new(false)),
+            flush_notify: Arc::new(Notify::new()),
+        }
+    }
+
+    fn build_trace_producer(&self, name_srv_addr: &str) -> DefaultMQProducer {
+        let type_name = match self.type_ {
+            Type::Produce => "PRODUCE",
+            Type::Consume => "CONSUME",
+        };
+        let client_config = ClientConfig {
+            namesrv_addr: Some(CheetahString::from_slice(name_srv_addr)),
+            instance_name: CheetahString::from_string(format!(
+                "{}_{}",
+                TraceConstants::TRACE_INSTANCE_NAME,
+                name_srv_addr
+            )),
+            namespace_v2: self.namespace_v2.clone(),
+            vip_channel_enabled: false,
+            enable_trace: false,
+            ..Default::default()
+        };
+        let mut trace_producer = DefaultMQProducer::builder()
+            .client_config(client_config)
+            .producer_group(format!(
+                "{}-{}-{}",
+                TraceConstants::GROUP_NAME_PREFIX,
+                self.group,
+                type_name
+            ))
+            .send_msg_timeout(TRACE_PRODUCER_SEND_TIMEOUT_MILLIS as u32)
+            .max_message_size(self.max_msg_size as u32)
+            .build();
+        if self.rpc_hook.is_some() {
+            trace_producer.set_rpc_hook(self.rpc_hook.clone());
+            let producer_impl = DefaultMQProducerImpl::new(
+                trace_producer.client_config().clone(),
+                trace_producer.producer_config().clone(),
+                self.rpc_hook.clone(),
+            );
+            trace_producer.set_default_mqproducer_impl(producer_impl);
+        }
+        trace_producer
+    }
+
+    pub fn trace_topic_name(&self) -> &CheetahString {
+        &self.trace_topic_name
+    }
+
+    pub fn host_producer(&self) -> Option<&ArcMut<DefaultMQProducerImpl>> {
+        self.host_producer.as_ref()
+    }
+
+    pub fn host_consumer(&self) -> Option<&ArcMut<DefaultMQPushConsumerImpl>> {
+        self.host_consumer.as_ref()
+    }
+
+    pub fn discard_count(&self) -> u64 {
+        self.discard_count.load(Ordering::Relaxed)
+    }
+
+    pub fn set_host_producer(&mut self, host_producer: ArcMut<DefaultMQProducerImpl>) {
+        self.host_producer = Some(host_producer);
+    }
+
+    pub fn set_host_consumer(&mut self, host_consumer: ArcMut<DefaultMQPushConsumerImpl>) {
+        self.host_consumer = Some(host_consumer);
+    }
+
+    pub fn set_namespace_v2(&mut self, namespace_v2: Option<CheetahString>) {
+        self.namespace_v2 = namespace_v2;
     }
 }
 
 impl TraceDispatcher for AsyncTraceDispatcher {
     fn start(&self, name_srv_addr: &str, access_channel: AccessChannel) -> crate::Result<()> {
-        todo!()
+        let Some(receiver) = self.trace_receiver.lock().take() else {
+            return Ok(());
+        };
+        let trace_producer = self.build_trace_producer(name_srv_addr);
+        let worker = TraceDispatchWorker {
+            trace_producer,
+            receiver,
+            trace_topic_name: self.trace_topic_name.clone(),
+            access_channel,
+            batch_size: self.batch_size,
+            max_msg_size: self.max_msg_size,
+            stopped: self.stopped.clone(),
+            flush_notify: self.flush_notify.clone(),
+        };
+        tokio::spawn(worker.run());
+        Ok(())
     }
 
     fn append(&self, ctx: &dyn Any) -> bool {
-        todo!()
+        let Some(ctx) = ctx.downcast_ref::<TraceContext>() else {
+            return false;
+        };
+        if self.stopped.load(Ordering::Acquire) {
+            return false;
+        }
+        match self.trace_sender.try_send(ctx.clone()) {
+            Ok(_) => true,
+            Err(_) => {
+                let discard_count = self.discard_count.fetch_add(1, Ordering::Relaxed) + 1;
+                if discard_count % 100 == 1 {
+                    warn!(
+                        "trace context queue is full, discard trace data, discardCount={}",
+                        discard_count
+                    );
+                }
+                false
+            }
+        }
     }
 
     fn flush(&self) -> crate::Result<()> {
-        todo!()
+        self.flush_notify.notify_one();
+        Ok(())
     }
 
     fn shutdown(&self) {
-        todo!()
+        if !self.stopped.swap(true, Ordering::AcqRel) {
+            self.flush_notify.notify_one();
+        }
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -66,9 +236,133 @@ impl TraceDispatcher for AsyncTraceDispatcher {
     }
 }
 
-impl AsyncTraceDispatcher {
-    pub fn set_host_producer(&mut self, host_producer: ArcMut<DefaultMQProducerImpl>) {}
-    pub fn set_host_consumer(&mut self, host_producer: ArcMut<DefaultMQPushConsumerImpl>) {}
+struct TraceDispatchWorker {
+    trace_producer: DefaultMQProducer,
+    receiver: mpsc::Receiver<TraceContext>,
+    trace_topic_name: CheetahString,
+    access_channel: AccessChannel,
+    batch_size: usize,
+    max_msg_size: usize,
+    stopped: Arc<AtomicBool>,
+    flush_notify: Arc<Notify>,
+}
 
-    pub fn set_namespace_v2(&mut self, namespace_v2: Option<CheetahString>) {}
+impl TraceDispatchWorker {
+    async fn run(mut self) {
+        if let Err(e) = self.trace_producer.start().await {
+            warn!("start trace producer failed: {}", e);
+            return;
+        }
+        let mut interval = tokio::time::interval(Duration::from_millis(WAIT_TIME_THRESHOLD_MILLIS));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut contexts = Vec::with_capacity(self.batch_size);
+        loop {
+            tokio::select! {
+                ctx = self.receiver.recv() => {
+                    match ctx {
+                        Some(ctx) => {
+                            contexts.push(ctx);
+                            if contexts.len() < self.batch_size {
+                                continue;
+                            }
+                        }
+                        None => self.stopped.store(true, Ordering::Release),
+                    }
+                }
+                _ = self.flush_notify.notified() => {}
+                _ = interval.tick() => {}
+            }
+            let stopped = self.stopped.load(Ordering::Acquire);
+            if stopped {
+                while let Ok(ctx) = self.receiver.try_recv() {
+                    contexts.push(ctx);
+                }
+            }
+            if !contexts.is_empty() {
+                let batch = std::mem::replace(&mut contexts, Vec::with_capacity(self.batch_size));
+                self.send_trace_data(batch).await;
+            }
+            if stopped {
+                break;
+            }
+        }
+        self.trace_producer.shutdown().await;
+        info!(
+            "trace dispatcher for topic {} stopped",
+            self.trace_topic_name
+        );
+    }
+
+    async fn send_trace_data(&mut self, contexts: Vec<TraceContext>) {
+        let mut trans_bean_map: HashMap<(CheetahString, CheetahString), Vec<TraceTransferBean>> =
+            HashMap::new();
+        for ctx in contexts {
+            let topic = match ctx.trace_beans.as_ref().and_then(|beans| beans.first()) {
+                Some(bean) => bean.topic.clone(),
+                None => continue,
+            };
+            if let Some(transfer_bean) = TraceDataEncoder::encoder_from_context_bean(&ctx) {
+                trans_bean_map
+                    .entry((topic, ctx.region_id.clone()))
+                    .or_default()
+                    .push(transfer_bean);
+            }
+        }
+        for ((_, region_id), transfer_beans) in trans_bean_map {
+            self.flush_data(transfer_beans, &region_id).await;
+        }
+    }
+
+    async fn flush_data(&mut self, transfer_beans: Vec<TraceTransferBean>, region_id: &str) {
+        let mut key_set = HashSet::new();
+        let mut buffer = String::with_capacity(1024);
+        for transfer_bean in transfer_beans {
+            key_set.extend(transfer_bean.trans_key);
+            buffer.push_str(&transfer_bean.trans_data);
+            if buffer.len() >= self.max_msg_size {
+                self.send_trace_data_by_mq(std::mem::take(&mut key_set), &buffer, region_id)
+                    .await;
+                buffer.clear();
+            }
+        }
+        if !buffer.is_empty() {
+            self.send_trace_data_by_mq(key_set, &buffer, region_id)
+                .await;
+        }
+    }
+
+    async fn send_trace_data_by_mq(
+        &mut self,
+        key_set: HashSet<CheetahString>,
+        data: &str,
+        region_id: &str,
+    ) {
+        let trace_topic = if self.access_channel == AccessChannel::Cloud {
+            CheetahString::from_string(format!(
+                "{}{}",
+                TraceConstants::TRACE_TOPIC_PREFIX,
+                region_id
+            ))
+        } else {
+            self.trace_topic_name.clone()
+        };
+        let mut message = Message::new(trace_topic.clone(), data.as_bytes());
+        message.set_keys(CheetahString::from_string(
+            key_set
+                .iter()
+                .map(|key| key.as_str())
+                .collect::<Vec<_>>()
+                .join(MessageConst::KEY_SEPARATOR),
+        ));
+        if let Err(e) = self
+            .trace_producer
+            .send_with_timeout(message, TRACE_PRODUCER_SEND_TIMEOUT_MILLIS)
+            .await
+        {
+            warn!(
+                "send trace data failed, topic={}, keys={:?}: {}",
+                trace_topic, key_set, e
+            );
+        }
+    }
 }
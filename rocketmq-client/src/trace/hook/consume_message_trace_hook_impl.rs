@@ -16,9 +16,20 @@
  */
 use std::sync::Arc;
 
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
+
+use crate::consumer::listener::consume_return_type::ConsumeReturnType;
 use crate::hook::consume_message_context::ConsumeMessageContext;
 use crate::hook::consume_message_hook::ConsumeMessageHook;
+use crate::trace::trace_bean::TraceBean;
+use crate::trace::trace_context::TraceContext;
 use crate::trace::trace_dispatcher::TraceDispatcher;
+use crate::trace::trace_type::TraceType;
 
 pub struct ConsumeMessageTraceHookImpl {
     trace_dispatcher: Arc<Box<dyn TraceDispatcher + Send + Sync>>,
@@ -32,14 +43,110 @@ impl ConsumeMessageTraceHookImpl {
 
 impl ConsumeMessageHook for ConsumeMessageTraceHookImpl {
     fn hook_name(&self) -> &str {
-        todo!()
+        "ConsumeMessageTraceHook"
     }
 
     fn consume_message_before(&self, context: Option<&mut ConsumeMessageContext>) {
-        todo!()
+        let Some(context) = context else {
+            return;
+        };
+        if context.msg_list.is_empty() {
+            return;
+        }
+        let mut trace_context = TraceContext {
+            trace_type: Some(TraceType::SubBefore),
+            group_name: CheetahString::from_string(NamespaceUtil::without_namespace(
+                context.consumer_group.as_str(),
+            )),
+            ..TraceContext::new()
+        };
+        let mut beans = Vec::with_capacity(context.msg_list.len());
+        for msg in context.msg_list {
+            let trace_on = msg.get_property(&CheetahString::from_static_str(
+                MessageConst::PROPERTY_TRACE_SWITCH,
+            ));
+            if trace_on.as_deref() == Some("false") {
+                continue;
+            }
+            if let Some(region_id) = msg.get_property(&CheetahString::from_static_str(
+                MessageConst::PROPERTY_MSG_REGION,
+            )) {
+                trace_context.region_id = region_id;
+            }
+            let message_ext = &msg.message_ext_inner;
+            beans.push(TraceBean {
+                topic: CheetahString::from_string(NamespaceUtil::without_namespace(
+                    msg.get_topic(),
+                )),
+                msg_id: msg.get_msg_id(),
+                tags: msg.get_tags().unwrap_or_default(),
+                keys: msg.get_keys().unwrap_or_default(),
+                store_time: message_ext.store_timestamp(),
+                body_length: message_ext.store_size(),
+                retry_times: message_ext.reconsume_times(),
+                ..Default::default()
+            });
+        }
+        if beans.is_empty() {
+            return;
+        }
+        trace_context.trace_beans = Some(beans);
+        trace_context.time_stamp = get_current_millis();
+        self.trace_dispatcher.append(&trace_context);
+        context.mq_trace_context = Some(Arc::new(Box::new(trace_context)));
     }
 
     fn consume_message_after(&self, context: Option<&mut ConsumeMessageContext>) {
-        todo!()
+        let Some(context) = context else {
+            return;
+        };
+        if context.msg_list.is_empty() {
+            return;
+        }
+        let Some(sub_before_context) = context
+            .mq_trace_context
+            .as_ref()
+            .and_then(|ctx| ctx.downcast_ref::<TraceContext>())
+        else {
+            return;
+        };
+        if sub_before_context.trace_beans.as_ref().map_or(0, Vec::len) == 0 {
+            return;
+        }
+        let cost_time = (get_current_millis().saturating_sub(sub_before_context.time_stamp)
+            / context.msg_list.len() as u64) as i32;
+        let context_code = context
+            .props
+            .get(mix_all::CONSUME_CONTEXT_TYPE)
+            .and_then(|context_type| consume_return_type_code(context_type))
+            .unwrap_or_default();
+        let sub_after_context = TraceContext {
+            trace_type: Some(TraceType::SubAfter),
+            region_id: sub_before_context.region_id.clone(),
+            group_name: CheetahString::from_string(NamespaceUtil::without_namespace(
+                sub_before_context.group_name.as_str(),
+            )),
+            request_id: sub_before_context.request_id.clone(),
+            access_channel: context.access_channel,
+            is_success: context.success,
+            cost_time,
+            context_code,
+            trace_beans: sub_before_context.trace_beans.clone(),
+            ..TraceContext::new()
+        };
+        self.trace_dispatcher.append(&sub_after_context);
     }
 }
+
+fn consume_return_type_code(context_type: &str) -> Option<i32> {
+    [
+        ConsumeReturnType::Success,
+        ConsumeReturnType::TimeOut,
+        ConsumeReturnType::Exception,
+        ConsumeReturnType::ReturnNull,
+        ConsumeReturnType::Failed,
+    ]
+    .into_iter()
+    .find(|return_type| return_type.to_string() == context_type)
+    .map(i32::from)
+}
@@ -16,9 +16,18 @@
  */
 use std::sync::Arc;
 
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_enum::MessageType;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
+
 use crate::hook::end_transaction_context::EndTransactionContext;
 use crate::hook::end_transaction_hook::EndTransactionHook;
+use crate::trace::async_trace_dispatcher::AsyncTraceDispatcher;
+use crate::trace::trace_bean::TraceBean;
+use crate::trace::trace_context::TraceContext;
 use crate::trace::trace_dispatcher::TraceDispatcher;
+use crate::trace::trace_type::TraceType;
 
 pub struct EndTransactionTraceHookImpl {
     trace_dispatcher: Arc<Box<dyn TraceDispatcher + Send + Sync>>,
@@ -36,6 +45,42 @@ impl EndTransactionHook for EndTransactionTraceHookImpl {
     }
 
     fn end_transaction(&self, context: &EndTransactionContext) {
-        todo!()
+        let is_trace_topic = self
+            .trace_dispatcher
+            .as_any()
+            .downcast_ref::<AsyncTraceDispatcher>()
+            .is_some_and(|dispatcher| {
+                context
+                    .message
+                    .get_topic()
+                    .starts_with(dispatcher.trace_topic_name().as_str())
+            });
+        if is_trace_topic {
+            return;
+        }
+        let message = context.message;
+        let trace_bean = TraceBean {
+            topic: CheetahString::from_string(NamespaceUtil::without_namespace(
+                message.get_topic(),
+            )),
+            tags: message.get_tags().unwrap_or_default(),
+            keys: message.get_keys().unwrap_or_default(),
+            store_host: context.broker_addr.clone(),
+            msg_type: Some(MessageType::TransMsgCommit),
+            msg_id: context.msg_id.clone(),
+            transaction_state: Some(context.transaction_state),
+            transaction_id: Some(context.transaction_id.clone()),
+            from_transaction_check: context.from_transaction_check,
+            ..Default::default()
+        };
+        let trace_context = TraceContext {
+            trace_type: Some(TraceType::EndTransaction),
+            group_name: CheetahString::from_string(NamespaceUtil::without_namespace(
+                context.producer_group.as_str(),
+            )),
+            trace_beans: Some(vec![trace_bean]),
+            ..TraceContext::new()
+        };
+        self.trace_dispatcher.append(&trace_context);
     }
 }
@@ -0,0 +1,276 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fmt::Write;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::MessageConst;
+
+use crate::base::access_channel::AccessChannel;
+use crate::trace::trace_constants::TraceConstants;
+use crate::trace::trace_context::TraceContext;
+use crate::trace::trace_transfer_bean::TraceTransferBean;
+use crate::trace::trace_type::TraceType;
+
+/// Encodes trace contexts into the text format stored on the trace topic.
+pub struct TraceDataEncoder;
+
+impl TraceDataEncoder {
+    /// Encodes a trace context, returning `None` when it carries no trace beans.
+    pub fn encoder_from_context_bean(ctx: &TraceContext) -> Option<TraceTransferBean> {
+        let trace_beans = ctx.trace_beans.as_ref().filter(|beans| !beans.is_empty())?;
+        let trace_type = ctx.trace_type?;
+        let content = TraceConstants::CONTENT_SPLITOR;
+        let field = TraceConstants::FIELD_SPLITOR;
+        let mut sb = String::with_capacity(1024);
+        match trace_type {
+            TraceType::Pub => {
+                let bean = &trace_beans[0];
+                let _ = write!(
+                    sb,
+                    "{trace_type}{content}{}{content}{}{content}{}{content}{}{content}{}{content}{}\
+                     {content}{}{content}{}{content}{}{content}{}{content}{}{content}{}{content}{}\
+                     {field}",
+                    ctx.time_stamp,
+                    ctx.region_id,
+                    ctx.group_name,
+                    bean.topic,
+                    bean.msg_id,
+                    bean.tags,
+                    bean.keys,
+                    bean.store_host,
+                    bean.body_length,
+                    ctx.cost_time,
+                    bean.msg_type.unwrap_or_default() as i32,
+                    bean.offset_msg_id,
+                    ctx.is_success,
+                );
+            }
+            TraceType::SubBefore => {
+                for bean in trace_beans {
+                    let _ = write!(
+                        sb,
+                        "{trace_type}{content}{}{content}{}{content}{}{content}{}{content}{}\
+                         {content}{}{content}{}{field}",
+                        ctx.time_stamp,
+                        ctx.region_id,
+                        ctx.group_name,
+                        ctx.request_id,
+                        bean.msg_id,
+                        bean.retry_times,
+                        bean.keys,
+                    );
+                }
+            }
+            TraceType::SubAfter => {
+                for bean in trace_beans {
+                    let _ = write!(
+                        sb,
+                        "{trace_type}{content}{}{content}{}{content}{}{content}{}{content}{}\
+                         {content}{}{content}",
+                        ctx.request_id,
+                        bean.msg_id,
+                        ctx.cost_time,
+                        ctx.is_success,
+                        bean.keys,
+                        ctx.context_code,
+                    );
+                    if ctx.access_channel != Some(AccessChannel::Cloud) {
+                        let _ = write!(sb, "{}{content}{}{field}", ctx.time_stamp, ctx.group_name);
+                    }
+                }
+            }
+            TraceType::EndTransaction => {
+                let bean = &trace_beans[0];
+                let _ = write!(
+                    sb,
+                    "{trace_type}{content}{}{content}{}{content}{}{content}{}{content}{}{content}{}\
+                     {content}{}{content}{}{content}{}{content}{}{content}{}{content}{}{field}",
+                    ctx.time_stamp,
+                    ctx.region_id,
+                    ctx.group_name,
+                    bean.topic,
+                    bean.msg_id,
+                    bean.tags,
+                    bean.keys,
+                    bean.store_host,
+                    bean.msg_type.unwrap_or_default() as i32,
+                    bean.transaction_id.clone().unwrap_or_default(),
+                    bean.transaction_state
+                        .map(|state| state.to_string())
+                        .unwrap_or_default(),
+                    bean.from_transaction_check,
+                );
+            }
+        }
+
+        let mut transfer_bean = TraceTransferBean {
+            trans_data: sb,
+            ..Default::default()
+        };
+        for bean in trace_beans {
+            transfer_bean.trans_key.insert(bean.msg_id.clone());
+            if !bean.keys.is_empty() {
+                transfer_bean.trans_key.extend(
+                    bean.keys
+                        .split(MessageConst::KEY_SEPARATOR)
+                        .filter(|key| !key.is_empty())
+                        .map(CheetahString::from_slice),
+                );
+            }
+        }
+        Some(transfer_bean)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::message::message_enum::MessageType;
+
+    use super::*;
+    use crate::producer::local_transaction_state::LocalTransactionState;
+    use crate::trace::trace_bean::TraceBean;
+
+    fn split_fields(data: &str) -> Vec<&str> {
+        data.trim_end_matches(TraceConstants::FIELD_SPLITOR)
+            .split(TraceConstants::CONTENT_SPLITOR)
+            .collect()
+    }
+
+    #[test]
+    fn encode_pub_context() {
+        let ctx = TraceContext {
+            trace_type: Some(TraceType::Pub),
+            time_stamp: 1000,
+            region_id: CheetahString::from("region"),
+            group_name: CheetahString::from("group"),
+            cost_time: 5,
+            is_success: true,
+            trace_beans: Some(vec![TraceBean {
+                topic: CheetahString::from("topic"),
+                msg_id: CheetahString::from("msg_id"),
+                offset_msg_id: CheetahString::from("offset_msg_id"),
+                tags: CheetahString::from("tag"),
+                keys: CheetahString::from("k1 k2"),
+                store_host: CheetahString::from("127.0.0.1:10911"),
+                body_length: 3,
+                msg_type: Some(MessageType::DelayMsg),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        let bean = TraceDataEncoder::encoder_from_context_bean(&ctx).unwrap();
+        assert!(bean.trans_data.ends_with(TraceConstants::FIELD_SPLITOR));
+        assert_eq!(
+            split_fields(&bean.trans_data),
+            vec![
+                "Pub",
+                "1000",
+                "region",
+                "group",
+                "topic",
+                "msg_id",
+                "tag",
+                "k1 k2",
+                "127.0.0.1:10911",
+                "3",
+                "5",
+                "3",
+                "offset_msg_id",
+                "true"
+            ]
+        );
+        assert_eq!(bean.trans_key.len(), 3);
+        assert!(bean.trans_key.contains("msg_id"));
+        assert!(bean.trans_key.contains("k1"));
+        assert!(bean.trans_key.contains("k2"));
+    }
+
+    #[test]
+    fn encode_sub_after_context_per_bean() {
+        let ctx = TraceContext {
+            trace_type: Some(TraceType::SubAfter),
+            time_stamp: 1000,
+            group_name: CheetahString::from("group"),
+            request_id: CheetahString::from("request"),
+            cost_time: 7,
+            is_success: false,
+            context_code: 2,
+            access_channel: Some(AccessChannel::Local),
+            trace_beans: Some(vec![
+                TraceBean {
+                    msg_id: CheetahString::from("m1"),
+                    ..Default::default()
+                },
+                TraceBean {
+                    msg_id: CheetahString::from("m2"),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+        let bean = TraceDataEncoder::encoder_from_context_bean(&ctx).unwrap();
+        let records: Vec<&str> = bean
+            .trans_data
+            .split_terminator(TraceConstants::FIELD_SPLITOR)
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[1]
+                .split(TraceConstants::CONTENT_SPLITOR)
+                .collect::<Vec<_>>(),
+            vec!["SubAfter", "request", "m2", "7", "false", "", "2", "1000", "group"]
+        );
+        assert_eq!(bean.trans_key.len(), 2);
+    }
+
+    #[test]
+    fn encode_end_transaction_context() {
+        let ctx = TraceContext {
+            trace_type: Some(TraceType::EndTransaction),
+            time_stamp: 1000,
+            group_name: CheetahString::from("group"),
+            trace_beans: Some(vec![TraceBean {
+                topic: CheetahString::from("topic"),
+                msg_id: CheetahString::from("msg_id"),
+                msg_type: Some(MessageType::TransMsgCommit),
+                transaction_id: Some(CheetahString::from("tx")),
+                transaction_state: Some(LocalTransactionState::CommitMessage),
+                from_transaction_check: true,
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        let bean = TraceDataEncoder::encoder_from_context_bean(&ctx).unwrap();
+        let fields = split_fields(&bean.trans_data);
+        assert_eq!(fields.len(), 13);
+        assert_eq!(fields[0], "EndTransaction");
+        assert_eq!(fields[9], "2");
+        assert_eq!(fields[10], "tx");
+        assert_eq!(fields[11], "COMMIT_MESSAGE");
+        assert_eq!(fields[12], "true");
+    }
+
+    #[test]
+    fn encode_without_beans_returns_none() {
+        let ctx = TraceContext {
+            trace_type: Some(TraceType::Pub),
+            trace_beans: Some(vec![]),
+            ..Default::default()
+        };
+        assert!(TraceDataEncoder::encoder_from_context_bean(&ctx).is_none());
+    }
+}
@@ -19,6 +19,7 @@ use std::any::Any;
 use crate::base::access_channel::AccessChannel;
 use crate::Result;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Type {
     Produce,
     Consume,
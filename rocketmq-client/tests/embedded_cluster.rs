@@ -0,0 +1,39 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_client_rust::producer::default_mq_producer::DefaultMQProducer;
+use rocketmq_client_rust::producer::mq_producer::MQProducer;
+use rocketmq_client_rust::producer::send_status::SendStatus;
+use rocketmq_common::common::message::message_single::Message;
+use rocketmq_test_util::EmbeddedCluster;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn producer_sends_to_embedded_broker() {
+    let cluster = EmbeddedCluster::start().await.unwrap();
+
+    let mut producer = DefaultMQProducer::builder()
+        .producer_group("embedded_cluster_producer".to_string())
+        .name_server_addr(cluster.namesrv_addr())
+        .build();
+    producer.start().await.unwrap();
+
+    let message = Message::with_tags("EmbeddedClusterTopic", "TagA", "Hello RocketMQ".as_bytes());
+    let send_result = producer.send_with_timeout(message, 5000).await.unwrap();
+    assert_eq!(send_result.send_status, SendStatus::SendOk);
+
+    producer.shutdown().await;
+    cluster.shutdown().await;
+}
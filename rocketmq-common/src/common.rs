@@ -35,8 +35,10 @@ pub mod broker;
 pub mod compression;
 pub mod config;
 pub mod config_manager;
+pub mod config_value;
 pub mod constant;
 pub mod consumer;
+pub mod controller;
 mod faq;
 pub mod filter;
 pub mod future;
@@ -44,6 +46,7 @@ pub mod hasher;
 pub mod key_builder;
 pub mod macros;
 pub mod message;
+pub mod metrics;
 pub mod mix_all;
 pub mod mq_version;
 pub mod namesrv;
@@ -56,8 +59,10 @@ pub mod stats;
 pub mod sys_flag;
 
 pub mod system_clock;
+pub mod telemetry;
 pub mod thread;
 pub mod topic;
+pub mod validation;
 
 #[derive(Clone, Default, Eq, PartialEq, Copy)]
 pub enum TopicFilterType {
@@ -134,7 +139,7 @@ impl<'de> Deserialize<'de> for TopicFilterType {
             type Value = TopicFilterType;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("a string representing TopicFilterType")
+                formatter.write_str("a name or ordinal of TopicFilterType")
             }
 
             fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -150,9 +155,23 @@ impl<'de> Deserialize<'de> for TopicFilterType {
                     )),
                 }
             }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match value {
+                    0 => Ok(TopicFilterType::SingleTag),
+                    1 => Ok(TopicFilterType::MultiTag),
+                    _ => Err(serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Unsigned(value),
+                        &self,
+                    )),
+                }
+            }
         }
 
-        deserializer.deserialize_str(TopicFilterTypeVisitor)
+        deserializer.deserialize_any(TopicFilterTypeVisitor)
     }
 }
 
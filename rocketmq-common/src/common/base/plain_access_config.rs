@@ -21,7 +21,7 @@ use serde::Deserialize;
 use serde::Serialize;
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct PlainAccessConfig {
     pub access_key: Option<CheetahString>,
     pub secret_key: Option<CheetahString>,
@@ -16,6 +16,7 @@
  */
 
 use std::any::Any;
+use std::cmp;
 use std::collections::HashMap;
 
 use cheetah_string::CheetahString;
@@ -23,8 +24,11 @@ use lazy_static::lazy_static;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::common::config_value::AtomicConfigValue;
+use crate::common::config_value::SwapConfigValue;
 use crate::common::constant::PermName;
 use crate::common::message::message_enum::MessageRequestMode;
+use crate::common::metrics::metrics_exporter_type::MetricsExporterType;
 use crate::common::mix_all;
 use crate::common::mix_all::NAMESRV_ADDR_PROPERTY;
 use crate::common::server::config::ServerConfig;
@@ -123,7 +127,7 @@ pub struct BrokerConfig {
     pub broker_name: CheetahString,
     pub region_id: CheetahString,
     pub trace_on: bool,
-    pub broker_permission: u32,
+    pub broker_permission: AtomicConfigValue<u32>,
     pub async_send_enable: bool,
     pub store_path_root_dir: CheetahString,
     pub enable_split_registration: bool,
@@ -134,13 +138,13 @@ pub struct BrokerConfig {
     pub recover_concurrently: bool,
     pub duplication_enable: bool,
     pub start_accept_send_request_time_stamp: i64,
-    pub auto_create_topic_enable: bool,
+    pub auto_create_topic_enable: AtomicConfigValue<bool>,
     pub enable_single_topic_register: bool,
     pub broker_topic_enable: bool,
     pub cluster_topic_enable: bool,
     pub revive_queue_num: u32,
     pub enable_slave_acting_master: bool,
-    pub reject_transaction_message: bool,
+    pub reject_transaction_message: AtomicConfigValue<bool>,
     pub enable_detail_stat: bool,
     pub flush_consumer_offset_interval: u64,
     pub force_register: bool,
@@ -149,23 +153,30 @@ pub struct BrokerConfig {
     pub namesrv_addr: Option<CheetahString>,
     pub fetch_name_srv_addr_by_dns_lookup: bool,
     pub lite_pull_message_enable: bool,
-    pub auto_create_subscription_group: bool,
+    pub auto_create_subscription_group: AtomicConfigValue<bool>,
     pub channel_expired_timeout: u64,
     pub subscription_expired_timeout: u64,
     pub enable_property_filter: bool,
     pub filter_support_retry: bool,
     pub use_server_side_reset_offset: bool,
-    pub slave_read_enable: bool,
-    pub commercial_base_count: i32,
+    pub slave_read_enable: AtomicConfigValue<bool>,
+    /// Cold bytes a consumer group may read per second before its pulls are throttled.
+    pub cg_cold_read_threshold: AtomicConfigValue<i64>,
+    /// Cold bytes all consumer groups together may read per second before pulls are throttled.
+    pub global_cold_read_threshold: AtomicConfigValue<i64>,
+    pub commercial_base_count: AtomicConfigValue<i32>,
     pub reject_pull_consumer_enable: bool,
     pub consumer_offset_update_version_step: i64,
     pub enable_broadcast_offset_store: bool,
     pub transfer_msg_by_heap: bool,
     pub short_polling_time_mills: u64,
-    pub long_polling_enable: bool,
+    pub long_polling_enable: AtomicConfigValue<bool>,
     pub max_error_rate_of_bloom_filter: i32,
     pub expect_consumer_num_use_filter: i32,
     pub bit_map_length_consume_queue_ext: i32,
+    /// Evaluate SQL filters at dispatch time and store the matching groups as a bloom filter
+    /// bitmap in the consume queue ext, see `enable_consume_queue_ext` of the store.
+    pub enable_calc_filter_bit_map: bool,
     pub validate_system_topic_when_update_topic: bool,
     pub enable_mixed_message_type: bool,
     pub auto_delete_unused_stats: bool,
@@ -178,6 +189,39 @@ pub struct BrokerConfig {
     pub default_pop_share_queue_num: i32,
     pub load_balance_poll_name_server_interval: u64,
     pub server_load_balancer_enable: bool,
+    pub notify_consumer_ids_changed_enable: bool,
+    pub real_time_notify_consumer_change: bool,
+    pub send_message_thread_pool_nums: u32,
+    pub pull_message_thread_pool_nums: u32,
+    pub send_thread_pool_queue_capacity: u32,
+    pub pull_thread_pool_queue_capacity: u32,
+    pub broker_fast_failure_enable: bool,
+    pub wait_time_mills_in_send_queue: AtomicConfigValue<u64>,
+    pub wait_time_mills_in_pull_queue: AtomicConfigValue<u64>,
+    /// How long a shutdown waits for in-flight requests before closing the stores.
+    pub shutdown_drain_timeout_mills: AtomicConfigValue<u64>,
+    /// Sends per second accepted for each topic, 0 disables the limit.
+    pub topic_send_rate_limit: AtomicConfigValue<u64>,
+    /// Per topic overrides of `topic_send_rate_limit` as `topic:permits` pairs separated by
+    /// `;`.
+    pub topic_send_rate_limit_overrides: SwapConfigValue<CheetahString>,
+    /// Pulls per second accepted for each consumer group, 0 disables the limit.
+    pub group_pull_rate_limit: AtomicConfigValue<u64>,
+    /// Per group overrides of `group_pull_rate_limit` as `group:permits` pairs separated by
+    /// `;`.
+    pub group_pull_rate_limit_overrides: SwapConfigValue<CheetahString>,
+    pub controller_addr: Option<CheetahString>,
+    pub controller_heartbeat_timeout_mills: u64,
+    pub broker_heartbeat_interval: u64,
+    pub broker_election_priority: i32,
+    pub sync_controller_metadata_period: u64,
+    pub compressed_register: bool,
+    pub broker_not_active_timeout_millis: i64,
+    pub acl_enable: bool,
+    pub metrics_exporter_type: MetricsExporterType,
+    pub metrics_prom_exporter_host: CheetahString,
+    pub metrics_prom_exporter_port: u16,
+    pub enable_telemetry: bool,
 }
 
 impl Default for BrokerConfig {
@@ -204,7 +248,7 @@ impl Default for BrokerConfig {
             broker_name: default_broker_name().into(),
             region_id: CheetahString::from_static_str(mix_all::DEFAULT_TRACE_REGION_ID),
             trace_on: true,
-            broker_permission: PermName::PERM_WRITE | PermName::PERM_READ,
+            broker_permission: AtomicConfigValue::new(PermName::PERM_WRITE | PermName::PERM_READ),
             async_send_enable: false,
             store_path_root_dir: dirs::home_dir()
                 .unwrap()
@@ -220,13 +264,13 @@ impl Default for BrokerConfig {
             recover_concurrently: false,
             duplication_enable: false,
             start_accept_send_request_time_stamp: 0,
-            auto_create_topic_enable: true,
+            auto_create_topic_enable: AtomicConfigValue::new(true),
             enable_single_topic_register: true,
             broker_topic_enable: true,
             cluster_topic_enable: true,
             revive_queue_num: 8,
             enable_slave_acting_master: false,
-            reject_transaction_message: false,
+            reject_transaction_message: AtomicConfigValue::new(false),
             enable_detail_stat: true,
             flush_consumer_offset_interval: 1000 * 5,
             force_register: true,
@@ -235,23 +279,26 @@ impl Default for BrokerConfig {
             namesrv_addr: NAMESRV_ADDR.clone().map(|addr| addr.into()),
             fetch_name_srv_addr_by_dns_lookup: false,
             lite_pull_message_enable: true,
-            auto_create_subscription_group: true,
+            auto_create_subscription_group: AtomicConfigValue::new(true),
             channel_expired_timeout: 1000 * 120,
             subscription_expired_timeout: 1000 * 60 * 10,
             enable_property_filter: false,
             filter_support_retry: false,
             use_server_side_reset_offset: true,
-            slave_read_enable: false,
-            commercial_base_count: 1,
+            slave_read_enable: AtomicConfigValue::new(false),
+            cg_cold_read_threshold: AtomicConfigValue::new(3 * 1024 * 1024),
+            global_cold_read_threshold: AtomicConfigValue::new(100 * 1024 * 1024),
+            commercial_base_count: AtomicConfigValue::new(1),
             reject_pull_consumer_enable: false,
             consumer_offset_update_version_step: 500,
             enable_broadcast_offset_store: true,
             transfer_msg_by_heap: true,
             short_polling_time_mills: 1000,
-            long_polling_enable: true,
+            long_polling_enable: AtomicConfigValue::new(true),
             max_error_rate_of_bloom_filter: 20,
             expect_consumer_num_use_filter: 32,
             bit_map_length_consume_queue_ext: 64,
+            enable_calc_filter_bit_map: false,
             forward_timeout: 3 * 1000,
             validate_system_topic_when_update_topic: true,
             enable_mixed_message_type: false,
@@ -264,6 +311,32 @@ impl Default for BrokerConfig {
             default_pop_share_queue_num: -1,
             load_balance_poll_name_server_interval: 30_000,
             server_load_balancer_enable: true,
+            notify_consumer_ids_changed_enable: true,
+            real_time_notify_consumer_change: true,
+            send_message_thread_pool_nums: cmp::min(num_cpus::get() as u32, 4),
+            pull_message_thread_pool_nums: 16 + num_cpus::get() as u32 * 2,
+            send_thread_pool_queue_capacity: 10000,
+            pull_thread_pool_queue_capacity: 100000,
+            broker_fast_failure_enable: true,
+            wait_time_mills_in_send_queue: AtomicConfigValue::new(200),
+            wait_time_mills_in_pull_queue: AtomicConfigValue::new(5 * 1000),
+            shutdown_drain_timeout_mills: AtomicConfigValue::new(10 * 1000),
+            topic_send_rate_limit: AtomicConfigValue::new(0),
+            topic_send_rate_limit_overrides: SwapConfigValue::default(),
+            group_pull_rate_limit: AtomicConfigValue::new(0),
+            group_pull_rate_limit_overrides: SwapConfigValue::default(),
+            controller_addr: None,
+            controller_heartbeat_timeout_mills: 10 * 1000,
+            broker_heartbeat_interval: 1000,
+            broker_election_priority: i32::MAX,
+            sync_controller_metadata_period: 10 * 1000,
+            compressed_register: false,
+            broker_not_active_timeout_millis: 10 * 1000,
+            acl_enable: false,
+            metrics_exporter_type: MetricsExporterType::Disable,
+            metrics_prom_exporter_host: CheetahString::new(),
+            metrics_prom_exporter_port: 5557,
+            enable_telemetry: false,
         }
     }
 }
@@ -298,7 +371,7 @@ impl BrokerConfig {
     }
 
     pub fn broker_permission(&self) -> u32 {
-        self.broker_permission
+        self.broker_permission.get()
     }
 
     pub fn get_broker_addr(&self) -> String {
@@ -309,6 +382,89 @@ impl BrokerConfig {
         self.start_accept_send_request_time_stamp
     }
 
+    /// Update a property which can be changed at runtime, `Ok(false)` if the key is not one of
+    /// them. Readers pick up the new value on their next access, so keys which are only read at
+    /// startup are not accepted here.
+    pub fn update_property(&self, key: &str, value: &str) -> Result<bool, String> {
+        match key {
+            "brokerPermission" => self
+                .broker_permission
+                .set(mix_all::parse_property_value(key, value)?),
+            "autoCreateTopicEnable" => self
+                .auto_create_topic_enable
+                .set(mix_all::parse_property_value(key, value)?),
+            "autoCreateSubscriptionGroup" => self
+                .auto_create_subscription_group
+                .set(mix_all::parse_property_value(key, value)?),
+            "rejectTransactionMessage" => self
+                .reject_transaction_message
+                .set(mix_all::parse_property_value(key, value)?),
+            "slaveReadEnable" => self
+                .slave_read_enable
+                .set(mix_all::parse_property_value(key, value)?),
+            "cgColdReadThreshold" => self
+                .cg_cold_read_threshold
+                .set(mix_all::parse_property_value(key, value)?),
+            "globalColdReadThreshold" => self
+                .global_cold_read_threshold
+                .set(mix_all::parse_property_value(key, value)?),
+            "longPollingEnable" => self
+                .long_polling_enable
+                .set(mix_all::parse_property_value(key, value)?),
+            "waitTimeMillsInSendQueue" => self
+                .wait_time_mills_in_send_queue
+                .set(mix_all::parse_property_value(key, value)?),
+            "waitTimeMillsInPullQueue" => self
+                .wait_time_mills_in_pull_queue
+                .set(mix_all::parse_property_value(key, value)?),
+            "shutdownDrainTimeoutMills" => self
+                .shutdown_drain_timeout_mills
+                .set(mix_all::parse_property_value(key, value)?),
+            "commercialBaseCount" => self
+                .commercial_base_count
+                .set(mix_all::parse_property_value(key, value)?),
+            "topicSendRateLimit" => self
+                .topic_send_rate_limit
+                .set(mix_all::parse_property_value(key, value)?),
+            "topicSendRateLimitOverrides" => {
+                Self::parse_rate_limit_overrides(key, value)?;
+                self.topic_send_rate_limit_overrides
+                    .set(value.trim().into());
+            }
+            "groupPullRateLimit" => self
+                .group_pull_rate_limit
+                .set(mix_all::parse_property_value(key, value)?),
+            "groupPullRateLimitOverrides" => {
+                Self::parse_rate_limit_overrides(key, value)?;
+                self.group_pull_rate_limit_overrides
+                    .set(value.trim().into());
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Parses rate limit overrides in the `name:permits;name:permits` format.
+    pub fn parse_rate_limit_overrides(
+        key: &str,
+        value: &str,
+    ) -> Result<HashMap<CheetahString, u64>, String> {
+        value
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (name, permits) = entry
+                    .rsplit_once(':')
+                    .ok_or_else(|| format!("Invalid value '{}' for key '{}'", value, key))?;
+                Ok((
+                    CheetahString::from(name.trim()),
+                    mix_all::parse_property_value(key, permits)?,
+                ))
+            })
+            .collect()
+    }
+
     pub fn get_properties(&self) -> HashMap<CheetahString, CheetahString> {
         let mut properties = HashMap::new();
         properties.insert("brokerName".into(), self.broker_name.clone());
@@ -372,7 +528,7 @@ impl BrokerConfig {
         properties.insert("traceOn".into(), self.trace_on.to_string().into());
         properties.insert(
             "brokerPermission".into(),
-            self.broker_permission.to_string().into(),
+            self.broker_permission.get().to_string().into(),
         );
         properties.insert(
             "asyncSendEnable".into(),
@@ -413,7 +569,7 @@ impl BrokerConfig {
         );
         properties.insert(
             "autoCreateTopicEnable".into(),
-            self.auto_create_topic_enable.to_string().into(),
+            self.auto_create_topic_enable.get().to_string().into(),
         );
         properties.insert(
             "enableSingleTopicRegister".into(),
@@ -437,7 +593,7 @@ impl BrokerConfig {
         );
         properties.insert(
             "rejectTransactionMessage".into(),
-            self.reject_transaction_message.to_string().into(),
+            self.reject_transaction_message.get().to_string().into(),
         );
         properties.insert(
             "enableDetailStat".into(),
@@ -473,7 +629,7 @@ impl BrokerConfig {
         );
         properties.insert(
             "autoCreateSubscriptionGroup".into(),
-            self.auto_create_subscription_group.to_string().into(),
+            self.auto_create_subscription_group.get().to_string().into(),
         );
         properties.insert(
             "channelExpiredTimeout".into(),
@@ -497,11 +653,19 @@ impl BrokerConfig {
         );
         properties.insert(
             "slaveReadEnable".into(),
-            self.slave_read_enable.to_string().into(),
+            self.slave_read_enable.get().to_string().into(),
+        );
+        properties.insert(
+            "cgColdReadThreshold".into(),
+            self.cg_cold_read_threshold.get().to_string().into(),
+        );
+        properties.insert(
+            "globalColdReadThreshold".into(),
+            self.global_cold_read_threshold.get().to_string().into(),
         );
         properties.insert(
             "commercialBaseCount".into(),
-            self.commercial_base_count.to_string().into(),
+            self.commercial_base_count.get().to_string().into(),
         );
         properties.insert(
             "rejectPullConsumerEnable".into(),
@@ -525,7 +689,7 @@ impl BrokerConfig {
         );
         properties.insert(
             "longPollingEnable".into(),
-            self.long_polling_enable.to_string().into(),
+            self.long_polling_enable.get().to_string().into(),
         );
         properties.insert(
             "maxErrorRateOfBloomFilter".into(),
@@ -539,6 +703,10 @@ impl BrokerConfig {
             "bitMapLengthConsumeQueueExt".into(),
             self.bit_map_length_consume_queue_ext.to_string().into(),
         );
+        properties.insert(
+            "enableCalcFilterBitMap".into(),
+            self.enable_calc_filter_bit_map.to_string().into(),
+        );
         properties.insert(
             "validateSystemTopicWhenUpdateTopic".into(),
             self.validate_system_topic_when_update_topic
@@ -557,6 +725,91 @@ impl BrokerConfig {
             "forwardTimeout".into(),
             self.forward_timeout.to_string().into(),
         );
+        properties.insert(
+            "notifyConsumerIdsChangedEnable".into(),
+            self.notify_consumer_ids_changed_enable.to_string().into(),
+        );
+        properties.insert(
+            "realTimeNotifyConsumerChange".into(),
+            self.real_time_notify_consumer_change.to_string().into(),
+        );
+        properties.insert(
+            "sendMessageThreadPoolNums".into(),
+            self.send_message_thread_pool_nums.to_string().into(),
+        );
+        properties.insert(
+            "pullMessageThreadPoolNums".into(),
+            self.pull_message_thread_pool_nums.to_string().into(),
+        );
+        properties.insert(
+            "sendThreadPoolQueueCapacity".into(),
+            self.send_thread_pool_queue_capacity.to_string().into(),
+        );
+        properties.insert(
+            "pullThreadPoolQueueCapacity".into(),
+            self.pull_thread_pool_queue_capacity.to_string().into(),
+        );
+        properties.insert(
+            "brokerFastFailureEnable".into(),
+            self.broker_fast_failure_enable.to_string().into(),
+        );
+        properties.insert(
+            "waitTimeMillsInSendQueue".into(),
+            self.wait_time_mills_in_send_queue.get().to_string().into(),
+        );
+        properties.insert(
+            "waitTimeMillsInPullQueue".into(),
+            self.wait_time_mills_in_pull_queue.get().to_string().into(),
+        );
+        properties.insert(
+            "shutdownDrainTimeoutMills".into(),
+            self.shutdown_drain_timeout_mills.get().to_string().into(),
+        );
+        properties.insert(
+            "topicSendRateLimit".into(),
+            self.topic_send_rate_limit.get().to_string().into(),
+        );
+        properties.insert(
+            "topicSendRateLimitOverrides".into(),
+            self.topic_send_rate_limit_overrides.get().as_ref().clone(),
+        );
+        properties.insert(
+            "groupPullRateLimit".into(),
+            self.group_pull_rate_limit.get().to_string().into(),
+        );
+        properties.insert(
+            "groupPullRateLimitOverrides".into(),
+            self.group_pull_rate_limit_overrides.get().as_ref().clone(),
+        );
+        properties.insert(
+            "controllerAddr".into(),
+            self.controller_addr.clone().unwrap_or_default(),
+        );
+        properties.insert(
+            "controllerHeartbeatTimeoutMills".into(),
+            self.controller_heartbeat_timeout_mills.to_string().into(),
+        );
+        properties.insert(
+            "brokerHeartbeatInterval".into(),
+            self.broker_heartbeat_interval.to_string().into(),
+        );
+        properties.insert(
+            "brokerElectionPriority".into(),
+            self.broker_election_priority.to_string().into(),
+        );
+        properties.insert(
+            "syncControllerMetadataPeriod".into(),
+            self.sync_controller_metadata_period.to_string().into(),
+        );
+        properties.insert(
+            "compressedRegister".into(),
+            self.compressed_register.to_string().into(),
+        );
+        properties.insert(
+            "brokerNotActiveTimeoutMillis".into(),
+            self.broker_not_active_timeout_millis.to_string().into(),
+        );
+        properties.insert("aclEnable".into(), self.acl_enable.to_string().into());
         properties
     }
 }
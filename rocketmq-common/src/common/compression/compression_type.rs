@@ -1,19 +1,8 @@
-use std::io::Read;
-use std::io::Write;
-use std::io::{self};
-
-use bytes::buf::Reader;
-use bytes::buf::Writer;
-use bytes::Buf;
-use bytes::BufMut;
 use bytes::Bytes;
-use flate2::read::ZlibDecoder;
-use flate2::write::ZlibEncoder;
-use flate2::Compression;
-use lz4_flex::compress_prepend_size;
-use lz4_flex::decompress_size_prepended;
 
+use crate::common::compression::compressor_factory::CompressorFactory;
 use crate::common::sys_flag::message_sys_flag::MessageSysFlag;
+use crate::Result;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum CompressionType {
@@ -49,85 +38,11 @@ impl CompressionType {
         }
     }
 
-    pub fn compression(&self, data: &[u8]) -> Bytes {
-        match self {
-            CompressionType::LZ4 => {
-                let compressed = compress_prepend_size(data);
-                Bytes::from(compressed)
-            }
-            CompressionType::Zstd => {
-                let compressed = zstd::encode_all(data.reader(), 5).unwrap();
-                Bytes::from(compressed)
-            }
-            CompressionType::Zlib => {
-                let mut zlib_encoder = ZlibEncoder::new(Vec::new(), Compression::default());
-                let _ = zlib_encoder.write_all(data);
-                let result = zlib_encoder.finish().unwrap();
-                Bytes::from(result)
-            }
-        }
-    }
-
-    pub fn decompression(&self, data: &[u8]) -> Bytes {
-        match self {
-            CompressionType::LZ4 => {
-                let compressed = decompress_size_prepended(data).unwrap();
-                Bytes::from(compressed)
-            }
-            CompressionType::Zstd => {
-                let compressed = zstd::decode_all(data.reader()).unwrap();
-                Bytes::from(compressed)
-            }
-            CompressionType::Zlib => {
-                let mut zlib_encoder = ZlibDecoder::new(data.reader());
-                let mut decompressed_data = Vec::new();
-                zlib_encoder.read_to_end(&mut decompressed_data).unwrap();
-                Bytes::from(decompressed_data)
-            }
-        }
-    }
-}
-
-struct BytesWriter {
-    bytes: Bytes,
-}
-
-impl BytesWriter {
-    fn new(bytes: Bytes) -> Self {
-        Self { bytes }
+    pub fn compression(&self, data: &[u8], level: i32) -> Result<Bytes> {
+        CompressorFactory::get_compressor(*self).compress(data, level)
     }
-}
-
-impl Write for BytesWriter {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        let mut bytes_mut = self.bytes.to_vec();
-        bytes_mut.extend_from_slice(buf);
-        self.bytes = Bytes::from(bytes_mut);
-        Ok(buf.len())
-    }
-
-    fn flush(&mut self) -> std::io::Result<()> {
-        Ok(())
-    }
-}
-
-struct BytesReader {
-    bytes: Bytes,
-    position: usize,
-}
-
-impl BytesReader {
-    fn new(bytes: Bytes) -> Self {
-        Self { bytes, position: 0 }
-    }
-}
 
-impl Read for BytesReader {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        let remaining_bytes = &self.bytes[self.position..];
-        let bytes_to_copy = std::cmp::min(buf.len(), remaining_bytes.len());
-        buf[..bytes_to_copy].copy_from_slice(&remaining_bytes[..bytes_to_copy]);
-        self.position += bytes_to_copy;
-        Ok(bytes_to_copy)
+    pub fn decompression(&self, data: &[u8]) -> Result<Bytes> {
+        CompressorFactory::get_compressor(*self).decompress(data)
     }
 }
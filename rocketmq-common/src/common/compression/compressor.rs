@@ -28,7 +28,7 @@ pub trait Compressor {
     ///
     /// # Returns
     ///
-    /// Compressed byte data or an `std::io::Error`.
+    /// Compressed byte data or an error when compression fails.
     fn compress(&self, src: &[u8], level: i32) -> Result<Bytes>;
 
     /// Decompress message by different compressor.
@@ -39,6 +39,6 @@ pub trait Compressor {
     ///
     /// # Returns
     ///
-    /// Decompressed byte data or an `std::io::Error`.
+    /// Decompressed byte data or an error when `src` is not valid compressed data.
     fn decompress(&self, src: &[u8]) -> Result<Bytes>;
 }
@@ -14,20 +14,83 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::io::Read;
+use std::io::Write;
+
 use bytes::Bytes;
+use lz4_flex::frame::BlockMode;
+use lz4_flex::frame::BlockSize;
+use lz4_flex::frame::FrameDecoder;
+use lz4_flex::frame::FrameEncoder;
+use lz4_flex::frame::FrameInfo;
 
-use crate::common::compression::compression_type::CompressionType;
 use crate::common::compression::compressor::Compressor;
+use crate::error::Error;
 use crate::Result;
 
+/// LZ4 compressor writing the LZ4 frame format, the format of `LZ4FrameOutputStream` used by
+/// the Java client: independent blocks of at most 4 MiB without checksums. LZ4 has no
+/// compression level, so the level is ignored.
 pub struct Lz4Compressor;
 
 impl Compressor for Lz4Compressor {
-    fn compress(&self, src: &[u8], level: i32) -> Result<Bytes> {
-        Ok(CompressionType::LZ4.compression(src))
+    fn compress(&self, src: &[u8], _level: i32) -> Result<Bytes> {
+        let frame_info = FrameInfo::new()
+            .block_size(BlockSize::Max4MB)
+            .block_mode(BlockMode::Independent);
+        let mut encoder =
+            FrameEncoder::with_frame_info(frame_info, Vec::with_capacity(src.len() + 16));
+        encoder
+            .write_all(src)
+            .map_err(|e| Error::RuntimeException(format!("lz4 compression failed: {}", e)))?;
+        let frame = encoder
+            .finish()
+            .map_err(|e| Error::RuntimeException(format!("lz4 compression failed: {}", e)))?;
+        Ok(Bytes::from(frame))
     }
 
     fn decompress(&self, src: &[u8]) -> Result<Bytes> {
-        Ok(CompressionType::LZ4.decompression(src))
+        let mut content = Vec::with_capacity(src.len() * 2);
+        FrameDecoder::new(src)
+            .read_to_end(&mut content)
+            .map_err(|e| Error::RuntimeException(format!("lz4 decompression failed: {}", e)))?;
+        Ok(Bytes::from(content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compress_round_trip() {
+        let body = "RocketMQ message body ".repeat(1000).into_bytes();
+        let compressed = Lz4Compressor.compress(&body, 5).unwrap();
+        assert!(compressed.len() < body.len());
+        assert_eq!(&compressed[..4], &0x184D2204u32.to_le_bytes());
+        assert_eq!(Lz4Compressor.decompress(&compressed).unwrap(), body);
+
+        let compressed = Lz4Compressor.compress(b"", 5).unwrap();
+        assert!(Lz4Compressor.decompress(&compressed).unwrap().is_empty());
+    }
+
+    #[test]
+    fn decompress_frame_with_uncompressed_block_and_checksums() {
+        // `printf 'hello lz4' | lz4 -c -BX`, an uncompressed block with block and content
+        // checksums
+        let mut frame = vec![
+            0x04, 0x22, 0x4d, 0x18, 0x74, 0x40, 0xbd, 0x09, 0x00, 0x00, 0x80, 0x68, 0x65, 0x6c,
+            0x6c, 0x6f, 0x20, 0x6c, 0x7a, 0x34, 0x79, 0x8d, 0x85, 0xaf, 0x00, 0x00, 0x00, 0x00,
+            0x79, 0x8d, 0x85, 0xaf,
+        ];
+        assert_eq!(
+            Lz4Compressor.decompress(&frame).unwrap().as_ref(),
+            b"hello lz4"
+        );
+
+        let last = frame.len() - 1;
+        frame[last] ^= 0xFF;
+        assert!(Lz4Compressor.decompress(&frame).is_err());
+        assert!(Lz4Compressor.decompress(b"not lz4").is_err());
     }
 }
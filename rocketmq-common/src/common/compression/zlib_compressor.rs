@@ -14,22 +14,57 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use std::io::Error;
+use std::io::Read;
+use std::io::Write;
 
 use bytes::Bytes;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 
-use crate::common::compression::compression_type::CompressionType;
 use crate::common::compression::compressor::Compressor;
+use crate::error::Error;
 use crate::Result;
 
+/// Zlib compressor, the `Deflater` of the Java client. Levels range from 0 to 9, other levels
+/// use the default level.
 pub struct ZlibCompressor;
 
 impl Compressor for ZlibCompressor {
     fn compress(&self, src: &[u8], level: i32) -> Result<Bytes> {
-        Ok(CompressionType::Zlib.compression(src))
+        let compression = match level {
+            0..=9 => Compression::new(level as u32),
+            _ => Compression::default(),
+        };
+        let mut encoder = ZlibEncoder::new(Vec::with_capacity(src.len() / 2), compression);
+        encoder
+            .write_all(src)
+            .and_then(|_| encoder.finish())
+            .map(Bytes::from)
+            .map_err(|e| Error::RuntimeException(format!("zlib compression failed: {}", e)))
     }
 
     fn decompress(&self, src: &[u8]) -> Result<Bytes> {
-        Ok(CompressionType::Zlib.decompression(src))
+        let mut decompressed = Vec::with_capacity(src.len() * 2);
+        ZlibDecoder::new(src)
+            .read_to_end(&mut decompressed)
+            .map_err(|e| Error::RuntimeException(format!("zlib decompression failed: {}", e)))?;
+        Ok(Bytes::from(decompressed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compress_round_trip() {
+        let body = "RocketMQ message body ".repeat(1000).into_bytes();
+        for level in [-1, 0, 5, 9] {
+            let compressed = ZlibCompressor.compress(&body, level).unwrap();
+            assert_eq!(ZlibCompressor.decompress(&compressed).unwrap(), body);
+        }
+        assert!(ZlibCompressor.compress(&body, 9).unwrap().len() < body.len());
+        assert!(ZlibCompressor.decompress(b"not zlib").is_err());
     }
 }
@@ -14,22 +14,42 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use std::io::Error;
-
 use bytes::Bytes;
 
-use crate::common::compression::compression_type::CompressionType;
 use crate::common::compression::compressor::Compressor;
+use crate::error::Error;
 use crate::Result;
 
+/// Zstandard compressor. The level is passed to zstd as is, like the `ZstdOutputStream` of the
+/// Java client does.
 pub struct ZstdCompressor;
 
 impl Compressor for ZstdCompressor {
     fn compress(&self, src: &[u8], level: i32) -> Result<Bytes> {
-        Ok(CompressionType::Zstd.compression(src))
+        zstd::bulk::compress(src, level)
+            .map(Bytes::from)
+            .map_err(|e| Error::RuntimeException(format!("zstd compression failed: {}", e)))
     }
 
     fn decompress(&self, src: &[u8]) -> Result<Bytes> {
-        Ok(CompressionType::Zstd.decompression(src))
+        zstd::decode_all(src)
+            .map(Bytes::from)
+            .map_err(|e| Error::RuntimeException(format!("zstd decompression failed: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compress_round_trip() {
+        let body = "RocketMQ message body ".repeat(1000).into_bytes();
+        for level in [1, 5, 19] {
+            let compressed = ZstdCompressor.compress(&body, level).unwrap();
+            assert!(compressed.len() < body.len());
+            assert_eq!(ZstdCompressor.decompress(&compressed).unwrap(), body);
+        }
+        assert!(ZstdCompressor.decompress(b"not zstd").is_err());
     }
 }
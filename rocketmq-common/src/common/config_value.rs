@@ -0,0 +1,249 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Config values which can be updated at runtime while other threads read them.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+/// A primitive which fits in 64 bits.
+pub trait AtomicPrimitive: Copy {
+    fn to_bits(self) -> u64;
+
+    fn from_bits(bits: u64) -> Self;
+}
+
+macro_rules! impl_atomic_primitive {
+    ($($ty:ty),*) => {
+        $(
+            impl AtomicPrimitive for $ty {
+                #[inline]
+                fn to_bits(self) -> u64 {
+                    self as u64
+                }
+
+                #[inline]
+                fn from_bits(bits: u64) -> Self {
+                    bits as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_atomic_primitive!(i32, u32, i64, u64, usize);
+
+impl AtomicPrimitive for bool {
+    #[inline]
+    fn to_bits(self) -> u64 {
+        self as u64
+    }
+
+    #[inline]
+    fn from_bits(bits: u64) -> Self {
+        bits != 0
+    }
+}
+
+/// A primitive config value stored in an atomic, (de)serialized as the plain value.
+pub struct AtomicConfigValue<T: AtomicPrimitive> {
+    bits: AtomicU64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: AtomicPrimitive> AtomicConfigValue<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            bits: AtomicU64::new(value.to_bits()),
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn get(&self) -> T {
+        T::from_bits(self.bits.load(Ordering::Relaxed))
+    }
+
+    #[inline]
+    pub fn set(&self, value: T) {
+        self.bits.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl<T: AtomicPrimitive> From<T> for AtomicConfigValue<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: AtomicPrimitive + Default> Default for AtomicConfigValue<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: AtomicPrimitive> Clone for AtomicConfigValue<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.get())
+    }
+}
+
+impl<T: AtomicPrimitive + PartialEq> PartialEq for AtomicConfigValue<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: AtomicPrimitive + Debug> Debug for AtomicConfigValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.get(), f)
+    }
+}
+
+impl<T: AtomicPrimitive + Display> Display for AtomicConfigValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.get(), f)
+    }
+}
+
+impl<T: AtomicPrimitive + Serialize> Serialize for AtomicConfigValue<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'de, T: AtomicPrimitive + Deserialize<'de>> Deserialize<'de> for AtomicConfigValue<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// A config value which is replaced as a whole, readers keep the snapshot they loaded.
+pub struct SwapConfigValue<T> {
+    value: ArcSwap<T>,
+}
+
+impl<T> SwapConfigValue<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value: ArcSwap::from_pointee(value),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self) -> Arc<T> {
+        self.value.load_full()
+    }
+
+    #[inline]
+    pub fn set(&self, value: T) {
+        self.value.store(Arc::new(value));
+    }
+}
+
+impl<T> From<T> for SwapConfigValue<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Default> Default for SwapConfigValue<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> Clone for SwapConfigValue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: ArcSwap::new(self.get()),
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for SwapConfigValue<T> {
+    fn eq(&self, other: &Self) -> bool {
+        *self.get() == *other.get()
+    }
+}
+
+impl<T: Debug> Debug for SwapConfigValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.get(), f)
+    }
+}
+
+impl<T: Display> Display for SwapConfigValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&*self.get(), f)
+    }
+}
+
+impl<T: Serialize> Serialize for SwapConfigValue<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SwapConfigValue<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+
+    #[test]
+    fn atomic_config_value_round_trips_primitives() {
+        let value = AtomicConfigValue::new(-5i32);
+        assert_eq!(value.get(), -5);
+        value.set(i32::MAX);
+        assert_eq!(value.get(), i32::MAX);
+
+        let flag = AtomicConfigValue::new(true);
+        flag.set(false);
+        assert!(!flag.get());
+        assert_eq!(serde_json::to_string(&flag).unwrap(), "false");
+        let flag: AtomicConfigValue<bool> = serde_json::from_str("true").unwrap();
+        assert!(flag.get());
+    }
+
+    #[test]
+    fn swap_config_value_keeps_loaded_snapshot() {
+        let value = SwapConfigValue::new(CheetahString::from("a:1"));
+        let snapshot = value.get();
+        value.set(CheetahString::from("b:2"));
+        assert_eq!(*snapshot, "a:1");
+        assert_eq!(value.to_string(), "b:2");
+        assert_eq!(serde_json::to_string(&value).unwrap(), "\"b:2\"");
+    }
+}
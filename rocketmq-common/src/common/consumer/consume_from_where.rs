@@ -108,7 +108,7 @@ impl<'de> Deserialize<'de> for ConsumeFromWhere {
             type Value = ConsumeFromWhere;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("a string representing TopicFilterType")
+                formatter.write_str("a name or ordinal of ConsumeFromWhere")
             }
 
             fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -137,9 +137,27 @@ impl<'de> Deserialize<'de> for ConsumeFromWhere {
                     )),
                 }
             }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match value {
+                    0 => Ok(ConsumeFromWhere::ConsumeFromLastOffset),
+                    1 => Ok(ConsumeFromWhere::ConsumeFromLastOffsetAndFromMinWhenBootFirst),
+                    2 => Ok(ConsumeFromWhere::ConsumeFromMinOffset),
+                    3 => Ok(ConsumeFromWhere::ConsumeFromMaxOffset),
+                    4 => Ok(ConsumeFromWhere::ConsumeFromFirstOffset),
+                    5 => Ok(ConsumeFromWhere::ConsumeFromTimestamp),
+                    _ => Err(serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Unsigned(value),
+                        &self,
+                    )),
+                }
+            }
         }
 
-        deserializer.deserialize_str(ConsumeFromWhereVisitor)
+        deserializer.deserialize_any(ConsumeFromWhereVisitor)
     }
 }
 
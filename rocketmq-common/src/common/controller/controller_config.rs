@@ -0,0 +1,89 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::env;
+
+use serde::Deserialize;
+
+use crate::common::mix_all::ROCKETMQ_HOME_ENV;
+use crate::common::mix_all::ROCKETMQ_HOME_PROPERTY;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ControllerConfig {
+    pub rocketmq_home: String,
+
+    /// Interval in milliseconds of the scan that expires brokers which stopped sending heartbeats.
+    pub scan_not_active_broker_interval: u64,
+
+    pub controller_thread_pool_nums: usize,
+
+    /// Whether a replica outside the sync state set may be elected when no in-sync replica is
+    /// alive. Doing so can lose messages that only reached the old master.
+    pub enable_elect_unclean_master: bool,
+
+    /// Whether the brokers of a group are notified right away when their master changed.
+    pub notify_broker_role_changed: bool,
+
+    /// Name of the raft group formed by the controllers.
+    #[serde(alias = "controllerDLegerGroup")]
+    pub controller_dledger_group: String,
+
+    /// Members of the raft group in the `n0-127.0.0.1:9878;n1-127.0.0.1:9868` format. Empty
+    /// runs a single controller that elects itself.
+    #[serde(alias = "controllerDLegerPeers")]
+    pub controller_dledger_peers: String,
+
+    /// Id of this controller in `controller_dledger_peers`.
+    #[serde(alias = "controllerDLegerSelfId")]
+    pub controller_dledger_self_id: String,
+
+    /// Directory of the raft log and state, `~/DledgerController` when empty.
+    pub controller_store_path: String,
+}
+
+impl Default for ControllerConfig {
+    fn default() -> Self {
+        let rocketmq_home = env::var(ROCKETMQ_HOME_PROPERTY)
+            .unwrap_or_else(|_| env::var(ROCKETMQ_HOME_ENV).unwrap_or_default());
+        ControllerConfig {
+            rocketmq_home,
+            scan_not_active_broker_interval: 5 * 1000,
+            controller_thread_pool_nums: 16,
+            enable_elect_unclean_master: false,
+            notify_broker_role_changed: true,
+            controller_dledger_group: "DefaultControllerGroup".to_string(),
+            controller_dledger_peers: String::new(),
+            controller_dledger_self_id: "n0".to_string(),
+            controller_store_path: String::new(),
+        }
+    }
+}
+
+impl ControllerConfig {
+    pub fn get_controller_store_path(&self) -> String {
+        if self.controller_store_path.is_empty() {
+            dirs::home_dir()
+                .unwrap_or_default()
+                .join("DledgerController")
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            self.controller_store_path.clone()
+        }
+    }
+}
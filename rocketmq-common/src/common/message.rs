@@ -85,8 +85,8 @@ pub trait MessageTrait: Any + Display + Debug {
                 name
             );
         }
-        if value.is_empty() || name.is_empty() {
-            panic!("The name or value of property can not be null or blank string!");
+        if let Err(e) = message_decoder::check_message_property(&name, &value) {
+            panic!("{}", e);
         }
         self.put_property(name, value);
     }
@@ -557,6 +557,7 @@ impl MessageConst {
     pub const PROPERTY_TIMER_OUT_MS: &'static str = "TIMER_OUT_MS";
     pub const PROPERTY_TIMER_ROLL_TIMES: &'static str = "TIMER_ROLL_TIMES";
     pub const PROPERTY_TRACE_CONTEXT: &'static str = "TRACE_CONTEXT";
+    pub const PROPERTY_TRACE_PARENT: &'static str = "TRACEPARENT";
     pub const PROPERTY_TRACE_SWITCH: &'static str = "TRACE_ON";
     pub const PROPERTY_TRANSACTION_CHECK_TIMES: &'static str = "TRANSACTION_CHECK_TIMES";
     pub const PROPERTY_TRANSACTION_ID: &'static str = "__transactionId__";
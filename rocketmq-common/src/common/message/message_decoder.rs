@@ -29,8 +29,8 @@ use bytes::BufMut;
 use bytes::Bytes;
 use bytes::BytesMut;
 use cheetah_string::CheetahString;
+use tracing::warn;
 
-use crate::common::compression::compression_type::CompressionType;
 use crate::common::compression::compressor_factory::CompressorFactory;
 use crate::common::message::message_client_ext::MessageClientExt;
 use crate::common::message::message_ext::MessageExt;
@@ -40,6 +40,7 @@ use crate::common::message::MessageConst;
 use crate::common::message::MessageTrait;
 use crate::common::message::MessageVersion;
 use crate::common::sys_flag::message_sys_flag::MessageSysFlag;
+use crate::error::Error;
 use crate::utils::util_all;
 use crate::CRC32Utils::crc32;
 use crate::MessageAccessor::MessageAccessor;
@@ -92,29 +93,33 @@ pub fn string_to_message_properties(
 pub fn str_to_message_properties(
     properties: Option<&str>,
 ) -> HashMap<CheetahString, CheetahString> {
-    let mut map = HashMap::new();
-    if let Some(properties) = properties {
-        let mut index = 0;
-        let len = properties.len();
-        while index < len {
-            let new_index = properties[index..]
-                .find(PROPERTY_SEPARATOR)
-                .map_or(len, |i| index + i);
-            if new_index - index >= 3 {
-                if let Some(kv_sep_index) = properties[index..new_index].find(NAME_VALUE_SEPARATOR)
-                {
-                    let kv_sep_index = index + kv_sep_index;
-                    if kv_sep_index > index && kv_sep_index < new_index - 1 {
-                        let k = &properties[index..kv_sep_index];
-                        let v = &properties[kv_sep_index + 1..new_index];
-                        map.insert(CheetahString::from_slice(k), CheetahString::from_slice(v));
-                    }
-                }
-            }
-            index = new_index + 1;
-        }
+    string_to_message_properties(properties.map(CheetahString::from_slice).as_ref())
+}
+
+/// Validates a single message property so that it survives the
+/// `name 0x01 value 0x02` wire encoding used by [`message_properties_to_string`].
+///
+/// Both the name and the value must be non-empty and must not contain the
+/// [`NAME_VALUE_SEPARATOR`] or [`PROPERTY_SEPARATOR`] characters.
+pub fn check_message_property(name: &str, value: &str) -> Result<()> {
+    if name.is_empty() || value.is_empty() {
+        return Err(Error::RuntimeException(
+            "The name or value of property can not be null or blank string!".to_string(),
+        ));
     }
-    map
+    if name.contains([NAME_VALUE_SEPARATOR, PROPERTY_SEPARATOR]) {
+        return Err(Error::RuntimeException(format!(
+            "The name of property<{}> contains illegal separator characters",
+            name.escape_default()
+        )));
+    }
+    if value.contains([NAME_VALUE_SEPARATOR, PROPERTY_SEPARATOR]) {
+        return Err(Error::RuntimeException(format!(
+            "The value of property<{}> contains illegal separator characters",
+            name
+        )));
+    }
+    Ok(())
 }
 
 pub fn message_properties_to_string(
@@ -130,6 +135,9 @@ pub fn message_properties_to_string(
 
     let mut sb = String::with_capacity(len);
     for (name, value) in properties.iter() {
+        if value.is_empty() {
+            continue;
+        }
         sb.push_str(name);
         sb.push(NAME_VALUE_SEPARATOR);
 
@@ -194,7 +202,7 @@ pub fn decode(
 
     // 2 MAGICCODE
     let magic_code = byte_buffer.get_i32();
-    let version = MessageVersion::value_of_magic_code(magic_code).unwrap();
+    let version = MessageVersion::value_of_magic_code(magic_code).ok()?;
 
     // 3 BODYCRC
     let body_crc = byte_buffer.get_u32();
@@ -225,29 +233,22 @@ pub fn decode(
     msg_ext.set_born_timestamp(born_time_stamp);
 
     // 10 BORNHOST
-    let (born_host_address, born_host_ip_length) =
-        if sys_flag & MessageSysFlag::BORNHOST_V6_FLAG != 0 {
-            let mut born_host = [0; 16];
-            byte_buffer.copy_to_slice(&mut born_host);
-            let port = byte_buffer.get_i32();
-            (
-                SocketAddr::V6(SocketAddrV6::new(
-                    Ipv6Addr::from(born_host),
-                    port as u16,
-                    0,
-                    0,
-                )),
-                16,
-            )
-        } else {
-            let mut born_host = [0; 4];
-            byte_buffer.copy_to_slice(&mut born_host);
-            let port = byte_buffer.get_i32();
-            (
-                SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::from(born_host), port as u16)),
-                4,
-            )
-        };
+    let born_host_address = if sys_flag & MessageSysFlag::BORNHOST_V6_FLAG != 0 {
+        let mut born_host = [0; 16];
+        byte_buffer.copy_to_slice(&mut born_host);
+        let port = byte_buffer.get_i32();
+        SocketAddr::V6(SocketAddrV6::new(
+            Ipv6Addr::from(born_host),
+            port as u16,
+            0,
+            0,
+        ))
+    } else {
+        let mut born_host = [0; 4];
+        byte_buffer.copy_to_slice(&mut born_host);
+        let port = byte_buffer.get_i32();
+        SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::from(born_host), port as u16))
+    };
     msg_ext.set_born_host(born_host_address);
 
     // 11 STORETIMESTAMP
@@ -255,29 +256,22 @@ pub fn decode(
     msg_ext.set_store_timestamp(store_timestamp);
 
     // 12 STOREHOST
-    let (store_host_address, store_host_ip_length) =
-        if sys_flag & MessageSysFlag::STOREHOSTADDRESS_V6_FLAG != 0 {
-            let mut store_host = [0; 16];
-            byte_buffer.copy_to_slice(&mut store_host);
-            let port = byte_buffer.get_i32();
-            (
-                SocketAddr::V6(SocketAddrV6::new(
-                    Ipv6Addr::from(store_host),
-                    port as u16,
-                    0,
-                    0,
-                )),
-                16,
-            )
-        } else {
-            let mut store_host = [0; 4];
-            byte_buffer.copy_to_slice(&mut store_host);
-            let port = byte_buffer.get_i32();
-            (
-                SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::from(store_host), port as u16)),
-                4,
-            )
-        };
+    let store_host_address = if sys_flag & MessageSysFlag::STOREHOSTADDRESS_V6_FLAG != 0 {
+        let mut store_host = [0; 16];
+        byte_buffer.copy_to_slice(&mut store_host);
+        let port = byte_buffer.get_i32();
+        SocketAddr::V6(SocketAddrV6::new(
+            Ipv6Addr::from(store_host),
+            port as u16,
+            0,
+            0,
+        ))
+    } else {
+        let mut store_host = [0; 4];
+        byte_buffer.copy_to_slice(&mut store_host);
+        let port = byte_buffer.get_i32();
+        SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::from(store_host), port as u16))
+    };
     msg_ext.set_store_host(store_host_address);
 
     // 13 RECONSUMETIMES
@@ -305,25 +299,14 @@ pub fn decode(
             if de_compress_body
                 && (sys_flag & MessageSysFlag::COMPRESSED_FLAG) == MessageSysFlag::COMPRESSED_FLAG
             {
-                let compression_type = CompressionType::find_by_value(
-                    (flag & MessageSysFlag::COMPRESSION_TYPE_COMPARATOR) >> 8,
-                );
-                body_bytes = compression_type.decompression(&body_bytes)
+                match MessageSysFlag::get_compression_type(sys_flag).decompression(&body_bytes) {
+                    Ok(decompressed) => body_bytes = decompressed,
+                    Err(e) => warn!("failed to decompress message body: {}", e),
+                }
             }
             msg_ext.message.body = Some(body_bytes);
         } else {
-            let _ = byte_buffer.split_to(
-                BORN_TIMESTAMP_POSITION
-                    + born_host_ip_length
-                    + 4
-                    + 8
-                    + store_host_ip_length
-                    + 4
-                    + 4
-                    + 8
-                    + 4
-                    + body_len as usize,
-            );
+            byte_buffer.advance(body_len as usize);
         }
     }
 
@@ -559,6 +542,16 @@ pub fn decode_message_id(msg_id: &str) -> MessageId {
     }
 }
 
+/// Decodes an offset message id like [`decode_message_id`], returning `None` instead of
+/// panicking when `msg_id` is not the hex encoding of an IPv4 or IPv6 store host and offset.
+pub fn try_decode_message_id(msg_id: &str) -> Option<MessageId> {
+    if (msg_id.len() != 32 && msg_id.len() != 56) || !msg_id.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    Some(decode_message_id(msg_id))
+}
+
 pub fn encode(message_ext: &MessageExt, need_compress: bool) -> Result<Bytes> {
     let body = message_ext.get_body().unwrap();
     let topics = message_ext.get_topic().as_bytes();
@@ -588,35 +581,35 @@ pub fn encode(message_ext: &MessageExt, need_compress: bool) -> Result<Bytes> {
         None
     };
     let body_len = new_body.as_ref().map_or(body.len(), |b| b.len());
-    let store_size = message_ext.store_size;
-    let mut byte_buffer = if store_size > 0 {
-        BytesMut::with_capacity(store_size as usize)
+    let version = if topic_len > i8::MAX as usize {
+        MessageVersion::V2
     } else {
-        let store_size = 4 // 1 TOTALSIZE
-             + 4 // 2 MAGICCODE
-             + 4 // 3 BODYCRC
-             + 4 // 4 QUEUEID
-             + 4 // 5 FLAG
-             + 8 // 6 QUEUEOFFSET
-             + 8 // 7 PHYSICALOFFSET
-             + 4 // 8 SYSFLAG
-             + 8 // 9 BORNTIMESTAMP
-             + born_host_length // 10 BORNHOST
-             + 8 // 11 STORETIMESTAMP
-             + store_host_address_length // 12 STOREHOSTADDRESS
-             + 4 // 13 RECONSUMETIMES
-             + 8 // 14 Prepared Transaction Offset
-             + 4 + body_len // 14 BODY
-             + 1 + topic_len // 15 TOPIC
-             + 2 + properties_length; // 16 propertiesLength
-        BytesMut::with_capacity(store_size)
+        MessageVersion::V1
     };
+    let store_size = 4 // 1 TOTALSIZE
+         + 4 // 2 MAGICCODE
+         + 4 // 3 BODYCRC
+         + 4 // 4 QUEUEID
+         + 4 // 5 FLAG
+         + 8 // 6 QUEUEOFFSET
+         + 8 // 7 PHYSICALOFFSET
+         + 4 // 8 SYSFLAG
+         + 8 // 9 BORNTIMESTAMP
+         + born_host_length // 10 BORNHOST
+         + 8 // 11 STORETIMESTAMP
+         + store_host_address_length // 12 STOREHOSTADDRESS
+         + 4 // 13 RECONSUMETIMES
+         + 8 // 14 Prepared Transaction Offset
+         + 4 + body_len // 14 BODY
+         + version.get_topic_length_size() + topic_len // 15 TOPIC
+         + 2 + properties_length; // 16 propertiesLength
+    let mut byte_buffer = BytesMut::with_capacity(store_size);
 
     // 1 TOTALSIZE
-    byte_buffer.put_i32(store_size);
+    byte_buffer.put_i32(store_size as i32);
 
     // 2 MAGICCODE
-    byte_buffer.put_i32(MESSAGE_MAGIC_CODE);
+    byte_buffer.put_i32(version.get_magic_code());
 
     // 3 BODYCRC
     byte_buffer.put_u32(message_ext.body_crc);
@@ -677,7 +670,10 @@ pub fn encode(message_ext: &MessageExt, need_compress: bool) -> Result<Bytes> {
     }
 
     // 16 TOPIC
-    byte_buffer.put_i16(topic_len as i16);
+    match version {
+        MessageVersion::V1 => byte_buffer.put_u8(topic_len as u8),
+        MessageVersion::V2 => byte_buffer.put_i16(topic_len as i16),
+    }
     byte_buffer.put_slice(topics);
 
     // 17 properties
@@ -711,33 +707,33 @@ pub fn encode_uniquely(message_ext: &MessageExt, need_compress: bool) -> Result<
         None
     };
     let body_len = new_body.as_ref().map_or(body.len(), |b| b.len());
-    let store_size = message_ext.store_size;
-    let mut byte_buffer = if store_size > 0 {
-        BytesMut::with_capacity((store_size - 8) as usize)
+    let version = if topic_len > i8::MAX as usize {
+        MessageVersion::V2
     } else {
-        let store_size = 4 // 1 TOTALSIZE
-             + 4 // 2 MAGICCODE
-             + 4 // 3 BODYCRC
-             + 4 // 4 QUEUEID
-             + 4 // 5 FLAG
-             + 8 // 6 QUEUEOFFSET
-             + 8 // 7 PHYSICALOFFSET
-             + 4 // 8 SYSFLAG
-             + 8 // 9 BORNTIMESTAMP
-             + born_host_length // 10 BORNHOST
-             + 4 // 11 RECONSUMETIMES
-             + 8 // 12 Prepared Transaction Offset
-             + 4 + body_len // 13 BODY
-             + 1 + topic_len // 14 TOPIC
-             + 2 + properties_length; // 15 propertiesLength
-        BytesMut::with_capacity(store_size)
+        MessageVersion::V1
     };
+    let store_size = 4 // 1 TOTALSIZE
+         + 4 // 2 MAGICCODE
+         + 4 // 3 BODYCRC
+         + 4 // 4 QUEUEID
+         + 4 // 5 FLAG
+         + 8 // 6 QUEUEOFFSET
+         + 8 // 7 PHYSICALOFFSET
+         + 4 // 8 SYSFLAG
+         + 8 // 9 BORNTIMESTAMP
+         + born_host_length // 10 BORNHOST
+         + 4 // 11 RECONSUMETIMES
+         + 8 // 12 Prepared Transaction Offset
+         + 4 + body_len // 13 BODY
+         + version.get_topic_length_size() + topic_len // 14 TOPIC
+         + 2 + properties_length; // 15 propertiesLength
+    let mut byte_buffer = BytesMut::with_capacity(store_size);
 
     // 1 TOTALSIZE
-    byte_buffer.put_i32(store_size);
+    byte_buffer.put_i32(store_size as i32);
 
     // 2 MAGICCODE
-    byte_buffer.put_i32(MESSAGE_MAGIC_CODE);
+    byte_buffer.put_i32(version.get_magic_code());
 
     // 3 BODYCRC
     byte_buffer.put_u32(message_ext.body_crc);
@@ -784,7 +780,10 @@ pub fn encode_uniquely(message_ext: &MessageExt, need_compress: bool) -> Result<
     }
 
     // 14 TOPIC
-    byte_buffer.put_i16(topic_len as i16);
+    match version {
+        MessageVersion::V1 => byte_buffer.put_u8(topic_len as u8),
+        MessageVersion::V2 => byte_buffer.put_i16(topic_len as i16),
+    }
     byte_buffer.put_slice(topics);
 
     // 15 properties
@@ -815,6 +814,7 @@ mod tests {
     use bytes::BytesMut;
 
     use super::*;
+    use crate::common::compression::compression_type::CompressionType;
 
     #[test]
     fn count_inner_msg_num_counts_correctly_for_multiple_messages() {
@@ -855,6 +855,13 @@ mod tests {
         assert_eq!(message_id.offset, 860316681131967304);
     }
 
+    #[test]
+    fn try_decode_message_id_rejects_invalid_ids() {
+        assert!(try_decode_message_id("7F0000010007D8260BF075769D36C348").is_some());
+        assert!(try_decode_message_id("7F0000010007D826").is_none());
+        assert!(try_decode_message_id("ZZ0000010007D8260BF075769D36C348").is_none());
+    }
+
     #[test]
     fn encode_with_compression() {
         let mut message_ext = MessageExt::default();
@@ -865,6 +872,26 @@ mod tests {
         assert!(!bytes.is_empty());
     }
 
+    #[test]
+    fn decode_decompresses_body_by_sys_flag() {
+        let body = Bytes::from("Hello, World! ".repeat(100));
+        for compression_type in [
+            CompressionType::LZ4,
+            CompressionType::Zstd,
+            CompressionType::Zlib,
+        ] {
+            let mut message_ext = MessageExt::default();
+            message_ext.set_topic(CheetahString::from_static_str("TopicTest"));
+            message_ext.set_body(body.clone());
+            message_ext.set_sys_flag(
+                MessageSysFlag::COMPRESSED_FLAG | compression_type.get_compression_flag(),
+            );
+            let mut encoded = encode(&message_ext, true).unwrap();
+            let decoded = decode(&mut encoded, true, true, false, false, false).unwrap();
+            assert_eq!(decoded.get_body(), Some(&body));
+        }
+    }
+
     #[test]
     fn encode_without_compression() {
         let mut message_ext = MessageExt::default();
@@ -936,4 +963,143 @@ mod tests {
         let bytes = result.unwrap();
         assert!(!bytes.is_empty());
     }
+
+    #[test]
+    fn message_properties_round_trip() {
+        let mut properties = HashMap::new();
+        properties.insert(CheetahString::from("KEYS"), CheetahString::from("k1 k2"));
+        properties.insert(CheetahString::from("TAGS"), CheetahString::from("TagA"));
+        properties.insert(CheetahString::from("a"), CheetahString::from("b"));
+
+        let encoded = message_properties_to_string(&properties);
+        assert_eq!(encoded.matches(NAME_VALUE_SEPARATOR).count(), 3);
+        assert_eq!(encoded.matches(PROPERTY_SEPARATOR).count(), 3);
+        assert_eq!(string_to_message_properties(Some(&encoded)), properties);
+        assert_eq!(
+            str_to_message_properties(Some(encoded.as_str())),
+            properties
+        );
+    }
+
+    #[test]
+    fn string_to_message_properties_skips_malformed_entries() {
+        let properties = "\u{0001}v\u{0002}k\u{0001}\u{0002}TAGS\u{0001}TagA\u{0002}kv\u{0002}";
+        let map = str_to_message_properties(Some(properties));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("TAGS").unwrap(), "TagA");
+        assert!(str_to_message_properties(None).is_empty());
+    }
+
+    #[test]
+    fn check_message_property_rejects_separators() {
+        assert!(check_message_property("KEYS", "value").is_ok());
+        assert!(check_message_property("", "value").is_err());
+        assert!(check_message_property("KEYS", "").is_err());
+        assert!(check_message_property("K\u{0001}EYS", "value").is_err());
+        assert!(check_message_property("KEYS", "va\u{0002}lue").is_err());
+    }
+
+    fn new_message_ext(topic: &str, born_host: SocketAddr, store_host: SocketAddr) -> MessageExt {
+        let body = Bytes::from_static(b"Hello, World!");
+        let mut message_ext = MessageExt::default();
+        message_ext.set_topic(CheetahString::from_slice(topic));
+        message_ext.set_body_crc(crc32(&body));
+        message_ext.set_body(body);
+        message_ext.set_queue_id(3);
+        message_ext.set_queue_offset(100);
+        message_ext.set_commit_log_offset(4096);
+        message_ext.set_born_timestamp(1_700_000_000_000);
+        message_ext.set_store_timestamp(1_700_000_000_100);
+        message_ext.set_reconsume_times(2);
+        let mut sys_flag = 0;
+        if born_host.is_ipv6() {
+            sys_flag |= MessageSysFlag::BORNHOST_V6_FLAG;
+        }
+        if store_host.is_ipv6() {
+            sys_flag |= MessageSysFlag::STOREHOSTADDRESS_V6_FLAG;
+        }
+        message_ext.set_sys_flag(sys_flag);
+        message_ext.set_born_host(born_host);
+        message_ext.set_store_host(store_host);
+        message_ext.put_property(CheetahString::from("TAGS"), CheetahString::from("TagA"));
+        message_ext
+    }
+
+    fn assert_decoded_eq(decoded: &MessageExt, expected: &MessageExt, store_size: usize) {
+        assert_eq!(decoded.store_size as usize, store_size);
+        assert_eq!(decoded.get_topic(), expected.get_topic());
+        assert_eq!(decoded.get_body(), expected.get_body());
+        assert_eq!(decoded.body_crc, expected.body_crc);
+        assert_eq!(decoded.queue_id, expected.queue_id);
+        assert_eq!(decoded.queue_offset, expected.queue_offset);
+        assert_eq!(decoded.commit_log_offset, expected.commit_log_offset);
+        assert_eq!(decoded.sys_flag, expected.sys_flag);
+        assert_eq!(decoded.born_timestamp, expected.born_timestamp);
+        assert_eq!(decoded.store_timestamp, expected.store_timestamp);
+        assert_eq!(decoded.born_host, expected.born_host);
+        assert_eq!(decoded.store_host, expected.store_host);
+        assert_eq!(decoded.reconsume_times, expected.reconsume_times);
+        assert_eq!(decoded.get_properties(), expected.get_properties());
+    }
+
+    #[test]
+    fn encode_decode_round_trip_ipv4() {
+        let message_ext = new_message_ext(
+            "TopicTest",
+            "192.168.0.1:10911".parse().unwrap(),
+            "10.0.0.1:10911".parse().unwrap(),
+        );
+        let mut bytes = encode(&message_ext, false).unwrap();
+        let store_size = bytes.len();
+        assert_eq!((&bytes[..4]).get_i32() as usize, store_size);
+        assert_eq!((&bytes[4..8]).get_i32(), MESSAGE_MAGIC_CODE);
+        let decoded = decode(&mut bytes, true, false, false, false, true).unwrap();
+        assert_decoded_eq(&decoded, &message_ext, store_size);
+        assert!(!bytes.has_remaining());
+    }
+
+    #[test]
+    fn encode_decode_round_trip_ipv6_and_long_topic() {
+        let topic = "T".repeat(200);
+        let message_ext = new_message_ext(
+            &topic,
+            "[::1]:10911".parse().unwrap(),
+            "[fe80::1]:10912".parse().unwrap(),
+        );
+        let mut bytes = encode(&message_ext, false).unwrap();
+        let store_size = bytes.len();
+        assert_eq!((&bytes[4..8]).get_i32(), MESSAGE_MAGIC_CODE_V2);
+        let decoded = decode(&mut bytes, true, false, false, false, true).unwrap();
+        assert_decoded_eq(&decoded, &message_ext, store_size);
+    }
+
+    #[test]
+    fn decode_skips_body_when_not_read() {
+        let message_ext = new_message_ext(
+            "TopicTest",
+            "192.168.0.1:10911".parse().unwrap(),
+            "10.0.0.1:10911".parse().unwrap(),
+        );
+        let mut bytes = encode(&message_ext, false).unwrap();
+        let decoded = decode(&mut bytes, false, false, false, false, false).unwrap();
+        assert!(decoded.get_body().is_none());
+        assert_eq!(decoded.get_topic(), message_ext.get_topic());
+        assert_eq!(decoded.get_properties(), message_ext.get_properties());
+    }
+
+    #[test]
+    fn decode_rejects_bad_crc_and_magic_code() {
+        let mut message_ext = new_message_ext(
+            "TopicTest",
+            "192.168.0.1:10911".parse().unwrap(),
+            "10.0.0.1:10911".parse().unwrap(),
+        );
+        message_ext.set_body_crc(1);
+        let mut bytes = encode(&message_ext, false).unwrap();
+        assert!(decode(&mut bytes, true, false, false, false, true).is_none());
+
+        let mut bytes = BytesMut::from(encode(&message_ext, false).unwrap().as_ref());
+        bytes[4..8].copy_from_slice(&BLANK_MAGIC_CODE.to_be_bytes());
+        assert!(decode(&mut bytes.freeze(), true, false, false, false, false).is_none());
+    }
 }
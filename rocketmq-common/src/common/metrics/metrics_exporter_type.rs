@@ -0,0 +1,66 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fmt::Display;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Where broker and name server metrics are exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MetricsExporterType {
+    #[default]
+    Disable,
+    OtlpGrpc,
+    Prom,
+    Log,
+}
+
+impl MetricsExporterType {
+    pub fn is_enable(&self) -> bool {
+        *self != MetricsExporterType::Disable
+    }
+}
+
+impl Display for MetricsExporterType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetricsExporterType::Disable => write!(f, "DISABLE"),
+            MetricsExporterType::OtlpGrpc => write!(f, "OTLP_GRPC"),
+            MetricsExporterType::Prom => write!(f, "PROM"),
+            MetricsExporterType::Log => write!(f, "LOG"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_exporter_type_uses_java_names() {
+        assert_eq!(
+            serde_json::to_string(&MetricsExporterType::OtlpGrpc).unwrap(),
+            "\"OTLP_GRPC\""
+        );
+        let prom: MetricsExporterType = serde_json::from_str("\"PROM\"").unwrap();
+        assert_eq!(prom, MetricsExporterType::Prom);
+        assert!(prom.is_enable());
+        assert!(!MetricsExporterType::default().is_enable());
+        assert_eq!(MetricsExporterType::Log.to_string(), "LOG");
+    }
+}
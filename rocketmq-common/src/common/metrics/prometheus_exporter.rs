@@ -0,0 +1,167 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+use tracing::info;
+use tracing::warn;
+
+use crate::common::metrics::prometheus_text_encoder::PrometheusTextEncoder;
+
+const MAX_REQUEST_HEAD_SIZE: usize = 8 * 1024;
+
+/// Produces the current metrics in the Prometheus text format.
+pub type MetricsCollector = Arc<dyn Fn() -> String + Send + Sync>;
+
+/// A minimal HTTP endpoint that serves `GET /metrics` for Prometheus scrapes.
+pub struct PrometheusExporter;
+
+impl PrometheusExporter {
+    pub const METRICS_PATH: &'static str = "/metrics";
+
+    /// Binds `host:port` and serves the collector output until the returned task is aborted.
+    pub fn start(host: &str, port: u16, collector: MetricsCollector) -> JoinHandle<()> {
+        let host = if host.is_empty() { "0.0.0.0" } else { host };
+        let addr = format!("{}:{}", host, port);
+        tokio::spawn(async move {
+            let listener = match TcpListener::bind(addr.as_str()).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    warn!("Prometheus exporter bind {} failed: {}", addr, e);
+                    return;
+                }
+            };
+            info!("Prometheus exporter listening on {}", addr);
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let collector = collector.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = Self::handle_connection(stream, collector).await {
+                                warn!("Prometheus exporter connection error: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("Prometheus exporter accept failed: {}", e),
+                }
+            }
+        })
+    }
+
+    async fn handle_connection(
+        mut stream: TcpStream,
+        collector: MetricsCollector,
+    ) -> std::io::Result<()> {
+        let mut head = Vec::with_capacity(1024);
+        let mut buf = [0u8; 1024];
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") && head.len() < MAX_REQUEST_HEAD_SIZE {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            head.extend_from_slice(&buf[..n]);
+        }
+        let request = String::from_utf8_lossy(&head);
+        let response =
+            Self::build_response(request.lines().next().unwrap_or_default(), || collector());
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    fn build_response(request_line: &str, collect: impl FnOnce() -> String) -> String {
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default();
+        let path = parts
+            .next()
+            .unwrap_or_default()
+            .split('?')
+            .next()
+            .unwrap_or_default();
+        let (status, content_type, body) = match (method, path) {
+            ("GET", Self::METRICS_PATH) => {
+                ("200 OK", PrometheusTextEncoder::CONTENT_TYPE, collect())
+            }
+            ("GET", _) => ("404 Not Found", "text/plain", String::from("Not Found\n")),
+            _ => (
+                "405 Method Not Allowed",
+                "text/plain",
+                String::from("Method Not Allowed\n"),
+            ),
+        };
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_response_serves_metrics_path() {
+        let response =
+            PrometheusExporter::build_response("GET /metrics HTTP/1.1", || String::from("m 1\n"));
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Length: 4\r\n"));
+        assert!(response.ends_with("\r\n\r\nm 1\n"));
+    }
+
+    #[test]
+    fn build_response_rejects_other_requests() {
+        let response = PrometheusExporter::build_response("GET /other HTTP/1.1", || unreachable!());
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        let response =
+            PrometheusExporter::build_response("POST /metrics HTTP/1.1", || unreachable!());
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+    }
+
+    #[tokio::test]
+    async fn exporter_serves_scrape_over_tcp() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let handle =
+            PrometheusExporter::start("127.0.0.1", port, Arc::new(|| String::from("m 1\n")));
+        let mut stream = None;
+        for _ in 0..50 {
+            if let Ok(s) = TcpStream::connect(("127.0.0.1", port)).await {
+                stream = Some(s);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        let mut stream = stream.expect("exporter should accept connections");
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("m 1\n"));
+        handle.abort();
+    }
+}
@@ -0,0 +1,200 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Kind of a Prometheus metric family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+}
+
+impl MetricType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+        }
+    }
+}
+
+struct MetricFamily {
+    name: String,
+    help: String,
+    metric_type: MetricType,
+    samples: Vec<(String, f64)>,
+}
+
+/// Renders samples in the Prometheus text exposition format (version 0.0.4).
+///
+/// Samples of the same metric name are grouped under a single `# HELP`/`# TYPE` header, in the
+/// order the metric names were first added.
+#[derive(Default)]
+pub struct PrometheusTextEncoder {
+    families: Vec<MetricFamily>,
+    index: HashMap<String, usize>,
+}
+
+impl PrometheusTextEncoder {
+    pub const CONTENT_TYPE: &'static str = "text/plain; version=0.0.4; charset=utf-8";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn gauge(&mut self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        self.add_sample(name, help, MetricType::Gauge, labels, value);
+    }
+
+    pub fn counter(&mut self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        self.add_sample(name, help, MetricType::Counter, labels, value);
+    }
+
+    pub fn add_sample(
+        &mut self,
+        name: &str,
+        help: &str,
+        metric_type: MetricType,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) {
+        let index = match self.index.get(name) {
+            Some(index) => *index,
+            None => {
+                self.families.push(MetricFamily {
+                    name: name.to_string(),
+                    help: help.to_string(),
+                    metric_type,
+                    samples: Vec::new(),
+                });
+                self.index.insert(name.to_string(), self.families.len() - 1);
+                self.families.len() - 1
+            }
+        };
+        let mut label_str = String::new();
+        for (i, (key, label_value)) in labels.iter().enumerate() {
+            if i > 0 {
+                label_str.push(',');
+            }
+            let _ = write!(label_str, "{}=\"{}\"", key, escape_label_value(label_value));
+        }
+        self.families[index].samples.push((label_str, value));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.families.is_empty()
+    }
+
+    pub fn encode(&self) -> String {
+        let mut out = String::with_capacity(self.families.len() * 128);
+        for family in &self.families {
+            let _ = writeln!(out, "# HELP {} {}", family.name, escape_help(&family.help));
+            let _ = writeln!(
+                out,
+                "# TYPE {} {}",
+                family.name,
+                family.metric_type.as_str()
+            );
+            for (labels, value) in &family.samples {
+                if labels.is_empty() {
+                    let _ = writeln!(out, "{} {}", family.name, format_value(*value));
+                } else {
+                    let _ = writeln!(
+                        out,
+                        "{}{{{}}} {}",
+                        family.name,
+                        labels,
+                        format_value(*value)
+                    );
+                }
+            }
+        }
+        out
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 {
+            "+Inf".to_string()
+        } else {
+            "-Inf".to_string()
+        }
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_groups_samples_by_family() {
+        let mut encoder = PrometheusTextEncoder::new();
+        encoder.gauge(
+            "rocketmq_broker_tps",
+            "Broker put tps",
+            &[("broker", "b1")],
+            1.5,
+        );
+        encoder.counter(
+            "rocketmq_messages_in_total",
+            "Messages in",
+            &[("topic", "t1")],
+            3.0,
+        );
+        encoder.gauge(
+            "rocketmq_broker_tps",
+            "Broker put tps",
+            &[("broker", "b2")],
+            2.0,
+        );
+        let text = encoder.encode();
+        assert_eq!(
+            text,
+            "# HELP rocketmq_broker_tps Broker put tps\n# TYPE rocketmq_broker_tps \
+             gauge\nrocketmq_broker_tps{broker=\"b1\"} 1.5\nrocketmq_broker_tps{broker=\"b2\"} \
+             2\n# HELP rocketmq_messages_in_total Messages in\n# TYPE rocketmq_messages_in_total \
+             counter\nrocketmq_messages_in_total{topic=\"t1\"} 3\n"
+        );
+    }
+
+    #[test]
+    fn encode_escapes_label_values_and_special_numbers() {
+        let mut encoder = PrometheusTextEncoder::new();
+        encoder.gauge("m", "h", &[("k", "a\"b\\c\nd")], f64::NAN);
+        encoder.gauge("n", "h", &[], f64::INFINITY);
+        let text = encoder.encode();
+        assert!(text.contains("m{k=\"a\\\"b\\\\c\\nd\"} NaN\n"));
+        assert!(text.contains("n +Inf\n"));
+    }
+}
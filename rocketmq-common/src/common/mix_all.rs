@@ -17,6 +17,7 @@
 
 use std::collections::HashMap;
 use std::env;
+use std::str::FromStr;
 
 use cheetah_string::CheetahString;
 use once_cell::sync::Lazy;
@@ -183,6 +184,28 @@ pub fn string_to_properties(input: &str) -> Option<HashMap<CheetahString, Cheeta
     Some(properties)
 }
 
+/// Format properties as `key=value` lines sorted by key.
+pub fn properties_to_string(properties: &HashMap<CheetahString, CheetahString>) -> String {
+    let mut entries = properties.iter().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    let mut result = String::new();
+    for (key, value) in entries {
+        result.push_str(key.as_str());
+        result.push('=');
+        result.push_str(value.as_str());
+        result.push('\n');
+    }
+    result
+}
+
+/// Parse the value of a config property, the error names the key.
+pub fn parse_property_value<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid value '{}' for key '{}'", value, key))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -294,4 +317,22 @@ mod tests {
         let result = string_to_properties(input);
         assert!(result.is_none(), "Parsing should fail for invalid input");
     }
+
+    #[test]
+    fn properties_round_trip() {
+        let mut properties = HashMap::new();
+        properties.insert(CheetahString::from("b"), CheetahString::from("2"));
+        properties.insert(CheetahString::from("a"), CheetahString::from("1"));
+        let content = properties_to_string(&properties);
+        assert_eq!(content, "a=1\nb=2\n");
+        assert_eq!(string_to_properties(&content), Some(properties));
+    }
+
+    #[test]
+    fn parse_property_value_reports_key() {
+        assert_eq!(parse_property_value::<u32>("sendThreads", " 8 "), Ok(8));
+        assert!(parse_property_value::<bool>("enable", "yes")
+            .unwrap_err()
+            .contains("enable"));
+    }
 }
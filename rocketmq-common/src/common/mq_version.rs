@@ -1260,7 +1260,34 @@ impl From<RocketMqVersion> for i32 {
     }
 }
 impl RocketMqVersion {
-    pub const CURRENT_VERSION: RocketMqVersion = RocketMqVersion::HigherVerSion;
+    /// Version advertised in every command this process sends, matching the Java release whose
+    /// wire protocol is implemented.
+    pub const CURRENT_VERSION: RocketMqVersion = RocketMqVersion::V531;
+
+    /// Oldest version speaking the POP consumption protocol (pop, ack, change invisible time,
+    /// notification and polling info).
+    pub const POP_MIN_VERSION: RocketMqVersion = RocketMqVersion::V500;
+
+    /// Oldest version able to store a batch sent with `SendBatchMessage`.
+    pub const BATCH_MIN_VERSION: RocketMqVersion = RocketMqVersion::V410Snapshot;
+
+    /// Whether a peer advertising `version` supports features introduced in `required`.
+    #[inline]
+    pub fn is_supported(version: i32, required: RocketMqVersion) -> bool {
+        version >= i32::from(required)
+    }
+
+    /// Human readable name of a peer's version, falling back to `HigherVerSion` for versions
+    /// newer than this table and to the raw number for negative values.
+    pub fn version_desc(version: i32) -> String {
+        match RocketMqVersion::try_from(version) {
+            Ok(v) => v.to_string(),
+            Err(_) if version > i32::from(RocketMqVersion::HigherVerSion) => {
+                RocketMqVersion::HigherVerSion.to_string()
+            }
+            Err(_) => version.to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -1888,6 +1915,31 @@ impl fmt::Display for RocketMqVersion {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_version_from_str() {}
+
+    #[test]
+    fn version_desc_covers_known_newer_and_invalid_versions() {
+        assert_eq!(
+            RocketMqVersion::version_desc(i32::from(RocketMqVersion::V500)),
+            "V500"
+        );
+        assert_eq!(RocketMqVersion::version_desc(10_000), "HigherVerSion");
+        assert_eq!(RocketMqVersion::version_desc(-1), "-1");
+    }
+
+    #[test]
+    fn is_supported_compares_against_required_version() {
+        let v500 = i32::from(RocketMqVersion::V500);
+        assert!(RocketMqVersion::is_supported(
+            v500,
+            RocketMqVersion::POP_MIN_VERSION
+        ));
+        assert!(!RocketMqVersion::is_supported(
+            v500 - 1,
+            RocketMqVersion::POP_MIN_VERSION
+        ));
+    }
 }
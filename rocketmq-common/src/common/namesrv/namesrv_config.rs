@@ -22,6 +22,7 @@ use cheetah_string::CheetahString;
 use serde::Deserialize;
 use serde_json::Value;
 
+use crate::common::metrics::metrics_exporter_type::MetricsExporterType;
 use crate::common::mix_all::ROCKETMQ_HOME_ENV;
 use crate::common::mix_all::ROCKETMQ_HOME_PROPERTY;
 
@@ -92,6 +93,15 @@ pub struct NamesrvConfig {
 
     #[serde(alias = "configBlackList")]
     pub config_black_list: String,
+
+    #[serde(alias = "metricsExporterType")]
+    pub metrics_exporter_type: MetricsExporterType,
+
+    #[serde(alias = "metricsPromExporterHost")]
+    pub metrics_prom_exporter_host: String,
+
+    #[serde(alias = "metricsPromExporterPort")]
+    pub metrics_prom_exporter_port: u16,
 }
 
 impl Default for NamesrvConfig {
@@ -139,6 +149,9 @@ impl Default for NamesrvConfig {
             wait_seconds_for_service: 45,
             delete_topic_with_broker_registration: false,
             config_black_list: "configBlackList;configStorePath;kvConfigPath".to_string(),
+            metrics_exporter_type: MetricsExporterType::Disable,
+            metrics_prom_exporter_host: String::new(),
+            metrics_prom_exporter_port: 5558,
         }
     }
 }
@@ -236,6 +249,18 @@ impl NamesrvConfig {
             "configBlackList".to_string(),
             Value::String(self.config_black_list.clone()),
         );
+        json_map.insert(
+            "metricsExporterType".to_string(),
+            Value::String(self.metrics_exporter_type.to_string()),
+        );
+        json_map.insert(
+            "metricsPromExporterHost".to_string(),
+            Value::String(self.metrics_prom_exporter_host.clone()),
+        );
+        json_map.insert(
+            "metricsPromExporterPort".to_string(),
+            Value::Number(self.metrics_prom_exporter_port.into()),
+        );
 
         // Convert the HashMap to a JSON value
         match serde_json::to_string_pretty(&json_map) {
@@ -21,7 +21,16 @@ use serde::Serialize;
 #[serde(rename_all = "camelCase")]
 pub struct ServerConfig {
     pub listen_port: u32,
+    /// Host to bind, or a `unix:<path>` / `inproc:<name>` address served instead of TCP, in
+    /// which case `listen_port` is ignored.
     pub bind_address: String,
+    /// Requests taking longer than this to process are logged as slow requests.
+    #[serde(default = "default_slow_request_threshold_millis")]
+    pub slow_request_threshold_millis: u64,
+}
+
+fn default_slow_request_threshold_millis() -> u64 {
+    1000
 }
 
 impl Default for ServerConfig {
@@ -29,6 +38,7 @@ impl Default for ServerConfig {
         ServerConfig {
             listen_port: 10911,
             bind_address: "0.0.0.0".to_string(),
+            slow_request_threshold_millis: default_slow_request_threshold_millis(),
         }
     }
 }
@@ -42,3 +52,21 @@ impl ServerConfig {
         self.listen_port
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slow_request_threshold_defaults_when_missing() {
+        let config: ServerConfig =
+            serde_json::from_str(r#"{"listenPort":10911,"bindAddress":"0.0.0.0"}"#).unwrap();
+        assert_eq!(config.slow_request_threshold_millis, 1000);
+
+        let config: ServerConfig = serde_json::from_str(
+            r#"{"listenPort":10911,"bindAddress":"0.0.0.0","slowRequestThresholdMillis":200}"#,
+        )
+        .unwrap();
+        assert_eq!(config.slow_request_threshold_millis, 200);
+    }
+}
@@ -19,6 +19,7 @@ pub mod moment_stats_item;
 pub mod moment_stats_item_set;
 pub mod stats_item;
 pub mod stats_item_set;
+pub mod stats_scheduler;
 pub mod stats_snapshot;
 
 pub struct Stats;
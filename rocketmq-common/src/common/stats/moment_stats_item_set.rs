@@ -15,35 +15,25 @@
  * limitations under the License.
  */
 use std::sync::Arc;
-use std::sync::Mutex;
 
 use dashmap::DashMap;
-use tokio::task;
-use tokio::time::interval;
-use tokio::time::Duration;
 
 use crate::common::stats::moment_stats_item::MomentStatsItem;
-use crate::TimeUtils::get_current_millis;
-use crate::UtilAll::compute_next_minutes_time_millis;
 
 #[derive(Clone)]
 pub struct MomentStatsItemSet {
     stats_item_table: Arc<DashMap<String, MomentStatsItem>>,
     stats_name: String,
-    scheduled_task: Arc<Mutex<Option<task::JoinHandle<()>>>>,
 }
 
 impl MomentStatsItemSet {
+    /// Creates an empty set, its items are printed once it is registered to a started
+    /// [`StatsScheduler`](crate::common::stats::stats_scheduler::StatsScheduler).
     pub fn new(stats_name: String) -> Self {
-        let stats_item_table = Arc::new(DashMap::new());
-        let scheduled_task = Arc::new(Mutex::new(None));
-        let set = MomentStatsItemSet {
-            stats_item_table,
+        MomentStatsItemSet {
+            stats_item_table: Arc::new(DashMap::new()),
             stats_name,
-            scheduled_task,
-        };
-        set.init();
-        set
+        }
     }
 
     pub fn get_stats_item_table(&self) -> Arc<DashMap<String, MomentStatsItem>> {
@@ -54,26 +44,8 @@ impl MomentStatsItemSet {
         &self.stats_name
     }
 
-    pub fn init(&self) {
-        let stats_item_table = Arc::clone(&self.stats_item_table);
-        let initial_delay = Duration::from_millis(
-            (compute_next_minutes_time_millis() as i64 - get_current_millis() as i64)
-                .unsigned_abs(),
-        );
-
-        let mut interval = tokio::time::interval(Duration::from_secs(300));
-
-        task::spawn(async move {
-            tokio::time::sleep(initial_delay).await;
-            loop {
-                interval.tick().await;
-                MomentStatsItemSet::print_at_minutes(&stats_item_table);
-            }
-        });
-    }
-
-    fn print_at_minutes(stats_item_table: &DashMap<String, MomentStatsItem>) {
-        for entry in stats_item_table.iter() {
+    pub fn print_at_minutes(&self) {
+        for entry in self.stats_item_table.iter() {
             entry.value().print_at_minutes();
         }
     }
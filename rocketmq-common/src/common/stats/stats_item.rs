@@ -20,8 +20,6 @@ use std::fmt;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::thread;
-use std::time::Duration;
 use std::time::SystemTime;
 
 use parking_lot::Mutex;
@@ -87,191 +85,87 @@ impl StatsItem {
         Self::compute_stats_data(Arc::clone(&self.cs_list_day))
     }
 
-    pub fn init(&self) {
-        let cs_list_minute = Arc::clone(&self.cs_list_minute);
-        let cs_list_minute_clone = Arc::clone(&self.cs_list_minute);
-        let cs_list_hour = Arc::clone(&self.cs_list_hour);
-        let cs_list_hour_clone = Arc::clone(&self.cs_list_hour);
-        let cs_list_day = Arc::clone(&self.cs_list_day);
-        let cs_list_day_clone = Arc::clone(&self.cs_list_day);
-        let stats_name = self.stats_name.clone();
-        let stats_key = self.stats_key.clone();
-
-        thread::spawn(move || loop {
-            thread::sleep(Duration::from_secs(10));
-            Self::sampling_in_seconds(cs_list_minute.clone());
-        });
-
-        thread::spawn(move || loop {
-            thread::sleep(Duration::from_secs(600));
-            Self::sampling_in_minutes(cs_list_hour.clone());
-        });
-
-        thread::spawn(move || loop {
-            thread::sleep(Duration::from_secs(3600));
-            Self::sampling_in_hour(cs_list_day.clone());
-        });
-
-        let stats_name_clone = stats_name.clone();
-        let stats_key_clone = stats_key.clone();
-        thread::spawn(move || loop {
-            let sleep_time = Self::compute_next_minutes_time_millis()
-                - SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64;
-            thread::sleep(Duration::from_millis(sleep_time));
-            Self::print_at_minutes(
-                &stats_name_clone,
-                &stats_key_clone,
-                cs_list_minute_clone.clone(),
-            );
-        });
-
-        let stats_name_clone = stats_name.clone();
-        let stats_key_clone = stats_key.clone();
-        thread::spawn(move || loop {
-            let sleep_time = Self::compute_next_hour_time_millis()
-                - SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64;
-            thread::sleep(Duration::from_millis(sleep_time));
-            Self::print_at_hour(
-                &stats_name_clone,
-                &stats_key_clone,
-                cs_list_hour_clone.clone(),
-            );
-        });
-
-        thread::spawn(move || loop {
-            let sleep_time = Self::compute_next_morning_time_millis()
-                - SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64
-                - 2000;
-            thread::sleep(Duration::from_millis(sleep_time));
-            Self::print_at_day(&stats_name, &stats_key, cs_list_day_clone.clone());
-        });
+    pub fn add_value(&self, inc_value: u64, inc_times: u64) {
+        self.value.fetch_add(inc_value, Ordering::Relaxed);
+        self.times.fetch_add(inc_times, Ordering::Relaxed);
     }
 
-    pub fn sampling_in_seconds(cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>) {
-        let mut cs_list = cs_list.lock();
-        if cs_list.is_empty() {
-            cs_list.push_back(CallSnapshot::new(
-                SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64
-                    - 10 * 1000,
-                0,
-                0,
-            ));
-        }
-        cs_list.push_back(CallSnapshot::new(
-            SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
-            0,
-            0,
-        ));
-        if cs_list.len() > 7 {
-            cs_list.pop_front();
-        }
+    pub fn get_value(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
     }
 
-    pub fn sampling_in_minutes(cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>) {
-        let mut cs_list = cs_list.lock();
-        if cs_list.is_empty() {
-            cs_list.push_back(CallSnapshot::new(
-                SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64
-                    - 10 * 60 * 1000,
-                0,
-                0,
-            ));
-        }
-        cs_list.push_back(CallSnapshot::new(
-            SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
-            0,
-            0,
-        ));
-        if cs_list.len() > 7 {
-            cs_list.pop_front();
-        }
+    pub fn get_times(&self) -> u64 {
+        self.times.load(Ordering::Relaxed)
+    }
+
+    pub fn get_stats_name(&self) -> &str {
+        &self.stats_name
+    }
+
+    pub fn get_stats_key(&self) -> &str {
+        &self.stats_key
     }
 
-    pub fn sampling_in_hour(cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>) {
+    /// Samples the counters every ten seconds, keeping one minute of history.
+    pub fn sampling_in_seconds(&self) {
+        self.sampling(&self.cs_list_minute, 10 * 1000, 7);
+    }
+
+    /// Samples the counters every ten minutes, keeping one hour of history.
+    pub fn sampling_in_minutes(&self) {
+        self.sampling(&self.cs_list_hour, 10 * 60 * 1000, 7);
+    }
+
+    /// Samples the counters every hour, keeping one day of history.
+    pub fn sampling_in_hour(&self) {
+        self.sampling(&self.cs_list_day, 60 * 60 * 1000, 25);
+    }
+
+    fn sampling(&self, cs_list: &Mutex<LinkedList<CallSnapshot>>, period_millis: u64, max: usize) {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
         let mut cs_list = cs_list.lock();
         if cs_list.is_empty() {
-            cs_list.push_back(CallSnapshot::new(
-                SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64
-                    - 60 * 60 * 1000,
-                0,
-                0,
-            ));
+            cs_list.push_back(CallSnapshot::new(now - period_millis, 0, 0));
         }
         cs_list.push_back(CallSnapshot::new(
-            SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_millis() as u64,
-            0,
-            0,
+            now,
+            self.times.load(Ordering::Relaxed),
+            self.value.load(Ordering::Relaxed),
         ));
-        if cs_list.len() > 25 {
+        if cs_list.len() > max {
             cs_list.pop_front();
         }
     }
 
-    pub fn print_at_minutes(
-        stats_name: &str,
-        stats_key: &str,
-        cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>,
-    ) {
-        let ss = Self::compute_stats_data(cs_list);
+    pub fn print_at_minutes(&self) {
+        let ss = self.get_stats_data_in_minute();
         info!(
             "[{}] [{}] Stats In One Minute, {}",
-            stats_name,
-            stats_key,
+            self.stats_name,
+            self.stats_key,
             Self::stat_print_detail(ss)
         );
     }
 
-    pub fn print_at_hour(
-        stats_name: &str,
-        stats_key: &str,
-        cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>,
-    ) {
-        let ss = Self::compute_stats_data(cs_list);
+    pub fn print_at_hour(&self) {
+        let ss = self.get_stats_data_in_hour();
         info!(
             "[{}] [{}] Stats In One Hour, {}",
-            stats_name,
-            stats_key,
+            self.stats_name,
+            self.stats_key,
             Self::stat_print_detail(ss)
         );
     }
 
-    pub fn print_at_day(
-        stats_name: &str,
-        stats_key: &str,
-        cs_list: Arc<Mutex<LinkedList<CallSnapshot>>>,
-    ) {
-        let ss = Self::compute_stats_data(cs_list);
+    pub fn print_at_day(&self) {
+        let ss = self.get_stats_data_in_day();
         info!(
             "[{}] [{}] Stats In One Day, {}",
-            stats_name,
-            stats_key,
+            self.stats_name,
+            self.stats_key,
             Self::stat_print_detail(ss)
         );
     }
@@ -384,4 +278,20 @@ mod tests {
         assert_eq!(snapshot.get_times(), 0);
         assert_eq!(snapshot.get_avgpt(), 0.0);
     }
+
+    #[test]
+    fn sampling_records_accumulated_values() {
+        let stats_item = StatsItem::new("TestName", "TestKey");
+        stats_item.sampling_in_seconds();
+        stats_item.add_value(30, 3);
+        stats_item.add_value(70, 2);
+        stats_item.sampling_in_seconds();
+
+        assert_eq!(stats_item.get_value(), 100);
+        assert_eq!(stats_item.get_times(), 5);
+        let snapshot = stats_item.get_stats_data_in_minute();
+        assert_eq!(snapshot.get_sum(), 100);
+        assert_eq!(snapshot.get_times(), 5);
+        assert_eq!(snapshot.get_avgpt(), 20.0);
+    }
 }
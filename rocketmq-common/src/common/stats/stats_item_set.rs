@@ -14,17 +14,171 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::sync::Arc;
+
+use dashmap::DashMap;
+
+use crate::common::stats::stats_item::StatsItem;
 use crate::common::stats::stats_snapshot::StatsSnapshot;
 
-#[derive(Debug)]
-pub struct StatsItemSet {}
+#[derive(Debug, Clone)]
+pub struct StatsItemSet {
+    stats_item_table: Arc<DashMap<String, Arc<StatsItem>>>,
+    stats_name: String,
+}
 
 impl StatsItemSet {
-    pub fn new(_stats_name: String) -> Self {
-        StatsItemSet {}
+    /// Creates an empty set, its items are sampled and printed once it is registered to a
+    /// started [`StatsScheduler`](crate::common::stats::stats_scheduler::StatsScheduler).
+    pub fn new(stats_name: String) -> Self {
+        StatsItemSet {
+            stats_item_table: Arc::new(DashMap::new()),
+            stats_name,
+        }
+    }
+
+    pub fn get_stats_name(&self) -> &str {
+        &self.stats_name
+    }
+
+    pub fn sampling_in_seconds(&self) {
+        self.for_each_item(StatsItem::sampling_in_seconds);
+    }
+
+    pub fn sampling_in_minutes(&self) {
+        self.for_each_item(StatsItem::sampling_in_minutes);
+    }
+
+    pub fn sampling_in_hour(&self) {
+        self.for_each_item(StatsItem::sampling_in_hour);
+    }
+
+    pub fn print_at_minutes(&self) {
+        self.for_each_item(StatsItem::print_at_minutes);
+    }
+
+    pub fn print_at_hour(&self) {
+        self.for_each_item(StatsItem::print_at_hour);
+    }
+
+    pub fn print_at_day(&self) {
+        self.for_each_item(StatsItem::print_at_day);
+    }
+
+    fn for_each_item(&self, action: impl Fn(&StatsItem)) {
+        for entry in self.stats_item_table.iter() {
+            action(entry.value());
+        }
+    }
+
+    pub fn add_value(&self, stats_key: &str, inc_value: i32, inc_times: i32) {
+        let stats_item = self.get_and_create_stats_item(stats_key);
+        stats_item.add_value(inc_value as u64, inc_times as u64);
+    }
+
+    pub fn del_value(&self, stats_key: &str) {
+        self.stats_item_table.remove(stats_key);
+    }
+
+    pub fn del_value_by_prefix_key(&self, stats_key: &str, separator: &str) {
+        let prefix = format!("{}{}", stats_key, separator);
+        self.stats_item_table
+            .retain(|key, _| !key.starts_with(prefix.as_str()));
+    }
+
+    pub fn del_value_by_infix_key(&self, stats_key: &str, separator: &str) {
+        let infix = format!("{}{}{}", separator, stats_key, separator);
+        self.stats_item_table
+            .retain(|key, _| !key.contains(infix.as_str()));
+    }
+
+    pub fn del_value_by_suffix_key(&self, stats_key: &str, separator: &str) {
+        let suffix = format!("{}{}", separator, stats_key);
+        self.stats_item_table
+            .retain(|key, _| !key.ends_with(suffix.as_str()));
+    }
+
+    pub fn get_and_create_stats_item(&self, stats_key: &str) -> Arc<StatsItem> {
+        if let Some(stats_item) = self.stats_item_table.get(stats_key) {
+            return Arc::clone(stats_item.value());
+        }
+        self.stats_item_table
+            .entry(stats_key.to_string())
+            .or_insert_with(|| Arc::new(StatsItem::new(&self.stats_name, stats_key)))
+            .value()
+            .clone()
+    }
+
+    /// Returns a snapshot of all the items currently in this set.
+    pub fn get_stats_items(&self) -> Vec<Arc<StatsItem>> {
+        self.stats_item_table
+            .iter()
+            .map(|entry| Arc::clone(entry.value()))
+            .collect()
+    }
+
+    pub fn get_stats_item(&self, stats_key: &str) -> Option<Arc<StatsItem>> {
+        self.stats_item_table
+            .get(stats_key)
+            .map(|stats_item| Arc::clone(stats_item.value()))
     }
 
     pub fn get_stats_data_in_minute(&self, stats_key: &str) -> StatsSnapshot {
-        StatsSnapshot::new()
+        match self.get_stats_item(stats_key) {
+            Some(stats_item) => stats_item.get_stats_data_in_minute(),
+            None => StatsSnapshot::new(),
+        }
+    }
+
+    pub fn get_stats_data_in_hour(&self, stats_key: &str) -> StatsSnapshot {
+        match self.get_stats_item(stats_key) {
+            Some(stats_item) => stats_item.get_stats_data_in_hour(),
+            None => StatsSnapshot::new(),
+        }
+    }
+
+    pub fn get_stats_data_in_day(&self, stats_key: &str) -> StatsSnapshot {
+        match self.get_stats_item(stats_key) {
+            Some(stats_item) => stats_item.get_stats_data_in_day(),
+            None => StatsSnapshot::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_value_accumulates_per_key() {
+        let stats_set = StatsItemSet::new("TestName".to_string());
+        stats_set.add_value("TestKey", 10, 1);
+        stats_set.add_value("TestKey", 5, 2);
+        stats_set.add_value("OtherKey", 1, 1);
+
+        let stats_item = stats_set.get_stats_item("TestKey").unwrap();
+        assert_eq!(stats_item.get_value(), 15);
+        assert_eq!(stats_item.get_times(), 3);
+        assert_eq!(stats_item.get_stats_name(), "TestName");
+        assert_eq!(stats_set.get_stats_item("OtherKey").unwrap().get_value(), 1);
+        assert!(stats_set.get_stats_item("MissingKey").is_none());
+    }
+
+    #[test]
+    fn del_value_removes_matching_keys() {
+        let stats_set = StatsItemSet::new("TestName".to_string());
+        stats_set.add_value("topic@group", 1, 1);
+        stats_set.add_value("0@topic@group", 1, 1);
+        stats_set.add_value("other@group2", 1, 1);
+        stats_set.add_value("single", 1, 1);
+
+        stats_set.del_value_by_prefix_key("topic", "@");
+        assert!(stats_set.get_stats_item("topic@group").is_none());
+        stats_set.del_value_by_infix_key("topic", "@");
+        assert!(stats_set.get_stats_item("0@topic@group").is_none());
+        stats_set.del_value_by_suffix_key("group2", "@");
+        assert!(stats_set.get_stats_item("other@group2").is_none());
+        stats_set.del_value("single");
+        assert!(stats_set.get_stats_item("single").is_none());
     }
 }
@@ -0,0 +1,176 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use parking_lot::RwLock;
+use tokio::task::JoinHandle;
+use tokio::time::interval_at;
+use tokio::time::Duration;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::common::stats::moment_stats_item_set::MomentStatsItemSet;
+use crate::common::stats::stats_item::StatsItem;
+use crate::common::stats::stats_item_set::StatsItemSet;
+use crate::TimeUtils::get_current_millis;
+
+/// Samples and prints the stats sets registered by one stats manager on a single background
+/// task, the counterpart of the scheduled executor a Java stats manager hands to its sets.
+///
+/// Sets can be registered before the scheduler is started, so stats managers can be built
+/// outside of a runtime. The task is cancelled by [`StatsScheduler::shutdown`] or when the
+/// scheduler is dropped.
+#[derive(Default)]
+pub struct StatsScheduler {
+    item_sets: Arc<RwLock<Vec<StatsItemSet>>>,
+    moment_item_sets: Arc<RwLock<Vec<MomentStatsItemSet>>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl StatsScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, item_set: StatsItemSet) {
+        self.item_sets.write().push(item_set);
+    }
+
+    pub fn register_moment(&self, item_set: MomentStatsItemSet) {
+        self.moment_item_sets.write().push(item_set);
+    }
+
+    /// Starts sampling and printing on the current runtime, does nothing if it is already
+    /// started.
+    pub fn start(&self) {
+        let mut task = self.task.lock();
+        if task.is_some() {
+            return;
+        }
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            warn!("stats scheduler is not started, no tokio runtime is available");
+            return;
+        };
+        let item_sets = Arc::clone(&self.item_sets);
+        let moment_item_sets = Arc::clone(&self.moment_item_sets);
+        *task = Some(handle.spawn(async move {
+            let now = Instant::now();
+            let mut sampling_in_seconds = interval_at(now, Duration::from_secs(10));
+            let mut sampling_in_minutes = interval_at(now, Duration::from_secs(600));
+            let mut sampling_in_hour = interval_at(now, Duration::from_secs(3600));
+            let next_minute = now + delay_until(StatsItem::compute_next_minutes_time_millis());
+            let mut print_at_minutes = interval_at(next_minute, Duration::from_secs(60));
+            let mut print_moment_at_minutes = interval_at(next_minute, Duration::from_secs(300));
+            let mut print_at_hour = interval_at(
+                now + delay_until(StatsItem::compute_next_hour_time_millis()),
+                Duration::from_secs(3600),
+            );
+            let mut print_at_day = interval_at(
+                now + delay_until(StatsItem::compute_next_morning_time_millis())
+                    .saturating_sub(Duration::from_secs(2)),
+                Duration::from_secs(24 * 3600),
+            );
+            loop {
+                tokio::select! {
+                    _ = sampling_in_seconds.tick() => {
+                        item_sets.read().iter().for_each(StatsItemSet::sampling_in_seconds);
+                    }
+                    _ = sampling_in_minutes.tick() => {
+                        item_sets.read().iter().for_each(StatsItemSet::sampling_in_minutes);
+                    }
+                    _ = sampling_in_hour.tick() => {
+                        item_sets.read().iter().for_each(StatsItemSet::sampling_in_hour);
+                    }
+                    _ = print_at_minutes.tick() => {
+                        item_sets.read().iter().for_each(StatsItemSet::print_at_minutes);
+                    }
+                    _ = print_moment_at_minutes.tick() => {
+                        moment_item_sets
+                            .read()
+                            .iter()
+                            .for_each(MomentStatsItemSet::print_at_minutes);
+                    }
+                    _ = print_at_hour.tick() => {
+                        item_sets.read().iter().for_each(StatsItemSet::print_at_hour);
+                    }
+                    _ = print_at_day.tick() => {
+                        item_sets.read().iter().for_each(StatsItemSet::print_at_day);
+                    }
+                }
+            }
+        }));
+    }
+
+    /// Cancels the background task. The registered sets keep their values and the scheduler
+    /// can be started again.
+    pub fn shutdown(&self) {
+        if let Some(task) = self.task.lock().take() {
+            task.abort();
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.task.lock().is_some()
+    }
+}
+
+impl Drop for StatsScheduler {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn delay_until(time_millis: u64) -> Duration {
+    Duration::from_millis(time_millis.saturating_sub(get_current_millis()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_can_be_registered_without_a_runtime() {
+        let scheduler = StatsScheduler::new();
+        scheduler.register(StatsItemSet::new("TestName".to_string()));
+        scheduler.register_moment(MomentStatsItemSet::new("TestName".to_string()));
+        scheduler.start();
+        assert!(!scheduler.is_started());
+    }
+
+    #[tokio::test]
+    async fn samples_registered_sets_until_shutdown() {
+        let scheduler = StatsScheduler::new();
+        let item_set = StatsItemSet::new("TestName".to_string());
+        item_set.add_value("key", 10, 1);
+        scheduler.register(item_set.clone());
+        scheduler.start();
+        assert!(scheduler.is_started());
+
+        // the sampling intervals fire once right after start
+        for _ in 0..100 {
+            if item_set.get_stats_data_in_minute("key").get_sum() == 10 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(item_set.get_stats_data_in_minute("key").get_sum(), 10);
+
+        scheduler.shutdown();
+        assert!(!scheduler.is_started());
+    }
+}
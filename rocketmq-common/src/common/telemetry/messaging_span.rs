@@ -0,0 +1,70 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use crate::common::telemetry::trace_parent::TraceParent;
+
+/// OpenTelemetry messaging semantic convention attribute keys recorded on RocketMQ spans.
+pub mod attributes {
+    pub const MESSAGING_SYSTEM: &str = "messaging.system";
+    pub const MESSAGING_OPERATION: &str = "messaging.operation";
+    pub const MESSAGING_DESTINATION_NAME: &str = "messaging.destination.name";
+    pub const MESSAGING_MESSAGE_ID: &str = "messaging.message.id";
+    pub const MESSAGING_CONSUMER_GROUP: &str = "messaging.rocketmq.client_group";
+    pub const MESSAGING_MESSAGE_TAG: &str = "messaging.rocketmq.message.tag";
+    pub const MESSAGING_MESSAGE_KEYS: &str = "messaging.rocketmq.message.keys";
+    pub const MESSAGING_QUEUE_ID: &str = "messaging.rocketmq.queue_id";
+    pub const MESSAGING_QUEUE_OFFSET: &str = "messaging.rocketmq.queue_offset";
+    pub const MESSAGING_BATCH_MESSAGE_COUNT: &str = "messaging.batch.message_count";
+    pub const SERVER_ADDRESS: &str = "server.address";
+
+    pub const MESSAGING_SYSTEM_ROCKETMQ: &str = "rocketmq";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpanKind {
+    Producer,
+    Consumer,
+    Client,
+    Server,
+}
+
+impl SpanKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpanKind::Producer => "producer",
+            SpanKind::Consumer => "consumer",
+            SpanKind::Client => "client",
+            SpanKind::Server => "server",
+        }
+    }
+}
+
+/// A span started by a
+/// [`TracerProvider`](crate::common::telemetry::tracer_provider::TracerProvider).
+///
+/// The span is finished by calling [`end`](MessagingSpan::end); dropping it without ending
+/// is treated as ending it by the default provider.
+pub trait MessagingSpan: Send {
+    /// The trace context of this span, propagated to downstream spans.
+    fn context(&self) -> TraceParent;
+
+    fn set_attribute(&mut self, key: &'static str, value: String);
+
+    /// Marks the span as failed.
+    fn record_error(&mut self, error: &str);
+
+    fn end(self: Box<Self>);
+}
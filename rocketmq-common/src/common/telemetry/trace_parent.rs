@@ -0,0 +1,217 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use cheetah_string::CheetahString;
+use uuid::Uuid;
+
+use crate::common::message::MessageConst;
+use crate::common::message::MessageTrait;
+
+/// W3C trace context (`traceparent`) carried in the `TRACEPARENT` message property, used to
+/// link producer, broker and consumer spans of the same message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceParent {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    trace_flags: u8,
+}
+
+impl TraceParent {
+    pub const VERSION: u8 = 0;
+    pub const FLAG_SAMPLED: u8 = 0x01;
+
+    pub fn new(trace_id: [u8; 16], span_id: [u8; 8], trace_flags: u8) -> Self {
+        TraceParent {
+            trace_id,
+            span_id,
+            trace_flags,
+        }
+    }
+
+    /// Starts a new sampled trace with random trace and span ids.
+    pub fn new_root() -> Self {
+        TraceParent::new(
+            *Uuid::new_v4().as_bytes(),
+            random_span_id(),
+            Self::FLAG_SAMPLED,
+        )
+    }
+
+    /// Creates the context of a child span in the same trace.
+    pub fn new_child(&self) -> Self {
+        TraceParent::new(self.trace_id, random_span_id(), self.trace_flags)
+    }
+
+    #[inline]
+    pub fn trace_id(&self) -> &[u8; 16] {
+        &self.trace_id
+    }
+
+    #[inline]
+    pub fn span_id(&self) -> &[u8; 8] {
+        &self.span_id
+    }
+
+    #[inline]
+    pub fn trace_flags(&self) -> u8 {
+        self.trace_flags
+    }
+
+    #[inline]
+    pub fn is_sampled(&self) -> bool {
+        self.trace_flags & Self::FLAG_SAMPLED != 0
+    }
+
+    pub fn trace_id_hex(&self) -> String {
+        to_hex(&self.trace_id)
+    }
+
+    pub fn span_id_hex(&self) -> String {
+        to_hex(&self.span_id)
+    }
+
+    /// Parses a `traceparent` header value, returning `None` for malformed or invalid values.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('-');
+        let version = parse_hex::<1>(parts.next()?)?[0];
+        let trace_id = parse_hex::<16>(parts.next()?)?;
+        let span_id = parse_hex::<8>(parts.next()?)?;
+        let trace_flags = parse_hex::<1>(parts.next()?)?[0];
+        // version 0xff is forbidden, and version 0 has exactly four fields
+        if version == 0xff || (version == Self::VERSION && parts.next().is_some()) {
+            return None;
+        }
+        if trace_id == [0u8; 16] || span_id == [0u8; 8] {
+            return None;
+        }
+        Some(TraceParent::new(trace_id, span_id, trace_flags))
+    }
+
+    /// Reads the trace context from the message properties.
+    pub fn from_message<M: MessageTrait + ?Sized>(msg: &M) -> Option<Self> {
+        msg.get_property(&CheetahString::from_static_str(
+            MessageConst::PROPERTY_TRACE_PARENT,
+        ))
+        .and_then(|value| Self::parse(value.as_str()))
+    }
+
+    /// Writes the trace context into the message properties.
+    pub fn inject<M: MessageTrait + ?Sized>(&self, msg: &mut M) {
+        msg.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_TRACE_PARENT),
+            CheetahString::from_string(self.to_string()),
+        );
+    }
+}
+
+impl Display for TraceParent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02x}-{}-{}-{:02x}",
+            Self::VERSION,
+            self.trace_id_hex(),
+            self.span_id_hex(),
+            self.trace_flags
+        )
+    }
+}
+
+fn random_span_id() -> [u8; 8] {
+    let mut span_id = [0u8; 8];
+    span_id.copy_from_slice(&Uuid::new_v4().as_bytes()[..8]);
+    if span_id == [0u8; 8] {
+        span_id[7] = 1;
+    }
+    span_id
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn parse_hex<const N: usize>(value: &str) -> Option<[u8; N]> {
+    // only lowercase hex is valid in a traceparent
+    if value.len() != N * 2
+        || !value
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::message::message_single::Message;
+
+    #[test]
+    fn parse_and_format_round_trip() {
+        let value = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let trace_parent = TraceParent::parse(value).unwrap();
+        assert_eq!(
+            trace_parent.trace_id_hex(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(trace_parent.span_id_hex(), "00f067aa0ba902b7");
+        assert!(trace_parent.is_sampled());
+        assert_eq!(trace_parent.to_string(), value);
+    }
+
+    #[test]
+    fn parse_rejects_invalid_values() {
+        assert!(TraceParent::parse("").is_none());
+        assert!(
+            TraceParent::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none()
+        );
+        assert!(
+            TraceParent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01").is_none()
+        );
+        assert!(
+            TraceParent::parse("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01").is_none()
+        );
+        assert!(
+            TraceParent::parse("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01").is_none()
+        );
+        assert!(TraceParent::parse(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra"
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn child_keeps_trace_id_and_injects_into_message() {
+        let root = TraceParent::new_root();
+        let child = root.new_child();
+        assert_eq!(root.trace_id(), child.trace_id());
+        assert_ne!(root.span_id(), child.span_id());
+
+        let mut msg = Message::default();
+        assert!(TraceParent::from_message(&msg).is_none());
+        child.inject(&mut msg);
+        assert_eq!(TraceParent::from_message(&msg), Some(child));
+    }
+}
@@ -0,0 +1,150 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use tracing::field;
+use tracing::Span;
+
+use crate::common::telemetry::messaging_span::attributes;
+use crate::common::telemetry::messaging_span::MessagingSpan;
+use crate::common::telemetry::messaging_span::SpanKind;
+use crate::common::telemetry::trace_parent::TraceParent;
+
+/// Creates the spans of the send, pull and consume paths.
+///
+/// Install an implementation backed by an OpenTelemetry SDK tracer with
+/// [`set_tracer_provider`] to export spans to Jaeger, Tempo or any OTLP backend.
+pub trait TracerProvider: Send + Sync {
+    /// Starts a span, as a child of `parent` when the caller has a propagated context.
+    fn start_span(
+        &self,
+        name: String,
+        kind: SpanKind,
+        parent: Option<TraceParent>,
+    ) -> Box<dyn MessagingSpan>;
+}
+
+static GLOBAL_TRACER_PROVIDER: Lazy<RwLock<Arc<dyn TracerProvider>>> =
+    Lazy::new(|| RwLock::new(Arc::new(TracingTracerProvider)));
+
+/// Replaces the process wide tracer provider.
+pub fn set_tracer_provider(provider: Arc<dyn TracerProvider>) {
+    *GLOBAL_TRACER_PROVIDER.write() = provider;
+}
+
+pub fn tracer_provider() -> Arc<dyn TracerProvider> {
+    GLOBAL_TRACER_PROVIDER.read().clone()
+}
+
+/// Starts a span with the process wide tracer provider.
+pub fn start_span(
+    name: String,
+    kind: SpanKind,
+    parent: Option<TraceParent>,
+) -> Box<dyn MessagingSpan> {
+    tracer_provider().start_span(name, kind, parent)
+}
+
+/// Default provider that emits `tracing` spans, with the W3C ids recorded as span fields so
+/// they can be correlated by a `tracing` subscriber.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TracingTracerProvider;
+
+impl TracerProvider for TracingTracerProvider {
+    fn start_span(
+        &self,
+        name: String,
+        kind: SpanKind,
+        parent: Option<TraceParent>,
+    ) -> Box<dyn MessagingSpan> {
+        let context = parent.map_or_else(TraceParent::new_root, |parent| parent.new_child());
+        let span = tracing::info_span!(
+            target: "rocketmq::telemetry",
+            "messaging",
+            otel.name = %name,
+            otel.kind = kind.as_str(),
+            otel.status_code = field::Empty,
+            trace_id = %context.trace_id_hex(),
+            span_id = %context.span_id_hex(),
+            parent_span_id = parent.map(|parent| parent.span_id_hex()),
+            messaging.system = attributes::MESSAGING_SYSTEM_ROCKETMQ,
+            messaging.operation = field::Empty,
+            messaging.destination.name = field::Empty,
+            messaging.message.id = field::Empty,
+            messaging.rocketmq.client_group = field::Empty,
+            messaging.rocketmq.message.tag = field::Empty,
+            messaging.rocketmq.message.keys = field::Empty,
+            messaging.rocketmq.queue_id = field::Empty,
+            messaging.rocketmq.queue_offset = field::Empty,
+            messaging.batch.message_count = field::Empty,
+            server.address = field::Empty,
+            error.message = field::Empty,
+        );
+        Box::new(TracingMessagingSpan { span, context })
+    }
+}
+
+struct TracingMessagingSpan {
+    span: Span,
+    context: TraceParent,
+}
+
+impl MessagingSpan for TracingMessagingSpan {
+    fn context(&self) -> TraceParent {
+        self.context
+    }
+
+    fn set_attribute(&mut self, key: &'static str, value: String) {
+        // keys that are not declared on the span are ignored by `tracing`
+        self.span.record(key, value.as_str());
+    }
+
+    fn record_error(&mut self, error: &str) {
+        self.span.record("otel.status_code", "ERROR");
+        self.span.record("error.message", error);
+    }
+
+    fn end(self: Box<Self>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_provider_continues_parent_trace() {
+        let parent = TraceParent::new_root();
+        let mut span = start_span(
+            "TopicTest send".to_string(),
+            SpanKind::Producer,
+            Some(parent),
+        );
+        span.set_attribute(
+            attributes::MESSAGING_DESTINATION_NAME,
+            "TopicTest".to_string(),
+        );
+        let context = span.context();
+        span.end();
+        assert_eq!(context.trace_id(), parent.trace_id());
+        assert_ne!(context.span_id(), parent.span_id());
+
+        let root = start_span("TopicTest send".to_string(), SpanKind::Producer, None);
+        assert!(root.context().is_sampled());
+    }
+}
@@ -21,7 +21,7 @@ use std::sync::Mutex;
 use cheetah_string::CheetahString;
 use lazy_static::lazy_static;
 
-pub const TOPIC_MAX_LENGTH: usize = 127;
+pub const TOPIC_MAX_LENGTH: usize = crate::common::validation::TOPIC_MAX_LENGTH;
 
 lazy_static! {
     static ref VALID_CHAR_BIT_MAP: [bool; 128] = {
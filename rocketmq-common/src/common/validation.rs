@@ -0,0 +1,242 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use cheetah_string::CheetahString;
+
+use crate::common::message::message_decoder::NAME_VALUE_SEPARATOR;
+use crate::common::message::message_decoder::PROPERTY_SEPARATOR;
+use crate::common::topic::TopicValidator;
+
+// Limits shared by the client-side validators and the broker processors, so the client rejects
+// exactly what the broker would reject.
+
+/// Max length of a consumer or producer group name.
+pub const CHARACTER_MAX_LENGTH: usize = 255;
+pub const TOPIC_MAX_LENGTH: usize = 127;
+/// Default max body size, brokers and producers can lower or raise it in their config.
+pub const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 4;
+/// The encoded properties length is stored as a short in the commit log.
+pub const MAX_PROPERTIES_LENGTH: usize = i16::MAX as usize;
+pub const PROPERTY_KEY_MAX_LENGTH: usize = CHARACTER_MAX_LENGTH;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    IllegalTopic,
+    IllegalGroup,
+    /// A system topic that producers must not send to.
+    ForbiddenTopic,
+    IllegalMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    kind: ValidationErrorKind,
+    remark: String,
+}
+
+impl ValidationError {
+    fn new(kind: ValidationErrorKind, remark: impl Into<String>) -> Self {
+        Self {
+            kind,
+            remark: remark.into(),
+        }
+    }
+
+    pub fn kind(&self) -> ValidationErrorKind {
+        self.kind
+    }
+
+    pub fn remark(&self) -> &str {
+        &self.remark
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.remark)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+pub fn check_topic(topic: &str) -> Result<(), ValidationError> {
+    let result = TopicValidator::validate_topic(topic);
+    if !result.valid() {
+        return Err(ValidationError::new(
+            ValidationErrorKind::IllegalTopic,
+            result.remark().as_str(),
+        ));
+    }
+    Ok(())
+}
+
+pub fn check_send_topic(topic: &str) -> Result<(), ValidationError> {
+    if TopicValidator::is_not_allowed_send_topic(topic) {
+        return Err(ValidationError::new(
+            ValidationErrorKind::ForbiddenTopic,
+            format!("Sending message to topic[{}] is forbidden.", topic),
+        ));
+    }
+    Ok(())
+}
+
+pub fn check_group(group: &str) -> Result<(), ValidationError> {
+    if group.trim().is_empty() {
+        return Err(ValidationError::new(
+            ValidationErrorKind::IllegalGroup,
+            "the specified group is blank",
+        ));
+    }
+    if group.len() > CHARACTER_MAX_LENGTH {
+        return Err(ValidationError::new(
+            ValidationErrorKind::IllegalGroup,
+            format!(
+                "the specified group is longer than group max length {}.",
+                CHARACTER_MAX_LENGTH
+            ),
+        ));
+    }
+    if TopicValidator::is_topic_or_group_illegal(group) {
+        return Err(ValidationError::new(
+            ValidationErrorKind::IllegalGroup,
+            format!(
+                "the specified group[{}] contains illegal characters, allowing only \
+                 ^[%|a-zA-Z0-9_-]+$",
+                group
+            ),
+        ));
+    }
+    Ok(())
+}
+
+pub fn check_body_size(body_length: usize, max_message_size: usize) -> Result<(), ValidationError> {
+    if body_length > max_message_size {
+        return Err(ValidationError::new(
+            ValidationErrorKind::IllegalMessage,
+            format!(
+                "the message body size over max value, MAX: {}",
+                max_message_size
+            ),
+        ));
+    }
+    Ok(())
+}
+
+pub fn check_properties_length(properties_length: usize) -> Result<(), ValidationError> {
+    if properties_length > MAX_PROPERTIES_LENGTH {
+        return Err(ValidationError::new(
+            ValidationErrorKind::IllegalMessage,
+            format!(
+                "the message properties length over max value, MAX: {}",
+                MAX_PROPERTIES_LENGTH
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks every property can be encoded, keys and values must not contain the separators used
+/// by the properties wire format.
+pub fn check_properties(
+    properties: &HashMap<CheetahString, CheetahString>,
+) -> Result<(), ValidationError> {
+    let is_separator = |c: char| c == NAME_VALUE_SEPARATOR || c == PROPERTY_SEPARATOR;
+    let mut properties_length = 0;
+    for (key, value) in properties {
+        if key.is_empty() || key.len() > PROPERTY_KEY_MAX_LENGTH {
+            return Err(ValidationError::new(
+                ValidationErrorKind::IllegalMessage,
+                format!(
+                    "the message property key length must be between 1 and {}, key: {}",
+                    PROPERTY_KEY_MAX_LENGTH, key
+                ),
+            ));
+        }
+        if key.contains(is_separator) || value.contains(is_separator) {
+            return Err(ValidationError::new(
+                ValidationErrorKind::IllegalMessage,
+                format!(
+                    "the message property {} contains a reserved separator character",
+                    key
+                ),
+            ));
+        }
+        // key, value and the two separators
+        properties_length += key.len() + value.len() + 2;
+    }
+    check_properties_length(properties_length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_group_rejects_blank_long_and_illegal_names() {
+        assert_eq!(
+            check_group("").unwrap_err().kind(),
+            ValidationErrorKind::IllegalGroup
+        );
+        assert!(check_group(&"a".repeat(CHARACTER_MAX_LENGTH + 1)).is_err());
+        assert!(check_group("illegal@group").is_err());
+        assert!(check_group("valid_group-1%").is_ok());
+    }
+
+    #[test]
+    fn check_topic_and_send_topic() {
+        assert_eq!(
+            check_topic(&"a".repeat(TOPIC_MAX_LENGTH + 1))
+                .unwrap_err()
+                .kind(),
+            ValidationErrorKind::IllegalTopic
+        );
+        assert!(check_topic("valid_topic").is_ok());
+        assert_eq!(
+            check_send_topic(TopicValidator::RMQ_SYS_SCHEDULE_TOPIC)
+                .unwrap_err()
+                .kind(),
+            ValidationErrorKind::ForbiddenTopic
+        );
+        assert!(check_send_topic("valid_topic").is_ok());
+    }
+
+    #[test]
+    fn check_message_limits() {
+        assert!(check_body_size(MAX_MESSAGE_SIZE, MAX_MESSAGE_SIZE).is_ok());
+        assert!(check_body_size(MAX_MESSAGE_SIZE + 1, MAX_MESSAGE_SIZE).is_err());
+        assert!(check_properties_length(MAX_PROPERTIES_LENGTH + 1).is_err());
+
+        let mut properties = HashMap::new();
+        properties.insert(
+            CheetahString::from_static_str("KEYS"),
+            CheetahString::from_static_str("order-1"),
+        );
+        assert!(check_properties(&properties).is_ok());
+        properties.insert(
+            CheetahString::from_static_str("bad"),
+            CheetahString::from_string(format!("a{}b", PROPERTY_SEPARATOR)),
+        );
+        assert_eq!(
+            check_properties(&properties).unwrap_err().kind(),
+            ValidationErrorKind::IllegalMessage
+        );
+    }
+}
@@ -53,15 +53,20 @@ pub fn file_to_string_impl(file: &File) -> Result<String, io::Error> {
 pub fn string_to_file(str_content: &str, file_name: &str) -> io::Result<()> {
     let lock = LOCK.lock();
 
-    let bak_file = format!("{}.bak", file_name);
+    // Write new content to a temporary file first, so a crash never leaves a truncated file
+    let tmp_file = format!("{}.tmp", file_name);
+    string_to_file_not_safe(str_content, &tmp_file)?;
 
     // Read previous content and create a backup
+    let bak_file = format!("{}.bak", file_name);
     if let Ok(prev_content) = file_to_string(file_name) {
-        string_to_file_not_safe(&prev_content, &bak_file)?;
+        if !prev_content.is_empty() {
+            string_to_file_not_safe(&prev_content, &bak_file)?;
+        }
     }
 
-    // Write new content to the file
-    string_to_file_not_safe(str_content, file_name)?;
+    // Replace the file with the new content
+    std::fs::rename(&tmp_file, file_name)?;
     drop(lock);
     Ok(())
 }
@@ -80,6 +85,7 @@ fn write_string_to_file(file: &File, data: &str, _encoding: &str) -> io::Result<
     let mut os = io::BufWriter::new(file);
 
     os.write_all(data.as_bytes())?;
+    os.flush()?;
 
     Ok(())
 }
@@ -120,4 +126,21 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(std::fs::read_to_string(file_path).unwrap(), content);
     }
+
+    #[test]
+    fn test_string_to_file_keeps_backup() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("config").join("topics.json");
+        let file_path = file_path.to_str().unwrap();
+
+        string_to_file("first", file_path).unwrap();
+        string_to_file("second", file_path).unwrap();
+
+        assert_eq!(std::fs::read_to_string(file_path).unwrap(), "second");
+        assert_eq!(
+            std::fs::read_to_string(format!("{}.bak", file_path)).unwrap(),
+            "first"
+        );
+        assert!(!Path::new(&format!("{}.tmp", file_path)).exists());
+    }
 }
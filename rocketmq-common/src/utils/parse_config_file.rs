@@ -15,16 +15,38 @@
  * limitations under the License.
  */
 
+use std::collections::HashMap;
 use std::fmt::Debug;
+use std::path::Path;
 use std::path::PathBuf;
 
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Context;
+use cheetah_string::CheetahString;
 use config::Config;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
 
-pub fn parse_config_file<'de, C>(config_file: PathBuf) -> anyhow::Result<C, anyhow::Error>
+pub fn parse_config_file<C>(config_file: PathBuf) -> anyhow::Result<C, anyhow::Error>
 where
-    C: Default + Debug + Deserialize<'de>,
+    C: Default + Debug + DeserializeOwned,
 {
+    // `config` lowercases the keys, TOML and JSON are read directly so camelCase keys match
+    let extension = config_file.extension().and_then(|ext| ext.to_str());
+    if matches!(extension, Some("toml") | Some("json")) && config_file.exists() {
+        let content = std::fs::read_to_string(&config_file)
+            .with_context(|| format!("read config file {} failed", config_file.display()))?;
+        let config = if extension == Some("toml") {
+            toml::from_str(&content)?
+        } else {
+            serde_json::from_str(&content)?
+        };
+        return Ok(config);
+    }
     let config_file = Config::builder()
         .add_source(config::File::with_name(
             config_file.to_string_lossy().into_owned().as_str(),
@@ -36,3 +58,396 @@ where
     //info!("parse config: {:?}", config_file);
     Ok(config_file)
 }
+
+/// Whether the file holds Java style `key=value` properties (`broker.conf`,
+/// `namesrv.properties`) rather than a format understood by [`parse_config_file`].
+pub fn is_properties_file(config_file: &Path) -> bool {
+    matches!(
+        config_file.extension().and_then(|ext| ext.to_str()),
+        Some("conf") | Some("properties") | None
+    )
+}
+
+/// Reads a Java style properties file.
+pub fn parse_properties_file(
+    config_file: &Path,
+) -> anyhow::Result<HashMap<CheetahString, CheetahString>> {
+    let content = std::fs::read_to_string(config_file)
+        .with_context(|| format!("read config file {} failed", config_file.display()))?;
+    Ok(parse_properties(&content))
+}
+
+/// Parses Java style properties: `#` and `!` comments, `=`, `:` or whitespace as the key/value
+/// separator and a trailing `\` to continue a value on the next line.
+pub fn parse_properties(content: &str) -> HashMap<CheetahString, CheetahString> {
+    let mut properties = HashMap::new();
+    let mut logical_line = String::new();
+    for line in content.lines() {
+        let line = line.trim_start();
+        if logical_line.is_empty() && (line.is_empty() || line.starts_with(['#', '!'])) {
+            continue;
+        }
+        let continued = line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1;
+        if continued {
+            logical_line.push_str(&line[..line.len() - 1]);
+            continue;
+        }
+        logical_line.push_str(line);
+        if let Some((key, value)) = split_property(&logical_line) {
+            properties.insert(CheetahString::from(key), CheetahString::from(value));
+        }
+        logical_line.clear();
+    }
+    if let Some((key, value)) = split_property(&logical_line) {
+        properties.insert(CheetahString::from(key), CheetahString::from(value));
+    }
+    properties
+}
+
+fn split_property(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let mut key = String::new();
+    let mut chars = line.chars();
+    let mut escaped = false;
+    for c in chars.by_ref() {
+        if escaped {
+            key.push(unescape(c));
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '=' || c == ':' || c.is_whitespace() {
+            break;
+        } else {
+            key.push(c);
+        }
+    }
+    let rest = chars.as_str().trim_start();
+    let rest = rest
+        .strip_prefix(['=', ':'])
+        .map_or(rest, |rest| rest.trim_start());
+    let mut value = String::with_capacity(rest.len());
+    let mut escaped = false;
+    for c in rest.chars() {
+        if escaped {
+            value.push(unescape(c));
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else {
+            value.push(c);
+        }
+    }
+    Some((key, value.trim_end().to_string()))
+}
+
+fn unescape(c: char) -> char {
+    match c {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        other => other,
+    }
+}
+
+/// Collects the environment variables starting with `prefix`, mapping
+/// `<prefix>LISTEN_PORT` to the property `listenPort`.
+pub fn env_properties(prefix: &str) -> HashMap<CheetahString, CheetahString> {
+    env_properties_from(std::env::vars(), prefix)
+}
+
+fn env_properties_from(
+    vars: impl Iterator<Item = (String, String)>,
+    prefix: &str,
+) -> HashMap<CheetahString, CheetahString> {
+    vars.filter_map(|(key, value)| {
+        let name = key.strip_prefix(prefix)?;
+        if name.is_empty() {
+            return None;
+        }
+        let mut property = String::with_capacity(name.len());
+        for (index, word) in name.split('_').filter(|word| !word.is_empty()).enumerate() {
+            let word = word.to_ascii_lowercase();
+            if index == 0 {
+                property.push_str(&word);
+            } else {
+                let mut chars = word.chars();
+                if let Some(first) = chars.next() {
+                    property.push(first.to_ascii_uppercase());
+                    property.push_str(chars.as_str());
+                }
+            }
+        }
+        Some((CheetahString::from(property), CheetahString::from(value)))
+    })
+    .collect()
+}
+
+/// Applies `properties` on top of `config`, matching keys against the camelCase field names of
+/// the config and of its nested structs. Values are converted to the type of the field they
+/// replace, so a malformed number or boolean is reported with its key. Returns the updated config
+/// and the keys that matched no field.
+pub fn apply_properties<C>(
+    config: &C,
+    properties: &HashMap<CheetahString, CheetahString>,
+) -> anyhow::Result<(C, Vec<CheetahString>)>
+where
+    C: Serialize + DeserializeOwned,
+{
+    let mut value = serde_json::to_value(config)?;
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("config is not a struct"))?;
+    let mut unknown = Vec::new();
+    for (key, raw) in properties {
+        if !set_property(object, key.as_str(), raw.as_str())? {
+            unknown.push(key.clone());
+        }
+    }
+    let config = serde_json::from_value(value).context("invalid config value")?;
+    Ok((config, unknown))
+}
+
+fn set_property(object: &mut Map<String, Value>, key: &str, raw: &str) -> anyhow::Result<bool> {
+    let mut matched = false;
+    if let Some(current) = object.get_mut(key) {
+        if !current.is_object() {
+            *current = convert_property(current, key, raw)?;
+            matched = true;
+        }
+    }
+    for nested in object.values_mut() {
+        if let Value::Object(nested) = nested {
+            matched |= set_property(nested, key, raw)?;
+        }
+    }
+    Ok(matched)
+}
+
+fn convert_property(current: &Value, key: &str, raw: &str) -> anyhow::Result<Value> {
+    let raw = raw.trim();
+    let invalid = || anyhow!("Invalid value '{}' for key '{}'", raw, key);
+    Ok(match current {
+        Value::Bool(_) => Value::Bool(raw.to_ascii_lowercase().parse().map_err(|_| invalid())?),
+        Value::Number(number) if number.is_u64() => {
+            Value::from(raw.parse::<u64>().map_err(|_| invalid())?)
+        }
+        Value::Number(number) if number.is_i64() => {
+            Value::from(raw.parse::<i64>().map_err(|_| invalid())?)
+        }
+        Value::Number(_) => Value::from(raw.parse::<f64>().map_err(|_| invalid())?),
+        Value::Array(_) => Value::Array(
+            raw.split([',', ';'])
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        ),
+        _ => Value::String(raw.to_string()),
+    })
+}
+
+/// Flattens a config into `key=value` properties, nested structs contribute their own fields.
+pub fn config_to_properties<C: Serialize>(
+    config: &C,
+) -> anyhow::Result<HashMap<CheetahString, CheetahString>> {
+    let mut properties = HashMap::new();
+    if let Value::Object(object) = serde_json::to_value(config)? {
+        flatten_properties(&object, &mut properties);
+    }
+    Ok(properties)
+}
+
+fn flatten_properties(
+    object: &Map<String, Value>,
+    properties: &mut HashMap<CheetahString, CheetahString>,
+) {
+    for (key, value) in object {
+        let value = match value {
+            Value::Object(nested) => {
+                flatten_properties(nested, properties);
+                continue;
+            }
+            Value::Null => String::new(),
+            Value::String(value) => value.clone(),
+            other => other.to_string(),
+        };
+        properties
+            .entry(CheetahString::from(key.as_str()))
+            .or_insert_with(|| CheetahString::from(value));
+    }
+}
+
+/// Renders configs in the format of `config_file`, so a config changed at runtime can be
+/// written back to the TOML or JSON file it was loaded from. The fields of all configs share
+/// the top level, as [`parse_config_file`] reads each of them from the whole file.
+pub fn config_file_content(config_file: &Path, configs: Vec<Value>) -> anyhow::Result<String> {
+    let mut merged = Map::new();
+    for config in configs {
+        if let Value::Object(object) = config {
+            merged.extend(object);
+        }
+    }
+    let mut value = Value::Object(merged);
+    remove_nulls(&mut value);
+    match config_file.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => Ok(toml::to_string_pretty(&value)?),
+        Some("json") => Ok(serde_json::to_string_pretty(&value)?),
+        _ => bail!(
+            "can not write config file {}, only TOML and JSON are supported",
+            config_file.display()
+        ),
+    }
+}
+
+/// Unset optional fields are left out, TOML has no null.
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.retain(|_, value| !value.is_null());
+            object.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(array) => array.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Identity {
+        broker_name: String,
+        broker_id: u64,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct TestConfig {
+        identity: Identity,
+        broker_name: String,
+        listen_port: u32,
+        enable: bool,
+        ratio: f64,
+        namesrv_addr: Option<String>,
+    }
+
+    impl Default for TestConfig {
+        fn default() -> Self {
+            Self {
+                identity: Identity::default(),
+                broker_name: String::new(),
+                listen_port: 10911,
+                enable: false,
+                ratio: 0.5,
+                namesrv_addr: None,
+            }
+        }
+    }
+
+    #[test]
+    fn parse_properties_follows_java_syntax() {
+        let properties = parse_properties(
+            "# comment\n! another\nbrokerName = broker-a\nlistenPort:10921\nnamesrvAddr \
+             127.0.0.1:9876;\\\n  127.0.0.2:9876\n\nempty=\n",
+        );
+        assert_eq!(properties.get("brokerName").unwrap(), "broker-a");
+        assert_eq!(properties.get("listenPort").unwrap(), "10921");
+        assert_eq!(
+            properties.get("namesrvAddr").unwrap(),
+            "127.0.0.1:9876;127.0.0.2:9876"
+        );
+        assert_eq!(properties.get("empty").unwrap(), "");
+        assert_eq!(properties.len(), 4);
+    }
+
+    #[test]
+    fn env_properties_map_to_camel_case() {
+        let vars = vec![
+            (
+                "ROCKETMQ_BROKER_LISTEN_PORT".to_string(),
+                "10921".to_string(),
+            ),
+            ("ROCKETMQ_BROKER_".to_string(), "ignored".to_string()),
+            ("PATH".to_string(), "/bin".to_string()),
+        ];
+        let properties = env_properties_from(vars.into_iter(), "ROCKETMQ_BROKER_");
+        assert_eq!(properties.len(), 1);
+        assert_eq!(properties.get("listenPort").unwrap(), "10921");
+    }
+
+    #[test]
+    fn apply_properties_updates_nested_fields_and_reports_unknown() {
+        let mut properties = HashMap::new();
+        properties.insert(
+            CheetahString::from("brokerName"),
+            CheetahString::from("broker-b"),
+        );
+        properties.insert(
+            CheetahString::from("listenPort"),
+            CheetahString::from("10921"),
+        );
+        properties.insert(CheetahString::from("enable"), CheetahString::from("TRUE"));
+        properties.insert(CheetahString::from("ratio"), CheetahString::from("0.75"));
+        properties.insert(
+            CheetahString::from("namesrvAddr"),
+            CheetahString::from("127.0.0.1:9876"),
+        );
+        properties.insert(CheetahString::from("unknownKey"), CheetahString::from("1"));
+        let (config, unknown) = apply_properties(&TestConfig::default(), &properties).unwrap();
+        assert_eq!(config.broker_name, "broker-b");
+        assert_eq!(config.identity.broker_name, "broker-b");
+        assert_eq!(config.listen_port, 10921);
+        assert!(config.enable);
+        assert_eq!(config.ratio, 0.75);
+        assert_eq!(config.namesrv_addr.as_deref(), Some("127.0.0.1:9876"));
+        assert_eq!(unknown, vec![CheetahString::from("unknownKey")]);
+    }
+
+    #[test]
+    fn apply_properties_rejects_malformed_values() {
+        let mut properties = HashMap::new();
+        properties.insert(CheetahString::from("brokerId"), CheetahString::from("-1"));
+        let err = apply_properties(&TestConfig::default(), &properties).unwrap_err();
+        assert!(err.to_string().contains("brokerId"));
+    }
+
+    #[test]
+    fn config_file_content_round_trips_toml() {
+        let dir = std::env::temp_dir().join(format!("config-file-content-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("broker.toml");
+        let config = TestConfig {
+            broker_name: "broker-b".to_string(),
+            listen_port: 10921,
+            ratio: 0.25,
+            ..TestConfig::default()
+        };
+        let content =
+            config_file_content(&path, vec![serde_json::to_value(&config).unwrap()]).unwrap();
+        std::fs::write(&path, content).unwrap();
+        let parsed: TestConfig = parse_config_file(path.clone()).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert_eq!(parsed.broker_name, "broker-b");
+        assert_eq!(parsed.listen_port, 10921);
+        assert_eq!(parsed.ratio, 0.25);
+        assert!(parsed.namesrv_addr.is_none());
+        assert!(config_file_content(Path::new("broker.yaml"), Vec::new()).is_err());
+    }
+
+    #[test]
+    fn config_to_properties_flattens_nested_fields() {
+        let properties = config_to_properties(&TestConfig::default()).unwrap();
+        assert_eq!(properties.get("listenPort").unwrap(), "10911");
+        assert_eq!(properties.get("brokerId").unwrap(), "0");
+        assert_eq!(properties.get("namesrvAddr").unwrap(), "");
+    }
+}
@@ -0,0 +1,145 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rocketmq_common::common::controller::controller_config::ControllerConfig;
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+use rocketmq_remoting::remoting_server::server::RocketMQServer;
+use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+use rocketmq_runtime::RocketMQRuntime;
+use rocketmq_rust::wait_for_signal;
+use rocketmq_rust::ArcMut;
+use tracing::error;
+use tracing::info;
+
+use crate::processor::ControllerRequestProcessor;
+use crate::ControllerManager;
+
+pub struct ControllerBootstrap {
+    controller_runtime: ControllerRuntime,
+}
+
+pub struct Builder {
+    controller_config: Option<ControllerConfig>,
+    server_config: Option<ServerConfig>,
+}
+
+struct ControllerRuntime {
+    controller_config: Arc<ControllerConfig>,
+    server_config: Arc<ServerConfig>,
+    controller_manager: ControllerManager,
+    controller_runtime: Option<RocketMQRuntime>,
+}
+
+impl ControllerBootstrap {
+    pub async fn boot(mut self) {
+        tokio::join!(self.controller_runtime.start(), wait_for_signal());
+    }
+}
+
+impl ControllerRuntime {
+    pub async fn start(&mut self) {
+        self.controller_manager.initialize();
+        if let Err(e) = self.controller_manager.start() {
+            error!("Rocketmq Controller(Rust) start raft failed: {}", e);
+            return;
+        }
+        let heartbeat_manager = self.controller_manager.heartbeat_manager().clone();
+        let scan_interval =
+            Duration::from_millis(self.controller_config.scan_not_active_broker_interval);
+        self.controller_runtime
+            .as_ref()
+            .unwrap()
+            .schedule_at_fixed_rate(
+                move || heartbeat_manager.scan_not_active_broker(),
+                Some(scan_interval),
+                scan_interval,
+            );
+
+        let request_processor = ControllerRequestProcessor::new(self.controller_manager.clone());
+        let server = RocketMQServer::new(self.server_config.clone());
+        tokio::spawn(async move {
+            server.run(request_processor).await;
+        });
+        info!(
+            "Rocketmq Controller(Rust) started, listen on {}",
+            self.server_config.listen_port
+        );
+    }
+}
+
+impl Drop for ControllerRuntime {
+    fn drop(&mut self) {
+        self.controller_manager.shutdown();
+        if let Some(runtime) = self.controller_runtime.take() {
+            runtime.shutdown();
+        }
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Builder {
+            controller_config: None,
+            server_config: None,
+        }
+    }
+
+    pub fn set_controller_config(mut self, controller_config: ControllerConfig) -> Self {
+        self.controller_config = Some(controller_config);
+        self
+    }
+
+    pub fn set_server_config(mut self, server_config: ServerConfig) -> Self {
+        self.server_config = Some(server_config);
+        self
+    }
+
+    /// Builds the controller, failing when its raft log can't be opened.
+    pub fn build(self) -> std::io::Result<ControllerBootstrap> {
+        let controller_config = Arc::new(self.controller_config.unwrap_or_default());
+        let runtime = RocketMQRuntime::new_multi(
+            controller_config.controller_thread_pool_nums,
+            "controller-thread",
+        );
+        let remoting_client = ArcMut::new(RocketmqDefaultClient::new(
+            Arc::new(TokioClientConfig::default()),
+            DefaultRemotingRequestProcessor,
+        ));
+        Ok(ControllerBootstrap {
+            controller_runtime: ControllerRuntime {
+                controller_manager: ControllerManager::new(
+                    controller_config.clone(),
+                    remoting_client,
+                )?,
+                controller_config,
+                server_config: Arc::new(self.server_config.unwrap()),
+                controller_runtime: Some(runtime),
+            },
+        })
+    }
+}
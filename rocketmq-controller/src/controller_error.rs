@@ -0,0 +1,68 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use thiserror::Error;
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Error)]
+pub enum ControllerError {
+    #[error("{0}")]
+    ControllerRemotingError(#[from] ControllerRemotingErrorWithMessage),
+
+    #[error("{0}")]
+    MQControllerError(String),
+
+    #[error("The controller is not leader, the leader is {0:?}")]
+    NotLeader(Option<String>),
+
+    #[error("Wait for the raft log to commit timed out")]
+    CommitTimeout,
+}
+
+#[derive(Debug, Error)]
+#[error("Controller error: {error}, error message:{message}")]
+pub struct ControllerRemotingErrorWithMessage {
+    pub error: rocketmq_remoting::remoting_error::RemotingError,
+    pub message: String,
+}
+
+impl ControllerRemotingErrorWithMessage {
+    pub fn new(error: rocketmq_remoting::remoting_error::RemotingError, message: String) -> Self {
+        ControllerRemotingErrorWithMessage { error, message }
+    }
+}
+
+impl From<ControllerError> for rocketmq_remoting::remoting_error::RemotingError {
+    #[inline]
+    fn from(value: ControllerError) -> Self {
+        match value {
+            ControllerError::ControllerRemotingError(e) => {
+                rocketmq_remoting::remoting_error::RemotingError::RemotingCommandError(
+                    e.to_string(),
+                )
+            }
+            ControllerError::MQControllerError(e) => {
+                rocketmq_remoting::remoting_error::RemotingError::RemotingCommandError(e)
+            }
+            ControllerError::NotLeader(_) | ControllerError::CommitTimeout => {
+                rocketmq_remoting::remoting_error::RemotingError::RemotingCommandError(
+                    value.to_string(),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,407 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use parking_lot::RwLock;
+use rocketmq_common::common::controller::controller_config::ControllerConfig;
+use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+use rocketmq_remoting::clients::RemotingClient;
+use rocketmq_remoting::code::request_code::ControllerRequestCode;
+use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::body::sync_state_set::SyncStateSet;
+use rocketmq_remoting::protocol::command_custom_header::CommandCustomHeader;
+use rocketmq_remoting::protocol::header::broker::broker_heartbeat_request_header::BrokerHeartbeatRequestHeader;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoRequestHeader;
+use rocketmq_remoting::protocol::header::controller::notify_broker_role_changed_request_header::NotifyBrokerRoleChangedRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use rocketmq_rust::ArcMut;
+use tracing::info;
+use tracing::warn;
+
+use crate::controller_error::ControllerError;
+use crate::elect::default_elect_policy::DefaultElectPolicy;
+use crate::heartbeat::default_broker_heartbeat_manager::BrokerLifecycleListener;
+use crate::heartbeat::default_broker_heartbeat_manager::DefaultBrokerHeartbeatManager;
+use crate::manager::controller_result::ControllerResult;
+use crate::manager::replicas_info_manager::ReplicasInfoManager;
+use crate::raft::raft_node::RaftNode;
+
+/// Serves the controller requests on top of the replica state machine and triggers a
+/// new election when a master stops sending heartbeats.
+///
+/// This controller runs as a single node: the events produced by the state machine are
+/// applied locally under one lock, in the same order they were produced.
+#[derive(Clone)]
+pub struct ControllerManager {
+    controller_config: Arc<ControllerConfig>,
+    heartbeat_manager: DefaultBrokerHeartbeatManager,
+    replicas_info_manager: Arc<RwLock<ReplicasInfoManager>>,
+    elect_policy: Arc<DefaultElectPolicy>,
+    remoting_client: ArcMut<RocketmqDefaultClient>,
+    raft_node: Arc<RaftNode>,
+    /// Serializes the requests turning the replica state into events, so each one is
+    /// computed from a state that includes the events of the previous one.
+    event_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl ControllerManager {
+    /// Creates the manager and replays the committed part of the controller's raft log.
+    pub fn new(
+        controller_config: Arc<ControllerConfig>,
+        remoting_client: ArcMut<RocketmqDefaultClient>,
+    ) -> std::io::Result<Self> {
+        let heartbeat_manager = DefaultBrokerHeartbeatManager::default();
+        let alive_checker = heartbeat_manager.clone();
+        let info_getter = heartbeat_manager.clone();
+        let elect_policy = DefaultElectPolicy::new(
+            Arc::new(move |cluster_name, broker_name, broker_id| {
+                alive_checker.is_broker_active(cluster_name, broker_name, broker_id)
+            }),
+            Some(Arc::new(move |cluster_name, broker_name, broker_id| {
+                info_getter.get_broker_live_info(cluster_name, broker_name, broker_id)
+            })),
+        );
+        let replicas_info_manager = Arc::new(RwLock::new(ReplicasInfoManager::default()));
+        let state_machine = replicas_info_manager.clone();
+        let raft_node = RaftNode::new(
+            controller_config.as_ref(),
+            Box::new(move |events| {
+                let mut replicas_info_manager = state_machine.write();
+                for event in events {
+                    replicas_info_manager.apply_event(event);
+                }
+            }),
+        )?;
+        Ok(Self {
+            controller_config,
+            heartbeat_manager,
+            replicas_info_manager,
+            elect_policy: Arc::new(elect_policy),
+            remoting_client,
+            raft_node: Arc::new(raft_node),
+            event_lock: Arc::new(tokio::sync::Mutex::new(())),
+        })
+    }
+
+    /// Starts watching the broker heartbeats for inactive masters.
+    pub fn initialize(&self) {
+        self.heartbeat_manager
+            .register_broker_lifecycle_listener(Arc::new(self.clone()));
+    }
+
+    /// Joins the raft group of the controllers.
+    pub fn start(&self) -> std::io::Result<()> {
+        self.raft_node.start()
+    }
+
+    pub fn shutdown(&self) {
+        self.raft_node.shutdown();
+    }
+
+    pub fn raft_node(&self) -> &Arc<RaftNode> {
+        &self.raft_node
+    }
+
+    pub fn replicas_info_manager(&self) -> &Arc<RwLock<ReplicasInfoManager>> {
+        &self.replicas_info_manager
+    }
+
+    pub fn heartbeat_manager(&self) -> &DefaultBrokerHeartbeatManager {
+        &self.heartbeat_manager
+    }
+
+    pub fn on_broker_heartbeat(&self, request: &BrokerHeartbeatRequestHeader) {
+        self.heartbeat_manager.on_broker_heartbeat(request);
+    }
+
+    pub async fn register_broker(
+        &self,
+        request: &RegisterBrokerToControllerRequestHeader,
+    ) -> RemotingCommand {
+        let _event_lock = self.event_lock.lock().await;
+        if let Err(e) = self.raft_node.wait_for_ready().await {
+            return to_error_response(e);
+        }
+        let result = self
+            .replicas_info_manager
+            .read()
+            .register_broker(request, &|cluster, broker, id| {
+                self.heartbeat_manager.is_broker_active(cluster, broker, id)
+            });
+        self.commit(result).await
+    }
+
+    pub async fn elect_master(&self, request: &ElectMasterRequestHeader) -> RemotingCommand {
+        let _event_lock = self.event_lock.lock().await;
+        if let Err(e) = self.raft_node.wait_for_ready().await {
+            return to_error_response(e);
+        }
+        let result = self.replicas_info_manager.read().elect_master(
+            request,
+            self.elect_policy.as_ref(),
+            &|cluster, broker, id| self.heartbeat_manager.is_broker_active(cluster, broker, id),
+            self.controller_config.enable_elect_unclean_master,
+        );
+        let success = result.is_success();
+        let response = self.commit(result).await;
+        if success
+            && ResponseCode::from(response.code()) == ResponseCode::Success
+            && self.controller_config.notify_broker_role_changed
+        {
+            self.notify_broker_role_changed(&request.broker_name);
+        }
+        response
+    }
+
+    pub async fn alter_sync_state_set(
+        &self,
+        request: &AlterSyncStateSetRequestHeader,
+        sync_state_set: &SyncStateSet,
+    ) -> RemotingCommand {
+        let _event_lock = self.event_lock.lock().await;
+        if let Err(e) = self.raft_node.wait_for_ready().await {
+            return to_error_response(e);
+        }
+        let result = self.replicas_info_manager.read().alter_sync_state_set(
+            request,
+            sync_state_set,
+            &|cluster, broker, id| self.heartbeat_manager.is_broker_active(cluster, broker, id),
+        );
+        self.commit(result).await
+    }
+
+    pub async fn get_replica_info(&self, request: &GetReplicaInfoRequestHeader) -> RemotingCommand {
+        if let Err(e) = self.raft_node.wait_for_ready().await {
+            return to_error_response(e);
+        }
+        to_response(self.replicas_info_manager.read().get_replica_info(request))
+    }
+
+    /// Replicates the events of `result` and answers once they are applied.
+    async fn commit<T>(&self, result: ControllerResult<T>) -> RemotingCommand
+    where
+        T: CommandCustomHeader + Clone + Sync + Send + 'static,
+    {
+        if !result.events().is_empty() {
+            if let Err(e) = self.raft_node.propose(result.events().to_vec()).await {
+                return to_error_response(e);
+            }
+        }
+        to_response(result)
+    }
+
+    /// Tells every replica of `broker_name` who its master is now.
+    fn notify_broker_role_changed(&self, broker_name: &CheetahString) {
+        let replicas_info_manager = self.replicas_info_manager.read();
+        let (Some(sync_state_info), Some(replica_info)) = (
+            replicas_info_manager.get_sync_state_info(broker_name),
+            replicas_info_manager.get_broker_replica_info(broker_name),
+        ) else {
+            return;
+        };
+        let request_header = NotifyBrokerRoleChangedRequestHeader {
+            master_address: sync_state_info
+                .master_broker_id()
+                .and_then(|master| replica_info.get_broker_address(master).cloned()),
+            master_epoch: Some(sync_state_info.master_epoch()),
+            sync_state_set_epoch: Some(sync_state_info.sync_state_set_epoch()),
+            master_broker_id: sync_state_info.master_broker_id(),
+        };
+        let body = SyncStateSet::new(
+            sync_state_info.sync_state_set().clone(),
+            sync_state_info.sync_state_set_epoch(),
+        )
+        .encode()
+        .unwrap_or_default();
+        for broker_addr in replica_info.get_broker_id_table().values() {
+            let remoting_client = self.remoting_client.clone();
+            let broker_addr = broker_addr.clone();
+            let request = RemotingCommand::create_request_command(
+                ControllerRequestCode::NotifyBrokerRoleChanged,
+                request_header.clone(),
+            )
+            .set_body(body.clone());
+            tokio::spawn(async move {
+                remoting_client
+                    .invoke_oneway(&broker_addr, request, 3000)
+                    .await;
+            });
+        }
+    }
+}
+
+impl BrokerLifecycleListener for ControllerManager {
+    fn on_broker_inactive(
+        &self,
+        cluster_name: &CheetahString,
+        broker_name: &CheetahString,
+        broker_id: i64,
+    ) {
+        if !self.raft_node.is_leader() {
+            return;
+        }
+        let is_master = self
+            .replicas_info_manager
+            .read()
+            .get_sync_state_info(broker_name)
+            .is_some_and(|sync_state_info| sync_state_info.master_broker_id() == Some(broker_id));
+        if !is_master {
+            return;
+        }
+        info!(
+            "The master {} of broker group {} is inactive, trigger election",
+            broker_id, broker_name
+        );
+        let controller_manager = self.clone();
+        let request = ElectMasterRequestHeader::of_controller_trigger(
+            cluster_name.clone(),
+            broker_name.clone(),
+        );
+        tokio::spawn(async move {
+            let response = controller_manager.elect_master(&request).await;
+            if let Some(remark) = response.remark() {
+                warn!(
+                    "Election of broker group {} : {}",
+                    request.broker_name, remark
+                );
+            }
+        });
+    }
+}
+
+fn to_response<T>(result: ControllerResult<T>) -> RemotingCommand
+where
+    T: CommandCustomHeader + Clone + Sync + Send + 'static,
+{
+    let mut response = RemotingCommand::create_response_command_with_code(result.response_code())
+        .set_command_custom_header(result.response().clone())
+        .set_remark_option(result.remark().cloned());
+    if let Some(body) = result.body() {
+        response = response.set_body(body.clone());
+    }
+    response
+}
+
+fn to_error_response(error: ControllerError) -> RemotingCommand {
+    match error {
+        ControllerError::NotLeader(_) => RemotingCommand::create_response_command_with_code_remark(
+            ResponseCode::ControllerNotLeader,
+            error.to_string(),
+        ),
+        _ => RemotingCommand::create_response_command_with_code_remark(
+            RemotingSysResponseCode::SystemError,
+            error.to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+    use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+
+    use super::*;
+
+    fn start_controller_manager(controller_config: ControllerConfig) -> ControllerManager {
+        let remoting_client = ArcMut::new(RocketmqDefaultClient::new(
+            Arc::new(TokioClientConfig::default()),
+            DefaultRemotingRequestProcessor,
+        ));
+        let controller_manager =
+            ControllerManager::new(Arc::new(controller_config), remoting_client).unwrap();
+        controller_manager.start().unwrap();
+        controller_manager
+    }
+
+    fn register_request(broker_id: i64) -> RegisterBrokerToControllerRequestHeader {
+        RegisterBrokerToControllerRequestHeader {
+            cluster_name: CheetahString::from_static_str("cluster"),
+            broker_name: CheetahString::from_static_str("broker-a"),
+            broker_id,
+            broker_address: CheetahString::from_string(format!("127.0.0.1:{}", 10911 + broker_id)),
+            invoke_time: None,
+        }
+    }
+
+    async fn wait_for_leader(controller_manager: &ControllerManager) {
+        for _ in 0..200 {
+            if controller_manager.raft_node().is_leader() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("the standalone controller did not elect itself");
+    }
+
+    // The remoting client owns a runtime, so the managers are created and dropped outside
+    // of the async context of the test.
+    #[test]
+    fn registered_brokers_are_replayed_after_restart() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let dir = tempfile::tempdir().unwrap();
+        let controller_config = ControllerConfig {
+            controller_store_path: dir.path().to_string_lossy().into_owned(),
+            notify_broker_role_changed: false,
+            ..ControllerConfig::default()
+        };
+        let controller_manager = start_controller_manager(controller_config.clone());
+        let response = runtime.block_on(async {
+            wait_for_leader(&controller_manager).await;
+            controller_manager
+                .register_broker(&register_request(1))
+                .await
+        });
+        assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+        controller_manager.shutdown();
+
+        let controller_manager = start_controller_manager(controller_config);
+        assert!(controller_manager
+            .replicas_info_manager()
+            .read()
+            .get_broker_replica_info("broker-a")
+            .is_some_and(|replica_info| replica_info.get_broker_id_table().contains_key(&1)));
+        controller_manager.shutdown();
+    }
+
+    #[test]
+    fn follower_rejects_requests_with_not_leader() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let dir = tempfile::tempdir().unwrap();
+        let controller_manager = start_controller_manager(ControllerConfig {
+            controller_store_path: dir.path().to_string_lossy().into_owned(),
+            controller_dledger_peers: "n0-127.0.0.1:0;n1-127.0.0.1:1;n2-127.0.0.1:2".to_string(),
+            ..ControllerConfig::default()
+        });
+        let response = runtime.block_on(controller_manager.register_broker(&register_request(1)));
+        assert_eq!(
+            ResponseCode::from(response.code()),
+            ResponseCode::ControllerNotLeader
+        );
+        controller_manager.shutdown();
+    }
+}
@@ -0,0 +1,198 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::elect::elect_policy::ElectPolicy;
+use crate::heartbeat::broker_live_info::BrokerLiveInfo;
+
+/// Tells whether a replica is alive.
+pub type BrokerValidPredicate = Arc<dyn Fn(&str, &str, i64) -> bool + Send + Sync>;
+
+/// Looks up the last heartbeat of a replica.
+pub type BrokerLiveInfoGetter =
+    Arc<dyn Fn(&str, &str, i64) -> Option<BrokerLiveInfo> + Send + Sync>;
+
+/// Keeps an alive old master, otherwise prefers the requesting replica, otherwise ranks the
+/// alive candidates by epoch, max offset and election priority.
+pub struct DefaultElectPolicy {
+    valid_predicate: BrokerValidPredicate,
+    additional_info_getter: Option<BrokerLiveInfoGetter>,
+}
+
+impl DefaultElectPolicy {
+    pub fn new(
+        valid_predicate: BrokerValidPredicate,
+        additional_info_getter: Option<BrokerLiveInfoGetter>,
+    ) -> Self {
+        Self {
+            valid_predicate,
+            additional_info_getter,
+        }
+    }
+
+    pub fn valid_predicate(&self) -> &BrokerValidPredicate {
+        &self.valid_predicate
+    }
+
+    fn try_elect(
+        &self,
+        cluster_name: &str,
+        broker_name: &str,
+        brokers: &HashSet<i64>,
+        old_master: Option<i64>,
+        prefer_broker_id: Option<i64>,
+    ) -> Option<i64> {
+        let candidates: Vec<i64> = brokers
+            .iter()
+            .copied()
+            .filter(|broker_id| (self.valid_predicate)(cluster_name, broker_name, *broker_id))
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        if let Some(old_master) = old_master.filter(|id| candidates.contains(id)) {
+            return Some(old_master);
+        }
+        if let Some(prefer_broker_id) = prefer_broker_id {
+            return candidates
+                .contains(&prefer_broker_id)
+                .then_some(prefer_broker_id);
+        }
+        let Some(ref info_getter) = self.additional_info_getter else {
+            return candidates.into_iter().min();
+        };
+        let mut live_infos: Vec<BrokerLiveInfo> = candidates
+            .into_iter()
+            .filter_map(|broker_id| info_getter(cluster_name, broker_name, broker_id))
+            .collect();
+        live_infos.sort_by(compare_live_info);
+        live_infos.first().map(|live_info| live_info.broker_id)
+    }
+}
+
+/// Higher epoch first, then higher max offset, then lower election priority value.
+fn compare_live_info(left: &BrokerLiveInfo, right: &BrokerLiveInfo) -> Ordering {
+    right
+        .epoch
+        .cmp(&left.epoch)
+        .then_with(|| right.max_offset.cmp(&left.max_offset))
+        .then_with(|| left.election_priority.cmp(&right.election_priority))
+        .then_with(|| left.broker_id.cmp(&right.broker_id))
+}
+
+impl ElectPolicy for DefaultElectPolicy {
+    fn elect(
+        &self,
+        cluster_name: &str,
+        broker_name: &str,
+        sync_state_brokers: &HashSet<i64>,
+        all_replica_brokers: Option<&HashSet<i64>>,
+        old_master: Option<i64>,
+        prefer_broker_id: Option<i64>,
+    ) -> Option<i64> {
+        self.try_elect(
+            cluster_name,
+            broker_name,
+            sync_state_brokers,
+            old_master,
+            prefer_broker_id,
+        )
+        .or_else(|| {
+            all_replica_brokers.and_then(|brokers| {
+                self.try_elect(
+                    cluster_name,
+                    broker_name,
+                    brokers,
+                    old_master,
+                    prefer_broker_id,
+                )
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+
+    fn live_info(broker_id: i64, epoch: i32, max_offset: i64, priority: i32) -> BrokerLiveInfo {
+        BrokerLiveInfo {
+            broker_name: CheetahString::from_static_str("broker-a"),
+            broker_addr: CheetahString::from_static_str("127.0.0.1:10911"),
+            heartbeat_timeout_millis: 10_000,
+            broker_id,
+            last_update_timestamp: 0,
+            epoch,
+            max_offset,
+            confirm_offset: max_offset,
+            election_priority: priority,
+        }
+    }
+
+    fn policy(alive: &'static [i64]) -> DefaultElectPolicy {
+        DefaultElectPolicy::new(
+            Arc::new(move |_, _, broker_id| alive.contains(&broker_id)),
+            Some(Arc::new(|_, _, broker_id| match broker_id {
+                1 => Some(live_info(1, 2, 100, 1)),
+                2 => Some(live_info(2, 2, 300, 5)),
+                3 => Some(live_info(3, 2, 300, 2)),
+                _ => None,
+            })),
+        )
+    }
+
+    #[test]
+    fn alive_old_master_is_kept() {
+        let brokers = HashSet::from([1, 2, 3]);
+        let elected = policy(&[1, 2, 3]).elect("c", "b", &brokers, None, Some(1), None);
+        assert_eq!(elected, Some(1));
+    }
+
+    #[test]
+    fn candidates_are_ranked_by_offset_then_priority() {
+        let brokers = HashSet::from([1, 2, 3]);
+        let elected = policy(&[2, 3]).elect("c", "b", &brokers, None, Some(1), None);
+        assert_eq!(elected, Some(3));
+    }
+
+    #[test]
+    fn unclean_election_only_uses_all_replicas_when_given() {
+        let sync_state_set = HashSet::from([1]);
+        let all_replicas = HashSet::from([1, 2]);
+        let policy = policy(&[2]);
+        assert_eq!(
+            policy.elect("c", "b", &sync_state_set, None, Some(1), None),
+            None
+        );
+        assert_eq!(
+            policy.elect(
+                "c",
+                "b",
+                &sync_state_set,
+                Some(&all_replicas),
+                Some(1),
+                None
+            ),
+            Some(2)
+        );
+    }
+}
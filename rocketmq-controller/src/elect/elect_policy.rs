@@ -0,0 +1,35 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashSet;
+
+/// Chooses the new master of a broker group.
+pub trait ElectPolicy: Send + Sync {
+    /// Returns the elected broker id, or `None` when no replica can take over.
+    ///
+    /// `all_replica_brokers` is only given when unclean elections are allowed.
+    /// `prefer_broker_id` is the replica that asked for the election.
+    fn elect(
+        &self,
+        cluster_name: &str,
+        broker_name: &str,
+        sync_state_brokers: &HashSet<i64>,
+        all_replica_brokers: Option<&HashSet<i64>>,
+        old_master: Option<i64>,
+        prefer_broker_id: Option<i64>,
+    ) -> Option<i64>;
+}
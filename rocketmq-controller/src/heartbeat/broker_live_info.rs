@@ -0,0 +1,54 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cheetah_string::CheetahString;
+
+/// Identifies one replica of a broker group.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BrokerIdentityInfo {
+    pub cluster_name: CheetahString,
+    pub broker_name: CheetahString,
+    pub broker_id: i64,
+}
+
+impl BrokerIdentityInfo {
+    pub fn new(
+        cluster_name: impl Into<CheetahString>,
+        broker_name: impl Into<CheetahString>,
+        broker_id: i64,
+    ) -> Self {
+        Self {
+            cluster_name: cluster_name.into(),
+            broker_name: broker_name.into(),
+            broker_id,
+        }
+    }
+}
+
+/// What the controller knows about a replica from its last heartbeat.
+#[derive(Debug, Clone)]
+pub struct BrokerLiveInfo {
+    pub broker_name: CheetahString,
+    pub broker_addr: CheetahString,
+    pub heartbeat_timeout_millis: u64,
+    pub broker_id: i64,
+    pub last_update_timestamp: u64,
+    pub epoch: i32,
+    pub max_offset: i64,
+    pub confirm_offset: i64,
+    pub election_priority: i32,
+}
@@ -0,0 +1,227 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use parking_lot::RwLock;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::protocol::header::broker::broker_heartbeat_request_header::BrokerHeartbeatRequestHeader;
+use tracing::info;
+
+use crate::heartbeat::broker_live_info::BrokerIdentityInfo;
+use crate::heartbeat::broker_live_info::BrokerLiveInfo;
+
+const DEFAULT_BROKER_CHANNEL_EXPIRED_TIME: u64 = 1000 * 10;
+
+/// Notified when a replica is removed because it stopped sending heartbeats.
+pub trait BrokerLifecycleListener: Send + Sync {
+    fn on_broker_inactive(
+        &self,
+        cluster_name: &CheetahString,
+        broker_name: &CheetahString,
+        broker_id: i64,
+    );
+}
+
+#[derive(Clone, Default)]
+pub struct DefaultBrokerHeartbeatManager {
+    broker_live_table: Arc<RwLock<HashMap<BrokerIdentityInfo, BrokerLiveInfo>>>,
+    lifecycle_listeners: Arc<RwLock<Vec<Arc<dyn BrokerLifecycleListener>>>>,
+}
+
+impl DefaultBrokerHeartbeatManager {
+    pub fn register_broker_lifecycle_listener(&self, listener: Arc<dyn BrokerLifecycleListener>) {
+        self.lifecycle_listeners.write().push(listener);
+    }
+
+    pub fn on_broker_heartbeat(&self, header: &BrokerHeartbeatRequestHeader) {
+        let Some(broker_id) = header.broker_id else {
+            return;
+        };
+        let identity = BrokerIdentityInfo::new(
+            header.cluster_name.clone(),
+            header.broker_name.clone(),
+            broker_id,
+        );
+        let heartbeat_timeout_millis = header
+            .heartbeat_timeout_mills
+            .filter(|timeout| *timeout > 0)
+            .map_or(DEFAULT_BROKER_CHANNEL_EXPIRED_TIME, |timeout| {
+                timeout as u64
+            });
+        let now = get_current_millis();
+        let mut broker_live_table = self.broker_live_table.write();
+        match broker_live_table.get_mut(&identity) {
+            Some(live_info) => {
+                live_info.last_update_timestamp = now;
+                live_info.heartbeat_timeout_millis = heartbeat_timeout_millis;
+                live_info.broker_addr = header.broker_addr.clone();
+                if let Some(election_priority) = header.election_priority {
+                    live_info.election_priority = election_priority;
+                }
+                // an older epoch means a stale heartbeat of the previous term
+                if let Some(epoch) = header.epoch.filter(|epoch| *epoch >= live_info.epoch) {
+                    live_info.epoch = epoch;
+                    live_info.max_offset = header.max_offset.unwrap_or(live_info.max_offset);
+                    live_info.confirm_offset =
+                        header.confirm_offset.unwrap_or(live_info.confirm_offset);
+                }
+            }
+            None => {
+                info!(
+                    "New broker registered, {:?}, heartbeat timeout {}ms",
+                    identity, heartbeat_timeout_millis
+                );
+                broker_live_table.insert(
+                    identity,
+                    BrokerLiveInfo {
+                        broker_name: header.broker_name.clone(),
+                        broker_addr: header.broker_addr.clone(),
+                        heartbeat_timeout_millis,
+                        broker_id,
+                        last_update_timestamp: now,
+                        epoch: header.epoch.unwrap_or(-1),
+                        max_offset: header.max_offset.unwrap_or(-1),
+                        confirm_offset: header.confirm_offset.unwrap_or(-1),
+                        election_priority: header.election_priority.unwrap_or(i32::MAX),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Removes the replicas whose heartbeat expired and notifies the listeners.
+    pub fn scan_not_active_broker(&self) {
+        let now = get_current_millis();
+        let mut expired = Vec::new();
+        self.broker_live_table
+            .write()
+            .retain(|identity, live_info| {
+                let active =
+                    live_info.last_update_timestamp + live_info.heartbeat_timeout_millis > now;
+                if !active {
+                    info!(
+                        "The broker channel {} expired, {:?}",
+                        live_info.broker_addr, identity
+                    );
+                    expired.push(identity.clone());
+                }
+                active
+            });
+        if expired.is_empty() {
+            return;
+        }
+        let listeners = self.lifecycle_listeners.read().clone();
+        for identity in expired {
+            for listener in &listeners {
+                listener.on_broker_inactive(
+                    &identity.cluster_name,
+                    &identity.broker_name,
+                    identity.broker_id,
+                );
+            }
+        }
+    }
+
+    pub fn is_broker_active(&self, cluster_name: &str, broker_name: &str, broker_id: i64) -> bool {
+        self.get_broker_live_info(cluster_name, broker_name, broker_id)
+            .is_some_and(|live_info| {
+                live_info.last_update_timestamp + live_info.heartbeat_timeout_millis
+                    > get_current_millis()
+            })
+    }
+
+    pub fn get_broker_live_info(
+        &self,
+        cluster_name: &str,
+        broker_name: &str,
+        broker_id: i64,
+    ) -> Option<BrokerLiveInfo> {
+        self.broker_live_table
+            .read()
+            .get(&BrokerIdentityInfo::new(
+                CheetahString::from_slice(cluster_name),
+                CheetahString::from_slice(broker_name),
+                broker_id,
+            ))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    fn heartbeat(broker_id: i64, timeout: i64) -> BrokerHeartbeatRequestHeader {
+        BrokerHeartbeatRequestHeader {
+            cluster_name: CheetahString::from_static_str("cluster"),
+            broker_addr: CheetahString::from_static_str("127.0.0.1:10911"),
+            broker_name: CheetahString::from_static_str("broker-a"),
+            broker_id: Some(broker_id),
+            epoch: Some(1),
+            max_offset: Some(100),
+            confirm_offset: Some(100),
+            heartbeat_timeout_mills: Some(timeout),
+            election_priority: None,
+        }
+    }
+
+    struct CountingListener(AtomicUsize);
+
+    impl BrokerLifecycleListener for CountingListener {
+        fn on_broker_inactive(&self, _: &CheetahString, _: &CheetahString, broker_id: i64) {
+            assert_eq!(broker_id, 2);
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn heartbeat_makes_broker_active() {
+        let manager = DefaultBrokerHeartbeatManager::default();
+        manager.on_broker_heartbeat(&heartbeat(1, 10_000));
+        assert!(manager.is_broker_active("cluster", "broker-a", 1));
+        assert!(!manager.is_broker_active("cluster", "broker-a", 2));
+        let live_info = manager
+            .get_broker_live_info("cluster", "broker-a", 1)
+            .unwrap();
+        assert_eq!(live_info.max_offset, 100);
+        assert_eq!(live_info.election_priority, i32::MAX);
+    }
+
+    #[test]
+    fn scan_removes_expired_brokers_and_notifies_listeners() {
+        let manager = DefaultBrokerHeartbeatManager::default();
+        let listener = Arc::new(CountingListener(AtomicUsize::new(0)));
+        manager.register_broker_lifecycle_listener(listener.clone());
+        manager.on_broker_heartbeat(&heartbeat(1, 10_000));
+        manager.on_broker_heartbeat(&heartbeat(2, 1));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+
+        manager.scan_not_active_broker();
+
+        assert_eq!(listener.0.load(Ordering::SeqCst), 1);
+        assert!(manager
+            .get_broker_live_info("cluster", "broker-a", 2)
+            .is_none());
+        assert!(manager.is_broker_active("cluster", "broker-a", 1));
+    }
+}
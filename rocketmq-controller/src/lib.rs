@@ -0,0 +1,34 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Controller mode of RocketMQ: tracks the replicas of every broker group, elects a
+//! new master when the current one stops sending heartbeats, and keeps the sync state
+//! set of each group.
+
+pub use self::controller_manager::ControllerManager;
+pub use self::manager::replicas_info_manager::ReplicasInfoManager;
+
+pub mod bootstrap;
+pub(crate) mod controller_error;
+pub mod controller_manager;
+pub mod elect;
+pub mod heartbeat;
+pub mod manager;
+pub mod processor;
+pub mod raft;
+
+pub type Result<T> = std::result::Result<T, controller_error::ControllerError>;
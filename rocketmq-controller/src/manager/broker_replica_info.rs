@@ -0,0 +1,83 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use cheetah_string::CheetahString;
+use rocketmq_remoting::protocol::body::broker_body::broker_member_group::BrokerMemberGroup;
+
+/// The replicas registered for a broker group, by broker id.
+#[derive(Debug, Clone)]
+pub struct BrokerReplicaInfo {
+    cluster_name: CheetahString,
+    broker_name: CheetahString,
+    broker_id_table: HashMap<i64, CheetahString>,
+}
+
+impl BrokerReplicaInfo {
+    pub fn new(cluster_name: CheetahString, broker_name: CheetahString) -> Self {
+        Self {
+            cluster_name,
+            broker_name,
+            broker_id_table: HashMap::new(),
+        }
+    }
+
+    pub fn add_broker(&mut self, broker_id: i64, broker_address: CheetahString) {
+        self.broker_id_table.insert(broker_id, broker_address);
+    }
+
+    pub fn remove_broker_id(&mut self, broker_id: i64) {
+        self.broker_id_table.remove(&broker_id);
+    }
+
+    pub fn is_broker_exist(&self, broker_id: i64) -> bool {
+        self.broker_id_table.contains_key(&broker_id)
+    }
+
+    pub fn get_broker_address(&self, broker_id: i64) -> Option<&CheetahString> {
+        self.broker_id_table.get(&broker_id)
+    }
+
+    pub fn get_all_broker(&self) -> HashSet<i64> {
+        self.broker_id_table.keys().copied().collect()
+    }
+
+    pub fn get_broker_id_table(&self) -> &HashMap<i64, CheetahString> {
+        &self.broker_id_table
+    }
+
+    pub fn cluster_name(&self) -> &CheetahString {
+        &self.cluster_name
+    }
+
+    pub fn broker_name(&self) -> &CheetahString {
+        &self.broker_name
+    }
+
+    pub fn to_broker_member_group(&self) -> BrokerMemberGroup {
+        let mut broker_member_group =
+            BrokerMemberGroup::new(self.cluster_name.clone(), self.broker_name.clone());
+        broker_member_group.broker_addrs = self
+            .broker_id_table
+            .iter()
+            .map(|(broker_id, address)| (*broker_id as u64, address.clone()))
+            .collect();
+        broker_member_group
+    }
+}
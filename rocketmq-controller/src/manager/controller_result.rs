@@ -0,0 +1,95 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cheetah_string::CheetahString;
+use rocketmq_remoting::code::response_code::ResponseCode;
+
+use crate::manager::event::EventMessage;
+
+/// The outcome of a request: the response to send back and the events to apply.
+#[derive(Debug)]
+pub struct ControllerResult<T> {
+    events: Vec<EventMessage>,
+    response: T,
+    body: Option<Vec<u8>>,
+    response_code: ResponseCode,
+    remark: Option<CheetahString>,
+}
+
+impl<T> ControllerResult<T> {
+    pub fn new(response: T) -> Self {
+        Self {
+            events: Vec::new(),
+            response,
+            body: None,
+            response_code: ResponseCode::Success,
+            remark: None,
+        }
+    }
+
+    pub fn of(events: Vec<EventMessage>, response: T) -> Self {
+        Self {
+            events,
+            ..Self::new(response)
+        }
+    }
+
+    pub fn add_event(&mut self, event: EventMessage) {
+        self.events.push(event);
+    }
+
+    pub fn set_code_and_remark(
+        &mut self,
+        response_code: ResponseCode,
+        remark: impl Into<CheetahString>,
+    ) {
+        self.response_code = response_code;
+        self.remark = Some(remark.into());
+    }
+
+    pub fn set_body(&mut self, body: Vec<u8>) {
+        self.body = Some(body);
+    }
+
+    pub fn events(&self) -> &[EventMessage] {
+        &self.events
+    }
+
+    pub fn response(&self) -> &T {
+        &self.response
+    }
+
+    pub fn response_mut(&mut self) -> &mut T {
+        &mut self.response
+    }
+
+    pub fn body(&self) -> Option<&Vec<u8>> {
+        self.body.as_ref()
+    }
+
+    pub fn response_code(&self) -> ResponseCode {
+        self.response_code
+    }
+
+    pub fn remark(&self) -> Option<&CheetahString> {
+        self.remark.as_ref()
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.response_code == ResponseCode::Success
+    }
+}
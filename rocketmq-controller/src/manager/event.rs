@@ -0,0 +1,56 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashSet;
+
+use cheetah_string::CheetahString;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A change of the replicas metadata. Requests only produce events, the state is
+/// modified when the events are applied, so the same events can be replayed on
+/// every controller.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventMessage {
+    ApplyBrokerId(ApplyBrokerIdEvent),
+    ElectMaster(ElectMasterEvent),
+    AlterSyncStateSet(AlterSyncStateSetEvent),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyBrokerIdEvent {
+    pub cluster_name: CheetahString,
+    pub broker_name: CheetahString,
+    pub broker_address: CheetahString,
+    pub broker_id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElectMasterEvent {
+    pub broker_name: CheetahString,
+    /// `None` when no replica could be elected and the group is left without master.
+    pub new_master_broker_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlterSyncStateSetEvent {
+    pub broker_name: CheetahString,
+    pub new_sync_state_set: HashSet<i64>,
+}
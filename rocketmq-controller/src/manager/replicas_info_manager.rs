@@ -0,0 +1,610 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use cheetah_string::CheetahString;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::body::sync_state_set::ElectMasterResponseBody;
+use rocketmq_remoting::protocol::body::sync_state_set::SyncStateSet;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetRequestHeader;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetResponseHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterResponseHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoResponseHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerResponseHeader;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use tracing::info;
+
+use crate::elect::elect_policy::ElectPolicy;
+use crate::manager::broker_replica_info::BrokerReplicaInfo;
+use crate::manager::controller_result::ControllerResult;
+use crate::manager::event::AlterSyncStateSetEvent;
+use crate::manager::event::ApplyBrokerIdEvent;
+use crate::manager::event::ElectMasterEvent;
+use crate::manager::event::EventMessage;
+use crate::manager::sync_state_info::SyncStateInfo;
+
+/// Tells whether a replica of a broker group is alive.
+pub type BrokerAlivePredicate<'a> = &'a dyn Fn(&str, &str, i64) -> bool;
+
+/// The replica state machine of the controller.
+///
+/// Every request is validated against the current state and answered with a
+/// [`ControllerResult`] carrying the events that realize it; nothing changes until
+/// those events are passed to [`ReplicasInfoManager::apply_event`]. Master and sync
+/// state set changes are fenced by epochs so that a deposed master cannot shrink or
+/// expand the sync state set of its successor.
+#[derive(Default)]
+pub struct ReplicasInfoManager {
+    replica_info_table: HashMap<CheetahString, BrokerReplicaInfo>,
+    sync_state_set_info_table: HashMap<CheetahString, SyncStateInfo>,
+}
+
+impl ReplicasInfoManager {
+    pub fn register_broker(
+        &self,
+        request: &RegisterBrokerToControllerRequestHeader,
+        is_alive: BrokerAlivePredicate<'_>,
+    ) -> ControllerResult<RegisterBrokerToControllerResponseHeader> {
+        let mut result = ControllerResult::new(RegisterBrokerToControllerResponseHeader {
+            cluster_name: Some(request.cluster_name.clone()),
+            broker_name: Some(request.broker_name.clone()),
+            ..Default::default()
+        });
+        if request.broker_id < 0 {
+            result.set_code_and_remark(
+                ResponseCode::ControllerBrokerIdInvalid,
+                format!("Broker id {} is invalid", request.broker_id),
+            );
+            return result;
+        }
+
+        let registered_address = self
+            .replica_info_table
+            .get(&request.broker_name)
+            .and_then(|replica_info| replica_info.get_broker_address(request.broker_id));
+        if registered_address != Some(&request.broker_address) {
+            result.add_event(EventMessage::ApplyBrokerId(ApplyBrokerIdEvent {
+                cluster_name: request.cluster_name.clone(),
+                broker_name: request.broker_name.clone(),
+                broker_address: request.broker_address.clone(),
+                broker_id: request.broker_id,
+            }));
+        }
+
+        if let (Some(sync_state_info), Some(replica_info)) = (
+            self.sync_state_set_info_table.get(&request.broker_name),
+            self.replica_info_table.get(&request.broker_name),
+        ) {
+            if let Some(master_broker_id) = sync_state_info.master_broker_id().filter(|id| {
+                is_alive(
+                    request.cluster_name.as_str(),
+                    request.broker_name.as_str(),
+                    *id,
+                )
+            }) {
+                let response = result.response_mut();
+                response.master_broker_id = Some(master_broker_id);
+                response.master_address =
+                    replica_info.get_broker_address(master_broker_id).cloned();
+                response.master_epoch = Some(sync_state_info.master_epoch());
+                response.sync_state_set_epoch = Some(sync_state_info.sync_state_set_epoch());
+            }
+            if let Ok(body) = SyncStateSet::new(
+                sync_state_info.sync_state_set().clone(),
+                sync_state_info.sync_state_set_epoch(),
+            )
+            .encode()
+            {
+                result.set_body(body);
+            }
+        }
+        result
+    }
+
+    pub fn elect_master(
+        &self,
+        request: &ElectMasterRequestHeader,
+        elect_policy: &dyn ElectPolicy,
+        is_alive: BrokerAlivePredicate<'_>,
+        enable_elect_unclean_master: bool,
+    ) -> ControllerResult<ElectMasterResponseHeader> {
+        let mut result = ControllerResult::new(ElectMasterResponseHeader::default());
+        let broker_name = &request.broker_name;
+        let (Some(sync_state_info), Some(replica_info)) = (
+            self.sync_state_set_info_table.get(broker_name),
+            self.replica_info_table.get(broker_name),
+        ) else {
+            result.set_code_and_remark(
+                ResponseCode::ControllerBrokerNeedToBeRegistered,
+                format!("Broker {} hasn't been registered", broker_name),
+            );
+            return result;
+        };
+        let cluster_name = replica_info.cluster_name().as_str();
+        let old_master = sync_state_info.master_broker_id();
+
+        let new_master = if request.designate_elect.unwrap_or(false) {
+            let Some(broker_id) = request
+                .broker_id
+                .filter(|id| is_alive(cluster_name, broker_name.as_str(), *id))
+            else {
+                result.set_code_and_remark(
+                    ResponseCode::ControllerElectMasterFailed,
+                    format!(
+                        "The designated broker {:?} of {} is not alive",
+                        request.broker_id, broker_name
+                    ),
+                );
+                return result;
+            };
+            Some(broker_id)
+        } else if sync_state_info.is_first_time_for_elect() && request.broker_id.is_some() {
+            // the first replica asking for an election takes over an empty group
+            request
+                .broker_id
+                .filter(|broker_id| replica_info.is_broker_exist(*broker_id))
+        } else {
+            let all_replica_brokers = replica_info.get_all_broker();
+            elect_policy.elect(
+                cluster_name,
+                broker_name.as_str(),
+                sync_state_info.sync_state_set(),
+                enable_elect_unclean_master.then_some(&all_replica_brokers),
+                old_master,
+                request.broker_id,
+            )
+        };
+
+        match new_master {
+            Some(new_master) if Some(new_master) == old_master => {
+                let response = result.response_mut();
+                response.master_broker_id = Some(new_master);
+                response.master_address = replica_info.get_broker_address(new_master).cloned();
+                response.master_epoch = Some(sync_state_info.master_epoch());
+                response.sync_state_set_epoch = Some(sync_state_info.sync_state_set_epoch());
+                if let Ok(body) =
+                    member_group_body(replica_info, sync_state_info.sync_state_set().clone())
+                {
+                    result.set_body(body);
+                }
+                result.set_code_and_remark(
+                    ResponseCode::ControllerMasterStillExist,
+                    format!("The old master {} is still alive", new_master),
+                );
+            }
+            Some(new_master) => {
+                let response = result.response_mut();
+                response.master_broker_id = Some(new_master);
+                response.master_address = replica_info.get_broker_address(new_master).cloned();
+                response.master_epoch = Some(sync_state_info.master_epoch() + 1);
+                response.sync_state_set_epoch = Some(sync_state_info.sync_state_set_epoch() + 1);
+                if let Ok(body) = member_group_body(replica_info, HashSet::from([new_master])) {
+                    result.set_body(body);
+                }
+                result.add_event(EventMessage::ElectMaster(ElectMasterEvent {
+                    broker_name: broker_name.clone(),
+                    new_master_broker_id: Some(new_master),
+                }));
+            }
+            None => {
+                if old_master.is_some() {
+                    result.add_event(EventMessage::ElectMaster(ElectMasterEvent {
+                        broker_name: broker_name.clone(),
+                        new_master_broker_id: None,
+                    }));
+                }
+                result.set_code_and_remark(
+                    ResponseCode::ControllerMasterNotAvailable,
+                    format!("Failed to elect a new master for {}", broker_name),
+                );
+            }
+        }
+        result
+    }
+
+    pub fn alter_sync_state_set(
+        &self,
+        request: &AlterSyncStateSetRequestHeader,
+        sync_state_set: &SyncStateSet,
+        is_alive: BrokerAlivePredicate<'_>,
+    ) -> ControllerResult<AlterSyncStateSetResponseHeader> {
+        let mut result = ControllerResult::new(AlterSyncStateSetResponseHeader::default());
+        let broker_name = &request.broker_name;
+        let (Some(sync_state_info), Some(replica_info)) = (
+            self.sync_state_set_info_table.get(broker_name),
+            self.replica_info_table.get(broker_name),
+        ) else {
+            result.set_code_and_remark(
+                ResponseCode::ControllerAlterSyncStateSetFailed,
+                format!("Broker {} hasn't been registered", broker_name),
+            );
+            return result;
+        };
+
+        if sync_state_info.master_broker_id() != Some(request.master_broker_id) {
+            result.set_code_and_remark(
+                ResponseCode::ControllerInvalidMaster,
+                format!(
+                    "Broker {} is not the master of {}",
+                    request.master_broker_id, broker_name
+                ),
+            );
+            return result;
+        }
+        if sync_state_info.master_epoch() != request.master_epoch {
+            result.set_code_and_remark(
+                ResponseCode::ControllerFencedMasterEpoch,
+                format!(
+                    "Master epoch {} is fenced, current is {}",
+                    request.master_epoch,
+                    sync_state_info.master_epoch()
+                ),
+            );
+            return result;
+        }
+        if sync_state_info.sync_state_set_epoch() != sync_state_set.sync_state_set_epoch {
+            result.set_code_and_remark(
+                ResponseCode::ControllerFencedSyncStateSetEpoch,
+                format!(
+                    "Sync state set epoch {} is fenced, current is {}",
+                    sync_state_set.sync_state_set_epoch,
+                    sync_state_info.sync_state_set_epoch()
+                ),
+            );
+            return result;
+        }
+        let cluster_name = replica_info.cluster_name().as_str();
+        for replica in &sync_state_set.sync_state_set {
+            if !replica_info.is_broker_exist(*replica) {
+                result.set_code_and_remark(
+                    ResponseCode::ControllerInvalidReplicas,
+                    format!("Replica {} of {} is not registered", replica, broker_name),
+                );
+                return result;
+            }
+            if !sync_state_info.sync_state_set().contains(replica)
+                && !is_alive(cluster_name, broker_name.as_str(), *replica)
+            {
+                result.set_code_and_remark(
+                    ResponseCode::ControllerBrokerNotAlive,
+                    format!("Replica {} of {} is not alive", replica, broker_name),
+                );
+                return result;
+            }
+        }
+        if !sync_state_set
+            .sync_state_set
+            .contains(&request.master_broker_id)
+        {
+            result.set_code_and_remark(
+                ResponseCode::ControllerInvalidMaster,
+                "The new sync state set must contain the master",
+            );
+            return result;
+        }
+
+        result.response_mut().new_sync_state_set_epoch =
+            Some(sync_state_info.sync_state_set_epoch() + 1);
+        if let Ok(body) = SyncStateSet::new(
+            sync_state_set.sync_state_set.clone(),
+            sync_state_info.sync_state_set_epoch() + 1,
+        )
+        .encode()
+        {
+            result.set_body(body);
+        }
+        result.add_event(EventMessage::AlterSyncStateSet(AlterSyncStateSetEvent {
+            broker_name: broker_name.clone(),
+            new_sync_state_set: sync_state_set.sync_state_set.clone(),
+        }));
+        result
+    }
+
+    pub fn get_replica_info(
+        &self,
+        request: &GetReplicaInfoRequestHeader,
+    ) -> ControllerResult<GetReplicaInfoResponseHeader> {
+        let mut result = ControllerResult::new(GetReplicaInfoResponseHeader::default());
+        let broker_name = &request.broker_name;
+        let (Some(sync_state_info), Some(replica_info)) = (
+            self.sync_state_set_info_table.get(broker_name),
+            self.replica_info_table.get(broker_name),
+        ) else {
+            result.set_code_and_remark(
+                ResponseCode::ControllerBrokerMetadataNotExist,
+                format!("Broker metadata of {} does not exist", broker_name),
+            );
+            return result;
+        };
+        let response = result.response_mut();
+        response.master_broker_id = sync_state_info.master_broker_id();
+        response.master_address = sync_state_info
+            .master_broker_id()
+            .and_then(|master| replica_info.get_broker_address(master).cloned());
+        response.master_epoch = Some(sync_state_info.master_epoch());
+        response.sync_state_set_epoch = Some(sync_state_info.sync_state_set_epoch());
+        if let Ok(body) = member_group_body(replica_info, sync_state_info.sync_state_set().clone())
+        {
+            result.set_body(body);
+        }
+        result
+    }
+
+    pub fn apply_event(&mut self, event: &EventMessage) {
+        match event {
+            EventMessage::ApplyBrokerId(event) => {
+                self.replica_info_table
+                    .entry(event.broker_name.clone())
+                    .or_insert_with(|| {
+                        BrokerReplicaInfo::new(
+                            event.cluster_name.clone(),
+                            event.broker_name.clone(),
+                        )
+                    })
+                    .add_broker(event.broker_id, event.broker_address.clone());
+                self.sync_state_set_info_table
+                    .entry(event.broker_name.clone())
+                    .or_insert_with(|| {
+                        SyncStateInfo::new(event.cluster_name.clone(), event.broker_name.clone())
+                    });
+            }
+            EventMessage::ElectMaster(event) => {
+                let Some(sync_state_info) =
+                    self.sync_state_set_info_table.get_mut(&event.broker_name)
+                else {
+                    return;
+                };
+                sync_state_info.update_master_info(event.new_master_broker_id);
+                if let Some(new_master) = event.new_master_broker_id {
+                    sync_state_info.update_sync_state_set_info(HashSet::from([new_master]));
+                }
+                info!(
+                    "Broker group {} elected master {:?} at epoch {}",
+                    event.broker_name,
+                    event.new_master_broker_id,
+                    sync_state_info.master_epoch()
+                );
+            }
+            EventMessage::AlterSyncStateSet(event) => {
+                if let Some(sync_state_info) =
+                    self.sync_state_set_info_table.get_mut(&event.broker_name)
+                {
+                    sync_state_info.update_sync_state_set_info(event.new_sync_state_set.clone());
+                }
+            }
+        }
+    }
+
+    pub fn is_contains_broker(&self, broker_name: &str) -> bool {
+        self.replica_info_table.contains_key(broker_name)
+    }
+
+    pub fn get_sync_state_info(&self, broker_name: &str) -> Option<&SyncStateInfo> {
+        self.sync_state_set_info_table.get(broker_name)
+    }
+
+    pub fn get_broker_replica_info(&self, broker_name: &str) -> Option<&BrokerReplicaInfo> {
+        self.replica_info_table.get(broker_name)
+    }
+}
+
+fn member_group_body(
+    replica_info: &BrokerReplicaInfo,
+    sync_state_set: HashSet<i64>,
+) -> Result<Vec<u8>, rocketmq_common::error::Error> {
+    ElectMasterResponseBody {
+        broker_member_group: Some(replica_info.to_broker_member_group()),
+        sync_state_set,
+    }
+    .encode()
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::protocol::RemotingDeserializable;
+
+    use super::*;
+    use crate::elect::default_elect_policy::DefaultElectPolicy;
+
+    fn register(manager: &mut ReplicasInfoManager, broker_id: i64) {
+        let result = manager.register_broker(
+            &RegisterBrokerToControllerRequestHeader {
+                cluster_name: CheetahString::from_static_str("cluster"),
+                broker_name: CheetahString::from_static_str("broker-a"),
+                broker_id,
+                broker_address: CheetahString::from_string(format!(
+                    "127.0.0.1:{}",
+                    10911 + broker_id
+                )),
+                invoke_time: None,
+            },
+            &|_, _, _| true,
+        );
+        assert!(result.is_success());
+        for event in result.events() {
+            manager.apply_event(event);
+        }
+    }
+
+    fn elect(
+        manager: &mut ReplicasInfoManager,
+        request: ElectMasterRequestHeader,
+        alive: &[i64],
+    ) -> ControllerResult<ElectMasterResponseHeader> {
+        let alive_brokers = alive.to_vec();
+        let policy = DefaultElectPolicy::new(
+            std::sync::Arc::new(move |_, _, broker_id| alive_brokers.contains(&broker_id)),
+            None,
+        );
+        let is_alive = |_: &str, _: &str, broker_id: i64| alive.contains(&broker_id);
+        let result = manager.elect_master(&request, &policy, &is_alive, false);
+        for event in result.events() {
+            manager.apply_event(event);
+        }
+        result
+    }
+
+    #[test]
+    fn first_election_makes_requesting_broker_master() {
+        let mut manager = ReplicasInfoManager::default();
+        register(&mut manager, 1);
+        register(&mut manager, 2);
+
+        let result = elect(
+            &mut manager,
+            ElectMasterRequestHeader::of_broker_trigger("cluster", "broker-a", 2),
+            &[1, 2],
+        );
+
+        assert!(result.is_success());
+        assert_eq!(result.response().master_broker_id, Some(2));
+        assert_eq!(result.response().master_epoch, Some(1));
+        let sync_state_info = manager.get_sync_state_info("broker-a").unwrap();
+        assert_eq!(sync_state_info.master_broker_id(), Some(2));
+        assert_eq!(sync_state_info.sync_state_set(), &HashSet::from([2]));
+        assert_eq!(sync_state_info.sync_state_set_epoch(), 1);
+    }
+
+    #[test]
+    fn alive_master_is_not_replaced() {
+        let mut manager = ReplicasInfoManager::default();
+        register(&mut manager, 1);
+        register(&mut manager, 2);
+        elect(
+            &mut manager,
+            ElectMasterRequestHeader::of_broker_trigger("cluster", "broker-a", 1),
+            &[1, 2],
+        );
+
+        let result = elect(
+            &mut manager,
+            ElectMasterRequestHeader::of_broker_trigger("cluster", "broker-a", 2),
+            &[1, 2],
+        );
+
+        assert_eq!(
+            result.response_code(),
+            ResponseCode::ControllerMasterStillExist
+        );
+        assert!(result.events().is_empty());
+        assert_eq!(result.response().master_broker_id, Some(1));
+    }
+
+    #[test]
+    fn failover_elects_in_sync_replica_and_bumps_epochs() {
+        let mut manager = ReplicasInfoManager::default();
+        register(&mut manager, 1);
+        register(&mut manager, 2);
+        elect(
+            &mut manager,
+            ElectMasterRequestHeader::of_broker_trigger("cluster", "broker-a", 1),
+            &[1, 2],
+        );
+        let altered = manager.alter_sync_state_set(
+            &AlterSyncStateSetRequestHeader {
+                broker_name: CheetahString::from_static_str("broker-a"),
+                master_broker_id: 1,
+                master_epoch: 1,
+                invoke_time: None,
+            },
+            &SyncStateSet::new(HashSet::from([1, 2]), 1),
+            &|_, _, _| true,
+        );
+        assert!(altered.is_success());
+        assert_eq!(altered.response().new_sync_state_set_epoch, Some(2));
+        for event in altered.events() {
+            manager.apply_event(event);
+        }
+
+        let result = elect(
+            &mut manager,
+            ElectMasterRequestHeader::of_controller_trigger("cluster", "broker-a"),
+            &[2],
+        );
+
+        assert!(result.is_success());
+        assert_eq!(result.response().master_broker_id, Some(2));
+        assert_eq!(result.response().master_epoch, Some(2));
+        let body = ElectMasterResponseBody::decode(result.body().unwrap()).unwrap();
+        assert_eq!(body.sync_state_set, HashSet::from([2]));
+        assert_eq!(body.broker_member_group.unwrap().broker_addrs.len(), 2);
+        let sync_state_info = manager.get_sync_state_info("broker-a").unwrap();
+        assert_eq!(sync_state_info.master_epoch(), 2);
+        assert_eq!(sync_state_info.sync_state_set_epoch(), 3);
+    }
+
+    #[test]
+    fn no_in_sync_replica_leaves_group_without_master() {
+        let mut manager = ReplicasInfoManager::default();
+        register(&mut manager, 1);
+        register(&mut manager, 2);
+        elect(
+            &mut manager,
+            ElectMasterRequestHeader::of_broker_trigger("cluster", "broker-a", 1),
+            &[1, 2],
+        );
+
+        let result = elect(
+            &mut manager,
+            ElectMasterRequestHeader::of_controller_trigger("cluster", "broker-a"),
+            &[2],
+        );
+
+        assert_eq!(
+            result.response_code(),
+            ResponseCode::ControllerMasterNotAvailable
+        );
+        assert!(!manager
+            .get_sync_state_info("broker-a")
+            .unwrap()
+            .is_master_exist());
+    }
+
+    #[test]
+    fn alter_sync_state_set_is_fenced_by_master_epoch() {
+        let mut manager = ReplicasInfoManager::default();
+        register(&mut manager, 1);
+        register(&mut manager, 2);
+        elect(
+            &mut manager,
+            ElectMasterRequestHeader::of_broker_trigger("cluster", "broker-a", 1),
+            &[1, 2],
+        );
+
+        let result = manager.alter_sync_state_set(
+            &AlterSyncStateSetRequestHeader {
+                broker_name: CheetahString::from_static_str("broker-a"),
+                master_broker_id: 1,
+                master_epoch: 0,
+                invoke_time: None,
+            },
+            &SyncStateSet::new(HashSet::from([1, 2]), 1),
+            &|_, _, _| true,
+        );
+
+        assert_eq!(
+            result.response_code(),
+            ResponseCode::ControllerFencedMasterEpoch
+        );
+        assert!(result.events().is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashSet;
+
+use cheetah_string::CheetahString;
+
+/// The master and sync state set of a broker group, each versioned by its own epoch.
+#[derive(Debug, Clone)]
+pub struct SyncStateInfo {
+    cluster_name: CheetahString,
+    broker_name: CheetahString,
+    master_broker_id: Option<i64>,
+    master_epoch: i32,
+    sync_state_set: HashSet<i64>,
+    sync_state_set_epoch: i32,
+}
+
+impl SyncStateInfo {
+    pub fn new(cluster_name: CheetahString, broker_name: CheetahString) -> Self {
+        Self {
+            cluster_name,
+            broker_name,
+            master_broker_id: None,
+            master_epoch: 0,
+            sync_state_set: HashSet::new(),
+            sync_state_set_epoch: 0,
+        }
+    }
+
+    /// Every change of master, including losing it, starts a new master epoch.
+    pub fn update_master_info(&mut self, master_broker_id: Option<i64>) {
+        self.master_broker_id = master_broker_id;
+        self.master_epoch += 1;
+    }
+
+    pub fn update_sync_state_set_info(&mut self, sync_state_set: HashSet<i64>) {
+        self.sync_state_set = sync_state_set;
+        self.sync_state_set_epoch += 1;
+    }
+
+    pub fn is_first_time_for_elect(&self) -> bool {
+        self.master_epoch == 0
+    }
+
+    pub fn is_master_exist(&self) -> bool {
+        self.master_broker_id.is_some()
+    }
+
+    pub fn cluster_name(&self) -> &CheetahString {
+        &self.cluster_name
+    }
+
+    pub fn broker_name(&self) -> &CheetahString {
+        &self.broker_name
+    }
+
+    pub fn master_broker_id(&self) -> Option<i64> {
+        self.master_broker_id
+    }
+
+    pub fn master_epoch(&self) -> i32 {
+        self.master_epoch
+    }
+
+    pub fn sync_state_set(&self) -> &HashSet<i64> {
+        &self.sync_state_set
+    }
+
+    pub fn sync_state_set_epoch(&self) -> i32 {
+        self.sync_state_set_epoch
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rocketmq_remoting::code::request_code::ControllerRequestCode;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::body::sync_state_set::SyncStateSet;
+use rocketmq_remoting::protocol::header::broker::broker_heartbeat_request_header::BrokerHeartbeatRequestHeader;
+use rocketmq_remoting::protocol::header::controller::alter_sync_state_set_request_header::AlterSyncStateSetRequestHeader;
+use rocketmq_remoting::protocol::header::controller::elect_master_request_header::ElectMasterRequestHeader;
+use rocketmq_remoting::protocol::header::controller::get_replica_info_request_header::GetReplicaInfoRequestHeader;
+use rocketmq_remoting::protocol::header::controller::register_broker_to_controller_request_header::RegisterBrokerToControllerRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_remoting::runtime::processor::RequestProcessor;
+use rocketmq_remoting::Result;
+use tracing::debug;
+
+use crate::controller_error::ControllerRemotingErrorWithMessage;
+use crate::ControllerManager;
+
+#[derive(Clone)]
+pub struct ControllerRequestProcessor {
+    controller_manager: ControllerManager,
+}
+
+impl ControllerRequestProcessor {
+    pub fn new(controller_manager: ControllerManager) -> Self {
+        Self { controller_manager }
+    }
+
+    async fn process_controller_request(
+        &self,
+        request_code: ControllerRequestCode,
+        request: RemotingCommand,
+    ) -> crate::Result<RemotingCommand> {
+        let response = match request_code {
+            ControllerRequestCode::ControllerRegisterBroker => {
+                let request_header = request
+                    .decode_command_custom_header::<RegisterBrokerToControllerRequestHeader>()
+                    .map_err(|e| {
+                        ControllerRemotingErrorWithMessage::new(
+                            e,
+                            "decode RegisterBrokerToControllerRequestHeader fail".to_string(),
+                        )
+                    })?;
+                self.controller_manager
+                    .register_broker(&request_header)
+                    .await
+            }
+            ControllerRequestCode::ControllerElectMaster => {
+                let request_header = request
+                    .decode_command_custom_header::<ElectMasterRequestHeader>()
+                    .map_err(|e| {
+                        ControllerRemotingErrorWithMessage::new(
+                            e,
+                            "decode ElectMasterRequestHeader fail".to_string(),
+                        )
+                    })?;
+                self.controller_manager.elect_master(&request_header).await
+            }
+            ControllerRequestCode::ControllerAlterSyncStateSet => {
+                let request_header = request
+                    .decode_command_custom_header::<AlterSyncStateSetRequestHeader>()
+                    .map_err(|e| {
+                        ControllerRemotingErrorWithMessage::new(
+                            e,
+                            "decode AlterSyncStateSetRequestHeader fail".to_string(),
+                        )
+                    })?;
+                let Some(sync_state_set) = request
+                    .get_body()
+                    .and_then(|body| SyncStateSet::decode(body).ok())
+                else {
+                    return Ok(RemotingCommand::create_response_command_with_code_remark(
+                        RemotingSysResponseCode::SystemError,
+                        "The sync state set body is missing or malformed",
+                    ));
+                };
+                self.controller_manager
+                    .alter_sync_state_set(&request_header, &sync_state_set)
+                    .await
+            }
+            ControllerRequestCode::ControllerGetReplicaInfo => {
+                let request_header = request
+                    .decode_command_custom_header::<GetReplicaInfoRequestHeader>()
+                    .map_err(|e| {
+                        ControllerRemotingErrorWithMessage::new(
+                            e,
+                            "decode GetReplicaInfoRequestHeader fail".to_string(),
+                        )
+                    })?;
+                self.controller_manager
+                    .get_replica_info(&request_header)
+                    .await
+            }
+            _ => RemotingCommand::create_response_command_with_code_remark(
+                RemotingSysResponseCode::RequestCodeNotSupported,
+                format!("request type {:?} not supported", request_code),
+            ),
+        };
+        Ok(response)
+    }
+
+    fn process_broker_heartbeat(&self, request: RemotingCommand) -> crate::Result<RemotingCommand> {
+        let request_header = request
+            .decode_command_custom_header::<BrokerHeartbeatRequestHeader>()
+            .map_err(|e| {
+                ControllerRemotingErrorWithMessage::new(
+                    e,
+                    "decode BrokerHeartbeatRequestHeader fail".to_string(),
+                )
+            })?;
+        self.controller_manager.on_broker_heartbeat(&request_header);
+        Ok(RemotingCommand::create_response_command())
+    }
+}
+
+impl RequestProcessor for ControllerRequestProcessor {
+    async fn process_request(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        request: RemotingCommand,
+    ) -> Result<Option<RemotingCommand>> {
+        let result = if let Some(request_code) = ControllerRequestCode::value_of(request.code()) {
+            debug!("Controller received request code: {:?}", request_code);
+            self.process_controller_request(request_code, request).await
+        } else if RequestCode::from(request.code()) == RequestCode::BrokerHeartbeat {
+            self.process_broker_heartbeat(request)
+        } else {
+            Ok(RemotingCommand::create_response_command_with_code_remark(
+                RemotingSysResponseCode::RequestCodeNotSupported,
+                format!("request code {} not supported", request.code()),
+            ))
+        };
+        result.map(Some).map_err(Into::into)
+    }
+}
@@ -0,0 +1,384 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rocketmq_common::utils::file_utils;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::error;
+use tracing::warn;
+
+use crate::manager::event::EventMessage;
+
+const STATE_FILE: &str = "raft_state.json";
+const LOG_FILE: &str = "raft_log";
+
+/// One entry of the replicated log: the events of one controller request. An entry without
+/// events is the no-op a new leader appends to commit the entries of older terms.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub index: i64,
+    pub term: i64,
+    pub events: Vec<EventMessage>,
+}
+
+/// State that must survive a restart for the elections to stay safe.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HardState {
+    pub current_term: i64,
+    pub voted_for: Option<String>,
+    /// Entries up to this index are committed and get applied again on startup.
+    pub commit_index: i64,
+}
+
+/// Entries appended to the log file but not synced yet. The sync runs without holding the
+/// lock the log is guarded by, [`RaftLog::mark_synced`] records it afterwards.
+pub struct PendingSync {
+    log_file: Arc<File>,
+    index: i64,
+    generation: u64,
+}
+
+impl PendingSync {
+    pub fn sync(&self) -> std::io::Result<()> {
+        self.log_file.sync_data()
+    }
+}
+
+/// The raft log of a controller, stored as one JSON entry per line next to the hard state.
+///
+/// Entries are numbered from 1, index 0 stands for the empty log with term 0.
+pub struct RaftLog {
+    dir: PathBuf,
+    state_path: PathBuf,
+    log_path: PathBuf,
+    hard_state: HardState,
+    entries: Vec<LogEntry>,
+    log_file: Arc<File>,
+    /// Entries up to this index are durable, only they may be reported as stored.
+    synced_index: i64,
+    /// Bumped whenever the log file is rewritten, syncs of the old file no longer count.
+    generation: u64,
+}
+
+impl RaftLog {
+    pub fn open(store_path: &str) -> std::io::Result<Self> {
+        let dir = Path::new(store_path);
+        std::fs::create_dir_all(dir)?;
+        let state_path = dir.join(STATE_FILE);
+        let hard_state = match file_utils::file_to_string(&state_path.to_string_lossy())? {
+            content if content.is_empty() => HardState::default(),
+            content => serde_json::from_str(content.as_str())?,
+        };
+
+        let log_path = dir.join(LOG_FILE);
+        let content = match std::fs::read(&log_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let mut entries: Vec<LogEntry> = Vec::new();
+        let mut valid_size = 0;
+        for line in content.split_inclusive(|b| *b == b'\n') {
+            let entry = match serde_json::from_slice::<LogEntry>(line) {
+                Ok(entry) if line.ends_with(b"\n") && entry.index == entries.len() as i64 + 1 => {
+                    entry
+                }
+                _ => break,
+            };
+            valid_size += line.len();
+            entries.push(entry);
+        }
+        let log_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)?;
+        if valid_size < content.len() {
+            warn!(
+                "drop {} bytes of torn entries at the end of the raft log {}",
+                content.len() - valid_size,
+                log_path.display()
+            );
+            log_file.set_len(valid_size as u64)?;
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            state_path,
+            log_path,
+            hard_state,
+            synced_index: entries.len() as i64,
+            entries,
+            log_file: Arc::new(log_file),
+            generation: 0,
+        })
+    }
+
+    pub fn hard_state(&self) -> &HardState {
+        &self.hard_state
+    }
+
+    pub fn last_index(&self) -> i64 {
+        self.entries.len() as i64
+    }
+
+    pub fn synced_index(&self) -> i64 {
+        self.synced_index
+    }
+
+    pub fn last_term(&self) -> i64 {
+        self.entries.last().map_or(0, |entry| entry.term)
+    }
+
+    /// Term of the entry at `index`, `None` past the end of the log.
+    pub fn term_at(&self, index: i64) -> Option<i64> {
+        if index == 0 {
+            return Some(0);
+        }
+        self.entry(index).map(|entry| entry.term)
+    }
+
+    pub fn entry(&self, index: i64) -> Option<&LogEntry> {
+        if index < 1 {
+            return None;
+        }
+        self.entries.get(index as usize - 1)
+    }
+
+    /// At most `max` entries starting at `index`.
+    pub fn entries_from(&self, index: i64, max: usize) -> Vec<LogEntry> {
+        let start = (index.max(1) as usize - 1).min(self.entries.len());
+        self.entries[start..].iter().take(max).cloned().collect()
+    }
+
+    /// Writes `entries` to the log file. They are not durable before the returned sync ran.
+    pub fn append(&mut self, entries: Vec<LogEntry>) -> std::io::Result<PendingSync> {
+        let mut buffer = Vec::new();
+        for (offset, entry) in entries.iter().enumerate() {
+            if entry.index != self.last_index() + 1 + offset as i64 {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "raft entry {} does not follow the last index {}",
+                        entry.index,
+                        self.last_index()
+                    ),
+                ));
+            }
+            serde_json::to_writer(&mut buffer, entry)?;
+            buffer.push(b'\n');
+        }
+        (&*self.log_file).write_all(&buffer)?;
+        self.entries.extend(entries);
+        Ok(self.pending_sync())
+    }
+
+    /// Sync of every entry written so far.
+    pub fn pending_sync(&self) -> PendingSync {
+        PendingSync {
+            log_file: self.log_file.clone(),
+            index: self.last_index(),
+            generation: self.generation,
+        }
+    }
+
+    pub fn mark_synced(&mut self, pending_sync: &PendingSync) {
+        if pending_sync.generation == self.generation {
+            self.synced_index = self.synced_index.max(pending_sync.index);
+        }
+    }
+
+    /// Drops the entries from `index` on, they conflict with the leader's log.
+    pub fn truncate_from(&mut self, index: i64) -> std::io::Result<()> {
+        let keep = (index.max(1) as usize - 1).min(self.entries.len());
+        self.entries.truncate(keep);
+        let mut buffer = Vec::new();
+        for entry in &self.entries {
+            serde_json::to_writer(&mut buffer, entry)?;
+            buffer.push(b'\n');
+        }
+        self.generation += 1;
+        self.synced_index = self.synced_index.min(keep as i64);
+        write_durably(&self.dir, &self.log_path, &buffer)?;
+        self.log_file = Arc::new(OpenOptions::new().append(true).open(&self.log_path)?);
+        self.synced_index = keep as i64;
+        Ok(())
+    }
+
+    /// Persists the term and the vote, which must be durable before the vote is granted or
+    /// the election of the term started.
+    pub fn set_term_and_vote(
+        &mut self,
+        term: i64,
+        voted_for: Option<String>,
+    ) -> std::io::Result<()> {
+        if self.hard_state.current_term == term && self.hard_state.voted_for == voted_for {
+            return Ok(());
+        }
+        let hard_state = HardState {
+            current_term: term,
+            voted_for,
+            commit_index: self.hard_state.commit_index,
+        };
+        self.persist(&hard_state)?;
+        self.hard_state = hard_state;
+        Ok(())
+    }
+
+    pub fn set_commit_index(&mut self, commit_index: i64) {
+        if commit_index <= self.hard_state.commit_index {
+            return;
+        }
+        let hard_state = HardState {
+            commit_index,
+            ..self.hard_state.clone()
+        };
+        // Committed entries are replayed from the last persisted commit index, a failed write
+        // only means they are applied later on the next start.
+        match self.persist(&hard_state) {
+            Ok(()) => self.hard_state = hard_state,
+            Err(e) => error!(
+                "persist raft state to {} failed: {}",
+                self.state_path.display(),
+                e
+            ),
+        }
+    }
+
+    fn persist(&self, hard_state: &HardState) -> std::io::Result<()> {
+        let content = serde_json::to_vec(hard_state)?;
+        write_durably(&self.dir, &self.state_path, &content)
+    }
+}
+
+/// Replaces `path` with `content` through a synced temp file, so a crash leaves either the
+/// old or the new content.
+fn write_durably(dir: &Path, path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut tmp_file = File::create(&tmp_path)?;
+    tmp_file.write_all(content)?;
+    tmp_file.sync_all()?;
+    std::fs::rename(&tmp_path, path)?;
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+    use crate::manager::event::ElectMasterEvent;
+
+    fn entry(index: i64, term: i64) -> LogEntry {
+        LogEntry {
+            index,
+            term,
+            events: vec![EventMessage::ElectMaster(ElectMasterEvent {
+                broker_name: CheetahString::from_static_str("broker-a"),
+                new_master_broker_id: Some(index),
+            })],
+        }
+    }
+
+    #[test]
+    fn entries_and_hard_state_survive_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let store_path = dir.path().to_str().unwrap();
+        let mut log = RaftLog::open(store_path).unwrap();
+        let pending_sync = log.append(vec![entry(1, 1), entry(2, 1)]).unwrap();
+        log.append(vec![entry(3, 2)]).unwrap();
+        assert_eq!(log.synced_index(), 0);
+        pending_sync.sync().unwrap();
+        log.mark_synced(&pending_sync);
+        assert_eq!(log.synced_index(), 2);
+        log.set_term_and_vote(2, Some("n1".to_string())).unwrap();
+        log.set_commit_index(2);
+        assert!(log.append(vec![entry(5, 2)]).is_err());
+        drop(log);
+
+        let log = RaftLog::open(store_path).unwrap();
+        assert_eq!(log.last_index(), 3);
+        assert_eq!(log.last_term(), 2);
+        assert_eq!(log.term_at(0), Some(0));
+        assert_eq!(log.term_at(2), Some(1));
+        assert_eq!(log.term_at(4), None);
+        assert_eq!(log.entries_from(2, 10), vec![entry(2, 1), entry(3, 2)]);
+        assert_eq!(
+            log.hard_state(),
+            &HardState {
+                current_term: 2,
+                voted_for: Some("n1".to_string()),
+                commit_index: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn truncated_and_torn_entries_are_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let store_path = dir.path().to_str().unwrap();
+        let mut log = RaftLog::open(store_path).unwrap();
+        log.append(vec![entry(1, 1), entry(2, 1), entry(3, 1)])
+            .unwrap();
+        let stale_sync = log.pending_sync();
+        log.truncate_from(2).unwrap();
+        assert_eq!(log.synced_index(), 1);
+        log.mark_synced(&stale_sync);
+        assert_eq!(log.synced_index(), 1);
+        log.append(vec![entry(2, 2)]).unwrap();
+        drop(log);
+
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(LOG_FILE))
+            .unwrap();
+        file.write_all(b"{\"index\":3,\"te").unwrap();
+        drop(file);
+
+        let mut log = RaftLog::open(store_path).unwrap();
+        assert_eq!(log.entries_from(1, 10), vec![entry(1, 1), entry(2, 2)]);
+        log.append(vec![entry(3, 2)]).unwrap();
+        drop(log);
+        assert_eq!(RaftLog::open(store_path).unwrap().last_index(), 3);
+    }
+
+    #[test]
+    fn failed_vote_persist_keeps_the_old_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let store_path = dir.path().join("raft");
+        let mut log = RaftLog::open(store_path.to_str().unwrap()).unwrap();
+        log.set_term_and_vote(1, Some("n0".to_string())).unwrap();
+
+        std::fs::remove_dir_all(&store_path).unwrap();
+        assert!(log.set_term_and_vote(2, Some("n1".to_string())).is_err());
+        assert_eq!(
+            log.hard_state(),
+            &HardState {
+                current_term: 1,
+                voted_for: Some("n0".to_string()),
+                commit_index: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,949 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use rand::Rng;
+use rocketmq_common::common::controller::controller_config::ControllerConfig;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::task::JoinSet;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::controller_error::ControllerError;
+use crate::manager::event::EventMessage;
+use crate::raft::raft_log::LogEntry;
+use crate::raft::raft_log::PendingSync;
+use crate::raft::raft_log::RaftLog;
+use crate::raft::raft_protocol::AppendEntriesRequest;
+use crate::raft::raft_protocol::AppendEntriesResponse;
+use crate::raft::raft_protocol::RaftRequest;
+use crate::raft::raft_protocol::RaftResponse;
+use crate::raft::raft_protocol::VoteRequest;
+use crate::raft::raft_protocol::VoteResponse;
+
+/// Interval of the leader heartbeats, also the longest a replicator waits for new entries.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A follower starts an election after not hearing from a leader for this long plus a
+/// random part of it.
+const ELECTION_TIMEOUT_MILLIS: u64 = 500;
+
+const ELECTION_CHECK_INTERVAL: Duration = Duration::from_millis(30);
+
+const RPC_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long a proposal waits for its entry to be committed and applied.
+const COMMIT_TIMEOUT: Duration = Duration::from_secs(3);
+
+const MAX_ENTRIES_PER_APPEND: usize = 64;
+
+/// Applies the events of a committed entry to the controller state.
+pub type EntryApplier = Box<dyn Fn(&[EventMessage]) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaftRole {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+impl Display for RaftRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RaftRole::Follower => write!(f, "FOLLOWER"),
+            RaftRole::Candidate => write!(f, "CANDIDATE"),
+            RaftRole::Leader => write!(f, "LEADER"),
+        }
+    }
+}
+
+struct RaftState {
+    role: RaftRole,
+    leader_id: Option<String>,
+    log: RaftLog,
+    commit_index: i64,
+    last_applied: i64,
+    /// Highest index known to be replicated on each follower, only meaningful on the leader.
+    match_index: HashMap<String, i64>,
+}
+
+/// Raft replication of the controller events within the controller group.
+///
+/// Every request changing the broker replicas is turned into events by the leader, which
+/// appends them to its log and only answers once a majority of the group stored them. Each
+/// controller applies the committed entries, in order, to its own `ReplicasInfoManager`,
+/// so a newly elected leader continues with the same replica state. The term, the vote and
+/// the log are persisted under the controller store path and replayed on restart.
+pub struct RaftNode {
+    group: String,
+    self_id: String,
+    /// `(id, address)` of every member, this controller included. Empty for a standalone
+    /// controller.
+    peers: Vec<(String, String)>,
+    state: parking_lot::Mutex<RaftState>,
+    applier: EntryApplier,
+    last_leader_contact: parking_lot::Mutex<Instant>,
+    connections: HashMap<String, tokio::sync::Mutex<Option<TcpStream>>>,
+    append_notify: Notify,
+    apply_notify: Notify,
+    running: AtomicBool,
+    tasks: parking_lot::Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl RaftNode {
+    /// Opens the raft log of this controller and applies the entries it knows to be
+    /// committed.
+    pub fn new(
+        controller_config: &ControllerConfig,
+        applier: EntryApplier,
+    ) -> std::io::Result<Self> {
+        let peers = parse_peers(controller_config.controller_dledger_peers.as_str());
+        let self_id = controller_config.controller_dledger_self_id.clone();
+        let connections = peers
+            .iter()
+            .filter(|(id, _)| *id != self_id)
+            .map(|(id, _)| (id.clone(), tokio::sync::Mutex::new(None)))
+            .collect();
+        let log = RaftLog::open(controller_config.get_controller_store_path().as_str())?;
+        let commit_index = log.hard_state().commit_index.min(log.last_index());
+        for index in 1..=commit_index {
+            if let Some(entry) = log.entry(index) {
+                applier(&entry.events);
+            }
+        }
+        info!(
+            "controller {} recovered {} raft entries, {} of them committed",
+            self_id,
+            log.last_index(),
+            commit_index
+        );
+        Ok(Self {
+            group: controller_config.controller_dledger_group.clone(),
+            self_id,
+            peers,
+            state: parking_lot::Mutex::new(RaftState {
+                role: RaftRole::Follower,
+                leader_id: None,
+                log,
+                commit_index,
+                last_applied: commit_index,
+                match_index: HashMap::new(),
+            }),
+            applier,
+            last_leader_contact: parking_lot::Mutex::new(Instant::now()),
+            connections,
+            append_notify: Notify::new(),
+            apply_notify: Notify::new(),
+            running: AtomicBool::new(false),
+            tasks: parking_lot::Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Binds the address of this controller and starts taking part in elections. A
+    /// standalone controller elects itself right away.
+    pub fn start(self: &Arc<Self>) -> std::io::Result<()> {
+        self.running.store(true, Ordering::Release);
+        let mut tasks = Vec::new();
+        if !self.peers.is_empty() {
+            let address = self
+                .peer_address(self.self_id.as_str())
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("controller self id {} is not in the peers", self.self_id),
+                    )
+                })?
+                .to_string();
+            let port = address
+                .rsplit_once(':')
+                .and_then(|(_, port)| port.parse::<u16>().ok())
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("invalid controller address {}", address),
+                    )
+                })?;
+            let listener = std::net::TcpListener::bind(("0.0.0.0", port))?;
+            listener.set_nonblocking(true)?;
+            let listener = TcpListener::from_std(listener)?;
+            info!("controller raft of {} listen on {}", self.self_id, address);
+
+            let node = self.clone();
+            tasks.push(tokio::spawn(async move {
+                loop {
+                    match listener.accept().await {
+                        Ok((stream, _)) => {
+                            let _ = stream.set_nodelay(true);
+                            tokio::spawn(node.clone().serve(stream));
+                        }
+                        Err(e) => {
+                            error!("controller raft accept connection failed: {}", e);
+                        }
+                    }
+                }
+            }));
+        }
+        tasks.push(tokio::spawn(self.clone().run_election()));
+        self.tasks.lock().extend(tasks);
+        Ok(())
+    }
+
+    pub fn shutdown(&self) {
+        self.running.store(false, Ordering::Release);
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
+        let mut state = self.state.lock();
+        state.role = RaftRole::Follower;
+        state.leader_id = None;
+        self.apply_notify.notify_waiters();
+    }
+
+    pub fn role(&self) -> RaftRole {
+        self.state.lock().role
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.role() == RaftRole::Leader
+    }
+
+    pub fn self_id(&self) -> &str {
+        self.self_id.as_str()
+    }
+
+    pub fn leader_id(&self) -> Option<String> {
+        self.state.lock().leader_id.clone()
+    }
+
+    /// Raft address of the current leader, if known.
+    pub fn leader_address(&self) -> Option<String> {
+        let leader_id = self.leader_id()?;
+        self.peer_address(leader_id.as_str()).map(str::to_string)
+    }
+
+    pub fn current_term(&self) -> i64 {
+        self.state.lock().log.hard_state().current_term
+    }
+
+    pub fn commit_index(&self) -> i64 {
+        self.state.lock().commit_index
+    }
+
+    /// Appends `events` to the log and waits until they are committed and applied.
+    pub async fn propose(&self, events: Vec<EventMessage>) -> crate::Result<()> {
+        let append_failed =
+            |e| ControllerError::MQControllerError(format!("append raft entry failed: {}", e));
+        let (index, term, pending_sync) = {
+            let mut state = self.state.lock();
+            if state.role != RaftRole::Leader {
+                return Err(ControllerError::NotLeader(state.leader_id.clone()));
+            }
+            let index = state.log.last_index() + 1;
+            let term = state.log.hard_state().current_term;
+            let pending_sync = state
+                .log
+                .append(vec![LogEntry {
+                    index,
+                    term,
+                    events,
+                }])
+                .map_err(append_failed)?;
+            (index, term, pending_sync)
+        };
+        // Followers can store the entry while the leader syncs it.
+        self.append_notify.notify_waiters();
+        self.sync_log(pending_sync).await.map_err(append_failed)?;
+        self.advance_commit_index(term);
+        self.wait_for_applied(index, term).await
+    }
+
+    /// Waits until this controller leads the group and applied its whole log, so the state
+    /// it answers from includes everything earlier leaders committed.
+    pub async fn wait_for_ready(&self) -> crate::Result<()> {
+        let (index, term) = {
+            let state = self.state.lock();
+            if state.role != RaftRole::Leader {
+                return Err(ControllerError::NotLeader(state.leader_id.clone()));
+            }
+            (state.log.last_index(), state.log.hard_state().current_term)
+        };
+        self.wait_for_applied(index, term).await
+    }
+
+    async fn wait_for_applied(&self, index: i64, term: i64) -> crate::Result<()> {
+        let deadline = tokio::time::Instant::now() + COMMIT_TIMEOUT;
+        loop {
+            let notified = self.apply_notify.notified();
+            {
+                let state = self.state.lock();
+                if state.last_applied >= index {
+                    return if state.log.term_at(index) == Some(term) {
+                        Ok(())
+                    } else {
+                        Err(ControllerError::NotLeader(state.leader_id.clone()))
+                    };
+                }
+                if state.role != RaftRole::Leader || state.log.hard_state().current_term != term {
+                    return Err(ControllerError::NotLeader(state.leader_id.clone()));
+                }
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return Err(ControllerError::CommitTimeout);
+            }
+        }
+    }
+
+    async fn serve(self: Arc<Self>, mut stream: TcpStream) {
+        loop {
+            let request = match RaftRequest::read_from(&mut stream).await {
+                Ok(request) => request,
+                Err(_) => return,
+            };
+            if !self.running.load(Ordering::Acquire) {
+                return;
+            }
+            let response = match request {
+                RaftRequest::Vote(request) => RaftResponse::Vote(self.handle_vote(&request)),
+                RaftRequest::AppendEntries(request) => {
+                    RaftResponse::AppendEntries(self.handle_append_entries(request).await)
+                }
+            };
+            if let Err(e) = response.write_to(&mut stream).await {
+                warn!("controller raft write response failed: {}", e);
+                return;
+            }
+        }
+    }
+
+    fn handle_vote(&self, request: &VoteRequest) -> VoteResponse {
+        let mut state = self.state.lock();
+        if request.group != self.group {
+            warn!(
+                "reject the vote of {} from the raft group {}",
+                request.candidate_id, request.group
+            );
+            return VoteResponse {
+                term: state.log.hard_state().current_term,
+                vote_granted: false,
+            };
+        }
+        if request.term > state.log.hard_state().current_term {
+            self.become_follower(&mut state, request.term, None);
+        }
+        let hard_state = state.log.hard_state();
+        let log_is_current = (request.last_log_term, request.last_log_index)
+            >= (state.log.last_term(), state.log.last_index());
+        let mut vote_granted = request.term == hard_state.current_term
+            && (hard_state.voted_for.is_none()
+                || hard_state.voted_for.as_deref() == Some(request.candidate_id.as_str()))
+            && log_is_current;
+        if vote_granted {
+            // A vote which is not durable could be granted twice after a restart.
+            if let Err(e) = state
+                .log
+                .set_term_and_vote(request.term, Some(request.candidate_id.clone()))
+            {
+                error!(
+                    "persist the vote for {} in term {} failed: {}",
+                    request.candidate_id, request.term, e
+                );
+                vote_granted = false;
+            }
+        }
+        if vote_granted {
+            *self.last_leader_contact.lock() = Instant::now();
+        }
+        VoteResponse {
+            term: state.log.hard_state().current_term,
+            vote_granted,
+        }
+    }
+
+    /// Stores the entries of the leader. Success is only reported once they are synced, which
+    /// happens without holding the state lock.
+    async fn handle_append_entries(&self, request: AppendEntriesRequest) -> AppendEntriesResponse {
+        let term = request.term;
+        let prev_log_index = request.prev_log_index;
+        let (last_new_index, leader_commit, pending_sync) = match self.append_entries(request) {
+            Ok(appended) => appended,
+            Err(response) => return response,
+        };
+        if let Some(pending_sync) = pending_sync {
+            if let Err(e) = self.sync_log(pending_sync).await {
+                error!("sync raft entries failed: {}", e);
+                return AppendEntriesResponse {
+                    term,
+                    success: false,
+                    match_index: self.state.lock().log.synced_index().min(prev_log_index),
+                };
+            }
+        }
+        let mut state = self.state.lock();
+        self.commit_to(&mut state, leader_commit.min(last_new_index));
+        AppendEntriesResponse {
+            term,
+            success: true,
+            match_index: last_new_index,
+        }
+    }
+
+    /// Checks the entries against the log and writes the new ones, returning the last index of
+    /// the request, the leader's commit index and the sync the entries still need.
+    fn append_entries(
+        &self,
+        request: AppendEntriesRequest,
+    ) -> Result<(i64, i64, Option<PendingSync>), AppendEntriesResponse> {
+        let mut state = self.state.lock();
+        let current_term = state.log.hard_state().current_term;
+        if request.group != self.group || request.term < current_term {
+            return Err(AppendEntriesResponse {
+                term: current_term,
+                success: false,
+                match_index: state.log.last_index(),
+            });
+        }
+        if request.term > current_term || state.role != RaftRole::Follower {
+            self.become_follower(&mut state, request.term, Some(request.leader_id.clone()));
+        }
+        if state.log.hard_state().current_term != request.term {
+            // The term of the leader could not be persisted.
+            return Err(AppendEntriesResponse {
+                term: current_term,
+                success: false,
+                match_index: state.log.last_index(),
+            });
+        }
+        state.leader_id = Some(request.leader_id.clone());
+        *self.last_leader_contact.lock() = Instant::now();
+
+        if state.log.term_at(request.prev_log_index) != Some(request.prev_log_term) {
+            let match_index = (request.prev_log_index - 1).min(state.log.last_index());
+            return Err(AppendEntriesResponse {
+                term: request.term,
+                success: false,
+                match_index: match_index.max(0),
+            });
+        }
+        let last_new_index = request.prev_log_index + request.entries.len() as i64;
+        let new_entries: Vec<LogEntry> = request
+            .entries
+            .into_iter()
+            .skip_while(|entry| state.log.term_at(entry.index) == Some(entry.term))
+            .collect();
+        if let Some(first) = new_entries.first() {
+            let mut result = Ok(());
+            if first.index <= state.log.last_index() {
+                if first.index <= state.commit_index {
+                    error!(
+                        "the leader {} conflicts with the committed raft entry {}",
+                        request.leader_id, first.index
+                    );
+                }
+                warn!(
+                    "truncate the raft log from {} to follow the leader {}",
+                    first.index, request.leader_id
+                );
+                result = state.log.truncate_from(first.index);
+            }
+            if let Err(e) = result.and_then(|_| state.log.append(new_entries)) {
+                error!("append raft entries failed: {}", e);
+                return Err(AppendEntriesResponse {
+                    term: request.term,
+                    success: false,
+                    match_index: state.log.last_index().min(request.prev_log_index),
+                });
+            }
+        }
+        // Entries matching the request may still wait for the sync of an earlier append.
+        let pending_sync =
+            (state.log.synced_index() < last_new_index).then(|| state.log.pending_sync());
+        Ok((last_new_index, request.leader_commit, pending_sync))
+    }
+
+    async fn run_election(self: Arc<Self>) {
+        if self.peers.len() <= 1 {
+            self.clone().elect().await;
+        }
+        let mut election_timeout = random_election_timeout();
+        loop {
+            tokio::time::sleep(ELECTION_CHECK_INTERVAL).await;
+            if self.is_leader() || self.last_leader_contact.lock().elapsed() < election_timeout {
+                continue;
+            }
+            self.clone().elect().await;
+            *self.last_leader_contact.lock() = Instant::now();
+            election_timeout = random_election_timeout();
+        }
+    }
+
+    async fn elect(self: Arc<Self>) {
+        let request = {
+            let mut state = self.state.lock();
+            let term = state.log.hard_state().current_term + 1;
+            if let Err(e) = state
+                .log
+                .set_term_and_vote(term, Some(self.self_id.clone()))
+            {
+                error!("persist the vote of term {} failed: {}", term, e);
+                return;
+            }
+            state.role = RaftRole::Candidate;
+            state.leader_id = None;
+            VoteRequest {
+                group: self.group.clone(),
+                term,
+                candidate_id: self.self_id.clone(),
+                last_log_index: state.log.last_index(),
+                last_log_term: state.log.last_term(),
+            }
+        };
+        let term = request.term;
+        info!(
+            "controller {} starts the election of term {}",
+            self.self_id, term
+        );
+
+        let mut calls = JoinSet::new();
+        for peer_id in self.connections.keys() {
+            let node = self.clone();
+            let peer_id = peer_id.clone();
+            let request = RaftRequest::Vote(request.clone());
+            calls.spawn(async move { node.call(&peer_id, request).await });
+        }
+        let quorum = self.quorum();
+        let mut votes = 1;
+        while votes < quorum {
+            let Some(result) = calls.join_next().await else {
+                break;
+            };
+            if let Ok(Some(RaftResponse::Vote(response))) = result {
+                if response.term > term {
+                    self.step_down(response.term);
+                    return;
+                }
+                if response.vote_granted {
+                    votes += 1;
+                }
+            }
+        }
+        if votes < quorum {
+            return;
+        }
+
+        let pending_sync = {
+            let mut state = self.state.lock();
+            if state.role != RaftRole::Candidate || state.log.hard_state().current_term != term {
+                return;
+            }
+            // Entries of older terms only commit together with one of the current term.
+            let index = state.log.last_index() + 1;
+            let pending_sync = match state.log.append(vec![LogEntry {
+                index,
+                term,
+                events: Vec::new(),
+            }]) {
+                Ok(pending_sync) => pending_sync,
+                Err(e) => {
+                    error!("append the raft entry of the new term failed: {}", e);
+                    state.role = RaftRole::Follower;
+                    return;
+                }
+            };
+            state.role = RaftRole::Leader;
+            state.leader_id = Some(self.self_id.clone());
+            state.match_index.clear();
+            pending_sync
+        };
+        info!(
+            "controller {} becomes the leader of term {}",
+            self.self_id, term
+        );
+        {
+            let mut tasks = self.tasks.lock();
+            tasks.retain(|task| !task.is_finished());
+            for peer_id in self.connections.keys() {
+                tasks.push(tokio::spawn(self.clone().replicate(peer_id.clone(), term)));
+            }
+        }
+        match self.sync_log(pending_sync).await {
+            Ok(()) => self.advance_commit_index(term),
+            Err(e) => error!("sync the raft entry of the new term failed: {}", e),
+        }
+    }
+
+    /// Sends the leader's log to one follower for as long as this controller leads `term`.
+    async fn replicate(self: Arc<Self>, peer_id: String, term: i64) {
+        let mut next_index = self.state.lock().log.last_index();
+        loop {
+            let notified = self.append_notify.notified();
+            let request = {
+                let state = self.state.lock();
+                if state.role != RaftRole::Leader || state.log.hard_state().current_term != term {
+                    return;
+                }
+                let prev_log_index = next_index - 1;
+                AppendEntriesRequest {
+                    group: self.group.clone(),
+                    term,
+                    leader_id: self.self_id.clone(),
+                    prev_log_index,
+                    prev_log_term: state.log.term_at(prev_log_index).unwrap_or_default(),
+                    entries: state.log.entries_from(next_index, MAX_ENTRIES_PER_APPEND),
+                    leader_commit: state.commit_index,
+                }
+            };
+            let sent = request.entries.len();
+            let prev_log_index = request.prev_log_index;
+            if let Some(RaftResponse::AppendEntries(response)) = self
+                .call(&peer_id, RaftRequest::AppendEntries(request))
+                .await
+            {
+                if response.term > term {
+                    self.step_down(response.term);
+                    return;
+                }
+                if response.success {
+                    next_index = response.match_index + 1;
+                    self.state
+                        .lock()
+                        .match_index
+                        .insert(peer_id.clone(), response.match_index);
+                    self.advance_commit_index(term);
+                    if sent > 0 {
+                        continue;
+                    }
+                } else if response.match_index < prev_log_index {
+                    next_index = response.match_index + 1;
+                    continue;
+                }
+            }
+            let _ = tokio::time::timeout(HEARTBEAT_INTERVAL, notified).await;
+        }
+    }
+
+    /// Commits the highest index stored on a majority. Entries of older terms only get
+    /// committed together with one of the current term.
+    fn advance_commit_index(&self, term: i64) {
+        let mut state = self.state.lock();
+        if state.role != RaftRole::Leader || state.log.hard_state().current_term != term {
+            return;
+        }
+        let mut indexes = vec![state.log.synced_index()];
+        indexes.extend(
+            self.connections
+                .keys()
+                .map(|peer_id| state.match_index.get(peer_id).copied().unwrap_or(0)),
+        );
+        indexes.sort_unstable_by(|a, b| b.cmp(a));
+        let majority_index = indexes[self.quorum() - 1];
+        if state.log.term_at(majority_index) != Some(term) {
+            return;
+        }
+        if self.commit_to(&mut state, majority_index) {
+            self.append_notify.notify_waiters();
+        }
+    }
+
+    /// Commits and applies the entries up to `commit_index`.
+    fn commit_to(&self, state: &mut RaftState, commit_index: i64) -> bool {
+        if commit_index <= state.commit_index {
+            return false;
+        }
+        state.commit_index = commit_index;
+        state.log.set_commit_index(commit_index);
+        while state.last_applied < state.commit_index {
+            state.last_applied += 1;
+            if let Some(entry) = state.log.entry(state.last_applied) {
+                (self.applier)(&entry.events);
+            }
+        }
+        self.apply_notify.notify_waiters();
+        true
+    }
+
+    fn step_down(&self, term: i64) {
+        let mut state = self.state.lock();
+        if term > state.log.hard_state().current_term {
+            self.become_follower(&mut state, term, None);
+        }
+    }
+
+    fn become_follower(&self, state: &mut RaftState, term: i64, leader_id: Option<String>) {
+        if term > state.log.hard_state().current_term {
+            // The term stays behind and is adopted again with the next request carrying it.
+            if let Err(e) = state.log.set_term_and_vote(term, None) {
+                error!("persist raft term {} failed: {}", term, e);
+            }
+        }
+        if state.role != RaftRole::Follower {
+            info!(
+                "controller {} becomes follower in term {}, was {}",
+                self.self_id, term, state.role
+            );
+        }
+        state.role = RaftRole::Follower;
+        state.leader_id = leader_id;
+        // Proposals waiting on the old term give up.
+        self.apply_notify.notify_waiters();
+    }
+
+    /// Syncs appended entries on the blocking pool, the state lock stays free meanwhile.
+    async fn sync_log(&self, pending_sync: PendingSync) -> std::io::Result<()> {
+        let pending_sync = tokio::task::spawn_blocking(move || {
+            pending_sync.sync()?;
+            Ok::<_, std::io::Error>(pending_sync)
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+        self.state.lock().log.mark_synced(&pending_sync);
+        Ok(())
+    }
+
+    fn quorum(&self) -> usize {
+        self.peers.len().max(1) / 2 + 1
+    }
+
+    fn peer_address(&self, peer_id: &str) -> Option<&str> {
+        self.peers
+            .iter()
+            .find(|(id, _)| id == peer_id)
+            .map(|(_, address)| address.as_str())
+    }
+
+    async fn call(&self, peer_id: &str, request: RaftRequest) -> Option<RaftResponse> {
+        let address = self.peer_address(peer_id)?;
+        let mut connection = self.connections.get(peer_id)?.lock().await;
+        if connection.is_none() {
+            match tokio::time::timeout(RPC_TIMEOUT, TcpStream::connect(address)).await {
+                Ok(Ok(stream)) => {
+                    let _ = stream.set_nodelay(true);
+                    *connection = Some(stream);
+                }
+                _ => return None,
+            }
+        }
+        let stream = connection.as_mut()?;
+        let result = tokio::time::timeout(RPC_TIMEOUT, async {
+            request.write_to(stream).await?;
+            RaftResponse::read_from(stream).await
+        })
+        .await;
+        match result {
+            Ok(Ok(response)) => Some(response),
+            _ => {
+                *connection = None;
+                None
+            }
+        }
+    }
+}
+
+/// Parses peers in the `n0-127.0.0.1:9878;n1-127.0.0.1:9868` format into `(id, address)`
+/// pairs.
+pub fn parse_peers(peers: &str) -> Vec<(String, String)> {
+    peers
+        .split(';')
+        .filter_map(|peer| {
+            let (id, address) = peer.trim().split_once('-')?;
+            Some((id.trim().to_string(), address.trim().to_string()))
+        })
+        .collect()
+}
+
+fn random_election_timeout() -> Duration {
+    Duration::from_millis(
+        ELECTION_TIMEOUT_MILLIS + rand::thread_rng().gen_range(0..ELECTION_TIMEOUT_MILLIS),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+    use crate::manager::event::ElectMasterEvent;
+
+    type Applied = Arc<parking_lot::Mutex<Vec<EventMessage>>>;
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn event(broker_id: i64) -> EventMessage {
+        EventMessage::ElectMaster(ElectMasterEvent {
+            broker_name: CheetahString::from_static_str("broker-a"),
+            new_master_broker_id: Some(broker_id),
+        })
+    }
+
+    fn start_node(dir: &tempfile::TempDir, self_id: &str, peers: &str) -> (Arc<RaftNode>, Applied) {
+        let controller_config = ControllerConfig {
+            controller_dledger_peers: peers.to_string(),
+            controller_dledger_self_id: self_id.to_string(),
+            controller_store_path: dir.path().join(self_id).to_string_lossy().into_owned(),
+            ..ControllerConfig::default()
+        };
+        let applied = Applied::default();
+        let state_machine = applied.clone();
+        let node = Arc::new(
+            RaftNode::new(
+                &controller_config,
+                Box::new(move |events| state_machine.lock().extend_from_slice(events)),
+            )
+            .unwrap(),
+        );
+        node.start().unwrap();
+        (node, applied)
+    }
+
+    async fn wait_for_leader(nodes: &[&Arc<RaftNode>]) -> Arc<RaftNode> {
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while Instant::now() < deadline {
+            if let Some(leader) = nodes.iter().find(|node| node.is_leader()) {
+                return Arc::clone(leader);
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("no controller got elected");
+    }
+
+    async fn wait_for_applied(applied: &Applied, expected: &[EventMessage]) {
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while applied.lock().as_slice() != expected {
+            assert!(
+                Instant::now() < deadline,
+                "applied {:?}, expected {:?}",
+                applied.lock(),
+                expected
+            );
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    #[test]
+    fn vote_is_only_granted_once_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let store_path = dir.path().join("n1");
+        let controller_config = ControllerConfig {
+            controller_dledger_peers: "n0-127.0.0.1:1;n1-127.0.0.1:2".to_string(),
+            controller_dledger_self_id: "n1".to_string(),
+            controller_store_path: store_path.to_string_lossy().into_owned(),
+            ..ControllerConfig::default()
+        };
+        let node = RaftNode::new(&controller_config, Box::new(|_| {})).unwrap();
+        let request = VoteRequest {
+            group: node.group.clone(),
+            term: 1,
+            candidate_id: "n0".to_string(),
+            last_log_index: 0,
+            last_log_term: 0,
+        };
+
+        std::fs::remove_dir_all(&store_path).unwrap();
+        let response = node.handle_vote(&request);
+        assert!(!response.vote_granted);
+        assert_eq!(node.current_term(), 0);
+
+        std::fs::create_dir_all(&store_path).unwrap();
+        let response = node.handle_vote(&request);
+        assert!(response.vote_granted);
+        assert_eq!(response.term, 1);
+        drop(node);
+        let log = RaftLog::open(store_path.to_str().unwrap()).unwrap();
+        assert_eq!(log.hard_state().voted_for.as_deref(), Some("n0"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn standalone_controller_leads_and_replays_its_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let (node, applied) = start_node(&dir, "n0", "");
+        let leader = wait_for_leader(&[&node]).await;
+        leader.propose(vec![event(1)]).await.unwrap();
+        leader.propose(vec![event(2)]).await.unwrap();
+        assert_eq!(applied.lock().as_slice(), &[event(1), event(2)]);
+        node.shutdown();
+
+        let (node, applied) = start_node(&dir, "n0", "");
+        assert_eq!(applied.lock().as_slice(), &[event(1), event(2)]);
+        assert!(node.current_term() >= 1);
+        node.shutdown();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn committed_events_survive_a_leader_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let peers = format!(
+            "n0-127.0.0.1:{};n1-127.0.0.1:{};n2-127.0.0.1:{}",
+            free_port(),
+            free_port(),
+            free_port()
+        );
+        let mut nodes: Vec<(Arc<RaftNode>, Applied)> = ["n0", "n1", "n2"]
+            .iter()
+            .map(|self_id| start_node(&dir, self_id, peers.as_str()))
+            .collect();
+
+        let leader = wait_for_leader(&nodes.iter().map(|(node, _)| node).collect::<Vec<_>>()).await;
+        leader.propose(vec![event(1)]).await.unwrap();
+        for (_, applied) in &nodes {
+            wait_for_applied(applied, &[event(1)]).await;
+        }
+        let follower = nodes.iter().find(|(node, _)| !node.is_leader()).unwrap();
+        assert!(matches!(
+            follower.0.propose(vec![event(9)]).await,
+            Err(ControllerError::NotLeader(_))
+        ));
+
+        leader.shutdown();
+        let old_leader_id = leader.self_id().to_string();
+        let survivors: Vec<&Arc<RaftNode>> = nodes
+            .iter()
+            .map(|(node, _)| node)
+            .filter(|node| node.self_id() != old_leader_id)
+            .collect();
+        let new_leader = wait_for_leader(&survivors).await;
+        assert_ne!(new_leader.self_id(), old_leader_id);
+        new_leader.propose(vec![event(2)]).await.unwrap();
+
+        let position = nodes
+            .iter()
+            .position(|(node, _)| node.self_id() == old_leader_id)
+            .unwrap();
+        nodes[position] = start_node(&dir, old_leader_id.as_str(), peers.as_str());
+        for (_, applied) in &nodes {
+            wait_for_applied(applied, &[event(1), event(2)]).await;
+        }
+        for (node, _) in &nodes {
+            node.shutdown();
+        }
+    }
+}
@@ -0,0 +1,171 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+
+use crate::raft::raft_log::LogEntry;
+
+/// Frames larger than this are rejected instead of allocated.
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteRequest {
+    pub group: String,
+    pub term: i64,
+    pub candidate_id: String,
+    pub last_log_index: i64,
+    pub last_log_term: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteResponse {
+    pub term: i64,
+    pub vote_granted: bool,
+}
+
+/// Replicates the entries following `prev_log_index`, no entries is a heartbeat.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppendEntriesRequest {
+    pub group: String,
+    pub term: i64,
+    pub leader_id: String,
+    pub prev_log_index: i64,
+    pub prev_log_term: i64,
+    pub entries: Vec<LogEntry>,
+    pub leader_commit: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppendEntriesResponse {
+    pub term: i64,
+    pub success: bool,
+    /// Last index known to match the leader's log, the leader resends from the next one.
+    pub match_index: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RaftRequest {
+    Vote(VoteRequest),
+    AppendEntries(AppendEntriesRequest),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RaftResponse {
+    Vote(VoteResponse),
+    AppendEntries(AppendEntriesResponse),
+}
+
+impl RaftRequest {
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_frame(writer, self).await
+    }
+
+    pub async fn read_from<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Self> {
+        read_frame(reader).await
+    }
+}
+
+impl RaftResponse {
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_frame(writer, self).await
+    }
+
+    pub async fn read_from<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Self> {
+        read_frame(reader).await
+    }
+}
+
+/// A frame is the length of the JSON body (4 bytes) followed by the body.
+async fn write_frame<W: AsyncWrite + Unpin, T: Serialize>(
+    writer: &mut W,
+    value: &T,
+) -> std::io::Result<()> {
+    let body = serde_json::to_vec(value)?;
+    let mut frame = Vec::with_capacity(4 + body.len());
+    frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&body);
+    writer.write_all(&frame).await?;
+    writer.flush().await
+}
+
+async fn read_frame<R: AsyncRead + Unpin, T: DeserializeOwned>(
+    reader: &mut R,
+) -> std::io::Result<T> {
+    let size = reader.read_u32().await? as usize;
+    if size > MAX_FRAME_SIZE {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("raft frame of {} bytes is too large", size),
+        ));
+    }
+    let mut body = vec![0u8; size];
+    reader.read_exact(&mut body).await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+    use crate::manager::event::ElectMasterEvent;
+    use crate::manager::event::EventMessage;
+
+    #[tokio::test]
+    async fn append_entries_round_trip_keeps_events() {
+        let request = RaftRequest::AppendEntries(AppendEntriesRequest {
+            group: "group".to_string(),
+            term: 3,
+            leader_id: "n1".to_string(),
+            prev_log_index: 4,
+            prev_log_term: 2,
+            entries: vec![LogEntry {
+                index: 5,
+                term: 3,
+                events: vec![EventMessage::ElectMaster(ElectMasterEvent {
+                    broker_name: CheetahString::from_static_str("broker-a"),
+                    new_master_broker_id: Some(1),
+                })],
+            }],
+            leader_commit: 4,
+        });
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        request.write_to(&mut client).await.unwrap();
+        assert_eq!(RaftRequest::read_from(&mut server).await.unwrap(), request);
+
+        let response = RaftResponse::AppendEntries(AppendEntriesResponse {
+            term: 3,
+            success: true,
+            match_index: 5,
+        });
+        response.write_to(&mut server).await.unwrap();
+        assert_eq!(
+            RaftResponse::read_from(&mut client).await.unwrap(),
+            response
+        );
+    }
+}
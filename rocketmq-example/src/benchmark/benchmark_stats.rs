@@ -0,0 +1,138 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fmt;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::benchmark::latency_histogram::LatencyHistogram;
+use crate::benchmark::latency_histogram::LatencySnapshot;
+
+/// Counters shared by the tasks of a benchmark run.
+#[derive(Default)]
+pub struct BenchmarkStats {
+    success: AtomicU64,
+    failed: AtomicU64,
+    total_success: AtomicU64,
+    total_failed: AtomicU64,
+    latency: LatencyHistogram,
+}
+
+/// What happened during one reporting window.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BenchmarkReport {
+    pub tps: f64,
+    pub failed: u64,
+    pub latency: LatencySnapshot,
+}
+
+impl BenchmarkStats {
+    pub fn record_success(&self, latency_millis: u64) {
+        self.success.fetch_add(1, Ordering::Relaxed);
+        self.total_success.fetch_add(1, Ordering::Relaxed);
+        self.latency.record(latency_millis);
+    }
+
+    pub fn record_failure(&self) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+        self.total_failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Successes and failures since the run started.
+    pub fn totals(&self) -> (u64, u64) {
+        (
+            self.total_success.load(Ordering::Relaxed),
+            self.total_failed.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Figures of the window that lasted `elapsed`, counters restart from zero afterwards.
+    pub fn report(&self, elapsed: Duration) -> BenchmarkReport {
+        let success = self.success.swap(0, Ordering::Relaxed);
+        let failed = self.failed.swap(0, Ordering::Relaxed);
+        let secs = elapsed.as_secs_f64();
+        BenchmarkReport {
+            tps: if secs > 0.0 {
+                success as f64 / secs
+            } else {
+                0.0
+            },
+            failed,
+            latency: self.latency.snapshot_and_reset(),
+        }
+    }
+
+    /// Prints a report line prefixed with `label` every `interval` until the process exits.
+    pub fn spawn_reporter(
+        self: &Arc<Self>,
+        label: &'static str,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let stats = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            let mut last = tokio::time::Instant::now();
+            loop {
+                ticker.tick().await;
+                let now = tokio::time::Instant::now();
+                println!("{} {}", label, stats.report(now - last));
+                last = now;
+            }
+        })
+    }
+}
+
+impl fmt::Display for BenchmarkReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TPS: {:.1} | Failed: {} | Avg RT(ms): {:.3} | Max RT(ms): {} | P50(ms): {} | \
+             P99(ms): {} | P999(ms): {}",
+            self.tps,
+            self.failed,
+            self.latency.avg,
+            self.latency.max,
+            self.latency.p50,
+            self.latency.p99,
+            self.latency.p999
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_counts_the_window_and_resets() {
+        let stats = BenchmarkStats::default();
+        for _ in 0..20 {
+            stats.record_success(2);
+        }
+        stats.record_failure();
+        let report = stats.report(Duration::from_secs(2));
+        assert_eq!(report.tps, 10.0);
+        assert_eq!(report.failed, 1);
+        assert_eq!(report.latency.p99, 2);
+
+        let report = stats.report(Duration::from_secs(2));
+        assert_eq!(report, BenchmarkReport::default());
+        assert_eq!(stats.totals(), (20, 1));
+    }
+}
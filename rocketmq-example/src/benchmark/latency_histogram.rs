@@ -0,0 +1,133 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+/// Latencies above this many milliseconds all land in the last bucket.
+pub const MAX_TRACKED_LATENCY_MILLIS: usize = 10_000;
+
+/// Lock free histogram of latencies with one bucket per millisecond, cheap enough to be
+/// recorded from every sending or consuming task.
+pub struct LatencyHistogram {
+    buckets: Box<[AtomicU64]>,
+    max: AtomicU64,
+}
+
+/// Latency figures of one reporting window, in milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LatencySnapshot {
+    pub count: u64,
+    pub avg: f64,
+    pub max: u64,
+    pub p50: u64,
+    pub p99: u64,
+    pub p999: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            buckets: (0..=MAX_TRACKED_LATENCY_MILLIS)
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, latency_millis: u64) {
+        let bucket = (latency_millis as usize).min(MAX_TRACKED_LATENCY_MILLIS);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.max.fetch_max(latency_millis, Ordering::Relaxed);
+    }
+
+    /// Returns the figures recorded since the previous call and starts a new window.
+    pub fn snapshot_and_reset(&self) -> LatencySnapshot {
+        let counts = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.swap(0, Ordering::Relaxed))
+            .collect::<Vec<_>>();
+        let max = self.max.swap(0, Ordering::Relaxed);
+        let count = counts.iter().sum::<u64>();
+        if count == 0 {
+            return LatencySnapshot::default();
+        }
+        let total = counts
+            .iter()
+            .enumerate()
+            .map(|(latency, num)| latency as u64 * num)
+            .sum::<u64>();
+        LatencySnapshot {
+            count,
+            avg: total as f64 / count as f64,
+            max,
+            p50: percentile(&counts, count, 0.5),
+            p99: percentile(&counts, count, 0.99),
+            p999: percentile(&counts, count, 0.999),
+        }
+    }
+}
+
+/// The smallest latency at or below which `quantile` of the `count` samples fall.
+fn percentile(counts: &[u64], count: u64, quantile: f64) -> u64 {
+    let rank = ((count as f64 * quantile).ceil() as u64).max(1);
+    let mut seen = 0;
+    for (latency, num) in counts.iter().enumerate() {
+        seen += num;
+        if seen >= rank {
+            return latency as u64;
+        }
+    }
+    (counts.len() - 1) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_reports_percentiles_and_resets() {
+        let histogram = LatencyHistogram::new();
+        for latency in 1..=1000 {
+            histogram.record(latency);
+        }
+        let snapshot = histogram.snapshot_and_reset();
+        assert_eq!(snapshot.count, 1000);
+        assert_eq!(snapshot.max, 1000);
+        assert_eq!(snapshot.p50, 500);
+        assert_eq!(snapshot.p99, 990);
+        assert_eq!(snapshot.p999, 999);
+        assert!((snapshot.avg - 500.5).abs() < f64::EPSILON);
+
+        assert_eq!(histogram.snapshot_and_reset(), LatencySnapshot::default());
+    }
+
+    #[test]
+    fn slow_samples_share_the_last_bucket() {
+        let histogram = LatencyHistogram::new();
+        histogram.record(MAX_TRACKED_LATENCY_MILLIS as u64 * 3);
+        let snapshot = histogram.snapshot_and_reset();
+        assert_eq!(snapshot.max, MAX_TRACKED_LATENCY_MILLIS as u64 * 3);
+        assert_eq!(snapshot.p999, MAX_TRACKED_LATENCY_MILLIS as u64);
+    }
+}
@@ -0,0 +1,103 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Parser;
+use rocketmq_client_rust::consumer::default_mq_push_consumer::DefaultMQPushConsumer;
+use rocketmq_client_rust::consumer::listener::consume_concurrently_context::ConsumeConcurrentlyContext;
+use rocketmq_client_rust::consumer::listener::consume_concurrently_status::ConsumeConcurrentlyStatus;
+use rocketmq_client_rust::consumer::listener::message_listener_concurrently::MessageListenerConcurrently;
+use rocketmq_client_rust::consumer::mq_push_consumer::MQPushConsumer;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_example::benchmark::benchmark_stats::BenchmarkStats;
+use rocketmq_rust::rocketmq;
+
+/// Consumes a topic and reports TPS and the born-to-consume RT percentiles.
+#[derive(Parser, Debug)]
+#[command(name = "benchmark-consumer")]
+struct Args {
+    /// Name server address
+    #[arg(short = 'n', long, default_value = "127.0.0.1:9876")]
+    namesrv_addr: String,
+
+    /// Topic to consume
+    #[arg(short = 't', long, default_value = "BenchmarkTest")]
+    topic: String,
+
+    /// Consumer group
+    #[arg(short = 'g', long, default_value = "benchmark_consumer")]
+    group: String,
+
+    /// Subscription expression
+    #[arg(short = 'e', long, default_value = "*")]
+    expression: String,
+
+    /// Number of consuming threads
+    #[arg(short = 'w', long, default_value_t = 20)]
+    thread_count: u32,
+
+    /// Seconds between two report lines
+    #[arg(short = 'i', long, default_value_t = 10)]
+    report_interval_secs: u64,
+}
+
+#[rocketmq::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let stats = Arc::new(BenchmarkStats::default());
+    let mut consumer = DefaultMQPushConsumer::builder()
+        .consumer_group(args.group.clone())
+        .name_server_addr(args.namesrv_addr.clone())
+        .consume_thread_min(args.thread_count)
+        .consume_thread_max(args.thread_count)
+        .build();
+    consumer.subscribe(args.topic.as_str(), args.expression.as_str())?;
+    consumer.register_message_listener_concurrently(BenchmarkListener {
+        stats: stats.clone(),
+    });
+    consumer.start().await?;
+
+    let reporter = stats.spawn_reporter(
+        "[benchmark-consumer]",
+        Duration::from_secs(args.report_interval_secs.max(1)),
+    );
+    let _ = tokio::signal::ctrl_c().await;
+    reporter.abort();
+    consumer.shutdown().await;
+    Ok(())
+}
+
+struct BenchmarkListener {
+    stats: Arc<BenchmarkStats>,
+}
+
+impl MessageListenerConcurrently for BenchmarkListener {
+    fn consume_message(
+        &self,
+        msgs: &[&MessageExt],
+        _context: &ConsumeConcurrentlyContext,
+    ) -> rocketmq_client_rust::Result<ConsumeConcurrentlyStatus> {
+        let now = get_current_millis() as i64;
+        for msg in msgs {
+            self.stats
+                .record_success((now - msg.born_timestamp()).max(0) as u64);
+        }
+        Ok(ConsumeConcurrentlyStatus::ConsumeSuccess)
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use bytes::Bytes;
+use clap::Parser;
+use rocketmq_client_rust::producer::default_mq_producer::DefaultMQProducer;
+use rocketmq_client_rust::producer::mq_producer::MQProducer;
+use rocketmq_client_rust::producer::send_status::SendStatus;
+use rocketmq_common::common::message::message_single::Message;
+use rocketmq_example::benchmark::benchmark_stats::BenchmarkStats;
+use rocketmq_rust::rocketmq;
+
+/// Sends fixed size messages from many concurrent tasks and reports TPS and RT percentiles.
+#[derive(Parser, Debug)]
+#[command(name = "benchmark-producer")]
+struct Args {
+    /// Name server address
+    #[arg(short = 'n', long, default_value = "127.0.0.1:9876")]
+    namesrv_addr: String,
+
+    /// Topic to send to
+    #[arg(short = 't', long, default_value = "BenchmarkTest")]
+    topic: String,
+
+    /// Producer group
+    #[arg(short = 'g', long, default_value = "benchmark_producer")]
+    group: String,
+
+    /// Number of concurrent sending tasks
+    #[arg(short = 'w', long, default_value_t = 64)]
+    task_count: usize,
+
+    /// Body size of every message in bytes
+    #[arg(short = 's', long, default_value_t = 128)]
+    message_size: usize,
+
+    /// Messages to send in total, 0 keeps sending until interrupted
+    #[arg(short = 'm', long, default_value_t = 0)]
+    message_num: u64,
+
+    /// Timeout of a single send in milliseconds
+    #[arg(long, default_value_t = 3000)]
+    send_timeout_millis: u64,
+
+    /// Seconds between two report lines
+    #[arg(short = 'i', long, default_value_t = 10)]
+    report_interval_secs: u64,
+}
+
+#[rocketmq::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let mut producer = DefaultMQProducer::builder()
+        .producer_group(args.group.clone())
+        .name_server_addr(args.namesrv_addr.clone())
+        .build();
+    producer.start().await?;
+
+    let stats = Arc::new(BenchmarkStats::default());
+    let reporter = stats.spawn_reporter(
+        "[benchmark-producer]",
+        Duration::from_secs(args.report_interval_secs.max(1)),
+    );
+    let started = Instant::now();
+    let body = Bytes::from(vec![b'a'; args.message_size]);
+    let sent = Arc::new(AtomicU64::new(0));
+    let mut tasks = Vec::with_capacity(args.task_count);
+    for _ in 0..args.task_count {
+        let mut producer = producer.clone();
+        let stats = stats.clone();
+        let sent = sent.clone();
+        let body = body.clone();
+        let topic = args.topic.clone();
+        let message_num = args.message_num;
+        let send_timeout_millis = args.send_timeout_millis;
+        tasks.push(tokio::spawn(async move {
+            while message_num == 0 || sent.fetch_add(1, Ordering::Relaxed) < message_num {
+                let message = Message::new_body(topic.as_str(), Some(body.clone()));
+                let begin = Instant::now();
+                match producer
+                    .send_with_timeout(message, send_timeout_millis)
+                    .await
+                {
+                    Ok(result) if result.send_status == SendStatus::SendOk => {
+                        stats.record_success(begin.elapsed().as_millis() as u64)
+                    }
+                    _ => stats.record_failure(),
+                }
+            }
+        }));
+    }
+
+    if args.message_num == 0 {
+        let _ = tokio::signal::ctrl_c().await;
+    } else {
+        for task in tasks {
+            let _ = task.await;
+        }
+    }
+    reporter.abort();
+    let elapsed = started.elapsed().as_secs_f64();
+    let (success, failed) = stats.totals();
+    println!(
+        "[benchmark-producer] finished in {:.3}s | Sent: {} | Failed: {} | Average TPS: {:.1}",
+        elapsed,
+        success,
+        failed,
+        success as f64 / elapsed
+    );
+    producer.shutdown().await;
+    Ok(())
+}
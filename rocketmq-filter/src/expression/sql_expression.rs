@@ -0,0 +1,241 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::any::Any;
+use std::cmp::Ordering;
+use std::error::Error;
+
+use cheetah_string::CheetahString;
+
+use crate::expression::evaluation_context::EvaluationContext;
+use crate::expression::Expression;
+
+/// A literal, or the value of a message property, while evaluating a SQL92 expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Long(i64),
+    Double(f64),
+    String(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonOperator {
+    Equal,
+    NotEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringOperator {
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+/// Syntax tree of a SQL92 filter expression, built by
+/// [`SelectorParser`](crate::parser::selector_parser::SelectorParser).
+///
+/// Evaluation follows SQL's three-valued logic: anything involving a missing property is
+/// unknown, and a message only matches when the whole expression is `TRUE`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlExpression {
+    Constant(Value),
+    Property(String),
+    Not(Box<SqlExpression>),
+    And(Box<SqlExpression>, Box<SqlExpression>),
+    Or(Box<SqlExpression>, Box<SqlExpression>),
+    Comparison(ComparisonOperator, Box<SqlExpression>, Box<SqlExpression>),
+    IsNull {
+        expression: Box<SqlExpression>,
+        negated: bool,
+    },
+    In {
+        expression: Box<SqlExpression>,
+        values: Vec<String>,
+        negated: bool,
+    },
+    StringMatch {
+        operator: StringOperator,
+        expression: Box<SqlExpression>,
+        pattern: String,
+        negated: bool,
+    },
+}
+
+impl SqlExpression {
+    /// Whether the expression evaluates to a boolean, as required at the top level and for the
+    /// operands of `AND`, `OR` and `NOT`.
+    pub fn is_boolean(&self) -> bool {
+        match self {
+            SqlExpression::Constant(value) => matches!(value, Value::Bool(_)),
+            SqlExpression::Property(_) => false,
+            _ => true,
+        }
+    }
+
+    pub fn evaluate_value(&self, context: &dyn EvaluationContext) -> Value {
+        match self {
+            SqlExpression::Constant(value) => value.clone(),
+            SqlExpression::Property(name) => property_value(context, name),
+            SqlExpression::Not(expression) => match expression.evaluate_value(context) {
+                Value::Bool(value) => Value::Bool(!value),
+                _ => Value::Null,
+            },
+            SqlExpression::And(left, right) => {
+                let left = left.evaluate_value(context);
+                if left == Value::Bool(false) {
+                    return left;
+                }
+                match (left, right.evaluate_value(context)) {
+                    (_, Value::Bool(false)) => Value::Bool(false),
+                    (Value::Bool(true), Value::Bool(true)) => Value::Bool(true),
+                    _ => Value::Null,
+                }
+            }
+            SqlExpression::Or(left, right) => {
+                let left = left.evaluate_value(context);
+                if left == Value::Bool(true) {
+                    return left;
+                }
+                match (left, right.evaluate_value(context)) {
+                    (_, Value::Bool(true)) => Value::Bool(true),
+                    (Value::Bool(false), Value::Bool(false)) => Value::Bool(false),
+                    _ => Value::Null,
+                }
+            }
+            SqlExpression::Comparison(operator, left, right) => {
+                let left = left.evaluate_value(context);
+                let right = right.evaluate_value(context);
+                if left == Value::Null || right == Value::Null {
+                    return Value::Null;
+                }
+                // values which can not be compared make the comparison unknown, whatever the
+                // operator, like a comparison to NULL
+                let Some(ordering) = compare(&left, &right) else {
+                    return Value::Null;
+                };
+                let matched = match operator {
+                    ComparisonOperator::Equal => ordering == Ordering::Equal,
+                    ComparisonOperator::NotEqual => ordering != Ordering::Equal,
+                    ComparisonOperator::GreaterThan => ordering == Ordering::Greater,
+                    ComparisonOperator::GreaterThanOrEqual => ordering != Ordering::Less,
+                    ComparisonOperator::LessThan => ordering == Ordering::Less,
+                    ComparisonOperator::LessThanOrEqual => ordering != Ordering::Greater,
+                };
+                Value::Bool(matched)
+            }
+            SqlExpression::IsNull {
+                expression,
+                negated,
+            } => Value::Bool((expression.evaluate_value(context) == Value::Null) != *negated),
+            SqlExpression::In {
+                expression,
+                values,
+                negated,
+            } => match expression.evaluate_value(context) {
+                Value::String(value) => Value::Bool(values.contains(&value) != *negated),
+                _ => Value::Null,
+            },
+            SqlExpression::StringMatch {
+                operator,
+                expression,
+                pattern,
+                negated,
+            } => match expression.evaluate_value(context) {
+                Value::String(value) => {
+                    let matched = match operator {
+                        StringOperator::Contains => value.contains(pattern.as_str()),
+                        StringOperator::StartsWith => value.starts_with(pattern.as_str()),
+                        StringOperator::EndsWith => value.ends_with(pattern.as_str()),
+                    };
+                    Value::Bool(matched != *negated)
+                }
+                _ => Value::Null,
+            },
+        }
+    }
+}
+
+impl Expression for SqlExpression {
+    fn evaluate(&self, context: &dyn EvaluationContext) -> Result<Box<dyn Any>, Box<dyn Error>> {
+        Ok(Box::new(self.evaluate_value(context) == Value::Bool(true)))
+    }
+}
+
+/// Message properties are strings, typed values are accepted for contexts built by hand.
+fn property_value(context: &dyn EvaluationContext, name: &str) -> Value {
+    let Some(value) = context.get(name) else {
+        return Value::Null;
+    };
+    if let Some(value) = value.downcast_ref::<CheetahString>() {
+        Value::String(value.to_string())
+    } else if let Some(value) = value.downcast_ref::<String>() {
+        Value::String(value.clone())
+    } else if let Some(value) = value.downcast_ref::<i64>() {
+        Value::Long(*value)
+    } else if let Some(value) = value.downcast_ref::<i32>() {
+        Value::Long(*value as i64)
+    } else if let Some(value) = value.downcast_ref::<f64>() {
+        Value::Double(*value)
+    } else if let Some(value) = value.downcast_ref::<bool>() {
+        Value::Bool(*value)
+    } else {
+        Value::Null
+    }
+}
+
+/// Compares two non-null values, converting a string to the type of the other side the way
+/// properties compared to numeric or boolean literals are. `None` if they are not comparable.
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Long(left), Value::Long(right)) => Some(left.cmp(right)),
+        (Value::Long(_) | Value::Double(_), Value::Long(_) | Value::Double(_)) => {
+            as_double(left)?.partial_cmp(&as_double(right)?)
+        }
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        (Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+        (Value::String(value), other) => compare(&parse_as(value, other)?, other),
+        (other, Value::String(value)) => compare(other, &parse_as(value, other)?),
+        _ => None,
+    }
+}
+
+fn as_double(value: &Value) -> Option<f64> {
+    match value {
+        Value::Long(value) => Some(*value as f64),
+        Value::Double(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn parse_as(value: &str, like: &Value) -> Option<Value> {
+    let value = value.trim();
+    match like {
+        Value::Long(_) | Value::Double(_) => value
+            .parse::<i64>()
+            .map(Value::Long)
+            .or_else(|_| value.parse::<f64>().map(Value::Double))
+            .ok(),
+        Value::Bool(_) if value.eq_ignore_ascii_case("true") => Some(Value::Bool(true)),
+        Value::Bool(_) if value.eq_ignore_ascii_case("false") => Some(Value::Bool(false)),
+        _ => None,
+    }
+}
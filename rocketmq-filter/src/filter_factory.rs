@@ -0,0 +1,110 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+
+use crate::filter_spi::FilterSpi;
+use crate::sql_filter::SqlFilter;
+
+static FILTER_SPI_TABLE: Lazy<RwLock<HashMap<String, Arc<dyn FilterSpi>>>> = Lazy::new(|| {
+    let sql_filter: Arc<dyn FilterSpi> = Arc::new(SqlFilter);
+    RwLock::new(HashMap::from([(
+        sql_filter.of_type().to_string(),
+        sql_filter,
+    )]))
+});
+
+/// Registry of the filters available to the broker, keyed by expression type. [`SqlFilter`] is
+/// registered by default.
+pub struct FilterFactory;
+
+impl FilterFactory {
+    /// Registers `filter_spi`, replacing any filter registered for the same type.
+    pub fn register(filter_spi: Arc<dyn FilterSpi>) {
+        FILTER_SPI_TABLE
+            .write()
+            .insert(filter_spi.of_type().to_string(), filter_spi);
+    }
+
+    /// Removes and returns the filter registered for `type_`.
+    pub fn unregister(type_: &str) -> Option<Arc<dyn FilterSpi>> {
+        FILTER_SPI_TABLE.write().remove(type_)
+    }
+
+    pub fn get(type_: &str) -> Option<Arc<dyn FilterSpi>> {
+        FILTER_SPI_TABLE.read().get(type_).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::error::Error;
+
+    use super::*;
+    use crate::expression::evaluation_context::EvaluationContext;
+    use crate::expression::Expression;
+
+    struct AlwaysTrue;
+
+    impl Expression for AlwaysTrue {
+        fn evaluate(
+            &self,
+            _context: &dyn EvaluationContext,
+        ) -> Result<Box<dyn Any>, Box<dyn Error>> {
+            Ok(Box::new(true))
+        }
+    }
+
+    struct AlwaysTrueFilter;
+
+    impl FilterSpi for AlwaysTrueFilter {
+        fn compile(
+            &self,
+            _expr: &str,
+        ) -> Result<Box<dyn Expression + Send + Sync + 'static>, Box<dyn Error + Send + Sync>>
+        {
+            Ok(Box::new(AlwaysTrue))
+        }
+
+        fn of_type(&self) -> &str {
+            "ALWAYS_TRUE"
+        }
+    }
+
+    #[test]
+    fn sql92_is_registered_by_default() {
+        let sql_filter = FilterFactory::get(SqlFilter::TYPE).unwrap();
+        assert!(sql_filter.compile("a > 1").is_ok());
+        assert!(sql_filter.compile("a >").is_err());
+    }
+
+    #[test]
+    fn registered_filters_are_looked_up_by_type() {
+        assert!(FilterFactory::get("ALWAYS_TRUE").is_none());
+        FilterFactory::register(Arc::new(AlwaysTrueFilter));
+        assert!(FilterFactory::get("ALWAYS_TRUE")
+            .unwrap()
+            .compile("a > 1")
+            .is_ok());
+        assert!(FilterFactory::unregister("ALWAYS_TRUE").is_some());
+        assert!(FilterFactory::get("ALWAYS_TRUE").is_none());
+    }
+}
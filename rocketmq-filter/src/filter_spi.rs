@@ -0,0 +1,31 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::error::Error;
+
+use crate::expression::Expression;
+
+/// Compiles expressions of one type (e.g. `SQL92`) so they can be evaluated against messages.
+pub trait FilterSpi: Send + Sync {
+    /// Compile `expr` into an evaluable expression.
+    fn compile(
+        &self,
+        expr: &str,
+    ) -> Result<Box<dyn Expression + Send + Sync + 'static>, Box<dyn Error + Send + Sync>>;
+
+    /// The expression type this filter handles, matched case-sensitively.
+    fn of_type(&self) -> &str;
+}
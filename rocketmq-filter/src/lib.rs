@@ -16,4 +16,8 @@
  */
 
 pub mod expression;
+pub mod filter_factory;
+pub mod filter_spi;
+pub mod parser;
+pub mod sql_filter;
 pub mod utils;
@@ -0,0 +1,559 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::error::Error;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use crate::expression::sql_expression::ComparisonOperator;
+use crate::expression::sql_expression::SqlExpression;
+use crate::expression::sql_expression::StringOperator;
+use crate::expression::sql_expression::Value;
+
+/// A SQL92 expression that could not be parsed, `position` is the byte offset of the problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Long(i64),
+    Double(f64),
+    Keyword(Keyword),
+    LeftParen,
+    RightParen,
+    Comma,
+    Equal,
+    NotEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+    Plus,
+    Minus,
+    Eof,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keyword {
+    And,
+    Or,
+    Not,
+    Is,
+    Null,
+    True,
+    False,
+    In,
+    Between,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+impl Keyword {
+    fn of(word: &str) -> Option<Keyword> {
+        let keyword = match word.to_ascii_uppercase().as_str() {
+            "AND" => Keyword::And,
+            "OR" => Keyword::Or,
+            "NOT" => Keyword::Not,
+            "IS" => Keyword::Is,
+            "NULL" => Keyword::Null,
+            "TRUE" => Keyword::True,
+            "FALSE" => Keyword::False,
+            "IN" => Keyword::In,
+            "BETWEEN" => Keyword::Between,
+            "CONTAINS" => Keyword::Contains,
+            "STARTSWITH" => Keyword::StartsWith,
+            "ENDSWITH" => Keyword::EndsWith,
+            _ => return None,
+        };
+        Some(keyword)
+    }
+}
+
+/// Parses the SQL92 subset consumers subscribe with, e.g.
+/// `region = 'eu' AND (a BETWEEN 1 AND 3 OR b IS NULL)`.
+///
+/// Supported are `AND`, `OR`, `NOT`, the comparisons `=`, `<>`, `>`, `>=`, `<`, `<=`,
+/// `[NOT] BETWEEN`, `[NOT] IN` with string lists, `IS [NOT] NULL` and
+/// `[NOT] CONTAINS | STARTSWITH | ENDSWITH`. Keywords are case-insensitive, string literals are
+/// single quoted with `''` as escaped quote.
+pub struct SelectorParser {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+}
+
+impl SelectorParser {
+    pub fn parse(sql: &str) -> Result<SqlExpression, ParseError> {
+        let mut parser = SelectorParser {
+            tokens: tokenize(sql)?,
+            index: 0,
+        };
+        let expression = parser.parse_or()?;
+        if parser.peek() != &Token::Eof {
+            return Err(parser.error("unexpected token"));
+        }
+        if !expression.is_boolean() {
+            return Err(ParseError {
+                message: "expression is not a boolean expression".to_string(),
+                position: 0,
+            });
+        }
+        Ok(expression)
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.index].0.clone();
+        if token != Token::Eof {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == token {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, what: &str) -> Result<(), ParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {}", what).as_str()))
+        }
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            message: format!("{}, found {:?}", message, self.peek()),
+            position: self.tokens[self.index].1,
+        }
+    }
+
+    fn boolean(&self, expression: SqlExpression) -> Result<Box<SqlExpression>, ParseError> {
+        if expression.is_boolean() {
+            Ok(Box::new(expression))
+        } else {
+            Err(self.error("operand of AND, OR or NOT is not a boolean expression"))
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<SqlExpression, ParseError> {
+        let mut left = self.parse_and()?;
+        while self.eat(&Token::Keyword(Keyword::Or)) {
+            let right = self.parse_and()?;
+            left = SqlExpression::Or(self.boolean(left)?, self.boolean(right)?);
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<SqlExpression, ParseError> {
+        let mut left = self.parse_not()?;
+        while self.eat(&Token::Keyword(Keyword::And)) {
+            let right = self.parse_not()?;
+            left = SqlExpression::And(self.boolean(left)?, self.boolean(right)?);
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<SqlExpression, ParseError> {
+        if self.eat(&Token::Keyword(Keyword::Not)) {
+            let expression = self.parse_not()?;
+            return Ok(SqlExpression::Not(self.boolean(expression)?));
+        }
+        self.parse_predicate()
+    }
+
+    fn parse_predicate(&mut self) -> Result<SqlExpression, ParseError> {
+        let left = self.parse_value()?;
+        let operator = match self.peek() {
+            Token::Equal => Some(ComparisonOperator::Equal),
+            Token::NotEqual => Some(ComparisonOperator::NotEqual),
+            Token::GreaterThan => Some(ComparisonOperator::GreaterThan),
+            Token::GreaterThanOrEqual => Some(ComparisonOperator::GreaterThanOrEqual),
+            Token::LessThan => Some(ComparisonOperator::LessThan),
+            Token::LessThanOrEqual => Some(ComparisonOperator::LessThanOrEqual),
+            _ => None,
+        };
+        if let Some(operator) = operator {
+            self.next();
+            let right = self.parse_value()?;
+            return Ok(SqlExpression::Comparison(
+                operator,
+                Box::new(left),
+                Box::new(right),
+            ));
+        }
+        if self.eat(&Token::Keyword(Keyword::Is)) {
+            let negated = self.eat(&Token::Keyword(Keyword::Not));
+            self.expect(&Token::Keyword(Keyword::Null), "NULL")?;
+            return Ok(SqlExpression::IsNull {
+                expression: Box::new(left),
+                negated,
+            });
+        }
+        let negated = self.eat(&Token::Keyword(Keyword::Not));
+        match self.peek().clone() {
+            Token::Keyword(Keyword::Between) => {
+                self.next();
+                let low = self.parse_value()?;
+                self.expect(&Token::Keyword(Keyword::And), "AND")?;
+                let high = self.parse_value()?;
+                let between = SqlExpression::And(
+                    Box::new(SqlExpression::Comparison(
+                        ComparisonOperator::GreaterThanOrEqual,
+                        Box::new(left.clone()),
+                        Box::new(low),
+                    )),
+                    Box::new(SqlExpression::Comparison(
+                        ComparisonOperator::LessThanOrEqual,
+                        Box::new(left),
+                        Box::new(high),
+                    )),
+                );
+                Ok(if negated {
+                    SqlExpression::Not(Box::new(between))
+                } else {
+                    between
+                })
+            }
+            Token::Keyword(Keyword::In) => {
+                self.next();
+                self.expect(&Token::LeftParen, "(")?;
+                let mut values = vec![self.parse_string()?];
+                while self.eat(&Token::Comma) {
+                    values.push(self.parse_string()?);
+                }
+                self.expect(&Token::RightParen, ")")?;
+                Ok(SqlExpression::In {
+                    expression: Box::new(left),
+                    values,
+                    negated,
+                })
+            }
+            Token::Keyword(
+                keyword @ (Keyword::Contains | Keyword::StartsWith | Keyword::EndsWith),
+            ) => {
+                self.next();
+                let operator = match keyword {
+                    Keyword::Contains => StringOperator::Contains,
+                    Keyword::StartsWith => StringOperator::StartsWith,
+                    _ => StringOperator::EndsWith,
+                };
+                Ok(SqlExpression::StringMatch {
+                    operator,
+                    expression: Box::new(left),
+                    pattern: self.parse_string()?,
+                    negated,
+                })
+            }
+            _ if negated => {
+                Err(self.error("expected BETWEEN, IN, CONTAINS, STARTSWITH or ENDSWITH"))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, ParseError> {
+        match self.peek().clone() {
+            Token::String(value) => {
+                self.next();
+                Ok(value)
+            }
+            _ => Err(self.error("expected a string literal")),
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<SqlExpression, ParseError> {
+        let negative = match self.peek() {
+            Token::Minus => true,
+            Token::Plus => false,
+            _ => return self.parse_primary(),
+        };
+        self.next();
+        let value = match self.peek() {
+            Token::Long(value) if negative => Value::Long(-value),
+            Token::Double(value) if negative => Value::Double(-value),
+            Token::Long(value) => Value::Long(*value),
+            Token::Double(value) => Value::Double(*value),
+            _ => return Err(self.error("expected a number after the sign")),
+        };
+        self.next();
+        Ok(SqlExpression::Constant(value))
+    }
+
+    fn parse_primary(&mut self) -> Result<SqlExpression, ParseError> {
+        let expression = match self.peek().clone() {
+            Token::Eof => return Err(self.error("unexpected end of expression")),
+            Token::String(value) => SqlExpression::Constant(Value::String(value)),
+            Token::Long(value) => SqlExpression::Constant(Value::Long(value)),
+            Token::Double(value) => SqlExpression::Constant(Value::Double(value)),
+            Token::Keyword(Keyword::True) => SqlExpression::Constant(Value::Bool(true)),
+            Token::Keyword(Keyword::False) => SqlExpression::Constant(Value::Bool(false)),
+            Token::Keyword(Keyword::Null) => SqlExpression::Constant(Value::Null),
+            Token::Identifier(name) => SqlExpression::Property(name),
+            Token::LeftParen => {
+                self.next();
+                let expression = self.parse_or()?;
+                self.expect(&Token::RightParen, ")")?;
+                return Ok(expression);
+            }
+            _ => return Err(self.error("expected a value")),
+        };
+        self.next();
+        Ok(expression)
+    }
+}
+
+fn tokenize(sql: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let start = index;
+        let c = bytes[index];
+        let token = match c {
+            b' ' | b'\t' | b'\r' | b'\n' => {
+                index += 1;
+                continue;
+            }
+            b'(' => Token::LeftParen,
+            b')' => Token::RightParen,
+            b',' => Token::Comma,
+            b'+' => Token::Plus,
+            b'-' => Token::Minus,
+            b'=' => Token::Equal,
+            b'<' if bytes.get(index + 1) == Some(&b'>') => {
+                index += 1;
+                Token::NotEqual
+            }
+            b'<' if bytes.get(index + 1) == Some(&b'=') => {
+                index += 1;
+                Token::LessThanOrEqual
+            }
+            b'<' => Token::LessThan,
+            b'>' if bytes.get(index + 1) == Some(&b'=') => {
+                index += 1;
+                Token::GreaterThanOrEqual
+            }
+            b'>' => Token::GreaterThan,
+            b'\'' => {
+                let mut value = Vec::new();
+                index += 1;
+                loop {
+                    match bytes.get(index) {
+                        None => {
+                            return Err(ParseError {
+                                message: "unterminated string literal".to_string(),
+                                position: start,
+                            })
+                        }
+                        Some(b'\'') if bytes.get(index + 1) == Some(&b'\'') => {
+                            value.push(b'\'');
+                            index += 2;
+                        }
+                        Some(b'\'') => break,
+                        Some(c) => {
+                            value.push(*c);
+                            index += 1;
+                        }
+                    }
+                }
+                Token::String(String::from_utf8_lossy(&value).into_owned())
+            }
+            b'0'..=b'9' | b'.' => {
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'.')
+                {
+                    // exponent sign, e.g. 1.5E-3
+                    if matches!(bytes[index], b'e' | b'E')
+                        && matches!(bytes.get(index + 1), Some(b'+' | b'-'))
+                    {
+                        index += 1;
+                    }
+                    index += 1;
+                }
+                let literal = &sql[start..index];
+                index -= 1;
+                number(literal).ok_or_else(|| ParseError {
+                    message: format!("invalid number {}", literal),
+                    position: start,
+                })?
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' || c == b'$' => {
+                while index + 1 < bytes.len()
+                    && (bytes[index + 1].is_ascii_alphanumeric()
+                        || matches!(bytes[index + 1], b'_' | b'$' | b'.'))
+                {
+                    index += 1;
+                }
+                let word = &sql[start..=index];
+                match Keyword::of(word) {
+                    Some(keyword) => Token::Keyword(keyword),
+                    None => Token::Identifier(word.to_string()),
+                }
+            }
+            _ => {
+                return Err(ParseError {
+                    message: format!("unexpected character {:?}", sql[start..].chars().next()),
+                    position: start,
+                })
+            }
+        };
+        tokens.push((token, start));
+        index += 1;
+    }
+    tokens.push((Token::Eof, sql.len()));
+    Ok(tokens)
+}
+
+fn number(literal: &str) -> Option<Token> {
+    let long = literal
+        .strip_suffix('L')
+        .or_else(|| literal.strip_suffix('l'))
+        .unwrap_or(literal);
+    if let Ok(value) = long.parse::<i64>() {
+        return Some(Token::Long(value));
+    }
+    let double = literal
+        .strip_suffix('D')
+        .or_else(|| literal.strip_suffix('d'))
+        .or_else(|| literal.strip_suffix('F'))
+        .or_else(|| literal.strip_suffix('f'))
+        .unwrap_or(literal);
+    double.parse::<f64>().ok().map(Token::Double)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(name: &str) -> Box<SqlExpression> {
+        Box::new(SqlExpression::Property(name.to_string()))
+    }
+
+    #[test]
+    fn parses_comparisons_with_precedence() {
+        let expression = SelectorParser::parse("a > 1 OR b = 'x' AND NOT c <> -2.5").unwrap();
+        assert_eq!(
+            expression,
+            SqlExpression::Or(
+                Box::new(SqlExpression::Comparison(
+                    ComparisonOperator::GreaterThan,
+                    property("a"),
+                    Box::new(SqlExpression::Constant(Value::Long(1))),
+                )),
+                Box::new(SqlExpression::And(
+                    Box::new(SqlExpression::Comparison(
+                        ComparisonOperator::Equal,
+                        property("b"),
+                        Box::new(SqlExpression::Constant(Value::String("x".to_string()))),
+                    )),
+                    Box::new(SqlExpression::Not(Box::new(SqlExpression::Comparison(
+                        ComparisonOperator::NotEqual,
+                        property("c"),
+                        Box::new(SqlExpression::Constant(Value::Double(-2.5))),
+                    )))),
+                )),
+            )
+        );
+    }
+
+    #[test]
+    fn parses_predicates_case_insensitively() {
+        let expression = SelectorParser::parse(
+            "region not in ('eu', 'it''s') and name is not null and tag startswith 'v1' and a not \
+             between 1 and 3",
+        )
+        .unwrap();
+        let SqlExpression::And(left, between) = expression else {
+            panic!("expected AND");
+        };
+        assert!(matches!(*between, SqlExpression::Not(_)));
+        let SqlExpression::And(left, starts_with) = *left else {
+            panic!("expected AND");
+        };
+        assert_eq!(
+            *starts_with,
+            SqlExpression::StringMatch {
+                operator: StringOperator::StartsWith,
+                expression: property("tag"),
+                pattern: "v1".to_string(),
+                negated: false,
+            }
+        );
+        let SqlExpression::And(in_list, is_not_null) = *left else {
+            panic!("expected AND");
+        };
+        assert_eq!(
+            *is_not_null,
+            SqlExpression::IsNull {
+                expression: property("name"),
+                negated: true,
+            }
+        );
+        assert_eq!(
+            *in_list,
+            SqlExpression::In {
+                expression: property("region"),
+                values: vec!["eu".to_string(), "it's".to_string()],
+                negated: true,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for sql in [
+            "",
+            "a",
+            "a = ",
+            "a = 'unterminated",
+            "(a = 1",
+            "a = 1 b = 2",
+            "a IN (1, 2)",
+            "a NOT = 1",
+            "a NOT",
+            "a = -",
+            "1 AND a = 1",
+            "a = 1 # 2",
+        ] {
+            assert!(SelectorParser::parse(sql).is_err(), "{}", sql);
+        }
+        let error = SelectorParser::parse("a = 1 AND").unwrap_err();
+        assert_eq!(error.position, 9);
+    }
+}
@@ -0,0 +1,120 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::error::Error;
+
+use crate::expression::Expression;
+use crate::filter_spi::FilterSpi;
+use crate::parser::selector_parser::SelectorParser;
+
+/// Filters messages by their properties with a SQL92 expression, registered by default in
+/// [`FilterFactory`](crate::filter_factory::FilterFactory).
+pub struct SqlFilter;
+
+impl SqlFilter {
+    pub const TYPE: &'static str = "SQL92";
+}
+
+impl FilterSpi for SqlFilter {
+    fn compile(
+        &self,
+        expr: &str,
+    ) -> Result<Box<dyn Expression + Send + Sync + 'static>, Box<dyn Error + Send + Sync>> {
+        Ok(Box::new(SelectorParser::parse(expr)?))
+    }
+
+    fn of_type(&self) -> &str {
+        SqlFilter::TYPE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::collections::HashMap;
+
+    use cheetah_string::CheetahString;
+
+    use super::*;
+    use crate::expression::evaluation_context::EvaluationContext;
+
+    struct Properties(HashMap<String, CheetahString>);
+
+    impl EvaluationContext for Properties {
+        fn get(&self, name: &str) -> Option<&dyn Any> {
+            self.0.get(name).map(|value| value as &dyn Any)
+        }
+
+        fn key_values(&self) -> HashMap<String, Box<dyn Any>> {
+            HashMap::new()
+        }
+    }
+
+    fn matches(expr: &str, properties: &[(&str, &str)]) -> bool {
+        let context = Properties(
+            properties
+                .iter()
+                .map(|(key, value)| (key.to_string(), CheetahString::from(*value)))
+                .collect(),
+        );
+        let result = SqlFilter.compile(expr).unwrap().evaluate(&context).unwrap();
+        *result.downcast_ref::<bool>().unwrap()
+    }
+
+    #[test]
+    fn evaluates_against_string_properties() {
+        let properties = [("a", "2"), ("price", "9.5"), ("region", "eu-west")];
+        assert!(matches("a BETWEEN 1 AND 3", &properties));
+        assert!(!matches("a NOT BETWEEN 1 AND 3", &properties));
+        assert!(matches("a = 2 AND price < 10", &properties));
+        assert!(matches("a > 1.5", &properties));
+        assert!(matches("region IN ('eu-west', 'us-east')", &properties));
+        assert!(matches(
+            "region STARTSWITH 'eu' AND region ENDSWITH 'west'",
+            &properties
+        ));
+        assert!(matches("region NOT CONTAINS 'us'", &properties));
+        assert!(matches("region > 'eu'", &properties));
+        assert!(!matches("region > 1", &properties));
+        assert!(matches("missing IS NULL AND a IS NOT NULL", &properties));
+    }
+
+    #[test]
+    fn missing_properties_never_match() {
+        assert!(!matches("missing = 1", &[]));
+        assert!(!matches("NOT (missing = 1)", &[]));
+        assert!(!matches("missing NOT IN ('a')", &[]));
+        assert!(matches("missing = 1 OR TRUE", &[]));
+        assert!(!matches("missing = 1 AND TRUE", &[]));
+    }
+
+    #[test]
+    fn mismatched_types_never_match() {
+        let properties = [("region", "eu-west"), ("flag", "yes")];
+        assert!(!matches("region <> 1", &properties));
+        assert!(!matches("region = 1", &properties));
+        assert!(!matches("NOT (region <> 1)", &properties));
+        assert!(!matches("flag <> TRUE", &properties));
+        assert!(!matches("TRUE <> 1", &[]));
+        assert!(matches("region <> 1 OR region = 'eu-west'", &properties));
+    }
+
+    #[test]
+    fn compile_reports_parse_errors() {
+        let error = SqlFilter.compile("a >").err().unwrap();
+        assert!(error.to_string().contains("position 3"), "{}", error);
+    }
+}
@@ -80,6 +80,50 @@ impl BloomFilter {
         self.m
     }
 
+    /// Computes the `k` bit positions of `key`, e.g. `consumerGroup#topic`.
+    pub fn hash_to(&self, key: &str) -> BloomFilterData {
+        BloomFilterData::new(self.calc_bit_positions(key), self.m as u32)
+    }
+
+    /// Sets the bits of `filter_data` in `bits`, a bitmap of `m / 8` bytes.
+    pub fn hash_to_bits(&self, filter_data: &BloomFilterData, bits: &mut [u8]) {
+        if !self.is_valid(Some(filter_data)) || bits.len() * 8 < self.m as usize {
+            return;
+        }
+        for pos in filter_data.bit_pos() {
+            bits[*pos as usize / 8] |= 1 << (*pos as usize % 8);
+        }
+    }
+
+    /// Whether every bit of `filter_data` is set in `bits`. False positives are possible at the
+    /// configured error rate, false negatives are not.
+    pub fn is_hit(&self, filter_data: &BloomFilterData, bits: &[u8]) -> bool {
+        if !self.is_valid(Some(filter_data)) || bits.len() * 8 < self.m as usize {
+            return true;
+        }
+        filter_data
+            .bit_pos()
+            .iter()
+            .all(|pos| bits[*pos as usize / 8] & (1 << (*pos as usize % 8)) != 0)
+    }
+
+    // Derives the k positions from one murmur3 hash, see Kirsch and Mitzenmacher,
+    // "Less Hashing, Same Performance".
+    fn calc_bit_positions(&self, key: &str) -> Vec<i32> {
+        let hash = murmur3_32(key.as_bytes());
+        let hash1 = hash;
+        let hash2 = ((hash as u32) >> 16) as i32;
+        (1..=self.k)
+            .map(|i| {
+                let mut combined = hash1.wrapping_add(i.wrapping_mul(hash2));
+                if combined < 0 {
+                    combined = !combined;
+                }
+                combined % self.m
+            })
+            .collect()
+    }
+
     pub fn is_valid(&self, filter_data: Option<&BloomFilterData>) -> bool {
         match filter_data {
             Some(data) => {
@@ -89,3 +133,56 @@ impl BloomFilter {
         }
     }
 }
+
+fn murmur3_32(data: &[u8]) -> i32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+
+    let mut h1 = 0u32;
+    let chunks = data.chunks_exact(4);
+    let tail = chunks.remainder();
+    for chunk in chunks {
+        let k1 = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        h1 ^= k1.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        h1 = h1.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+    if !tail.is_empty() {
+        let mut k1 = 0u32;
+        for (i, b) in tail.iter().enumerate() {
+            k1 ^= (*b as u32) << (8 * i);
+        }
+        h1 ^= k1.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+    }
+
+    h1 ^= data.len() as u32;
+    h1 ^= h1 >> 16;
+    h1 = h1.wrapping_mul(0x85eb_ca6b);
+    h1 ^= h1 >> 13;
+    h1 = h1.wrapping_mul(0xc2b2_ae35);
+    h1 ^= h1 >> 16;
+    h1 as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn murmur3_matches_reference_values() {
+        assert_eq!(murmur3_32(b""), 0);
+        assert_eq!(murmur3_32(b"hello") as u32, 0x248b_fa47);
+    }
+
+    #[test]
+    fn hashed_keys_hit_their_own_bits() {
+        let bloom_filter = BloomFilter::new(20, 64).unwrap();
+        let group_a = bloom_filter.hash_to("group_a#topic");
+        let group_b = bloom_filter.hash_to("group_b#topic");
+        assert!(bloom_filter.is_valid(Some(&group_a)));
+
+        let mut bits = vec![0u8; bloom_filter.m() as usize / 8];
+        bloom_filter.hash_to_bits(&group_a, &mut bits);
+        assert!(bloom_filter.is_hit(&group_a, &bits));
+        assert!(!bloom_filter.is_hit(&group_b, &vec![0u8; bits.len()]));
+    }
+}
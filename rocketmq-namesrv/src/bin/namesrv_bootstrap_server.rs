@@ -18,11 +18,9 @@
 use std::path::PathBuf;
 
 use clap::Parser;
-use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
-use rocketmq_common::common::server::config::ServerConfig;
 use rocketmq_common::EnvUtils::EnvUtils;
-use rocketmq_common::ParseConfigFile;
 use rocketmq_namesrv::bootstrap::Builder;
+use rocketmq_namesrv::load_namesrv_config;
 use rocketmq_rust::rocketmq;
 use tracing::info;
 
@@ -32,19 +30,38 @@ async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
     let home = EnvUtils::get_rocketmq_home();
 
+    let config_file = args.config.unwrap_or_else(|| {
+        PathBuf::from(home.as_str())
+            .join("conf")
+            .join("namesrv.toml")
+    });
+    let (namesrv_config, mut server_config) = load_namesrv_config(&config_file)?;
+    if let Some(port) = args.port {
+        server_config.listen_port = port;
+    }
+    if let Some(ip) = args.ip {
+        server_config.bind_address = ip;
+    }
+    if args.print_config_item {
+        println!(
+            "{}",
+            namesrv_config
+                .get_all_configs_format_string()
+                .map_err(|e| anyhow::anyhow!(e))?
+        );
+        println!("listenPort={}", server_config.listen_port);
+        println!("bindAddress={}", server_config.bind_address);
+        return Ok(());
+    }
+
     info!("Rocketmq(Rust) home: {}", home);
     info!(
         "Rocketmq name remoting_server(Rust) running on: {}:{}",
-        args.ip, args.port
+        server_config.bind_address, server_config.listen_port
     );
-    let config_file = PathBuf::from(home).join("conf").join("namesrv.toml");
-    let namesrv_config = ParseConfigFile::parse_config_file::<NamesrvConfig>(config_file.clone())?;
     Builder::new()
         .set_name_server_config(namesrv_config)
-        .set_server_config(ServerConfig {
-            listen_port: args.port,
-            bind_address: args.ip,
-        })
+        .set_server_config(server_config)
         .build()
         .boot()
         .await;
@@ -59,27 +76,18 @@ async fn main() -> anyhow::Result<()> {
     about = "RocketMQ Name remoting_server(Rust)"
 )]
 struct Args {
-    /// rocketmq name remoting_server port
-    #[arg(
-        short,
-        long,
-        value_name = "PORT",
-        default_missing_value = "9876",
-        default_value = "9876",
-        required = false
-    )]
-    port: u32,
+    /// rocketmq name remoting_server port, overrides `listenPort` in the config file
+    #[arg(long, value_name = "PORT", required = false)]
+    port: Option<u32>,
 
-    /// rocketmq name remoting_server ip
-    #[arg(
-        short,
-        long,
-        value_name = "IP",
-        default_value = "0.0.0.0",
-        required = false
-    )]
-    ip: String,
+    /// rocketmq name remoting_server ip, overrides `bindAddress` in the config file
+    #[arg(short, long, value_name = "IP", required = false)]
+    ip: Option<String>,
     /// rocketmq name remoting_server config file
     #[arg(short, long, value_name = "FILE", default_missing_value = "None")]
     config: Option<PathBuf>,
+
+    /// Print all config item
+    #[arg(short, long, required = false)]
+    print_config_item: bool,
 }
@@ -19,6 +19,8 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use cheetah_string::CheetahString;
+use rocketmq_common::common::metrics::metrics_exporter_type::MetricsExporterType;
+use rocketmq_common::common::metrics::prometheus_exporter::PrometheusExporter;
 use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
 use rocketmq_common::common::server::config::ServerConfig;
 use rocketmq_common::utils::network_util::NetworkUtil;
@@ -34,8 +36,10 @@ use rocketmq_rust::ArcMut;
 use tokio::sync::broadcast;
 use tracing::info;
 
+use crate::metrics::namesrv_metrics_manager::NamesrvMetricsManager;
 use crate::processor::ClientRequestProcessor;
 use crate::processor::NameServerRequestProcessor;
+use crate::route::batch_unregistration_service::BatchUnregistrationService;
 use crate::KVConfigManager;
 use crate::RouteInfoManager;
 
@@ -56,6 +60,7 @@ struct NameServerRuntime {
     kvconfig_manager: KVConfigManager,
     name_server_runtime: Option<RocketMQRuntime>,
     remoting_client: ArcMut<RocketmqDefaultClient>,
+    server_shutdown: tokio::sync::watch::Sender<bool>,
 }
 
 impl NameServerBootstrap {
@@ -66,6 +71,18 @@ impl NameServerBootstrap {
             }
         }*/
         tokio::join!(self.name_server_runtime.start(), wait_for_signal());
+        self.name_server_runtime.shutdown();
+    }
+
+    /// Starts serving without waiting for a shutdown signal, for name servers embedded in
+    /// another process such as tests.
+    pub async fn start(&mut self) {
+        self.name_server_runtime.start().await;
+    }
+
+    /// Stops accepting requests.
+    pub fn shutdown(&mut self) {
+        self.name_server_runtime.shutdown();
     }
 }
 
@@ -75,8 +92,13 @@ impl NameServerRuntime {
         let receiver = notify_conn_disconnect.subscribe();
         let request_processor = self.init_processors(receiver);
         let server = RocketMQServer::new(self.server_config.clone());
+        let mut server_shutdown = self.server_shutdown.subscribe();
         tokio::spawn(async move {
-            server.run(request_processor).await;
+            server
+                .run_until(request_processor, async move {
+                    let _ = server_shutdown.wait_for(|shutdown| *shutdown).await;
+                })
+                .await;
         });
         let namesrv = CheetahString::from_string(format!(
             "{}:{}",
@@ -88,9 +110,26 @@ impl NameServerRuntime {
             .update_name_server_address_list(vec![namesrv])
             .await;
         self.remoting_client.start(weak_arc_mut).await;
+        self.start_metrics_exporter();
         info!("Rocketmq NameServer(Rust) started");
     }
 
+    fn shutdown(&mut self) {
+        let _ = self.server_shutdown.send(true);
+    }
+
+    fn start_metrics_exporter(&self) {
+        if self.name_server_config.metrics_exporter_type != MetricsExporterType::Prom {
+            return;
+        }
+        let metrics_manager = NamesrvMetricsManager::new(self.route_info_manager.clone());
+        PrometheusExporter::start(
+            self.name_server_config.metrics_prom_exporter_host.as_str(),
+            self.name_server_config.metrics_prom_exporter_port,
+            Arc::new(move || metrics_manager.collect()),
+        );
+    }
+
     fn init_processors(
         &self,
         receiver: broadcast::Receiver<SocketAddr>,
@@ -106,9 +145,15 @@ impl NameServerRuntime {
             crate::processor::default_request_processor::DefaultRequestProcessor::new(
                 self.route_info_manager.clone(),
                 self.kvconfig_manager.clone(),
+                BatchUnregistrationService::start(
+                    self.route_info_manager.clone(),
+                    self.name_server_config.unregister_broker_queue_capacity as usize,
+                ),
             );
 
         let mut route_info_manager_arc = self.route_info_manager.clone();
+        let scan_not_active_broker_interval =
+            Duration::from_millis(self.name_server_config.scan_not_active_broker_interval);
         self.name_server_runtime
             .as_ref()
             .unwrap()
@@ -116,8 +161,8 @@ impl NameServerRuntime {
                 move || {
                     route_info_manager_arc.scan_not_active_broker();
                 },
-                Some(Duration::from_secs(5)),
-                Duration::from_secs(5),
+                Some(scan_not_active_broker_interval),
+                scan_not_active_broker_interval,
             );
         NameServerRequestProcessor {
             client_request_processor: ArcMut::new(client_request_processor),
@@ -166,6 +211,8 @@ impl Builder {
             tokio_client_config.clone(),
             DefaultRemotingRequestProcessor,
         ));
+        let mut kvconfig_manager = KVConfigManager::new(name_server_config.clone());
+        kvconfig_manager.load();
 
         NameServerBootstrap {
             name_server_runtime: NameServerRuntime {
@@ -176,9 +223,10 @@ impl Builder {
                     name_server_config.clone(),
                     remoting_client.clone(),
                 ),
-                kvconfig_manager: KVConfigManager::new(name_server_config),
+                kvconfig_manager,
                 name_server_runtime: Some(runtime),
                 remoting_client,
+                server_shutdown: tokio::sync::watch::channel(false).0,
             },
         }
     }
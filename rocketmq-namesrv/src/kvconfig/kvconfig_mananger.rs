@@ -84,14 +84,23 @@ impl KVConfigManager {
 impl KVConfigManager {
     /// Loads key-value configurations from a file.
     pub fn load(&mut self) {
-        let result = FileUtils::file_to_string(self.namesrv_config.kv_config_path.as_str());
-        if let Ok(content) = result {
-            let wrapper =
-                SerdeJsonUtils::decode::<KVConfigSerializeWrapper>(content.as_bytes()).unwrap();
-            if let Some(config_table) = wrapper.config_table {
-                let mut table = self.config_table.write();
-                table.extend(config_table);
-                info!("load KV config success");
+        let content = match FileUtils::file_to_string(self.namesrv_config.kv_config_path.as_str()) {
+            Ok(content) if !content.is_empty() => content,
+            _ => return,
+        };
+        match SerdeJsonUtils::decode::<KVConfigSerializeWrapper>(content.as_bytes()) {
+            Ok(wrapper) => {
+                if let Some(config_table) = wrapper.config_table {
+                    let mut table = self.config_table.write();
+                    table.extend(config_table);
+                    info!("load KV config success");
+                }
+            }
+            Err(err) => {
+                error!(
+                    "load KV config table from {} failed: {}",
+                    self.namesrv_config.kv_config_path, err
+                );
             }
         }
     }
@@ -269,6 +278,32 @@ mod tests {
         assert_eq!(value, Some("value".into()));
     }
 
+    #[test]
+    fn load_restores_persisted_config_table() {
+        let kv_config_path = std::env::temp_dir().join(format!(
+            "kvConfig-{}.json",
+            rocketmq_common::TimeUtils::get_current_nano()
+        ));
+        let namesrv_config = ArcMut::new(NamesrvConfig {
+            kv_config_path: kv_config_path.to_string_lossy().into_owned(),
+            ..NamesrvConfig::default()
+        });
+        let mut manager = KVConfigManager::new(namesrv_config.clone());
+        manager.put_kv_config(
+            "ORDER_TOPIC_CONFIG".into(),
+            "TopicOrder".into(),
+            "broker-a:4".into(),
+        );
+
+        let mut reloaded = KVConfigManager::new(namesrv_config);
+        reloaded.load();
+        assert_eq!(
+            reloaded.get_kvconfig(&"ORDER_TOPIC_CONFIG".into(), &"TopicOrder".into()),
+            Some("broker-a:4".into())
+        );
+        let _ = std::fs::remove_file(kv_config_path);
+    }
+
     #[test]
     fn get_kvconfig_returns_none_if_key_does_not_exist() {
         let manager = create_kv_config_manager();
@@ -18,11 +18,13 @@
 #![allow(dead_code)]
 
 pub use self::kvconfig::kvconfig_mananger::KVConfigManager;
+pub use self::namesrv_config_parse::load_namesrv_config;
 pub use self::namesrv_config_parse::parse_command_and_config_file;
 pub use self::route::route_info_manager::RouteInfoManager;
 
 pub mod bootstrap;
 mod kvconfig;
+mod metrics;
 mod namesrv_config_parse;
 pub(crate) mod namesrv_error;
 pub mod processor;
@@ -0,0 +1,86 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_common::common::metrics::prometheus_text_encoder::PrometheusTextEncoder;
+
+use crate::RouteInfoManager;
+
+const LABEL_CLUSTER: &str = "cluster";
+
+/// Collects route statistics of the name server for the Prometheus exporter.
+pub(crate) struct NamesrvMetricsManager {
+    route_info_manager: RouteInfoManager,
+}
+
+impl NamesrvMetricsManager {
+    pub fn new(route_info_manager: RouteInfoManager) -> Self {
+        NamesrvMetricsManager { route_info_manager }
+    }
+
+    /// Renders the current route statistics in the Prometheus text exposition format.
+    pub fn collect(&self) -> String {
+        let mut encoder = PrometheusTextEncoder::new();
+        let manager = &self.route_info_manager;
+
+        encoder.gauge(
+            "rocketmq_namesrv_topic_number",
+            "Number of topics registered in the name server",
+            &[],
+            manager.topic_queue_table.len() as f64,
+        );
+        encoder.gauge(
+            "rocketmq_namesrv_cluster_number",
+            "Number of clusters registered in the name server",
+            &[],
+            manager.cluster_addr_table.len() as f64,
+        );
+        encoder.gauge(
+            "rocketmq_namesrv_live_broker_number",
+            "Number of broker addresses with a live heartbeat",
+            &[],
+            manager.broker_live_table.len() as f64,
+        );
+
+        let mut clusters = manager
+            .cluster_addr_table
+            .iter()
+            .map(|(cluster, broker_names)| {
+                let addrs = broker_names
+                    .iter()
+                    .filter_map(|broker_name| manager.broker_addr_table.get(broker_name))
+                    .map(|broker_data| broker_data.broker_addrs().len())
+                    .sum::<usize>();
+                (cluster.as_str(), (broker_names.len(), addrs))
+            })
+            .collect::<Vec<_>>();
+        clusters.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        for (cluster, (broker_names, addrs)) in clusters {
+            encoder.gauge(
+                "rocketmq_namesrv_broker_number",
+                "Number of broker names in the cluster",
+                &[(LABEL_CLUSTER, cluster)],
+                broker_names as f64,
+            );
+            encoder.gauge(
+                "rocketmq_namesrv_broker_addr_number",
+                "Number of broker addresses in the cluster",
+                &[(LABEL_CLUSTER, cluster)],
+                addrs as f64,
+            );
+        }
+        encoder.encode()
+    }
+}
@@ -15,12 +15,25 @@
  * limitations under the License.
  */
 
+use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 
+use anyhow::anyhow;
+use anyhow::bail;
+use cheetah_string::CheetahString;
 use config::Config;
 use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_common::ParseConfigFile;
 use tracing::info;
 
+/// Environment variables with this prefix override the config file, e.g.
+/// `ROCKETMQ_NAMESRV_LISTEN_PORT=9877`.
+pub const NAMESRV_ENV_PREFIX: &str = "ROCKETMQ_NAMESRV_";
+
+const DEFAULT_NAMESRV_PORT: u32 = 9876;
+
 pub fn parse_command_and_config_file(
     config_file: PathBuf,
 ) -> anyhow::Result<NamesrvConfig, anyhow::Error> {
@@ -37,3 +50,92 @@ pub fn parse_command_and_config_file(
     info!("rocketmq-namesrv config: {:?}", namesrv_config);
     Ok(namesrv_config)
 }
+
+/// Loads the name server config in layers: defaults, then `config_file` (Java style properties
+/// for `.conf`/`.properties` files, otherwise any format understood by the `config` crate), then
+/// `ROCKETMQ_NAMESRV_*` environment variables. `listenPort` and `bindAddress` go to the
+/// returned [`ServerConfig`].
+pub fn load_namesrv_config(config_file: &Path) -> anyhow::Result<(NamesrvConfig, ServerConfig)> {
+    load_namesrv_config_with(
+        config_file,
+        ParseConfigFile::env_properties(NAMESRV_ENV_PREFIX),
+    )
+}
+
+fn load_namesrv_config_with(
+    config_file: &Path,
+    env_properties: HashMap<CheetahString, CheetahString>,
+) -> anyhow::Result<(NamesrvConfig, ServerConfig)> {
+    let (mut namesrv_config, mut properties) = if ParseConfigFile::is_properties_file(config_file) {
+        (
+            NamesrvConfig::default(),
+            ParseConfigFile::parse_properties_file(config_file)?,
+        )
+    } else {
+        (
+            ParseConfigFile::parse_config_file::<NamesrvConfig>(config_file.to_path_buf())?,
+            HashMap::new(),
+        )
+    };
+    properties.extend(env_properties);
+
+    let mut server_config = ServerConfig {
+        listen_port: DEFAULT_NAMESRV_PORT,
+        ..ServerConfig::default()
+    };
+    if let Some(listen_port) = properties.remove("listenPort") {
+        server_config.listen_port = listen_port
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("Invalid value '{}' for key 'listenPort'", listen_port))?;
+    }
+    if let Some(bind_address) = properties.remove("bindAddress") {
+        server_config.bind_address = bind_address.trim().to_string();
+    }
+    namesrv_config.update(properties).map_err(|e| anyhow!(e))?;
+
+    if server_config.listen_port == 0 || server_config.listen_port > 65535 {
+        bail!("Invalid listenPort: {}", server_config.listen_port);
+    }
+    Ok((namesrv_config, server_config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn properties_file_then_env() {
+        let path = write_config(
+            "namesrv.conf",
+            "listenPort=9877\nclusterTest=true\nscanNotActiveBrokerInterval=3000\n",
+        );
+        let mut env = HashMap::new();
+        env.insert("scanNotActiveBrokerInterval".into(), "6000".into());
+        let (namesrv_config, server_config) = load_namesrv_config_with(&path, env).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(server_config.listen_port, 9877);
+        assert!(namesrv_config.cluster_test);
+        assert_eq!(namesrv_config.scan_not_active_broker_interval, 6000);
+    }
+
+    #[test]
+    fn unknown_and_invalid_items_are_rejected() {
+        let path = write_config("namesrv-unknown.conf", "noSuchItem=1\n");
+        let result = load_namesrv_config_with(&path, HashMap::new());
+        std::fs::remove_file(path).unwrap();
+        assert!(result.is_err());
+
+        let path = write_config("namesrv-invalid.conf", "listenPort=-1\n");
+        let result = load_namesrv_config_with(&path, HashMap::new());
+        std::fs::remove_file(path).unwrap();
+        assert!(result.unwrap_err().to_string().contains("listenPort"));
+    }
+}
@@ -20,6 +20,7 @@ use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use cheetah_string::CheetahString;
+use rocketmq_common::common::mix_all;
 use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
 use rocketmq_common::common::FAQUrl;
 use rocketmq_common::TimeUtils;
@@ -29,6 +30,8 @@ use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::header::client_request_header::GetRouteInfoRequestHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::route::route_data_view::BrokerData;
+use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
 use rocketmq_rust::ArcMut;
@@ -125,6 +128,9 @@ impl ClientRequestProcessor {
                 } else {
                     topic_route_data.encode()
                 };*/
+                if let Some(zone_name) = Self::requested_zone(&request) {
+                    Self::filter_by_zone_name(&mut topic_route_data, zone_name);
+                }
                 let content = topic_route_data
                     .encode()
                     .map_err(|_| MQNamesrvError("encode TopicRouteData failed".to_string()))?;
@@ -138,6 +144,50 @@ impl ClientRequestProcessor {
 }
 
 impl ClientRequestProcessor {
+    /// Returns the zone the client asked to be routed to, only when zone mode is enabled on the
+    /// request and a zone name is present.
+    fn requested_zone(request: &RemotingCommand) -> Option<&CheetahString> {
+        let ext_fields = request.get_ext_fields()?;
+        let zone_mode = ext_fields
+            .get(mix_all::ZONE_MODE)
+            .is_some_and(|mode| mode.trim().eq_ignore_ascii_case("true"));
+        if !zone_mode {
+            return None;
+        }
+        ext_fields
+            .get(mix_all::ZONE_NAME)
+            .filter(|zone_name| !zone_name.trim().is_empty())
+    }
+
+    /// Keeps only the brokers of `zone_name`. A broker whose master is down is always kept so
+    /// that its slaves can still be consumed from, which breaks the nearby route rule on purpose.
+    fn filter_by_zone_name(topic_route_data: &mut TopicRouteData, zone_name: &str) {
+        let (reserved, removed): (Vec<BrokerData>, Vec<BrokerData>) = topic_route_data
+            .broker_datas
+            .drain(..)
+            .partition(|broker_data| {
+                !broker_data.broker_addrs().contains_key(&mix_all::MASTER_ID)
+                    || broker_data
+                        .zone_name()
+                        .as_ref()
+                        .is_some_and(|zone| zone.eq_ignore_ascii_case(zone_name))
+            });
+        topic_route_data.broker_datas = reserved;
+        if removed.is_empty() {
+            return;
+        }
+        topic_route_data.queue_datas.retain(|queue_data| {
+            !removed
+                .iter()
+                .any(|broker_data| broker_data.broker_name() == queue_data.broker_name())
+        });
+        for broker_data in &removed {
+            for broker_addr in broker_data.broker_addrs().values() {
+                topic_route_data.filter_server_table.remove(broker_addr);
+            }
+        }
+    }
+
     pub fn process_request(
         &mut self,
         _channel: Channel,
@@ -148,3 +198,133 @@ impl ClientRequestProcessor {
         self.get_route_info_by_topic(request)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rocketmq_common::common::config::TopicConfig;
+    use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+    use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigAndMappingSerializeWrapper;
+    use rocketmq_remoting::protocol::RemotingDeserializable;
+    use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+    use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+
+    use super::*;
+
+    fn register_broker(
+        route_info_manager: &RouteInfoManager,
+        broker_name: &str,
+        broker_addr: &str,
+        zone_name: &str,
+    ) {
+        let mut wrapper = TopicConfigAndMappingSerializeWrapper::default();
+        for topic in ["TopicA", "TopicB"] {
+            wrapper
+                .topic_config_serialize_wrapper
+                .topic_config_table
+                .insert(topic.into(), TopicConfig::with_queues(topic, 4, 4));
+        }
+        route_info_manager.register_broker(
+            "DefaultCluster".into(),
+            broker_addr.into(),
+            broker_name.into(),
+            0,
+            "127.0.0.1:10912".into(),
+            Some(zone_name.into()),
+            None,
+            None,
+            wrapper,
+            vec![],
+            broker_addr.parse().unwrap(),
+        );
+    }
+
+    fn new_processor() -> ClientRequestProcessor {
+        let namesrv_config = ArcMut::new(NamesrvConfig::default());
+        let route_info_manager = RouteInfoManager::new(
+            namesrv_config.clone(),
+            ArcMut::new(RocketmqDefaultClient::new(
+                Arc::new(TokioClientConfig::default()),
+                DefaultRemotingRequestProcessor,
+            )),
+        );
+        register_broker(&route_info_manager, "broker-a", "127.0.0.1:10911", "zone-a");
+        ClientRequestProcessor::new(
+            route_info_manager,
+            namesrv_config.clone(),
+            KVConfigManager::new(namesrv_config),
+        )
+    }
+
+    fn route_request(topic: &str) -> RemotingCommand {
+        let mut request = RemotingCommand::create_request_command(
+            RequestCode::GetRouteinfoByTopic,
+            GetRouteInfoRequestHeader::new(topic, None),
+        );
+        request.make_custom_header_to_net();
+        request
+    }
+
+    #[test]
+    fn get_route_info_by_topic_returns_route() {
+        let processor = new_processor();
+        let response = processor
+            .get_route_info_by_topic(route_request("TopicA"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+        let route = TopicRouteData::decode(response.get_body().unwrap()).unwrap();
+        assert_eq!(route.queue_datas.len(), 1);
+        assert_eq!(route.broker_datas.len(), 1);
+        assert_eq!(route.broker_datas[0].broker_name(), "broker-a");
+    }
+
+    #[test]
+    fn get_route_info_by_unknown_topic_returns_topic_not_exist() {
+        let processor = new_processor();
+        let response = processor
+            .get_route_info_by_topic(route_request("UnknownTopic"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            ResponseCode::from(response.code()),
+            ResponseCode::TopicNotExist
+        );
+    }
+
+    #[test]
+    fn get_route_info_by_topic_filters_brokers_of_other_zones() {
+        let processor = new_processor();
+        register_broker(
+            &processor.route_info_manager,
+            "broker-b",
+            "127.0.0.1:20911",
+            "zone-b",
+        );
+
+        let response = processor
+            .get_route_info_by_topic(route_request("TopicA"))
+            .unwrap()
+            .unwrap();
+        let route = TopicRouteData::decode(response.get_body().unwrap()).unwrap();
+        assert_eq!(route.broker_datas.len(), 2);
+
+        let mut request = route_request("TopicA");
+        request.add_ext_field(mix_all::ZONE_MODE, "true");
+        request.add_ext_field(mix_all::ZONE_NAME, "ZONE-B");
+        let response = processor.get_route_info_by_topic(request).unwrap().unwrap();
+        let route = TopicRouteData::decode(response.get_body().unwrap()).unwrap();
+        assert_eq!(route.broker_datas.len(), 1);
+        assert_eq!(route.broker_datas[0].broker_name(), "broker-b");
+        assert_eq!(route.queue_datas.len(), 1);
+        assert_eq!(route.queue_datas[0].broker_name(), "broker-b");
+
+        let mut request = route_request("TopicA");
+        request.add_ext_field(mix_all::ZONE_MODE, "false");
+        request.add_ext_field(mix_all::ZONE_NAME, "zone-b");
+        let response = processor.get_route_info_by_topic(request).unwrap().unwrap();
+        let route = TopicRouteData::decode(response.get_body().unwrap()).unwrap();
+        assert_eq!(route.broker_datas.len(), 2);
+    }
+}
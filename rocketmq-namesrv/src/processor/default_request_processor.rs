@@ -62,12 +62,14 @@ use tracing::warn;
 use crate::namesrv_error::NamesrvError::MQNamesrvError;
 use crate::namesrv_error::NamesrvRemotingErrorWithMessage;
 use crate::processor::NAMESPACE_ORDER_TOPIC_CONFIG;
+use crate::route::batch_unregistration_service::BatchUnregistrationService;
 use crate::route::route_info_manager::RouteInfoManager;
 use crate::KVConfigManager;
 
 pub struct DefaultRequestProcessor {
     route_info_manager: RouteInfoManager,
     kvconfig_manager: KVConfigManager,
+    un_register_service: BatchUnregistrationService,
 }
 
 impl DefaultRequestProcessor {
@@ -163,13 +165,11 @@ impl DefaultRequestProcessor {
                 .set_command_custom_header(GetKVConfigResponseHeader::new(value)));
         }
         Ok(
-            RemotingCommand::create_response_command_with_code(
-                RemotingSysResponseCode::SystemError,
-            )
-            .set_remark(format!(
-                "No config item, Namespace: {} Key: {}",
-                request_header.namespace, request_header.key
-            )),
+            RemotingCommand::create_response_command_with_code(ResponseCode::QueryNotFound)
+                .set_remark(format!(
+                    "No config item, Namespace: {} Key: {}",
+                    request_header.namespace, request_header.key
+                )),
         )
     }
 
@@ -226,10 +226,15 @@ impl DefaultRequestProcessor {
 
 #[allow(clippy::new_without_default)]
 impl DefaultRequestProcessor {
-    pub fn new(route_info_manager: RouteInfoManager, kvconfig_manager: KVConfigManager) -> Self {
+    pub fn new(
+        route_info_manager: RouteInfoManager,
+        kvconfig_manager: KVConfigManager,
+        un_register_service: BatchUnregistrationService,
+    ) -> Self {
         Self {
             route_info_manager,
             kvconfig_manager,
+            un_register_service,
         }
     }
 }
@@ -323,8 +328,15 @@ impl DefaultRequestProcessor {
                     "decode UnRegisterBrokerRequestHeader fail".to_string(),
                 )
             })?;
-        self.route_info_manager
-            .un_register_broker(vec![request_header]);
+        if !self.un_register_service.submit(request_header.clone()) {
+            warn!(
+                "Couldn't submit the unregister broker request to handler, broker info: {:?}",
+                request_header
+            );
+            return Ok(RemotingCommand::create_response_command_with_code(
+                RemotingSysResponseCode::SystemError,
+            ));
+        }
         Ok(RemotingCommand::create_response_command())
     }
 }
@@ -0,0 +1,133 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_remoting::protocol::header::namesrv::broker_request::UnRegisterBrokerRequestHeader;
+use tokio::sync::mpsc;
+use tracing::info;
+use tracing::warn;
+
+use crate::route::route_info_manager::RouteInfoManager;
+
+/// Collects unregister broker requests and applies them to the route tables in batches.
+///
+/// Requests are queued in a bounded channel, the background task drains everything that is
+/// pending at once so that a wave of broker shutdowns only takes the route lock a few times.
+#[derive(Clone)]
+pub struct BatchUnregistrationService {
+    sender: mpsc::Sender<UnRegisterBrokerRequestHeader>,
+}
+
+impl BatchUnregistrationService {
+    pub fn start(route_info_manager: RouteInfoManager, queue_capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::channel(queue_capacity.max(1));
+        tokio::spawn(Self::run(route_info_manager, receiver));
+        Self { sender }
+    }
+
+    /// Queue an unregister request, returns `false` when the queue is full.
+    pub fn submit(&self, request: UnRegisterBrokerRequestHeader) -> bool {
+        match self.sender.try_send(request) {
+            Ok(_) => true,
+            Err(err) => {
+                warn!("submit unregister broker request failed: {}", err);
+                false
+            }
+        }
+    }
+
+    async fn run(
+        mut route_info_manager: RouteInfoManager,
+        mut receiver: mpsc::Receiver<UnRegisterBrokerRequestHeader>,
+    ) {
+        info!("BatchUnregistrationService started");
+        while let Some(request) = receiver.recv().await {
+            let mut requests = vec![request];
+            while let Ok(request) = receiver.try_recv() {
+                requests.push(request);
+            }
+            info!("unregister {} broker(s) in batch", requests.len());
+            route_info_manager.un_register_broker(requests);
+        }
+        info!("BatchUnregistrationService stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+    use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+    use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigAndMappingSerializeWrapper;
+    use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+    use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+    use rocketmq_rust::ArcMut;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn submitted_requests_are_unregistered() {
+        let route_info_manager = RouteInfoManager::new(
+            ArcMut::new(NamesrvConfig::default()),
+            ArcMut::new(RocketmqDefaultClient::new(
+                Arc::new(TokioClientConfig::default()),
+                DefaultRemotingRequestProcessor,
+            )),
+        );
+        for (broker_name, broker_addr) in [
+            ("broker-a", "127.0.0.1:10911"),
+            ("broker-b", "127.0.0.1:10921"),
+        ] {
+            route_info_manager.register_broker(
+                "DefaultCluster".into(),
+                broker_addr.into(),
+                broker_name.into(),
+                0,
+                broker_addr.into(),
+                None,
+                None,
+                None,
+                TopicConfigAndMappingSerializeWrapper::default(),
+                vec![],
+                broker_addr.parse().unwrap(),
+            );
+        }
+        assert_eq!(route_info_manager.broker_addr_table.len(), 2);
+
+        let service = BatchUnregistrationService::start(route_info_manager.clone(), 16);
+        for (broker_name, broker_addr) in [
+            ("broker-a", "127.0.0.1:10911"),
+            ("broker-b", "127.0.0.1:10921"),
+        ] {
+            assert!(service.submit(UnRegisterBrokerRequestHeader {
+                broker_name: broker_name.into(),
+                broker_addr: broker_addr.into(),
+                cluster_name: "DefaultCluster".into(),
+                broker_id: 0,
+            }));
+        }
+
+        for _ in 0..100 {
+            if route_info_manager.broker_addr_table.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(route_info_manager.broker_addr_table.is_empty());
+        assert!(route_info_manager.broker_live_table.is_empty());
+    }
+}
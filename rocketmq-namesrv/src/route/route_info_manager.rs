@@ -116,7 +116,7 @@ impl RouteInfoManager {
         broker_id: u64,
         ha_server_addr: CheetahString,
         zone_name: Option<CheetahString>,
-        _timeout_millis: Option<i64>,
+        timeout_millis: Option<i64>,
         enable_acting_master: Option<bool>,
         topic_config_serialize_wrapper: TopicConfigAndMappingSerializeWrapper,
         filter_server_list: Vec<String>,
@@ -246,8 +246,8 @@ impl RouteInfoManager {
                     .keys()
                     .map(|item| item.to_string())
                     .collect::<HashSet<String>>();
-                let to_delete_topics = new_topic_set
-                    .difference(&old_topic_set)
+                let to_delete_topics = old_topic_set
+                    .difference(&new_topic_set)
                     .map(|item| item.to_string())
                     .collect::<HashSet<String>>();
                 for to_delete_topic in to_delete_topics {
@@ -315,7 +315,7 @@ impl RouteInfoManager {
             broker_addr_info.clone(),
             BrokerLiveInfo::new(
                 get_current_millis() as i64,
-                DEFAULT_BROKER_CHANNEL_EXPIRED_TIME,
+                timeout_millis.unwrap_or(DEFAULT_BROKER_CHANNEL_EXPIRED_TIME),
                 topic_config_serialize_wrapper
                     .topic_config_serialize_wrapper
                     .data_version()
@@ -342,7 +342,7 @@ impl RouteInfoManager {
                     .get(BrokerAddrInfo::new(cluster_name.clone(), master_addr.clone()).as_ref());
                 if let Some(info) = master_livie_info {
                     result.ha_server_addr = info.ha_server_addr().clone();
-                    result.master_addr = info.ha_server_addr().clone();
+                    result.master_addr = master_addr.clone();
                 }
             }
         }
@@ -889,13 +889,25 @@ impl RouteInfoManager {
         }
     }
 
+    /// Evict the brokers whose last heartbeat is older than their heartbeat timeout.
     pub fn scan_not_active_broker(&mut self) {
-        for (broker_addr_info, broker_live_info) in self.broker_live_table.clone().iter() {
-            if broker_live_info.heartbeat_timeout_millis + broker_live_info.last_update_timestamp
-                < TimeUtils::get_current_millis() as i64
-            {
-                self.on_connection_disconnected(broker_addr_info);
-            }
+        let now = TimeUtils::get_current_millis() as i64;
+        let expired = self
+            .broker_live_table
+            .iter()
+            .filter(|(_, live_info)| {
+                live_info.last_update_timestamp + live_info.heartbeat_timeout_millis < now
+            })
+            .map(|(broker_addr_info, live_info)| {
+                (broker_addr_info.clone(), live_info.heartbeat_timeout_millis)
+            })
+            .collect::<Vec<_>>();
+        for (broker_addr_info, heartbeat_timeout_millis) in expired {
+            warn!(
+                "The broker channel expired, {} {}ms",
+                broker_addr_info, heartbeat_timeout_millis
+            );
+            self.on_connection_disconnected(&broker_addr_info);
         }
     }
 
@@ -947,6 +959,8 @@ impl RouteInfoManager {
         let mut remove_broker = HashSet::<CheetahString>::new();
         let mut reduced_broker = HashSet::<CheetahString>::new();
         let mut need_notify_broker_map = HashMap::<CheetahString, BrokerStatusChangeInfo>::new();
+        let lock = self.lock.clone();
+        let write = lock.write();
 
         for un_register_request in un_register_requests {
             let broker_name = &un_register_request.broker_name;
@@ -985,14 +999,14 @@ impl RouteInfoManager {
                         remove_broker_id_set.insert(*broker_id);
                     }
                 }
-                let _removed = if remove_broker_id_set.is_empty() {
-                    for broker_id in remove_broker_id_set {
-                        broker_data.broker_addrs_mut().remove(&broker_id);
-                    }
-                    true
-                } else {
-                    false
-                };
+                for broker_id in remove_broker_id_set {
+                    let removed = broker_data.broker_addrs_mut().remove(&broker_id);
+                    info!(
+                        "unregisterBroker, remove addr from brokerAddrTable {}, {}",
+                        if removed.is_some() { "OK" } else { "Fail" },
+                        broker_addr
+                    );
+                }
 
                 if broker_data.broker_addrs_mut().is_empty() {
                     self.broker_addr_table.remove(broker_name.as_str());
@@ -1023,6 +1037,7 @@ impl RouteInfoManager {
             }
         }
         self.clean_topic_by_un_register_requests(remove_broker, reduced_broker);
+        drop(write);
         if !need_notify_broker_map.is_empty() && self.namesrv_config.notify_min_broker_id_changed {
             for (broker_name, broker_status_change_info) in need_notify_broker_map {
                 let broker_data = self.broker_addr_table.get(&broker_name);
@@ -1051,7 +1066,7 @@ impl RouteInfoManager {
         for (topic, queue_data_map) in self.topic_queue_table.iter_mut() {
             for broker_name in &removed_broker {
                 if let Some(removed_qd) = queue_data_map.remove(broker_name) {
-                    println!(
+                    info!(
                         "removeTopicByBrokerName, remove one broker's topic {} {:?}",
                         topic, removed_qd
                     );
@@ -1059,7 +1074,7 @@ impl RouteInfoManager {
             }
 
             if queue_data_map.is_empty() {
-                println!(
+                info!(
                     "removeTopicByBrokerName, remove the topic all queue {}",
                     topic
                 );
@@ -1074,31 +1089,8 @@ impl RouteInfoManager {
                         .is_some_and(|b| b.enable_acting_master())
                     {
                         // Master has been unregistered, wipe the write perm
-                        let flag = {
-                            let broker_data = self.broker_addr_table.get(broker_name);
-                            if broker_data.is_none() {
-                                true
-                            } else {
-                                let broker_data_unwrap = broker_data.unwrap();
-                                if broker_data_unwrap.broker_addrs().is_empty() {
-                                    true
-                                } else {
-                                    broker_data_unwrap
-                                        .broker_addrs()
-                                        .keys()
-                                        .cloned()
-                                        .min()
-                                        .unwrap()
-                                        > 0
-                                }
-                            }
-                        };
-                        //if self.is_no_master_exists(broker_name.as_str()) {
-                        if flag {
-                            // Update the queue data's permission, assuming PermName is an enum
-                            // For simplicity, I'm using 0 as PERM_WRITE value, please replace it
-                            // with the actual value
-                            queue_data.perm &= !PermName::PERM_WRITE
+                        if Self::no_master_exists(&self.broker_addr_table, broker_name) {
+                            queue_data.perm &= !PermName::PERM_WRITE;
                         }
                     }
                 }
@@ -1109,22 +1101,15 @@ impl RouteInfoManager {
         }
     }
 
-    fn is_no_master_exists(&self, broker_name: &str) -> bool {
-        let broker_data = self.broker_addr_table.get(broker_name);
-        if broker_data.is_none() {
-            return true;
-        }
-        let broker_data_unwrap = broker_data.unwrap();
-        if broker_data_unwrap.broker_addrs().is_empty() {
-            return true;
+    fn no_master_exists(broker_addr_table: &BrokerAddrTable, broker_name: &str) -> bool {
+        match broker_addr_table.get(broker_name) {
+            None => true,
+            Some(broker_data) => broker_data
+                .broker_addrs()
+                .keys()
+                .min()
+                .map_or(true, |min_broker_id| *min_broker_id > 0),
         }
-        broker_data_unwrap
-            .broker_addrs()
-            .keys()
-            .cloned()
-            .min()
-            .unwrap()
-            > 0
     }
 
     pub fn connection_disconnected(&mut self, socket_addr: SocketAddr) {
@@ -1157,3 +1142,143 @@ impl RouteInfoManager {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+    use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+
+    use super::*;
+
+    fn new_route_info_manager() -> RouteInfoManager {
+        RouteInfoManager::new(
+            ArcMut::new(NamesrvConfig::default()),
+            ArcMut::new(RocketmqDefaultClient::new(
+                Arc::new(TokioClientConfig::default()),
+                DefaultRemotingRequestProcessor,
+            )),
+        )
+    }
+
+    fn register(
+        manager: &RouteInfoManager,
+        broker_addr: &str,
+        broker_id: u64,
+        timeout_millis: Option<i64>,
+    ) {
+        let mut wrapper = TopicConfigAndMappingSerializeWrapper::default();
+        for topic in ["TopicA", "TopicB"] {
+            wrapper
+                .topic_config_serialize_wrapper
+                .topic_config_table
+                .insert(topic.into(), TopicConfig::with_queues(topic, 4, 4));
+        }
+        manager.register_broker(
+            "DefaultCluster".into(),
+            broker_addr.into(),
+            "broker-a".into(),
+            broker_id,
+            broker_addr.into(),
+            None,
+            timeout_millis,
+            None,
+            wrapper,
+            vec![],
+            "127.0.0.1:10911".parse().unwrap(),
+        );
+    }
+
+    #[test]
+    fn register_broker_updates_route_tables() {
+        let manager = new_route_info_manager();
+        register(&manager, "127.0.0.1:10911", 0, None);
+
+        assert!(manager
+            .cluster_addr_table
+            .get("DefaultCluster")
+            .unwrap()
+            .contains("broker-a"));
+        assert_eq!(
+            manager
+                .broker_addr_table
+                .get("broker-a")
+                .unwrap()
+                .broker_addrs()[&0],
+            "127.0.0.1:10911"
+        );
+        assert_eq!(manager.topic_queue_table.len(), 2);
+        let live_info = manager
+            .broker_live_table
+            .get(&BrokerAddrInfo::new("DefaultCluster", "127.0.0.1:10911"))
+            .unwrap();
+        assert_eq!(
+            live_info.heartbeat_timeout_millis(),
+            DEFAULT_BROKER_CHANNEL_EXPIRED_TIME
+        );
+        assert!(manager.pickup_topic_route_data(&"TopicA".into()).is_some());
+    }
+
+    #[test]
+    fn scan_not_active_broker_evicts_expired_broker() {
+        let mut manager = new_route_info_manager();
+        register(&manager, "127.0.0.1:10911", 0, Some(-1));
+        register(&manager, "127.0.0.1:10921", 1, None);
+
+        manager.scan_not_active_broker();
+
+        assert!(!manager
+            .broker_live_table
+            .contains_key(&BrokerAddrInfo::new("DefaultCluster", "127.0.0.1:10911")));
+        assert!(manager
+            .broker_live_table
+            .contains_key(&BrokerAddrInfo::new("DefaultCluster", "127.0.0.1:10921")));
+        let broker_addrs = manager
+            .broker_addr_table
+            .get("broker-a")
+            .unwrap()
+            .broker_addrs()
+            .clone();
+        assert_eq!(broker_addrs.len(), 1);
+        assert!(broker_addrs.contains_key(&1));
+    }
+
+    #[test]
+    fn un_register_last_broker_removes_routes() {
+        let mut manager = new_route_info_manager();
+        register(&manager, "127.0.0.1:10911", 0, None);
+
+        let request_header = UnRegisterBrokerRequestHeader {
+            broker_name: "broker-a".into(),
+            broker_addr: "127.0.0.1:10911".into(),
+            cluster_name: "DefaultCluster".into(),
+            broker_id: 0,
+        };
+        manager.un_register_broker(vec![request_header]);
+
+        assert!(manager.broker_live_table.is_empty());
+        assert!(manager.broker_addr_table.is_empty());
+        assert!(manager.cluster_addr_table.is_empty());
+        assert!(manager.topic_queue_table.is_empty());
+    }
+
+    #[test]
+    fn wipe_and_add_write_perm_of_broker() {
+        let manager = new_route_info_manager();
+        register(&manager, "127.0.0.1:10911", 0, None);
+
+        let broker_name = CheetahString::from_static_str("broker-a");
+        assert_eq!(manager.wipe_write_perm_of_broker_by_lock(&broker_name), 2);
+        for queue_data_map in manager.topic_queue_table.values() {
+            assert!(!PermName::is_writeable(queue_data_map["broker-a"].perm));
+        }
+
+        assert_eq!(manager.add_write_perm_of_broker_by_lock(&broker_name), 2);
+        for queue_data_map in manager.topic_queue_table.values() {
+            assert!(PermName::is_writeable(queue_data_map["broker-a"].perm));
+        }
+        assert_eq!(
+            manager.wipe_write_perm_of_broker_by_lock(&"broker-b".into()),
+            0
+        );
+    }
+}
@@ -0,0 +1,79 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::path::Path;
+use std::sync::Arc;
+
+use rocketmq_rust::wait_for_signal;
+use tokio::net::TcpListener;
+use tracing::error;
+use tracing::info;
+
+use crate::config::ProxyConfig;
+use crate::grpc::server::GrpcServer;
+use crate::grpc::tls::build_tls_config;
+use crate::processor::messaging_processor::MessagingProcessor;
+use crate::service::messaging_service::MessagingService;
+use crate::Result;
+
+pub struct ProxyBootstrap {
+    proxy_config: Arc<ProxyConfig>,
+}
+
+impl ProxyBootstrap {
+    pub fn new(proxy_config: ProxyConfig) -> Self {
+        ProxyBootstrap {
+            proxy_config: Arc::new(proxy_config),
+        }
+    }
+
+    pub async fn boot(self) -> Result<()> {
+        let processor = Arc::new(MessagingProcessor::new(self.proxy_config.clone()));
+        processor.start().await;
+        let messaging_service = Arc::new(MessagingService::new(processor));
+        messaging_service.start();
+        let mut server = GrpcServer::new(
+            messaging_service,
+            self.proxy_config.max_message_size as usize,
+        );
+        if !self.proxy_config.tls_cert_path.is_empty() && !self.proxy_config.tls_key_path.is_empty()
+        {
+            server = server.with_tls_config(build_tls_config(
+                Path::new(&self.proxy_config.tls_cert_path),
+                Path::new(&self.proxy_config.tls_key_path),
+            )?);
+            info!("gRPC server TLS enabled");
+        }
+        let listener = TcpListener::bind((
+            self.proxy_config.bind_address.as_str(),
+            self.proxy_config.grpc_server_port,
+        ))
+        .await?;
+        info!(
+            "Rocketmq Proxy(Rust) started in {:?} mode",
+            self.proxy_config.proxy_mode
+        );
+        tokio::select! {
+            result = server.run(listener) => {
+                if let Err(e) = &result {
+                    error!("gRPC server stopped: {}", e);
+                }
+                result
+            }
+            _ = wait_for_signal() => Ok(()),
+        }
+    }
+}
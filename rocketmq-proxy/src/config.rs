@@ -0,0 +1,132 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use serde::Deserialize;
+
+/// How the proxy reaches the brokers behind it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum ProxyMode {
+    /// The proxy is deployed next to a single broker and forwards every request to it.
+    #[default]
+    #[serde(alias = "LOCAL", alias = "local")]
+    Local,
+    /// The proxy is deployed on its own and routes requests by the topic route in the name
+    /// server.
+    #[serde(alias = "CLUSTER", alias = "cluster")]
+    Cluster,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    #[serde(alias = "proxyMode")]
+    pub proxy_mode: ProxyMode,
+
+    #[serde(alias = "grpcServerPort")]
+    pub grpc_server_port: u16,
+
+    #[serde(alias = "bindAddress")]
+    pub bind_address: String,
+
+    #[serde(alias = "namesrvAddr")]
+    pub namesrv_addr: String,
+
+    /// Broker address used in [`ProxyMode::Local`].
+    #[serde(alias = "localBrokerAddr")]
+    pub local_broker_addr: String,
+
+    /// Producer group the proxy sends with when a client does not provide one.
+    #[serde(alias = "proxyProducerGroup")]
+    pub proxy_producer_group: String,
+
+    /// Interval of the heartbeats registering the proxy producer group on brokers.
+    #[serde(alias = "heartbeatIntervalMillis")]
+    pub heartbeat_interval_millis: u64,
+
+    /// PEM certificate chain of the gRPC server. TLS is enabled when both the certificate and
+    /// the key are set.
+    #[serde(alias = "tlsCertPath")]
+    pub tls_cert_path: String,
+
+    /// PEM private key of the gRPC server.
+    #[serde(alias = "tlsKeyPath")]
+    pub tls_key_path: String,
+
+    #[serde(alias = "remotingTimeoutMillis")]
+    pub remoting_timeout_millis: u64,
+
+    #[serde(alias = "longPollingReserveTimeMillis")]
+    pub long_polling_reserve_time_millis: u64,
+
+    #[serde(alias = "maxReceiveBatchSize")]
+    pub max_receive_batch_size: i32,
+
+    /// Invisible duration of received messages when the client does not request one.
+    #[serde(alias = "defaultInvisibleTimeMillis")]
+    pub default_invisible_time_millis: u64,
+
+    #[serde(alias = "maxInvisibleTimeMillis")]
+    pub max_invisible_time_millis: u64,
+
+    /// Deliveries of a message before it is sent to the dead letter queue, used when the
+    /// client does not report a backoff policy.
+    #[serde(alias = "maxDeliveryAttempts")]
+    pub max_delivery_attempts: i32,
+
+    #[serde(alias = "maxMessageSize")]
+    pub max_message_size: i32,
+
+    #[serde(alias = "enableTopicMessageTypeCheck")]
+    pub enable_topic_message_type_check: bool,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        ProxyConfig {
+            proxy_mode: ProxyMode::default(),
+            grpc_server_port: 8081,
+            bind_address: "0.0.0.0".to_string(),
+            namesrv_addr: "127.0.0.1:9876".to_string(),
+            local_broker_addr: "127.0.0.1:10911".to_string(),
+            proxy_producer_group: "PROXY_PRODUCER_GROUP".to_string(),
+            heartbeat_interval_millis: 30_000,
+            tls_cert_path: String::new(),
+            tls_key_path: String::new(),
+            remoting_timeout_millis: 3000,
+            long_polling_reserve_time_millis: 100,
+            max_receive_batch_size: 32,
+            default_invisible_time_millis: 60_000,
+            max_invisible_time_millis: 12 * 60 * 60 * 1000,
+            max_delivery_attempts: 17,
+            max_message_size: 4 * 1024 * 1024,
+            enable_topic_message_type_check: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_partial_config() {
+        let config: ProxyConfig =
+            serde_json::from_str(r#"{"proxyMode":"CLUSTER","grpcServerPort":9081}"#).unwrap();
+        assert_eq!(config.proxy_mode, ProxyMode::Cluster);
+        assert_eq!(config.grpc_server_port, 9081);
+        assert_eq!(config.remoting_timeout_millis, 3000);
+    }
+}
@@ -0,0 +1,314 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! gRPC server of `apache.rocketmq.v2.MessagingService`, serving the service generated from
+//! `service.proto`. Methods the proxy does not serve answer `UNIMPLEMENTED`.
+
+use std::sync::Arc;
+
+use rocketmq_remoting::grpc::v2;
+use rocketmq_remoting::grpc::v2::messaging_service_server;
+use rocketmq_remoting::grpc::v2::messaging_service_server::MessagingServiceServer;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::codegen::BoxStream;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::transport::ServerTlsConfig;
+use tonic::Request;
+use tonic::Response;
+use tonic::Status;
+use tonic::Streaming;
+use tracing::debug;
+use tracing::info;
+
+use crate::proxy_context::ProxyContext;
+use crate::proxy_error::ProxyError;
+use crate::service::messaging_service::MessagingService;
+use crate::Result;
+
+const INITIAL_WINDOW_SIZE: u32 = 1024 * 1024;
+const INITIAL_CONNECTION_WINDOW_SIZE: u32 = 8 * 1024 * 1024;
+/// Commands buffered for a telemetry stream before the writer waits for the client.
+const TELEMETRY_CHANNEL_CAPACITY: usize = 64;
+
+pub struct GrpcServer {
+    messaging_service: Arc<MessagingService>,
+    max_message_size: usize,
+    tls_config: Option<ServerTlsConfig>,
+}
+
+impl GrpcServer {
+    pub fn new(messaging_service: Arc<MessagingService>, max_message_size: usize) -> Self {
+        GrpcServer {
+            messaging_service,
+            max_message_size,
+            tls_config: None,
+        }
+    }
+
+    /// Serves connections over TLS instead of plaintext HTTP/2.
+    pub fn with_tls_config(mut self, tls_config: ServerTlsConfig) -> Self {
+        self.tls_config = Some(tls_config);
+        self
+    }
+
+    /// Accepts connections until the listener fails.
+    pub async fn run(self, listener: TcpListener) -> Result<()> {
+        info!("gRPC server listening on {}", listener.local_addr()?);
+        let incoming = TcpIncoming::from_listener(listener, true, None)
+            .map_err(|e| ProxyError::IoError(std::io::Error::other(e)))?;
+        let mut builder = Server::builder()
+            .initial_stream_window_size(INITIAL_WINDOW_SIZE)
+            .initial_connection_window_size(INITIAL_CONNECTION_WINDOW_SIZE);
+        if let Some(tls_config) = self.tls_config {
+            builder = builder.tls_config(tls_config)?;
+        }
+        let service = MessagingServiceServer::new(GrpcMessagingService {
+            messaging_service: self.messaging_service,
+        })
+        .max_decoding_message_size(self.max_message_size)
+        .max_encoding_message_size(self.max_message_size);
+        builder
+            .add_service(service)
+            .serve_with_incoming(incoming)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Adapts [`MessagingService`] to the service trait generated by tonic.
+struct GrpcMessagingService {
+    messaging_service: Arc<MessagingService>,
+}
+
+fn context<T>(request: &Request<T>) -> ProxyContext {
+    ProxyContext::from_metadata(request.metadata(), request.remote_addr())
+}
+
+#[tonic::async_trait]
+impl messaging_service_server::MessagingService for GrpcMessagingService {
+    async fn query_route(
+        &self,
+        request: Request<v2::QueryRouteRequest>,
+    ) -> std::result::Result<Response<v2::QueryRouteResponse>, Status> {
+        let ctx = context(&request);
+        let response = self
+            .messaging_service
+            .query_route(&ctx, request.into_inner())
+            .await;
+        Ok(Response::new(response))
+    }
+
+    async fn heartbeat(
+        &self,
+        request: Request<v2::HeartbeatRequest>,
+    ) -> std::result::Result<Response<v2::HeartbeatResponse>, Status> {
+        let ctx = context(&request);
+        let response = self
+            .messaging_service
+            .heartbeat(&ctx, request.into_inner())
+            .await;
+        Ok(Response::new(response))
+    }
+
+    async fn send_message(
+        &self,
+        request: Request<v2::SendMessageRequest>,
+    ) -> std::result::Result<Response<v2::SendMessageResponse>, Status> {
+        let ctx = context(&request);
+        let response = self
+            .messaging_service
+            .send_message(&ctx, request.into_inner())
+            .await;
+        Ok(Response::new(response))
+    }
+
+    async fn query_assignment(
+        &self,
+        request: Request<v2::QueryAssignmentRequest>,
+    ) -> std::result::Result<Response<v2::QueryAssignmentResponse>, Status> {
+        let ctx = context(&request);
+        let response = self
+            .messaging_service
+            .query_assignment(&ctx, request.into_inner())
+            .await;
+        Ok(Response::new(response))
+    }
+
+    async fn receive_message(
+        &self,
+        request: Request<v2::ReceiveMessageRequest>,
+    ) -> std::result::Result<Response<BoxStream<v2::ReceiveMessageResponse>>, Status> {
+        let ctx = context(&request);
+        let responses = self
+            .messaging_service
+            .receive_message(&ctx, request.into_inner())
+            .await;
+        let stream: BoxStream<v2::ReceiveMessageResponse> =
+            Box::pin(tokio_stream::iter(responses.into_iter().map(Ok)));
+        Ok(Response::new(stream))
+    }
+
+    async fn ack_message(
+        &self,
+        request: Request<v2::AckMessageRequest>,
+    ) -> std::result::Result<Response<v2::AckMessageResponse>, Status> {
+        let ctx = context(&request);
+        let response = self
+            .messaging_service
+            .ack_message(&ctx, request.into_inner())
+            .await;
+        Ok(Response::new(response))
+    }
+
+    async fn end_transaction(
+        &self,
+        request: Request<v2::EndTransactionRequest>,
+    ) -> std::result::Result<Response<v2::EndTransactionResponse>, Status> {
+        let ctx = context(&request);
+        let response = self
+            .messaging_service
+            .end_transaction(&ctx, request.into_inner())
+            .await;
+        Ok(Response::new(response))
+    }
+
+    /// Serves the bidirectional telemetry stream, answering each command as it arrives. The
+    /// proxy also writes transaction checks to the streams of producers.
+    async fn telemetry(
+        &self,
+        request: Request<Streaming<v2::TelemetryCommand>>,
+    ) -> std::result::Result<Response<BoxStream<v2::TelemetryCommand>>, Status> {
+        let ctx = context(&request);
+        let mut commands = request.into_inner();
+        let (tx, rx) = mpsc::channel(TELEMETRY_CHANNEL_CAPACITY);
+        let messaging_service = self.messaging_service.clone();
+        tokio::spawn(async move {
+            loop {
+                let command = match commands.message().await {
+                    Ok(Some(command)) => command,
+                    Ok(None) => break,
+                    Err(status) => {
+                        debug!(
+                            "telemetry stream of client {} failed: {}",
+                            ctx.client_id, status
+                        );
+                        break;
+                    }
+                };
+                if let Some(reply) = messaging_service.telemetry(&ctx, command, &tx).await {
+                    if tx.send(reply).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            messaging_service.close_telemetry(&ctx, &tx);
+        });
+        let stream: BoxStream<v2::TelemetryCommand> = Box::pin(ReceiverStream::new(rx).map(Ok));
+        Ok(Response::new(stream))
+    }
+
+    async fn notify_client_termination(
+        &self,
+        request: Request<v2::NotifyClientTerminationRequest>,
+    ) -> std::result::Result<Response<v2::NotifyClientTerminationResponse>, Status> {
+        let ctx = context(&request);
+        let response = self
+            .messaging_service
+            .notify_client_termination(&ctx, request.into_inner())
+            .await;
+        Ok(Response::new(response))
+    }
+
+    async fn change_invisible_duration(
+        &self,
+        request: Request<v2::ChangeInvisibleDurationRequest>,
+    ) -> std::result::Result<Response<v2::ChangeInvisibleDurationResponse>, Status> {
+        let ctx = context(&request);
+        let response = self
+            .messaging_service
+            .change_invisible_duration(&ctx, request.into_inner())
+            .await;
+        Ok(Response::new(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rocketmq_remoting::grpc::v2::messaging_service_client::MessagingServiceClient;
+    use rocketmq_remoting::grpc::v2::settings::PubSub;
+    use rocketmq_remoting::grpc::v2::telemetry_command::Command;
+    use tonic::transport::Channel;
+    use tonic::Code;
+
+    use super::*;
+    use crate::config::ProxyConfig;
+    use crate::processor::messaging_processor::MessagingProcessor;
+
+    #[test]
+    fn serve_telemetry_and_reject_unknown_methods() {
+        let processor = Arc::new(MessagingProcessor::new(Arc::new(ProxyConfig::default())));
+        let service = Arc::new(MessagingService::new(processor));
+        let server = GrpcServer::new(service.clone(), 1024 * 1024);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async move {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(server.run(listener));
+            let channel = Channel::from_shared(format!("http://{}", addr))
+                .unwrap()
+                .connect_lazy();
+            let mut client = MessagingServiceClient::new(channel);
+
+            let settings = v2::TelemetryCommand {
+                status: None,
+                command: Some(Command::Settings(v2::Settings {
+                    client_type: Some(v2::ClientType::Producer as i32),
+                    pub_sub: Some(PubSub::Publishing(v2::Publishing::default())),
+                    ..Default::default()
+                })),
+            };
+            let mut request = Request::new(tokio_stream::iter(vec![settings]));
+            request
+                .metadata_mut()
+                .insert(ProxyContext::CLIENT_ID_KEY, "client-1".parse().unwrap());
+            let mut replies = client.telemetry(request).await.unwrap().into_inner();
+            let reply = replies.message().await.unwrap().unwrap();
+            assert_eq!(reply.status.unwrap().code, v2::Code::Ok as i32);
+            match reply.command {
+                Some(Command::Settings(v2::Settings {
+                    pub_sub: Some(PubSub::Publishing(publishing)),
+                    ..
+                })) => assert_eq!(publishing.max_body_size, 4 * 1024 * 1024),
+                command => panic!("unexpected telemetry reply {:?}", command),
+            }
+
+            let status = client
+                .pull_message(v2::PullMessageRequest::default())
+                .await
+                .err()
+                .unwrap();
+            assert_eq!(status.code(), Code::Unimplemented);
+        });
+        drop(runtime);
+        drop(service);
+    }
+}
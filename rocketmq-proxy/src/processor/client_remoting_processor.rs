@@ -0,0 +1,157 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_common::common::message::message_decoder;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::check_transaction_state_request_header::CheckTransactionStateRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_remoting::runtime::processor::RequestProcessor;
+use rocketmq_remoting::Result;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::warn;
+
+use crate::processor::receipt_handle::TransactionId;
+
+/// A broker asking the producer of a half message whether to commit or roll it back.
+#[derive(Debug, Clone)]
+pub struct TransactionCheck {
+    pub message: MessageExt,
+    pub transaction_id: TransactionId,
+}
+
+/// Serves the requests brokers send to the proxy over its remoting connections. Transaction
+/// checks of half messages are handed to [`MessagingService`], which relays them to a producer
+/// over its telemetry stream.
+///
+/// [`MessagingService`]: crate::service::messaging_service::MessagingService
+#[derive(Clone)]
+pub struct ProxyClientRemotingProcessor {
+    transaction_check_sender: mpsc::Sender<TransactionCheck>,
+}
+
+impl ProxyClientRemotingProcessor {
+    pub fn new(transaction_check_sender: mpsc::Sender<TransactionCheck>) -> Self {
+        ProxyClientRemotingProcessor {
+            transaction_check_sender,
+        }
+    }
+
+    fn check_transaction_state(&self, request: RemotingCommand) -> Result<Option<RemotingCommand>> {
+        let request_header =
+            request.decode_command_custom_header::<CheckTransactionStateRequestHeader>()?;
+        let broker_name = request_header
+            .rpc_request_header
+            .and_then(|rpc_request_header| rpc_request_header.broker_name)
+            .filter(|broker_name| !broker_name.is_empty());
+        let Some(broker_name) = broker_name else {
+            warn!("transaction check without broker name, ignore it");
+            return Ok(None);
+        };
+        let message = request.body().clone().and_then(|mut body| {
+            message_decoder::decode(&mut body, true, true, false, false, false)
+        });
+        let Some(message) = message else {
+            warn!(
+                "transaction check of broker {} without message, ignore it",
+                broker_name
+            );
+            return Ok(None);
+        };
+        let check = TransactionCheck {
+            message,
+            transaction_id: TransactionId::new(
+                broker_name,
+                request_header.commit_log_offset as u64,
+                request_header.tran_state_table_offset as u64,
+            ),
+        };
+        // The broker checks the half message again later, so a check is dropped rather than
+        // holding up the connection when producers fall behind.
+        if let Err(e) = self.transaction_check_sender.try_send(check) {
+            warn!("drop transaction check: {}", e);
+        }
+        Ok(None)
+    }
+}
+
+impl RequestProcessor for ProxyClientRemotingProcessor {
+    async fn process_request(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        request: RemotingCommand,
+    ) -> Result<Option<RemotingCommand>> {
+        match RequestCode::from(request.code()) {
+            RequestCode::CheckTransactionState => self.check_transaction_state(request),
+            request_code => {
+                debug!("ignore request {:?} of broker", request_code);
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use cheetah_string::CheetahString;
+    use rocketmq_common::common::message::MessageTrait;
+    use rocketmq_remoting::rpc::rpc_request_header::RpcRequestHeader;
+
+    use super::*;
+
+    #[test]
+    fn transaction_check_is_handed_over() {
+        let (sender, mut receiver) = mpsc::channel(1);
+        let processor = ProxyClientRemotingProcessor::new(sender);
+        let mut message = MessageExt::default();
+        message.set_topic(CheetahString::from_static_str("TopicTest"));
+        message.set_body(Bytes::from_static(b"hello"));
+        let body = message_decoder::encode(&message, false).unwrap();
+        let mut request = RemotingCommand::create_request_command(
+            RequestCode::CheckTransactionState,
+            CheckTransactionStateRequestHeader {
+                topic: Some(CheetahString::from_static_str("TopicTest")),
+                tran_state_table_offset: 7,
+                commit_log_offset: 1024,
+                msg_id: None,
+                transaction_id: None,
+                offset_msg_id: None,
+                rpc_request_header: Some(RpcRequestHeader {
+                    broker_name: Some(CheetahString::from_static_str("broker-a")),
+                    ..Default::default()
+                }),
+            },
+        )
+        .set_body(body);
+        request.make_custom_header_to_net();
+
+        assert!(processor
+            .check_transaction_state(request)
+            .unwrap()
+            .is_none());
+        let check = receiver.try_recv().unwrap();
+        assert_eq!(check.message.get_topic(), "TopicTest");
+        assert_eq!(
+            check.transaction_id,
+            TransactionId::new(CheetahString::from_static_str("broker-a"), 1024, 7)
+        );
+    }
+}
@@ -0,0 +1,639 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Weak;
+use std::time::Duration;
+use std::time::Instant;
+
+use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::common::constant::consume_init_mode::ConsumeInitMode;
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::message::message_decoder;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
+use rocketmq_common::utils::network_util::NetworkUtil;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+use rocketmq_remoting::clients::RemotingClient;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::header::ack_message_request_header::AckMessageRequestHeader;
+use rocketmq_remoting::protocol::header::change_invisible_time_request_header::ChangeInvisibleTimeRequestHeader;
+use rocketmq_remoting::protocol::header::change_invisible_time_response_header::ChangeInvisibleTimeResponseHeader;
+use rocketmq_remoting::protocol::header::client_request_header::GetRouteInfoRequestHeader;
+use rocketmq_remoting::protocol::header::consumer_send_msg_back_request_header::ConsumerSendMsgBackRequestHeader;
+use rocketmq_remoting::protocol::header::end_transaction_request_header::EndTransactionRequestHeader;
+use rocketmq_remoting::protocol::header::extra_info_util::ExtraInfoUtil;
+use rocketmq_remoting::protocol::header::heartbeat_request_header::HeartbeatRequestHeader;
+use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header::SendMessageRequestHeader;
+use rocketmq_remoting::protocol::header::message_operation_header::send_message_response_header::SendMessageResponseHeader;
+use rocketmq_remoting::protocol::header::pop_message_request_header::PopMessageRequestHeader;
+use rocketmq_remoting::protocol::header::pop_message_response_header::PopMessageResponseHeader;
+use rocketmq_remoting::protocol::heartbeat::heartbeat_data::HeartbeatData;
+use rocketmq_remoting::protocol::heartbeat::producer_data::ProducerData;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use rocketmq_remoting::remoting::RemotingService;
+use rocketmq_remoting::rpc::rpc_request_header::RpcRequestHeader;
+use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+use rocketmq_rust::ArcMut;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::config::ProxyConfig;
+use crate::config::ProxyMode;
+use crate::processor::client_remoting_processor::ProxyClientRemotingProcessor;
+use crate::processor::client_remoting_processor::TransactionCheck;
+use crate::processor::receipt_handle::ReceiptHandle;
+use crate::processor::receipt_handle::TransactionId;
+use crate::proxy_error::ProxyError;
+use crate::Result;
+
+const TOPIC_ROUTE_CACHE_MILLIS: u64 = 30_000;
+/// Transaction checks buffered until they are relayed to producers.
+const TRANSACTION_CHECK_CAPACITY: usize = 1024;
+
+/// Forwards the requests of gRPC clients to brokers over the remoting protocol.
+pub struct MessagingProcessor {
+    proxy_config: Arc<ProxyConfig>,
+    remoting_client: ArcMut<RocketmqDefaultClient<ProxyClientRemotingProcessor>>,
+    /// Client id the proxy registers its producer group with on brokers.
+    client_id: CheetahString,
+    transaction_check_receiver: Mutex<Option<mpsc::Receiver<TransactionCheck>>>,
+    topic_route_table: Mutex<HashMap<CheetahString, (Instant, TopicRouteData)>>,
+    send_queue_index: AtomicUsize,
+}
+
+impl MessagingProcessor {
+    pub fn new(proxy_config: Arc<ProxyConfig>) -> Self {
+        let (transaction_check_sender, transaction_check_receiver) =
+            mpsc::channel(TRANSACTION_CHECK_CAPACITY);
+        let remoting_client = ArcMut::new(RocketmqDefaultClient::new(
+            Arc::new(TokioClientConfig::default()),
+            ProxyClientRemotingProcessor::new(transaction_check_sender),
+        ));
+        let client_id = format!(
+            "{}@{}",
+            NetworkUtil::get_local_address().unwrap_or_default(),
+            std::process::id()
+        );
+        MessagingProcessor {
+            proxy_config,
+            remoting_client,
+            client_id: CheetahString::from_string(client_id),
+            transaction_check_receiver: Mutex::new(Some(transaction_check_receiver)),
+            topic_route_table: Mutex::new(HashMap::new()),
+            send_queue_index: AtomicUsize::new(0),
+        }
+    }
+
+    pub async fn start(self: &Arc<Self>) {
+        let wrapper = ArcMut::downgrade(&self.remoting_client);
+        self.remoting_client.start(wrapper).await;
+        let namesrv_addr_list = self
+            .proxy_config
+            .namesrv_addr
+            .split(';')
+            .filter(|addr| !addr.is_empty())
+            .map(CheetahString::from_slice)
+            .collect();
+        self.remoting_client
+            .update_name_server_address_list(namesrv_addr_list)
+            .await;
+        let processor = Arc::downgrade(self);
+        let heartbeat_interval = Duration::from_millis(self.proxy_config.heartbeat_interval_millis);
+        tokio::spawn(Self::send_heartbeat_periodically(
+            processor,
+            heartbeat_interval,
+        ));
+    }
+
+    /// Takes the transaction checks brokers send for half messages of the proxy producer group.
+    /// Only the first caller gets them.
+    pub fn take_transaction_check_receiver(&self) -> Option<mpsc::Receiver<TransactionCheck>> {
+        self.transaction_check_receiver.lock().take()
+    }
+
+    async fn send_heartbeat_periodically(processor: Weak<Self>, heartbeat_interval: Duration) {
+        let mut interval = tokio::time::interval(heartbeat_interval);
+        loop {
+            interval.tick().await;
+            let Some(processor) = processor.upgrade() else {
+                return;
+            };
+            processor.send_heartbeat_to_all_brokers().await;
+        }
+    }
+
+    /// Registers the proxy producer group on every master broker the proxy knows of, so brokers
+    /// can check the state of half messages sent through the proxy.
+    async fn send_heartbeat_to_all_brokers(&self) {
+        let heartbeat_data = HeartbeatData {
+            client_id: self.client_id.clone(),
+            producer_data_set: HashSet::from([ProducerData {
+                group_name: CheetahString::from_slice(&self.proxy_config.proxy_producer_group),
+            }]),
+            ..Default::default()
+        };
+        let body = match heartbeat_data.encode() {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("encode heartbeat failed: {}", e);
+                return;
+            }
+        };
+        for broker_addr in self.master_broker_addrs() {
+            let request = RemotingCommand::create_request_command(
+                RequestCode::HeartBeat,
+                HeartbeatRequestHeader::default(),
+            )
+            .set_body(body.clone());
+            match self
+                .remoting_client
+                .invoke_async(Some(&broker_addr), request, self.timeout_millis())
+                .await
+            {
+                Ok(response) if ResponseCode::from(response.code()) == ResponseCode::Success => {}
+                Ok(response) => warn!(
+                    "heartbeat to broker {} failed: {}",
+                    broker_addr,
+                    broker_error(&response, &broker_addr)
+                ),
+                Err(e) => warn!("heartbeat to broker {} failed: {}", broker_addr, e),
+            }
+        }
+    }
+
+    /// Master brokers of the cached topic routes. In [`ProxyMode::Local`] only the broker the
+    /// proxy is deployed with.
+    fn master_broker_addrs(&self) -> HashSet<CheetahString> {
+        match self.proxy_config.proxy_mode {
+            ProxyMode::Local => HashSet::from([CheetahString::from_slice(
+                &self.proxy_config.local_broker_addr,
+            )]),
+            ProxyMode::Cluster => self
+                .topic_route_table
+                .lock()
+                .values()
+                .flat_map(|(_, route)| route.broker_datas.iter())
+                .filter_map(|broker_data| broker_data.broker_addrs().get(&mix_all::MASTER_ID))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn proxy_config(&self) -> &ProxyConfig {
+        &self.proxy_config
+    }
+
+    fn timeout_millis(&self) -> u64 {
+        self.proxy_config.remoting_timeout_millis
+    }
+
+    pub async fn get_topic_route(&self, topic: &CheetahString) -> Result<TopicRouteData> {
+        if let Some((update_time, route)) = self.topic_route_table.lock().get(topic) {
+            if update_time.elapsed() < Duration::from_millis(TOPIC_ROUTE_CACHE_MILLIS) {
+                return Ok(route.clone());
+            }
+        }
+        let header = GetRouteInfoRequestHeader {
+            topic: topic.clone(),
+            ..Default::default()
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::GetRouteinfoByTopic, header);
+        let response = self
+            .remoting_client
+            .invoke_async(None, request, self.timeout_millis())
+            .await?;
+        match ResponseCode::from(response.code()) {
+            ResponseCode::Success => {
+                let route = response
+                    .body()
+                    .as_ref()
+                    .and_then(|body| TopicRouteData::decode(body).ok())
+                    .ok_or_else(|| ProxyError::TopicRouteNotFound(topic.to_string()))?;
+                self.topic_route_table
+                    .lock()
+                    .insert(topic.clone(), (Instant::now(), route.clone()));
+                Ok(route)
+            }
+            ResponseCode::TopicNotExist => {
+                self.topic_route_table.lock().remove(topic);
+                Err(ProxyError::TopicRouteNotFound(topic.to_string()))
+            }
+            _ => Err(broker_error(&response, "")),
+        }
+    }
+
+    /// Address of the master of `broker_name`. In [`ProxyMode::Local`] every broker is served
+    /// by the broker the proxy is deployed with.
+    pub fn broker_addr(&self, route: &TopicRouteData, broker_name: &str) -> Option<CheetahString> {
+        match self.proxy_config.proxy_mode {
+            ProxyMode::Local => Some(CheetahString::from_slice(
+                &self.proxy_config.local_broker_addr,
+            )),
+            ProxyMode::Cluster => route
+                .broker_datas
+                .iter()
+                .find(|broker_data| broker_data.broker_name() == broker_name)
+                .and_then(|broker_data| broker_data.select_broker_addr()),
+        }
+    }
+
+    /// Picks a writable queue for a message. Messages of the same `message_group` always go to
+    /// the same queue, others are spread round robin.
+    pub fn select_write_queue(
+        &self,
+        route: &TopicRouteData,
+        message_group: Option<&str>,
+    ) -> Option<(CheetahString, i32)> {
+        let mut queue_datas = route
+            .queue_datas
+            .iter()
+            .filter(|queue_data| {
+                PermName::is_writeable(queue_data.perm) && queue_data.write_queue_nums > 0
+            })
+            .collect::<Vec<_>>();
+        queue_datas.sort_by(|a, b| a.broker_name.cmp(&b.broker_name));
+        let queues = queue_datas
+            .iter()
+            .flat_map(|queue_data| {
+                (0..queue_data.write_queue_nums as i32)
+                    .map(move |queue_id| (queue_data.broker_name.clone(), queue_id))
+            })
+            .collect::<Vec<_>>();
+        if queues.is_empty() {
+            return None;
+        }
+        let index = match message_group {
+            Some(message_group) => {
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                message_group.hash(&mut hasher);
+                hasher.finish() as usize
+            }
+            None => self.send_queue_index.fetch_add(1, Ordering::Relaxed),
+        };
+        Some(queues[index % queues.len()].clone())
+    }
+
+    /// Sends a message to a broker, returning the response code and header of the broker.
+    pub async fn send_message(
+        &self,
+        broker_addr: &CheetahString,
+        request_header: SendMessageRequestHeader,
+        body: Vec<u8>,
+    ) -> Result<(ResponseCode, SendMessageResponseHeader)> {
+        let request =
+            RemotingCommand::create_request_command(RequestCode::SendMessage, request_header)
+                .set_body(body);
+        let response = self
+            .remoting_client
+            .invoke_async(Some(broker_addr), request, self.timeout_millis())
+            .await?;
+        let response_code = ResponseCode::from(response.code());
+        match response_code {
+            ResponseCode::Success
+            | ResponseCode::FlushDiskTimeout
+            | ResponseCode::FlushSlaveTimeout
+            | ResponseCode::SlaveNotAvailable => {
+                let response_header = response
+                    .decode_command_custom_header_fast::<SendMessageResponseHeader>()
+                    .map_err(|_| broker_error(&response, broker_addr))?;
+                Ok((response_code, response_header))
+            }
+            _ => Err(broker_error(&response, broker_addr)),
+        }
+    }
+
+    /// Pops up to `batch_size` messages of one queue, they stay invisible to the other consumers
+    /// of the group for `invisible_duration` unless they are acked. Like the Java client, every
+    /// message carries the `POP_CK` property its receipt handle is built from and the topic of
+    /// the request, also when it was popped from the group's retry topic.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn pop_messages(
+        &self,
+        broker_addr: &CheetahString,
+        broker_name: &CheetahString,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+        expression_type: &str,
+        expression: &CheetahString,
+        batch_size: i32,
+        invisible_duration: Duration,
+        long_polling_timeout_millis: u64,
+    ) -> Result<Vec<MessageExt>> {
+        let poll_time = long_polling_timeout_millis
+            .saturating_sub(self.proxy_config.long_polling_reserve_time_millis);
+        let request_header = PopMessageRequestHeader {
+            consumer_group: group.clone(),
+            topic: topic.clone(),
+            queue_id,
+            max_msg_nums: batch_size,
+            invisible_time: invisible_duration.as_millis() as i64,
+            poll_time: poll_time as i64,
+            born_time: get_current_millis() as i64,
+            init_mode: ConsumeInitMode::MAX,
+            exp_type: Some(CheetahString::from_slice(expression_type)),
+            exp: Some(expression.clone()),
+            order: Some(false),
+            attempt_id: None,
+            topic_request_header: None,
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::PopMessage, request_header);
+        let mut response = self
+            .remoting_client
+            .invoke_async(
+                Some(broker_addr),
+                request,
+                poll_time + self.timeout_millis(),
+            )
+            .await?;
+        match ResponseCode::from(response.code()) {
+            ResponseCode::Success => {}
+            ResponseCode::PullNotFound | ResponseCode::PollingTimeout => return Ok(vec![]),
+            _ => return Err(broker_error(&response, broker_addr)),
+        }
+        let response_header = response
+            .decode_command_custom_header::<PopMessageResponseHeader>()
+            .map_err(|_| broker_error(&response, broker_addr))?;
+        let start_offset_info = response_header
+            .start_offset_info
+            .as_deref()
+            .filter(|info| !info.is_empty())
+            .map(ExtraInfoUtil::parse_start_offset_info)
+            .transpose()
+            .map_err(|_| broker_error(&response, broker_addr))?
+            .unwrap_or_default();
+        let mut messages = match response.take_body() {
+            Some(mut body) => message_decoder::decodes_batch(&mut body, true, true),
+            None => vec![],
+        };
+        for message in messages.iter_mut() {
+            let key = ExtraInfoUtil::get_start_offset_info_map_key(
+                message.topic(),
+                message.queue_id() as i64,
+            );
+            let start_offset = start_offset_info
+                .get(&key)
+                .copied()
+                .unwrap_or(message.queue_offset());
+            let pop_ck = ExtraInfoUtil::build_extra_info_with_msg_queue_offset(
+                start_offset,
+                response_header.pop_time as i64,
+                response_header.invisible_time as i64,
+                response_header.revive_qid as i32,
+                message.topic(),
+                broker_name,
+                message.queue_id(),
+                message.queue_offset(),
+            );
+            message.put_property(
+                CheetahString::from_static_str(MessageConst::PROPERTY_POP_CK),
+                CheetahString::from_string(pop_ck),
+            );
+            let first_pop_time =
+                CheetahString::from_static_str(MessageConst::PROPERTY_FIRST_POP_TIME);
+            if message.get_property(&first_pop_time).is_none() {
+                message.put_property(
+                    first_pop_time,
+                    CheetahString::from_string(response_header.pop_time.to_string()),
+                );
+            }
+            message.set_broker_name(broker_name.clone());
+            message.set_topic(topic.clone());
+        }
+        Ok(messages)
+    }
+
+    /// Makes a popped message invisible for `invisible_duration` from now on, returning the
+    /// receipt handle the client has to use from then on.
+    pub async fn change_invisible_duration(
+        &self,
+        broker_addr: &CheetahString,
+        group: &CheetahString,
+        topic: &CheetahString,
+        receipt_handle: &ReceiptHandle,
+        invisible_duration: Duration,
+    ) -> Result<ReceiptHandle> {
+        let request_header = ChangeInvisibleTimeRequestHeader {
+            consumer_group: group.clone(),
+            topic: real_topic(receipt_handle, topic, group)?,
+            queue_id: receipt_handle.queue_id,
+            extra_info: CheetahString::from_string(receipt_handle.extra_info()),
+            offset: receipt_handle.offset,
+            invisible_time: invisible_duration.as_millis() as i64,
+            topic_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::ChangeMessageInvisibleTime,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(Some(broker_addr), request, self.timeout_millis())
+            .await?;
+        if ResponseCode::from(response.code()) != ResponseCode::Success {
+            return Err(broker_error(&response, broker_addr));
+        }
+        let response_header = response
+            .decode_command_custom_header::<ChangeInvisibleTimeResponseHeader>()
+            .map_err(|_| broker_error(&response, broker_addr))?;
+        Ok(ReceiptHandle {
+            retrieve_time: response_header.pop_time,
+            invisible_time: response_header.invisible_time,
+            revive_queue_id: response_header.revive_qid,
+            ..receipt_handle.clone()
+        })
+    }
+
+    /// Acks a popped message so the broker does not deliver it again.
+    pub async fn ack_message(
+        &self,
+        broker_addr: &CheetahString,
+        group: &CheetahString,
+        topic: &CheetahString,
+        receipt_handle: &ReceiptHandle,
+    ) -> Result<()> {
+        let request_header = AckMessageRequestHeader {
+            consumer_group: group.clone(),
+            topic: real_topic(receipt_handle, topic, group)?,
+            queue_id: receipt_handle.queue_id,
+            extra_info: CheetahString::from_string(receipt_handle.extra_info()),
+            offset: receipt_handle.offset,
+            topic_request_header: None,
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::AckMessage, request_header);
+        let response = self
+            .remoting_client
+            .invoke_async(Some(broker_addr), request, self.timeout_millis())
+            .await?;
+        if ResponseCode::from(response.code()) != ResponseCode::Success {
+            return Err(broker_error(&response, broker_addr));
+        }
+        Ok(())
+    }
+
+    /// Sends a popped message which used up its delivery attempts to the dead letter queue of
+    /// the group, it still has to be acked afterwards.
+    pub async fn forward_message_to_dead_letter_queue(
+        &self,
+        broker_addr: &CheetahString,
+        group: &CheetahString,
+        topic: &CheetahString,
+        message_id: &CheetahString,
+        receipt_handle: &ReceiptHandle,
+        max_reconsume_times: i32,
+    ) -> Result<()> {
+        let request_header = ConsumerSendMsgBackRequestHeader {
+            offset: receipt_handle.commit_log_offset,
+            group: group.clone(),
+            delay_level: -1,
+            origin_msg_id: Some(message_id.clone()),
+            origin_topic: Some(topic.clone()),
+            unit_mode: false,
+            max_reconsume_times: Some(max_reconsume_times),
+            rpc_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::ConsumerSendMsgBack,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(Some(broker_addr), request, self.timeout_millis())
+            .await?;
+        if ResponseCode::from(response.code()) != ResponseCode::Success {
+            return Err(broker_error(&response, broker_addr));
+        }
+        Ok(())
+    }
+
+    /// Commits or rolls back a half message.
+    pub async fn end_transaction(
+        &self,
+        broker_addr: &CheetahString,
+        topic: &CheetahString,
+        message_id: &CheetahString,
+        transaction_id: &TransactionId,
+        commit: bool,
+        from_transaction_check: bool,
+    ) {
+        let request_header = EndTransactionRequestHeader {
+            topic: topic.clone(),
+            producer_group: CheetahString::from_slice(&self.proxy_config.proxy_producer_group),
+            tran_state_table_offset: transaction_id.tran_state_table_offset,
+            commit_log_offset: transaction_id.commit_log_offset,
+            commit_or_rollback: if commit {
+                MessageSysFlag::TRANSACTION_COMMIT_TYPE
+            } else {
+                MessageSysFlag::TRANSACTION_ROLLBACK_TYPE
+            },
+            from_transaction_check,
+            msg_id: message_id.clone(),
+            transaction_id: Some(CheetahString::from_string(transaction_id.to_string())),
+            rpc_request_header: RpcRequestHeader {
+                broker_name: Some(transaction_id.broker_name.clone()),
+                ..Default::default()
+            },
+        };
+        let request =
+            RemotingCommand::create_request_command(RequestCode::EndTransaction, request_header);
+        self.remoting_client
+            .invoke_oneway(broker_addr, request, self.timeout_millis())
+            .await;
+    }
+}
+
+/// The topic a message was popped from, the group's pop retry topic for retried messages.
+fn real_topic(
+    receipt_handle: &ReceiptHandle,
+    topic: &CheetahString,
+    group: &CheetahString,
+) -> Result<CheetahString> {
+    receipt_handle.real_topic(topic, group).ok_or_else(|| {
+        ProxyError::IllegalArgumentError(format!("receipt handle {} is invalid", receipt_handle))
+    })
+}
+
+fn broker_error(response: &RemotingCommand, broker_addr: &str) -> ProxyError {
+    ProxyError::MQBrokerError(
+        response.code(),
+        response
+            .remark()
+            .map_or(String::new(), |remark| remark.to_string()),
+        broker_addr.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::protocol::route::route_data_view::QueueData;
+
+    use super::*;
+
+    #[test]
+    fn select_write_queue_keeps_message_group_on_one_queue() {
+        let processor = MessagingProcessor::new(Arc::new(ProxyConfig::default()));
+        let route = TopicRouteData {
+            queue_datas: vec![
+                QueueData::new(
+                    "broker-a".into(),
+                    4,
+                    4,
+                    PermName::PERM_READ | PermName::PERM_WRITE,
+                    0,
+                ),
+                QueueData::new("broker-b".into(), 4, 4, PermName::PERM_READ, 0),
+            ],
+            ..Default::default()
+        };
+        let first = processor
+            .select_write_queue(&route, Some("order-1"))
+            .unwrap();
+        for _ in 0..8 {
+            assert_eq!(
+                processor
+                    .select_write_queue(&route, Some("order-1"))
+                    .unwrap(),
+                first
+            );
+        }
+        let queues = (0..4)
+            .map(|_| processor.select_write_queue(&route, None).unwrap())
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(queues.len(), 4);
+        assert!(queues
+            .iter()
+            .all(|(broker_name, _)| broker_name == "broker-a"));
+    }
+}
@@ -0,0 +1,206 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fmt;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_remoting::protocol::header::extra_info_util::ExtraInfoUtil;
+
+const SEPARATOR: char = '|';
+
+/// Receipt handle handed to clients with every received message, the `POP_CK` extra info the
+/// broker popped the message with followed by the commit log offset of the message, like the
+/// Java proxy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptHandle {
+    pub start_offset: i64,
+    pub retrieve_time: i64,
+    pub invisible_time: i64,
+    pub revive_queue_id: i32,
+    /// Whether the message was popped from the pop retry topic, see [`ExtraInfoUtil`].
+    pub topic_type: CheetahString,
+    pub broker_name: CheetahString,
+    pub queue_id: i32,
+    pub offset: i64,
+    pub commit_log_offset: i64,
+}
+
+impl ReceiptHandle {
+    /// The receipt handle of a popped message carrying the `POP_CK` property.
+    pub fn from_pop_ck(pop_ck: &str, commit_log_offset: i64) -> Option<Self> {
+        Self::decode(&format!(
+            "{pop_ck}{}{commit_log_offset}",
+            MessageConst::KEY_SEPARATOR
+        ))
+    }
+
+    pub fn decode(handle: &str) -> Option<Self> {
+        let parts = handle
+            .split(MessageConst::KEY_SEPARATOR)
+            .collect::<Vec<_>>();
+        if parts.len() < 8 || parts[5].is_empty() {
+            return None;
+        }
+        let commit_log_offset = match parts.get(8) {
+            Some(offset) => offset.parse().ok()?,
+            None => -1,
+        };
+        Some(ReceiptHandle {
+            start_offset: parts[0].parse().ok()?,
+            retrieve_time: parts[1].parse().ok()?,
+            invisible_time: parts[2].parse().ok()?,
+            revive_queue_id: parts[3].parse().ok()?,
+            topic_type: CheetahString::from_slice(parts[4]),
+            broker_name: CheetahString::from_slice(parts[5]),
+            queue_id: parts[6].parse().ok()?,
+            offset: parts[7].parse().ok()?,
+            commit_log_offset,
+        })
+    }
+
+    /// The extra info the broker expects in ack and change invisible time requests.
+    pub fn extra_info(&self) -> String {
+        let sep = MessageConst::KEY_SEPARATOR;
+        format!(
+            "{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}",
+            self.start_offset,
+            self.retrieve_time,
+            self.invisible_time,
+            self.revive_queue_id,
+            self.topic_type,
+            self.broker_name,
+            self.queue_id,
+            self.offset
+        )
+    }
+
+    /// The topic the message was popped from, the group's pop retry topic for retried messages.
+    pub fn real_topic(&self, topic: &str, group: &str) -> Option<CheetahString> {
+        ExtraInfoUtil::get_real_topic_with_retry(topic, group, &self.topic_type)
+            .ok()
+            .map(CheetahString::from_string)
+    }
+}
+
+impl fmt::Display for ReceiptHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.extra_info(),
+            MessageConst::KEY_SEPARATOR,
+            self.commit_log_offset
+        )
+    }
+}
+
+/// Transaction id handed to producers of half messages. It carries the store location of the
+/// half message, which the broker needs to commit or roll it back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionId {
+    pub broker_name: CheetahString,
+    pub commit_log_offset: u64,
+    pub tran_state_table_offset: u64,
+}
+
+impl TransactionId {
+    pub fn new(
+        broker_name: CheetahString,
+        commit_log_offset: u64,
+        tran_state_table_offset: u64,
+    ) -> Self {
+        TransactionId {
+            broker_name,
+            commit_log_offset,
+            tran_state_table_offset,
+        }
+    }
+
+    pub fn decode(transaction_id: &str) -> Option<Self> {
+        let mut parts = transaction_id.splitn(3, SEPARATOR);
+        let commit_log_offset = parts.next()?.parse().ok()?;
+        let tran_state_table_offset = parts.next()?.parse().ok()?;
+        let broker_name = parts.next().filter(|name| !name.is_empty())?;
+        Some(TransactionId::new(
+            CheetahString::from_slice(broker_name),
+            commit_log_offset,
+            tran_state_table_offset,
+        ))
+    }
+}
+
+impl fmt::Display for TransactionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{SEPARATOR}{}{SEPARATOR}{}",
+            self.commit_log_offset, self.tran_state_table_offset, self.broker_name
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receipt_handle_round_trip() {
+        let pop_ck = ExtraInfoUtil::build_extra_info_with_msg_queue_offset(
+            40,
+            1000,
+            60000,
+            0,
+            "%RETRY%group_topic",
+            "broker-a",
+            3,
+            42,
+        );
+        let handle = ReceiptHandle::from_pop_ck(&pop_ck, 4096).unwrap();
+        assert_eq!(handle.start_offset, 40);
+        assert_eq!(handle.retrieve_time, 1000);
+        assert_eq!(handle.broker_name, "broker-a");
+        assert_eq!(handle.queue_id, 3);
+        assert_eq!(handle.offset, 42);
+        assert_eq!(handle.commit_log_offset, 4096);
+        assert_eq!(handle.extra_info(), pop_ck);
+        assert_eq!(
+            handle.real_topic("topic", "group").as_deref(),
+            Some("%RETRY%group_topic")
+        );
+        assert_eq!(ReceiptHandle::decode(&handle.to_string()), Some(handle));
+        assert_eq!(ReceiptHandle::decode("40 1000 60000 0 0 broker-a 3"), None);
+        assert_eq!(ReceiptHandle::decode("40 x 60000 0 0 broker-a 3 42"), None);
+        assert_eq!(
+            ReceiptHandle::decode("40 1000 60000 0 0 broker-a 3 42")
+                .unwrap()
+                .real_topic("topic", "group")
+                .as_deref(),
+            Some("topic")
+        );
+    }
+
+    #[test]
+    fn transaction_id_round_trip() {
+        let transaction_id =
+            TransactionId::new(CheetahString::from_static_str("broker-a"), 4096, 7);
+        assert_eq!(
+            TransactionId::decode(&transaction_id.to_string()),
+            Some(transaction_id)
+        );
+        assert_eq!(TransactionId::decode("7F0000011F90"), None);
+    }
+}
@@ -0,0 +1,51 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::net::SocketAddr;
+
+use cheetah_string::CheetahString;
+use tonic::metadata::MetadataMap;
+
+/// Per call information taken from the gRPC request metadata.
+#[derive(Debug, Clone, Default)]
+pub struct ProxyContext {
+    pub client_id: CheetahString,
+    pub remote_address: Option<SocketAddr>,
+    pub language: CheetahString,
+    pub client_version: CheetahString,
+}
+
+impl ProxyContext {
+    pub const CLIENT_ID_KEY: &'static str = "x-mq-client-id";
+    pub const LANGUAGE_KEY: &'static str = "x-mq-language";
+    pub const CLIENT_VERSION_KEY: &'static str = "x-mq-client-version";
+
+    pub fn from_metadata(metadata: &MetadataMap, remote_address: Option<SocketAddr>) -> Self {
+        let header = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(|value| value.to_str().ok())
+                .map(CheetahString::from_slice)
+                .unwrap_or_default()
+        };
+        ProxyContext {
+            client_id: header(Self::CLIENT_ID_KEY),
+            remote_address,
+            language: header(Self::LANGUAGE_KEY),
+            client_version: header(Self::CLIENT_VERSION_KEY),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use thiserror::Error;
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Error)]
+pub enum ProxyError {
+    #[error("proxy remoting error: {0}")]
+    ProxyRemotingError(#[from] rocketmq_remoting::remoting_error::RemotingError),
+
+    #[error("Broker error: CODE:{0}, broker address:{2}, Message:{1}")]
+    MQBrokerError(i32, String, String),
+
+    #[error("{0}")]
+    IllegalArgumentError(String),
+
+    #[error("Topic route of {0} not found")]
+    TopicRouteNotFound(String),
+
+    #[error("gRPC transport error: {0}")]
+    TransportError(#[from] tonic::transport::Error),
+
+    #[error("TLS error: {0}")]
+    TlsError(String),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
@@ -0,0 +1,355 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! Conversion between `apache.rocketmq.v2` messages and the messages of the remoting protocol.
+
+use std::collections::HashMap;
+use std::io::Read;
+
+use cheetah_string::CheetahString;
+use flate2::read::GzDecoder;
+use rocketmq_common::common::message::message_decoder;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::STRING_HASH_SET;
+use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
+use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_common::utils::crc32_utils;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::grpc;
+use rocketmq_remoting::grpc::v2;
+use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header::SendMessageRequestHeader;
+
+use crate::processor::receipt_handle::ReceiptHandle;
+use crate::proxy_error::ProxyError;
+use crate::Result;
+
+/// Builds the remoting send request of a message received from a gRPC producer.
+pub fn to_send_message_request(
+    message: &v2::Message,
+    producer_group: &CheetahString,
+    queue_id: i32,
+) -> Result<(SendMessageRequestHeader, Vec<u8>)> {
+    let topic = message
+        .topic
+        .as_ref()
+        .map(|topic| CheetahString::from_slice(&topic.name))
+        .filter(|topic| !topic.is_empty())
+        .ok_or_else(|| ProxyError::IllegalArgumentError("topic is required".to_string()))?;
+    let system_properties = message.system_properties.clone().unwrap_or_default();
+    let body = match system_properties.body_encoding() {
+        v2::Encoding::Gzip => {
+            let mut body = Vec::new();
+            GzDecoder::new(message.body.as_slice())
+                .read_to_end(&mut body)
+                .map_err(|e| {
+                    ProxyError::IllegalArgumentError(format!("invalid gzip message body: {}", e))
+                })?;
+            body
+        }
+        _ => message.body.clone(),
+    };
+
+    let mut properties = message
+        .user_properties
+        .iter()
+        .map(|(key, value)| {
+            (
+                CheetahString::from_slice(key),
+                CheetahString::from_slice(value),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+    let mut put_property = |key: &'static str, value: String| {
+        properties.insert(
+            CheetahString::from_static_str(key),
+            CheetahString::from_string(value),
+        );
+    };
+    if !system_properties.message_id.is_empty() {
+        put_property(
+            MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX,
+            system_properties.message_id.clone(),
+        );
+    }
+    if let Some(tag) = system_properties.tag.clone().filter(|tag| !tag.is_empty()) {
+        put_property(MessageConst::PROPERTY_TAGS, tag);
+    }
+    if !system_properties.keys.is_empty() {
+        put_property(
+            MessageConst::PROPERTY_KEYS,
+            system_properties.keys.join(MessageConst::KEY_SEPARATOR),
+        );
+    }
+    if let Some(message_group) = system_properties.message_group.clone() {
+        put_property(MessageConst::PROPERTY_SHARDING_KEY, message_group);
+    }
+    if let Some(delivery_timestamp) = system_properties.delivery_timestamp {
+        put_property(
+            MessageConst::PROPERTY_TIMER_DELIVER_MS,
+            grpc::timestamp_to_millis(&delivery_timestamp).to_string(),
+        );
+    }
+    if let Some(trace_context) = system_properties.trace_context.clone() {
+        put_property(MessageConst::PROPERTY_TRACE_PARENT, trace_context);
+    }
+    let mut sys_flag = 0;
+    if system_properties.message_type() == v2::MessageType::Transaction {
+        put_property(
+            MessageConst::PROPERTY_TRANSACTION_PREPARED,
+            "true".to_string(),
+        );
+        put_property(
+            MessageConst::PROPERTY_PRODUCER_GROUP,
+            producer_group.to_string(),
+        );
+        sys_flag |= MessageSysFlag::TRANSACTION_PREPARED_TYPE;
+    }
+
+    let request_header = SendMessageRequestHeader {
+        producer_group: producer_group.clone(),
+        topic,
+        default_topic: CheetahString::from_static_str(TopicValidator::AUTO_CREATE_TOPIC_KEY_TOPIC),
+        default_topic_queue_nums: 4,
+        queue_id,
+        sys_flag,
+        born_timestamp: system_properties.born_timestamp.map_or_else(
+            || get_current_millis() as i64,
+            |timestamp| grpc::timestamp_to_millis(&timestamp),
+        ),
+        flag: 0,
+        properties: Some(message_decoder::message_properties_to_string(&properties)),
+        reconsume_times: Some(0),
+        unit_mode: Some(false),
+        batch: Some(false),
+        max_reconsume_times: None,
+        topic_request_header: None,
+    };
+    Ok((request_header, body))
+}
+
+/// Builds the message handed to a gRPC client from a message of a broker. Messages pulled for
+/// consumers carry a receipt handle, half messages of transaction checks do not.
+pub fn to_grpc_message(
+    message: &MessageExt,
+    receipt_handle: Option<&ReceiptHandle>,
+    invisible_duration: Option<grpc::Duration>,
+) -> v2::Message {
+    let properties = message.properties();
+    let property = |key: &'static str| {
+        properties
+            .get(&CheetahString::from_static_str(key))
+            .map(|value| value.to_string())
+    };
+    let body = message.body().map(|body| body.to_vec()).unwrap_or_default();
+    let message_group = property(MessageConst::PROPERTY_SHARDING_KEY);
+    let delivery_timestamp = property(MessageConst::PROPERTY_TIMER_DELIVER_MS)
+        .and_then(|value| value.parse::<i64>().ok());
+    let message_type = if property(MessageConst::PROPERTY_TRANSACTION_PREPARED)
+        .is_some_and(|value| value == "true")
+    {
+        v2::MessageType::Transaction
+    } else if message_group.is_some() {
+        v2::MessageType::Fifo
+    } else if delivery_timestamp.is_some() {
+        v2::MessageType::Delay
+    } else {
+        v2::MessageType::Normal
+    };
+    let system_properties = v2::SystemProperties {
+        tag: property(MessageConst::PROPERTY_TAGS),
+        keys: property(MessageConst::PROPERTY_KEYS)
+            .map(|keys| {
+                keys.split(MessageConst::KEY_SEPARATOR)
+                    .filter(|key| !key.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        message_id: property(MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX)
+            .unwrap_or_else(|| message.msg_id().to_string()),
+        body_digest: Some(v2::Digest {
+            r#type: v2::DigestType::Crc32 as i32,
+            checksum: format!("{:X}", crc32_utils::crc32(&body)),
+        }),
+        body_encoding: v2::Encoding::Identity as i32,
+        message_type: message_type as i32,
+        born_timestamp: Some(grpc::timestamp_from_millis(message.born_timestamp())),
+        born_host: message.born_host().ip().to_string(),
+        store_timestamp: Some(grpc::timestamp_from_millis(message.store_timestamp())),
+        store_host: message.store_host().to_string(),
+        delivery_timestamp: delivery_timestamp.map(grpc::timestamp_from_millis),
+        receipt_handle: receipt_handle.map(ToString::to_string),
+        queue_id: message.queue_id(),
+        queue_offset: Some(message.queue_offset()),
+        invisible_duration,
+        delivery_attempt: Some(message.reconsume_times() + 1),
+        message_group,
+        trace_context: property(MessageConst::PROPERTY_TRACE_PARENT),
+        orphaned_transaction_recovery_duration: None,
+        dead_letter_queue: property(MessageConst::PROPERTY_DLQ_ORIGIN_TOPIC)
+            .zip(property(MessageConst::PROPERTY_DLQ_ORIGIN_MESSAGE_ID))
+            .map(|(topic, message_id)| v2::DeadLetterQueue { topic, message_id }),
+    };
+    v2::Message {
+        topic: Some(v2::Resource::new(message.topic().as_str())),
+        user_properties: properties
+            .iter()
+            .filter(|(key, _)| !STRING_HASH_SET.contains(key.as_str()))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+        system_properties: Some(system_properties),
+        body,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use bytes::Bytes;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use rocketmq_common::common::message::MessageTrait;
+
+    use super::*;
+
+    fn grpc_message(body: Vec<u8>, body_encoding: i32) -> v2::Message {
+        v2::Message {
+            topic: Some(v2::Resource::new("TopicTest")),
+            user_properties: HashMap::from([("color".to_string(), "red".to_string())]),
+            system_properties: Some(v2::SystemProperties {
+                tag: Some("TagA".to_string()),
+                keys: vec!["k1".to_string(), "k2".to_string()],
+                message_id: "0100AB".to_string(),
+                body_encoding,
+                message_type: v2::MessageType::Transaction as i32,
+                message_group: Some("order-1".to_string()),
+                ..Default::default()
+            }),
+            body,
+        }
+    }
+
+    #[test]
+    fn send_request_carries_system_properties() {
+        let message = grpc_message(b"hello".to_vec(), v2::Encoding::Identity as i32);
+        let (header, body) =
+            to_send_message_request(&message, &CheetahString::from_static_str("PG"), 2).unwrap();
+        assert_eq!(body, b"hello");
+        assert_eq!(header.topic, "TopicTest");
+        assert_eq!(header.queue_id, 2);
+        assert_eq!(header.sys_flag, MessageSysFlag::TRANSACTION_PREPARED_TYPE);
+        let properties = message_decoder::string_to_message_properties(header.properties.as_ref());
+        let property = |key: &str| properties.get(&CheetahString::from_slice(key)).unwrap();
+        assert_eq!(property(MessageConst::PROPERTY_TAGS), "TagA");
+        assert_eq!(property(MessageConst::PROPERTY_KEYS), "k1 k2");
+        assert_eq!(
+            property(MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX),
+            "0100AB"
+        );
+        assert_eq!(property(MessageConst::PROPERTY_SHARDING_KEY), "order-1");
+        assert_eq!(property(MessageConst::PROPERTY_PRODUCER_GROUP), "PG");
+        assert_eq!(property("color"), "red");
+    }
+
+    #[test]
+    fn gzip_body_is_decompressed() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"compressed body").unwrap();
+        let message = grpc_message(encoder.finish().unwrap(), v2::Encoding::Gzip as i32);
+        let (_, body) =
+            to_send_message_request(&message, &CheetahString::from_static_str("PG"), 0).unwrap();
+        assert_eq!(body, b"compressed body");
+
+        let message = grpc_message(b"not gzip".to_vec(), v2::Encoding::Gzip as i32);
+        assert!(
+            to_send_message_request(&message, &CheetahString::from_static_str("PG"), 0).is_err()
+        );
+    }
+
+    #[test]
+    fn grpc_message_splits_user_and_system_properties() {
+        let mut message = MessageExt::default();
+        message.set_topic(CheetahString::from_static_str("TopicTest"));
+        message.set_body(Bytes::from_static(b"hello"));
+        message.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_TAGS),
+            CheetahString::from_static_str("TagA"),
+        );
+        message.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX),
+            CheetahString::from_static_str("0100AB"),
+        );
+        message.put_property(
+            CheetahString::from_static_str("color"),
+            CheetahString::from_static_str("red"),
+        );
+        message.set_queue_id(1);
+        message.set_queue_offset(42);
+        message.set_reconsume_times(2);
+        let receipt_handle =
+            ReceiptHandle::from_pop_ck("40 1000 60000 0 0 broker-a 1 42", 4096).unwrap();
+
+        let grpc_message = to_grpc_message(&message, Some(&receipt_handle), None);
+        let system_properties = grpc_message.system_properties.unwrap();
+        assert_eq!(system_properties.tag.as_deref(), Some("TagA"));
+        assert_eq!(system_properties.message_id, "0100AB");
+        assert_eq!(
+            system_properties.message_type,
+            v2::MessageType::Normal as i32
+        );
+        assert_eq!(system_properties.queue_offset, Some(42));
+        assert_eq!(system_properties.delivery_attempt, Some(3));
+        assert_eq!(
+            system_properties.receipt_handle.as_deref(),
+            Some("40 1000 60000 0 0 broker-a 1 42 4096")
+        );
+        assert_eq!(system_properties.dead_letter_queue, None);
+        assert_eq!(
+            system_properties.body_digest.unwrap().checksum,
+            format!("{:X}", crc32_utils::crc32(b"hello"))
+        );
+        assert_eq!(
+            grpc_message.user_properties,
+            HashMap::from([("color".to_string(), "red".to_string())])
+        );
+        assert_eq!(grpc_message.body, b"hello");
+    }
+
+    #[test]
+    fn grpc_message_of_dead_letter_queue_carries_origin() {
+        let mut message = MessageExt::default();
+        message.set_topic(CheetahString::from_static_str("%DLQ%group"));
+        message.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_DLQ_ORIGIN_TOPIC),
+            CheetahString::from_static_str("TopicTest"),
+        );
+        message.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_DLQ_ORIGIN_MESSAGE_ID),
+            CheetahString::from_static_str("0100AB"),
+        );
+
+        let grpc_message = to_grpc_message(&message, None, None);
+        assert_eq!(
+            grpc_message.system_properties.unwrap().dead_letter_queue,
+            Some(v2::DeadLetterQueue {
+                topic: "TopicTest".to_string(),
+                message_id: "0100AB".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,1055 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Weak;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::filter::expression_type::ExpressionType;
+use rocketmq_common::common::message::message_decoder;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::grpc;
+use rocketmq_remoting::grpc::v2;
+use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use rocketmq_remoting::remoting_error::RemotingError;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::processor::client_remoting_processor::TransactionCheck;
+use crate::processor::messaging_processor::MessagingProcessor;
+use crate::processor::receipt_handle::ReceiptHandle;
+use crate::processor::receipt_handle::TransactionId;
+use crate::proxy_context::ProxyContext;
+use crate::proxy_error::ProxyError;
+use crate::service::message_converter;
+
+const DEFAULT_LONG_POLLING_TIMEOUT_MILLIS: u64 = 20_000;
+
+/// Telemetry stream of a producer, which transaction checks of its topics are relayed to.
+struct ProducerTelemetry {
+    topics: HashSet<String>,
+    sender: mpsc::Sender<v2::TelemetryCommand>,
+}
+
+/// Implements the methods of `apache.rocketmq.v2.MessagingService` on top of a
+/// [`MessagingProcessor`]. Failures are reported through the `status` of each response.
+pub struct MessagingService {
+    processor: Arc<MessagingProcessor>,
+    client_settings_table: Mutex<HashMap<CheetahString, v2::Settings>>,
+    producer_telemetry_table: Mutex<HashMap<CheetahString, ProducerTelemetry>>,
+}
+
+impl MessagingService {
+    pub fn new(processor: Arc<MessagingProcessor>) -> Self {
+        MessagingService {
+            processor,
+            client_settings_table: Mutex::new(HashMap::new()),
+            producer_telemetry_table: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Relays the transaction checks brokers send to the proxy to producers, as
+    /// `RecoverOrphanedTransactionCommand`s written to their telemetry streams.
+    pub fn start(self: &Arc<Self>) {
+        let Some(mut receiver) = self.processor.take_transaction_check_receiver() else {
+            return;
+        };
+        let service = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while let Some(check) = receiver.recv().await {
+                let Some(service) = Weak::upgrade(&service) else {
+                    return;
+                };
+                service.recover_orphaned_transaction(check);
+            }
+        });
+    }
+
+    pub async fn query_route(
+        &self,
+        _ctx: &ProxyContext,
+        request: v2::QueryRouteRequest,
+    ) -> v2::QueryRouteResponse {
+        let topic = resource_name(&request.topic);
+        let route = match self.processor.get_topic_route(&topic).await {
+            Ok(route) => route,
+            Err(e) => {
+                return v2::QueryRouteResponse {
+                    status: Some(status_from_error(&e)),
+                    message_queues: vec![],
+                }
+            }
+        };
+        let endpoints = request
+            .endpoints
+            .unwrap_or_else(|| self.default_endpoints());
+        v2::QueryRouteResponse {
+            status: Some(v2::Status::ok()),
+            message_queues: build_message_queues(&topic, &route, &endpoints),
+        }
+    }
+
+    pub async fn heartbeat(
+        &self,
+        _ctx: &ProxyContext,
+        _request: v2::HeartbeatRequest,
+    ) -> v2::HeartbeatResponse {
+        v2::HeartbeatResponse {
+            status: Some(v2::Status::ok()),
+        }
+    }
+
+    pub async fn send_message(
+        &self,
+        _ctx: &ProxyContext,
+        request: v2::SendMessageRequest,
+    ) -> v2::SendMessageResponse {
+        if request.messages.is_empty() {
+            return v2::SendMessageResponse {
+                status: Some(v2::Status::new(v2::Code::BadRequest, "no message to send")),
+                entries: vec![],
+            };
+        }
+        let mut entries = Vec::with_capacity(request.messages.len());
+        for message in &request.messages {
+            let entry = match self.send_one_message(message).await {
+                Ok(entry) => entry,
+                Err(status) => v2::SendResultEntry {
+                    status: Some(status),
+                    ..Default::default()
+                },
+            };
+            entries.push(entry);
+        }
+        v2::SendMessageResponse {
+            status: Some(merge_status(entries.iter().map(|entry| &entry.status))),
+            entries,
+        }
+    }
+
+    async fn send_one_message(
+        &self,
+        message: &v2::Message,
+    ) -> Result<v2::SendResultEntry, v2::Status> {
+        let proxy_config = self.processor.proxy_config();
+        if message.body.len() > proxy_config.max_message_size as usize {
+            return Err(v2::Status::new(
+                v2::Code::MessageBodyTooLarge,
+                format!(
+                    "message body size {} exceeds the limit {}",
+                    message.body.len(),
+                    proxy_config.max_message_size
+                ),
+            ));
+        }
+        let topic = resource_name(&message.topic);
+        let route = self
+            .processor
+            .get_topic_route(&topic)
+            .await
+            .map_err(|e| status_from_error(&e))?;
+        let message_group = message
+            .system_properties
+            .as_ref()
+            .and_then(|system_properties| system_properties.message_group.as_deref());
+        let (broker_name, queue_id) = self
+            .processor
+            .select_write_queue(&route, message_group)
+            .ok_or_else(|| {
+                v2::Status::new(
+                    v2::Code::Forbidden,
+                    format!("topic {} is not writable", topic),
+                )
+            })?;
+        let broker_addr = self.broker_addr(&route, &broker_name)?;
+        let producer_group = CheetahString::from_slice(&proxy_config.proxy_producer_group);
+        let (request_header, body) =
+            message_converter::to_send_message_request(message, &producer_group, queue_id)
+                .map_err(|e| status_from_error(&e))?;
+        let (response_code, response_header) = self
+            .processor
+            .send_message(&broker_addr, request_header, body)
+            .await
+            .map_err(|e| status_from_error(&e))?;
+        let status = match response_code {
+            ResponseCode::FlushDiskTimeout => {
+                v2::Status::new(v2::Code::MasterPersistenceTimeout, "flush disk timeout")
+            }
+            ResponseCode::FlushSlaveTimeout => {
+                v2::Status::new(v2::Code::SlavePersistenceTimeout, "flush slave timeout")
+            }
+            ResponseCode::SlaveNotAvailable => {
+                v2::Status::new(v2::Code::HaNotAvailable, "slave not available")
+            }
+            _ => v2::Status::ok(),
+        };
+        let is_transaction = message
+            .system_properties
+            .as_ref()
+            .is_some_and(|properties| {
+                properties.message_type == v2::MessageType::Transaction as i32
+            });
+        let transaction_id = if is_transaction {
+            message_decoder::try_decode_message_id(response_header.msg_id())
+                .map(|message_id| {
+                    TransactionId::new(
+                        broker_name.clone(),
+                        message_id.offset as u64,
+                        response_header.queue_offset() as u64,
+                    )
+                    .to_string()
+                })
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let message_id = message
+            .system_properties
+            .as_ref()
+            .map(|properties| properties.message_id.clone())
+            .filter(|message_id| !message_id.is_empty())
+            .unwrap_or_else(|| response_header.msg_id().to_string());
+        Ok(v2::SendResultEntry {
+            status: Some(status),
+            message_id,
+            transaction_id,
+            offset: response_header.queue_offset(),
+        })
+    }
+
+    pub async fn query_assignment(
+        &self,
+        _ctx: &ProxyContext,
+        request: v2::QueryAssignmentRequest,
+    ) -> v2::QueryAssignmentResponse {
+        let topic = resource_name(&request.topic);
+        let route = match self.processor.get_topic_route(&topic).await {
+            Ok(route) => route,
+            Err(e) => {
+                return v2::QueryAssignmentResponse {
+                    status: Some(status_from_error(&e)),
+                    assignments: vec![],
+                }
+            }
+        };
+        let endpoints = request
+            .endpoints
+            .unwrap_or_else(|| self.default_endpoints());
+        let assignments = build_message_queues(&topic, &route, &endpoints)
+            .into_iter()
+            .filter(|queue| {
+                queue.permission == v2::Permission::Read as i32
+                    || queue.permission == v2::Permission::ReadWrite as i32
+            })
+            .map(|queue| v2::Assignment {
+                message_queue: Some(queue),
+            })
+            .collect::<Vec<_>>();
+        let status = if assignments.is_empty() {
+            v2::Status::new(
+                v2::Code::Forbidden,
+                format!("topic {} is not readable", topic),
+            )
+        } else {
+            v2::Status::ok()
+        };
+        v2::QueryAssignmentResponse {
+            status: Some(status),
+            assignments,
+        }
+    }
+
+    /// Receives messages of one queue. The responses are written to the stream in order: the
+    /// status first, then one response per message.
+    pub async fn receive_message(
+        &self,
+        ctx: &ProxyContext,
+        request: v2::ReceiveMessageRequest,
+    ) -> Vec<v2::ReceiveMessageResponse> {
+        match self.receive_messages(ctx, request).await {
+            Ok(messages) if messages.is_empty() => vec![status_response(v2::Status::new(
+                v2::Code::MessageNotFound,
+                "no new message",
+            ))],
+            Ok(messages) => std::iter::once(status_response(v2::Status::ok()))
+                .chain(
+                    messages
+                        .into_iter()
+                        .map(|message| v2::ReceiveMessageResponse {
+                            content: Some(v2::receive_message_response::Content::Message(message)),
+                        }),
+                )
+                .collect(),
+            Err(status) => vec![status_response(status)],
+        }
+    }
+
+    async fn receive_messages(
+        &self,
+        ctx: &ProxyContext,
+        request: v2::ReceiveMessageRequest,
+    ) -> Result<Vec<v2::Message>, v2::Status> {
+        let proxy_config = self.processor.proxy_config();
+        let group = resource_name(&request.group);
+        let message_queue = request.message_queue.unwrap_or_default();
+        let topic = resource_name(&message_queue.topic);
+        let broker_name = message_queue
+            .broker
+            .map(|broker| CheetahString::from_string(broker.name))
+            .unwrap_or_default();
+        if group.is_empty() || topic.is_empty() || broker_name.is_empty() || message_queue.id < 0 {
+            return Err(v2::Status::new(
+                v2::Code::BadRequest,
+                "group, topic, broker and queue id are required",
+            ));
+        }
+        let filter_expression = request.filter_expression.unwrap_or_default();
+        let expression_type = match filter_expression.r#type() {
+            v2::FilterType::Sql => ExpressionType::SQL92,
+            _ => ExpressionType::TAG,
+        };
+        let batch_size = request
+            .batch_size
+            .clamp(1, proxy_config.max_receive_batch_size);
+        let long_polling_timeout_millis = request
+            .long_polling_timeout
+            .or_else(|| {
+                self.client_settings_table
+                    .lock()
+                    .get(&ctx.client_id)
+                    .and_then(|settings| match &settings.pub_sub {
+                        Some(v2::settings::PubSub::Subscription(subscription)) => {
+                            subscription.long_polling_timeout
+                        }
+                        _ => None,
+                    })
+            })
+            .map_or(DEFAULT_LONG_POLLING_TIMEOUT_MILLIS, |duration| {
+                grpc::duration_to_millis(&duration)
+            });
+        let invisible_duration_millis = self.invisible_duration_millis(request.invisible_duration);
+
+        let max_attempts = self.max_delivery_attempts(ctx);
+
+        let route = self
+            .processor
+            .get_topic_route(&topic)
+            .await
+            .map_err(|e| status_from_error(&e))?;
+        let broker_addr = self.broker_addr(&route, &broker_name)?;
+        let messages = self
+            .processor
+            .pop_messages(
+                &broker_addr,
+                &broker_name,
+                &group,
+                &topic,
+                message_queue.id,
+                expression_type,
+                &CheetahString::from_string(filter_expression.expression),
+                batch_size,
+                Duration::from_millis(invisible_duration_millis),
+                long_polling_timeout_millis,
+            )
+            .await
+            .map_err(|e| status_from_error(&e))?;
+        let mut grpc_messages = Vec::with_capacity(messages.len());
+        for message in messages {
+            let Some(receipt_handle) = message
+                .get_property(&CheetahString::from_static_str(
+                    MessageConst::PROPERTY_POP_CK,
+                ))
+                .and_then(|pop_ck| {
+                    ReceiptHandle::from_pop_ck(&pop_ck, message.commit_log_offset())
+                })
+            else {
+                warn!(
+                    "popped message {} of topic {} has no receipt handle",
+                    message.msg_id(),
+                    topic
+                );
+                continue;
+            };
+            if message.reconsume_times() + 1 > max_attempts {
+                self.forward_to_dead_letter_queue(
+                    &broker_addr,
+                    &group,
+                    &topic,
+                    &message,
+                    &receipt_handle,
+                    max_attempts,
+                )
+                .await;
+                continue;
+            }
+            grpc_messages.push(message_converter::to_grpc_message(
+                &message,
+                Some(&receipt_handle),
+                Some(grpc::duration_from_millis(invisible_duration_millis)),
+            ));
+        }
+        Ok(grpc_messages)
+    }
+
+    /// Sends a message which used up its delivery attempts to the dead letter queue and acks it,
+    /// like the Java proxy. A failed forward leaves the message to be delivered again.
+    async fn forward_to_dead_letter_queue(
+        &self,
+        broker_addr: &CheetahString,
+        group: &CheetahString,
+        topic: &CheetahString,
+        message: &MessageExt,
+        receipt_handle: &ReceiptHandle,
+        max_attempts: i32,
+    ) {
+        let message_id = message
+            .get_property(&CheetahString::from_static_str(
+                MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX,
+            ))
+            .unwrap_or_else(|| message.msg_id().clone());
+        let result = match self
+            .processor
+            .forward_message_to_dead_letter_queue(
+                broker_addr,
+                group,
+                topic,
+                &message_id,
+                receipt_handle,
+                max_attempts - 1,
+            )
+            .await
+        {
+            Ok(()) => {
+                self.processor
+                    .ack_message(broker_addr, group, topic, receipt_handle)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            warn!(
+                "forward message {} of group {} to the dead letter queue failed: {}",
+                message_id, group, e
+            );
+        }
+    }
+
+    /// Deliveries of a message before it is sent to the dead letter queue, from the backoff
+    /// policy reported by the client.
+    fn max_delivery_attempts(&self, ctx: &ProxyContext) -> i32 {
+        self.client_settings_table
+            .lock()
+            .get(&ctx.client_id)
+            .and_then(|settings| settings.backoff_policy.as_ref())
+            .map(|policy| policy.max_attempts)
+            .filter(|max_attempts| *max_attempts > 0)
+            .unwrap_or(self.processor.proxy_config().max_delivery_attempts)
+    }
+
+    pub async fn ack_message(
+        &self,
+        _ctx: &ProxyContext,
+        request: v2::AckMessageRequest,
+    ) -> v2::AckMessageResponse {
+        let group = resource_name(&request.group);
+        let topic = resource_name(&request.topic);
+        let route = match self.processor.get_topic_route(&topic).await {
+            Ok(route) => route,
+            Err(e) => {
+                return v2::AckMessageResponse {
+                    status: Some(status_from_error(&e)),
+                    entries: vec![],
+                }
+            }
+        };
+        let mut entries = Vec::with_capacity(request.entries.len());
+        for entry in request.entries {
+            let status = match self
+                .ack_one_message(&route, &group, &topic, &entry.receipt_handle)
+                .await
+            {
+                Ok(()) => v2::Status::ok(),
+                Err(status) => status,
+            };
+            entries.push(v2::AckMessageResultEntry {
+                message_id: entry.message_id,
+                receipt_handle: entry.receipt_handle,
+                status: Some(status),
+            });
+        }
+        v2::AckMessageResponse {
+            status: Some(merge_status(entries.iter().map(|entry| &entry.status))),
+            entries,
+        }
+    }
+
+    async fn ack_one_message(
+        &self,
+        route: &TopicRouteData,
+        group: &CheetahString,
+        topic: &CheetahString,
+        receipt_handle: &str,
+    ) -> Result<(), v2::Status> {
+        let invalid_receipt_handle = || {
+            v2::Status::new(
+                v2::Code::InvalidReceiptHandle,
+                format!("receipt handle {} is invalid", receipt_handle),
+            )
+        };
+        let receipt_handle =
+            ReceiptHandle::decode(receipt_handle).ok_or_else(invalid_receipt_handle)?;
+        let broker_addr = self.broker_addr(route, &receipt_handle.broker_name)?;
+        self.processor
+            .ack_message(&broker_addr, group, topic, &receipt_handle)
+            .await
+            .map_err(|e| status_from_error(&e))
+    }
+
+    pub async fn end_transaction(
+        &self,
+        _ctx: &ProxyContext,
+        request: v2::EndTransactionRequest,
+    ) -> v2::EndTransactionResponse {
+        let status = match self.do_end_transaction(request).await {
+            Ok(()) => v2::Status::ok(),
+            Err(status) => status,
+        };
+        v2::EndTransactionResponse {
+            status: Some(status),
+        }
+    }
+
+    async fn do_end_transaction(
+        &self,
+        request: v2::EndTransactionRequest,
+    ) -> Result<(), v2::Status> {
+        let transaction_id = TransactionId::decode(&request.transaction_id).ok_or_else(|| {
+            v2::Status::new(
+                v2::Code::InvalidTransactionId,
+                format!("transaction id {} is invalid", request.transaction_id),
+            )
+        })?;
+        let commit = match request.resolution() {
+            v2::TransactionResolution::Commit => true,
+            v2::TransactionResolution::Rollback => false,
+            v2::TransactionResolution::Unspecified => {
+                return Err(v2::Status::new(
+                    v2::Code::BadRequest,
+                    format!("unknown transaction resolution {}", request.resolution),
+                ))
+            }
+        };
+        let topic = resource_name(&request.topic);
+        let from_transaction_check = request.source() == v2::TransactionSource::SourceServerCheck;
+        let route = self
+            .processor
+            .get_topic_route(&topic)
+            .await
+            .map_err(|e| status_from_error(&e))?;
+        let broker_addr = self.broker_addr(&route, &transaction_id.broker_name)?;
+        self.processor
+            .end_transaction(
+                &broker_addr,
+                &topic,
+                &CheetahString::from_string(request.message_id),
+                &transaction_id,
+                commit,
+                from_transaction_check,
+            )
+            .await;
+        Ok(())
+    }
+
+    /// Handles a command of the telemetry stream, returning the command to write back. A
+    /// producer reporting its settings gets the transaction checks of its topics on `sender`.
+    pub async fn telemetry(
+        &self,
+        ctx: &ProxyContext,
+        command: v2::TelemetryCommand,
+        sender: &mpsc::Sender<v2::TelemetryCommand>,
+    ) -> Option<v2::TelemetryCommand> {
+        match command.command {
+            Some(v2::telemetry_command::Command::Settings(settings)) => {
+                let settings = self.merge_settings(settings);
+                if let Some(v2::settings::PubSub::Publishing(publishing)) = &settings.pub_sub {
+                    let producer_telemetry = ProducerTelemetry {
+                        topics: publishing
+                            .topics
+                            .iter()
+                            .map(|topic| topic.name.clone())
+                            .collect(),
+                        sender: sender.clone(),
+                    };
+                    self.producer_telemetry_table
+                        .lock()
+                        .insert(ctx.client_id.clone(), producer_telemetry);
+                }
+                self.client_settings_table
+                    .lock()
+                    .insert(ctx.client_id.clone(), settings.clone());
+                Some(v2::TelemetryCommand {
+                    status: Some(v2::Status::ok()),
+                    command: Some(v2::telemetry_command::Command::Settings(settings)),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Forgets the telemetry stream of a client once it ends, unless the client opened a newer
+    /// one in the meantime.
+    pub fn close_telemetry(&self, ctx: &ProxyContext, sender: &mpsc::Sender<v2::TelemetryCommand>) {
+        let mut producer_telemetry_table = self.producer_telemetry_table.lock();
+        if producer_telemetry_table
+            .get(&ctx.client_id)
+            .is_some_and(|producer_telemetry| producer_telemetry.sender.same_channel(sender))
+        {
+            producer_telemetry_table.remove(&ctx.client_id);
+        }
+    }
+
+    /// Asks a producer of the topic of a half message to resolve it. The producer answers with
+    /// an `EndTransaction` request whose source is `SOURCE_SERVER_CHECK`.
+    fn recover_orphaned_transaction(&self, check: TransactionCheck) {
+        let topic = check.message.get_topic().as_str();
+        let sender = {
+            let producer_telemetry_table = self.producer_telemetry_table.lock();
+            let producers = producer_telemetry_table.values();
+            // Producers that did not declare their topics may publish to any topic.
+            producers
+                .clone()
+                .find(|producer_telemetry| producer_telemetry.topics.contains(topic))
+                .or_else(|| {
+                    producers
+                        .clone()
+                        .find(|producer_telemetry| producer_telemetry.topics.is_empty())
+                })
+                .map(|producer_telemetry| producer_telemetry.sender.clone())
+        };
+        let Some(sender) = sender else {
+            warn!(
+                "no producer of topic {} to check transaction {}",
+                topic, check.transaction_id
+            );
+            return;
+        };
+        let command = v2::TelemetryCommand {
+            status: None,
+            command: Some(
+                v2::telemetry_command::Command::RecoverOrphanedTransactionCommand(
+                    v2::RecoverOrphanedTransactionCommand {
+                        message: Some(message_converter::to_grpc_message(
+                            &check.message,
+                            None,
+                            None,
+                        )),
+                        transaction_id: check.transaction_id.to_string(),
+                    },
+                ),
+            ),
+        };
+        // The broker checks the half message again later if this check is lost.
+        if let Err(e) = sender.try_send(command) {
+            warn!(
+                "relay check of transaction {} failed: {}",
+                check.transaction_id, e
+            );
+        }
+    }
+
+    pub async fn notify_client_termination(
+        &self,
+        ctx: &ProxyContext,
+        _request: v2::NotifyClientTerminationRequest,
+    ) -> v2::NotifyClientTerminationResponse {
+        self.client_settings_table.lock().remove(&ctx.client_id);
+        self.producer_telemetry_table.lock().remove(&ctx.client_id);
+        v2::NotifyClientTerminationResponse {
+            status: Some(v2::Status::ok()),
+        }
+    }
+
+    /// Moves the time a received message is delivered again if it is not acked. The broker
+    /// tracks the message under a new pop time from then on, so a new receipt handle is returned.
+    pub async fn change_invisible_duration(
+        &self,
+        _ctx: &ProxyContext,
+        request: v2::ChangeInvisibleDurationRequest,
+    ) -> v2::ChangeInvisibleDurationResponse {
+        match self.do_change_invisible_duration(&request).await {
+            Ok(receipt_handle) => v2::ChangeInvisibleDurationResponse {
+                status: Some(v2::Status::ok()),
+                receipt_handle: receipt_handle.to_string(),
+            },
+            Err(status) => v2::ChangeInvisibleDurationResponse {
+                status: Some(status),
+                receipt_handle: request.receipt_handle,
+            },
+        }
+    }
+
+    async fn do_change_invisible_duration(
+        &self,
+        request: &v2::ChangeInvisibleDurationRequest,
+    ) -> Result<ReceiptHandle, v2::Status> {
+        let receipt_handle = ReceiptHandle::decode(&request.receipt_handle).ok_or_else(|| {
+            v2::Status::new(
+                v2::Code::InvalidReceiptHandle,
+                format!("receipt handle {} is invalid", request.receipt_handle),
+            )
+        })?;
+        let topic = resource_name(&request.topic);
+        let route = self
+            .processor
+            .get_topic_route(&topic)
+            .await
+            .map_err(|e| status_from_error(&e))?;
+        let broker_addr = self.broker_addr(&route, &receipt_handle.broker_name)?;
+        self.processor
+            .change_invisible_duration(
+                &broker_addr,
+                &resource_name(&request.group),
+                &topic,
+                &receipt_handle,
+                Duration::from_millis(self.invisible_duration_millis(request.invisible_duration)),
+            )
+            .await
+            .map_err(|e| status_from_error(&e))
+    }
+
+    /// Invisible duration requested by a client, bounded by the proxy config.
+    fn invisible_duration_millis(&self, invisible_duration: Option<grpc::Duration>) -> u64 {
+        let proxy_config = self.processor.proxy_config();
+        invisible_duration
+            .map_or(proxy_config.default_invisible_time_millis, |duration| {
+                grpc::duration_to_millis(&duration)
+            })
+            .min(proxy_config.max_invisible_time_millis)
+    }
+
+    /// Fills the settings reported by a client with the limits of the proxy.
+    fn merge_settings(&self, mut settings: v2::Settings) -> v2::Settings {
+        let proxy_config = self.processor.proxy_config();
+        settings.request_timeout = Some(grpc::duration_from_millis(
+            proxy_config.remoting_timeout_millis,
+        ));
+        settings.metric = Some(v2::Metric::default());
+        match settings.pub_sub.as_mut() {
+            Some(v2::settings::PubSub::Publishing(publishing)) => {
+                publishing.max_body_size = proxy_config.max_message_size;
+                publishing.validate_message_type = proxy_config.enable_topic_message_type_check;
+            }
+            Some(v2::settings::PubSub::Subscription(subscription)) => {
+                subscription.fifo = Some(subscription.fifo.unwrap_or(false));
+                subscription.receive_batch_size = Some(
+                    subscription
+                        .receive_batch_size
+                        .unwrap_or(proxy_config.max_receive_batch_size)
+                        .clamp(1, proxy_config.max_receive_batch_size),
+                );
+                subscription.long_polling_timeout = Some(
+                    subscription
+                        .long_polling_timeout
+                        .unwrap_or(grpc::duration_from_millis(
+                            DEFAULT_LONG_POLLING_TIMEOUT_MILLIS,
+                        )),
+                );
+            }
+            None => {}
+        }
+        if matches!(
+            settings.pub_sub,
+            Some(v2::settings::PubSub::Subscription(_))
+        ) {
+            let backoff_policy = settings.backoff_policy.get_or_insert_with(Default::default);
+            if backoff_policy.max_attempts <= 0 {
+                backoff_policy.max_attempts = proxy_config.max_delivery_attempts;
+            }
+        }
+        settings
+    }
+
+    fn broker_addr(
+        &self,
+        route: &TopicRouteData,
+        broker_name: &str,
+    ) -> Result<CheetahString, v2::Status> {
+        self.processor
+            .broker_addr(route, broker_name)
+            .ok_or_else(|| {
+                v2::Status::new(
+                    v2::Code::NotFound,
+                    format!("broker {} is not found in the topic route", broker_name),
+                )
+            })
+    }
+
+    fn default_endpoints(&self) -> v2::Endpoints {
+        let proxy_config = self.processor.proxy_config();
+        v2::Endpoints {
+            scheme: v2::AddressScheme::IPv4 as i32,
+            addresses: vec![v2::Address {
+                host: proxy_config.bind_address.clone(),
+                port: proxy_config.grpc_server_port as i32,
+            }],
+        }
+    }
+}
+
+fn resource_name(resource: &Option<v2::Resource>) -> CheetahString {
+    resource
+        .as_ref()
+        .map(|resource| CheetahString::from_slice(&resource.name))
+        .unwrap_or_default()
+}
+
+fn status_response(status: v2::Status) -> v2::ReceiveMessageResponse {
+    v2::ReceiveMessageResponse {
+        content: Some(v2::receive_message_response::Content::Status(status)),
+    }
+}
+
+/// Status of a request made of several entries: the shared status of the entries, or
+/// `MULTIPLE_RESULTS` when they differ.
+fn merge_status<'a>(statuses: impl Iterator<Item = &'a Option<v2::Status>>) -> v2::Status {
+    let mut merged: Option<v2::Status> = None;
+    for status in statuses.flatten() {
+        match &merged {
+            None => merged = Some(status.clone()),
+            Some(merged_status) if merged_status.code == status.code => {}
+            Some(_) => {
+                return v2::Status::new(v2::Code::MultipleResults, "entries have different results")
+            }
+        }
+    }
+    merged.unwrap_or_else(v2::Status::ok)
+}
+
+/// Splits the queues of each broker into read-write queues and read-only or write-only queues
+/// the same way clients of the remoting protocol do.
+fn build_message_queues(
+    topic: &CheetahString,
+    route: &TopicRouteData,
+    endpoints: &v2::Endpoints,
+) -> Vec<v2::MessageQueue> {
+    let mut message_queues = Vec::new();
+    for queue_data in &route.queue_datas {
+        let broker = v2::Broker {
+            name: queue_data.broker_name.to_string(),
+            id: 0,
+            endpoints: Some(endpoints.clone()),
+        };
+        let readable = PermName::is_readable(queue_data.perm);
+        let writeable = PermName::is_writeable(queue_data.perm);
+        let read_queue_nums = if readable {
+            queue_data.read_queue_nums
+        } else {
+            0
+        };
+        let write_queue_nums = if writeable {
+            queue_data.write_queue_nums
+        } else {
+            0
+        };
+        let queue_nums = queue_data.read_queue_nums.max(queue_data.write_queue_nums);
+        for queue_id in 0..queue_nums {
+            let permission = match (queue_id < read_queue_nums, queue_id < write_queue_nums) {
+                (true, true) => v2::Permission::ReadWrite as i32,
+                (true, false) => v2::Permission::Read as i32,
+                (false, true) => v2::Permission::Write as i32,
+                (false, false) => v2::Permission::None as i32,
+            };
+            message_queues.push(v2::MessageQueue {
+                topic: Some(v2::Resource::new(topic.as_str())),
+                id: queue_id as i32,
+                permission,
+                broker: Some(broker.clone()),
+                accept_message_types: vec![
+                    v2::MessageType::Normal as i32,
+                    v2::MessageType::Fifo as i32,
+                    v2::MessageType::Delay as i32,
+                    v2::MessageType::Transaction as i32,
+                ],
+            });
+        }
+    }
+    message_queues
+}
+
+/// Maps a failure of the proxy to the status reported to clients.
+pub fn status_from_error(error: &ProxyError) -> v2::Status {
+    let status_code = match error {
+        ProxyError::TopicRouteNotFound(_) => v2::Code::TopicNotFound,
+        ProxyError::IllegalArgumentError(_) => v2::Code::BadRequest,
+        ProxyError::MQBrokerError(response_code, _, _) => {
+            match ResponseCode::from(*response_code) {
+                ResponseCode::TopicNotExist => v2::Code::TopicNotFound,
+                ResponseCode::NoPermission => v2::Code::Forbidden,
+                ResponseCode::SystemBusy => v2::Code::TooManyRequests,
+                ResponseCode::MessageIllegal => v2::Code::BadRequest,
+                ResponseCode::SubscriptionGroupNotExist => v2::Code::ConsumerGroupNotFound,
+                // Changing the invisible time of a message which is no longer in flight.
+                ResponseCode::NoMessage => v2::Code::InvalidReceiptHandle,
+                _ => v2::Code::InternalServerError,
+            }
+        }
+        ProxyError::ProxyRemotingError(RemotingError::RemotingTimeoutError(_, _)) => {
+            v2::Code::ProxyTimeout
+        }
+        ProxyError::ProxyRemotingError(RemotingError::RemotingTooMuchRequestError(_)) => {
+            v2::Code::TooManyRequests
+        }
+        _ => v2::Code::InternalServerError,
+    };
+    if status_code == v2::Code::InternalServerError {
+        warn!("proxy request failed: {}", error);
+    }
+    v2::Status::new(status_code, error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::message::message_ext::MessageExt;
+    use rocketmq_remoting::protocol::route::route_data_view::QueueData;
+
+    use super::*;
+    use crate::config::ProxyConfig;
+
+    #[test]
+    fn message_queues_follow_read_and_write_queue_nums() {
+        let route = TopicRouteData {
+            queue_datas: vec![QueueData::new(
+                "broker-a".into(),
+                2,
+                3,
+                PermName::PERM_READ | PermName::PERM_WRITE,
+                0,
+            )],
+            ..Default::default()
+        };
+        let queues = build_message_queues(
+            &CheetahString::from_static_str("TopicTest"),
+            &route,
+            &v2::Endpoints::default(),
+        );
+        let permissions = queues
+            .iter()
+            .map(|queue| (queue.id, queue.permission))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            permissions,
+            vec![
+                (0, v2::Permission::ReadWrite as i32),
+                (1, v2::Permission::ReadWrite as i32),
+                (2, v2::Permission::Write as i32)
+            ]
+        );
+        assert_eq!(queues[0].broker.as_ref().unwrap().name, "broker-a");
+    }
+
+    #[test]
+    fn merge_status_reports_multiple_results() {
+        let ok = Some(v2::Status::ok());
+        let not_found = Some(v2::Status::new(v2::Code::InvalidReceiptHandle, "invalid"));
+        assert_eq!(
+            merge_status([&ok, &ok].into_iter()).code,
+            v2::Code::Ok as i32
+        );
+        assert_eq!(
+            merge_status([&not_found].into_iter()).code,
+            v2::Code::InvalidReceiptHandle as i32
+        );
+        assert_eq!(
+            merge_status([&ok, &not_found].into_iter()).code,
+            v2::Code::MultipleResults as i32
+        );
+    }
+
+    #[test]
+    fn transaction_checks_are_relayed_to_producers_of_the_topic() {
+        let processor = Arc::new(MessagingProcessor::new(Arc::new(ProxyConfig::default())));
+        let service = MessagingService::new(processor);
+        let ctx = ProxyContext {
+            client_id: CheetahString::from_static_str("producer-1"),
+            ..Default::default()
+        };
+        let (sender, mut receiver) = mpsc::channel(8);
+        let settings = v2::TelemetryCommand {
+            status: None,
+            command: Some(v2::telemetry_command::Command::Settings(v2::Settings {
+                pub_sub: Some(v2::settings::PubSub::Publishing(v2::Publishing {
+                    topics: vec![v2::Resource::new("TopicTest")],
+                    ..Default::default()
+                })),
+                ..Default::default()
+            })),
+        };
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        assert!(runtime
+            .block_on(service.telemetry(&ctx, settings, &sender))
+            .is_some());
+
+        let mut message = MessageExt::default();
+        message.set_topic(CheetahString::from_static_str("TopicTest"));
+        let transaction_id =
+            TransactionId::new(CheetahString::from_static_str("broker-a"), 1024, 7);
+        let check = TransactionCheck {
+            message,
+            transaction_id: transaction_id.clone(),
+        };
+        service.recover_orphaned_transaction(check.clone());
+        match receiver.try_recv().unwrap().command {
+            Some(v2::telemetry_command::Command::RecoverOrphanedTransactionCommand(command)) => {
+                assert_eq!(command.transaction_id, transaction_id.to_string());
+                assert_eq!(command.message.unwrap().topic.unwrap().name, "TopicTest");
+            }
+            command => panic!("unexpected telemetry command {:?}", command),
+        }
+
+        let mut other_topic = check.clone();
+        other_topic
+            .message
+            .set_topic(CheetahString::from_static_str("TopicOther"));
+        service.recover_orphaned_transaction(other_topic);
+        assert!(receiver.try_recv().is_err());
+
+        service.close_telemetry(&ctx, &sender);
+        service.recover_orphaned_transaction(check);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn broker_errors_map_to_grpc_codes() {
+        let error = ProxyError::MQBrokerError(
+            ResponseCode::NoPermission as i32,
+            "no permission".to_string(),
+            "127.0.0.1:10911".to_string(),
+        );
+        assert_eq!(status_from_error(&error).code, v2::Code::Forbidden as i32);
+        assert_eq!(
+            status_from_error(&ProxyError::TopicRouteNotFound("T".to_string())).code,
+            v2::Code::TopicNotFound as i32
+        );
+    }
+}
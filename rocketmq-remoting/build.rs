@@ -0,0 +1,37 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Generates the `apache.rocketmq.v2` messages and services from the protos vendored from
+//! `apache/rocketmq-apis`. The protos are parsed with `protox`, so no `protoc` is needed. Servers
+//! get default stubs answering `UNIMPLEMENTED`, so they only implement the methods they serve.
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto");
+    let file_descriptors = protox::compile(
+        [
+            "apache/rocketmq/v2/service.proto",
+            "apache/rocketmq/v2/admin.proto",
+        ],
+        ["proto"],
+    )?;
+    tonic_build::configure()
+        .build_server(true)
+        .build_client(true)
+        .generate_default_stubs(true)
+        .compile_fds(file_descriptors)?;
+    Ok(())
+}
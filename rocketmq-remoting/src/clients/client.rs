@@ -20,6 +20,7 @@ use futures_util::SinkExt;
 use futures_util::StreamExt;
 use rocketmq_rust::ArcMut;
 use tokio::sync::mpsc::Receiver;
+use tokio_util::sync::CancellationToken;
 use tracing::error;
 use tracing::warn;
 
@@ -28,6 +29,8 @@ use crate::base::response_future::ResponseFuture;
 use crate::code::response_code::ResponseCode;
 use crate::connection::Connection;
 use crate::net::channel::Channel;
+use crate::net::transport;
+use crate::net::transport::TransportConnection;
 use crate::protocol::remoting_command::RemotingCommand;
 use crate::protocol::RemotingCommandType;
 use crate::remoting_error::RemotingError::ConnectionInvalid;
@@ -56,6 +59,7 @@ struct ClientInner {
     channel: Channel,
     ctx: ArcMut<ConnectionHandlerContextWrapper>,
     tx: tokio::sync::mpsc::Sender<SendMessage>,
+    shutdown: CancellationToken,
 }
 
 type SendMessage = (
@@ -65,13 +69,35 @@ type SendMessage = (
 );
 
 async fn run_send(mut client: ArcMut<ClientInner>, mut rx: Receiver<SendMessage>) {
-    while let Some((request, tx, timeout)) = rx.recv().await {
+    let shutdown = client.shutdown.clone();
+    loop {
+        let message = tokio::select! {
+            _ = shutdown.cancelled() => None,
+            message = rx.recv() => message,
+        };
+        let Some((request, tx, timeout)) = message else {
+            break;
+        };
         let _ = client.send(request, tx, timeout).await;
     }
+    client.ctx.channel.connection.ok = false;
+    let _ = client.ctx.channel.connection.writer.close().await;
 }
 
 async fn run_recv<PR: RequestProcessor>(mut client: ArcMut<ClientInner>, mut processor: PR) {
-    while let Some(response) = client.ctx.channel.connection.reader.next().await {
+    let shutdown = client.shutdown.clone();
+    loop {
+        let response = tokio::select! {
+            _ = shutdown.cancelled() => {
+                // Dropping the pending futures fails their waiting requests.
+                client.response_table.clear();
+                return;
+            }
+            response = client.ctx.channel.connection.reader.next() => response,
+        };
+        let Some(response) = response else {
+            return;
+        };
         match response {
             Ok(msg) => match msg.get_type() {
                 // handle request
@@ -132,22 +158,19 @@ async fn run_recv<PR: RequestProcessor>(mut client: ArcMut<ClientInner>, mut pro
 }
 
 impl ClientInner {
-    pub async fn connect<T, PR>(
-        addr: T,
+    pub async fn connect<PR>(
+        addr: &str,
         processor: PR,
         tx: Option<&tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
     ) -> Result<(tokio::sync::mpsc::Sender<SendMessage>, ArcMut<ClientInner>)>
     where
-        T: tokio::net::ToSocketAddrs,
         PR: RequestProcessor + 'static,
     {
-        let tcp_stream = tokio::net::TcpStream::connect(addr).await;
-        if tcp_stream.is_err() {
-            return Err(Io(tcp_stream.err().unwrap()));
-        }
-        let stream = tcp_stream?;
-        let local_addr = stream.local_addr()?;
-        let remote_address = stream.peer_addr()?;
+        let TransportConnection {
+            stream,
+            local_addr,
+            remote_addr: remote_address,
+        } = transport::connect(addr).await.map_err(Io)?;
         let connection = Connection::new(stream);
         let response_table = ArcMut::new(HashMap::with_capacity(128));
         let channel = Channel::new(
@@ -166,6 +189,7 @@ impl ClientInner {
             response_table,
             channel,
             tx: tx_.clone(),
+            shutdown: CancellationToken::new(),
         };
         let client = ArcMut::new(client);
 
@@ -215,18 +239,18 @@ impl Client {
     ///
     /// # Arguments
     ///
-    /// * `addr` - The address to connect to.
+    /// * `addr` - The address to connect to, `host:port` for TCP or an address with a [`transport`]
+    ///   scheme.
     ///
     /// # Returns
     ///
     /// A new `Client` instance wrapped in a `Result`. Returns an error if the connection fails.
-    pub async fn connect<T, PR>(
-        addr: T,
+    pub async fn connect<PR>(
+        addr: &str,
         processor: PR,
         tx: Option<&tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
     ) -> Result<Client>
     where
-        T: tokio::net::ToSocketAddrs,
         PR: RequestProcessor + 'static,
     {
         /*let tcp_stream = tokio::net::TcpStream::connect(addr).await;
@@ -348,7 +372,42 @@ impl Client {
         self.inner.ctx.channel.connection_ref()
     }
 
+    /// Closes the connection: the send and receive tasks stop, the write half is shut down and
+    /// requests still waiting for a response fail.
+    pub fn close(&self) {
+        self.inner.shutdown.cancel();
+    }
+
     pub fn connection_mut(&mut self) -> &mut Connection {
         self.inner.ctx.channel.connection_mut()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+
+    #[tokio::test]
+    async fn close_shuts_the_connection_down() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let client = Client::connect(&addr, DefaultRemotingRequestProcessor, None)
+            .await
+            .unwrap();
+        let (mut peer, _) = listener.accept().await.unwrap();
+
+        client.close();
+        let mut buf = [0u8; 16];
+        let read = tokio::time::timeout(Duration::from_secs(3), peer.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(read, 0);
+    }
+}
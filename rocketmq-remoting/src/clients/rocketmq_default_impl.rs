@@ -16,6 +16,7 @@
  */
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::sync::atomic::AtomicI32;
 use std::sync::Arc;
 use std::time::Duration;
@@ -27,6 +28,7 @@ use rocketmq_rust::ArcMut;
 use rocketmq_rust::WeakArcMut;
 use tokio::sync::Mutex;
 use tokio::time;
+use tokio_util::sync::CancellationToken;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -59,6 +61,8 @@ pub struct RocketmqDefaultClient<PR = DefaultRemotingRequestProcessor> {
     client_runtime: Arc<RocketMQRuntime>,
     processor: PR,
     tx: Option<tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
+    rpc_hooks: Vec<Arc<Box<dyn RPCHook>>>,
+    shutdown: CancellationToken,
 }
 impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
     pub fn new(tokio_client_config: Arc<TokioClientConfig>, processor: PR) -> Self {
@@ -80,11 +84,43 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
             client_runtime: Arc::new(RocketMQRuntime::new_multi(10, "client-thread")),
             processor,
             tx,
+            rpc_hooks: Vec::new(),
+            shutdown: CancellationToken::new(),
         }
     }
 }
 
 impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
+    fn do_before_rpc_hooks(
+        &self,
+        addr: Option<&CheetahString>,
+        request: &mut RemotingCommand,
+    ) -> Result<()> {
+        if self.rpc_hooks.is_empty() {
+            return Ok(());
+        }
+        let remote_addr = hook_remote_addr(addr);
+        for hook in self.rpc_hooks.iter() {
+            hook.do_before_request(remote_addr, request)?;
+        }
+        Ok(())
+    }
+
+    fn do_after_rpc_hooks(
+        &self,
+        addr: Option<&CheetahString>,
+        response: &mut RemotingCommand,
+    ) -> Result<()> {
+        if self.rpc_hooks.is_empty() {
+            return Ok(());
+        }
+        let remote_addr = hook_remote_addr(addr);
+        for hook in self.rpc_hooks.iter() {
+            hook.do_after_response(remote_addr, response)?;
+        }
+        Ok(())
+    }
+
     async fn get_and_create_nameserver_client(&self) -> Option<Client> {
         let mut addr = self.namesrv_addr_choosed.as_ref().clone();
         if let Some(ref addr) = addr {
@@ -160,6 +196,9 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
     }
 
     async fn create_client(&self, addr: &CheetahString, duration: Duration) -> Option<Client> {
+        if self.shutdown.is_cancelled() {
+            return None;
+        }
         let mut connection_tables = self.connection_tables.lock().await;
         let cw = connection_tables.get(addr);
         if let Some(cw) = cw {
@@ -182,7 +221,7 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
         let addr_inner = addr.to_string();
 
         match time::timeout(duration, async {
-            Client::connect(addr_inner, self.processor.clone(), self.tx.as_ref()).await
+            Client::connect(&addr_inner, self.processor.clone(), self.tx.as_ref()).await
         })
         .await
         {
@@ -241,25 +280,36 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingService for Rocketmq
     async fn start(&self, this: WeakArcMut<Self>) {
         if let Some(client) = this.upgrade() {
             let connect_timeout_millis = self.tokio_client_config.connect_timeout_millis as u64;
+            let shutdown = self.shutdown.clone();
             self.client_runtime.get_handle().spawn(async move {
                 loop {
                     client.scan_available_name_srv().await;
-                    time::sleep(Duration::from_millis(connect_timeout_millis)).await;
+                    tokio::select! {
+                        _ = shutdown.cancelled() => return,
+                        _ = time::sleep(Duration::from_millis(connect_timeout_millis)) => {}
+                    }
                 }
             });
         }
     }
 
     fn shutdown(&mut self) {
-        todo!()
+        self.shutdown.cancel();
+        let connection_tables = self.connection_tables.clone();
+        self.client_runtime.get_handle().spawn(async move {
+            for (addr, client) in connection_tables.lock().await.drain() {
+                client.close();
+                info!("shutdown remoting client, close the connection to {}", addr);
+            }
+        });
     }
 
     fn register_rpc_hook(&mut self, hook: Arc<Box<dyn RPCHook>>) {
-        todo!()
+        self.rpc_hooks.push(hook);
     }
 
     fn clear_rpc_hook(&mut self) {
-        todo!()
+        self.rpc_hooks.clear();
     }
 }
 
@@ -331,6 +381,8 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingClient for RocketmqD
         request: RemotingCommand,
         timeout_millis: u64,
     ) -> Result<RemotingCommand> {
+        let mut request = request;
+        self.do_before_rpc_hooks(addr, &mut request)?;
         let client = self.get_and_create_client(addr).await;
         match client {
             None => Err(RemotingError::RemoteError("get client failed".to_string())),
@@ -348,7 +400,10 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingClient for RocketmqD
                 {
                     Ok(result) => match result {
                         Ok(response) => match response {
-                            Ok(value) => Ok(value),
+                            Ok(mut value) => {
+                                self.do_after_rpc_hooks(addr, &mut value)?;
+                                Ok(value)
+                            }
                             Err(e) => Err(RemotingError::RemoteError(e.to_string())),
                         },
                         Err(err) => Err(RemotingError::RemoteError(err.to_string())),
@@ -365,6 +420,11 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingClient for RocketmqD
         request: RemotingCommand,
         timeout_millis: u64,
     ) {
+        let mut request = request;
+        if let Err(err) = self.do_before_rpc_hooks(Some(addr), &mut request) {
+            error!("execute rpc hook before oneway request failed: {}", err);
+            return;
+        }
         let client = self.get_and_create_client(Some(addr)).await;
         match client {
             None => {
@@ -400,6 +460,13 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingClient for RocketmqD
     }
 }
 
+/// Hooks take a socket address, name server and broker addresses that can not be parsed
+/// (host names for example) are reported as the unspecified address.
+fn hook_remote_addr(addr: Option<&CheetahString>) -> SocketAddr {
+    addr.and_then(|addr| addr.as_str().parse().ok())
+        .unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 0)))
+}
+
 fn init_value_index() -> i32 {
     let mut rng = rand::thread_rng();
     rng.gen_range(0..999)
@@ -143,11 +143,15 @@ pub enum RequestCode {
     ResetMasterFlushOffset = 908,
     GetAllProducerInfo = 328,
     DeleteExpiredCommitlog = 329,
+    QueryMessageByOffsetRange = 330,
 
     UpdateColdDataFlowCtrConfig = 2001,
     RemoveColdDataFlowCtrConfig = 2002,
     GetColdDataFlowCtrInfo = 2003,
     SetCommitlogReadMode = 2004,
+    /// Rust-only extension without a Java counterpart, Java brokers answer it with
+    /// `RequestCodeNotSupported`.
+    GetPopStats = 2005,
     Unknown = -9999999,
 }
 
@@ -286,10 +290,12 @@ impl From<i32> for RequestCode {
             908 => RequestCode::ResetMasterFlushOffset,
             328 => RequestCode::GetAllProducerInfo,
             329 => RequestCode::DeleteExpiredCommitlog,
+            330 => RequestCode::QueryMessageByOffsetRange,
             2001 => RequestCode::UpdateColdDataFlowCtrConfig,
             2002 => RequestCode::RemoveColdDataFlowCtrConfig,
             2003 => RequestCode::GetColdDataFlowCtrInfo,
             2004 => RequestCode::SetCommitlogReadMode,
+            2005 => RequestCode::GetPopStats,
             _ => RequestCode::Unknown,
         }
     }
@@ -321,3 +327,34 @@ pub enum ControllerRequestCode {
     ControllerGetNextBrokerId = 1012,
     ControllerApplyBrokerId = 1013,
 }
+
+impl From<ControllerRequestCode> for i32 {
+    fn from(value: ControllerRequestCode) -> Self {
+        value as i32
+    }
+}
+
+impl ControllerRequestCode {
+    pub fn to_i32(self) -> i32 {
+        self.into()
+    }
+
+    pub fn value_of(code: i32) -> Option<Self> {
+        match code {
+            1001 => Some(ControllerRequestCode::ControllerAlterSyncStateSet),
+            1002 => Some(ControllerRequestCode::ControllerElectMaster),
+            1003 => Some(ControllerRequestCode::ControllerRegisterBroker),
+            1004 => Some(ControllerRequestCode::ControllerGetReplicaInfo),
+            1005 => Some(ControllerRequestCode::ControllerGetMetadataInfo),
+            1006 => Some(ControllerRequestCode::ControllerGetSyncStateData),
+            1007 => Some(ControllerRequestCode::GetBrokerEpochCache),
+            1008 => Some(ControllerRequestCode::NotifyBrokerRoleChanged),
+            1009 => Some(ControllerRequestCode::UpdateControllerConfig),
+            1010 => Some(ControllerRequestCode::GetControllerConfig),
+            1011 => Some(ControllerRequestCode::CleanBrokerData),
+            1012 => Some(ControllerRequestCode::ControllerGetNextBrokerId),
+            1013 => Some(ControllerRequestCode::ControllerApplyBrokerId),
+            _ => None,
+        }
+    }
+}
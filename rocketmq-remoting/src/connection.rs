@@ -20,17 +20,17 @@ use std::hash::Hasher;
 use futures_util::stream::SplitSink;
 use futures_util::stream::SplitStream;
 use futures_util::StreamExt;
-use tokio::net::TcpStream;
 use tokio_util::codec::Framed;
 
 use crate::codec::remoting_command_codec::RemotingCommandCodec;
+use crate::net::transport::BoxedTransportStream;
 use crate::protocol::remoting_command::RemotingCommand;
 
 /// Send and receive `Frame` values from a remote peer.
 ///
 /// When implementing networking protocols, a message on that protocol is
 /// often composed of several smaller messages known as frames. The purpose of
-/// `Connection` is to read and write frames on the underlying `TcpStream`.
+/// `Connection` is to read and write frames on the underlying transport stream.
 ///
 /// To read frames, the `Connection` uses an internal framed, which is filled
 /// up until there are enough bytes to create a full frame. Once this happens,
@@ -39,11 +39,12 @@ use crate::protocol::remoting_command::RemotingCommand;
 /// When sending frames, the frame is first encoded into the write buffer.
 /// The contents of the write buffer are then written to the socket.
 pub struct Connection {
-    /// The `Framed` instance used for reading from and writing to the TCP stream.
+    /// The `Framed` instance used for reading from and writing to the transport stream.
     /// It leverages the `RemotingCommandCodec` for encoding and decoding frames.
-    //pub(crate) framed: Framed<TcpStream, RemotingCommandCodec>,
-    pub(crate) writer: SplitSink<Framed<TcpStream, RemotingCommandCodec>, RemotingCommand>,
-    pub(crate) reader: SplitStream<Framed<TcpStream, RemotingCommandCodec>>,
+    //pub(crate) framed: Framed<BoxedTransportStream, RemotingCommandCodec>,
+    pub(crate) writer:
+        SplitSink<Framed<BoxedTransportStream, RemotingCommandCodec>, RemotingCommand>,
+    pub(crate) reader: SplitStream<Framed<BoxedTransportStream, RemotingCommandCodec>>,
 
     /// A boolean flag indicating the current state of the connection.
     /// `true` means the connection is in a good state, while `false` indicates
@@ -59,12 +60,15 @@ impl Hash for Connection {
         // Use the addr: *const _ess of writer and reader to hash them (they serve as a unique
         // identifier for these components)
         let writer_addr: *const SplitSink<
-            Framed<TcpStream, RemotingCommandCodec>,
+            Framed<BoxedTransportStream, RemotingCommandCodec>,
             RemotingCommand,
         > = &self.writer
-            as *const SplitSink<Framed<TcpStream, RemotingCommandCodec>, RemotingCommand>;
-        let reader_addr: *const SplitStream<Framed<TcpStream, RemotingCommandCodec>> =
-            &self.reader as *const SplitStream<Framed<TcpStream, RemotingCommandCodec>>;
+            as *const SplitSink<
+                Framed<BoxedTransportStream, RemotingCommandCodec>,
+                RemotingCommand,
+            >;
+        let reader_addr: *const SplitStream<Framed<BoxedTransportStream, RemotingCommandCodec>> =
+            &self.reader as *const SplitStream<Framed<BoxedTransportStream, RemotingCommandCodec>>;
 
         writer_addr.hash(state);
         reader_addr.hash(state);
@@ -89,13 +93,14 @@ impl Connection {
     ///
     /// # Arguments
     ///
-    /// * `tcp_stream` - The `TcpStream` associated with the connection.
+    /// * `stream` - The stream associated with the connection, TCP or any other
+    ///   [`TransportStream`](crate::net::transport::TransportStream).
     ///
     /// # Returns
     ///
     /// A new `Connection` instance.
-    pub fn new(tcp_stream: TcpStream) -> Connection {
-        let framed = Framed::with_capacity(tcp_stream, RemotingCommandCodec::new(), 1024 * 4);
+    pub fn new(stream: BoxedTransportStream) -> Connection {
+        let framed = Framed::with_capacity(stream, RemotingCommandCodec::new(), 1024 * 4);
         let (writer, reader) = framed.split();
         Self {
             writer,
@@ -106,14 +111,16 @@ impl Connection {
 }
 
 impl Connection {
-    /*pub fn framed(&self) -> &Framed<TcpStream, RemotingCommandCodec> {
+    /*pub fn framed(&self) -> &Framed<BoxedTransportStream, RemotingCommandCodec> {
         &self.framed
     }*/
-    pub fn reader(&self) -> &SplitStream<Framed<TcpStream, RemotingCommandCodec>> {
+    pub fn reader(&self) -> &SplitStream<Framed<BoxedTransportStream, RemotingCommandCodec>> {
         &self.reader
     }
 
-    pub fn writer(&self) -> &SplitSink<Framed<TcpStream, RemotingCommandCodec>, RemotingCommand> {
+    pub fn writer(
+        &self,
+    ) -> &SplitSink<Framed<BoxedTransportStream, RemotingCommandCodec>, RemotingCommand> {
         &self.writer
     }
 }
@@ -0,0 +1,115 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! The `apache.rocketmq.v2` gRPC protocol spoken by gRPC clients and proxies. Messages and
+//! services are generated from the protos vendored from `apache/rocketmq-apis` under `proto/`.
+
+pub use prost_types::Duration;
+pub use prost_types::Timestamp;
+
+pub mod v2 {
+    #![allow(clippy::all)]
+    tonic::include_proto!("apache.rocketmq.v2");
+
+    impl Status {
+        pub fn new(code: Code, message: impl Into<String>) -> Self {
+            Status {
+                code: code as i32,
+                message: message.into(),
+            }
+        }
+
+        pub fn ok() -> Self {
+            Status::new(Code::Ok, "OK")
+        }
+    }
+
+    impl Resource {
+        pub fn new(name: impl Into<String>) -> Self {
+            Resource {
+                resource_namespace: String::new(),
+                name: name.into(),
+            }
+        }
+    }
+}
+
+pub fn duration_from_millis(millis: u64) -> Duration {
+    Duration {
+        seconds: (millis / 1000) as i64,
+        nanos: ((millis % 1000) * 1_000_000) as i32,
+    }
+}
+
+/// Milliseconds of a duration, negative durations count as zero.
+pub fn duration_to_millis(duration: &Duration) -> u64 {
+    (duration.seconds.max(0) as u64) * 1000 + (duration.nanos.max(0) / 1_000_000) as u64
+}
+
+pub fn timestamp_from_millis(millis: i64) -> Timestamp {
+    Timestamp {
+        seconds: millis.div_euclid(1000),
+        nanos: (millis.rem_euclid(1000) * 1_000_000) as i32,
+    }
+}
+
+pub fn timestamp_to_millis(timestamp: &Timestamp) -> i64 {
+    timestamp.seconds * 1000 + (timestamp.nanos / 1_000_000) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+
+    use super::*;
+
+    #[test]
+    fn millis_round_trip() {
+        assert_eq!(duration_to_millis(&duration_from_millis(30_250)), 30_250);
+        assert_eq!(
+            timestamp_to_millis(&timestamp_from_millis(1_700_000_000_123)),
+            1_700_000_000_123
+        );
+        assert_eq!(timestamp_from_millis(-1).seconds, -1);
+        assert_eq!(timestamp_to_millis(&timestamp_from_millis(-1)), -1);
+    }
+
+    #[test]
+    fn telemetry_settings_round_trip() {
+        let command = v2::TelemetryCommand {
+            status: Some(v2::Status::ok()),
+            command: Some(v2::telemetry_command::Command::Settings(v2::Settings {
+                client_type: Some(v2::ClientType::SimpleConsumer as i32),
+                request_timeout: Some(duration_from_millis(3000)),
+                pub_sub: Some(v2::settings::PubSub::Subscription(v2::Subscription {
+                    group: Some(v2::Resource::new("GID")),
+                    subscriptions: vec![v2::SubscriptionEntry {
+                        topic: Some(v2::Resource::new("TopicTest")),
+                        expression: Some(v2::FilterExpression {
+                            r#type: v2::FilterType::Tag as i32,
+                            expression: "*".to_string(),
+                        }),
+                    }],
+                    long_polling_timeout: Some(duration_from_millis(30_000)),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            })),
+        };
+        let decoded = v2::TelemetryCommand::decode(command.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, command);
+    }
+}
@@ -23,6 +23,7 @@ pub mod clients;
 pub mod code;
 pub mod codec;
 pub mod connection;
+pub mod grpc;
 pub mod net;
 pub mod protocol;
 pub mod remoting_error;
@@ -16,3 +16,4 @@
  */
 
 pub mod channel;
+pub mod transport;
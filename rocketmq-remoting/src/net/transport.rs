@@ -0,0 +1,340 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! Byte stream transports the remoting protocol runs on, selected by address scheme:
+//!
+//! * `host:port` - TCP, the default.
+//! * `unix:<path>` - Unix domain socket, for a proxy co-located with its broker.
+//! * `inproc:<name>` - in-process pipe, for the embedded proxy and tests.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::IpAddr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::DuplexStream;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+pub const UNIX_SCHEME: &str = "unix:";
+pub const IN_PROCESS_SCHEME: &str = "inproc:";
+
+const IN_PROCESS_BUFFER_SIZE: usize = 256 * 1024;
+
+lazy_static! {
+    static ref IN_PROCESS_ACCEPTORS: Mutex<HashMap<String, mpsc::Sender<DuplexStream>>> =
+        Mutex::new(HashMap::new());
+}
+
+static NEXT_SYNTHETIC_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A bidirectional byte stream a [`Connection`](crate::connection::Connection) can frame
+/// remoting commands on.
+pub trait TransportStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> TransportStream for T {}
+
+pub type BoxedTransportStream = Box<dyn TransportStream>;
+
+/// The transport an address resolves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportAddr<'a> {
+    Tcp(&'a str),
+    Unix(&'a str),
+    InProcess(&'a str),
+}
+
+impl<'a> TransportAddr<'a> {
+    pub fn parse(addr: &'a str) -> Self {
+        if let Some(path) = addr.strip_prefix(UNIX_SCHEME) {
+            TransportAddr::Unix(path)
+        } else if let Some(name) = addr.strip_prefix(IN_PROCESS_SCHEME) {
+            TransportAddr::InProcess(name)
+        } else {
+            TransportAddr::Tcp(addr)
+        }
+    }
+}
+
+/// An established stream with the socket addresses identifying its channel. Transports without
+/// socket addresses get a unique address in the discard-only `100::/64` prefix (RFC 6666), so a
+/// synthetic address never names a reachable host.
+pub struct TransportConnection {
+    pub stream: BoxedTransportStream,
+    pub local_addr: SocketAddr,
+    pub remote_addr: SocketAddr,
+}
+
+impl TransportConnection {
+    fn tcp(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            local_addr: stream.local_addr()?,
+            remote_addr: stream.peer_addr()?,
+            stream: Box::new(stream),
+        })
+    }
+
+    fn synthetic(stream: BoxedTransportStream) -> Self {
+        Self {
+            stream,
+            local_addr: synthetic_addr(),
+            remote_addr: synthetic_addr(),
+        }
+    }
+}
+
+fn synthetic_addr() -> SocketAddr {
+    let id = NEXT_SYNTHETIC_ID.fetch_add(1, Ordering::Relaxed);
+    let ip = Ipv6Addr::from((0x0100u128 << 112) | id as u128);
+    SocketAddr::new(IpAddr::V6(ip), 0)
+}
+
+/// Connects to `addr` over the transport its scheme selects.
+pub async fn connect(addr: &str) -> io::Result<TransportConnection> {
+    match TransportAddr::parse(addr) {
+        TransportAddr::Tcp(addr) => TransportConnection::tcp(TcpStream::connect(addr).await?),
+        #[cfg(unix)]
+        TransportAddr::Unix(path) => Ok(TransportConnection::synthetic(Box::new(
+            tokio::net::UnixStream::connect(path).await?,
+        ))),
+        #[cfg(not(unix))]
+        TransportAddr::Unix(_) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "unix domain sockets are not supported on this platform",
+        )),
+        TransportAddr::InProcess(name) => {
+            let acceptor = IN_PROCESS_ACCEPTORS.lock().get(name).cloned();
+            let Some(acceptor) = acceptor else {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionRefused,
+                    format!("no in-process listener named {name}"),
+                ));
+            };
+            let (client, server) = tokio::io::duplex(IN_PROCESS_BUFFER_SIZE);
+            acceptor.send(server).await.map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::ConnectionRefused,
+                    format!("in-process listener {name} is closed"),
+                )
+            })?;
+            Ok(TransportConnection::synthetic(Box::new(client)))
+        }
+    }
+}
+
+/// Removes the socket file at `path` if nothing accepts connections on it any more.
+#[cfg(unix)]
+fn remove_stale_unix_socket(path: &str) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {}
+        _ => return Ok(()),
+    }
+    match std::os::unix::net::UnixStream::connect(path) {
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("unix socket {path} is in use by another listener"),
+        )),
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => std::fs::remove_file(path),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Accepts connections on one transport.
+pub enum TransportListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, std::path::PathBuf),
+    InProcess(mpsc::Receiver<DuplexStream>, String),
+}
+
+impl TransportListener {
+    /// Binds `addr` on the transport its scheme selects. A unix socket file left by a previous
+    /// process is replaced only once a connect to it is refused; a live socket, like an
+    /// in-process name already bound, is rejected.
+    pub async fn bind(addr: &str) -> io::Result<Self> {
+        match TransportAddr::parse(addr) {
+            TransportAddr::Tcp(addr) => Ok(TransportListener::Tcp(TcpListener::bind(addr).await?)),
+            #[cfg(unix)]
+            TransportAddr::Unix(path) => {
+                remove_stale_unix_socket(path)?;
+                Ok(TransportListener::Unix(
+                    tokio::net::UnixListener::bind(path)?,
+                    path.into(),
+                ))
+            }
+            #[cfg(not(unix))]
+            TransportAddr::Unix(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "unix domain sockets are not supported on this platform",
+            )),
+            TransportAddr::InProcess(name) => {
+                let mut acceptors = IN_PROCESS_ACCEPTORS.lock();
+                if acceptors.contains_key(name) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        format!("in-process listener {name} is already bound"),
+                    ));
+                }
+                let (tx, rx) = mpsc::channel(128);
+                acceptors.insert(name.to_string(), tx);
+                Ok(TransportListener::InProcess(rx, name.to_string()))
+            }
+        }
+    }
+
+    pub async fn accept(&mut self) -> io::Result<TransportConnection> {
+        match self {
+            TransportListener::Tcp(listener) => {
+                let (stream, _) = listener.accept().await?;
+                TransportConnection::tcp(stream)
+            }
+            #[cfg(unix)]
+            TransportListener::Unix(listener, _) => {
+                let (stream, _) = listener.accept().await?;
+                Ok(TransportConnection::synthetic(Box::new(stream)))
+            }
+            TransportListener::InProcess(rx, name) => match rx.recv().await {
+                Some(stream) => Ok(TransportConnection::synthetic(Box::new(stream))),
+                None => Err(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    format!("in-process listener {name} is closed"),
+                )),
+            },
+        }
+    }
+}
+
+impl Drop for TransportListener {
+    fn drop(&mut self) {
+        match self {
+            TransportListener::Tcp(_) => {}
+            #[cfg(unix)]
+            TransportListener::Unix(_, path) => {
+                let _ = std::fs::remove_file(path);
+            }
+            TransportListener::InProcess(_, name) => {
+                IN_PROCESS_ACCEPTORS.lock().remove(name.as_str());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+
+    use super::*;
+
+    #[test]
+    fn scheme_selects_transport() {
+        assert_eq!(
+            TransportAddr::parse("127.0.0.1:9876"),
+            TransportAddr::Tcp("127.0.0.1:9876")
+        );
+        assert_eq!(
+            TransportAddr::parse("unix:/tmp/broker.sock"),
+            TransportAddr::Unix("/tmp/broker.sock")
+        );
+        assert_eq!(
+            TransportAddr::parse("inproc:broker"),
+            TransportAddr::InProcess("broker")
+        );
+    }
+
+    #[tokio::test]
+    async fn in_process_listener_accepts_connections_until_dropped() {
+        let mut listener = TransportListener::bind("inproc:transport_test")
+            .await
+            .unwrap();
+        assert!(TransportListener::bind("inproc:transport_test")
+            .await
+            .is_err());
+
+        let mut client = connect("inproc:transport_test").await.unwrap();
+        let mut server = listener.accept().await.unwrap();
+        client.stream.write_all(b"ping").await.unwrap();
+        let mut buf = [0u8; 4];
+        server.stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"ping");
+
+        drop(listener);
+        assert!(connect("inproc:transport_test").await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_listener_accepts_connections() {
+        let dir = std::env::temp_dir().join(format!("rocketmq-uds-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let addr = format!("{UNIX_SCHEME}{}", dir.join("remoting.sock").display());
+        let mut listener = TransportListener::bind(&addr).await.unwrap();
+
+        let mut client = connect(&addr).await.unwrap();
+        let mut server = listener.accept().await.unwrap();
+        server.stream.write_all(b"pong").await.unwrap();
+        let mut buf = [0u8; 4];
+        client.stream.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"pong");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_listener_replaces_only_stale_sockets() {
+        let dir = std::env::temp_dir().join(format!("rocketmq-uds-stale-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("remoting.sock");
+        let addr = format!("{UNIX_SCHEME}{}", path.display());
+
+        // A std listener leaves its socket file behind when dropped.
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+        let listener = TransportListener::bind(&addr).await.unwrap();
+
+        let err = TransportListener::bind(&addr).await.err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+        assert!(connect(&addr).await.is_ok());
+
+        drop(listener);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn synthetic_addresses_are_unique_and_unroutable() {
+        let first = synthetic_addr();
+        let second = synthetic_addr();
+        assert_ne!(first, second);
+        for addr in [first, second] {
+            let IpAddr::V6(ip) = addr.ip() else {
+                panic!("synthetic address {addr} is not in the discard prefix");
+            };
+            assert_eq!(ip.segments()[..4], [0x0100, 0, 0, 0]);
+            assert!(!ip.is_loopback());
+        }
+    }
+}
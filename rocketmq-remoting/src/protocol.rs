@@ -37,6 +37,7 @@ use crate::rocketmq_serializable::RocketMQSerializable;
 pub mod admin;
 pub mod body;
 pub mod command_custom_header;
+pub mod fastjson_compat;
 pub mod filter;
 pub mod forbidden_type;
 pub mod header;
@@ -85,7 +86,7 @@ impl RemotingCommandType {
 }
 
 #[repr(u8)]
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Default, Hash, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Default, Hash, Copy)]
 pub enum LanguageCode {
     JAVA,
     CPP,
@@ -103,6 +104,45 @@ pub enum LanguageCode {
     RUST,
 }
 
+/// Accepts the name of the language as well as its code, which is what Java peers write when
+/// enums are serialized as ordinals.
+impl<'de> Deserialize<'de> for LanguageCode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct LanguageCodeVisitor;
+
+        impl serde::de::Visitor<'_> for LanguageCodeVisitor {
+            type Value = LanguageCode;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a name or code of LanguageCode")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                LanguageCode::get_code_from_name(value)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(value), &self))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                u8::try_from(value)
+                    .ok()
+                    .and_then(LanguageCode::value_of)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Unsigned(value), &self))
+            }
+        }
+
+        deserializer.deserialize_any(LanguageCodeVisitor)
+    }
+}
+
 impl fmt::Display for LanguageCode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -430,7 +470,7 @@ impl<T: Serialize> RemotingSerializable for T {
 impl<T: serde::de::DeserializeOwned> RemotingDeserializable for T {
     type Output = T;
     fn decode(bytes: &[u8]) -> Result<Self::Output, Error> {
-        SerdeJsonUtils::decode(bytes)
+        fastjson_compat::decode(bytes)
     }
 }
 
@@ -25,6 +25,7 @@ use serde_json_any_key::*;
 use crate::protocol::admin::offset_wrapper::OffsetWrapper;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ConsumeStats {
     #[serde(with = "any_key_map")]
     offset_table: HashMap<MessageQueue, OffsetWrapper>,
@@ -53,8 +54,12 @@ impl ConsumeStats {
             .sum()
     }
 
-    pub fn get_offset_table(&self) -> HashMap<MessageQueue, OffsetWrapper> {
-        self.offset_table.clone()
+    pub fn get_offset_table(&self) -> &HashMap<MessageQueue, OffsetWrapper> {
+        &self.offset_table
+    }
+
+    pub fn get_offset_table_mut(&mut self) -> &mut HashMap<MessageQueue, OffsetWrapper> {
+        &mut self.offset_table
     }
 
     pub fn set_offset_table(&mut self, offset_table: HashMap<MessageQueue, OffsetWrapper>) {
@@ -69,3 +74,73 @@ impl ConsumeStats {
         self.consume_tps = consume_tps;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+
+    fn offset_wrapper(broker_offset: i64, consumer_offset: i64, pull_offset: i64) -> OffsetWrapper {
+        let mut offset_wrapper = OffsetWrapper::new();
+        offset_wrapper.set_broker_offset(broker_offset);
+        offset_wrapper.set_consumer_offset(consumer_offset);
+        offset_wrapper.set_pull_offset(pull_offset);
+        offset_wrapper
+    }
+
+    #[test]
+    fn compute_diff_sums_all_queues() {
+        let mut consume_stats = ConsumeStats::new();
+        for (queue_id, wrapper) in [offset_wrapper(100, 40, 60), offset_wrapper(50, 50, 50)]
+            .into_iter()
+            .enumerate()
+        {
+            consume_stats.get_offset_table_mut().insert(
+                MessageQueue::from_parts(
+                    CheetahString::from_static_str("topic"),
+                    CheetahString::from_static_str("broker-a"),
+                    queue_id as i32,
+                ),
+                wrapper,
+            );
+        }
+        assert_eq!(consume_stats.get_offset_table().len(), 2);
+        assert_eq!(consume_stats.compute_total_diff(), 60);
+        assert_eq!(consume_stats.compute_inflight_total_diff(), 20);
+    }
+
+    #[test]
+    fn consume_stats_serializes_in_camel_case() {
+        let mut consume_stats = ConsumeStats::new();
+        consume_stats.set_consume_tps(1.5);
+        let json = serde_json::to_string(&consume_stats).unwrap();
+        assert!(json.contains("\"offsetTable\""));
+        assert!(json.contains("\"consumeTps\""));
+    }
+
+    #[test]
+    fn consume_stats_round_trips_through_json() {
+        let mut consume_stats = ConsumeStats::new();
+        let mut wrapper = offset_wrapper(100, 40, 60);
+        wrapper.set_last_timestamp(1_700_000_000_000);
+        let mq = MessageQueue::from_parts(
+            CheetahString::from_static_str("topic"),
+            CheetahString::from_static_str("broker-a"),
+            3,
+        );
+        consume_stats
+            .get_offset_table_mut()
+            .insert(mq.clone(), wrapper);
+        consume_stats.set_consume_tps(2.5);
+
+        let bytes = serde_json::to_vec(&consume_stats).unwrap();
+        let decoded: ConsumeStats = serde_json::from_slice(&bytes).unwrap();
+        let decoded_wrapper = decoded.get_offset_table().get(&mq).unwrap();
+        assert_eq!(decoded_wrapper.get_broker_offset(), 100);
+        assert_eq!(decoded_wrapper.get_consumer_offset(), 40);
+        assert_eq!(decoded_wrapper.get_last_timestamp(), 1_700_000_000_000);
+        assert_eq!(decoded.get_consume_tps(), 2.5);
+        assert_eq!(decoded.compute_total_diff(), 60);
+    }
+}
@@ -19,6 +19,7 @@ use serde::Deserialize;
 use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
 pub struct OffsetWrapper {
     broker_offset: i64,
     consumer_offset: i64,
@@ -30,17 +30,23 @@ pub mod cluster_acl_version_info;
 pub mod cm_result;
 pub mod connection;
 pub mod consume_message_directly_result;
+pub mod consume_status;
+pub mod consumer_offset_serialize_wrapper;
 pub mod group_list;
 pub mod kv_table;
 pub mod pop_process_queue_info;
+pub mod pop_stats;
 pub mod process_queue_info;
 pub mod producer_connection;
 pub mod query_assignment_request_body;
 pub mod query_assignment_response_body;
 pub mod queue_time_span;
 pub mod request;
+pub mod reset_offset_body;
 pub mod response;
 pub mod set_message_request_mode_request_body;
+pub mod subscription_group_wrapper;
+pub mod sync_state_set;
 pub mod topic;
 pub mod topic_info_wrapper;
 pub mod unlock_batch_request_body;
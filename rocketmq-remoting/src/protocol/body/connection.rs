@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 use cheetah_string::CheetahString;
+use rocketmq_common::common::mq_version::RocketMqVersion;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -27,6 +28,9 @@ pub struct Connection {
     client_addr: CheetahString,
     language: LanguageCode,
     version: i32,
+    /// Last heartbeat time of the client in milliseconds, 0 when unknown.
+    #[serde(default)]
+    last_update_timestamp: i64,
 }
 
 impl Connection {
@@ -36,6 +40,7 @@ impl Connection {
             client_addr: CheetahString::default(),
             language: LanguageCode::default(),
             version: 0,
+            last_update_timestamp: 0,
         }
     }
 }
@@ -72,4 +77,32 @@ impl Connection {
     pub fn set_version(&mut self, version: i32) {
         self.version = version;
     }
+
+    /// Name of the client's version, e.g. `V531`, for display in connection listings.
+    pub fn get_version_desc(&self) -> String {
+        RocketMqVersion::version_desc(self.version)
+    }
+
+    pub fn get_last_update_timestamp(&self) -> i64 {
+        self.last_update_timestamp
+    }
+
+    pub fn set_last_update_timestamp(&mut self, last_update_timestamp: i64) {
+        self.last_update_timestamp = last_update_timestamp;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_deserializes_without_last_update_timestamp() {
+        let json = r#"{"clientId":"client","clientAddr":"127.0.0.1:1234","language":"JAVA","version":453}"#;
+        let connection: Connection = serde_json::from_str(json).unwrap();
+        assert_eq!(connection.get_client_id(), "client");
+        assert_eq!(connection.get_version(), 453);
+        assert_eq!(connection.get_last_update_timestamp(), 0);
+        assert_eq!(connection.get_version_desc(), "V520");
+    }
 }
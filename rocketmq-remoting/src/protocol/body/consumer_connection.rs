@@ -23,6 +23,8 @@ use cheetah_string::CheetahString;
 use parking_lot::RwLock;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
 use serde::ser::SerializeStruct;
+use serde::Deserialize;
+use serde::Deserializer;
 use serde::Serialize;
 use serde::Serializer;
 
@@ -58,15 +60,46 @@ impl Serialize for ConsumerConnection {
         S: Serializer,
     {
         let mut s = serializer.serialize_struct("ConsumerConnection", 5)?;
-        s.serialize_field("connection_set", &self.connection_set)?;
-        s.serialize_field("subscription_table", &*self.subscription_table.read())?;
-        s.serialize_field("consume_type", &*self.consume_type.read())?;
-        s.serialize_field("message_model", &*self.message_model.read())?;
-        s.serialize_field("consume_from_where", &*self.consume_from_where.read())?;
+        s.serialize_field("connectionSet", &self.connection_set)?;
+        s.serialize_field("subscriptionTable", &*self.subscription_table.read())?;
+        s.serialize_field("consumeType", &*self.consume_type.read())?;
+        s.serialize_field("messageModel", &*self.message_model.read())?;
+        s.serialize_field("consumeFromWhere", &*self.consume_from_where.read())?;
         s.end()
     }
 }
 
+impl<'de> Deserialize<'de> for ConsumerConnection {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ConsumerConnectionData {
+            #[serde(default)]
+            connection_set: HashSet<Connection>,
+            #[serde(default)]
+            subscription_table: HashMap<CheetahString, SubscriptionData>,
+            #[serde(default)]
+            consume_type: ConsumeType,
+            #[serde(default)]
+            message_model: MessageModel,
+            #[serde(default)]
+            consume_from_where: ConsumeFromWhere,
+        }
+
+        let data = ConsumerConnectionData::deserialize(deserializer)?;
+        Ok(ConsumerConnection {
+            connection_set: data.connection_set,
+            subscription_table: Arc::new(RwLock::new(data.subscription_table)),
+            consume_type: Arc::new(RwLock::new(data.consume_type)),
+            message_model: Arc::new(RwLock::new(data.message_model)),
+            consume_from_where: Arc::new(RwLock::new(data.consume_from_where)),
+        })
+    }
+}
+
 impl ConsumerConnection {
     pub fn get_connection_set(&self) -> HashSet<Connection> {
         self.connection_set.clone()
@@ -111,3 +144,25 @@ impl ConsumerConnection {
         *self.consume_from_where.write() = consume_from_where;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consumer_connection_round_trips_with_camel_case_fields() {
+        let mut connection = Connection::new();
+        connection.set_client_id(CheetahString::from_static_str("client"));
+        let mut consumer_connection = ConsumerConnection::new();
+        consumer_connection.set_connection_set(HashSet::from([connection]));
+        consumer_connection.set_consume_type(ConsumeType::ConsumePassively);
+
+        let json = serde_json::to_string(&consumer_connection).unwrap();
+        assert!(json.contains("\"connectionSet\":["));
+        assert!(json.contains("\"consumeFromWhere\""));
+
+        let decoded: ConsumerConnection = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.get_connection_set().len(), 1);
+        assert_eq!(decoded.get_consume_type(), ConsumeType::ConsumePassively);
+    }
+}
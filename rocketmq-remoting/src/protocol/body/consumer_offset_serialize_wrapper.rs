@@ -0,0 +1,53 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use cheetah_string::CheetahString;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::DataVersion;
+
+/// The committed consumer offsets of a broker, as returned by `GetAllConsumerOffset`.
+///
+/// The offset table is keyed by `topic@group`, then by queue id.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerOffsetSerializeWrapper {
+    #[serde(default)]
+    pub offset_table: HashMap<CheetahString, HashMap<i32, i64>>,
+    #[serde(default)]
+    pub data_version: DataVersion,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::RemotingDeserializable;
+
+    #[test]
+    fn decode_ignores_the_broker_only_tables() {
+        let json = r#"{"dataVersion":{"counter":3,"stateVersion":0,"timestamp":1},
+            "offsetTable":{"TopicA@GroupA":{"0":10,"1":20}},
+            "pullOffsetTable":{"TopicA@GroupA":{"0":12}},
+            "resetOffsetTable":{}}"#;
+        let wrapper = ConsumerOffsetSerializeWrapper::decode(json.as_bytes()).unwrap();
+        let offsets = wrapper.offset_table.get("TopicA@GroupA").unwrap();
+        assert_eq!(offsets.get(&0), Some(&10));
+        assert_eq!(offsets.get(&1), Some(&20));
+    }
+}
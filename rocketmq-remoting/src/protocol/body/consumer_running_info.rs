@@ -14,5 +14,262 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-#[derive(Clone)]
-pub struct ConsumerRunningInfo {}
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::TimeUtils::get_current_millis;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json_any_key::*;
+
+use crate::protocol::body::consume_status::ConsumeStatus;
+use crate::protocol::body::pop_process_queue_info::PopProcessQueueInfo;
+use crate::protocol::body::process_queue_info::ProcessQueueInfo;
+use crate::protocol::heartbeat::subscription_data::SubscriptionData;
+
+/// Snapshot of a consumer instance reported through `GET_CONSUMER_RUNNING_INFO`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsumerRunningInfo {
+    pub properties: BTreeMap<String, String>,
+    pub subscription_set: HashSet<SubscriptionData>,
+    #[serde(with = "any_key_map")]
+    pub mq_table: BTreeMap<MessageQueue, ProcessQueueInfo>,
+    #[serde(with = "any_key_map", default)]
+    pub mq_pop_table: BTreeMap<MessageQueue, PopProcessQueueInfo>,
+    pub status_table: BTreeMap<CheetahString, ConsumeStatus>,
+    #[serde(default)]
+    pub user_consumer_info: BTreeMap<String, String>,
+    pub jstack: Option<String>,
+}
+
+impl ConsumerRunningInfo {
+    pub const PROP_NAMESERVER_ADDR: &'static str = "PROP_NAMESERVER_ADDR";
+    pub const PROP_THREADPOOL_CORE_SIZE: &'static str = "PROP_THREADPOOL_CORE_SIZE";
+    pub const PROP_CONSUME_ORDERLY: &'static str = "PROP_CONSUMEORDERLY";
+    pub const PROP_CONSUME_TYPE: &'static str = "PROP_CONSUME_TYPE";
+    pub const PROP_CLIENT_VERSION: &'static str = "PROP_CLIENT_VERSION";
+    pub const PROP_CONSUMER_START_TIMESTAMP: &'static str = "PROP_CONSUMER_START_TIMESTAMP";
+
+    /// Checks that every push consumer of a group, once running for a while, reports the same
+    /// non-empty subscription set.
+    pub fn analyze_subscription(cri_table: &BTreeMap<String, ConsumerRunningInfo>) -> bool {
+        let Some(first) = cri_table.values().next() else {
+            return true;
+        };
+        let push = first
+            .properties
+            .get(Self::PROP_CONSUME_TYPE)
+            .is_some_and(|consume_type| consume_type == "CONSUME_PASSIVELY");
+        let start_for_a_while = first
+            .properties
+            .get(Self::PROP_CONSUMER_START_TIMESTAMP)
+            .and_then(|timestamp| timestamp.parse::<u64>().ok())
+            .is_some_and(|timestamp| {
+                get_current_millis().saturating_sub(timestamp) > 2 * 60 * 1000
+            });
+        if push && start_for_a_while {
+            if cri_table
+                .values()
+                .any(|info| info.subscription_set != first.subscription_set)
+            {
+                return false;
+            }
+            if first.subscription_set.is_empty() {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn format_string(&self) -> String {
+        let mut sb = String::new();
+        sb.push_str("#Consumer Properties#\n");
+        for (key, value) in self.properties.iter() {
+            let _ = writeln!(sb, "{:<40}: {}", key, value);
+        }
+
+        sb.push_str("\n\n#Consumer Subscription#\n");
+        let mut subscriptions: Vec<_> = self.subscription_set.iter().collect();
+        subscriptions.sort_by(|a, b| a.topic.cmp(&b.topic));
+        for (index, subscription) in subscriptions.into_iter().enumerate() {
+            let _ = writeln!(
+                sb,
+                "{:03} Topic: {:<40} ClassFilter: {:<8} SubExpression: {}",
+                index + 1,
+                subscription.topic,
+                subscription.class_filter_mode,
+                subscription.sub_string
+            );
+        }
+
+        sb.push_str("\n\n#Consumer Offset#\n");
+        let _ = writeln!(
+            sb,
+            "{:<64}  {:<32}  {:<4}  {:<20}",
+            "#Topic", "#Broker Name", "#QID", "#Consumer Offset"
+        );
+        for (mq, info) in self.mq_table.iter() {
+            let _ = writeln!(
+                sb,
+                "{:<32}  {:<32}  {:<4}  {:<20}",
+                mq.get_topic(),
+                mq.get_broker_name(),
+                mq.get_queue_id(),
+                info.commit_offset
+            );
+        }
+
+        sb.push_str("\n\n#Consumer MQ Detail#\n");
+        let _ = writeln!(
+            sb,
+            "{:<64}  {:<32}  {:<4}  {:<20}",
+            "#Topic", "#Broker Name", "#QID", "#ProcessQueueInfo"
+        );
+        for (mq, info) in self.mq_table.iter() {
+            let _ = writeln!(
+                sb,
+                "{:<64}  {:<32}  {:<4}  {}",
+                mq.get_topic(),
+                mq.get_broker_name(),
+                mq.get_queue_id(),
+                info
+            );
+        }
+
+        sb.push_str("\n\n#Consumer Pop Detail#\n");
+        let _ = writeln!(
+            sb,
+            "{:<32}  {:<32}  {:<4}  {:<20}",
+            "#Topic", "#Broker Name", "#QID", "#ProcessQueueInfo"
+        );
+        for (mq, info) in self.mq_pop_table.iter() {
+            let _ = writeln!(
+                sb,
+                "{:<32}  {:<32}  {:<4}  {}",
+                mq.get_topic(),
+                mq.get_broker_name(),
+                mq.get_queue_id(),
+                info
+            );
+        }
+
+        sb.push_str("\n\n#Consumer RT&TPS#\n");
+        let _ = writeln!(
+            sb,
+            "{:<64}  {:>14} {:>14} {:>14} {:>14} {:>18} {:>25}",
+            "#Topic",
+            "#Pull RT",
+            "#Pull TPS",
+            "#Consume RT",
+            "#ConsumeOK TPS",
+            "#ConsumeFailed TPS",
+            "#ConsumeFailedMsgsInHour"
+        );
+        for (topic, status) in self.status_table.iter() {
+            let _ = writeln!(
+                sb,
+                "{:<32}  {:>14.2} {:>14.2} {:>14.2} {:>14.2} {:>18.2} {:>25}",
+                topic,
+                status.pull_rt,
+                status.pull_tps,
+                status.consume_rt,
+                status.consume_ok_tps,
+                status.consume_failed_tps,
+                status.consume_failed_msgs
+            );
+        }
+
+        if !self.user_consumer_info.is_empty() {
+            sb.push_str("\n\n#User Consume Info#\n");
+            for (key, value) in self.user_consumer_info.iter() {
+                let _ = writeln!(sb, "{:<40}: {}", key, value);
+            }
+        }
+
+        if let Some(jstack) = self.jstack.as_ref() {
+            sb.push_str("\n\n#Consumer jstack#\n");
+            sb.push_str(jstack);
+        }
+        sb
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::RemotingDeserializable;
+    use crate::protocol::RemotingSerializable;
+
+    fn running_info(topic: &str, start_timestamp: u64) -> ConsumerRunningInfo {
+        let mut info = ConsumerRunningInfo::default();
+        info.properties.insert(
+            ConsumerRunningInfo::PROP_CONSUME_TYPE.to_string(),
+            "CONSUME_PASSIVELY".to_string(),
+        );
+        info.properties.insert(
+            ConsumerRunningInfo::PROP_CONSUMER_START_TIMESTAMP.to_string(),
+            start_timestamp.to_string(),
+        );
+        info.subscription_set.insert(SubscriptionData {
+            topic: topic.into(),
+            sub_string: SubscriptionData::SUB_ALL.into(),
+            ..Default::default()
+        });
+        info
+    }
+
+    #[test]
+    fn consumer_running_info_round_trips_through_json() {
+        let mut info = running_info("topic", 1);
+        let mq = MessageQueue::from_parts("topic", "broker-a", 0);
+        info.mq_table.insert(
+            mq.clone(),
+            ProcessQueueInfo {
+                commit_offset: 10,
+                cached_msg_size_in_mib: 2,
+                ..Default::default()
+            },
+        );
+        info.status_table.insert(
+            "topic".into(),
+            ConsumeStatus {
+                consume_ok_tps: 3.5,
+                ..Default::default()
+            },
+        );
+
+        let bytes = info.encode().unwrap();
+        let json = String::from_utf8(bytes.clone()).unwrap();
+        assert!(json.contains("\"cachedMsgSizeInMiB\""));
+        assert!(json.contains("\"consumeOKTPS\""));
+        let decoded = ConsumerRunningInfo::decode(&bytes).unwrap();
+        assert_eq!(decoded.mq_table.get(&mq).unwrap().commit_offset, 10);
+        assert_eq!(decoded.subscription_set, info.subscription_set);
+        assert_eq!(
+            decoded.status_table.get("topic").unwrap().consume_ok_tps,
+            3.5
+        );
+        assert!(decoded.format_string().contains("#Consumer RT&TPS#"));
+    }
+
+    #[test]
+    fn analyze_subscription_detects_inconsistent_push_consumers() {
+        let mut cri_table = BTreeMap::new();
+        cri_table.insert("client-a".to_string(), running_info("topic-a", 1));
+        cri_table.insert("client-b".to_string(), running_info("topic-a", 1));
+        assert!(ConsumerRunningInfo::analyze_subscription(&cri_table));
+
+        cri_table.insert("client-c".to_string(), running_info("topic-b", 1));
+        assert!(!ConsumerRunningInfo::analyze_subscription(&cri_table));
+
+        // consumers that just started may not have finished subscribing yet
+        let mut fresh_table = BTreeMap::new();
+        fresh_table.insert("client-a".to_string(), running_info("topic-a", u64::MAX));
+        fresh_table.insert("client-b".to_string(), running_info("topic-b", u64::MAX));
+        assert!(ConsumerRunningInfo::analyze_subscription(&fresh_table));
+    }
+}
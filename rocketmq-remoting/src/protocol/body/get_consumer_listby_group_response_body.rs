@@ -23,3 +23,24 @@ use serde::Serialize;
 pub struct GetConsumerListByGroupResponseBody {
     pub consumer_id_list: Vec<CheetahString>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::RemotingDeserializable;
+    use crate::protocol::RemotingSerializable;
+
+    #[test]
+    fn get_consumer_list_by_group_response_body_round_trip() {
+        let body = GetConsumerListByGroupResponseBody {
+            consumer_id_list: vec![
+                CheetahString::from("127.0.0.1@1"),
+                CheetahString::from("127.0.0.1@2"),
+            ],
+        };
+        let encoded = body.encode().unwrap();
+        assert!(String::from_utf8_lossy(&encoded).contains("consumerIdList"));
+        let decoded = GetConsumerListByGroupResponseBody::decode(&encoded).unwrap();
+        assert_eq!(decoded.consumer_id_list, body.consumer_id_list);
+    }
+}
@@ -0,0 +1,75 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use cheetah_string::CheetahString;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// POP consumption state of a group on one broker.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PopStats {
+    pub consumer_group: CheetahString,
+    pub topic: CheetahString,
+    /// Popped but not yet acked messages, keyed by queue id.
+    pub inflight_message_num: HashMap<i32, i64>,
+    /// Messages in each revive queue not yet processed by the revive service, i.e. checkpoints
+    /// whose invisible time expiration has not been handled yet.
+    pub revive_behind_messages: HashMap<i32, i64>,
+    /// Messages between the group's consumer offset and the max offset, keyed by queue id.
+    pub backlog: HashMap<i32, i64>,
+}
+
+impl PopStats {
+    pub fn total_inflight_message_num(&self) -> i64 {
+        self.inflight_message_num.values().sum()
+    }
+
+    pub fn total_revive_behind_messages(&self) -> i64 {
+        self.revive_behind_messages.values().sum()
+    }
+
+    pub fn total_backlog(&self) -> i64 {
+        self.backlog.values().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::RemotingDeserializable;
+    use crate::protocol::RemotingSerializable;
+
+    #[test]
+    fn pop_stats_round_trip() {
+        let mut stats = PopStats {
+            consumer_group: "group".into(),
+            topic: "topic".into(),
+            ..Default::default()
+        };
+        stats.inflight_message_num.insert(0, 3);
+        stats.inflight_message_num.insert(1, 2);
+        stats.revive_behind_messages.insert(0, 7);
+        stats.backlog.insert(0, 10);
+        let decoded = PopStats::decode(&stats.encode().unwrap()).unwrap();
+        assert_eq!(decoded.total_inflight_message_num(), 5);
+        assert_eq!(decoded.total_revive_behind_messages(), 7);
+        assert_eq!(decoded.total_backlog(), 10);
+        assert_eq!(decoded.consumer_group, "group");
+    }
+}
@@ -0,0 +1,59 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json_any_key::*;
+
+/// Body of `INVOKE_BROKER_TO_RESET_OFFSET` responses and `RESET_CONSUMER_CLIENT_OFFSET`
+/// requests, carrying the offset each queue should be reset to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetOffsetBody {
+    #[serde(with = "any_key_map")]
+    pub offset_table: HashMap<MessageQueue, i64>,
+}
+
+impl ResetOffsetBody {
+    pub fn new(offset_table: HashMap<MessageQueue, i64>) -> Self {
+        Self { offset_table }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+
+    #[test]
+    fn reset_offset_body_round_trips_through_json() {
+        let mq = MessageQueue::from_parts(
+            CheetahString::from_static_str("topic"),
+            CheetahString::from_static_str("broker-a"),
+            1,
+        );
+        let body = ResetOffsetBody::new(HashMap::from([(mq.clone(), 42)]));
+
+        let json = serde_json::to_string(&body).unwrap();
+        assert!(json.contains("\"offsetTable\""));
+        let decoded: ResetOffsetBody = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.offset_table.get(&mq), Some(&42));
+    }
+}
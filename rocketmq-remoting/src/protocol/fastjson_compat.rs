@@ -0,0 +1,284 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Decoding of the JSON bodies Java peers serialize with fastjson.
+//!
+//! fastjson writes map keys that are not strings without quotes (`{0:"127.0.0.1:10911"}`) and
+//! map keys that are objects as bare objects (`{{"brokerName":"a","queueId":0,"topic":"t"}:{}}`),
+//! neither of which is valid JSON. When a body fails to decode, [`decode`] rewrites these keys into
+//! standard JSON and tries again, unless the compatibility layer is turned off through
+//! [`FASTJSON_COMPAT_PROPERTY`], [`FASTJSON_COMPAT_ENV`] or [`set_enabled`].
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use lazy_static::lazy_static;
+use rocketmq_common::error::Error;
+use rocketmq_common::utils::serde_json_utils::SerdeJsonUtils;
+use serde::de::DeserializeOwned;
+
+pub const FASTJSON_COMPAT_PROPERTY: &str = "rocketmq.remoting.fastjsonCompat";
+pub const FASTJSON_COMPAT_ENV: &str = "ROCKETMQ_REMOTING_FASTJSON_COMPAT";
+
+lazy_static! {
+    static ref FASTJSON_COMPAT_ENABLED: AtomicBool = {
+        let enabled = std::env::var(FASTJSON_COMPAT_PROPERTY)
+            .or_else(|_| std::env::var(FASTJSON_COMPAT_ENV))
+            .map_or(true, |value| !value.eq_ignore_ascii_case("false"));
+        AtomicBool::new(enabled)
+    };
+}
+
+/// Whether bodies that are not standard JSON are decoded as fastjson output.
+pub fn is_enabled() -> bool {
+    FASTJSON_COMPAT_ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(enabled: bool) {
+    FASTJSON_COMPAT_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Decodes a JSON body, falling back to [`normalize`] when the compatibility layer is enabled.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Error> {
+    decode_with(bytes, is_enabled())
+}
+
+/// Decodes a JSON body, falling back to [`normalize`] when `compat` is set.
+pub fn decode_with<T: DeserializeOwned>(bytes: &[u8], compat: bool) -> Result<T, Error> {
+    let error = match SerdeJsonUtils::decode(bytes) {
+        Ok(value) => return Ok(value),
+        Err(error) => error,
+    };
+    if !compat {
+        return Err(error);
+    }
+    match normalize(bytes) {
+        Some(normalized) if normalized != bytes => SerdeJsonUtils::decode(&normalized),
+        _ => Err(error),
+    }
+}
+
+/// Rewrites fastjson output into standard JSON: unquoted keys are quoted, and object or array
+/// keys become strings holding their JSON, the form `serde_json_any_key` decodes. Returns `None`
+/// if `json` is malformed beyond these quirks.
+pub fn normalize(json: &[u8]) -> Option<Vec<u8>> {
+    let mut normalizer = Normalizer {
+        input: json,
+        pos: 0,
+    };
+    let mut out = Vec::with_capacity(json.len() + 16);
+    normalizer.value(&mut out)?;
+    normalizer.skip_whitespace();
+    (normalizer.pos == json.len()).then_some(out)
+}
+
+struct Normalizer<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Normalizer<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self, out: &mut Vec<u8>) -> Option<()> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'{' => self.object(out),
+            b'[' => self.array(out),
+            b'"' => self.string(out),
+            _ => {
+                let token = self.token();
+                if token.is_empty() {
+                    return None;
+                }
+                out.extend_from_slice(token);
+                Some(())
+            }
+        }
+    }
+
+    fn object(&mut self, out: &mut Vec<u8>) -> Option<()> {
+        self.pos += 1;
+        out.push(b'{');
+        self.skip_whitespace();
+        if self.peek()? == b'}' {
+            self.pos += 1;
+            out.push(b'}');
+            return Some(());
+        }
+        loop {
+            self.skip_whitespace();
+            match self.peek()? {
+                b'"' => self.string(out)?,
+                b'{' | b'[' => {
+                    let mut key = Vec::new();
+                    self.value(&mut key)?;
+                    write_json_string(out, &key);
+                }
+                _ => {
+                    let token = self.token();
+                    if token.is_empty() {
+                        return None;
+                    }
+                    write_json_string(out, token);
+                }
+            }
+            self.skip_whitespace();
+            if self.next()? != b':' {
+                return None;
+            }
+            out.push(b':');
+            self.value(out)?;
+            self.skip_whitespace();
+            match self.next()? {
+                b',' => out.push(b','),
+                b'}' => {
+                    out.push(b'}');
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self, out: &mut Vec<u8>) -> Option<()> {
+        self.pos += 1;
+        out.push(b'[');
+        self.skip_whitespace();
+        if self.peek()? == b']' {
+            self.pos += 1;
+            out.push(b']');
+            return Some(());
+        }
+        loop {
+            self.value(out)?;
+            self.skip_whitespace();
+            match self.next()? {
+                b',' => out.push(b','),
+                b']' => {
+                    out.push(b']');
+                    return Some(());
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn string(&mut self, out: &mut Vec<u8>) -> Option<()> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.next()? {
+                b'\\' => {
+                    self.next()?;
+                }
+                b'"' => break,
+                _ => {}
+            }
+        }
+        out.extend_from_slice(&self.input[start..self.pos]);
+        Some(())
+    }
+
+    /// Reads a number, literal or unquoted key.
+    fn token(&mut self) -> &[u8] {
+        let start = self.pos;
+        while self.peek().is_some_and(|byte| {
+            !byte.is_ascii_whitespace() && !matches!(byte, b',' | b':' | b'{' | b'}' | b'[' | b']')
+        }) {
+            self.pos += 1;
+        }
+        &self.input[start..self.pos]
+    }
+}
+
+fn write_json_string(out: &mut Vec<u8>, raw: &[u8]) {
+    out.push(b'"');
+    for &byte in raw {
+        match byte {
+            b'"' => out.extend_from_slice(b"\\\""),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b'\t' => out.extend_from_slice(b"\\t"),
+            _ => out.push(byte),
+        }
+    }
+    out.push(b'"');
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn normalized(json: &str) -> String {
+        String::from_utf8(normalize(json.as_bytes()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn normalize_quotes_bare_keys() {
+        assert_eq!(
+            normalized(r#"{"brokerAddrs":{0:"a:1", 1 : "b:1"}}"#),
+            r#"{"brokerAddrs":{"0":"a:1","1":"b:1"}}"#
+        );
+    }
+
+    #[test]
+    fn normalize_turns_object_keys_into_strings() {
+        assert_eq!(
+            normalized(r#"{{"queueId":0,"topic":"t"}:{"consumerOffset":1}}"#),
+            r#"{"{\"queueId\":0,\"topic\":\"t\"}":{"consumerOffset":1}}"#
+        );
+    }
+
+    #[test]
+    fn normalize_keeps_standard_json_and_string_contents() {
+        let json = r#"{"remark":"a {0:b} \"quoted\" value","list":[1,-2.5e3,true,null]}"#;
+        assert_eq!(normalized(json), json);
+    }
+
+    #[test]
+    fn normalize_rejects_malformed_json() {
+        assert!(normalize(br#"{"a":1"#).is_none());
+        assert!(normalize(br#"{"a" 1}"#).is_none());
+        assert!(normalize(br#"{"a":1} trailing"#).is_none());
+    }
+
+    #[test]
+    fn decode_with_falls_back_only_when_compat_is_set() {
+        let json = br#"{1:"broker-a",2:"broker-b"}"#;
+        assert!(decode_with::<HashMap<u64, String>>(json, false).is_err());
+        let decoded = decode_with::<HashMap<u64, String>>(json, true).unwrap();
+        assert_eq!(decoded.get(&2).map(String::as_str), Some("broker-b"));
+    }
+}
@@ -14,11 +14,16 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+pub mod ack_message_request_header;
 pub mod broker;
+pub mod change_invisible_time_request_header;
+pub mod change_invisible_time_response_header;
 pub mod check_transaction_state_request_header;
 pub mod client_request_header;
+pub mod clone_group_offset_request_header;
 pub mod consume_message_directly_result_request_header;
 pub mod consumer_send_msg_back_request_header;
+pub mod controller;
 pub mod create_topic_request_header;
 pub mod delete_subscription_group_request_header;
 pub mod delete_topic_request_header;
@@ -30,11 +35,14 @@ pub mod get_consumer_connection_list_request_header;
 pub mod get_consumer_listby_group_request_header;
 pub mod get_consumer_listby_group_response_header;
 pub mod get_consumer_running_info_request_header;
+pub mod get_earliest_msg_storetime_request_header;
 pub mod get_earliest_msg_storetime_response_header;
 pub mod get_max_offset_request_header;
 pub mod get_max_offset_response_header;
 pub mod get_min_offset_request_header;
 pub mod get_min_offset_response_header;
+pub mod get_pop_stats_request_header;
+pub mod get_producer_connection_list_request_header;
 pub mod get_topic_config_request_header;
 pub mod get_topic_stats_info_request_header;
 pub mod get_topic_stats_request_header;
@@ -42,12 +50,21 @@ pub mod heartbeat_request_header;
 pub mod lock_batch_mq_request_header;
 pub mod message_operation_header;
 pub mod namesrv;
+pub mod notification_request_header;
+pub mod notification_response_header;
 pub mod notify_consumer_ids_changed_request_header;
+pub mod peek_message_request_header;
+pub mod polling_info_request_header;
+pub mod polling_info_response_header;
+pub mod pop_message_request_header;
+pub mod pop_message_response_header;
 pub mod pull_message_request_header;
 pub mod pull_message_response_header;
 pub mod query_consume_time_span_request_header;
 pub mod query_consumer_offset_request_header;
 pub mod query_consumer_offset_response_header;
+pub mod query_message_by_offset_range_request_header;
+pub mod query_message_by_offset_range_response_header;
 pub mod query_message_request_header;
 pub mod query_message_response_header;
 pub mod query_subscription_by_consumer_request_header;
@@ -55,6 +72,7 @@ pub mod query_topic_consume_by_who_request_header;
 pub mod query_topics_by_consumer_request_header;
 pub mod reply_message_request_header;
 pub mod reset_offset_request_header;
+pub mod search_offset_request_header;
 pub mod search_offset_response_header;
 pub mod unlock_batch_mq_request_header;
 pub mod unregister_client_request_header;
@@ -0,0 +1,56 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cheetah_string::CheetahString;
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::header::namesrv::topic_operation_header::TopicRequestHeader;
+
+/// Acks a popped message, `extra_info` is the `POP_CK` property the message was popped with.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct AckMessageRequestHeader {
+    #[required]
+    pub consumer_group: CheetahString,
+    #[required]
+    pub topic: CheetahString,
+    #[required]
+    pub queue_id: i32,
+    #[required]
+    pub extra_info: CheetahString,
+    #[required]
+    pub offset: i64,
+    #[serde(flatten)]
+    pub topic_request_header: Option<TopicRequestHeader>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ack_message_request_header_deserializes_correctly() {
+        let data = r#"{"consumerGroup":"group","topic":"test_topic","queueId":1,"extraInfo":"0 1000 60000 0 0 broker-a 1 5","offset":5}"#;
+        let header: AckMessageRequestHeader = serde_json::from_str(data).unwrap();
+        assert_eq!(header.consumer_group, "group");
+        assert_eq!(header.queue_id, 1);
+        assert_eq!(header.extra_info, "0 1000 60000 0 0 broker-a 1 5");
+        assert_eq!(header.offset, 5);
+    }
+}
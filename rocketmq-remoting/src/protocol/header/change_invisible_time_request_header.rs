@@ -0,0 +1,57 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cheetah_string::CheetahString;
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::header::namesrv::topic_operation_header::TopicRequestHeader;
+
+/// Makes a popped message invisible for `invisible_time` milliseconds from now on.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeInvisibleTimeRequestHeader {
+    #[required]
+    pub consumer_group: CheetahString,
+    #[required]
+    pub topic: CheetahString,
+    #[required]
+    pub queue_id: i32,
+    #[required]
+    pub extra_info: CheetahString,
+    #[required]
+    pub offset: i64,
+    #[required]
+    pub invisible_time: i64,
+    #[serde(flatten)]
+    pub topic_request_header: Option<TopicRequestHeader>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn change_invisible_time_request_header_deserializes_correctly() {
+        let data = r#"{"consumerGroup":"group","topic":"test_topic","queueId":1,"extraInfo":"0 1000 60000 0 0 broker-a 1 5","offset":5,"invisibleTime":30000}"#;
+        let header: ChangeInvisibleTimeRequestHeader = serde_json::from_str(data).unwrap();
+        assert_eq!(header.topic, "test_topic");
+        assert_eq!(header.offset, 5);
+        assert_eq!(header.invisible_time, 30000);
+    }
+}
@@ -0,0 +1,47 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The new pop time of a message whose invisible time was changed, the receipt handle of the
+/// message has to be rebuilt with it.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeInvisibleTimeResponseHeader {
+    #[required]
+    pub pop_time: i64,
+    #[required]
+    pub invisible_time: i64,
+    #[required]
+    pub revive_qid: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn change_invisible_time_response_header_deserializes_correctly() {
+        let data = r#"{"popTime":2000,"invisibleTime":30000,"reviveQid":0}"#;
+        let header: ChangeInvisibleTimeResponseHeader = serde_json::from_str(data).unwrap();
+        assert_eq!(header.pop_time, 2000);
+        assert_eq!(header.invisible_time, 30000);
+        assert_eq!(header.revive_qid, 0);
+    }
+}
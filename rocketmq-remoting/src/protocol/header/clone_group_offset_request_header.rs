@@ -0,0 +1,53 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use cheetah_string::CheetahString;
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::rpc::rpc_request_header::RpcRequestHeader;
+
+/// Copies the committed offsets of `src_group` to `dest_group`, an empty `topic` clones every
+/// topic consumed by `src_group`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneGroupOffsetRequestHeader {
+    #[required]
+    pub src_group: CheetahString,
+    #[required]
+    pub dest_group: CheetahString,
+    pub topic: Option<CheetahString>,
+    /// Skips the check that the source group still subscribes to the topic.
+    pub offline: bool,
+    #[serde(flatten)]
+    pub rpc_request_header: Option<RpcRequestHeader>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clone_group_offset_request_header_deserializes_correctly() {
+        let data = r#"{"srcGroup":"src","destGroup":"dest","topic":"test_topic","offline":true}"#;
+        let header: CloneGroupOffsetRequestHeader = serde_json::from_str(data).unwrap();
+        assert_eq!(header.src_group, "src");
+        assert_eq!(header.dest_group, "dest");
+        assert_eq!(header.topic.as_deref(), Some("test_topic"));
+        assert!(header.offline);
+    }
+}
@@ -0,0 +1,116 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cheetah_string::CheetahString;
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct ElectMasterRequestHeader {
+    #[required]
+    pub cluster_name: CheetahString,
+
+    #[required]
+    pub broker_name: CheetahString,
+
+    /// The broker that asks for the election, `None` when the controller triggers it itself.
+    pub broker_id: Option<i64>,
+
+    /// Whether `broker_id` must be elected as the new master.
+    pub designate_elect: Option<bool>,
+
+    pub invoke_time: Option<u64>,
+}
+
+impl ElectMasterRequestHeader {
+    pub fn of_broker_trigger(
+        cluster_name: impl Into<CheetahString>,
+        broker_name: impl Into<CheetahString>,
+        broker_id: i64,
+    ) -> Self {
+        Self {
+            cluster_name: cluster_name.into(),
+            broker_name: broker_name.into(),
+            broker_id: Some(broker_id),
+            designate_elect: Some(false),
+            invoke_time: None,
+        }
+    }
+
+    pub fn of_controller_trigger(
+        cluster_name: impl Into<CheetahString>,
+        broker_name: impl Into<CheetahString>,
+    ) -> Self {
+        Self {
+            cluster_name: cluster_name.into(),
+            broker_name: broker_name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn of_admin_trigger(
+        cluster_name: impl Into<CheetahString>,
+        broker_name: impl Into<CheetahString>,
+        broker_id: i64,
+    ) -> Self {
+        Self {
+            cluster_name: cluster_name.into(),
+            broker_name: broker_name.into(),
+            broker_id: Some(broker_id),
+            designate_elect: Some(true),
+            invoke_time: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct ElectMasterResponseHeader {
+    pub master_broker_id: Option<i64>,
+
+    pub master_address: Option<CheetahString>,
+
+    pub master_epoch: Option<i32>,
+
+    pub sync_state_set_epoch: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::protocol::command_custom_header::CommandCustomHeader;
+    use crate::protocol::command_custom_header::FromMap;
+
+    #[test]
+    fn elect_master_request_header_round_trips_through_map() {
+        let header = ElectMasterRequestHeader::of_admin_trigger("cluster", "broker-a", 2);
+        let map: HashMap<CheetahString, CheetahString> = header.to_map().unwrap();
+        assert_eq!(
+            map.get(&CheetahString::from_static_str("designateElect")),
+            Some(&CheetahString::from_static_str("true"))
+        );
+        let decoded = <ElectMasterRequestHeader as FromMap>::from(&map).unwrap();
+        assert_eq!(decoded.cluster_name, "cluster");
+        assert_eq!(decoded.broker_name, "broker-a");
+        assert_eq!(decoded.broker_id, Some(2));
+        assert_eq!(decoded.designate_elect, Some(true));
+    }
+}
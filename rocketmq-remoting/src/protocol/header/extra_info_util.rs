@@ -37,7 +37,10 @@ impl ExtraInfoUtil {
         if extra_info.is_empty() {
             return Err(IllegalArgument("split extraInfo is empty".to_string()));
         }
-        Ok(extra_info.split('|').map(String::from).collect())
+        Ok(extra_info
+            .split(MessageConst::KEY_SEPARATOR)
+            .map(String::from)
+            .collect())
     }
 
     pub fn get_ck_queue_offset(extra_info_strs: &[String]) -> crate::Result<i64> {
@@ -291,10 +294,11 @@ impl ExtraInfoUtil {
             string_builder.push(';');
         }
         string_builder.push_str(&format!(
-            "{}{}{}",
+            "{}{}{}{}",
             retry,
             MessageConst::KEY_SEPARATOR,
-            queue_id
+            queue_id,
+            MessageConst::KEY_SEPARATOR
         ));
         for (i, msg_offset) in msg_offsets.iter().enumerate() {
             string_builder.push_str(&msg_offset.to_string());
@@ -451,10 +455,7 @@ impl ExtraInfoUtil {
     }
 
     fn get_retry_from_split(split_info: &[String]) -> String {
-        if split_info.is_empty() {
-            return NORMAL_TOPIC.to_string();
-        }
-        ExtraInfoUtil::get_retry(split_info[0].as_str())
+        ExtraInfoUtil::get_retry_slice(split_info).unwrap_or_else(|_| NORMAL_TOPIC.to_string())
     }
 }
 
@@ -467,10 +468,49 @@ mod tests {
 
     #[test]
     fn split_with_valid_string() {
-        let result = ExtraInfoUtil::split("a|b|c").unwrap();
+        let result = ExtraInfoUtil::split("a b c").unwrap();
         assert_eq!(result, vec!["a", "b", "c"]);
     }
 
+    #[test]
+    fn extra_info_round_trips_through_split() {
+        let extra_info = ExtraInfoUtil::build_extra_info_with_msg_queue_offset(
+            3,
+            1000,
+            60000,
+            0,
+            "%RETRY%group_topic",
+            "broker-a",
+            1,
+            42,
+        );
+        let split = ExtraInfoUtil::split(&extra_info).unwrap();
+        assert_eq!(ExtraInfoUtil::get_pop_time(&split).unwrap(), 1000);
+        assert_eq!(ExtraInfoUtil::get_retry_slice(&split).unwrap(), "1");
+        assert_eq!(ExtraInfoUtil::get_broker_name(&split).unwrap(), "broker-a");
+        assert_eq!(ExtraInfoUtil::get_queue_id(&split).unwrap(), 1);
+        assert_eq!(ExtraInfoUtil::get_queue_offset(&split).unwrap(), 42);
+        assert_eq!(
+            ExtraInfoUtil::get_start_offset_info_map_key_with_pop_ck("topic", &extra_info, 1),
+            "1@1"
+        );
+    }
+
+    #[test]
+    fn msg_offset_info_round_trips() {
+        let mut msg_offset_info = String::new();
+        ExtraInfoUtil::build_msg_offset_info(&mut msg_offset_info, "topic", 1, vec![5, 6]);
+        ExtraInfoUtil::build_msg_offset_info(
+            &mut msg_offset_info,
+            "%RETRY%group_topic",
+            0,
+            vec![7],
+        );
+        let parsed = ExtraInfoUtil::parse_msg_offset_info(&msg_offset_info).unwrap();
+        assert_eq!(parsed.get("0@1"), Some(&vec![5, 6]));
+        assert_eq!(parsed.get("1@0"), Some(&vec![7]));
+    }
+
     #[test]
     fn split_with_empty_string() {
         let result = ExtraInfoUtil::split("").unwrap_err();
@@ -807,7 +847,7 @@ mod tests {
     fn build_msg_offset_info_creates_correct_string() {
         let mut string_builder = String::new();
         ExtraInfoUtil::build_msg_offset_info(&mut string_builder, "topic", 7, vec![100, 200, 300]);
-        assert_eq!(string_builder, "0 7100,200,300");
+        assert_eq!(string_builder, "0 7 100,200,300");
     }
 
     #[test]
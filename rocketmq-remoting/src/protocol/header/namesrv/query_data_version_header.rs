@@ -122,6 +122,10 @@ impl QueryDataVersionResponseHeader {
     pub fn new(changed: bool) -> Self {
         Self { changed }
     }
+
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
 }
 
 impl CommandCustomHeader for QueryDataVersionResponseHeader {
@@ -0,0 +1,61 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use cheetah_string::CheetahString;
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::header::namesrv::topic_operation_header::TopicRequestHeader;
+
+/// Asks whether a group has messages left to consume without transferring any body,
+/// `queue_id` -1 checks every readable queue and `poll_time` is how long the broker may hold the
+/// request waiting for new messages.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRequestHeader {
+    #[required]
+    pub consumer_group: CheetahString,
+    #[required]
+    pub topic: CheetahString,
+    #[required]
+    pub queue_id: i32,
+    #[required]
+    pub poll_time: i64,
+    #[required]
+    pub born_time: i64,
+    pub order: Option<bool>,
+    pub attempt_id: Option<CheetahString>,
+    #[serde(flatten)]
+    pub topic_request_header: Option<TopicRequestHeader>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notification_request_header_deserializes_correctly() {
+        let data = r#"{"consumerGroup":"group","topic":"test_topic","queueId":-1,"pollTime":15000,"bornTime":1000}"#;
+        let header: NotificationRequestHeader = serde_json::from_str(data).unwrap();
+        assert_eq!(header.consumer_group, "group");
+        assert_eq!(header.topic, "test_topic");
+        assert_eq!(header.queue_id, -1);
+        assert_eq!(header.poll_time, 15000);
+        assert_eq!(header.born_time, 1000);
+        assert!(header.order.is_none());
+    }
+}
@@ -0,0 +1,57 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cheetah_string::CheetahString;
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Reads the raw messages stored in `[start_offset, end_offset)`. The offsets are consume queue
+/// offsets of `topic`/`queue_id`, or commit log offsets when `by_commit_log_offset` is set, in
+/// which case every message of the range is returned whatever its topic.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryMessageByOffsetRangeRequestHeader {
+    #[required]
+    pub topic: CheetahString,
+    #[required]
+    pub queue_id: i32,
+    #[required]
+    pub start_offset: i64,
+    #[required]
+    pub end_offset: i64,
+    #[required]
+    pub max_msg_nums: i32,
+    pub by_commit_log_offset: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_message_by_offset_range_request_header_deserializes_correctly() {
+        let data = r#"{"topic":"TopicTest","queueId":1,"startOffset":10,"endOffset":20,"maxMsgNums":32,"byCommitLogOffset":true}"#;
+        let header: QueryMessageByOffsetRangeRequestHeader = serde_json::from_str(data).unwrap();
+        assert_eq!(header.topic, "TopicTest");
+        assert_eq!(header.queue_id, 1);
+        assert_eq!(header.start_offset, 10);
+        assert_eq!(header.end_offset, 20);
+        assert_eq!(header.max_msg_nums, 32);
+        assert!(header.by_commit_log_offset);
+    }
+}
@@ -0,0 +1,52 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use cheetah_string::CheetahString;
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::rpc::topic_request_header::TopicRequestHeader;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchOffsetRequestHeader {
+    #[required]
+    pub topic: CheetahString,
+    #[required]
+    pub queue_id: i32,
+    #[required]
+    pub timestamp: i64,
+    /// `LOWER` (default) or `UPPER`, see `BoundaryType`.
+    pub boundary_type: Option<CheetahString>,
+    #[serde(flatten)]
+    pub topic_request_header: Option<TopicRequestHeader>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_offset_request_header_deserializes_correctly() {
+        let data = r#"{"topic":"test_topic","queueId":2,"timestamp":1700000000000,"boundaryType":"UPPER"}"#;
+        let header: SearchOffsetRequestHeader = serde_json::from_str(data).unwrap();
+        assert_eq!(header.topic, "test_topic");
+        assert_eq!(header.queue_id, 2);
+        assert_eq!(header.timestamp, 1700000000000);
+        assert_eq!(header.boundary_type.as_deref(), Some("UPPER"));
+    }
+}
@@ -66,7 +66,7 @@ impl<'de> Deserialize<'de> for ConsumeType {
             type Value = ConsumeType;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("a string representing TopicFilterType")
+                formatter.write_str("a name or ordinal of ConsumeType")
             }
 
             fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -83,9 +83,25 @@ impl<'de> Deserialize<'de> for ConsumeType {
                     )),
                 }
             }
+
+            /// Ordinal of the Java enum, the form fastjson writes without `WriteEnumUsingName`.
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match value {
+                    0 => Ok(ConsumeType::ConsumeActively),
+                    1 => Ok(ConsumeType::ConsumePassively),
+                    2 => Ok(ConsumeType::ConsumePop),
+                    _ => Err(serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Unsigned(value),
+                        &self,
+                    )),
+                }
+            }
         }
 
-        deserializer.deserialize_str(ConsumeTypeVisitor)
+        deserializer.deserialize_any(ConsumeTypeVisitor)
     }
 }
 
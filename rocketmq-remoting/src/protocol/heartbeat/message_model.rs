@@ -62,7 +62,7 @@ impl<'de> Deserialize<'de> for MessageModel {
             type Value = MessageModel;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("a string representing TopicFilterType")
+                formatter.write_str("a name or ordinal of MessageModel")
             }
 
             fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -78,9 +78,23 @@ impl<'de> Deserialize<'de> for MessageModel {
                     )),
                 }
             }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match value {
+                    0 => Ok(MessageModel::Broadcasting),
+                    1 => Ok(MessageModel::Clustering),
+                    _ => Err(serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Unsigned(value),
+                        &self,
+                    )),
+                }
+            }
         }
 
-        deserializer.deserialize_str(MessageModelVisitor)
+        deserializer.deserialize_any(MessageModelVisitor)
     }
 }
 
@@ -71,8 +71,8 @@ fn set_cmd_version(cmd: &mut RemotingCommand) {
         let v = match std::env::var("REMOTING_VERSION_KEY") {
             Ok(value) => value
                 .parse::<i32>()
-                .unwrap_or(i32::from(RocketMqVersion::V500)),
-            Err(_) => i32::from(RocketMqVersion::V500),
+                .unwrap_or(i32::from(RocketMqVersion::CURRENT_VERSION)),
+            Err(_) => i32::from(RocketMqVersion::CURRENT_VERSION),
         };
         *CONFIG_VERSION.write().unwrap() = v;
     });
@@ -202,12 +202,17 @@ impl RemotingCommand {
         requestId.fetch_add(1, Ordering::AcqRel)
     }
 
-    pub fn set_cmd_version(self) -> Self {
+    /// Stamps the version this process speaks so the peer can gate version dependent features.
+    pub fn set_cmd_version(mut self) -> Self {
+        set_cmd_version(&mut self);
         self
     }
 
     pub fn create_response_command_with_code(code: impl Into<i32>) -> Self {
-        Self::default().set_code(code).mark_response_type()
+        Self::default()
+            .set_code(code)
+            .mark_response_type()
+            .set_cmd_version()
     }
 
     pub fn create_response_command_with_code_remark(
@@ -218,12 +223,14 @@ impl RemotingCommand {
             .set_code(code)
             .set_remark_option(Some(remark.into()))
             .mark_response_type()
+            .set_cmd_version()
     }
 
     pub fn create_response_command() -> Self {
         Self::default()
             .set_code(RemotingSysResponseCode::Success)
             .mark_response_type()
+            .set_cmd_version()
     }
 
     pub fn create_response_command_with_header(
@@ -233,6 +240,7 @@ impl RemotingCommand {
             .set_code(RemotingSysResponseCode::Success)
             .set_command_custom_header(header)
             .mark_response_type()
+            .set_cmd_version()
     }
 
     pub fn set_command_custom_header<T>(mut self, command_custom_header: T) -> Self
@@ -642,9 +650,9 @@ impl RemotingCommand {
         key: impl Into<CheetahString>,
         value: impl Into<CheetahString>,
     ) -> &mut Self {
-        if let Some(ref mut ext) = self.ext_fields {
-            ext.insert(key.into(), value.into());
-        }
+        self.ext_fields
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value.into());
         self
     }
 
@@ -776,6 +784,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn responses_carry_the_current_version() {
+        let response = RemotingCommand::create_response_command();
+        assert!(RocketMqVersion::is_supported(
+            response.version(),
+            RocketMqVersion::POP_MIN_VERSION
+        ));
+    }
+
     #[test]
     fn test_mark_serialize_type() {
         let i = RemotingCommand::mark_serialize_type(261, SerializeType::JSON);
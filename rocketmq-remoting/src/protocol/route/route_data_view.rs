@@ -33,7 +33,8 @@ pub struct BrokerData {
     broker_addrs: HashMap<u64 /* broker id */, CheetahString /* broker ip */>,
     #[serde(rename = "zoneName")]
     zone_name: Option<CheetahString>,
-    #[serde(rename = "enableActingMaster")]
+    /// Absent from the bodies of brokers older than 5.0.
+    #[serde(rename = "enableActingMaster", default)]
     enable_acting_master: bool,
 }
 
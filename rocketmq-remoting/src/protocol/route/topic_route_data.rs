@@ -28,13 +28,14 @@ use crate::protocol::static_topic::topic_queue_info::TopicQueueMappingInfo;
 pub struct TopicRouteData {
     #[serde(rename = "orderTopicConf")]
     pub order_topic_conf: Option<CheetahString>,
-    #[serde(rename = "queueDatas")]
+    #[serde(rename = "queueDatas", default)]
     pub queue_datas: Vec<QueueData>,
-    #[serde(rename = "brokerDatas")]
+    #[serde(rename = "brokerDatas", default)]
     pub broker_datas: Vec<BrokerData>,
-    #[serde(rename = "filterServerTable")]
+    #[serde(rename = "filterServerTable", default)]
     pub filter_server_table: HashMap<CheetahString, Vec<CheetahString>>,
-    #[serde(rename = "topicQueueMappingInfo")]
+    /// Named like the field of the Java class, earlier Rust peers wrote `topicQueueMappingInfo`.
+    #[serde(rename = "topicQueueMappingByBroker", alias = "topicQueueMappingInfo")]
     pub topic_queue_mapping_by_broker: Option<HashMap<CheetahString, TopicQueueMappingInfo>>,
 }
 
@@ -142,7 +143,7 @@ mod tests {
         assert!(serialized.contains("\"queueDatas\":["));
         assert!(serialized.contains("\"brokerDatas\":["));
         assert!(serialized.contains("\"filterServerTable\":{\"key\":[\"value\"]}"));
-        assert!(serialized.contains("\"topicQueueMappingInfo\":{\"broker\":{"));
+        assert!(serialized.contains("\"topicQueueMappingByBroker\":{\"broker\":{"));
     }
 
     /*    #[test]
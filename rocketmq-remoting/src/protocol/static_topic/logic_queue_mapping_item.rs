@@ -38,7 +38,7 @@ impl Default for LogicQueueMappingItem {
 
 impl LogicQueueMappingItem {
     pub fn compute_static_queue_offset_strictly(&self, physical_queue_offset: i64) -> i64 {
-        if physical_queue_offset > self.start_offset {
+        if physical_queue_offset < self.start_offset {
             return self.logic_offset;
         }
         self.logic_offset + (physical_queue_offset - self.start_offset)
@@ -78,3 +78,20 @@ impl LogicQueueMappingItem {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_static_queue_offset_strictly_adds_offset_delta() {
+        let item = LogicQueueMappingItem {
+            logic_offset: 100,
+            start_offset: 10,
+            ..Default::default()
+        };
+        assert_eq!(item.compute_static_queue_offset_strictly(10), 100);
+        assert_eq!(item.compute_static_queue_offset_strictly(15), 105);
+        assert_eq!(item.compute_static_queue_offset_strictly(5), 100);
+    }
+}
@@ -17,6 +17,8 @@
 use rocketmq_common::common::mix_all;
 
 use crate::protocol::static_topic::logic_queue_mapping_item::LogicQueueMappingItem;
+use crate::remoting_error::RemotingError;
+use crate::Result;
 
 pub struct TopicQueueMappingUtils;
 
@@ -80,6 +82,101 @@ impl TopicQueueMappingUtils {
         None
     }
 
+    /// Checks the items of a logical queue, ordered from the oldest to the leader, have
+    /// increasing generations and logical offsets.
+    pub fn check_logic_queue_mapping_item_offset(items: &[LogicQueueMappingItem]) -> Result<()> {
+        let mut last_gen = -1;
+        let mut last_offset = -1;
+        for (i, item) in items.iter().enumerate().rev() {
+            if item.start_offset < 0 || item.gen < 0 || item.queue_id < 0 {
+                return Err(illegal_mapping(
+                    "The field is illegal, should not be negative",
+                ));
+            }
+            if i + 1 < items.len() && item.logic_offset < 0 {
+                return Err(illegal_mapping(
+                    "The non-latest item has negative logic offset",
+                ));
+            }
+            if last_gen != -1 && item.gen >= last_gen {
+                return Err(illegal_mapping("The gen does not increase monotonically"));
+            }
+            if item.end_offset != -1 && item.end_offset < item.start_offset {
+                return Err(illegal_mapping(
+                    "The endOffset is smaller than the start offset",
+                ));
+            }
+            if last_offset != -1 && item.logic_offset != -1 {
+                if item.logic_offset >= last_offset {
+                    return Err(illegal_mapping(
+                        "The base logic offset does not increase monotonically",
+                    ));
+                }
+                if item.compute_max_static_queue_offset() >= last_offset {
+                    return Err(illegal_mapping(
+                        "The max logic offset does not increase monotonically",
+                    ));
+                }
+            }
+            last_gen = item.gen;
+            last_offset = item.logic_offset;
+        }
+        Ok(())
+    }
+
+    /// Checks the items of a logical queue already known to a broker are left unchanged by a
+    /// new mapping, and that the leader only changes along with the epoch.
+    pub fn make_sure_logic_queue_mapping_item_immutable(
+        old_items: &[LogicQueueMappingItem],
+        new_items: &[LogicQueueMappingItem],
+        epoch_equal: bool,
+    ) -> Result<()> {
+        if old_items.is_empty() {
+            return Ok(());
+        }
+        if new_items.is_empty() {
+            return Err(illegal_mapping("The new item list is null or empty"));
+        }
+        let (mut i_old, mut i_new) = (0, 0);
+        while i_old < old_items.len() && i_new < new_items.len() {
+            let old_item = &old_items[i_old];
+            let new_item = &new_items[i_new];
+            if new_item.gen < old_item.gen {
+                i_new += 1;
+            } else if old_item.gen < new_item.gen {
+                i_old += 1;
+            } else {
+                if old_item.bname != new_item.bname
+                    || old_item.queue_id != new_item.queue_id
+                    || old_item.start_offset != new_item.start_offset
+                    || (old_item.logic_offset != -1
+                        && old_item.logic_offset != new_item.logic_offset)
+                {
+                    return Err(illegal_mapping(&format!(
+                        "The item of gen {} is changed",
+                        old_item.gen
+                    )));
+                }
+                i_old += 1;
+                i_new += 1;
+            }
+        }
+        if epoch_equal {
+            let old_leader = &old_items[old_items.len() - 1];
+            let new_leader = &new_items[new_items.len() - 1];
+            if new_leader.gen != old_leader.gen
+                || new_leader.bname != old_leader.bname
+                || new_leader.queue_id != old_leader.queue_id
+                || new_leader.start_offset != old_leader.start_offset
+            {
+                return Err(illegal_mapping(
+                    "The new leader is different but epoch equal",
+                ));
+            }
+        }
+        Ok(())
+    }
+
     pub fn get_mock_broker_name(scope: &str) -> String {
         assert!(!scope.is_empty(), "Scope cannot be null");
 
@@ -94,3 +191,80 @@ impl TopicQueueMappingUtils {
         }
     }
 }
+
+fn illegal_mapping(reason: &str) -> RemotingError {
+    RemotingError::IllegalArgument(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+
+    fn item(
+        gen: i32,
+        bname: &'static str,
+        logic_offset: i64,
+        end_offset: i64,
+    ) -> LogicQueueMappingItem {
+        LogicQueueMappingItem {
+            gen,
+            queue_id: 0,
+            bname: Some(CheetahString::from_static_str(bname)),
+            logic_offset,
+            start_offset: 0,
+            end_offset,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn check_item_offsets() {
+        let items = vec![item(0, "broker-a", 0, 100), item(1, "broker-b", 1000, -1)];
+        assert!(TopicQueueMappingUtils::check_logic_queue_mapping_item_offset(&items).is_ok());
+
+        let items = vec![item(1, "broker-a", 0, 100), item(1, "broker-b", 1000, -1)];
+        assert!(TopicQueueMappingUtils::check_logic_queue_mapping_item_offset(&items).is_err());
+
+        let items = vec![item(0, "broker-a", 0, 1000), item(1, "broker-b", 1000, -1)];
+        assert!(TopicQueueMappingUtils::check_logic_queue_mapping_item_offset(&items).is_err());
+
+        let items = vec![item(0, "broker-a", -1, 100), item(1, "broker-b", 1000, -1)];
+        assert!(TopicQueueMappingUtils::check_logic_queue_mapping_item_offset(&items).is_err());
+    }
+
+    #[test]
+    fn known_items_are_immutable() {
+        let old_items = vec![item(0, "broker-a", 0, -1)];
+        let moved = vec![item(0, "broker-a", 0, 100), item(1, "broker-b", 1000, -1)];
+        assert!(
+            TopicQueueMappingUtils::make_sure_logic_queue_mapping_item_immutable(
+                &old_items, &moved, false
+            )
+            .is_ok()
+        );
+        assert!(
+            TopicQueueMappingUtils::make_sure_logic_queue_mapping_item_immutable(
+                &old_items, &moved, true
+            )
+            .is_err()
+        );
+
+        let changed = vec![item(0, "broker-c", 0, -1)];
+        assert!(
+            TopicQueueMappingUtils::make_sure_logic_queue_mapping_item_immutable(
+                &old_items, &changed, false
+            )
+            .is_err()
+        );
+        assert!(
+            TopicQueueMappingUtils::make_sure_logic_queue_mapping_item_immutable(
+                &old_items,
+                &[],
+                false
+            )
+            .is_err()
+        );
+    }
+}
@@ -19,25 +19,32 @@ use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
 use futures::SinkExt;
 use rocketmq_common::common::server::config::ServerConfig;
 use rocketmq_rust::ArcMut;
-use tokio::net::TcpListener;
-use tokio::net::TcpStream;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::Semaphore;
 use tokio::time;
 use tokio_stream::StreamExt;
+use tracing::debug;
 use tracing::error;
 use tracing::info;
+use tracing::info_span;
 use tracing::warn;
+use tracing::Instrument;
+use tracing::Span;
 
 use crate::base::response_future::ResponseFuture;
+use crate::code::request_code::RequestCode;
 use crate::code::response_code::ResponseCode;
 use crate::connection::Connection;
 use crate::net::channel::Channel;
+use crate::net::transport::TransportAddr;
+use crate::net::transport::TransportConnection;
+use crate::net::transport::TransportListener;
 use crate::protocol::remoting_command::RemotingCommand;
 use crate::protocol::RemotingCommandType;
 use crate::remoting_error::RemotingError;
@@ -64,6 +71,7 @@ pub struct ConnectionHandler<RP> {
     conn_disconnect_notify: Option<broadcast::Sender<SocketAddr>>,
     rpc_hooks: Arc<Vec<Box<dyn RPCHook>>>,
     response_table: ArcMut<HashMap<i32, ResponseFuture>>,
+    slow_request_threshold: Duration,
 }
 
 impl<RP> Drop for ConnectionHandler<RP> {
@@ -105,6 +113,25 @@ impl<RP> ConnectionHandler<RP> {
         }
         Ok(())
     }
+
+    /// Records the processing latency on the request span, requests slower than
+    /// `slow_request_threshold` are logged at warn level.
+    fn log_request_latency(&self, span: &Span, request_code: i32, latency: Duration) {
+        let latency_ms = latency.as_millis() as u64;
+        span.record("latency_ms", latency_ms);
+        span.in_scope(|| {
+            if latency >= self.slow_request_threshold {
+                warn!(
+                    "slow request, code={:?}({}), latency={}ms",
+                    RequestCode::from(request_code),
+                    request_code,
+                    latency_ms
+                );
+            } else {
+                debug!("request processed, latency={}ms", latency_ms);
+            }
+        });
+    }
 }
 
 impl<RP: RequestProcessor + Sync + 'static> ConnectionHandler<RP> {
@@ -142,11 +169,19 @@ impl<RP: RequestProcessor + Sync + 'static> ConnectionHandler<RP> {
             }
             let opaque = cmd.opaque();
             let oneway_rpc = cmd.is_oneway_rpc();
+            let request_code = cmd.code();
+            let span = info_span!(
+                "remoting_request",
+                code = request_code,
+                opaque,
+                remote_addr = %self.channel.remote_address(),
+                latency_ms = tracing::field::Empty,
+            );
+            let begin = Instant::now();
             //before handle request hooks
-            let exception = match self.do_before_rpc_hooks(&self.channel, Some(&mut cmd)) {
-                Ok(_) => None,
-                Err(error) => Some(error),
-            };
+            let exception = self
+                .do_before_rpc_hooks(&self.channel, Some(&mut cmd))
+                .err();
             //handle error if return have
             match self.handle_error(oneway_rpc, opaque, exception).await {
                 HandleErrorResult::Continue => continue,
@@ -158,7 +193,7 @@ impl<RP: RequestProcessor + Sync + 'static> ConnectionHandler<RP> {
                 let channel = self.channel.clone();
                 let ctx = ArcMut::downgrade(&self.connection_handler_context);
                 tokio::select! {
-                    result = self.request_processor.process_request(channel,ctx,cmd) =>  match result{
+                    result = self.request_processor.process_request(channel,ctx,cmd).instrument(span.clone()) =>  match result{
                         Ok(value) => value,
                         Err(_err) => Some(RemotingCommand::create_response_command_with_code(
                                         ResponseCode::SystemError,
@@ -166,11 +201,11 @@ impl<RP: RequestProcessor + Sync + 'static> ConnectionHandler<RP> {
                     },
                 }
             };
+            self.log_request_latency(&span, request_code, begin.elapsed());
 
-            let exception = match self.do_before_rpc_hooks(&self.channel, response.as_mut()) {
-                Ok(_) => None,
-                Err(error) => Some(error),
-            };
+            let exception = self
+                .do_after_rpc_hooks(&self.channel, response.as_mut())
+                .err();
 
             match self.handle_error(oneway_rpc, opaque, exception).await {
                 HandleErrorResult::Continue => continue,
@@ -267,8 +302,8 @@ enum HandleErrorResult {
 /// Server listener state. Created in the `run` call. It includes a `run` method
 /// which performs the TCP listening and initialization of per-connection state.
 struct ConnectionListener<RP> {
-    /// The TCP listener supplied by the `run` caller.
-    listener: TcpListener,
+    /// The listener supplied by the `run` caller, TCP or any other transport.
+    listener: TransportListener,
 
     /// Limit the max number of connections.
     ///
@@ -289,6 +324,8 @@ struct ConnectionListener<RP> {
     request_processor: RP,
 
     rpc_hooks: Arc<Vec<Box<dyn RPCHook>>>,
+
+    slow_request_threshold: Duration,
 }
 
 impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
@@ -305,15 +342,18 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
             // Accept a new socket. This will attempt to perform error handling.
             // The `accept` method internally attempts to recover errors, so an
             // error here is non-recoverable.
-            let (socket, remote_addr) = self.accept().await?;
+            let TransportConnection {
+                stream,
+                local_addr,
+                remote_addr,
+            } = self.accept().await?;
             info!("Accepted connection, client ip:{}", remote_addr);
-            socket.set_nodelay(true).expect("set nodelay failed");
 
             let response_table = ArcMut::new(HashMap::with_capacity(128));
             let channel = Channel::new(
-                socket.local_addr()?,
+                local_addr,
                 remote_addr,
-                Connection::new(socket),
+                Connection::new(stream),
                 response_table.clone(),
             );
             //create per connection handler state
@@ -330,6 +370,7 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
                 conn_disconnect_notify: self.conn_disconnect_notify.clone(),
                 rpc_hooks: self.rpc_hooks.clone(),
                 response_table,
+                slow_request_threshold: self.slow_request_threshold,
             };
 
             tokio::spawn(async move {
@@ -349,7 +390,7 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
         }
     }
 
-    async fn accept(&mut self) -> anyhow::Result<(TcpStream, SocketAddr)> {
+    async fn accept(&mut self) -> anyhow::Result<TransportConnection> {
         let mut backoff = 1;
 
         // Try to accept a few times
@@ -357,7 +398,7 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
             // Perform the accept operation. If a socket is successfully
             // accepted, return it. Otherwise, save the error.
             match self.listener.accept().await {
-                Ok((socket, remote_addr)) => return Ok((socket, remote_addr)),
+                Ok(connection) => return Ok(connection),
                 Err(err) => {
                     if backoff > 64 {
                         // Accept has failed too many times. Return the error.
@@ -377,6 +418,7 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
 
 pub struct RocketMQServer<RP> {
     config: Arc<ServerConfig>,
+    rpc_hooks: Vec<Arc<Box<dyn RPCHook>>>,
     _phantom_data: std::marker::PhantomData<RP>,
 }
 
@@ -384,41 +426,61 @@ impl<RP> RocketMQServer<RP> {
     pub fn new(config: Arc<ServerConfig>) -> Self {
         Self {
             config,
+            rpc_hooks: Vec::new(),
             _phantom_data: std::marker::PhantomData,
         }
     }
+
+    /// Register a hook invoked around every request handled by this server.
+    pub fn register_rpc_hook(&mut self, hook: Arc<Box<dyn RPCHook>>) {
+        self.rpc_hooks.push(hook);
+    }
 }
 
 impl<RP: RequestProcessor + Sync + 'static + Clone> RocketMQServer<RP> {
     pub async fn run(&self, request_processor: RP) {
-        let listener = TcpListener::bind(&format!(
-            "{}:{}",
-            self.config.bind_address, self.config.listen_port
-        ))
-        .await
-        .unwrap();
-        info!(
-            "Bind local address: {}",
-            format!("{}:{}", self.config.bind_address, self.config.listen_port)
-        );
+        self.run_until(request_processor, tokio::signal::ctrl_c())
+            .await
+    }
+
+    /// Serves until `shutdown` completes, then stops accepting connections and returns once
+    /// the requests being processed have been answered.
+    pub async fn run_until(&self, request_processor: RP, shutdown: impl Future) {
+        let listen_addr = listen_addr(&self.config);
+        let listener = TransportListener::bind(&listen_addr).await.unwrap();
+        info!("Bind local address: {}", listen_addr);
         let (notify_conn_disconnect, _) = broadcast::channel::<SocketAddr>(100);
         run(
             listener,
-            tokio::signal::ctrl_c(),
+            shutdown,
             request_processor,
             Some(notify_conn_disconnect),
-            vec![],
+            self.rpc_hooks
+                .iter()
+                .map(|hook| Box::new(hook.clone()) as Box<dyn RPCHook>)
+                .collect(),
+            Duration::from_millis(self.config.slow_request_threshold_millis),
         )
         .await;
     }
 }
 
+/// The address to listen on: `bind_address` alone when it carries a transport scheme such as
+/// `unix:/path/to/socket`, `bind_address:listen_port` over TCP otherwise.
+fn listen_addr(config: &ServerConfig) -> String {
+    match TransportAddr::parse(&config.bind_address) {
+        TransportAddr::Tcp(bind_address) => format!("{}:{}", bind_address, config.listen_port),
+        _ => config.bind_address.clone(),
+    }
+}
+
 pub async fn run<RP: RequestProcessor + Sync + 'static + Clone>(
-    listener: TcpListener,
+    listener: TransportListener,
     shutdown: impl Future,
     request_processor: RP,
     conn_disconnect_notify: Option<broadcast::Sender<SocketAddr>>,
     rpc_hooks: Vec<Box<dyn RPCHook>>,
+    slow_request_threshold: Duration,
 ) {
     let (notify_shutdown, _) = broadcast::channel(1);
     let (shutdown_complete_tx, mut shutdown_complete_rx) = mpsc::channel(1);
@@ -431,6 +493,7 @@ pub async fn run<RP: RequestProcessor + Sync + 'static + Clone>(
         limit_connections: Arc::new(Semaphore::new(DEFAULT_MAX_CONNECTIONS)),
         request_processor,
         rpc_hooks: Arc::new(rpc_hooks),
+        slow_request_threshold,
     };
 
     tokio::select! {
@@ -499,3 +562,195 @@ impl Shutdown {
         self.is_shutdown = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tracing::field::Field;
+    use tracing::field::Visit;
+    use tracing::span;
+    use tracing::Event;
+    use tracing::Level;
+    use tracing::Subscriber;
+    use tracing_subscriber::layer::Context;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Layer;
+
+    use super::*;
+    use crate::clients::Client;
+    use crate::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+    use crate::runtime::connection_handler_context::ConnectionHandlerContext;
+
+    #[derive(Clone)]
+    struct SuccessProcessor;
+
+    impl RequestProcessor for SuccessProcessor {
+        async fn process_request(
+            &mut self,
+            _channel: Channel,
+            _ctx: ConnectionHandlerContext,
+            _request: RemotingCommand,
+        ) -> Result<Option<RemotingCommand>> {
+            Ok(Some(RemotingCommand::create_response_command()))
+        }
+    }
+
+    /// Answers after a delay, noting the span the request is processed in.
+    #[derive(Clone)]
+    struct SlowProcessor {
+        spans: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl RequestProcessor for SlowProcessor {
+        async fn process_request(
+            &mut self,
+            _channel: Channel,
+            _ctx: ConnectionHandlerContext,
+            _request: RemotingCommand,
+        ) -> Result<Option<RemotingCommand>> {
+            if let Some(metadata) = Span::current().metadata() {
+                self.spans.lock().unwrap().push(metadata.name());
+            }
+            time::sleep(Duration::from_millis(50)).await;
+            Ok(Some(RemotingCommand::create_response_command()))
+        }
+    }
+
+    /// Collects the recorded request latencies and the warn events.
+    #[derive(Clone, Default)]
+    struct TraceRecorder {
+        latencies: Arc<Mutex<Vec<u64>>>,
+        warnings: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct LatencyVisitor(Option<u64>);
+
+    impl Visit for LatencyVisitor {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            if field.name() == "latency_ms" {
+                self.0 = Some(value);
+            }
+        }
+
+        fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+    }
+
+    struct MessageVisitor(String);
+
+    impl Visit for MessageVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = format!("{:?}", value);
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for TraceRecorder {
+        fn on_record(&self, _span: &span::Id, values: &span::Record<'_>, _ctx: Context<'_, S>) {
+            let mut visitor = LatencyVisitor(None);
+            values.record(&mut visitor);
+            if let Some(latency) = visitor.0 {
+                self.latencies.lock().unwrap().push(latency);
+            }
+        }
+
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            if *event.metadata().level() == Level::WARN {
+                let mut visitor = MessageVisitor(String::new());
+                event.record(&mut visitor);
+                self.warnings.lock().unwrap().push(visitor.0);
+            }
+        }
+    }
+
+    #[test]
+    fn listen_addr_keeps_scheme_addresses() {
+        let mut config = ServerConfig {
+            bind_address: "0.0.0.0".to_string(),
+            listen_port: 10911,
+            ..Default::default()
+        };
+        assert_eq!(listen_addr(&config), "0.0.0.0:10911");
+        config.bind_address = "unix:/tmp/broker.sock".to_string();
+        assert_eq!(listen_addr(&config), "unix:/tmp/broker.sock");
+    }
+
+    #[tokio::test]
+    async fn serves_requests_over_the_in_process_transport() {
+        let listener = TransportListener::bind("inproc:server_test").await.unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(run(
+            listener,
+            shutdown_rx,
+            SuccessProcessor,
+            None,
+            Vec::new(),
+            Duration::from_secs(1),
+        ));
+
+        let mut client =
+            Client::connect("inproc:server_test", DefaultRemotingRequestProcessor, None)
+                .await
+                .unwrap();
+        let response = client
+            .send_read(RemotingCommand::create_remoting_command(10), 3000)
+            .await
+            .unwrap();
+        assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+
+        let _ = shutdown_tx.send(());
+        drop(client);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn requests_are_traced_with_their_latency() {
+        let recorder = TraceRecorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+        let processor = SlowProcessor {
+            spans: Arc::new(Mutex::new(Vec::new())),
+        };
+        let listener = TransportListener::bind("inproc:server_trace_test")
+            .await
+            .unwrap();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(run(
+            listener,
+            shutdown_rx,
+            processor.clone(),
+            None,
+            Vec::new(),
+            Duration::from_millis(10),
+        ));
+
+        let mut client = Client::connect(
+            "inproc:server_trace_test",
+            DefaultRemotingRequestProcessor,
+            None,
+        )
+        .await
+        .unwrap();
+        let response = client
+            .send_read(RemotingCommand::create_remoting_command(10), 3000)
+            .await
+            .unwrap();
+        assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+
+        let _ = shutdown_tx.send(());
+        drop(client);
+        server.await.unwrap();
+
+        assert_eq!(*processor.spans.lock().unwrap(), vec!["remoting_request"]);
+        let latencies = recorder.latencies.lock().unwrap();
+        assert_eq!(latencies.len(), 1);
+        assert!(latencies[0] >= 50);
+        assert!(recorder
+            .warnings
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|warning| warning.starts_with("slow request, code=")));
+    }
+}
@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use crate::protocol::remoting_command::RemotingCommand;
 use crate::Result;
@@ -74,3 +75,23 @@ pub trait RPCHook: Send + Sync + 'static {
         response: &mut RemotingCommand,
     ) -> Result<()>;
 }
+
+impl RPCHook for Arc<Box<dyn RPCHook>> {
+    #[inline]
+    fn do_before_request(
+        &self,
+        remote_addr: SocketAddr,
+        request: &mut RemotingCommand,
+    ) -> Result<()> {
+        self.as_ref().do_before_request(remote_addr, request)
+    }
+
+    #[inline]
+    fn do_after_response(
+        &self,
+        remote_addr: SocketAddr,
+        response: &mut RemotingCommand,
+    ) -> Result<()> {
+        self.as_ref().do_after_response(remote_addr, response)
+    }
+}
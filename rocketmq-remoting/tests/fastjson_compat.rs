@@ -0,0 +1,226 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Decodes bodies in the shape fastjson gives them on Java brokers and name servers, with non
+//! string map keys left unquoted and object map keys written as bare objects.
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::TopicFilterType;
+use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
+use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
+use rocketmq_remoting::protocol::body::consumer_connection::ConsumerConnection;
+use rocketmq_remoting::protocol::fastjson_compat;
+use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
+use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
+use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use rocketmq_remoting::protocol::LanguageCode;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+const CLUSTER_INFO: &[u8] = include_bytes!("fixtures/fastjson/cluster_info.json");
+const CONSUME_STATS: &[u8] = include_bytes!("fixtures/fastjson/consume_stats.json");
+const TOPIC_ROUTE_DATA: &[u8] = include_bytes!("fixtures/fastjson/topic_route_data.json");
+const CONSUMER_CONNECTION: &[u8] = include_bytes!("fixtures/fastjson/consumer_connection.json");
+
+/// A JSON document in a form two encodings of the same body compare equal in: object keys
+/// holding JSON are rewritten canonically and null fields, which fastjson leaves out, dropped.
+fn canonical(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| {
+                    let key = match serde_json::from_str::<Value>(&key) {
+                        Ok(key @ (Value::Object(_) | Value::Array(_))) => {
+                            canonical(key).to_string()
+                        }
+                        _ => key,
+                    };
+                    (key, canonical(value))
+                })
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(canonical).collect()),
+        value => value,
+    }
+}
+
+/// Decodes a body written by a Java peer and checks that encoding it again yields the same
+/// fields under the same names.
+fn assert_round_trip<T: DeserializeOwned + RemotingSerializable>(java: &[u8]) {
+    let decoded: T = fastjson_compat::decode_with(java, true).unwrap();
+    let java = serde_json::from_slice(&fastjson_compat::normalize(java).unwrap()).unwrap();
+    let encoded = serde_json::from_str(&decoded.to_json().unwrap()).unwrap();
+    assert_eq!(canonical(encoded), canonical(java));
+}
+
+#[test]
+fn fixtures_are_not_standard_json() {
+    for fixture in [CLUSTER_INFO, CONSUME_STATS, TOPIC_ROUTE_DATA] {
+        assert!(serde_json::from_slice::<serde_json::Value>(fixture).is_err());
+        assert!(fastjson_compat::decode_with::<serde_json::Value>(fixture, false).is_err());
+    }
+}
+
+#[test]
+fn cluster_info_keeps_every_broker_address() {
+    let cluster_info = ClusterInfo::decode(CLUSTER_INFO).unwrap();
+
+    let broker_addr_table = cluster_info.broker_addr_table.unwrap();
+    let broker_a = broker_addr_table.get("broker-a").unwrap();
+    assert_eq!(broker_a.cluster(), "DefaultCluster");
+    assert_eq!(broker_a.broker_addrs().len(), 2);
+    assert_eq!(
+        broker_a.broker_addrs().get(&1).map(CheetahString::as_str),
+        Some("192.168.1.11:10911")
+    );
+    assert_eq!(broker_a.zone_name().as_deref(), Some("zone-1"));
+    assert!(broker_addr_table
+        .get("broker-b")
+        .unwrap()
+        .enable_acting_master());
+
+    let cluster_addr_table = cluster_info.cluster_addr_table.unwrap();
+    assert_eq!(cluster_addr_table.get("DefaultCluster").unwrap().len(), 2);
+}
+
+#[test]
+fn consume_stats_keeps_every_queue_offset() {
+    let consume_stats = ConsumeStats::decode(CONSUME_STATS).unwrap();
+
+    assert_eq!(consume_stats.get_consume_tps(), 12.5);
+    assert_eq!(consume_stats.get_offset_table().len(), 2);
+    let wrapper = consume_stats
+        .get_offset_table()
+        .get(&MessageQueue::from_parts("TopicTest", "broker-a", 0))
+        .unwrap();
+    assert_eq!(wrapper.get_broker_offset(), 120);
+    assert_eq!(wrapper.get_consumer_offset(), 100);
+    assert_eq!(wrapper.get_pull_offset(), 110);
+    assert_eq!(wrapper.get_last_timestamp(), 1_700_000_000_000);
+    assert_eq!(consume_stats.compute_total_diff(), 20);
+}
+
+#[test]
+fn consume_stats_of_brokers_without_pull_offset() {
+    let json = br#"{"consumeTps":0.0,"offsetTable":{{"brokerName":"broker-a","queueId":0,"topic":"TopicTest"}:{"brokerOffset":5,"consumerOffset":3,"lastTimestamp":0}}}"#;
+    let consume_stats = ConsumeStats::decode(json).unwrap();
+    assert_eq!(consume_stats.compute_total_diff(), 2);
+}
+
+#[test]
+fn topic_route_data_keeps_static_topic_mapping() {
+    let topic_route_data: TopicRouteData =
+        fastjson_compat::decode_with(TOPIC_ROUTE_DATA, true).unwrap();
+
+    assert_eq!(topic_route_data.queue_datas.len(), 1);
+    assert_eq!(topic_route_data.queue_datas[0].perm(), 6);
+    assert_eq!(topic_route_data.broker_datas[0].broker_addrs().len(), 2);
+    let mapping = topic_route_data.topic_queue_mapping_by_broker.unwrap();
+    let mapping = mapping.get("broker-a").unwrap();
+    assert_eq!(mapping.total_queues, 2);
+    assert_eq!(mapping.epoch, 1_700_000_000_000);
+    assert_eq!(mapping.curr_id_map.as_ref().unwrap().get(&1), Some(&1));
+}
+
+#[test]
+fn topic_route_data_of_brokers_older_than_5_0() {
+    let json = br#"{"brokerDatas":[{"brokerAddrs":{0:"192.168.1.10:10911"},"brokerName":"broker-a","cluster":"DefaultCluster"}],"queueDatas":[{"brokerName":"broker-a","perm":6,"readQueueNums":4,"topicSysFlag":0,"writeQueueNums":4}]}"#;
+    let topic_route_data = TopicRouteData::decode(json).unwrap();
+    assert!(!topic_route_data.broker_datas[0].enable_acting_master());
+    assert!(topic_route_data.filter_server_table.is_empty());
+    assert!(topic_route_data.topic_queue_mapping_by_broker.is_none());
+}
+
+#[test]
+fn bodies_round_trip_with_java_field_names() {
+    assert_round_trip::<ClusterInfo>(CLUSTER_INFO);
+    assert_round_trip::<ConsumeStats>(CONSUME_STATS);
+    assert_round_trip::<TopicRouteData>(TOPIC_ROUTE_DATA);
+
+    let topic_route_data: TopicRouteData =
+        fastjson_compat::decode_with(TOPIC_ROUTE_DATA, true).unwrap();
+    assert!(topic_route_data
+        .to_json()
+        .unwrap()
+        .contains("\"topicQueueMappingByBroker\":{"));
+}
+
+#[test]
+fn consumer_connection_decodes_enum_names_and_ordinals() {
+    let by_name = ConsumerConnection::decode(CONSUMER_CONNECTION).unwrap();
+    assert_eq!(by_name.get_consume_type(), ConsumeType::ConsumePassively);
+    assert_eq!(by_name.get_message_model(), MessageModel::Clustering);
+    assert_eq!(
+        by_name.get_consume_from_where(),
+        ConsumeFromWhere::ConsumeFromFirstOffset
+    );
+    let connection = by_name.get_connection_set().into_iter().next().unwrap();
+    assert_eq!(connection.get_language(), LanguageCode::JAVA);
+    assert_eq!(connection.get_version(), 453);
+    assert_eq!(
+        by_name.get_subscription_table()["TopicTest"].sub_version,
+        1_700_000_000_000
+    );
+
+    // the same body written with enums as ordinals
+    let by_ordinal = String::from_utf8(CONSUMER_CONNECTION.to_vec())
+        .unwrap()
+        .replace("\"JAVA\"", "0")
+        .replace("\"CONSUME_FROM_FIRST_OFFSET\"", "4")
+        .replace("\"CONSUME_PASSIVELY\"", "1")
+        .replace("\"CLUSTERING\"", "1");
+    let by_ordinal = ConsumerConnection::decode(by_ordinal.as_bytes()).unwrap();
+    assert_eq!(by_ordinal.get_consume_type(), by_name.get_consume_type());
+    assert_eq!(by_ordinal.get_message_model(), by_name.get_message_model());
+    assert_eq!(
+        by_ordinal.get_consume_from_where(),
+        by_name.get_consume_from_where()
+    );
+    assert_eq!(
+        by_ordinal.get_connection_set(),
+        by_name.get_connection_set()
+    );
+
+    // enums are written by name, like fastjson does by default
+    let encoded = ConsumerConnection::decode(by_name.to_json().unwrap().as_bytes()).unwrap();
+    assert_eq!(encoded.get_connection_set(), by_name.get_connection_set());
+    assert_eq!(
+        encoded.get_subscription_table(),
+        by_name.get_subscription_table()
+    );
+    assert!(by_name
+        .to_json()
+        .unwrap()
+        .contains("\"consumeFromWhere\":\"CONSUME_FROM_FIRST_OFFSET\""));
+}
+
+#[test]
+fn topic_config_decodes_filter_type_ordinal() {
+    let json = br#"{"attributes":{},"order":false,"perm":6,"readQueueNums":8,"topicFilterType":1,"topicName":"TopicTest","topicSysFlag":0,"writeQueueNums":8}"#;
+    let topic_config: TopicConfig = serde_json::from_slice(json).unwrap();
+    assert_eq!(topic_config.topic_filter_type, TopicFilterType::MultiTag);
+    assert_eq!(topic_config.read_queue_nums, 8);
+    assert!(serde_json::to_string(&topic_config)
+        .unwrap()
+        .contains("\"topicFilterType\":\"MULTI_TAG\""));
+}
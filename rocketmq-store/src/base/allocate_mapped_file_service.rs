@@ -14,100 +14,218 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::mpsc;
 use std::sync::mpsc::Receiver;
+use std::sync::mpsc::RecvTimeoutError;
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
+use std::time::Duration;
 
+use cheetah_string::CheetahString;
+use parking_lot::Condvar;
 use parking_lot::Mutex;
+use rocketmq_rust::ArcMut;
+use tokio::runtime::Handle;
+use tokio::runtime::RuntimeFlavor;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
 
+use crate::config::message_store_config::MessageStoreConfig;
 use crate::log_file::mapped_file::default_mapped_file_impl::DefaultMappedFile;
+use crate::log_file::mapped_file::MappedFile;
+
+const WAIT_TIMEOUT_MILLIS: u64 = 1000 * 5;
 
+/// Creates commit log files on a background thread, always one file ahead of the one being
+/// asked for, so rolling over to a new file does not stall the write path.
 pub struct AllocateMappedFileService {
-    tx: Sender<AllocateRequest>,
-    rx: Arc<Mutex<Receiver<AllocateRequest>>>,
-    request_table: Arc<parking_lot::Mutex<HashMap<String, AllocateRequest>>>,
+    message_store_config: ArcMut<MessageStoreConfig>,
+    tx: Sender<Arc<AllocateRequest>>,
+    rx: Arc<Mutex<Option<Receiver<Arc<AllocateRequest>>>>>,
+    request_table: Arc<Mutex<HashMap<String, Arc<AllocateRequest>>>>,
+    running: Arc<AtomicBool>,
 }
 
 impl AllocateMappedFileService {
-    pub fn new() -> Self {
+    pub fn new(message_store_config: ArcMut<MessageStoreConfig>) -> Self {
         let (tx, rx) = mpsc::channel();
         Self {
+            message_store_config,
             tx,
-            rx: Arc::new(parking_lot::Mutex::new(rx)),
+            rx: Arc::new(Mutex::new(Some(rx))),
             request_table: Arc::new(Default::default()),
+            running: Arc::new(AtomicBool::new(false)),
         }
     }
 }
 
-impl Default for AllocateMappedFileService {
-    fn default() -> Self {
-        Self::new()
+impl AllocateMappedFileService {
+    pub fn start(&self) {
+        let Some(rx) = self.rx.lock().take() else {
+            warn!("{} has been started", self.get_service_name());
+            return;
+        };
+        self.running.store(true, Ordering::Release);
+        let running = self.running.clone();
+        let request_table = self.request_table.clone();
+        let message_store_config = self.message_store_config.clone();
+        let result = std::thread::Builder::new()
+            .name(self.get_service_name())
+            .spawn(move || {
+                info!("AllocateMappedFileService service started");
+                while running.load(Ordering::Acquire) {
+                    match rx.recv_timeout(Duration::from_millis(100)) {
+                        Ok(request) => {
+                            Self::mmap_operation(&request_table, &message_store_config, request)
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                info!("AllocateMappedFileService service end");
+            });
+        if let Err(e) = result {
+            self.running.store(false, Ordering::Release);
+            error!("start {} failed: {}", self.get_service_name(), e);
+        }
     }
-}
 
-impl AllocateMappedFileService {
+    pub fn shutdown(&self) {
+        self.running.store(false, Ordering::Release);
+        for request in self.request_table.lock().values() {
+            if let Some(mapped_file) = request.mapped_file.lock().as_ref() {
+                info!(
+                    "delete pre allocated mapped file, {}",
+                    mapped_file.get_file_name()
+                );
+                mapped_file.destroy(1000);
+            }
+        }
+    }
+
+    /// Returns the file for `next_file_path` and queues the creation of `next_next_file_path`.
+    /// `None` means the file could not be allocated in time and the caller has to create it
+    /// itself.
     pub fn put_request_and_return_mapped_file(
         &self,
         next_file_path: String,
         next_next_file_path: String,
-        file_size: i32,
-    ) -> DefaultMappedFile {
-        unimplemented!()
+        file_size: u64,
+    ) -> Option<DefaultMappedFile> {
+        if !self.running.load(Ordering::Acquire) {
+            return None;
+        }
+        self.submit_request(next_file_path.clone(), file_size);
+        self.submit_request(next_next_file_path, file_size);
+
+        let request = self.request_table.lock().get(&next_file_path).cloned()?;
+        let wait_mapped_file = || {
+            let mut guard = request.mapped_file.lock();
+            if guard.is_none() {
+                request
+                    .done
+                    .wait_for(&mut guard, Duration::from_millis(WAIT_TIMEOUT_MILLIS));
+            }
+            guard.take()
+        };
+        // the write path calls this from async code, so let the runtime move its other tasks
+        // off this worker while waiting for the allocation thread
+        let mapped_file = match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(wait_mapped_file)
+            }
+            _ => wait_mapped_file(),
+        };
+        self.request_table.lock().remove(&next_file_path);
+        if mapped_file.is_none() {
+            warn!(
+                "create mmap timeout {} {}",
+                request.file_path, request.file_size
+            );
+        }
+        mapped_file
     }
-}
 
-/*impl ThreadService for AllocateMappedFileService {
-    fn start(&self) {
-        let service_name = self.get_service_name();
-        let builder = std::thread::Builder::new().name(service_name);
-        let rx = self.rx.clone();
-        let request_table = self.request_table.clone();
-        let _ = builder
-            .spawn(move || loop {
-                match rx.lock().recv() {
-                    Ok(ref req) => {
-                        let guard = request_table.lock();
-                        let expected_request = guard.get(&req.file_path);
-                        if expected_request.is_none() {
-                            warn!(
-                                "this mmap request expired, maybe cause timeout {} {}",
-                                req.file_path, req.file_size,
-                            );
-                            continue;
-                        }
-                        if expected_request.unwrap() != req {
-                            warn!(
-                                "this mmap request expired, maybe cause timeout {} {}",
-                                req.file_path, req.file_size,
-                            );
-                            continue;
-                        }
-                        if req.mapped_file.is_none() {}
-                    }
-                    Err(err) => {
-                        error!("{}", err)
-                    }
-                }
-            })
-            .unwrap()
-            .join();
+    fn submit_request(&self, file_path: String, file_size: u64) {
+        let mut request_table = self.request_table.lock();
+        if request_table.contains_key(&file_path) {
+            return;
+        }
+        let request = Arc::new(AllocateRequest::new(file_path.clone(), file_size));
+        request_table.insert(file_path, request.clone());
+        if self.tx.send(request).is_err() {
+            warn!("{} has been shutdown", self.get_service_name());
+        }
     }
 
-    fn shutdown(&self) {}
+    fn mmap_operation(
+        request_table: &Mutex<HashMap<String, Arc<AllocateRequest>>>,
+        message_store_config: &MessageStoreConfig,
+        request: Arc<AllocateRequest>,
+    ) {
+        let expected = request_table
+            .lock()
+            .get(&request.file_path)
+            .is_some_and(|expected| Arc::ptr_eq(expected, &request));
+        if !expected {
+            warn!(
+                "this mmap request expired, maybe cause timeout {} {}",
+                request.file_path, request.file_size
+            );
+            return;
+        }
+        let begin_time = std::time::Instant::now();
+        let mapped_file = DefaultMappedFile::new(
+            CheetahString::from_string(request.file_path.clone()),
+            request.file_size,
+        );
+        let elapsed = begin_time.elapsed().as_millis();
+        if elapsed > 10 {
+            warn!(
+                "create mappedFile spent time(ms) {} {}",
+                elapsed, request.file_path
+            );
+        }
+        if message_store_config.warm_mapped_file_enable
+            && request.file_size >= message_store_config.mapped_file_size_commit_log as u64
+        {
+            mapped_file.warm_mapped_file(
+                message_store_config.flush_disk_type,
+                message_store_config.flush_least_pages_when_warm_mapped_file,
+            );
+        }
+        *request.mapped_file.lock() = Some(mapped_file);
+        request.done.notify_all();
+    }
 
     fn get_service_name(&self) -> String {
         "AllocateMappedFileService".to_string()
     }
-}*/
+}
 
 struct AllocateRequest {
     file_path: String,
-    file_size: i32,
-    mapped_file: Option<DefaultMappedFile>,
+    file_size: u64,
+    mapped_file: Mutex<Option<DefaultMappedFile>>,
+    done: Condvar,
+}
+
+impl AllocateRequest {
+    fn new(file_path: String, file_size: u64) -> Self {
+        Self {
+            file_path,
+            file_size,
+            mapped_file: Mutex::new(None),
+            done: Condvar::new(),
+        }
+    }
 }
 
 impl Display for AllocateRequest {
@@ -127,3 +245,60 @@ impl PartialEq<Self> for AllocateRequest {
 }
 
 impl Eq for AllocateRequest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocates_requested_file_and_pre_creates_the_next_one() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = AllocateMappedFileService::new(ArcMut::new(MessageStoreConfig::default()));
+        service.start();
+        let next = temp_dir.path().join("00000000000000000000");
+        let next_next = temp_dir.path().join("00000000000000001024");
+
+        let mapped_file = service
+            .put_request_and_return_mapped_file(
+                next.to_string_lossy().to_string(),
+                next_next.to_string_lossy().to_string(),
+                1024,
+            )
+            .unwrap();
+        assert_eq!(mapped_file.get_file_from_offset(), 0);
+        assert_eq!(mapped_file.get_file_size(), 1024);
+        assert!(next.exists());
+        service.shutdown();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn allocates_from_a_runtime_worker() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = AllocateMappedFileService::new(ArcMut::new(MessageStoreConfig::default()));
+        service.start();
+        let next = temp_dir.path().join("00000000000000000000");
+        let next_next = temp_dir.path().join("00000000000000001024");
+
+        let mapped_file = service.put_request_and_return_mapped_file(
+            next.to_string_lossy().to_string(),
+            next_next.to_string_lossy().to_string(),
+            1024,
+        );
+        assert!(mapped_file.is_some());
+        service.shutdown();
+    }
+
+    #[test]
+    fn returns_none_when_not_started() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let service = AllocateMappedFileService::new(ArcMut::new(MessageStoreConfig::default()));
+        let next = temp_dir.path().join("00000000000000000000");
+        assert!(service
+            .put_request_and_return_mapped_file(
+                next.to_string_lossy().to_string(),
+                String::new(),
+                1024,
+            )
+            .is_none());
+    }
+}
@@ -29,6 +29,7 @@ use rocketmq_common::utils::message_utils;
 use rocketmq_common::CRC32Utils::crc32;
 use rocketmq_common::MessageDecoder::create_crc32;
 use rocketmq_common::MessageUtils::build_batch_message_id;
+use rocketmq_rust::ArcMut;
 use rocketmq_rust::SyncUnsafeCellWrapper;
 
 use crate::base::message_result::AppendMessageResult;
@@ -82,13 +83,13 @@ const END_FILE_MIN_BLANK_LENGTH: i32 = 4 + 4;
 pub(crate) struct DefaultAppendMessageCallback {
     msg_store_item_memory: SyncUnsafeCellWrapper<bytes::BytesMut>,
     crc32_reserved_length: i32,
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
 }
 
 impl DefaultAppendMessageCallback {
     pub fn new(
-        message_store_config: Arc<MessageStoreConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
         topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
     ) -> Self {
         let crc32_reserved_length = if message_store_config.enabled_append_prop_crc {
@@ -152,6 +153,8 @@ impl AppendMessageCallback for DefaultAppendMessageCallback {
             bytes.put_i32(BLANK_MAGIC_CODE);
             let instant = Instant::now();
             mapped_file.write_bytes_segment(bytes.as_ref(), wrote_offset as usize, 0, bytes.len());
+            // keep the encoded message, it is appended again to the next file
+            msg_inner.encoded_buff = Some(pre_encode_buffer);
             return AppendMessageResult {
                 status: AppendMessageStatus::EndOfFile,
                 wrote_offset,
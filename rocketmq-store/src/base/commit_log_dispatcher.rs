@@ -18,5 +18,8 @@
 use crate::base::dispatch_request::DispatchRequest;
 
 pub trait CommitLogDispatcher: Send + Sync + 'static {
-    fn dispatch(&self, dispatch_request: &DispatchRequest);
+    /// Builds derived data (consume queues, index, filter bitmaps...) from a message appended
+    /// to the commit log. Dispatchers run in order, so earlier ones may enrich the request for
+    /// later ones.
+    fn dispatch(&self, dispatch_request: &mut DispatchRequest);
 }
@@ -16,6 +16,7 @@
  */
 use std::fmt;
 
+use crate::base::message_status_enum::BatchFullStatus;
 use crate::base::message_status_enum::GetMessageStatus;
 use crate::base::select_result::SelectMappedBufferResult;
 
@@ -31,6 +32,8 @@ pub struct GetMessageResult {
     message_queue_offset: Vec<u64>,
     /// The status of getting the message.
     status: Option<GetMessageStatus>,
+    /// The limit that stopped the batch, if any.
+    batch_full_status: Option<BatchFullStatus>,
     /// The next begin offset.
     next_begin_offset: i64,
     /// The minimum offset.
@@ -55,9 +58,11 @@ impl fmt::Display for GetMessageResult {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "GetMessageResult [status={:?}, nextBeginOffset={}, minOffset={}, maxOffset={}, \
-             bufferTotalSize={}, messageCount={}, suggestPullingFromSlave={}]",
+            "GetMessageResult [status={:?}, batchFullStatus={:?}, nextBeginOffset={}, \
+             minOffset={}, maxOffset={}, bufferTotalSize={}, messageCount={}, \
+             suggestPullingFromSlave={}]",
             self.status,
+            self.batch_full_status,
             self.next_begin_offset,
             self.min_offset,
             self.max_offset,
@@ -92,6 +97,9 @@ impl GetMessageResult {
     pub fn status(&self) -> Option<GetMessageStatus> {
         self.status
     }
+    pub fn batch_full_status(&self) -> Option<BatchFullStatus> {
+        self.batch_full_status
+    }
     pub fn next_begin_offset(&self) -> i64 {
         self.next_begin_offset
     }
@@ -119,6 +127,9 @@ impl GetMessageResult {
     pub fn cold_data_sum(&self) -> i64 {
         self.cold_data_sum
     }
+    pub fn message_queue_offset(&self) -> &[u64] {
+        self.message_queue_offset.as_slice()
+    }
 
     pub fn set_message_mapped_list(&mut self, message_mapped_list: Vec<SelectMappedBufferResult>) {
         self.message_mapped_list = message_mapped_list;
@@ -132,6 +143,9 @@ impl GetMessageResult {
     pub fn set_status(&mut self, status: Option<GetMessageStatus>) {
         self.status = status;
     }
+    pub fn set_batch_full_status(&mut self, batch_full_status: Option<BatchFullStatus>) {
+        self.batch_full_status = batch_full_status;
+    }
     pub fn set_next_begin_offset(&mut self, next_begin_offset: i64) {
         self.next_begin_offset = next_begin_offset;
     }
@@ -219,7 +233,7 @@ mod tests {
 
         //result.set_message_buffer_list(buffer_list);
         result.set_message_queue_offset(queue_offset);
-        result.set_status(status.clone());
+        result.set_status(status);
         result.set_next_begin_offset(next_begin_offset);
         result.set_min_offset(min_offset);
         result.set_max_offset(max_offset);
@@ -229,6 +243,7 @@ mod tests {
         result.set_msg_count4_commercial(msg_count4_commercial);
         result.set_commercial_size_per_msg(commercial_size_per_msg);
         result.set_cold_data_sum(cold_data_sum);
+        result.set_batch_full_status(Some(BatchFullStatus::BufferFull));
 
         assert_eq!(result.message_mapped_list.len(), 0);
         // assert_eq!(result.message_buffer_list.len(), 10);
@@ -246,5 +261,9 @@ mod tests {
         assert_eq!(result.msg_count4_commercial, msg_count4_commercial);
         assert_eq!(result.commercial_size_per_msg, commercial_size_per_msg);
         assert_eq!(result.cold_data_sum, cold_data_sum);
+        assert_eq!(
+            result.batch_full_status(),
+            Some(BatchFullStatus::BufferFull)
+        );
     }
 }
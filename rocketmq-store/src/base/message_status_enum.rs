@@ -72,3 +72,18 @@ impl std::fmt::Display for GetMessageStatus {
         write!(f, "{:?}", self)
     }
 }
+
+/// Reason a get message call stopped collecting messages before reaching the end of the queue.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BatchFullStatus {
+    /// The message count limit of the request or of the transfer was reached.
+    Full,
+    /// The byte limit of the request or of the transfer was reached.
+    BufferFull,
+}
+
+impl std::fmt::Display for BatchFullStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -17,6 +17,7 @@
 use std::hash::DefaultHasher;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::sync::Arc;
 
 use tokio::sync::Mutex;
 
@@ -25,7 +26,7 @@ const MAXIMUM_CAPACITY: usize = 1 << 6;
 pub(crate) struct TopicQueueLock {
     pub(crate) size: usize,
     pub(crate) size_: usize,
-    pub(crate) lock_vec: Vec<Mutex<()>>,
+    pub(crate) lock_vec: Vec<Arc<Mutex<()>>>,
 }
 
 impl TopicQueueLock {
@@ -33,7 +34,7 @@ impl TopicQueueLock {
         let size = table_size_for(size);
         let mut lock_vec = Vec::with_capacity(size);
         for _ in 0..size {
-            lock_vec.push(Mutex::new(()));
+            lock_vec.push(Arc::new(Mutex::new(())));
         }
         TopicQueueLock {
             size,
@@ -45,7 +46,7 @@ impl TopicQueueLock {
 
 impl TopicQueueLock {
     #[inline]
-    pub(crate) fn lock(&self, topic_queue_key: &str) -> &Mutex<()> {
+    pub(crate) fn lock(&self, topic_queue_key: &str) -> &Arc<Mutex<()>> {
         let hash = calculate_hash(topic_queue_key);
         let index = (hash & self.size_ as u64) as usize;
         &self.lock_vec[index]
@@ -20,7 +20,10 @@ use std::path::PathBuf;
 
 use cheetah_string::CheetahString;
 use lazy_static::lazy_static;
+use rocketmq_common::common::config_value::AtomicConfigValue;
+use rocketmq_common::common::mix_all;
 use serde::Deserialize;
+use serde::Serialize;
 
 use crate::base::store_enum::StoreType;
 use crate::config::broker_role::BrokerRole;
@@ -31,10 +34,12 @@ lazy_static! {
     static ref USER_HOME: PathBuf = dirs::home_dir().unwrap();
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct MessageStoreConfig {
     pub store_path_root_dir: CheetahString,
+    /// Commit log directories, several disks can be listed separated by
+    /// `rocketmq.broker.multiPathSplitter` (`,` by default).
     pub store_path_commit_log: Option<CheetahString>,
     pub store_path_dledger_commit_log: Option<CheetahString>,
     pub store_path_epoch_file: Option<CheetahString>,
@@ -73,13 +78,13 @@ pub struct MessageStoreConfig {
     pub mapped_file_size_consume_queue_ext: usize,
     pub mapper_file_size_batch_consume_queue: usize,
     pub bit_map_length_consume_queue_ext: usize,
-    pub flush_interval_commit_log: i32,
-    pub commit_interval_commit_log: u64,
+    pub flush_interval_commit_log: AtomicConfigValue<i32>,
+    pub commit_interval_commit_log: AtomicConfigValue<u64>,
     pub max_recovery_commit_log_files: usize,
     pub disk_space_warning_level_ratio: usize,
     pub disk_space_clean_forcibly_ratio: usize,
     pub use_reentrant_lock_when_put_message: bool,
-    pub flush_commit_log_timed: bool,
+    pub flush_commit_log_timed: AtomicConfigValue<bool>,
     pub flush_interval_consume_queue: usize,
     pub clean_resource_interval: usize,
     pub delete_commit_log_files_interval: usize,
@@ -93,12 +98,12 @@ pub struct MessageStoreConfig {
     pub put_msg_index_hight_water: usize,
     pub max_message_size: i32,
     pub check_crc_on_recover: bool,
-    pub flush_commit_log_least_pages: i32,
-    pub commit_commit_log_least_pages: i32,
+    pub flush_commit_log_least_pages: AtomicConfigValue<i32>,
+    pub commit_commit_log_least_pages: AtomicConfigValue<i32>,
     pub flush_least_pages_when_warm_mapped_file: usize,
     pub flush_consume_queue_least_pages: usize,
-    pub flush_commit_log_thorough_interval: i32,
-    pub commit_commit_log_thorough_interval: u64,
+    pub flush_commit_log_thorough_interval: AtomicConfigValue<i32>,
+    pub commit_commit_log_thorough_interval: AtomicConfigValue<u64>,
     pub flush_consume_queue_thorough_interval: usize,
     pub max_transfer_bytes_on_message_in_memory: u64,
     pub max_transfer_count_on_message_in_memory: u64,
@@ -134,9 +139,15 @@ pub struct MessageStoreConfig {
     pub transient_store_pool_enable: bool,
     pub transient_store_pool_size: usize,
     pub fast_fail_if_no_buffer_in_store_pool: bool,
+    /// Replaces master/slave replication with a raft replicated commit log, the members
+    /// of the group are listed in `dledger_peers` as `n0-host:port;n1-host:port`.
+    #[serde(alias = "enableDLegerCommitLog")]
     pub enable_dledger_commit_log: bool,
+    #[serde(alias = "dLegerGroup")]
     pub dledger_group: Option<String>,
+    #[serde(alias = "dLegerPeers")]
     pub dledger_peers: Option<String>,
+    #[serde(alias = "dLegerSelfId")]
     pub dledger_self_id: Option<String>,
     pub preferred_leader_id: Option<String>,
     pub enable_batch_push: bool,
@@ -206,6 +217,23 @@ pub struct MessageStoreConfig {
     pub enable_rocksdb_log: bool,
     pub topic_queue_lock_num: usize,
     pub max_filter_message_size: i32,
+
+    /// Copies sealed commit log and consume queue files to object storage, which then serves
+    /// the offsets the local store no longer retains.
+    pub tiered_store_enable: bool,
+    /// `file` keeps objects below `tiered_store_file_path`, `s3` keeps them in an
+    /// S3-compatible bucket.
+    pub tiered_backend_service_provider: String,
+    pub tiered_store_file_path: String,
+    pub tiered_store_upload_interval_ms: u64,
+    pub object_store_endpoint: String,
+    pub object_store_region: String,
+    pub object_store_bucket: String,
+    pub object_store_access_key: String,
+    pub object_store_secret_key: String,
+    pub object_store_connect_timeout_ms: u64,
+    /// Bounds a whole request to the object storage, including reading the response body.
+    pub object_store_request_timeout_ms: u64,
 }
 
 impl Default for MessageStoreConfig {
@@ -259,13 +287,13 @@ impl Default for MessageStoreConfig {
             mapped_file_size_consume_queue_ext: 48 * 1024 * 1024,
             mapper_file_size_batch_consume_queue: 300000 * 46,
             bit_map_length_consume_queue_ext: 64,
-            flush_interval_commit_log: 500,
-            commit_interval_commit_log: 200,
+            flush_interval_commit_log: AtomicConfigValue::new(500),
+            commit_interval_commit_log: AtomicConfigValue::new(200),
             max_recovery_commit_log_files: 0,
             disk_space_warning_level_ratio: 0,
             disk_space_clean_forcibly_ratio: 0,
             use_reentrant_lock_when_put_message: false,
-            flush_commit_log_timed: true,
+            flush_commit_log_timed: AtomicConfigValue::new(true),
             flush_interval_consume_queue: 1000,
             clean_resource_interval: 10000,
             delete_commit_log_files_interval: 100,
@@ -279,12 +307,12 @@ impl Default for MessageStoreConfig {
             put_msg_index_hight_water: 0,
             max_message_size: 1024 * 1024 * 4,
             check_crc_on_recover: false,
-            flush_commit_log_least_pages: 0,
-            commit_commit_log_least_pages: 4,
-            flush_least_pages_when_warm_mapped_file: 0,
+            flush_commit_log_least_pages: AtomicConfigValue::new(0),
+            commit_commit_log_least_pages: AtomicConfigValue::new(4),
+            flush_least_pages_when_warm_mapped_file: 1024 / 4 * 16,
             flush_consume_queue_least_pages: 0,
-            flush_commit_log_thorough_interval: 1000 * 10,
-            commit_commit_log_thorough_interval: 200,
+            flush_commit_log_thorough_interval: AtomicConfigValue::new(1000 * 10),
+            commit_commit_log_thorough_interval: AtomicConfigValue::new(200),
             flush_consume_queue_thorough_interval: 0,
             max_transfer_bytes_on_message_in_memory: 1024 * 256,
             max_transfer_count_on_message_in_memory: 32,
@@ -296,12 +324,12 @@ impl Default for MessageStoreConfig {
             max_index_num: 5000000 * 4,
             max_msgs_num_batch: 64,
             message_index_safe: false,
-            ha_listen_port: 0,
-            ha_send_heartbeat_interval: 0,
-            ha_housekeeping_interval: 0,
-            ha_transfer_batch_size: 0,
+            ha_listen_port: 10912,
+            ha_send_heartbeat_interval: 1000 * 5,
+            ha_housekeeping_interval: 1000 * 20,
+            ha_transfer_batch_size: 1024 * 32,
             ha_master_address: None,
-            ha_max_gap_not_in_sync: 0,
+            ha_max_gap_not_in_sync: 1024 * 1024 * 256,
             broker_role: Default::default(),
             flush_disk_type: FlushDiskType::SyncFlush,
             sync_flush_timeout: 1000 * 5,
@@ -329,7 +357,7 @@ impl Default for MessageStoreConfig {
             enable_schedule_message_stats: false,
             enable_lmq: false,
             enable_multi_dispatch: false,
-            max_lmq_consume_queue_num: 0,
+            max_lmq_consume_queue_num: 20000,
             enable_schedule_async_deliver: false,
             schedule_async_deliver_max_pending_limit: 0,
             schedule_async_deliver_max_resend_num2_blocked: 0,
@@ -392,6 +420,17 @@ impl Default for MessageStoreConfig {
             enable_rocksdb_log: false,
             topic_queue_lock_num: 32,
             max_filter_message_size: 16000,
+            tiered_store_enable: false,
+            tiered_backend_service_provider: "file".to_string(),
+            tiered_store_file_path: "".to_string(),
+            tiered_store_upload_interval_ms: 60 * 1000,
+            object_store_endpoint: "".to_string(),
+            object_store_region: "us-east-1".to_string(),
+            object_store_bucket: "".to_string(),
+            object_store_access_key: "".to_string(),
+            object_store_secret_key: "".to_string(),
+            object_store_connect_timeout_ms: 3000,
+            object_store_request_timeout_ms: 30 * 1000,
         }
     }
 }
@@ -407,6 +446,18 @@ impl MessageStoreConfig {
         self.store_path_commit_log.clone().unwrap().to_string()
     }
 
+    /// Commit log directory used instead of `store_path_commit_log` when the dledger commit
+    /// log is enabled.
+    pub fn get_store_path_dledger_commit_log(&self) -> String {
+        match self.store_path_dledger_commit_log {
+            Some(ref path) => path.to_string(),
+            None => PathBuf::from(self.store_path_root_dir.to_string())
+                .join("dledger_commitlog")
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+
     pub fn is_enable_rocksdb_store(&self) -> bool {
         self.store_type == StoreType::RocksDB
     }
@@ -420,6 +471,37 @@ impl MessageStoreConfig {
         self.timer_wheel_enable
     }
 
+    /// Update a property which can be changed at runtime, `Ok(false)` if the key is not one of
+    /// them. Only the flush and commit settings re-read by the flush services on every round are
+    /// accepted.
+    pub fn update_property(&self, key: &str, value: &str) -> Result<bool, String> {
+        match key {
+            "flushIntervalCommitLog" => self
+                .flush_interval_commit_log
+                .set(mix_all::parse_property_value(key, value)?),
+            "flushCommitLogTimed" => self
+                .flush_commit_log_timed
+                .set(mix_all::parse_property_value(key, value)?),
+            "flushCommitLogLeastPages" => self
+                .flush_commit_log_least_pages
+                .set(mix_all::parse_property_value(key, value)?),
+            "flushCommitLogThoroughInterval" => self
+                .flush_commit_log_thorough_interval
+                .set(mix_all::parse_property_value(key, value)?),
+            "commitIntervalCommitLog" => self
+                .commit_interval_commit_log
+                .set(mix_all::parse_property_value(key, value)?),
+            "commitCommitLogLeastPages" => self
+                .commit_commit_log_least_pages
+                .set(mix_all::parse_property_value(key, value)?),
+            "commitCommitLogThoroughInterval" => self
+                .commit_commit_log_thorough_interval
+                .set(mix_all::parse_property_value(key, value)?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
     pub fn get_properties(&self) -> HashMap<CheetahString, CheetahString> {
         let mut properties: HashMap<String, String> = HashMap::new();
         properties.insert(
@@ -595,11 +677,11 @@ impl MessageStoreConfig {
         );
         properties.insert(
             "flushIntervalCommitLog".to_string(),
-            self.flush_interval_commit_log.to_string(),
+            self.flush_interval_commit_log.get().to_string(),
         );
         properties.insert(
             "commitIntervalCommitLog".to_string(),
-            self.commit_interval_commit_log.to_string(),
+            self.commit_interval_commit_log.get().to_string(),
         );
         properties.insert(
             "maxRecoveryCommitLogFiles".to_string(),
@@ -619,7 +701,7 @@ impl MessageStoreConfig {
         );
         properties.insert(
             "flushCommitLogTimed".to_string(),
-            self.flush_commit_log_timed.to_string(),
+            self.flush_commit_log_timed.get().to_string(),
         );
         properties.insert(
             "flushIntervalConsumeQueue".to_string(),
@@ -672,11 +754,11 @@ impl MessageStoreConfig {
         );
         properties.insert(
             "flushCommitLogLeastPages".to_string(),
-            self.flush_commit_log_least_pages.to_string(),
+            self.flush_commit_log_least_pages.get().to_string(),
         );
         properties.insert(
             "commitCommitLogLeastPages".to_string(),
-            self.commit_commit_log_least_pages.to_string(),
+            self.commit_commit_log_least_pages.get().to_string(),
         );
         properties.insert(
             "flushLeastPagesWhenWarmMappedFile".to_string(),
@@ -688,11 +770,11 @@ impl MessageStoreConfig {
         );
         properties.insert(
             "flushCommitLogThoroughInterval".to_string(),
-            self.flush_commit_log_thorough_interval.to_string(),
+            self.flush_commit_log_thorough_interval.get().to_string(),
         );
         properties.insert(
             "commitCommitLogThoroughInterval".to_string(),
-            self.commit_commit_log_thorough_interval.to_string(),
+            self.commit_commit_log_thorough_interval.get().to_string(),
         );
         properties.insert(
             "flushConsumeQueueThoroughInterval".to_string(),
@@ -1102,6 +1184,36 @@ impl MessageStoreConfig {
             "maxFilterMessageSize".into(),
             self.max_filter_message_size.to_string(),
         );
+        properties.insert(
+            "tieredStoreEnable".into(),
+            self.tiered_store_enable.to_string(),
+        );
+        properties.insert(
+            "tieredBackendServiceProvider".into(),
+            self.tiered_backend_service_provider.clone(),
+        );
+        properties.insert(
+            "tieredStoreFilePath".into(),
+            self.tiered_store_file_path.clone(),
+        );
+        properties.insert(
+            "tieredStoreUploadIntervalMs".into(),
+            self.tiered_store_upload_interval_ms.to_string(),
+        );
+        properties.insert(
+            "objectStoreEndpoint".into(),
+            self.object_store_endpoint.clone(),
+        );
+        properties.insert("objectStoreRegion".into(), self.object_store_region.clone());
+        properties.insert("objectStoreBucket".into(), self.object_store_bucket.clone());
+        properties.insert(
+            "objectStoreConnectTimeoutMs".into(),
+            self.object_store_connect_timeout_ms.to_string(),
+        );
+        properties.insert(
+            "objectStoreRequestTimeoutMs".into(),
+            self.object_store_request_timeout_ms.to_string(),
+        );
         properties
             .into_iter()
             .map(|(k, v)| (k.into(), v.into()))
@@ -25,17 +25,24 @@ use std::sync::Arc;
 use cheetah_string::CheetahString;
 use log::warn;
 use parking_lot::RwLock;
+use rocketmq_common::common::mix_all::MULTI_PATH_SPLITTER;
 use rocketmq_common::UtilAll::offset_to_file_name;
 use tracing::info;
 
+use crate::base::allocate_mapped_file_service::AllocateMappedFileService;
 use crate::log_file::mapped_file::default_mapped_file_impl::DefaultMappedFile;
 use crate::log_file::mapped_file::MappedFile;
-use crate::services::allocate_mapped_file_service::AllocateMappedFileService;
+use crate::utils::store_util::StoreUtil;
 
 #[derive(Default, Clone)]
 pub struct MappedFileQueue {
+    /// One or more directories joined by `MULTI_PATH_SPLITTER`, new mapped files go to the one
+    /// with the most free space.
     pub(crate) store_path: String,
 
+    /// Directories whose mapped files are loaded but never written to again.
+    pub(crate) read_only_store_path: Option<String>,
+
     pub(crate) mapped_file_size: u64,
     //pub(crate) mapped_files: Arc<Mutex<Vec<LocalMappedFile>>>,
     //pub(crate) mapped_files: Vec<Arc<Mutex<LocalMappedFile>>>,
@@ -43,7 +50,7 @@ pub struct MappedFileQueue {
     //pub(crate) mapped_files: Vec<Arc<DefaultMappedFile>>,
     pub(crate) mapped_files: Arc<RwLock<Vec<Arc<DefaultMappedFile>>>>,
     //  pub(crate) mapped_files: Vec<LocalMappedFile>,
-    pub(crate) allocate_mapped_file_service: Option<AllocateMappedFileService>,
+    pub(crate) allocate_mapped_file_service: Option<Arc<AllocateMappedFileService>>,
 
     pub(crate) flushed_where: Arc<AtomicU64>,
 
@@ -56,10 +63,11 @@ impl MappedFileQueue {
     pub fn new(
         store_path: String,
         mapped_file_size: u64,
-        allocate_mapped_file_service: Option<AllocateMappedFileService>,
+        allocate_mapped_file_service: Option<Arc<AllocateMappedFileService>>,
     ) -> MappedFileQueue {
         MappedFileQueue {
             store_path,
+            read_only_store_path: None,
             mapped_file_size,
             mapped_files: Arc::new(RwLock::new(Vec::new())),
             allocate_mapped_file_service,
@@ -73,15 +81,48 @@ impl MappedFileQueue {
 impl MappedFileQueue {
     pub fn load(&mut self) -> bool {
         //list dir files
-        let dir = Path::new(&self.store_path);
-        if let Ok(ls) = fs::read_dir(dir) {
-            let files: Vec<_> = ls
-                .filter_map(Result::ok)
-                .map(|entry| entry.path())
-                .collect();
-            return self.do_load(files);
+        let mut files = Vec::new();
+        let read_only_paths = self
+            .read_only_store_path
+            .as_deref()
+            .map(split_store_paths)
+            .unwrap_or_default();
+        for store_path in split_store_paths(&self.store_path)
+            .into_iter()
+            .chain(read_only_paths)
+        {
+            if let Ok(ls) = fs::read_dir(Path::new(store_path)) {
+                files.extend(ls.filter_map(Result::ok).map(|entry| entry.path()));
+            }
         }
-        true
+        self.do_load(files)
+    }
+
+    /// Returns the writable directory that should hold the next mapped file.
+    pub fn select_store_path(&self) -> &str {
+        let store_paths = split_store_paths(&self.store_path);
+        if store_paths.len() <= 1 {
+            return store_paths
+                .first()
+                .copied()
+                .unwrap_or(self.store_path.as_str());
+        }
+        let read_only_paths = self
+            .read_only_store_path
+            .as_deref()
+            .map(split_store_paths)
+            .unwrap_or_default();
+        let writable_paths: Vec<&str> = store_paths
+            .iter()
+            .copied()
+            .filter(|path| !read_only_paths.contains(path))
+            .collect();
+        let candidates = if writable_paths.is_empty() {
+            store_paths
+        } else {
+            writable_paths
+        };
+        select_path_with_most_free_space(&candidates, StoreUtil::get_available_space)
     }
 
     pub fn commit(&self, commit_least_pages: i32) -> bool {
@@ -199,27 +240,34 @@ impl MappedFileQueue {
     }
 
     pub fn try_create_mapped_file(&mut self, create_offset: u64) -> Option<Arc<DefaultMappedFile>> {
-        let next_file_path =
-            PathBuf::from(self.store_path.clone()).join(offset_to_file_name(create_offset));
-        let next_next_file_path = PathBuf::from(self.store_path.clone())
-            .join(offset_to_file_name(create_offset + self.mapped_file_size));
+        let store_path = PathBuf::from(self.select_store_path());
+        let next_file_path = store_path.join(offset_to_file_name(create_offset));
+        let next_next_file_path =
+            store_path.join(offset_to_file_name(create_offset + self.mapped_file_size));
         self.do_create_mapped_file(next_file_path, next_next_file_path)
     }
 
     fn do_create_mapped_file(
         &mut self,
         next_file_path: PathBuf,
-        _next_next_file_path: PathBuf,
+        next_next_file_path: PathBuf,
     ) -> Option<Arc<DefaultMappedFile>> {
-        let mut mapped_file = match self.allocate_mapped_file_service {
-            None => DefaultMappedFile::new(
+        let allocated = self
+            .allocate_mapped_file_service
+            .as_ref()
+            .and_then(|service| {
+                service.put_request_and_return_mapped_file(
+                    next_file_path.to_string_lossy().to_string(),
+                    next_next_file_path.to_string_lossy().to_string(),
+                    self.mapped_file_size,
+                )
+            });
+        let mut mapped_file = allocated.unwrap_or_else(|| {
+            DefaultMappedFile::new(
                 CheetahString::from_string(next_file_path.to_string_lossy().to_string()),
                 self.mapped_file_size,
-            ),
-            Some(ref _value) => {
-                unimplemented!()
-            }
-        };
+            )
+        });
 
         if self.mapped_files.read().is_empty() {
             mapped_file.set_first_create_in_queue(true);
@@ -264,6 +312,7 @@ impl MappedFileQueue {
                 }
             }
         }
+        self.delete_expired_file(will_remove_files);
     }
 
     pub fn get_max_offset(&self) -> i64 {
@@ -287,10 +336,7 @@ impl MappedFileQueue {
     }
 
     pub(crate) fn delete_expired_file(&mut self, files: Vec<Arc<DefaultMappedFile>>) {
-        let mut files = files;
-        let read_guard = self.mapped_files.read();
         if !files.is_empty() {
-            files.retain(|mf| read_guard.contains(mf));
             self.mapped_files.write().retain(|mf| !files.contains(mf));
         }
     }
@@ -301,9 +347,11 @@ impl MappedFileQueue {
         }
         self.mapped_files.write().clear();
         self.set_flushed_where(0);
-        let path = PathBuf::from(&self.store_path);
-        if path.is_dir() {
-            let _ = fs::remove_dir_all(path);
+        for store_path in split_store_paths(&self.store_path) {
+            let path = PathBuf::from(store_path);
+            if path.is_dir() {
+                let _ = fs::remove_dir_all(path);
+            }
         }
     }
 
@@ -403,10 +451,78 @@ impl MappedFileQueue {
     }
 }
 
+fn split_store_paths(store_path: &str) -> Vec<&str> {
+    store_path
+        .split(MULTI_PATH_SPLITTER.as_str())
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .collect()
+}
+
+fn select_path_with_most_free_space<'a>(
+    store_paths: &[&'a str],
+    available_space: impl Fn(&str) -> u64,
+) -> &'a str {
+    // Ties keep the first configured path so allocation stays deterministic.
+    let mut selected = store_paths[0];
+    let mut selected_space = available_space(selected);
+    for path in &store_paths[1..] {
+        let space = available_space(path);
+        if space > selected_space {
+            selected = path;
+            selected_space = space;
+        }
+    }
+    selected
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn select_path_prefers_most_free_space() {
+        let paths = ["/disk1/commitlog", "/disk2/commitlog", "/disk3/commitlog"];
+        let selected = select_path_with_most_free_space(&paths, |path| match path {
+            "/disk2/commitlog" => 300,
+            "/disk3/commitlog" => 300,
+            _ => 100,
+        });
+        assert_eq!(selected, "/disk2/commitlog");
+    }
+
+    #[test]
+    fn test_load_from_multiple_dirs() {
+        let temp_dir1 = tempfile::tempdir().unwrap();
+        let temp_dir2 = tempfile::tempdir().unwrap();
+        fs::write(
+            temp_dir1.path().join(offset_to_file_name(0)),
+            vec![0u8; 1024],
+        )
+        .unwrap();
+        fs::write(
+            temp_dir2.path().join(offset_to_file_name(1024)),
+            vec![0u8; 1024],
+        )
+        .unwrap();
+
+        let mut queue = MappedFileQueue {
+            store_path: format!(
+                "{}{}{}",
+                temp_dir1.path().to_string_lossy(),
+                MULTI_PATH_SPLITTER.as_str(),
+                temp_dir2.path().to_string_lossy()
+            ),
+            mapped_file_size: 1024,
+            ..MappedFileQueue::default()
+        };
+        assert!(queue.load());
+        let mapped_files = queue.mapped_files.read();
+        assert_eq!(mapped_files.len(), 2);
+        assert_eq!(mapped_files[0].get_file_from_offset(), 0);
+        assert_eq!(mapped_files[1].get_file_from_offset(), 1024);
+    }
+
     #[test]
     fn test_load_empty_dir() {
         let mut queue = MappedFileQueue {
@@ -0,0 +1,176 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+
+/// Size of the frame header: the length of the JSON header (4 bytes) followed by the
+/// length of the raw commit log data (4 bytes).
+pub const FRAME_HEADER_SIZE: usize = 4 + 4;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteRequest {
+    pub term: i64,
+    pub candidate_id: String,
+    pub last_log_term: i64,
+    pub last_log_offset: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoteResponse {
+    pub term: i64,
+    pub vote_granted: bool,
+}
+
+/// Replicates the commit log bytes starting at `start_offset`, an empty `data` is a
+/// heartbeat.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppendRequest {
+    pub term: i64,
+    pub leader_id: String,
+    pub start_offset: i64,
+    /// Term of the leader's log right before `start_offset`.
+    pub prev_term: i64,
+    pub commit_offset: i64,
+    /// `(start_offset, term)` of every term that wrote to the leader's log.
+    pub term_starts: Vec<(i64, i64)>,
+    #[serde(skip)]
+    pub data: Bytes,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppendResponse {
+    pub term: i64,
+    pub success: bool,
+    /// Offset the leader should replicate from next.
+    pub next_offset: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DLedgerRequest {
+    Vote(VoteRequest),
+    Append(AppendRequest),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DLedgerResponse {
+    Vote(VoteResponse),
+    Append(AppendResponse),
+}
+
+impl DLedgerRequest {
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> std::io::Result<()> {
+        let data = match self {
+            DLedgerRequest::Append(request) => request.data.clone(),
+            DLedgerRequest::Vote(_) => Bytes::new(),
+        };
+        write_frame(writer, self, &data).await
+    }
+
+    pub async fn read_from<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Self> {
+        let (mut request, data) = read_frame::<R, DLedgerRequest>(reader).await?;
+        if let DLedgerRequest::Append(ref mut append) = request {
+            append.data = data;
+        }
+        Ok(request)
+    }
+}
+
+impl DLedgerResponse {
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_frame(writer, self, &[]).await
+    }
+
+    pub async fn read_from<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Self> {
+        read_frame::<R, DLedgerResponse>(reader)
+            .await
+            .map(|(response, _)| response)
+    }
+}
+
+async fn write_frame<W: AsyncWrite + Unpin, T: Serialize>(
+    writer: &mut W,
+    header: &T,
+    data: &[u8],
+) -> std::io::Result<()> {
+    let header = serde_json::to_vec(header)?;
+    let mut frame = BytesMut::with_capacity(FRAME_HEADER_SIZE + header.len() + data.len());
+    frame.put_i32(header.len() as i32);
+    frame.put_i32(data.len() as i32);
+    frame.put_slice(&header);
+    frame.put_slice(data);
+    writer.write_all(&frame).await?;
+    writer.flush().await
+}
+
+async fn read_frame<R: AsyncRead + Unpin, T: DeserializeOwned>(
+    reader: &mut R,
+) -> std::io::Result<(T, Bytes)> {
+    let header_size = reader.read_i32().await? as usize;
+    let data_size = reader.read_i32().await? as usize;
+    let mut header = vec![0u8; header_size];
+    reader.read_exact(&mut header).await?;
+    let mut data = vec![0u8; data_size];
+    reader.read_exact(&mut data).await?;
+    Ok((serde_json::from_slice(&header)?, Bytes::from(data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn append_request_round_trip_keeps_data() {
+        let request = DLedgerRequest::Append(AppendRequest {
+            term: 3,
+            leader_id: "n1".to_string(),
+            start_offset: 1024,
+            prev_term: 2,
+            commit_offset: 512,
+            term_starts: vec![(0, 1), (512, 2), (1024, 3)],
+            data: Bytes::from_static(b"commit log bytes"),
+        });
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        request.write_to(&mut client).await.unwrap();
+        assert_eq!(
+            DLedgerRequest::read_from(&mut server).await.unwrap(),
+            request
+        );
+
+        let response = DLedgerResponse::Vote(VoteResponse {
+            term: 3,
+            vote_granted: true,
+        });
+        response.write_to(&mut server).await.unwrap();
+        assert_eq!(
+            DLedgerResponse::read_from(&mut client).await.unwrap(),
+            response
+        );
+    }
+}
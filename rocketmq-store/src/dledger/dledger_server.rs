@@ -0,0 +1,692 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use bytes::Bytes;
+use rand::Rng;
+use rocketmq_rust::ArcMut;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::task::JoinSet;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::base::message_status_enum::PutMessageStatus;
+use crate::base::store_checkpoint::StoreCheckpoint;
+use crate::config::broker_role::BrokerRole;
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::dledger::dledger_protocol::AppendRequest;
+use crate::dledger::dledger_protocol::AppendResponse;
+use crate::dledger::dledger_protocol::DLedgerRequest;
+use crate::dledger::dledger_protocol::DLedgerResponse;
+use crate::dledger::dledger_protocol::VoteRequest;
+use crate::dledger::dledger_protocol::VoteResponse;
+use crate::dledger::member_state::AppendCheck;
+use crate::dledger::member_state::DLedgerRole;
+use crate::dledger::member_state::MemberState;
+use crate::log_file::commit_log::CommitLog;
+use crate::log_file::mapped_file::MappedFile;
+use crate::store_path_config_helper::get_dledger_state_path;
+
+/// Interval of the leader heartbeats, also the longest a replicator waits for new data.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A follower starts an election after not hearing from a leader for this long plus a
+/// random part of it.
+const ELECTION_TIMEOUT_MILLIS: u64 = 1000;
+
+const ELECTION_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+const RPC_TIMEOUT: Duration = Duration::from_secs(1);
+
+pub type RoleChangeListener = Box<dyn Fn(DLedgerRole, i64) + Send + Sync>;
+
+/// Raft replication of the commit log within a dledger group.
+///
+/// Every member listens on its address in `dledger_peers`. The elected leader is the only
+/// member accepting writes, it pushes its commit log to the followers and a put only
+/// succeeds once a majority of the group stored the message. Followers append what the
+/// leader sends and only dispatch the committed part of their commit log.
+pub struct DLedgerServer {
+    message_store_config: ArcMut<MessageStoreConfig>,
+    commit_log: CommitLog,
+    store_checkpoint: Arc<StoreCheckpoint>,
+    member_state: parking_lot::Mutex<MemberState>,
+    commit_offset: AtomicI64,
+    match_offsets: parking_lot::Mutex<HashMap<String, i64>>,
+    last_leader_contact: parking_lot::Mutex<Instant>,
+    connections: HashMap<String, tokio::sync::Mutex<Option<TcpStream>>>,
+    append_lock: tokio::sync::Mutex<()>,
+    append_notify: Notify,
+    commit_notify: Notify,
+    role_change_listeners: parking_lot::Mutex<Vec<RoleChangeListener>>,
+    tasks: parking_lot::Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl DLedgerServer {
+    pub fn new(
+        message_store_config: ArcMut<MessageStoreConfig>,
+        commit_log: CommitLog,
+        store_checkpoint: Arc<StoreCheckpoint>,
+    ) -> std::io::Result<Self> {
+        let peers =
+            MemberState::parse_peers(message_store_config.dledger_peers.as_deref().unwrap_or(""));
+        let self_id = message_store_config
+            .dledger_self_id
+            .clone()
+            .unwrap_or_else(|| "n0".to_string());
+        let connections = peers
+            .iter()
+            .filter(|(id, _)| *id != self_id)
+            .map(|(id, _)| (id.clone(), tokio::sync::Mutex::new(None)))
+            .collect();
+        let member_state = MemberState::new(
+            self_id,
+            peers,
+            get_dledger_state_path(message_store_config.store_path_root_dir.as_str()),
+        )?;
+        let commit_offset = store_checkpoint.confirm_phy_offset() as i64;
+        Ok(Self {
+            message_store_config,
+            commit_log,
+            store_checkpoint,
+            member_state: parking_lot::Mutex::new(member_state),
+            commit_offset: AtomicI64::new(commit_offset),
+            match_offsets: parking_lot::Mutex::new(HashMap::new()),
+            last_leader_contact: parking_lot::Mutex::new(Instant::now()),
+            connections,
+            append_lock: tokio::sync::Mutex::new(()),
+            append_notify: Notify::new(),
+            commit_notify: Notify::new(),
+            role_change_listeners: parking_lot::Mutex::new(Vec::new()),
+            tasks: parking_lot::Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Binds the address of this member and starts taking part in elections.
+    pub fn start(self: &Arc<Self>) -> std::io::Result<()> {
+        let address = {
+            let member_state = self.member_state.lock();
+            member_state
+                .self_address()
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!(
+                            "dledger self id {} is not in the peers",
+                            member_state.self_id()
+                        ),
+                    )
+                })?
+        };
+        let port = address
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse::<u16>().ok())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("invalid dledger address {}", address),
+                )
+            })?;
+        let listener = std::net::TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let listener = TcpListener::from_std(listener)?;
+        info!("DLedgerServer listen on {}", address);
+
+        let server = self.clone();
+        let accept_task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let _ = stream.set_nodelay(true);
+                        tokio::spawn(server.clone().serve(stream));
+                    }
+                    Err(e) => {
+                        error!("DLedgerServer accept connection failed: {}", e);
+                    }
+                }
+            }
+        });
+        let election_task = tokio::spawn(self.clone().run_election());
+        let mut tasks = self.tasks.lock();
+        tasks.push(accept_task);
+        tasks.push(election_task);
+        Ok(())
+    }
+
+    pub fn shutdown(&self) {
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
+    }
+
+    /// Registers a listener called with the new role and term whenever this member
+    /// becomes leader or follower.
+    pub fn register_role_change_listener(&self, listener: RoleChangeListener) {
+        self.role_change_listeners.lock().push(listener);
+    }
+
+    pub fn role(&self) -> DLedgerRole {
+        self.member_state.lock().role()
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.role() == DLedgerRole::Leader
+    }
+
+    pub fn self_id(&self) -> String {
+        self.member_state.lock().self_id().to_string()
+    }
+
+    pub fn leader_id(&self) -> Option<String> {
+        self.member_state.lock().leader_id().map(str::to_string)
+    }
+
+    pub fn current_term(&self) -> i64 {
+        self.member_state.lock().current_term()
+    }
+
+    /// Offset up to which the commit log is stored on a majority of the group.
+    pub fn get_commit_offset(&self) -> i64 {
+        self.commit_offset.load(Ordering::Acquire)
+    }
+
+    /// Wakes up the replicators, new data was appended to the leader's commit log.
+    pub fn wakeup(&self) {
+        self.append_notify.notify_waiters();
+    }
+
+    /// Blocks a producer until its message is committed, or `sync_flush_timeout` elapsed.
+    pub async fn wait_for_commit(&self, next_offset: i64) -> PutMessageStatus {
+        let deadline = tokio::time::Instant::now()
+            + Duration::from_millis(self.message_store_config.sync_flush_timeout);
+        self.wakeup();
+        loop {
+            let notified = self.commit_notify.notified();
+            if self.get_commit_offset() >= next_offset {
+                return PutMessageStatus::PutOk;
+            }
+            if !self.is_leader() {
+                return PutMessageStatus::FlushSlaveTimeout;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return PutMessageStatus::FlushSlaveTimeout;
+            }
+        }
+    }
+
+    async fn serve(self: Arc<Self>, mut stream: TcpStream) {
+        loop {
+            let request = match DLedgerRequest::read_from(&mut stream).await {
+                Ok(request) => request,
+                Err(_) => return,
+            };
+            let response = match request {
+                DLedgerRequest::Vote(request) => DLedgerResponse::Vote(self.handle_vote(&request)),
+                DLedgerRequest::Append(request) => {
+                    DLedgerResponse::Append(self.handle_append(request).await)
+                }
+            };
+            if let Err(e) = response.write_to(&mut stream).await {
+                warn!("DLedgerServer write response failed: {}", e);
+                return;
+            }
+        }
+    }
+
+    fn handle_vote(&self, request: &VoteRequest) -> VoteResponse {
+        let max_offset = self.commit_log.get_max_offset();
+        let mut member_state = self.member_state.lock();
+        let role = member_state.role();
+        let response = member_state.handle_vote(request, max_offset);
+        let role_changed = role != member_state.role();
+        drop(member_state);
+        if response.vote_granted {
+            *self.last_leader_contact.lock() = Instant::now();
+        }
+        if role_changed {
+            self.on_role_change(DLedgerRole::Follower, response.term);
+        }
+        response
+    }
+
+    async fn handle_append(&self, request: AppendRequest) -> AppendResponse {
+        let _lock = self.append_lock.lock().await;
+        let max_offset = self.commit_log.get_max_offset();
+        let (check, term, role_changed) = {
+            let mut member_state = self.member_state.lock();
+            let role = member_state.role();
+            let check = member_state.check_append(&request, max_offset, self.get_commit_offset());
+            (
+                check,
+                member_state.current_term(),
+                role != member_state.role(),
+            )
+        };
+        if role_changed {
+            self.on_role_change(DLedgerRole::Follower, term);
+        }
+
+        let truncate_offset = match check {
+            AppendCheck::StaleTerm => {
+                return AppendResponse {
+                    term,
+                    success: false,
+                    next_offset: max_offset,
+                };
+            }
+            AppendCheck::Mismatch(next_offset) => {
+                *self.last_leader_contact.lock() = Instant::now();
+                return AppendResponse {
+                    term,
+                    success: false,
+                    next_offset,
+                };
+            }
+            AppendCheck::Accept(truncate_offset) => truncate_offset,
+        };
+        *self.last_leader_contact.lock() = Instant::now();
+        let mut commit_log = self.commit_log.clone();
+        if let Some(offset) = truncate_offset {
+            warn!(
+                "truncate the commit log from {} to {} to follow the leader {}",
+                max_offset, offset, request.leader_id
+            );
+            commit_log.truncate_dirty_files(offset).await;
+        }
+        if !request.data.is_empty()
+            && !commit_log
+                .append_data(request.start_offset, &request.data)
+                .await
+        {
+            error!(
+                "DLedgerServer append data to commit log failed, offset {}",
+                request.start_offset
+            );
+            return AppendResponse {
+                term,
+                success: false,
+                next_offset: commit_log.get_max_offset(),
+            };
+        }
+        let next_offset = request.start_offset + request.data.len() as i64;
+        if let Err(e) = self
+            .member_state
+            .lock()
+            .accept_term_starts(&request.term_starts, next_offset)
+        {
+            error!("persist the dledger terms of the leader failed: {}", e);
+            return AppendResponse {
+                term,
+                success: false,
+                next_offset: request.start_offset,
+            };
+        }
+        self.update_commit_offset(request.commit_offset.min(next_offset));
+        AppendResponse {
+            term,
+            success: true,
+            next_offset,
+        }
+    }
+
+    async fn run_election(self: Arc<Self>) {
+        let mut election_timeout = random_election_timeout();
+        loop {
+            tokio::time::sleep(ELECTION_CHECK_INTERVAL).await;
+            if self.is_leader() || self.last_leader_contact.lock().elapsed() < election_timeout {
+                continue;
+            }
+            self.clone().elect().await;
+            *self.last_leader_contact.lock() = Instant::now();
+            election_timeout = random_election_timeout();
+        }
+    }
+
+    async fn elect(self: Arc<Self>) {
+        let max_offset = self.commit_log.get_max_offset();
+        let (request, quorum) = {
+            let mut member_state = self.member_state.lock();
+            match member_state.become_candidate(max_offset) {
+                Ok(request) => (request, member_state.quorum()),
+                Err(e) => {
+                    error!("persist the dledger vote of the next term failed: {}", e);
+                    return;
+                }
+            }
+        };
+        let term = request.term;
+        info!(
+            "{} starts the election of term {}",
+            request.candidate_id, term
+        );
+
+        let mut calls = JoinSet::new();
+        for peer_id in self.connections.keys() {
+            let server = self.clone();
+            let peer_id = peer_id.clone();
+            let request = DLedgerRequest::Vote(request.clone());
+            calls.spawn(async move { server.call(&peer_id, request).await });
+        }
+        let mut votes = 1;
+        while votes < quorum {
+            let Some(result) = calls.join_next().await else {
+                break;
+            };
+            if let Ok(Some(DLedgerResponse::Vote(response))) = result {
+                if response.term > term {
+                    self.step_down(response.term);
+                    return;
+                }
+                if response.vote_granted {
+                    votes += 1;
+                }
+            }
+        }
+        if votes < quorum {
+            return;
+        }
+
+        {
+            let mut member_state = self.member_state.lock();
+            if member_state.role() != DLedgerRole::Candidate || member_state.current_term() != term
+            {
+                return;
+            }
+            if let Err(e) = member_state.become_leader(self.commit_log.get_max_offset()) {
+                error!("persist the dledger term {} failed: {}", term, e);
+                return;
+            }
+        }
+        self.match_offsets.lock().clear();
+        self.on_role_change(DLedgerRole::Leader, term);
+        let mut tasks = self.tasks.lock();
+        tasks.retain(|task| !task.is_finished());
+        for peer_id in self.connections.keys() {
+            tasks.push(tokio::spawn(self.clone().replicate(peer_id.clone(), term)));
+        }
+    }
+
+    /// Pushes the leader's commit log to one follower for as long as this member leads
+    /// `term`.
+    async fn replicate(self: Arc<Self>, peer_id: String, term: i64) {
+        let batch_size = self.message_store_config.ha_transfer_batch_size;
+        let mut next_offset = self.commit_log.get_max_offset();
+        loop {
+            let notified = self.append_notify.notified();
+            let data = if next_offset < self.commit_log.get_max_offset() {
+                self.transfer_data(next_offset, batch_size)
+                    .unwrap_or_default()
+            } else {
+                Bytes::new()
+            };
+            let request = {
+                let member_state = self.member_state.lock();
+                if member_state.role() != DLedgerRole::Leader || member_state.current_term() != term
+                {
+                    return;
+                }
+                AppendRequest {
+                    term,
+                    leader_id: member_state.self_id().to_string(),
+                    start_offset: next_offset,
+                    prev_term: member_state.term_at(next_offset),
+                    commit_offset: self.get_commit_offset(),
+                    term_starts: member_state.term_starts().to_vec(),
+                    data,
+                }
+            };
+            let size = request.data.len() as i64;
+            if let Some(DLedgerResponse::Append(response)) =
+                self.call(&peer_id, DLedgerRequest::Append(request)).await
+            {
+                if response.term > term {
+                    self.step_down(response.term);
+                    return;
+                }
+                if response.success {
+                    next_offset += size;
+                    self.match_offsets
+                        .lock()
+                        .insert(peer_id.clone(), next_offset);
+                    self.advance_commit_offset(term);
+                    if size > 0 {
+                        continue;
+                    }
+                } else if response.next_offset != next_offset {
+                    next_offset = response.next_offset;
+                    continue;
+                }
+            }
+            let _ = tokio::time::timeout(HEARTBEAT_INTERVAL, notified).await;
+        }
+    }
+
+    /// Copies at most `batch_size` bytes of commit log data starting at `offset`.
+    fn transfer_data(&self, offset: i64, batch_size: usize) -> Option<Bytes> {
+        let result = self.commit_log.get_data(offset)?;
+        let mapped_file = result.mapped_file.as_ref()?;
+        let pos = (result.start_offset - mapped_file.get_file_from_offset()) as usize;
+        let size = (result.size as usize).min(batch_size);
+        let data = Bytes::copy_from_slice(&mapped_file.get_mapped_file()[pos..pos + size]);
+        mapped_file.release();
+        Some(data)
+    }
+
+    /// Commits the highest offset stored on a majority. Like in raft, data of older terms
+    /// only gets committed together with data of the current term.
+    fn advance_commit_offset(&self, term: i64) {
+        let member_state = self.member_state.lock();
+        if member_state.role() != DLedgerRole::Leader || member_state.current_term() != term {
+            return;
+        }
+        let mut offsets = vec![self.commit_log.get_max_offset()];
+        offsets.extend(self.match_offsets.lock().values().copied());
+        offsets.resize(member_state.peers().len().max(offsets.len()), 0);
+        offsets.sort_unstable_by(|a, b| b.cmp(a));
+        let majority_offset = offsets[member_state.quorum() - 1];
+        if member_state.term_at(majority_offset) != term {
+            return;
+        }
+        drop(member_state);
+        if self.update_commit_offset(majority_offset) {
+            self.wakeup();
+        }
+    }
+
+    fn update_commit_offset(&self, commit_offset: i64) -> bool {
+        let previous = self
+            .commit_offset
+            .fetch_max(commit_offset, Ordering::AcqRel);
+        if commit_offset <= previous {
+            return false;
+        }
+        self.store_checkpoint
+            .set_confirm_phy_offset(commit_offset as u64);
+        self.commit_notify.notify_waiters();
+        true
+    }
+
+    fn step_down(&self, term: i64) {
+        let changed = self.member_state.lock().step_down(term, None);
+        if changed {
+            self.on_role_change(DLedgerRole::Follower, term);
+        }
+    }
+
+    /// Only the leader accepts writes, the other members act as slaves of the group.
+    fn on_role_change(&self, role: DLedgerRole, term: i64) {
+        info!("dledger role changed to {} in term {}", role, term);
+        self.message_store_config.mut_from_ref().broker_role = match role {
+            DLedgerRole::Leader => BrokerRole::SyncMaster,
+            DLedgerRole::Follower | DLedgerRole::Candidate => BrokerRole::Slave,
+        };
+        for listener in self.role_change_listeners.lock().iter() {
+            listener(role, term);
+        }
+    }
+
+    async fn call(&self, peer_id: &str, request: DLedgerRequest) -> Option<DLedgerResponse> {
+        let address = self
+            .member_state
+            .lock()
+            .peers()
+            .iter()
+            .find(|(id, _)| id == peer_id)
+            .map(|(_, address)| address.clone())?;
+        let mut connection = self.connections.get(peer_id)?.lock().await;
+        if connection.is_none() {
+            match tokio::time::timeout(RPC_TIMEOUT, TcpStream::connect(address.as_str())).await {
+                Ok(Ok(stream)) => {
+                    let _ = stream.set_nodelay(true);
+                    *connection = Some(stream);
+                }
+                _ => return None,
+            }
+        }
+        let stream = connection.as_mut()?;
+        let result = tokio::time::timeout(RPC_TIMEOUT, async {
+            request.write_to(stream).await?;
+            DLedgerResponse::read_from(stream).await
+        })
+        .await;
+        match result {
+            Ok(Ok(response)) => Some(response),
+            _ => {
+                *connection = None;
+                None
+            }
+        }
+    }
+}
+
+fn random_election_timeout() -> Duration {
+    Duration::from_millis(
+        ELECTION_TIMEOUT_MILLIS + rand::thread_rng().gen_range(0..ELECTION_TIMEOUT_MILLIS),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rocketmq_common::common::broker::broker_config::BrokerConfig;
+    use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+
+    use super::*;
+    use crate::log_file::MessageStore;
+    use crate::message_store::default_message_store::DefaultMessageStore;
+
+    async fn start_store(
+        root_dir: &tempfile::TempDir,
+        self_id: &str,
+        peers: &str,
+    ) -> ArcMut<DefaultMessageStore> {
+        let message_store_config = MessageStoreConfig {
+            store_path_root_dir: root_dir.path().to_string_lossy().to_string().into(),
+            mapped_file_size_commit_log: 64 * 1024,
+            enable_dledger_commit_log: true,
+            dledger_peers: Some(peers.to_string()),
+            dledger_self_id: Some(self_id.to_string()),
+            ..MessageStoreConfig::default()
+        };
+        let mut store = ArcMut::new(DefaultMessageStore::new(
+            ArcMut::new(message_store_config),
+            ArcMut::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        ));
+        let store_arc = store.clone();
+        store.set_message_store_arc(Some(store_arc));
+        assert!(store.load().await);
+        store.start().unwrap();
+        store
+    }
+
+    fn message() -> MessageExtBrokerInner {
+        let mut msg = MessageExtBrokerInner::default();
+        msg.message_ext_inner.message.topic = "TopicTest".into();
+        msg.message_ext_inner.message.body = Some(Bytes::from_static(b"replicated"));
+        msg
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn group_elects_a_leader_and_replicates_puts() {
+        let ports: Vec<u16> = (0..3)
+            .map(|_| {
+                std::net::TcpListener::bind("127.0.0.1:0")
+                    .unwrap()
+                    .local_addr()
+                    .unwrap()
+                    .port()
+            })
+            .collect();
+        let peers = format!(
+            "n0-127.0.0.1:{};n1-127.0.0.1:{};n2-127.0.0.1:{}",
+            ports[0], ports[1], ports[2]
+        );
+        let dirs: Vec<tempfile::TempDir> = (0..3).map(|_| tempfile::tempdir().unwrap()).collect();
+        let mut stores = Vec::new();
+        for (index, dir) in dirs.iter().enumerate() {
+            stores.push(start_store(dir, format!("n{}", index).as_str(), peers.as_str()).await);
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(15);
+        let leader = loop {
+            let leaders: Vec<usize> = (0..3)
+                .filter(|index| stores[*index].get_dledger_server().unwrap().is_leader())
+                .collect();
+            if leaders.len() == 1 {
+                break leaders[0];
+            }
+            assert!(Instant::now() < deadline, "no leader elected");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        };
+
+        let follower = (leader + 1) % 3;
+        assert_eq!(
+            stores[follower]
+                .put_message(message())
+                .await
+                .put_message_status(),
+            PutMessageStatus::ServiceNotAvailable
+        );
+        let result = stores[leader].put_message(message()).await;
+        assert_eq!(result.put_message_status(), PutMessageStatus::PutOk);
+
+        let leader_max_offset = stores[leader].get_max_phy_offset();
+        let committed = stores
+            .iter()
+            .filter(|store| store.get_max_phy_offset() == leader_max_offset)
+            .count();
+        assert!(committed >= 2);
+        for store in stores.iter_mut() {
+            store.shutdown().await;
+        }
+    }
+}
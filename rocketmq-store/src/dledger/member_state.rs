@@ -0,0 +1,485 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt::Display;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use rocketmq_common::utils::file_utils;
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::error;
+use tracing::info;
+
+use crate::dledger::dledger_protocol::AppendRequest;
+use crate::dledger::dledger_protocol::VoteRequest;
+use crate::dledger::dledger_protocol::VoteResponse;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DLedgerRole {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+impl Display for DLedgerRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DLedgerRole::Follower => write!(f, "FOLLOWER"),
+            DLedgerRole::Candidate => write!(f, "CANDIDATE"),
+            DLedgerRole::Leader => write!(f, "LEADER"),
+        }
+    }
+}
+
+/// The part of the raft state that has to survive a restart, so that a member never
+/// votes twice in the same term.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistentState {
+    pub current_term: i64,
+    pub voted_for: Option<String>,
+    /// `(start_offset, term)` of every term that wrote to the commit log, ascending.
+    pub term_starts: Vec<(i64, i64)>,
+}
+
+/// Outcome of checking an append request against the local log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppendCheck {
+    /// The request is from a stale leader.
+    StaleTerm,
+    /// The logs diverge, the leader has to resend from the given offset.
+    Mismatch(i64),
+    /// The data can be appended, after truncating the local log to the given offset if any.
+    Accept(Option<i64>),
+}
+
+/// Raft state of one member of a dledger group.
+///
+/// The replicated log is the commit log itself: the physical offset plays the role of the
+/// log index, and the term of a byte range is looked up in `term_starts`.
+pub struct MemberState {
+    self_id: String,
+    peers: Vec<(String, String)>,
+    role: DLedgerRole,
+    leader_id: Option<String>,
+    state: PersistentState,
+    state_path: String,
+}
+
+impl MemberState {
+    /// Loads the persisted state. A state file which cannot be read or parsed is an error,
+    /// starting from an empty state could grant a second vote in a term.
+    pub fn new(
+        self_id: String,
+        peers: Vec<(String, String)>,
+        state_path: String,
+    ) -> std::io::Result<Self> {
+        let content = file_utils::file_to_string(state_path.as_str())?;
+        let state = if content.is_empty() {
+            PersistentState::default()
+        } else {
+            serde_json::from_str(content.as_str()).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("parse dledger state {} failed: {}", state_path, e),
+                )
+            })?
+        };
+        Ok(Self {
+            self_id,
+            peers,
+            role: DLedgerRole::Follower,
+            leader_id: None,
+            state,
+            state_path,
+        })
+    }
+
+    /// Parses peers in the `n0-127.0.0.1:40911;n1-127.0.0.1:40912` format into
+    /// `(id, address)` pairs.
+    pub fn parse_peers(peers: &str) -> Vec<(String, String)> {
+        peers
+            .split(';')
+            .filter_map(|peer| {
+                let (id, address) = peer.trim().split_once('-')?;
+                Some((id.trim().to_string(), address.trim().to_string()))
+            })
+            .collect()
+    }
+
+    pub fn self_id(&self) -> &str {
+        self.self_id.as_str()
+    }
+
+    pub fn peers(&self) -> &[(String, String)] {
+        &self.peers
+    }
+
+    pub fn self_address(&self) -> Option<&str> {
+        self.peers
+            .iter()
+            .find(|(id, _)| *id == self.self_id)
+            .map(|(_, address)| address.as_str())
+    }
+
+    /// Number of members, including this one, needed to elect a leader or commit data.
+    pub fn quorum(&self) -> usize {
+        self.peers.len() / 2 + 1
+    }
+
+    pub fn role(&self) -> DLedgerRole {
+        self.role
+    }
+
+    pub fn leader_id(&self) -> Option<&str> {
+        self.leader_id.as_deref()
+    }
+
+    pub fn current_term(&self) -> i64 {
+        self.state.current_term
+    }
+
+    pub fn term_starts(&self) -> &[(i64, i64)] {
+        &self.state.term_starts
+    }
+
+    /// Term of the data right before `offset`, 0 for an empty prefix.
+    pub fn term_at(&self, offset: i64) -> i64 {
+        self.state
+            .term_starts
+            .iter()
+            .rev()
+            .find(|(start, _)| *start < offset)
+            .map_or(0, |(_, term)| *term)
+    }
+
+    /// Moves to `term` as a follower, returns whether the role or the term changed. The term
+    /// is only taken over once it is persisted.
+    pub fn step_down(&mut self, term: i64, leader_id: Option<String>) -> bool {
+        let mut changed = self.role != DLedgerRole::Follower;
+        if term > self.state.current_term {
+            let state = PersistentState {
+                current_term: term,
+                voted_for: None,
+                ..self.state.clone()
+            };
+            match self.persist(state) {
+                Ok(()) => changed = true,
+                Err(e) => error!("persist dledger term {} failed: {}", term, e),
+            }
+        }
+        self.role = DLedgerRole::Follower;
+        if leader_id.is_some() || changed {
+            self.leader_id = leader_id;
+        }
+        changed
+    }
+
+    /// Starts an election for the next term and votes for itself, refused when the vote
+    /// cannot be persisted.
+    pub fn become_candidate(&mut self, max_offset: i64) -> std::io::Result<VoteRequest> {
+        self.persist(PersistentState {
+            current_term: self.state.current_term + 1,
+            voted_for: Some(self.self_id.clone()),
+            ..self.state.clone()
+        })?;
+        self.role = DLedgerRole::Candidate;
+        self.leader_id = None;
+        Ok(VoteRequest {
+            term: self.state.current_term,
+            candidate_id: self.self_id.clone(),
+            last_log_term: self.term_at(max_offset),
+            last_log_offset: max_offset,
+        })
+    }
+
+    /// Takes the leadership of the current term, the data written from `max_offset` on
+    /// belongs to this term.
+    pub fn become_leader(&mut self, max_offset: i64) -> std::io::Result<()> {
+        let term = self.state.current_term;
+        let mut state = self.state.clone();
+        state.term_starts.retain(|(start, _)| *start < max_offset);
+        state.term_starts.push((max_offset, term));
+        self.persist(state)?;
+        self.role = DLedgerRole::Leader;
+        self.leader_id = Some(self.self_id.clone());
+        info!(
+            "{} becomes the dledger leader of term {} at offset {}",
+            self.self_id, term, max_offset
+        );
+        Ok(())
+    }
+
+    /// Grants the vote when the candidate's log is at least as up to date as the local one
+    /// and no other candidate got the vote in this term.
+    pub fn handle_vote(&mut self, request: &VoteRequest, max_offset: i64) -> VoteResponse {
+        if request.term > self.state.current_term {
+            self.step_down(request.term, None);
+        }
+        let last_log_term = self.term_at(max_offset);
+        let log_up_to_date = request.last_log_term > last_log_term
+            || (request.last_log_term == last_log_term && request.last_log_offset >= max_offset);
+        let can_vote = self
+            .state
+            .voted_for
+            .as_ref()
+            .map_or(true, |voted_for| *voted_for == request.candidate_id);
+        let mut vote_granted =
+            request.term == self.state.current_term && can_vote && log_up_to_date;
+        if vote_granted && self.state.voted_for.is_none() {
+            let state = PersistentState {
+                voted_for: Some(request.candidate_id.clone()),
+                ..self.state.clone()
+            };
+            if let Err(e) = self.persist(state) {
+                error!(
+                    "persist the vote for {} in term {} failed: {}",
+                    request.candidate_id, request.term, e
+                );
+                vote_granted = false;
+            }
+        }
+        VoteResponse {
+            term: self.state.current_term,
+            vote_granted,
+        }
+    }
+
+    /// Checks whether the data of an append request continues the local log. Only data
+    /// after `commit_offset` may be truncated, everything before is the same on every
+    /// member.
+    pub fn check_append(
+        &mut self,
+        request: &AppendRequest,
+        max_offset: i64,
+        commit_offset: i64,
+    ) -> AppendCheck {
+        if request.term < self.state.current_term {
+            return AppendCheck::StaleTerm;
+        }
+        self.step_down(request.term, Some(request.leader_id.clone()));
+        if request.term != self.state.current_term {
+            // The term of the leader could not be persisted.
+            return AppendCheck::StaleTerm;
+        }
+        if request.start_offset < commit_offset {
+            return AppendCheck::Mismatch(commit_offset);
+        }
+        if request.start_offset > max_offset {
+            return AppendCheck::Mismatch(max_offset);
+        }
+        if self.term_at(request.start_offset) != request.prev_term {
+            return AppendCheck::Mismatch(commit_offset);
+        }
+        if request.start_offset < max_offset {
+            AppendCheck::Accept(Some(request.start_offset))
+        } else {
+            AppendCheck::Accept(None)
+        }
+    }
+
+    /// Takes over the leader's terms for the data before `max_offset`, which now matches
+    /// the leader's log.
+    pub fn accept_term_starts(
+        &mut self,
+        term_starts: &[(i64, i64)],
+        max_offset: i64,
+    ) -> std::io::Result<()> {
+        let term_starts: Vec<(i64, i64)> = term_starts
+            .iter()
+            .filter(|(start, _)| *start < max_offset)
+            .copied()
+            .collect();
+        if term_starts == self.state.term_starts {
+            return Ok(());
+        }
+        self.persist(PersistentState {
+            term_starts,
+            ..self.state.clone()
+        })
+    }
+
+    /// Replaces the state file through a synced temp file and only then takes over `state`,
+    /// so the in-memory state never runs ahead of the persisted one.
+    fn persist(&mut self, state: PersistentState) -> std::io::Result<()> {
+        let content = serde_json::to_vec(&state)?;
+        let state_path = Path::new(self.state_path.as_str());
+        if let Some(dir) = state_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp_path = state_path.with_extension("tmp");
+        let mut tmp_file = File::create(&tmp_path)?;
+        tmp_file.write_all(&content)?;
+        tmp_file.sync_all()?;
+        std::fs::rename(&tmp_path, state_path)?;
+        if let Some(dir) = state_path.parent() {
+            File::open(dir)?.sync_all()?;
+        }
+        self.state = state;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    fn member_state(dir: &tempfile::TempDir, self_id: &str) -> MemberState {
+        MemberState::new(
+            self_id.to_string(),
+            MemberState::parse_peers("n0-127.0.0.1:40911;n1-127.0.0.1:40912;n2-127.0.0.1:40913"),
+            dir.path().join(self_id).to_string_lossy().into_owned(),
+        )
+        .unwrap()
+    }
+
+    fn append_request(term: i64, start_offset: i64, prev_term: i64) -> AppendRequest {
+        AppendRequest {
+            term,
+            leader_id: "n1".to_string(),
+            start_offset,
+            prev_term,
+            commit_offset: 0,
+            term_starts: vec![],
+            data: Bytes::new(),
+        }
+    }
+
+    #[test]
+    fn parse_peers_and_quorum() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = member_state(&dir, "n2");
+        assert_eq!(state.peers().len(), 3);
+        assert_eq!(state.self_address(), Some("127.0.0.1:40913"));
+        assert_eq!(state.quorum(), 2);
+    }
+
+    #[test]
+    fn votes_once_per_term_and_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = member_state(&dir, "n0");
+        let request = VoteRequest {
+            term: 1,
+            candidate_id: "n1".to_string(),
+            last_log_term: 0,
+            last_log_offset: 0,
+        };
+        assert!(state.handle_vote(&request, 0).vote_granted);
+
+        let mut restarted = member_state(&dir, "n0");
+        assert_eq!(restarted.current_term(), 1);
+        let other = VoteRequest {
+            candidate_id: "n2".to_string(),
+            ..request.clone()
+        };
+        assert!(!restarted.handle_vote(&other, 0).vote_granted);
+        assert!(restarted.handle_vote(&request, 0).vote_granted);
+    }
+
+    #[test]
+    fn rejects_candidate_with_stale_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = member_state(&dir, "n0");
+        state.become_candidate(0).unwrap();
+        state.become_leader(0).unwrap();
+        assert_eq!(state.term_at(100), 1);
+
+        let behind = VoteRequest {
+            term: 2,
+            candidate_id: "n1".to_string(),
+            last_log_term: 1,
+            last_log_offset: 50,
+        };
+        let response = state.handle_vote(&behind, 100);
+        assert!(!response.vote_granted);
+        assert_eq!(response.term, 2);
+        assert_eq!(state.role(), DLedgerRole::Follower);
+    }
+
+    #[test]
+    fn append_check_finds_the_divergence() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = member_state(&dir, "n0");
+        state.accept_term_starts(&[(0, 1), (100, 2)], 200).unwrap();
+
+        assert_eq!(
+            state.check_append(&append_request(1, 0, 0), 200, 0),
+            AppendCheck::Accept(Some(0))
+        );
+        let mut state = member_state(&dir, "n0");
+        state.step_down(3, None);
+        assert_eq!(
+            state.check_append(&append_request(2, 200, 2), 200, 0),
+            AppendCheck::StaleTerm
+        );
+        assert_eq!(
+            state.check_append(&append_request(3, 300, 3), 200, 0),
+            AppendCheck::Mismatch(200)
+        );
+        assert_eq!(
+            state.check_append(&append_request(3, 200, 3), 200, 50),
+            AppendCheck::Mismatch(50)
+        );
+        assert_eq!(
+            state.check_append(&append_request(3, 150, 2), 200, 50),
+            AppendCheck::Accept(Some(150))
+        );
+        assert_eq!(
+            state.check_append(&append_request(3, 200, 2), 200, 50),
+            AppendCheck::Accept(None)
+        );
+        assert_eq!(state.leader_id(), Some("n1"));
+    }
+
+    #[test]
+    fn refuses_to_vote_when_the_state_cannot_be_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = member_state(&dir, "n0");
+        // A directory in place of the state file makes every persist fail.
+        std::fs::create_dir(dir.path().join("n0")).unwrap();
+        let request = VoteRequest {
+            term: 1,
+            candidate_id: "n1".to_string(),
+            last_log_term: 0,
+            last_log_offset: 0,
+        };
+        assert!(!state.handle_vote(&request, 0).vote_granted);
+        assert_eq!(state.current_term(), 0);
+        assert!(state.become_candidate(0).is_err());
+        assert_eq!(state.role(), DLedgerRole::Follower);
+        assert_eq!(
+            state.check_append(&append_request(1, 0, 0), 0, 0),
+            AppendCheck::StaleTerm
+        );
+    }
+
+    #[test]
+    fn corrupt_state_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("n0"), "{\"currentTerm\":").unwrap();
+        assert!(MemberState::new(
+            "n0".to_string(),
+            vec![],
+            dir.path().join("n0").to_string_lossy().into_owned(),
+        )
+        .is_err());
+    }
+}
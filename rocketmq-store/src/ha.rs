@@ -0,0 +1,28 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+pub mod default_ha_client;
+pub mod default_ha_connection;
+pub mod default_ha_service;
+pub mod group_transfer_service;
+
+/// Size of the header the master puts in front of every transfer frame:
+/// the physical offset of the data (8 bytes) followed by the body size (4 bytes).
+pub const TRANSFER_HEADER_SIZE: usize = 8 + 4;
+
+/// Size of the report a slave sends back to the master: its max physical offset.
+pub const REPORT_HEADER_SIZE: usize = 8;
@@ -0,0 +1,240 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_rust::ArcMut;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::ha::TRANSFER_HEADER_SIZE;
+use crate::log_file::commit_log::CommitLog;
+
+/// Slave side of the replication: connects to the master HA port, reports the
+/// local max offset and appends whatever commit log data the master pushes.
+#[derive(Clone)]
+pub struct DefaultHAClient {
+    message_store_config: ArcMut<MessageStoreConfig>,
+    commit_log: CommitLog,
+    master_address: Arc<parking_lot::RwLock<Option<CheetahString>>>,
+}
+
+impl DefaultHAClient {
+    pub fn new(message_store_config: ArcMut<MessageStoreConfig>, commit_log: CommitLog) -> Self {
+        Self {
+            message_store_config,
+            commit_log,
+            master_address: Arc::new(parking_lot::RwLock::new(None)),
+        }
+    }
+
+    pub fn update_master_address(&self, new_address: CheetahString) {
+        let mut master_address = self.master_address.write();
+        if master_address.as_ref() != Some(&new_address) {
+            info!(
+                "update master ha address, OLD: {:?} NEW: {}",
+                *master_address, new_address
+            );
+            *master_address = Some(new_address);
+        }
+    }
+
+    pub fn get_master_address(&self) -> Option<CheetahString> {
+        self.master_address.read().clone()
+    }
+
+    /// Keeps (re)connecting to the master for as long as the task is alive.
+    pub async fn run(self) {
+        loop {
+            let Some(master_address) = self.get_master_address() else {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            };
+            match TcpStream::connect(master_address.as_str()).await {
+                Ok(stream) => {
+                    info!("HAClient connect to master {}", master_address);
+                    self.transfer(stream).await;
+                    info!("HAClient close connection with master {}", master_address);
+                }
+                Err(e) => {
+                    warn!(
+                        "HAClient connect to master {} failed: {}",
+                        master_address, e
+                    );
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    }
+
+    async fn transfer(&self, stream: TcpStream) {
+        let (reader, writer) = stream.into_split();
+        let report_notify = Arc::new(Notify::new());
+        // the master sends at most one batch, or one message when it is larger than a batch
+        let max_body_size = self
+            .message_store_config
+            .ha_transfer_batch_size
+            .max(self.message_store_config.max_message_size.max(0) as usize);
+        tokio::select! {
+            _ = dispatch_read_request(
+                reader,
+                self.commit_log.clone(),
+                Duration::from_millis(self.message_store_config.ha_housekeeping_interval as u64),
+                max_body_size,
+                report_notify.clone(),
+            ) => {}
+            _ = report_slave_max_offset(
+                writer,
+                self.commit_log.clone(),
+                Duration::from_millis(self.message_store_config.ha_send_heartbeat_interval as u64),
+                report_notify,
+            ) => {}
+        }
+    }
+}
+
+/// Reads the frames pushed by the master and appends them to the local commit log.
+async fn dispatch_read_request(
+    mut reader: OwnedReadHalf,
+    mut commit_log: CommitLog,
+    housekeeping_interval: Duration,
+    max_body_size: usize,
+    report_notify: Arc<Notify>,
+) {
+    loop {
+        let mut header = [0u8; TRANSFER_HEADER_SIZE];
+        match tokio::time::timeout(housekeeping_interval, reader.read_exact(&mut header)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => {
+                warn!("HAClient read from master failed: {}", e);
+                return;
+            }
+            Err(_) => {
+                warn!(
+                    "HAClient, housekeeping, found this connection expired, {:?}",
+                    housekeeping_interval
+                );
+                return;
+            }
+        }
+        let Some((master_phy_offset, body_size)) = parse_transfer_header(&header, max_body_size)
+        else {
+            error!(
+                "HAClient received an illegal frame header from master, body size {}, max {}",
+                i32::from_be_bytes(header[8..].try_into().unwrap()),
+                max_body_size
+            );
+            return;
+        };
+        let mut body = vec![0u8; body_size];
+        if let Err(e) = reader.read_exact(&mut body).await {
+            warn!("HAClient read body from master failed: {}", e);
+            return;
+        }
+
+        let slave_phy_offset = commit_log.get_max_offset();
+        if slave_phy_offset != 0 && slave_phy_offset != master_phy_offset {
+            error!(
+                "master pushed offset not equal the max phy offset in slave, SLAVE: {} MASTER: {}",
+                slave_phy_offset, master_phy_offset
+            );
+            return;
+        }
+        if body_size > 0 {
+            if !commit_log.append_data(master_phy_offset, &body).await {
+                error!(
+                    "HAClient append data to commit log failed, offset {}",
+                    master_phy_offset
+                );
+                return;
+            }
+            report_notify.notify_one();
+        }
+    }
+}
+
+/// The master offset and the body size of a frame, `None` for a body size the master can not
+/// have sent, reading such a body would allocate whatever a corrupt header says.
+fn parse_transfer_header(
+    header: &[u8; TRANSFER_HEADER_SIZE],
+    max_body_size: usize,
+) -> Option<(i64, usize)> {
+    let master_phy_offset = i64::from_be_bytes(header[..8].try_into().unwrap());
+    let body_size = usize::try_from(i32::from_be_bytes(header[8..].try_into().unwrap())).ok()?;
+    (body_size <= max_body_size).then_some((master_phy_offset, body_size))
+}
+
+/// Reports the local max offset after each append and at least once per heartbeat interval.
+async fn report_slave_max_offset(
+    mut writer: OwnedWriteHalf,
+    commit_log: CommitLog,
+    heartbeat_interval: Duration,
+    report_notify: Arc<Notify>,
+) {
+    let mut current_reported_offset = -1i64;
+    let mut last_write_timestamp = Instant::now();
+    loop {
+        let max_offset = commit_log.get_max_offset();
+        if max_offset > current_reported_offset
+            || last_write_timestamp.elapsed() >= heartbeat_interval
+        {
+            if let Err(e) = writer.write_i64(max_offset).await {
+                warn!("HAClient report slave max offset failed: {}", e);
+                return;
+            }
+            current_reported_offset = max_offset;
+            last_write_timestamp = Instant::now();
+        }
+        let wait = heartbeat_interval.saturating_sub(last_write_timestamp.elapsed());
+        let _ = tokio::time::timeout(wait, report_notify.notified()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(master_phy_offset: i64, body_size: i32) -> [u8; TRANSFER_HEADER_SIZE] {
+        let mut header = [0u8; TRANSFER_HEADER_SIZE];
+        header[..8].copy_from_slice(&master_phy_offset.to_be_bytes());
+        header[8..].copy_from_slice(&body_size.to_be_bytes());
+        header
+    }
+
+    #[test]
+    fn transfer_header_rejects_illegal_body_sizes() {
+        assert_eq!(parse_transfer_header(&header(42, 0), 1024), Some((42, 0)));
+        assert_eq!(
+            parse_transfer_header(&header(42, 1024), 1024),
+            Some((42, 1024))
+        );
+        assert_eq!(parse_transfer_header(&header(42, 1025), 1024), None);
+        assert_eq!(parse_transfer_header(&header(42, -1), 1024), None);
+        assert_eq!(parse_transfer_header(&header(42, i32::MIN), 1024), None);
+    }
+}
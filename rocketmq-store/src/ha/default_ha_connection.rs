@@ -0,0 +1,216 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
+use rocketmq_rust::ArcMut;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::TcpStream;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use tracing::info;
+use tracing::warn;
+
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::ha::group_transfer_service::GroupTransferService;
+use crate::ha::TRANSFER_HEADER_SIZE;
+use crate::log_file::commit_log::CommitLog;
+use crate::log_file::mapped_file::MappedFile;
+
+/// Master side of a replication connection: reads the offsets reported by the
+/// slave and pushes commit log data from where the slave left off.
+pub struct DefaultHAConnection {
+    message_store_config: ArcMut<MessageStoreConfig>,
+    commit_log: CommitLog,
+    group_transfer_service: Arc<GroupTransferService>,
+    wait_notify: Arc<Notify>,
+    client_addr: SocketAddr,
+}
+
+impl DefaultHAConnection {
+    pub fn new(
+        message_store_config: ArcMut<MessageStoreConfig>,
+        commit_log: CommitLog,
+        group_transfer_service: Arc<GroupTransferService>,
+        wait_notify: Arc<Notify>,
+        client_addr: SocketAddr,
+    ) -> Self {
+        Self {
+            message_store_config,
+            commit_log,
+            group_transfer_service,
+            wait_notify,
+            client_addr,
+        }
+    }
+
+    /// Serves the slave until either direction of the connection fails.
+    pub async fn run(self, stream: TcpStream) {
+        let client_addr = self.client_addr;
+        let group_transfer_service = self.group_transfer_service.clone();
+        group_transfer_service.add_slave(client_addr);
+
+        let (reader, writer) = stream.into_split();
+        let (request_offset_tx, request_offset_rx) = tokio::sync::watch::channel(-1i64);
+        let housekeeping_interval =
+            Duration::from_millis(self.message_store_config.ha_housekeeping_interval as u64);
+        // Both directions are polled in place so that aborting the connection task
+        // also tears down the socket.
+        tokio::select! {
+            _ = read_socket_service(
+                reader,
+                client_addr,
+                housekeeping_interval,
+                group_transfer_service.clone(),
+                request_offset_tx,
+            ) => {}
+            _ = self.write_socket_service(writer, request_offset_rx) => {}
+        }
+
+        group_transfer_service.remove_slave(&client_addr);
+        info!("HA connection of slave {} closed", client_addr);
+    }
+
+    async fn write_socket_service(
+        self,
+        mut writer: OwnedWriteHalf,
+        mut request_offset_rx: tokio::sync::watch::Receiver<i64>,
+    ) {
+        let slave_request_offset = match request_offset_rx.wait_for(|offset| *offset != -1).await {
+            Ok(offset) => *offset,
+            Err(_) => return,
+        };
+        let mut next_transfer_from_where = if slave_request_offset == 0 {
+            let master_offset = self.commit_log.get_max_offset();
+            let master_offset = master_offset
+                - (master_offset % self.message_store_config.mapped_file_size_commit_log as i64);
+            master_offset.max(0)
+        } else {
+            slave_request_offset
+        };
+        info!(
+            "master transfer data from {} to slave[{}], and slave request {}",
+            next_transfer_from_where, self.client_addr, slave_request_offset
+        );
+
+        let heartbeat_interval =
+            Duration::from_millis(self.message_store_config.ha_send_heartbeat_interval as u64);
+        let batch_size = self.message_store_config.ha_transfer_batch_size;
+        let mut last_write_timestamp = Instant::now();
+        loop {
+            match self.transfer_data(next_transfer_from_where, batch_size) {
+                Some(body) => {
+                    let size = body.len();
+                    if let Err(e) =
+                        write_frame(&mut writer, next_transfer_from_where, Some(body)).await
+                    {
+                        warn!("HA write to slave {} failed: {}", self.client_addr, e);
+                        return;
+                    }
+                    next_transfer_from_where += size as i64;
+                    last_write_timestamp = Instant::now();
+                }
+                None => {
+                    if last_write_timestamp.elapsed() > heartbeat_interval {
+                        if let Err(e) =
+                            write_frame(&mut writer, next_transfer_from_where, None).await
+                        {
+                            warn!("HA heartbeat to slave {} failed: {}", self.client_addr, e);
+                            return;
+                        }
+                        last_write_timestamp = Instant::now();
+                    }
+                    let _ = tokio::time::timeout(
+                        Duration::from_millis(100),
+                        self.wait_notify.notified(),
+                    )
+                    .await;
+                }
+            }
+        }
+    }
+
+    /// Copies at most `batch_size` bytes of commit log data starting at `offset`.
+    fn transfer_data(&self, offset: i64, batch_size: usize) -> Option<Bytes> {
+        let result = self.commit_log.get_data(offset)?;
+        let mapped_file = result.mapped_file.as_ref()?;
+        let pos = (result.start_offset - mapped_file.get_file_from_offset()) as usize;
+        let size = (result.size as usize).min(batch_size);
+        let data = Bytes::copy_from_slice(&mapped_file.get_mapped_file()[pos..pos + size]);
+        mapped_file.release();
+        Some(data)
+    }
+}
+
+async fn read_socket_service(
+    mut reader: OwnedReadHalf,
+    client_addr: SocketAddr,
+    housekeeping_interval: Duration,
+    group_transfer_service: Arc<GroupTransferService>,
+    request_offset_tx: tokio::sync::watch::Sender<i64>,
+) {
+    loop {
+        let slave_ack_offset =
+            match tokio::time::timeout(housekeeping_interval, reader.read_i64()).await {
+                Ok(Ok(offset)) => offset,
+                Ok(Err(e)) => {
+                    info!("HA read from slave {} closed: {}", client_addr, e);
+                    return;
+                }
+                Err(_) => {
+                    warn!(
+                        "HA housekeeping, found this connection[{}] expired, {:?}",
+                        client_addr, housekeeping_interval
+                    );
+                    return;
+                }
+            };
+        request_offset_tx.send_if_modified(|request_offset| {
+            if *request_offset == -1 {
+                *request_offset = slave_ack_offset;
+                info!("slave[{}] request offset {}", client_addr, slave_ack_offset);
+                true
+            } else {
+                false
+            }
+        });
+        group_transfer_service.notify_transfer_some(client_addr, slave_ack_offset);
+    }
+}
+
+async fn write_frame(
+    writer: &mut OwnedWriteHalf,
+    phy_offset: i64,
+    body: Option<Bytes>,
+) -> std::io::Result<()> {
+    let body_size = body.as_ref().map_or(0, |body| body.len());
+    let mut frame = BytesMut::with_capacity(TRANSFER_HEADER_SIZE + body_size);
+    frame.put_i64(phy_offset);
+    frame.put_i32(body_size as i32);
+    if let Some(body) = body {
+        frame.put(body);
+    }
+    writer.write_all(&frame).await
+}
@@ -0,0 +1,198 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_rust::ArcMut;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tracing::error;
+use tracing::info;
+
+use crate::base::message_status_enum::PutMessageStatus;
+use crate::config::broker_role::BrokerRole;
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::ha::default_ha_client::DefaultHAClient;
+use crate::ha::default_ha_connection::DefaultHAConnection;
+use crate::ha::group_transfer_service::GroupTransferService;
+use crate::log_file::commit_log::CommitLog;
+
+/// Master/slave replication of the commit log.
+///
+/// A master accepts slave connections on `ha_listen_port` and pushes commit log
+/// data to them, a slave connects to `ha_master_address` and appends what it
+/// receives.
+pub struct DefaultHAService {
+    message_store_config: ArcMut<MessageStoreConfig>,
+    commit_log: CommitLog,
+    group_transfer_service: Arc<GroupTransferService>,
+    wait_notify: Arc<Notify>,
+    ha_client: DefaultHAClient,
+    tasks: Arc<parking_lot::Mutex<Vec<JoinHandle<()>>>>,
+    accepting: AtomicBool,
+}
+
+impl DefaultHAService {
+    pub fn new(message_store_config: ArcMut<MessageStoreConfig>, commit_log: CommitLog) -> Self {
+        let ha_client = DefaultHAClient::new(message_store_config.clone(), commit_log.clone());
+        if let Some(ref master_address) = message_store_config.ha_master_address {
+            ha_client.update_master_address(CheetahString::from_string(master_address.clone()));
+        }
+        Self {
+            message_store_config,
+            commit_log,
+            group_transfer_service: Arc::new(GroupTransferService::new()),
+            wait_notify: Arc::new(Notify::new()),
+            ha_client,
+            tasks: Arc::new(parking_lot::Mutex::new(Vec::new())),
+            accepting: AtomicBool::new(false),
+        }
+    }
+
+    /// Binds the HA port on the master, or starts the HA client on a slave.
+    pub fn start(&self) -> std::io::Result<()> {
+        if self.message_store_config.broker_role == BrokerRole::Slave {
+            self.start_client();
+            return Ok(());
+        }
+        self.start_acceptor()
+    }
+
+    /// Stops replicating from a master and starts accepting slave connections instead.
+    /// Does nothing when the slave connections are already accepted.
+    pub fn change_to_master(&self) -> std::io::Result<()> {
+        if self.accepting.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        self.shutdown();
+        self.start_acceptor()
+    }
+
+    /// Drops the slave connections and starts replicating from `master_address`.
+    pub fn change_to_slave(&self, master_address: CheetahString) {
+        self.shutdown();
+        self.ha_client.update_master_address(master_address);
+        self.start_client();
+    }
+
+    fn start_client(&self) {
+        self.accepting.store(false, Ordering::Release);
+        let ha_client = self.ha_client.clone();
+        self.tasks.lock().push(tokio::spawn(ha_client.run()));
+    }
+
+    fn start_acceptor(&self) -> std::io::Result<()> {
+        let listener = std::net::TcpListener::bind((
+            "0.0.0.0",
+            self.message_store_config.ha_listen_port as u16,
+        ))?;
+        listener.set_nonblocking(true)?;
+        let listener = TcpListener::from_std(listener)?;
+        self.accepting.store(true, Ordering::Release);
+        info!(
+            "HAService listen on port {}",
+            self.message_store_config.ha_listen_port
+        );
+
+        let message_store_config = self.message_store_config.clone();
+        let commit_log = self.commit_log.clone();
+        let group_transfer_service = self.group_transfer_service.clone();
+        let wait_notify = self.wait_notify.clone();
+        let tasks = self.tasks.clone();
+        self.tasks.lock().push(tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, client_addr)) => {
+                        info!("HAService receive new connection, {}", client_addr);
+                        let connection = DefaultHAConnection::new(
+                            message_store_config.clone(),
+                            commit_log.clone(),
+                            group_transfer_service.clone(),
+                            wait_notify.clone(),
+                            client_addr,
+                        );
+                        let mut tasks = tasks.lock();
+                        tasks.retain(|task| !task.is_finished());
+                        tasks.push(tokio::spawn(connection.run(stream)));
+                    }
+                    Err(e) => {
+                        error!("HAService accept connection failed: {}", e);
+                    }
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    pub fn shutdown(&self) {
+        for task in self.tasks.lock().drain(..) {
+            task.abort();
+        }
+        self.group_transfer_service.remove_all_slaves();
+        self.accepting.store(false, Ordering::Release);
+    }
+
+    pub fn update_master_address(&self, new_address: CheetahString) {
+        self.ha_client.update_master_address(new_address);
+    }
+
+    pub fn get_master_address(&self) -> Option<CheetahString> {
+        self.ha_client.get_master_address()
+    }
+
+    pub fn get_connection_count(&self) -> usize {
+        self.group_transfer_service.connection_count()
+    }
+
+    pub fn get_push2_slave_max_offset(&self) -> i64 {
+        self.group_transfer_service.push2_slave_max_offset()
+    }
+
+    pub fn is_slave_ok(&self, master_put_where: i64) -> bool {
+        self.group_transfer_service.is_slave_ok(
+            master_put_where,
+            self.message_store_config.ha_max_gap_not_in_sync as i64,
+        )
+    }
+
+    /// Wakes up the connections waiting for new commit log data.
+    pub fn wakeup_all(&self) {
+        self.wait_notify.notify_waiters();
+    }
+
+    /// Blocks a `SYNC_MASTER` producer until its message reached enough slaves or
+    /// `sync_flush_timeout` elapsed.
+    pub async fn wait_for_transfer(
+        &self,
+        next_offset: i64,
+        need_ack_nums: u32,
+    ) -> PutMessageStatus {
+        self.wakeup_all();
+        self.group_transfer_service
+            .wait_for_transfer(
+                next_offset,
+                need_ack_nums,
+                Duration::from_millis(self.message_store_config.sync_flush_timeout),
+            )
+            .await
+    }
+}
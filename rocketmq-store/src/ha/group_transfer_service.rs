@@ -0,0 +1,195 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tracing::warn;
+
+use crate::base::message_status_enum::PutMessageStatus;
+
+/// Tracks how far every connected slave has caught up and lets producers in
+/// `SYNC_MASTER` mode wait until their message has been acknowledged by enough
+/// replicas.
+pub struct GroupTransferService {
+    push2_slave_max_offset: AtomicI64,
+    slave_ack_offsets: parking_lot::Mutex<HashMap<SocketAddr, i64>>,
+    notify_transfer: watch::Sender<()>,
+}
+
+impl Default for GroupTransferService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GroupTransferService {
+    pub fn new() -> Self {
+        let (notify_transfer, _) = watch::channel(());
+        Self {
+            push2_slave_max_offset: AtomicI64::new(0),
+            slave_ack_offsets: parking_lot::Mutex::new(HashMap::new()),
+            notify_transfer,
+        }
+    }
+
+    /// Registers a newly accepted slave connection that has not acknowledged anything yet.
+    pub fn add_slave(&self, slave_addr: SocketAddr) {
+        self.slave_ack_offsets.lock().insert(slave_addr, -1);
+    }
+
+    pub fn remove_slave(&self, slave_addr: &SocketAddr) {
+        self.slave_ack_offsets.lock().remove(slave_addr);
+    }
+
+    /// Forgets every slave, used when the master steps down and drops its connections.
+    pub fn remove_all_slaves(&self) {
+        self.slave_ack_offsets.lock().clear();
+    }
+
+    pub fn connection_count(&self) -> usize {
+        self.slave_ack_offsets.lock().len()
+    }
+
+    pub fn push2_slave_max_offset(&self) -> i64 {
+        self.push2_slave_max_offset.load(Ordering::Acquire)
+    }
+
+    /// Records the offset acknowledged by `slave_addr` and wakes up waiting producers.
+    pub fn notify_transfer_some(&self, slave_addr: SocketAddr, slave_ack_offset: i64) {
+        self.slave_ack_offsets
+            .lock()
+            .insert(slave_addr, slave_ack_offset);
+        self.push2_slave_max_offset
+            .fetch_max(slave_ack_offset, Ordering::AcqRel);
+        self.notify_transfer.send_modify(|_| {});
+    }
+
+    /// A slave is considered available when at least one is connected and the
+    /// furthest one is not lagging more than `ha_max_gap_not_in_sync` bytes behind.
+    pub fn is_slave_ok(&self, master_put_where: i64, ha_max_gap_not_in_sync: i64) -> bool {
+        self.connection_count() > 0
+            && master_put_where - self.push2_slave_max_offset() < ha_max_gap_not_in_sync
+    }
+
+    /// Waits until `need_ack_nums` replicas (the master included) hold data up to
+    /// `next_offset`, returning `FlushSlaveTimeout` if that does not happen in time.
+    pub async fn wait_for_transfer(
+        &self,
+        next_offset: i64,
+        need_ack_nums: u32,
+        timeout: Duration,
+    ) -> PutMessageStatus {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut notified = self.notify_transfer.subscribe();
+        loop {
+            if self.is_transfer_ok(next_offset, need_ack_nums) {
+                return PutMessageStatus::PutOk;
+            }
+            match tokio::time::timeout_at(deadline, notified.changed()).await {
+                Ok(Ok(_)) => {}
+                _ => break,
+            }
+        }
+        if self.is_transfer_ok(next_offset, need_ack_nums) {
+            return PutMessageStatus::PutOk;
+        }
+        warn!(
+            "transfer message to slave timeout, offset : {}, request acks: {}",
+            next_offset, need_ack_nums
+        );
+        PutMessageStatus::FlushSlaveTimeout
+    }
+
+    fn is_transfer_ok(&self, next_offset: i64, need_ack_nums: u32) -> bool {
+        if need_ack_nums <= 2 {
+            return self.push2_slave_max_offset() >= next_offset;
+        }
+        // Include master
+        let ack_nums = 1 + self
+            .slave_ack_offsets
+            .lock()
+            .values()
+            .filter(|ack_offset| **ack_offset >= next_offset)
+            .count();
+        ack_nums >= need_ack_nums as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn wait_for_transfer_completes_once_slave_acks() {
+        let service = Arc::new(GroupTransferService::new());
+        let slave: SocketAddr = "127.0.0.1:20000".parse().unwrap();
+        service.add_slave(slave);
+        assert!(service.is_slave_ok(100, 1024));
+
+        let waiter = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                service
+                    .wait_for_transfer(100, 2, Duration::from_secs(3))
+                    .await
+            })
+        };
+        service.notify_transfer_some(slave, 50);
+        service.notify_transfer_some(slave, 100);
+        assert_eq!(waiter.await.unwrap(), PutMessageStatus::PutOk);
+        assert_eq!(service.push2_slave_max_offset(), 100);
+    }
+
+    #[tokio::test]
+    async fn wait_for_transfer_times_out_without_enough_acks() {
+        let service = GroupTransferService::new();
+        let first: SocketAddr = "127.0.0.1:20000".parse().unwrap();
+        let second: SocketAddr = "127.0.0.1:20001".parse().unwrap();
+        service.add_slave(first);
+        service.add_slave(second);
+        service.notify_transfer_some(first, 100);
+
+        let status = service
+            .wait_for_transfer(100, 3, Duration::from_millis(50))
+            .await;
+        assert_eq!(status, PutMessageStatus::FlushSlaveTimeout);
+
+        service.notify_transfer_some(second, 100);
+        let status = service
+            .wait_for_transfer(100, 3, Duration::from_millis(50))
+            .await;
+        assert_eq!(status, PutMessageStatus::PutOk);
+    }
+
+    #[test]
+    fn slave_is_not_ok_without_connections_or_with_large_gap() {
+        let service = GroupTransferService::new();
+        assert!(!service.is_slave_ok(0, 1024));
+        let slave: SocketAddr = "127.0.0.1:20000".parse().unwrap();
+        service.add_slave(slave);
+        assert!(!service.is_slave_ok(4096, 1024));
+        service.remove_slave(&slave);
+        assert_eq!(service.connection_count(), 0);
+    }
+}
@@ -15,3 +15,4 @@
  * limitations under the License.
  */
 pub mod put_message_hook;
+pub mod send_message_back_hook;
@@ -0,0 +1,47 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use rocketmq_common::common::message::message_ext::MessageExt;
+
+/// Trait for hook that sends messages back to another broker, for example to the master once a
+/// slave acting as master steps down.
+pub trait SendMessageBackHook {
+    /// Returns the name of the hook.
+    fn hook_name(&self) -> String;
+
+    /// Sends the messages to the given broker.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg_list` - The messages to send back
+    /// * `broker_name` - The name of the target broker
+    /// * `broker_addr` - The address of the target broker
+    ///
+    /// # Returns
+    ///
+    /// `true` if all messages were sent back
+    fn execute_send_message_back(
+        &self,
+        msg_list: &[MessageExt],
+        broker_name: &str,
+        broker_addr: &str,
+    ) -> bool;
+}
+
+/// Alias for `Arc<dyn SendMessageBackHook>`.
+pub type ArcSendMessageBackHook = Arc<dyn SendMessageBackHook + Send + Sync + 'static>;
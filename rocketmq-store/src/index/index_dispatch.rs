@@ -15,7 +15,7 @@
  * limitations under the License.
  */
 
-use std::sync::Arc;
+use rocketmq_rust::ArcMut;
 
 use crate::base::commit_log_dispatcher::CommitLogDispatcher;
 use crate::base::dispatch_request::DispatchRequest;
@@ -25,11 +25,14 @@ use crate::index::index_service::IndexService;
 #[derive(Clone)]
 pub struct CommitLogDispatcherBuildIndex {
     index_service: IndexService,
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
 }
 
 impl CommitLogDispatcherBuildIndex {
-    pub fn new(index_service: IndexService, message_store_config: Arc<MessageStoreConfig>) -> Self {
+    pub fn new(
+        index_service: IndexService,
+        message_store_config: ArcMut<MessageStoreConfig>,
+    ) -> Self {
         Self {
             index_service,
             message_store_config,
@@ -38,7 +41,7 @@ impl CommitLogDispatcherBuildIndex {
 }
 
 impl CommitLogDispatcher for CommitLogDispatcherBuildIndex {
-    fn dispatch(&self, dispatch_request: &DispatchRequest) {
+    fn dispatch(&self, dispatch_request: &mut DispatchRequest) {
         if self.message_store_config.message_index_enable {
             self.index_service.build_index(dispatch_request);
         }
@@ -26,6 +26,7 @@ use rocketmq_common::common::message::MessageConst;
 use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_common::UtilAll::time_millis_to_human_string;
+use rocketmq_rust::ArcMut;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
@@ -45,13 +46,13 @@ pub struct IndexService {
     index_num: u32,
     store_path: String,
     index_file_list: Arc<RwLock<Vec<Arc<IndexFile>>>>,
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     store_checkpoint: Arc<StoreCheckpoint>,
 }
 
 impl IndexService {
     pub fn new(
-        message_store_config: Arc<MessageStoreConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
         store_checkpoint: Arc<StoreCheckpoint>,
     ) -> Self {
         Self {
@@ -15,16 +15,19 @@
  * limitations under the License.
  */
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::error::Error;
 use std::sync::Arc;
 
 use cheetah_string::CheetahString;
 use parking_lot::RwLock;
+use rocketmq_common::common::boundary_type::BoundaryType;
 use rocketmq_common::common::message::message_batch::MessageExtBatch;
 use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
 use rocketmq_common::TimeUtils::get_current_millis;
 
+use crate::base::commit_log_dispatcher::CommitLogDispatcher;
 use crate::base::dispatch_request::DispatchRequest;
 use crate::base::get_message_result::GetMessageResult;
 use crate::base::message_result::PutMessageResult;
@@ -32,6 +35,7 @@ use crate::base::query_message_result::QueryMessageResult;
 use crate::base::select_result::SelectMappedBufferResult;
 use crate::filter::MessageFilter;
 use crate::hook::put_message_hook::BoxedPutMessageHook;
+use crate::hook::send_message_back_hook::ArcSendMessageBackHook;
 use crate::queue::ArcConsumeQueue;
 use crate::stats::broker_stats_manager::BrokerStatsManager;
 use crate::store::running_flags::RunningFlags;
@@ -61,7 +65,7 @@ pub trait RocketMQMessageStore: Sync + 'static {
     fn start(&mut self) -> Result<(), Box<dyn Error>>;
 
     /// Shutdown the message store.
-    fn shutdown(&mut self);
+    async fn shutdown(&mut self);
 
     /// Set the confirm offset.
     ///
@@ -143,6 +147,15 @@ pub trait RocketMQMessageStore: Sync + 'static {
         false
     }
 
+    /// Check if the transient store pool has run out of buffers.
+    ///
+    /// # Returns
+    ///
+    /// `true` if no transient store buffer is left; `false` otherwise.
+    fn is_transient_store_pool_deficient(&self) -> bool {
+        self.remain_transient_store_buffer_nums() == 0
+    }
+
     /// Get the running flags of the message store.
     ///
     /// # Returns
@@ -171,6 +184,23 @@ pub trait RocketMQMessageStore: Sync + 'static {
     /// * `put_message_hook` - The hook to set.
     fn set_put_message_hook(&self, put_message_hook: BoxedPutMessageHook);
 
+    /// Set the hook used to send messages back to another broker.
+    ///
+    /// # Arguments
+    ///
+    /// * `send_message_back_hook` - The hook to set.
+    fn set_send_message_back_hook(&self, send_message_back_hook: ArcSendMessageBackHook);
+
+    /// Get the hook used to send messages back to another broker, if one is set.
+    fn get_send_message_back_hook(&self) -> Option<ArcSendMessageBackHook>;
+
+    /// Add a commit log dispatcher that runs before the consume queue and index dispatchers.
+    ///
+    /// # Arguments
+    ///
+    /// * `dispatcher` - The dispatcher to add.
+    fn add_first_dispatcher(&self, dispatcher: Box<dyn CommitLogDispatcher>);
+
     /// Get the broker statistics manager.
     ///
     /// # Returns
@@ -223,6 +253,47 @@ pub trait RocketMQMessageStore: Sync + 'static {
         committed: bool,
     ) -> i64;
 
+    /// Look up the consume queue offset of the first message stored at or after the given
+    /// timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic name.
+    /// * `queue_id` - The queue identifier.
+    /// * `timestamp` - The timestamp in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// The offset in the queue, clamped to `[min offset, max offset]`.
+    fn get_offset_in_queue_by_time(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        timestamp: i64,
+    ) -> i64;
+
+    /// Look up the consume queue offset by timestamp. With [`BoundaryType::Lower`] the first
+    /// message stored at or after `timestamp` is returned, with [`BoundaryType::Upper`] the last
+    /// message stored at or before it.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic name.
+    /// * `queue_id` - The queue identifier.
+    /// * `timestamp` - The timestamp in milliseconds.
+    /// * `boundary_type` - Which side of `timestamp` to search.
+    ///
+    /// # Returns
+    ///
+    /// The offset in the queue, clamped to `[min offset, max offset]`.
+    fn get_offset_in_queue_by_time_with_boundary(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        timestamp: i64,
+        boundary_type: BoundaryType,
+    ) -> i64;
+
     /// Get a message asynchronously.
     ///
     /// # Arguments
@@ -299,6 +370,18 @@ pub trait RocketMQMessageStore: Sync + 'static {
     /// The number of topics deleted.
     fn delete_topics(&mut self, delete_topics: Vec<&CheetahString>) -> i32;
 
+    /// Destroy the consume queues of every topic that is not in `retain_topics`. System topics
+    /// and LMQ topics are always kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `retain_topics` - The topics that still exist on the broker.
+    ///
+    /// # Returns
+    ///
+    /// The number of topics deleted.
+    fn clean_unused_topic(&mut self, retain_topics: &HashSet<CheetahString>) -> i32;
+
     /// Query messages asynchronously.
     ///
     /// # Arguments
@@ -418,6 +501,13 @@ pub trait RocketMQMessageStore: Sync + 'static {
     /// * `i64` - Timestamp of the earliest message in this store.
     fn get_earliest_message_time(&self) -> i64;
 
+    /// Get the store time of the earliest message still available in the queue.
+    ///
+    /// # Returns
+    ///
+    /// * `i64` - Timestamp of the earliest message, or -1 if the queue is empty.
+    fn get_earliest_message_time_in_queue(&self, topic: &CheetahString, queue_id: i32) -> i64;
+
     /// Get the store time of the earliest message in this store.
     fn get_timer_message_store(&self) -> Arc<TimerMessageStore>;
 
@@ -18,6 +18,7 @@
 #![allow(clippy::missing_const_for_thread_local)]
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::mem;
 use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
@@ -47,11 +48,14 @@ use rocketmq_common::MessageDecoder::SYSFLAG_POSITION;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_common::UtilAll::time_millis_to_human_string;
 use rocketmq_rust::ArcMut;
+use tokio::sync::oneshot;
+use tokio::sync::OwnedMutexGuard;
 use tokio::time::Instant;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::base::allocate_mapped_file_service::AllocateMappedFileService;
 use crate::base::append_message_callback::DefaultAppendMessageCallback;
 use crate::base::commit_log_dispatcher::CommitLogDispatcher;
 use crate::base::dispatch_request::DispatchRequest;
@@ -68,6 +72,8 @@ use crate::base::topic_queue_lock::TopicQueueLock;
 use crate::config::broker_role::BrokerRole;
 use crate::config::message_store_config::MessageStoreConfig;
 use crate::consume_queue::mapped_file_queue::MappedFileQueue;
+use crate::dledger::dledger_server::DLedgerServer;
+use crate::ha::default_ha_service::DefaultHAService;
 use crate::log_file::cold_data_check_service::ColdDataCheckService;
 use crate::log_file::flush_manager_impl::defalut_flush_manager::DefaultFlushManager;
 use crate::log_file::mapped_file::default_mapped_file_impl::DefaultMappedFile;
@@ -102,11 +108,11 @@ thread_local! {
 
 fn encode_message_ext(
     message_ext: &MessageExtBrokerInner,
-    message_store_config: &Arc<MessageStoreConfig>,
+    message_store_config: &ArcMut<MessageStoreConfig>,
 ) -> (Option<PutMessageResult>, ArcMut<BytesMut>) {
     PUT_MESSAGE_THREAD_LOCAL.with(|thread_local| {
         if thread_local.encoder.borrow().is_none() {
-            let encoder = MessageExtEncoder::new(Arc::clone(message_store_config));
+            let encoder = MessageExtEncoder::new(message_store_config.clone());
             //*thread_local.encoder.borrow_mut() = Some(encoder);
             thread_local.encoder.replace(Some(encoder));
         }
@@ -121,11 +127,11 @@ fn encode_message_ext(
 fn encode_message_ext_batch(
     message_ext_batch: &MessageExtBatch,
     put_message_context: &mut PutMessageContext,
-    message_store_config: &Arc<MessageStoreConfig>,
+    message_store_config: &ArcMut<MessageStoreConfig>,
 ) -> Option<BytesMut> {
     PUT_MESSAGE_THREAD_LOCAL.with(|thread_local| {
         if thread_local.encoder.borrow().is_none() {
-            let encoder = MessageExtEncoder::new(Arc::clone(message_store_config));
+            let encoder = MessageExtEncoder::new(message_store_config.clone());
             //*thread_local.encoder.borrow_mut() = Some(encoder);
             thread_local.encoder.replace(Some(encoder));
         }
@@ -181,11 +187,22 @@ pub fn get_message_num(
     message_num
 }
 
+/// A message encoded by its producer and waiting for the holder of the append lock to write it.
+///
+/// It owns the lock of its topic queue, so the queue offset it was assigned is not handed out
+/// again before the appender increases the offset, even if the producer stops waiting.
+struct PendingAppend {
+    msg: MessageExtBrokerInner,
+    put_message_context: PutMessageContext,
+    topic_queue_lock: OwnedMutexGuard<()>,
+    tx: oneshot::Sender<(PutMessageResult, MessageExtBrokerInner)>,
+}
+
 #[derive(Clone)]
 pub struct CommitLog {
     mapped_file_queue: MappedFileQueue,
-    message_store_config: Arc<MessageStoreConfig>,
-    broker_config: Arc<BrokerConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     enabled_append_prop_crc: bool,
     //local_file_message_store: Option<Weak<Mutex<LocalFileMessageStore>>>,
     dispatcher: CommitLogDispatcherDefault,
@@ -199,22 +216,38 @@ pub struct CommitLog {
     flush_manager: Arc<tokio::sync::Mutex<DefaultFlushManager>>,
     //flush_manager: Arc<parking_lot::Mutex<DefaultFlushManager>>,
     begin_time_in_lock: Arc<AtomicU64>,
+    pending_appends: Arc<parking_lot::Mutex<VecDeque<PendingAppend>>>,
     cold_data_check_service: Arc<ColdDataCheckService>,
+    ha_service: Option<Arc<DefaultHAService>>,
+    dledger_server: Option<Arc<DLedgerServer>>,
 }
 
 impl CommitLog {
     pub fn new(
-        message_store_config: Arc<MessageStoreConfig>,
-        broker_config: Arc<BrokerConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         dispatcher: &CommitLogDispatcherDefault,
         store_checkpoint: Arc<StoreCheckpoint>,
         topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
         consume_queue_store: ConsumeQueueStore,
+        allocate_mapped_file_service: Arc<AllocateMappedFileService>,
     ) -> Self {
         let enabled_append_prop_crc = message_store_config.enabled_append_prop_crc;
-        let store_path = message_store_config.get_store_path_commit_log();
+        let store_path = if message_store_config.enable_dledger_commit_log {
+            message_store_config.get_store_path_dledger_commit_log()
+        } else {
+            message_store_config.get_store_path_commit_log()
+        };
         let mapped_file_size = message_store_config.mapped_file_size_commit_log;
-        let mapped_file_queue = MappedFileQueue::new(store_path, mapped_file_size as u64, None);
+        let mut mapped_file_queue = MappedFileQueue::new(
+            store_path,
+            mapped_file_size as u64,
+            Some(allocate_mapped_file_service),
+        );
+        mapped_file_queue.read_only_store_path = message_store_config
+            .read_only_commit_log_store_paths
+            .as_ref()
+            .map(|paths| paths.to_string());
         Self {
             mapped_file_queue: mapped_file_queue.clone(),
             message_store_config: message_store_config.clone(),
@@ -240,7 +273,10 @@ impl CommitLog {
                 store_checkpoint,
             ))),
             begin_time_in_lock: Arc::new(AtomicU64::new(0)),
+            pending_appends: Arc::new(Default::default()),
             cold_data_check_service: Arc::new(Default::default()),
+            ha_service: None,
+            dledger_server: None,
         }
     }
 }
@@ -266,7 +302,51 @@ impl CommitLog {
         });
     }
 
-    pub fn shutdown(&mut self) {}
+    /// Writes out everything appended so far, so the next start needs no abnormal recovery.
+    pub async fn shutdown(&mut self) {
+        // waits for an in-flight disk flush to release the flush manager
+        self.flush_manager.lock().await.shutdown();
+        self.mapped_file_queue.commit(0);
+        self.mapped_file_queue.flush(0);
+        info!(
+            "commit log shutdown, flushed to {}",
+            self.mapped_file_queue.get_flushed_where()
+        );
+    }
+
+    pub fn set_ha_service(&mut self, ha_service: Option<Arc<DefaultHAService>>) {
+        self.ha_service = ha_service;
+    }
+
+    pub fn set_dledger_server(&mut self, dledger_server: Option<Arc<DLedgerServer>>) {
+        self.dledger_server = dledger_server;
+    }
+
+    /// Drops the data after `offset`, used by a dledger follower to discard what an old
+    /// leader did not get committed.
+    pub async fn truncate_dirty_files(&mut self, offset: i64) {
+        let _lock = self.put_message_lock.lock().await;
+        if self.mapped_file_queue.get_flushed_where() > offset {
+            self.mapped_file_queue.set_flushed_where(offset);
+        }
+        if self.mapped_file_queue.get_committed_where() > offset {
+            self.mapped_file_queue.set_committed_where(offset);
+        }
+        self.mapped_file_queue.truncate_dirty_files(offset);
+    }
+
+    /// Appends data replicated from the master at `start_offset`.
+    pub async fn append_data(&mut self, start_offset: i64, data: &[u8]) -> bool {
+        let _lock = self.put_message_lock.lock().await;
+        let Some(mapped_file) = self
+            .mapped_file_queue
+            .get_last_mapped_file_mut_start_offset(start_offset as u64, true)
+        else {
+            error!("appendData getLastMappedFile error  {}", start_offset);
+            return false;
+        };
+        mapped_file.append_message_offset_length(&Bytes::copy_from_slice(data), 0, data.len())
+    }
 
     pub fn destroy(&mut self) {}
 
@@ -291,11 +371,17 @@ impl CommitLog {
 
     pub fn set_confirm_offset(&mut self, phy_offset: i64) {
         self.confirm_offset = phy_offset;
-        self.store_checkpoint
-            .set_confirm_phy_offset(phy_offset as u64);
+        // with dledger the checkpoint keeps the offset committed by the group
+        if self.dledger_server.is_none() {
+            self.store_checkpoint
+                .set_confirm_phy_offset(phy_offset as u64);
+        }
     }
 
     pub async fn put_messages(&mut self, mut msg_batch: MessageExtBatch) -> PutMessageResult {
+        if !self.is_dledger_leader() {
+            return PutMessageResult::new_default(PutMessageStatus::ServiceNotAvailable);
+        }
         msg_batch
             .message_ext_broker_inner
             .message_ext_inner
@@ -485,6 +571,9 @@ impl CommitLog {
     }
 
     pub async fn put_message(&mut self, mut msg: MessageExtBrokerInner) -> PutMessageResult {
+        if !self.is_dledger_leader() {
+            return PutMessageResult::new_default(PutMessageStatus::ServiceNotAvailable);
+        }
         // Set the storage time
         if !self.message_store_config.duplication_enable {
             msg.message_ext_inner.store_timestamp = time_utils::get_current_millis() as i64;
@@ -525,16 +614,6 @@ impl CommitLog {
 
         let topic_queue_key = generate_key(&msg);
 
-        let mut _unlock_mapped_file = None;
-
-        //get last mapped file from mapped file queue
-        let mut mapped_file = self.mapped_file_queue.get_last_mapped_file();
-        // current offset is physical offset
-        let curr_offset = if let Some(ref mapped_file_inner) = mapped_file {
-            mapped_file_inner.get_wrote_position() as u64 + mapped_file_inner.get_file_from_offset()
-        } else {
-            0
-        };
         let need_ack_nums = self.message_store_config.in_sync_replicas;
         let need_handle_ha = self.need_handle_ha(&msg);
         if need_handle_ha && self.broker_config.enable_controller_mode {
@@ -549,7 +628,8 @@ impl CommitLog {
         let topic_queue_lock = self
             .topic_queue_lock
             .lock(topic_queue_key.as_str())
-            .lock()
+            .clone()
+            .lock_owned()
             .await;
         if need_assign_offset {
             self.assign_offset(&mut msg);
@@ -560,56 +640,120 @@ impl CommitLog {
         if let Some(result) = put_message_result {
             return result;
         }
-        msg.encoded_buff = Some(encoded_buff);
+        // The encoder buffer is reused by the next message encoded on this thread, detach the
+        // encoded bytes since the append may happen after other messages were encoded.
+        msg.encoded_buff = Some(ArcMut::new(encoded_buff.mut_from_ref().split()));
         let put_message_context = PutMessageContext::new(topic_queue_key);
-        let lock = self.put_message_lock.lock().await;
+
+        // Group commit: queue the encoded message, whoever gets the append lock first appends
+        // everything queued so far, so concurrent producers share one lock acquisition. The
+        // append runs in its own task, once queued the message is appended and its queue offset
+        // increased even if this future is dropped.
+        let (tx, rx) = oneshot::channel();
+        self.pending_appends.lock().push_back(PendingAppend {
+            msg,
+            put_message_context,
+            topic_queue_lock,
+            tx,
+        });
+        let mut appender = self.clone();
+        tokio::spawn(async move {
+            let put_message_lock = appender.put_message_lock.clone();
+            let _lock = put_message_lock.lock().await;
+            appender.append_pending_messages();
+        });
+        let Ok((put_message_result, msg)) = rx.await else {
+            return PutMessageResult::new_default(PutMessageStatus::UnknownError);
+        };
+
+        if put_message_result.put_message_status() == PutMessageStatus::PutOk {
+            self.handle_disk_flush_and_ha(put_message_result, msg, need_ack_nums, need_handle_ha)
+                .await
+        } else {
+            put_message_result
+        }
+    }
+
+    /// Appends every queued message and increases the queue offsets of the appended ones, must
+    /// be called while holding `put_message_lock`.
+    fn append_pending_messages(&mut self) {
+        let batch: Vec<PendingAppend> = self.pending_appends.lock().drain(..).collect();
+        if batch.is_empty() {
+            return;
+        }
+        let batch_size = batch.len();
         let begin_lock_timestamp = time_utils::get_current_millis();
         self.begin_time_in_lock
             .store(begin_lock_timestamp, std::sync::atomic::Ordering::Release);
         let start_time = Instant::now();
-        // Here settings are stored timestamp, in order to ensure an orderly global
-        if !self.message_store_config.duplication_enable {
-            msg.message_ext_inner.store_timestamp = begin_lock_timestamp as i64;
+        let mut mapped_file = self.mapped_file_queue.get_last_mapped_file();
+        for PendingAppend {
+            mut msg,
+            put_message_context,
+            topic_queue_lock,
+            tx,
+        } in batch
+        {
+            // Here settings are stored timestamp, in order to ensure an orderly global
+            if !self.message_store_config.duplication_enable {
+                msg.message_ext_inner.store_timestamp = begin_lock_timestamp as i64;
+            }
+            let put_message_result =
+                self.append_message_in_lock(&mut msg, &put_message_context, &mut mapped_file);
+            if put_message_result.put_message_status() == PutMessageStatus::PutOk {
+                let message_num = get_message_num(&self.topic_config_table, &msg);
+                self.increase_offset(&msg, message_num);
+            }
+            drop(topic_queue_lock);
+            let _ = tx.send((put_message_result, msg));
+        }
+        let elapsed_time_in_lock = start_time.elapsed().as_millis() as u64;
+        self.begin_time_in_lock
+            .store(0, std::sync::atomic::Ordering::Release);
+        if elapsed_time_in_lock > 500 {
+            warn!(
+                "[NOTIFYME]putMessage in lock cost time(ms)={}, batchSize={}",
+                elapsed_time_in_lock, batch_size,
+            );
         }
+    }
 
+    fn append_message_in_lock(
+        &mut self,
+        msg: &mut MessageExtBrokerInner,
+        put_message_context: &PutMessageContext,
+        mapped_file: &mut Option<Arc<DefaultMappedFile>>,
+    ) -> PutMessageResult {
         if mapped_file.is_none() || mapped_file.as_ref().unwrap().is_full() {
-            mapped_file = self
+            *mapped_file = self
                 .mapped_file_queue
                 .get_last_mapped_file_mut_start_offset(0, true);
         }
-
-        if mapped_file.is_none() {
-            drop(lock);
-            drop(topic_queue_lock);
+        let Some(file) = mapped_file.as_ref() else {
             error!(
                 "create mapped file error, topic: {}  clientAddr: {}",
                 msg.topic(),
                 msg.born_host()
             );
-            self.begin_time_in_lock
-                .store(0, std::sync::atomic::Ordering::Release);
             return PutMessageResult::new_default(PutMessageStatus::CreateMappedFileFailed);
-        }
+        };
 
-        let result = mapped_file.as_ref().unwrap().append_message(
-            &mut msg,
+        let result = file.append_message(
+            msg,
             self.append_message_callback.as_ref(),
-            &put_message_context,
+            put_message_context,
         );
-        let put_message_result = match result.status {
+        match result.status {
             AppendMessageStatus::PutOk => {
                 //onCommitLogAppend(msg, result, mappedFile); in java not support this version
                 PutMessageResult::new_append_result(PutMessageStatus::PutOk, Some(result))
             }
             AppendMessageStatus::EndOfFile => {
                 //onCommitLogAppend(msg, result, mappedFile); in java not support this version
-                _unlock_mapped_file = mapped_file;
-                mapped_file = self
+                *mapped_file = self
                     .mapped_file_queue
                     .get_last_mapped_file_mut_start_offset(0, true);
-                if mapped_file.is_none() {
-                    self.begin_time_in_lock
-                        .store(0, std::sync::atomic::Ordering::Release);
+                let Some(file) = mapped_file.as_ref() else {
                     error!(
                         "create mapped file error, topic: {}  clientAddr: {}",
                         msg.topic(),
@@ -619,11 +763,11 @@ impl CommitLog {
                         PutMessageStatus::CreateMappedFileFailed,
                         Some(result),
                     );
-                }
-                let result = mapped_file.as_ref().unwrap().append_message(
-                    &mut msg,
+                };
+                let result = file.append_message(
+                    msg,
                     self.append_message_callback.as_ref(),
-                    &put_message_context,
+                    put_message_context,
                 );
                 if AppendMessageStatus::PutOk == result.status {
                     PutMessageResult::new_append_result(PutMessageStatus::PutOk, Some(result))
@@ -636,38 +780,11 @@ impl CommitLog {
             }
             AppendMessageStatus::MessageSizeExceeded
             | AppendMessageStatus::PropertiesSizeExceeded => {
-                self.begin_time_in_lock
-                    .store(0, std::sync::atomic::Ordering::Release);
                 PutMessageResult::new_append_result(PutMessageStatus::MessageIllegal, Some(result))
             }
             AppendMessageStatus::UnknownError => {
-                self.begin_time_in_lock
-                    .store(0, std::sync::atomic::Ordering::Release);
                 PutMessageResult::new_append_result(PutMessageStatus::UnknownError, Some(result))
             }
-        };
-        let elapsed_time_in_lock = start_time.elapsed().as_millis() as u64;
-        drop(lock);
-        self.begin_time_in_lock
-            .store(0, std::sync::atomic::Ordering::Release);
-        if elapsed_time_in_lock > 500 {
-            warn!(
-                "[NOTIFYME]putMessage in lock cost time(ms)={}, bodyLength={} \
-                 AppendMessageResult={}",
-                elapsed_time_in_lock,
-                msg.body_len(),
-                put_message_result.append_message_result().as_ref().unwrap(),
-            );
-        }
-
-        if put_message_result.put_message_status() == PutMessageStatus::PutOk {
-            let message_num = get_message_num(&self.topic_config_table, &msg);
-            self.increase_offset(&msg, message_num);
-            drop(topic_queue_lock);
-            self.handle_disk_flush_and_ha(put_message_result, msg, need_ack_nums, need_handle_ha)
-                .await
-        } else {
-            put_message_result
         }
     }
 
@@ -711,7 +828,11 @@ impl CommitLog {
         });
 
         let replica_result_handle = tokio::spawn(async move {
-            if need_handle_ha {
+            if let Some(dledger_server) = commit_log_cloned.dledger_server.as_ref() {
+                let next_offset = put_message_result_cloned.wrote_offset
+                    + put_message_result_cloned.wrote_bytes as i64;
+                dledger_server.wait_for_commit(next_offset).await
+            } else if need_handle_ha {
                 commit_log_cloned
                     .handle_ha(put_message_result_cloned.as_ref(), need_ack_nums)
                     .await
@@ -745,10 +866,16 @@ impl CommitLog {
         if need_ack_nums <= 1 {
             return PutMessageStatus::PutOk;
         }
-
-        //HA service to do unimplemented
-
-        PutMessageStatus::PutOk
+        let Some(ref ha_service) = self.ha_service else {
+            return PutMessageStatus::PutOk;
+        };
+        let next_offset = put_message_result.wrote_offset + put_message_result.wrote_bytes as i64;
+        if !ha_service.is_slave_ok(next_offset) {
+            return PutMessageStatus::SlaveNotAvailable;
+        }
+        ha_service
+            .wait_for_transfer(next_offset, need_ack_nums)
+            .await
     }
 
     async fn handle_disk_flush(
@@ -763,6 +890,13 @@ impl CommitLog {
             .await
     }
 
+    /// Whether this commit log may take writes, always true without dledger.
+    fn is_dledger_leader(&self) -> bool {
+        self.dledger_server
+            .as_ref()
+            .map_or(true, |dledger_server| dledger_server.is_leader())
+    }
+
     fn need_handle_ha(&self, msg_inner: &MessageExtBrokerInner) -> bool {
         if !msg_inner.is_wait_store_msg_ok() {
             /*
@@ -784,7 +918,7 @@ impl CommitLog {
 
     fn on_commit_log_dispatch(
         &mut self,
-        request: &DispatchRequest,
+        request: &mut DispatchRequest,
         do_dispatch: bool,
         is_recover: bool,
         is_file_end: bool,
@@ -838,7 +972,7 @@ impl CommitLog {
                     break;
                 }
                 let mut msg_bytes = msg.unwrap();
-                let dispatch_request = check_message_and_return_size(
+                let mut dispatch_request = check_message_and_return_size(
                     &mut msg_bytes,
                     check_crc_on_recover,
                     check_dup_info,
@@ -849,12 +983,12 @@ impl CommitLog {
                 if dispatch_request.success && dispatch_request.msg_size > 0 {
                     last_valid_msg_phy_offset = process_offset + mapped_file_offset;
                     mapped_file_offset += dispatch_request.msg_size as u64;
-                    self.on_commit_log_dispatch(&dispatch_request, do_dispatch, true, false);
+                    self.on_commit_log_dispatch(&mut dispatch_request, do_dispatch, true, false);
                 } else if dispatch_request.success && dispatch_request.msg_size == 0 {
                     // Come the end of the file, switch to the next file Since the
                     // return 0 representatives met last hole,
                     // this can not be included in truncate offset
-                    self.on_commit_log_dispatch(&dispatch_request, do_dispatch, true, true);
+                    self.on_commit_log_dispatch(&mut dispatch_request, do_dispatch, true, true);
                     index += 1;
                     if index >= mapped_files_inner.len() {
                         info!(
@@ -937,6 +1071,9 @@ impl CommitLog {
 
     //Fetch and compute the newest confirmOffset.
     pub fn get_confirm_offset(&self) -> i64 {
+        if let Some(dledger_server) = self.dledger_server.as_ref() {
+            return dledger_server.get_commit_offset();
+        }
         if self.broker_config.enable_controller_mode {
             unimplemented!()
         } else if self.broker_config.duplication_enable {
@@ -992,7 +1129,7 @@ impl CommitLog {
                     break;
                 }
                 let mut msg_bytes = msg.unwrap();
-                let dispatch_request = check_message_and_return_size(
+                let mut dispatch_request = check_message_and_return_size(
                     &mut msg_bytes,
                     check_crc_on_recover,
                     check_dup_info,
@@ -1006,12 +1143,13 @@ impl CommitLog {
 
                     if self.message_store_config.duplication_enable
                         || self.broker_config.enable_controller_mode
+                        || self.dledger_server.is_some()
                     {
                         if dispatch_request.commit_log_offset + size as i64
                             <= self.get_confirm_offset()
                         {
                             self.on_commit_log_dispatch(
-                                &dispatch_request,
+                                &mut dispatch_request,
                                 do_dispatch,
                                 true,
                                 false,
@@ -1020,13 +1158,18 @@ impl CommitLog {
                                 dispatch_request.commit_log_offset as u64 + size as u64;
                         }
                     } else {
-                        self.on_commit_log_dispatch(&dispatch_request, do_dispatch, true, false);
+                        self.on_commit_log_dispatch(
+                            &mut dispatch_request,
+                            do_dispatch,
+                            true,
+                            false,
+                        );
                     }
                 } else if dispatch_request.success && dispatch_request.msg_size == 0 {
                     // Come the end of the file, switch to the next file Since the
                     // return 0 representatives met last hole,
                     // this can not be included in truncate offset
-                    self.on_commit_log_dispatch(&dispatch_request, do_dispatch, true, true);
+                    self.on_commit_log_dispatch(&mut dispatch_request, do_dispatch, true, true);
                     index += 1;
                     if index >= mapped_files_inner.len() {
                         info!(
@@ -1094,10 +1237,40 @@ impl CommitLog {
         }
     }
 
+    pub fn get_mapped_file_queue(&self) -> &MappedFileQueue {
+        &self.mapped_file_queue
+    }
+
     pub fn get_max_offset(&self) -> i64 {
         self.mapped_file_queue.get_max_offset()
     }
 
+    /// Reads the store timestamp of the message located at `offset` with the given `size`,
+    /// returning -1 when the message is not in the commit log.
+    pub fn pickup_store_timestamp(&self, offset: i64, size: i32) -> i64 {
+        if offset < self.get_min_offset() || offset + size as i64 > self.get_max_offset() {
+            return -1;
+        }
+        let Some(result) = self.get_message(offset, size) else {
+            return -1;
+        };
+        let buffer = result.get_buffer();
+        if buffer.len() < SYSFLAG_POSITION + mem::size_of::<i32>() {
+            return -1;
+        }
+        let sys_flag = (&buffer[SYSFLAG_POSITION..]).get_i32();
+        let born_host_length = if sys_flag & MessageSysFlag::BORNHOST_V6_FLAG == 0 {
+            8
+        } else {
+            20
+        };
+        let msg_store_time_pos = 4 + 4 + 4 + 4 + 4 + 8 + 8 + 4 + 8 + born_host_length;
+        if buffer.len() < msg_store_time_pos + mem::size_of::<i64>() {
+            return -1;
+        }
+        (&buffer[msg_store_time_pos..]).get_i64()
+    }
+
     pub fn get_min_offset(&self) -> i64 {
         match self.mapped_file_queue.get_first_mapped_file() {
             None => -1,
@@ -1146,7 +1319,7 @@ impl CommitLog {
             .begin_time_in_lock
             .load(std::sync::atomic::Ordering::Acquire);
         if begin > 0 {
-            (SystemClock::now() - (begin as u128)) as i64
+            SystemClock::now().saturating_sub(begin as u128) as i64
         } else {
             0
         }
@@ -1170,7 +1343,7 @@ pub fn check_message_and_return_size(
     check_crc: bool,
     check_dup_info: bool,
     read_body: bool,
-    message_store_config: &Arc<MessageStoreConfig>,
+    message_store_config: &ArcMut<MessageStoreConfig>,
 ) -> DispatchRequest {
     let total_size = bytes.get_i32();
     let magic_code = bytes.get_i32();
@@ -1354,7 +1527,7 @@ fn set_batch_size_if_needed(
 }
 
 fn is_mapped_file_matched_recover(
-    message_store_config: &Arc<MessageStoreConfig>,
+    message_store_config: &ArcMut<MessageStoreConfig>,
     mapped_file: &DefaultMappedFile,
     store_checkpoint: &StoreCheckpoint,
 ) -> bool {
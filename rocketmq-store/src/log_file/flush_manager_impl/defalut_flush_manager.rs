@@ -19,6 +19,7 @@ use std::sync::Weak;
 
 use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
 use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_rust::ArcMut;
 use tokio::sync::Mutex;
 use tokio::sync::Notify;
 use tokio::time;
@@ -36,13 +37,13 @@ pub struct DefaultFlushManager {
     group_commit_service: Option<GroupCommitService>,
     flush_real_time_service: Option<FlushRealTimeService>,
     commit_real_time_service: Option<CommitRealTimeService>,
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     mapped_file_queue: Option<MappedFileQueue>,
 }
 
 impl DefaultFlushManager {
     pub fn new(
-        message_store_config: Arc<MessageStoreConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
         mapped_file_queue: MappedFileQueue,
         store_checkpoint: Arc<StoreCheckpoint>,
     ) -> Self {
@@ -243,7 +244,7 @@ impl GroupCommitService {
 }
 
 struct FlushRealTimeService {
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     store_checkpoint: Arc<StoreCheckpoint>,
     notified: Arc<Notify>,
 }
@@ -256,12 +257,13 @@ impl FlushRealTimeService {
         tokio::spawn(async move {
             let mut last_flush_timestamp = 0;
             loop {
-                let flush_commit_log_timed = message_store_config.flush_commit_log_timed;
-                let interval = message_store_config.flush_interval_commit_log;
+                let flush_commit_log_timed = message_store_config.flush_commit_log_timed.get();
+                let interval = message_store_config.flush_interval_commit_log.get();
                 let mut flush_physic_queue_least_pages =
-                    message_store_config.flush_commit_log_least_pages;
-                let flush_physic_queue_thorough_interval =
-                    message_store_config.flush_commit_log_thorough_interval;
+                    message_store_config.flush_commit_log_least_pages.get();
+                let flush_physic_queue_thorough_interval = message_store_config
+                    .flush_commit_log_thorough_interval
+                    .get();
                 //let mut print_flush_progress = false;
 
                 let current_time_millis = get_current_millis();
@@ -290,7 +292,7 @@ impl FlushRealTimeService {
     }
 
     pub fn wakeup(&mut self) {
-        if !self.message_store_config.flush_commit_log_timed {
+        if !self.message_store_config.flush_commit_log_timed.get() {
             let notified = self.notified.clone();
             tokio::spawn(async move { notified.notified().await });
         }
@@ -300,7 +302,7 @@ impl FlushRealTimeService {
 }
 
 pub(crate) struct CommitRealTimeService {
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     store_checkpoint: Arc<StoreCheckpoint>,
     notified: Arc<Notify>,
     flush_manager: Option<Weak<Mutex<DefaultFlushManager>>>,
@@ -320,11 +322,12 @@ impl CommitRealTimeService {
         tokio::spawn(async move {
             let mut last_commit_timestamp = 0;
             loop {
-                let interval = message_store_config.commit_interval_commit_log;
+                let interval = message_store_config.commit_interval_commit_log.get();
                 let mut commit_data_least_pages =
-                    message_store_config.commit_commit_log_least_pages;
-                let commit_data_thorough_interval =
-                    message_store_config.commit_commit_log_thorough_interval;
+                    message_store_config.commit_commit_log_least_pages.get();
+                let commit_data_thorough_interval = message_store_config
+                    .commit_commit_log_thorough_interval
+                    .get();
                 //let mut print_flush_progress = false;
 
                 let begin = get_current_millis();
@@ -466,6 +466,7 @@ impl MappedFile for DefaultMappedFile {
                     size,
                     mapped_file: Some(self),
                     is_in_cache: true,
+                    bytes: None,
                 })
             } else {
                 None
@@ -488,6 +489,7 @@ impl MappedFile for DefaultMappedFile {
                 size: read_position - pos,
                 mapped_file: Some(self),
                 is_in_cache: true,
+                bytes: None,
             })
         } else {
             None
@@ -634,15 +636,71 @@ impl MappedFile for DefaultMappedFile {
     }
 
     fn mlock(&self) {
-        todo!()
+        #[cfg(unix)]
+        {
+            let begin_time = std::time::Instant::now();
+            let mmap = self.get_mapped_file();
+            if let Err(e) = mmap.lock() {
+                warn!("mlock {} failed: {}", self.file_name, e);
+            }
+            if let Err(e) = mmap.advise(memmap2::Advice::WillNeed) {
+                warn!("madvise {} failed: {}", self.file_name, e);
+            }
+            info!(
+                "mlock {} cost {} ms",
+                self.file_name,
+                begin_time.elapsed().as_millis()
+            );
+        }
     }
 
     fn munlock(&self) {
-        todo!()
+        #[cfg(unix)]
+        {
+            let begin_time = std::time::Instant::now();
+            if let Err(e) = self.get_mapped_file().unlock() {
+                warn!("munlock {} failed: {}", self.file_name, e);
+            }
+            info!(
+                "munlock {} cost {} ms",
+                self.file_name,
+                begin_time.elapsed().as_millis()
+            );
+        }
     }
 
     fn warm_mapped_file(&self, flush_disk_type: FlushDiskType, pages: usize) {
-        todo!()
+        let begin_time = std::time::Instant::now();
+        let mmap = self.get_mapped_file_mut();
+        let mut flush = 0usize;
+        // touch one byte of every page so the OS backs the whole file before it is written
+        for i in (0..self.file_size as usize).step_by(OS_PAGE_SIZE as usize) {
+            mmap[i] = 0;
+            if flush_disk_type == FlushDiskType::SyncFlush
+                && (i - flush) / OS_PAGE_SIZE as usize >= pages
+            {
+                flush = i;
+                if let Err(e) = mmap.flush() {
+                    error!("flush {} when warming failed: {}", self.file_name, e);
+                }
+            }
+        }
+        if flush_disk_type == FlushDiskType::SyncFlush {
+            info!(
+                "mapped file warm-up done, force to disk, mappedFile={}, costTime={}",
+                self.file_name,
+                begin_time.elapsed().as_millis()
+            );
+            if let Err(e) = mmap.flush() {
+                error!("flush {} when warming failed: {}", self.file_name, e);
+            }
+        }
+        info!(
+            "mapped file warm-up done. mappedFile={}, costTime={}",
+            self.file_name,
+            begin_time.elapsed().as_millis()
+        );
+        self.mlock();
     }
 
     fn swap_map(&self) -> bool {
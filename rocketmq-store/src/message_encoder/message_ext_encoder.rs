@@ -14,7 +14,6 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use std::sync::Arc;
 
 use bytes::Buf;
 use bytes::BufMut;
@@ -41,11 +40,11 @@ pub struct MessageExtEncoder {
     max_message_body_size: i32,
     max_message_size: i32,
     crc32_reserved_length: i32,
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
 }
 
 impl MessageExtEncoder {
-    pub fn new(message_store_config: Arc<MessageStoreConfig>) -> MessageExtEncoder {
+    pub fn new(message_store_config: ArcMut<MessageStoreConfig>) -> MessageExtEncoder {
         let max_message_body_size = message_store_config.max_message_size;
         let max_message_size = if i32::MAX - max_message_body_size >= 64 * 1024 {
             max_message_body_size + 64 * 1024
@@ -552,17 +551,16 @@ impl MessageExtEncoder {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
 
     use super::*;
 
     #[test]
     fn message_ext_encoder_new_creates_encoder_with_correct_config() {
-        let config = Arc::new(MessageStoreConfig::default());
-        let encoder = MessageExtEncoder::new(Arc::clone(&config));
+        let config = ArcMut::new(MessageStoreConfig::default());
+        let encoder = MessageExtEncoder::new(config.clone());
 
         assert_eq!(encoder.max_message_body_size, config.max_message_size);
-        assert_eq!(encoder.message_store_config, config);
+        assert_eq!(*encoder.message_store_config, *config);
     }
 
     #[test]
@@ -598,8 +596,8 @@ mod tests {
 
     #[test]
     fn encode_without_properties_encodes_message_correctly() {
-        let config = Arc::new(MessageStoreConfig::default());
-        let mut encoder = MessageExtEncoder::new(Arc::clone(&config));
+        let config = ArcMut::new(MessageStoreConfig::default());
+        let mut encoder = MessageExtEncoder::new(config.clone());
         let msg_inner = MessageExtBrokerInner::default();
 
         let result = encoder.encode_without_properties(&msg_inner);
@@ -609,8 +607,8 @@ mod tests {
 
     #[test]
     fn encode_encodes_message_correctly() {
-        let config = Arc::new(MessageStoreConfig::default());
-        let mut encoder = MessageExtEncoder::new(Arc::clone(&config));
+        let config = ArcMut::new(MessageStoreConfig::default());
+        let mut encoder = MessageExtEncoder::new(config.clone());
         let msg_inner = MessageExtBrokerInner::default();
 
         let result = encoder.encode(&msg_inner);
@@ -620,8 +618,8 @@ mod tests {
 
     #[test]
     fn get_encoder_buffer_returns_correct_buffer() {
-        let config = Arc::new(MessageStoreConfig::default());
-        let mut encoder = MessageExtEncoder::new(Arc::clone(&config));
+        let config = ArcMut::new(MessageStoreConfig::default());
+        let mut encoder = MessageExtEncoder::new(config.clone());
 
         let buffer = encoder.get_encoder_buffer();
 
@@ -630,8 +628,8 @@ mod tests {
 
     #[test]
     fn get_max_message_body_size_returns_correct_size() {
-        let config = Arc::new(MessageStoreConfig::default());
-        let encoder = MessageExtEncoder::new(Arc::clone(&config));
+        let config = ArcMut::new(MessageStoreConfig::default());
+        let encoder = MessageExtEncoder::new(config.clone());
 
         let size = encoder.get_max_message_body_size();
 
@@ -640,8 +638,8 @@ mod tests {
 
     #[test]
     fn update_encoder_buffer_capacity_updates_capacity_correctly() {
-        let config = Arc::new(MessageStoreConfig::default());
-        let mut encoder = MessageExtEncoder::new(Arc::clone(&config));
+        let config = ArcMut::new(MessageStoreConfig::default());
+        let mut encoder = MessageExtEncoder::new(config.clone());
 
         encoder.update_encoder_buffer_capacity(200);
 
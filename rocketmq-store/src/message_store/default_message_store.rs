@@ -17,6 +17,7 @@
 #![allow(unused_variables)]
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::error::Error;
 use std::fs;
 use std::path::PathBuf;
@@ -31,12 +32,14 @@ use std::time::Instant;
 use bytes::Buf;
 use cheetah_string::CheetahString;
 use rocketmq_common::common::attribute::cleanup_policy::CleanupPolicy;
+use rocketmq_common::common::boundary_type::BoundaryType;
 use rocketmq_common::common::message::message_batch::MessageExtBatch;
 use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::mix_all::is_lmq;
 use rocketmq_common::common::mix_all::is_sys_consumer_group_for_no_cold_read_limit;
 use rocketmq_common::common::mix_all::MULTI_DISPATCH_QUEUE_SPLITTER;
 use rocketmq_common::common::mix_all::RETRY_GROUP_TOPIC_PREFIX;
+use rocketmq_common::common::topic::TopicValidator;
 use rocketmq_common::utils::util_all;
 use rocketmq_common::CleanupPolicyUtils::get_delete_policy;
 use rocketmq_common::TimeUtils::get_current_millis;
@@ -66,6 +69,7 @@ use crate::base::dispatch_request::DispatchRequest;
 use crate::base::get_message_result::GetMessageResult;
 use crate::base::message_arriving_listener::MessageArrivingListener;
 use crate::base::message_result::PutMessageResult;
+use crate::base::message_status_enum::BatchFullStatus;
 use crate::base::message_status_enum::GetMessageStatus;
 use crate::base::message_status_enum::PutMessageStatus;
 use crate::base::query_message_result::QueryMessageResult;
@@ -77,8 +81,11 @@ use crate::config::broker_role::BrokerRole;
 use crate::config::message_store_config::MessageStoreConfig;
 use crate::config::store_path_config_helper::get_store_path_batch_consume_queue;
 use crate::config::store_path_config_helper::get_store_path_consume_queue_ext;
+use crate::dledger::dledger_server::DLedgerServer;
 use crate::filter::MessageFilter;
+use crate::ha::default_ha_service::DefaultHAService;
 use crate::hook::put_message_hook::BoxedPutMessageHook;
+use crate::hook::send_message_back_hook::ArcSendMessageBackHook;
 use crate::index::index_dispatch::CommitLogDispatcherBuildIndex;
 use crate::index::index_service::IndexService;
 use crate::kv::compaction_service::CompactionService;
@@ -97,14 +104,18 @@ use crate::store::running_flags::RunningFlags;
 use crate::store_path_config_helper::get_abort_file;
 use crate::store_path_config_helper::get_store_checkpoint;
 use crate::store_path_config_helper::get_store_path_consume_queue;
+use crate::store_path_config_helper::get_tiered_store_metadata_path;
+use crate::tiered::tiered_backend::DefaultTieredBackend;
+use crate::tiered::tiered_message_store::TieredMessageStore;
 use crate::timer::timer_message_store::TimerMessageStore;
 use crate::utils::store_util::TOTAL_PHYSICAL_MEMORY_SIZE;
 
 ///Using local files to store message data, which is also the default method.
 pub struct DefaultMessageStore {
-    message_store_config: Arc<MessageStoreConfig>,
-    broker_config: Arc<BrokerConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
+    broker_config: ArcMut<BrokerConfig>,
     put_message_hook_list: Arc<parking_lot::RwLock<Vec<BoxedPutMessageHook>>>,
+    send_message_back_hook: Arc<parking_lot::RwLock<Option<ArcSendMessageBackHook>>>,
     topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
     //message_store_runtime: Option<RocketMQRuntime>,
     commit_log: CommitLog,
@@ -133,12 +144,15 @@ pub struct DefaultMessageStore {
     timer_message_store: Arc<TimerMessageStore>,
     transient_store_pool: TransientStorePool,
     message_store_arc: Option<ArcMut<DefaultMessageStore>>,
+    ha_service: Option<Arc<DefaultHAService>>,
+    dledger_server: Option<Arc<DLedgerServer>>,
+    tiered_message_store: Option<Arc<TieredMessageStore<DefaultTieredBackend>>>,
 }
 
 impl DefaultMessageStore {
     pub fn new(
-        message_store_config: Arc<MessageStoreConfig>,
-        broker_config: Arc<BrokerConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
         broker_stats_manager: Option<Arc<BrokerStatsManager>>,
         notify_message_arrive_in_batch: bool,
@@ -166,22 +180,72 @@ impl DefaultMessageStore {
             CommitLogDispatcherBuildConsumeQueue::new(consume_queue_store.clone());
 
         let dispatcher = CommitLogDispatcherDefault {
-            dispatcher_vec: Arc::new(vec![Box::new(build_consume_queue), Box::new(build_index)]),
+            dispatcher_vec: Arc::new(parking_lot::RwLock::new(vec![
+                Box::new(build_consume_queue),
+                Box::new(build_index),
+            ])),
         };
 
-        let commit_log = CommitLog::new(
+        let allocate_mapped_file_service =
+            Arc::new(AllocateMappedFileService::new(message_store_config.clone()));
+        let mut commit_log = CommitLog::new(
             message_store_config.clone(),
             broker_config.clone(),
             &dispatcher,
             store_checkpoint.clone(),
             topic_config_table.clone(),
             consume_queue_store.clone(),
+            allocate_mapped_file_service.clone(),
         );
+        let ha_service = if !message_store_config.enable_dledger_commit_log
+            && !message_store_config.duplication_enable
+        {
+            Some(Arc::new(DefaultHAService::new(
+                message_store_config.clone(),
+                commit_log.clone(),
+            )))
+        } else {
+            None
+        };
+        commit_log.set_ha_service(ha_service.clone());
+        let dledger_server = if message_store_config.enable_dledger_commit_log {
+            match DLedgerServer::new(
+                message_store_config.clone(),
+                commit_log.clone(),
+                store_checkpoint.clone(),
+            ) {
+                Ok(dledger_server) => Some(Arc::new(dledger_server)),
+                Err(e) => {
+                    error!("create dledger server failed: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        commit_log.set_dledger_server(dledger_server.clone());
 
         ensure_dir_ok(message_store_config.store_path_root_dir.as_str());
         ensure_dir_ok(Self::get_store_path_physic(&message_store_config).as_str());
         ensure_dir_ok(Self::get_store_path_logic(&message_store_config).as_str());
 
+        let tiered_message_store = if message_store_config.tiered_store_enable {
+            match DefaultTieredBackend::new(&message_store_config) {
+                Ok(backend) => Some(Arc::new(TieredMessageStore::new(
+                    backend,
+                    get_tiered_store_metadata_path(
+                        message_store_config.store_path_root_dir.as_str(),
+                    ),
+                ))),
+                Err(e) => {
+                    error!("create tiered store backend failed: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         let identity = broker_config.broker_identity.clone();
         let transient_store_pool = TransientStorePool::new(
             message_store_config.transient_store_pool_size,
@@ -191,6 +255,7 @@ impl DefaultMessageStore {
             message_store_config: message_store_config.clone(),
             broker_config,
             put_message_hook_list: Arc::new(parking_lot::RwLock::new(vec![])),
+            send_message_back_hook: Arc::new(parking_lot::RwLock::new(None)),
             topic_config_table,
             // message_store_runtime: Some(RocketMQRuntime::new_multi(10, "message-store-thread")),
             commit_log,
@@ -198,7 +263,7 @@ impl DefaultMessageStore {
             store_checkpoint: Some(store_checkpoint),
             master_flushed_offset: Arc::new(AtomicI64::new(-1)),
             index_service,
-            allocate_mapped_file_service: Arc::new(AllocateMappedFileService::new()),
+            allocate_mapped_file_service,
             consume_queue_store,
             dispatcher,
             broker_init_max_offset: Arc::new(AtomicI64::new(-1)),
@@ -222,23 +287,24 @@ impl DefaultMessageStore {
             timer_message_store: Arc::new(TimerMessageStore::new_empty()),
             transient_store_pool,
             message_store_arc: None,
+            ha_service,
+            dledger_server,
+            tiered_message_store,
         }
     }
 
-    pub fn get_store_path_physic(message_store_config: &Arc<MessageStoreConfig>) -> String {
+    pub fn get_store_path_physic(message_store_config: &ArcMut<MessageStoreConfig>) -> String {
         match message_store_config.enable_dledger_commit_log {
-            true => {
-                unimplemented!("dledger commit log is not supported yet")
-            }
+            true => message_store_config.get_store_path_dledger_commit_log(),
             false => message_store_config.get_store_path_commit_log(),
         }
     }
 
-    pub fn get_store_path_logic(message_store_config: &Arc<MessageStoreConfig>) -> String {
+    pub fn get_store_path_logic(message_store_config: &ArcMut<MessageStoreConfig>) -> String {
         get_store_path_consume_queue(message_store_config.store_path_root_dir.as_str())
     }
 
-    pub fn message_store_config(&self) -> Arc<MessageStoreConfig> {
+    pub fn message_store_config(&self) -> ArcMut<MessageStoreConfig> {
         self.message_store_config.clone()
     }
 
@@ -248,6 +314,115 @@ impl DefaultMessageStore {
                 || self.message_store_config().broker_role != BrokerRole::Slave)
     }
 
+    pub fn get_ha_service(&self) -> Option<&Arc<DefaultHAService>> {
+        self.ha_service.as_ref()
+    }
+
+    pub fn get_dledger_server(&self) -> Option<&Arc<DLedgerServer>> {
+        self.dledger_server.as_ref()
+    }
+
+    pub fn get_tiered_message_store(
+        &self,
+    ) -> Option<&Arc<TieredMessageStore<DefaultTieredBackend>>> {
+        self.tiered_message_store.as_ref()
+    }
+
+    /// Reads messages of a queue below the local min offset back from the tiered store.
+    ///
+    /// Returns the next begin offset, or `None` if the tier does not hold `offset` either.
+    #[allow(clippy::too_many_arguments)]
+    async fn get_message_from_tiered_store(
+        &self,
+        tiered_message_store: &TieredMessageStore<DefaultTieredBackend>,
+        topic: &CheetahString,
+        queue_id: i32,
+        offset: i64,
+        end_offset: i64,
+        max_msg_nums: i32,
+        max_total_msg_size: i32,
+        message_filter: Option<&dyn MessageFilter>,
+        get_result: &mut GetMessageResult,
+    ) -> Option<i64> {
+        let max_total_msg_size = max_total_msg_size.max(100);
+        let mut next_begin_offset = offset;
+        'read: while next_begin_offset < end_offset
+            && get_result.message_count() < max_msg_nums
+            && get_result.buffer_total_size() < max_total_msg_size
+        {
+            let max_count = (end_offset - next_begin_offset)
+                .min((max_msg_nums - get_result.message_count()) as i64)
+                as usize;
+            let units = match tiered_message_store
+                .get_consume_queue_units(topic, queue_id, next_begin_offset, max_count)
+                .await
+            {
+                Ok(units) if !units.is_empty() => units,
+                Ok(_) => break,
+                Err(e) => {
+                    warn!(
+                        "read consume queue from tiered store failed, topic: {}, queueId: {}, \
+                         offset: {}, error: {}",
+                        topic, queue_id, next_begin_offset, e
+                    );
+                    break;
+                }
+            };
+            let locations = units
+                .iter()
+                .map(|&(offset_py, size_py, _)| (offset_py, size_py))
+                .collect::<Vec<_>>();
+            let batch = match tiered_message_store
+                .get_commit_log_data_batch(&locations)
+                .await
+            {
+                Ok(batch) => batch,
+                Err(e) => {
+                    warn!(
+                        "read commit log from tiered store failed, topic: {}, queueId: {}, \
+                         offset: {}, error: {}",
+                        topic, queue_id, next_begin_offset, e
+                    );
+                    break;
+                }
+            };
+            for ((offset_py, _, tags_code), data) in units.into_iter().zip(batch) {
+                if get_result.message_count() >= max_msg_nums
+                    || get_result.buffer_total_size() >= max_total_msg_size
+                {
+                    break 'read;
+                }
+                let Some(data) = data else {
+                    break 'read;
+                };
+                let queue_offset = next_begin_offset;
+                next_begin_offset += 1;
+                if let Some(filter) = message_filter {
+                    if !filter.is_matched_by_consume_queue(Some(tags_code), None)
+                        || !filter.is_matched_by_commit_log(Some(&data), None)
+                    {
+                        continue;
+                    }
+                }
+                self.store_stats_service
+                    .get_message_transferred_msg_count()
+                    .fetch_add(1, Ordering::Relaxed);
+                get_result.add_message(
+                    SelectMappedBufferResult::from_bytes(offset_py as u64, data),
+                    queue_offset as u64,
+                    1,
+                );
+            }
+        }
+        (next_begin_offset > offset).then_some(next_begin_offset)
+    }
+
+    pub fn update_ha_master_address(&self, new_addr: CheetahString) {
+        if let Some(ha_service) = self.ha_service.as_ref() {
+            ha_service.update_master_address(new_addr);
+        }
+    }
+
     pub fn set_message_store_arc(
         &mut self,
         message_store_arc: Option<ArcMut<DefaultMessageStore>>,
@@ -273,6 +448,19 @@ impl DefaultMessageStore {
         self.topic_config_table.lock().get(topic).cloned()
     }
 
+    /// Refuses messages for light message queues once the broker hosts too many of them.
+    fn is_lmq_consume_queue_num_exceeded(&self, msg: &MessageExtBrokerInner) -> bool {
+        self.message_store_config.enable_lmq
+            && self.message_store_config.enable_multi_dispatch
+            && msg
+                .message_ext_inner
+                .properties()
+                .get(MessageConst::PROPERTY_INNER_MULTI_DISPATCH)
+                .is_some_and(|queues| !queues.trim().is_empty())
+            && self.consume_queue_store.get_lmq_queue_num()
+                > self.message_store_config.max_lmq_consume_queue_num
+    }
+
     fn is_temp_file_exist(&self) -> bool {
         let file_name = get_abort_file(self.message_store_config.store_path_root_dir.as_str());
         fs::metadata(file_name).is_ok()
@@ -374,7 +562,7 @@ impl DefaultMessageStore {
 
     pub fn on_commit_log_dispatch(
         &mut self,
-        dispatch_request: &DispatchRequest,
+        dispatch_request: &mut DispatchRequest,
         do_dispatch: bool,
         is_recover: bool,
         _is_file_end: bool,
@@ -384,7 +572,7 @@ impl DefaultMessageStore {
         }
     }
 
-    pub fn do_dispatch(&mut self, dispatch_request: &DispatchRequest) {
+    pub fn do_dispatch(&mut self, dispatch_request: &mut DispatchRequest) {
         self.dispatcher.dispatch(dispatch_request)
     }
 
@@ -493,16 +681,52 @@ impl DefaultMessageStore {
     }
 }
 
+/// Binary searches `[min_offset, max_offset)` by store time. A negative store time means the
+/// message has been cleaned up, which only happens to the older part of the queue, so it is
+/// treated as earlier than any timestamp.
+fn search_offset_by_time(
+    min_offset: i64,
+    max_offset: i64,
+    timestamp: i64,
+    boundary_type: BoundaryType,
+    store_time_at: impl Fn(i64) -> i64,
+) -> i64 {
+    let mut low = min_offset;
+    let mut high = max_offset;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let store_time = store_time_at(mid);
+        let go_right = match boundary_type {
+            BoundaryType::Lower => store_time < timestamp,
+            BoundaryType::Upper => store_time <= timestamp,
+        };
+        if go_right {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    // low is now the first offset past the boundary, the upper boundary is the one before it
+    let offset = match boundary_type {
+        BoundaryType::Lower => low,
+        BoundaryType::Upper => low - 1,
+    };
+    offset.clamp(min_offset, max_offset.max(min_offset))
+}
+
 fn estimate_in_mem_by_commit_offset(
     offset_py: i64,
     max_offset_py: i64,
-    message_store_config: &Arc<MessageStoreConfig>,
+    message_store_config: &ArcMut<MessageStoreConfig>,
 ) -> bool {
     let memory = (*TOTAL_PHYSICAL_MEMORY_SIZE as f64)
         * (message_store_config.access_message_in_memory_max_ratio as f64 / 100.0);
     (max_offset_py - offset_py) <= memory as i64
 }
 
+/// Returns the limit that would be exceeded by adding the next unit to the batch, if any.
+///
+/// The first unit is always transferred so a single oversized message can't stall the queue.
 fn is_the_batch_full(
     size_py: i32,
     unit_batch_num: i32,
@@ -511,37 +735,40 @@ fn is_the_batch_full(
     buffer_total: i32,
     message_total: i32,
     is_in_mem: bool,
-    message_store_config: &Arc<MessageStoreConfig>,
-) -> bool {
+    message_store_config: &ArcMut<MessageStoreConfig>,
+) -> Option<BatchFullStatus> {
     if buffer_total == 0 || message_total == 0 {
-        return false;
+        return None;
     }
 
-    if message_total + unit_batch_num > max_msg_nums {
-        return true;
+    if message_total as i64 + unit_batch_num as i64 > max_msg_nums as i64 {
+        return Some(BatchFullStatus::Full);
     }
 
     if buffer_total as i64 + size_py as i64 > max_msg_size {
-        return true;
+        return Some(BatchFullStatus::BufferFull);
     }
 
-    if is_in_mem {
-        if (buffer_total + size_py) as u64
-            > message_store_config.max_transfer_bytes_on_message_in_memory
-        {
-            return true;
-        }
-
-        message_total as u64 > message_store_config.max_transfer_count_on_message_in_memory - 1
+    let (max_transfer_bytes, max_transfer_count) = if is_in_mem {
+        (
+            message_store_config.max_transfer_bytes_on_message_in_memory,
+            message_store_config.max_transfer_count_on_message_in_memory,
+        )
     } else {
-        if (buffer_total + size_py) as u64
-            > message_store_config.max_transfer_bytes_on_message_in_disk
-        {
-            return true;
-        }
+        (
+            message_store_config.max_transfer_bytes_on_message_in_disk,
+            message_store_config.max_transfer_count_on_message_in_disk,
+        )
+    };
+
+    if (buffer_total as u64).saturating_add(size_py.max(0) as u64) > max_transfer_bytes {
+        return Some(BatchFullStatus::BufferFull);
+    }
 
-        message_total as u64 > message_store_config.max_transfer_count_on_message_in_disk - 1
+    if message_total as u64 >= max_transfer_count {
+        return Some(BatchFullStatus::Full);
     }
+    None
 }
 
 #[allow(unused_variables)]
@@ -558,11 +785,25 @@ impl MessageStore for DefaultMessageStore {
             },
             self.message_store_config.store_path_root_dir
         );
+        if self.message_store_config.enable_dledger_commit_log && self.dledger_server.is_none() {
+            error!("load dledger state failed");
+            return false;
+        }
         //load Commit log-- init commit mapped file queue
         let mut result = self.commit_log.load();
         if !result {
             return result;
         }
+        if self.message_store_config.tiered_store_enable {
+            match self.tiered_message_store.as_ref() {
+                Some(tiered_message_store) => result &= tiered_message_store.load(),
+                None => result = false,
+            }
+            if !result {
+                error!("load tiered message store failed");
+                return result;
+            }
+        }
         // load Consume Queue-- init Consume log mapped file queue
         result &= self.consume_queue_store.load();
 
@@ -593,13 +834,14 @@ impl MessageStore for DefaultMessageStore {
         info!("load over, and the max phy offset = {}", max_offset);
 
         if !result {
-            // self.allocate_mapped_file_service.shutdown();
+            self.allocate_mapped_file_service.shutdown();
         }
         result
     }
 
     fn start(&mut self) -> Result<(), Box<dyn Error>> {
         self.create_temp_file();
+        self.allocate_mapped_file_service.start();
 
         self.reput_message_service
             .set_reput_from_offset(self.commit_log.get_confirm_offset());
@@ -611,18 +853,64 @@ impl MessageStore for DefaultMessageStore {
             self.message_store_arc.clone().unwrap(),
         );
 
+        if let Some(ha_service) = self.ha_service.as_ref() {
+            ha_service.start()?;
+        }
+        if let Some(dledger_server) = self.dledger_server.as_ref() {
+            dledger_server.start()?;
+        }
+
         self.commit_log.start();
 
+        if let Some(tiered_message_store) = self.tiered_message_store.as_ref() {
+            if self.message_store_config.enable_dledger_commit_log {
+                warn!("dledger commit log is not uploaded to the tiered store");
+            } else {
+                tiered_message_store
+                    .watch_commit_log(self.commit_log.get_mapped_file_queue().clone());
+            }
+            tiered_message_store
+                .watch_consume_queue_table(self.consume_queue_store.get_consume_queue_table());
+            tiered_message_store.clone().start(Duration::from_millis(
+                self.message_store_config.tiered_store_upload_interval_ms,
+            ));
+        }
+
         //self.add_schedule_task();
 
         Ok(())
     }
 
-    fn shutdown(&mut self) {
+    async fn shutdown(&mut self) {
         if !self.shutdown.load(Ordering::Acquire) {
             self.shutdown.store(true, Ordering::SeqCst);
+            if let Some(ha_service) = self.ha_service.as_ref() {
+                ha_service.shutdown();
+            }
+            if let Some(dledger_server) = self.dledger_server.as_ref() {
+                dledger_server.shutdown();
+            }
+            if let Some(tiered_message_store) = self.tiered_message_store.as_ref() {
+                tiered_message_store.shutdown();
+            }
             self.reput_message_service.shutdown();
-            self.commit_log.shutdown();
+            self.commit_log.shutdown().await;
+            self.allocate_mapped_file_service.shutdown();
+            for consume_queues in self
+                .consume_queue_store
+                .get_consume_queue_table()
+                .lock()
+                .values()
+            {
+                for consume_queue in consume_queues.values() {
+                    consume_queue.flush(0);
+                }
+            }
+            if let Some(store_checkpoint) = self.store_checkpoint.as_ref() {
+                if let Err(e) = store_checkpoint.flush() {
+                    error!("flush store checkpoint failed: {}", e);
+                }
+            }
 
             if self.running_flags.is_writeable() {
                 //delete abort file
@@ -677,6 +965,9 @@ impl MessageStore for DefaultMessageStore {
                 return PutMessageResult::new_default(PutMessageStatus::MessageIllegal);
             }
         }
+        if self.is_lmq_consume_queue_num_exceeded(&msg) {
+            return PutMessageResult::new_default(PutMessageStatus::LmqConsumeQueueNumExceeded);
+        }
         let begin_time = Instant::now();
         //put message to commit log
         let result = self.commit_log.put_message(msg).await;
@@ -729,7 +1020,10 @@ impl MessageStore for DefaultMessageStore {
 
     fn is_os_page_cache_busy(&self) -> bool {
         let begin = self.commit_log.begin_time_in_lock().load(Ordering::Relaxed);
-        let diff = get_current_millis() - begin;
+        if begin == 0 {
+            return false;
+        }
+        let diff = get_current_millis().saturating_sub(begin);
         diff < 10000000 && diff > self.message_store_config.os_page_cache_busy_timeout_mills
     }
 
@@ -750,6 +1044,18 @@ impl MessageStore for DefaultMessageStore {
         self.put_message_hook_list.write().push(put_message_hook);
     }
 
+    fn set_send_message_back_hook(&self, send_message_back_hook: ArcSendMessageBackHook) {
+        *self.send_message_back_hook.write() = Some(send_message_back_hook);
+    }
+
+    fn get_send_message_back_hook(&self) -> Option<ArcSendMessageBackHook> {
+        self.send_message_back_hook.read().clone()
+    }
+
+    fn add_first_dispatcher(&self, dispatcher: Box<dyn CommitLogDispatcher>) {
+        self.dispatcher.add_first(dispatcher);
+    }
+
     fn get_broker_stats_manager(&self) -> Option<Arc<BrokerStatsManager>> {
         self.broker_stats_manager.clone()
     }
@@ -786,6 +1092,44 @@ impl MessageStore for DefaultMessageStore {
         }
     }
 
+    fn get_offset_in_queue_by_time(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        timestamp: i64,
+    ) -> i64 {
+        self.get_offset_in_queue_by_time_with_boundary(
+            topic,
+            queue_id,
+            timestamp,
+            BoundaryType::Lower,
+        )
+    }
+
+    fn get_offset_in_queue_by_time_with_boundary(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        timestamp: i64,
+        boundary_type: BoundaryType,
+    ) -> i64 {
+        let Some(consume_queue) = self.find_consume_queue(topic, queue_id) else {
+            return 0;
+        };
+        search_offset_by_time(
+            consume_queue.get_min_offset_in_queue(),
+            consume_queue.get_max_offset_in_queue(),
+            timestamp,
+            boundary_type,
+            |offset| match consume_queue.get(offset) {
+                Some(cq_unit) => self
+                    .commit_log
+                    .pickup_store_timestamp(cq_unit.pos, cq_unit.size),
+                None => -1,
+            },
+        )
+    }
+
     async fn get_message(
         &self,
         group: &CheetahString,
@@ -840,6 +1184,30 @@ impl MessageStore for DefaultMessageStore {
             } else if offset < min_offset {
                 status = GetMessageStatus::OffsetTooSmall;
                 next_begin_offset = self.next_offset_correction(offset, min_offset);
+                if let Some(tiered_message_store) = self.tiered_message_store.as_ref() {
+                    let get_result_ref = get_result.as_mut().unwrap();
+                    if let Some(next_offset) = self
+                        .get_message_from_tiered_store(
+                            tiered_message_store,
+                            topic,
+                            queue_id,
+                            offset,
+                            min_offset,
+                            max_msg_nums,
+                            max_total_msg_size,
+                            message_filter,
+                            get_result_ref,
+                        )
+                        .await
+                    {
+                        status = if get_result_ref.message_count() > 0 {
+                            GetMessageStatus::Found
+                        } else {
+                            GetMessageStatus::NoMatchedMessage
+                        };
+                        next_begin_offset = next_offset;
+                    }
+                }
             } else if offset == max_offset {
                 status = GetMessageStatus::OffsetOverflowOne;
                 next_begin_offset = self.next_offset_correction(offset, offset);
@@ -908,7 +1276,7 @@ impl MessageStore for DefaultMessageStore {
                                 break;
                             }
                             let get_result_ref = get_result.as_mut().unwrap();
-                            if is_the_batch_full(
+                            let batch_full_status = is_the_batch_full(
                                 size_py,
                                 cq_unit.batch_num as i32,
                                 max_msg_nums,
@@ -917,10 +1285,14 @@ impl MessageStore for DefaultMessageStore {
                                 get_result_ref.message_count(),
                                 is_in_mem,
                                 &self.message_store_config,
-                            ) {
+                            );
+                            if batch_full_status.is_some() {
+                                get_result_ref.set_batch_full_status(batch_full_status);
                                 break;
                             }
                             if get_result_ref.buffer_total_size() >= max_pull_size {
+                                get_result_ref
+                                    .set_batch_full_status(Some(BatchFullStatus::BufferFull));
                                 break;
                             }
                             max_phy_offset_pulling = offset_py;
@@ -954,7 +1326,7 @@ impl MessageStore for DefaultMessageStore {
                             }
                             if self.message_store_config.cold_data_flow_control_enable
                                 && !is_sys_consumer_group_for_no_cold_read_limit(group)
-                                && !select_result.as_ref().unwrap().is_in_cache
+                                && (!is_in_mem || !select_result.as_ref().unwrap().is_in_cache)
                             {
                                 get_result_ref.set_cold_data_sum(
                                     get_result_ref.cold_data_sum() + size_py as i64,
@@ -983,6 +1355,8 @@ impl MessageStore for DefaultMessageStore {
                             );
                             status = GetMessageStatus::Found;
                             next_phy_file_start_offset = i64::MIN;
+                        } else {
+                            break;
                         }
                     }
                 }
@@ -1064,7 +1438,8 @@ impl MessageStore for DefaultMessageStore {
     }
 
     fn notify_message_arrive_if_necessary(&self, dispatch_request: &mut DispatchRequest) {
-        if self.broker_config.long_polling_enable && self.message_arriving_listener.is_some() {
+        if self.broker_config.long_polling_enable.get() && self.message_arriving_listener.is_some()
+        {
             self.message_arriving_listener.as_ref().unwrap().arriving(
                 dispatch_request.topic.as_ref(),
                 dispatch_request.queue_id,
@@ -1131,6 +1506,29 @@ impl MessageStore for DefaultMessageStore {
 
         delete_count
     }
+
+    fn clean_unused_topic(&mut self, retain_topics: &HashSet<CheetahString>) -> i32 {
+        let unused_topics = self
+            .consume_queue_store
+            .get_consume_queue_table()
+            .lock()
+            .keys()
+            .filter(|topic| {
+                !retain_topics.contains(*topic)
+                    && !TopicValidator::is_system_topic(topic)
+                    && !is_lmq(Some(topic.as_str()))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if !unused_topics.is_empty() {
+            info!(
+                "cleanUnusedTopic: deleting unused topics {:?}",
+                unused_topics
+            );
+        }
+        self.delete_topics(unused_topics.iter().collect())
+    }
+
     async fn query_message(
         &self,
         topic: &CheetahString,
@@ -1234,18 +1632,12 @@ impl MessageStore for DefaultMessageStore {
         queue_id: i32,
         consume_queue_offset: i64,
     ) -> i64 {
-        let consume_queue = self.find_consume_queue(topic, queue_id);
-        if let Some(consume_queue) = consume_queue {
-            if let Some((_, store_time)) =
-                consume_queue.get_cq_unit_and_store_time(consume_queue_offset)
-            {
-                store_time
-            } else {
-                -1
-            }
-        } else {
-            -1
-        }
+        self.find_consume_queue(topic, queue_id)
+            .and_then(|consume_queue| consume_queue.get(consume_queue_offset))
+            .map_or(-1, |cq_unit| {
+                self.commit_log
+                    .pickup_store_timestamp(cq_unit.pos, cq_unit.size)
+            })
     }
     fn get_runtime_info(&self) -> HashMap<String, String> {
         self.store_stats_service.get_runtime_info()
@@ -1259,6 +1651,11 @@ impl MessageStore for DefaultMessageStore {
         -1
     }
 
+    fn get_earliest_message_time_in_queue(&self, topic: &CheetahString, queue_id: i32) -> i64 {
+        let min_offset = self.get_min_offset_in_queue(topic, queue_id);
+        self.get_message_store_timestamp(topic, queue_id, min_offset)
+    }
+
     fn get_timer_message_store(&self) -> Arc<TimerMessageStore> {
         self.timer_message_store.clone()
     }
@@ -1287,14 +1684,22 @@ impl MessageStore for DefaultMessageStore {
 pub struct CommitLogDispatcherDefault {
     /*build_index: CommitLogDispatcherBuildIndex,
     build_consume_queue: CommitLogDispatcherBuildConsumeQueue,*/
-    dispatcher_vec: Arc<Vec<Box<dyn CommitLogDispatcher>>>,
+    dispatcher_vec: Arc<parking_lot::RwLock<Vec<Box<dyn CommitLogDispatcher>>>>,
+}
+
+impl CommitLogDispatcherDefault {
+    /// Inserts `dispatcher` ahead of the built-in ones, so whatever it adds to the request is
+    /// persisted by the consume queue dispatcher.
+    pub fn add_first(&self, dispatcher: Box<dyn CommitLogDispatcher>) {
+        self.dispatcher_vec.write().insert(0, dispatcher);
+    }
 }
 
 impl CommitLogDispatcher for CommitLogDispatcherDefault {
-    fn dispatch(&self, dispatch_request: &DispatchRequest) {
+    fn dispatch(&self, dispatch_request: &mut DispatchRequest) {
         /*self.build_index.dispatch(dispatch_request);
         self.build_consume_queue.dispatch(dispatch_request);*/
-        for dispatcher in self.dispatcher_vec.iter() {
+        for dispatcher in self.dispatcher_vec.read().iter() {
             dispatcher.dispatch(dispatch_request);
         }
     }
@@ -1303,7 +1708,7 @@ impl CommitLogDispatcher for CommitLogDispatcherDefault {
 struct ReputMessageService {
     tx: Option<Arc<Sender<()>>>,
     reput_from_offset: Option<Arc<AtomicI64>>,
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     inner: Option<ReputMessageServiceInner>,
 }
 
@@ -1370,7 +1775,7 @@ impl ReputMessageService {
     pub fn start(
         &mut self,
         commit_log: Arc<CommitLog>,
-        message_store_config: Arc<MessageStoreConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
         dispatcher: CommitLogDispatcherDefault,
         notify_message_arrive_in_batch: bool,
         message_store: ArcMut<DefaultMessageStore>,
@@ -1441,7 +1846,7 @@ impl ReputMessageService {
 struct ReputMessageServiceInner {
     reput_from_offset: Arc<AtomicI64>,
     commit_log: Arc<CommitLog>,
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     dispatcher: CommitLogDispatcherDefault,
     notify_message_arrive_in_batch: bool,
     message_store: ArcMut<DefaultMessageStore>,
@@ -1554,7 +1959,7 @@ impl ReputMessageServiceInner {
                 if dispatch_request.success {
                     match dispatch_request.msg_size.cmp(&0) {
                         std::cmp::Ordering::Greater => {
-                            self.dispatcher.dispatch(&dispatch_request);
+                            self.dispatcher.dispatch(&mut dispatch_request);
                             if !self.notify_message_arrive_in_batch {
                                 self.message_store
                                     .notify_message_arrive_if_necessary(&mut dispatch_request);
@@ -1562,11 +1967,6 @@ impl ReputMessageServiceInner {
                             self.reput_from_offset
                                 .fetch_add(dispatch_request.msg_size as i64, Ordering::AcqRel);
                             read_size += dispatch_request.msg_size;
-                            if !self.message_store_config.duplication_enable
-                                && self.message_store_config.broker_role == BrokerRole::Slave
-                            {
-                                unimplemented!()
-                            }
                         }
                         std::cmp::Ordering::Equal => {
                             self.reput_from_offset.store(
@@ -1587,9 +1987,6 @@ impl ReputMessageServiceInner {
                         .fetch_add(dispatch_request.msg_size as i64, Ordering::SeqCst);
                 } else {
                     do_next = false;
-                    if self.message_store_config.enable_dledger_commit_log {
-                        unimplemented!()
-                    }
                 }
 
                 if !(read_size < result.size
@@ -1640,3 +2037,343 @@ impl CorrectLogicOffsetService {
         println!("correct logic offset service run unimplemented!")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::BufMut;
+
+    use super::*;
+    use crate::hook::send_message_back_hook::SendMessageBackHook;
+    use crate::tiered::tiered_metadata::TieredFileType;
+
+    #[test]
+    fn batch_full_respects_transfer_limits() {
+        let config = ArcMut::new(MessageStoreConfig {
+            max_transfer_bytes_on_message_in_memory: 1000,
+            max_transfer_count_on_message_in_memory: 4,
+            max_transfer_bytes_on_message_in_disk: 500,
+            max_transfer_count_on_message_in_disk: 2,
+            ..MessageStoreConfig::default()
+        });
+        // the first unit is always transferred
+        assert_eq!(
+            is_the_batch_full(10_000, 1, 32, 100, 0, 0, false, &config),
+            None
+        );
+        assert_eq!(
+            is_the_batch_full(100, 1, 32, 4096, 300, 3, true, &config),
+            None
+        );
+        assert_eq!(
+            is_the_batch_full(100, 1, 32, 4096, 300, 4, true, &config),
+            Some(BatchFullStatus::Full)
+        );
+        assert_eq!(
+            is_the_batch_full(100, 1, 32, 4096, 950, 1, true, &config),
+            Some(BatchFullStatus::BufferFull)
+        );
+        assert_eq!(
+            is_the_batch_full(100, 1, 32, 4096, 100, 2, false, &config),
+            Some(BatchFullStatus::Full)
+        );
+        assert_eq!(
+            is_the_batch_full(100, 1, 32, 4096, 450, 1, false, &config),
+            Some(BatchFullStatus::BufferFull)
+        );
+        assert_eq!(
+            is_the_batch_full(100, 1, 2, 4096, 100, 2, true, &config),
+            Some(BatchFullStatus::Full)
+        );
+        assert_eq!(
+            is_the_batch_full(100, 1, 32, 250, 200, 1, true, &config),
+            Some(BatchFullStatus::BufferFull)
+        );
+    }
+
+    struct NamedSendMessageBackHook;
+
+    impl SendMessageBackHook for NamedSendMessageBackHook {
+        fn hook_name(&self) -> String {
+            "named".to_string()
+        }
+
+        fn execute_send_message_back(
+            &self,
+            _msg_list: &[MessageExt],
+            _broker_name: &str,
+            _broker_addr: &str,
+        ) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn send_message_back_hook_is_replaced() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let message_store_config = MessageStoreConfig {
+            store_path_root_dir: temp_dir.path().to_string_lossy().to_string().into(),
+            ..MessageStoreConfig::default()
+        };
+        let store = DefaultMessageStore::new(
+            ArcMut::new(message_store_config),
+            ArcMut::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        );
+        assert!(store.get_send_message_back_hook().is_none());
+
+        store.set_send_message_back_hook(Arc::new(NamedSendMessageBackHook));
+        let hook = store.get_send_message_back_hook().unwrap();
+        assert_eq!(hook.hook_name(), "named");
+        assert!(hook.execute_send_message_back(&[], "broker-a", "127.0.0.1:10911"));
+    }
+
+    #[test]
+    fn lock_time_does_not_underflow_when_the_lock_began_later() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let message_store_config = MessageStoreConfig {
+            store_path_root_dir: temp_dir.path().to_string_lossy().to_string().into(),
+            ..MessageStoreConfig::default()
+        };
+        let store = DefaultMessageStore::new(
+            ArcMut::new(message_store_config),
+            ArcMut::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        );
+        assert_eq!(store.lock_time_mills(), 0);
+
+        let begin_time_in_lock = store.commit_log.begin_time_in_lock();
+        begin_time_in_lock.store(get_current_millis() + 60_000, Ordering::Release);
+        assert_eq!(store.lock_time_mills(), 0);
+        begin_time_in_lock.store(get_current_millis() - 60_000, Ordering::Release);
+        assert!(store.lock_time_mills() >= 60_000);
+    }
+
+    #[test]
+    fn batch_full_with_zero_transfer_count_does_not_panic() {
+        let config = ArcMut::new(MessageStoreConfig {
+            max_transfer_count_on_message_in_disk: 0,
+            ..MessageStoreConfig::default()
+        });
+        assert_eq!(
+            is_the_batch_full(10, 1, 32, 4096, 10, 1, false, &config),
+            Some(BatchFullStatus::Full)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_puts_get_distinct_offsets() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let message_store_config = MessageStoreConfig {
+            store_path_root_dir: temp_dir.path().to_string_lossy().to_string().into(),
+            mapped_file_size_commit_log: 64 * 1024,
+            ..MessageStoreConfig::default()
+        };
+        let store = ArcMut::new(DefaultMessageStore::new(
+            ArcMut::new(message_store_config),
+            ArcMut::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        ));
+        store.mut_from_ref().commit_log.start();
+
+        let mut handles = Vec::new();
+        for index in 0..96 {
+            let mut store = store.clone();
+            handles.push(tokio::spawn(async move {
+                let topic = format!("TopicTest{}", index % 3);
+                let queue_id = (index / 3) % 4;
+                let mut msg = MessageExtBrokerInner::default();
+                msg.message_ext_inner.message.topic = topic.as_str().into();
+                msg.message_ext_inner.message.body = Some(bytes::Bytes::from(vec![b'a'; 1024]));
+                msg.message_ext_inner.queue_id = queue_id;
+                (topic, queue_id, store.put_message(msg).await)
+            }));
+        }
+        let mut logics_offsets: HashMap<(String, i32), Vec<i64>> = HashMap::new();
+        let mut wrote_offsets = Vec::new();
+        for handle in handles {
+            let (topic, queue_id, result) = handle.await.unwrap();
+            assert_eq!(result.put_message_status(), PutMessageStatus::PutOk);
+            let append_result = result.append_message_result().unwrap();
+            logics_offsets
+                .entry((topic, queue_id))
+                .or_default()
+                .push(append_result.logics_offset);
+            wrote_offsets.push(append_result.wrote_offset);
+        }
+        // 3 topics with 4 queues each, every queue got 8 messages at offsets 0..8
+        assert_eq!(logics_offsets.len(), 12);
+        for offsets in logics_offsets.values_mut() {
+            offsets.sort_unstable();
+            assert_eq!(*offsets, (0..8).collect::<Vec<i64>>());
+        }
+        wrote_offsets.sort_unstable();
+        wrote_offsets.dedup();
+        assert_eq!(wrote_offsets.len(), 96);
+    }
+
+    #[tokio::test]
+    async fn dropped_put_still_increases_queue_offset() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let message_store_config = MessageStoreConfig {
+            store_path_root_dir: temp_dir.path().to_string_lossy().to_string().into(),
+            mapped_file_size_commit_log: 64 * 1024,
+            ..MessageStoreConfig::default()
+        };
+        let mut store = DefaultMessageStore::new(
+            ArcMut::new(message_store_config),
+            ArcMut::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        );
+        store.commit_log.start();
+        let message = || {
+            let mut msg = MessageExtBrokerInner::default();
+            msg.message_ext_inner.message.topic = "TopicTest".into();
+            msg.message_ext_inner.message.body = Some(bytes::Bytes::from_static(b"body"));
+            msg
+        };
+
+        // polled once, the message is queued for the appender and the future is dropped
+        let dropped = tokio::select! {
+            biased;
+            _ = store.put_message(message()) => false,
+            _ = std::future::ready(()) => true,
+        };
+        assert!(dropped);
+
+        let result = store.put_message(message()).await;
+        assert_eq!(result.put_message_status(), PutMessageStatus::PutOk);
+        assert_eq!(result.append_message_result().unwrap().logics_offset, 1);
+    }
+
+    #[tokio::test]
+    async fn get_message_falls_back_to_tiered_store() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let message_store_config = MessageStoreConfig {
+            store_path_root_dir: temp_dir.path().to_string_lossy().to_string().into(),
+            tiered_store_enable: true,
+            ..MessageStoreConfig::default()
+        };
+        let store = DefaultMessageStore::new(
+            ArcMut::new(message_store_config),
+            ArcMut::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            None,
+            false,
+        );
+        let tiered_message_store = store.get_tiered_message_store().unwrap().clone();
+        assert!(tiered_message_store.load());
+
+        let topic = CheetahString::from_static_str("TopicTest");
+        tiered_message_store
+            .upload_segment(
+                TieredFileType::CommitLog,
+                None,
+                0,
+                0,
+                bytes::Bytes::from_static(b"first!second"),
+            )
+            .await
+            .unwrap();
+        let mut units = bytes::BytesMut::new();
+        for (offset, size) in [(0i64, 6i32), (6, 6)] {
+            units.put_i64(offset);
+            units.put_i32(size);
+            units.put_i64(0);
+        }
+        tiered_message_store
+            .upload_segment(
+                TieredFileType::ConsumeQueue,
+                Some(topic.clone()),
+                0,
+                0,
+                units.freeze(),
+            )
+            .await
+            .unwrap();
+
+        let mut get_result = GetMessageResult::new();
+        let next_offset = store
+            .get_message_from_tiered_store(
+                &tiered_message_store,
+                &topic,
+                0,
+                0,
+                5,
+                32,
+                1024,
+                None,
+                &mut get_result,
+            )
+            .await;
+        assert_eq!(next_offset, Some(2));
+        assert_eq!(get_result.message_count(), 2);
+        assert_eq!(get_result.buffer_total_size(), 12);
+
+        let mut get_result = GetMessageResult::new();
+        let next_offset = store
+            .get_message_from_tiered_store(
+                &tiered_message_store,
+                &topic,
+                1,
+                0,
+                5,
+                32,
+                1024,
+                None,
+                &mut get_result,
+            )
+            .await;
+        assert_eq!(next_offset, None);
+        assert_eq!(get_result.message_count(), 0);
+    }
+
+    #[test]
+    fn search_offset_by_time_respects_boundary() {
+        // offsets 10..15 stored at 100, 200, 200, 200, 300
+        let store_times = [100, 200, 200, 200, 300];
+        let store_time_at = |offset: i64| store_times[(offset - 10) as usize];
+
+        assert_eq!(
+            search_offset_by_time(10, 15, 200, BoundaryType::Lower, store_time_at),
+            11
+        );
+        assert_eq!(
+            search_offset_by_time(10, 15, 200, BoundaryType::Upper, store_time_at),
+            13
+        );
+        assert_eq!(
+            search_offset_by_time(10, 15, 250, BoundaryType::Lower, store_time_at),
+            14
+        );
+        assert_eq!(
+            search_offset_by_time(10, 15, 250, BoundaryType::Upper, store_time_at),
+            13
+        );
+        assert_eq!(
+            search_offset_by_time(10, 15, 50, BoundaryType::Upper, store_time_at),
+            10
+        );
+        assert_eq!(
+            search_offset_by_time(10, 15, 400, BoundaryType::Lower, store_time_at),
+            15
+        );
+    }
+
+    #[test]
+    fn search_offset_by_time_skips_cleaned_messages() {
+        let store_times = [-1, -1, 100, 200];
+        let store_time_at = |offset: i64| store_times[offset as usize];
+        assert_eq!(
+            search_offset_by_time(0, 4, 0, BoundaryType::Lower, store_time_at),
+            2
+        );
+    }
+}
@@ -26,6 +26,7 @@ use rocketmq_rust::ArcMut;
 use crate::base::dispatch_request::DispatchRequest;
 use crate::base::swappable::Swappable;
 use crate::consume_queue::consume_queue_ext::CqExtUnit;
+use crate::consume_queue::mapped_file_queue::MappedFileQueue;
 use crate::filter::MessageFilter;
 use crate::queue::consume_queue_ext::ConsumeQueueExt;
 use crate::queue::queue_offset_operator::QueueOffsetOperator;
@@ -34,6 +35,7 @@ mod batch_consume_queue;
 pub mod build_consume_queue;
 mod consume_queue_ext;
 pub mod local_file_consume_queue_store;
+pub(crate) mod multi_dispatch_utils;
 mod queue_offset_operator;
 pub mod single_consume_queue;
 
@@ -734,6 +736,9 @@ pub trait ConsumeQueueTrait: Send + Sync + FileQueueLifeCycle {
     /// The consume queue type as `CQType`.
     fn get_cq_type(&self) -> CQType;
 
+    /// Returns the mapped files holding the units of the consume queue.
+    fn get_mapped_file_queue(&self) -> &MappedFileQueue;
+
     /// Returns the total size of the consume queue.
     ///
     /// This method calculates the total size of the consume queue in bytes, which includes the size
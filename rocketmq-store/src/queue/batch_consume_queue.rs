@@ -24,6 +24,7 @@ use cheetah_string::CheetahString;
 use rocketmq_common::common::attribute::cq_type::CQType;
 use rocketmq_common::common::boundary_type::BoundaryType;
 use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+use rocketmq_rust::ArcMut;
 use tracing::info;
 
 use crate::base::dispatch_request::DispatchRequest;
@@ -60,7 +61,7 @@ const INVALID_POS: i32 = -1;
 /// CommitLog Physical Offset(8) + Body Size(4) + Tag HashCode(8) + Store time(8) +
 /// msgBaseOffset(8) + batchSize(2) + compactedOffset(4) + reserved(4)= 46 Bytes
 pub struct BatchConsumeQueue {
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     mapped_file_queue: MappedFileQueue,
     //message_store: Arc<RwLock<dyn MessageStore>>,
     topic: CheetahString,
@@ -84,7 +85,7 @@ impl BatchConsumeQueue {
         store_path: CheetahString,
         mapped_file_size: usize,
         subfolder: Option<CheetahString>,
-        message_store_config: Arc<MessageStoreConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
     ) -> Self {
         let commit_log_size = message_store_config.mapped_file_size_commit_log;
 
@@ -262,7 +263,11 @@ impl ConsumeQueueTrait for BatchConsumeQueue {
     }
 
     fn get_cq_type(&self) -> CQType {
-        todo!()
+        CQType::BatchCQ
+    }
+
+    fn get_mapped_file_queue(&self) -> &MappedFileQueue {
+        &self.mapped_file_queue
     }
 
     fn get_total_size(&self) -> i64 {
@@ -90,6 +90,10 @@ impl ConsumeQueueExt {
         unimplemented!()
     }
 
+    pub fn flush(&self, flush_least_pages: i32) -> bool {
+        self.mapped_file_queue.flush(flush_least_pages)
+    }
+
     pub fn destroy(&mut self) {
         self.mapped_file_queue.destroy();
     }
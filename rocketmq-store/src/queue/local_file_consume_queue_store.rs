@@ -26,15 +26,21 @@ use cheetah_string::CheetahString;
 use rocketmq_common::common::attribute::cq_type::CQType;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::common::message::message_decoder::message_properties_to_string;
 use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_common::common::mix_all;
 use rocketmq_common::utils::queue_type_utils::QueueTypeUtils;
 use rocketmq_rust::ArcMut;
+use tracing::error;
 use tracing::info;
 
 use crate::base::dispatch_request::DispatchRequest;
 use crate::base::store_checkpoint::StoreCheckpoint;
 use crate::config::message_store_config::MessageStoreConfig;
 use crate::queue::batch_consume_queue::BatchConsumeQueue;
+use crate::queue::multi_dispatch_utils;
 use crate::queue::queue_offset_operator::QueueOffsetOperator;
 use crate::queue::single_consume_queue::ConsumeQueue;
 use crate::queue::ArcConsumeQueue;
@@ -56,8 +62,8 @@ pub struct ConsumeQueueStore {
 
 struct Inner {
     // commit_log: Arc<Mutex<CommitLog>>,
-    pub(crate) message_store_config: Arc<MessageStoreConfig>,
-    pub(crate) broker_config: Arc<BrokerConfig>,
+    pub(crate) message_store_config: ArcMut<MessageStoreConfig>,
+    pub(crate) broker_config: ArcMut<BrokerConfig>,
     pub(crate) queue_offset_operator: QueueOffsetOperator,
     pub(crate) consume_queue_table: Arc<ConsumeQueueTable>,
 }
@@ -74,8 +80,8 @@ impl Inner {
 
 impl ConsumeQueueStore {
     pub fn new(
-        message_store_config: Arc<MessageStoreConfig>,
-        broker_config: Arc<BrokerConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
         running_flags: Arc<RunningFlags>,
         store_checkpoint: Arc<StoreCheckpoint>,
@@ -95,6 +101,122 @@ impl ConsumeQueueStore {
     }
 }
 
+impl ConsumeQueueStore {
+    /// Number of light message queues created on this store.
+    pub fn get_lmq_queue_num(&self) -> usize {
+        self.inner
+            .consume_queue_table
+            .lock()
+            .keys()
+            .filter(|topic| mix_all::is_lmq(Some(topic.as_str())))
+            .count()
+    }
+
+    fn multi_dispatch_queues(msg: &MessageExtBrokerInner) -> Option<Vec<CheetahString>> {
+        let queues = msg.get_property(&CheetahString::from_static_str(
+            MessageConst::PROPERTY_INNER_MULTI_DISPATCH,
+        ))?;
+        if queues.trim().is_empty() {
+            return None;
+        }
+        Some(
+            queues
+                .split(mix_all::MULTI_DISPATCH_QUEUE_SPLITTER)
+                .map(CheetahString::from)
+                .collect(),
+        )
+    }
+
+    /// Records the offset every light message queue assigns to the message, so the commit log
+    /// entry can be dispatched to them once it is stored.
+    fn wrap_multi_dispatch(&self, msg: &mut MessageExtBrokerInner) {
+        let Some(queues) = Self::multi_dispatch_queues(msg) else {
+            return;
+        };
+        let config = &self.inner.message_store_config;
+        let queue_offsets = queues
+            .iter()
+            .map(|queue| {
+                let key = multi_dispatch_utils::queue_key(config, queue, msg.queue_id());
+                if config.enable_lmq && mix_all::is_lmq(Some(queue.as_str())) {
+                    self.get_lmq_queue_offset(&key)
+                } else {
+                    -1
+                }
+            })
+            .map(|offset| offset.to_string())
+            .collect::<Vec<_>>()
+            .join(mix_all::MULTI_DISPATCH_QUEUE_SPLITTER);
+        msg.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET),
+            CheetahString::from_string(queue_offsets),
+        );
+        msg.properties_string =
+            message_properties_to_string(msg.message_ext_inner.message.properties());
+    }
+
+    fn update_multi_queue_offset(&self, msg: &MessageExtBrokerInner) {
+        let Some(queues) = Self::multi_dispatch_queues(msg) else {
+            return;
+        };
+        let config = &self.inner.message_store_config;
+        for queue in queues {
+            if config.enable_lmq && mix_all::is_lmq(Some(queue.as_str())) {
+                let key = multi_dispatch_utils::queue_key(config, &queue, msg.queue_id());
+                self.inner
+                    .queue_offset_operator
+                    .increase_lmq_offset(&key, 1);
+            }
+        }
+    }
+
+    fn multi_dispatch_lmq_queue(&self, request: &DispatchRequest) {
+        let properties = request.properties_map.as_ref().unwrap();
+        let queues = properties
+            .get(MessageConst::PROPERTY_INNER_MULTI_DISPATCH)
+            .unwrap()
+            .split(mix_all::MULTI_DISPATCH_QUEUE_SPLITTER)
+            .collect::<Vec<_>>();
+        let queue_offsets = properties
+            .get(MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET)
+            .unwrap()
+            .split(mix_all::MULTI_DISPATCH_QUEUE_SPLITTER)
+            .collect::<Vec<_>>();
+        if queues.len() != queue_offsets.len() {
+            error!(
+                "[bug] queues.length!=queueOffsets.length {} {}",
+                request.topic, request.commit_log_offset
+            );
+            return;
+        }
+        for (queue_name, queue_offset) in queues.into_iter().zip(queue_offsets) {
+            let Some(queue_offset) = queue_offset
+                .parse::<i64>()
+                .ok()
+                .filter(|offset| *offset >= 0)
+            else {
+                continue;
+            };
+            let mut queue_id = request.queue_id;
+            if self.inner.message_store_config.enable_lmq && mix_all::is_lmq(Some(queue_name)) {
+                queue_id = mix_all::LMQ_QUEUE_ID as i32;
+            }
+            let lmq_request = DispatchRequest {
+                topic: CheetahString::from(queue_name),
+                queue_id,
+                commit_log_offset: request.commit_log_offset,
+                msg_size: request.msg_size,
+                tags_code: request.tags_code,
+                store_timestamp: request.store_timestamp,
+                consume_queue_offset: queue_offset,
+                ..DispatchRequest::default()
+            };
+            let mut cq = self.find_or_create_consume_queue(&lmq_request.topic, queue_id);
+            self.put_message_position_info_wrapper_with_cq(&mut **cq.as_mut(), &lmq_request);
+        }
+    }
+}
+
 #[allow(unused_variables)]
 impl ConsumeQueueStoreTrait for ConsumeQueueStore {
     fn start(&self) {
@@ -203,7 +325,12 @@ impl ConsumeQueueStoreTrait for ConsumeQueueStore {
     fn put_message_position_info_wrapper(&self, request: &DispatchRequest) {
         let mut cq = self.find_or_create_consume_queue(request.topic.as_ref(), request.queue_id);
         self.put_message_position_info_wrapper_with_cq(&mut **cq.as_mut(), request);
-        // println!("put_message_position_info_wrapper-----{}", request.topic)
+        if multi_dispatch_utils::check_multi_dispatch_queue(
+            &self.inner.message_store_config,
+            request,
+        ) {
+            self.multi_dispatch_lmq_queue(request);
+        }
     }
 
     fn put_message_position_info_wrapper_with_cq(
@@ -232,19 +359,33 @@ impl ConsumeQueueStoreTrait for ConsumeQueueStore {
     fn increase_queue_offset(&self, msg: &MessageExtBrokerInner, message_num: i16) {
         let consume_queue = self.find_or_create_consume_queue(msg.get_topic(), msg.queue_id());
         consume_queue.increase_queue_offset(&self.inner.queue_offset_operator, msg, message_num);
+        if multi_dispatch_utils::is_need_handle_multi_dispatch(
+            &self.inner.message_store_config,
+            msg.get_topic(),
+        ) {
+            self.update_multi_queue_offset(msg);
+        }
     }
 
     fn assign_queue_offset(&self, msg: &mut MessageExtBrokerInner) {
         let consume_queue = self.find_or_create_consume_queue(msg.get_topic(), msg.queue_id());
         consume_queue.assign_queue_offset(&self.inner.queue_offset_operator, msg);
+        if multi_dispatch_utils::is_need_handle_multi_dispatch(
+            &self.inner.message_store_config,
+            msg.get_topic(),
+        ) {
+            self.wrap_multi_dispatch(msg);
+        }
     }
 
     fn increase_lmq_offset(&mut self, queue_key: &CheetahString, message_num: i16) {
-        todo!()
+        self.inner
+            .queue_offset_operator
+            .increase_lmq_offset(queue_key, message_num);
     }
 
     fn get_lmq_queue_offset(&self, queue_key: &CheetahString) -> i64 {
-        todo!()
+        self.inner.queue_offset_operator.get_lmq_offset(queue_key)
     }
 
     fn recover_offset_table(&mut self, min_phy_offset: i64) {
@@ -387,7 +528,8 @@ impl ConsumeQueueStoreTrait for ConsumeQueueStore {
     }
 
     fn get_max_offset_in_queue(&self, topic: &CheetahString, queue_id: i32) -> i64 {
-        todo!()
+        let queue = self.find_or_create_consume_queue(topic, queue_id);
+        queue.get_max_offset_in_queue()
     }
 
     fn get_consume_queue_table(&self) -> Arc<ConsumeQueueTable> {
@@ -545,3 +687,67 @@ impl ConsumeQueueStore {
         self.find_or_create_consume_queue(topic, queue_id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_store(root: &Path) -> ConsumeQueueStore {
+        let message_store_config = MessageStoreConfig {
+            store_path_root_dir: root.to_string_lossy().to_string().into(),
+            enable_lmq: true,
+            enable_multi_dispatch: true,
+            ..MessageStoreConfig::default()
+        };
+        ConsumeQueueStore::new(
+            ArcMut::new(message_store_config),
+            ArcMut::new(BrokerConfig::default()),
+            Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            Arc::new(RunningFlags::new()),
+            Arc::new(StoreCheckpoint::new(root.join("checkpoint")).unwrap()),
+        )
+    }
+
+    #[test]
+    fn multi_dispatch_assigns_and_dispatches_lmq_offsets() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = new_store(dir.path());
+        let mut msg = MessageExtBrokerInner::default();
+        msg.message_ext_inner.message.topic = "TopicTest".into();
+        msg.message_ext_inner.queue_id = 2;
+        msg.put_property(
+            MessageConst::PROPERTY_INNER_MULTI_DISPATCH.into(),
+            "%LMQ%client1,%LMQ%client2".into(),
+        );
+
+        for expected in ["0,0", "1,1"] {
+            store.assign_queue_offset(&mut msg);
+            let offsets = msg.get_property(&MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET.into());
+            assert_eq!(offsets.unwrap().as_str(), expected);
+            assert!(msg.properties_string.contains(expected));
+            store.increase_queue_offset(&msg, 1);
+        }
+        assert_eq!(store.get_lmq_queue_offset(&"%LMQ%client1-0".into()), 2);
+
+        let request = DispatchRequest {
+            topic: "TopicTest".into(),
+            queue_id: 2,
+            commit_log_offset: 0,
+            msg_size: 100,
+            properties_map: Some(HashMap::from([
+                (
+                    MessageConst::PROPERTY_INNER_MULTI_DISPATCH.into(),
+                    "%LMQ%client1,%LMQ%client2".into(),
+                ),
+                (
+                    MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET.into(),
+                    "0,0".into(),
+                ),
+            ])),
+            ..DispatchRequest::default()
+        };
+        store.put_message_position_info_wrapper(&request);
+        assert_eq!(store.get_lmq_queue_num(), 2);
+        assert_eq!(store.get_max_offset_in_queue(&"%LMQ%client2".into(), 0), 1);
+    }
+}
@@ -0,0 +1,119 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::common::topic::TopicValidator;
+
+use crate::base::dispatch_request::DispatchRequest;
+use crate::config::message_store_config::MessageStoreConfig;
+
+/// Key of the offset table entry of a light message queue, which always uses queue 0.
+pub fn lmq_queue_key(queue_name: &str) -> CheetahString {
+    CheetahString::from_string(format!("{}-{}", queue_name, mix_all::LMQ_QUEUE_ID))
+}
+
+/// Key of the offset table entry of a queue a message of `queue_id` is dispatched to.
+pub fn queue_key(
+    message_store_config: &MessageStoreConfig,
+    queue_name: &str,
+    queue_id: i32,
+) -> CheetahString {
+    if message_store_config.enable_lmq && mix_all::is_lmq(Some(queue_name)) {
+        return lmq_queue_key(queue_name);
+    }
+    CheetahString::from_string(format!("{}-{}", queue_name, queue_id))
+}
+
+pub fn is_need_handle_multi_dispatch(
+    message_store_config: &MessageStoreConfig,
+    topic: &str,
+) -> bool {
+    message_store_config.enable_multi_dispatch
+        && !topic.starts_with(mix_all::RETRY_GROUP_TOPIC_PREFIX)
+        && !topic.starts_with(TopicValidator::SYSTEM_TOPIC_PREFIX)
+        && topic != TopicValidator::RMQ_SYS_SCHEDULE_TOPIC
+}
+
+/// Checks the dispatched message carries both the extra queues and their assigned offsets.
+pub fn check_multi_dispatch_queue(
+    message_store_config: &MessageStoreConfig,
+    dispatch_request: &DispatchRequest,
+) -> bool {
+    if !is_need_handle_multi_dispatch(message_store_config, dispatch_request.topic.as_str()) {
+        return false;
+    }
+    let Some(properties) = dispatch_request.properties_map.as_ref() else {
+        return false;
+    };
+    let not_blank = |name: &str| {
+        properties
+            .get(name)
+            .is_some_and(|value| !value.trim().is_empty())
+    };
+    not_blank(MessageConst::PROPERTY_INNER_MULTI_DISPATCH)
+        && not_blank(MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn queue_key_pins_lmq_to_queue_zero() {
+        let mut config = MessageStoreConfig::default();
+        assert_eq!(
+            queue_key(&config, "%LMQ%client1", 3).as_str(),
+            "%LMQ%client1-3"
+        );
+        config.enable_lmq = true;
+        assert_eq!(
+            queue_key(&config, "%LMQ%client1", 3).as_str(),
+            "%LMQ%client1-0"
+        );
+        assert_eq!(queue_key(&config, "TopicTest", 3).as_str(), "TopicTest-3");
+    }
+
+    #[test]
+    fn check_multi_dispatch_queue_needs_both_properties() {
+        let config = MessageStoreConfig {
+            enable_multi_dispatch: true,
+            ..MessageStoreConfig::default()
+        };
+        let mut request = DispatchRequest {
+            topic: "TopicTest".into(),
+            properties_map: Some(HashMap::from([(
+                MessageConst::PROPERTY_INNER_MULTI_DISPATCH.into(),
+                "%LMQ%a,%LMQ%b".into(),
+            )])),
+            ..DispatchRequest::default()
+        };
+        assert!(!check_multi_dispatch_queue(&config, &request));
+
+        request.properties_map.as_mut().unwrap().insert(
+            MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET.into(),
+            "0,4".into(),
+        );
+        assert!(check_multi_dispatch_queue(&config, &request));
+
+        request.topic = TopicValidator::RMQ_SYS_SCHEDULE_TOPIC.into();
+        assert!(!check_multi_dispatch_queue(&config, &request));
+    }
+}
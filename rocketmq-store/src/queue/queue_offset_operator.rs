@@ -19,6 +19,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use cheetah_string::CheetahString;
+use rocketmq_common::common::mix_all;
 use tracing::info;
 
 pub struct QueueOffsetOperator {
@@ -129,7 +130,7 @@ impl QueueOffsetOperator {
     pub fn set_lmq_topic_queue_table(&self, lmq_topic_queue_table: HashMap<CheetahString, i64>) {
         let mut table = HashMap::new();
         for (key, value) in lmq_topic_queue_table.iter() {
-            if key.contains("lmq") {
+            if mix_all::is_lmq(Some(key.as_str())) {
                 table.insert(key.clone(), *value);
             }
         }
@@ -27,6 +27,7 @@ use cheetah_string::CheetahString;
 use rocketmq_common::common::attribute::cq_type::CQType;
 use rocketmq_common::common::boundary_type::BoundaryType;
 use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+use rocketmq_rust::ArcMut;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -68,7 +69,7 @@ pub const MSG_TAG_OFFSET_INDEX: i32 = 12;
 /// 20 Bytes
 #[derive(Clone)]
 pub struct ConsumeQueue {
-    message_store_config: Arc<MessageStoreConfig>,
+    message_store_config: ArcMut<MessageStoreConfig>,
     mapped_file_queue: MappedFileQueue,
     topic: CheetahString,
     queue_id: i32,
@@ -87,7 +88,7 @@ impl ConsumeQueue {
         queue_id: i32,
         store_path: CheetahString,
         mapped_file_size: i32,
-        message_store_config: Arc<MessageStoreConfig>,
+        message_store_config: ArcMut<MessageStoreConfig>,
         running_flags: Arc<RunningFlags>,
         store_checkpoint: Arc<StoreCheckpoint>,
     ) -> Self {
@@ -445,7 +446,11 @@ impl FileQueueLifeCycle for ConsumeQueue {
     }
 
     fn flush(&self, flush_least_pages: i32) -> bool {
-        todo!()
+        let mut result = self.mapped_file_queue.flush(flush_least_pages);
+        if let Some(consume_queue_ext) = self.consume_queue_ext.as_ref() {
+            result &= consume_queue_ext.flush(flush_least_pages);
+        }
+        result
     }
 
     fn destroy(&mut self) {
@@ -504,10 +509,7 @@ impl ConsumeQueueTrait for ConsumeQueue {
     }
 
     fn get(&self, index: i64) -> Option<CqUnit> {
-        match self.iterate_from(index) {
-            None => None,
-            Some(value) => None,
-        }
+        self.iterate_from(index).and_then(|mut iter| iter.next())
     }
 
     fn get_cq_unit_and_store_time(&self, index: i64) -> Option<(CqUnit, i64)> {
@@ -566,6 +568,10 @@ impl ConsumeQueueTrait for ConsumeQueue {
         CQType::SimpleCQ
     }
 
+    fn get_mapped_file_queue(&self) -> &MappedFileQueue {
+        &self.mapped_file_queue
+    }
+
     fn get_total_size(&self) -> i64 {
         todo!()
     }
@@ -747,7 +753,8 @@ impl ConsumeQueueTrait for ConsumeQueue {
                 if self.message_store_config.broker_role == BrokerRole::Slave
                     || self.message_store_config.enable_dledger_commit_log
                 {
-                    unimplemented!("slave or dledger commit log not support")
+                    self.store_checkpoint
+                        .set_physic_msg_timestamp(request.store_timestamp as u64);
                 }
                 self.store_checkpoint
                     .set_logics_msg_timestamp(request.store_timestamp as u64);
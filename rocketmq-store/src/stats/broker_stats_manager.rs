@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use cheetah_string::CheetahString;
@@ -29,7 +30,10 @@ use rocketmq_common::common::statistics::statistics_kind_meta::StatisticsKindMet
 use rocketmq_common::common::statistics::statistics_manager::StatisticsManager;
 use rocketmq_common::common::stats::moment_stats_item_set::MomentStatsItemSet;
 use rocketmq_common::common::stats::stats_item_set::StatsItemSet;
+use rocketmq_common::common::stats::stats_scheduler::StatsScheduler;
 use rocketmq_common::common::stats::Stats;
+use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_rust::ArcMut;
 
 pub struct BrokerStatsManager {
     stats_table: Arc<parking_lot::RwLock<HashMap<String, StatsItemSet>>>,
@@ -40,7 +44,8 @@ pub struct BrokerStatsManager {
     account_stat_manager: StatisticsManager,
     producer_state_getter: Option<Arc<dyn StateGetter>>,
     consumer_state_getter: Option<Arc<dyn StateGetter>>,
-    broker_config: Option<Arc<BrokerConfig>>,
+    broker_config: Option<ArcMut<BrokerConfig>>,
+    scheduler: StatsScheduler,
 }
 
 impl BrokerStatsManager {
@@ -97,7 +102,7 @@ impl BrokerStatsManager {
 }
 
 impl BrokerStatsManager {
-    pub fn new(broker_config: Arc<BrokerConfig>) -> Self {
+    pub fn new(broker_config: ArcMut<BrokerConfig>) -> Self {
         let stats_table = Arc::new(parking_lot::RwLock::new(HashMap::new()));
         let enable_queue_stat = broker_config.enable_detail_stat;
         let cluster_name = broker_config
@@ -114,13 +119,14 @@ impl BrokerStatsManager {
             producer_state_getter: None,
             consumer_state_getter: None,
             broker_config: Some(broker_config),
+            scheduler: StatsScheduler::new(),
         };
         broker_stats_manager.init();
         broker_stats_manager
     }
 
     pub fn new_with_name(
-        broker_config: Arc<BrokerConfig>,
+        broker_config: ArcMut<BrokerConfig>,
         cluster_name: String,
         enable_queue_stat: bool,
     ) -> Self {
@@ -139,6 +145,7 @@ impl BrokerStatsManager {
             producer_state_getter: None,
             consumer_state_getter: None,
             broker_config: Some(broker_config),
+            scheduler: StatsScheduler::new(),
         };
         broker_stats_manager.init();
         broker_stats_manager
@@ -153,9 +160,7 @@ impl BrokerStatsManager {
             Stats::GROUP_GET_FALL_TIME.to_string(),
         )));
 
-        let enable_queue_stat = true; // replace with actual condition
-
-        if enable_queue_stat {
+        if self.enable_queue_stat {
             self.stats_table.write().insert(
                 Stats::QUEUE_PUT_NUMS.to_string(),
                 StatsItemSet::new(Stats::QUEUE_PUT_NUMS.to_string()),
@@ -299,6 +304,20 @@ impl BrokerStatsManager {
             StatsItemSet::new(Self::CHANNEL_ACTIVITY.to_string()),
         );
 
+        for item_set in self.stats_table.read().values() {
+            self.scheduler.register(item_set.clone());
+        }
+        for moment_item_set in [
+            &self.moment_stats_item_set_fall_size,
+            &self.moment_stats_item_set_fall_time,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            self.scheduler
+                .register_moment(moment_item_set.as_ref().clone());
+        }
+
         let formatter = StatisticsItemFormatter;
 
         self.account_stat_manager.set_brief_meta(vec![
@@ -432,6 +451,15 @@ impl BrokerStatsManager {
             }));
     }
 
+    /// Starts sampling and printing the stats sets, must be called inside a runtime.
+    pub fn start(&self) {
+        self.scheduler.start();
+    }
+
+    pub fn shutdown(&self) {
+        self.scheduler.shutdown();
+    }
+
     pub fn set_producer_state_getter(&mut self, state_getter: Arc<dyn StateGetter>) {
         self.producer_state_getter = Some(state_getter);
     }
@@ -461,11 +489,26 @@ impl BrokerStatsManager {
     }
 
     pub fn get_broker_puts_num_without_system_topic(&self) -> u64 {
-        0
+        self.get_broker_value(Self::BROKER_PUT_NUMS_WITHOUT_SYSTEM_TOPIC)
     }
 
     pub fn get_broker_gets_num_without_system_topic(&self) -> u64 {
-        0
+        self.get_broker_value(Self::BROKER_GET_NUMS_WITHOUT_SYSTEM_TOPIC)
+    }
+
+    fn get_broker_value(&self, stats_name: &str) -> u64 {
+        match self.stats_table.read().get(stats_name) {
+            Some(stats) => stats
+                .get_and_create_stats_item(self.cluster_name.as_str())
+                .get_value(),
+            None => 0,
+        }
+    }
+
+    fn add_value(&self, stats_name: &str, stats_key: &str, inc_value: i32, inc_times: i32) {
+        if let Some(stats) = self.stats_table.read().get(stats_name) {
+            stats.add_value(stats_key, inc_value, inc_times);
+        }
     }
 
     pub fn record_disk_fall_behind_size(
@@ -475,26 +518,188 @@ impl BrokerStatsManager {
         queue_id: i32,
         fall_behind: i64,
     ) {
+        let stats_key = format!("{}@{}@{}", queue_id, topic, group);
+        if let Some(stats) = &self.moment_stats_item_set_fall_size {
+            stats
+                .get_and_create_stats_item(stats_key)
+                .get_value()
+                .store(fall_behind, Ordering::Relaxed);
+        }
+    }
+
+    pub fn inc_topic_put_nums(&self, topic: &str, num: i32, times: i32) {
+        self.add_value(Stats::TOPIC_PUT_NUMS, topic, num, times);
+    }
+
+    pub fn inc_topic_put_size(&self, topic: &str, size: i32) {
+        self.add_value(Stats::TOPIC_PUT_SIZE, topic, size, 1);
+    }
+
+    pub fn inc_group_get_nums(&self, group: &str, topic: &str, inc_value: i32) {
+        let stats_key = build_stats_key(Some(topic), Some(group));
+        self.add_value(Stats::GROUP_GET_NUMS, &stats_key, inc_value, 1);
     }
 
-    pub fn inc_topic_put_nums(&self, topic: &str, num: i32, times: i32) {}
+    pub fn inc_group_get_size(&self, group: &str, topic: &str, inc_value: i32) {
+        let stats_key = build_stats_key(Some(topic), Some(group));
+        self.add_value(Stats::GROUP_GET_SIZE, &stats_key, inc_value, 1);
+    }
 
-    pub fn inc_topic_put_size(&self, topic: &str, size: i32) {}
+    pub fn inc_group_get_latency(&self, group: &str, topic: &str, queue_id: i32, inc_value: i32) {
+        let stats_key = format!("{}@{}@{}", queue_id, topic, group);
+        self.add_value(Stats::GROUP_GET_LATENCY, &stats_key, inc_value, 1);
+    }
 
-    pub fn inc_group_get_nums(&self, group: &str, topic: &str, inc_value: i32) {}
-    pub fn inc_group_get_size(&self, group: &str, topic: &str, inc_value: i32) {}
+    pub fn inc_group_ck_nums(&self, group: &str, topic: &str, inc_value: i32) {
+        let stats_key = build_stats_key(Some(topic), Some(group));
+        self.add_value(Self::GROUP_CK_NUMS, &stats_key, inc_value, 1);
+    }
 
-    pub fn inc_group_ck_nums(&self, group: &str, topic: &str, inc_value: i32) {}
+    pub fn inc_group_ack_nums(&self, group: &str, topic: &str, inc_value: i32) {
+        let stats_key = build_stats_key(Some(topic), Some(group));
+        self.add_value(Self::GROUP_ACK_NUMS, &stats_key, inc_value, 1);
+    }
 
-    pub fn inc_group_ack_nums(&self, group: &str, topic: &str, inc_value: i32) {}
-    pub fn inc_broker_get_nums(&self, group: &str, inc_value: i32) {}
-    pub fn inc_broker_put_nums(&self, group: &str, inc_value: i32) {}
+    pub fn inc_broker_get_nums(&self, topic: &str, inc_value: i32) {
+        self.add_value(
+            Stats::BROKER_GET_NUMS,
+            self.cluster_name.as_str(),
+            inc_value,
+            1,
+        );
+        if !TopicValidator::is_system_topic(topic) {
+            self.add_value(
+                Self::BROKER_GET_NUMS_WITHOUT_SYSTEM_TOPIC,
+                self.cluster_name.as_str(),
+                inc_value,
+                1,
+            );
+        }
+    }
 
-    pub fn on_topic_deleted(&self, topic: &CheetahString) {}
+    pub fn inc_broker_put_nums(&self, topic: &str, inc_value: i32) {
+        self.add_value(
+            Stats::BROKER_PUT_NUMS,
+            self.cluster_name.as_str(),
+            inc_value,
+            1,
+        );
+        if !TopicValidator::is_system_topic(topic) {
+            self.add_value(
+                Self::BROKER_PUT_NUMS_WITHOUT_SYSTEM_TOPIC,
+                self.cluster_name.as_str(),
+                inc_value,
+                1,
+            );
+        }
+    }
+
+    pub fn inc_channel_connect_num(&self) {
+        self.add_value(Self::CHANNEL_ACTIVITY, Self::CHANNEL_ACTIVITY_CONNECT, 1, 1);
+    }
 
-    pub fn inc_queue_put_nums(&self, topic: &str, queue_id: i32, num: i32, times: i32) {}
-    pub fn inc_queue_put_size(&self, topic: &str, queue_id: i32, size: i32) {}
-    pub fn inc_topic_put_latency(&self, topic: &str, queue_id: i32, inc_value: i32) {}
+    pub fn inc_channel_close_num(&self) {
+        self.add_value(Self::CHANNEL_ACTIVITY, Self::CHANNEL_ACTIVITY_CLOSE, 1, 1);
+    }
+
+    pub fn inc_channel_exception_num(&self) {
+        self.add_value(
+            Self::CHANNEL_ACTIVITY,
+            Self::CHANNEL_ACTIVITY_EXCEPTION,
+            1,
+            1,
+        );
+    }
+
+    pub fn inc_channel_idle_num(&self) {
+        self.add_value(Self::CHANNEL_ACTIVITY, Self::CHANNEL_ACTIVITY_IDLE, 1, 1);
+    }
+
+    pub fn inc_send_back_nums(&self, group: &str, topic: &str) {
+        let stats_key = build_stats_key(Some(topic), Some(group));
+        self.add_value(Stats::SNDBCK_PUT_NUMS, &stats_key, 1, 1);
+    }
+
+    pub fn inc_dlq_stat_value(
+        &self,
+        key: &str,
+        owner: &str,
+        group: &str,
+        topic: &str,
+        type_: &str,
+        inc_value: i32,
+    ) {
+        let stats_key = build_commercial_stats_key(owner, topic, group, type_);
+        self.add_value(key, &stats_key, inc_value, 1);
+    }
+
+    pub fn on_topic_deleted(&self, topic: &CheetahString) {
+        let stats_table = self.stats_table.read();
+        for stats_name in [Stats::TOPIC_PUT_NUMS, Stats::TOPIC_PUT_SIZE] {
+            if let Some(stats) = stats_table.get(stats_name) {
+                stats.del_value(topic);
+            }
+        }
+        for stats_name in [
+            Stats::QUEUE_PUT_NUMS,
+            Stats::QUEUE_PUT_SIZE,
+            Stats::GROUP_GET_NUMS,
+            Stats::GROUP_GET_SIZE,
+            Stats::SNDBCK_PUT_NUMS,
+        ] {
+            if let Some(stats) = stats_table.get(stats_name) {
+                stats.del_value_by_prefix_key(topic, "@");
+            }
+        }
+        if let Some(stats) = stats_table.get(Stats::GROUP_GET_LATENCY) {
+            stats.del_value_by_infix_key(topic, "@");
+        }
+        if let Some(stats) = &self.moment_stats_item_set_fall_size {
+            stats.del_value_by_infix_key(topic, "@");
+        }
+        if let Some(stats) = &self.moment_stats_item_set_fall_time {
+            stats.del_value_by_infix_key(topic, "@");
+        }
+    }
+
+    pub fn on_group_deleted(&self, group: &CheetahString) {
+        let stats_table = self.stats_table.read();
+        for stats_name in [
+            Stats::GROUP_GET_NUMS,
+            Stats::GROUP_GET_SIZE,
+            Stats::SNDBCK_PUT_NUMS,
+            Stats::GROUP_GET_LATENCY,
+        ] {
+            if let Some(stats) = stats_table.get(stats_name) {
+                stats.del_value_by_suffix_key(group, "@");
+            }
+        }
+        if let Some(stats) = &self.moment_stats_item_set_fall_size {
+            stats.del_value_by_suffix_key(group, "@");
+        }
+        if let Some(stats) = &self.moment_stats_item_set_fall_time {
+            stats.del_value_by_suffix_key(group, "@");
+        }
+    }
+
+    pub fn inc_queue_put_nums(&self, topic: &str, queue_id: i32, num: i32, times: i32) {
+        if self.enable_queue_stat {
+            let stats_key = build_stats_key(Some(topic), Some(queue_id.to_string().as_str()));
+            self.add_value(Stats::QUEUE_PUT_NUMS, &stats_key, num, times);
+        }
+    }
+
+    pub fn inc_queue_put_size(&self, topic: &str, queue_id: i32, size: i32) {
+        if self.enable_queue_stat {
+            let stats_key = build_stats_key(Some(topic), Some(queue_id.to_string().as_str()));
+            self.add_value(Stats::QUEUE_PUT_SIZE, &stats_key, size, 1);
+        }
+    }
+
+    pub fn inc_topic_put_latency(&self, topic: &str, queue_id: i32, inc_value: i32) {
+        let stats_key = format!("{}@{}", queue_id, topic);
+        self.add_value(Self::TOPIC_PUT_LATENCY, &stats_key, inc_value, 1);
+    }
 
     pub fn tps_group_get_nums(&self, group: &str, topic: &str) -> f64 {
         let stats_key = build_stats_key(Some(topic), Some(group));
@@ -522,7 +727,7 @@ pub fn create_statistics_kind_meta(
     item_names: Vec<&str>,
     formatter: &StatisticsItemFormatter,
     interval: u64,
-    broker_config: &Arc<BrokerConfig>,
+    broker_config: &ArcMut<BrokerConfig>,
 ) -> Arc<StatisticsKindMeta> {
     let printer = StatisticsItemPrinter::new(formatter);
     let scheduled_printer = StatisticsItemScheduledPrinter;
@@ -644,4 +849,85 @@ mod tests {
         let parts = split_account_stat_key("part1|part2|part3|part4|part5");
         assert_eq!(parts, vec!["part1", "part2", "part3", "part4", "part5"]);
     }
+
+    #[tokio::test]
+    async fn broker_put_and_get_nums_skip_system_topics() {
+        let manager = BrokerStatsManager::new(ArcMut::new(BrokerConfig::default()));
+        manager.inc_broker_put_nums("TopicTest", 3);
+        manager.inc_broker_put_nums(TopicValidator::RMQ_SYS_SCHEDULE_TOPIC, 5);
+        manager.inc_broker_get_nums("TopicTest", 2);
+
+        assert_eq!(manager.get_broker_puts_num_without_system_topic(), 3);
+        assert_eq!(manager.get_broker_gets_num_without_system_topic(), 2);
+        let stats_table = manager.get_stats_table();
+        let broker_put_nums = stats_table.read().get(Stats::BROKER_PUT_NUMS).cloned();
+        assert_eq!(
+            broker_put_nums
+                .unwrap()
+                .get_stats_item(manager.get_cluster_name())
+                .unwrap()
+                .get_value(),
+            8
+        );
+    }
+
+    #[tokio::test]
+    async fn channel_activity_is_counted_per_kind() {
+        let manager = BrokerStatsManager::new(ArcMut::new(BrokerConfig::default()));
+        manager.inc_channel_idle_num();
+        manager.inc_channel_idle_num();
+        manager.inc_channel_close_num();
+
+        let stats_table = manager.get_stats_table();
+        let channel_activity = stats_table
+            .read()
+            .get(BrokerStatsManager::CHANNEL_ACTIVITY)
+            .cloned()
+            .unwrap();
+        let value_of = |kind: &str| {
+            channel_activity
+                .get_stats_item(kind)
+                .map_or(0, |item| item.get_value())
+        };
+        assert_eq!(value_of(BrokerStatsManager::CHANNEL_ACTIVITY_IDLE), 2);
+        assert_eq!(value_of(BrokerStatsManager::CHANNEL_ACTIVITY_CLOSE), 1);
+        assert_eq!(value_of(BrokerStatsManager::CHANNEL_ACTIVITY_CONNECT), 0);
+    }
+
+    #[tokio::test]
+    async fn topic_and_group_stats_are_removed_on_deletion() {
+        let manager = BrokerStatsManager::new(ArcMut::new(BrokerConfig::default()));
+        manager.inc_topic_put_nums("TopicTest", 1, 1);
+        manager.inc_group_get_nums("GroupTest", "TopicTest", 4);
+        manager.inc_group_get_nums("OtherGroup", "OtherTopic", 4);
+        let stats_table = manager.get_stats_table();
+        let group_get_nums = stats_table
+            .read()
+            .get(Stats::GROUP_GET_NUMS)
+            .cloned()
+            .unwrap();
+        let topic_put_nums = stats_table
+            .read()
+            .get(Stats::TOPIC_PUT_NUMS)
+            .cloned()
+            .unwrap();
+        assert_eq!(
+            group_get_nums
+                .get_stats_item("TopicTest@GroupTest")
+                .unwrap()
+                .get_value(),
+            4
+        );
+
+        manager.on_topic_deleted(&CheetahString::from_static_str("TopicTest"));
+        assert!(topic_put_nums.get_stats_item("TopicTest").is_none());
+        assert!(group_get_nums
+            .get_stats_item("TopicTest@GroupTest")
+            .is_none());
+
+        manager.on_group_deleted(&CheetahString::from_static_str("OtherGroup"));
+        assert!(group_get_nums
+            .get_stats_item("OtherTopic@OtherGroup")
+            .is_none());
+    }
 }
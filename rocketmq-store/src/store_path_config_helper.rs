@@ -66,6 +66,14 @@ pub fn get_lock_file(root_dir: &str) -> String {
         .into_owned()
 }
 
+pub fn get_dledger_state_path(root_dir: &str) -> String {
+    PathBuf::from(root_dir)
+        .join("dledger")
+        .join("currentState.json")
+        .to_string_lossy()
+        .into_owned()
+}
+
 pub fn get_delay_offset_store_path(root_dir: &str) -> String {
     PathBuf::from(root_dir)
         .join("config")
@@ -74,6 +82,21 @@ pub fn get_delay_offset_store_path(root_dir: &str) -> String {
         .into_owned()
 }
 
+pub fn get_tiered_store_metadata_path(root_dir: &str) -> String {
+    PathBuf::from(root_dir)
+        .join("config")
+        .join("tieredStoreMetadata.json")
+        .to_string_lossy()
+        .into_owned()
+}
+
+pub fn get_store_path_tiered(root_dir: &str) -> String {
+    PathBuf::from(root_dir)
+        .join("tiered")
+        .to_string_lossy()
+        .into_owned()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -132,6 +155,14 @@ mod tests {
                 .to_string_lossy()
                 .into_owned()
         );
+        assert_eq!(
+            get_dledger_state_path(root_dir),
+            PathBuf::from(root_dir)
+                .join("dledger")
+                .join("currentState.json")
+                .to_string_lossy()
+                .into_owned()
+        );
         assert_eq!(
             get_delay_offset_store_path(root_dir),
             PathBuf::from(root_dir)
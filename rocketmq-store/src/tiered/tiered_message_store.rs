@@ -0,0 +1,463 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Buf;
+use bytes::Bytes;
+use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::common::attribute::cq_type::CQType;
+use rocketmq_common::TimeUtils::get_current_millis;
+use tokio::sync::Notify;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::consume_queue::mapped_file_queue::MappedFileQueue;
+use crate::log_file::mapped_file::MappedFile;
+use crate::queue::single_consume_queue::CQ_STORE_UNIT_SIZE;
+use crate::queue::ConsumeQueueTable;
+use crate::tiered::tiered_backend::TieredBackend;
+use crate::tiered::tiered_metadata::TieredFileType;
+use crate::tiered::tiered_metadata::TieredMetadataStore;
+use crate::tiered::tiered_metadata::TieredSegmentMetadata;
+
+/// Largest span of a segment read at once to serve the commit log data of several messages.
+const MAX_BATCH_READ_SIZE: i64 = 4 * 1024 * 1024;
+
+#[derive(Clone)]
+struct WatchedQueue {
+    file_type: TieredFileType,
+    topic: Option<CheetahString>,
+    queue_id: i32,
+    mapped_file_queue: MappedFileQueue,
+}
+
+/// Moves sealed commit log and consume queue segments to a [`TieredBackend`] and serves reads
+/// for offsets the local store no longer retains.
+pub struct TieredMessageStore<B> {
+    backend: Arc<B>,
+    metadata: TieredMetadataStore,
+    watched_queues: Mutex<Vec<WatchedQueue>>,
+    /// Consume queues of the local store, every simple consume queue in it is uploaded without
+    /// being registered one by one.
+    consume_queue_table: Mutex<Option<Arc<ConsumeQueueTable>>>,
+    shutdown: Arc<Notify>,
+}
+
+impl<B: TieredBackend> TieredMessageStore<B> {
+    pub fn new(backend: B, metadata_path: impl Into<String>) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            metadata: TieredMetadataStore::new(metadata_path),
+            watched_queues: Mutex::new(Vec::new()),
+            consume_queue_table: Mutex::new(None),
+            shutdown: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn load(&self) -> bool {
+        match self.metadata.load() {
+            Ok(_) => true,
+            Err(e) => {
+                error!("load tiered store metadata failed: {}", e);
+                false
+            }
+        }
+    }
+
+    pub fn metadata(&self) -> &TieredMetadataStore {
+        &self.metadata
+    }
+
+    /// Registers the commit log so its sealed segments get uploaded.
+    pub fn watch_commit_log(&self, mapped_file_queue: MappedFileQueue) {
+        self.watched_queues.lock().push(WatchedQueue {
+            file_type: TieredFileType::CommitLog,
+            topic: None,
+            queue_id: 0,
+            mapped_file_queue,
+        });
+    }
+
+    /// Registers a consume queue so its sealed segments get uploaded.
+    pub fn watch_consume_queue(
+        &self,
+        topic: CheetahString,
+        queue_id: i32,
+        mapped_file_queue: MappedFileQueue,
+    ) {
+        self.watched_queues.lock().push(WatchedQueue {
+            file_type: TieredFileType::ConsumeQueue,
+            topic: Some(topic),
+            queue_id,
+            mapped_file_queue,
+        });
+    }
+
+    /// Registers the consume queues of the local store, including the ones created later.
+    pub fn watch_consume_queue_table(&self, consume_queue_table: Arc<ConsumeQueueTable>) {
+        *self.consume_queue_table.lock() = Some(consume_queue_table);
+    }
+
+    /// The watched queues, with the consume queues found in the consume queue table.
+    fn collect_watched_queues(&self) -> Vec<WatchedQueue> {
+        let mut queues = self.watched_queues.lock().clone();
+        let consume_queue_table = self.consume_queue_table.lock().clone();
+        if let Some(consume_queue_table) = consume_queue_table {
+            for (topic, consume_queues) in consume_queue_table.lock().iter() {
+                for (queue_id, consume_queue) in consume_queues {
+                    if consume_queue.get_cq_type() != CQType::SimpleCQ
+                        || queues.iter().any(|queue| {
+                            queue.file_type == TieredFileType::ConsumeQueue
+                                && queue.topic.as_ref() == Some(topic)
+                                && queue.queue_id == *queue_id
+                        })
+                    {
+                        continue;
+                    }
+                    queues.push(WatchedQueue {
+                        file_type: TieredFileType::ConsumeQueue,
+                        topic: Some(topic.clone()),
+                        queue_id: *queue_id,
+                        mapped_file_queue: consume_queue.get_mapped_file_queue().clone(),
+                    });
+                }
+            }
+        }
+        queues
+    }
+
+    /// Uploads one sealed segment and records it, returning the stored metadata.
+    pub async fn upload_segment(
+        &self,
+        file_type: TieredFileType,
+        topic: Option<CheetahString>,
+        queue_id: i32,
+        base_offset: i64,
+        data: Bytes,
+    ) -> io::Result<TieredSegmentMetadata> {
+        let object_key = match (file_type, topic.as_ref()) {
+            (TieredFileType::CommitLog, _) => format!("commitlog/{:020}", base_offset),
+            (TieredFileType::ConsumeQueue, topic) => format!(
+                "consumequeue/{}/{}/{:020}",
+                topic.map(|topic| topic.as_str()).unwrap_or_default(),
+                queue_id,
+                base_offset
+            ),
+        };
+        let size = data.len() as u64;
+        self.backend.put_object(&object_key, data).await?;
+        let segment = TieredSegmentMetadata {
+            file_type,
+            topic,
+            queue_id,
+            base_offset,
+            size,
+            object_key,
+            upload_timestamp: get_current_millis(),
+        };
+        self.metadata.insert(segment.clone());
+        self.metadata.persist()?;
+        Ok(segment)
+    }
+
+    /// Uploads the full segments of every watched queue not copied yet, except the last one
+    /// of each queue which may still be written. Returns the number of uploaded segments.
+    pub async fn upload_sealed_segments(&self) -> io::Result<usize> {
+        let mut pending = Vec::new();
+        for queue in self.collect_watched_queues().iter() {
+            let mapped_files = queue.mapped_file_queue.get_mapped_files();
+            let mapped_files = mapped_files.read();
+            let sealed = mapped_files.len().saturating_sub(1);
+            for mapped_file in mapped_files.iter().take(sealed) {
+                let base_offset = mapped_file.get_file_from_offset() as i64;
+                if !mapped_file.is_full()
+                    || self.metadata.contains_segment(
+                        queue.file_type,
+                        queue.topic.as_ref(),
+                        queue.queue_id,
+                        base_offset,
+                    )
+                {
+                    continue;
+                }
+                let Some(data) = mapped_file.get_bytes(0, mapped_file.get_read_position() as usize)
+                else {
+                    continue;
+                };
+                pending.push((
+                    queue.file_type,
+                    queue.topic.clone(),
+                    queue.queue_id,
+                    base_offset,
+                    data,
+                ));
+            }
+        }
+
+        let uploaded = pending.len();
+        for (file_type, topic, queue_id, base_offset, data) in pending {
+            self.upload_segment(file_type, topic, queue_id, base_offset, data)
+                .await?;
+        }
+        Ok(uploaded)
+    }
+
+    /// Reads `size` bytes of the commit log at the physical `offset` from the remote tier.
+    pub async fn get_commit_log_data(&self, offset: i64, size: i32) -> io::Result<Option<Bytes>> {
+        let Some(segment) = self.metadata.find_commit_log_segment(offset) else {
+            return Ok(None);
+        };
+        if offset + size as i64 > segment.base_offset + segment.size as i64 {
+            return Ok(None);
+        }
+        let data = self
+            .backend
+            .get_object(
+                &segment.object_key,
+                (offset - segment.base_offset) as u64,
+                size as usize,
+            )
+            .await?;
+        Ok(Some(data))
+    }
+
+    /// Reads the commit log data of `units`, given as `(physical offset, size)`, in order.
+    /// Neighbouring units of one segment are fetched with a single ranged read, units the remote
+    /// tier does not hold are `None`.
+    pub async fn get_commit_log_data_batch(
+        &self,
+        units: &[(i64, i32)],
+    ) -> io::Result<Vec<Option<Bytes>>> {
+        let mut batch = Vec::with_capacity(units.len());
+        let mut index = 0;
+        while index < units.len() {
+            let (offset, size) = units[index];
+            let Some(segment) = self.metadata.find_commit_log_segment(offset) else {
+                batch.push(None);
+                index += 1;
+                continue;
+            };
+            let segment_end = segment.base_offset + segment.size as i64;
+            let (mut start, mut end) = (offset, offset + size as i64);
+            if end > segment_end {
+                batch.push(None);
+                index += 1;
+                continue;
+            }
+            let mut last = index + 1;
+            while let Some(&(next_offset, next_size)) = units.get(last) {
+                let next_end = next_offset + next_size as i64;
+                if next_offset < segment.base_offset
+                    || next_end > segment_end
+                    || end.max(next_end) - start.min(next_offset) > MAX_BATCH_READ_SIZE
+                {
+                    break;
+                }
+                start = start.min(next_offset);
+                end = end.max(next_end);
+                last += 1;
+            }
+            let data = self
+                .backend
+                .get_object(
+                    &segment.object_key,
+                    (start - segment.base_offset) as u64,
+                    (end - start) as usize,
+                )
+                .await?;
+            for &(offset, size) in &units[index..last] {
+                let position = (offset - start) as usize;
+                batch.push(Some(data.slice(position..position + size as usize)));
+            }
+            index = last;
+        }
+        Ok(batch)
+    }
+
+    /// Reads the consume queue unit at `queue_offset` as `(physical offset, size, tags code)`.
+    pub async fn get_consume_queue_unit(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        queue_offset: i64,
+    ) -> io::Result<Option<(i64, i32, i64)>> {
+        Ok(self
+            .get_consume_queue_units(topic, queue_id, queue_offset, 1)
+            .await?
+            .pop())
+    }
+
+    /// Reads up to `max_count` consume queue units from `queue_offset` on with a single read,
+    /// stopping at the end of the segment holding `queue_offset`.
+    pub async fn get_consume_queue_units(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        queue_offset: i64,
+        max_count: usize,
+    ) -> io::Result<Vec<(i64, i32, i64)>> {
+        let offset = queue_offset * CQ_STORE_UNIT_SIZE as i64;
+        let Some(segment) = self
+            .metadata
+            .find_consume_queue_segment(topic, queue_id, offset)
+        else {
+            return Ok(Vec::new());
+        };
+        let position = (offset - segment.base_offset) as u64;
+        let count =
+            ((segment.size - position) as usize / CQ_STORE_UNIT_SIZE as usize).min(max_count);
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let mut units = self
+            .backend
+            .get_object(
+                &segment.object_key,
+                position,
+                count * CQ_STORE_UNIT_SIZE as usize,
+            )
+            .await?;
+        Ok((0..count)
+            .map(|_| (units.get_i64(), units.get_i32(), units.get_i64()))
+            .collect())
+    }
+
+    /// Reads the message at `queue_offset` of a queue entirely from the remote tier.
+    pub async fn get_message(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        queue_offset: i64,
+    ) -> io::Result<Option<Bytes>> {
+        match self
+            .get_consume_queue_unit(topic, queue_id, queue_offset)
+            .await?
+        {
+            Some((physical_offset, size, _)) => {
+                self.get_commit_log_data(physical_offset, size).await
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Starts uploading sealed segments every `interval` until [`Self::shutdown`] is called.
+    pub fn start(self: Arc<Self>, interval: Duration) {
+        let shutdown = self.shutdown.clone();
+        tokio::spawn(async move {
+            info!("tiered store upload service started");
+            loop {
+                tokio::select! {
+                    _ = shutdown.notified() => break,
+                    _ = tokio::time::sleep(interval) => {}
+                }
+                match self.upload_sealed_segments().await {
+                    Ok(0) => {}
+                    Ok(uploaded) => info!("tiered store uploaded {} segments", uploaded),
+                    Err(e) => warn!("tiered store upload failed: {}", e),
+                }
+            }
+            info!("tiered store upload service end");
+        });
+    }
+
+    pub fn shutdown(&self) {
+        self.shutdown.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BufMut;
+    use bytes::BytesMut;
+
+    use super::*;
+    use crate::tiered::tiered_backend::FileTieredBackend;
+
+    #[tokio::test]
+    async fn uploads_sealed_segments_and_reads_them_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TieredMessageStore::new(
+            FileTieredBackend::new(dir.path().join("bucket")),
+            dir.path().join("tiered.json").to_string_lossy(),
+        );
+
+        let commit_log_path = dir.path().join("commitlog");
+        let mut commit_log =
+            MappedFileQueue::new(commit_log_path.to_string_lossy().to_string(), 64, None);
+        let first = commit_log.try_create_mapped_file(0).unwrap();
+        assert!(first.append_message_bytes(&Bytes::from(vec![7u8; 64])));
+        let second = commit_log.try_create_mapped_file(64).unwrap();
+        assert!(second.append_message_bytes(&Bytes::from_static(b"hot")));
+        store.watch_commit_log(commit_log);
+
+        let mut unit = BytesMut::new();
+        unit.put_i64(16);
+        unit.put_i32(8);
+        unit.put_i64(0);
+        let topic = CheetahString::from_static_str("TopicTest");
+        store
+            .upload_segment(
+                TieredFileType::ConsumeQueue,
+                Some(topic.clone()),
+                0,
+                0,
+                unit.freeze(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(store.upload_sealed_segments().await.unwrap(), 1);
+        assert_eq!(store.upload_sealed_segments().await.unwrap(), 0);
+        assert_eq!(store.metadata().get_min_commit_log_offset(), 0);
+
+        let message = store.get_message(&topic, 0, 0).await.unwrap().unwrap();
+        assert_eq!(message.as_ref(), &[7u8; 8]);
+        assert!(store.get_message(&topic, 0, 1).await.unwrap().is_none());
+        assert!(store.get_commit_log_data(64, 3).await.unwrap().is_none());
+
+        let units = store
+            .get_consume_queue_units(&topic, 0, 0, 32)
+            .await
+            .unwrap();
+        assert_eq!(units, vec![(16, 8, 0)]);
+        let batch = store
+            .get_commit_log_data_batch(&[(0, 4), (8, 4), (60, 4), (64, 3)])
+            .await
+            .unwrap();
+        assert_eq!(
+            batch,
+            vec![
+                Some(Bytes::from_static(&[7u8; 4])),
+                Some(Bytes::from_static(&[7u8; 4])),
+                Some(Bytes::from_static(&[7u8; 4])),
+                None
+            ]
+        );
+
+        let reloaded = TieredMessageStore::new(
+            FileTieredBackend::new(dir.path().join("bucket")),
+            dir.path().join("tiered.json").to_string_lossy(),
+        );
+        assert!(reloaded.load());
+        assert!(reloaded.get_commit_log_data(60, 4).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,230 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+
+use cheetah_string::CheetahString;
+use parking_lot::RwLock;
+use rocketmq_common::utils::file_utils;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TieredFileType {
+    CommitLog,
+    ConsumeQueue,
+}
+
+/// Describes one sealed segment copied to the remote tier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TieredSegmentMetadata {
+    pub file_type: TieredFileType,
+    pub topic: Option<CheetahString>,
+    pub queue_id: i32,
+    /// Physical offset of the first byte of the segment.
+    pub base_offset: i64,
+    pub size: u64,
+    pub object_key: String,
+    pub upload_timestamp: u64,
+}
+
+impl TieredSegmentMetadata {
+    pub fn contains(&self, offset: i64) -> bool {
+        offset >= self.base_offset && offset < self.base_offset + self.size as i64
+    }
+}
+
+type QueueKey = (CheetahString, i32);
+
+#[derive(Default, Serialize, Deserialize)]
+struct TieredMetadataWrapper {
+    segments: Vec<TieredSegmentMetadata>,
+}
+
+/// Keeps track of the uploaded segments, persisted as JSON next to the local store.
+pub struct TieredMetadataStore {
+    path: String,
+    commit_log_segments: RwLock<BTreeMap<i64, TieredSegmentMetadata>>,
+    consume_queue_segments: RwLock<HashMap<QueueKey, BTreeMap<i64, TieredSegmentMetadata>>>,
+}
+
+impl TieredMetadataStore {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            commit_log_segments: RwLock::new(BTreeMap::new()),
+            consume_queue_segments: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn load(&self) -> io::Result<()> {
+        let content = file_utils::file_to_string(&self.path)?;
+        if content.is_empty() {
+            return Ok(());
+        }
+        let wrapper: TieredMetadataWrapper = serde_json::from_str(&content)?;
+        for segment in wrapper.segments {
+            self.insert(segment);
+        }
+        Ok(())
+    }
+
+    pub fn persist(&self) -> io::Result<()> {
+        let mut segments: Vec<TieredSegmentMetadata> =
+            self.commit_log_segments.read().values().cloned().collect();
+        for queue_segments in self.consume_queue_segments.read().values() {
+            segments.extend(queue_segments.values().cloned());
+        }
+        let content = serde_json::to_string(&TieredMetadataWrapper { segments })?;
+        file_utils::string_to_file(&content, &self.path)
+    }
+
+    pub fn insert(&self, segment: TieredSegmentMetadata) {
+        match segment.file_type {
+            TieredFileType::CommitLog => {
+                self.commit_log_segments
+                    .write()
+                    .insert(segment.base_offset, segment);
+            }
+            TieredFileType::ConsumeQueue => {
+                let key = (segment.topic.clone().unwrap_or_default(), segment.queue_id);
+                self.consume_queue_segments
+                    .write()
+                    .entry(key)
+                    .or_default()
+                    .insert(segment.base_offset, segment);
+            }
+        }
+    }
+
+    pub fn contains_segment(
+        &self,
+        file_type: TieredFileType,
+        topic: Option<&CheetahString>,
+        queue_id: i32,
+        base_offset: i64,
+    ) -> bool {
+        match file_type {
+            TieredFileType::CommitLog => self.commit_log_segments.read().contains_key(&base_offset),
+            TieredFileType::ConsumeQueue => self
+                .consume_queue_segments
+                .read()
+                .get(&(topic.cloned().unwrap_or_default(), queue_id))
+                .is_some_and(|segments| segments.contains_key(&base_offset)),
+        }
+    }
+
+    /// Finds the commit log segment holding `offset`.
+    pub fn find_commit_log_segment(&self, offset: i64) -> Option<TieredSegmentMetadata> {
+        Self::find_segment(&self.commit_log_segments.read(), offset)
+    }
+
+    /// Finds the consume queue segment holding the byte `offset` of a queue.
+    pub fn find_consume_queue_segment(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        offset: i64,
+    ) -> Option<TieredSegmentMetadata> {
+        self.consume_queue_segments
+            .read()
+            .get(&(topic.clone(), queue_id))
+            .and_then(|segments| Self::find_segment(segments, offset))
+    }
+
+    /// The smallest physical offset kept by the remote tier, or -1 when nothing is uploaded.
+    pub fn get_min_commit_log_offset(&self) -> i64 {
+        self.commit_log_segments
+            .read()
+            .keys()
+            .next()
+            .copied()
+            .unwrap_or(-1)
+    }
+
+    fn find_segment(
+        segments: &BTreeMap<i64, TieredSegmentMetadata>,
+        offset: i64,
+    ) -> Option<TieredSegmentMetadata> {
+        segments
+            .range(..=offset)
+            .next_back()
+            .map(|(_, segment)| segment)
+            .filter(|segment| segment.contains(offset))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit_log_segment(base_offset: i64, size: u64) -> TieredSegmentMetadata {
+        TieredSegmentMetadata {
+            file_type: TieredFileType::CommitLog,
+            topic: None,
+            queue_id: 0,
+            base_offset,
+            size,
+            object_key: format!("commitlog/{:020}", base_offset),
+            upload_timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn find_segment_by_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TieredMetadataStore::new(dir.path().join("tiered.json").to_string_lossy());
+        store.insert(commit_log_segment(0, 100));
+        store.insert(commit_log_segment(100, 100));
+
+        assert_eq!(store.find_commit_log_segment(99).unwrap().base_offset, 0);
+        assert_eq!(store.find_commit_log_segment(100).unwrap().base_offset, 100);
+        assert!(store.find_commit_log_segment(200).is_none());
+        assert_eq!(store.get_min_commit_log_offset(), 0);
+    }
+
+    #[test]
+    fn persist_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tiered.json").to_string_lossy().to_string();
+        let store = TieredMetadataStore::new(path.clone());
+        store.insert(commit_log_segment(0, 100));
+        store.insert(TieredSegmentMetadata {
+            file_type: TieredFileType::ConsumeQueue,
+            topic: Some("TopicTest".into()),
+            queue_id: 1,
+            object_key: "consumequeue/TopicTest/1/00000000000000000000".to_string(),
+            ..commit_log_segment(0, 60)
+        });
+        store.persist().unwrap();
+
+        let loaded = TieredMetadataStore::new(path);
+        loaded.load().unwrap();
+        assert!(loaded.contains_segment(TieredFileType::CommitLog, None, 0, 0));
+        assert_eq!(
+            loaded
+                .find_consume_queue_segment(&"TopicTest".into(), 1, 40)
+                .unwrap()
+                .size,
+            60
+        );
+    }
+}
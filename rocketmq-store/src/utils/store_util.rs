@@ -14,7 +14,10 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::path::Path;
+
 use once_cell::sync::Lazy;
+use sysinfo::Disks;
 use sysinfo::System;
 
 pub struct StoreUtil;
@@ -29,4 +32,18 @@ impl StoreUtil {
         let physical_total = sys.total_memory();
         physical_total * 1024 // Convert from kilobytes to bytes
     }
+
+    /// Returns the bytes available on the disk mounted closest to `path`, or 0 when the path
+    /// does not resolve to any known disk.
+    pub fn get_available_space(path: &str) -> u64 {
+        let Ok(path) = Path::new(path).canonicalize() else {
+            return 0;
+        };
+        let disks = Disks::new_with_refreshed_list();
+        disks
+            .iter()
+            .filter(|disk| path.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())
+            .map_or(0, |disk| disk.available_space())
+    }
 }
@@ -0,0 +1,118 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::io;
+use std::path::Path;
+
+use cheetah_string::CheetahString;
+use rocketmq_broker::BrokerBootstrap;
+use rocketmq_broker::Builder;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use tempfile::TempDir;
+
+use crate::port_util;
+
+/// Attempts at finding a listen port whose fast port (`port - 2`) is free as well.
+const PICK_PORT_ATTEMPTS: usize = 32;
+
+/// Commit log files are kept small, tests rarely write more than a few messages.
+const MAPPED_FILE_SIZE_COMMIT_LOG: usize = 16 * 1024 * 1024;
+
+/// A broker serving on random local ports and storing into a temporary directory removed on
+/// drop, registered to the given name server.
+pub struct EmbeddedBroker {
+    bootstrap: BrokerBootstrap,
+    port: u16,
+    store_dir: TempDir,
+}
+
+impl EmbeddedBroker {
+    /// Starts a broker registering to `namesrv_addr` and waits until it accepts connections.
+    pub async fn start(namesrv_addr: &str) -> io::Result<Self> {
+        let store_dir = tempfile::tempdir()?;
+        let port = pick_listen_port()?;
+        let store_path_root_dir =
+            CheetahString::from_string(store_dir.path().to_string_lossy().into_owned());
+        let broker_config = BrokerConfig {
+            broker_ip1: CheetahString::from_static_str(port_util::LOCAL_HOST),
+            listen_port: port as u32,
+            namesrv_addr: Some(CheetahString::from_slice(namesrv_addr)),
+            store_path_root_dir: store_path_root_dir.clone(),
+            ..BrokerConfig::default()
+        };
+        let message_store_config = MessageStoreConfig {
+            store_path_root_dir,
+            mapped_file_size_commit_log: MAPPED_FILE_SIZE_COMMIT_LOG,
+            ha_listen_port: port_util::free_port()? as usize,
+            ..MessageStoreConfig::default()
+        };
+        let server_config = ServerConfig {
+            listen_port: port as u32,
+            bind_address: port_util::LOCAL_HOST.to_string(),
+            ..broker_config.broker_server_config.clone()
+        };
+        let mut bootstrap = Builder::new()
+            .set_broker_config(broker_config)
+            .set_message_store_config(message_store_config)
+            .set_server_config(server_config)
+            .build();
+        if !bootstrap.initialize().await {
+            return Err(io::Error::other("embedded broker failed to initialize"));
+        }
+        bootstrap.start().await;
+        port_util::wait_for_port(port, port_util::READY_TIMEOUT).await?;
+        Ok(Self {
+            bootstrap,
+            port,
+            store_dir,
+        })
+    }
+
+    /// `ip:port` clients reach the broker at, as registered to the name server.
+    pub fn addr(&self) -> String {
+        format!("{}:{}", port_util::LOCAL_HOST, self.port)
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn store_dir(&self) -> &Path {
+        self.store_dir.path()
+    }
+
+    /// Unregisters from the name server and flushes the store, the storage directory is removed
+    /// once `self` is dropped.
+    pub async fn shutdown(mut self) {
+        self.bootstrap.shutdown().await;
+    }
+}
+
+/// The broker also serves on `listen_port - 2`, so both ports have to be free.
+fn pick_listen_port() -> io::Result<u16> {
+    for _ in 0..PICK_PORT_ATTEMPTS {
+        let port = port_util::free_port()?;
+        if port > 2 && port_util::is_port_free(port - 2) {
+            return Ok(port);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::AddrInUse,
+        "no free port pair for the embedded broker",
+    ))
+}
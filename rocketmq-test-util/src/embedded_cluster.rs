@@ -0,0 +1,78 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::io;
+
+use crate::EmbeddedBroker;
+use crate::EmbeddedNamesrv;
+
+/// One name server and one broker registered to it, the smallest cluster a client can send
+/// to and consume from.
+pub struct EmbeddedCluster {
+    namesrv: EmbeddedNamesrv,
+    broker: EmbeddedBroker,
+}
+
+impl EmbeddedCluster {
+    pub async fn start() -> io::Result<Self> {
+        let namesrv = EmbeddedNamesrv::start().await?;
+        let broker = match EmbeddedBroker::start(&namesrv.addr()).await {
+            Ok(broker) => broker,
+            Err(e) => {
+                namesrv.shutdown();
+                return Err(e);
+            }
+        };
+        Ok(Self { namesrv, broker })
+    }
+
+    pub fn namesrv_addr(&self) -> String {
+        self.namesrv.addr()
+    }
+
+    pub fn broker_addr(&self) -> String {
+        self.broker.addr()
+    }
+
+    pub fn namesrv(&self) -> &EmbeddedNamesrv {
+        &self.namesrv
+    }
+
+    pub fn broker(&self) -> &EmbeddedBroker {
+        &self.broker
+    }
+
+    /// Stops the broker first so it can unregister while the name server is still up.
+    pub async fn shutdown(self) {
+        self.broker.shutdown().await;
+        self.namesrv.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn starts_and_tears_down_a_cluster() {
+        let cluster = EmbeddedCluster::start().await.unwrap();
+        assert_ne!(cluster.namesrv().port(), cluster.broker().port());
+        let store_dir = cluster.broker().store_dir().to_path_buf();
+        assert!(store_dir.exists());
+        cluster.shutdown().await;
+        assert!(!store_dir.exists());
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::io;
+
+use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_namesrv::bootstrap::Builder;
+use rocketmq_namesrv::bootstrap::NameServerBootstrap;
+use tempfile::TempDir;
+
+use crate::port_util;
+
+/// A name server serving on a random local port, with its kv config kept in a temporary
+/// directory removed on drop.
+pub struct EmbeddedNamesrv {
+    bootstrap: NameServerBootstrap,
+    port: u16,
+    home_dir: TempDir,
+}
+
+impl EmbeddedNamesrv {
+    /// Starts the name server and waits until it accepts connections.
+    pub async fn start() -> io::Result<Self> {
+        let home_dir = tempfile::tempdir()?;
+        let port = port_util::free_port()?;
+        let home = home_dir.path();
+        let name_server_config = NamesrvConfig {
+            rocketmq_home: home.to_string_lossy().into_owned(),
+            kv_config_path: home.join("kvConfig.json").to_string_lossy().into_owned(),
+            config_store_path: home
+                .join("namesrv.properties")
+                .to_string_lossy()
+                .into_owned(),
+            ..NamesrvConfig::default()
+        };
+        let server_config = ServerConfig {
+            listen_port: port as u32,
+            bind_address: port_util::LOCAL_HOST.to_string(),
+            ..ServerConfig::default()
+        };
+        let mut bootstrap = Builder::new()
+            .set_name_server_config(name_server_config)
+            .set_server_config(server_config)
+            .build();
+        bootstrap.start().await;
+        port_util::wait_for_port(port, port_util::READY_TIMEOUT).await?;
+        Ok(Self {
+            bootstrap,
+            port,
+            home_dir,
+        })
+    }
+
+    /// `ip:port` to hand to clients and brokers as the name server address.
+    pub fn addr(&self) -> String {
+        format!("{}:{}", port_util::LOCAL_HOST, self.port)
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn home_dir(&self) -> &std::path::Path {
+        self.home_dir.path()
+    }
+
+    pub fn shutdown(mut self) {
+        self.bootstrap.shutdown();
+    }
+}
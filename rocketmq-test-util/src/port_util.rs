@@ -0,0 +1,70 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::io;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::time::Duration;
+use std::time::Instant;
+
+pub(crate) const LOCAL_HOST: &str = "127.0.0.1";
+
+/// How long a started server may take to accept connections.
+pub(crate) const READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A port nothing listens on right now, picked by the OS.
+pub(crate) fn free_port() -> io::Result<u16> {
+    Ok(TcpListener::bind((LOCAL_HOST, 0))?.local_addr()?.port())
+}
+
+pub(crate) fn is_port_free(port: u16) -> bool {
+    TcpListener::bind((LOCAL_HOST, port)).is_ok()
+}
+
+/// Polls `port` until it accepts connections or `timeout` elapses.
+pub(crate) async fn wait_for_port(port: u16, timeout: Duration) -> io::Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if TcpStream::connect((LOCAL_HOST, port)).is_ok() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("port {} not ready within {:?}", port, timeout),
+            ));
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn wait_for_port_sees_listener() {
+        let listener = TcpListener::bind((LOCAL_HOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(!is_port_free(port));
+        wait_for_port(port, Duration::from_secs(1)).await.unwrap();
+        drop(listener);
+        let err = wait_for_port(port, Duration::from_millis(100))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
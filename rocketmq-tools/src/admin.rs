@@ -14,8 +14,10 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+pub mod broker_metadata_bundle;
 pub mod common;
 pub mod default_mq_admin_ext;
 pub mod default_mq_admin_ext_impl;
+pub mod message_range;
 pub mod mq_admin_ext;
 pub mod mq_admin_ext_async;
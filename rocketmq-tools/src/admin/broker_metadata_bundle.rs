@@ -0,0 +1,237 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
+use serde::Deserialize;
+use serde::Serialize;
+
+const TOPIC_GROUP_SEPARATOR: char = '@';
+
+/// The topic configs, subscription groups and consumer offsets of one broker, exported as a
+/// single JSON document so that they can be imported into another broker.
+///
+/// The broker is read through the regular admin requests, so a bundle can be exported from a
+/// Java broker as well as from this implementation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerMetadataBundle {
+    pub broker_addr: CheetahString,
+    pub export_time: u64,
+    #[serde(default)]
+    pub topic_config_table: HashMap<CheetahString, TopicConfig>,
+    #[serde(default)]
+    pub subscription_group_table: HashMap<CheetahString, SubscriptionGroupConfig>,
+    /// Committed offsets keyed by `topic@group`, then by queue id.
+    #[serde(default)]
+    pub offset_table: HashMap<CheetahString, HashMap<i32, i64>>,
+}
+
+/// What an import wrote to the target broker.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataImportResult {
+    pub topics: usize,
+    pub subscription_groups: usize,
+    pub offsets: usize,
+    /// One message for every topic, group or offset the target broker rejected.
+    pub failures: Vec<String>,
+}
+
+impl BrokerMetadataBundle {
+    /// Reads the metadata of the broker at `broker_addr`. System topics and groups are only kept
+    /// when `include_system` is set, since the target broker creates its own.
+    #[cfg(feature = "async")]
+    pub async fn export<A>(
+        admin_ext: &A,
+        broker_addr: CheetahString,
+        include_system: bool,
+        timeout_millis: u64,
+    ) -> crate::Result<Self>
+    where
+        A: crate::admin::mq_admin_ext_async::MQAdminExt,
+    {
+        let topic_config_wrapper = admin_ext
+            .get_all_topic_config(broker_addr.clone(), timeout_millis)
+            .await?;
+        let subscription_group_wrapper = admin_ext
+            .get_all_subscription_group(broker_addr.clone(), timeout_millis)
+            .await?;
+        let consumer_offset_wrapper = admin_ext
+            .get_all_consumer_offset(broker_addr.clone(), timeout_millis)
+            .await?;
+        let mut bundle = BrokerMetadataBundle {
+            broker_addr,
+            export_time: get_current_millis(),
+            topic_config_table: topic_config_wrapper
+                .topic_config_table()
+                .cloned()
+                .unwrap_or_default(),
+            subscription_group_table: subscription_group_wrapper.subscription_group_table,
+            offset_table: consumer_offset_wrapper.offset_table,
+        };
+        if !include_system {
+            bundle.retain_user_metadata();
+        }
+        Ok(bundle)
+    }
+
+    /// Writes the metadata into the broker at `broker_addr`, named `broker_name`: topics first,
+    /// then subscription groups, then offsets, because the broker only accepts offsets of
+    /// existing topics and groups. A rejected entry is recorded and the import goes on.
+    #[cfg(feature = "async")]
+    pub async fn import<A>(
+        &self,
+        admin_ext: &A,
+        broker_addr: CheetahString,
+        broker_name: CheetahString,
+    ) -> MetadataImportResult
+    where
+        A: crate::admin::mq_admin_ext_async::MQAdminExt,
+    {
+        let mut result = MetadataImportResult::default();
+        for (topic, topic_config) in &self.topic_config_table {
+            match admin_ext
+                .create_and_update_topic_config(broker_addr.clone(), topic_config.clone())
+                .await
+            {
+                Ok(()) => result.topics += 1,
+                Err(e) => result.failures.push(format!("topic {}: {}", topic, e)),
+            }
+        }
+        for (group, config) in &self.subscription_group_table {
+            match admin_ext
+                .create_and_update_subscription_group_config(broker_addr.clone(), config.clone())
+                .await
+            {
+                Ok(()) => result.subscription_groups += 1,
+                Err(e) => result
+                    .failures
+                    .push(format!("subscription group {}: {}", group, e)),
+            }
+        }
+        for (topic, group, queue_id, offset) in self.offsets() {
+            let mq = MessageQueue::from_parts(topic, broker_name.clone(), queue_id);
+            match admin_ext
+                .update_consume_offset(broker_addr.clone(), group.into(), mq, offset.max(0) as u64)
+                .await
+            {
+                Ok(()) => result.offsets += 1,
+                Err(e) => result.failures.push(format!(
+                    "offset {}{}{}[{}]: {}",
+                    topic, TOPIC_GROUP_SEPARATOR, group, queue_id, e
+                )),
+            }
+        }
+        result
+    }
+
+    /// Drops system topics and groups, and the offsets that belong to them.
+    pub fn retain_user_metadata(&mut self) {
+        self.topic_config_table
+            .retain(|topic, _| !TopicValidator::is_system_topic(topic));
+        self.subscription_group_table
+            .retain(|group, _| !mix_all::is_sys_consumer_group(group));
+        self.offset_table.retain(|key, _| {
+            key.split_once(TOPIC_GROUP_SEPARATOR)
+                .is_some_and(|(topic, group)| {
+                    !TopicValidator::is_system_topic(topic)
+                        && !mix_all::is_sys_consumer_group(group)
+                })
+        });
+    }
+
+    /// Returns every `(topic, group, queue_id, offset)` of the offset table, in a stable order.
+    pub fn offsets(&self) -> Vec<(&str, &str, i32, i64)> {
+        let mut offsets: Vec<_> = self
+            .offset_table
+            .iter()
+            .filter_map(|(key, queue_offsets)| {
+                key.split_once(TOPIC_GROUP_SEPARATOR)
+                    .map(|(topic, group)| (topic, group, queue_offsets))
+            })
+            .flat_map(|(topic, group, queue_offsets)| {
+                queue_offsets
+                    .iter()
+                    .map(move |(queue_id, offset)| (topic, group, *queue_id, *offset))
+            })
+            .collect();
+        offsets.sort();
+        offsets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::protocol::RemotingDeserializable;
+    use rocketmq_remoting::protocol::RemotingSerializable;
+
+    use super::*;
+
+    fn bundle() -> BrokerMetadataBundle {
+        BrokerMetadataBundle {
+            broker_addr: "127.0.0.1:10911".into(),
+            export_time: 1,
+            topic_config_table: HashMap::from([
+                ("TopicA".into(), TopicConfig::with_queues("TopicA", 4, 4)),
+                (
+                    TopicValidator::RMQ_SYS_SCHEDULE_TOPIC.into(),
+                    TopicConfig::with_queues(TopicValidator::RMQ_SYS_SCHEDULE_TOPIC, 18, 18),
+                ),
+            ]),
+            subscription_group_table: HashMap::from([(
+                "GroupA".into(),
+                SubscriptionGroupConfig::new("GroupA".into()),
+            )]),
+            offset_table: HashMap::from([
+                ("TopicA@GroupA".into(), HashMap::from([(1, 20), (0, 10)])),
+                (
+                    format!("{}@GroupA", TopicValidator::RMQ_SYS_SCHEDULE_TOPIC).into(),
+                    HashMap::from([(0, 5)]),
+                ),
+            ]),
+        }
+    }
+
+    #[test]
+    fn bundle_round_trips_through_json() {
+        let json = bundle().to_json_pretty().unwrap();
+        let decoded = BrokerMetadataBundle::decode(json.as_bytes()).unwrap();
+        assert_eq!(decoded.topic_config_table.len(), 2);
+        assert_eq!(decoded.subscription_group_table.len(), 1);
+        assert_eq!(decoded.offsets().len(), 3);
+    }
+
+    #[test]
+    fn retain_user_metadata_drops_system_topics_and_their_offsets() {
+        let mut bundle = bundle();
+        bundle.retain_user_metadata();
+        assert_eq!(
+            bundle.topic_config_table.keys().collect::<Vec<_>>(),
+            vec!["TopicA"]
+        );
+        assert_eq!(
+            bundle.offsets(),
+            vec![("TopicA", "GroupA", 0, 10), ("TopicA", "GroupA", 1, 20)]
+        );
+    }
+}
@@ -17,32 +17,40 @@
 #![allow(dead_code)]
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use cheetah_string::CheetahString;
 use rocketmq_client_rust::base::client_config::ClientConfig;
 use rocketmq_common::common::base::plain_access_config::PlainAccessConfig;
 use rocketmq_common::common::config::TopicConfig;
 use rocketmq_common::common::message::message_enum::MessageRequestMode;
+use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::topic::TopicValidator;
 use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
 use rocketmq_remoting::protocol::admin::topic_stats_table::TopicStatsTable;
 use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
 use rocketmq_remoting::protocol::body::consumer_connection::ConsumerConnection;
+use rocketmq_remoting::protocol::body::consumer_offset_serialize_wrapper::ConsumerOffsetSerializeWrapper;
 use rocketmq_remoting::protocol::body::consumer_running_info::ConsumerRunningInfo;
 use rocketmq_remoting::protocol::body::group_list::GroupList;
 use rocketmq_remoting::protocol::body::kv_table::KVTable;
+use rocketmq_remoting::protocol::body::pop_stats::PopStats;
 use rocketmq_remoting::protocol::body::producer_connection::ProducerConnection;
+use rocketmq_remoting::protocol::body::subscription_group_wrapper::SubscriptionGroupWrapper;
 use rocketmq_remoting::protocol::body::topic::topic_list::TopicList;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::TopicConfigSerializeWrapper;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_detail::TopicQueueMappingDetail;
 use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
+use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_rust::ArcMut;
 
 use crate::admin::common::admin_tool_result::AdminToolResult;
 use crate::admin::default_mq_admin_ext_impl::DefaultMQAdminExtImpl;
+use crate::admin::message_range::MessageRange;
 use crate::admin::mq_admin_ext_async::MQAdminExt;
 
 pub struct DefaultMQAdminExt {
@@ -53,16 +61,64 @@ pub struct DefaultMQAdminExt {
     default_mqadmin_ext_impl: DefaultMQAdminExtImpl,
 }
 
+impl Default for DefaultMQAdminExt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DefaultMQAdminExt {
+    pub fn new() -> Self {
+        Self::with_rpc_hook_and_timeout(None, 5000)
+    }
+
+    pub fn with_timeout(timeout_millis: u64) -> Self {
+        Self::with_rpc_hook_and_timeout(None, timeout_millis)
+    }
+
+    pub fn with_rpc_hook(rpc_hook: Arc<Box<dyn RPCHook>>) -> Self {
+        Self::with_rpc_hook_and_timeout(Some(rpc_hook), 5000)
+    }
+
+    pub fn with_rpc_hook_and_timeout(
+        rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+        timeout_millis: u64,
+    ) -> Self {
+        let client_config = ArcMut::new(ClientConfig::new());
+        DefaultMQAdminExt {
+            client_config: client_config.clone(),
+            admin_ext_group: CheetahString::from_static_str("admin_ext_group"),
+            create_topic_key: CheetahString::from_static_str(
+                TopicValidator::AUTO_CREATE_TOPIC_KEY_TOPIC,
+            ),
+            timeout_millis,
+            default_mqadmin_ext_impl: DefaultMQAdminExtImpl::new(
+                rpc_hook,
+                timeout_millis,
+                client_config,
+            ),
+        }
+    }
+
+    pub fn client_config(&self) -> &ArcMut<ClientConfig> {
+        &self.client_config
+    }
+
+    pub fn set_namesrv_addr(&mut self, namesrv_addr: impl Into<CheetahString>) {
+        self.client_config.mut_from_ref().namesrv_addr = Some(namesrv_addr.into());
+    }
+}
+
 #[allow(unused_variables)]
 #[allow(unused_mut)]
 #[cfg(feature = "async")]
 impl MQAdminExt for DefaultMQAdminExt {
-    async fn start(&self) -> crate::Result<()> {
-        todo!()
+    async fn start(&mut self) -> crate::Result<()> {
+        self.default_mqadmin_ext_impl.start().await
     }
 
-    async fn shutdown(&self) {
-        todo!()
+    async fn shutdown(&mut self) {
+        self.default_mqadmin_ext_impl.shutdown().await
     }
 
     async fn add_broker_to_container(
@@ -103,7 +159,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         addr: CheetahString,
         config: TopicConfig,
     ) -> crate::Result<()> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .create_and_update_topic_config(addr, config)
+            .await
     }
 
     async fn create_and_update_topic_config_list(
@@ -111,7 +169,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         addr: CheetahString,
         topic_config_list: Vec<TopicConfig>,
     ) -> crate::Result<()> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .create_and_update_topic_config_list(addr, topic_config_list)
+            .await
     }
 
     async fn create_and_update_plain_access_config(
@@ -151,7 +211,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         addr: CheetahString,
         config: SubscriptionGroupConfig,
     ) -> crate::Result<()> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .create_and_update_subscription_group_config(addr, config)
+            .await
     }
 
     async fn create_and_update_subscription_group_config_list(
@@ -159,7 +221,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         broker_addr: CheetahString,
         configs: Vec<SubscriptionGroupConfig>,
     ) -> crate::Result<()> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .create_and_update_subscription_group_config_list(broker_addr, configs)
+            .await
     }
 
     async fn examine_subscription_group_config(
@@ -186,7 +250,7 @@ impl MQAdminExt for DefaultMQAdminExt {
     }
 
     async fn fetch_all_topic_list(&self) -> crate::Result<TopicList> {
-        todo!()
+        self.default_mqadmin_ext_impl.fetch_all_topic_list().await
     }
 
     async fn fetch_topics_by_cluster(
@@ -200,7 +264,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         &self,
         broker_addr: CheetahString,
     ) -> crate::Result<KVTable> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .fetch_broker_runtime_stats(broker_addr)
+            .await
     }
 
     async fn examine_consume_stats(
@@ -211,18 +277,41 @@ impl MQAdminExt for DefaultMQAdminExt {
         broker_addr: Option<CheetahString>,
         timeout_millis: Option<u64>,
     ) -> crate::Result<ConsumeStats> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .examine_consume_stats(
+                consumer_group,
+                topic,
+                cluster_name,
+                broker_addr,
+                timeout_millis,
+            )
+            .await
+    }
+
+    async fn examine_pop_stats(
+        &self,
+        broker_addr: CheetahString,
+        consumer_group: CheetahString,
+        topic: CheetahString,
+    ) -> crate::Result<PopStats> {
+        self.default_mqadmin_ext_impl
+            .examine_pop_stats(broker_addr, consumer_group, topic)
+            .await
     }
 
     async fn examine_broker_cluster_info(&self) -> crate::Result<ClusterInfo> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .examine_broker_cluster_info()
+            .await
     }
 
     async fn examine_topic_route_info(
         &self,
         topic: CheetahString,
     ) -> crate::Result<TopicRouteData> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .examine_topic_route_info(topic)
+            .await
     }
 
     async fn examine_consumer_connection_info(
@@ -242,7 +331,9 @@ impl MQAdminExt for DefaultMQAdminExt {
     }
 
     async fn get_name_server_address_list(&self) -> Vec<CheetahString> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .get_name_server_address_list()
+            .await
     }
 
     async fn wipe_write_perm_of_broker(
@@ -250,7 +341,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         namesrv_addr: CheetahString,
         broker_name: CheetahString,
     ) -> crate::Result<i32> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .wipe_write_perm_of_broker(namesrv_addr, broker_name)
+            .await
     }
 
     async fn add_write_perm_of_broker(
@@ -258,7 +351,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         namesrv_addr: CheetahString,
         broker_name: CheetahString,
     ) -> crate::Result<i32> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .add_write_perm_of_broker(namesrv_addr, broker_name)
+            .await
     }
 
     async fn put_kv_config(
@@ -287,7 +382,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         topic_name: CheetahString,
         cluster_name: CheetahString,
     ) -> crate::Result<()> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .delete_topic(topic_name, cluster_name)
+            .await
     }
 
     async fn delete_topic_in_broker(
@@ -295,7 +392,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         addrs: HashSet<CheetahString>,
         topic: CheetahString,
     ) -> crate::Result<()> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .delete_topic_in_broker(addrs, topic)
+            .await
     }
 
     async fn delete_topic_in_name_server(
@@ -304,7 +403,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         cluster_name: Option<CheetahString>,
         topic: CheetahString,
     ) -> crate::Result<()> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .delete_topic_in_name_server(addrs, cluster_name, topic)
+            .await
     }
 
     async fn delete_subscription_group(
@@ -341,7 +442,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         timestamp: u64,
         is_force: bool,
     ) -> crate::Result<HashMap<MessageQueue, u64>> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .reset_offset_by_timestamp(cluster_name, topic, group, timestamp, is_force)
+            .await
     }
 
     async fn reset_offset_new(
@@ -425,7 +528,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         jstack: bool,
         metrics: Option<bool>,
     ) -> crate::Result<ConsumerRunningInfo> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .get_consumer_running_info(consumer_group, client_id, jstack, metrics)
+            .await
     }
 
     async fn consume_message_directly(
@@ -435,7 +540,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         topic: CheetahString,
         msg_id: CheetahString,
     ) -> crate::Result<ConsumeMessageDirectlyResult> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .consume_message_directly(consumer_group, client_id, topic, msg_id)
+            .await
     }
 
     async fn consume_message_directly_ext(
@@ -449,6 +556,46 @@ impl MQAdminExt for DefaultMQAdminExt {
         todo!()
     }
 
+    async fn view_message(
+        &self,
+        topic: CheetahString,
+        msg_id: CheetahString,
+    ) -> crate::Result<MessageExt> {
+        self.default_mqadmin_ext_impl
+            .view_message(topic, msg_id)
+            .await
+    }
+
+    async fn query_messages_by_queue_offset(
+        &self,
+        broker_addr: CheetahString,
+        mq: MessageQueue,
+        start_offset: i64,
+        end_offset: i64,
+        max_msg_nums: i32,
+    ) -> crate::Result<MessageRange> {
+        self.default_mqadmin_ext_impl
+            .query_messages_by_queue_offset(broker_addr, mq, start_offset, end_offset, max_msg_nums)
+            .await
+    }
+
+    async fn query_messages_by_commit_log_offset(
+        &self,
+        broker_addr: CheetahString,
+        start_offset: i64,
+        end_offset: i64,
+        max_msg_nums: i32,
+    ) -> crate::Result<MessageRange> {
+        self.default_mqadmin_ext_impl
+            .query_messages_by_commit_log_offset(
+                broker_addr,
+                start_offset,
+                end_offset,
+                max_msg_nums,
+            )
+            .await
+    }
+
     async fn clone_group_offset(
         &self,
         src_group: CheetahString,
@@ -472,7 +619,29 @@ impl MQAdminExt for DefaultMQAdminExt {
         broker_addr: CheetahString,
         timeout_millis: u64,
     ) -> crate::Result<TopicConfigSerializeWrapper> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .get_all_topic_config(broker_addr, timeout_millis)
+            .await
+    }
+
+    async fn get_all_subscription_group(
+        &self,
+        broker_addr: CheetahString,
+        timeout_millis: u64,
+    ) -> crate::Result<SubscriptionGroupWrapper> {
+        self.default_mqadmin_ext_impl
+            .get_all_subscription_group(broker_addr, timeout_millis)
+            .await
+    }
+
+    async fn get_all_consumer_offset(
+        &self,
+        broker_addr: CheetahString,
+        timeout_millis: u64,
+    ) -> crate::Result<ConsumerOffsetSerializeWrapper> {
+        self.default_mqadmin_ext_impl
+            .get_all_consumer_offset(broker_addr, timeout_millis)
+            .await
     }
 
     async fn get_user_topic_config(
@@ -491,7 +660,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         mq: MessageQueue,
         offset: u64,
     ) -> crate::Result<()> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .update_consume_offset(broker_addr, consume_group, mq, offset)
+            .await
     }
 
     async fn update_name_server_config(
@@ -22,33 +22,53 @@ use std::sync::Arc;
 
 use cheetah_string::CheetahString;
 use lazy_static::lazy_static;
+use rocketmq_client_rust::base::client_config::ClientConfig;
+use rocketmq_client_rust::client_error::ClientErr;
+use rocketmq_client_rust::client_error::MQClientError;
 use rocketmq_client_rust::factory::mq_client_instance::MQClientInstance;
+use rocketmq_client_rust::implementation::mq_client_api_impl::MQClientAPIImpl;
+use rocketmq_client_rust::implementation::mq_client_manager::MQClientManager;
 use rocketmq_common::common::base::plain_access_config::PlainAccessConfig;
 use rocketmq_common::common::base::service_state::ServiceState;
 use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::common::key_builder::KeyBuilder;
 use rocketmq_common::common::message::message_enum::MessageRequestMode;
+use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::mix_all;
+use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_common::MessageDecoder;
+use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
 use rocketmq_remoting::protocol::admin::topic_stats_table::TopicStatsTable;
 use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
 use rocketmq_remoting::protocol::body::consumer_connection::ConsumerConnection;
+use rocketmq_remoting::protocol::body::consumer_offset_serialize_wrapper::ConsumerOffsetSerializeWrapper;
 use rocketmq_remoting::protocol::body::consumer_running_info::ConsumerRunningInfo;
 use rocketmq_remoting::protocol::body::group_list::GroupList;
 use rocketmq_remoting::protocol::body::kv_table::KVTable;
+use rocketmq_remoting::protocol::body::pop_stats::PopStats;
 use rocketmq_remoting::protocol::body::producer_connection::ProducerConnection;
+use rocketmq_remoting::protocol::body::subscription_group_wrapper::SubscriptionGroupWrapper;
 use rocketmq_remoting::protocol::body::topic::topic_list::TopicList;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::TopicConfigSerializeWrapper;
+use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::TopicRequestHeader;
+use rocketmq_remoting::protocol::header::query_message_by_offset_range_request_header::QueryMessageByOffsetRangeRequestHeader;
+use rocketmq_remoting::protocol::header::update_consumer_offset_header::UpdateConsumerOffsetRequestHeader;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_detail::TopicQueueMappingDetail;
 use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
+use rocketmq_remoting::rpc::rpc_request_header::RpcRequestHeader;
 use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_rust::ArcMut;
+use tracing::info;
 
 use crate::admin::common::admin_tool_result::AdminToolResult;
+use crate::admin::message_range::MessageRange;
 use crate::admin::mq_admin_ext_async::MQAdminExt;
+use crate::command_util::CommandUtil;
 
 lazy_static! {
     static ref SYSTEM_GROUP_SET: HashSet<CheetahString> = {
@@ -79,18 +99,99 @@ pub struct DefaultMQAdminExtImpl {
     rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
     timeout_millis: u64,
     kv_namespace_to_delete_list: Vec<CheetahString>,
+    client_config: ArcMut<ClientConfig>,
+}
+
+impl DefaultMQAdminExtImpl {
+    pub fn new(
+        rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+        timeout_millis: u64,
+        client_config: ArcMut<ClientConfig>,
+    ) -> Self {
+        DefaultMQAdminExtImpl {
+            service_state: ServiceState::CreateJust,
+            client_instance: None,
+            rpc_hook,
+            timeout_millis,
+            kv_namespace_to_delete_list: vec![CheetahString::from_static_str("ORDER_TOPIC_CONFIG")],
+            client_config,
+        }
+    }
+
+    fn mq_client_api_impl(&self) -> crate::Result<ArcMut<MQClientAPIImpl>> {
+        match self.client_instance.as_ref() {
+            Some(client_instance) if self.service_state == ServiceState::Running => {
+                Ok(client_instance.get_mq_client_api_impl())
+            }
+            _ => Err(MQClientError::MQClientErr(ClientErr::new(format!(
+                "The AdminExt service state not OK, {:?}",
+                self.service_state
+            )))
+            .into()),
+        }
+    }
+
+    async fn query_message_by_offset_range(
+        &self,
+        broker_addr: CheetahString,
+        request_header: QueryMessageByOffsetRangeRequestHeader,
+    ) -> crate::Result<MessageRange> {
+        let (messages, response_header) = self
+            .mq_client_api_impl()?
+            .query_message_by_offset_range(&broker_addr, request_header, self.timeout_millis)
+            .await?;
+        Ok(MessageRange {
+            messages,
+            next_offset: response_header.next_offset,
+            max_offset: response_header.max_offset,
+        })
+    }
 }
 
 #[allow(unused_variables)]
 #[allow(unused_mut)]
 #[cfg(feature = "async")]
 impl MQAdminExt for DefaultMQAdminExtImpl {
-    async fn start(&self) -> crate::Result<()> {
-        todo!()
+    async fn start(&mut self) -> crate::Result<()> {
+        match self.service_state {
+            ServiceState::CreateJust => {
+                self.service_state = ServiceState::StartFailed;
+                self.client_config.change_instance_name_to_pid();
+                let mut client_instance = MQClientManager::get_instance()
+                    .get_or_create_mq_client_instance(
+                        self.client_config.as_ref().clone(),
+                        self.rpc_hook.clone(),
+                    )
+                    .await;
+                let cloned = client_instance.clone();
+                client_instance.start(cloned).await?;
+                self.client_instance = Some(client_instance);
+                self.service_state = ServiceState::Running;
+                info!(
+                    "the adminExt [{}] start OK",
+                    self.client_config.instance_name
+                );
+                Ok(())
+            }
+            _ => Err(MQClientError::MQClientErr(ClientErr::new(format!(
+                "The AdminExt service state not OK, maybe started once, {:?}",
+                self.service_state
+            )))
+            .into()),
+        }
     }
 
-    async fn shutdown(&self) {
-        todo!()
+    async fn shutdown(&mut self) {
+        if self.service_state == ServiceState::Running {
+            if let Some(client_instance) = self.client_instance.as_mut() {
+                client_instance.shutdown().await;
+            }
+            self.service_state = ServiceState::ShutdownAlready;
+            info!(
+                "the adminExt [{}] shutdown OK",
+                self.client_config.instance_name
+            );
+        }
     }
 
     async fn add_broker_to_container(
@@ -131,7 +232,15 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         addr: CheetahString,
         config: TopicConfig,
     ) -> crate::Result<()> {
-        todo!()
+        self.mq_client_api_impl()?
+            .create_topic(
+                &addr,
+                &CheetahString::from_static_str(TopicValidator::AUTO_CREATE_TOPIC_KEY_TOPIC),
+                &config,
+                self.timeout_millis,
+            )
+            .await?;
+        Ok(())
     }
 
     async fn create_and_update_topic_config_list(
@@ -139,7 +248,11 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         addr: CheetahString,
         topic_config_list: Vec<TopicConfig>,
     ) -> crate::Result<()> {
-        todo!()
+        for topic_config in topic_config_list {
+            self.create_and_update_topic_config(addr.clone(), topic_config)
+                .await?;
+        }
+        Ok(())
     }
 
     async fn create_and_update_plain_access_config(
@@ -179,7 +292,10 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         addr: CheetahString,
         config: SubscriptionGroupConfig,
     ) -> crate::Result<()> {
-        todo!()
+        self.mq_client_api_impl()?
+            .create_subscription_group(&addr, &config, self.timeout_millis)
+            .await?;
+        Ok(())
     }
 
     async fn create_and_update_subscription_group_config_list(
@@ -187,7 +303,11 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         broker_addr: CheetahString,
         configs: Vec<SubscriptionGroupConfig>,
     ) -> crate::Result<()> {
-        todo!()
+        for config in configs {
+            self.create_and_update_subscription_group_config(broker_addr.clone(), config)
+                .await?;
+        }
+        Ok(())
     }
 
     async fn examine_subscription_group_config(
@@ -214,7 +334,10 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
     }
 
     async fn fetch_all_topic_list(&self) -> crate::Result<TopicList> {
-        todo!()
+        Ok(self
+            .mq_client_api_impl()?
+            .get_topic_list_from_name_server(self.timeout_millis)
+            .await?)
     }
 
     async fn fetch_topics_by_cluster(
@@ -228,7 +351,22 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         &self,
         broker_addr: CheetahString,
     ) -> crate::Result<KVTable> {
-        todo!()
+        Ok(self
+            .mq_client_api_impl()?
+            .get_broker_runtime_info(&broker_addr, self.timeout_millis)
+            .await?)
+    }
+
+    async fn examine_pop_stats(
+        &self,
+        broker_addr: CheetahString,
+        consumer_group: CheetahString,
+        topic: CheetahString,
+    ) -> crate::Result<PopStats> {
+        Ok(self
+            .mq_client_api_impl()?
+            .get_pop_stats(&broker_addr, &consumer_group, &topic, self.timeout_millis)
+            .await?)
     }
 
     async fn examine_consume_stats(
@@ -239,18 +377,97 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         broker_addr: Option<CheetahString>,
         timeout_millis: Option<u64>,
     ) -> crate::Result<ConsumeStats> {
-        todo!()
+        let mq_client_api_impl = self.mq_client_api_impl()?;
+        let timeout_millis = timeout_millis.unwrap_or(self.timeout_millis);
+        let broker_addrs = match broker_addr {
+            Some(broker_addr) => vec![broker_addr],
+            None => {
+                // The retry topic may have no route yet, fall back to the topic itself and
+                // its pop retry topic before giving up.
+                let mut route_topics = vec![CheetahString::from_string(mix_all::get_retry_topic(
+                    consumer_group.as_str(),
+                ))];
+                if let Some(topic) = topic.as_ref() {
+                    route_topics.push(topic.clone());
+                    route_topics.push(CheetahString::from_string(
+                        KeyBuilder::build_pop_retry_topic(
+                            topic.as_str(),
+                            consumer_group.as_str(),
+                            false,
+                        ),
+                    ));
+                }
+                let mut topic_route_data = TopicRouteData::default();
+                for (index, route_topic) in route_topics.iter().enumerate() {
+                    match self.examine_topic_route_info(route_topic.clone()).await {
+                        Ok(route_data) => {
+                            topic_route_data = route_data;
+                            break;
+                        }
+                        Err(e) if index == route_topics.len() - 1 => return Err(e),
+                        Err(_) => {}
+                    }
+                }
+                topic_route_data
+                    .broker_datas
+                    .iter()
+                    .filter(|broker_data| {
+                        cluster_name
+                            .as_ref()
+                            .map_or(true, |cluster_name| broker_data.cluster() == cluster_name)
+                    })
+                    .filter_map(|broker_data| broker_data.select_broker_addr())
+                    .collect()
+            }
+        };
+
+        let mut result = ConsumeStats::new();
+        for addr in broker_addrs {
+            let consume_stats = mq_client_api_impl
+                .get_consume_stats(&addr, &consumer_group, topic.as_ref(), timeout_millis)
+                .await?;
+            let consume_tps = result.get_consume_tps() + consume_stats.get_consume_tps();
+            result
+                .get_offset_table_mut()
+                .extend(consume_stats.get_offset_table().clone());
+            result.set_consume_tps(consume_tps);
+        }
+        if result.get_offset_table().is_empty() {
+            return Err(MQClientError::MQClientErr(ClientErr::new_with_code(
+                ResponseCode::ConsumerNotOnline as i32,
+                "Not found the consumer group consume stats, because return offset table is \
+                 empty, maybe the consumer not consume any message",
+            ))
+            .into());
+        }
+        Ok(result)
     }
 
     async fn examine_broker_cluster_info(&self) -> crate::Result<ClusterInfo> {
-        todo!()
+        Ok(self
+            .mq_client_api_impl()?
+            .get_broker_cluster_info(self.timeout_millis)
+            .await?)
     }
 
     async fn examine_topic_route_info(
         &self,
         topic: CheetahString,
     ) -> crate::Result<TopicRouteData> {
-        todo!()
+        let topic_route_data = self
+            .mq_client_api_impl()?
+            .get_topic_route_info_from_name_server(topic.as_str(), self.timeout_millis)
+            .await?;
+        topic_route_data.ok_or_else(|| {
+            MQClientError::MQClientErr(ClientErr::new_with_code(
+                ResponseCode::TopicNotExist as i32,
+                format!(
+                    "No topic route info in name server for the topic: {}",
+                    topic
+                ),
+            ))
+            .into()
+        })
     }
 
     async fn examine_consumer_connection_info(
@@ -258,7 +475,43 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         consumer_group: CheetahString,
         broker_addr: Option<CheetahString>,
     ) -> crate::Result<ConsumerConnection> {
-        todo!()
+        let mq_client_api_impl = self.mq_client_api_impl()?;
+        let broker_addrs = match broker_addr {
+            Some(broker_addr) => vec![broker_addr],
+            None => {
+                let retry_topic =
+                    CheetahString::from_string(mix_all::get_retry_topic(&consumer_group));
+                self.examine_topic_route_info(retry_topic)
+                    .await?
+                    .broker_datas
+                    .iter()
+                    .filter_map(|broker_data| broker_data.select_broker_addr())
+                    .collect()
+            }
+        };
+        // every broker sees the same group metadata, only the connections are merged
+        let mut result: Option<ConsumerConnection> = None;
+        for addr in broker_addrs {
+            let consumer_connection = mq_client_api_impl
+                .get_consumer_connection_list(&addr, &consumer_group, self.timeout_millis)
+                .await?;
+            match result.as_mut() {
+                Some(result) => {
+                    let mut connection_set = result.get_connection_set();
+                    connection_set.extend(consumer_connection.get_connection_set());
+                    result.set_connection_set(connection_set);
+                }
+                None => result = Some(consumer_connection),
+            }
+        }
+        match result {
+            Some(result) if !result.get_connection_set().is_empty() => Ok(result),
+            _ => Err(MQClientError::MQClientErr(ClientErr::new(format!(
+                "Not found the consumer group connection, group: {}",
+                consumer_group
+            )))
+            .into()),
+        }
     }
 
     async fn examine_producer_connection_info(
@@ -266,11 +519,36 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         producer_group: CheetahString,
         topic: CheetahString,
     ) -> crate::Result<ProducerConnection> {
-        todo!()
+        let mq_client_api_impl = self.mq_client_api_impl()?;
+        let topic_route_data = self.examine_topic_route_info(topic).await?;
+        let mut result = ProducerConnection::default();
+        for addr in topic_route_data
+            .broker_datas
+            .iter()
+            .filter_map(|broker_data| broker_data.select_broker_addr())
+        {
+            let producer_connection = mq_client_api_impl
+                .get_producer_connection_list(&addr, &producer_group, self.timeout_millis)
+                .await?;
+            result
+                .connection_set
+                .extend(producer_connection.connection_set);
+        }
+        if result.connection_set.is_empty() {
+            return Err(MQClientError::MQClientErr(ClientErr::new(format!(
+                "Not found the producer group connection, group: {}",
+                producer_group
+            )))
+            .into());
+        }
+        Ok(result)
     }
 
     async fn get_name_server_address_list(&self) -> Vec<CheetahString> {
-        todo!()
+        match self.mq_client_api_impl() {
+            Ok(mq_client_api_impl) => mq_client_api_impl.get_name_server_address_list().to_vec(),
+            Err(_) => vec![],
+        }
     }
 
     async fn wipe_write_perm_of_broker(
@@ -278,7 +556,10 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         namesrv_addr: CheetahString,
         broker_name: CheetahString,
     ) -> crate::Result<i32> {
-        todo!()
+        self.mq_client_api_impl()?
+            .wipe_write_perm_of_broker(&namesrv_addr, &broker_name, self.timeout_millis)
+            .await
+            .map_err(Into::into)
     }
 
     async fn add_write_perm_of_broker(
@@ -286,7 +567,10 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         namesrv_addr: CheetahString,
         broker_name: CheetahString,
     ) -> crate::Result<i32> {
-        todo!()
+        self.mq_client_api_impl()?
+            .add_write_perm_of_broker(&namesrv_addr, &broker_name, self.timeout_millis)
+            .await
+            .map_err(Into::into)
     }
 
     async fn put_kv_config(
@@ -315,7 +599,17 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         topic_name: CheetahString,
         cluster_name: CheetahString,
     ) -> crate::Result<()> {
-        todo!()
+        let broker_addrs =
+            CommandUtil::fetch_master_and_slave_addr_by_cluster_name(self, &cluster_name).await?;
+        self.delete_topic_in_broker(broker_addrs, topic_name.clone())
+            .await?;
+        let name_server_addrs = self
+            .get_name_server_address_list()
+            .await
+            .into_iter()
+            .collect();
+        self.delete_topic_in_name_server(name_server_addrs, Some(cluster_name), topic_name)
+            .await
     }
 
     async fn delete_topic_in_broker(
@@ -323,7 +617,13 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         addrs: HashSet<CheetahString>,
         topic: CheetahString,
     ) -> crate::Result<()> {
-        todo!()
+        let mq_client_api_impl = self.mq_client_api_impl()?;
+        for addr in addrs {
+            mq_client_api_impl
+                .delete_topic_in_broker(&addr, &topic, self.timeout_millis)
+                .await?;
+        }
+        Ok(())
     }
 
     async fn delete_topic_in_name_server(
@@ -332,7 +632,27 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         cluster_name: Option<CheetahString>,
         topic: CheetahString,
     ) -> crate::Result<()> {
-        todo!()
+        let mq_client_api_impl = self.mq_client_api_impl()?;
+        let addrs = if addrs.is_empty() {
+            mq_client_api_impl
+                .get_name_server_address_list()
+                .iter()
+                .cloned()
+                .collect()
+        } else {
+            addrs
+        };
+        for addr in addrs {
+            mq_client_api_impl
+                .delete_topic_in_name_server(
+                    &addr,
+                    &topic,
+                    cluster_name.as_ref(),
+                    self.timeout_millis,
+                )
+                .await?;
+        }
+        Ok(())
     }
 
     async fn delete_subscription_group(
@@ -369,7 +689,34 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         timestamp: u64,
         is_force: bool,
     ) -> crate::Result<HashMap<MessageQueue, u64>> {
-        todo!()
+        let mq_client_api_impl = self.mq_client_api_impl()?;
+        let topic_route_data = self.examine_topic_route_info(topic.clone()).await?;
+        let mut all_offset_table = HashMap::new();
+        for broker_data in topic_route_data.broker_datas.iter().filter(|broker_data| {
+            cluster_name
+                .as_ref()
+                .map_or(true, |cluster_name| broker_data.cluster() == cluster_name)
+        }) {
+            let Some(addr) = broker_data.select_broker_addr() else {
+                continue;
+            };
+            let offset_table = mq_client_api_impl
+                .invoke_broker_to_reset_offset(
+                    &addr,
+                    &topic,
+                    &group,
+                    timestamp as i64,
+                    is_force,
+                    self.timeout_millis,
+                )
+                .await?;
+            all_offset_table.extend(
+                offset_table
+                    .into_iter()
+                    .map(|(mq, offset)| (mq, offset.max(0) as u64)),
+            );
+        }
+        Ok(all_offset_table)
     }
 
     async fn reset_offset_new(
@@ -443,7 +790,25 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         cluster: Option<CheetahString>,
         addr: Option<CheetahString>,
     ) -> crate::Result<bool> {
-        todo!()
+        let broker_addrs = match (addr, cluster) {
+            (Some(addr), _) => HashSet::from([addr]),
+            (None, Some(cluster)) => {
+                CommandUtil::fetch_master_addr_by_cluster_name(self, &cluster).await?
+            }
+            (None, None) => {
+                return Err(MQClientError::MQClientErr(ClientErr::new(
+                    "either the broker address or the cluster name is required",
+                ))
+                .into());
+            }
+        };
+        let mq_client_api_impl = self.mq_client_api_impl()?;
+        for addr in broker_addrs {
+            mq_client_api_impl
+                .clean_unused_topic(&addr, self.timeout_millis)
+                .await?;
+        }
+        Ok(true)
     }
 
     async fn get_consumer_running_info(
@@ -453,7 +818,29 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         jstack: bool,
         metrics: Option<bool>,
     ) -> crate::Result<ConsumerRunningInfo> {
-        todo!()
+        let mq_client_api_impl = self.mq_client_api_impl()?;
+        let retry_topic = CheetahString::from_string(mix_all::get_retry_topic(&consumer_group));
+        let topic_route_data = self.examine_topic_route_info(retry_topic).await?;
+        let Some(addr) = topic_route_data
+            .broker_datas
+            .iter()
+            .find_map(|broker_data| broker_data.select_broker_addr())
+        else {
+            return Err(MQClientError::MQClientErr(ClientErr::new(format!(
+                "no broker serves the consumer group {}",
+                consumer_group
+            )))
+            .into());
+        };
+        Ok(mq_client_api_impl
+            .get_consumer_running_info(
+                &addr,
+                &consumer_group,
+                &client_id,
+                jstack,
+                self.timeout_millis,
+            )
+            .await?)
     }
 
     async fn consume_message_directly(
@@ -463,7 +850,25 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         topic: CheetahString,
         msg_id: CheetahString,
     ) -> crate::Result<ConsumeMessageDirectlyResult> {
-        todo!()
+        let mq_client_api_impl = self.mq_client_api_impl()?;
+        let Some(message_id) = MessageDecoder::try_decode_message_id(&msg_id) else {
+            return Err(MQClientError::MQClientErr(ClientErr::new(format!(
+                "{} is not an offset message id",
+                msg_id
+            )))
+            .into());
+        };
+        let addr = CheetahString::from_string(message_id.address.to_string());
+        Ok(mq_client_api_impl
+            .consume_message_directly(
+                &addr,
+                &consumer_group,
+                &client_id,
+                &topic,
+                &msg_id,
+                self.timeout_millis,
+            )
+            .await?)
     }
 
     async fn consume_message_directly_ext(
@@ -477,6 +882,66 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         todo!()
     }
 
+    async fn view_message(
+        &self,
+        topic: CheetahString,
+        msg_id: CheetahString,
+    ) -> crate::Result<MessageExt> {
+        let client_instance = match self.client_instance.as_ref() {
+            Some(client_instance) if self.service_state == ServiceState::Running => client_instance,
+            _ => {
+                return Err(MQClientError::MQClientErr(ClientErr::new(format!(
+                    "The AdminExt service state not OK, {:?}",
+                    self.service_state
+                )))
+                .into());
+            }
+        };
+        Ok(client_instance
+            .get_mq_admin_impl()
+            .view_message(&topic, &msg_id)
+            .await?)
+    }
+
+    async fn query_messages_by_queue_offset(
+        &self,
+        broker_addr: CheetahString,
+        mq: MessageQueue,
+        start_offset: i64,
+        end_offset: i64,
+        max_msg_nums: i32,
+    ) -> crate::Result<MessageRange> {
+        let request_header = QueryMessageByOffsetRangeRequestHeader {
+            topic: mq.get_topic_cs().clone(),
+            queue_id: mq.get_queue_id(),
+            start_offset,
+            end_offset,
+            max_msg_nums,
+            by_commit_log_offset: false,
+        };
+        self.query_message_by_offset_range(broker_addr, request_header)
+            .await
+    }
+
+    async fn query_messages_by_commit_log_offset(
+        &self,
+        broker_addr: CheetahString,
+        start_offset: i64,
+        end_offset: i64,
+        max_msg_nums: i32,
+    ) -> crate::Result<MessageRange> {
+        let request_header = QueryMessageByOffsetRangeRequestHeader {
+            topic: CheetahString::default(),
+            queue_id: 0,
+            start_offset,
+            end_offset,
+            max_msg_nums,
+            by_commit_log_offset: true,
+        };
+        self.query_message_by_offset_range(broker_addr, request_header)
+            .await
+    }
+
     async fn clone_group_offset(
         &self,
         src_group: CheetahString,
@@ -484,7 +949,24 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         topic: CheetahString,
         is_offline: bool,
     ) -> crate::Result<()> {
-        todo!()
+        let mq_client_api_impl = self.mq_client_api_impl()?;
+        let topic_route_data = self.examine_topic_route_info(topic.clone()).await?;
+        for broker_data in topic_route_data.broker_datas.iter() {
+            let Some(addr) = broker_data.select_broker_addr() else {
+                continue;
+            };
+            mq_client_api_impl
+                .clone_group_offset(
+                    &addr,
+                    &src_group,
+                    &dest_group,
+                    &topic,
+                    is_offline,
+                    self.timeout_millis,
+                )
+                .await?;
+        }
+        Ok(())
     }
 
     async fn get_cluster_list(&self, topic: String) -> crate::Result<HashSet<CheetahString>> {
@@ -500,7 +982,32 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         broker_addr: CheetahString,
         timeout_millis: u64,
     ) -> crate::Result<TopicConfigSerializeWrapper> {
-        todo!()
+        Ok(self
+            .mq_client_api_impl()?
+            .get_all_topic_config(&broker_addr, timeout_millis)
+            .await?)
+    }
+
+    async fn get_all_subscription_group(
+        &self,
+        broker_addr: CheetahString,
+        timeout_millis: u64,
+    ) -> crate::Result<SubscriptionGroupWrapper> {
+        Ok(self
+            .mq_client_api_impl()?
+            .get_all_subscription_group(&broker_addr, timeout_millis)
+            .await?)
+    }
+
+    async fn get_all_consumer_offset(
+        &self,
+        broker_addr: CheetahString,
+        timeout_millis: u64,
+    ) -> crate::Result<ConsumerOffsetSerializeWrapper> {
+        Ok(self
+            .mq_client_api_impl()?
+            .get_all_consumer_offset(&broker_addr, timeout_millis)
+            .await?)
     }
 
     async fn get_user_topic_config(
@@ -519,7 +1026,25 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         mq: MessageQueue,
         offset: u64,
     ) -> crate::Result<()> {
-        todo!()
+        let request_header = UpdateConsumerOffsetRequestHeader {
+            consumer_group: consume_group,
+            topic: mq.get_topic_cs().clone(),
+            queue_id: mq.get_queue_id(),
+            commit_offset: offset as i64,
+            topic_request_header: Some(TopicRequestHeader {
+                lo: None,
+                rpc: Some(RpcRequestHeader {
+                    namespace: None,
+                    namespaced: None,
+                    broker_name: Some(mq.get_broker_name().clone()),
+                    oneway: None,
+                }),
+            }),
+        };
+        self.mq_client_api_impl()?
+            .update_consumer_offset(&broker_addr, request_header, self.timeout_millis)
+            .await?;
+        Ok(())
     }
 
     async fn update_name_server_config(
@@ -571,7 +1096,7 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
             .await
         {
             Ok(_) => Ok(()),
-            Err(e) => Err(crate::tools_error::ToolsError::MQClientError(e)),
+            Err(e) => Err(e.into()),
         }
     }
 
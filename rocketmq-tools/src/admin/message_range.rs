@@ -0,0 +1,63 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rocketmq_common::common::message::message_ext::MessageExt;
+
+/// A batch of the messages stored in an offset range, read by
+/// [`MQAdminExt::query_messages_by_queue_offset`] or
+/// [`MQAdminExt::query_messages_by_commit_log_offset`].
+///
+/// [`MQAdminExt::query_messages_by_queue_offset`]: crate::admin::mq_admin_ext_async::MQAdminExt::query_messages_by_queue_offset
+/// [`MQAdminExt::query_messages_by_commit_log_offset`]: crate::admin::mq_admin_ext_async::MQAdminExt::query_messages_by_commit_log_offset
+#[derive(Debug, Default)]
+pub struct MessageRange {
+    pub messages: Vec<MessageExt>,
+    /// The offset to read the next batch from.
+    pub next_offset: i64,
+    /// The max offset of the queue or commit log when the batch was read.
+    pub max_offset: i64,
+}
+
+impl MessageRange {
+    /// Whether messages remain to be read before `end_offset`.
+    pub fn has_more(&self, end_offset: i64) -> bool {
+        self.next_offset < end_offset.min(self.max_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn has_more_stops_at_the_end_of_the_range_or_of_the_store() {
+        let range = MessageRange {
+            messages: Vec::new(),
+            next_offset: 10,
+            max_offset: 20,
+        };
+        assert!(range.has_more(15));
+        assert!(!range.has_more(10));
+        assert!(range.has_more(i64::MAX));
+
+        let exhausted = MessageRange {
+            next_offset: 20,
+            ..range
+        };
+        assert!(!exhausted.has_more(i64::MAX));
+    }
+}
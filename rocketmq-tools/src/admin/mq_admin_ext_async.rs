@@ -21,16 +21,20 @@ use cheetah_string::CheetahString;
 use rocketmq_common::common::base::plain_access_config::PlainAccessConfig;
 use rocketmq_common::common::config::TopicConfig;
 use rocketmq_common::common::message::message_enum::MessageRequestMode;
+use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
 use rocketmq_remoting::protocol::admin::topic_stats_table::TopicStatsTable;
 use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
 use rocketmq_remoting::protocol::body::consumer_connection::ConsumerConnection;
+use rocketmq_remoting::protocol::body::consumer_offset_serialize_wrapper::ConsumerOffsetSerializeWrapper;
 use rocketmq_remoting::protocol::body::consumer_running_info::ConsumerRunningInfo;
 use rocketmq_remoting::protocol::body::group_list::GroupList;
 use rocketmq_remoting::protocol::body::kv_table::KVTable;
+use rocketmq_remoting::protocol::body::pop_stats::PopStats;
 use rocketmq_remoting::protocol::body::producer_connection::ProducerConnection;
+use rocketmq_remoting::protocol::body::subscription_group_wrapper::SubscriptionGroupWrapper;
 use rocketmq_remoting::protocol::body::topic::topic_list::TopicList;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::TopicConfigSerializeWrapper;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
@@ -39,14 +43,15 @@ use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_detail::Topic
 use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
 
 use crate::admin::common::admin_tool_result::AdminToolResult;
+use crate::admin::message_range::MessageRange;
 use crate::Result;
 
 #[cfg(feature = "async")]
 #[allow(dead_code)]
 #[trait_variant::make(MQAdminExt: Send)]
 pub trait MQAdminExtLocal: Sync {
-    async fn start(&self) -> Result<()>;
-    async fn shutdown(&self);
+    async fn start(&mut self) -> Result<()>;
+    async fn shutdown(&mut self);
     async fn add_broker_to_container(
         &self,
         broker_container_addr: CheetahString,
@@ -152,6 +157,14 @@ pub trait MQAdminExtLocal: Sync {
         timeout_millis: Option<u64>,
     ) -> Result<ConsumeStats>;
 
+    /// Inflight, revive lag and backlog of a POP consumer group on one broker.
+    async fn examine_pop_stats(
+        &self,
+        broker_addr: CheetahString,
+        consumer_group: CheetahString,
+        topic: CheetahString,
+    ) -> Result<PopStats>;
+
     /*async fn check_rocksdb_cq_write_progress(
         &self,
         broker_addr: CheetahString,
@@ -364,6 +377,30 @@ pub trait MQAdminExtLocal: Sync {
         msg_id: CheetahString,
     ) -> Result<ConsumeMessageDirectlyResult>;
 
+    async fn view_message(&self, topic: CheetahString, msg_id: CheetahString)
+        -> Result<MessageExt>;
+
+    /// Reads at most `max_msg_nums` messages of `mq` between two consume queue offsets,
+    /// `end_offset` excluded.
+    async fn query_messages_by_queue_offset(
+        &self,
+        broker_addr: CheetahString,
+        mq: MessageQueue,
+        start_offset: i64,
+        end_offset: i64,
+        max_msg_nums: i32,
+    ) -> Result<MessageRange>;
+
+    /// Reads at most `max_msg_nums` messages a broker stores between two commit log offsets,
+    /// `end_offset` excluded. `start_offset` must be the offset of a message.
+    async fn query_messages_by_commit_log_offset(
+        &self,
+        broker_addr: CheetahString,
+        start_offset: i64,
+        end_offset: i64,
+        max_msg_nums: i32,
+    ) -> Result<MessageRange>;
+
     /*async fn message_track_detail(
         &self,
         msg: MessageExt,
@@ -400,11 +437,11 @@ pub trait MQAdminExtLocal: Sync {
 
     async fn get_topic_cluster_list(&self, topic: String) -> Result<HashSet<CheetahString>>;
 
-    /*async fn get_all_subscription_group(
+    async fn get_all_subscription_group(
         &self,
         broker_addr: CheetahString,
         timeout_millis: u64,
-    ) -> Result<SubscriptionGroupWrapper>;*/
+    ) -> Result<SubscriptionGroupWrapper>;
 
     /*async fn get_user_subscription_group(
         &self,
@@ -425,6 +462,12 @@ pub trait MQAdminExtLocal: Sync {
         timeout_millis: u64,
     ) -> Result<TopicConfigSerializeWrapper>;
 
+    async fn get_all_consumer_offset(
+        &self,
+        broker_addr: CheetahString,
+        timeout_millis: u64,
+    ) -> Result<ConsumerOffsetSerializeWrapper>;
+
     async fn update_consume_offset(
         &self,
         broker_addr: CheetahString,
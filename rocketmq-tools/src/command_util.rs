@@ -0,0 +1,129 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashSet;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::mix_all;
+use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
+use rocketmq_remoting::protocol::route::route_data_view::BrokerData;
+
+use crate::tools_error::ToolsError;
+use crate::Result;
+
+/// Helpers shared by the admin commands to resolve broker addresses from cluster information.
+pub struct CommandUtil;
+
+impl CommandUtil {
+    /// Returns the master address of every broker that belongs to `cluster_name`.
+    #[cfg(feature = "async")]
+    pub async fn fetch_master_addr_by_cluster_name<A>(
+        admin_ext: &A,
+        cluster_name: &CheetahString,
+    ) -> Result<HashSet<CheetahString>>
+    where
+        A: crate::admin::mq_admin_ext_async::MQAdminExt,
+    {
+        let cluster_info = admin_ext.examine_broker_cluster_info().await?;
+        let addrs = Self::broker_datas_of_cluster(&cluster_info, cluster_name)?
+            .filter_map(|broker_data| broker_data.broker_addrs().get(&mix_all::MASTER_ID).cloned())
+            .collect();
+        Ok(addrs)
+    }
+
+    /// Returns the master and slave addresses of every broker that belongs to `cluster_name`.
+    #[cfg(feature = "async")]
+    pub async fn fetch_master_and_slave_addr_by_cluster_name<A>(
+        admin_ext: &A,
+        cluster_name: &CheetahString,
+    ) -> Result<HashSet<CheetahString>>
+    where
+        A: crate::admin::mq_admin_ext_async::MQAdminExt,
+    {
+        let cluster_info = admin_ext.examine_broker_cluster_info().await?;
+        let addrs = Self::broker_datas_of_cluster(&cluster_info, cluster_name)?
+            .flat_map(|broker_data| broker_data.broker_addrs().values().cloned())
+            .collect();
+        Ok(addrs)
+    }
+
+    fn broker_datas_of_cluster<'a>(
+        cluster_info: &'a ClusterInfo,
+        cluster_name: &CheetahString,
+    ) -> Result<impl Iterator<Item = &'a BrokerData>> {
+        let broker_names = cluster_info
+            .cluster_addr_table
+            .as_ref()
+            .and_then(|table| table.get(cluster_name))
+            .ok_or_else(|| ToolsError::ClusterNotFoundError(cluster_name.to_string()))?;
+        let broker_addr_table = cluster_info.broker_addr_table.as_ref();
+        Ok(broker_names.iter().filter_map(move |broker_name| {
+            broker_addr_table.and_then(|table| table.get(broker_name))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn cluster_info() -> ClusterInfo {
+        let broker_data = BrokerData::new(
+            CheetahString::from_static_str("DefaultCluster"),
+            CheetahString::from_static_str("broker-a"),
+            HashMap::from([
+                (0, CheetahString::from_static_str("127.0.0.1:10911")),
+                (1, CheetahString::from_static_str("127.0.0.1:10921")),
+            ]),
+            None,
+        );
+        ClusterInfo::new(
+            Some(HashMap::from([(
+                CheetahString::from_static_str("broker-a"),
+                broker_data,
+            )])),
+            Some(HashMap::from([(
+                CheetahString::from_static_str("DefaultCluster"),
+                HashSet::from([CheetahString::from_static_str("broker-a")]),
+            )])),
+        )
+    }
+
+    #[test]
+    fn broker_datas_of_cluster_resolves_brokers() {
+        let cluster_info = cluster_info();
+        let broker_datas: Vec<_> = CommandUtil::broker_datas_of_cluster(
+            &cluster_info,
+            &CheetahString::from_static_str("DefaultCluster"),
+        )
+        .unwrap()
+        .collect();
+        assert_eq!(broker_datas.len(), 1);
+        assert_eq!(broker_datas[0].broker_addrs().len(), 2);
+    }
+
+    #[test]
+    fn broker_datas_of_unknown_cluster_is_an_error() {
+        let cluster_info = cluster_info();
+        let result = CommandUtil::broker_datas_of_cluster(
+            &cluster_info,
+            &CheetahString::from_static_str("UnknownCluster"),
+        );
+        assert!(matches!(result, Err(ToolsError::ClusterNotFoundError(_))));
+    }
+}
@@ -17,6 +17,7 @@
 use crate::tools_error::ToolsError;
 
 pub mod admin;
+pub mod command_util;
 pub mod tools_error;
 
 pub type Result<T> = std::result::Result<T, ToolsError>;
@@ -14,12 +14,13 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use rocketmq_client_rust::client_error::MQClientError;
 use thiserror::Error;
 #[allow(clippy::enum_variant_names)]
 #[derive(Debug, Error)]
 pub enum ToolsError {
     #[error("MQ client error occurred. {0}")]
-    MQClientError(#[from] rocketmq_client_rust::client_error::MQClientError),
+    MQClientError(Box<MQClientError>),
     #[error("MQ broker error occurred.")]
     MQBrokerError,
     #[error("Remoting timeout.")]
@@ -32,4 +33,19 @@ pub enum ToolsError {
     UnsupportedEncodingError,
     #[error("Operation interrupted.")]
     InterruptedError,
+    #[error(
+        "Make sure the specified clusterName exists or the name server connected to is correct. \
+         clusterName: {0}"
+    )]
+    ClusterNotFoundError(String),
+    #[error("Illegal argument. {0}")]
+    IllegalArgumentError(String),
+    #[error("Broker metadata file error. {0}")]
+    MetadataFileError(String),
+}
+
+impl From<MQClientError> for ToolsError {
+    fn from(value: MQClientError) -> Self {
+        ToolsError::MQClientError(Box::new(value))
+    }
 }
@@ -0,0 +1,98 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cheetah_string::CheetahString;
+use rocketmq_client_rust::producer::default_mq_producer::DefaultMQProducer;
+use rocketmq_client_rust::producer::mq_producer::MQProducer;
+use rocketmq_common::common::message::message_single::Message;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_test_util::EmbeddedCluster;
+use rocketmq_tools::admin::default_mq_admin_ext::DefaultMQAdminExt;
+use rocketmq_tools::admin::mq_admin_ext_async::MQAdminExt;
+
+const TOPIC: &str = "OffsetRangeTopic";
+
+fn body_of(message: &impl MessageTrait) -> String {
+    String::from_utf8(message.get_body().unwrap().to_vec()).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn messages_are_read_back_by_queue_and_commit_log_offset_range() {
+    let cluster = EmbeddedCluster::start().await.unwrap();
+
+    let mut producer = DefaultMQProducer::builder()
+        .producer_group("offset_range_producer".to_string())
+        .name_server_addr(cluster.namesrv_addr())
+        .build();
+    producer.start().await.unwrap();
+    let first = producer
+        .send_with_timeout(Message::with_tags(TOPIC, "TagA", b"message-0"), 5000)
+        .await
+        .unwrap();
+    let mq = first.message_queue.clone().unwrap();
+    for i in 1..3 {
+        let message = Message::with_tags(TOPIC, "TagA", format!("message-{}", i).as_bytes());
+        producer
+            .send_to_queue_with_timeout(message, mq.clone(), 5000)
+            .await
+            .unwrap();
+    }
+    producer.shutdown().await;
+
+    let mut admin = DefaultMQAdminExt::new();
+    admin.set_namesrv_addr(cluster.namesrv_addr());
+    admin.start().await.unwrap();
+    let broker_addr = CheetahString::from(cluster.broker_addr());
+    let start_offset = first.queue_offset as i64;
+
+    let by_queue = admin
+        .query_messages_by_queue_offset(
+            broker_addr.clone(),
+            mq.clone(),
+            start_offset,
+            start_offset + 2,
+            32,
+        )
+        .await
+        .unwrap();
+    let bodies = by_queue.messages.iter().map(body_of).collect::<Vec<_>>();
+    assert_eq!(bodies, vec!["message-0", "message-1"]);
+    assert_eq!(by_queue.next_offset, start_offset + 2);
+    assert!(!by_queue.has_more(start_offset + 2));
+    assert!(by_queue.has_more(i64::MAX));
+
+    let by_commit_log = admin
+        .query_messages_by_commit_log_offset(
+            broker_addr,
+            by_queue.messages[0].commit_log_offset,
+            i64::MAX,
+            32,
+        )
+        .await
+        .unwrap();
+    let bodies = by_commit_log
+        .messages
+        .iter()
+        .filter(|message| message.get_topic() == TOPIC)
+        .map(body_of)
+        .collect::<Vec<_>>();
+    assert_eq!(bodies, vec!["message-0", "message-1", "message-2"]);
+    assert!(!by_commit_log.has_more(i64::MAX));
+
+    admin.shutdown().await;
+    cluster.shutdown().await;
+}
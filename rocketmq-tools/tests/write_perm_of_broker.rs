@@ -0,0 +1,78 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_test_util::EmbeddedCluster;
+use rocketmq_tools::admin::default_mq_admin_ext::DefaultMQAdminExt;
+use rocketmq_tools::admin::mq_admin_ext_async::MQAdminExt;
+
+/// A topic every broker registers to the name server.
+const TOPIC: &str = TopicValidator::AUTO_CREATE_TOPIC_KEY_TOPIC;
+
+/// Whether the broker accepts writes to `TOPIC` according to the name server.
+async fn topic_writeable(admin: &DefaultMQAdminExt) -> bool {
+    let route = admin
+        .examine_topic_route_info(CheetahString::from_static_str(TOPIC))
+        .await
+        .unwrap();
+    PermName::is_writeable(route.queue_datas[0].perm)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn write_perm_of_broker_is_wiped_and_added_back() {
+    let cluster = EmbeddedCluster::start().await.unwrap();
+
+    let mut admin = DefaultMQAdminExt::new();
+    admin.set_namesrv_addr(cluster.namesrv_addr());
+    admin.start().await.unwrap();
+    let namesrv_addr = CheetahString::from(cluster.namesrv_addr());
+    let route = admin
+        .examine_topic_route_info(CheetahString::from_static_str(TOPIC))
+        .await
+        .unwrap();
+    let broker_name = route.queue_datas[0].broker_name.clone();
+    assert!(topic_writeable(&admin).await);
+
+    let wiped = admin
+        .wipe_write_perm_of_broker(namesrv_addr.clone(), broker_name.clone())
+        .await
+        .unwrap();
+    assert!(wiped > 0);
+    assert!(!topic_writeable(&admin).await);
+
+    let added = admin
+        .add_write_perm_of_broker(namesrv_addr.clone(), broker_name.clone())
+        .await
+        .unwrap();
+    assert_eq!(added, wiped);
+    assert!(topic_writeable(&admin).await);
+
+    // the name server knows no such broker
+    let unknown = admin
+        .wipe_write_perm_of_broker(
+            namesrv_addr,
+            CheetahString::from_static_str("no-such-broker"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(unknown, 0);
+
+    admin.shutdown().await;
+    cluster.shutdown().await;
+}